@@ -1,3 +1,5 @@
+mod sprites;
+
 use amethyst::{
     animation::{
         AnimationPrefab, AnimationSetPrefab, InterpolationFunction, Sampler, SpriteRenderChannel,
@@ -72,6 +74,14 @@ fn default_interpolation_step() -> f32 {
 struct FramesMap(BTreeMap<String, Frame>);
 
 fn main() -> Result<(), failure::Error> {
+    match env::args().nth(1).as_deref() {
+        Some("sprites") => sprites::pack_sprites(),
+        Some(mode) => Err(failure::format_err!("Unknown mode: {}", mode)),
+        None => pack_animations(),
+    }
+}
+
+fn pack_animations() -> Result<(), failure::Error> {
     let config = TexturePackerConfig {
         allow_rotation: false,
         ..Default::default()
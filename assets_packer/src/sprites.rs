@@ -0,0 +1,102 @@
+use amethyst::renderer::sprite::Sprites;
+use failure::{self, format_err};
+use image::{self, GenericImageView};
+use ron::ser::{to_string_pretty, PrettyConfig};
+use texture_packer::{
+    exporter::ImageExporter, importer::ImageImporter, TexturePacker, TexturePackerConfig,
+};
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::Path,
+};
+
+use gv_client_shared::sprite_atlas::{
+    sheet_sprites_path, sheet_texture_path, AtlasSprite, SpriteAtlas, SPRITE_ATLAS_METADATA_PATH,
+    SPRITE_ATLAS_SHEETS, SPRITE_ATLAS_TEXTURE_PATH,
+};
+
+/// Packs every sprite of `SPRITE_ATLAS_SHEETS` into the sprite atlas, is run from
+/// the repository root.
+pub fn pack_sprites() -> Result<(), failure::Error> {
+    let config = TexturePackerConfig {
+        allow_rotation: false,
+        // Sprites keep their sizes, as nothing offsets them back after trimming.
+        trim: false,
+        ..Default::default()
+    };
+    let mut packer = TexturePacker::new_skyline(config);
+
+    let mut sprite_counts = Vec::new();
+    for name in SPRITE_ATLAS_SHEETS.iter() {
+        let mut texture = ImageImporter::import_from_file(Path::new(&sheet_texture_path(name)))
+            .map_err(|err| format_err!("{}: {}", sheet_texture_path(name), err))?;
+        let sprites: Sprites = ron::de::from_reader(File::open(sheet_sprites_path(name))?)?;
+        let sprite_list = match sprites {
+            Sprites::List(sprite_list) => sprite_list,
+            Sprites::Grid(_) => {
+                return Err(format_err!(
+                    "{}: only sprite lists can be packed",
+                    sheet_sprites_path(name)
+                ))
+            }
+        };
+        if (sprite_list.texture_width, sprite_list.texture_height) != texture.dimensions() {
+            return Err(format_err!(
+                "{}: the texture size doesn't match {}",
+                sheet_sprites_path(name),
+                sheet_texture_path(name)
+            ));
+        }
+
+        for (i, sprite) in sprite_list.sprites.iter().enumerate() {
+            let sprite_texture = texture.crop(sprite.x, sprite.y, sprite.width, sprite.height);
+            packer.pack_own(sprite_key(name, i), sprite_texture);
+        }
+        sprite_counts.push((name, sprite_list.sprites.len()));
+    }
+
+    let frames = packer.get_frames();
+    let mut sheets = BTreeMap::new();
+    for (name, sprites_count) in sprite_counts {
+        let sprites = (0..sprites_count)
+            .map(|i| {
+                let frame = &frames
+                    .get(&sprite_key(name, i))
+                    .ok_or_else(|| format_err!("Sprite {} of {} doesn't fit the atlas", i, name))?
+                    .frame;
+                Ok(AtlasSprite {
+                    x: frame.x,
+                    y: frame.y,
+                    width: frame.w,
+                    height: frame.h,
+                })
+            })
+            .collect::<Result<Vec<_>, failure::Error>>()?;
+        sheets.insert((*name).to_owned(), sprites);
+    }
+    let sprite_atlas = SpriteAtlas {
+        texture_width: packer.width(),
+        texture_height: packer.height(),
+        sheets,
+    };
+
+    let exporter = ImageExporter::export(&packer).unwrap();
+    let mut file = File::create(SPRITE_ATLAS_TEXTURE_PATH)?;
+    exporter.write_to(&mut file, image::PNG)?;
+
+    let ron_metadata = to_string_pretty(
+        &sprite_atlas,
+        PrettyConfig {
+            new_line: "\n".to_owned(),
+            ..PrettyConfig::default()
+        },
+    )?;
+    fs::write(SPRITE_ATLAS_METADATA_PATH, ron_metadata)?;
+    Ok(())
+}
+
+fn sprite_key(sheet_name: &str, i: usize) -> String {
+    format!("{}_{:04}", sheet_name, i)
+}
@@ -0,0 +1,274 @@
+use amethyst::ecs::{Join, ReadExpect, ReadStorage, World, WorldExt, WriteExpect};
+
+use std::{collections::HashMap, time::Duration};
+
+use gv_core::{
+    actions::{
+        monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+        player::CastSpell,
+    },
+    ecs::{
+        components::{Dead, Monster, Player},
+        resources::{net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState},
+    },
+    math::{deterministic, Vector2},
+    profiling::FrameTimings,
+};
+use gv_game::{ecs::resources::MatchSeed, utils::world::random_spawn_position};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_JOIN_FRAMES: u64 = 300;
+/// Spawning all the monsters in one frame would make a single huge world update.
+const MAX_SPAWNS_PER_FRAME: usize = 50;
+/// Bots never die, so that a match doesn't end in the middle of a run.
+const BOT_HEALTH: f32 = 1_000_000_000.0;
+const BOT_TURN_INTERVAL_FRAMES: u64 = 30;
+const BOT_CAST_INTERVAL_FRAMES: u64 = 20;
+const BOT_CAST_DISTANCE: f32 = 200.0;
+const TOTAL_ROW_NAME: &str = "(frame total)";
+
+struct BenchConfig {
+    frames: u64,
+    warmup_frames: u64,
+    bots: usize,
+    monsters: usize,
+    csv: bool,
+}
+
+fn main() -> amethyst::Result<()> {
+    let cli_matches = clap::App::new("gv_bench")
+        .about("Runs a heavy match headlessly and prints per-system timing percentiles")
+        .arg(
+            clap::Arg::with_name("frames")
+                .short("f")
+                .long("frames")
+                .value_name("FRAMES")
+                .help("Specifies how many frames to measure")
+                .default_value("3600")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("warmup")
+                .short("w")
+                .long("warmup")
+                .value_name("FRAMES")
+                .help("Specifies how many frames to run before measuring")
+                .default_value("300")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("bots")
+                .short("b")
+                .long("bots")
+                .value_name("BOTS")
+                .help("Specifies how many bot players join the match")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("monsters")
+                .short("m")
+                .long("monsters")
+                .value_name("MONSTERS")
+                .help("Specifies how many monsters are kept alive during the match")
+                .default_value("1000")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("csv")
+                .long("csv")
+                .help("Prints the results as CSV, to compare them between runs"),
+        )
+        .get_matches();
+
+    let parse_arg = |name: &str| -> amethyst::Result<u64> {
+        let value = cli_matches
+            .value_of(name)
+            .expect("Expected a default value");
+        value
+            .parse()
+            .map_err(|_| amethyst::Error::from_string(format!("Invalid {}: {}", name, value)))
+    };
+    let config = BenchConfig {
+        frames: parse_arg("frames")?,
+        warmup_frames: parse_arg("warmup")?,
+        bots: parse_arg("bots")?.max(1) as usize,
+        monsters: parse_arg("monsters")? as usize,
+        csv: cli_matches.is_present("csv"),
+    };
+
+    let mut harness = TestHarness::new(LoopbackConfig::default())?;
+    for i in 0..config.bots {
+        harness.add_client(&format!("bot{}", i));
+    }
+    if !harness.join_and_start(MAX_JOIN_FRAMES) {
+        return Err(amethyst::Error::from_string(
+            "Bots failed to join and start the game",
+        ));
+    }
+
+    for _ in 0..config.warmup_frames {
+        step(&mut harness, &config);
+    }
+
+    let mut timings = HashMap::<&'static str, Vec<Duration>>::new();
+    let mut last_recorded_frame = None;
+    for _ in 0..config.frames {
+        step(&mut harness, &config);
+
+        let frame_timings = harness.server().world().read_resource::<FrameTimings>();
+        frame_timings.with_history(|frames| {
+            // The server doesn't run its systems while waiting for players.
+            let frame = match frames.back() {
+                Some(frame) if Some(frame.frame_number) != last_recorded_frame => frame,
+                _ => return,
+            };
+            last_recorded_frame = Some(frame.frame_number);
+            timings
+                .entry(TOTAL_ROW_NAME)
+                .or_default()
+                .push(frame.busy_duration);
+            for system in &frame.systems {
+                timings
+                    .entry(system.name)
+                    .or_default()
+                    .push(system.duration);
+            }
+        });
+    }
+
+    print_percentiles(timings, &config);
+    Ok(())
+}
+
+fn step(harness: &mut TestHarness, config: &BenchConfig) {
+    let frame_number = harness.server().game_frame_number();
+    for bot in 0..harness.clients().len() {
+        let client = harness.client_mut(bot);
+        if client.disconnect_reason().is_some() {
+            continue;
+        }
+        // Bots walk in circles of different phases and shoot ahead.
+        let phase = (frame_number / BOT_TURN_INTERVAL_FRAMES) as f32 * 0.5 + bot as f32;
+        let direction = deterministic::rotate(&Vector2::new(1.0, 0.0), phase);
+        if frame_number % BOT_TURN_INTERVAL_FRAMES == 0 {
+            client.walk(Some(direction), 0);
+        }
+        if frame_number % BOT_CAST_INTERVAL_FRAMES == 0 {
+            client.cast(CastSpell::Missile, direction * BOT_CAST_DISTANCE);
+        }
+    }
+
+    let world = harness.server_mut().world_mut();
+    {
+        let mut players = world.write_storage::<Player>();
+        for player in (&mut players).join() {
+            player.max_health = BOT_HEALTH;
+            player.health = BOT_HEALTH;
+        }
+    }
+    spawn_missing_monsters(world, config.monsters, frame_number + 1);
+
+    harness.step();
+}
+
+fn spawn_missing_monsters(world: &World, monsters_count: usize, frame_number: u64) {
+    let (
+        game_level_state,
+        mut framed_spawn_actions,
+        mut entity_net_metadata_storage,
+        monsters,
+        dead,
+        match_seed,
+    ) = world.system_data::<(
+        ReadExpect<GameLevelState>,
+        WriteExpect<FramedUpdates<SpawnActions>>,
+        WriteExpect<EntityNetMetadataStorage>,
+        ReadStorage<Monster>,
+        ReadStorage<Dead>,
+        ReadExpect<MatchSeed>,
+    )>();
+
+    let alive_monsters = (&monsters, !&dead).join().count();
+    let missing_monsters = monsters_count
+        .saturating_sub(alive_monsters)
+        .min(MAX_SPAWNS_PER_FRAME);
+    if missing_monsters == 0 {
+        return;
+    }
+
+    let spawn_actions = framed_spawn_actions
+        .update_frame(frame_number)
+        .expect("Expected SpawnActions for the next frame");
+    let mut rng = match_seed.frame_rng(frame_number);
+    for entity_net_id in entity_net_metadata_storage.reserve_ids(missing_monsters) {
+        spawn_actions.spawn_actions.push(SpawnAction {
+            spawn_type: SpawnType::Single {
+                entity_net_id: Some(entity_net_id),
+                position: random_spawn_position(&game_level_state, &mut rng),
+                affixes: Vec::new(),
+            },
+            telegraph_frames: 0,
+        });
+    }
+}
+
+fn print_percentiles(timings: HashMap<&'static str, Vec<Duration>>, config: &BenchConfig) {
+    let mut rows = timings
+        .into_iter()
+        .map(|(name, mut durations)| {
+            durations.sort();
+            let percentiles = [
+                percentile(&durations, 50.0),
+                percentile(&durations, 90.0),
+                percentile(&durations, 99.0),
+                *durations.last().expect("Expected at least one timing"),
+            ];
+            (name, percentiles)
+        })
+        .collect::<Vec<_>>();
+    // The slowest systems go first.
+    rows.sort_by(|(name_a, a), (name_b, b)| b[2].cmp(&a[2]).then(name_a.cmp(name_b)));
+
+    if config.csv {
+        println!("system,p50_us,p90_us,p99_us,max_us");
+        for (name, percentiles) in rows {
+            let [p50, p90, p99, max] = percentiles;
+            println!(
+                "{},{},{},{},{}",
+                name,
+                p50.as_micros(),
+                p90.as_micros(),
+                p99.as_micros(),
+                max.as_micros()
+            );
+        }
+        return;
+    }
+
+    println!(
+        "{} frames, {} bots, {} monsters (timings in microseconds)",
+        config.frames, config.bots, config.monsters
+    );
+    println!(
+        "{:<40} {:>8} {:>8} {:>8} {:>8}",
+        "system", "p50", "p90", "p99", "max"
+    );
+    for (name, percentiles) in rows {
+        let [p50, p90, p99, max] = percentiles;
+        println!(
+            "{:<40} {:>8} {:>8} {:>8} {:>8}",
+            name,
+            p50.as_micros(),
+            p90.as_micros(),
+            p99.as_micros(),
+            max.as_micros()
+        );
+    }
+}
+
+/// Expects sorted durations.
+fn percentile(durations: &[Duration], percentile: f64) -> Duration {
+    let rank = (percentile / 100.0 * durations.len() as f64).ceil() as usize;
+    durations[rank.max(1).min(durations.len()) - 1]
+}
@@ -0,0 +1,301 @@
+//! Captures the game window into short clips. A running `ffmpeg` grabs the window into
+//! a few rotating segments all the time, and saving a clip joins the segments that are
+//! there into a video or a GIF. Nothing is rendered twice, so the game doesn't slow down,
+//! but `ffmpeg` has to be installed and the window isn't followed if it's moved.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::gv_dirs::GvDirs;
+
+const CAPTURE_FRAME_RATE: u32 = 30;
+const SEGMENT_SECS: u32 = 5;
+/// Clips are at most `SEGMENT_SECS * SEGMENTS` long.
+const SEGMENTS: u32 = 4;
+const GIF_FRAME_RATE: u32 = 15;
+const GIF_WIDTH: u32 = 640;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipFormat {
+    Mp4,
+    Gif,
+}
+
+impl ClipFormat {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "mp4" => Some(ClipFormat::Mp4),
+            "gif" => Some(ClipFormat::Gif),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ClipFormat::Mp4 => "mp4",
+            ClipFormat::Gif => "gif",
+        }
+    }
+}
+
+/// The physical pixels of the window's client area on the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Is started by `ClipCaptureSystem` once the window is there.
+pub struct ClipCapture {
+    format: Option<ClipFormat>,
+    segments_dir: PathBuf,
+    ffmpeg: Option<Child>,
+    saved_clips: u32,
+}
+
+impl ClipCapture {
+    pub fn disabled() -> Self {
+        Self {
+            format: None,
+            segments_dir: PathBuf::new(),
+            ffmpeg: None,
+            saved_clips: 0,
+        }
+    }
+
+    pub fn new(format: ClipFormat) -> Self {
+        Self {
+            format: Some(format),
+            segments_dir: env::temp_dir().join(format!("gv_clip_segments_{}", std::process::id())),
+            ..Self::disabled()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.format.is_some()
+    }
+
+    /// (Re)starts grabbing the window, the segments of the previous region are dropped.
+    /// Capturing is turned off if `ffmpeg` fails to start.
+    pub fn start(&mut self, region: CaptureRegion, window_title: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.stop();
+
+        let result = fs::create_dir_all(&self.segments_dir).and_then(|_| {
+            Command::new("ffmpeg")
+                .args(&["-loglevel", "error", "-nostdin", "-y"])
+                .args(grab_args(region, window_title))
+                .args(&[
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    "ultrafast",
+                    "-pix_fmt",
+                    "yuv420p",
+                ])
+                .args(&["-f", "segment", "-reset_timestamps", "1"])
+                .arg("-segment_time")
+                .arg(SEGMENT_SECS.to_string())
+                .arg("-segment_wrap")
+                .arg(SEGMENTS.to_string())
+                // MPEG-TS segments can be read while they're still being written.
+                .arg(self.segments_dir.join("segment_%d.ts"))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn()
+        });
+        match result {
+            Ok(ffmpeg) => {
+                log::info!("Capturing clips of {:?}", region);
+                self.ffmpeg = Some(ffmpeg);
+            }
+            Err(err) => {
+                log::error!("Failed to start ffmpeg, clips won't be captured: {:?}", err);
+                self.format = None;
+            }
+        }
+    }
+
+    /// Joins the captured segments into a clip in the media directory. Encoding takes
+    /// a while, so it happens in the background, the returned path is written once it's done.
+    pub fn save_clip(&mut self) -> io::Result<PathBuf> {
+        let format = match (self.format, &self.ffmpeg) {
+            (Some(format), Some(_)) => format,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "Clips aren't being captured",
+                ))
+            }
+        };
+
+        let mut segments = fs::read_dir(&self.segments_dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                if !is_segment(&entry.path()) {
+                    return None;
+                }
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                Some((modified, entry.path()))
+            })
+            .collect::<Vec<_>>();
+        if segments.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Nothing has been captured yet",
+            ));
+        }
+        segments.sort();
+
+        let media_dir = GvDirs::new().media_dir();
+        fs::create_dir_all(&media_dir)?;
+        self.saved_clips += 1;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = media_dir.join(format!(
+            "clip_{}_{}.{}",
+            timestamp,
+            self.saved_clips,
+            format.extension()
+        ));
+
+        // Copying the segments, as ffmpeg will overwrite them before the encoding is done.
+        let clip_dir = self.segments_dir.join(format!("clip_{}", self.saved_clips));
+        fs::create_dir_all(&clip_dir)?;
+        let mut inputs = Vec::new();
+        for (i, (_, segment_path)) in segments.iter().enumerate() {
+            let input = clip_dir.join(format!("{}.ts", i));
+            fs::copy(segment_path, &input)?;
+            inputs.push(input.to_string_lossy().into_owned());
+        }
+
+        let output = path.clone();
+        thread::spawn(move || {
+            let result = Command::new("ffmpeg")
+                .args(&["-loglevel", "error", "-nostdin", "-y"])
+                .arg("-i")
+                .arg(format!("concat:{}", inputs.join("|")))
+                .args(encode_args(format))
+                .arg(&output)
+                .status();
+            match result {
+                Ok(status) if status.success() => {
+                    log::info!("Saved a clip to {}", output.display())
+                }
+                Ok(status) => log::error!("Failed to encode a clip, ffmpeg exited with {}", status),
+                Err(err) => log::error!("Failed to encode a clip: {:?}", err),
+            }
+            let _ = fs::remove_dir_all(&clip_dir);
+        });
+        Ok(path)
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut ffmpeg) = self.ffmpeg.take() {
+            let _ = ffmpeg.kill();
+            let _ = ffmpeg.wait();
+        }
+        remove_segments(&self.segments_dir);
+    }
+}
+
+impl Drop for ClipCapture {
+    fn drop(&mut self) {
+        self.stop();
+        if self.segments_dir.exists() {
+            let _ = fs::remove_dir_all(&self.segments_dir);
+        }
+    }
+}
+
+fn remove_segments(segments_dir: &Path) {
+    if let Ok(entries) = fs::read_dir(segments_dir) {
+        for entry in entries.filter_map(Result::ok) {
+            if is_segment(&entry.path()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Copies of the segments being encoded into clips are kept in subdirectories.
+fn is_segment(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .map_or(false, |extension| extension == "ts")
+}
+
+#[cfg(target_os = "windows")]
+fn grab_args(_region: CaptureRegion, window_title: &str) -> Vec<String> {
+    vec![
+        "-f".to_owned(),
+        "gdigrab".to_owned(),
+        "-framerate".to_owned(),
+        CAPTURE_FRAME_RATE.to_string(),
+        "-i".to_owned(),
+        format!("title={}", window_title),
+    ]
+}
+
+#[cfg(target_os = "macos")]
+fn grab_args(region: CaptureRegion, _window_title: &str) -> Vec<String> {
+    // AVFoundation grabs the whole screen, the window is cropped out of it.
+    vec![
+        "-f".to_owned(),
+        "avfoundation".to_owned(),
+        "-framerate".to_owned(),
+        CAPTURE_FRAME_RATE.to_string(),
+        "-i".to_owned(),
+        "Capture screen 0:none".to_owned(),
+        "-vf".to_owned(),
+        format!(
+            "crop={}:{}:{}:{}",
+            region.width / 2 * 2,
+            region.height / 2 * 2,
+            region.x,
+            region.y
+        ),
+    ]
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn grab_args(region: CaptureRegion, _window_title: &str) -> Vec<String> {
+    let display = env::var("DISPLAY").unwrap_or_else(|_| ":0".to_owned());
+    vec![
+        "-f".to_owned(),
+        "x11grab".to_owned(),
+        "-framerate".to_owned(),
+        CAPTURE_FRAME_RATE.to_string(),
+        "-video_size".to_owned(),
+        // libx264 needs even dimensions.
+        format!("{}x{}", region.width / 2 * 2, region.height / 2 * 2),
+        "-i".to_owned(),
+        format!("{}+{},{}", display, region.x.max(0), region.y.max(0)),
+    ]
+}
+
+fn encode_args(format: ClipFormat) -> Vec<String> {
+    match format {
+        ClipFormat::Mp4 => vec!["-c".to_owned(), "copy".to_owned()],
+        // A palette generated from the clip itself looks a lot better than the default one.
+        ClipFormat::Gif => vec![
+            "-vf".to_owned(),
+            format!(
+                "fps={},scale={}:-1:flags=lanczos,split[a][b];[a]palettegen[p];[b][p]paletteuse",
+                GIF_FRAME_RATE, GIF_WIDTH
+            ),
+        ],
+    }
+}
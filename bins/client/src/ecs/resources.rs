@@ -1,20 +1,166 @@
-use amethyst::Error;
+use amethyst::{assets::Handle, ecs::Entity, renderer::Texture, Error};
 
 use std::{
     env::current_exe,
-    net::SocketAddr,
-    process::{Child, Command, ExitStatus},
+    io::{BufRead, BufReader},
+    net::{SocketAddr, UdpSocket},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use gv_client_shared::lighting::PointLight;
+use gv_core::{
+    classes::PlayerClass,
+    emotes::Emote,
+    level_scripts::DAYLIGHT,
+    net::{
+        rendezvous::HostCode, server_message::RoomInfo, NetIdentifier, RoomId, SERVER_READY_PREFIX,
+    },
+    room_settings::RoomSettings,
+    votes::{Vote, VoteKind},
 };
 
 #[derive(Default)]
 pub struct DisplayDebugInfoSettings {
     pub display_health: bool,
     pub display_network_debug_info: bool,
+    pub display_frame_timings: bool,
+    /// Gameplay input is ignored while the developer console is open.
+    pub display_console: bool,
+    /// The camera is detached from the player and flies with WASD, gameplay input is ignored.
+    pub free_camera: bool,
+    /// Is picked by clicking an entity while the free camera is on.
+    pub inspected_entity: Option<Entity>,
+}
+
+/// Is updated by `WindowFocusSystem` from the window events.
+pub struct WindowFocus {
+    /// Gameplay input is ignored while the window is in the background.
+    pub is_focused: bool,
+    /// Is `true` while the window is unfocused and `mute_unfocused` is turned on.
+    pub is_muted: bool,
 }
 
-pub struct LastAcknowledgedUpdate {
-    pub id: u64,
-    pub frame_number: u64,
+impl Default for WindowFocus {
+    fn default() -> Self {
+        Self {
+            is_focused: true,
+            is_muted: false,
+        }
+    }
+}
+
+/// What the camera shows while the player is dead, is picked by `SpectatorCameraSystem`
+/// and applied by `CameraSystem`.
+#[derive(Default)]
+pub struct SpectatorCamera {
+    /// Is `true` while the player is dead, number keys follow players instead of playing emotes.
+    pub is_active: bool,
+    pub mode: SpectatorCameraMode,
+    /// The player followed in the `Follow` and `Director` modes, the camera stays
+    /// with the dead player if there's none.
+    pub target: Option<Entity>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpectatorCameraMode {
+    Follow,
+    /// Flies with WASD like the debug free camera.
+    FreeFly,
+    /// Switches to the player who has the most monsters around.
+    Director,
+}
+
+impl Default for SpectatorCameraMode {
+    fn default() -> Self {
+        SpectatorCameraMode::Follow
+    }
+}
+
+/// Highlights saved during the last game, the results screen lists them.
+#[derive(Default)]
+pub struct Highlights {
+    /// Titles of the highlights with the paths they are saved to.
+    pub saved: Vec<(String, PathBuf)>,
+}
+
+/// Is taken by the render graph, which saves the next rendered frame to `path`.
+#[derive(Default)]
+pub struct ScreenshotRequest {
+    pub path: Option<PathBuf>,
+    /// Custom-drawn HUD elements check this, UI entities are hidden by `ScreenshotSystem`.
+    pub is_hud_hidden: bool,
+}
+
+/// The share of the window resolution the world is rendered at, is picked by
+/// `RenderScaleSystem` and applied by the render graph.
+pub struct RenderScale(pub f32);
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        RenderScale(1.0)
+    }
+}
+
+/// Is filled by `LightingSystem` and drawn by `LightingPlugin`.
+pub struct SceneLighting {
+    /// The level is drawn as is when it's `DAYLIGHT`.
+    pub ambient: f32,
+    pub lights: Vec<PointLight>,
+}
+
+impl Default for SceneLighting {
+    fn default() -> Self {
+        Self {
+            ambient: DAYLIGHT,
+            lights: Vec::new(),
+        }
+    }
+}
+
+/// Effect strengths that `DrawPostProcessing` applies to the frame, zero turns an effect off.
+#[derive(Default)]
+pub struct PostProcessing {
+    pub bloom: f32,
+    pub chromatic_aberration: f32,
+    pub low_health_tint: f32,
+    /// The lookup table of the current level, `None` if color grading is off.
+    pub color_grading_lut: Option<Handle<Texture>>,
+}
+
+/// Crash reports left by previous launches, the main menu offers to view or delete them.
+#[derive(Default)]
+pub struct CrashReports {
+    pub paths: Vec<PathBuf>,
+    pub is_prompt_shown: bool,
+}
+
+/// Emotes played with hotkeys and received from the server.
+#[derive(Default)]
+pub struct Emotes {
+    /// Is sent to the server by ClientNetworkSystem.
+    pub outgoing: Option<Emote>,
+    /// Connection ids of the players and their emotes, are shown by EmoteSystem while playing
+    /// and by the multiplayer room menu otherwise.
+    pub incoming: Vec<(NetIdentifier, Emote)>,
+}
+
+/// Votes started and cast by the player, and the running vote received from the server.
+#[derive(Default)]
+pub struct Votes {
+    /// Is sent to the server by ClientNetworkSystem.
+    pub outgoing_start: Option<VoteKind>,
+    /// Is sent to the server by ClientNetworkSystem, `true` votes yes.
+    pub outgoing_ballot: Option<bool>,
+    pub running: Option<Vote>,
+    /// When the running vote was received, the remaining time is counted from it.
+    pub running_since: Duration,
+    /// The last ended vote and whether it has passed, is shown for a while.
+    pub last_result: Option<(VoteKind, bool)>,
+    pub last_result_since: Duration,
 }
 
 #[derive(Default)]
@@ -26,57 +172,225 @@ pub enum UiNetworkCommand {
     Host {
         nickname: String,
         server_addr: SocketAddr,
+        /// Lets players behind NATs join without port forwarding.
+        rendezvous_addr: Option<SocketAddr>,
     },
     Connect {
         nickname: String,
         server_addr: SocketAddr,
+        /// Is set when connecting to a server hosting several rooms.
+        room: Option<RoomRequest>,
+    },
+    ConnectViaRendezvous {
+        nickname: String,
+        rendezvous_addr: SocketAddr,
+        host_code: HostCode,
     },
+    /// Players who aren't the host start a vote to kick instead.
     Kick {
         player_number: usize,
     },
+    SelectClass(PlayerClass),
+    /// Is sent by the host, the settings change once the server accepts them.
+    UpdateRoomSettings(RoomSettings),
     Start,
+    StartCampaign,
+    /// Starts the match saved by the hosted server.
+    Resume,
     Leave,
+    /// Leaves the matchmaking queue and resets the connection state.
+    CancelMatchmaking,
     Reset,
 }
 
+pub enum RoomRequest {
+    Create,
+    Enter(RoomId),
+    FindMatch,
+    /// Lists the rooms for `RoomBrowser` instead of entering one.
+    List,
+}
+
+/// Rooms of a server hosting several of them, are listed by the lobby menu.
+#[derive(Default)]
+pub struct RoomBrowser {
+    pub server_addr: Option<SocketAddr>,
+    pub rooms: Vec<RoomInfo>,
+    /// The room the join address is set to.
+    pub selected: Option<usize>,
+    /// Is set once the list or the selection changes, the lobby menu redraws the list then.
+    pub is_updated: bool,
+}
+
+/// How long a started server has to report that it's ready.
+const SERVER_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// A server that crashes after getting ready is restarted up to this many times per session.
+const MAX_SERVER_RESTARTS: u32 = 3;
+/// Is doubled with every restart.
+const SERVER_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+
+pub enum ServerProcessEvent {
+    /// The server has bound its socket to the address and is about to connect to the host.
+    Ready(SocketAddr),
+    /// The server has crashed, a new one is started after the delay.
+    Restarting {
+        exit_code: Option<i32>,
+        delay: Duration,
+    },
+    Closed,
+    /// The server has crashed after running out of restarts.
+    Crashed(Option<i32>),
+    /// The server couldn't be spawned, or it exited or timed out before getting ready.
+    StartFailed,
+}
+
+/// Everything needed to start the server again after a crash.
+struct ServerLaunch {
+    addr: SocketAddr,
+    host_client_addr: SocketAddr,
+    rendezvous: Option<(SocketAddr, HostCode)>,
+    saved_match_path: PathBuf,
+    mods_dir: PathBuf,
+}
+
 pub struct ServerCommand {
+    launch: Option<ServerLaunch>,
     process: Option<ServerProcess>,
+    restarts: u32,
+    restart_at: Option<Instant>,
 }
 
 impl ServerCommand {
     pub fn new() -> Self {
-        Self { process: None }
+        Self {
+            launch: None,
+            process: None,
+            restarts: 0,
+            restart_at: None,
+        }
     }
 
-    pub fn start(&mut self, addr: SocketAddr, host_client_addr: SocketAddr) -> Result<(), Error> {
-        self.process = Some(ServerProcess::new(addr, Some(host_client_addr))?);
-        Ok(())
+    /// Returns the address the server is started at, which differs from `addr`
+    /// if its port is taken.
+    pub fn start(
+        &mut self,
+        addr: SocketAddr,
+        host_client_addr: SocketAddr,
+        rendezvous: Option<(SocketAddr, HostCode)>,
+        saved_match_path: &Path,
+        mods_dir: &Path,
+    ) -> Result<SocketAddr, Error> {
+        self.stop();
+        let launch = ServerLaunch {
+            addr: free_udp_addr(addr)?,
+            host_client_addr,
+            rendezvous,
+            saved_match_path: saved_match_path.to_owned(),
+            mods_dir: mods_dir.to_owned(),
+        };
+        self.process = Some(ServerProcess::new(&launch)?);
+        let addr = launch.addr;
+        self.launch = Some(launch);
+        Ok(addr)
     }
 
+    /// Is true while the server is running or waiting for a restart.
     pub fn is_started(&self) -> bool {
-        self.process.is_some()
+        self.launch.is_some()
     }
 
     pub fn stop(&mut self) {
+        self.launch = None;
         self.process = None;
+        self.restarts = 0;
+        self.restart_at = None;
     }
 
-    pub fn exit_status(&mut self) -> Option<ExitStatus> {
-        self.process.as_mut().and_then(|process| {
-            process
-                .cmd
-                .try_wait()
-                .expect("Expected to get a process status")
-        })
+    /// Checks on the server process, is expected to be called every frame.
+    pub fn update(&mut self) -> Option<ServerProcessEvent> {
+        if let Some(restart_at) = self.restart_at {
+            if Instant::now() < restart_at {
+                return None;
+            }
+            self.restart_at = None;
+            let launch = self.launch.as_ref()?;
+            log::info!("Restarting the server at {}", launch.addr);
+            return match ServerProcess::new(launch) {
+                Ok(process) => {
+                    self.process = Some(process);
+                    None
+                }
+                Err(err) => {
+                    log::error!("Couldn't restart the server: {:?}", err);
+                    self.stop();
+                    Some(ServerProcessEvent::StartFailed)
+                }
+            };
+        }
+
+        let process = self.process.as_mut()?;
+        if let Ok(addr) = process.ready_receiver.try_recv() {
+            process.is_ready = true;
+            return Some(ServerProcessEvent::Ready(addr));
+        }
+
+        let exit_status = process
+            .cmd
+            .try_wait()
+            .expect("Expected to get a process status");
+        let exit_status = match exit_status {
+            Some(exit_status) => exit_status,
+            None => {
+                if !process.is_ready && process.started_at.elapsed() > SERVER_STARTUP_TIMEOUT {
+                    log::error!(
+                        "The server hasn't got ready in {:?}",
+                        SERVER_STARTUP_TIMEOUT
+                    );
+                    self.stop();
+                    return Some(ServerProcessEvent::StartFailed);
+                }
+                return None;
+            }
+        };
+
+        let was_ready = process.is_ready;
+        self.process = None;
+        let event = match exit_status.code() {
+            Some(0) => ServerProcessEvent::Closed,
+            _ if !was_ready => ServerProcessEvent::StartFailed,
+            exit_code if self.restarts < MAX_SERVER_RESTARTS => {
+                let delay = SERVER_RESTART_BACKOFF * 2u32.pow(self.restarts);
+                self.restarts += 1;
+                self.restart_at = Some(Instant::now() + delay);
+                return Some(ServerProcessEvent::Restarting { exit_code, delay });
+            }
+            exit_code => ServerProcessEvent::Crashed(exit_code),
+        };
+        self.stop();
+        Some(event)
     }
 }
 
+/// Keeps the requested address if it's free, otherwise lets the OS pick a port.
+/// The probing socket is closed before the server binds the port, so there's a small chance
+/// that someone else takes it first, which is reported as a startup failure.
+fn free_udp_addr(addr: SocketAddr) -> Result<SocketAddr, Error> {
+    if addr.port() != 0 && UdpSocket::bind(addr).is_ok() {
+        return Ok(addr);
+    }
+    let socket = UdpSocket::bind(SocketAddr::new(addr.ip(), 0))?;
+    Ok(socket.local_addr()?)
+}
+
 pub struct ServerProcess {
     cmd: Child,
+    started_at: Instant,
+    is_ready: bool,
+    ready_receiver: Receiver<SocketAddr>,
 }
 
 impl ServerProcess {
-    pub fn new(addr: SocketAddr, host_client_addr: Option<SocketAddr>) -> Result<Self, Error> {
+    fn new(launch: &ServerLaunch) -> Result<Self, Error> {
         let executable_path = {
             let mut path = current_exe()?;
             path.pop();
@@ -84,17 +398,62 @@ impl ServerProcess {
         };
 
         let mut command_builder = Command::new(executable_path);
-        command_builder.arg("--addr").arg(addr.to_string());
+        command_builder
+            .arg("--addr")
+            .arg(launch.addr.to_string())
+            .arg("--client-addr")
+            .arg(launch.host_client_addr.to_string())
+            .arg("--managed");
 
-        if let Some(host_client_addr) = host_client_addr {
+        if let Some((rendezvous_addr, host_code)) = launch.rendezvous {
             command_builder
-                .arg("--client-addr")
-                .arg(host_client_addr.to_string());
+                .arg("--rendezvous")
+                .arg(rendezvous_addr.to_string())
+                .arg("--host-code")
+                .arg(host_code.to_string());
         }
 
-        let cmd = command_builder.spawn()?;
+        command_builder
+            .arg("--save")
+            .arg(&launch.saved_match_path)
+            .arg("--mods")
+            .arg(&launch.mods_dir);
+
+        // The server exits once its stdin is closed, so it doesn't outlive the client
+        // even if the client crashes and never kills it.
+        let mut cmd = command_builder
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
 
-        Ok(ServerProcess { cmd })
+        let stdout = cmd.stdout.take().expect("Expected a piped stdout");
+        let (ready_sender, ready_receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.starts_with(SERVER_READY_PREFIX) {
+                    match line[SERVER_READY_PREFIX.len()..].parse() {
+                        Ok(addr) => {
+                            let _ = ready_sender.send(addr);
+                        }
+                        Err(err) => log::error!("Couldn't parse the server address: {:?}", err),
+                    }
+                } else {
+                    // The server logs to stdout.
+                    println!("{}", line);
+                }
+            }
+        });
+
+        Ok(ServerProcess {
+            cmd,
+            started_at: Instant::now(),
+            is_ready: false,
+            ready_receiver,
+        })
     }
 }
 
@@ -0,0 +1,131 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage},
+    ui::UiText,
+};
+
+use std::collections::VecDeque;
+
+use gv_client_shared::settings::Settings;
+use gv_core::{
+    achievements::{Achievement, AchievementProgress, ACHIEVEMENT_TOAST_SECS},
+    ecs::{
+        components::{ClientPlayerActions, EntityNetMetadata, Player},
+        resources::GameEngineState,
+        system_data::time::GameTimeService,
+    },
+    room_settings::RoomSettings,
+};
+use gv_game::ecs::resources::GameEvents;
+
+use crate::ecs::system_data::ui::UiFinderMut;
+
+/// Counts the received game events towards the achievements and shows a toast for every
+/// unlocked one. Unlocks are saved to the profile right away, the kill count is saved once
+/// a game is over. Runs before `EventTickerSystem`, which drains the events.
+#[derive(Default)]
+pub struct AchievementSystem {
+    progress: Option<AchievementProgress>,
+    has_unsaved_progress: bool,
+    last_health: Option<f32>,
+    /// Is reset between campaign levels.
+    has_taken_damage: bool,
+    toasts: VecDeque<Achievement>,
+    toast_shown_at: Option<f32>,
+}
+
+impl<'s> System<'s> for AchievementSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameEvents>,
+        ReadExpect<'s, RoomSettings>,
+        WriteExpect<'s, Settings>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, Player>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            game_engine_state,
+            game_events,
+            room_settings,
+            mut settings,
+            client_player_actions,
+            entity_net_metadata,
+            players,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let progress = self
+            .progress
+            .get_or_insert_with(|| settings.profile().achievements.clone());
+
+        let local_player = (&client_player_actions, &entity_net_metadata, &players)
+            .join()
+            .next();
+        if let Some((_, _, player)) = local_player {
+            if self
+                .last_health
+                .map_or(false, |last_health| player.health < last_health)
+            {
+                self.has_taken_damage = true;
+            }
+            self.last_health = Some(player.health);
+        }
+
+        let local_player = local_player.map(|(_, entity_net_metadata, _)| entity_net_metadata.id);
+        for event in &game_events.received {
+            self.has_unsaved_progress = true;
+            if let Some(achievement) = progress.record_event(
+                event,
+                local_player,
+                room_settings.difficulty,
+                self.has_taken_damage,
+            ) {
+                log::info!("Achievement unlocked: {}", achievement.name());
+                if let Err(err) = settings.save_achievements(progress.clone()) {
+                    log::error!("Failed to save the achievements: {:?}", err);
+                }
+                self.has_unsaved_progress = false;
+                self.toasts.push_back(achievement);
+            }
+        }
+
+        if !game_engine_state.is_playing() {
+            self.last_health = None;
+            self.has_taken_damage = false;
+            if self.has_unsaved_progress {
+                if let Err(err) = settings.save_achievements(progress.clone()) {
+                    log::error!("Failed to save the achievements: {:?}", err);
+                }
+                self.has_unsaved_progress = false;
+            }
+        }
+
+        let ui_toast_label = match ui_finder.find("ui_achievement_toast_label") {
+            Some(ui_toast_label) => ui_toast_label,
+            None => return,
+        };
+        let now = game_time_service.engine_time().absolute_real_time_seconds() as f32;
+        if self
+            .toast_shown_at
+            .map_or(false, |shown_at| now - shown_at >= ACHIEVEMENT_TOAST_SECS)
+        {
+            self.toasts.pop_front();
+            self.toast_shown_at = None;
+        }
+        ui_texts.get_mut(ui_toast_label).unwrap().text = match self.toasts.front() {
+            Some(achievement) => {
+                self.toast_shown_at.get_or_insert(now);
+                format!("Achievement unlocked: {}", achievement.name())
+            }
+            None => String::new(),
+        };
+    }
+}
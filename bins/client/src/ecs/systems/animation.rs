@@ -1,11 +1,12 @@
 use amethyst::{
     animation::{AnimationCommand, AnimationControlSet, AnimationSet, EndControl},
     core::{Named, Parent, Transform},
-    ecs::{Entities, Join, ReadStorage, System, WriteStorage},
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage},
     renderer::SpriteRender,
 };
 
 use gv_animation_prefabs::AnimationId;
+use gv_client_shared::ecs::components::{Culled, MenuDemoActor};
 use gv_core::{
     ecs::{
         components::{Dead, Monster, Player},
@@ -13,7 +14,7 @@ use gv_core::{
     },
     math::Vector3,
 };
-use gv_game::utils::entities::is_dead;
+use gv_game::{ecs::resources::ClassDefinitions, utils::entities::is_dead};
 
 pub struct AnimationSystem;
 
@@ -21,9 +22,12 @@ impl<'s> System<'s> for AnimationSystem {
     type SystemData = (
         GameTimeService<'s>,
         Entities<'s>,
+        ReadExpect<'s, ClassDefinitions>,
         ReadStorage<'s, Player>,
         ReadStorage<'s, Monster>,
+        ReadStorage<'s, MenuDemoActor>,
         ReadStorage<'s, Dead>,
+        ReadStorage<'s, Culled>,
         ReadStorage<'s, Parent>,
         ReadStorage<'s, Named>,
         ReadStorage<'s, AnimationSet<AnimationId, SpriteRender>>,
@@ -36,9 +40,12 @@ impl<'s> System<'s> for AnimationSystem {
         (
             game_time_service,
             entities,
+            class_definitions,
             players,
             monsters,
+            menu_demo_actors,
             dead,
+            culled,
             parents,
             named_entities,
             animation_sets,
@@ -57,6 +64,22 @@ impl<'s> System<'s> for AnimationSystem {
         {
             let entity_is_dead =
                 is_dead(parent.entity, &dead, game_time_service.game_frame_number());
+            // All the classes share the mage prefab, its body parts get the class scale
+            // the first time we see them.
+            if !animation_control_sets.contains(entity) {
+                let class = players
+                    .get(parent.entity)
+                    .map(|player| player.class)
+                    .or_else(|| {
+                        menu_demo_actors
+                            .get(parent.entity)
+                            .and_then(|menu_demo_actor| menu_demo_actor.class)
+                    });
+                if let Some(class) = class {
+                    let scale = class_definitions.get(class).scale;
+                    transform.set_scale(Vector3::new(scale, scale, 1.0));
+                }
+            }
             let control_set = animation_control_sets
                 .entry(entity)
                 .ok()
@@ -73,7 +96,10 @@ impl<'s> System<'s> for AnimationSystem {
                         } else {
                             AnimationCommand::Start
                         };
-                        if players.contains(parent.entity) || monsters.contains(parent.entity) {
+                        if players.contains(parent.entity)
+                            || monsters.contains(parent.entity)
+                            || menu_demo_actors.contains(parent.entity)
+                        {
                             control_set.add_animation(
                                 AnimationId::Walk,
                                 &animation_set.get(&AnimationId::Walk).unwrap(),
@@ -87,13 +113,29 @@ impl<'s> System<'s> for AnimationSystem {
                 })
                 .expect("Expected an initialized AnimationControlSet");
 
+            // Paused animations aren't sampled, which saves a lot with big waves of monsters.
+            // Commands of dead entities are left alone, not to override aborting the Walk one.
+            let is_culled = culled.contains(parent.entity);
+            if !entity_is_dead {
+                if is_culled {
+                    control_set.pause(AnimationId::Walk);
+                } else {
+                    control_set.start(AnimationId::Walk);
+                }
+            }
+            if is_culled {
+                continue;
+            }
+
             let player = players.get(parent.entity);
             let monster = monsters.get(parent.entity);
+            let menu_demo_actor = menu_demo_actors.get(parent.entity);
 
             // TODO: set rate depending on base speed.
             let entity_velocity = player
                 .map(|player| player.velocity)
-                .or_else(|| monster.map(|monster| monster.velocity));
+                .or_else(|| monster.map(|monster| monster.velocity))
+                .or_else(|| menu_demo_actor.map(|actor| actor.velocity));
             if let Some(entity_velocity) = entity_velocity {
                 let rate = if entity_is_dead || entity_velocity.norm_squared() == 0.0 {
                     0.0
@@ -126,6 +168,17 @@ impl<'s> System<'s> for AnimationSystem {
                     transform.translation().z,
                 );
                 transform.face_towards(Vector3::new(0.0, 0.0, 1.0), direction);
+            } else if let Some(menu_demo_actor) = menu_demo_actor {
+                // Bots look where they walk or shoot, just like players.
+                let direction = if menu_demo_actor.is_monster {
+                    menu_demo_actor.looking_direction
+                } else if named.name == "mage_legs" {
+                    -menu_demo_actor.velocity
+                } else {
+                    -menu_demo_actor.looking_direction
+                };
+                let direction = Vector3::new(direction.x, direction.y, transform.translation().z);
+                transform.face_towards(Vector3::new(0.0, 0.0, 1.0), direction);
             }
         }
     }
@@ -0,0 +1,92 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::{output::Output, Source},
+    ecs::{Join, Read, ReadExpect, ReadStorage, System},
+};
+
+use gv_client_shared::{ecs::resources::LevelAssets, settings::Settings};
+use gv_core::{
+    announcer::{AnnouncerCooldowns, AnnouncerLine},
+    ecs::{
+        components::{ClientPlayerActions, EntityNetMetadata},
+        resources::GameEngineState,
+        system_data::time::GameTimeService,
+    },
+};
+use gv_game::ecs::resources::GameEvents;
+
+use crate::ecs::resources::WindowFocus;
+
+/// Plays announcer lines for the received game events, unless the announcer is turned off
+/// in the settings. Each line has its own cooldown, so a burst of events of the same kind
+/// is announced once. Runs before `EventTickerSystem`, which drains the events.
+#[derive(Default)]
+pub struct AnnouncerSystem {
+    cooldowns: AnnouncerCooldowns,
+}
+
+impl<'s> System<'s> for AnnouncerSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, WindowFocus>,
+        ReadExpect<'s, GameEvents>,
+        Read<'s, LevelAssets>,
+        Option<Read<'s, Output>>,
+        Read<'s, AssetStorage<Source>>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, EntityNetMetadata>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            settings,
+            window_focus,
+            game_events,
+            level_assets,
+            output,
+            source_storage,
+            client_player_actions,
+            entity_net_metadata,
+        ): Self::SystemData,
+    ) {
+        if !game_engine_state.is_playing() {
+            self.cooldowns.reset();
+            return;
+        }
+        let audio_settings = settings.audio();
+        if !audio_settings.announcer || window_focus.is_muted || game_events.received.is_empty() {
+            return;
+        }
+        let (level_asset_handles, output) = match (level_assets.0.as_ref(), output) {
+            (Some(level_asset_handles), Some(output)) => (level_asset_handles, output),
+            _ => return,
+        };
+
+        let local_player = (&client_player_actions, &entity_net_metadata)
+            .join()
+            .next()
+            .map(|(_, entity_net_metadata)| entity_net_metadata.id);
+        let now = game_time_service.engine_time().absolute_real_time();
+        for event in &game_events.received {
+            let line = match AnnouncerLine::from_event(event, local_player) {
+                Some(line) => line,
+                None => continue,
+            };
+            if !self.cooldowns.try_play(line, now) {
+                continue;
+            }
+            if let Some(source) = level_asset_handles
+                .announcer
+                .get(line.index())
+                .and_then(|handle| source_storage.get(handle))
+            {
+                output.play_once(source, audio_settings.announcer_volume);
+            }
+        }
+    }
+}
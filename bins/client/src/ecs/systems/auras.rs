@@ -0,0 +1,123 @@
+use amethyst::{
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+};
+
+use gv_client_shared::ecs::resources::AssetHandles;
+use gv_core::ecs::{
+    components::{
+        aura::{AuraEffect, Auras},
+        Dead, WorldPosition,
+    },
+    system_data::time::GameTimeService,
+};
+
+const RING_Z: f32 = -0.4;
+const RING_SPRITE_RADIUS: f32 = 32.0;
+const PULSE_SPEED: f32 = 2.0;
+const RING_ALPHA: f32 = 0.6;
+const PULSE_ALPHA: f32 = 0.15;
+
+/// Draws a ring on the ground around each aura of players and elites. Rings follow
+/// their owners and are hidden while the owners are dead.
+#[derive(Default)]
+pub struct AuraRingSystem {
+    rings: Vec<(Entity, Entity)>,
+}
+
+impl<'s> System<'s> for AuraRingSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        ReadStorage<'s, Auras>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            asset_handles,
+            entities,
+            auras,
+            world_positions,
+            dead,
+            mut sprite_renders,
+            mut transforms,
+            mut tints,
+        ): Self::SystemData,
+    ) {
+        let sprite_sheet = match asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.auras.clone(),
+            None => return,
+        };
+
+        self.rings.retain(|(owner, ring)| {
+            let is_owner_alive = entities.is_alive(*owner) && auras.contains(*owner);
+            if !is_owner_alive {
+                entities
+                    .delete(*ring)
+                    .expect("Expected to delete an aura ring");
+            }
+            is_owner_alive
+        });
+
+        for (owner, owner_auras) in (&entities, &auras).join() {
+            if self
+                .rings
+                .iter()
+                .any(|(ringed_owner, _)| *ringed_owner == owner)
+            {
+                continue;
+            }
+            for aura in &owner_auras.0 {
+                let sprite_number = match aura.effect {
+                    AuraEffect::Damage { .. } => 0,
+                    AuraEffect::Slow { .. } => 1,
+                    AuraEffect::Healing { .. } => 2,
+                };
+                let scale = aura.radius / RING_SPRITE_RADIUS;
+                let mut transform = Transform::default();
+                transform.set_scale(Vector3::new(scale, scale, 1.0));
+                let ring = entities
+                    .build_entity()
+                    .with(
+                        SpriteRender {
+                            sprite_sheet: sprite_sheet.clone(),
+                            sprite_number,
+                        },
+                        &mut sprite_renders,
+                    )
+                    .with(transform, &mut transforms)
+                    .build();
+                self.rings.push((owner, ring));
+            }
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let seconds = frame_number as f32 * game_time_service.tick_seconds();
+        let pulse_alpha = RING_ALPHA + PULSE_ALPHA * (seconds * PULSE_SPEED).sin();
+        for (owner, ring) in &self.rings {
+            let position = match world_positions.get(*owner) {
+                Some(world_position) => **world_position,
+                None => continue,
+            };
+            if let Some(transform) = transforms.get_mut(*ring) {
+                transform.set_translation_xyz(position.x, position.y, RING_Z);
+            }
+            let is_owner_dead = dead
+                .get(*owner)
+                .map_or(false, |dead| dead.is_dead(frame_number));
+            let alpha = if is_owner_dead { 0.0 } else { pulse_alpha };
+            tints
+                .insert(*ring, Tint(Srgba::new(1.0, 1.0, 1.0, alpha)))
+                .expect("Expected to insert a Tint");
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use amethyst::{
+    core::{
+        math::Vector3,
+        transform::{Parent, Transform},
+        Time,
+    },
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+    window::ScreenDimensions,
+};
+
+use gv_client_shared::settings::Settings;
+use gv_core::{
+    ecs::{
+        components::{affixes::MonsterAffixes, Dead, WorldPosition},
+        resources::GameLevelState,
+    },
+    math::Vector2,
+};
+
+use crate::ecs::resources::{DisplayDebugInfoSettings, SpectatorCamera, SpectatorCameraMode};
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 1.5;
+const ZOOM_STEP: f32 = 0.1;
+/// The free camera can be zoomed out further to look at the whole level.
+const FREE_CAMERA_MAX_ZOOM: f32 = 4.0;
+/// Units per second at 100% zoom.
+const FREE_CAMERA_SPEED: f32 = 800.0;
+/// Framing a boss never zooms out further than this.
+const MAX_FRAMING_ZOOM: f32 = 2.0;
+/// Elites further than this from the player aren't framed.
+const FRAMING_RADIUS: f32 = 600.0;
+/// Space left between a framed boss and the screen edges.
+const FRAMING_PADDING: f32 = 96.0;
+
+/// Moves the camera attached to the player. The camera catches up with the player
+/// with `VideoSettings::camera_smoothing`, is zoomed with the mouse wheel and widens
+/// to keep the nearest living elite on screen. It never shows anything beyond the level borders.
+///
+/// The camera stays a child of the player entity, so this system only changes its local
+/// translation and projection. With `DisplayDebugInfoSettings::free_camera` on, it flies
+/// with WASD instead and isn't kept within the level. While the player is dead, it follows
+/// `SpectatorCamera::target` or flies in the same way.
+#[derive(Default)]
+pub struct CameraSystem {
+    camera_entity: Option<Entity>,
+    position: Vector2,
+    zoom: f32,
+    current_zoom: f32,
+}
+
+impl<'s> System<'s> for CameraSystem {
+    type SystemData = (
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, SpectatorCamera>,
+        Read<'s, Time>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        Entities<'s>,
+        ReadStorage<'s, Parent>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, MonsterAffixes>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_level_state,
+            screen_dimensions,
+            settings,
+            display_debug_info_settings,
+            spectator_camera,
+            time,
+            input,
+            entities,
+            parents,
+            world_positions,
+            monster_affixes,
+            dead,
+            mut cameras,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        let components = (&entities, &parents, &mut cameras).join().next();
+        let (camera_entity, camera_parent, camera) = match components {
+            Some(components) => components,
+            None => return,
+        };
+        let player_position = match world_positions.get(camera_parent.entity) {
+            Some(player_position) => **player_position,
+            None => return,
+        };
+
+        let is_new_camera = self.camera_entity != Some(camera_entity);
+        if is_new_camera {
+            self.camera_entity = Some(camera_entity);
+            self.position = player_position;
+            self.zoom = 1.0;
+            self.current_zoom = 1.0;
+        }
+
+        let is_free_camera = display_debug_info_settings.free_camera
+            || (spectator_camera.is_active
+                && spectator_camera.mode == SpectatorCameraMode::FreeFly);
+        let max_zoom = if is_free_camera {
+            FREE_CAMERA_MAX_ZOOM
+        } else {
+            MAX_ZOOM
+        };
+        // Scrolling in the console mustn't zoom the camera.
+        if !display_debug_info_settings.display_console {
+            let wheel = input.mouse_wheel_value(false);
+            if wheel != 0.0 {
+                self.zoom = (self.zoom - wheel.signum() * ZOOM_STEP).clamp(MIN_ZOOM, max_zoom);
+            }
+        }
+        self.zoom = self.zoom.min(max_zoom);
+
+        let screen_half_size =
+            Vector2::new(screen_dimensions.width(), screen_dimensions.height()) / 2.0;
+
+        if is_free_camera {
+            let direction = Vector2::new(
+                input.axis_value("horizontal").unwrap_or_default(),
+                input.axis_value("vertical").unwrap_or_default(),
+            );
+            if !display_debug_info_settings.display_console {
+                self.position +=
+                    direction * FREE_CAMERA_SPEED * self.current_zoom * time.delta_seconds();
+            }
+            self.current_zoom = self.zoom;
+            self.apply(
+                camera,
+                camera_entity,
+                &mut transforms,
+                &screen_dimensions,
+                self.position,
+                player_position,
+            );
+            return;
+        }
+
+        let followed_position = spectator_camera
+            .target
+            .filter(|_| spectator_camera.is_active)
+            .and_then(|target| world_positions.get(target))
+            .map_or(player_position, |world_position| **world_position);
+        let mut target_position = followed_position;
+        let mut target_zoom = self.zoom;
+        let boss_position = (&monster_affixes, &world_positions, !&dead)
+            .join()
+            .filter(|(affixes, _, _)| !affixes.affixes.is_empty())
+            .map(|(_, world_position, _)| **world_position)
+            .filter(|position| (position - followed_position).norm() < FRAMING_RADIUS)
+            .min_by(|a, b| {
+                (a - followed_position)
+                    .norm_squared()
+                    .partial_cmp(&(b - followed_position).norm_squared())
+                    .expect("Expected a non-NaN distance")
+            });
+        if let Some(boss_position) = boss_position {
+            target_position = (followed_position + boss_position) / 2.0;
+            let required_half_size = (boss_position - followed_position).abs() / 2.0
+                + Vector2::new(1.0, 1.0) * FRAMING_PADDING;
+            let required_zoom = (required_half_size.x / screen_half_size.x)
+                .max(required_half_size.y / screen_half_size.y);
+            target_zoom = target_zoom.max(required_zoom.min(MAX_FRAMING_ZOOM));
+        }
+
+        let smoothing = settings.video().camera_smoothing;
+        let t = if smoothing > 0.0 && !is_new_camera {
+            1.0 - (-smoothing * time.delta_seconds()).exp()
+        } else {
+            1.0
+        };
+        self.current_zoom += (target_zoom - self.current_zoom) * t;
+        self.position += (target_position - self.position) * t;
+
+        // Keeping the visible area within the level, or centered if the level is smaller.
+        let visible_half_size = screen_half_size * self.current_zoom;
+        let level_half_size = game_level_state.dimensions_half_size();
+        let clamp_axis = |position: f32, visible_half_size: f32, level_half_size: f32| {
+            let max_offset = level_half_size - visible_half_size;
+            if max_offset > 0.0 {
+                position.clamp(-max_offset, max_offset)
+            } else {
+                0.0
+            }
+        };
+        let camera_position = Vector2::new(
+            clamp_axis(self.position.x, visible_half_size.x, level_half_size.x),
+            clamp_axis(self.position.y, visible_half_size.y, level_half_size.y),
+        );
+
+        self.apply(
+            camera,
+            camera_entity,
+            &mut transforms,
+            &screen_dimensions,
+            camera_position,
+            player_position,
+        );
+    }
+}
+
+impl CameraSystem {
+    fn apply(
+        &self,
+        camera: &mut Camera,
+        camera_entity: Entity,
+        transforms: &mut WriteStorage<'_, Transform>,
+        screen_dimensions: &ScreenDimensions,
+        camera_position: Vector2,
+        player_position: Vector2,
+    ) {
+        *camera = Camera::standard_2d(
+            screen_dimensions.width() * self.current_zoom,
+            screen_dimensions.height() * self.current_zoom,
+        );
+
+        let camera_translation = camera_position - player_position;
+        let camera_transform = transforms
+            .get_mut(camera_entity)
+            .expect("Expected a Transform");
+        camera_transform.set_translation(Vector3::new(
+            camera_translation.x,
+            camera_translation.y,
+            camera_transform.translation().z,
+        ));
+    }
+}
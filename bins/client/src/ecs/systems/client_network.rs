@@ -10,34 +10,58 @@ use std::{
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use gv_client_shared::ecs::resources::{ConnectionStatus, MultiplayerRoomState};
+use gv_client_shared::{
+    ecs::resources::{ConnectionStatus, MultiplayerRoomState},
+    settings::Settings,
+};
 use gv_core::{
     actions::monster_spawn::SpawnActions,
+    balance::Balance,
+    content_packs::{ContentPackInfo, ContentPacks},
     ecs::{
         components::NetConnectionModel,
         resources::{
-            net::{MultiplayerGameState, PlayersNetStatus},
+            is_supported_tick_rate,
+            net::{
+                LastAcknowledgedUpdate, MultiplayerGameState, PlayersNetStatus,
+                ReconciliationStatus, Rendezvous, RendezvousHost, UnacknowledgedInputs,
+            },
             world::{
-                FramedUpdates, PlayerActionUpdates, ReceivedPlayerUpdate,
-                ReceivedServerWorldUpdate, ServerWorldUpdate, PAUSE_FRAME_THRESHOLD,
+                FramedUpdates, PlayerActionUpdates, ReceivedServerWorldUpdate,
+                LAG_COMPENSATION_FRAMES_LIMIT, PAUSE_FRAME_THRESHOLD,
             },
-            GameEngineState, NewGameEngineState,
+            GameEngineState, MatchScore, NewGameEngineState,
         },
         system_data::time::GameTimeService,
     },
     net::{
         client_message::ClientMessagePayload,
-        server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
-        NetEvent, NetIdentifier, INTERPOLATION_FRAME_DELAY,
+        rendezvous::HostCode,
+        server_message::{
+            ClientNetworkError, DisconnectReason, ServerMessage, ServerMessagePayload,
+        },
+        session_recording::{RecordedMessage, SessionRecorder},
+        NetEvent, NetIdentifier,
     },
+    room_settings::RoomSettings,
+    votes::VoteKind,
 };
 use gv_game::{
-    ecs::resources::ConnectionEvents,
-    utils::net::{send_message_reliable, send_message_unreliable},
+    console::Console,
+    ecs::resources::{
+        ActiveLevelScript, AugmentChoices, ClassDefinitions, ConnectionEvents, GameEvents,
+        MonsterDefinitions, ShopPurchases, SpellDefinitions, SummonRequests, WaveReadiness,
+    },
+    scripting::ScriptHooks,
+    utils::{
+        net::{send_message_reliable, send_message_unreliable},
+        world_updates::{discard_walk_actions, receive_world_update},
+    },
 };
 
 use crate::ecs::resources::{
-    LastAcknowledgedUpdate, ServerCommand, UiNetworkCommand, UiNetworkCommandResource,
+    Emotes, RoomBrowser, RoomRequest, ServerCommand, ServerProcessEvent, UiNetworkCommand,
+    UiNetworkCommandResource, Votes,
 };
 
 const HEARTBEAT_FRAME_INTERVAL: u64 = 10;
@@ -46,10 +70,12 @@ const HEARTBEAT_FRAME_INTERVAL: u64 = 10;
 pub struct ClientNetworkSystemData<'s> {
     game_time_service: GameTimeService<'s>,
     game_engine_state: ReadExpect<'s, GameEngineState>,
+    settings: ReadExpect<'s, Settings>,
     entities: Entities<'s>,
     connection_events: WriteExpect<'s, ConnectionEvents>,
     multiplayer_room_state: WriteExpect<'s, MultiplayerRoomState>,
     multiplayer_game_state: WriteExpect<'s, MultiplayerGameState>,
+    room_settings: WriteExpect<'s, RoomSettings>,
     new_game_engine_sate: WriteExpect<'s, NewGameEngineState>,
     last_acknowledged_update: WriteExpect<'s, LastAcknowledgedUpdate>,
     framed_updates: WriteExpect<'s, FramedUpdates<ReceivedServerWorldUpdate>>,
@@ -57,7 +83,28 @@ pub struct ClientNetworkSystemData<'s> {
     spawn_actions: WriteExpect<'s, FramedUpdates<SpawnActions>>,
     server_command: WriteExpect<'s, ServerCommand>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
+    emotes: WriteExpect<'s, Emotes>,
+    votes: WriteExpect<'s, Votes>,
+    room_browser: WriteExpect<'s, RoomBrowser>,
+    augment_choices: WriteExpect<'s, AugmentChoices>,
+    summon_requests: WriteExpect<'s, SummonRequests>,
+    shop_purchases: WriteExpect<'s, ShopPurchases>,
+    wave_readiness: WriteExpect<'s, WaveReadiness>,
+    active_level_script: WriteExpect<'s, ActiveLevelScript>,
+    game_events: WriteExpect<'s, GameEvents>,
     players_net_status: WriteExpect<'s, PlayersNetStatus>,
+    reconciliation_status: WriteExpect<'s, ReconciliationStatus>,
+    unacknowledged_inputs: WriteExpect<'s, UnacknowledgedInputs>,
+    match_score: WriteExpect<'s, MatchScore>,
+    balance: WriteExpect<'s, Balance>,
+    class_definitions: WriteExpect<'s, ClassDefinitions>,
+    monster_definitions: WriteExpect<'s, MonsterDefinitions>,
+    spell_definitions: WriteExpect<'s, SpellDefinitions>,
+    content_packs: WriteExpect<'s, ContentPacks>,
+    script_hooks: WriteExpect<'s, ScriptHooks>,
+    console: WriteExpect<'s, Console>,
+    rendezvous: Write<'s, Rendezvous>,
+    session_recorder: WriteExpect<'s, SessionRecorder>,
     net_connection_models: WriteStorage<'s, NetConnectionModel>,
     transport: Write<'s, TransportResource>,
     laminar_socket: WriteExpect<'s, LaminarSocketResource>,
@@ -69,6 +116,8 @@ pub struct ClientNetworkSystem {
     last_heartbeat_frame: u64,
     has_sent_join_message: bool,
     nickname: String,
+    /// The packs from the last Handshake.
+    advertised_content_packs: Vec<ContentPackInfo>,
 }
 
 impl ClientNetworkSystem {
@@ -78,6 +127,61 @@ impl ClientNetworkSystem {
         id
     }
 
+    fn join_room_message(&self, settings: &Settings) -> ClientMessagePayload {
+        ClientMessagePayload::JoinRoom {
+            sent_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Expected a duration unix timestamp"),
+            nickname: self.nickname.clone(),
+            class: settings.profile().class,
+            cosmetics: settings.profile().cosmetics.clone(),
+        }
+    }
+
+    fn connect(
+        &mut self,
+        system_data: &mut ClientNetworkSystemData,
+        server_addr: SocketAddr,
+        room: Option<RoomRequest>,
+    ) {
+        let net_connection_model = NetConnectionModel::new(0, self.next_session_id(), server_addr);
+
+        // With a room requested, JoinRoom is sent after the server confirms entering it.
+        let payload = match room {
+            None => {
+                log::info!("Sending a JoinRoom message");
+                self.has_sent_join_message = true;
+                self.join_room_message(&system_data.settings)
+            }
+            Some(RoomRequest::Create) => {
+                log::info!("Sending a CreateRoom message");
+                ClientMessagePayload::CreateRoom {
+                    name: format!("{}'s room", self.nickname),
+                }
+            }
+            Some(RoomRequest::Enter(room_id)) => {
+                log::info!("Sending an EnterRoom message (room id: {})", room_id);
+                ClientMessagePayload::EnterRoom(room_id)
+            }
+            Some(RoomRequest::FindMatch) => {
+                let rating = system_data.settings.profile().rating;
+                log::info!("Sending a FindMatch message (rating: {})", rating);
+                ClientMessagePayload::FindMatch { rating }
+            }
+            Some(RoomRequest::List) => {
+                log::info!("Sending a ListRooms message");
+                ClientMessagePayload::ListRooms
+            }
+        };
+        send_message_reliable(&mut system_data.transport, &net_connection_model, payload);
+
+        system_data
+            .entities
+            .build_entity()
+            .with(net_connection_model, &mut system_data.net_connection_models)
+            .build();
+    }
+
     fn process_ui_network_command(
         &mut self,
         system_data: &mut ClientNetworkSystemData,
@@ -87,6 +191,7 @@ impl ClientNetworkSystem {
             UiNetworkCommand::Host {
                 nickname,
                 server_addr,
+                rendezvous_addr,
             } => {
                 self.nickname = nickname;
                 system_data.multiplayer_room_state.is_active = true;
@@ -104,19 +209,41 @@ impl ClientNetworkSystem {
                     SocketAddr::V4(addr) => addr.set_ip(Ipv4Addr::new(127, 0, 0, 1)),
                     SocketAddr::V6(addr) => addr.set_ip(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
                 };
-                if let Err(err) = system_data
-                    .server_command
-                    .start(server_addr, host_client_addr)
-                {
-                    log::error!("Couldn't start the server: {:?}", err);
-                    system_data.multiplayer_room_state.connection_status =
-                        ConnectionStatus::ServerStartFailed;
+                let rendezvous = rendezvous_addr.map(|rendezvous_addr| {
+                    let host_code = rand::random::<HostCode>() % 1_000_000;
+                    system_data.multiplayer_room_state.invite =
+                        Some(format!("{:06}@{}", host_code, rendezvous_addr));
+                    (rendezvous_addr, host_code)
+                });
+                let saved_match_path = system_data.settings.saved_match_path();
+                let mods_dir = system_data.settings.mods_dir();
+                match system_data.server_command.start(
+                    server_addr,
+                    host_client_addr,
+                    rendezvous,
+                    &saved_match_path,
+                    &mods_dir,
+                ) {
+                    Ok(started_at_addr) if started_at_addr != server_addr => {
+                        log::warn!(
+                            "The port {} is taken, hosting at {} instead",
+                            server_addr.port(),
+                            started_at_addr
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        log::error!("Couldn't start the server: {:?}", err);
+                        system_data.multiplayer_room_state.connection_status =
+                            ConnectionStatus::ServerStartFailed;
+                    }
                 }
             }
 
             UiNetworkCommand::Connect {
                 nickname,
                 server_addr,
+                room,
             } => {
                 self.nickname = nickname;
                 system_data.multiplayer_room_state.is_active = true;
@@ -124,50 +251,61 @@ impl ClientNetworkSystem {
                 system_data.multiplayer_room_state.connection_status =
                     ConnectionStatus::Connecting(Instant::now());
 
-                let net_connection_model =
-                    NetConnectionModel::new(0, self.next_session_id(), server_addr);
+                self.connect(system_data, server_addr, room);
+            }
 
-                log::info!("Sending a JoinRoom message");
-                self.has_sent_join_message = true;
+            UiNetworkCommand::ConnectViaRendezvous {
+                nickname,
+                rendezvous_addr,
+                host_code,
+            } => {
+                self.nickname = nickname;
+                system_data.multiplayer_room_state.is_active = true;
+                system_data.multiplayer_room_state.is_host = false;
+                system_data.multiplayer_room_state.connection_status =
+                    ConnectionStatus::Connecting(Instant::now());
+                // The connection is created once the rendezvous server introduces the host.
+                *system_data.rendezvous = Rendezvous::guest(rendezvous_addr, host_code);
+            }
+
+            UiNetworkCommand::Kick { player_number } => {
+                let kicked_connection_id =
+                    system_data.multiplayer_game_state.players[player_number].connection_id;
                 send_message_reliable(
                     &mut system_data.transport,
-                    &net_connection_model,
-                    ClientMessagePayload::JoinRoom {
-                        sent_at: SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .expect("Expected a duration unix timestamp"),
-                        nickname: self.nickname.clone(),
+                    server_connection(&mut system_data.net_connection_models),
+                    if system_data.multiplayer_room_state.is_host {
+                        ClientMessagePayload::Kick {
+                            kicked_connection_id,
+                        }
+                    } else {
+                        ClientMessagePayload::StartVote(VoteKind::Kick(kicked_connection_id))
                     },
                 );
-
-                system_data
-                    .entities
-                    .build_entity()
-                    .with(net_connection_model, &mut system_data.net_connection_models)
-                    .build();
             }
 
-            UiNetworkCommand::Kick { player_number } => send_message_reliable(
+            UiNetworkCommand::SelectClass(class) => send_message_reliable(
+                &mut system_data.transport,
+                server_connection(&mut system_data.net_connection_models),
+                ClientMessagePayload::SelectClass(class),
+            ),
+
+            UiNetworkCommand::UpdateRoomSettings(room_settings) => send_message_reliable(
                 &mut system_data.transport,
                 server_connection(&mut system_data.net_connection_models),
-                ClientMessagePayload::Kick {
-                    kicked_connection_id: system_data.multiplayer_game_state.players[player_number]
-                        .connection_id,
-                },
+                ClientMessagePayload::UpdateRoomSettings(room_settings),
             ),
 
             UiNetworkCommand::Start => {
-                if system_data.multiplayer_room_state.is_host {
-                    send_message_reliable(
-                        &mut system_data.transport,
-                        server_connection(&mut system_data.net_connection_models),
-                        ClientMessagePayload::StartHostedGame,
-                    );
-                } else {
-                    log::error!(
-                        "Client check failed: only host can send a StartHostedGame message"
-                    );
-                }
+                start_hosted_game(system_data, ClientMessagePayload::StartHostedGame)
+            }
+
+            UiNetworkCommand::StartCampaign => {
+                start_hosted_game(system_data, ClientMessagePayload::StartHostedCampaign)
+            }
+
+            UiNetworkCommand::Resume => {
+                start_hosted_game(system_data, ClientMessagePayload::ResumeHostedGame)
             }
 
             UiNetworkCommand::Leave => {
@@ -188,13 +326,31 @@ impl ClientNetworkSystem {
                     }
             }
 
+            UiNetworkCommand::CancelMatchmaking => {
+                if let Some(net_connection_model) =
+                    (&mut system_data.net_connection_models).join().next()
+                {
+                    send_message_reliable(
+                        &mut system_data.transport,
+                        net_connection_model,
+                        ClientMessagePayload::CancelMatchmaking,
+                    );
+                    net_connection_model.disconnected = true;
+                }
+                self.process_ui_network_command(system_data, UiNetworkCommand::Reset);
+            }
+
             UiNetworkCommand::Reset => {
                 self.has_sent_join_message = false;
+                system_data.rendezvous.reset();
                 self.last_heartbeat_frame = 0;
                 system_data.multiplayer_room_state.connection_status =
                     ConnectionStatus::NotConnected;
                 system_data.multiplayer_game_state.reset();
                 system_data.multiplayer_room_state.reset();
+                *system_data.emotes = Emotes::default();
+                *system_data.votes = Votes::default();
+                *system_data.room_settings = RoomSettings::default();
             }
         }
     }
@@ -214,23 +370,68 @@ impl<'s> System<'s> for ClientNetworkSystem {
             return;
         }
 
-        if system_data.server_command.is_started() {
-            if let Some(exit_status) = system_data.server_command.exit_status() {
-                let code = exit_status.code().expect("Expected an exit status code");
-                if code == 0 {
+        if let Some(server_process_event) = system_data.server_command.update() {
+            let connection_status = match server_process_event {
+                ServerProcessEvent::Ready(addr) => {
+                    log::info!("The server is ready at {}", addr);
+                    None
+                }
+                ServerProcessEvent::Restarting { exit_code, delay } => {
+                    log::error!(
+                        "The server crashed (exit code: {:?}), restarting it in {:?}",
+                        exit_code,
+                        delay
+                    );
+                    // The new server connects and sends a Handshake, just like the first one.
+                    self.has_sent_join_message = false;
+                    system_data.net_connection_models.clear();
+                    // HiddenMenuScreen takes the player back to the room once it's connected.
+                    system_data.multiplayer_game_state.is_playing = false;
+                    Some(ConnectionStatus::Connecting(Instant::now()))
+                }
+                ServerProcessEvent::Closed => {
                     log::info!("The server has closed");
-                    system_data.multiplayer_room_state.connection_status =
-                        ConnectionStatus::Disconnected(DisconnectReason::Closed);
-                } else {
+                    Some(ConnectionStatus::Disconnected(DisconnectReason::Closed))
+                }
+                ServerProcessEvent::Crashed(Some(code)) => {
                     log::error!("The server crashed with the exit code {}", code);
-                    system_data.multiplayer_room_state.connection_status =
-                        ConnectionStatus::Disconnected(DisconnectReason::ServerCrashed(code));
+                    Some(ConnectionStatus::Disconnected(
+                        DisconnectReason::ServerCrashed(code),
+                    ))
+                }
+                ServerProcessEvent::Crashed(None) => {
+                    log::error!("The server was terminated without an exit status code");
+                    Some(ConnectionStatus::Disconnected(
+                        DisconnectReason::ClientNetworkError(ClientNetworkError::ServerTerminated),
+                    ))
                 }
-                system_data.server_command.stop();
+                ServerProcessEvent::StartFailed => Some(ConnectionStatus::ServerStartFailed),
+            };
+            if let Some(connection_status) = connection_status {
+                system_data.multiplayer_room_state.connection_status = connection_status;
             }
         }
 
         if system_data.net_connection_models.count() == 0 {
+            match system_data.rendezvous.guest_host() {
+                Some(RendezvousHost::Punched(host_addr))
+                | Some(RendezvousHost::Relayed(host_addr)) => {
+                    log::info!(
+                        "Connecting to the host {} via the rendezvous server",
+                        host_addr
+                    );
+                    self.connect(&mut system_data, host_addr, None);
+                    return;
+                }
+                Some(RendezvousHost::NotFound) => {
+                    system_data.rendezvous.reset();
+                    system_data.multiplayer_room_state.connection_status =
+                        ConnectionStatus::Disconnected(DisconnectReason::RendezvousHostNotFound);
+                    return;
+                }
+                _ => {}
+            }
+
             if system_data.multiplayer_game_state.is_playing
                 && *system_data.game_engine_state == GameEngineState::Playing
             {
@@ -241,7 +442,76 @@ impl<'s> System<'s> for ClientNetworkSystem {
 
         // TODO: implement rejecting incoming connections for client, cause this can fail badly.
         let net_connection_model = server_connection(&mut system_data.net_connection_models);
+        if let Some(emote) = system_data.emotes.outgoing.take() {
+            send_message_reliable(
+                &mut system_data.transport,
+                net_connection_model,
+                ClientMessagePayload::Emote(emote),
+            );
+        }
+        if let Some(kind) = system_data.votes.outgoing_start.take() {
+            send_message_reliable(
+                &mut system_data.transport,
+                net_connection_model,
+                ClientMessagePayload::StartVote(kind),
+            );
+        }
+        if let Some(is_yes) = system_data.votes.outgoing_ballot.take() {
+            if let Some(vote_id) = system_data.votes.running.as_ref().map(|vote| vote.id) {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::CastVote { vote_id, is_yes },
+                );
+            }
+        }
+        if system_data.multiplayer_game_state.is_playing {
+            if let Some(augment) = system_data.augment_choices.local.take() {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::ChooseAugment(augment),
+                );
+            }
+            if std::mem::replace(&mut system_data.summon_requests.local, false) {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::Summon,
+                );
+            }
+            if let Some(item) = system_data.shop_purchases.local.take() {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::BuyItem(item),
+                );
+            }
+            if std::mem::replace(&mut system_data.wave_readiness.local, false) {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::ReadyForWave,
+                );
+            }
+            for line in system_data.console.outgoing.drain(..) {
+                send_message_reliable(
+                    &mut system_data.transport,
+                    net_connection_model,
+                    ClientMessagePayload::ConsoleCommand(line),
+                );
+            }
+        }
         for connection_event in system_data.connection_events.0.drain(..) {
+            if let NetEvent::Message(message) = &connection_event.event {
+                if system_data.session_recorder.is_recording() {
+                    system_data.session_recorder.record(
+                        system_data.game_time_service.game_frame_number(),
+                        RecordedMessage::Incoming(message.clone()),
+                    );
+                }
+            }
+
             // Ignore all the messages for disconnected models, except for Disconnected or Handshake.
             if net_connection_model.disconnected {
                 let ignore_event = match connection_event.event {
@@ -271,8 +541,11 @@ impl<'s> System<'s> for ClientNetworkSystem {
                         payload,
                     }) => match payload {
                         ServerMessagePayload::Handshake { .. } => true,
-                        ServerMessagePayload::UpdateRoomPlayers(_) => true,
+                        // UpdateRoomPlayers isn't ignored: players keep their entity net ids
+                        // during a game, the updates mark the ones who've lost the connection.
                         ServerMessagePayload::StartGame(_) => true,
+                        ServerMessagePayload::ResumeGame { .. } => true,
+                        ServerMessagePayload::RejoinGame { .. } => true,
                         _ => false,
                     },
                     _ => false,
@@ -287,6 +560,25 @@ impl<'s> System<'s> for ClientNetworkSystem {
                     session_id: _,
                     payload,
                 }) => {
+                    // Resuming or rejoining is the same as starting a game, but with the saved match
+                    // or the snapshot applied.
+                    let payload = match payload {
+                        ServerMessagePayload::ResumeGame {
+                            entity_net_ids,
+                            saved_match,
+                        } => {
+                            system_data.multiplayer_game_state.resumed_match = Some(saved_match);
+                            ServerMessagePayload::StartGame(entity_net_ids)
+                        }
+                        ServerMessagePayload::RejoinGame {
+                            entity_net_ids,
+                            snapshot,
+                        } => {
+                            system_data.multiplayer_game_state.rejoined_match = Some(snapshot);
+                            ServerMessagePayload::StartGame(entity_net_ids)
+                        }
+                        payload => payload,
+                    };
                     match payload {
                         // Are covered by NetConnectionManager.
                         ServerMessagePayload::Heartbeat
@@ -296,12 +588,54 @@ impl<'s> System<'s> for ClientNetworkSystem {
                         ServerMessagePayload::Handshake {
                             net_id: connection_id,
                             is_host,
+                            tick_rate,
+                            content_packs,
                         } => {
                             log::info!(
-                                "Received Handshake from a server ({}), is_host: {}",
+                                "Received Handshake from a server ({}), is_host: {}, tick rate: {}, content packs: {}",
                                 connection_id,
-                                is_host
+                                is_host,
+                                tick_rate,
+                                content_packs.len()
                             );
+                            let missing_content_packs =
+                                match system_data.content_packs.verify(&content_packs) {
+                                    Ok(missing_content_packs) => missing_content_packs,
+                                    Err(err) => {
+                                        disconnect_with_error(
+                                            &mut system_data.transport,
+                                            &mut system_data.multiplayer_room_state,
+                                            net_connection_model,
+                                            err,
+                                        );
+                                        break;
+                                    }
+                                };
+                            for name in missing_content_packs.iter().cloned() {
+                                log::info!("Requesting the content pack {}", name);
+                                send_message_reliable(
+                                    &mut system_data.transport,
+                                    net_connection_model,
+                                    ClientMessagePayload::RequestContentPack(name),
+                                );
+                            }
+                            self.advertised_content_packs = content_packs;
+                            if missing_content_packs.is_empty() {
+                                use_content_packs(
+                                    &system_data.content_packs,
+                                    &mut system_data.script_hooks,
+                                    &self.advertised_content_packs,
+                                );
+                            }
+
+                            if is_supported_tick_rate(tick_rate) {
+                                system_data.multiplayer_room_state.tick_rate = tick_rate;
+                            } else {
+                                log::error!(
+                                    "The server runs at an unsupported tick rate {}",
+                                    tick_rate
+                                );
+                            }
                             // A hosting client won't send a join packet first, as a server initiates
                             // a connection.
                             if !self.has_sent_join_message {
@@ -310,12 +644,21 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 send_message_reliable(
                                     &mut system_data.transport,
                                     net_connection_model,
-                                    ClientMessagePayload::JoinRoom {
-                                        sent_at: SystemTime::now()
-                                            .duration_since(UNIX_EPOCH)
-                                            .expect("Expected a duration unix timestamp"),
-                                        nickname: self.nickname.clone(),
-                                    },
+                                    self.join_room_message(&system_data.settings),
+                                );
+                            }
+                            // Without a preference, the server's balance decides.
+                            if let Some(lagging_frames) =
+                                system_data.settings.network().lag_pause.lagging_frames()
+                            {
+                                log::info!(
+                                    "Sending a SetPauseThreshold message ({} frames)",
+                                    lagging_frames
+                                );
+                                send_message_reliable(
+                                    &mut system_data.transport,
+                                    net_connection_model,
+                                    ClientMessagePayload::SetPauseThreshold(lagging_frames),
                                 );
                             }
 
@@ -330,76 +673,65 @@ impl<'s> System<'s> for ClientNetworkSystem {
                         ServerMessagePayload::StartGame(entity_net_ids) => {
                             system_data.last_acknowledged_update.frame_number = 0;
                             system_data.last_acknowledged_update.id = 0;
+                            *system_data.reconciliation_status = ReconciliationStatus::default();
+                            system_data.unacknowledged_inputs.clear();
 
-                            let connection_id = system_data
-                                .multiplayer_room_state
-                                .connection_status
-                                .connection_id()
-                                .expect(
-                                    "Expected to be connected when receiving StartGame message",
-                                );
-
-                            let mut found_ourselves = false;
-                            // Looking for an entity_net_id of a client's player
-                            // and storing it in the MultiplayerRoomState.
-                            for (i, player) in system_data
-                                .multiplayer_game_state
-                                .update_players()
-                                .iter_mut()
-                                .enumerate()
-                            {
-                                player.entity_net_id = entity_net_ids[i];
-                                if connection_id == player.connection_id {
-                                    log::info!(
-                                        "Starting a new game as a player with net id {}",
-                                        player.entity_net_id
+                            let player_net_id = match assign_player_net_ids(
+                                &system_data.multiplayer_room_state.connection_status,
+                                &mut system_data.multiplayer_game_state,
+                                entity_net_ids,
+                            ) {
+                                Ok(player_net_id) => player_net_id,
+                                Err(err) => {
+                                    disconnect_with_error(
+                                        &mut system_data.transport,
+                                        &mut system_data.multiplayer_room_state,
+                                        net_connection_model,
+                                        err,
                                     );
-                                    found_ourselves = true;
-                                    system_data.multiplayer_room_state.player_net_id =
-                                        player.entity_net_id;
+                                    break;
                                 }
-                            }
-                            if !found_ourselves {
-                                panic!(
-                                    "Couldn't found a player with connection id {}",
-                                    connection_id
-                                );
-                            }
+                            };
+                            log::info!(
+                                "Starting a new game as a player with net id {}",
+                                player_net_id
+                            );
+                            system_data.multiplayer_room_state.player_net_id = player_net_id;
                             system_data.multiplayer_game_state.is_playing = true;
                             system_data.new_game_engine_sate.0 = GameEngineState::Playing;
                         }
-                        ServerMessagePayload::UpdateWorld { id, mut updates } => {
+                        ServerMessagePayload::LevelTransition { level_number } => {
+                            log::info!("Moving on to the campaign level {}", level_number);
+                            system_data.multiplayer_game_state.campaign_level = Some(level_number);
+                            // The next level is started with ResumeGame, which is ignored
+                            // while playing.
+                            system_data.multiplayer_game_state.is_playing = false;
+                            system_data.new_game_engine_sate.0 = GameEngineState::LevelTransition;
+                        }
+                        ServerMessagePayload::UpdateWorld { id, updates } => {
                             send_message_unreliable(
                                 &mut system_data.transport,
                                 net_connection_model,
                                 ClientMessagePayload::AcknowledgeWorldUpdate(id),
                             );
 
-                            if system_data.last_acknowledged_update.id < id {
-                                updates.sort_by(|a, b| a.frame_number.cmp(&b.frame_number));
-
-                                system_data.last_acknowledged_update.id = id;
-                                system_data.last_acknowledged_update.frame_number =
-                                    system_data.last_acknowledged_update.frame_number.max(
-                                        updates
-                                            .last()
-                                            .map(|update| update.frame_number)
-                                            .unwrap_or(0),
-                                    );
-
-                                let frame_to_reserve = system_data
-                                    .last_acknowledged_update
-                                    .frame_number
-                                    .max(system_data.game_time_service.game_frame_number());
-                                system_data.framed_updates.reserve_updates(frame_to_reserve);
-                                system_data.spawn_actions.reserve_updates(frame_to_reserve);
-
-                                apply_world_updates(
-                                    vec![system_data.multiplayer_room_state.player_net_id],
-                                    &mut system_data.framed_updates,
-                                    &mut system_data.spawn_actions,
-                                    updates,
+                            if let Err(err) = receive_world_update(
+                                &[system_data.multiplayer_room_state.player_net_id],
+                                &mut system_data.last_acknowledged_update,
+                                &mut system_data.framed_updates,
+                                &mut system_data.spawn_actions,
+                                system_data.game_time_service.game_frame_number(),
+                                system_data.game_time_service.interpolation_frame_delay(),
+                                id,
+                                updates,
+                            ) {
+                                disconnect_with_error(
+                                    &mut system_data.transport,
+                                    &mut system_data.multiplayer_room_state,
+                                    net_connection_model,
+                                    err,
                                 );
+                                break;
                             }
                         }
                         ServerMessagePayload::DiscardWalkActions(discarded_actions) => {
@@ -430,6 +762,19 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data.multiplayer_game_state.lagging_players = players;
                             }
                         }
+                        ServerMessagePayload::UpdateLaggingPlayers { pause_id, players } => {
+                            if system_data
+                                .multiplayer_game_state
+                                .waiting_for_players_pause_id
+                                == pause_id
+                                && !system_data
+                                    .multiplayer_game_state
+                                    .lagging_players
+                                    .is_empty()
+                            {
+                                system_data.multiplayer_game_state.lagging_players = players;
+                            }
+                        }
                         ServerMessagePayload::UnpauseWaitingForPlayers(id) => {
                             if system_data
                                 .multiplayer_game_state
@@ -443,6 +788,204 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data.multiplayer_game_state.lagging_players.clear();
                             }
                         }
+                        ServerMessagePayload::UpdateScore(match_score) => {
+                            if system_data.match_score.frame_number <= match_score.frame_number {
+                                *system_data.match_score = match_score;
+                            }
+                        }
+                        ServerMessagePayload::Emote {
+                            connection_id,
+                            emote,
+                        } => {
+                            system_data.emotes.incoming.push((connection_id, emote));
+                        }
+                        ServerMessagePayload::UpdateAugments {
+                            entity_net_id,
+                            augments,
+                        } => {
+                            system_data
+                                .augment_choices
+                                .confirmed
+                                .push((entity_net_id, augments));
+                        }
+                        ServerMessagePayload::UpdateWallet {
+                            entity_net_id,
+                            wallet,
+                        } => {
+                            system_data
+                                .shop_purchases
+                                .confirmed
+                                .push((entity_net_id, wallet));
+                        }
+                        ServerMessagePayload::UpdateIntermission(intermission) => {
+                            system_data.wave_readiness.confirmed = Some(intermission);
+                        }
+                        ServerMessagePayload::EndIntermission(wave) => {
+                            system_data.wave_readiness.confirmed_end = Some(wave);
+                        }
+                        ServerMessagePayload::LevelScriptActions(actions) => {
+                            system_data.active_level_script.confirmed.extend(actions);
+                        }
+                        ServerMessagePayload::UpdatePayload(progress) => {
+                            system_data.active_level_script.confirmed_payload = Some(progress);
+                        }
+                        ServerMessagePayload::GameEvents(events) => {
+                            system_data.game_events.received.extend(events);
+                        }
+                        ServerMessagePayload::PauseThreshold(lagging_frames) => {
+                            log::info!(
+                                "The server has accepted the pause threshold of {} frames",
+                                lagging_frames
+                            );
+                            net_connection_model.pause_lagging_frames = Some(lagging_frames);
+                        }
+                        ServerMessagePayload::MatchmakingQueued { players_in_queue } => {
+                            log::info!(
+                                "Waiting for a match (players in the queue: {})",
+                                players_in_queue
+                            );
+                        }
+                        ServerMessagePayload::RoomEntered(room_id)
+                        | ServerMessagePayload::MatchFound(room_id) => {
+                            log::info!("Entered the room {}", room_id);
+                            if !self.has_sent_join_message {
+                                log::info!("Sending a JoinRoom message");
+                                self.has_sent_join_message = true;
+                                send_message_reliable(
+                                    &mut system_data.transport,
+                                    net_connection_model,
+                                    self.join_room_message(&system_data.settings),
+                                );
+                            }
+                        }
+                        ServerMessagePayload::UpdateBalance(balance) => {
+                            log::info!("Received the server's balance");
+                            *system_data.class_definitions = ClassDefinitions::new(&balance);
+                            *system_data.monster_definitions = MonsterDefinitions::new(&balance);
+                            *system_data.spell_definitions = SpellDefinitions::new(&balance);
+                            *system_data.balance = balance;
+                        }
+                        ServerMessagePayload::ContentPack(content_pack) => {
+                            let name = content_pack.name.clone();
+                            log::info!("Received the content pack {}", name);
+                            if let Err(err) = system_data
+                                .content_packs
+                                .add_downloaded(content_pack, &self.advertised_content_packs)
+                            {
+                                disconnect_with_error(
+                                    &mut system_data.transport,
+                                    &mut system_data.multiplayer_room_state,
+                                    net_connection_model,
+                                    err,
+                                );
+                                break;
+                            }
+                            let mods_dir = system_data.settings.mods_dir();
+                            if let Err(err) = system_data
+                                .content_packs
+                                .get(&name)
+                                .expect("Expected an added content pack")
+                                .save(&mods_dir)
+                            {
+                                log::error!("Failed to save the content pack {}: {}", name, err);
+                            }
+                            let has_missing_content_packs = system_data
+                                .content_packs
+                                .verify(&self.advertised_content_packs)
+                                .map_or(true, |missing_content_packs| {
+                                    !missing_content_packs.is_empty()
+                                });
+                            if !has_missing_content_packs {
+                                use_content_packs(
+                                    &system_data.content_packs,
+                                    &mut system_data.script_hooks,
+                                    &self.advertised_content_packs,
+                                );
+                            }
+                        }
+                        ServerMessagePayload::ConsoleOutput(output) => {
+                            system_data.console.output.push(output);
+                        }
+                        ServerMessagePayload::ReconciliationReport(report) => {
+                            system_data
+                                .reconciliation_status
+                                .pending_reports
+                                .push(report);
+                        }
+                        ServerMessagePayload::RoomList(rooms) => {
+                            log::info!("Received a list of {} rooms", rooms.len());
+                            *system_data.room_browser = RoomBrowser {
+                                server_addr: Some(net_connection_model.addr),
+                                rooms,
+                                selected: None,
+                                is_updated: true,
+                            };
+                            // The connection is made only to list the rooms.
+                            net_connection_model.disconnected = true;
+                            system_data.multiplayer_room_state.is_active = false;
+                            system_data.multiplayer_room_state.connection_status =
+                                ConnectionStatus::NotConnected;
+                        }
+                        ServerMessagePayload::AcknowledgeInputs(client_action_id) => {
+                            system_data
+                                .unacknowledged_inputs
+                                .acknowledge(client_action_id);
+                        }
+                        ServerMessagePayload::UpdateAfkPlayers(afk_players) => {
+                            system_data.multiplayer_game_state.afk_players = afk_players;
+                        }
+                        ServerMessagePayload::UpdateVote(vote) => {
+                            let now = system_data
+                                .game_time_service
+                                .engine_time()
+                                .absolute_real_time();
+                            let is_new = system_data
+                                .votes
+                                .running
+                                .as_ref()
+                                .map_or(true, |running| running.id != vote.id);
+                            if is_new {
+                                log::info!("A vote {:?} has started", vote.kind);
+                                system_data.votes.running_since = now;
+                            }
+                            system_data.votes.running = Some(vote);
+                        }
+                        ServerMessagePayload::EndVote {
+                            vote_id,
+                            has_passed,
+                        } => {
+                            let running = system_data.votes.running.take();
+                            match running {
+                                Some(vote) if vote.id == vote_id => {
+                                    log::info!(
+                                        "The vote {:?} has ended (passed: {})",
+                                        vote.kind,
+                                        has_passed
+                                    );
+                                    system_data.votes.last_result = Some((vote.kind, has_passed));
+                                    system_data.votes.last_result_since = system_data
+                                        .game_time_service
+                                        .engine_time()
+                                        .absolute_real_time();
+                                }
+                                running => system_data.votes.running = running,
+                            }
+                        }
+                        ServerMessagePayload::ReturnToLobby => {
+                            log::info!("The players have voted to return to the lobby");
+                            // HiddenMenuScreen takes the player back to the room.
+                            system_data.multiplayer_game_state.is_playing = false;
+                        }
+                        ServerMessagePayload::UpdateRoomSettings(room_settings) => {
+                            log::info!("Updated the room settings: {:?}", room_settings);
+                            *system_data.room_settings = room_settings;
+                        }
+                        // The client doesn't join games as a spectator yet.
+                        ServerMessagePayload::StartSpectating { .. }
+                        | ServerMessagePayload::RelayWorld { .. } => {}
+                        // Are converted into StartGame above.
+                        ServerMessagePayload::ResumeGame { .. }
+                        | ServerMessagePayload::RejoinGame { .. } => {}
                         ServerMessagePayload::Disconnect(disconnect_reason) => {
                             if !system_data
                                 .multiplayer_room_state
@@ -517,18 +1060,21 @@ impl<'s> System<'s> for ClientNetworkSystem {
                 .map_or(0, |update| update.frame_number);
 
             system_data.multiplayer_game_state.waiting_for_players =
-                system_data.game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY
+                system_data.game_time_service.game_frame_number()
+                    + system_data.game_time_service.interpolation_frame_delay()
                     >= server_frame;
         }
 
         if *system_data.game_engine_state == GameEngineState::Playing
             && system_data.multiplayer_game_state.is_playing
         {
-            // We always skip first INTERPOLATION_FRAME_DELAY frames on game start.
+            let interpolation_frame_delay =
+                system_data.game_time_service.interpolation_frame_delay();
+            // We always skip first interpolation_frame_delay frames on game start.
             match system_data
                 .game_time_service
                 .game_frame_number_absolute()
-                .cmp(&INTERPOLATION_FRAME_DELAY)
+                .cmp(&interpolation_frame_delay)
             {
                 Ordering::Less => {
                     system_data.multiplayer_game_state.waiting_network = true;
@@ -540,20 +1086,32 @@ impl<'s> System<'s> for ClientNetworkSystem {
                 _ => {}
             }
 
-            // Wait if we a server is lagging behind for PAUSE_FRAME_THRESHOLD frames.
+            // Wait if we a server is lagging behind for PAUSE_FRAME_THRESHOLD frames,
+            // or for as many as the player prefers, see `SetPauseThreshold`.
+            // The last received update is behind by the latency, the estimated server clock
+            // isn't. It's trusted only that far ahead of the updates, in case the server
+            // stops responding.
+            let last_update_frame = system_data.last_acknowledged_update.frame_number;
+            let server_frame = system_data
+                .game_time_service
+                .estimated_server_frame_number()
+                .map_or(last_update_frame, |estimated_server_frame| {
+                    estimated_server_frame
+                        .min(last_update_frame + LAG_COMPENSATION_FRAMES_LIMIT as u64)
+                        .max(last_update_frame)
+                });
             let frames_ahead = system_data
                 .game_time_service
                 .game_frame_number()
-                .saturating_sub(
-                    system_data
-                        .last_acknowledged_update
-                        .frame_number
-                        .saturating_sub(INTERPOLATION_FRAME_DELAY),
-                );
+                .saturating_sub(server_frame.saturating_sub(interpolation_frame_delay));
             log::trace!("Frames ahead: {}", frames_ahead);
             if system_data.multiplayer_game_state.waiting_network {
                 system_data.multiplayer_game_state.waiting_network = frames_ahead != 0;
-            } else if frames_ahead > PAUSE_FRAME_THRESHOLD {
+            } else if frames_ahead
+                > net_connection_model
+                    .pause_lagging_frames
+                    .unwrap_or(PAUSE_FRAME_THRESHOLD)
+            {
                 system_data.multiplayer_game_state.waiting_network = true;
             }
 
@@ -565,159 +1123,87 @@ impl<'s> System<'s> for ClientNetworkSystem {
                     frames_ahead,
                     system_data.game_time_service.game_frame_number(),
                     system_data.last_acknowledged_update.frame_number,
-                    net_connection_model.ping_pong_data.last_stored_game_frame(),
+                    system_data
+                        .game_time_service
+                        .estimated_server_frame_number()
+                        .unwrap_or_else(|| net_connection_model.ping_pong_data.last_stored_game_frame()),
                 );
             }
         }
     }
 }
 
-fn server_connection<'a>(
-    net_connection_models: &'a mut WriteStorage<NetConnectionModel>,
-) -> &'a mut NetConnectionModel {
-    net_connection_models
-        .join()
-        .next()
-        .expect("Expected a server connection")
-}
-
-// Expects incoming_updates to be sorted (lowest frame first).
-fn apply_world_updates(
-    controlled_players: Vec<NetIdentifier>,
-    framed_updates: &mut FramedUpdates<ReceivedServerWorldUpdate>,
-    spawn_actions: &mut FramedUpdates<SpawnActions>,
-    mut incoming_updates: Vec<ServerWorldUpdate>,
-) {
-    if incoming_updates.is_empty() {
-        return;
-    }
-
-    let first_incoming_frame_number = incoming_updates
-        .first()
-        .unwrap()
-        .frame_number
-        .saturating_sub(INTERPOLATION_FRAME_DELAY);
-    let first_available_frame_number = framed_updates.updates.front().unwrap().frame_number;
-    assert!(
-        first_incoming_frame_number >= first_available_frame_number,
-        "Tried to apply a too old ServerUpdate (frame {}), when the first available frame is {}",
-        first_incoming_frame_number,
-        first_available_frame_number,
-    );
+/// Stores entity net ids from a StartGame message, returns the one of the client's player.
+fn assign_player_net_ids(
+    connection_status: &ConnectionStatus,
+    multiplayer_game_state: &mut MultiplayerGameState,
+    entity_net_ids: Vec<NetIdentifier>,
+) -> Result<NetIdentifier, ClientNetworkError> {
+    let connection_id = connection_status
+        .connection_id()
+        .ok_or(ClientNetworkError::StartGameBeforeHandshake)?;
 
-    let controlled_player_updates =
-        collect_controlled_player_updates(&controlled_players, &mut incoming_updates);
-
-    let (controlled_start_frame_number, others_start_frame_number) = incoming_updates
-        .first()
-        .map(|update| {
-            (
-                update
-                    .frame_number
-                    .saturating_sub(INTERPOLATION_FRAME_DELAY),
-                update.frame_number,
-            )
-        })
-        .unwrap();
-
-    spawn_actions.oldest_updated_frame = others_start_frame_number;
-    for (spawn_actions, server_update) in spawn_actions
-        .updates_iter_mut(others_start_frame_number)
-        .zip(incoming_updates.iter())
-    {
-        spawn_actions.spawn_actions = server_update.spawn_actions.clone()
+    let players = multiplayer_game_state.update_players();
+    if players.len() != entity_net_ids.len() {
+        return Err(ClientNetworkError::PlayersCountMismatch {
+            players: players.len(),
+            entity_net_ids: entity_net_ids.len(),
+        });
     }
 
-    framed_updates.oldest_updated_frame = controlled_start_frame_number;
-    let mut controlled_player_updates_iter = controlled_player_updates.into_iter();
-    let mut incoming_updates_iter = incoming_updates.into_iter();
-
-    for frame_updates in framed_updates.updates_iter_mut(controlled_start_frame_number) {
-        if let Some(controlled_player_updates) = controlled_player_updates_iter.next() {
-            frame_updates.controlled_player_updates = controlled_player_updates;
-        }
-        if frame_updates.frame_number >= others_start_frame_number {
-            let server_update = incoming_updates_iter.next();
-            if server_update.is_none() {
-                return;
-            }
-            frame_updates.apply_server_update(server_update.unwrap());
+    let mut player_net_id = None;
+    for (player, entity_net_id) in players.iter_mut().zip(entity_net_ids) {
+        player.entity_net_id = entity_net_id;
+        if player.connection_id == connection_id {
+            player_net_id = Some(entity_net_id);
         }
     }
+    player_net_id.ok_or(ClientNetworkError::PlayerNotFound { connection_id })
+}
+
+/// Once every pack advertised by the server is installed, the client uses only them.
+fn use_content_packs(
+    content_packs: &ContentPacks,
+    script_hooks: &mut ScriptHooks,
+    advertised: &[ContentPackInfo],
+) {
+    *script_hooks = ScriptHooks::from_content_packs(&content_packs.select(advertised));
 }
 
-fn collect_controlled_player_updates(
-    controlled_players: &[NetIdentifier],
-    incoming_updates: &mut Vec<ServerWorldUpdate>,
-) -> Vec<ReceivedPlayerUpdate> {
-    incoming_updates
-        .iter_mut()
-        .skip_while(|update| {
-            // Skips the first 10 frames, as there shouldn't be any player updates on game start.
-            update.frame_number < INTERPOLATION_FRAME_DELAY
-        })
-        .map(|update| {
-            let mut controlled_player_update = ReceivedPlayerUpdate::default();
-
-            let walk_action_pos = update
-                .player_walk_actions_updates
-                .iter()
-                .position(|action| controlled_players.contains(&action.entity_net_id));
-            if let Some(walk_action_pos) = walk_action_pos {
-                let walk_action = update.player_walk_actions_updates.remove(walk_action_pos);
-                controlled_player_update
-                    .player_walk_actions_updates
-                    .push(walk_action);
-            }
-
-            let cast_action_pos = update
-                .player_cast_actions_updates
-                .iter()
-                .position(|action| controlled_players.contains(&action.entity_net_id));
-            if let Some(cast_action_pos) = cast_action_pos {
-                let cast_action = update.player_cast_actions_updates.remove(cast_action_pos);
-                controlled_player_update
-                    .player_cast_actions_updates
-                    .push(cast_action);
-            }
-
-            let look_action_pos = update
-                .player_look_actions_updates
-                .iter()
-                .position(|action| controlled_players.contains(&action.entity_net_id));
-            if let Some(look_action_pos) = look_action_pos {
-                // We just remove a look action here, as we are not interested in replaying it.
-                update.player_look_actions_updates.remove(look_action_pos);
-            }
-
-            controlled_player_update
-        })
-        .collect()
+fn start_hosted_game(system_data: &mut ClientNetworkSystemData, payload: ClientMessagePayload) {
+    if system_data.multiplayer_room_state.is_host {
+        send_message_reliable(
+            &mut system_data.transport,
+            server_connection(&mut system_data.net_connection_models),
+            payload,
+        );
+    } else {
+        log::error!("Client check failed: only host can start a game");
+    }
 }
 
-fn discard_walk_actions(
-    client_player_updates: &mut FramedUpdates<PlayerActionUpdates>,
-    mut discarded_updates: Vec<NetIdentifier>,
+fn disconnect_with_error(
+    transport: &mut TransportResource,
+    multiplayer_room_state: &mut MultiplayerRoomState,
+    net_connection_model: &mut NetConnectionModel,
+    error: ClientNetworkError,
 ) {
-    let mut oldest_updated_frame = client_player_updates.oldest_updated_frame;
-    for update in client_player_updates.updates.iter_mut().rev() {
-        let update_frame_number = update.frame_number;
-        update.walk_action_updates.retain(|net_update| {
-            if let Some(i) = discarded_updates
-                .iter()
-                .position(|discarded_update| *discarded_update == net_update.data.client_action_id)
-            {
-                discarded_updates.remove(i);
-                oldest_updated_frame = update_frame_number;
-                false
-            } else {
-                true
-            }
-        });
+    log::error!("Disconnecting from the server: {:?}", error);
+    send_message_reliable(
+        transport,
+        net_connection_model,
+        ClientMessagePayload::Disconnect,
+    );
+    net_connection_model.disconnected = true;
+    multiplayer_room_state.connection_status =
+        ConnectionStatus::Disconnected(DisconnectReason::ClientNetworkError(error));
+}
 
-        if discarded_updates.is_empty() {
-            break;
-        }
-    }
-    client_player_updates.oldest_updated_frame = oldest_updated_frame;
+fn server_connection<'a>(
+    net_connection_models: &'a mut WriteStorage<NetConnectionModel>,
+) -> &'a mut NetConnectionModel {
+    net_connection_models
+        .join()
+        .next()
+        .expect("Expected a server connection")
 }
@@ -0,0 +1,77 @@
+use amethyst::{
+    ecs::{ReadExpect, System, WriteExpect},
+    input::{InputHandler, StringBindings},
+    window::{ScreenDimensions, Window},
+};
+
+use gv_client_shared::settings::Settings;
+
+use crate::{
+    clip_capture::{CaptureRegion, ClipCapture},
+    ecs::resources::DisplayDebugInfoSettings,
+};
+
+/// Starts capturing the window once it's there (and again if it's resized) when clips
+/// are turned on with `--capture-clips`, and saves a clip on `save_clip`.
+#[derive(Default)]
+pub struct ClipCaptureSystem {
+    captured_dimensions: Option<(f32, f32)>,
+    is_save_clip_down: bool,
+}
+
+impl<'s> System<'s> for ClipCaptureSystem {
+    type SystemData = (
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, Settings>,
+        Option<ReadExpect<'s, Window>>,
+        WriteExpect<'s, ClipCapture>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            display_debug_info_settings,
+            screen_dimensions,
+            settings,
+            window,
+            mut clip_capture,
+        ): Self::SystemData,
+    ) {
+        if !clip_capture.is_enabled() {
+            return;
+        }
+
+        let dimensions = (screen_dimensions.width(), screen_dimensions.height());
+        if self.captured_dimensions != Some(dimensions) {
+            if let Some(region) = window.as_ref().and_then(|window| window_region(window)) {
+                self.captured_dimensions = Some(dimensions);
+                clip_capture.start(region, &settings.display().title);
+            }
+        }
+
+        let is_save_clip_down = !display_debug_info_settings.display_console
+            && input.action_is_down("save_clip").unwrap_or_default();
+        if is_save_clip_down && !self.is_save_clip_down {
+            match clip_capture.save_clip() {
+                Ok(path) => log::info!("Saving a clip to {}", path.display()),
+                Err(err) => log::error!("Failed to save a clip: {:?}", err),
+            }
+        }
+        self.is_save_clip_down = is_save_clip_down;
+    }
+}
+
+fn window_region(window: &Window) -> Option<CaptureRegion> {
+    let hidpi_factor = window.get_hidpi_factor();
+    let position = window.get_inner_position()?.to_physical(hidpi_factor);
+    let size = window.get_inner_size()?.to_physical(hidpi_factor);
+    Some(CaptureRegion {
+        x: position.x.round() as i32,
+        y: position.y.round() as i32,
+        width: size.width.round() as u32,
+        height: size.height.round() as u32,
+    })
+}
@@ -0,0 +1,30 @@
+use amethyst::ecs::{Join, ReadStorage, System, WriteExpect};
+
+use gv_core::ecs::{components::NetConnectionModel, resources::GameTime};
+use gv_game::ecs::system_data::GameStateHelper;
+
+/// Exposes the server clock estimated from the pongs via `GameTimeService`.
+pub struct ClockSyncSystem;
+
+impl<'s> System<'s> for ClockSyncSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        ReadStorage<'s, NetConnectionModel>,
+        WriteExpect<'s, GameTime>,
+    );
+
+    fn run(&mut self, (game_state_helper, net_connection_models, mut game_time): Self::SystemData) {
+        if !game_state_helper.is_multiplayer() {
+            game_time.server_clock = None;
+            return;
+        }
+
+        game_time.server_clock =
+            (&net_connection_models)
+                .join()
+                .next()
+                .and_then(|net_connection_model| {
+                    net_connection_model.ping_pong_data.clock_sync.estimate()
+                });
+    }
+}
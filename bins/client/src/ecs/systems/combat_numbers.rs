@@ -0,0 +1,223 @@
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, World, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::{ResourceId, SystemData},
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use gv_client_shared::{
+    ecs::{components::CombatNumber, resources::AssetHandles},
+    settings::Settings,
+};
+use gv_core::ecs::{
+    components::{damage_history::DamageHistory, Monster, Player, WorldPosition},
+    resources::GameEngineState,
+    system_data::time::GameTimeService,
+};
+
+const NUMBER_OFFSET_Y: f32 = 30.0;
+const NUMBER_FLOAT_SPEED: f32 = 40.0;
+const NUMBER_DURATION_SECS: f32 = 0.8;
+const DIGIT_WIDTH: f32 = 8.0;
+const PLUS_SPRITE_NUMBER: usize = 10;
+/// Smaller changes are left out, as rewinds in multiplayer can shift health slightly.
+const MIN_HEALTH_CHANGE: f32 = 0.5;
+/// Damage numbers of critical hits are drawn this many times larger.
+const CRIT_NUMBER_SCALE: f32 = 1.5;
+/// Server updates may add critical hits to the frames a client has already simulated.
+const CRIT_LOOKBACK_FRAMES: u64 = 60;
+
+#[derive(SystemData)]
+pub struct CombatNumberSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_engine_state: ReadExpect<'s, GameEngineState>,
+    settings: ReadExpect<'s, Settings>,
+    asset_handles: Option<Read<'s, AssetHandles>>,
+    entities: Entities<'s>,
+    players: ReadStorage<'s, Player>,
+    monsters: ReadStorage<'s, Monster>,
+    damage_histories: ReadStorage<'s, DamageHistory>,
+    world_positions: ReadStorage<'s, WorldPosition>,
+    combat_numbers: WriteStorage<'s, CombatNumber>,
+    sprite_renders: WriteStorage<'s, SpriteRender>,
+    transforms: WriteStorage<'s, Transform>,
+    tints: WriteStorage<'s, Tint>,
+}
+
+/// Shows floating numbers for damage (red) and healing (green) of players and monsters,
+/// the colors and the size are taken from the accessibility settings.
+/// The numbers are derived from health changes, so they work the same way for
+/// the entities simulated locally and for the ones updated by a server.
+/// Damage is drawn larger if the entity has recently taken a critical hit that hasn't been
+/// shown yet.
+#[derive(Default)]
+pub struct CombatNumberSystem {
+    last_health: HashMap<Entity, f32>,
+    /// Entities and the frames of the critical hits that have been shown.
+    shown_crits: HashSet<(Entity, u64)>,
+}
+
+impl<'s> System<'s> for CombatNumberSystem {
+    type SystemData = CombatNumberSystemData<'s>;
+
+    fn run(&mut self, mut system_data: Self::SystemData) {
+        if *system_data.game_engine_state != GameEngineState::Playing {
+            self.last_health.clear();
+            self.shown_crits.clear();
+            return;
+        }
+        let sprite_sheet = match system_data.asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.combat_numbers.clone(),
+            None => return,
+        };
+        let now = system_data
+            .game_time_service
+            .engine_time()
+            .absolute_real_time();
+        let delta_seconds = system_data.game_time_service.engine_time().delta_seconds();
+
+        let first_crit_frame = system_data
+            .game_time_service
+            .game_frame_number()
+            .saturating_sub(CRIT_LOOKBACK_FRAMES);
+        self.shown_crits
+            .retain(|(_, frame_number)| *frame_number >= first_crit_frame);
+        let healths = (
+            &system_data.entities,
+            system_data.players.maybe(),
+            system_data.monsters.maybe(),
+            system_data.damage_histories.maybe(),
+            &system_data.world_positions,
+        )
+            .join()
+            .filter_map(
+                |(entity, player, monster, damage_history, world_position)| {
+                    let health = player
+                        .map(|player| player.health)
+                        .or_else(|| monster.map(|monster| monster.health))?;
+                    let crit_frames = damage_history
+                        .map(|damage_history| crit_frames(damage_history, first_crit_frame))
+                        .unwrap_or_default();
+                    Some((entity, health, crit_frames, **world_position))
+                },
+            )
+            .collect::<Vec<_>>();
+        let mut last_health = HashMap::with_capacity(healths.len());
+        for (entity, health, crit_frames, position) in healths {
+            if let Some(previous_health) = self.last_health.get(&entity) {
+                let change = health - previous_health;
+                if change.abs() >= MIN_HEALTH_CHANGE {
+                    let mut transform = Transform::default();
+                    transform.set_translation_xyz(position.x, position.y + NUMBER_OFFSET_Y, 70.0);
+                    let mut is_crit = false;
+                    if change < 0.0 {
+                        for frame_number in crit_frames {
+                            is_crit |= self.shown_crits.insert((entity, frame_number));
+                        }
+                    }
+                    show_number(
+                        &mut system_data,
+                        &sprite_sheet,
+                        transform,
+                        change,
+                        is_crit,
+                        now,
+                    );
+                }
+            }
+            last_health.insert(entity, health);
+        }
+        self.last_health = last_health;
+
+        for (entity, combat_number, transform) in (
+            &system_data.entities,
+            &system_data.combat_numbers,
+            &mut system_data.transforms,
+        )
+            .join()
+        {
+            let elapsed = now
+                .checked_sub(combat_number.started_at)
+                .unwrap_or_default()
+                .as_secs_f32();
+            if elapsed >= NUMBER_DURATION_SECS {
+                system_data
+                    .entities
+                    .delete(entity)
+                    .expect("Expected to delete a CombatNumber");
+                continue;
+            }
+            transform.prepend_translation_y(NUMBER_FLOAT_SPEED * delta_seconds);
+        }
+    }
+}
+
+fn show_number(
+    system_data: &mut CombatNumberSystemData,
+    sprite_sheet: &Handle<SpriteSheet>,
+    transform: Transform,
+    health_change: f32,
+    is_crit: bool,
+    started_at: Duration,
+) {
+    let amount = health_change.abs().round() as u32;
+    let accessibility = system_data.settings.accessibility();
+    let (color, mut sprite_numbers) = if health_change > 0.0 {
+        (accessibility.palette.heal_color(), vec![PLUS_SPRITE_NUMBER])
+    } else {
+        (accessibility.palette.damage_color(), Vec::new())
+    };
+    let scale = if is_crit {
+        accessibility.hud_scale * CRIT_NUMBER_SCALE
+    } else {
+        accessibility.hud_scale
+    };
+    let digit_width = DIGIT_WIDTH * scale;
+    sprite_numbers.extend(
+        amount
+            .to_string()
+            .chars()
+            .map(|digit| digit.to_digit(10).expect("Expected a digit") as usize),
+    );
+
+    let start_x = -(sprite_numbers.len() as f32 - 1.0) * digit_width / 2.0;
+    for (i, sprite_number) in sprite_numbers.into_iter().enumerate() {
+        let mut transform = transform.clone();
+        transform.prepend_translation_x(start_x + i as f32 * digit_width);
+        transform.set_scale(Vector3::new(scale, scale, 1.0));
+        system_data
+            .entities
+            .build_entity()
+            .with(CombatNumber { started_at }, &mut system_data.combat_numbers)
+            .with(
+                SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number,
+                },
+                &mut system_data.sprite_renders,
+            )
+            .with(
+                Tint(Srgba::new(color[0], color[1], color[2], 1.0)),
+                &mut system_data.tints,
+            )
+            .with(transform, &mut system_data.transforms)
+            .build();
+    }
+}
+
+fn crit_frames(damage_history: &DamageHistory, first_frame_number: u64) -> Vec<u64> {
+    damage_history
+        .history
+        .iter()
+        .rev()
+        .take_while(|entries| entries.frame_number >= first_frame_number)
+        .filter(|entries| entries.entries.iter().any(|entry| entry.is_crit))
+        .map(|entries| entries.frame_number)
+        .collect()
+}
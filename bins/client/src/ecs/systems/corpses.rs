@@ -0,0 +1,164 @@
+use amethyst::{
+    core::{math::Vector3, HiddenPropagate, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    renderer::SpriteRender,
+};
+use rand::{self, Rng};
+
+use std::{collections::VecDeque, f32::consts::PI};
+
+use gv_client_shared::{
+    ecs::{
+        components::{BuriedCorpse, Culled},
+        resources::AssetHandles,
+    },
+    settings::Settings,
+};
+use gv_core::ecs::{
+    components::{Dead, Monster, WorldPosition},
+    resources::GameEngineState,
+    system_data::time::GameTimeService,
+};
+use gv_game::utils::entities::is_dead;
+
+const BLOOD_Z: f32 = -0.6;
+const BLOOD_SPRITES: usize = 3;
+const MIN_BLOOD_SCALE: f32 = 0.8;
+const MAX_BLOOD_SCALE: f32 = 1.3;
+
+/// Keeps the corpses of dead monsters on the ground, with pools of blood under them
+/// if gore is on. Once there are more corpses than `VideoSettings::corpse_budget`,
+/// the oldest ones are hidden along with their blood, so that large waves don't leave
+/// an unbounded number of sprites to draw. Monsters revived by a rewind lose their blood.
+#[derive(Default)]
+pub struct CorpseSystem {
+    /// Dead monsters and their pools of blood, the oldest ones go first.
+    corpses: VecDeque<(Entity, Option<Entity>)>,
+}
+
+impl<'s> System<'s> for CorpseSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, WorldPosition>,
+        WriteStorage<'s, BuriedCorpse>,
+        WriteStorage<'s, Culled>,
+        WriteStorage<'s, HiddenPropagate>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            settings,
+            asset_handles,
+            entities,
+            monsters,
+            dead,
+            world_positions,
+            mut buried_corpses,
+            mut culled,
+            mut hidden_propagates,
+            mut sprite_renders,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        let delete_blood = |blood: Option<Entity>| {
+            if let Some(blood) = blood {
+                entities
+                    .delete(blood)
+                    .expect("Expected to delete a pool of blood");
+            }
+        };
+
+        // Monsters are deleted along with the level, so are their corpses.
+        if *game_engine_state != GameEngineState::Playing {
+            for (_, blood) in self.corpses.drain(..) {
+                delete_blood(blood);
+            }
+            return;
+        }
+        let sprite_sheet = match asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.blood.clone(),
+            None => return,
+        };
+        let frame_number = game_time_service.game_frame_number();
+
+        self.corpses.retain(|(monster, blood)| {
+            let is_corpse = entities.is_alive(*monster) && is_dead(*monster, &dead, frame_number);
+            if !is_corpse {
+                delete_blood(*blood);
+            }
+            is_corpse
+        });
+        // The culling shows revived monsters again.
+        let revived_monsters = (&entities, &buried_corpses)
+            .join()
+            .filter(|(monster, _)| !is_dead(*monster, &dead, frame_number))
+            .map(|(monster, _)| monster)
+            .collect::<Vec<_>>();
+        for monster in revived_monsters {
+            buried_corpses.remove(monster);
+        }
+
+        let mut rng = rand::thread_rng();
+        let new_corpses = (&entities, &monsters, &world_positions, !&buried_corpses)
+            .join()
+            .filter(|(monster, _, _, _)| {
+                is_dead(*monster, &dead, frame_number)
+                    && !self
+                        .corpses
+                        .iter()
+                        .any(|(known_monster, _)| known_monster == monster)
+            })
+            .map(|(monster, _, world_position, _)| (monster, **world_position))
+            .collect::<Vec<_>>();
+        for (monster, position) in new_corpses {
+            let blood = if settings.video().gore {
+                let scale = rng.gen_range(MIN_BLOOD_SCALE, MAX_BLOOD_SCALE);
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(position.x, position.y, BLOOD_Z);
+                transform.set_rotation_2d(rng.gen_range(0.0, PI * 2.0));
+                transform.set_scale(Vector3::new(scale, scale, 1.0));
+                Some(
+                    entities
+                        .build_entity()
+                        .with(
+                            SpriteRender {
+                                sprite_sheet: sprite_sheet.clone(),
+                                sprite_number: rng.gen_range(0, BLOOD_SPRITES),
+                            },
+                            &mut sprite_renders,
+                        )
+                        .with(transform, &mut transforms)
+                        .build(),
+                )
+            } else {
+                None
+            };
+            self.corpses.push_back((monster, blood));
+        }
+
+        while self.corpses.len() > settings.video().corpse_budget {
+            let (monster, blood) = self.corpses.pop_front().unwrap();
+            delete_blood(blood);
+            buried_corpses
+                .insert(monster, BuriedCorpse)
+                .expect("Expected to insert a BuriedCorpse component");
+            culled
+                .insert(monster, Culled)
+                .expect("Expected to insert a Culled component");
+            hidden_propagates
+                .insert(monster, HiddenPropagate::new())
+                .expect("Expected to insert HiddenPropagate");
+        }
+    }
+}
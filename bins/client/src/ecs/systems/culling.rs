@@ -0,0 +1,159 @@
+use amethyst::{
+    core::{math::Point2, HiddenPropagate, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    renderer::{ActiveCamera, Camera},
+    window::ScreenDimensions,
+};
+
+use gv_client_shared::ecs::components::{BuriedCorpse, Culled};
+use gv_core::{
+    ecs::components::{missile::Missile, Monster, WorldPosition},
+    math::Vector2,
+};
+
+use crate::utils::camera;
+
+/// Entities are drawn a bit outside of the screen, so that sprites and health bars don't pop in.
+const CULLING_MARGIN: f32 = 100.0;
+
+/// Marks monsters and missiles outside of the screen as `Culled`. Monsters also get
+/// `HiddenPropagate`, so that `RenderFlat2D` skips their body parts. Buried corpses
+/// are left to `CorpseSystem`.
+#[derive(Default)]
+pub struct CullingSystem {
+    newly_culled: Vec<Entity>,
+    newly_visible: Vec<Entity>,
+}
+
+impl CullingSystem {
+    fn queue_change(&mut self, entity: Entity, is_visible: bool, is_culled: bool) {
+        match (is_visible, is_culled) {
+            (false, false) => self.newly_culled.push(entity),
+            (true, true) => self.newly_visible.push(entity),
+            _ => {}
+        }
+    }
+}
+
+impl<'s> System<'s> for CullingSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, ActiveCamera>,
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Missile>,
+        ReadStorage<'s, BuriedCorpse>,
+        WriteStorage<'s, Culled>,
+        WriteStorage<'s, HiddenPropagate>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            screen_dimensions,
+            active_camera,
+            entities,
+            cameras,
+            transforms,
+            world_positions,
+            monsters,
+            missiles,
+            buried_corpses,
+            mut culled,
+            mut hidden_propagates,
+        ): Self::SystemData,
+    ) {
+        let camera = active_camera
+            .entity
+            .and_then(|entity| Some((cameras.get(entity)?, transforms.get(entity)?)))
+            .or_else(|| (&cameras, &transforms).join().next());
+        let (camera, camera_transform) = match camera {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        let screen_left_bottom = camera::screen_to_world_from_global_matrix(
+            camera,
+            Point2::new(0.0, screen_dimensions.height()),
+            camera_transform.global_matrix(),
+            &screen_dimensions,
+        );
+        let screen_right_top = camera::screen_to_world_from_global_matrix(
+            camera,
+            Point2::new(screen_dimensions.width(), 0.0),
+            camera_transform.global_matrix(),
+            &screen_dimensions,
+        );
+        let visible_area = VisibleArea {
+            min: Vector2::new(
+                screen_left_bottom.x.min(screen_right_top.x) - CULLING_MARGIN,
+                screen_left_bottom.y.min(screen_right_top.y) - CULLING_MARGIN,
+            ),
+            max: Vector2::new(
+                screen_left_bottom.x.max(screen_right_top.x) + CULLING_MARGIN,
+                screen_left_bottom.y.max(screen_right_top.y) + CULLING_MARGIN,
+            ),
+        };
+
+        self.newly_culled.clear();
+        self.newly_visible.clear();
+        for (entity, world_position, is_culled, _, _) in (
+            &entities,
+            &world_positions,
+            culled.maybe(),
+            &monsters,
+            !&buried_corpses,
+        )
+            .join()
+        {
+            self.queue_change(
+                entity,
+                visible_area.contains(world_position),
+                is_culled.is_some(),
+            );
+        }
+        for (entity, world_position, is_culled, _) in
+            (&entities, &world_positions, culled.maybe(), &missiles).join()
+        {
+            self.queue_change(
+                entity,
+                visible_area.contains(world_position),
+                is_culled.is_some(),
+            );
+        }
+
+        for entity in self.newly_culled.drain(..) {
+            culled
+                .insert(entity, Culled)
+                .expect("Expected to insert a Culled component");
+            if monsters.contains(entity) {
+                hidden_propagates
+                    .insert(entity, HiddenPropagate::new())
+                    .expect("Expected to insert HiddenPropagate");
+            }
+        }
+        for entity in self.newly_visible.drain(..) {
+            culled.remove(entity);
+            if monsters.contains(entity) {
+                hidden_propagates.remove(entity);
+            }
+        }
+    }
+}
+
+struct VisibleArea {
+    min: Vector2,
+    max: Vector2,
+}
+
+impl VisibleArea {
+    fn contains(&self, position: &Vector2) -> bool {
+        position.x >= self.min.x
+            && position.x <= self.max.x
+            && position.y >= self.min.y
+            && position.y <= self.max.y
+    }
+}
@@ -4,7 +4,10 @@ use amethyst::{
         math::{Point3, Vector3},
         Parent, Transform,
     },
-    renderer::camera::{ActiveCamera, Camera},
+    renderer::{
+        camera::{ActiveCamera, Camera},
+        SpriteRender,
+    },
 };
 use derivative::Derivative;
 
@@ -15,7 +18,8 @@ use std::cmp::Ordering;
 pub struct SpriteOrdering(pub Vec<Entity>);
 
 /// Determines what entities to be drawn. Will also sort transparent entities back to front based on
-/// position on the Z axis.
+/// position on the Z axis. Sprites at the same depth are grouped by their sprite sheets, so that
+/// the render pass can draw them in fewer batches.
 ///
 /// The sprite render pass should draw all sprites without semi-transparent pixels, then draw the
 /// sprites with semi-transparent pixels from far to near.
@@ -34,6 +38,7 @@ struct Internals {
     centroid: Point3<f32>,
     camera_distance: f32,
     from_camera: Vector3<f32>,
+    sprite_sheet_id: u32,
 }
 
 impl CustomSpriteSortingSystem {
@@ -50,6 +55,7 @@ impl<'a> System<'a> for CustomSpriteSortingSystem {
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
         ReadStorage<'a, Transform>,
+        ReadStorage<'a, SpriteRender>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, Parent>,
     );
@@ -62,6 +68,7 @@ impl<'a> System<'a> for CustomSpriteSortingSystem {
             active,
             camera,
             transform,
+            sprite_renders,
             players,
             parents,
         ): Self::SystemData,
@@ -83,17 +90,18 @@ impl<'a> System<'a> for CustomSpriteSortingSystem {
 
         self.centroids.clear();
         self.centroids.extend(
-            (&*entities, &transform, &parents)
+            (&*entities, &transform, &sprite_renders, &parents)
                 .join()
-                .filter(|(_, _, parent)| players.contains(parent.entity))
-                .map(|(e, t, _)| (e, t.global_matrix().transform_point(&origin)))
+                .filter(|(_, _, _, parent)| players.contains(parent.entity))
+                .map(|(e, t, s, _)| (e, t.global_matrix().transform_point(&origin), s))
                 // filter entities behind the camera
-                .filter(|(_, c)| (c - camera_centroid).dot(&camera_backward) < 0.0)
-                .map(|(entity, centroid)| Internals {
+                .filter(|(_, c, _)| (c - camera_centroid).dot(&camera_backward) < 0.0)
+                .map(|(entity, centroid, sprite_render)| Internals {
                     entity,
                     centroid,
                     camera_distance: (centroid.z - camera_centroid.z).abs(),
                     from_camera: centroid - camera_centroid,
+                    sprite_sheet_id: sprite_render.sprite_sheet.id(),
                 }),
         );
 
@@ -103,6 +111,7 @@ impl<'a> System<'a> for CustomSpriteSortingSystem {
             b.camera_distance
                 .partial_cmp(&a.camera_distance)
                 .unwrap_or(Ordering::Equal)
+                .then(a.sprite_sheet_id.cmp(&b.sprite_sheet_id))
         });
 
         sprite_ordering.0.clear();
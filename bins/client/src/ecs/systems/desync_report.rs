@@ -0,0 +1,85 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System},
+    input::{InputHandler, StringBindings},
+};
+
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::{
+    ecs::{
+        components::{EntityNetMetadata, WorldPosition},
+        resources::net::MultiplayerGameState,
+        system_data::time::GameTimeService,
+    },
+    gv_dirs::GvDirs,
+    net::session_recording::SessionRecorder,
+};
+
+use crate::ecs::resources::DisplayDebugInfoSettings;
+
+/// Saves a `DesyncReport` with the messages of the current multiplayer game and the positions
+/// the client simulated on `report_desync`, if the client was started with `--desync-reports`.
+#[derive(Default)]
+pub struct DesyncReportSystem {
+    is_report_desync_down: bool,
+}
+
+impl<'s> System<'s> for DesyncReportSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, SessionRecorder>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, WorldPosition>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            input,
+            display_debug_info_settings,
+            multiplayer_game_state,
+            session_recorder,
+            entity_net_metadata,
+            world_positions,
+        ): Self::SystemData,
+    ) {
+        let is_report_desync_down = !display_debug_info_settings.display_console
+            && input.action_is_down("report_desync").unwrap_or_default();
+        let was_report_desync_down =
+            std::mem::replace(&mut self.is_report_desync_down, is_report_desync_down);
+        if !is_report_desync_down || was_report_desync_down || !multiplayer_game_state.is_playing {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let client_positions = (&entity_net_metadata, &world_positions)
+            .join()
+            .map(|(entity_net_metadata, world_position)| (entity_net_metadata.id, **world_position))
+            .collect();
+
+        let desync_reports_dir = GvDirs::new().desync_reports_dir();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = desync_reports_dir.join(format!("{}_{}.gvdesync", timestamp, frame_number));
+        let result = fs::create_dir_all(&desync_reports_dir).and_then(|_| {
+            session_recorder.save_desync_report(&path, frame_number, client_positions)
+        });
+        match result {
+            Ok(()) => log::info!(
+                "Saved a desync report (frame {}) to {}",
+                frame_number,
+                path.display()
+            ),
+            Err(err) => log::error!("Failed to save a desync report: {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use amethyst::{
+    core::{math::Vector3, Parent, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    renderer::SpriteRender,
+};
+
+use std::collections::HashMap;
+
+use gv_client_shared::{ecs::resources::AssetHandles, settings::Settings};
+use gv_core::{
+    damage::DamageType,
+    ecs::components::{
+        affixes::{MonsterAffix, MonsterAffixes},
+        Dead, Monster,
+    },
+};
+use gv_game::ecs::resources::MonsterDefinitions;
+
+const NAMEPLATE_OFFSET_Y: f32 = 24.0;
+const NAMEPLATE_LINE_HEIGHT: f32 = 10.0;
+const RESISTANCE_ICON_SPACING: f32 = 10.0;
+
+/// Shows a nameplate with an icon and a name of every affix above elite monsters,
+/// topped with icons of the damage types their definition resists.
+/// The nameplates are rebuilt if the affixes change, and removed when elites die.
+/// They are scaled along with the HUD.
+#[derive(Default)]
+pub struct EliteNameplateSystem {
+    nameplates: HashMap<Entity, (Vec<MonsterAffix>, Vec<Entity>)>,
+}
+
+impl<'s> System<'s> for EliteNameplateSystem {
+    type SystemData = (
+        Option<Read<'s, AssetHandles>>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, MonsterDefinitions>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, MonsterAffixes>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Parent>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            asset_handles,
+            settings,
+            monster_definitions,
+            entities,
+            monsters,
+            monster_affixes,
+            dead,
+            mut sprite_renders,
+            mut transforms,
+            mut parents,
+        ): Self::SystemData,
+    ) {
+        let (sprite_sheet, resistances_sprite_sheet) = match asset_handles.as_ref() {
+            Some(asset_handles) => (
+                asset_handles.elite_affixes.clone(),
+                asset_handles.resistances.clone(),
+            ),
+            None => return,
+        };
+        let scale = settings.accessibility().hud_scale;
+
+        let outdated_nameplates = self
+            .nameplates
+            .iter()
+            .filter(|(elite, (affixes, _))| {
+                !entities.is_alive(**elite)
+                    || dead.contains(**elite)
+                    || monster_affixes
+                        .get(**elite)
+                        .map_or(true, |monster_affixes| monster_affixes.affixes != *affixes)
+            })
+            .map(|(elite, _)| *elite)
+            .collect::<Vec<_>>();
+        for elite in outdated_nameplates {
+            let (_, lines) = self.nameplates.remove(&elite).unwrap();
+            for line in lines {
+                entities
+                    .delete(line)
+                    .expect("Expected to delete a nameplate line");
+            }
+        }
+
+        for (elite, monster, monster_affixes, _) in
+            (&entities, &monsters, &monster_affixes, !&dead).join()
+        {
+            if monster_affixes.affixes.is_empty() || self.nameplates.contains_key(&elite) {
+                continue;
+            }
+            let mut lines = monster_affixes
+                .affixes
+                .iter()
+                .enumerate()
+                .map(|(i, affix)| {
+                    let sprite_number = MonsterAffix::ALL
+                        .iter()
+                        .position(|known_affix| known_affix == affix)
+                        .expect("Expected a known affix");
+                    let mut transform = Transform::default();
+                    transform.set_translation_xyz(
+                        0.0,
+                        NAMEPLATE_OFFSET_Y + i as f32 * NAMEPLATE_LINE_HEIGHT * scale,
+                        0.1,
+                    );
+                    transform.set_scale(Vector3::new(scale, scale, 1.0));
+                    entities
+                        .build_entity()
+                        .with(
+                            SpriteRender {
+                                sprite_sheet: sprite_sheet.clone(),
+                                sprite_number,
+                            },
+                            &mut sprite_renders,
+                        )
+                        .with(transform, &mut transforms)
+                        .with(Parent::new(elite), &mut parents)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+
+            let resisted = monster_definitions
+                .0
+                .get(&monster.name)
+                .map_or_else(Vec::new, |monster_definition| {
+                    monster_definition.resistances.resisted().collect()
+                });
+            let resistances_y =
+                NAMEPLATE_OFFSET_Y + lines.len() as f32 * NAMEPLATE_LINE_HEIGHT * scale;
+            let first_icon_x = -(resisted.len() as f32 - 1.0) / 2.0 * RESISTANCE_ICON_SPACING;
+            for (i, damage_type) in resisted.into_iter().enumerate() {
+                let sprite_number = DamageType::ALL
+                    .iter()
+                    .position(|known_damage_type| *known_damage_type == damage_type)
+                    .expect("Expected a known damage type");
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(
+                    (first_icon_x + i as f32 * RESISTANCE_ICON_SPACING) * scale,
+                    resistances_y,
+                    0.1,
+                );
+                transform.set_scale(Vector3::new(scale, scale, 1.0));
+                lines.push(
+                    entities
+                        .build_entity()
+                        .with(
+                            SpriteRender {
+                                sprite_sheet: resistances_sprite_sheet.clone(),
+                                sprite_number,
+                            },
+                            &mut sprite_renders,
+                        )
+                        .with(transform, &mut transforms)
+                        .with(Parent::new(elite), &mut parents)
+                        .build(),
+                );
+            }
+            self.nameplates
+                .insert(elite, (monster_affixes.affixes.clone(), lines));
+        }
+    }
+}
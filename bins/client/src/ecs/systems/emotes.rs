@@ -0,0 +1,218 @@
+use amethyst::{
+    core::{math::Vector3, Parent, Transform},
+    ecs::{
+        Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, World, WriteExpect,
+        WriteStorage,
+    },
+    input::{InputHandler, StringBindings},
+    renderer::SpriteRender,
+    shred::{ResourceId, SystemData},
+};
+
+use std::time::Duration;
+
+use gv_client_shared::ecs::{
+    components::EmoteBubble,
+    resources::{AssetHandles, ConnectionStatus, MultiplayerRoomState},
+};
+use gv_core::{
+    ecs::{
+        components::ClientPlayerActions,
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            GameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    emotes::{Emote, EMOTE_COOLDOWN_FRAMES, EMOTE_DURATION_MS},
+};
+
+use crate::ecs::resources::{Emotes, SpectatorCamera};
+
+const BUBBLE_OFFSET_Y: f32 = 45.0;
+const BUBBLE_FLOAT_SPEED: f32 = 8.0;
+const BUBBLE_POP_IN_SECS: f32 = 0.15;
+
+#[derive(SystemData)]
+pub struct EmoteSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    input: ReadExpect<'s, InputHandler<StringBindings>>,
+    game_engine_state: ReadExpect<'s, GameEngineState>,
+    multiplayer_room_state: ReadExpect<'s, MultiplayerRoomState>,
+    multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    entity_net_metadata_storage: ReadExpect<'s, EntityNetMetadataStorage>,
+    spectator_camera: ReadExpect<'s, SpectatorCamera>,
+    asset_handles: Option<Read<'s, AssetHandles>>,
+    emotes: WriteExpect<'s, Emotes>,
+    entities: Entities<'s>,
+    client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    emote_bubbles: WriteStorage<'s, EmoteBubble>,
+    sprite_renders: WriteStorage<'s, SpriteRender>,
+    transforms: WriteStorage<'s, Transform>,
+    parents: WriteStorage<'s, Parent>,
+}
+
+/// Plays emotes bound to hotkeys and shows bubbles above the players while playing.
+#[derive(Default)]
+pub struct EmoteSystem {
+    pressed_hotkeys: [bool; 4],
+    last_emote_frame: Option<u64>,
+}
+
+impl<'s> System<'s> for EmoteSystem {
+    type SystemData = EmoteSystemData<'s>;
+
+    fn run(&mut self, mut system_data: Self::SystemData) {
+        let is_playing = *system_data.game_engine_state == GameEngineState::Playing;
+        let is_in_room = system_data.multiplayer_room_state.is_active
+            && matches!(
+                system_data.multiplayer_room_state.connection_status,
+                ConnectionStatus::Connected(_)
+            );
+
+        // Number keys follow other players while spectating.
+        let emote = self
+            .read_hotkeys(&system_data)
+            .filter(|_| !system_data.spectator_camera.is_active);
+        if let Some(emote) = emote {
+            if is_in_room {
+                system_data.emotes.outgoing = Some(emote);
+            } else if is_playing {
+                let player = (&system_data.entities, &system_data.client_player_actions)
+                    .join()
+                    .next()
+                    .map(|(entity, _)| entity);
+                if let Some(player) = player {
+                    show_bubble(&mut system_data, player, emote);
+                }
+            }
+        }
+
+        // The multiplayer room menu takes care of emotes in the lobby,
+        // the ones received during level transitions are dropped.
+        if *system_data.game_engine_state != GameEngineState::Menu {
+            let incoming = std::mem::replace(&mut system_data.emotes.incoming, Vec::new());
+            if is_playing {
+                for (connection_id, emote) in incoming {
+                    let player = system_data
+                        .multiplayer_game_state
+                        .find_player_by_connection_id(connection_id)
+                        .and_then(|player| {
+                            system_data
+                                .entity_net_metadata_storage
+                                .get_entity(player.entity_net_id)
+                        });
+                    if let Some(player) = player {
+                        show_bubble(&mut system_data, player, emote);
+                    }
+                }
+            }
+        }
+
+        let now = system_data
+            .game_time_service
+            .engine_time()
+            .absolute_real_time();
+        for (entity, emote_bubble, transform) in (
+            &system_data.entities,
+            &system_data.emote_bubbles,
+            &mut system_data.transforms,
+        )
+            .join()
+        {
+            if !system_data.entities.is_alive(entity) {
+                continue;
+            }
+            let elapsed = now.checked_sub(emote_bubble.started_at).unwrap_or_default();
+            if elapsed >= Duration::from_millis(EMOTE_DURATION_MS) {
+                system_data
+                    .entities
+                    .delete(entity)
+                    .expect("Expected to delete an EmoteBubble");
+                continue;
+            }
+
+            // A bubble pops in and then slowly floats up.
+            let elapsed = elapsed.as_secs_f32();
+            let scale = (elapsed / BUBBLE_POP_IN_SECS).min(1.0);
+            transform.set_scale(Vector3::new(scale, scale, 1.0));
+            transform.set_translation_y(BUBBLE_OFFSET_Y + elapsed * BUBBLE_FLOAT_SPEED);
+        }
+    }
+}
+
+impl EmoteSystem {
+    fn read_hotkeys(&mut self, system_data: &EmoteSystemData) -> Option<Emote> {
+        let frame_number = system_data.game_time_service.engine_time().frame_number();
+        let mut played_emote = None;
+        for (i, emote) in Emote::ALL.iter().enumerate() {
+            let is_down = system_data
+                .input
+                .action_is_down(&format!("emote_{}", i + 1))
+                .unwrap_or_default();
+            let was_down = std::mem::replace(&mut self.pressed_hotkeys[i], is_down);
+            if !is_down || was_down || played_emote.is_some() {
+                continue;
+            }
+
+            // The server drops emotes sent too often anyway.
+            let is_cooling_down = self.last_emote_frame.map_or(false, |last_emote_frame| {
+                frame_number - last_emote_frame < EMOTE_COOLDOWN_FRAMES
+            });
+            if !is_cooling_down {
+                self.last_emote_frame = Some(frame_number);
+                played_emote = Some(*emote);
+            }
+        }
+        played_emote
+    }
+}
+
+fn show_bubble(system_data: &mut EmoteSystemData, player: Entity, emote: Emote) {
+    let sprite_sheet = match system_data.asset_handles.as_ref() {
+        Some(asset_handles) => asset_handles.emotes.clone(),
+        None => return,
+    };
+
+    // A new emote replaces the one that's still shown.
+    for (entity, _, parent) in (
+        &system_data.entities,
+        &system_data.emote_bubbles,
+        &system_data.parents,
+    )
+        .join()
+    {
+        if parent.entity == player && system_data.entities.is_alive(entity) {
+            system_data
+                .entities
+                .delete(entity)
+                .expect("Expected to delete an EmoteBubble");
+        }
+    }
+
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(0.0, BUBBLE_OFFSET_Y, 0.2);
+    transform.set_scale(Vector3::new(0.0, 0.0, 1.0));
+    system_data
+        .entities
+        .build_entity()
+        .with(
+            EmoteBubble {
+                started_at: system_data
+                    .game_time_service
+                    .engine_time()
+                    .absolute_real_time(),
+            },
+            &mut system_data.emote_bubbles,
+        )
+        .with(
+            SpriteRender {
+                sprite_sheet,
+                sprite_number: emote.sprite_number(),
+            },
+            &mut system_data.sprite_renders,
+        )
+        .with(transform, &mut system_data.transforms)
+        .with(Parent::new(player), &mut system_data.parents)
+        .build();
+}
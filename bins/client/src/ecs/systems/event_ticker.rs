@@ -0,0 +1,191 @@
+use amethyst::{
+    ecs::{Read, ReadExpect, System, WriteExpect, WriteStorage},
+    renderer::SpriteRender,
+    ui::{UiImage, UiText},
+};
+
+use std::{collections::VecDeque, time::Duration};
+
+use gv_client_shared::{accessibility::MarkerShape, ecs::resources::AssetHandles};
+use gv_core::{
+    campaign::CAMPAIGN_LEVELS,
+    ecs::{
+        resources::{net::MultiplayerGameState, GameEngineState},
+        system_data::time::GameTimeService,
+    },
+    game_events::GameEvent,
+    net::NetIdentifier,
+};
+use gv_game::ecs::resources::GameEvents;
+
+use crate::ecs::system_data::ui::UiFinderMut;
+
+/// The number of `ui_event_ticker_label_*` rows in `resources/ui/hud.ron`.
+const TICKER_ROWS: usize = 5;
+const ENTRY_DURATION: Duration = Duration::from_secs(6);
+/// Entries fade out during the last part of their duration.
+const FADE_OUT_SECS: f32 = 1.0;
+const TEAM_EVENT_COLOR: [f32; 3] = [0.95, 0.8, 0.35];
+const WAVE_EVENT_COLOR: [f32; 3] = [0.55, 0.75, 0.95];
+const NEUTRAL_EVENT_COLOR: [f32; 3] = [0.972, 0.917, 0.827];
+
+struct TickerEntry {
+    text: String,
+    color: [f32; 3],
+    /// The marker of the player the event is about, team-wide events have none.
+    marker: Option<MarkerShape>,
+    shown_at: Duration,
+}
+
+/// Shows kills, player deaths, level-ups and wave transitions in the top left corner,
+/// the newest events first. Player events are colored with the player's color.
+#[derive(Default)]
+pub struct EventTickerSystem {
+    entries: VecDeque<TickerEntry>,
+}
+
+impl<'s> System<'s> for EventTickerSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        Option<Read<'s, AssetHandles>>,
+        WriteExpect<'s, GameEvents>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            game_engine_state,
+            multiplayer_game_state,
+            asset_handles,
+            mut game_events,
+            mut ui_texts,
+            mut ui_images,
+        ): Self::SystemData,
+    ) {
+        if !game_engine_state.is_playing() {
+            self.entries.clear();
+            game_events.received.clear();
+            return;
+        }
+
+        let now = game_time_service.engine_time().absolute_real_time();
+        for event in game_events.received.drain(..) {
+            let player = event
+                .player()
+                .map(|entity_net_id| player_appearance(&multiplayer_game_state, entity_net_id));
+            let (text, color) = event_text(&event, player.as_ref());
+            self.entries.push_front(TickerEntry {
+                text,
+                color,
+                marker: player.map(|(_, _, marker)| marker),
+                shown_at: now,
+            });
+        }
+        self.entries
+            .retain(|entry| now.checked_sub(entry.shown_at).unwrap_or_default() < ENTRY_DURATION);
+        self.entries.truncate(TICKER_ROWS);
+
+        for i in 0..TICKER_ROWS {
+            let entry = self.entries.get(i);
+            if let Some(ui_event_ticker_label) =
+                ui_finder.find(&format!("ui_event_ticker_label_{}", i + 1))
+            {
+                let ui_text = ui_texts.get_mut(ui_event_ticker_label).unwrap();
+                match entry {
+                    Some(entry) => {
+                        let remaining_secs = (ENTRY_DURATION
+                            - now.checked_sub(entry.shown_at).unwrap_or_default())
+                        .as_secs_f32();
+                        let alpha = (remaining_secs / FADE_OUT_SECS).min(1.0);
+                        ui_text.text = entry.text.clone();
+                        ui_text.color = [entry.color[0], entry.color[1], entry.color[2], alpha];
+                    }
+                    None => ui_text.text = String::new(),
+                }
+            }
+            if let Some(ui_event_ticker_icon) =
+                ui_finder.find(&format!("ui_event_ticker_icon_{}", i + 1))
+            {
+                let ui_image = match (entry.and_then(|entry| entry.marker), &asset_handles) {
+                    (Some(marker), Some(asset_handles)) => UiImage::Sprite(SpriteRender {
+                        sprite_sheet: asset_handles.player_markers.clone(),
+                        sprite_number: marker.sprite_number(),
+                    }),
+                    _ => UiImage::SolidColor([0.0, 0.0, 0.0, 0.0]),
+                };
+                ui_images
+                    .insert(ui_event_ticker_icon, ui_image)
+                    .expect("Expected to insert a UiImage");
+            }
+        }
+    }
+}
+
+/// The nickname, color and marker of a player, single player games have no room players.
+fn player_appearance(
+    multiplayer_game_state: &MultiplayerGameState,
+    entity_net_id: NetIdentifier,
+) -> (String, [f32; 3], MarkerShape) {
+    multiplayer_game_state
+        .players
+        .iter()
+        .enumerate()
+        .find(|(_, player)| player.entity_net_id == entity_net_id)
+        .map_or_else(
+            || {
+                (
+                    "You".to_owned(),
+                    NEUTRAL_EVENT_COLOR,
+                    MarkerShape::for_player(0),
+                )
+            },
+            |(i, player)| {
+                (
+                    player.nickname.clone(),
+                    player.color,
+                    MarkerShape::for_player(i),
+                )
+            },
+        )
+}
+
+fn event_text(
+    event: &GameEvent,
+    player: Option<&(String, [f32; 3], MarkerShape)>,
+) -> (String, [f32; 3]) {
+    let nickname = player.map_or("", |(nickname, _, _)| nickname.as_str());
+    let player_color = player.map_or(NEUTRAL_EVENT_COLOR, |(_, color, _)| *color);
+    match event {
+        GameEvent::Kill {
+            killer,
+            monster,
+            is_elite,
+        } => {
+            let monster = if *is_elite {
+                format!("an elite {}", monster)
+            } else {
+                format!("a {}", monster)
+            };
+            if killer.is_some() {
+                (format!("{} killed {}", nickname, monster), player_color)
+            } else {
+                (format!("Killed {}", monster), NEUTRAL_EVENT_COLOR)
+            }
+        }
+        GameEvent::PlayerDied(_) => (format!("{} fell", nickname), player_color),
+        GameEvent::LevelUp(level) => (format!("Level {}!", level), TEAM_EVENT_COLOR),
+        GameEvent::WaveCleared(wave) => (format!("Wave {} cleared", wave), WAVE_EVENT_COLOR),
+        GameEvent::WaveStarted(wave) => (format!("Wave {} has started", wave), WAVE_EVENT_COLOR),
+        GameEvent::CampaignLevelCompleted(level_number) => (
+            format!("{} completed", CAMPAIGN_LEVELS[*level_number].name),
+            TEAM_EVENT_COLOR,
+        ),
+    }
+}
@@ -0,0 +1,40 @@
+use amethyst::ecs::{ReadExpect, System};
+
+use gv_client_shared::{
+    frame_pacing::{frame_rate_limit, FramePacer},
+    settings::Settings,
+};
+use gv_core::ecs::resources::GameEngineState;
+
+use crate::ecs::resources::WindowFocus;
+
+/// Paces frames at the FPS caps from the video settings, the focused one or the unfocused one
+/// depending on `WindowFocus`. Runs after rendering as the last thread-local system, so
+/// the wait ends the frame.
+#[derive(Default)]
+pub struct FramePacingSystem {
+    frame_pacer: FramePacer,
+}
+
+impl<'s> System<'s> for FramePacingSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, WindowFocus>,
+    );
+
+    fn run(&mut self, (game_engine_state, settings, window_focus): Self::SystemData) {
+        let video = settings.video();
+        let limit = frame_rate_limit(
+            video.fps_cap,
+            video.unfocused_fps_cap,
+            window_focus.is_focused,
+            game_engine_state.is_playing(),
+        );
+        if self.frame_pacer.frame_rate() != limit {
+            log::debug!("Frame rate limit: {:?}", limit);
+        }
+        self.frame_pacer.set_frame_rate(limit);
+        self.frame_pacer.wait();
+    }
+}
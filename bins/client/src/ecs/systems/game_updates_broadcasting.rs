@@ -1,21 +1,32 @@
 use amethyst::{
-    ecs::{Join, System, Write, WriteExpect, WriteStorage},
+    ecs::{Join, ReadExpect, System, Write, WriteExpect, WriteStorage},
     network::simulation::TransportResource,
 };
 
 use std::iter::FromIterator;
 
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
 use gv_core::{
     ecs::{
         components::NetConnectionModel,
-        resources::world::{
-            ClientWorldUpdates, ImmediatePlayerActionsUpdates, PlayerLookActionUpdates,
+        resources::{
+            net::{input_resend_frames, PlayersNetStatus, UnacknowledgedInputs},
+            world::{
+                ClientWorldUpdates, ImmediatePlayerActionsUpdates, InputFrame,
+                PlayerLookActionUpdates,
+            },
         },
         system_data::time::GameTimeService,
     },
-    net::{client_message::ClientMessagePayload, INTERPOLATION_FRAME_DELAY},
+    net::{
+        client_message::ClientMessagePayload,
+        session_recording::{RecordedMessage, SessionRecorder},
+    },
+};
+use gv_game::{
+    ecs::system_data::GameStateHelper,
+    utils::net::{send_message_reliable, send_message_unreliable},
 };
-use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_reliable};
 
 const BROADCAST_FRAME_INTERVAL: u64 = 5;
 
@@ -28,8 +39,12 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
     type SystemData = (
         GameTimeService<'s>,
         GameStateHelper<'s>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        ReadExpect<'s, PlayersNetStatus>,
         Write<'s, TransportResource>,
         WriteExpect<'s, ClientWorldUpdates>,
+        WriteExpect<'s, UnacknowledgedInputs>,
+        WriteExpect<'s, SessionRecorder>,
         WriteStorage<'s, NetConnectionModel>,
     );
 
@@ -38,8 +53,12 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         (
             game_time_service,
             game_state_helper,
+            multiplayer_room_state,
+            players_net_status,
             mut transport,
             mut client_world_updates,
+            mut unacknowledged_inputs,
+            mut session_recorder,
             mut net_connection_models,
         ): Self::SystemData,
     ) {
@@ -51,39 +70,62 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
             return;
         }
 
+        let interpolation_frame_delay = game_time_service.interpolation_frame_delay();
         let net_connection = (&mut net_connection_models)
             .join()
             .next()
             .expect("Expected a server connection");
 
-        if !client_world_updates.walk_action_updates.is_empty() {
-            send_message_reliable(
-                &mut transport,
-                net_connection,
-                ClientMessagePayload::WalkActions(ImmediatePlayerActionsUpdates {
-                    frame_number: game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY,
-                    updates: client_world_updates.walk_action_updates.clone(),
-                }),
+        let frame_number = game_time_service.game_frame_number() + interpolation_frame_delay;
+        if !client_world_updates.walk_action_updates.is_empty() && session_recorder.is_recording() {
+            // Recordings keep the walk actions of each frame, whichever packets carry them.
+            session_recorder.record(
+                game_time_service.game_frame_number(),
+                RecordedMessage::Outcoming(ClientMessagePayload::WalkActions(
+                    ImmediatePlayerActionsUpdates {
+                        frame_number,
+                        updates: client_world_updates.walk_action_updates.clone(),
+                    },
+                )),
             );
-            client_world_updates.walk_action_updates.clear();
+        }
+        if !client_world_updates.walk_action_updates.is_empty()
+            || !client_world_updates.cast_action_updates.is_empty()
+            || !client_world_updates.interact_action_updates.is_empty()
+        {
+            unacknowledged_inputs.push(InputFrame {
+                frame_number,
+                walk_action_updates: client_world_updates.walk_action_updates.split_off(0),
+                cast_action_updates: client_world_updates.cast_action_updates.split_off(0),
+                interact_action_updates: client_world_updates.interact_action_updates.split_off(0),
+            });
         }
 
-        if !client_world_updates.cast_action_updates.is_empty() {
-            send_message_reliable(
+        let latency_ms = multiplayer_room_state
+            .connection_status
+            .connection_id()
+            .and_then(|connection_id| {
+                players_net_status
+                    .players
+                    .iter()
+                    .find(|player_net_status| player_net_status.connection_id == connection_id)
+            })
+            .map_or(0, |player_net_status| player_net_status.latency_ms);
+        let resend_frames = input_resend_frames(latency_ms, game_time_service.tick_rate());
+        if let Some(input_frames) =
+            unacknowledged_inputs.packet(game_time_service.game_frame_number(), resend_frames)
+        {
+            send_message_unreliable(
                 &mut transport,
                 net_connection,
-                ClientMessagePayload::CastActions(ImmediatePlayerActionsUpdates {
-                    frame_number: game_time_service.game_frame_number() + INTERPOLATION_FRAME_DELAY,
-                    updates: client_world_updates.cast_action_updates.clone(),
-                }),
+                ClientMessagePayload::PlayerInputs(input_frames),
             );
-            client_world_updates.cast_action_updates.clear();
         }
 
         let is_time_to_broadcast = game_time_service
             .game_frame_number()
             .wrapping_sub(self.last_broadcasted_frame)
-            > BROADCAST_FRAME_INTERVAL;
+            > game_time_service.scale_frames(BROADCAST_FRAME_INTERVAL);
         if !is_time_to_broadcast {
             return;
         }
@@ -94,7 +136,7 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
             net_connection,
             ClientMessagePayload::LookActions(PlayerLookActionUpdates {
                 updates: Vec::from_iter(client_world_updates.look_actions_updates.drain(..).map(
-                    |(frame_number, update)| (frame_number + INTERPOLATION_FRAME_DELAY, update),
+                    |(frame_number, update)| (frame_number + interpolation_frame_delay, update),
                 )),
             }),
         );
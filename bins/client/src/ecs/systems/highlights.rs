@@ -0,0 +1,145 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::{
+    ecs::{
+        resources::{net::MultiplayerGameState, GameEngineState, FRAME_RATE},
+        system_data::time::GameTimeService,
+    },
+    game_events::GameEvent,
+    gv_dirs::GvDirs,
+    net::session_recording::SessionRecorder,
+};
+use gv_game::ecs::resources::GameEvents;
+
+use crate::{clip_capture::ClipCapture, ecs::resources::Highlights};
+
+/// A highlight contains this much of the game before it's saved.
+pub const HIGHLIGHT_WINDOW_FRAMES: u64 = 30 * FRAME_RATE as u64;
+/// Highlights are saved a bit later than their events to show what happened next.
+const AFTERMATH_FRAMES: u64 = 3 * FRAME_RATE as u64;
+
+/// Saves the messages recorded around player deaths and elite kills of a multiplayer game
+/// into the highlights directory. Single player games don't receive any messages to save.
+/// Clips of the highlights are saved as well if they're being captured.
+#[derive(Default)]
+pub struct HighlightSystem {
+    /// Titles of the highlights to save, with the game frames to save them at.
+    pending: Vec<(u64, String)>,
+    last_frame_number: u64,
+}
+
+impl<'s> System<'s> for HighlightSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, GameEvents>,
+        ReadExpect<'s, SessionRecorder>,
+        WriteExpect<'s, Highlights>,
+        WriteExpect<'s, ClipCapture>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            multiplayer_game_state,
+            game_events,
+            session_recorder,
+            mut highlights,
+            mut clip_capture,
+        ): Self::SystemData,
+    ) {
+        // The game is over, there's no aftermath to wait for.
+        if !game_engine_state.is_playing() {
+            for (_, title) in std::mem::replace(&mut self.pending, Vec::new()) {
+                save_highlight(&session_recorder, &mut highlights, &mut clip_capture, title);
+            }
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        if frame_number < self.last_frame_number {
+            highlights.saved.clear();
+        }
+        self.last_frame_number = frame_number;
+        if !multiplayer_game_state.is_playing {
+            return;
+        }
+
+        let elapsed_secs = game_time_service.level_duration().as_secs();
+        let nickname = |entity_net_id| {
+            multiplayer_game_state
+                .players
+                .iter()
+                .find(|player| player.entity_net_id == entity_net_id)
+                .map_or("Someone", |player| player.nickname.as_str())
+        };
+        for event in &game_events.received {
+            let title = match event {
+                GameEvent::PlayerDied(player) => format!("{} fell", nickname(*player)),
+                GameEvent::Kill {
+                    killer,
+                    monster,
+                    is_elite: true,
+                } => match killer {
+                    Some(killer) => format!("{} killed an elite {}", nickname(*killer), monster),
+                    None => format!("An elite {} died", monster),
+                },
+                _ => continue,
+            };
+            self.pending.push((
+                frame_number + AFTERMATH_FRAMES,
+                format!("{} ({}:{:02})", title, elapsed_secs / 60, elapsed_secs % 60),
+            ));
+        }
+
+        let (due, pending) = std::mem::replace(&mut self.pending, Vec::new())
+            .into_iter()
+            .partition(|(save_at_frame, _)| *save_at_frame <= frame_number);
+        self.pending = pending;
+        for (_, title) in due {
+            save_highlight(&session_recorder, &mut highlights, &mut clip_capture, title);
+        }
+    }
+}
+
+fn save_highlight(
+    session_recorder: &SessionRecorder,
+    highlights: &mut Highlights,
+    clip_capture: &mut ClipCapture,
+    title: String,
+) {
+    let highlights_dir = GvDirs::new().highlights_dir();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = highlights_dir.join(format!(
+        "{}_{}.gvrec",
+        timestamp,
+        highlights.saved.len() + 1
+    ));
+    let result =
+        fs::create_dir_all(&highlights_dir).and_then(|_| session_recorder.save_highlight(&path));
+    match result {
+        Ok(()) => {
+            log::info!("Saved a highlight \"{}\" to {}", title, path.display());
+            highlights.saved.push((title, path));
+        }
+        Err(err) => log::error!("Failed to save a highlight \"{}\": {:?}", title, err),
+    }
+
+    if clip_capture.is_enabled() {
+        match clip_capture.save_clip() {
+            Ok(path) => log::info!("Saving a clip of the highlight to {}", path.display()),
+            Err(err) => log::error!("Failed to save a clip of the highlight: {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use amethyst::{
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage},
+    ui::UiText,
+};
+
+use gv_client_shared::{
+    hints::{next_hint, Hint, HINT_DURATION_SECS, LOW_HEALTH_FRACTION},
+    settings::Settings,
+};
+use gv_core::{
+    ecs::{
+        components::{ClientPlayerActions, Dead, Player, WorldPosition},
+        resources::{GameEngineState, GameLevelState},
+        system_data::time::GameTimeService,
+    },
+    level_scripts::SwitchKind,
+};
+use gv_game::ecs::resources::ActiveLevelScript;
+
+use crate::ecs::system_data::ui::UiFinderMut;
+
+/// Shows a hint naming the bound keys the first time its situation comes up, and remembers
+/// it in the player profile so that it isn't shown again.
+#[derive(Default)]
+pub struct HintSystem {
+    shown_at: Option<f32>,
+}
+
+impl<'s> System<'s> for HintSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        WriteExpect<'s, Settings>,
+        Entities<'s>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, WorldPosition>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            game_engine_state,
+            game_level_state,
+            active_level_script,
+            mut settings,
+            entities,
+            client_player_actions,
+            players,
+            dead,
+            world_positions,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let ui_hint_label = match ui_finder.find("ui_hint_label") {
+            Some(ui_hint_label) => ui_hint_label,
+            None => return,
+        };
+        if !game_engine_state.is_playing() {
+            self.shown_at = None;
+            ui_texts.get_mut(ui_hint_label).unwrap().text = String::new();
+            return;
+        }
+
+        let now = game_time_service.engine_time().absolute_real_time_seconds() as f32;
+        if let Some(shown_at) = self.shown_at {
+            if now - shown_at < HINT_DURATION_SECS {
+                return;
+            }
+            self.shown_at = None;
+            ui_texts.get_mut(ui_hint_label).unwrap().text = String::new();
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let local_player = (
+            &entities,
+            &client_player_actions,
+            &players,
+            &world_positions,
+        )
+            .join()
+            .next()
+            .filter(|(entity, ..)| {
+                !dead
+                    .get(*entity)
+                    .map_or(false, |dead| dead.is_dead(frame_number))
+            });
+        let (player, position) = match local_player {
+            Some((_, _, player, world_position)) => (player, world_position.position),
+            None => return,
+        };
+
+        let hint = next_hint(&settings.profile().shown_hints, |hint| match hint {
+            Hint::Heal => player.health / player.max_health < LOW_HEALTH_FRACTION,
+            Hint::Interact => active_level_script.runner.as_ref().map_or(false, |runner| {
+                runner.script().switches.iter().any(|switch| {
+                    switch.kind == SwitchKind::Lever
+                        && !active_level_script
+                            .activated_switches
+                            .contains(&switch.name)
+                        && (position.x - switch.position.0).hypot(position.y - switch.position.1)
+                            <= switch.radius
+                })
+            }),
+            Hint::ReadyForWave => game_level_state.intermission.is_some(),
+        });
+        if let Some(hint) = hint {
+            ui_texts.get_mut(ui_hint_label).unwrap().text = hint.text(settings.bindings());
+            self.shown_at = Some(now);
+            if let Err(err) = settings.save_shown_hint(hint) {
+                log::error!("Failed to save the shown hint: {:?}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use amethyst::{
+    assets::{AssetStorage, Handle},
+    audio::{output::Output, Source},
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, World, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::{ResourceId, SystemData},
+};
+
+use std::{collections::HashSet, time::Duration};
+
+use gv_client_shared::{
+    ecs::{components::HitMarker, resources::AssetHandles},
+    settings::Settings,
+};
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::DamageHistory, missile::Missile, ClientPlayerActions, WorldPosition,
+        },
+        resources::{net::LastAcknowledgedUpdate, GameEngineState},
+        system_data::time::GameTimeService,
+    },
+    hit_registration::{HitRegistration, PredictedHits},
+};
+use gv_game::ecs::system_data::GameStateHelper;
+
+use crate::ecs::resources::WindowFocus;
+
+const MARKER_Z: f32 = 69.0;
+const MARKER_DURATION_SECS: f32 = 0.35;
+/// No-regs are corrected quietly, their markers are fainter and stay a bit longer.
+const NO_REG_MARKER_DURATION_SECS: f32 = 0.6;
+const NO_REG_MARKER_ALPHA: f32 = 0.6;
+const HIT_SPRITE_NUMBER: usize = 0;
+const NO_REG_SPRITE_NUMBER: usize = 1;
+
+#[derive(SystemData)]
+pub struct HitMarkerSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    game_state_helper: GameStateHelper<'s>,
+    game_engine_state: ReadExpect<'s, GameEngineState>,
+    last_acknowledged_update: ReadExpect<'s, LastAcknowledgedUpdate>,
+    settings: ReadExpect<'s, Settings>,
+    window_focus: ReadExpect<'s, WindowFocus>,
+    asset_handles: Option<Read<'s, AssetHandles>>,
+    output: Option<Read<'s, Output>>,
+    source_storage: Read<'s, AssetStorage<Source>>,
+    entities: Entities<'s>,
+    client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    missiles: ReadStorage<'s, Missile>,
+    damage_histories: ReadStorage<'s, DamageHistory>,
+    world_positions: ReadStorage<'s, WorldPosition>,
+    hit_markers: WriteStorage<'s, HitMarker>,
+    sprite_renders: WriteStorage<'s, SpriteRender>,
+    transforms: WriteStorage<'s, Transform>,
+    tints: WriteStorage<'s, Tint>,
+}
+
+/// Shows hit markers and plays a confirm sound as soon as a missile of the local player
+/// is predicted to hit a monster. Once the server's damage for the frames around the hit
+/// is received, hits without any damage of the missile's type get a faint no-reg marker,
+/// so that players can tell the shots that didn't land on the server.
+#[derive(Default)]
+pub struct HitMarkerSystem {
+    /// Missiles and the monsters they have been predicted to hit, rewinds don't repeat them.
+    seen_hits: HashSet<(Entity, Entity)>,
+    predicted_hits: PredictedHits<Entity>,
+}
+
+impl<'s> System<'s> for HitMarkerSystem {
+    type SystemData = HitMarkerSystemData<'s>;
+
+    fn run(&mut self, mut system_data: Self::SystemData) {
+        if *system_data.game_engine_state != GameEngineState::Playing {
+            self.seen_hits.clear();
+            self.predicted_hits.clear();
+            return;
+        }
+        let (sprite_sheet, hit_confirm) = match system_data.asset_handles.as_ref() {
+            Some(asset_handles) => (
+                asset_handles.hit_markers.clone(),
+                asset_handles.hit_confirm.clone(),
+            ),
+            None => return,
+        };
+        let now = system_data
+            .game_time_service
+            .engine_time()
+            .absolute_real_time();
+        let frame_number = system_data.game_time_service.game_frame_number();
+
+        let local_player = (&system_data.entities, &system_data.client_player_actions)
+            .join()
+            .next()
+            .map(|(entity, _)| entity);
+        let mut new_hits = Vec::new();
+        for (missile_entity, missile) in (&system_data.entities, &system_data.missiles).join() {
+            if Some(missile.caster) != local_player {
+                continue;
+            }
+            for monster in &missile.hit_monsters {
+                if self.seen_hits.insert((missile_entity, *monster)) {
+                    self.predicted_hits
+                        .add(*monster, frame_number, missile.damage_type);
+                    new_hits.push(*monster);
+                }
+            }
+        }
+        let missiles = &system_data.missiles;
+        self.seen_hits
+            .retain(|(missile_entity, _)| missiles.contains(*missile_entity));
+
+        // A single player game deals the damage of hits right away.
+        let acknowledged_frame = if system_data.game_state_helper.is_multiplayer() {
+            system_data.last_acknowledged_update.frame_number
+        } else {
+            frame_number
+        };
+        let damage_histories = &system_data.damage_histories;
+        let no_regs = self
+            .predicted_hits
+            .resolve(acknowledged_frame, |hit| {
+                damage_histories
+                    .get(hit.target)
+                    .map_or(false, |damage_history| {
+                        damage_history.has_damage(hit.frames(), hit.damage_type)
+                    })
+            })
+            .into_iter()
+            .filter(|(_, registration)| *registration == HitRegistration::NoReg)
+            .map(|(hit, _)| hit.target)
+            .collect::<Vec<_>>();
+        if !no_regs.is_empty() {
+            log::debug!(
+                "{} predicted hit(s) didn't register (frame {})",
+                no_regs.len(),
+                frame_number
+            );
+        }
+
+        if !new_hits.is_empty() && !system_data.window_focus.is_muted {
+            if let (Some(output), Some(source)) = (
+                system_data.output.as_ref(),
+                system_data.source_storage.get(&hit_confirm),
+            ) {
+                output.play_once(source, system_data.settings.audio().effects_volume);
+            }
+        }
+        for monster in new_hits {
+            show_marker(&mut system_data, &sprite_sheet, monster, false, now);
+        }
+        for monster in no_regs {
+            show_marker(&mut system_data, &sprite_sheet, monster, true, now);
+        }
+
+        for (entity, hit_marker, sprite_render, tint) in (
+            &system_data.entities,
+            &system_data.hit_markers,
+            &system_data.sprite_renders,
+            &mut system_data.tints,
+        )
+            .join()
+        {
+            let (duration_secs, alpha) = if sprite_render.sprite_number == NO_REG_SPRITE_NUMBER {
+                (NO_REG_MARKER_DURATION_SECS, NO_REG_MARKER_ALPHA)
+            } else {
+                (MARKER_DURATION_SECS, 1.0)
+            };
+            let elapsed = now
+                .checked_sub(hit_marker.started_at)
+                .unwrap_or_default()
+                .as_secs_f32();
+            if elapsed >= duration_secs {
+                system_data
+                    .entities
+                    .delete(entity)
+                    .expect("Expected to delete a HitMarker");
+                continue;
+            }
+            tint.0.alpha = alpha * (1.0 - elapsed / duration_secs);
+        }
+    }
+}
+
+fn show_marker(
+    system_data: &mut HitMarkerSystemData,
+    sprite_sheet: &Handle<SpriteSheet>,
+    monster: Entity,
+    is_no_reg: bool,
+    started_at: Duration,
+) {
+    let position = match system_data.world_positions.get(monster) {
+        Some(world_position) => **world_position,
+        None => return,
+    };
+    let (sprite_number, alpha) = if is_no_reg {
+        (NO_REG_SPRITE_NUMBER, NO_REG_MARKER_ALPHA)
+    } else {
+        (HIT_SPRITE_NUMBER, 1.0)
+    };
+    let scale = system_data.settings.accessibility().hud_scale;
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(position.x, position.y, MARKER_Z);
+    transform.set_scale(Vector3::new(scale, scale, 1.0));
+    system_data
+        .entities
+        .build_entity()
+        .with(HitMarker { started_at }, &mut system_data.hit_markers)
+        .with(
+            SpriteRender {
+                sprite_sheet: sprite_sheet.clone(),
+                sprite_number,
+            },
+            &mut system_data.sprite_renders,
+        )
+        .with(
+            Tint(Srgba::new(1.0, 1.0, 1.0, alpha)),
+            &mut system_data.tints,
+        )
+        .with(transform, &mut system_data.transforms)
+        .build();
+}
@@ -1,13 +1,41 @@
 use amethyst::{
-    ecs::{Join, ReadExpect, ReadStorage, System, WriteStorage},
-    ui::UiText,
+    core::Transform,
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    input::{InputHandler, StringBindings},
+    renderer::SpriteRender,
+    ui::{UiImage, UiText},
     window::ScreenDimensions,
 };
 
-use gv_client_shared::ecs::{components::HealthUiGraphics, resources::HEALTH_UI_SCREEN_PADDING};
-use gv_core::{ecs::components::Player, math::Vector2};
+use gv_client_shared::{
+    accessibility::MarkerShape,
+    ecs::{
+        components::HealthUiGraphics,
+        resources::{AssetHandles, MultiplayerRoomState, HEALTH_UI_SCREEN_PADDING},
+    },
+    hints::action_keys,
+    settings::Settings,
+};
+use gv_core::{
+    actions::player::CastSpell,
+    augments::{PlayerAugments, MAX_AUGMENTS},
+    economy::{PlayerWallet, ShopItem},
+    ecs::{
+        components::{ClientPlayerActions, NetConnectionModel, Player, WorldPosition},
+        resources::{net::MultiplayerGameState, GameEngineState, GameLevelState, MatchScore},
+        system_data::time::GameTimeService,
+    },
+    math::{Vector2, Vector3},
+    objectives::Trigger,
+    room_settings::RoomSettings,
+    stats::Stat,
+};
+use gv_game::{
+    ecs::resources::{ActiveLevelScript, ActiveObjectives, ClassDefinitions, SpellDefinitions},
+    utils::entities::player_stat_modifiers,
+};
 
-use crate::ecs::system_data::ui::UiFinderMut;
+use crate::ecs::{resources::DisplayDebugInfoSettings, system_data::ui::UiFinderMut};
 
 pub struct HealthUiSystem;
 
@@ -15,6 +43,7 @@ impl<'s> System<'s> for HealthUiSystem {
     type SystemData = (
         UiFinderMut<'s>,
         ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, Settings>,
         ReadStorage<'s, Player>,
         WriteStorage<'s, HealthUiGraphics>,
         WriteStorage<'s, UiText>,
@@ -22,22 +51,723 @@ impl<'s> System<'s> for HealthUiSystem {
 
     fn run(
         &mut self,
-        (ui_finder, screen_dimensions, players, mut health_uis, mut ui_texts): Self::SystemData,
+        (ui_finder, screen_dimensions, settings, players, mut health_uis, mut ui_texts): Self::SystemData,
     ) {
         let half_screen_width = screen_dimensions.width() / 2.0;
         let half_screen_height = screen_dimensions.height() / 2.0;
 
         for (player, health_ui) in (&players, &mut health_uis).join() {
-            health_ui.health = player.health / 100.0;
+            health_ui.health = player.health / player.max_health;
+            health_ui.scale_ratio = settings.accessibility().hud_scale;
             health_ui.screen_position = Vector2::new(
                 -half_screen_width + HEALTH_UI_SCREEN_PADDING,
                 -half_screen_height + HEALTH_UI_SCREEN_PADDING,
             );
 
             if let Some(ui_health_label) = ui_finder.find("ui_health_label") {
-                ui_texts.get_mut(ui_health_label).unwrap().text =
-                    format!("{:.0}/100", num::Float::max(0.0, player.health));
+                ui_texts.get_mut(ui_health_label).unwrap().text = format!(
+                    "{:.0}/{:.0}",
+                    num::Float::max(0.0, player.health),
+                    player.max_health
+                );
+            }
+        }
+    }
+}
+
+pub struct ScoreUiSystem;
+
+impl<'s> System<'s> for ScoreUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        ReadExpect<'s, MatchScore>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            match_score,
+            multiplayer_game_state,
+            multiplayer_room_state,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        if let Some(ui_score_label) = ui_finder.find("ui_score_label") {
+            ui_texts.get_mut(ui_score_label).unwrap().text = if match_score.multiplier > 1 {
+                format!("{} x{}", match_score.score, match_score.multiplier)
+            } else {
+                match_score.score.to_string()
+            };
+        }
+        if let Some(ui_afk_label) = ui_finder.find("ui_afk_label") {
+            // Only the host can do something about AFK players.
+            let afk_nicknames = if multiplayer_room_state.is_host {
+                multiplayer_game_state
+                    .afk_players
+                    .iter()
+                    .filter_map(|connection_id| {
+                        multiplayer_game_state.find_player_by_connection_id(*connection_id)
+                    })
+                    .map(|player| player.nickname.as_str())
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            };
+            let disconnected_nicknames = multiplayer_game_state
+                .players
+                .iter()
+                .filter(|player| player.is_disconnected)
+                .map(|player| player.nickname.as_str())
+                .collect::<Vec<_>>();
+            let mut lines = Vec::new();
+            if !afk_nicknames.is_empty() {
+                lines.push(format!("AFK: {}", afk_nicknames.join(", ")));
+            }
+            if !disconnected_nicknames.is_empty() {
+                lines.push(format!(
+                    "Disconnected: {}",
+                    disconnected_nicknames.join(", ")
+                ));
+            }
+            ui_texts.get_mut(ui_afk_label).unwrap().text = lines.join("\n");
+        }
+    }
+}
+
+/// Jitter above this is considered unstable.
+const WARNING_JITTER_MS: u32 = 50;
+/// Packet loss above this is considered unstable.
+const WARNING_LOSS_RATE: f32 = 0.1;
+/// Pings unanswered for this long are counted as lost.
+const PING_TIMEOUT_SECS: f32 = 1.0;
+/// The warning stays on screen for this long after the connection gets stable.
+const WARNING_LINGER_SECS: f32 = 3.0;
+
+/// Warns about packet loss and jitter measured with pings to the server,
+/// can be turned off with `NetworkSettings::connection_warnings`.
+#[derive(Default)]
+pub struct ConnectionWarningUiSystem {
+    last_warning: Option<(u64, String)>,
+}
+
+impl<'s> System<'s> for ConnectionWarningUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        ReadStorage<'s, NetConnectionModel>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            settings,
+            multiplayer_room_state,
+            net_connection_models,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let ui_connection_warning_label = match ui_finder.find("ui_connection_warning_label") {
+            Some(ui_connection_warning_label) => ui_connection_warning_label,
+            None => return,
+        };
+
+        let engine_frame_number = game_time_service.engine_time().frame_number();
+        let delta_seconds = game_time_service.engine_time().delta_seconds();
+        let net_connection_model = (&net_connection_models).join().next();
+        let warning = match net_connection_model {
+            Some(net_connection_model)
+                if multiplayer_room_state.is_active
+                    && settings.network().connection_warnings
+                    && delta_seconds > 0.0 =>
+            {
+                let timeout_frames = (PING_TIMEOUT_SECS / delta_seconds).ceil() as u64;
+                let ping_pong_data = &net_connection_model.ping_pong_data;
+                let loss_rate = ping_pong_data.loss_rate(engine_frame_number, timeout_frames);
+                let jitter_ms = ping_pong_data.jitter_ms(delta_seconds);
+                if loss_rate > WARNING_LOSS_RATE || jitter_ms > WARNING_JITTER_MS {
+                    Some(format!(
+                        "Connection unstable: {:.0}% packet loss, {} ms jitter",
+                        loss_rate * 100.0,
+                        jitter_ms
+                    ))
+                } else {
+                    None
+                }
             }
+            _ => {
+                self.last_warning = None;
+                None
+            }
+        };
+
+        if let Some(warning) = warning {
+            self.last_warning = Some((engine_frame_number, warning));
+        }
+        let text = match &self.last_warning {
+            Some((frame_number, warning))
+                if (engine_frame_number - frame_number) as f32 * delta_seconds
+                    < WARNING_LINGER_SECS =>
+            {
+                warning.clone()
+            }
+            _ => String::new(),
+        };
+        ui_texts.get_mut(ui_connection_warning_label).unwrap().text = text;
+    }
+}
+
+pub struct AugmentsUiSystem;
+
+impl<'s> System<'s> for AugmentsUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        ReadExpect<'s, MatchScore>,
+        ReadExpect<'s, Settings>,
+        Option<Read<'s, AssetHandles>>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, PlayerAugments>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            match_score,
+            settings,
+            asset_handles,
+            client_player_actions,
+            player_augments,
+            mut ui_texts,
+            mut ui_images,
+        ): Self::SystemData,
+    ) {
+        let (asset_handles, player_augments) = match (
+            asset_handles,
+            (&client_player_actions, &player_augments).join().next(),
+        ) {
+            (Some(asset_handles), Some((_, player_augments))) => (asset_handles, player_augments),
+            _ => return,
+        };
+
+        if let Some(ui_level_label) = ui_finder.find("ui_level_label") {
+            ui_texts.get_mut(ui_level_label).unwrap().text =
+                format!("Level {}", match_score.level());
+        }
+        if let Some(ui_augment_hint_label) = ui_finder.find("ui_augment_hint_label") {
+            ui_texts.get_mut(ui_augment_hint_label).unwrap().text =
+                if player_augments.can_choose(match_score.level()) {
+                    format!(
+                        "Level up! Choose an augment: Multishot ({}), Pierce ({}), Chain ({})",
+                        action_keys(settings.bindings(), "augment_1"),
+                        action_keys(settings.bindings(), "augment_2"),
+                        action_keys(settings.bindings(), "augment_3"),
+                    )
+                } else {
+                    String::new()
+                };
+        }
+
+        for i in 0..MAX_AUGMENTS {
+            if let Some(ui_augment_icon) = ui_finder.find(&format!("ui_augment_icon_{}", i + 1)) {
+                let ui_image = player_augments.0.get(i).map_or(
+                    UiImage::SolidColor([0.0, 0.0, 0.0, 0.0]),
+                    |augment| {
+                        UiImage::Sprite(SpriteRender {
+                            sprite_sheet: asset_handles.augments.clone(),
+                            sprite_number: augment.sprite_number(),
+                        })
+                    },
+                );
+                ui_images
+                    .insert(ui_augment_icon, ui_image)
+                    .expect("Expected to insert a UiImage");
+            }
+        }
+    }
+}
+
+pub struct ShopUiSystem;
+
+impl<'s> System<'s> for ShopUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, Settings>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, PlayerWallet>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            game_level_state,
+            multiplayer_game_state,
+            settings,
+            client_player_actions,
+            player_wallets,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let wallet = match (&client_player_actions, &player_wallets).join().next() {
+            Some((_, wallet)) => wallet,
+            None => return,
+        };
+        let frame_number = game_time_service.game_frame_number();
+
+        if let Some(ui_coins_label) = ui_finder.find("ui_coins_label") {
+            ui_texts.get_mut(ui_coins_label).unwrap().text = format!("{} coins", wallet.coins);
+        }
+        if let Some(ui_items_label) = ui_finder.find("ui_items_label") {
+            ui_texts.get_mut(ui_items_label).unwrap().text = wallet
+                .items
+                .iter()
+                .filter(|(item, _)| wallet.has_item(*item, frame_number))
+                .map(|(item, expires_at)| {
+                    let seconds_left =
+                        (expires_at - frame_number) as f32 * game_time_service.tick_seconds();
+                    format!("{} ({:.0}s)", item.name(), seconds_left)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+        if let Some(ui_shop_label) = ui_finder.find("ui_shop_label") {
+            ui_texts.get_mut(ui_shop_label).unwrap().text = if let Some(intermission) =
+                game_level_state.intermission.as_ref()
+            {
+                let seconds_left = intermission.ends_at_frame.saturating_sub(frame_number) as f32
+                    * game_time_service.tick_seconds();
+                let readiness = if multiplayer_game_state.is_playing {
+                    format!(
+                        " ({}/{} ready)",
+                        intermission.ready_players.len(),
+                        multiplayer_game_state.players.len()
+                    )
+                } else {
+                    String::new()
+                };
+                let items = ShopItem::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let keys = action_keys(settings.bindings(), &format!("buy_{}", i + 1));
+                        format!("{} - {} ({})", item.name(), item.price(), keys)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "Next wave in {:.0}s, press {} to start it{}. Shop: {}",
+                    seconds_left,
+                    action_keys(settings.bindings(), "ready_for_wave"),
+                    readiness,
+                    items
+                )
+            } else {
+                String::new()
+            };
+        }
+    }
+}
+
+/// Lists the derived stats of the controlled player, is toggled with `toggle_character_panel`.
+#[derive(Default)]
+pub struct CharacterPanelUiSystem {
+    is_open: bool,
+    is_toggle_down: bool,
+}
+
+impl<'s> System<'s> for CharacterPanelUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, ClassDefinitions>,
+        ReadExpect<'s, SpellDefinitions>,
+        ReadExpect<'s, RoomSettings>,
+        Entities<'s>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PlayerWallet>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            input,
+            display_debug_info_settings,
+            class_definitions,
+            spell_definitions,
+            room_settings,
+            entities,
+            client_player_actions,
+            players,
+            player_wallets,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let is_toggle_down = !display_debug_info_settings.display_console
+            && input
+                .action_is_down("toggle_character_panel")
+                .unwrap_or_default();
+        if is_toggle_down && !self.is_toggle_down {
+            self.is_open = !self.is_open;
+        }
+        self.is_toggle_down = is_toggle_down;
+
+        let ui_character_panel_label = match ui_finder.find("ui_character_panel_label") {
+            Some(ui_character_panel_label) => ui_character_panel_label,
+            None => return,
+        };
+        let player = (&entities, &client_player_actions, &players)
+            .join()
+            .next()
+            .map(|(entity, _, player)| (entity, player));
+        let text = match player {
+            Some((entity, player)) if self.is_open => {
+                let frame_number = game_time_service.game_frame_number();
+                let stat_modifiers = player_stat_modifiers(
+                    entity,
+                    &player_wallets,
+                    &room_settings.mutators,
+                    frame_number,
+                );
+                let class_definition = class_definitions.get(player.class);
+                let missile_spell = spell_definitions.missile(class_definition);
+                let mut lines = vec![player.class.name().to_owned()];
+                for stat in Stat::ALL.iter() {
+                    let value = match stat {
+                        Stat::MoveSpeed => format!(
+                            "{:.0}",
+                            stat_modifiers.value(*stat, class_definition.base_speed)
+                        ),
+                        Stat::MissileDamage => {
+                            format!("{:.0}", stat_modifiers.value(*stat, missile_spell.damage))
+                        }
+                        Stat::DamageTaken => {
+                            format!("{:.0}%", stat_modifiers.value(*stat, 1.0) * 100.0)
+                        }
+                        Stat::CooldownReduction => format!(
+                            "{:.0}%",
+                            stat_modifiers.value(*stat, class_definition.base_cooldown_reduction)
+                                * 100.0
+                        ),
+                        Stat::Haste => format!(
+                            "x{:.2}",
+                            stat_modifiers.value(*stat, class_definition.base_haste)
+                        ),
+                        Stat::CritChance => format!(
+                            "{:.0}%",
+                            stat_modifiers.value(*stat, missile_spell.crit_chance) * 100.0
+                        ),
+                        Stat::CritMultiplier => format!(
+                            "x{:.2}",
+                            stat_modifiers.value(*stat, missile_spell.crit_multiplier)
+                        ),
+                        Stat::LifeSteal => {
+                            format!("{:.0}%", stat_modifiers.value(*stat, 0.0) * 100.0)
+                        }
+                    };
+                    lines.push(format!("{}: {}", stat.name(), value));
+                }
+                let cooldowns = [
+                    ("Missile", CastSpell::Missile),
+                    ("Heal", CastSpell::Heal),
+                    ("Zone", CastSpell::Zone),
+                    ("Taunt", CastSpell::Taunt),
+                ]
+                .iter()
+                .map(|(name, spell)| {
+                    format!(
+                        "{} {:.1}s",
+                        name,
+                        class_definitions.modified_cooldown_secs(
+                            player.class,
+                            *spell,
+                            &stat_modifiers
+                        )
+                    )
+                })
+                .collect::<Vec<_>>();
+                lines.push(format!("Cooldowns: {}", cooldowns.join(", ")));
+                lines.join("\n")
+            }
+            _ => String::new(),
+        };
+        ui_texts.get_mut(ui_character_panel_label).unwrap().text = text;
+    }
+}
+
+/// The marker sprite is drawn this many times larger than under players.
+const AREA_MARKER_SCALE: f32 = 3.0;
+/// For how long a message of a level script stays on the screen.
+const LEVEL_SCRIPT_MESSAGE_FRAMES: u64 = 60 * 5;
+
+/// Shows the current objective of the tutorial and marks the area that players have to enter.
+/// Messages of level scripts are shown with the same label.
+#[derive(Default)]
+pub struct ObjectiveUiSystem {
+    area_marker: Option<Entity>,
+}
+
+impl<'s> System<'s> for ObjectiveUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        Entities<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, ActiveObjectives>,
+        ReadExpect<'s, ActiveLevelScript>,
+        GameTimeService<'s>,
+        Option<Read<'s, AssetHandles>>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            entities,
+            game_engine_state,
+            multiplayer_game_state,
+            active_objectives,
+            active_level_script,
+            game_time_service,
+            asset_handles,
+            mut ui_texts,
+            mut sprite_renders,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        let objective_tracker = active_objectives
+            .0
+            .as_ref()
+            .filter(|_| *game_engine_state == GameEngineState::Playing)
+            .filter(|_| !multiplayer_game_state.is_playing);
+
+        let (description, hint) = match objective_tracker {
+            Some(objective_tracker) => match objective_tracker.current() {
+                Some(objective) => (
+                    match objective_tracker.progress_text() {
+                        Some(progress) => format!("{} ({})", objective.description, progress),
+                        None => objective.description.to_owned(),
+                    },
+                    objective.hint.to_owned(),
+                ),
+                None => (
+                    "Tutorial complete!".to_owned(),
+                    "You're ready for the real thing".to_owned(),
+                ),
+            },
+            None => match active_level_script.message.as_ref() {
+                Some((message, shown_at_frame))
+                    if *game_engine_state == GameEngineState::Playing
+                        && game_time_service
+                            .game_frame_number()
+                            .saturating_sub(*shown_at_frame)
+                            < game_time_service.scale_frames(LEVEL_SCRIPT_MESSAGE_FRAMES) =>
+                {
+                    (message.clone(), String::new())
+                }
+                _ => (String::new(), String::new()),
+            },
+        };
+        if let Some(ui_objective_label) = ui_finder.find("ui_objective_label") {
+            ui_texts.get_mut(ui_objective_label).unwrap().text = description;
+        }
+        if let Some(ui_objective_hint_label) = ui_finder.find("ui_objective_hint_label") {
+            ui_texts.get_mut(ui_objective_hint_label).unwrap().text = hint;
+        }
+
+        let area = objective_tracker
+            .and_then(|objective_tracker| objective_tracker.current())
+            .and_then(|objective| match objective.trigger {
+                Trigger::EnterArea { center, .. } => Some(center),
+                _ => None,
+            });
+        match (area, asset_handles.as_ref()) {
+            (Some(center), Some(asset_handles)) if self.area_marker.is_none() => {
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(center.x, center.y, 0.0);
+                transform.set_scale(Vector3::new(AREA_MARKER_SCALE, AREA_MARKER_SCALE, 1.0));
+                self.area_marker = Some(
+                    entities
+                        .build_entity()
+                        .with(
+                            SpriteRender {
+                                sprite_sheet: asset_handles.player_markers.clone(),
+                                sprite_number: MarkerShape::Circle.sprite_number(),
+                            },
+                            &mut sprite_renders,
+                        )
+                        .with(transform, &mut transforms)
+                        .build(),
+                );
+            }
+            (None, _) => {
+                if let Some(area_marker) = self.area_marker.take() {
+                    entities
+                        .delete(area_marker)
+                        .expect("Expected to delete an area marker");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+const PAYLOAD_BAR_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.7];
+const PAYLOAD_BAR_FILL_COLOR: [f32; 4] = [0.95, 0.75, 0.2, 1.0];
+
+/// Shows how far the payload of a level script has travelled, with a bar and a label.
+/// The fill is sized relative to the bar, so that it follows the UI scale.
+pub struct PayloadUiSystem;
+
+impl<'s> System<'s> for PayloadUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+    );
+
+    fn run(
+        &mut self,
+        (mut ui_finder, game_engine_state, active_level_script, mut ui_texts, mut ui_images): Self::SystemData,
+    ) {
+        let payload = active_level_script
+            .runner
+            .as_ref()
+            .and_then(|runner| runner.payload())
+            .filter(|_| *game_engine_state == GameEngineState::Playing);
+
+        if let Some(ui_payload_label) = ui_finder.find("ui_payload_label") {
+            ui_texts.get_mut(ui_payload_label).unwrap().text = match payload {
+                Some(payload) if payload.is_arrived() => "The payload has arrived".to_owned(),
+                Some(payload) => format!(
+                    "Escort the payload: {:.0}%{}",
+                    payload.fraction() * 100.0,
+                    if payload.progress().is_escorted {
+                        ""
+                    } else {
+                        " (stopped)"
+                    }
+                ),
+                None => String::new(),
+            };
+        }
+
+        let (bar_color, fill_color) = if payload.is_some() {
+            (PAYLOAD_BAR_COLOR, PAYLOAD_BAR_FILL_COLOR)
+        } else {
+            ([0.0; 4], [0.0; 4])
+        };
+        let bar_width = match ui_finder.find_with_mut_transform("ui_payload_bar") {
+            Some((ui_payload_bar, ui_transform)) => {
+                ui_images
+                    .insert(ui_payload_bar, UiImage::SolidColor(bar_color))
+                    .expect("Expected to insert a UiImage");
+                ui_transform.width
+            }
+            None => return,
+        };
+        if let Some((ui_payload_bar_fill, ui_transform)) =
+            ui_finder.find_with_mut_transform("ui_payload_bar_fill")
+        {
+            ui_transform.width = bar_width * payload.map_or(0.0, |payload| payload.fraction());
+            ui_images
+                .insert(ui_payload_bar_fill, UiImage::SolidColor(fill_color))
+                .expect("Expected to insert a UiImage");
+        }
+    }
+}
+
+/// The vignette starts showing this close to a level border.
+const BOUNDS_VIGNETTE_DISTANCE: f32 = 250.0;
+const BOUNDS_VIGNETTE_COLOR: [f32; 3] = [0.8, 0.1, 0.1];
+const BOUNDS_VIGNETTE_MAX_ALPHA: f32 = 0.5;
+
+/// Tints the screen edges that face the level borders as the local player gets close
+/// to them, the closer the player is, the stronger the tint.
+pub struct BoundsVignetteSystem;
+
+impl<'s> System<'s> for BoundsVignetteSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, WorldPosition>,
+        WriteStorage<'s, UiImage>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_engine_state,
+            game_level_state,
+            client_player_actions,
+            world_positions,
+            mut ui_images,
+        ): Self::SystemData,
+    ) {
+        let player_position = (&client_player_actions, &world_positions)
+            .join()
+            .next()
+            .map(|(_, world_position)| **world_position)
+            .filter(|_| *game_engine_state == GameEngineState::Playing);
+
+        let half_size = game_level_state.dimensions_half_size();
+        // Distances to the left, right, top and bottom borders.
+        let distances = player_position.map(|position| {
+            [
+                position.x + half_size.x,
+                half_size.x - position.x,
+                half_size.y - position.y,
+                position.y + half_size.y,
+            ]
+        });
+        let edges = [
+            "ui_bounds_vignette_left",
+            "ui_bounds_vignette_right",
+            "ui_bounds_vignette_top",
+            "ui_bounds_vignette_bottom",
+        ];
+        for (i, ui_id) in edges.iter().enumerate() {
+            let ui_entity = match ui_finder.find(ui_id) {
+                Some(ui_entity) => ui_entity,
+                None => return,
+            };
+            let alpha = distances.map_or(0.0, |distances| {
+                (1.0 - distances[i].max(0.0) / BOUNDS_VIGNETTE_DISTANCE).max(0.0)
+                    * BOUNDS_VIGNETTE_MAX_ALPHA
+            });
+            let [r, g, b] = BOUNDS_VIGNETTE_COLOR;
+            ui_images
+                .insert(ui_entity, UiImage::SolidColor([r, g, b, alpha]))
+                .expect("Expected to insert a UiImage");
         }
     }
 }
@@ -0,0 +1,215 @@
+use amethyst::{
+    ecs::{ReadExpect, System, World, WriteExpect},
+    input::{InputHandler, StringBindings},
+    window::ScreenDimensions,
+};
+use amethyst_imgui::imgui::{self, im_str, ImString};
+
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_core::{
+    ecs::resources::net::{MultiplayerGameState, PlayersNetStatus},
+    votes::VoteKind,
+};
+use gv_game::console::{CommandPermission, Console, ConsoleCommand, ConsoleCommands};
+
+use crate::ecs::resources::{DisplayDebugInfoSettings, Votes};
+
+const MAX_INPUT_LENGTH: usize = 256;
+const MAX_HISTORY_LINES: usize = 200;
+const VISIBLE_HISTORY_LINES: usize = 14;
+
+/// Registers the commands that need client-only resources.
+pub fn register_client_console_commands(console_commands: &mut ConsoleCommands) {
+    console_commands.register(ConsoleCommand {
+        name: "net stats",
+        usage: "net stats",
+        description: "Shows the latency and pending updates of the players",
+        permission: CommandPermission::Anyone,
+        handler: net_stats,
+    });
+    console_commands.register(ConsoleCommand {
+        name: "vote kick",
+        usage: "vote kick <nickname>",
+        description: "Starts a vote to kick the player",
+        permission: CommandPermission::Anyone,
+        handler: vote_kick,
+    });
+    console_commands.register(ConsoleCommand {
+        name: "vote lobby",
+        usage: "vote lobby",
+        description: "Starts a vote to end the game and return to the lobby",
+        permission: CommandPermission::Anyone,
+        handler: vote_lobby,
+    });
+    console_commands.register(ConsoleCommand {
+        name: "vote skip",
+        usage: "vote skip",
+        description: "Starts a vote to skip the intermission",
+        permission: CommandPermission::Anyone,
+        handler: vote_skip,
+    });
+}
+
+/// The drop-down developer console, is toggled with the backquote key.
+pub struct ImguiConsoleSystem {
+    input: ImString,
+    history: Vec<String>,
+    is_toggle_down: bool,
+    focus_input: bool,
+}
+
+impl Default for ImguiConsoleSystem {
+    fn default() -> Self {
+        Self {
+            input: ImString::with_capacity(MAX_INPUT_LENGTH),
+            history: Vec::new(),
+            is_toggle_down: false,
+            focus_input: false,
+        }
+    }
+}
+
+impl<'s> System<'s> for ImguiConsoleSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        WriteExpect<'s, DisplayDebugInfoSettings>,
+        WriteExpect<'s, Console>,
+    );
+
+    fn run(
+        &mut self,
+        (screen_dimensions, input, mut display_debug_info_settings, mut console): Self::SystemData,
+    ) {
+        let is_toggle_down = input.action_is_down("toggle_console").unwrap_or_default();
+        if is_toggle_down && !self.is_toggle_down {
+            display_debug_info_settings.display_console =
+                !display_debug_info_settings.display_console;
+            self.focus_input = display_debug_info_settings.display_console;
+        }
+        self.is_toggle_down = is_toggle_down;
+
+        for output in console.output.drain(..) {
+            self.history.extend(output.lines().map(str::to_owned));
+        }
+        let overflow = self.history.len().saturating_sub(MAX_HISTORY_LINES);
+        self.history.drain(..overflow);
+
+        if !display_debug_info_settings.display_console {
+            return;
+        }
+
+        let ImguiConsoleSystem {
+            input: console_input,
+            history,
+            focus_input,
+            ..
+        } = self;
+        amethyst_imgui::with(|ui| {
+            imgui::Window::new(im_str!("Console"))
+                .title_bar(false)
+                .movable(false)
+                .resizable(false)
+                .save_settings(false)
+                .collapsible(false)
+                .position([0.0, 0.0], imgui::Condition::Always)
+                .size(
+                    [
+                        screen_dimensions.width() / screen_dimensions.hidpi_factor() as f32,
+                        VISIBLE_HISTORY_LINES as f32 * 17.0 + 40.0,
+                    ],
+                    imgui::Condition::Always,
+                )
+                .bg_alpha(0.85)
+                .build(ui, || {
+                    let first_visible_line = history.len().saturating_sub(VISIBLE_HISTORY_LINES);
+                    for line in &history[first_visible_line..] {
+                        ui.text(line);
+                    }
+
+                    if std::mem::replace(focus_input, false) {
+                        ui.set_keyboard_focus_here(imgui::FocusedWidget::Next);
+                    }
+                    let is_submitted = ui
+                        .input_text(im_str!("##console_input"), console_input)
+                        .enter_returns_true(true)
+                        .build();
+                    if is_submitted {
+                        // The toggling key may end up in the input too.
+                        let line = console_input.to_str().replace('`', "").trim().to_owned();
+                        console_input.clear();
+                        if !line.is_empty() {
+                            history.push(format!("> {}", line));
+                            console.pending.push(line);
+                        }
+                        ui.set_keyboard_focus_here(imgui::FocusedWidget::Previous);
+                    }
+                });
+        });
+    }
+}
+
+fn net_stats(world: &mut World, _arguments: &[&str]) -> Result<String, String> {
+    let multiplayer_game_state = world.fetch::<MultiplayerGameState>();
+    if !multiplayer_game_state.is_playing {
+        return Err("It's not a multiplayer game".to_owned());
+    }
+
+    let players_net_status = world.fetch::<PlayersNetStatus>();
+    let lines = multiplayer_game_state
+        .players
+        .iter()
+        .map(|player| {
+            let player_net_status = players_net_status
+                .players
+                .iter()
+                .cloned()
+                .find(|player_net_status| player_net_status.connection_id == player.connection_id)
+                .unwrap_or_default();
+            format!(
+                "{}: latency {} ms, {} frames behind, {} pending updates",
+                player.nickname,
+                player_net_status.latency_ms,
+                player_net_status.average_lagging_behind,
+                player_net_status.pending_world_updates
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn vote_kick(world: &mut World, arguments: &[&str]) -> Result<String, String> {
+    let nickname = match arguments {
+        [nickname] => *nickname,
+        _ => return Err("Expected a nickname".to_owned()),
+    };
+    let connection_id = world
+        .fetch::<MultiplayerGameState>()
+        .players
+        .iter()
+        .find(|player| player.nickname.eq_ignore_ascii_case(nickname))
+        .map(|player| player.connection_id)
+        .ok_or_else(|| format!("There's no player {:?}", nickname))?;
+    start_vote(world, VoteKind::Kick(connection_id))
+}
+
+fn vote_lobby(world: &mut World, _arguments: &[&str]) -> Result<String, String> {
+    start_vote(world, VoteKind::ReturnToLobby)
+}
+
+fn vote_skip(world: &mut World, _arguments: &[&str]) -> Result<String, String> {
+    start_vote(world, VoteKind::SkipIntermission)
+}
+
+/// The server checks whether the vote can be started and tells everyone in the room.
+fn start_vote(world: &mut World, kind: VoteKind) -> Result<String, String> {
+    if !world
+        .fetch::<MultiplayerRoomState>()
+        .connection_status
+        .is_connected()
+    {
+        return Err("Votes are available only in multiplayer rooms".to_owned());
+    }
+    world.fetch_mut::<Votes>().outgoing_start = Some(kind);
+    Ok("Starting a vote".to_owned())
+}
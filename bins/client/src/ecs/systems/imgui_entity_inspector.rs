@@ -0,0 +1,208 @@
+use amethyst::{
+    core::{math::Point3, Parent, Transform},
+    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect},
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+    window::ScreenDimensions,
+    winit::MouseButton,
+};
+use amethyst_imgui::imgui::{self, im_str};
+
+use gv_core::{
+    ecs::{
+        components::{
+            affixes::MonsterAffixes, Dead, EntityNetMetadata, Monster, Player, WorldPosition,
+        },
+        resources::GameEngineState,
+    },
+    math::Vector2,
+};
+
+use crate::ecs::resources::DisplayDebugInfoSettings;
+
+/// Clicks further than this from any entity don't change the inspected one.
+const PICK_RADIUS: f32 = 32.0;
+
+/// Toggles the free camera with `toggle_free_camera` and shows the components of the entity
+/// clicked while it's on. The camera itself is moved by `CameraSystem`.
+#[derive(Default)]
+pub struct ImguiEntityInspectorSystem {
+    is_toggle_down: bool,
+    is_mouse_down: bool,
+}
+
+impl<'s> System<'s> for ImguiEntityInspectorSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        WriteExpect<'s, DisplayDebugInfoSettings>,
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Parent>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, MonsterAffixes>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, EntityNetMetadata>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_engine_state,
+            screen_dimensions,
+            input,
+            mut display_debug_info_settings,
+            entities,
+            cameras,
+            parents,
+            transforms,
+            world_positions,
+            players,
+            monsters,
+            monster_affixes,
+            dead,
+            entity_net_metadata,
+        ): Self::SystemData,
+    ) {
+        if !game_engine_state.is_playing() {
+            display_debug_info_settings.free_camera = false;
+            display_debug_info_settings.inspected_entity = None;
+            return;
+        }
+
+        let is_toggle_down = input
+            .action_is_down("toggle_free_camera")
+            .unwrap_or_default();
+        if is_toggle_down && !self.is_toggle_down && !display_debug_info_settings.display_console {
+            display_debug_info_settings.free_camera = !display_debug_info_settings.free_camera;
+            if !display_debug_info_settings.free_camera {
+                display_debug_info_settings.inspected_entity = None;
+            }
+        }
+        self.is_toggle_down = is_toggle_down;
+
+        let is_mouse_down = input.mouse_button_is_down(MouseButton::Left);
+        let is_clicked = is_mouse_down && !self.is_mouse_down;
+        self.is_mouse_down = is_mouse_down;
+
+        if !display_debug_info_settings.free_camera {
+            return;
+        }
+
+        if display_debug_info_settings
+            .inspected_entity
+            .map_or(false, |entity| !entities.is_alive(entity))
+        {
+            display_debug_info_settings.inspected_entity = None;
+        }
+
+        let mouse_world_position = input.mouse_position().and_then(|(mouse_x, mouse_y)| {
+            let (camera, _, camera_transform) = (&cameras, &parents, &transforms).join().next()?;
+            let position = camera.projection().screen_to_world_point(
+                Point3::new(mouse_x as f32, mouse_y as f32, 0.0),
+                screen_dimensions.diagonal(),
+                camera_transform,
+            );
+            Some(Vector2::new(position.x, position.y))
+        });
+
+        amethyst_imgui::with(|ui| {
+            if is_clicked && !ui.io().want_capture_mouse {
+                if let Some(mouse_world_position) = mouse_world_position {
+                    display_debug_info_settings.inspected_entity =
+                        pick_entity(&entities, &world_positions, mouse_world_position);
+                }
+            }
+
+            imgui::Window::new(im_str!("Entity Inspector"))
+                .resizable(false)
+                .save_settings(false)
+                .collapsible(false)
+                .focus_on_appearing(false)
+                .position(
+                    [
+                        0.0,
+                        screen_dimensions.height() / screen_dimensions.hidpi_factor() as f32,
+                    ],
+                    imgui::Condition::FirstUseEver,
+                )
+                .position_pivot([-0.1, 1.1])
+                .size([320.0, 220.0], imgui::Condition::FirstUseEver)
+                .bg_alpha(0.8)
+                .build(ui, || {
+                    ui.text("Free camera: WASD to fly, mouse wheel to zoom");
+                    ui.separator();
+
+                    let entity = match display_debug_info_settings.inspected_entity {
+                        Some(entity) => entity,
+                        None => {
+                            ui.text("Click an entity to inspect it");
+                            return;
+                        }
+                    };
+
+                    ui.text(format!("Entity: {}:{}", entity.id(), entity.gen().id()));
+                    if let Some(entity_net_metadata) = entity_net_metadata.get(entity) {
+                        ui.text(format!(
+                            "Net id: {} (spawned on frame {})",
+                            entity_net_metadata.id, entity_net_metadata.spawned_frame_number
+                        ));
+                    }
+                    if let Some(world_position) = world_positions.get(entity) {
+                        ui.text(format!(
+                            "Position: ({:.1}, {:.1})",
+                            world_position.x, world_position.y
+                        ));
+                    }
+                    if let Some(player) = players.get(entity) {
+                        ui.text(format!("Player: {:?}", player.class));
+                        ui.text(format!(
+                            "Health: {:.1}/{:.1}",
+                            player.health, player.max_health
+                        ));
+                        ui.text(format!(
+                            "Velocity: ({:.1}, {:.1})",
+                            player.velocity.x, player.velocity.y
+                        ));
+                    }
+                    if let Some(monster) = monsters.get(entity) {
+                        ui.text(format!("Monster: {}", monster.name));
+                        ui.text(format!("Health: {:.1}", monster.health));
+                        ui.text(format!(
+                            "AI state: {:?} (since frame {})",
+                            monster.action.action, monster.action.frame_number
+                        ));
+                        ui.text(format!(
+                            "Destination: ({:.1}, {:.1})",
+                            monster.destination.x, monster.destination.y
+                        ));
+                    }
+                    if let Some(monster_affixes) = monster_affixes.get(entity) {
+                        if !monster_affixes.affixes.is_empty() {
+                            ui.text(format!("Affixes: {:?}", monster_affixes.affixes));
+                        }
+                    }
+                    if dead.contains(entity) {
+                        ui.text_colored([1.0, 0.3, 0.3, 1.0], "Dead");
+                    }
+                });
+        });
+    }
+}
+
+fn pick_entity(
+    entities: &Entities<'_>,
+    world_positions: &ReadStorage<'_, WorldPosition>,
+    position: Vector2,
+) -> Option<Entity> {
+    (entities, world_positions)
+        .join()
+        .map(|(entity, world_position)| (entity, (**world_position - position).norm()))
+        .filter(|(_, distance)| *distance < PICK_RADIUS)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("Expected a non-NaN distance"))
+        .map(|(entity, _)| entity)
+}
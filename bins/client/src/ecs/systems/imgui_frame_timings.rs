@@ -0,0 +1,112 @@
+use amethyst::{
+    ecs::{ReadExpect, System},
+    window::ScreenDimensions,
+};
+use amethyst_imgui::imgui::{self, im_str, ImString};
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use gv_core::profiling::FrameTimings;
+
+use crate::ecs::resources::DisplayDebugInfoSettings;
+
+const FRAME_TRACE_FILE: &str = "frame_trace.json";
+const DISPLAYED_SYSTEMS: usize = 12;
+
+pub struct ImguiFrameTimingsSystem;
+
+impl<'s> System<'s> for ImguiFrameTimingsSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, FrameTimings>,
+    );
+
+    fn run(
+        &mut self,
+        (screen_dimensions, display_debug_info_settings, frame_timings): Self::SystemData,
+    ) {
+        if !display_debug_info_settings.display_frame_timings {
+            return;
+        }
+
+        let (frame_durations, slowest_systems) = frame_timings.with_history(|frames| {
+            let frame_durations = frames
+                .iter()
+                .map(|frame| as_millis(frame.duration))
+                .collect::<Vec<_>>();
+
+            let mut total_durations = HashMap::<&'static str, Duration>::new();
+            for system in frames.iter().flat_map(|frame| frame.systems.iter()) {
+                *total_durations.entry(system.name).or_default() += system.duration;
+            }
+            let frames_count = frames.len().max(1) as f32;
+            let mut average_durations = total_durations
+                .into_iter()
+                .map(|(name, duration)| (name, as_millis(duration) / frames_count))
+                .collect::<Vec<_>>();
+            average_durations.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+            average_durations.truncate(DISPLAYED_SYSTEMS);
+
+            (frame_durations, average_durations)
+        });
+
+        amethyst_imgui::with(|ui| {
+            imgui::Window::new(im_str!("Frame Timings"))
+                .title_bar(false)
+                .movable(false)
+                .resizable(false)
+                .save_settings(false)
+                .collapsible(false)
+                .focused(false)
+                .focus_on_appearing(false)
+                .no_nav()
+                .position(
+                    [
+                        0.0,
+                        screen_dimensions.height() / screen_dimensions.hidpi_factor() as f32,
+                    ],
+                    imgui::Condition::Always,
+                )
+                .position_pivot([-0.1, 1.1])
+                .size([320.0, 330.0], imgui::Condition::Always)
+                .bg_alpha(0.7)
+                .build(ui, || {
+                    let last_frame = frame_durations.last().cloned().unwrap_or_default();
+                    let max_frame = frame_durations.iter().cloned().fold(0.0, f32::max);
+                    ui.plot_lines(im_str!(""), &frame_durations)
+                        .overlay_text(&ImString::new(format!(
+                            "{:.2}ms (max {:.2}ms)",
+                            last_frame, max_frame
+                        )))
+                        .scale_min(0.0)
+                        .scale_max(max_frame.max(1000.0 / 60.0))
+                        .graph_size([300.0, 80.0])
+                        .build();
+
+                    ui.columns(2, im_str!("Frame Timings"), false);
+                    ui.text("System");
+                    ui.next_column();
+                    ui.text("Avg, ms");
+                    for (name, average_duration) in &slowest_systems {
+                        ui.next_column();
+                        ui.text(name);
+                        ui.next_column();
+                        ui.text(format!("{:.3}", average_duration));
+                    }
+                    ui.columns(1, im_str!("Frame Timings"), false);
+
+                    if ui.small_button(im_str!("Save trace")) {
+                        match frame_timings.write_chrome_trace(Path::new(FRAME_TRACE_FILE)) {
+                            Ok(()) => log::info!("Saved a frame trace to {}", FRAME_TRACE_FILE),
+                            Err(err) => log::error!("Failed to save a frame trace: {:?}", err),
+                        }
+                    }
+                });
+        });
+    }
+}
+
+fn as_millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
@@ -5,7 +5,8 @@ use amethyst::{
 use amethyst_imgui::imgui::{self, im_str};
 
 use gv_core::ecs::resources::{
-    net::{MultiplayerGameState, PlayersNetStatus},
+    net::{MultiplayerGameState, PlayersNetStatus, ReconciliationStatus},
+    world::{FramedUpdates, ReceivedServerWorldUpdate, WorldStates, SAVED_WORLD_STATES_LIMIT},
     GameEngineState,
 };
 
@@ -20,6 +21,9 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
         ReadExpect<'s, MultiplayerGameState>,
         ReadExpect<'s, DisplayDebugInfoSettings>,
         ReadExpect<'s, PlayersNetStatus>,
+        ReadExpect<'s, ReconciliationStatus>,
+        ReadExpect<'s, FramedUpdates<ReceivedServerWorldUpdate>>,
+        ReadExpect<'s, WorldStates>,
     );
 
     fn run(
@@ -30,6 +34,9 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
             multiplayer_game_state,
             display_debug_info_settings,
             players_net_status,
+            reconciliation_status,
+            framed_updates,
+            world_states,
         ): Self::SystemData,
     ) {
         if !game_engine_state.is_playing() {
@@ -55,7 +62,7 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                         imgui::Condition::Always,
                     )
                     .position_pivot([1.1, -0.1])
-                    .size([250.0, 150.0], imgui::Condition::Always)
+                    .size([300.0, 210.0], imgui::Condition::Always)
                     .bg_alpha(0.7)
                     .build(ui, || {
                         if !multiplayer_game_state.is_playing {
@@ -63,7 +70,31 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                             return;
                         }
 
-                        ui.columns(4, im_str!("Network Debug Info"), false);
+                        ui.text(format!(
+                            "Received updates: {}/{}",
+                            framed_updates.updates.len(),
+                            SAVED_WORLD_STATES_LIMIT
+                        ));
+                        ui.text(format!(
+                            "World states: {}/{}",
+                            world_states.len(),
+                            SAVED_WORLD_STATES_LIMIT
+                        ));
+                        if reconciliation_status.is_diverged() {
+                            ui.text_colored(
+                                [1.0, 0.3, 0.3, 1.0],
+                                format!(
+                                    "Diverged: {:.1} units, cooldowns: {} ({} reports)",
+                                    reconciliation_status.position_divergence,
+                                    reconciliation_status.cooldowns_diverged,
+                                    reconciliation_status.consecutive_divergences
+                                ),
+                            );
+                        } else {
+                            ui.text("Prediction is in sync with the server");
+                        }
+
+                        ui.columns(5, im_str!("Network Debug Info"), false);
                         ui.text("Name");
                         ui.next_column();
                         ui.text("Diff");
@@ -71,6 +102,8 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                         ui.text("Behind");
                         ui.next_column();
                         ui.text("Latency");
+                        ui.next_column();
+                        ui.text("Pending");
                         for player in &multiplayer_game_state.players {
                             let player_net_status = players_net_status
                                 .players
@@ -91,6 +124,8 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                             ui.text(player_net_status.average_lagging_behind.to_string());
                             ui.next_column();
                             ui.text(player_net_status.latency_ms.to_string());
+                            ui.next_column();
+                            ui.text(player_net_status.pending_world_updates.to_string());
                         }
                     });
             }
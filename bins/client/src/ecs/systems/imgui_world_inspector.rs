@@ -0,0 +1,286 @@
+use amethyst::{
+    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage},
+    input::{InputHandler, StringBindings},
+};
+use amethyst_imgui::imgui::{self, im_str, ImString};
+
+use gv_core::ecs::{
+    components::{missile::Missile, Dead, EntityNetMetadata, Monster, Player, WorldPosition},
+    resources::{
+        net::MultiplayerGameState,
+        world::{FramedUpdates, ReceivedServerWorldUpdate, WorldStates, SAVED_WORLD_STATES_LIMIT},
+        GameEngineState, GameLevelState,
+    },
+    system_data::time::GameTimeService,
+};
+
+use crate::ecs::resources::DisplayDebugInfoSettings;
+
+/// Entities having none of the checked components aren't listed.
+struct ComponentFilters {
+    players: bool,
+    monsters: bool,
+    missiles: bool,
+    others: bool,
+}
+
+impl Default for ComponentFilters {
+    fn default() -> Self {
+        Self {
+            players: true,
+            monsters: true,
+            missiles: false,
+            others: false,
+        }
+    }
+}
+
+/// A live entity and resource browser, is toggled with `toggle_world_inspector`. Components
+/// can be edited in single player only, as a client's edits would be overwritten by the server.
+///
+/// Is built with the `inspector` feature only.
+#[derive(Default)]
+pub struct ImguiWorldInspectorSystem {
+    is_toggle_down: bool,
+    is_open: bool,
+    filters: ComponentFilters,
+    selected: Option<Entity>,
+}
+
+impl<'s> System<'s> for ImguiWorldInspectorSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, FramedUpdates<ReceivedServerWorldUpdate>>,
+        ReadExpect<'s, WorldStates>,
+        ReadExpect<'s, GameLevelState>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Missile>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, EntityNetMetadata>,
+        WriteStorage<'s, WorldPosition>,
+        WriteStorage<'s, Player>,
+        WriteStorage<'s, Monster>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_engine_state,
+            input,
+            display_debug_info_settings,
+            multiplayer_game_state,
+            framed_updates,
+            world_states,
+            game_level_state,
+            game_time_service,
+            entities,
+            missiles,
+            dead,
+            entity_net_metadata,
+            mut world_positions,
+            mut players,
+            mut monsters,
+        ): Self::SystemData,
+    ) {
+        let is_toggle_down = input
+            .action_is_down("toggle_world_inspector")
+            .unwrap_or_default();
+        if is_toggle_down && !self.is_toggle_down && !display_debug_info_settings.display_console {
+            self.is_open = !self.is_open;
+        }
+        self.is_toggle_down = is_toggle_down;
+
+        if !self.is_open {
+            return;
+        }
+        if self
+            .selected
+            .map_or(false, |entity| !entities.is_alive(entity))
+        {
+            self.selected = None;
+        }
+        let is_editable = !multiplayer_game_state.is_playing;
+
+        let ImguiWorldInspectorSystem {
+            filters, selected, ..
+        } = self;
+        amethyst_imgui::with(|ui| {
+            imgui::Window::new(im_str!("World Inspector"))
+                .save_settings(false)
+                .position([20.0, 20.0], imgui::Condition::FirstUseEver)
+                .size([360.0, 520.0], imgui::Condition::FirstUseEver)
+                .bg_alpha(0.85)
+                .build(ui, || {
+                    if ui
+                        .collapsing_header(im_str!("Resources"))
+                        .default_open(true)
+                        .build()
+                    {
+                        ui.text(format!("Engine state: {:?}", *game_engine_state));
+                        ui.text(format!(
+                            "Game frame: {}",
+                            game_time_service.game_frame_number()
+                        ));
+                        ui.text(format!(
+                            "Level: {} ({}x{}), spawn level {}",
+                            game_level_state.map_name,
+                            game_level_state.dimensions.x,
+                            game_level_state.dimensions.y,
+                            game_level_state.spawn_level
+                        ));
+                        ui.text(format!(
+                            "Multiplayer: {}, {} players",
+                            multiplayer_game_state.is_playing,
+                            multiplayer_game_state.players.len()
+                        ));
+                        ui.text(format!(
+                            "Waiting for network: {}, for players: {}, lagging: {}",
+                            multiplayer_game_state.waiting_network,
+                            multiplayer_game_state.waiting_for_players,
+                            multiplayer_game_state.lagging_players.len()
+                        ));
+                        ui.text(format!(
+                            "Framed updates: {}/{} (oldest updated frame {})",
+                            framed_updates.updates.len(),
+                            SAVED_WORLD_STATES_LIMIT,
+                            framed_updates.oldest_updated_frame
+                        ));
+                        ui.text(format!(
+                            "World states: {}/{}",
+                            world_states.len(),
+                            SAVED_WORLD_STATES_LIMIT
+                        ));
+                    }
+
+                    if ui
+                        .collapsing_header(im_str!("Entities"))
+                        .default_open(true)
+                        .build()
+                    {
+                        ui.checkbox(im_str!("Players"), &mut filters.players);
+                        ui.same_line(0.0);
+                        ui.checkbox(im_str!("Monsters"), &mut filters.monsters);
+                        ui.same_line(0.0);
+                        ui.checkbox(im_str!("Missiles"), &mut filters.missiles);
+                        ui.same_line(0.0);
+                        ui.checkbox(im_str!("Others"), &mut filters.others);
+
+                        imgui::ChildWindow::new(im_str!("entity_list"))
+                            .size([0.0, 180.0])
+                            .border(true)
+                            .build(ui, || {
+                                for (entity, world_position) in (&entities, &world_positions).join()
+                                {
+                                    let kind = if let Some(player) = players.get(entity) {
+                                        if !filters.players {
+                                            continue;
+                                        }
+                                        format!("player ({:?})", player.class)
+                                    } else if let Some(monster) = monsters.get(entity) {
+                                        if !filters.monsters {
+                                            continue;
+                                        }
+                                        format!("monster ({})", monster.name)
+                                    } else if missiles.contains(entity) {
+                                        if !filters.missiles {
+                                            continue;
+                                        }
+                                        "missile".to_owned()
+                                    } else {
+                                        if !filters.others {
+                                            continue;
+                                        }
+                                        "other".to_owned()
+                                    };
+                                    let label = ImString::new(format!(
+                                        "{}:{} {} at ({:.0}, {:.0})",
+                                        entity.id(),
+                                        entity.gen().id(),
+                                        kind,
+                                        world_position.x,
+                                        world_position.y
+                                    ));
+                                    if imgui::Selectable::new(&label)
+                                        .selected(*selected == Some(entity))
+                                        .build(ui)
+                                    {
+                                        *selected = Some(entity);
+                                    }
+                                }
+                            });
+                    }
+
+                    let entity = match *selected {
+                        Some(entity) => entity,
+                        None => {
+                            ui.text("Select an entity to see its components");
+                            return;
+                        }
+                    };
+                    ui.separator();
+                    ui.text(format!("Entity {}:{}", entity.id(), entity.gen().id()));
+                    if !is_editable {
+                        ui.text_colored(
+                            [1.0, 0.8, 0.3, 1.0],
+                            "Components can be edited in single player only",
+                        );
+                    }
+                    if let Some(entity_net_metadata) = entity_net_metadata.get(entity) {
+                        ui.text(format!(
+                            "EntityNetMetadata: id {}, spawned on frame {}",
+                            entity_net_metadata.id, entity_net_metadata.spawned_frame_number
+                        ));
+                    }
+                    if let Some(world_position) = world_positions.get_mut(entity) {
+                        ui.text("WorldPosition");
+                        edit_f32(ui, im_str!("x"), &mut world_position.x, is_editable);
+                        edit_f32(ui, im_str!("y"), &mut world_position.y, is_editable);
+                    }
+                    if let Some(player) = players.get_mut(entity) {
+                        ui.text(format!("Player: {:?}", player.class));
+                        edit_f32(ui, im_str!("health"), &mut player.health, is_editable);
+                        edit_f32(
+                            ui,
+                            im_str!("max health"),
+                            &mut player.max_health,
+                            is_editable,
+                        );
+                        if is_editable {
+                            player.health = player.health.clamp(0.0, player.max_health);
+                        }
+                    }
+                    if let Some(monster) = monsters.get_mut(entity) {
+                        ui.text(format!("Monster: {}", monster.name));
+                        edit_f32(
+                            ui,
+                            im_str!("monster health"),
+                            &mut monster.health,
+                            is_editable,
+                        );
+                        edit_f32(
+                            ui,
+                            im_str!("attack damage"),
+                            &mut monster.attack_damage,
+                            is_editable,
+                        );
+                        ui.text(format!("Action: {:?}", monster.action.action));
+                    }
+                    if dead.contains(entity) {
+                        ui.text_colored([1.0, 0.3, 0.3, 1.0], "Dead");
+                    }
+                });
+        });
+    }
+}
+
+fn edit_f32(ui: &imgui::Ui, label: &imgui::ImStr, value: &mut f32, is_editable: bool) {
+    if is_editable {
+        ui.input_float(label, value).build();
+    } else {
+        ui.text(format!("{}: {:.2}", label.to_str(), value));
+    }
+}
@@ -10,28 +10,60 @@ use amethyst::{
     winit::MouseButton,
 };
 
+use gv_client_shared::settings::Settings;
 use gv_core::{
-    actions::player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
-    ecs::components::{ClientPlayerActions, WorldPosition},
+    actions::player::{
+        clamp_to_cast_range, CastSpell, PlayerCastAction, PlayerInteractAction, PlayerLookAction,
+        PlayerWalkAction,
+    },
+    aim_assist::{aim_assist_target, AimAssist, AimAssistStrength},
+    augments::Augment,
+    economy::ShopItem,
+    ecs::{
+        components::{Allegiance, ClientPlayerActions, Dead, Monster, WorldPosition},
+        system_data::time::GameTimeService,
+    },
     math::Vector2,
+    room_settings::RoomSettings,
+};
+use gv_game::{
+    ecs::{
+        resources::{AugmentChoices, ShopPurchases, SummonRequests, WaveReadiness},
+        system_data::GameStateHelper,
+    },
+    utils::entities::is_dead,
 };
-use gv_game::ecs::system_data::GameStateHelper;
 
 use std::collections::HashSet;
 
-use crate::ecs::resources::DisplayDebugInfoSettings;
+use crate::ecs::resources::{DisplayDebugInfoSettings, WindowFocus};
+
+/// Aiming with a stick targets a point this far from the player.
+const STICK_AIM_DISTANCE: f32 = 300.0;
 
 #[derive(SystemData)]
 pub struct InputSystemData<'s> {
     input: ReadExpect<'s, InputHandler<StringBindings>>,
     screen_dimensions: ReadExpect<'s, ScreenDimensions>,
+    game_time_service: GameTimeService<'s>,
+    settings: ReadExpect<'s, Settings>,
+    room_settings: ReadExpect<'s, RoomSettings>,
     transforms: ReadStorage<'s, Transform>,
+    monsters: ReadStorage<'s, Monster>,
+    allegiances: ReadStorage<'s, Allegiance>,
+    dead: ReadStorage<'s, Dead>,
+    window_focus: ReadExpect<'s, WindowFocus>,
     display_debug_info_settings: WriteExpect<'s, DisplayDebugInfoSettings>,
+    augment_choices: WriteExpect<'s, AugmentChoices>,
+    summon_requests: WriteExpect<'s, SummonRequests>,
+    shop_purchases: WriteExpect<'s, ShopPurchases>,
+    wave_readiness: WriteExpect<'s, WaveReadiness>,
 }
 
 #[derive(Default)]
 pub struct InputSystem {
     down_actions: HashSet<String>,
+    aim_assist: AimAssist,
 }
 
 impl<'s> System<'s> for InputSystem {
@@ -69,13 +101,27 @@ impl<'s> System<'s> for InputSystem {
         let client_player_actions = client_player_actions
             .get_mut(player_entity)
             .expect("Expected a ClientPlayerActions component");
+        let display_debug_info_settings = &input_system_data.display_debug_info_settings;
+        if display_debug_info_settings.display_console
+            || display_debug_info_settings.free_camera
+            || !input_system_data.window_focus.is_focused
+        {
+            // Typing into the console, flying the free camera or having the window
+            // in the background mustn't move the player or cast spells.
+            client_player_actions.walk_action = PlayerWalkAction::Stop;
+            client_player_actions.cast_action = None;
+            client_player_actions.interact_action = None;
+            return;
+        }
         let player_position = world_positions
             .get(player_entity)
             .expect("Expected a WorldPosition");
-        self.process_mouse_input(
+        self.process_aim_input(
             &mut input_system_data,
+            &entities,
             camera_entity,
             &cameras,
+            &world_positions,
             &mut *client_player_actions,
             **player_position,
         );
@@ -84,15 +130,36 @@ impl<'s> System<'s> for InputSystem {
 }
 
 impl InputSystem {
-    fn process_mouse_input(
+    /// Players aim with the mouse, or with the right stick of a gamepad when it's tilted.
+    fn process_aim_input(
         &mut self,
         system_data: &mut InputSystemData,
+        entities: &Entities<'_>,
         camera_entity: Entity,
         cameras: &ReadStorage<'_, Camera>,
+        world_positions: &ReadStorage<'_, WorldPosition>,
         client_player_actions: &mut ClientPlayerActions,
         player_position: Vector2,
     ) {
-        let mouse_world_position = {
+        let stick_direction = match (
+            system_data.input.axis_value("aim_horizontal"),
+            system_data.input.axis_value("aim_vertical"),
+        ) {
+            (Some(x), Some(y)) if x != 0.0 || y != 0.0 => Some(Vector2::new(x, y)),
+            _ => None,
+        };
+        let aim_position = if let Some(stick_direction) = stick_direction {
+            let aim_direction = stick_direction.normalize() * STICK_AIM_DISTANCE;
+            player_position
+                + self.assist_aim(
+                    system_data,
+                    entities,
+                    world_positions,
+                    player_position,
+                    aim_direction,
+                )
+        } else {
+            self.aim_assist.reset();
             let mouse_position = system_data.input.mouse_position();
             if mouse_position.is_none() {
                 return;
@@ -114,19 +181,81 @@ impl InputSystem {
         };
 
         client_player_actions.look_action = PlayerLookAction {
-            direction: mouse_world_position - player_position,
+            direction: aim_position - player_position,
+            aim_point: clamp_to_cast_range(player_position, aim_position),
         };
 
-        if system_data.input.mouse_button_is_down(MouseButton::Left) {
+        let spell = if system_data.input.mouse_button_is_down(MouseButton::Left) {
+            Some(CastSpell::Missile)
+        } else if system_data
+            .input
+            .action_is_down("cast_heal")
+            .unwrap_or_default()
+        {
+            Some(CastSpell::Heal)
+        } else if system_data
+            .input
+            .action_is_down("cast_zone")
+            .unwrap_or_default()
+        {
+            Some(CastSpell::Zone)
+        } else if system_data
+            .input
+            .action_is_down("cast_taunt")
+            .unwrap_or_default()
+        {
+            Some(CastSpell::Taunt)
+        } else {
+            None
+        };
+        if let Some(spell) = spell {
             client_player_actions.cast_action = Some(PlayerCastAction {
                 cast_position: player_position,
-                target_position: mouse_world_position,
+                target_position: clamp_to_cast_range(player_position, aim_position),
+                spell,
             });
         } else {
             client_player_actions.cast_action = None;
         }
     }
 
+    /// Bends the stick aim direction towards the closest monster in front of the player,
+    /// unless aim assist is turned off or the room disallows it.
+    fn assist_aim(
+        &mut self,
+        system_data: &InputSystemData,
+        entities: &Entities<'_>,
+        world_positions: &ReadStorage<'_, WorldPosition>,
+        player_position: Vector2,
+        aim_direction: Vector2,
+    ) -> Vector2 {
+        let strength = system_data.settings.accessibility().aim_assist;
+        if !system_data.room_settings.aim_assist || strength == AimAssistStrength::Off {
+            self.aim_assist.reset();
+            return aim_direction;
+        }
+
+        let frame_number = system_data.game_time_service.game_frame_number();
+        let target = aim_assist_target(
+            aim_direction,
+            (
+                entities,
+                &system_data.monsters,
+                world_positions,
+                !&system_data.allegiances,
+            )
+                .join()
+                .filter(|(monster, _, _, _)| !is_dead(*monster, &system_data.dead, frame_number))
+                .map(|(_, _, world_position, _)| **world_position - player_position),
+        );
+        self.aim_assist.apply(
+            aim_direction,
+            target,
+            strength,
+            system_data.game_time_service.engine_time().delta_seconds(),
+        )
+    }
+
     fn process_keyboard_input(
         &mut self,
         system_data: &mut InputSystemData,
@@ -157,6 +286,43 @@ impl InputSystem {
             *display_network_debug_info = !*display_network_debug_info;
         });
 
+        let display_frame_timings = &mut system_data
+            .display_debug_info_settings
+            .display_frame_timings;
+        self.process_toggle_action(&system_data.input, "toggle_frame_timings", || {
+            *display_frame_timings = !*display_frame_timings;
+        });
+
+        for (i, augment) in Augment::ALL.iter().enumerate() {
+            let local_choice = &mut system_data.augment_choices.local;
+            self.process_toggle_action(&system_data.input, &format!("augment_{}", i + 1), || {
+                *local_choice = Some(*augment);
+            });
+        }
+
+        for (i, item) in ShopItem::ALL.iter().enumerate() {
+            let local_purchase = &mut system_data.shop_purchases.local;
+            self.process_toggle_action(&system_data.input, &format!("buy_{}", i + 1), || {
+                *local_purchase = Some(*item);
+            });
+        }
+
+        let is_ready_for_wave = &mut system_data.wave_readiness.local;
+        self.process_toggle_action(&system_data.input, "ready_for_wave", || {
+            *is_ready_for_wave = true;
+        });
+
+        let summon_request = &mut system_data.summon_requests.local;
+        self.process_toggle_action(&system_data.input, "summon", || {
+            *summon_request = true;
+        });
+
+        let interact_action = &mut client_player_actions.interact_action;
+        *interact_action = None;
+        self.process_toggle_action(&system_data.input, "interact", || {
+            *interact_action = Some(PlayerInteractAction);
+        });
+
         #[cfg(feature = "profiler")]
         self.process_toggle_action(&system_data.input, "toggle_profiler", || {
             log::info!("Toggling profiler");
@@ -0,0 +1,39 @@
+use amethyst::ecs::{ReadExpect, System};
+
+use gv_client_shared::ecs::system_data::LevelAssetsLoader;
+use gv_core::{
+    ecs::resources::{net::MultiplayerGameState, GameEngineState},
+    level_loading::LevelKey,
+    room_settings::RoomSettings,
+};
+
+/// Starts loading the assets of the next level while the players are still in the menus
+/// or between campaign levels, so `LevelLoadingState` rarely has anything to wait for.
+/// Changing the map in a multiplayer room loads the new one.
+pub struct LevelPrefetchSystem;
+
+impl<'s> System<'s> for LevelPrefetchSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, RoomSettings>,
+        ReadExpect<'s, MultiplayerGameState>,
+        LevelAssetsLoader<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_engine_state,
+            room_settings,
+            multiplayer_game_state,
+            mut level_assets_loader,
+        ): Self::SystemData,
+    ) {
+        if matches!(
+            *game_engine_state,
+            GameEngineState::Menu | GameEngineState::LevelTransition
+        ) {
+            level_assets_loader.load(LevelKey::new(&room_settings, &multiplayer_game_state));
+        }
+    }
+}
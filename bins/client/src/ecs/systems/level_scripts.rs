@@ -0,0 +1,239 @@
+use amethyst::{
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Entity, Read, ReadExpect, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+};
+
+use gv_client_shared::{accessibility::MarkerShape, ecs::resources::AssetHandles};
+use gv_core::{ecs::resources::GameEngineState, level_scripts::SwitchKind};
+use gv_game::ecs::resources::ActiveLevelScript;
+
+const MARKER_Z: f32 = -0.5;
+/// The size of the marker sprites, see `resources/assets/player_markers.ron`.
+const MARKER_SPRITE_WIDTH: f32 = 40.0;
+const MARKER_SPRITE_HEIGHT: f32 = 20.0;
+const ACTIVATED_SWITCH_TINT: (f32, f32, f32) = (0.3, 1.0, 0.3);
+/// Safe zones and the payload escort radius reuse the healing aura ring,
+/// see `resources/assets/auras.ron`.
+const RING_SPRITE: usize = 2;
+const RING_SPRITE_RADIUS: f32 = 32.0;
+const SAFE_ZONE_RING_ALPHA: f32 = 0.7;
+const PAYLOAD_SIZE: f32 = 40.0;
+const PAYLOAD_TINT: (f32, f32, f32) = (0.95, 0.75, 0.2);
+const ESCORT_RING_ALPHA: f32 = 0.4;
+/// Share of the gap to the received payload progress that the marker covers every frame,
+/// multiplayer clients get the progress only a few times per second.
+const PAYLOAD_SMOOTHING: f32 = 0.2;
+
+/// Marks the switches and the closed doors of a level script: pressure plates with circles,
+/// levers with diamonds and doors with squares stretched over their cells.
+/// Activated switches are painted green, opened doors disappear. Safe zones are outlined
+/// with rings tinted in their colors. The payload is a golden square with a ring showing
+/// how close players have to stay to move it.
+#[derive(Default)]
+pub struct LevelScriptMarkerSystem {
+    are_markers_built: bool,
+    switch_markers: Vec<(String, Entity)>,
+    door_markers: Vec<(String, Entity)>,
+    safe_zone_rings: Vec<Entity>,
+    payload_markers: Vec<Entity>,
+    payload_distance: f32,
+}
+
+impl<'s> System<'s> for LevelScriptMarkerSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_engine_state,
+            active_level_script,
+            asset_handles,
+            entities,
+            mut sprite_renders,
+            mut transforms,
+            mut tints,
+        ): Self::SystemData,
+    ) {
+        let (sprite_sheet, rings_sprite_sheet) = match asset_handles.as_ref() {
+            Some(asset_handles) => (
+                asset_handles.player_markers.clone(),
+                asset_handles.auras.clone(),
+            ),
+            None => return,
+        };
+        let runner = match active_level_script.runner.as_ref() {
+            Some(runner) if *game_engine_state == GameEngineState::Playing => runner,
+            _ => {
+                self.are_markers_built = false;
+                for marker in self
+                    .switch_markers
+                    .drain(..)
+                    .chain(self.door_markers.drain(..))
+                    .map(|(_, marker)| marker)
+                    .chain(self.safe_zone_rings.drain(..))
+                    .chain(self.payload_markers.drain(..))
+                {
+                    // Markers could have been deleted along with the rest of a level.
+                    if entities.is_alive(marker) {
+                        entities
+                            .delete(marker)
+                            .expect("Expected to delete a marker");
+                    }
+                }
+                return;
+            }
+        };
+
+        if !self.are_markers_built {
+            self.are_markers_built = true;
+            let mut build_marker = |shape: MarkerShape, center: Vector3<f32>, scale| {
+                let mut transform = Transform::default();
+                transform.set_translation(center);
+                transform.set_scale(scale);
+                entities
+                    .build_entity()
+                    .with(
+                        SpriteRender {
+                            sprite_sheet: sprite_sheet.clone(),
+                            sprite_number: shape.sprite_number(),
+                        },
+                        &mut sprite_renders,
+                    )
+                    .with(transform, &mut transforms)
+                    .build()
+            };
+            let script = runner.script();
+            for switch in &script.switches {
+                let shape = match switch.kind {
+                    SwitchKind::PressurePlate => MarkerShape::Circle,
+                    SwitchKind::Lever => MarkerShape::Diamond,
+                };
+                let diameter = switch.radius * 2.0;
+                let marker = build_marker(
+                    shape,
+                    Vector3::new(switch.position.0, switch.position.1, MARKER_Z),
+                    Vector3::new(
+                        diameter / MARKER_SPRITE_WIDTH,
+                        diameter / MARKER_SPRITE_HEIGHT,
+                        1.0,
+                    ),
+                );
+                self.switch_markers.push((switch.name.clone(), marker));
+            }
+            for door in script
+                .doors
+                .iter()
+                .filter(|door| !active_level_script.opened_doors.contains(&door.name))
+            {
+                let marker = build_marker(
+                    MarkerShape::Square,
+                    Vector3::new(
+                        (door.from.0 + door.to.0) / 2.0,
+                        (door.from.1 + door.to.1) / 2.0,
+                        MARKER_Z,
+                    ),
+                    Vector3::new(
+                        (door.to.0 - door.from.0).abs() / MARKER_SPRITE_WIDTH,
+                        (door.to.1 - door.from.1).abs() / MARKER_SPRITE_HEIGHT,
+                        1.0,
+                    ),
+                );
+                self.door_markers.push((door.name.clone(), marker));
+            }
+            for safe_zone in &script.safe_zones {
+                let scale = safe_zone.radius / RING_SPRITE_RADIUS;
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(safe_zone.center.0, safe_zone.center.1, MARKER_Z);
+                transform.set_scale(Vector3::new(scale, scale, 1.0));
+                let (r, g, b) = safe_zone.color;
+                let ring = entities
+                    .build_entity()
+                    .with(
+                        SpriteRender {
+                            sprite_sheet: rings_sprite_sheet.clone(),
+                            sprite_number: RING_SPRITE,
+                        },
+                        &mut sprite_renders,
+                    )
+                    .with(transform, &mut transforms)
+                    .with(Tint(Srgba::new(r, g, b, SAFE_ZONE_RING_ALPHA)), &mut tints)
+                    .build();
+                self.safe_zone_rings.push(ring);
+            }
+            if let Some(payload) = runner.payload() {
+                self.payload_distance = payload.progress().distance;
+                let (r, g, b) = PAYLOAD_TINT;
+                let marker = build_marker(
+                    MarkerShape::Square,
+                    Vector3::new(0.0, 0.0, MARKER_Z),
+                    Vector3::new(
+                        PAYLOAD_SIZE / MARKER_SPRITE_WIDTH,
+                        PAYLOAD_SIZE / MARKER_SPRITE_HEIGHT,
+                        1.0,
+                    ),
+                );
+                tints
+                    .insert(marker, Tint(Srgba::new(r, g, b, 1.0)))
+                    .expect("Expected to insert a Tint");
+                let scale = payload.payload().escort_radius / RING_SPRITE_RADIUS;
+                let mut transform = Transform::default();
+                transform.set_scale(Vector3::new(scale, scale, 1.0));
+                let ring = entities
+                    .build_entity()
+                    .with(
+                        SpriteRender {
+                            sprite_sheet: rings_sprite_sheet.clone(),
+                            sprite_number: RING_SPRITE,
+                        },
+                        &mut sprite_renders,
+                    )
+                    .with(transform, &mut transforms)
+                    .with(Tint(Srgba::new(r, g, b, ESCORT_RING_ALPHA)), &mut tints)
+                    .build();
+                self.payload_markers = vec![marker, ring];
+            }
+        }
+
+        if let Some(payload) = runner.payload() {
+            self.payload_distance +=
+                (payload.progress().distance - self.payload_distance) * PAYLOAD_SMOOTHING;
+            let position = payload.position_at(self.payload_distance);
+            for marker in &self.payload_markers {
+                if let Some(transform) = transforms.get_mut(*marker) {
+                    transform.set_translation_xyz(position.x, position.y, MARKER_Z);
+                }
+            }
+        }
+
+        for (name, marker) in &self.switch_markers {
+            if active_level_script.activated_switches.contains(name) {
+                let (r, g, b) = ACTIVATED_SWITCH_TINT;
+                tints
+                    .insert(*marker, Tint(Srgba::new(r, g, b, 1.0)))
+                    .expect("Expected to insert a Tint");
+            } else {
+                tints.remove(*marker);
+            }
+        }
+        let opened_doors = &active_level_script.opened_doors;
+        self.door_markers.retain(|(name, marker)| {
+            if opened_doors.contains(name) {
+                entities
+                    .delete(*marker)
+                    .expect("Expected to delete a marker");
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
@@ -0,0 +1,140 @@
+use amethyst::{
+    core::{math::Point2, Transform},
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, WriteExpect},
+    renderer::{ActiveCamera, Camera},
+    window::ScreenDimensions,
+};
+
+use gv_client_shared::{
+    lighting::{retain_visible_lights, PointLight},
+    settings::Settings,
+};
+use gv_core::{
+    ecs::{
+        components::{missile::Missile, Dead, Player, WorldPosition},
+        resources::GameEngineState,
+        system_data::time::GameTimeService,
+    },
+    level_scripts::{LevelLighting, DAYLIGHT},
+    math::Vector2,
+};
+use gv_game::ecs::resources::ActiveLevelScript;
+
+use crate::{ecs::resources::SceneLighting, utils::camera};
+
+const PLAYER_LIGHT_RADIUS: f32 = 240.0;
+const PLAYER_LIGHT_INTENSITY: f32 = 0.9;
+const MISSILE_LIGHT_RADIUS: f32 = 110.0;
+const MISSILE_LIGHT_INTENSITY: f32 = 0.6;
+
+/// Fills `SceneLighting` with the ambient light level of the current level and the lights
+/// of living players, flying missiles and torches that reach into the screen.
+/// Levels without darkness and the `Off` quality tier leave it in daylight.
+#[derive(Default)]
+pub struct LightingSystem;
+
+impl<'s> System<'s> for LightingSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, ActiveCamera>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Missile>,
+        ReadStorage<'s, Dead>,
+        WriteExpect<'s, SceneLighting>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            active_level_script,
+            settings,
+            screen_dimensions,
+            active_camera,
+            cameras,
+            transforms,
+            world_positions,
+            players,
+            missiles,
+            dead,
+            mut scene_lighting,
+        ): Self::SystemData,
+    ) {
+        scene_lighting.ambient = DAYLIGHT;
+        scene_lighting.lights.clear();
+
+        let quality = settings.video().lighting_quality;
+        let script = match active_level_script.runner.as_ref() {
+            Some(runner) if *game_engine_state == GameEngineState::Playing => runner.script(),
+            _ => return,
+        };
+        if quality.max_lights() == 0 || script.lighting == LevelLighting::Daylight {
+            return;
+        }
+
+        let camera = active_camera
+            .entity
+            .and_then(|entity| Some((cameras.get(entity)?, transforms.get(entity)?)))
+            .or_else(|| (&cameras, &transforms).join().next());
+        let (camera, camera_transform) = match camera {
+            Some(camera) => camera,
+            None => return,
+        };
+        let screen_left_bottom = camera::screen_to_world_from_global_matrix(
+            camera,
+            Point2::new(0.0, screen_dimensions.height()),
+            camera_transform.global_matrix(),
+            &screen_dimensions,
+        );
+        let screen_right_top = camera::screen_to_world_from_global_matrix(
+            camera,
+            Point2::new(screen_dimensions.width(), 0.0),
+            camera_transform.global_matrix(),
+            &screen_dimensions,
+        );
+
+        scene_lighting.ambient = script
+            .lighting
+            .ambient_light(game_time_service.level_duration().as_secs_f32());
+        let frame_number = game_time_service.game_frame_number();
+        let lights = &mut scene_lighting.lights;
+        lights.extend(script.torches.iter().map(PointLight::from_torch));
+        for (world_position, _, dead) in (&world_positions, &players, dead.maybe()).join() {
+            if dead.map_or(false, |dead| dead.is_dead(frame_number)) {
+                continue;
+            }
+            lights.push(PointLight {
+                position: world_position.position,
+                radius: PLAYER_LIGHT_RADIUS,
+                intensity: PLAYER_LIGHT_INTENSITY,
+            });
+        }
+        for (world_position, _, _) in (&world_positions, &missiles, !&dead).join() {
+            lights.push(PointLight {
+                position: world_position.position,
+                radius: MISSILE_LIGHT_RADIUS,
+                intensity: MISSILE_LIGHT_INTENSITY,
+            });
+        }
+        retain_visible_lights(
+            lights,
+            Vector2::new(
+                screen_left_bottom.x.min(screen_right_top.x),
+                screen_left_bottom.y.min(screen_right_top.y),
+            ),
+            Vector2::new(
+                screen_left_bottom.x.max(screen_right_top.x),
+                screen_left_bottom.y.max(screen_right_top.y),
+            ),
+            quality.max_lights(),
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use gv_core::achievements::{Achievement, MONSTER_SLAYER_KILLS};
+
+use super::*;
+
+pub struct AchievementsMenuScreen;
+
+impl MenuScreen for AchievementsMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![UI_ACHIEVEMENTS_LABEL, UI_MAIN_MENU_BUTTON]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let progress = &system_data.settings.profile().achievements;
+        let text = Achievement::ALL
+            .iter()
+            .map(|achievement| {
+                let status = if progress.is_unlocked(*achievement) {
+                    "unlocked".to_owned()
+                } else if *achievement == Achievement::MonsterSlayer {
+                    format!("{}/{}", progress.kills, MONSTER_SLAYER_KILLS)
+                } else {
+                    "locked".to_owned()
+                };
+                format!(
+                    "{}: {} ({})",
+                    achievement.name(),
+                    achievement.description(),
+                    status
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_ACHIEVEMENTS_LABEL)
+            .expect("Expected an achievements label") = text;
+    }
+
+    fn update(
+        &mut self,
+        _system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        match button_pressed {
+            Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
+            _ => StateUpdate::None,
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use gv_client_shared::{
+    accessibility::next_scale,
+    frame_pacing::{FPS_CAPS, UNFOCUSED_FPS_CAPS},
+};
+
+use super::*;
+
+pub struct CustomizationMenuScreen;
+
+impl MenuScreen for CustomizationMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![
+            UI_CUSTOMIZATION_COLOR_BUTTON,
+            UI_CUSTOMIZATION_HAT_BUTTON,
+            UI_CUSTOMIZATION_PALETTE_BUTTON,
+            UI_CUSTOMIZATION_MARKERS_BUTTON,
+            UI_CUSTOMIZATION_UI_SCALE_BUTTON,
+            UI_CUSTOMIZATION_HUD_SCALE_BUTTON,
+            UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON,
+            UI_CUSTOMIZATION_GORE_BUTTON,
+            UI_CUSTOMIZATION_RENDER_SCALE_BUTTON,
+            UI_CUSTOMIZATION_LIGHTING_BUTTON,
+            UI_CUSTOMIZATION_BLOOM_BUTTON,
+            UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON,
+            UI_CUSTOMIZATION_COLOR_GRADING_BUTTON,
+            UI_CUSTOMIZATION_THEME_BUTTON,
+            UI_CUSTOMIZATION_FPS_CAP_BUTTON,
+            UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON,
+            UI_CUSTOMIZATION_ANNOUNCER_BUTTON,
+            UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON,
+            UI_CUSTOMIZATION_LAG_PAUSE_BUTTON,
+            UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON,
+            UI_CUSTOMIZATION_AIM_ASSIST_BUTTON,
+            UI_MAIN_MENU_BUTTON,
+        ]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        set_cosmetics_buttons_text(system_data);
+        set_accessibility_buttons_text(system_data);
+        set_video_buttons_text(system_data);
+        set_network_buttons_text(system_data);
+        set_audio_buttons_text(system_data);
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        let mut cosmetics = system_data.settings.profile().cosmetics.clone();
+        let mut accessibility = system_data.settings.accessibility().clone();
+        let mut video = system_data.settings.video().clone();
+        let mut network = system_data.settings.network().clone();
+        let mut audio = system_data.settings.audio().clone();
+        let button = match button_pressed {
+            Some(UI_CUSTOMIZATION_COLOR_BUTTON) => {
+                cosmetics.next_color();
+                UI_CUSTOMIZATION_COLOR_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_HAT_BUTTON) => {
+                cosmetics.next_hat();
+                UI_CUSTOMIZATION_HAT_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_PALETTE_BUTTON) => {
+                accessibility.palette = accessibility.palette.next();
+                UI_CUSTOMIZATION_PALETTE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_MARKERS_BUTTON) => {
+                accessibility.shape_markers = !accessibility.shape_markers;
+                UI_CUSTOMIZATION_MARKERS_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_UI_SCALE_BUTTON) => {
+                accessibility.ui_scale = next_scale(accessibility.ui_scale);
+                UI_CUSTOMIZATION_UI_SCALE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_HUD_SCALE_BUTTON) => {
+                accessibility.hud_scale = next_scale(accessibility.hud_scale);
+                UI_CUSTOMIZATION_HUD_SCALE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_AIM_ASSIST_BUTTON) => {
+                accessibility.aim_assist = accessibility.aim_assist.next();
+                UI_CUSTOMIZATION_AIM_ASSIST_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON) => {
+                video.menu_background = !video.menu_background;
+                UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_GORE_BUTTON) => {
+                video.gore = !video.gore;
+                UI_CUSTOMIZATION_GORE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_RENDER_SCALE_BUTTON) => {
+                video.render_scale = video.render_scale.next();
+                UI_CUSTOMIZATION_RENDER_SCALE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_LIGHTING_BUTTON) => {
+                video.lighting_quality = video.lighting_quality.next();
+                UI_CUSTOMIZATION_LIGHTING_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_BLOOM_BUTTON) => {
+                video.post_processing.bloom = !video.post_processing.bloom;
+                UI_CUSTOMIZATION_BLOOM_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON) => {
+                video.post_processing.chromatic_aberration =
+                    !video.post_processing.chromatic_aberration;
+                UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_COLOR_GRADING_BUTTON) => {
+                video.post_processing.color_grading = !video.post_processing.color_grading;
+                UI_CUSTOMIZATION_COLOR_GRADING_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_THEME_BUTTON) => {
+                if let Some(theme) = system_data.ui_themes.next_theme(&video.ui_theme) {
+                    video.ui_theme = theme.name.clone();
+                }
+                UI_CUSTOMIZATION_THEME_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_FPS_CAP_BUTTON) => {
+                video.fps_cap = video.fps_cap.next(&FPS_CAPS);
+                UI_CUSTOMIZATION_FPS_CAP_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON) => {
+                video.unfocused_fps_cap = video.unfocused_fps_cap.next(&UNFOCUSED_FPS_CAPS);
+                UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_ANNOUNCER_BUTTON) => {
+                audio.announcer = !audio.announcer;
+                UI_CUSTOMIZATION_ANNOUNCER_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON) => {
+                audio.mute_unfocused = !audio.mute_unfocused;
+                UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_LAG_PAUSE_BUTTON) => {
+                network.lag_pause = network.lag_pause.next();
+                UI_CUSTOMIZATION_LAG_PAUSE_BUTTON
+            }
+            Some(UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON) => {
+                network.connection_warnings = !network.connection_warnings;
+                UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON
+            }
+            Some(UI_MAIN_MENU_BUTTON) => {
+                return StateUpdate::new_menu_screen(GameMenuScreen::MainMenu);
+            }
+            _ => return StateUpdate::None,
+        };
+
+        if button == UI_CUSTOMIZATION_COLOR_BUTTON || button == UI_CUSTOMIZATION_HAT_BUTTON {
+            if let Err(err) = system_data.settings.save_cosmetics(cosmetics) {
+                log::error!("Failed to save the cosmetics: {:?}", err);
+            }
+            set_cosmetics_buttons_text(system_data);
+        } else if button == UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON
+            || button == UI_CUSTOMIZATION_GORE_BUTTON
+            || button == UI_CUSTOMIZATION_RENDER_SCALE_BUTTON
+            || button == UI_CUSTOMIZATION_LIGHTING_BUTTON
+            || button == UI_CUSTOMIZATION_BLOOM_BUTTON
+            || button == UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON
+            || button == UI_CUSTOMIZATION_COLOR_GRADING_BUTTON
+            || button == UI_CUSTOMIZATION_THEME_BUTTON
+            || button == UI_CUSTOMIZATION_FPS_CAP_BUTTON
+            || button == UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON
+        {
+            if let Err(err) = system_data.settings.save_video(video) {
+                log::error!("Failed to save the video settings: {:?}", err);
+            }
+            set_video_buttons_text(system_data);
+        } else if button == UI_CUSTOMIZATION_LAG_PAUSE_BUTTON
+            || button == UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON
+        {
+            if let Err(err) = system_data.settings.save_network(network) {
+                log::error!("Failed to save the network settings: {:?}", err);
+            }
+            set_network_buttons_text(system_data);
+        } else if button == UI_CUSTOMIZATION_ANNOUNCER_BUTTON
+            || button == UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON
+        {
+            if let Err(err) = system_data.settings.save_audio(audio) {
+                log::error!("Failed to save the audio settings: {:?}", err);
+            }
+            set_audio_buttons_text(system_data);
+        } else {
+            if let Err(err) = system_data.settings.save_accessibility(accessibility) {
+                log::error!("Failed to save the accessibility settings: {:?}", err);
+            }
+            set_accessibility_buttons_text(system_data);
+        }
+        // Fading the button out and in makes it interactable again.
+        StateUpdate::CustomAnimation {
+            elements_to_hide: vec![button],
+            elements_to_show: vec![button],
+        }
+    }
+}
+
+fn set_cosmetics_buttons_text(system_data: &mut MenuSystemData) {
+    let cosmetics = system_data.settings.profile().cosmetics.clone();
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_COLOR_BUTTON)
+        .expect("Expected a color button text") = format!("Color: {}", cosmetics.color_name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_HAT_BUTTON)
+        .expect("Expected a hat button text") = format!("Hat: {}", cosmetics.hat_name());
+}
+
+fn set_accessibility_buttons_text(system_data: &mut MenuSystemData) {
+    let accessibility = system_data.settings.accessibility().clone();
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_PALETTE_BUTTON)
+        .expect("Expected a palette button text") =
+        format!("Palette: {}", accessibility.palette.name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_MARKERS_BUTTON)
+        .expect("Expected a markers button text") = format!(
+        "Shape markers: {}",
+        if accessibility.shape_markers {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_UI_SCALE_BUTTON)
+        .expect("Expected a UI scale button text") =
+        format!("UI scale: {:.0}%", accessibility.ui_scale * 100.0);
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_HUD_SCALE_BUTTON)
+        .expect("Expected a HUD scale button text") =
+        format!("HUD scale: {:.0}%", accessibility.hud_scale * 100.0);
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_AIM_ASSIST_BUTTON,
+        )
+        .expect("Expected an aim assist button text") =
+        format!("Aim assist: {}", accessibility.aim_assist.name());
+}
+
+fn set_video_buttons_text(system_data: &mut MenuSystemData) {
+    let video = system_data.settings.video().clone();
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON,
+        )
+        .expect("Expected a menu background button text") = format!(
+        "Menu background: {}",
+        if video.menu_background { "On" } else { "Off" }
+    );
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_GORE_BUTTON)
+        .expect("Expected a gore button text") =
+        format!("Gore: {}", if video.gore { "On" } else { "Off" });
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_RENDER_SCALE_BUTTON,
+        )
+        .expect("Expected a render scale button text") =
+        format!("Render scale: {}", video.render_scale.name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_LIGHTING_BUTTON)
+        .expect("Expected a lighting button text") =
+        format!("Lighting: {}", video.lighting_quality.name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_BLOOM_BUTTON)
+        .expect("Expected a bloom button text") = format!(
+        "Bloom: {}",
+        if video.post_processing.bloom {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON,
+        )
+        .expect("Expected a chromatic aberration button text") = format!(
+        "Chromatic aberration: {}",
+        if video.post_processing.chromatic_aberration {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_COLOR_GRADING_BUTTON,
+        )
+        .expect("Expected a color grading button text") = format!(
+        "Color grading: {}",
+        if video.post_processing.color_grading {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    let theme_name = system_data
+        .ui_themes
+        .theme(&video.ui_theme)
+        .map_or_else(|| video.ui_theme.clone(), |theme| theme.name.clone());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_THEME_BUTTON)
+        .expect("Expected a theme button text") = format!("Theme: {}", theme_name);
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_FPS_CAP_BUTTON)
+        .expect("Expected an FPS cap button text") = format!("FPS cap: {}", video.fps_cap.name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON,
+        )
+        .expect("Expected an unfocused FPS cap button text") =
+        format!("Unfocused FPS cap: {}", video.unfocused_fps_cap.name());
+}
+
+fn set_network_buttons_text(system_data: &mut MenuSystemData) {
+    let network = system_data.settings.network().clone();
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_LAG_PAUSE_BUTTON)
+        .expect("Expected a lag pause button text") =
+        format!("Pause on lag: {}", network.lag_pause.name());
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON,
+        )
+        .expect("Expected a connection warnings button text") = format!(
+        "Connection warnings: {}",
+        if network.connection_warnings {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+}
+
+fn set_audio_buttons_text(system_data: &mut MenuSystemData) {
+    let audio = system_data.settings.audio().clone();
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_CUSTOMIZATION_ANNOUNCER_BUTTON)
+        .expect("Expected an announcer button text") =
+        format!("Announcer: {}", if audio.announcer { "On" } else { "Off" });
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(
+            &mut system_data.ui_texts,
+            UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON,
+        )
+        .expect("Expected a mute when unfocused button text") = format!(
+        "Mute when unfocused: {}",
+        if audio.mute_unfocused { "On" } else { "Off" }
+    );
+}
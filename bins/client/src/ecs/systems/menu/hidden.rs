@@ -25,6 +25,21 @@ impl MenuScreen for HiddenMenuScreen {
             };
         }
 
+        // The players have voted to end the game.
+        if system_data.multiplayer_room_state.is_active
+            && system_data
+                .multiplayer_room_state
+                .connection_status
+                .is_connected()
+            && !system_data.multiplayer_game_state.is_playing
+        {
+            system_data.game_level_state.is_over = true;
+            return StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(GameMenuScreen::MultiplayerRoomMenu),
+            };
+        }
+
         let disconnected_modal_window_is_shown =
             modal_window_id.map_or(false, |modal_window_id| modal_window_id == DISCONNECTED);
         if !disconnected_modal_window_is_shown {
@@ -39,7 +54,7 @@ impl MenuScreen for HiddenMenuScreen {
                         show_confirmation: true,
                     }
                 }
-                ConnectionStatus::Disconnected(disconnect_reason) => {
+                ConnectionStatus::Disconnected(ref disconnect_reason) => {
                     return StateUpdate::ShowModalWindow {
                         id: DISCONNECTED.to_owned(),
                         title: disconnect_reason_title(disconnect_reason),
@@ -0,0 +1,98 @@
+use gv_core::campaign::CAMPAIGN_LEVELS;
+
+use super::*;
+use crate::ecs::resources::UiNetworkCommand;
+
+const LEVEL_LOADING_FAILED: &str = "LEVEL_LOADING_FAILED";
+
+pub struct LevelLoadingMenuScreen;
+
+impl MenuScreen for LevelLoadingMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![
+            UI_LEVEL_LOADING_LABEL,
+            UI_LEVEL_LOADING_BAR_BORDER_CONTAINER,
+            UI_LEVEL_LOADING_BAR_BG,
+            UI_LEVEL_LOADING_BAR_FILL,
+        ]
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        // The game won't start without the server, so we just return to the lobby.
+        if system_data.multiplayer_room_state.is_active
+            && system_data
+                .multiplayer_room_state
+                .connection_status
+                .is_not_connected()
+        {
+            system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
+            system_data.level_loading_error.0 = None;
+            return StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(GameMenuScreen::LobbyMenu),
+            };
+        }
+
+        let (key, progress) = match system_data.level_assets.0.as_ref() {
+            Some(level_asset_handles) => (level_asset_handles.key, level_asset_handles.progress()),
+            None => return StateUpdate::None,
+        };
+        let level_name = key
+            .campaign_level
+            .and_then(|campaign_level| CAMPAIGN_LEVELS.get(campaign_level))
+            .map_or_else(String::new, |campaign_level| {
+                format!(" {}", campaign_level.name)
+            });
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_LEVEL_LOADING_LABEL)
+            .expect("Expected a level loading label") = format!(
+            "Loading{}... {}%",
+            level_name,
+            (progress.fraction() * 100.0).floor()
+        );
+        let bar_width = system_data
+            .ui_finder
+            .find_with_mut_transform(UI_LEVEL_LOADING_BAR_BORDER_CONTAINER)
+            .map_or(0.0, |(_, ui_transform)| ui_transform.width);
+        if let Some((_, ui_transform)) = system_data
+            .ui_finder
+            .find_with_mut_transform(UI_LEVEL_LOADING_BAR_FILL)
+        {
+            ui_transform.width = bar_width * progress.fraction();
+        }
+
+        let error = match system_data.level_loading_error.0.as_ref() {
+            Some(error) => error,
+            None => return StateUpdate::None,
+        };
+        if let (Some(UI_MODAL_CONFIRM_BUTTON), Some(LEVEL_LOADING_FAILED)) =
+            (button_pressed, modal_window_id)
+        {
+            system_data.level_loading_error.0 = None;
+            let menu_screen = if system_data.multiplayer_room_state.is_active {
+                system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
+                GameMenuScreen::LobbyMenu
+            } else {
+                GameMenuScreen::MainMenu
+            };
+            return StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(menu_screen),
+            };
+        }
+        if modal_window_id != Some(LEVEL_LOADING_FAILED) {
+            return StateUpdate::ShowModalWindow {
+                id: LEVEL_LOADING_FAILED.to_owned(),
+                title: format!("Failed to load the level: {}", error),
+                show_confirmation: true,
+            };
+        }
+        StateUpdate::None
+    }
+}
@@ -0,0 +1,52 @@
+use gv_core::campaign::CAMPAIGN_LEVELS;
+
+use super::*;
+use crate::ecs::resources::UiNetworkCommand;
+
+pub struct LevelTransitionMenuScreen;
+
+impl MenuScreen for LevelTransitionMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![UI_LEVEL_TRANSITION_LABEL]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let title = system_data
+            .multiplayer_game_state
+            .campaign_level
+            .map(|level_number| {
+                format!(
+                    "Level {}: {}",
+                    level_number + 1,
+                    CAMPAIGN_LEVELS[level_number].name
+                )
+            })
+            .unwrap_or_default();
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_LEVEL_TRANSITION_LABEL)
+            .expect("Expected a level transition label") = title;
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        _button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        // The next level won't start without the server, so we just return to the lobby.
+        if system_data
+            .multiplayer_room_state
+            .connection_status
+            .is_not_connected()
+        {
+            system_data.game_level_state.is_over = true;
+            system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
+            return StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(GameMenuScreen::LobbyMenu),
+            };
+        }
+        StateUpdate::None
+    }
+}
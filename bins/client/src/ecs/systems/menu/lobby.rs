@@ -1,7 +1,13 @@
 use gv_client_shared::ecs::resources::ConnectionStatus;
+use gv_core::net::rendezvous::HostCode;
+
+use std::net::SocketAddr;
 
 use super::*;
-use crate::{ecs::resources::UiNetworkCommand, utils::ui::disconnect_reason_title};
+use crate::{
+    ecs::resources::{RoomBrowser, RoomRequest, UiNetworkCommand},
+    utils::ui::disconnect_reason_title,
+};
 
 pub struct LobbyMenuScreen;
 
@@ -9,6 +15,7 @@ const INVALID_IP_ADDRESS: &str = "LOBBY_INVALID_IP_ADDRESS";
 const SERVER_START_FAILED: &str = "LOBBY_SERVER_START_FAILED";
 const CONNECTING_PROGRESS: &str = "LOBBY_CONNECTING_PROGRESS";
 const CONNECTING_FAILED: &str = "LOBBY_CONNECTING_FAILED";
+const MATCHMAKING_PROGRESS: &str = "LOBBY_MATCHMAKING_PROGRESS";
 
 impl MenuScreen for LobbyMenuScreen {
     fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
@@ -22,6 +29,8 @@ impl MenuScreen for LobbyMenuScreen {
             UI_LOBBY_JOIN_IP_FIELD,
             UI_LOBBY_JOIN_IP_EDITABLE,
             UI_LOBBY_JOIN_BUTTON,
+            UI_LOBBY_ROOMS_BUTTON,
+            UI_LOBBY_ROOMS_LABEL,
             UI_MAIN_MENU_BUTTON,
         ]
     }
@@ -32,6 +41,25 @@ impl MenuScreen for LobbyMenuScreen {
         button_pressed: Option<&str>,
         modal_window_id: Option<&str>,
     ) -> StateUpdate {
+        if let Some(server_addr) = system_data.multiplayer_room_state.server_addr.take() {
+            if let Some(join_address) = system_data
+                .ui_finder
+                .get_ui_text_mut(&mut system_data.ui_texts, UI_LOBBY_JOIN_IP_EDITABLE)
+            {
+                *join_address = server_addr;
+            }
+        }
+        if system_data.room_browser.is_updated {
+            system_data.room_browser.is_updated = false;
+            let rooms_text = room_list_text(&system_data.room_browser);
+            if let Some(rooms_label) = system_data
+                .ui_finder
+                .get_ui_text_mut(&mut system_data.ui_texts, UI_LOBBY_ROOMS_LABEL)
+            {
+                *rooms_label = rooms_text;
+            }
+        }
+
         match (button_pressed, modal_window_id) {
             (Some(UI_MAIN_MENU_BUTTON), _) => {
                 StateUpdate::new_menu_screen(GameMenuScreen::MainMenu)
@@ -54,33 +82,117 @@ impl MenuScreen for LobbyMenuScreen {
                     .cloned()
                     .unwrap();
 
-                let server_addr = addr.parse();
-                if server_addr.is_err() {
+                let command = if is_host {
+                    parse_host_address(addr).map(|(server_addr, rendezvous_addr)| {
+                        UiNetworkCommand::Host {
+                            nickname,
+                            server_addr,
+                            rendezvous_addr,
+                        }
+                    })
+                } else if let Some((host_code, rendezvous_addr)) = parse_invite(addr) {
+                    Some(UiNetworkCommand::ConnectViaRendezvous {
+                        nickname,
+                        rendezvous_addr,
+                        host_code,
+                    })
+                } else {
+                    parse_join_address(addr).map(|(server_addr, room)| UiNetworkCommand::Connect {
+                        nickname,
+                        server_addr,
+                        room,
+                    })
+                };
+                if command.is_none() {
                     return StateUpdate::ShowModalWindow {
                         id: INVALID_IP_ADDRESS.to_owned(),
                         title: "Server IP address has invalid format".to_owned(),
                         show_confirmation: true,
                     };
                 }
-                let server_addr = server_addr.unwrap();
+                let command = command.unwrap();
+                let is_matchmaking = match command {
+                    UiNetworkCommand::Connect {
+                        room: Some(RoomRequest::FindMatch),
+                        ..
+                    } => true,
+                    _ => false,
+                };
 
-                log::info!("Joining {}...", server_addr);
-                if is_host {
-                    system_data.ui_network_command.command = Some(UiNetworkCommand::Host {
-                        nickname,
-                        server_addr,
-                    });
+                log::info!("Joining {}...", addr);
+                system_data.ui_network_command.command = Some(command);
+                if is_matchmaking {
+                    StateUpdate::ShowModalWindow {
+                        id: MATCHMAKING_PROGRESS.to_owned(),
+                        title: "Looking for a match... (confirm to cancel)".to_owned(),
+                        show_confirmation: true,
+                    }
                 } else {
-                    system_data.ui_network_command.command = Some(UiNetworkCommand::Connect {
-                        nickname,
-                        server_addr,
-                    });
+                    StateUpdate::ShowModalWindow {
+                        id: CONNECTING_PROGRESS.to_owned(),
+                        title: "Connecting...".to_owned(),
+                        show_confirmation: false,
+                    }
+                }
+            }
+            (Some(UI_LOBBY_ROOMS_BUTTON), _) => {
+                let addr = system_data
+                    .ui_finder
+                    .get_ui_text(&system_data.ui_texts, UI_LOBBY_JOIN_IP_EDITABLE)
+                    .unwrap();
+                let server_addr = match parse_join_address(addr) {
+                    Some((server_addr, _)) => server_addr,
+                    None => {
+                        return StateUpdate::ShowModalWindow {
+                            id: INVALID_IP_ADDRESS.to_owned(),
+                            title: "Server IP address has invalid format".to_owned(),
+                            show_confirmation: true,
+                        };
+                    }
+                };
+
+                // Pressing the button again cycles through the listed rooms.
+                let room_browser = &mut *system_data.room_browser;
+                let next_room = room_browser.selected.map_or(0, |selected| selected + 1);
+                if room_browser.server_addr == Some(server_addr)
+                    && next_room < room_browser.rooms.len()
+                {
+                    room_browser.selected = Some(next_room);
+                    room_browser.is_updated = true;
+                    let room_address =
+                        format!("{}/{}", server_addr, room_browser.rooms[next_room].id);
+                    if let Some(join_address) = system_data
+                        .ui_finder
+                        .get_ui_text_mut(&mut system_data.ui_texts, UI_LOBBY_JOIN_IP_EDITABLE)
+                    {
+                        *join_address = room_address;
+                    }
+                    return StateUpdate::None;
                 }
-                StateUpdate::ShowModalWindow {
-                    id: CONNECTING_PROGRESS.to_owned(),
-                    title: "Connecting...".to_owned(),
-                    show_confirmation: false,
+
+                let nickname = system_data
+                    .ui_finder
+                    .get_ui_text(&system_data.ui_texts, UI_LOBBY_NICKNAME_EDITABLE)
+                    .cloned()
+                    .unwrap();
+                log::info!("Listing the rooms of {}...", server_addr);
+                system_data.ui_network_command.command = Some(UiNetworkCommand::Connect {
+                    nickname,
+                    server_addr,
+                    room: Some(RoomRequest::List),
+                });
+                if let Some(rooms_label) = system_data
+                    .ui_finder
+                    .get_ui_text_mut(&mut system_data.ui_texts, UI_LOBBY_ROOMS_LABEL)
+                {
+                    *rooms_label = format!("Listing the rooms of {}...", server_addr);
                 }
+                StateUpdate::None
+            }
+            (Some(UI_MODAL_CONFIRM_BUTTON), Some(MATCHMAKING_PROGRESS)) => {
+                log::info!("Cancelling matchmaking...");
+                system_data.ui_network_command.command = Some(UiNetworkCommand::CancelMatchmaking);
+                StateUpdate::None
             }
             (Some(UI_MODAL_CONFIRM_BUTTON), Some(CONNECTING_FAILED)) => {
                 system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
@@ -118,7 +230,7 @@ impl MenuScreen for LobbyMenuScreen {
                         } else {
                             StateUpdate::ShowModalWindow {
                                 id: CONNECTING_FAILED.to_owned(),
-                                title: disconnect_reason_title(*disconnect_reason),
+                                title: disconnect_reason_title(disconnect_reason),
                                 show_confirmation: true,
                             }
                         }
@@ -141,6 +253,74 @@ impl MenuScreen for LobbyMenuScreen {
     }
 }
 
+/// Describes the rooms listed by a server, marking the one the join address is set to.
+fn room_list_text(room_browser: &RoomBrowser) -> String {
+    let server_addr = match room_browser.server_addr {
+        Some(server_addr) => server_addr,
+        None => return String::new(),
+    };
+    if room_browser.rooms.is_empty() {
+        return format!(
+            "{} has no rooms, join {}/new to create one",
+            server_addr, server_addr
+        );
+    }
+
+    let mut text = format!(
+        "Rooms of {} (press Rooms to pick the next one):\n",
+        server_addr
+    );
+    for (i, room) in room_browser.rooms.iter().enumerate() {
+        let marker = if room_browser.selected == Some(i) {
+            ">"
+        } else {
+            " "
+        };
+        text += &format!(
+            "{} #{} {} - {} players{}\n",
+            marker,
+            room.id,
+            room.name,
+            room.players_count,
+            if room.is_playing { ", playing" } else { "" }
+        );
+    }
+    text
+}
+
+/// Accepts `ADDR`, `ADDR/new` to create a room, `ADDR/match` to find a match
+/// or `ADDR/ROOM_ID` to enter a room, the last three are meant for servers hosting several rooms.
+fn parse_join_address(addr: &str) -> Option<(SocketAddr, Option<RoomRequest>)> {
+    let mut parts = addr.splitn(2, '/');
+    let server_addr = parts.next()?.parse().ok()?;
+    let room = match parts.next() {
+        None => None,
+        Some("new") => Some(RoomRequest::Create),
+        Some("match") => Some(RoomRequest::FindMatch),
+        Some(room_id) => Some(RoomRequest::Enter(room_id.parse().ok()?)),
+    };
+    Some((server_addr, room))
+}
+
+/// Accepts `ADDR` or `ADDR@RENDEZVOUS_ADDR` to let players behind NATs join.
+fn parse_host_address(addr: &str) -> Option<(SocketAddr, Option<SocketAddr>)> {
+    let mut parts = addr.splitn(2, '@');
+    let server_addr = parts.next()?.parse().ok()?;
+    let rendezvous_addr = match parts.next() {
+        None => None,
+        Some(rendezvous_addr) => Some(rendezvous_addr.parse().ok()?),
+    };
+    Some((server_addr, rendezvous_addr))
+}
+
+/// Parses `CODE@RENDEZVOUS_ADDR`, as shown to a player hosting via a rendezvous server.
+fn parse_invite(addr: &str) -> Option<(HostCode, SocketAddr)> {
+    let mut parts = addr.splitn(2, '@');
+    let host_code = parts.next()?.parse().ok()?;
+    let rendezvous_addr = parts.next()?.parse().ok()?;
+    Some((host_code, rendezvous_addr))
+}
+
 fn is_failed_modal_window(modal_window_id: Option<&str>) -> bool {
     modal_window_id.map_or(true, |modal_window_id| {
         modal_window_id == CONNECTING_FAILED || modal_window_id == SERVER_START_FAILED
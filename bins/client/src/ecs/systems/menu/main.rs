@@ -1,30 +1,150 @@
+use std::{path::Path, process::Command};
+
+use gv_core::{
+    crash_report::delete_crash_report, objectives::ObjectiveTracker, tutorial::tutorial_objectives,
+};
+
 use super::*;
 
+const CRASH_REPORT_MODAL_WINDOW_ID: &str = "crash_report_modal";
+
 pub struct MainMenuScreen;
 
 impl MenuScreen for MainMenuScreen {
-    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
-        vec![
+    fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
+        let mut elements = vec![
+            UI_CUSTOMIZE_BUTTON,
+            UI_CLASS_BUTTON,
+            UI_ACHIEVEMENTS_BUTTON,
             UI_SINGLE_PLAYER_BUTTON,
             UI_MULTIPLAYER_BUTTON,
+            UI_TUTORIAL_BUTTON,
             UI_QUIT_BUTTON,
-        ]
+        ];
+        if !system_data.crash_reports.paths.is_empty() {
+            elements.push(UI_VIEW_CRASH_REPORT_BUTTON);
+            elements.push(UI_DELETE_CRASH_REPORTS_BUTTON);
+        }
+        if personal_best(system_data).is_some() {
+            elements.push(UI_PERSONAL_BEST_LABEL);
+        }
+        elements
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        set_class_button_text(system_data, UI_CLASS_BUTTON);
+        if let Some(personal_best) = personal_best(system_data) {
+            *system_data
+                .ui_finder
+                .get_ui_text_mut(&mut system_data.ui_texts, UI_PERSONAL_BEST_LABEL)
+                .expect("Expected a personal best label") =
+                format!("Personal best: {}", personal_best);
+        }
     }
 
     fn update(
         &mut self,
-        _system_data: &mut MenuSystemData,
+        system_data: &mut MenuSystemData,
         button_pressed: Option<&str>,
         _modal_window_id: Option<&str>,
     ) -> StateUpdate {
+        if !system_data.crash_reports.is_prompt_shown && !system_data.crash_reports.paths.is_empty()
+        {
+            system_data.crash_reports.is_prompt_shown = true;
+            return StateUpdate::ShowModalWindow {
+                id: CRASH_REPORT_MODAL_WINDOW_ID.to_owned(),
+                title: "The game has crashed last time, a crash report has been saved".to_owned(),
+                show_confirmation: true,
+            };
+        }
+
         match button_pressed {
-            Some(UI_SINGLE_PLAYER_BUTTON) => StateUpdate::GameMenuUpdate {
-                game_engine_state: Some(GameEngineState::Playing),
-                menu_screen: Some(GameMenuScreen::Hidden),
-            },
-            Some(UI_MULTIPLAYER_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu),
+            Some(UI_CLASS_BUTTON) => {
+                select_next_class(system_data);
+                set_class_button_text(system_data, UI_CLASS_BUTTON);
+                StateUpdate::CustomAnimation {
+                    elements_to_hide: vec![UI_CLASS_BUTTON],
+                    elements_to_show: vec![UI_CLASS_BUTTON],
+                }
+            }
+            Some(UI_CUSTOMIZE_BUTTON) => {
+                StateUpdate::new_menu_screen(GameMenuScreen::CustomizationMenu)
+            }
+            Some(UI_ACHIEVEMENTS_BUTTON) => {
+                StateUpdate::new_menu_screen(GameMenuScreen::AchievementsMenu)
+            }
+            Some(UI_SINGLE_PLAYER_BUTTON) => {
+                system_data.active_objectives.0 = None;
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_TUTORIAL_BUTTON) => {
+                system_data.active_objectives.0 =
+                    Some(ObjectiveTracker::new(tutorial_objectives()));
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_MULTIPLAYER_BUTTON) => {
+                system_data.active_objectives.0 = None;
+                StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu)
+            }
             Some(UI_QUIT_BUTTON) => StateUpdate::new_game_engine_state(GameEngineState::Quit),
+            Some(UI_VIEW_CRASH_REPORT_BUTTON) => {
+                if let Some(path) = system_data.crash_reports.paths.first() {
+                    open_crash_report(path);
+                }
+                // Fading the button out and in makes it interactable again.
+                StateUpdate::CustomAnimation {
+                    elements_to_hide: vec![UI_VIEW_CRASH_REPORT_BUTTON],
+                    elements_to_show: vec![UI_VIEW_CRASH_REPORT_BUTTON],
+                }
+            }
+            Some(UI_DELETE_CRASH_REPORTS_BUTTON) => {
+                for path in system_data.crash_reports.paths.drain(..) {
+                    delete_crash_report(&path);
+                }
+                StateUpdate::CustomAnimation {
+                    elements_to_hide: vec![
+                        UI_VIEW_CRASH_REPORT_BUTTON,
+                        UI_DELETE_CRASH_REPORTS_BUTTON,
+                    ],
+                    elements_to_show: vec![],
+                }
+            }
             _ => StateUpdate::None,
         }
     }
 }
+
+fn personal_best(system_data: &MenuSystemData) -> Option<u64> {
+    system_data
+        .settings
+        .profile()
+        .personal_bests
+        .get(system_data.game_level_state.map_name)
+        .cloned()
+}
+
+/// Opens the report with the application the system associates with text files.
+fn open_crash_report(path: &Path) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(&["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    if let Err(err) = command.arg(path).spawn() {
+        log::error!(
+            "Failed to open the crash report {}: {:?}",
+            path.display(),
+            err
+        );
+    }
+}
@@ -1,4 +1,8 @@
+mod achievements;
+mod customization;
 mod hidden;
+mod level_loading;
+mod level_transition;
 mod lobby;
 mod main;
 mod multiplayer_room;
@@ -6,7 +10,7 @@ mod restart;
 
 use amethyst::{
     core::{HiddenPropagate, ParentHierarchy},
-    ecs::{Entity, ReadExpect, System, SystemData, World, Write, WriteExpect, WriteStorage},
+    ecs::{Entity, Read, ReadExpect, System, SystemData, World, Write, WriteExpect, WriteStorage},
     shred::ResourceId,
     shrev::{EventChannel, ReaderId},
     ui::{Interactable, UiEvent, UiEventType, UiImage, UiText},
@@ -15,17 +19,31 @@ use lazy_static::lazy_static;
 
 use std::{collections::VecDeque, time::Duration};
 
-use gv_client_shared::ecs::resources::MultiplayerRoomState;
-use gv_core::ecs::{
-    resources::{net::MultiplayerGameState, GameEngineState, GameLevelState, NewGameEngineState},
-    system_data::time::GameTimeService,
+use gv_client_shared::{
+    ecs::resources::{LevelAssets, LevelLoadingError, MultiplayerRoomState},
+    settings::Settings,
+    ui_theme::UiThemes,
 };
+use gv_core::{
+    classes::PlayerClass,
+    ecs::{
+        resources::{
+            net::MultiplayerGameState, GameEngineState, GameLevelState, MatchScore,
+            NewGameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    room_settings::RoomSettings,
+};
+use gv_game::ecs::resources::ActiveObjectives;
 
 use crate::ecs::{
-    resources::UiNetworkCommandResource,
+    resources::{CrashReports, Emotes, Highlights, RoomBrowser, UiNetworkCommandResource},
     system_data::ui::UiFinderMut,
     systems::menu::{
-        hidden::HiddenMenuScreen, lobby::LobbyMenuScreen, main::MainMenuScreen,
+        achievements::AchievementsMenuScreen, customization::CustomizationMenuScreen,
+        hidden::HiddenMenuScreen, level_loading::LevelLoadingMenuScreen,
+        level_transition::LevelTransitionMenuScreen, lobby::LobbyMenuScreen, main::MainMenuScreen,
         multiplayer_room::MultiplayerRoomMenuScreen, restart::RestartMenuScreen,
     },
 };
@@ -39,12 +57,46 @@ const MODAL_TAG: &str = "_modal";
 const UI_MAIN_CONTAINER: &str = "ui_main_container";
 const UI_LOADING_LABEL: &str = "ui_loading_label";
 
+const UI_CUSTOMIZE_BUTTON: &str = "ui_customize_button";
+const UI_CLASS_BUTTON: &str = "ui_class_button";
+const UI_ACHIEVEMENTS_BUTTON: &str = "ui_achievements_button";
 const UI_SINGLE_PLAYER_BUTTON: &str = "ui_single_player_button";
 const UI_MULTIPLAYER_BUTTON: &str = "ui_multiplayer_button";
+const UI_TUTORIAL_BUTTON: &str = "ui_tutorial_button";
 const UI_QUIT_BUTTON: &str = "ui_quit_button";
+const UI_VIEW_CRASH_REPORT_BUTTON: &str = "ui_view_crash_report_button";
+const UI_DELETE_CRASH_REPORTS_BUTTON: &str = "ui_delete_crash_reports_button";
+const UI_PERSONAL_BEST_LABEL: &str = "ui_personal_best_label";
+const UI_ACHIEVEMENTS_LABEL: &str = "ui_achievements_label";
 
 const UI_RESTART_BUTTON: &str = "ui_restart_button";
 const UI_MAIN_MENU_BUTTON: &str = "ui_main_menu_button";
+const UI_RESULTS_LABEL: &str = "ui_results_label";
+const UI_HIGHLIGHTS_LABEL: &str = "ui_highlights_label";
+
+const UI_CUSTOMIZATION_COLOR_BUTTON: &str = "ui_customization_color_button";
+const UI_CUSTOMIZATION_HAT_BUTTON: &str = "ui_customization_hat_button";
+const UI_CUSTOMIZATION_PALETTE_BUTTON: &str = "ui_customization_palette_button";
+const UI_CUSTOMIZATION_MARKERS_BUTTON: &str = "ui_customization_markers_button";
+const UI_CUSTOMIZATION_UI_SCALE_BUTTON: &str = "ui_customization_ui_scale_button";
+const UI_CUSTOMIZATION_HUD_SCALE_BUTTON: &str = "ui_customization_hud_scale_button";
+const UI_CUSTOMIZATION_AIM_ASSIST_BUTTON: &str = "ui_customization_aim_assist_button";
+const UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON: &str = "ui_customization_menu_background_button";
+const UI_CUSTOMIZATION_GORE_BUTTON: &str = "ui_customization_gore_button";
+const UI_CUSTOMIZATION_RENDER_SCALE_BUTTON: &str = "ui_customization_render_scale_button";
+const UI_CUSTOMIZATION_LIGHTING_BUTTON: &str = "ui_customization_lighting_button";
+const UI_CUSTOMIZATION_BLOOM_BUTTON: &str = "ui_customization_bloom_button";
+const UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON: &str =
+    "ui_customization_chromatic_aberration_button";
+const UI_CUSTOMIZATION_COLOR_GRADING_BUTTON: &str = "ui_customization_color_grading_button";
+const UI_CUSTOMIZATION_THEME_BUTTON: &str = "ui_customization_theme_button";
+const UI_CUSTOMIZATION_FPS_CAP_BUTTON: &str = "ui_customization_fps_cap_button";
+const UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON: &str = "ui_customization_unfocused_fps_cap_button";
+const UI_CUSTOMIZATION_ANNOUNCER_BUTTON: &str = "ui_customization_announcer_button";
+const UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON: &str = "ui_customization_mute_unfocused_button";
+const UI_CUSTOMIZATION_LAG_PAUSE_BUTTON: &str = "ui_customization_lag_pause_button";
+const UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON: &str =
+    "ui_customization_connection_warnings_button";
 
 const UI_LOBBY_NICKNAME_LABEL: &str = "ui_lobby_nickname_label";
 const UI_LOBBY_NICKNAME_FIELD: &str = "ui_lobby_nickname_field";
@@ -55,29 +107,56 @@ const UI_LOBBY_HOST_BUTTON: &str = "ui_lobby_host_button";
 const UI_LOBBY_JOIN_IP_FIELD: &str = "ui_lobby_join_ip_field";
 const UI_LOBBY_JOIN_IP_EDITABLE: &str = "ui_lobby_join_ip_editable";
 const UI_LOBBY_JOIN_BUTTON: &str = "ui_lobby_join_button";
+const UI_LOBBY_ROOMS_BUTTON: &str = "ui_lobby_rooms_button";
+const UI_LOBBY_ROOMS_LABEL: &str = "ui_lobby_rooms_label";
 
 const UI_MP_ROOM_START_BUTTON: &str = "ui_start_multiplayer_button";
+const UI_MP_ROOM_RESUME_BUTTON: &str = "ui_resume_multiplayer_button";
+const UI_MP_ROOM_CAMPAIGN_BUTTON: &str = "ui_campaign_multiplayer_button";
+const UI_MP_ROOM_CLASS_BUTTON: &str = "ui_class_multiplayer_button";
 const UI_MP_ROOM_LOBBY_BUTTON: &str = "ui_back_to_lobby_button";
+const UI_MP_ROOM_MAX_PLAYERS_BUTTON: &str = "ui_mp_room_max_players_button";
+const UI_MP_ROOM_DIFFICULTY_BUTTON: &str = "ui_mp_room_difficulty_button";
+const UI_MP_ROOM_MODE_BUTTON: &str = "ui_mp_room_mode_button";
+const UI_MP_ROOM_FRIENDLY_FIRE_BUTTON: &str = "ui_mp_room_friendly_fire_button";
+const UI_MP_ROOM_AIM_ASSIST_BUTTON: &str = "ui_mp_room_aim_assist_button";
+const UI_MP_ROOM_MAP_BUTTON: &str = "ui_mp_room_map_button";
+const UI_MP_ROOM_FAST_MONSTERS_BUTTON: &str = "ui_mp_room_fast_monsters_button";
+const UI_MP_ROOM_NO_COOLDOWNS_BUTTON: &str = "ui_mp_room_no_cooldowns_button";
+const UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON: &str = "ui_mp_room_one_hit_deaths_button";
+const UI_MP_ROOM_VAMPIRISM_BUTTON: &str = "ui_mp_room_vampirism_button";
+const UI_MP_ROOM_SETTINGS_LABEL: &str = "ui_mp_room_settings_label";
 const UI_MP_ROOM_PLAYER1_CONTAINER: &str = "ui_mp_room_player1_container";
 const UI_MP_ROOM_PLAYER1_BG: &str = "ui_mp_room_player1_bg";
 const UI_MP_ROOM_PLAYER1_NUMBER: &str = "ui_mp_room_player1_number";
 const UI_MP_ROOM_PLAYER1_NICKNAME: &str = "ui_mp_room_player1_nickname";
 const UI_MP_ROOM_PLAYER1_KICK: &str = "ui_mp_room_player1_kick";
+const UI_MP_ROOM_PLAYER1_EMOTE: &str = "ui_mp_room_player1_emote";
 const UI_MP_ROOM_PLAYER2_CONTAINER: &str = "ui_mp_room_player2_container";
 const UI_MP_ROOM_PLAYER2_BG: &str = "ui_mp_room_player2_bg";
 const UI_MP_ROOM_PLAYER2_NUMBER: &str = "ui_mp_room_player2_number";
 const UI_MP_ROOM_PLAYER2_NICKNAME: &str = "ui_mp_room_player2_nickname";
 const UI_MP_ROOM_PLAYER2_KICK: &str = "ui_mp_room_player2_kick";
+const UI_MP_ROOM_PLAYER2_EMOTE: &str = "ui_mp_room_player2_emote";
 const UI_MP_ROOM_PLAYER3_CONTAINER: &str = "ui_mp_room_player3_container";
 const UI_MP_ROOM_PLAYER3_BG: &str = "ui_mp_room_player3_bg";
 const UI_MP_ROOM_PLAYER3_NUMBER: &str = "ui_mp_room_player3_number";
 const UI_MP_ROOM_PLAYER3_NICKNAME: &str = "ui_mp_room_player3_nickname";
 const UI_MP_ROOM_PLAYER3_KICK: &str = "ui_mp_room_player3_kick";
+const UI_MP_ROOM_PLAYER3_EMOTE: &str = "ui_mp_room_player3_emote";
 const UI_MP_ROOM_PLAYER4_CONTAINER: &str = "ui_mp_room_player4_container";
 const UI_MP_ROOM_PLAYER4_BG: &str = "ui_mp_room_player4_bg";
 const UI_MP_ROOM_PLAYER4_NUMBER: &str = "ui_mp_room_player4_number";
 const UI_MP_ROOM_PLAYER4_NICKNAME: &str = "ui_mp_room_player4_nickname";
 const UI_MP_ROOM_PLAYER4_KICK: &str = "ui_mp_room_player4_kick";
+const UI_MP_ROOM_PLAYER4_EMOTE: &str = "ui_mp_room_player4_emote";
+
+const UI_LEVEL_TRANSITION_LABEL: &str = "ui_level_transition_label";
+
+const UI_LEVEL_LOADING_LABEL: &str = "ui_level_loading_label";
+const UI_LEVEL_LOADING_BAR_BORDER_CONTAINER: &str = "ui_level_loading_bar_border_container";
+const UI_LEVEL_LOADING_BAR_BG: &str = "ui_level_loading_bar_bg";
+const UI_LEVEL_LOADING_BAR_FILL: &str = "ui_level_loading_bar_fill";
 
 const UI_MODAL_BACKDROP_CONTAINER: &str = "ui_modal_backdrop_container";
 const UI_MODAL_WINDOW_BORDER_CONTAINER: &str = "ui_modal_window_border_container";
@@ -112,12 +191,20 @@ trait MenuScreen {
 
 lazy_static! {
     static ref MAIN_MENU_ELEMENTS: &'static [&'static str] = &[
+        UI_CUSTOMIZE_BUTTON,
+        UI_CLASS_BUTTON,
+        UI_ACHIEVEMENTS_BUTTON,
         UI_SINGLE_PLAYER_BUTTON,
         UI_MULTIPLAYER_BUTTON,
+        UI_TUTORIAL_BUTTON,
         UI_QUIT_BUTTON,
     ];
-    static ref RESTART_MENU_ELEMENTS: &'static [&'static str] =
-        &[UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON];
+    static ref RESTART_MENU_ELEMENTS: &'static [&'static str] = &[
+        UI_RESTART_BUTTON,
+        UI_MAIN_MENU_BUTTON,
+        UI_RESULTS_LABEL,
+        UI_HIGHLIGHTS_LABEL,
+    ];
     static ref LOBBY_MENU_ELEMENTS: &'static [&'static str] = &[
         UI_LOBBY_NICKNAME_LABEL,
         UI_LOBBY_NICKNAME_FIELD,
@@ -128,11 +215,16 @@ lazy_static! {
         UI_LOBBY_JOIN_IP_FIELD,
         UI_LOBBY_JOIN_IP_EDITABLE,
         UI_LOBBY_JOIN_BUTTON,
+        UI_LOBBY_ROOMS_BUTTON,
+        UI_LOBBY_ROOMS_LABEL,
         UI_MAIN_MENU_BUTTON,
     ];
     // TODO: implement and uncomment buttons.
     static ref MP_ROOM_MENU_ELEMENTS: &'static [&'static str] = &[
         UI_MP_ROOM_START_BUTTON,
+        UI_MP_ROOM_RESUME_BUTTON,
+        UI_MP_ROOM_CAMPAIGN_BUTTON,
+        UI_MP_ROOM_CLASS_BUTTON,
         UI_MP_ROOM_LOBBY_BUTTON,
         UI_MP_ROOM_PLAYER1_CONTAINER,
         UI_MP_ROOM_PLAYER1_BG,
@@ -163,6 +255,23 @@ lazy_static! {
     ];
 }
 
+/// Switches the profile to the next class and returns it.
+fn select_next_class(system_data: &mut MenuSystemData) -> PlayerClass {
+    let class = system_data.settings.profile().class.next();
+    if let Err(err) = system_data.settings.save_class(class) {
+        log::error!("Failed to save the selected class: {:?}", err);
+    }
+    class
+}
+
+fn set_class_button_text(system_data: &mut MenuSystemData, button_id: &str) {
+    let class = system_data.settings.profile().class;
+    *system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, button_id)
+        .expect("Expected a class button text") = format!("Class: {}", class.name());
+}
+
 #[derive(SystemData)]
 pub struct MenuSystemData<'s> {
     game_time_service: GameTimeService<'s>,
@@ -172,8 +281,19 @@ pub struct MenuSystemData<'s> {
     new_game_engine_state: WriteExpect<'s, NewGameEngineState>,
     game_level_state: WriteExpect<'s, GameLevelState>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
-    multiplayer_room_state: ReadExpect<'s, MultiplayerRoomState>,
+    crash_reports: WriteExpect<'s, CrashReports>,
+    emotes: WriteExpect<'s, Emotes>,
+    room_browser: WriteExpect<'s, RoomBrowser>,
+    settings: WriteExpect<'s, Settings>,
+    ui_themes: ReadExpect<'s, UiThemes>,
+    multiplayer_room_state: WriteExpect<'s, MultiplayerRoomState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    level_assets: Read<'s, LevelAssets>,
+    level_loading_error: Write<'s, LevelLoadingError>,
+    room_settings: ReadExpect<'s, RoomSettings>,
+    match_score: ReadExpect<'s, MatchScore>,
+    highlights: ReadExpect<'s, Highlights>,
+    active_objectives: WriteExpect<'s, ActiveObjectives>,
     ui_events: Write<'s, EventChannel<UiEvent>>,
     ui_texts: WriteStorage<'s, UiText>,
     ui_images: WriteStorage<'s, UiImage>,
@@ -202,9 +322,13 @@ struct MenuScreenAnimation {
 struct MenuScreens {
     lobby_menu_screen: LobbyMenuScreen,
     main_menu_screen: MainMenuScreen,
+    customization_menu_screen: CustomizationMenuScreen,
+    achievements_menu_screen: AchievementsMenuScreen,
     multiplayer_room_menu_screen: MultiplayerRoomMenuScreen,
     restart_menu_screen: RestartMenuScreen,
     hidden_menu_screen: HiddenMenuScreen,
+    level_transition_menu_screen: LevelTransitionMenuScreen,
+    level_loading_menu_screen: LevelLoadingMenuScreen,
 }
 
 impl MenuScreens {
@@ -212,9 +336,13 @@ impl MenuScreens {
         match screen {
             GameMenuScreen::LobbyMenu => Some(&mut self.lobby_menu_screen),
             GameMenuScreen::MainMenu => Some(&mut self.main_menu_screen),
+            GameMenuScreen::CustomizationMenu => Some(&mut self.customization_menu_screen),
+            GameMenuScreen::AchievementsMenu => Some(&mut self.achievements_menu_screen),
             GameMenuScreen::MultiplayerRoomMenu => Some(&mut self.multiplayer_room_menu_screen),
             GameMenuScreen::RestartMenu => Some(&mut self.restart_menu_screen),
             GameMenuScreen::Hidden => Some(&mut self.hidden_menu_screen),
+            GameMenuScreen::LevelTransition => Some(&mut self.level_transition_menu_screen),
+            GameMenuScreen::LevelLoading => Some(&mut self.level_loading_menu_screen),
             GameMenuScreen::Loading => None,
         }
     }
@@ -259,24 +387,69 @@ impl MenuSystem {
             menu_screens: MenuScreens {
                 lobby_menu_screen: LobbyMenuScreen,
                 main_menu_screen: MainMenuScreen,
+                customization_menu_screen: CustomizationMenuScreen,
+                achievements_menu_screen: AchievementsMenuScreen,
                 multiplayer_room_menu_screen: MultiplayerRoomMenuScreen::new(),
                 restart_menu_screen: RestartMenuScreen,
                 hidden_menu_screen: HiddenMenuScreen,
+                level_transition_menu_screen: LevelTransitionMenuScreen,
+                level_loading_menu_screen: LevelLoadingMenuScreen,
             },
             modal_window_id: None,
             mouse_reactive: vec![
+                UI_CUSTOMIZE_BUTTON,
+                UI_CLASS_BUTTON,
+                UI_ACHIEVEMENTS_BUTTON,
                 UI_SINGLE_PLAYER_BUTTON,
                 UI_MULTIPLAYER_BUTTON,
+                UI_TUTORIAL_BUTTON,
                 UI_QUIT_BUTTON,
+                UI_VIEW_CRASH_REPORT_BUTTON,
+                UI_DELETE_CRASH_REPORTS_BUTTON,
                 UI_RESTART_BUTTON,
                 UI_MAIN_MENU_BUTTON,
+                UI_CUSTOMIZATION_COLOR_BUTTON,
+                UI_CUSTOMIZATION_HAT_BUTTON,
+                UI_CUSTOMIZATION_PALETTE_BUTTON,
+                UI_CUSTOMIZATION_MARKERS_BUTTON,
+                UI_CUSTOMIZATION_UI_SCALE_BUTTON,
+                UI_CUSTOMIZATION_HUD_SCALE_BUTTON,
+                UI_CUSTOMIZATION_AIM_ASSIST_BUTTON,
+                UI_CUSTOMIZATION_MENU_BACKGROUND_BUTTON,
+                UI_CUSTOMIZATION_GORE_BUTTON,
+                UI_CUSTOMIZATION_RENDER_SCALE_BUTTON,
+                UI_CUSTOMIZATION_LIGHTING_BUTTON,
+                UI_CUSTOMIZATION_BLOOM_BUTTON,
+                UI_CUSTOMIZATION_CHROMATIC_ABERRATION_BUTTON,
+                UI_CUSTOMIZATION_COLOR_GRADING_BUTTON,
+                UI_CUSTOMIZATION_THEME_BUTTON,
+                UI_CUSTOMIZATION_FPS_CAP_BUTTON,
+                UI_CUSTOMIZATION_UNFOCUSED_FPS_CAP_BUTTON,
+                UI_CUSTOMIZATION_ANNOUNCER_BUTTON,
+                UI_CUSTOMIZATION_MUTE_UNFOCUSED_BUTTON,
+                UI_CUSTOMIZATION_LAG_PAUSE_BUTTON,
+                UI_CUSTOMIZATION_CONNECTION_WARNINGS_BUTTON,
                 UI_LOBBY_NICKNAME_EDITABLE,
                 UI_LOBBY_HOST_IP_EDITABLE,
                 UI_LOBBY_HOST_BUTTON,
                 UI_LOBBY_JOIN_IP_EDITABLE,
                 UI_LOBBY_JOIN_BUTTON,
+                UI_LOBBY_ROOMS_BUTTON,
                 UI_MP_ROOM_START_BUTTON,
+                UI_MP_ROOM_RESUME_BUTTON,
+                UI_MP_ROOM_CAMPAIGN_BUTTON,
+                UI_MP_ROOM_CLASS_BUTTON,
                 UI_MP_ROOM_LOBBY_BUTTON,
+                UI_MP_ROOM_MAX_PLAYERS_BUTTON,
+                UI_MP_ROOM_DIFFICULTY_BUTTON,
+                UI_MP_ROOM_MODE_BUTTON,
+                UI_MP_ROOM_FRIENDLY_FIRE_BUTTON,
+                UI_MP_ROOM_AIM_ASSIST_BUTTON,
+                UI_MP_ROOM_MAP_BUTTON,
+                UI_MP_ROOM_FAST_MONSTERS_BUTTON,
+                UI_MP_ROOM_NO_COOLDOWNS_BUTTON,
+                UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON,
+                UI_MP_ROOM_VAMPIRISM_BUTTON,
                 UI_MP_ROOM_PLAYER1_KICK,
                 UI_MP_ROOM_PLAYER2_KICK,
                 UI_MP_ROOM_PLAYER3_KICK,
@@ -301,10 +474,14 @@ impl Default for MenuSystem {
 enum GameMenuScreen {
     Loading,
     MainMenu,
+    CustomizationMenu,
+    AchievementsMenu,
     RestartMenu,
     LobbyMenu,
     MultiplayerRoomMenu,
     Hidden,
+    LevelTransition,
+    LevelLoading,
 }
 
 #[derive(Debug)]
@@ -396,6 +573,13 @@ impl<'s> System<'s> for MenuSystem {
             (GameEngineState::Menu, GameMenuScreen::Loading) => {
                 StateUpdate::new_menu_screen(GameMenuScreen::MainMenu)
             }
+            // A level can fail to load before the loading screen is shown.
+            (GameEngineState::Menu, menu_screen)
+                if menu_screen != GameMenuScreen::LevelLoading
+                    && system_data.level_loading_error.0.is_some() =>
+            {
+                StateUpdate::new_menu_screen(GameMenuScreen::LevelLoading)
+            }
             (GameEngineState::Menu, menu_screen) => {
                 let menu_screen = self
                     .menu_screens
@@ -412,6 +596,11 @@ impl<'s> System<'s> for MenuSystem {
                         .map(std::string::String::as_str),
                 )
             }
+            (GameEngineState::LevelTransition, menu_screen)
+                if menu_screen != GameMenuScreen::LevelTransition =>
+            {
+                StateUpdate::new_menu_screen(GameMenuScreen::LevelTransition)
+            }
             (GameEngineState::Playing, menu_screen) if menu_screen != GameMenuScreen::Hidden => {
                 StateUpdate::new_menu_screen(GameMenuScreen::Hidden)
             }
@@ -422,6 +611,37 @@ impl<'s> System<'s> for MenuSystem {
                     .as_ref()
                     .map(std::string::String::as_str),
             ),
+            (GameEngineState::LevelTransition, _) => {
+                self.menu_screens.level_transition_menu_screen.update(
+                    &mut system_data,
+                    button_pressed.as_ref().map(std::string::String::as_str),
+                    self.modal_window_id
+                        .as_ref()
+                        .map(std::string::String::as_str),
+                )
+            }
+            // Prefetched levels start right away, without flashing the loading screen.
+            (GameEngineState::LoadingLevel, menu_screen)
+                if menu_screen != GameMenuScreen::LevelLoading
+                    && !system_data
+                        .level_assets
+                        .0
+                        .as_ref()
+                        .map_or(false, |level_asset_handles| {
+                            level_asset_handles.progress().is_complete()
+                        }) =>
+            {
+                StateUpdate::new_menu_screen(GameMenuScreen::LevelLoading)
+            }
+            (GameEngineState::LoadingLevel, GameMenuScreen::LevelLoading) => {
+                self.menu_screens.level_loading_menu_screen.update(
+                    &mut system_data,
+                    button_pressed.as_ref().map(std::string::String::as_str),
+                    self.modal_window_id
+                        .as_ref()
+                        .map(std::string::String::as_str),
+                )
+            }
             _ => StateUpdate::None,
         };
 
@@ -1,16 +1,34 @@
 use gv_client_shared::ecs::resources::ConnectionStatus;
+use gv_core::{
+    emotes::EMOTE_DURATION_MS,
+    mutators::{mutators_text, toggle_mutator, Mutator},
+    room_settings::{RoomSettings, MAX_PLAYERS},
+};
 
 use super::*;
 use crate::{ecs::resources::UiNetworkCommand, utils::ui::disconnect_reason_title};
-use gv_core::ecs::resources::net::MultiplayerRoomPlayer;
+use gv_core::{ecs::resources::net::MultiplayerRoomPlayer, net::NetIdentifier};
 
 const DISCONNECTED: &str = "MP_DISCONNECTED";
 const DISCONNECTING: &str = "MP_DISCONNECTING";
+const INVITE: &str = "MP_INVITE";
 
 lazy_static! {
     static ref MP_ROOM_MENU_ELEMENTS_HOST: &'static [&'static str] = &[
         UI_MP_ROOM_START_BUTTON,
+        UI_MP_ROOM_CAMPAIGN_BUTTON,
+        UI_MP_ROOM_CLASS_BUTTON,
         UI_MP_ROOM_LOBBY_BUTTON,
+        UI_MP_ROOM_MAX_PLAYERS_BUTTON,
+        UI_MP_ROOM_DIFFICULTY_BUTTON,
+        UI_MP_ROOM_MODE_BUTTON,
+        UI_MP_ROOM_FRIENDLY_FIRE_BUTTON,
+        UI_MP_ROOM_AIM_ASSIST_BUTTON,
+        UI_MP_ROOM_MAP_BUTTON,
+        UI_MP_ROOM_FAST_MONSTERS_BUTTON,
+        UI_MP_ROOM_NO_COOLDOWNS_BUTTON,
+        UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON,
+        UI_MP_ROOM_VAMPIRISM_BUTTON,
         UI_MP_ROOM_PLAYER1_CONTAINER,
         UI_MP_ROOM_PLAYER1_BG,
         UI_MP_ROOM_PLAYER2_CONTAINER,
@@ -21,7 +39,9 @@ lazy_static! {
         UI_MP_ROOM_PLAYER4_BG,
     ];
     static ref MP_ROOM_MENU_ELEMENTS_JOIN: &'static [&'static str] = &[
+        UI_MP_ROOM_CLASS_BUTTON,
         UI_MP_ROOM_LOBBY_BUTTON,
+        UI_MP_ROOM_SETTINGS_LABEL,
         UI_MP_ROOM_PLAYER1_CONTAINER,
         UI_MP_ROOM_PLAYER1_BG,
         UI_MP_ROOM_PLAYER2_CONTAINER,
@@ -35,14 +55,24 @@ lazy_static! {
 
 pub struct MultiplayerRoomMenuScreen {
     initiated_disconnecting: bool,
+    has_shown_invite: bool,
     players: Vec<MultiplayerRoomPlayer>,
+    afk_players: Vec<NetIdentifier>,
+    /// The settings shown on the settings panel.
+    room_settings: Option<RoomSettings>,
+    /// When the emotes shown next to the players' rows were received.
+    emotes_shown_at: [Option<Duration>; 4],
 }
 
 impl MultiplayerRoomMenuScreen {
     pub fn new() -> Self {
         Self {
             initiated_disconnecting: false,
+            has_shown_invite: false,
             players: Vec::new(),
+            afk_players: Vec::new(),
+            room_settings: None,
+            emotes_shown_at: [None; 4],
         }
     }
 }
@@ -50,7 +80,11 @@ impl MultiplayerRoomMenuScreen {
 impl MenuScreen for MultiplayerRoomMenuScreen {
     fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
         if system_data.multiplayer_room_state.is_host {
-            MP_ROOM_MENU_ELEMENTS_HOST.to_vec()
+            let mut elements = MP_ROOM_MENU_ELEMENTS_HOST.to_vec();
+            if system_data.settings.saved_match_path().exists() {
+                elements.push(UI_MP_ROOM_RESUME_BUTTON);
+            }
+            elements
         } else {
             MP_ROOM_MENU_ELEMENTS_JOIN.to_vec()
         }
@@ -59,32 +93,51 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
     fn elements_to_hide(&self, _system_data: &MenuSystemData) -> Vec<&'static str> {
         vec![
             UI_MP_ROOM_START_BUTTON,
+            UI_MP_ROOM_RESUME_BUTTON,
+            UI_MP_ROOM_CAMPAIGN_BUTTON,
+            UI_MP_ROOM_CLASS_BUTTON,
             UI_MP_ROOM_LOBBY_BUTTON,
+            UI_MP_ROOM_MAX_PLAYERS_BUTTON,
+            UI_MP_ROOM_DIFFICULTY_BUTTON,
+            UI_MP_ROOM_MODE_BUTTON,
+            UI_MP_ROOM_FRIENDLY_FIRE_BUTTON,
+            UI_MP_ROOM_AIM_ASSIST_BUTTON,
+            UI_MP_ROOM_MAP_BUTTON,
+            UI_MP_ROOM_FAST_MONSTERS_BUTTON,
+            UI_MP_ROOM_NO_COOLDOWNS_BUTTON,
+            UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON,
+            UI_MP_ROOM_VAMPIRISM_BUTTON,
+            UI_MP_ROOM_SETTINGS_LABEL,
             UI_MP_ROOM_PLAYER1_CONTAINER,
             UI_MP_ROOM_PLAYER1_BG,
             UI_MP_ROOM_PLAYER1_NUMBER,
             UI_MP_ROOM_PLAYER1_NICKNAME,
             UI_MP_ROOM_PLAYER1_KICK,
+            UI_MP_ROOM_PLAYER1_EMOTE,
             UI_MP_ROOM_PLAYER2_CONTAINER,
             UI_MP_ROOM_PLAYER2_BG,
             UI_MP_ROOM_PLAYER2_NUMBER,
             UI_MP_ROOM_PLAYER2_NICKNAME,
             UI_MP_ROOM_PLAYER2_KICK,
+            UI_MP_ROOM_PLAYER2_EMOTE,
             UI_MP_ROOM_PLAYER3_CONTAINER,
             UI_MP_ROOM_PLAYER3_BG,
             UI_MP_ROOM_PLAYER3_NUMBER,
             UI_MP_ROOM_PLAYER3_NICKNAME,
             UI_MP_ROOM_PLAYER3_KICK,
+            UI_MP_ROOM_PLAYER3_EMOTE,
             UI_MP_ROOM_PLAYER4_CONTAINER,
             UI_MP_ROOM_PLAYER4_BG,
             UI_MP_ROOM_PLAYER4_NUMBER,
             UI_MP_ROOM_PLAYER4_NICKNAME,
             UI_MP_ROOM_PLAYER4_KICK,
+            UI_MP_ROOM_PLAYER4_EMOTE,
         ]
     }
 
-    fn show(&mut self, _system_data: &mut MenuSystemData) {
+    fn show(&mut self, system_data: &mut MenuSystemData) {
         *self = Self::new();
+        set_class_button_text(system_data, UI_MP_ROOM_CLASS_BUTTON);
     }
 
     fn update(
@@ -107,7 +160,7 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
                         show_confirmation: true,
                     }
                 }
-                ConnectionStatus::Disconnected(disconnect_reason) => {
+                ConnectionStatus::Disconnected(ref disconnect_reason) => {
                     if self.initiated_disconnecting {
                         self.initiated_disconnecting = false;
                         system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
@@ -124,6 +177,17 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
             };
         }
 
+        if !self.has_shown_invite {
+            if let Some(invite) = &system_data.multiplayer_room_state.invite {
+                self.has_shown_invite = true;
+                return StateUpdate::ShowModalWindow {
+                    id: INVITE.to_owned(),
+                    title: format!("Other players can join with {}", invite),
+                    show_confirmation: true,
+                };
+            }
+        }
+
         match (button_pressed, modal_window_id) {
             (Some(UI_MP_ROOM_PLAYER1_KICK), _) => {
                 system_data.ui_network_command.command =
@@ -163,10 +227,90 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
                     }
                 }
             }
+            (Some(UI_MP_ROOM_CLASS_BUTTON), _) => {
+                let class = select_next_class(system_data);
+                set_class_button_text(system_data, UI_MP_ROOM_CLASS_BUTTON);
+                system_data.ui_network_command.command = Some(UiNetworkCommand::SelectClass(class));
+                // Fading the button out and in makes it interactable again.
+                StateUpdate::CustomAnimation {
+                    elements_to_hide: vec![UI_MP_ROOM_CLASS_BUTTON],
+                    elements_to_show: vec![UI_MP_ROOM_CLASS_BUTTON],
+                }
+            }
+            (Some(UI_MP_ROOM_MAX_PLAYERS_BUTTON), _) => {
+                // Skips the values that the server would reject.
+                let players_count = system_data.multiplayer_game_state.players.len().max(1);
+                edit_room_settings(
+                    system_data,
+                    UI_MP_ROOM_MAX_PLAYERS_BUTTON,
+                    |room_settings| {
+                        room_settings.max_players = if room_settings.max_players >= MAX_PLAYERS {
+                            players_count
+                        } else {
+                            room_settings.max_players + 1
+                        };
+                    },
+                )
+            }
+            (Some(UI_MP_ROOM_DIFFICULTY_BUTTON), _) => {
+                edit_room_settings(system_data, UI_MP_ROOM_DIFFICULTY_BUTTON, |room_settings| {
+                    room_settings.difficulty = room_settings.difficulty.next();
+                })
+            }
+            (Some(UI_MP_ROOM_MODE_BUTTON), _) => {
+                edit_room_settings(system_data, UI_MP_ROOM_MODE_BUTTON, |room_settings| {
+                    room_settings.mode = room_settings.mode.next();
+                })
+            }
+            (Some(UI_MP_ROOM_FRIENDLY_FIRE_BUTTON), _) => edit_room_settings(
+                system_data,
+                UI_MP_ROOM_FRIENDLY_FIRE_BUTTON,
+                |room_settings| {
+                    room_settings.friendly_fire = !room_settings.friendly_fire;
+                },
+            ),
+            (Some(UI_MP_ROOM_AIM_ASSIST_BUTTON), _) => {
+                edit_room_settings(system_data, UI_MP_ROOM_AIM_ASSIST_BUTTON, |room_settings| {
+                    room_settings.aim_assist = !room_settings.aim_assist;
+                })
+            }
+            (Some(UI_MP_ROOM_MAP_BUTTON), _) => {
+                edit_room_settings(system_data, UI_MP_ROOM_MAP_BUTTON, |room_settings| {
+                    room_settings.map = room_settings.next_map().to_owned();
+                })
+            }
+            (Some(UI_MP_ROOM_FAST_MONSTERS_BUTTON), _) => edit_room_settings(
+                system_data,
+                UI_MP_ROOM_FAST_MONSTERS_BUTTON,
+                |room_settings| toggle_mutator(&mut room_settings.mutators, Mutator::FastMonsters),
+            ),
+            (Some(UI_MP_ROOM_NO_COOLDOWNS_BUTTON), _) => edit_room_settings(
+                system_data,
+                UI_MP_ROOM_NO_COOLDOWNS_BUTTON,
+                |room_settings| toggle_mutator(&mut room_settings.mutators, Mutator::NoCooldowns),
+            ),
+            (Some(UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON), _) => edit_room_settings(
+                system_data,
+                UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON,
+                |room_settings| toggle_mutator(&mut room_settings.mutators, Mutator::OneHitDeaths),
+            ),
+            (Some(UI_MP_ROOM_VAMPIRISM_BUTTON), _) => {
+                edit_room_settings(system_data, UI_MP_ROOM_VAMPIRISM_BUTTON, |room_settings| {
+                    toggle_mutator(&mut room_settings.mutators, Mutator::Vampirism)
+                })
+            }
             (Some(UI_MP_ROOM_START_BUTTON), _) => {
                 system_data.ui_network_command.command = Some(UiNetworkCommand::Start);
                 StateUpdate::None
             }
+            (Some(UI_MP_ROOM_CAMPAIGN_BUTTON), _) => {
+                system_data.ui_network_command.command = Some(UiNetworkCommand::StartCampaign);
+                StateUpdate::None
+            }
+            (Some(UI_MP_ROOM_RESUME_BUTTON), _) => {
+                system_data.ui_network_command.command = Some(UiNetworkCommand::Resume);
+                StateUpdate::None
+            }
             (Some(UI_MODAL_CONFIRM_BUTTON), Some(DISCONNECTED)) => {
                 system_data.ui_network_command.command = Some(UiNetworkCommand::Reset);
                 StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu)
@@ -176,13 +320,117 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
     }
 }
 
+/// Sends the edited settings to the server, the panel shows them once the server accepts them.
+fn edit_room_settings(
+    system_data: &mut MenuSystemData,
+    button: &'static str,
+    edit: impl FnOnce(&mut RoomSettings),
+) -> StateUpdate {
+    let mut room_settings = system_data.room_settings.clone();
+    edit(&mut room_settings);
+    system_data.ui_network_command.command =
+        Some(UiNetworkCommand::UpdateRoomSettings(room_settings));
+    // Fading the button out and in makes it interactable again.
+    StateUpdate::CustomAnimation {
+        elements_to_hide: vec![button],
+        elements_to_show: vec![button],
+    }
+}
+
 impl MultiplayerRoomMenuScreen {
+    fn update_room_settings(&mut self, system_data: &mut MenuSystemData) {
+        if self.room_settings.as_ref() == Some(&*system_data.room_settings) {
+            return;
+        }
+        let room_settings = system_data.room_settings.clone();
+        let texts = [
+            (
+                UI_MP_ROOM_MAX_PLAYERS_BUTTON,
+                format!("Max players: {}", room_settings.max_players),
+            ),
+            (
+                UI_MP_ROOM_DIFFICULTY_BUTTON,
+                format!("Difficulty: {}", room_settings.difficulty.name()),
+            ),
+            (
+                UI_MP_ROOM_MODE_BUTTON,
+                format!("Mode: {}", room_settings.mode.name()),
+            ),
+            (
+                UI_MP_ROOM_FRIENDLY_FIRE_BUTTON,
+                format!(
+                    "Friendly fire: {}",
+                    if room_settings.friendly_fire {
+                        "On"
+                    } else {
+                        "Off"
+                    }
+                ),
+            ),
+            (
+                UI_MP_ROOM_AIM_ASSIST_BUTTON,
+                format!(
+                    "Aim assist: {}",
+                    if room_settings.aim_assist {
+                        "Allowed"
+                    } else {
+                        "Disallowed"
+                    }
+                ),
+            ),
+            (UI_MP_ROOM_MAP_BUTTON, format!("Map: {}", room_settings.map)),
+        ];
+        let mutator_texts = [
+            (UI_MP_ROOM_FAST_MONSTERS_BUTTON, Mutator::FastMonsters),
+            (UI_MP_ROOM_NO_COOLDOWNS_BUTTON, Mutator::NoCooldowns),
+            (UI_MP_ROOM_ONE_HIT_DEATHS_BUTTON, Mutator::OneHitDeaths),
+            (UI_MP_ROOM_VAMPIRISM_BUTTON, Mutator::Vampirism),
+        ]
+        .iter()
+        .map(|(button, mutator)| {
+            let is_enabled = room_settings.mutators.contains(mutator);
+            let text = format!(
+                "{}: {}",
+                mutator.name(),
+                if is_enabled { "On" } else { "Off" }
+            );
+            (*button, text)
+        })
+        .collect::<Vec<_>>();
+
+        // Players who aren't the host see the settings as a read-only label.
+        let mut settings_lines = texts
+            .iter()
+            .map(|(_, text)| text.clone())
+            .collect::<Vec<_>>();
+        settings_lines.push(format!(
+            "Mutators: {}",
+            mutators_text(&room_settings.mutators)
+        ));
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_MP_ROOM_SETTINGS_LABEL)
+            .expect("Expected a room settings label") = settings_lines.join("\n");
+        for (button, text) in texts.iter().chain(mutator_texts.iter()) {
+            *system_data
+                .ui_finder
+                .get_ui_text_mut(&mut system_data.ui_texts, button)
+                .expect("Expected a room settings button text") = text.clone();
+        }
+        self.room_settings = Some(room_settings);
+    }
+
     fn update_players(&mut self, system_data: &mut MenuSystemData) -> StateUpdate {
         let mut elements_to_hide = Vec::new();
         let mut elements_to_show = Vec::new();
 
-        if self.players != system_data.multiplayer_game_state.players {
+        self.update_room_settings(system_data);
+
+        if self.players != system_data.multiplayer_game_state.players
+            || self.afk_players != system_data.multiplayer_game_state.afk_players
+        {
             self.players = system_data.multiplayer_game_state.players.clone();
+            self.afk_players = system_data.multiplayer_game_state.afk_players.clone();
             #[rustfmt::skip]
             let rows = [
                 (UI_MP_ROOM_PLAYER1_NUMBER, UI_MP_ROOM_PLAYER1_NICKNAME, UI_MP_ROOM_PLAYER1_KICK),
@@ -197,11 +445,24 @@ impl MultiplayerRoomMenuScreen {
                             .ui_finder
                             .get_ui_text_mut(&mut system_data.ui_texts, row.1)
                             .expect("Expected a player nickname text component");
-                        *player_nickname_text = player.nickname.clone();
+                        *player_nickname_text =
+                            format!("{} ({})", player.nickname, player.class.name());
+                        // Hints the host whom to kick.
+                        if system_data.multiplayer_room_state.is_host
+                            && self.afk_players.contains(&player.connection_id)
+                        {
+                            player_nickname_text.push_str(" - AFK");
+                        }
 
                         elements_to_show.push(row.0);
                         elements_to_show.push(row.1);
-                        if system_data.multiplayer_room_state.is_host && !player.is_host {
+                        // Other players start a vote to kick.
+                        let is_own_row = system_data
+                            .multiplayer_room_state
+                            .connection_status
+                            .connection_id()
+                            == Some(player.connection_id);
+                        if !player.is_host && !is_own_row {
                             elements_to_show.push(row.2);
                         }
                     } else {
@@ -213,6 +474,8 @@ impl MultiplayerRoomMenuScreen {
             }
         }
 
+        self.update_emotes(system_data, &mut elements_to_hide, &mut elements_to_show);
+
         if elements_to_hide.is_empty() && elements_to_show.is_empty() {
             StateUpdate::None
         } else {
@@ -222,4 +485,47 @@ impl MultiplayerRoomMenuScreen {
             }
         }
     }
+    fn update_emotes(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        elements_to_hide: &mut Vec<MenuElement>,
+        elements_to_show: &mut Vec<MenuElement>,
+    ) {
+        let emote_labels = [
+            UI_MP_ROOM_PLAYER1_EMOTE,
+            UI_MP_ROOM_PLAYER2_EMOTE,
+            UI_MP_ROOM_PLAYER3_EMOTE,
+            UI_MP_ROOM_PLAYER4_EMOTE,
+        ];
+        let now = system_data
+            .game_time_service
+            .engine_time()
+            .absolute_real_time();
+
+        let incoming = std::mem::replace(&mut system_data.emotes.incoming, Vec::new());
+        for (connection_id, emote) in incoming {
+            let player_index = self
+                .players
+                .iter()
+                .position(|player| player.connection_id == connection_id);
+            if let Some(player_index) = player_index {
+                *system_data
+                    .ui_finder
+                    .get_ui_text_mut(&mut system_data.ui_texts, emote_labels[player_index])
+                    .expect("Expected an emote label") = emote.text().to_owned();
+                self.emotes_shown_at[player_index] = Some(now);
+                elements_to_show.push(emote_labels[player_index]);
+            }
+        }
+
+        for (emote_shown_at, emote_label) in self.emotes_shown_at.iter_mut().zip(&emote_labels) {
+            let has_expired = emote_shown_at.map_or(false, |emote_shown_at| {
+                now - emote_shown_at >= Duration::from_millis(EMOTE_DURATION_MS)
+            });
+            if has_expired {
+                *emote_shown_at = None;
+                elements_to_hide.push(*emote_label);
+            }
+        }
+    }
 }
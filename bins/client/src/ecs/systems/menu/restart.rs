@@ -1,18 +1,49 @@
+use gv_core::mutators::mutators_text;
+
 use super::*;
 
 pub struct RestartMenuScreen;
 
 impl MenuScreen for RestartMenuScreen {
     fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
-        vec![UI_RESTART_BUTTON, UI_MAIN_MENU_BUTTON]
+        vec![
+            UI_RESTART_BUTTON,
+            UI_MAIN_MENU_BUTTON,
+            UI_RESULTS_LABEL,
+            UI_HIGHLIGHTS_LABEL,
+        ]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let mut results = format!(
+            "Score: {}   Kills: {}   Healed: {}   Crits: {}",
+            system_data.match_score.score,
+            system_data.match_score.kills,
+            system_data.match_score.healing_done.round(),
+            system_data.match_score.critical_hits,
+        );
+        // Scores of games with mutators aren't comparable to the usual ones.
+        if !system_data.room_settings.mutators.is_empty() {
+            results.push_str(&format!(
+                "\nMutators: {}",
+                mutators_text(&system_data.room_settings.mutators)
+            ));
+        }
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_RESULTS_LABEL)
+            .expect("Expected a results label") = results;
+        set_highlights_text(system_data);
     }
 
     fn update(
         &mut self,
-        _system_data: &mut MenuSystemData,
+        system_data: &mut MenuSystemData,
         button_pressed: Option<&str>,
         _modal_window_id: Option<&str>,
     ) -> StateUpdate {
+        // Highlights waiting for their aftermath are saved once the game is over.
+        set_highlights_text(system_data);
         match button_pressed {
             Some(UI_RESTART_BUTTON) => StateUpdate::new_game_engine_state(GameEngineState::Playing),
             Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
@@ -20,3 +51,23 @@ impl MenuScreen for RestartMenuScreen {
         }
     }
 }
+
+fn set_highlights_text(system_data: &mut MenuSystemData) {
+    let highlights = if system_data.highlights.saved.is_empty() {
+        String::new()
+    } else {
+        let mut highlights = "Highlights:".to_owned();
+        for (title, path) in &system_data.highlights.saved {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            highlights.push_str(&format!("\n{} - {}", title, file_name));
+        }
+        highlights
+    };
+    let ui_highlights_text = system_data
+        .ui_finder
+        .get_ui_text_mut(&mut system_data.ui_texts, UI_HIGHLIGHTS_LABEL)
+        .expect("Expected a highlights label");
+    if *ui_highlights_text != highlights {
+        *ui_highlights_text = highlights;
+    }
+}
@@ -0,0 +1,229 @@
+use amethyst::{
+    assets::{Handle, Prefab},
+    core::{Time, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, System, WriteStorage},
+    renderer::{Camera, SpriteRender},
+    window::ScreenDimensions,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_animation_prefabs::GameSpriteAnimationPrefab;
+use gv_client_shared::{
+    ecs::{
+        components::{MenuDemoActor, MenuDemoEntity, MenuDemoMissile, PlayerColor},
+        resources::AssetHandles,
+    },
+    settings::Settings,
+};
+use gv_core::{
+    classes::PlayerClass,
+    demo::{DemoActor, DemoSkirmish},
+    ecs::resources::GameEngineState,
+    math::Vector2,
+    PLAYER_COLORS,
+};
+
+const DEMO_CLASSES: [PlayerClass; 3] = [
+    PlayerClass::Mage,
+    PlayerClass::Warden,
+    PlayerClass::Trickster,
+];
+
+/// Plays a `DemoSkirmish` behind the main menu. It isn't a part of the game world: its entities
+/// only have what's needed to draw and animate them and are deleted once the game leaves
+/// the menu or the background is turned off in the video settings.
+#[derive(Default)]
+pub struct MenuDemoSystem {
+    skirmish: Option<DemoSkirmish>,
+    entities: HashMap<u64, Entity>,
+}
+
+impl<'s> System<'s> for MenuDemoSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, Time>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, ScreenDimensions>,
+        Option<Read<'s, AssetHandles>>,
+        WriteStorage<'s, MenuDemoEntity>,
+        WriteStorage<'s, MenuDemoActor>,
+        WriteStorage<'s, MenuDemoMissile>,
+        WriteStorage<'s, Handle<Prefab<GameSpriteAnimationPrefab>>>,
+        WriteStorage<'s, PlayerColor>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, system_data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            game_engine_state,
+            settings,
+            screen_dimensions,
+            asset_handles,
+            mut menu_demo_entities,
+            mut menu_demo_actors,
+            mut menu_demo_missiles,
+            mut sprite_animation_handles,
+            mut player_colors,
+            mut cameras,
+            mut sprite_renders,
+            mut transforms,
+        ) = system_data;
+
+        let is_active = *game_engine_state == GameEngineState::Menu
+            && settings.video().menu_background
+            && asset_handles.is_some();
+        if !is_active {
+            if self.skirmish.take().is_some() {
+                // The entities may have already been deleted by `PlayingState`.
+                for (entity, _) in (&entities, &menu_demo_entities).join() {
+                    let _ = entities.delete(entity);
+                }
+                self.entities.clear();
+            }
+            return;
+        }
+        let asset_handles = asset_handles.unwrap();
+
+        if self.skirmish.is_none() {
+            let (width, height) = (screen_dimensions.width(), screen_dimensions.height());
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs());
+            self.skirmish = Some(DemoSkirmish::new(
+                seed,
+                Vector2::new(width / 2.0, height / 2.0),
+            ));
+
+            let mut transform = Transform::default();
+            transform.set_translation_z(100.0);
+            entities
+                .build_entity()
+                .with(MenuDemoEntity, &mut menu_demo_entities)
+                .with(Camera::standard_2d(width, height), &mut cameras)
+                .with(transform, &mut transforms)
+                .build();
+
+            let mut transform = Transform::default();
+            transform.set_translation_z(-1.0);
+            entities
+                .build_entity()
+                .with(MenuDemoEntity, &mut menu_demo_entities)
+                .with(
+                    SpriteRender {
+                        sprite_sheet: asset_handles.landscape.clone(),
+                        sprite_number: 0,
+                    },
+                    &mut sprite_renders,
+                )
+                .with(transform, &mut transforms)
+                .build();
+        }
+        let skirmish = self.skirmish.as_mut().unwrap();
+        skirmish.step(time.delta_seconds());
+
+        let palette = settings.accessibility().palette;
+        let mut alive_ids = HashSet::new();
+        let actors = skirmish
+            .bots()
+            .iter()
+            .enumerate()
+            .map(|(i, bot)| (bot, Some(i)))
+            .chain(skirmish.monsters().iter().map(|monster| (monster, None)));
+        for (actor, bot_index) in actors {
+            let DemoActor {
+                id,
+                position,
+                velocity,
+                looking_direction,
+                ..
+            } = *actor;
+            alive_ids.insert(id);
+            let entity = *self.entities.entry(id).or_insert_with(|| {
+                let builder = entities
+                    .build_entity()
+                    .with(MenuDemoEntity, &mut menu_demo_entities)
+                    .with(Transform::default(), &mut transforms);
+                match bot_index {
+                    Some(i) => builder
+                        .with(
+                            asset_handles.mage_prefab.clone(),
+                            &mut sprite_animation_handles,
+                        )
+                        // Bots are painted by PaintMagePlugin, like players.
+                        .with(
+                            PlayerColor(
+                                palette.player_color(i, PLAYER_COLORS[i % PLAYER_COLORS.len()]),
+                            ),
+                            &mut player_colors,
+                        )
+                        .build(),
+                    None => builder
+                        .with(
+                            asset_handles.beetle_prefab.clone(),
+                            &mut sprite_animation_handles,
+                        )
+                        .build(),
+                }
+            });
+
+            let transform = transforms
+                .get_mut(entity)
+                .expect("Expected a Transform for a menu demo actor");
+            transform.set_translation_xyz(position.x, position.y, 5.0);
+            menu_demo_actors
+                .insert(
+                    entity,
+                    MenuDemoActor {
+                        velocity,
+                        looking_direction,
+                        is_monster: bot_index.is_none(),
+                        class: bot_index.map(|i| DEMO_CLASSES[i % DEMO_CLASSES.len()]),
+                    },
+                )
+                .expect("Expected to insert a MenuDemoActor");
+        }
+
+        for missile in skirmish.missiles() {
+            alive_ids.insert(missile.id);
+            let entity = *self.entities.entry(missile.id).or_insert_with(|| {
+                entities
+                    .build_entity()
+                    .with(MenuDemoEntity, &mut menu_demo_entities)
+                    .with(Transform::default(), &mut transforms)
+                    .build()
+            });
+
+            let transform = transforms
+                .get_mut(entity)
+                .expect("Expected a Transform for a menu demo missile");
+            transform.set_translation_xyz(missile.position.x, missile.position.y, 5.0);
+            menu_demo_missiles
+                .insert(
+                    entity,
+                    MenuDemoMissile {
+                        age_secs: missile.age,
+                    },
+                )
+                .expect("Expected to insert a MenuDemoMissile");
+        }
+
+        self.entities.retain(|id, entity| {
+            let is_alive = alive_ids.contains(id);
+            if !is_alive {
+                entities
+                    .delete(*entity)
+                    .expect("Expected to delete a menu demo entity");
+            }
+            is_alive
+        });
+    }
+}
@@ -0,0 +1,74 @@
+use amethyst::{
+    core::{Parent, Transform},
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::SpriteRender,
+};
+
+use gv_client_shared::ecs::{components::PlayerColor, resources::AssetHandles};
+use gv_core::ecs::components::Allegiance;
+
+const MARKER_OFFSET_Y: f32 = -12.0;
+
+/// Paints minions in the colors of their owners and puts a ring marker under them.
+pub struct MinionMarkerSystem;
+
+impl<'s> System<'s> for MinionMarkerSystem {
+    type SystemData = (
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        ReadStorage<'s, Allegiance>,
+        WriteStorage<'s, PlayerColor>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Parent>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            asset_handles,
+            entities,
+            allegiances,
+            mut player_colors,
+            mut sprite_renders,
+            mut transforms,
+            mut parents,
+        ): Self::SystemData,
+    ) {
+        let sprite_sheet = match asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.minion_marker.clone(),
+            None => return,
+        };
+
+        let unmarked_minions = (&entities, &allegiances, !&player_colors)
+            .join()
+            .filter_map(|(minion, allegiance, _)| {
+                player_colors
+                    .get(allegiance.owner)
+                    .map(|owner_color| (minion, owner_color.0))
+            })
+            .collect::<Vec<_>>();
+        for (minion, color) in unmarked_minions {
+            // Both the body and the marker are drawn by PaintMagePlugin,
+            // as their parent has a PlayerColor.
+            player_colors
+                .insert(minion, PlayerColor(color))
+                .expect("Expected to insert a PlayerColor");
+
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(0.0, MARKER_OFFSET_Y, -0.1);
+            entities
+                .build_entity()
+                .with(
+                    SpriteRender {
+                        sprite_sheet: sprite_sheet.clone(),
+                        sprite_number: 0,
+                    },
+                    &mut sprite_renders,
+                )
+                .with(transform, &mut transforms)
+                .with(Parent::new(minion), &mut parents)
+                .build();
+        }
+    }
+}
@@ -1,25 +1,113 @@
+mod achievements;
 mod animation;
-mod camera_translation;
+mod announcer;
+mod auras;
+mod camera;
 mod client_network;
+mod clip_capture;
+mod clock_sync;
+mod combat_numbers;
+mod corpses;
+mod culling;
 mod custom_sprite_sorting;
+mod desync_report;
+mod elites;
+mod emotes;
+mod event_ticker;
+mod frame_pacing;
 mod game_updates_broadcasting;
+mod highlights;
+mod hints;
+mod hit_markers;
 mod hud;
+mod imgui_console;
+mod imgui_entity_inspector;
+mod imgui_frame_timings;
 mod imgui_network_debug_info;
+#[cfg(feature = "inspector")]
+mod imgui_world_inspector;
 mod input;
+mod level_prefetch;
+mod level_scripts;
+mod lighting;
 mod menu;
+mod menu_demo;
+mod minions;
+mod monster_sprite_batching;
+mod music;
 mod overlay;
 mod particle;
+mod personal_bests;
+mod post_processing;
+mod presence;
+mod reconciliation;
+mod render_scale;
+mod screenshot;
+mod spawn_telegraphs;
+mod spectator_camera;
+mod time_scale;
+mod ui_scale;
+mod ui_theme;
+mod votes;
+mod window_focus;
+mod zones;
 
 pub use self::{
+    achievements::AchievementSystem,
     animation::AnimationSystem,
-    camera_translation::CameraTranslationSystem,
+    announcer::AnnouncerSystem,
+    auras::AuraRingSystem,
+    camera::CameraSystem,
     client_network::ClientNetworkSystem,
+    clip_capture::ClipCaptureSystem,
+    clock_sync::ClockSyncSystem,
+    combat_numbers::CombatNumberSystem,
+    corpses::CorpseSystem,
+    culling::CullingSystem,
     custom_sprite_sorting::{CustomSpriteSortingSystem, SpriteOrdering},
+    desync_report::DesyncReportSystem,
+    elites::EliteNameplateSystem,
+    emotes::EmoteSystem,
+    event_ticker::EventTickerSystem,
+    frame_pacing::FramePacingSystem,
     game_updates_broadcasting::GameUpdatesBroadcastingSystem,
-    hud::HealthUiSystem,
+    highlights::{HighlightSystem, HIGHLIGHT_WINDOW_FRAMES},
+    hints::HintSystem,
+    hit_markers::HitMarkerSystem,
+    hud::{
+        AugmentsUiSystem, BoundsVignetteSystem, CharacterPanelUiSystem, ConnectionWarningUiSystem,
+        HealthUiSystem, ObjectiveUiSystem, PayloadUiSystem, ScoreUiSystem, ShopUiSystem,
+    },
+    imgui_console::{register_client_console_commands, ImguiConsoleSystem},
+    imgui_entity_inspector::ImguiEntityInspectorSystem,
+    imgui_frame_timings::ImguiFrameTimingsSystem,
     imgui_network_debug_info::ImguiNetworkDebugInfoSystem,
     input::InputSystem,
+    level_prefetch::LevelPrefetchSystem,
+    level_scripts::LevelScriptMarkerSystem,
+    lighting::LightingSystem,
     menu::MenuSystem,
+    menu_demo::MenuDemoSystem,
+    minions::MinionMarkerSystem,
+    monster_sprite_batching::{MonsterSpriteBatchingSystem, MonsterSprites},
+    music::MusicSystem,
     overlay::OverlaySystem,
     particle::ParticleSystem,
+    personal_bests::PersonalBestsSystem,
+    post_processing::PostProcessingSystem,
+    presence::PresenceSystem,
+    reconciliation::ReconciliationSystem,
+    render_scale::RenderScaleSystem,
+    screenshot::ScreenshotSystem,
+    spawn_telegraphs::SpawnTelegraphSystem,
+    spectator_camera::SpectatorCameraSystem,
+    time_scale::TimeScaleSystem,
+    ui_scale::UiScaleSystem,
+    ui_theme::UiThemeSystem,
+    votes::VoteSystem,
+    window_focus::WindowFocusSystem,
+    zones::ZoneDecalSystem,
 };
+
+#[cfg(feature = "inspector")]
+pub use self::imgui_world_inspector::ImguiWorldInspectorSystem;
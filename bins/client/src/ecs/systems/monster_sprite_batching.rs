@@ -0,0 +1,58 @@
+use amethyst::{
+    core::Parent,
+    ecs::{BitSet, Entities, Entity, Join, ReadStorage, System, Write},
+    renderer::sprite_visibility::SpriteVisibility,
+};
+
+use gv_core::ecs::components::Monster;
+
+/// Visible sprites of monsters, `MonsterSpritesPlugin` draws them.
+#[derive(Default, Debug)]
+pub struct MonsterSprites(pub BitSet);
+
+/// Moves the body parts of monsters out of `SpriteVisibility` into `MonsterSprites`, so that
+/// they are drawn in an instanced batch per sprite sheet instead of by `RenderFlat2D`, which
+/// starts a new batch whenever the sprite sheet changes between sorted sprites.
+///
+/// Has to run after `SpriteVisibilitySortingSystem`.
+#[derive(Default)]
+pub struct MonsterSpriteBatchingSystem;
+
+impl<'s> System<'s> for MonsterSpriteBatchingSystem {
+    type SystemData = (
+        Entities<'s>,
+        Write<'s, SpriteVisibility>,
+        Write<'s, MonsterSprites>,
+        ReadStorage<'s, Parent>,
+        ReadStorage<'s, Monster>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut sprite_visibility, mut monster_sprites, parents, monsters): Self::SystemData,
+    ) {
+        monster_sprites.0.clear();
+        let is_monster_sprite = |entity: Entity| {
+            parents
+                .get(entity)
+                .map_or(false, |parent| monsters.contains(parent.entity))
+        };
+
+        for (entity, parent) in (&entities, &parents).join() {
+            if monsters.contains(parent.entity)
+                && sprite_visibility.visible_unordered.remove(entity.id())
+            {
+                monster_sprites.0.add(entity.id());
+            }
+        }
+        let monster_sprites = &mut monster_sprites.0;
+        sprite_visibility.visible_ordered.retain(|entity| {
+            if is_monster_sprite(*entity) {
+                monster_sprites.add(entity.id());
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::{output::Output, AudioSink, Source},
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System},
+};
+
+use gv_client_shared::{
+    ecs::resources::AssetHandles,
+    music::{MusicIntensity, MusicStem, NEARBY_MONSTER_RADIUS},
+    settings::Settings,
+};
+use gv_core::ecs::{
+    components::{
+        affixes::MonsterAffixes, Allegiance, ClientPlayerActions, Dead, Monster, Player,
+        WorldPosition,
+    },
+    resources::GameEngineState,
+    system_data::time::GameTimeService,
+};
+use gv_game::utils::entities::is_dead;
+
+use crate::ecs::resources::WindowFocus;
+
+/// How long it takes a stem to fade in or out completely.
+const CROSSFADE_SECS: f32 = 2.0;
+
+/// Plays the music stems in sync and crossfades them by the intensity around the local player:
+/// the number of monsters nearby, whether an elite is among them and the player's health.
+/// The calm stem plays alone in the menus and while spectating.
+#[derive(Default)]
+pub struct MusicSystem {
+    /// Are created once there's an audio output, in the order of `MusicStem::ALL`.
+    sinks: Vec<AudioSink>,
+    volumes: [f32; 3],
+}
+
+impl<'s> System<'s> for MusicSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, WindowFocus>,
+        Option<Read<'s, AssetHandles>>,
+        Option<Read<'s, Output>>,
+        Read<'s, AssetStorage<Source>>,
+        Entities<'s>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, MonsterAffixes>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Dead>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            settings,
+            window_focus,
+            asset_handles,
+            output,
+            source_storage,
+            entities,
+            client_player_actions,
+            players,
+            monsters,
+            monster_affixes,
+            allegiances,
+            world_positions,
+            dead,
+        ): Self::SystemData,
+    ) {
+        let (asset_handles, output) = match (asset_handles, output) {
+            (Some(asset_handles), Some(output)) => (asset_handles, output),
+            _ => return,
+        };
+        let sources = match asset_handles
+            .music
+            .iter()
+            .map(|handle| source_storage.get(handle))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(sources) => sources,
+            None => return,
+        };
+        if self.sinks.is_empty() {
+            self.sinks = MusicStem::ALL
+                .iter()
+                .map(|_| AudioSink::new(&output))
+                .collect();
+        }
+        // The stems are equally long, so appending them together keeps them in sync.
+        if self.sinks.iter().all(AudioSink::empty) {
+            for (sink, source) in self.sinks.iter().zip(sources) {
+                if let Err(err) = sink.append(source) {
+                    log::error!("Failed to play a music stem: {:?}", err);
+                }
+            }
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let local_player = (
+            &entities,
+            &client_player_actions,
+            &players,
+            &world_positions,
+        )
+            .join()
+            .next();
+        let intensity = match local_player {
+            Some((_, _, player, player_position))
+                if *game_engine_state == GameEngineState::Playing =>
+            {
+                let mut nearby_monsters = 0;
+                let mut is_boss_active = false;
+                for (monster, _, monster_position, affixes, _) in (
+                    &entities,
+                    &monsters,
+                    &world_positions,
+                    monster_affixes.maybe(),
+                    !&allegiances,
+                )
+                    .join()
+                {
+                    let distance_squared = (**monster_position - **player_position).norm_squared();
+                    if is_dead(monster, &dead, frame_number)
+                        || distance_squared > NEARBY_MONSTER_RADIUS * NEARBY_MONSTER_RADIUS
+                    {
+                        continue;
+                    }
+                    nearby_monsters += 1;
+                    is_boss_active |= affixes.map_or(false, |affixes| !affixes.affixes.is_empty());
+                }
+                MusicIntensity::new(
+                    nearby_monsters,
+                    is_boss_active,
+                    player.health / player.max_health,
+                )
+            }
+            _ => MusicIntensity::default(),
+        };
+
+        let max_step = game_time_service.engine_time().delta_seconds() / CROSSFADE_SECS;
+        // Muted stems keep playing, so the music stays in sync when the window is focused again.
+        let music_volume = if window_focus.is_muted {
+            0.0
+        } else {
+            settings.audio().music_volume
+        };
+        for ((sink, volume), target_volume) in self
+            .sinks
+            .iter()
+            .zip(self.volumes.iter_mut())
+            .zip(intensity.volumes().iter())
+        {
+            *volume += (target_volume - *volume).max(-max_step).min(max_step);
+            sink.set_volume(*volume * music_volume);
+        }
+    }
+}
@@ -1,34 +1,62 @@
 use amethyst::{
     core::HiddenPropagate,
-    ecs::{ReadExpect, System, WriteStorage},
+    ecs::{ReadExpect, System, WriteExpect, WriteStorage},
+    input::{InputHandler, StringBindings},
     ui::UiText,
 };
 
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
 use gv_core::ecs::resources::net::MultiplayerGameState;
 
-use crate::ecs::system_data::ui::UiFinderMut;
+use crate::ecs::{
+    resources::{DisplayDebugInfoSettings, UiNetworkCommand, UiNetworkCommandResource},
+    system_data::ui::UiFinderMut,
+};
 
-pub struct OverlaySystem;
+/// Shows whom the game is paused for, and lets the host drop the player who lags the most.
+#[derive(Default)]
+pub struct OverlaySystem {
+    is_drop_hotkey_pressed: bool,
+}
 
 const UI_WAITING_FOR_PLAYERS_BORDER_CONTAINER: &str = "ui_waiting_for_players_border_container";
 const UI_WAITING_FOR_PLAYERS_ROW_1: &str = "ui_waiting_for_players_row_1";
 const UI_WAITING_FOR_PLAYERS_ROW_2: &str = "ui_waiting_for_players_row_2";
 const UI_WAITING_FOR_PLAYERS_ROW_3: &str = "ui_waiting_for_players_row_3";
 const UI_WAITING_FOR_PLAYERS_ROW_4: &str = "ui_waiting_for_players_row_4";
+const UI_WAITING_FOR_PLAYERS_HINT: &str = "ui_waiting_for_players_hint";
 const UI_WAITING_FOR_NETWORK_BORDER_CONTAINER: &str = "ui_waiting_for_network_border_container";
 
 impl<'s> System<'s> for OverlaySystem {
     type SystemData = (
         UiFinderMut<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, MultiplayerRoomState>,
         ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, UiNetworkCommandResource>,
         WriteStorage<'s, UiText>,
         WriteStorage<'s, HiddenPropagate>,
     );
 
     fn run(
         &mut self,
-        (mut ui_finder, multiplayer_game_state, mut ui_texts, mut hidden_propagates): Self::SystemData,
+        (
+            mut ui_finder,
+            input,
+            display_debug_info_settings,
+            multiplayer_room_state,
+            multiplayer_game_state,
+            mut ui_network_command,
+            mut ui_texts,
+            mut hidden_propagates,
+        ): Self::SystemData,
     ) {
+        let is_down = input
+            .action_is_down("drop_lagging_player")
+            .unwrap_or_default();
+        let was_down = std::mem::replace(&mut self.is_drop_hotkey_pressed, is_down);
+
         if !multiplayer_game_state.is_playing {
             return;
         }
@@ -68,14 +96,14 @@ impl<'s> System<'s> for OverlaySystem {
                     let player_name_text = ui_finder
                         .get_ui_text_mut(&mut ui_texts, ui_id)
                         .unwrap_or_else(|| panic!("Expected {} ui element", ui_id));
+                    let lagging_player = &lagging_players[lagging_player_index];
                     let player_name = multiplayer_game_state
-                        .find_player_by_connection_id(
-                            multiplayer_game_state.lagging_players[lagging_player_index],
-                        )
-                        .expect("Expected to find a lagging player")
-                        .nickname
-                        .clone();
-                    *player_name_text = player_name;
+                        .find_player_by_connection_id(lagging_player.connection_id)
+                        .map_or("Unknown player", |player| player.nickname.as_str());
+                    *player_name_text = format!(
+                        "{} - {} frames behind",
+                        player_name, lagging_player.frames_behind
+                    );
                 } else {
                     hidden_propagates
                         .insert(player_text_entity, HiddenPropagate::new())
@@ -87,6 +115,34 @@ impl<'s> System<'s> for OverlaySystem {
             update_name(UI_WAITING_FOR_PLAYERS_ROW_2, 1);
             update_name(UI_WAITING_FOR_PLAYERS_ROW_3, 2);
             update_name(UI_WAITING_FOR_PLAYERS_ROW_4, 3);
+
+            // Only the host can drop players, and it can't drop itself.
+            let own_connection_id = multiplayer_room_state.connection_status.connection_id();
+            let droppable_player = lagging_players
+                .iter()
+                .filter(|lagging_player| Some(lagging_player.connection_id) != own_connection_id)
+                .max_by_key(|lagging_player| lagging_player.frames_behind)
+                .and_then(|lagging_player| {
+                    multiplayer_game_state
+                        .players
+                        .iter()
+                        .position(|player| player.connection_id == lagging_player.connection_id)
+                })
+                .filter(|_| multiplayer_room_state.is_host);
+            let hint = ui_finder
+                .get_ui_text_mut(&mut ui_texts, UI_WAITING_FOR_PLAYERS_HINT)
+                .expect("Expected ui_waiting_for_players_hint ui element");
+            *hint = droppable_player.map_or_else(String::new, |player_number| {
+                format!(
+                    "F7 - drop {}",
+                    multiplayer_game_state.players[player_number].nickname
+                )
+            });
+            if let Some(player_number) = droppable_player {
+                if is_down && !was_down && !display_debug_info_settings.display_console {
+                    ui_network_command.command = Some(UiNetworkCommand::Kick { player_number });
+                }
+            }
         } else {
             hidden_propagates
                 .insert(
@@ -1,6 +1,6 @@
 use amethyst::{
     core::{math::Rotation2, Transform},
-    ecs::{Entities, Join, ReadStorage, System, WriteStorage},
+    ecs::{Entities, Entity, Join, ReadStorage, System, WriteStorage},
 };
 use rand::{self, Rng};
 
@@ -10,18 +10,29 @@ use gv_client_shared::ecs::components::SpellParticle;
 use gv_core::{
     ecs::{
         components::{missile::Missile, Dead, WorldPosition},
+        resources::pool::EntityPool,
         system_data::time::GameTimeService,
     },
     math::{Vector2, Vector3, ZeroVector},
 };
-use gv_game::{
-    ecs::systems::missile::{MISSILE_MAX_SPEED, MISSILE_MIN_SPEED},
-    utils::entities::{is_dead, missile_energy},
-};
+use gv_game::utils::entities::{is_dead, missile_energy};
 
 const PARTICLE_SPEED: f32 = 230.0;
 
-pub struct ParticleSystem;
+pub struct ParticleSystem {
+    // Particles aren't rolled back, so they can be reused right away.
+    particle_pool: EntityPool<SpellParticle>,
+    expired_particles: Vec<Entity>,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self {
+            particle_pool: EntityPool::new(0),
+            expired_particles: Vec::new(),
+        }
+    }
+}
 
 impl<'s> System<'s> for ParticleSystem {
     type SystemData = (
@@ -73,8 +84,13 @@ impl<'s> System<'s> for ParticleSystem {
                 Rotation2::new(angle) * Vector2::new(0.0, 0.5) * PARTICLE_SPEED
             } else {
                 let min_rotation = PI / 6.0;
-                let speed_multiplier = 1.0
-                    - (missile_speed - MISSILE_MIN_SPEED) / (MISSILE_MAX_SPEED - MISSILE_MIN_SPEED);
+                // Spells can fly with a constant speed.
+                let speed_range = missile.max_speed - missile.min_speed;
+                let speed_multiplier = if speed_range > 0.0 {
+                    1.0 - (missile_speed - missile.min_speed) / speed_range
+                } else {
+                    0.0
+                };
                 let possible_rotation = min_rotation + speed_multiplier * (PI * 0.8 - min_rotation);
                 let angle = rng.gen_range(0.0, possible_rotation) - possible_rotation / 2.0;
                 Rotation2::new(PI + angle) * missile.velocity.normalize() * PARTICLE_SPEED
@@ -85,28 +101,28 @@ impl<'s> System<'s> for ParticleSystem {
             } else {
                 missile.velocity
             };
-            entities
-                .build_entity()
-                .with(
+            let particle_entity = self.particle_pool.acquire(&entities, frame_number);
+            spell_particles
+                .insert(
+                    particle_entity,
                     SpellParticle {
                         inertia,
                         velocity: particle_velocity,
                         missile_energy,
                         frame_spawned: frame_number,
                     },
-                    &mut spell_particles,
                 )
-                .with(transform, &mut transforms)
-                .build();
+                .expect("Expected to insert a SpellParticle");
+            transforms
+                .insert(particle_entity, transform)
+                .expect("Expected to insert a Transform");
         }
 
         for (spell_particle_entity, spell_particle, particle_transform) in
             (&entities, &spell_particles, &mut transforms).join()
         {
             if game_time_service.seconds_to_frame(spell_particle.frame_spawned) > 0.25 {
-                entities
-                    .delete(spell_particle_entity)
-                    .expect("Expected to delete SpellParticle");
+                self.expired_particles.push(spell_particle_entity);
             } else {
                 let displacement = (spell_particle.velocity + spell_particle.inertia)
                     * game_time_service.engine_time().fixed_seconds();
@@ -114,5 +130,12 @@ impl<'s> System<'s> for ParticleSystem {
                     Vector3::new(displacement.x, displacement.y, 0.0);
             }
         }
+
+        for spell_particle_entity in self.expired_particles.drain(..) {
+            spell_particles.remove(spell_particle_entity);
+            transforms.remove(spell_particle_entity);
+            self.particle_pool
+                .release(spell_particle_entity, frame_number);
+        }
     }
 }
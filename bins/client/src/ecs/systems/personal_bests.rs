@@ -0,0 +1,90 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect};
+
+use gv_client_shared::settings::{rating_after_match, Settings};
+use gv_core::ecs::{
+    components::{Dead, Player},
+    resources::{net::MultiplayerGameState, GameEngineState, GameLevelState, MatchScore},
+    system_data::time::GameTimeService,
+};
+use gv_game::ecs::resources::ActiveObjectives;
+
+/// Saves the score of an endless game into the player profile once all the players are dead
+/// or the game is left, multiplayer games also update the matchmaking rating.
+#[derive(Default)]
+pub struct PersonalBestsSystem {
+    last_score: Option<(&'static str, u64)>,
+    /// The wave a multiplayer game has reached.
+    last_spawn_level: Option<usize>,
+}
+
+impl<'s> System<'s> for PersonalBestsSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, MatchScore>,
+        ReadExpect<'s, ActiveObjectives>,
+        WriteExpect<'s, Settings>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            game_level_state,
+            multiplayer_game_state,
+            match_score,
+            active_objectives,
+            mut settings,
+            entities,
+            players,
+            dead,
+        ): Self::SystemData,
+    ) {
+        let frame_number = game_time_service.game_frame_number();
+        let some_player_is_alive = (&entities, &players).join().any(|(entity, _)| {
+            !dead
+                .get(entity)
+                .map_or(false, |dead| dead.is_dead(frame_number))
+        });
+        // Campaign levels and the tutorial don't count.
+        let is_tutorial = !multiplayer_game_state.is_playing && active_objectives.0.is_some();
+        if game_engine_state.is_playing()
+            && multiplayer_game_state.campaign_level.is_none()
+            && !is_tutorial
+            && some_player_is_alive
+        {
+            self.last_score = Some((game_level_state.map_name, match_score.score));
+            self.last_spawn_level = if multiplayer_game_state.is_playing {
+                Some(game_level_state.spawn_level)
+            } else {
+                None
+            };
+            return;
+        }
+
+        if let Some((map_name, score)) = self.last_score.take() {
+            match settings.save_personal_best(map_name, score) {
+                Ok(true) => log::info!("New personal best on {}: {}", map_name, score),
+                Ok(false) => {}
+                Err(err) => log::error!("Failed to save the personal best: {:?}", err),
+            }
+        }
+        if let Some(spawn_level) = self.last_spawn_level.take() {
+            let rating = rating_after_match(settings.profile().rating, spawn_level);
+            log::info!(
+                "The rating is {} after reaching the wave {}",
+                rating,
+                spawn_level
+            );
+            if let Err(err) = settings.save_rating(rating) {
+                log::error!("Failed to save the rating: {:?}", err);
+            }
+        }
+    }
+}
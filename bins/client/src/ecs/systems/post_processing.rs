@@ -0,0 +1,82 @@
+use amethyst::{
+    core::Time,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, WriteExpect},
+};
+
+use gv_client_shared::{
+    ecs::resources::LevelAssets,
+    post_processing::{low_health_tint, HitFlash},
+    settings::Settings,
+};
+use gv_core::ecs::{
+    components::{ClientPlayerActions, Player},
+    resources::GameEngineState,
+};
+
+use crate::ecs::resources::PostProcessing;
+
+const BLOOM_STRENGTH: f32 = 0.6;
+
+/// Picks the effect strengths for `PostProcessing` from the video settings, the health
+/// of the local player and the color grading of the current level.
+#[derive(Default)]
+pub struct PostProcessingSystem {
+    hit_flash: HitFlash,
+}
+
+impl<'s> System<'s> for PostProcessingSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, Settings>,
+        Read<'s, LevelAssets>,
+        ReadStorage<'s, ClientPlayerActions>,
+        ReadStorage<'s, Player>,
+        WriteExpect<'s, PostProcessing>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            time,
+            game_engine_state,
+            settings,
+            level_assets,
+            client_player_actions,
+            players,
+            mut post_processing,
+        ): Self::SystemData,
+    ) {
+        let settings = &settings.video().post_processing;
+        let is_playing = *game_engine_state == GameEngineState::Playing;
+        let local_player = (&client_player_actions, &players)
+            .join()
+            .next()
+            .filter(|_| is_playing)
+            .map(|(_, player)| player);
+
+        let hit_flash = self.hit_flash.update(
+            local_player.map(|player| player.health),
+            time.delta_real_seconds(),
+        );
+        post_processing.bloom = if settings.bloom { BLOOM_STRENGTH } else { 0.0 };
+        post_processing.chromatic_aberration = if settings.chromatic_aberration {
+            hit_flash
+        } else {
+            0.0
+        };
+        post_processing.low_health_tint = match local_player {
+            Some(player) if settings.low_health_tint => {
+                low_health_tint(player.health, player.max_health)
+            }
+            _ => 0.0,
+        };
+
+        // The loaded level is the one being played, its lookup table is picked by its script.
+        post_processing.color_grading_lut = level_assets
+            .0
+            .as_ref()
+            .filter(|_| is_playing && settings.color_grading)
+            .map(|level_asset_handles| level_asset_handles.color_grading_lut.clone());
+    }
+}
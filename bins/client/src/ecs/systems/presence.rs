@@ -0,0 +1,94 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+
+use std::time::{Duration, Instant};
+
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_core::{
+    ecs::{
+        resources::{net::MultiplayerGameState, GameEngineState},
+        system_data::time::GameTimeService,
+    },
+    room_settings::RoomSettings,
+};
+
+use crate::presence::{create_publishers, Presence, PresencePublisher};
+
+/// Both Discord and Steam throttle presence updates.
+const MIN_PUBLISH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Publishes presence to the integrations enabled with cargo features and prefills
+/// the join address with accepted invites. Is thread-local, as the Steam client isn't `Send`.
+pub struct PresenceSystem {
+    publishers: Vec<Box<dyn PresencePublisher>>,
+    published_presence: Option<Presence>,
+    published_at: Option<Instant>,
+}
+
+impl PresenceSystem {
+    pub fn new() -> Self {
+        Self {
+            publishers: create_publishers(),
+            published_presence: None,
+            published_at: None,
+        }
+    }
+}
+
+impl<'s> System<'s> for PresenceSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, RoomSettings>,
+        WriteExpect<'s, MultiplayerRoomState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            multiplayer_game_state,
+            room_settings,
+            mut multiplayer_room_state,
+        ): Self::SystemData,
+    ) {
+        if self.publishers.is_empty() {
+            return;
+        }
+
+        for publisher in &mut self.publishers {
+            if let Some(server_addr) = publisher.poll_join_request() {
+                multiplayer_room_state.server_addr = Some(server_addr);
+            }
+        }
+
+        let presence = match *game_engine_state {
+            GameEngineState::Playing => Presence::InMatch {
+                players: multiplayer_game_state.players.len().max(1),
+                minutes: game_time_service.level_duration().as_secs() / 60,
+            },
+            _ if multiplayer_room_state.is_active => Presence::InLobby {
+                players: multiplayer_game_state.players.len(),
+                max_players: room_settings.max_players,
+                invite: multiplayer_room_state.invite.clone(),
+            },
+            _ => Presence::InMenu,
+        };
+
+        let now = Instant::now();
+        let is_throttled = self.published_at.map_or(false, |published_at| {
+            now - published_at < MIN_PUBLISH_INTERVAL
+        });
+        if self.published_presence.as_ref() == Some(&presence) || is_throttled {
+            return;
+        }
+
+        log::debug!("Publishing presence: {:?}", presence);
+        for publisher in &mut self.publishers {
+            publisher.publish(&presence);
+        }
+        self.published_presence = Some(presence);
+        self.published_at = Some(now);
+    }
+}
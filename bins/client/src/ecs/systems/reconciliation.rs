@@ -0,0 +1,92 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_core::ecs::resources::{
+    net::{
+        EntityNetMetadataStorage, LastAcknowledgedUpdate, ReconciliationStatus,
+        PERSISTENT_DIVERGENCE_REPORTS,
+    },
+    world::WorldStates,
+};
+use gv_game::ecs::system_data::GameStateHelper;
+
+/// Compares the server's reconciliation reports with the predicted states of the same frames,
+/// a report is checked once the world updates up to its frame are received and rolled back to.
+pub struct ReconciliationSystem;
+
+impl<'s> System<'s> for ReconciliationSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        ReadExpect<'s, LastAcknowledgedUpdate>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        ReadExpect<'s, WorldStates>,
+        WriteExpect<'s, ReconciliationStatus>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            multiplayer_room_state,
+            last_acknowledged_update,
+            entity_net_metadata_storage,
+            world_states,
+            mut reconciliation_status,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_multiplayer() || reconciliation_status.pending_reports.is_empty() {
+            return;
+        }
+        let player_entity =
+            match entity_net_metadata_storage.get_entity(multiplayer_room_state.player_net_id) {
+                Some(player_entity) => player_entity,
+                None => return,
+            };
+
+        let (ready_reports, pending_reports): (Vec<_>, Vec<_>) = reconciliation_status
+            .pending_reports
+            .drain(..)
+            .partition(|report| report.frame_number <= last_acknowledged_update.frame_number);
+        reconciliation_status.pending_reports = pending_reports;
+
+        for report in ready_reports {
+            let predicted = match world_states
+                .state(report.frame_number)
+                .and_then(|world_state| world_state.player_reconciliation(player_entity))
+            {
+                Some(predicted) => predicted,
+                None => {
+                    log::debug!(
+                        "No predicted state to reconcile frame {} with",
+                        report.frame_number
+                    );
+                    continue;
+                }
+            };
+
+            let was_diverged = reconciliation_status.is_diverged();
+            let has_diverged = reconciliation_status.check(
+                &report,
+                predicted.position,
+                &predicted.last_casted_spells,
+            );
+            if has_diverged {
+                log::warn!(
+                    "The predicted player has diverged from the server's for {} reports (frame: {}, position divergence: {}, cooldowns diverged: {}, server: {:?}, predicted: {:?})",
+                    PERSISTENT_DIVERGENCE_REPORTS,
+                    report.frame_number,
+                    reconciliation_status.position_divergence,
+                    reconciliation_status.cooldowns_diverged,
+                    report,
+                    predicted
+                );
+            } else if was_diverged && !reconciliation_status.is_diverged() {
+                log::info!(
+                    "The predicted player is in sync with the server's again (frame: {})",
+                    report.frame_number
+                );
+            }
+        }
+    }
+}
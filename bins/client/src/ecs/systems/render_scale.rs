@@ -0,0 +1,54 @@
+use amethyst::{
+    core::Time,
+    ecs::{Read, ReadExpect, System, WriteExpect},
+};
+
+use std::time::Duration;
+
+use gv_client_shared::{
+    render_scale::{RenderScaleController, RenderScaleSetting, RENDER_SCALES},
+    settings::Settings,
+};
+use gv_core::ecs::resources::FRAME_RATE;
+
+use crate::ecs::resources::{RenderScale, WindowFocus};
+
+/// Lowers the resolution the world is rendered at while frames take longer than the frame
+/// rate allows, and raises it back once they don't, unless the scale is fixed in the settings.
+#[derive(Default)]
+pub struct RenderScaleSystem {
+    controller: RenderScaleController,
+}
+
+impl<'s> System<'s> for RenderScaleSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, WindowFocus>,
+        WriteExpect<'s, RenderScale>,
+    );
+
+    fn run(&mut self, (time, settings, window_focus, mut render_scale): Self::SystemData) {
+        let scale = match settings.video().render_scale {
+            RenderScaleSetting::Fixed(scale) => {
+                self.controller.pause();
+                scale.max(RENDER_SCALES[RENDER_SCALES.len() - 1]).min(1.0)
+            }
+            // Menus are throttled in the background, which isn't slowness to react to.
+            RenderScaleSetting::Dynamic if !window_focus.is_focused => {
+                self.controller.pause();
+                self.controller.scale()
+            }
+            RenderScaleSetting::Dynamic => {
+                // Higher FPS caps can be held back by vsync, so missing them isn't a reason
+                // to lower the scale.
+                let budget = Duration::from_secs(1) / FRAME_RATE;
+                if let Some(scale) = self.controller.update(time.delta_real_time(), budget) {
+                    log::info!("Render scale: {}%", (scale * 100.0).round());
+                }
+                self.controller.scale()
+            }
+        };
+        render_scale.0 = scale;
+    }
+}
@@ -0,0 +1,147 @@
+use amethyst::{
+    core::{HiddenPropagate, Parent},
+    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage},
+    input::{InputHandler, StringBindings},
+    ui::UiTransform,
+};
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::gv_dirs::GvDirs;
+
+use crate::ecs::resources::{DisplayDebugInfoSettings, ScreenshotRequest};
+
+enum ScreenshotState {
+    Idle,
+    /// The HUD is hidden this frame, the screenshot is requested the next one.
+    HidingHud,
+    /// Waiting for the render graph to take the request.
+    Requested,
+}
+
+impl Default for ScreenshotState {
+    fn default() -> Self {
+        ScreenshotState::Idle
+    }
+}
+
+/// Takes a screenshot on `screenshot`, or without the HUD and the debug overlays
+/// on `screenshot_without_hud`, and saves it to the screenshots directory.
+#[derive(Default)]
+pub struct ScreenshotSystem {
+    state: ScreenshotState,
+    is_screenshot_down: bool,
+    /// The UI roots which are hidden for the screenshot and were visible before.
+    hidden_ui_roots: Vec<Entity>,
+    /// The debug overlays (network info, frame timings, console) that were shown.
+    hidden_overlays: Option<(bool, bool, bool)>,
+}
+
+impl<'s> System<'s> for ScreenshotSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        WriteExpect<'s, DisplayDebugInfoSettings>,
+        WriteExpect<'s, ScreenshotRequest>,
+        ReadStorage<'s, UiTransform>,
+        ReadStorage<'s, Parent>,
+        WriteStorage<'s, HiddenPropagate>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            input,
+            mut display_debug_info_settings,
+            mut screenshot_request,
+            ui_transforms,
+            parents,
+            mut hidden_propagates,
+        ): Self::SystemData,
+    ) {
+        match self.state {
+            ScreenshotState::Idle => {}
+            ScreenshotState::HidingHud => {
+                screenshot_request.path = Some(screenshot_path());
+                self.state = ScreenshotState::Requested;
+                return;
+            }
+            ScreenshotState::Requested => {
+                if screenshot_request.path.is_some() {
+                    return;
+                }
+                for ui_root in self.hidden_ui_roots.drain(..) {
+                    hidden_propagates.remove(ui_root);
+                }
+                if let Some((network_debug_info, frame_timings, console)) =
+                    self.hidden_overlays.take()
+                {
+                    display_debug_info_settings.display_network_debug_info = network_debug_info;
+                    display_debug_info_settings.display_frame_timings = frame_timings;
+                    display_debug_info_settings.display_console = console;
+                }
+                screenshot_request.is_hud_hidden = false;
+                self.state = ScreenshotState::Idle;
+            }
+        }
+
+        let is_screenshot_down = input.action_is_down("screenshot").unwrap_or_default();
+        let was_screenshot_down =
+            std::mem::replace(&mut self.is_screenshot_down, is_screenshot_down);
+        if !is_screenshot_down || was_screenshot_down {
+            return;
+        }
+
+        // `screenshot_without_hud` shares the key with `screenshot`.
+        if !input
+            .action_is_down("screenshot_without_hud")
+            .unwrap_or_default()
+        {
+            screenshot_request.path = Some(screenshot_path());
+            self.state = ScreenshotState::Requested;
+            return;
+        }
+
+        for (entity, _, _) in (&entities, &ui_transforms, !&parents).join() {
+            if !hidden_propagates.contains(entity) {
+                hidden_propagates
+                    .insert(entity, HiddenPropagate::new())
+                    .expect("Expected to insert HiddenPropagate");
+                self.hidden_ui_roots.push(entity);
+            }
+        }
+        self.hidden_overlays = Some((
+            std::mem::replace(
+                &mut display_debug_info_settings.display_network_debug_info,
+                false,
+            ),
+            std::mem::replace(
+                &mut display_debug_info_settings.display_frame_timings,
+                false,
+            ),
+            std::mem::replace(&mut display_debug_info_settings.display_console, false),
+        ));
+        screenshot_request.is_hud_hidden = true;
+        self.state = ScreenshotState::HidingHud;
+    }
+}
+
+fn screenshot_path() -> PathBuf {
+    let screenshots_dir = GvDirs::new().screenshots_dir();
+    if let Err(err) = fs::create_dir_all(&screenshots_dir) {
+        log::error!("Failed to create the screenshots directory: {:?}", err);
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    screenshots_dir.join(format!(
+        "screenshot_{}_{:03}.png",
+        timestamp.as_secs(),
+        timestamp.subsec_millis()
+    ))
+}
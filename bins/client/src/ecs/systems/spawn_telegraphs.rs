@@ -0,0 +1,136 @@
+use amethyst::{
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+};
+
+use std::collections::HashMap;
+
+use gv_client_shared::ecs::resources::AssetHandles;
+use gv_core::{
+    actions::monster_spawn::SPAWN_TELEGRAPH_FRAMES,
+    ecs::{
+        components::{Monster, WorldPosition},
+        system_data::time::GameTimeService,
+    },
+};
+
+const DECAL_Z: f32 = -0.4;
+/// Decals reuse the damage aura ring, see `resources/assets/auras.ron`.
+const DECAL_SPRITE: usize = 0;
+const RING_SPRITE_RADIUS: f32 = 32.0;
+const DECAL_TINT: (f32, f32, f32) = (1.0, 0.25, 0.15);
+/// A decal closes in from this many monster radii down to the monster itself.
+const DECAL_START_RADII: f32 = 3.0;
+const DECAL_MIN_ALPHA: f32 = 0.3;
+/// Emerging monsters grow from this share of their size.
+const EMERGING_MIN_SCALE: f32 = 0.2;
+
+/// Marks the monsters that are still emerging after spawning with a warning decal,
+/// which closes in on them until they become active. The monsters themselves grow out
+/// of the ground meanwhile.
+#[derive(Default)]
+pub struct SpawnTelegraphSystem {
+    decals: HashMap<Entity, Entity>,
+}
+
+impl<'s> System<'s> for SpawnTelegraphSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, WorldPosition>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            asset_handles,
+            entities,
+            monsters,
+            world_positions,
+            mut sprite_renders,
+            mut transforms,
+            mut tints,
+        ): Self::SystemData,
+    ) {
+        let sprite_sheet = match asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.auras.clone(),
+            None => return,
+        };
+        let frame_number = game_time_service.game_frame_number();
+
+        let emerged_monsters = self
+            .decals
+            .keys()
+            .filter(|monster| {
+                !entities.is_alive(**monster)
+                    || monsters
+                        .get(**monster)
+                        .map_or(true, |monster| !monster.is_emerging(frame_number))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        for monster in emerged_monsters {
+            let decal = self.decals.remove(&monster).unwrap();
+            entities
+                .delete(decal)
+                .expect("Expected to delete a spawn decal");
+            if let Some(transform) = transforms.get_mut(monster) {
+                transform.set_scale(Vector3::new(1.0, 1.0, 1.0));
+            }
+        }
+
+        let telegraph_frames = game_time_service.scale_frames(SPAWN_TELEGRAPH_FRAMES) as f32;
+        for (monster_entity, monster, world_position) in
+            (&entities, &monsters, &world_positions).join()
+        {
+            if !monster.is_emerging(frame_number) {
+                continue;
+            }
+            let frames_left = (monster.active_at_frame - frame_number) as f32;
+            let progress = 1.0 - (frames_left / telegraph_frames).min(1.0);
+
+            let decal = match self.decals.get(&monster_entity) {
+                Some(decal) => *decal,
+                None => {
+                    let decal = entities
+                        .build_entity()
+                        .with(
+                            SpriteRender {
+                                sprite_sheet: sprite_sheet.clone(),
+                                sprite_number: DECAL_SPRITE,
+                            },
+                            &mut sprite_renders,
+                        )
+                        .with(Transform::default(), &mut transforms)
+                        .build();
+                    self.decals.insert(monster_entity, decal);
+                    decal
+                }
+            };
+
+            let radii = DECAL_START_RADII + (1.0 - DECAL_START_RADII) * progress;
+            let decal_scale = monster.radius * radii / RING_SPRITE_RADIUS;
+            if let Some(transform) = transforms.get_mut(decal) {
+                transform.set_translation_xyz(world_position.x, world_position.y, DECAL_Z);
+                transform.set_scale(Vector3::new(decal_scale, decal_scale, 1.0));
+            }
+            let (r, g, b) = DECAL_TINT;
+            let alpha = DECAL_MIN_ALPHA + (1.0 - DECAL_MIN_ALPHA) * progress;
+            tints
+                .insert(decal, Tint(Srgba::new(r, g, b, alpha)))
+                .expect("Expected to insert a Tint");
+
+            let monster_scale = EMERGING_MIN_SCALE + (1.0 - EMERGING_MIN_SCALE) * progress;
+            if let Some(transform) = transforms.get_mut(monster_entity) {
+                transform.set_scale(Vector3::new(monster_scale, monster_scale, 1.0));
+            }
+        }
+    }
+}
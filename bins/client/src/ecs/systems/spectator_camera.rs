@@ -0,0 +1,239 @@
+use amethyst::{
+    ecs::{
+        Entities, Entity, Join, ReadExpect, ReadStorage, System, World, WriteExpect, WriteStorage,
+    },
+    input::{InputHandler, StringBindings},
+    shred::{ResourceId, SystemData},
+    ui::UiText,
+};
+
+use std::time::Duration;
+
+use gv_core::{
+    ecs::{
+        components::{ClientPlayerActions, Dead, Monster, WorldPosition},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            GameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    room_settings::MAX_PLAYERS,
+};
+
+use crate::ecs::{
+    resources::{DisplayDebugInfoSettings, SpectatorCamera, SpectatorCameraMode},
+    system_data::ui::UiFinderMut,
+};
+
+/// Monsters further than this from a player don't count as their action.
+const DIRECTOR_RADIUS: f32 = 500.0;
+/// The director doesn't switch players more often than this.
+const DIRECTOR_SWITCH_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(SystemData)]
+pub struct SpectatorCameraSystemData<'s> {
+    game_time_service: GameTimeService<'s>,
+    input: ReadExpect<'s, InputHandler<StringBindings>>,
+    game_engine_state: ReadExpect<'s, GameEngineState>,
+    display_debug_info_settings: ReadExpect<'s, DisplayDebugInfoSettings>,
+    multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    entity_net_metadata_storage: ReadExpect<'s, EntityNetMetadataStorage>,
+    spectator_camera: WriteExpect<'s, SpectatorCamera>,
+    ui_finder: UiFinderMut<'s>,
+    entities: Entities<'s>,
+    client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    monsters: ReadStorage<'s, Monster>,
+    dead: ReadStorage<'s, Dead>,
+    world_positions: ReadStorage<'s, WorldPosition>,
+    ui_texts: WriteStorage<'s, UiText>,
+}
+
+/// Lets dead players follow the others with number keys, fly the camera with
+/// `spectate_free_camera` or let the director switch to the action with `spectate_director`.
+#[derive(Default)]
+pub struct SpectatorCameraSystem {
+    pressed_hotkeys: [bool; MAX_PLAYERS],
+    is_free_camera_down: bool,
+    is_director_down: bool,
+    director_switched_at: Duration,
+}
+
+impl<'s> System<'s> for SpectatorCameraSystem {
+    type SystemData = SpectatorCameraSystemData<'s>;
+
+    fn run(&mut self, mut system_data: Self::SystemData) {
+        let local_player = (&system_data.entities, &system_data.client_player_actions)
+            .join()
+            .next()
+            .map(|(entity, _)| entity);
+        let is_spectating = system_data.game_engine_state.is_playing()
+            && local_player.map_or(false, |entity| system_data.dead.contains(entity));
+        if !is_spectating {
+            if system_data.spectator_camera.is_active {
+                *system_data.spectator_camera = SpectatorCamera::default();
+                set_label_text(&mut system_data, String::new());
+            }
+            return;
+        }
+
+        // Players in their room slot order, which is what the number keys follow.
+        let players = if system_data.multiplayer_game_state.players.is_empty() {
+            local_player.into_iter().collect::<Vec<_>>()
+        } else {
+            system_data
+                .multiplayer_game_state
+                .players
+                .iter()
+                .filter_map(|player| {
+                    system_data
+                        .entity_net_metadata_storage
+                        .get_entity(player.entity_net_id)
+                })
+                .collect()
+        };
+        let entities = &system_data.entities;
+        let dead = &system_data.dead;
+        let is_alive = |entity: Entity| entities.is_alive(entity) && !dead.contains(entity);
+
+        let now = system_data
+            .game_time_service
+            .engine_time()
+            .absolute_real_time();
+        if !system_data.spectator_camera.is_active {
+            system_data.spectator_camera.is_active = true;
+            system_data.spectator_camera.mode = SpectatorCameraMode::Follow;
+            system_data.spectator_camera.target = None;
+        }
+
+        if !system_data.display_debug_info_settings.display_console {
+            for i in 0..MAX_PLAYERS {
+                let is_down = system_data
+                    .input
+                    .action_is_down(&format!("follow_player_{}", i + 1))
+                    .unwrap_or_default();
+                let was_down = std::mem::replace(&mut self.pressed_hotkeys[i], is_down);
+                if !is_down || was_down {
+                    continue;
+                }
+                if let Some(player) = players.get(i).copied().filter(|player| is_alive(*player)) {
+                    system_data.spectator_camera.mode = SpectatorCameraMode::Follow;
+                    system_data.spectator_camera.target = Some(player);
+                }
+            }
+
+            let is_free_camera_down = system_data
+                .input
+                .action_is_down("spectate_free_camera")
+                .unwrap_or_default();
+            if is_free_camera_down && !self.is_free_camera_down {
+                system_data.spectator_camera.mode = SpectatorCameraMode::FreeFly;
+            }
+            self.is_free_camera_down = is_free_camera_down;
+
+            let is_director_down = system_data
+                .input
+                .action_is_down("spectate_director")
+                .unwrap_or_default();
+            if is_director_down && !self.is_director_down {
+                system_data.spectator_camera.mode = SpectatorCameraMode::Director;
+                // Picking a player right away.
+                system_data.spectator_camera.target = None;
+            }
+            self.is_director_down = is_director_down;
+        }
+
+        let target = system_data
+            .spectator_camera
+            .target
+            .filter(|target| is_alive(*target));
+        let target = match system_data.spectator_camera.mode {
+            SpectatorCameraMode::Follow => {
+                target.or_else(|| players.iter().copied().find(|player| is_alive(*player)))
+            }
+            SpectatorCameraMode::FreeFly => target,
+            SpectatorCameraMode::Director => {
+                let is_due = now
+                    .checked_sub(self.director_switched_at)
+                    .unwrap_or_default()
+                    >= DIRECTOR_SWITCH_INTERVAL;
+                if target.is_none() || is_due {
+                    let monsters = &system_data.monsters;
+                    let world_positions = &system_data.world_positions;
+                    let monsters_around = |player: Entity| {
+                        let player_position = match world_positions.get(player) {
+                            Some(player_position) => **player_position,
+                            None => return 0,
+                        };
+                        (monsters, world_positions, !dead)
+                            .join()
+                            .filter(|(_, world_position, _)| {
+                                (**world_position - player_position).norm() < DIRECTOR_RADIUS
+                            })
+                            .count()
+                    };
+                    // Sticking with the current player unless someone has strictly more action.
+                    let current_action = target.map_or(0, monsters_around);
+                    let busiest = players
+                        .iter()
+                        .copied()
+                        .filter(|player| is_alive(*player))
+                        .map(|player| (player, monsters_around(player)))
+                        .max_by_key(|(_, action)| *action);
+                    let new_target = match busiest {
+                        Some((player, action)) if target.is_none() || action > current_action => {
+                            Some(player)
+                        }
+                        _ => target,
+                    };
+                    if new_target != target {
+                        self.director_switched_at = now;
+                    }
+                    new_target
+                } else {
+                    target
+                }
+            }
+        };
+        system_data.spectator_camera.target = target;
+
+        let target_nickname = system_data.spectator_camera.target.and_then(|target| {
+            system_data
+                .multiplayer_game_state
+                .players
+                .iter()
+                .find(|player| {
+                    system_data
+                        .entity_net_metadata_storage
+                        .get_entity(player.entity_net_id)
+                        == Some(target)
+                })
+                .map(|player| player.nickname.clone())
+        });
+        let status = match (system_data.spectator_camera.mode, target_nickname) {
+            (SpectatorCameraMode::FreeFly, _) => "Free camera".to_owned(),
+            (SpectatorCameraMode::Director, Some(nickname)) => format!("Director: {}", nickname),
+            (SpectatorCameraMode::Director, None) => "Director".to_owned(),
+            (SpectatorCameraMode::Follow, Some(nickname)) => format!("Spectating {}", nickname),
+            (SpectatorCameraMode::Follow, None) => "Spectating".to_owned(),
+        };
+        set_label_text(
+            &mut system_data,
+            format!(
+                "{}\n1-{} follow players, V free camera, B director",
+                status,
+                players.len().max(1)
+            ),
+        );
+    }
+}
+
+fn set_label_text(system_data: &mut SpectatorCameraSystemData, text: String) {
+    if let Some(ui_spectator_label) = system_data.ui_finder.find("ui_spectator_label") {
+        system_data
+            .ui_texts
+            .get_mut(ui_spectator_label)
+            .unwrap()
+            .text = text;
+    }
+}
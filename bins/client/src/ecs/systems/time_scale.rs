@@ -0,0 +1,82 @@
+use amethyst::{
+    ecs::{ReadExpect, System, WriteExpect},
+    input::{InputHandler, StringBindings},
+};
+
+use gv_core::ecs::resources::{
+    net::MultiplayerGameState, GameEngineState, GameTime, MAX_TIME_SCALE, MIN_TIME_SCALE,
+};
+
+use crate::ecs::resources::DisplayDebugInfoSettings;
+
+const HOTKEYS: [&str; 4] = [
+    "slow_down_time",
+    "speed_up_time",
+    "toggle_frame_stepping",
+    "step_frame",
+];
+
+/// Halves or doubles the time scale of single player games, and pauses them to advance
+/// a frame at a time. Multiplayer games are paced by the server.
+#[derive(Default)]
+pub struct TimeScaleSystem {
+    pressed_hotkeys: [bool; 4],
+}
+
+impl<'s> System<'s> for TimeScaleSystem {
+    type SystemData = (
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, GameTime>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            display_debug_info_settings,
+            game_engine_state,
+            multiplayer_game_state,
+            mut game_time,
+        ): Self::SystemData,
+    ) {
+        let is_enabled = *game_engine_state == GameEngineState::Playing
+            && !multiplayer_game_state.is_playing
+            && !display_debug_info_settings.display_console;
+
+        for (i, action) in HOTKEYS.iter().enumerate() {
+            let is_down = input.action_is_down(action).unwrap_or_default();
+            let was_down = std::mem::replace(&mut self.pressed_hotkeys[i], is_down);
+            if !is_down || was_down || !is_enabled {
+                continue;
+            }
+
+            match *action {
+                "slow_down_time" => {
+                    game_time.time_scale = (game_time.time_scale / 2.0).max(MIN_TIME_SCALE);
+                }
+                "speed_up_time" => {
+                    game_time.time_scale = (game_time.time_scale * 2.0).min(MAX_TIME_SCALE);
+                }
+                "toggle_frame_stepping" => {
+                    game_time.is_frame_stepping = !game_time.is_frame_stepping;
+                }
+                _ => {
+                    game_time.step_frame();
+                    continue;
+                }
+            }
+            log::info!(
+                "Time scale: {}{}",
+                game_time.time_scale,
+                if game_time.is_frame_stepping {
+                    " (frame stepping)"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+}
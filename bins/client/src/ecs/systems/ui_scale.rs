@@ -0,0 +1,53 @@
+use amethyst::{
+    ecs::{Entities, Entity, Join, ReadExpect, System, WriteStorage},
+    ui::{UiText, UiTransform},
+};
+
+use std::collections::HashMap;
+
+use gv_client_shared::settings::Settings;
+
+/// Applies the UI scale from the accessibility settings to every UI element, including
+/// the ones created later. Only sizes are scaled, as scaling positions pushes the elements
+/// anchored to the bottom of the screen off it. The scale applied to each element is remembered,
+/// so that changing the setting rescales the elements instead of scaling them over and over.
+#[derive(Default)]
+pub struct UiScaleSystem {
+    applied_scales: HashMap<Entity, f32>,
+}
+
+impl<'s> System<'s> for UiScaleSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, Settings>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (entities, settings, mut ui_transforms, mut ui_texts): Self::SystemData) {
+        let scale = settings.accessibility().ui_scale;
+        // Elements that aren't remembered have the scale of 1.0.
+        if (scale - 1.0).abs() < std::f32::EPSILON && self.applied_scales.is_empty() {
+            return;
+        }
+
+        let mut applied_scales = HashMap::with_capacity(self.applied_scales.len());
+        for (entity, ui_transform, ui_text) in
+            (&entities, &mut ui_transforms, (&mut ui_texts).maybe()).join()
+        {
+            let applied_scale = self.applied_scales.get(&entity).copied().unwrap_or(1.0);
+            if (applied_scale - scale).abs() >= std::f32::EPSILON {
+                let ratio = scale / applied_scale;
+                ui_transform.width *= ratio;
+                ui_transform.height *= ratio;
+                if let Some(ui_text) = ui_text {
+                    ui_text.font_size *= ratio;
+                }
+            }
+            if (scale - 1.0).abs() >= std::f32::EPSILON {
+                applied_scales.insert(entity, scale);
+            }
+        }
+        self.applied_scales = applied_scales;
+    }
+}
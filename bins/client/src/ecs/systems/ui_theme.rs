@@ -0,0 +1,208 @@
+use amethyst::{
+    assets::{AssetStorage, Handle, Loader},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, System, Write, WriteStorage},
+    renderer::{ImageFormat, Texture},
+    shrev::{EventChannel, ReaderId},
+    ui::{
+        FontAsset, FontHandle, Stretch, TextEditing, TtfFormat, UiButtonActionRetrigger,
+        UiButtonActionType, UiEvent, UiEventType, UiImage, UiText, UiTransform,
+    },
+};
+
+use std::collections::HashSet;
+
+use gv_client_shared::{
+    settings::Settings,
+    ui_theme::{UiColorRole, UiTheme, UiThemes},
+};
+
+/// Restyles every UI element with the theme from the video settings, including the ones
+/// created later, and all of them again once the theme is switched. Button hover colors
+/// are set here as well, as the ones of the prefabs would be restored after a switch.
+#[derive(Default)]
+pub struct UiThemeSystem {
+    theme_name: Option<String>,
+    font: Option<FontHandle>,
+    button_image: Option<Handle<Texture>>,
+    themed: HashSet<Entity>,
+    hovered: HashSet<Entity>,
+    event_reader: Option<ReaderId<UiEvent>>,
+}
+
+impl<'s> System<'s> for UiThemeSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, Settings>,
+        ReadExpect<'s, UiThemes>,
+        ReadExpect<'s, Loader>,
+        Read<'s, AssetStorage<FontAsset>>,
+        Read<'s, AssetStorage<Texture>>,
+        Write<'s, EventChannel<UiEvent>>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+        WriteStorage<'s, TextEditing>,
+        WriteStorage<'s, UiButtonActionRetrigger>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            settings,
+            ui_themes,
+            loader,
+            font_storage,
+            texture_storage,
+            mut ui_events,
+            mut ui_transforms,
+            mut ui_texts,
+            mut ui_images,
+            mut text_editings,
+            mut button_retriggers,
+        ): Self::SystemData,
+    ) {
+        let theme = match ui_themes.theme(&settings.video().ui_theme) {
+            Some(theme) => theme,
+            None => return,
+        };
+        if self.theme_name.as_ref() != Some(&theme.name) {
+            self.theme_name = Some(theme.name.clone());
+            self.font = Some(loader.load(theme.font.as_str(), TtfFormat, (), &font_storage));
+            self.button_image = theme.button_image.as_ref().map(|button_image| {
+                loader.load(
+                    button_image.texture.as_str(),
+                    ImageFormat::default(),
+                    (),
+                    &texture_storage,
+                )
+            });
+            self.themed.clear();
+        }
+
+        let event_reader = self
+            .event_reader
+            .get_or_insert_with(|| ui_events.register_reader());
+        for event in ui_events.read(event_reader) {
+            let is_hovered = match event.event_type {
+                UiEventType::HoverStart => true,
+                UiEventType::HoverStop => false,
+                _ => continue,
+            };
+            if is_hovered {
+                self.hovered.insert(event.target);
+            } else {
+                self.hovered.remove(&event.target);
+            }
+            if self.themed.contains(&event.target) && button_retriggers.contains(event.target) {
+                if let Some(ui_text) = ui_texts.get_mut(event.target) {
+                    set_button_text_color(theme, ui_text, is_hovered);
+                }
+            }
+        }
+
+        let font = self.font.as_ref().expect("Expected a theme font");
+        for (entity, ui_transform) in (&entities, &mut ui_transforms).join() {
+            if !self.themed.insert(entity) {
+                continue;
+            }
+
+            let role = ui_themes.role(&ui_transform.id, ui_texts.contains(entity));
+            let button_retrigger = button_retriggers.get_mut(entity);
+            let is_button = button_retrigger.is_some();
+            if let Some(button_retrigger) = button_retrigger {
+                for actions in &mut [
+                    &mut button_retrigger.on_hover_start,
+                    &mut button_retrigger.on_hover_stop,
+                ] {
+                    actions.retain(|action| {
+                        !matches!(
+                            action,
+                            UiButtonActionType::SetTextColor(_)
+                                | UiButtonActionType::UnsetTextColor(_)
+                        )
+                    });
+                }
+                match button_image(theme, self.button_image.as_ref()) {
+                    Some(button_image) => {
+                        ui_images
+                            .insert(entity, button_image)
+                            .expect("Expected to insert a UiImage");
+                    }
+                    None => {
+                        ui_images.remove(entity);
+                    }
+                }
+            }
+
+            if let Some(ui_text) = ui_texts.get_mut(entity) {
+                ui_text.font = font.clone();
+                if is_button {
+                    set_button_text_color(theme, ui_text, self.hovered.contains(&entity));
+                } else if let Some(role) = role {
+                    set_rgb(&mut ui_text.color, theme.colors.color(role));
+                }
+            }
+            if let Some(text_editing) = text_editings.get_mut(entity) {
+                set_rgb(
+                    &mut text_editing.selected_text_color,
+                    theme.colors.selected_text,
+                );
+                set_rgb(
+                    &mut text_editing.selected_background_color,
+                    theme.colors.selected_background,
+                );
+            }
+            if let (Some(role), Some(UiImage::SolidColor(color))) =
+                (role, ui_images.get_mut(entity))
+            {
+                set_rgb(color, theme.colors.color(role));
+            }
+            if let (
+                Some(UiColorRole::Panel),
+                Stretch::XY {
+                    x_margin, y_margin, ..
+                },
+            ) = (role, &mut ui_transform.stretch)
+            {
+                *x_margin = theme.panel_padding;
+                *y_margin = theme.panel_padding;
+            }
+        }
+        self.themed.retain(|entity| entities.is_alive(*entity));
+        self.hovered.retain(|entity| entities.is_alive(*entity));
+    }
+}
+
+fn button_image(theme: &UiTheme, texture: Option<&Handle<Texture>>) -> Option<UiImage> {
+    let button_image = theme.button_image.as_ref()?;
+    let (width, height) = button_image.texture_size;
+    Some(UiImage::NineSlice {
+        x_start: 0,
+        y_start: 0,
+        width,
+        height,
+        left_dist: button_image.left,
+        right_dist: button_image.right,
+        top_dist: button_image.top,
+        bottom_dist: button_image.bottom,
+        tex: texture?.clone(),
+        texture_dimensions: [width, height],
+    })
+}
+
+fn set_button_text_color(theme: &UiTheme, ui_text: &mut UiText, is_hovered: bool) {
+    let color = if is_hovered {
+        theme.colors.button_hover_text
+    } else {
+        theme.colors.button_text
+    };
+    set_rgb(&mut ui_text.color, color);
+}
+
+/// Keeps the alpha, as menus fade their elements in and out.
+fn set_rgb(color: &mut [f32; 4], rgb: [f32; 3]) {
+    color[0] = rgb[0];
+    color[1] = rgb[1];
+    color[2] = rgb[2];
+}
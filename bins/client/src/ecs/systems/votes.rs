@@ -0,0 +1,122 @@
+use amethyst::{
+    ecs::{ReadExpect, System, WriteExpect, WriteStorage},
+    input::{InputHandler, StringBindings},
+    ui::UiText,
+};
+
+use std::time::Duration;
+
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_core::{
+    ecs::{resources::net::MultiplayerGameState, system_data::time::GameTimeService},
+    votes::{VoteKind, VOTE_DURATION_SECS},
+};
+
+use crate::ecs::{
+    resources::{DisplayDebugInfoSettings, Votes},
+    system_data::ui::UiFinderMut,
+};
+
+/// For how long the result of a vote stays on the screen.
+const VOTE_RESULT_DURATION_SECS: u64 = 3;
+
+/// Casts the player's ballots with hotkeys and shows the running vote to everyone in the room.
+#[derive(Default)]
+pub struct VoteSystem {
+    pressed_hotkeys: [bool; 2],
+}
+
+impl<'s> System<'s> for VoteSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, DisplayDebugInfoSettings>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, Votes>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            game_time_service,
+            input,
+            display_debug_info_settings,
+            multiplayer_room_state,
+            multiplayer_game_state,
+            mut votes,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let connection_id = multiplayer_room_state.connection_status.connection_id();
+        let can_vote = match (votes.running.as_ref(), connection_id) {
+            (Some(vote), Some(connection_id)) => vote.kind.is_voter(connection_id),
+            _ => false,
+        };
+
+        for (i, (action, is_yes)) in [("vote_yes", true), ("vote_no", false)].iter().enumerate() {
+            let is_down = input.action_is_down(action).unwrap_or_default();
+            let was_down = std::mem::replace(&mut self.pressed_hotkeys[i], is_down);
+            if is_down && !was_down && can_vote && !display_debug_info_settings.display_console {
+                votes.outgoing_ballot = Some(*is_yes);
+            }
+        }
+
+        let ui_vote_label = match ui_finder.find("ui_vote_label") {
+            Some(ui_vote_label) => ui_vote_label,
+            None => return,
+        };
+        let now = game_time_service.engine_time().absolute_real_time();
+        let text = if let Some(vote) = votes.running.as_ref() {
+            let seconds_left = Duration::from_secs(VOTE_DURATION_SECS)
+                .checked_sub(now - votes.running_since)
+                .unwrap_or_default()
+                .as_secs();
+            let ballot = match connection_id {
+                Some(connection_id) if vote.yes.contains(&connection_id) => "you've voted yes",
+                Some(connection_id) if vote.no.contains(&connection_id) => "you've voted no",
+                _ if can_vote => "F5 - yes, F6 - no",
+                _ => "you can't vote",
+            };
+            format!(
+                "{}? Yes: {}, no: {} of {} ({}s, {})",
+                vote_title(vote.kind, &multiplayer_game_state),
+                vote.yes.len(),
+                vote.no.len(),
+                vote.voters,
+                seconds_left,
+                ballot
+            )
+        } else if let Some((kind, has_passed)) = votes.last_result {
+            if now - votes.last_result_since < Duration::from_secs(VOTE_RESULT_DURATION_SECS) {
+                format!(
+                    "{}: the vote has {}",
+                    vote_title(kind, &multiplayer_game_state),
+                    if has_passed { "passed" } else { "failed" }
+                )
+            } else {
+                votes.last_result = None;
+                String::new()
+            }
+        } else {
+            String::new()
+        };
+        ui_texts.get_mut(ui_vote_label).unwrap().text = text;
+    }
+}
+
+fn vote_title(kind: VoteKind, multiplayer_game_state: &MultiplayerGameState) -> String {
+    match kind {
+        VoteKind::Kick(connection_id) => format!(
+            "Kick {}",
+            multiplayer_game_state
+                .find_player_by_connection_id(connection_id)
+                .map_or("the player", |player| player.nickname.as_str())
+        ),
+        VoteKind::ReturnToLobby => "Return to the lobby".to_owned(),
+        VoteKind::SkipIntermission => "Skip the intermission".to_owned(),
+    }
+}
@@ -0,0 +1,40 @@
+use amethyst::{
+    ecs::{ReadExpect, System, Write, WriteExpect},
+    shrev::{EventChannel, ReaderId},
+    winit::{Event, WindowEvent},
+};
+
+use gv_client_shared::settings::Settings;
+
+use crate::ecs::resources::WindowFocus;
+
+/// Keeps `WindowFocus` up to date. While the window is unfocused, the game is muted
+/// if `mute_unfocused` is on, `FramePacingSystem` switches to the unfocused FPS cap.
+#[derive(Default)]
+pub struct WindowFocusSystem {
+    event_reader: Option<ReaderId<Event>>,
+}
+
+impl<'s> System<'s> for WindowFocusSystem {
+    type SystemData = (
+        Write<'s, EventChannel<Event>>,
+        ReadExpect<'s, Settings>,
+        WriteExpect<'s, WindowFocus>,
+    );
+
+    fn run(&mut self, (mut window_events, settings, mut window_focus): Self::SystemData) {
+        let event_reader = self
+            .event_reader
+            .get_or_insert_with(|| window_events.register_reader());
+        for event in window_events.read(event_reader) {
+            if let Event::WindowEvent {
+                event: WindowEvent::Focused(is_focused),
+                ..
+            } = event
+            {
+                window_focus.is_focused = *is_focused;
+            }
+        }
+        window_focus.is_muted = !window_focus.is_focused && settings.audio().mute_unfocused;
+    }
+}
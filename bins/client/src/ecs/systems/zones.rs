@@ -0,0 +1,89 @@
+use amethyst::{
+    core::{math::Vector3, Transform},
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+};
+
+use gv_client_shared::ecs::resources::AssetHandles;
+use gv_core::ecs::{
+    components::zone::{Zone, ZoneKind},
+    system_data::time::GameTimeService,
+};
+
+const DECAL_Z: f32 = -0.5;
+const DECAL_SPRITE_RADIUS: f32 = 32.0;
+const PULSE_SPEED: f32 = 4.0;
+const PULSE_AMPLITUDE: f32 = 0.04;
+const FADE_OUT_SECS: f32 = 0.5;
+
+/// Draws zones as pulsing decals on the ground, which fade out before the zones expire.
+pub struct ZoneDecalSystem;
+
+impl<'s> System<'s> for ZoneDecalSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Option<Read<'s, AssetHandles>>,
+        Entities<'s>,
+        ReadStorage<'s, Zone>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            asset_handles,
+            entities,
+            zones,
+            mut sprite_renders,
+            mut transforms,
+            mut tints,
+        ): Self::SystemData,
+    ) {
+        let sprite_sheet = match asset_handles.as_ref() {
+            Some(asset_handles) => asset_handles.zones.clone(),
+            None => return,
+        };
+        let frame_number = game_time_service.game_frame_number();
+        let frame_seconds = game_time_service.tick_seconds();
+
+        for (entity, zone, transform) in (&entities, &zones, &mut transforms).join() {
+            if !sprite_renders.contains(entity) {
+                let sprite_number = match zone.kind {
+                    ZoneKind::FirePatch => 0,
+                    ZoneKind::SlowField => 1,
+                };
+                sprite_renders
+                    .insert(
+                        entity,
+                        SpriteRender {
+                            sprite_sheet: sprite_sheet.clone(),
+                            sprite_number,
+                        },
+                    )
+                    .expect("Expected to insert a SpriteRender");
+                transform.set_translation_z(DECAL_Z);
+            }
+
+            let seconds_active =
+                frame_number.saturating_sub(zone.frame_spawned) as f32 * frame_seconds;
+            let seconds_left =
+                zone.frame_expires.saturating_sub(frame_number) as f32 * frame_seconds;
+            let pulse = 1.0 + PULSE_AMPLITUDE * (seconds_active * PULSE_SPEED).sin();
+            let scale = zone.radius / DECAL_SPRITE_RADIUS * pulse;
+            transform.set_scale(Vector3::new(scale, scale, 1.0));
+
+            // Zones aren't shown before they are spawned, which is possible after a rewind.
+            let alpha = if zone.is_active(frame_number) {
+                (seconds_left / FADE_OUT_SECS).min(1.0)
+            } else {
+                0.0
+            };
+            tints
+                .insert(entity, Tint(Srgba::new(1.0, 1.0, 1.0, alpha)))
+                .expect("Expected to insert a Tint");
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! `gv_client --headless-bot --join <ADDR>` connects to a server without opening a window
+//! and plays with scripted inputs, so that many instances can be launched to load-test
+//! a server. A bot speaks the same protocol as `ClientNetworkSystem`, but doesn't run
+//! the game simulation: it only keeps track of what it needs to send its inputs.
+
+use amethyst::{
+    core::Time,
+    ecs::{ReaderId, World, WorldExt},
+    network::simulation::{
+        laminar::{LaminarNetworkBundle, LaminarSocket},
+        NetworkSimulationEvent, TransportResource,
+    },
+    prelude::{GameData, GameDataBuilder},
+    shrev::EventChannel,
+    Error,
+};
+
+use std::{
+    net::SocketAddr,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::{
+    actions::{
+        player::{CastSpell, PlayerCastAction, PlayerWalkAction},
+        ClientActionUpdate,
+    },
+    classes::PlayerClass,
+    cosmetics::PlayerCosmetics,
+    ecs::{
+        components::NetConnectionModel,
+        resources::{
+            net::{input_resend_frames, MultiplayerRoomPlayer, UnacknowledgedInputs},
+            world::InputFrame,
+            DEFAULT_TICK_RATE,
+        },
+    },
+    math::{deterministic, Vector2, ZeroVector},
+    net::{
+        client_message::ClientMessagePayload,
+        decode_server_message, interpolation_frame_delay,
+        server_message::{DisconnectReason, ServerMessagePayload},
+        NetIdentifier, NetUpdate,
+    },
+};
+use gv_game::utils::net::{send_message_reliable, send_message_unreliable};
+
+const TURN_INTERVAL_FRAMES: u64 = 30;
+const CAST_INTERVAL_FRAMES: u64 = 20;
+const CAST_DISTANCE: f32 = 200.0;
+/// Bots don't measure their latency, inputs are resent as if it was this high.
+const ASSUMED_LATENCY_MS: u32 = 100;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct HeadlessBotConfig {
+    pub server_addr: SocketAddr,
+    pub nickname: String,
+    /// A bot that becomes the host starts the game once the room has this many players.
+    pub start_players: usize,
+}
+
+pub struct HeadlessBot {
+    world: World,
+    network_data: GameData<'static, 'static>,
+    reader: ReaderId<NetworkSimulationEvent>,
+    config: HeadlessBotConfig,
+    connection: NetConnectionModel,
+    connection_id: Option<NetIdentifier>,
+    is_host: bool,
+    tick_rate: u32,
+    room_players: Vec<MultiplayerRoomPlayer>,
+    player_net_ids: Option<Vec<NetIdentifier>>,
+    game_frame_number: u64,
+    /// Fractions of a tick left from the previous updates.
+    tick_accumulator: f32,
+    is_paused: bool,
+    has_requested_start: bool,
+    client_action_id_autoinc: NetIdentifier,
+    unacknowledged_inputs: UnacknowledgedInputs,
+    has_sent_join: bool,
+    last_heartbeat_sent_at: Instant,
+    disconnect_reason: Option<DisconnectReason>,
+}
+
+impl HeadlessBot {
+    pub fn new(socket: LaminarSocket, config: HeadlessBotConfig) -> Result<Self, Error> {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.insert(TransportResource::default());
+        world.insert(EventChannel::<NetworkSimulationEvent>::default());
+
+        let network_data = GameDataBuilder::default()
+            .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+            .build(&mut world);
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+
+        let session_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |duration| duration.as_millis() as NetIdentifier);
+        let connection = NetConnectionModel::new(0, session_id, config.server_addr);
+        Ok(Self {
+            world,
+            network_data,
+            reader,
+            config,
+            connection,
+            connection_id: None,
+            is_host: false,
+            tick_rate: DEFAULT_TICK_RATE,
+            room_players: Vec::new(),
+            player_net_ids: None,
+            game_frame_number: 0,
+            tick_accumulator: 0.0,
+            is_paused: false,
+            has_requested_start: false,
+            client_action_id_autoinc: 0,
+            unacknowledged_inputs: UnacknowledgedInputs::default(),
+            has_sent_join: false,
+            last_heartbeat_sent_at: Instant::now(),
+            disconnect_reason: None,
+        })
+    }
+
+    /// Plays until the server disconnects the bot.
+    pub fn run(&mut self) -> Result<(), Error> {
+        log::info!(
+            "Starting a headless bot {} for {}",
+            self.config.nickname,
+            self.config.server_addr
+        );
+        let mut last_frame_at = Instant::now();
+        loop {
+            let frame_started_at = Instant::now();
+            let elapsed = frame_started_at - last_frame_at;
+            last_frame_at = frame_started_at;
+            self.update(elapsed);
+
+            if let Some(disconnect_reason) = self.disconnect_reason.take() {
+                log::info!("The bot has been disconnected: {:?}", disconnect_reason);
+                return Ok(());
+            }
+
+            let frame_duration = Duration::from_secs_f64(1.0 / f64::from(self.tick_rate));
+            if let Some(sleep_duration) = frame_duration.checked_sub(frame_started_at.elapsed()) {
+                thread::sleep(sleep_duration);
+            }
+        }
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.increment_frame_number();
+            time.set_delta_time(elapsed);
+        }
+        self.network_data.update(&self.world);
+
+        let server_addr = self.config.server_addr;
+        let messages = {
+            let net_events = self
+                .world
+                .read_resource::<EventChannel<NetworkSimulationEvent>>();
+            net_events
+                .read(&mut self.reader)
+                .filter_map(|net_event| match net_event {
+                    NetworkSimulationEvent::Message(addr, bytes) if *addr == server_addr => {
+                        Some(bytes.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        for bytes in messages {
+            self.receive(bytes.as_ref());
+        }
+
+        if !self.has_sent_join {
+            self.has_sent_join = true;
+            self.send_reliable(ClientMessagePayload::JoinRoom {
+                sent_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("Expected a duration unix timestamp"),
+                nickname: self.config.nickname.clone(),
+                class: PlayerClass::default(),
+                cosmetics: PlayerCosmetics::default(),
+            });
+        }
+
+        if self.is_host
+            && self.player_net_ids.is_none()
+            && !self.has_requested_start
+            && self.room_players.len() >= self.config.start_players
+        {
+            log::info!("Starting the game for {} bots", self.room_players.len());
+            self.has_requested_start = true;
+            self.send_reliable(ClientMessagePayload::StartHostedGame);
+        }
+
+        if self.player_net_ids.is_some() && !self.is_paused {
+            self.tick_accumulator += elapsed.as_secs_f32() * self.tick_rate as f32;
+            while self.tick_accumulator >= 1.0 {
+                self.tick_accumulator -= 1.0;
+                self.game_frame_number += 1;
+                self.play_frame();
+            }
+        }
+
+        if self.connection_id.is_some()
+            && self.last_heartbeat_sent_at.elapsed() > HEARTBEAT_INTERVAL
+        {
+            self.last_heartbeat_sent_at = Instant::now();
+            self.send_reliable(ClientMessagePayload::Heartbeat);
+        }
+    }
+
+    /// Walks in circles and shoots ahead, the same way `gv_bench` bots do.
+    fn play_frame(&mut self) {
+        let entity_net_id = match self.player_net_id() {
+            Some(entity_net_id) => entity_net_id,
+            None => return,
+        };
+        let frame_number = self.game_frame_number + interpolation_frame_delay(self.tick_rate);
+        let phase = (self.game_frame_number / TURN_INTERVAL_FRAMES) as f32 * 0.5
+            + (self.connection_id.unwrap_or_default() % 8) as f32;
+        let direction = deterministic::rotate(&Vector2::new(1.0, 0.0), phase);
+
+        let mut input_frame = InputFrame {
+            frame_number,
+            walk_action_updates: Vec::new(),
+            cast_action_updates: Vec::new(),
+            interact_action_updates: Vec::new(),
+        };
+        if self.game_frame_number % TURN_INTERVAL_FRAMES == 0 {
+            input_frame.walk_action_updates.push(NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id: self.next_client_action_id(),
+                    action: PlayerWalkAction::Walk { direction },
+                },
+            });
+        }
+        if self.game_frame_number % CAST_INTERVAL_FRAMES == 0 {
+            input_frame.cast_action_updates.push(NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id: self.next_client_action_id(),
+                    // The server fills in the cast position.
+                    action: PlayerCastAction {
+                        cast_position: Vector2::zero(),
+                        target_position: direction * CAST_DISTANCE,
+                        spell: CastSpell::Missile,
+                    },
+                },
+            });
+        }
+        if !input_frame.walk_action_updates.is_empty()
+            || !input_frame.cast_action_updates.is_empty()
+        {
+            self.unacknowledged_inputs.push(input_frame);
+        }
+
+        let resend_frames = input_resend_frames(ASSUMED_LATENCY_MS, self.tick_rate);
+        if let Some(input_frames) = self
+            .unacknowledged_inputs
+            .packet(self.game_frame_number, resend_frames)
+        {
+            send_message_unreliable(
+                &mut self.world.write_resource::<TransportResource>(),
+                &self.connection,
+                ClientMessagePayload::PlayerInputs(input_frames),
+            );
+        }
+    }
+
+    fn receive(&mut self, bytes: &[u8]) {
+        let message = match decode_server_message(bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                log::error!("The bot failed to decode a message: {:?}", err);
+                return;
+            }
+        };
+        if message.session_id != self.connection.session_id {
+            return;
+        }
+
+        match message.payload {
+            ServerMessagePayload::Handshake {
+                net_id,
+                is_host,
+                tick_rate,
+                ..
+            } => {
+                self.connection_id = Some(net_id);
+                self.is_host = is_host;
+                self.tick_rate = tick_rate;
+            }
+            ServerMessagePayload::UpdateRoomPlayers(players) => {
+                self.room_players = players;
+            }
+            ServerMessagePayload::StartGame(entity_net_ids)
+            | ServerMessagePayload::ResumeGame { entity_net_ids, .. } => {
+                self.start_game(entity_net_ids, 0);
+            }
+            ServerMessagePayload::RejoinGame {
+                entity_net_ids,
+                snapshot,
+            } => {
+                self.start_game(entity_net_ids, snapshot.frame_number);
+            }
+            ServerMessagePayload::LevelTransition { .. } | ServerMessagePayload::ReturnToLobby => {
+                self.player_net_ids = None;
+                self.has_requested_start = false;
+                self.is_paused = false;
+                self.unacknowledged_inputs.clear();
+            }
+            ServerMessagePayload::UpdateWorld { id, .. } => {
+                send_message_unreliable(
+                    &mut self.world.write_resource::<TransportResource>(),
+                    &self.connection,
+                    ClientMessagePayload::AcknowledgeWorldUpdate(id),
+                );
+            }
+            ServerMessagePayload::Ping(ping_id) => {
+                send_message_unreliable(
+                    &mut self.world.write_resource::<TransportResource>(),
+                    &self.connection,
+                    ClientMessagePayload::Pong {
+                        ping_id,
+                        frame_number: self.game_frame_number,
+                    },
+                );
+            }
+            ServerMessagePayload::PauseWaitingForPlayers { .. } => {
+                self.is_paused = true;
+            }
+            ServerMessagePayload::UnpauseWaitingForPlayers(_) => {
+                self.is_paused = false;
+            }
+            ServerMessagePayload::AcknowledgeInputs(client_action_id) => {
+                self.unacknowledged_inputs.acknowledge(client_action_id);
+            }
+            ServerMessagePayload::Disconnect(disconnect_reason) => {
+                self.disconnect_reason = Some(disconnect_reason);
+            }
+            _ => {}
+        }
+    }
+
+    fn start_game(&mut self, entity_net_ids: Vec<NetIdentifier>, frame_number: u64) {
+        log::info!("The game has started");
+        self.player_net_ids = Some(entity_net_ids);
+        self.game_frame_number = frame_number;
+        self.tick_accumulator = 0.0;
+        self.unacknowledged_inputs.clear();
+    }
+
+    fn player_net_id(&self) -> Option<NetIdentifier> {
+        let connection_id = self.connection_id?;
+        let player_index = self
+            .room_players
+            .iter()
+            .position(|player| player.connection_id == connection_id)?;
+        self.player_net_ids
+            .as_ref()
+            .and_then(|player_net_ids| player_net_ids.get(player_index).cloned())
+    }
+
+    fn next_client_action_id(&mut self) -> NetIdentifier {
+        let id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc += 1;
+        id
+    }
+
+    fn send_reliable(&mut self, payload: ClientMessagePayload) {
+        send_message_reliable(
+            &mut self.world.write_resource::<TransportResource>(),
+            &self.connection,
+            payload,
+        );
+    }
+}
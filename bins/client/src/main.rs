@@ -2,13 +2,17 @@
 #![feature(or_patterns)]
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+mod clip_capture;
 mod ecs;
+mod headless_bot;
+mod presence;
 mod rendering;
 mod utils;
 
 use amethyst::{
     animation::AnimationBundle,
-    assets::PrefabLoaderSystemDesc,
+    assets::{PrefabLoaderSystemDesc, Processor},
+    audio::AudioBundle,
     core::{
         frame_limiter::FrameRateLimitStrategy, transform::TransformBundle, HideHierarchySystemDesc,
     },
@@ -16,7 +20,7 @@ use amethyst::{
     network::simulation::laminar::{LaminarConfig, LaminarNetworkBundle, LaminarSocket},
     prelude::{Application, GameDataBuilder, SystemDesc},
     renderer::{
-        plugins::{RenderFlat2D, RenderFlat3D, RenderToWindow},
+        plugins::{RenderFlat2D, RenderFlat3D},
         types::DefaultBackend,
         RenderingBundle, SpriteRender,
     },
@@ -27,37 +31,57 @@ use amethyst_imgui::RenderImgui;
 
 use std::{
     env,
-    path::PathBuf,
     io::{Error, ErrorKind},
+    path::{Path, PathBuf},
 };
 
 use gv_animation_prefabs::{AnimationId, GameSpriteAnimationPrefab};
-use gv_client_shared::{ecs::resources::MultiplayerRoomState, settings::Settings};
-use gv_core::ecs::resources::world::{
-    ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate,
+use gv_client_shared::{
+    ecs::resources::MultiplayerRoomState, settings::Settings, sprite_atlas::SpriteAtlas,
+    ui_theme::UiThemes,
+};
+use gv_core::{
+    content_packs::ContentPacks,
+    crash_report,
+    ecs::resources::{
+        net::LastAcknowledgedUpdate,
+        world::{ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate},
+        FRAME_RATE,
+    },
+    gv_dirs::{resolve_path, GvDirs, CLIENT_LOGGING_CONFIG_FILE},
+    net::session_recording::SessionRecorder,
 };
 use gv_game::{
     build_game_logic_systems,
-    ecs::systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
+    console::ConsoleCommands,
+    ecs::systems::{
+        FrameTimingsSystem, NetConnectionManagerDesc, RendezvousDesc, RendezvousRelaySystem,
+        WorldPositionTransformSystem,
+    },
     states::LoadingState,
+    TimedSystemsBuilder,
 };
 
 use crate::{
+    clip_capture::{ClipCapture, ClipFormat},
     ecs::{
         resources::{
-            DisplayDebugInfoSettings, LastAcknowledgedUpdate, ServerCommand,
-            UiNetworkCommandResource,
+            CrashReports, DisplayDebugInfoSettings, Emotes, Highlights, PostProcessing,
+            RenderScale, RoomBrowser, SceneLighting, ScreenshotRequest, ServerCommand,
+            SpectatorCamera, UiNetworkCommandResource, Votes, WindowFocus,
         },
         systems::*,
     },
+    headless_bot::{HeadlessBot, HeadlessBotConfig},
     rendering::*,
 };
-use gv_core::ecs::resources::net::PlayersNetStatus;
+use gv_core::ecs::resources::net::{PlayersNetStatus, ReconciliationStatus, UnacknowledgedInputs};
 
 fn change_to_resources_parent_dir() -> Result<(), Error> {
-    let resources_in_working_dir = env::current_dir().ok()
+    let resources_in_working_dir = env::current_dir()
+        .ok()
         .map_or(false, |dir| dir.join("resources").exists());
-    
+
     let mut resources_in_binary_dir = false;
     if let Some(exe_path) = env::current_exe().ok() {
         if let Some(exe_parent) = exe_path.parent() {
@@ -75,7 +99,8 @@ fn change_to_resources_parent_dir() -> Result<(), Error> {
         new_dir.pop();
         new_dir.pop();
 
-        let is_package_folder = env::current_dir().ok()
+        let is_package_folder = env::current_dir()
+            .ok()
             .map_or(false, |dir| dir.starts_with(new_dir.clone()));
 
         if is_package_folder && new_dir.join("resources").exists() {
@@ -83,7 +108,7 @@ fn change_to_resources_parent_dir() -> Result<(), Error> {
             manifest_root = new_dir;
         }
     }
-    
+
     if resources_in_working_dir {
         println!("Using resources folder from working directory");
     } else if resources_in_binary_dir {
@@ -92,10 +117,15 @@ fn change_to_resources_parent_dir() -> Result<(), Error> {
         let exe_subpath = exe_path.parent().unwrap();
         env::set_current_dir(exe_subpath)?;
     } else if resources_in_manifest_root {
-        println!("Detected running in package subdirectory, changing working directory to crate's root");
+        println!(
+            "Detected running in package subdirectory, changing working directory to crate's root"
+        );
         env::set_current_dir(manifest_root)?;
     } else {
-        return Err(Error::new(ErrorKind::NotFound, "Could not find resources folder"));
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "Could not find resources folder",
+        ));
     }
 
     Ok(())
@@ -105,33 +135,114 @@ fn main() -> amethyst::Result<()> {
     #[cfg(feature = "profiler")]
     thread_profiler::disable_profiler();
 
+    crash_report::install_panic_hook("gv_client");
+
     change_to_resources_parent_dir()?;
 
-    let _cli_matches = clap::App::new("grumpy_visitors")
+    let cli_matches = clap::App::new("grumpy_visitors")
         .version("0.1")
         .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
         .about("A prototype of a top-down EvilInvasion-like 2D arcade/action")
+        .arg(
+            clap::Arg::with_name("record-session")
+                .long("record-session")
+                .value_name("FILE")
+                .help("Records all the network messages of a multiplayer session to a file")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("capture-clips")
+                .long("capture-clips")
+                .value_name("FORMAT")
+                .help("Captures the window with ffmpeg to save clips of highlights and on a hotkey")
+                .possible_values(&["mp4", "gif"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("desync-reports")
+                .long("desync-reports")
+                .help("Keeps the messages of whole games to save a desync report on a hotkey"),
+        )
+        .arg(
+            clap::Arg::with_name("join")
+                .long("join")
+                .value_name("JOIN_ADDR")
+                .help("Prefills the join address, is used by invites from Discord or Steam")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("headless-bot")
+                .long("headless-bot")
+                .requires("join")
+                .help("Plays on the --join server with scripted inputs and without a window"),
+        )
+        .arg(
+            clap::Arg::with_name("bot-start-players")
+                .long("bot-start-players")
+                .value_name("PLAYERS")
+                .help("Specifies how many players a hosting headless bot waits for to start")
+                .default_value("1")
+                .takes_value(true),
+        )
         .get_matches();
 
     let socket_addr = "0.0.0.0:0";
 
-    let logging_config: LoggerConfig = ::std::fs::read_to_string("client_logging_config.toml")
+    let dirs = GvDirs::new();
+    let logging_config_path = dirs.logging_config_path(CLIENT_LOGGING_CONFIG_FILE);
+    let mut logging_config: LoggerConfig = ::std::fs::read_to_string(&logging_config_path)
         .map_err(|err| {
             println!(
-                "Failed to read client_logging_config.toml, using the defaults: {:?}",
+                "Failed to read {}, using the defaults: {:?}",
+                logging_config_path.display(),
                 err
             )
         })
         .and_then(|config_contents| {
             toml::from_str(&config_contents).map_err(|err| {
                 println!(
-                    "Failed to read client_logging_config.toml, using the defaults: {:?}",
+                    "Failed to read {}, using the defaults: {:?}",
+                    logging_config_path.display(),
                     err
                 );
             })
         })
         .unwrap_or_default();
-    Logger::from_config(logging_config).start();
+    logging_config.log_file = logging_config
+        .log_file
+        .map(|log_file| resolve_path(&dirs.logs_dir(), log_file));
+    Logger::from_config_formatter(logging_config, |out, message, record| {
+        let line = format!(
+            "[{level}][{target}] {message}",
+            level = record.level(),
+            target = record.target(),
+            message = message,
+        );
+        out.finish(format_args!("{}", line));
+        crash_report::record_log_line(line);
+    })
+    .start();
+
+    let laminar_config = LaminarConfig {
+        receive_buffer_max_size: 14_500,
+        ..LaminarConfig::default()
+    };
+
+    if cli_matches.is_present("headless-bot") {
+        let socket = LaminarSocket::bind_with_config(socket_addr, laminar_config)?;
+        let config = HeadlessBotConfig {
+            server_addr: cli_matches
+                .value_of("join")
+                .expect("Expected a join address")
+                .parse()?,
+            nickname: format!("bot{}", std::process::id()),
+            start_players: cli_matches
+                .value_of("bot-start-players")
+                .expect("Expected a default value")
+                .parse()?,
+        };
+        return HeadlessBot::new(socket, config)?.run();
+    }
 
     let settings = Settings::new()?;
     let display_config = settings.display().clone();
@@ -139,15 +250,62 @@ fn main() -> amethyst::Result<()> {
     let bindings = settings.bindings().clone();
     let input_bundle = InputBundle::<StringBindings>::new().with_bindings(bindings);
 
+    let content_packs = ContentPacks::load(&settings.mods_dir()).unwrap_or_else(|err| {
+        log::error!("Failed to load the content packs: {}", err);
+        ContentPacks::default()
+    });
+
+    let ui_themes = UiThemes::load(Path::new("resources/ui/themes.ron")).unwrap_or_else(|err| {
+        log::error!("Failed to load the UI themes: {}", err);
+        UiThemes::default()
+    });
+
     let mut builder = Application::build("./", LoadingState::default())?;
     builder.world.insert(settings);
+    builder.world.insert(content_packs);
+    builder.world.insert(ui_themes);
     builder.world.insert(ServerCommand::new());
+    builder.world.insert(CrashReports {
+        paths: crash_report::find_crash_reports(),
+        is_prompt_shown: false,
+    });
+    let mut session_recorder =
+        if let Some(record_session_path) = cli_matches.value_of("record-session") {
+            SessionRecorder::create(resolve_path(&dirs.replays_dir(), record_session_path))?
+        } else {
+            SessionRecorder::disabled()
+        }
+        .with_highlights(HIGHLIGHT_WINDOW_FRAMES);
+    if cli_matches.is_present("desync-reports") {
+        session_recorder = session_recorder.with_desync_reports();
+    }
+    builder.world.insert(session_recorder);
+    builder.world.insert(Highlights::default());
+    builder.world.insert(ScreenshotRequest::default());
+    builder.world.insert(RenderScale::default());
+    builder.world.insert(SceneLighting::default());
+    builder.world.insert(PostProcessing::default());
+    builder.world.insert(
+        cli_matches
+            .value_of("capture-clips")
+            .and_then(ClipFormat::from_arg)
+            .map_or_else(ClipCapture::disabled, ClipCapture::new),
+    );
 
     // The resources which we need to remember to reset on starting a game.
     builder.world.insert(DisplayDebugInfoSettings::default());
     builder.world.insert(PlayersNetStatus::default());
+    builder.world.insert(ReconciliationStatus::default());
+    builder.world.insert(UnacknowledgedInputs::default());
     builder.world.insert(UiNetworkCommandResource::default());
-    builder.world.insert(MultiplayerRoomState::new());
+    builder.world.insert(Emotes::default());
+    builder.world.insert(Votes::default());
+    builder.world.insert(RoomBrowser::default());
+    builder.world.insert(SpectatorCamera::default());
+    builder.world.insert(WindowFocus::default());
+    let mut multiplayer_room_state = MultiplayerRoomState::new();
+    multiplayer_room_state.server_addr = cli_matches.value_of("join").map(str::to_owned);
+    builder.world.insert(multiplayer_room_state);
     builder.world.insert(ClientWorldUpdates::default());
     builder.world.insert(LastAcknowledgedUpdate {
         id: 0,
@@ -157,73 +315,280 @@ fn main() -> amethyst::Result<()> {
         .world
         .insert(FramedUpdates::<ReceivedServerWorldUpdate>::default());
 
-    let laminar_config = LaminarConfig {
-        receive_buffer_max_size: 14_500,
-        ..LaminarConfig::default()
-    };
-
     let socket = LaminarSocket::bind_with_config(socket_addr, laminar_config)?;
 
     let mut game_data_builder = GameDataBuilder::default()
         .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
-        .with(
+        .with_timed(
+            RendezvousDesc::default().build(&mut builder.world),
+            "rendezvous_system",
+            &[],
+        )
+        .with_timed(
             NetConnectionManagerDesc::default().build(&mut builder.world),
             "net_connection_manager_system",
             &[],
         )
-        .with(
+        .with_timed(
+            ClockSyncSystem,
+            "clock_sync_system",
+            &["net_connection_manager_system"],
+        )
+        .with_timed(
             ClientNetworkSystem::default(),
             "game_network_system",
-            &["net_connection_manager_system"],
+            &["net_connection_manager_system", "clock_sync_system"],
+        )
+        .with_timed(
+            OverlaySystem::default(),
+            "overlay_system",
+            &["game_network_system"],
         )
-        .with(OverlaySystem, "overlay_system", &["game_network_system"])
         .with_bundle(input_bundle)?
-        .with(InputSystem::default(), "mouse_system", &["input_system"])
-        .with(MenuSystem::new(), "menu_system", &[]);
+        .with_timed(
+            WindowFocusSystem::default(),
+            "window_focus_system",
+            &["input_system"],
+        )
+        .with_timed(
+            RenderScaleSystem::default(),
+            "render_scale_system",
+            &["window_focus_system"],
+        )
+        .with_timed(
+            InputSystem::default(),
+            "mouse_system",
+            &["input_system", "window_focus_system"],
+        )
+        .with_timed(EmoteSystem::default(), "emote_system", &["input_system"])
+        .with_timed(VoteSystem::default(), "vote_system", &["input_system"])
+        .with_timed(
+            SpectatorCameraSystem::default(),
+            "spectator_camera_system",
+            &["input_system"],
+        )
+        .with_timed(
+            ClipCaptureSystem::default(),
+            "clip_capture_system",
+            &["input_system"],
+        )
+        .with_timed(
+            ScreenshotSystem::default(),
+            "screenshot_system",
+            &["input_system"],
+        )
+        .with_timed(
+            DesyncReportSystem::default(),
+            "desync_report_system",
+            &["input_system"],
+        )
+        .with_timed(
+            TimeScaleSystem::default(),
+            "time_scale_system",
+            &["input_system"],
+        )
+        .with_timed(MenuSystem::new(), "menu_system", &[])
+        .with_timed(
+            LevelPrefetchSystem,
+            "level_prefetch_system",
+            &["game_network_system"],
+        )
+        .with_thread_local(PresenceSystem::new());
 
     game_data_builder = build_game_logic_systems(game_data_builder, &mut builder.world, false)?
-        .with(
+        .with_timed(
             GameUpdatesBroadcastingSystem::default(),
             "game_updates_broadcasting_system",
             &["action_system"],
         )
-        .with(ParticleSystem, "particle_system", &["missile_dying_system"])
-        .with(
+        .with_timed(
+            RendezvousRelaySystem,
+            "rendezvous_relay_system",
+            &[
+                "net_connection_manager_system",
+                "game_network_system",
+                "game_updates_broadcasting_system",
+            ],
+        )
+        .with_timed(
+            ReconciliationSystem,
+            "reconciliation_system",
+            &["action_system"],
+        )
+        .with_timed(
+            ParticleSystem::default(),
+            "particle_system",
+            &["missile_dying_system"],
+        )
+        .with_timed(
+            MinionMarkerSystem,
+            "minion_marker_system",
+            &["spawner_system"],
+        )
+        .with_timed(
+            EliteNameplateSystem::default(),
+            "elite_nameplate_system",
+            &["action_system"],
+        )
+        .with_timed(
+            CombatNumberSystem::default(),
+            "combat_number_system",
+            &["action_system"],
+        )
+        .with_timed(ZoneDecalSystem, "zone_decal_system", &["zone_dying_system"])
+        .with_timed(
+            AuraRingSystem::default(),
+            "aura_ring_system",
+            &["action_system"],
+        )
+        .with_timed(
+            SpawnTelegraphSystem::default(),
+            "spawn_telegraph_system",
+            &["action_system"],
+        )
+        .with_timed(CorpseSystem::default(), "corpse_system", &["action_system"])
+        .with_timed(MusicSystem::default(), "music_system", &["action_system"])
+        .with_timed(
+            HitMarkerSystem::default(),
+            "hit_marker_system",
+            &["action_system"],
+        )
+        .with_timed(
             WorldPositionTransformSystem,
             "world_position_transform_system",
             &["particle_system"],
         )
-        .with(
-            CameraTranslationSystem,
-            "camera_translation_system",
-            &["world_position_transform_system"],
+        .with_timed(
+            CameraSystem::default(),
+            "camera_system",
+            &["world_position_transform_system", "spectator_camera_system"],
         )
+        .with_timed(MenuDemoSystem::default(), "menu_demo_system", &[])
         .with_bundle(TransformBundle::new().with_dep(&[
             "world_position_transform_system",
-            "camera_translation_system",
+            "camera_system",
+            "menu_demo_system",
         ]))?
         .with_system_desc(
             PrefabLoaderSystemDesc::<GameSpriteAnimationPrefab>::default(),
             "",
             &[],
         )
+        .with(
+            Processor::<SpriteAtlas>::new(),
+            "sprite_atlas_processor",
+            &[],
+        )
+        .with_timed(
+            CullingSystem::default(),
+            "culling_system",
+            &["transform_system"],
+        )
         .with_system_desc(
             HideHierarchySystemDesc::default(),
             "",
-            &["parent_hierarchy_system"],
+            &["parent_hierarchy_system", "culling_system", "corpse_system"],
+        )
+        .with_timed(HealthUiSystem, "health_ui_system", &["action_system"])
+        .with_timed(ScoreUiSystem, "score_ui_system", &["action_system"])
+        .with_timed(AugmentsUiSystem, "augments_ui_system", &["action_system"])
+        .with_timed(ShopUiSystem, "shop_ui_system", &["action_system"])
+        .with_timed(HintSystem::default(), "hint_system", &["action_system"])
+        .with_timed(
+            CharacterPanelUiSystem::default(),
+            "character_panel_ui_system",
+            &["action_system"],
+        )
+        .with_timed(
+            HighlightSystem::default(),
+            "highlight_system",
+            &["game_event_system"],
+        )
+        .with_timed(
+            AnnouncerSystem::default(),
+            "announcer_system",
+            &["game_event_system"],
+        )
+        .with_timed(
+            AchievementSystem::default(),
+            "achievement_system",
+            &["game_event_system"],
+        )
+        .with_timed(
+            EventTickerSystem::default(),
+            "event_ticker_system",
+            &[
+                "game_event_system",
+                "highlight_system",
+                "announcer_system",
+                "achievement_system",
+            ],
+        )
+        .with_timed(
+            ConnectionWarningUiSystem::default(),
+            "connection_warning_ui_system",
+            &[],
+        )
+        .with_timed(
+            ObjectiveUiSystem::default(),
+            "objective_ui_system",
+            &["objective_system"],
+        )
+        .with_timed(
+            LevelScriptMarkerSystem::default(),
+            "level_script_marker_system",
+            &["level_script_system"],
+        )
+        .with_timed(
+            LightingSystem::default(),
+            "lighting_system",
+            &["level_script_system", "transform_system"],
+        )
+        .with_timed(
+            PostProcessingSystem::default(),
+            "post_processing_system",
+            &["level_script_system"],
+        )
+        .with_timed(
+            PayloadUiSystem,
+            "payload_ui_system",
+            &["level_script_system"],
         )
-        .with(HealthUiSystem, "health_ui_system", &["action_system"])
+        .with_timed(BoundsVignetteSystem, "bounds_vignette_system", &[])
+        .with_timed(
+            PersonalBestsSystem::default(),
+            "personal_bests_system",
+            &["action_system"],
+        )
+        .with_timed(UiScaleSystem::default(), "ui_scale_system", &[])
+        .with_timed(UiThemeSystem::default(), "ui_theme_system", &[])
         .with_bundle(UiBundle::<StringBindings>::new())?
-        .with(
+        .with_bundle(AudioBundle::default())?
+        .with_timed(
             AnimationSystem,
             "animation_system",
-            &["world_position_transform_system"],
+            &["world_position_transform_system", "culling_system"],
         )
-        .with(
+        .with_timed(
             ImguiNetworkDebugInfoSystem,
             "imgui_network_debug_info_system",
             &["game_network_system"],
         )
+        .with(
+            ImguiFrameTimingsSystem,
+            "imgui_frame_timings_system",
+            &["game_network_system"],
+        )
+        .with_timed(
+            ImguiConsoleSystem::default(),
+            "imgui_console_system",
+            &["input_system"],
+        )
+        .with_timed(
+            ImguiEntityInspectorSystem::default(),
+            "imgui_entity_inspector_system",
+            &["input_system", "imgui_console_system"],
+        )
         .with_bundle(
             AnimationBundle::<AnimationId, SpriteRender>::new(
                 "animation_control_system",
@@ -231,22 +596,37 @@ fn main() -> amethyst::Result<()> {
             )
             .with_dep(&["animation_system"]),
         )?
+        .with_barrier()
+        .with(FrameTimingsSystem, "frame_timings_system", &[])
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
-                .with_plugin(RenderToWindow::from_config(display_config))
+                .with_plugin(RenderToWindowWithScreenshots::from_config(display_config))
                 .with_plugin(RenderFlat3D::default())
                 .with_plugin(RenderFlat2D::default())
+                .with_plugin(MonsterSpritesPlugin::default())
                 .with_plugin(PaintMagePlugin::default())
                 .with_plugin(MissilePlugin::default())
                 .with_plugin(SpellParticlePlugin::default())
                 .with_plugin(MobHealthPlugin::default())
+                .with_plugin(LightingPlugin::default())
                 .with_plugin(HealthUiPlugin::default())
                 .with_plugin(RenderUi::default())
                 .with_plugin(RenderImgui::<amethyst::input::StringBindings>::default()),
-        )?;
+        )?
+        .with_thread_local(FramePacingSystem::default());
+    #[cfg(feature = "inspector")]
+    {
+        game_data_builder = game_data_builder.with_timed(
+            ImguiWorldInspectorSystem::default(),
+            "imgui_world_inspector_system",
+            &[],
+        );
+    }
+    register_client_console_commands(&mut builder.world.fetch_mut::<ConsoleCommands>());
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        // Frames are paced by `FramePacingSystem`.
+        .with_frame_limit(FrameRateLimitStrategy::Unlimited, FRAME_RATE)
         .build(game_data_builder)?;
 
     game.run();
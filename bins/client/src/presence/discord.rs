@@ -0,0 +1,74 @@
+use discord_rpc_client::{models::Event, Client};
+
+use std::sync::{Arc, Mutex};
+
+use super::{Presence, PresencePublisher};
+
+pub struct DiscordPresence {
+    client: Client,
+    join_requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl DiscordPresence {
+    pub fn new(application_id: u64) -> Self {
+        let mut client = Client::new(application_id);
+        let join_requests = Arc::new(Mutex::new(Vec::new()));
+
+        let join_requests_handle = join_requests.clone();
+        client.on_activity_join(move |context| {
+            if let Some(secret) = context.event["secret"].as_str() {
+                log::info!("Accepted a Discord invite: {}", secret);
+                join_requests_handle
+                    .lock()
+                    .expect("Expected to lock join requests")
+                    .push(secret.to_owned());
+            }
+        });
+        client.start();
+        if let Err(err) = client.subscribe(Event::ActivityJoin, |subscription| subscription) {
+            log::warn!("Failed to subscribe to Discord invites: {:?}", err);
+        }
+
+        Self {
+            client,
+            join_requests,
+        }
+    }
+}
+
+impl PresencePublisher for DiscordPresence {
+    fn publish(&mut self, presence: &Presence) {
+        let invite = presence.invite().map(str::to_owned);
+        let party_size = match presence {
+            Presence::InLobby {
+                players,
+                max_players,
+                ..
+            } => Some((*players as u32, *max_players as u32)),
+            _ => None,
+        };
+        let result = self.client.set_activity(|activity| {
+            let activity = activity.state(presence.details());
+            let activity = if let Some(party_size) = party_size {
+                activity.party(|party| party.size(party_size))
+            } else {
+                activity
+            };
+            if let Some(invite) = invite {
+                activity.secrets(|secrets| secrets.join(invite))
+            } else {
+                activity
+            }
+        });
+        if let Err(err) = result {
+            log::warn!("Failed to update Discord presence: {:?}", err);
+        }
+    }
+
+    fn poll_join_request(&mut self) -> Option<String> {
+        self.join_requests
+            .lock()
+            .expect("Expected to lock join requests")
+            .pop()
+    }
+}
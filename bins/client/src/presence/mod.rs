@@ -0,0 +1,82 @@
+#[cfg(feature = "discord")]
+mod discord;
+#[cfg(feature = "steam")]
+mod steam;
+
+/// What the player is doing, as shown to their friends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Presence {
+    InMenu,
+    InLobby {
+        players: usize,
+        max_players: usize,
+        /// Friends can join using this, if the host has one to share.
+        invite: Option<String>,
+    },
+    InMatch {
+        players: usize,
+        minutes: u64,
+    },
+}
+
+impl Presence {
+    pub fn details(&self) -> String {
+        match self {
+            Presence::InMenu => "In menu".to_owned(),
+            Presence::InLobby {
+                players,
+                max_players,
+                ..
+            } => format!("In lobby {}/{}", players, max_players),
+            Presence::InMatch {
+                players: 1,
+                minutes,
+            } => {
+                format!("Playing solo, {} min", minutes)
+            }
+            Presence::InMatch { players, minutes } => {
+                format!("In a match with {} players, {} min", players, minutes)
+            }
+        }
+    }
+
+    pub fn invite(&self) -> Option<&str> {
+        match self {
+            Presence::InLobby { invite, .. } => invite.as_ref().map(String::as_str),
+            _ => None,
+        }
+    }
+}
+
+/// A service presence is published to. Accepted invites come back as join addresses.
+pub trait PresencePublisher {
+    fn publish(&mut self, presence: &Presence);
+
+    fn poll_join_request(&mut self) -> Option<String>;
+}
+
+/// Returns a publisher for each integration enabled with cargo features,
+/// an integration failing to initialize is skipped.
+pub fn create_publishers() -> Vec<Box<dyn PresencePublisher>> {
+    #[allow(unused_mut)]
+    let mut publishers: Vec<Box<dyn PresencePublisher>> = Vec::new();
+
+    #[cfg(feature = "discord")]
+    match std::env::var("GV_DISCORD_APPLICATION_ID")
+        .ok()
+        .and_then(|application_id| application_id.parse().ok())
+    {
+        Some(application_id) => {
+            publishers.push(Box::new(discord::DiscordPresence::new(application_id)))
+        }
+        None => log::warn!("GV_DISCORD_APPLICATION_ID isn't set, Discord presence is disabled"),
+    }
+
+    #[cfg(feature = "steam")]
+    match steam::SteamPresence::new() {
+        Ok(steam_presence) => publishers.push(Box::new(steam_presence)),
+        Err(err) => log::warn!("Failed to initialize Steam: {:?}", err),
+    }
+
+    publishers
+}
@@ -0,0 +1,53 @@
+use steamworks::{Client, GameRichPresenceJoinRequested, SingleClient, SteamError};
+
+use std::sync::{Arc, Mutex};
+
+use super::{Presence, PresencePublisher};
+
+/// Isn't `Send`, so the system owning it has to be thread-local.
+pub struct SteamPresence {
+    client: Client,
+    single_client: SingleClient,
+    join_requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl SteamPresence {
+    pub fn new() -> Result<Self, SteamError> {
+        let (client, single_client) = Client::init()?;
+        let join_requests = Arc::new(Mutex::new(Vec::new()));
+
+        let join_requests_handle = join_requests.clone();
+        // The callback lives as long as the client, there's no need to keep the handle.
+        std::mem::forget(client.register_callback(
+            move |join_request: GameRichPresenceJoinRequested| {
+                log::info!("Accepted a Steam invite: {}", join_request.connect);
+                join_requests_handle
+                    .lock()
+                    .expect("Expected to lock join requests")
+                    .push(join_request.connect);
+            },
+        ));
+
+        Ok(Self {
+            client,
+            single_client,
+            join_requests,
+        })
+    }
+}
+
+impl PresencePublisher for SteamPresence {
+    fn publish(&mut self, presence: &Presence) {
+        let friends = self.client.friends();
+        friends.set_rich_presence("status", Some(&presence.details()));
+        friends.set_rich_presence("connect", presence.invite());
+    }
+
+    fn poll_join_request(&mut self) -> Option<String> {
+        self.single_client.run_callbacks();
+        self.join_requests
+            .lock()
+            .expect("Expected to lock join requests")
+            .pop()
+    }
+}
@@ -28,6 +28,8 @@ use std::path::PathBuf;
 use gv_client_shared::ecs::{components::HealthUiGraphics, resources::HealthUiMesh};
 use gv_core::math::Vector2;
 
+use crate::ecs::resources::ScreenshotRequest;
+
 #[derive(Default, Debug)]
 pub struct HealthUiPlugin {
     target: Target,
@@ -162,12 +164,14 @@ impl<B: Backend> RenderGroup<B, World> for DrawHealthUi<B> {
         _: hal::pass::Subpass<'_, B>,
         world: &World,
     ) {
-        let (mesh_storage, health_ui_mesh_handle, health_ui_graphics) = <(
-            Read<'_, AssetStorage<Mesh>>,
-            Option<Read<'_, HealthUiMesh>>,
-            ReadStorage<'_, HealthUiGraphics>,
-        )>::fetch(world);
-        if health_ui_mesh_handle.is_none() {
+        let (mesh_storage, health_ui_mesh_handle, screenshot_request, health_ui_graphics) =
+            <(
+                Read<'_, AssetStorage<Mesh>>,
+                Option<Read<'_, HealthUiMesh>>,
+                Read<'_, ScreenshotRequest>,
+                ReadStorage<'_, HealthUiGraphics>,
+            )>::fetch(world);
+        if health_ui_mesh_handle.is_none() || screenshot_request.is_hud_hidden {
             return;
         }
 
@@ -0,0 +1,449 @@
+use amethyst::{
+    core::ecs::{ReadExpect, SystemData, World},
+    error::Error,
+    renderer::{
+        bundle::{RenderOrder, RenderPlan, RenderPlugin, Target},
+        pipeline::{PipelineDescBuilder, PipelinesBuilder},
+        rendy::{
+            command::{QueueId, RenderPassEncoder},
+            factory::Factory,
+            graph::{
+                render::{PrepareResult, RenderGroup, RenderGroupDesc},
+                GraphContext, NodeBuffer, NodeImage,
+            },
+            hal::{self, device::Device, format::Format, pso},
+            mesh::AsVertex,
+            shader::{PathBufShaderInfo, Shader, ShaderKind, SourceLanguage, SpirvShader},
+            util::types::vertex::VertexFormat,
+        },
+        submodules::{DynamicVertexBuffer, FlatEnvironmentSub},
+        types::Backend,
+        util,
+    },
+};
+use derivative::Derivative;
+use glsl_layout::{float, vec2, AsStd140};
+
+use std::path::PathBuf;
+
+use gv_client_shared::{
+    lighting::{light_shape, shadow_origins},
+    settings::Settings,
+};
+use gv_core::{ecs::resources::GameLevelState, level_scripts::DAYLIGHT, math::Vector2};
+
+use crate::ecs::resources::SceneLighting;
+
+/// Darkens the world according to `SceneLighting`. The light level is accumulated in
+/// the alpha channel of the main target: it's set to the ambient light, the lit area of every
+/// light is added on top of it, and the colors are multiplied by it in the end.
+/// Everything drawn after it (the UI) isn't affected.
+#[derive(Default, Debug)]
+pub struct LightingPlugin {
+    target: Target,
+}
+
+impl<B: Backend> RenderPlugin<B> for LightingPlugin {
+    fn on_plan(
+        &mut self,
+        plan: &mut RenderPlan<B>,
+        _factory: &mut Factory<B>,
+        _world: &World,
+    ) -> Result<(), Error> {
+        plan.extend_target(self.target, |ctx| {
+            ctx.add(
+                RenderOrder::LinearPostEffects,
+                DrawLightingDesc::new().builder(),
+            )?;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SCREEN_VERTEX_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/screen.vert"),
+        ShaderKind::Vertex,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref SCREEN_VERTEX: SpirvShader = SpirvShader::new(
+        (*SCREEN_VERTEX_SRC).spirv().unwrap().to_vec(),
+        (*SCREEN_VERTEX_SRC).stage(),
+        "main",
+    );
+
+    static ref FILL_FRAGMENT_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/lighting_fill.frag"),
+        ShaderKind::Fragment,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref FILL_FRAGMENT: SpirvShader = SpirvShader::new(
+        (*FILL_FRAGMENT_SRC).spirv().unwrap().to_vec(),
+        (*FILL_FRAGMENT_SRC).stage(),
+        "main",
+    );
+
+    static ref LIGHT_VERTEX_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/light.vert"),
+        ShaderKind::Vertex,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref LIGHT_VERTEX: SpirvShader = SpirvShader::new(
+        (*LIGHT_VERTEX_SRC).spirv().unwrap().to_vec(),
+        (*LIGHT_VERTEX_SRC).stage(),
+        "main",
+    );
+
+    static ref LIGHT_FRAGMENT_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/light.frag"),
+        ShaderKind::Fragment,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref LIGHT_FRAGMENT: SpirvShader = SpirvShader::new(
+        (*LIGHT_FRAGMENT_SRC).spirv().unwrap().to_vec(),
+        (*LIGHT_FRAGMENT_SRC).stage(),
+        "main",
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct DrawLightingDesc;
+
+impl DrawLightingDesc {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<B: Backend> RenderGroupDesc<B, World> for DrawLightingDesc {
+    fn build(
+        self,
+        _ctx: &GraphContext<B>,
+        factory: &mut Factory<B>,
+        _queue: QueueId,
+        _world: &World,
+        framebuffer_width: u32,
+        framebuffer_height: u32,
+        subpass: hal::pass::Subpass<'_, B>,
+        _buffers: Vec<NodeBuffer>,
+        _images: Vec<NodeImage>,
+    ) -> Result<Box<dyn RenderGroup<B, World>>, failure::Error> {
+        let env = FlatEnvironmentSub::new(factory)?;
+        let vertex = DynamicVertexBuffer::new();
+
+        let (fill_pipelines, fill_pipeline_layout) =
+            build_fill_pipelines(factory, subpass, framebuffer_width, framebuffer_height)?;
+        let (light_pipeline, light_pipeline_layout) = build_light_pipeline(
+            factory,
+            subpass,
+            framebuffer_width,
+            framebuffer_height,
+            vec![env.raw_layout()],
+        )?;
+        let mut fill_pipelines = fill_pipelines.into_iter();
+
+        Ok(Box::new(DrawLighting::<B> {
+            ambient_pipeline: fill_pipelines.next().unwrap(),
+            apply_pipeline: fill_pipelines.next().unwrap(),
+            fill_pipeline_layout,
+            light_pipeline,
+            light_pipeline_layout,
+            env,
+            vertex,
+            vertices: Vec::new(),
+            shape: Vec::new(),
+            ambient: DAYLIGHT,
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, AsStd140)]
+#[repr(C, align(4))]
+pub struct LightVertexData {
+    pub pos: vec2,
+    pub intensity: float,
+    /// The distance to the light divided by its radius.
+    pub distance: float,
+}
+
+impl AsVertex for LightVertexData {
+    fn vertex() -> VertexFormat {
+        VertexFormat::new((
+            (Format::Rg32Sfloat, "pos"),
+            (Format::R32Sfloat, "intensity"),
+            (Format::R32Sfloat, "distance"),
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct DrawLighting<B: Backend> {
+    /// Writes the ambient light to the alpha channel.
+    ambient_pipeline: B::GraphicsPipeline,
+    /// Multiplies the colors by the alpha channel and resets it.
+    apply_pipeline: B::GraphicsPipeline,
+    fill_pipeline_layout: B::PipelineLayout,
+    /// Adds lit areas to the alpha channel.
+    light_pipeline: B::GraphicsPipeline,
+    light_pipeline_layout: B::PipelineLayout,
+    env: FlatEnvironmentSub<B>,
+    vertex: DynamicVertexBuffer<B, LightVertexData>,
+    /// Are kept between frames to avoid reallocating them.
+    vertices: Vec<LightVertexData>,
+    shape: Vec<Vector2>,
+    ambient: f32,
+}
+
+impl<B: Backend> DrawLighting<B> {
+    fn push_lit_area(&mut self, origin: Vector2, radius: f32, intensity: f32) {
+        let vertex = |point: Vector2| LightVertexData {
+            pos: [point.x, point.y],
+            intensity,
+            distance: (point - origin).norm() / radius,
+        };
+        for (i, point) in self.shape.iter().enumerate() {
+            let next_point = self.shape[(i + 1) % self.shape.len()];
+            self.vertices.push(vertex(origin));
+            self.vertices.push(vertex(*point));
+            self.vertices.push(vertex(next_point));
+        }
+    }
+}
+
+impl<B: Backend> RenderGroup<B, World> for DrawLighting<B> {
+    fn prepare(
+        &mut self,
+        factory: &Factory<B>,
+        _queue: QueueId,
+        index: usize,
+        _subpass: hal::pass::Subpass<'_, B>,
+        world: &World,
+    ) -> PrepareResult {
+        let (settings, game_level_state, scene_lighting) = <(
+            ReadExpect<'_, Settings>,
+            ReadExpect<'_, GameLevelState>,
+            ReadExpect<'_, SceneLighting>,
+        )>::fetch(world);
+
+        self.env.process(factory, index, world);
+        self.ambient = scene_lighting.ambient;
+        self.vertices.clear();
+
+        let quality = settings.video().lighting_quality;
+        let samples = quality.shadow_samples();
+        let grid = if samples > 0 {
+            Some(&game_level_state.collision_grid)
+        } else {
+            None
+        };
+        for light in &scene_lighting.lights {
+            let origins = shadow_origins(light.position, samples);
+            let intensity = light.intensity / origins.len() as f32;
+            for origin in origins {
+                light_shape(grid, origin, light.radius, quality.rays(), &mut self.shape);
+                self.push_lit_area(origin, light.radius, intensity);
+            }
+        }
+
+        self.vertex.write(
+            factory,
+            index,
+            self.vertices.len() as u64,
+            Some(&self.vertices),
+        );
+
+        PrepareResult::DrawRecord
+    }
+
+    fn draw_inline(
+        &mut self,
+        mut encoder: RenderPassEncoder<'_, B>,
+        index: usize,
+        _subpass: hal::pass::Subpass<'_, B>,
+        _world: &World,
+    ) {
+        if self.ambient >= DAYLIGHT {
+            return;
+        }
+
+        encoder.bind_graphics_pipeline(&self.ambient_pipeline);
+        unsafe {
+            encoder.push_constants(
+                &self.fill_pipeline_layout,
+                pso::ShaderStageFlags::FRAGMENT,
+                0,
+                &[self.ambient.to_bits()],
+            );
+            encoder.draw(0..3, 0..1);
+        }
+
+        if !self.vertices.is_empty() {
+            let layout = &self.light_pipeline_layout;
+            encoder.bind_graphics_pipeline(&self.light_pipeline);
+            self.env.bind(index, layout, 0, &mut encoder);
+            self.vertex.bind(index, 0, 0, &mut encoder);
+            unsafe {
+                encoder.draw(0..self.vertices.len() as u32, 0..1);
+            }
+        }
+
+        encoder.bind_graphics_pipeline(&self.apply_pipeline);
+        unsafe {
+            encoder.push_constants(
+                &self.fill_pipeline_layout,
+                pso::ShaderStageFlags::FRAGMENT,
+                0,
+                &[DAYLIGHT.to_bits()],
+            );
+            encoder.draw(0..3, 0..1);
+        }
+    }
+
+    fn dispose(self: Box<Self>, factory: &mut Factory<B>, _aux: &World) {
+        unsafe {
+            factory
+                .device()
+                .destroy_graphics_pipeline(self.ambient_pipeline);
+            factory
+                .device()
+                .destroy_graphics_pipeline(self.apply_pipeline);
+            factory
+                .device()
+                .destroy_graphics_pipeline(self.light_pipeline);
+            factory
+                .device()
+                .destroy_pipeline_layout(self.fill_pipeline_layout);
+            factory
+                .device()
+                .destroy_pipeline_layout(self.light_pipeline_layout);
+        }
+    }
+}
+
+/// Builds the fullscreen pipelines writing the light level given with a push constant:
+/// the first one sets the alpha channel to it, the second one multiplies the colors by
+/// the alpha channel and replaces it.
+fn build_fill_pipelines<B: Backend>(
+    factory: &Factory<B>,
+    subpass: hal::pass::Subpass<'_, B>,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+) -> Result<(Vec<B::GraphicsPipeline>, B::PipelineLayout), failure::Error> {
+    let pipeline_layout = unsafe {
+        factory.device().create_pipeline_layout(
+            Vec::<&B::DescriptorSetLayout>::new(),
+            vec![(pso::ShaderStageFlags::FRAGMENT, 0..4)],
+        )
+    }?;
+
+    let shader_vertex = unsafe { SCREEN_VERTEX.module(factory).unwrap() };
+    let shader_fragment = unsafe { FILL_FRAGMENT.module(factory).unwrap() };
+
+    let fill_pipeline = |blend_target| {
+        PipelineDescBuilder::new()
+            .with_input_assembler(pso::InputAssemblerDesc::new(hal::Primitive::TriangleList))
+            .with_shaders(util::simple_shader_set(
+                &shader_vertex,
+                Some(&shader_fragment),
+            ))
+            .with_layout(&pipeline_layout)
+            .with_subpass(subpass)
+            .with_framebuffer_size(framebuffer_width, framebuffer_height)
+            .with_blend_targets(vec![blend_target])
+    };
+    let pipes = PipelinesBuilder::new()
+        .with_pipeline(fill_pipeline(pso::ColorBlendDesc {
+            mask: pso::ColorMask::ALPHA,
+            blend: None,
+        }))
+        .with_pipeline(fill_pipeline(pso::ColorBlendDesc {
+            mask: pso::ColorMask::ALL,
+            blend: Some(pso::BlendState {
+                color: pso::BlendOp::Add {
+                    src: pso::Factor::Zero,
+                    dst: pso::Factor::DstAlpha,
+                },
+                alpha: pso::BlendOp::Add {
+                    src: pso::Factor::One,
+                    dst: pso::Factor::Zero,
+                },
+            }),
+        }))
+        .build(factory, None);
+
+    unsafe {
+        factory.destroy_shader_module(shader_vertex);
+        factory.destroy_shader_module(shader_fragment);
+    }
+
+    match pipes {
+        Err(e) => {
+            unsafe {
+                factory.device().destroy_pipeline_layout(pipeline_layout);
+            }
+            Err(e)
+        }
+        Ok(pipes) => Ok((pipes, pipeline_layout)),
+    }
+}
+
+fn build_light_pipeline<B: Backend>(
+    factory: &Factory<B>,
+    subpass: hal::pass::Subpass<'_, B>,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+    layouts: Vec<&B::DescriptorSetLayout>,
+) -> Result<(B::GraphicsPipeline, B::PipelineLayout), failure::Error> {
+    let pipeline_layout = unsafe {
+        factory
+            .device()
+            .create_pipeline_layout(layouts, None as Option<(_, _)>)
+    }?;
+
+    let shader_vertex = unsafe { LIGHT_VERTEX.module(factory).unwrap() };
+    let shader_fragment = unsafe { LIGHT_FRAGMENT.module(factory).unwrap() };
+
+    let pipes = PipelinesBuilder::new()
+        .with_pipeline(
+            PipelineDescBuilder::new()
+                .with_vertex_desc(&[(LightVertexData::vertex(), pso::VertexInputRate::Vertex)])
+                .with_input_assembler(pso::InputAssemblerDesc::new(hal::Primitive::TriangleList))
+                .with_shaders(util::simple_shader_set(
+                    &shader_vertex,
+                    Some(&shader_fragment),
+                ))
+                .with_layout(&pipeline_layout)
+                .with_subpass(subpass)
+                .with_framebuffer_size(framebuffer_width, framebuffer_height)
+                .with_blend_targets(vec![pso::ColorBlendDesc {
+                    mask: pso::ColorMask::ALPHA,
+                    blend: Some(pso::BlendState::ADD),
+                }]),
+        )
+        .build(factory, None);
+
+    unsafe {
+        factory.destroy_shader_module(shader_vertex);
+        factory.destroy_shader_module(shader_fragment);
+    }
+
+    match pipes {
+        Err(e) => {
+            unsafe {
+                factory.device().destroy_pipeline_layout(pipeline_layout);
+            }
+            Err(e)
+        }
+        Ok(mut pipes) => Ok((pipes.remove(0), pipeline_layout)),
+    }
+}
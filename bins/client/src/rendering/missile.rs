@@ -31,6 +31,7 @@ use glsl_layout::{float, vec2, AsStd140};
 
 use std::path::PathBuf;
 
+use gv_client_shared::ecs::components::{Culled, MenuDemoMissile};
 use gv_core::ecs::{
     components::{missile::Missile, Dead},
     system_data::time::GameTimeService,
@@ -137,7 +138,7 @@ impl<B: Backend> RenderGroupDesc<B, World> for DrawMissileDesc {
             pipeline_layout,
             env,
             vertex,
-            missiles_count: 0,
+            vertices: Vec::new(),
         }))
     }
 }
@@ -169,7 +170,8 @@ pub struct DrawMissile<B: Backend> {
     pipeline_layout: B::PipelineLayout,
     env: FlatEnvironmentSub<B>,
     vertex: DynamicVertexBuffer<B, MissileVertexData>,
-    missiles_count: u32,
+    /// Is kept between frames to avoid reallocating it.
+    vertices: Vec<MissileVertexData>,
 }
 
 impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
@@ -181,49 +183,72 @@ impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
         _subpass: hal::pass::Subpass<'_, B>,
         world: &World,
     ) -> PrepareResult {
-        let (game_time_service, transforms, missiles, dead) = <(
-            GameTimeService<'_>,
-            ReadStorage<'_, Transform>,
-            ReadStorage<'_, Missile>,
-            ReadStorage<'_, Dead>,
-        )>::fetch(world);
+        let (game_time_service, transforms, missiles, menu_demo_missiles, dead, culled) =
+            <(
+                GameTimeService<'_>,
+                ReadStorage<'_, Transform>,
+                ReadStorage<'_, Missile>,
+                ReadStorage<'_, MenuDemoMissile>,
+                ReadStorage<'_, Dead>,
+                ReadStorage<'_, Culled>,
+            )>::fetch(world);
 
         self.env.process(factory, index, world);
 
-        let vertices = (&transforms, &missiles, dead.maybe())
-            .join()
-            .map(|(transform, missile, dead)| {
-                let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
-                let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
-                    .xy()
-                    .into_pod();
-                let seconds_since_spawn = game_time_service.seconds_to_frame(missile.frame_spawned);
-                let opacity = missile_energy(
-                    &missile,
-                    dead.map_or(false, |dead| {
-                        dead.is_dead(game_time_service.game_frame_number())
-                    }),
-                    &game_time_service,
-                    game_time_service.game_frame_number(),
-                );
-                let ttl = dead.map_or(1.0, |dead| {
-                    1.0 - game_time_service
-                        .seconds_to_frame(dead.dead_since_frame)
-                        .clamp(0.0, MISSILE_TTL_SECS)
-                        / MISSILE_TTL_SECS
-                });
-                MissileVertexData {
-                    pos,
-                    seconds_since_spawn,
-                    opacity,
-                    ttl,
-                }
-            })
-            .collect::<Vec<_>>();
+        self.vertices.clear();
+        self.vertices
+            .extend((&transforms, &missiles, dead.maybe(), !&culled).join().map(
+                |(transform, missile, dead, _)| {
+                    let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                    let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
+                        .xy()
+                        .into_pod();
+                    let seconds_since_spawn =
+                        game_time_service.seconds_to_frame(missile.frame_spawned);
+                    let opacity = missile_energy(
+                        &missile,
+                        dead.map_or(false, |dead| {
+                            dead.is_dead(game_time_service.game_frame_number())
+                        }),
+                        &game_time_service,
+                        game_time_service.game_frame_number(),
+                    );
+                    let ttl = dead.map_or(1.0, |dead| {
+                        1.0 - game_time_service
+                            .seconds_to_frame(dead.dead_since_frame)
+                            .clamp(0.0, MISSILE_TTL_SECS)
+                            / MISSILE_TTL_SECS
+                    });
+                    MissileVertexData {
+                        pos,
+                        seconds_since_spawn,
+                        opacity,
+                        ttl,
+                    }
+                },
+            ));
+        self.vertices
+            .extend((&transforms, &menu_demo_missiles).join().map(
+                |(transform, menu_demo_missile)| {
+                    let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                    let pos = (transform * Vector4::new(0.0, 0.0, 0.0, 1.0))
+                        .xy()
+                        .into_pod();
+                    MissileVertexData {
+                        pos,
+                        seconds_since_spawn: menu_demo_missile.age_secs,
+                        opacity: 1.0,
+                        ttl: 1.0,
+                    }
+                },
+            ));
 
-        self.missiles_count = vertices.len() as u32;
-        self.vertex
-            .write(factory, index, vertices.len() as u64, Some(vertices));
+        self.vertex.write(
+            factory,
+            index,
+            self.vertices.len() as u64,
+            Some(&self.vertices),
+        );
 
         PrepareResult::DrawRecord
     }
@@ -240,7 +265,7 @@ impl<B: Backend> RenderGroup<B, World> for DrawMissile<B> {
         self.env.bind(index, layout, 0, &mut encoder);
         self.vertex.bind(index, 0, 0, &mut encoder);
         unsafe {
-            encoder.draw(0..4, 0..self.missiles_count);
+            encoder.draw(0..4, 0..self.vertices.len() as u32);
         }
     }
 
@@ -32,6 +32,7 @@ use glsl_layout::{float, vec2, AsStd140};
 use std::path::PathBuf;
 
 use crate::ecs::resources::DisplayDebugInfoSettings;
+use gv_client_shared::ecs::components::Culled;
 use gv_core::ecs::components::{Dead, Monster};
 
 const MONSTER_SPRITE_SIZE: f32 = 64.0;
@@ -136,7 +137,7 @@ impl<B: Backend> RenderGroupDesc<B, World> for DrawMobHealthDesc {
             pipeline_layout,
             env,
             vertex,
-            monsters_count: 0,
+            vertices: Vec::new(),
         }))
     }
 }
@@ -165,7 +166,8 @@ pub struct DrawMobHealth<B: Backend> {
     pipeline_layout: B::PipelineLayout,
     env: FlatEnvironmentSub<B>,
     vertex: DynamicVertexBuffer<B, MobHealthVertexData>,
-    monsters_count: u32,
+    /// Is kept between frames to avoid reallocating it.
+    vertices: Vec<MobHealthVertexData>,
 }
 
 impl<B: Backend> RenderGroup<B, World> for DrawMobHealth<B> {
@@ -177,38 +179,42 @@ impl<B: Backend> RenderGroup<B, World> for DrawMobHealth<B> {
         _subpass: hal::pass::Subpass<'_, B>,
         world: &World,
     ) -> PrepareResult {
-        let (display_debug_info_settings, transforms, monsters, dead) = <(
-            ReadExpect<'_, DisplayDebugInfoSettings>,
-            ReadStorage<'_, Transform>,
-            ReadStorage<'_, Monster>,
-            ReadStorage<'_, Dead>,
-        )>::fetch(world);
+        let (display_debug_info_settings, transforms, monsters, dead, culled) =
+            <(
+                ReadExpect<'_, DisplayDebugInfoSettings>,
+                ReadStorage<'_, Transform>,
+                ReadStorage<'_, Monster>,
+                ReadStorage<'_, Dead>,
+                ReadStorage<'_, Culled>,
+            )>::fetch(world);
 
         self.env.process(factory, index, world);
-        let vertices = if display_debug_info_settings.display_health {
-            (&transforms, &monsters, !&dead)
-                .join()
-                .map(|(transform, monster, _)| {
-                    let bar_y_displacement = -(MONSTER_SPRITE_SIZE / 2.0);
-                    let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
-                    let pos = (transform * Vector4::new(0.0, bar_y_displacement, 0.0, 1.0))
-                        .xy()
-                        .into_pod();
+        self.vertices.clear();
+        if display_debug_info_settings.display_health {
+            self.vertices
+                .extend((&transforms, &monsters, !&dead, !&culled).join().map(
+                    |(transform, monster, _, _)| {
+                        let bar_y_displacement = -(MONSTER_SPRITE_SIZE / 2.0);
+                        let transform = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                        let pos = (transform * Vector4::new(0.0, bar_y_displacement, 0.0, 1.0))
+                            .xy()
+                            .into_pod();
 
-                    MobHealthVertexData {
-                        pos,
-                        health: monster.health / 100.0,
-                        size: MONSTER_SPRITE_SIZE,
-                    }
-                })
-                .collect::<Vec<_>>()
-        } else {
-            Vec::new()
-        };
+                        MobHealthVertexData {
+                            pos,
+                            health: monster.health / 100.0,
+                            size: MONSTER_SPRITE_SIZE,
+                        }
+                    },
+                ));
+        }
 
-        self.monsters_count = vertices.len() as u32;
-        self.vertex
-            .write(factory, index, vertices.len() as u64, Some(vertices));
+        self.vertex.write(
+            factory,
+            index,
+            self.vertices.len() as u64,
+            Some(&self.vertices),
+        );
 
         PrepareResult::DrawRecord
     }
@@ -225,9 +231,8 @@ impl<B: Backend> RenderGroup<B, World> for DrawMobHealth<B> {
         self.env.bind(index, layout, 0, &mut encoder);
         self.vertex.bind(index, 0, 0, &mut encoder);
         unsafe {
-            encoder.draw(0..4, 0..self.monsters_count);
+            encoder.draw(0..4, 0..self.vertices.len() as u32);
         }
-        if self.monsters_count > 0 {}
     }
 
     fn dispose(self: Box<Self>, factory: &mut Factory<B>, _aux: &World) {
@@ -1,11 +1,18 @@
 pub use health_ui::HealthUiPlugin;
+pub use lighting::LightingPlugin;
 pub use missile::MissilePlugin;
 pub use mob_health::MobHealthPlugin;
+pub use monster_sprites::MonsterSpritesPlugin;
 pub use paint_mage::PaintMagePlugin;
+pub use screenshot::RenderToWindowWithScreenshots;
 pub use spell_particle::SpellParticlePlugin;
 
 mod health_ui;
+mod lighting;
 mod missile;
 mod mob_health;
+mod monster_sprites;
 mod paint_mage;
+mod post_processing;
+mod screenshot;
 mod spell_particle;
@@ -0,0 +1,315 @@
+use amethyst::{
+    assets::AssetStorage,
+    core::{
+        ecs::{DispatcherBuilder, Join, Read, ReadExpect, ReadStorage, SystemData, World},
+        transform::Transform,
+    },
+    error::Error,
+    renderer::{
+        batch::{GroupIterator, OneLevelBatch},
+        bundle::{RenderOrder, RenderPlan, RenderPlugin, Target},
+        pipeline::{PipelineDescBuilder, PipelinesBuilder},
+        pod::SpriteArgs,
+        rendy::{
+            command::{QueueId, RenderPassEncoder},
+            factory::Factory,
+            graph::{
+                render::{PrepareResult, RenderGroup, RenderGroupDesc},
+                GraphContext, NodeBuffer, NodeImage,
+            },
+            hal::{self, device::Device, pso},
+            mesh::AsVertex,
+            shader::{PathBufShaderInfo, Shader, ShaderKind, SourceLanguage, SpirvShader},
+        },
+        resources::Tint,
+        sprite::{SpriteRender, SpriteSheet},
+        submodules::{DynamicVertexBuffer, FlatEnvironmentSub, TextureId, TextureSub},
+        types::{Backend, Texture},
+        util,
+    },
+};
+use derivative::Derivative;
+
+use std::path::PathBuf;
+
+use crate::ecs::systems::{MonsterSpriteBatchingSystem, MonsterSprites};
+
+/// Draws the sprites of monsters with a single instanced draw call per sprite sheet.
+/// Monsters are drawn before the other transparent sprites and write depth, so the sprites
+/// under them (decals, auras) are still covered and the ones above them aren't.
+#[derive(Default, Debug)]
+pub struct MonsterSpritesPlugin {
+    target: Target,
+}
+
+impl<B: Backend> RenderPlugin<B> for MonsterSpritesPlugin {
+    fn on_build<'a, 'b>(
+        &mut self,
+        _world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        builder.add(
+            MonsterSpriteBatchingSystem,
+            "monster_sprite_batching_system",
+            &["sprite_visibility_system"],
+        );
+        Ok(())
+    }
+
+    fn on_plan(
+        &mut self,
+        plan: &mut RenderPlan<B>,
+        _factory: &mut Factory<B>,
+        _world: &World,
+    ) -> Result<(), Error> {
+        plan.extend_target(self.target, |ctx| {
+            ctx.add(
+                RenderOrder::BeforeTransparent,
+                DrawMonsterSpritesDesc::new().builder(),
+            )?;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    // Takes `SpriteArgs`, like the one of `PaintMagePlugin`.
+    static ref VERTEX_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/paint_mage.vert"),
+        ShaderKind::Vertex,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref VERTEX: SpirvShader = SpirvShader::new(
+        (*VERTEX_SRC).spirv().unwrap().to_vec(),
+        (*VERTEX_SRC).stage(),
+        "main",
+    );
+
+    static ref FRAGMENT_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/monster_sprite.frag"),
+        ShaderKind::Fragment,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref FRAGMENT: SpirvShader = SpirvShader::new(
+        (*FRAGMENT_SRC).spirv().unwrap().to_vec(),
+        (*FRAGMENT_SRC).stage(),
+        "main",
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct DrawMonsterSpritesDesc;
+
+impl DrawMonsterSpritesDesc {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<B: Backend> RenderGroupDesc<B, World> for DrawMonsterSpritesDesc {
+    fn build(
+        self,
+        _ctx: &GraphContext<B>,
+        factory: &mut Factory<B>,
+        _queue: QueueId,
+        _world: &World,
+        framebuffer_width: u32,
+        framebuffer_height: u32,
+        subpass: hal::pass::Subpass<'_, B>,
+        _buffers: Vec<NodeBuffer>,
+        _images: Vec<NodeImage>,
+    ) -> Result<Box<dyn RenderGroup<B, World>>, failure::Error> {
+        let env = FlatEnvironmentSub::new(factory)?;
+        let textures = TextureSub::new(factory)?;
+        let vertex = DynamicVertexBuffer::new();
+
+        let (pipeline, pipeline_layout) = build_sprite_pipeline(
+            factory,
+            subpass,
+            framebuffer_width,
+            framebuffer_height,
+            vec![env.raw_layout(), textures.raw_layout()],
+        )?;
+
+        Ok(Box::new(DrawMonsterSprites::<B> {
+            pipeline,
+            pipeline_layout,
+            env,
+            textures,
+            vertex,
+            sprites: Default::default(),
+            change: Default::default(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct DrawMonsterSprites<B: Backend> {
+    pipeline: B::GraphicsPipeline,
+    pipeline_layout: B::PipelineLayout,
+    env: FlatEnvironmentSub<B>,
+    textures: TextureSub<B>,
+    vertex: DynamicVertexBuffer<B, SpriteArgs>,
+    /// The instances of every sprite sheet are kept together, in any order.
+    sprites: OneLevelBatch<TextureId, SpriteArgs>,
+    change: util::ChangeDetection,
+}
+
+impl<B: Backend> RenderGroup<B, World> for DrawMonsterSprites<B> {
+    fn prepare(
+        &mut self,
+        factory: &Factory<B>,
+        _queue: QueueId,
+        index: usize,
+        _subpass: hal::pass::Subpass<'_, B>,
+        world: &World,
+    ) -> PrepareResult {
+        let (sprite_sheet_storage, tex_storage, monster_sprites, sprite_renders, transforms, tints) =
+            <(
+                Read<'_, AssetStorage<SpriteSheet>>,
+                Read<'_, AssetStorage<Texture>>,
+                ReadExpect<'_, MonsterSprites>,
+                ReadStorage<'_, SpriteRender>,
+                ReadStorage<'_, Transform>,
+                ReadStorage<'_, Tint>,
+            )>::fetch(world);
+
+        self.env.process(factory, index, world);
+        self.sprites.clear_inner();
+        let mut changed = false;
+
+        let sprites_ref = &mut self.sprites;
+        let textures_ref = &mut self.textures;
+        (
+            &sprite_renders,
+            &transforms,
+            tints.maybe(),
+            &monster_sprites.0,
+        )
+            .join()
+            .filter_map(|(sprite_render, global, tint, _)| {
+                let (batch_data, texture) = SpriteArgs::from_data(
+                    &tex_storage,
+                    &sprite_sheet_storage,
+                    &sprite_render,
+                    &global,
+                    tint,
+                )?;
+                let (tex_id, this_changed) = textures_ref.insert(
+                    factory,
+                    world,
+                    texture,
+                    hal::image::Layout::ShaderReadOnlyOptimal,
+                )?;
+                changed = changed || this_changed;
+                Some((tex_id, batch_data))
+            })
+            .for_each_group(|tex_id, batch_data| {
+                sprites_ref.insert(tex_id, batch_data.drain(..));
+            });
+
+        self.textures.maintain(factory, world);
+        self.sprites.prune();
+        changed = changed || self.sprites.changed();
+
+        self.vertex.write(
+            factory,
+            index,
+            self.sprites.count() as u64,
+            self.sprites.data(),
+        );
+
+        self.change.prepare_result(index, changed)
+    }
+
+    fn draw_inline(
+        &mut self,
+        mut encoder: RenderPassEncoder<'_, B>,
+        index: usize,
+        _subpass: hal::pass::Subpass<'_, B>,
+        _world: &World,
+    ) {
+        let layout = &self.pipeline_layout;
+        encoder.bind_graphics_pipeline(&self.pipeline);
+        self.env.bind(index, layout, 0, &mut encoder);
+        self.vertex.bind(index, 0, 0, &mut encoder);
+        for (&tex, range) in self.sprites.iter() {
+            if self.textures.loaded(tex) {
+                self.textures.bind(layout, 1, tex, &mut encoder);
+                unsafe {
+                    encoder.draw(0..4, range);
+                }
+            }
+        }
+    }
+
+    fn dispose(self: Box<Self>, factory: &mut Factory<B>, _aux: &World) {
+        unsafe {
+            factory.device().destroy_graphics_pipeline(self.pipeline);
+            factory
+                .device()
+                .destroy_pipeline_layout(self.pipeline_layout);
+        }
+    }
+}
+
+fn build_sprite_pipeline<B: Backend>(
+    factory: &Factory<B>,
+    subpass: hal::pass::Subpass<'_, B>,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+    layouts: Vec<&B::DescriptorSetLayout>,
+) -> Result<(B::GraphicsPipeline, B::PipelineLayout), failure::Error> {
+    let pipeline_layout = unsafe {
+        factory
+            .device()
+            .create_pipeline_layout(layouts, None as Option<(_, _)>)
+    }?;
+
+    let shader_vertex = unsafe { VERTEX.module(factory).unwrap() };
+    let shader_fragment = unsafe { FRAGMENT.module(factory).unwrap() };
+
+    let pipes = PipelinesBuilder::new()
+        .with_pipeline(
+            PipelineDescBuilder::new()
+                .with_vertex_desc(&[(SpriteArgs::vertex(), pso::VertexInputRate::Instance(1))])
+                .with_input_assembler(pso::InputAssemblerDesc::new(hal::Primitive::TriangleStrip))
+                .with_shaders(util::simple_shader_set(
+                    &shader_vertex,
+                    Some(&shader_fragment),
+                ))
+                .with_layout(&pipeline_layout)
+                .with_subpass(subpass)
+                .with_framebuffer_size(framebuffer_width, framebuffer_height)
+                .with_blend_targets(vec![pso::ColorBlendDesc {
+                    mask: pso::ColorMask::ALL,
+                    blend: Some(pso::BlendState::PREMULTIPLIED_ALPHA),
+                }])
+                .with_depth_test(pso::DepthTest {
+                    fun: pso::Comparison::Less,
+                    write: true,
+                }),
+        )
+        .build(factory, None);
+
+    unsafe {
+        factory.destroy_shader_module(shader_vertex);
+        factory.destroy_shader_module(shader_fragment);
+    }
+
+    match pipes {
+        Err(e) => {
+            unsafe {
+                factory.device().destroy_pipeline_layout(pipeline_layout);
+            }
+            Err(e)
+        }
+        Ok(mut pipes) => Ok((pipes.remove(0), pipeline_layout)),
+    }
+}
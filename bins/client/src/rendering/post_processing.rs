@@ -0,0 +1,343 @@
+use amethyst::{
+    core::ecs::{ReadExpect, SystemData, World},
+    renderer::{
+        pipeline::{PipelineDescBuilder, PipelinesBuilder},
+        rendy::{
+            command::{QueueId, RenderPassEncoder},
+            factory::Factory,
+            graph::{
+                render::{PrepareResult, RenderGroup, RenderGroupDesc},
+                GraphContext, ImageAccess, NodeBuffer, NodeImage,
+            },
+            hal::{
+                self,
+                device::Device,
+                format::{Aspects, Format, Swizzle},
+                image::{Filter, Layout, SubresourceRange, ViewKind, WrapMode},
+                pso,
+            },
+            resource::{
+                DescriptorSet, DescriptorSetLayout, Escape, Handle, ImageView, ImageViewInfo,
+                Sampler, SamplerInfo,
+            },
+            shader::{PathBufShaderInfo, Shader, ShaderKind, SourceLanguage, SpirvShader},
+        },
+        submodules::{TextureId, TextureSub},
+        types::Backend,
+        util,
+    },
+};
+
+use std::path::PathBuf;
+
+use crate::ecs::resources::PostProcessing;
+
+lazy_static::lazy_static! {
+    static ref VERTEX_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/screen.vert"),
+        ShaderKind::Vertex,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref VERTEX: SpirvShader = SpirvShader::new(
+        (*VERTEX_SRC).spirv().unwrap().to_vec(),
+        (*VERTEX_SRC).stage(),
+        "main",
+    );
+
+    static ref FRAGMENT_SRC: SpirvShader = PathBufShaderInfo::new(
+        PathBuf::from("resources/shaders/post_processing.frag"),
+        ShaderKind::Fragment,
+        SourceLanguage::GLSL,
+        "main",
+    ).precompile().unwrap();
+
+    static ref FRAGMENT: SpirvShader = SpirvShader::new(
+        (*FRAGMENT_SRC).spirv().unwrap().to_vec(),
+        (*FRAGMENT_SRC).stage(),
+        "main",
+    );
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+struct EffectsPushConstant {
+    texel_size: [f32; 2],
+    bloom: f32,
+    chromatic_aberration: f32,
+    low_health_tint: f32,
+    color_grading: f32,
+}
+
+/// Draws the main target image over the whole window with the effects from `PostProcessing`.
+#[derive(Debug)]
+pub(super) struct DrawPostProcessingDesc {
+    pub format: Format,
+    pub filter: Filter,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl<B: Backend> RenderGroupDesc<B, World> for DrawPostProcessingDesc {
+    fn images(&self) -> Vec<ImageAccess> {
+        vec![ImageAccess {
+            access: hal::image::Access::SHADER_READ,
+            usage: hal::image::Usage::SAMPLED,
+            layout: Layout::ShaderReadOnlyOptimal,
+            stages: pso::PipelineStage::FRAGMENT_SHADER,
+        }]
+    }
+
+    fn build(
+        self,
+        ctx: &GraphContext<B>,
+        factory: &mut Factory<B>,
+        _queue: QueueId,
+        _world: &World,
+        framebuffer_width: u32,
+        framebuffer_height: u32,
+        subpass: hal::pass::Subpass<'_, B>,
+        _buffers: Vec<NodeBuffer>,
+        images: Vec<NodeImage>,
+    ) -> Result<Box<dyn RenderGroup<B, World>>, failure::Error> {
+        let image = ctx
+            .get_image(images[0].id)
+            .expect("Expected the main target image")
+            .clone();
+        let image_view = factory.create_image_view(
+            image,
+            ImageViewInfo {
+                view_kind: ViewKind::D2,
+                format: self.format,
+                swizzle: Swizzle::NO,
+                range: SubresourceRange {
+                    aspects: Aspects::COLOR,
+                    levels: 0..1,
+                    layers: 0..1,
+                },
+            },
+        )?;
+        let sampler = factory.get_sampler(SamplerInfo::new(self.filter, WrapMode::Clamp))?;
+        let textures = TextureSub::new(factory)?;
+
+        let set_layout = Handle::from(factory.create_descriptor_set_layout(vec![
+            pso::DescriptorSetLayoutBinding {
+                binding: 0,
+                ty: pso::DescriptorType::CombinedImageSampler,
+                count: 1,
+                stage_flags: pso::ShaderStageFlags::FRAGMENT,
+                immutable_samplers: false,
+            },
+        ])?);
+        // The LUT set has the same layout, the screen set is bound in its place
+        // while there's no LUT to grade the colors with.
+        let set = factory.create_descriptor_set(set_layout.clone())?;
+        let no_lut_set = factory.create_descriptor_set(set_layout.clone())?;
+        unsafe {
+            for set in &[&set, &no_lut_set] {
+                factory
+                    .device()
+                    .write_descriptor_sets(Some(pso::DescriptorSetWrite {
+                        set: set.raw(),
+                        binding: 0,
+                        array_offset: 0,
+                        descriptors: Some(pso::Descriptor::CombinedImageSampler(
+                            image_view.raw(),
+                            Layout::ShaderReadOnlyOptimal,
+                            sampler.raw(),
+                        )),
+                    }));
+            }
+        }
+
+        let (pipeline, pipeline_layout) = build_pipeline(
+            factory,
+            subpass,
+            framebuffer_width,
+            framebuffer_height,
+            vec![set_layout.raw(), textures.raw_layout()],
+        )?;
+
+        Ok(Box::new(DrawPostProcessing::<B> {
+            pipeline,
+            pipeline_layout,
+            set,
+            no_lut_set,
+            textures,
+            lut: None,
+            effects: EffectsPushConstant {
+                texel_size: [1.0 / self.width as f32, 1.0 / self.height as f32],
+                ..Default::default()
+            },
+            _set_layout: set_layout,
+            _image_view: image_view,
+            _sampler: sampler,
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct DrawPostProcessing<B: Backend> {
+    pipeline: B::GraphicsPipeline,
+    pipeline_layout: B::PipelineLayout,
+    set: Escape<DescriptorSet<B>>,
+    no_lut_set: Escape<DescriptorSet<B>>,
+    textures: TextureSub<B>,
+    lut: Option<TextureId>,
+    effects: EffectsPushConstant,
+    _set_layout: Handle<DescriptorSetLayout<B>>,
+    _image_view: Escape<ImageView<B>>,
+    _sampler: Handle<Sampler<B>>,
+}
+
+impl<B: Backend> RenderGroup<B, World> for DrawPostProcessing<B> {
+    fn prepare(
+        &mut self,
+        factory: &Factory<B>,
+        _queue: QueueId,
+        _index: usize,
+        _subpass: hal::pass::Subpass<'_, B>,
+        world: &World,
+    ) -> PrepareResult {
+        let post_processing = <ReadExpect<'_, PostProcessing>>::fetch(world);
+        self.effects.bloom = post_processing.bloom;
+        self.effects.chromatic_aberration = post_processing.chromatic_aberration;
+        self.effects.low_health_tint = post_processing.low_health_tint;
+        self.lut = post_processing.color_grading_lut.as_ref().and_then(|lut| {
+            self.textures
+                .insert(factory, world, lut, Layout::ShaderReadOnlyOptimal)
+                .map(|(lut, _)| lut)
+        });
+        self.textures.maintain(factory, world);
+        PrepareResult::DrawRecord
+    }
+
+    fn draw_inline(
+        &mut self,
+        mut encoder: RenderPassEncoder<'_, B>,
+        _index: usize,
+        _: hal::pass::Subpass<'_, B>,
+        _world: &World,
+    ) {
+        encoder.bind_graphics_pipeline(&self.pipeline);
+        unsafe {
+            encoder.bind_graphics_descriptor_sets(
+                &self.pipeline_layout,
+                0,
+                Some(self.set.raw()),
+                std::iter::empty(),
+            );
+        }
+        let mut effects = self.effects;
+        match self.lut.filter(|lut| self.textures.loaded(*lut)) {
+            Some(lut) => {
+                self.textures
+                    .bind(&self.pipeline_layout, 1, lut, &mut encoder);
+                effects.color_grading = 1.0;
+            }
+            None => unsafe {
+                encoder.bind_graphics_descriptor_sets(
+                    &self.pipeline_layout,
+                    1,
+                    Some(self.no_lut_set.raw()),
+                    std::iter::empty(),
+                );
+            },
+        }
+        let push_constants: [u32; 6] = unsafe { std::mem::transmute(effects) };
+        unsafe {
+            encoder.push_constants(
+                &self.pipeline_layout,
+                pso::ShaderStageFlags::FRAGMENT,
+                0,
+                &push_constants,
+            );
+            encoder.draw(0..3, 0..1);
+        }
+    }
+
+    fn dispose(self: Box<Self>, factory: &mut Factory<B>, _aux: &World) {
+        unsafe {
+            factory.device().destroy_graphics_pipeline(self.pipeline);
+            factory
+                .device()
+                .destroy_pipeline_layout(self.pipeline_layout);
+        }
+    }
+}
+
+fn build_pipeline<B: Backend>(
+    factory: &Factory<B>,
+    subpass: hal::pass::Subpass<'_, B>,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+    layouts: Vec<&B::DescriptorSetLayout>,
+) -> Result<(B::GraphicsPipeline, B::PipelineLayout), failure::Error> {
+    let push_constants = vec![(
+        pso::ShaderStageFlags::FRAGMENT,
+        0..std::mem::size_of::<EffectsPushConstant>() as u32 / 4,
+    )];
+
+    let pipeline_layout = unsafe {
+        factory
+            .device()
+            .create_pipeline_layout(layouts, push_constants)
+    }?;
+
+    let shader_vertex = unsafe { VERTEX.module(factory).unwrap() };
+    let shader_fragment = unsafe { FRAGMENT.module(factory).unwrap() };
+
+    let pipes = PipelinesBuilder::new()
+        .with_pipeline(
+            PipelineDescBuilder::new()
+                .with_input_assembler(pso::InputAssemblerDesc::new(hal::Primitive::TriangleList))
+                .with_rasterizer(hal::pso::Rasterizer {
+                    polygon_mode: hal::pso::PolygonMode::Fill,
+                    cull_face: hal::pso::Face::NONE,
+                    front_face: hal::pso::FrontFace::Clockwise,
+                    depth_clamping: false,
+                    depth_bias: None,
+                    conservative: false,
+                })
+                .with_shaders(util::simple_shader_set(
+                    &shader_vertex,
+                    Some(&shader_fragment),
+                ))
+                .with_layout(&pipeline_layout)
+                .with_subpass(subpass)
+                .with_baked_states(hal::pso::BakedStates {
+                    viewport: Some(hal::pso::Viewport {
+                        rect: hal::pso::Rect {
+                            x: 0,
+                            y: 0,
+                            w: framebuffer_width as i16,
+                            h: framebuffer_height as i16,
+                        },
+                        depth: 0.0..1.0,
+                    }),
+                    scissor: None,
+                    ..Default::default()
+                })
+                .with_blend_targets(vec![pso::ColorBlendDesc {
+                    mask: pso::ColorMask::ALL,
+                    blend: None,
+                }]),
+        )
+        .build(factory, None);
+
+    unsafe {
+        factory.destroy_shader_module(shader_vertex);
+        factory.destroy_shader_module(shader_fragment);
+    }
+
+    match pipes {
+        Err(e) => {
+            unsafe {
+                factory.device().destroy_pipeline_layout(pipeline_layout);
+            }
+            Err(e)
+        }
+        Ok(mut pipes) => Ok((pipes.remove(0), pipeline_layout)),
+    }
+}
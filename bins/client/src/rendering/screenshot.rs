@@ -0,0 +1,430 @@
+use amethyst::{
+    core::{
+        ecs::{DispatcherBuilder, ReadExpect, SystemData, World},
+        SystemBundle,
+    },
+    error::Error,
+    renderer::{
+        bundle::{
+            ImageOptions, OutputColor, RenderOrder, RenderPlan, RenderPlugin, Target, TargetImage,
+            TargetPlanOutputs,
+        },
+        rendy::{
+            command::{
+                CommandBuffer, CommandPool, Family, Fence, IndividualReset, InitialState, OneShot,
+                PendingOnceState, PrimaryLevel, Queue, Submission, Transfer,
+            },
+            factory::Factory,
+            frame::Frames,
+            graph::{
+                gfx_acquire_barriers, gfx_release_barriers, GraphContext, ImageAccess, Node,
+                NodeBuffer, NodeDesc, NodeImage,
+            },
+            hal::{
+                self,
+                command::{BufferImageCopy, ClearColor, ClearDepthStencil, ClearValue},
+                format::{Aspects, Format},
+                image::{Extent, Filter, Kind, Layout, Offset, SubresourceLayers},
+                pso,
+            },
+            memory::Download,
+            resource::{Buffer, BufferInfo, Escape},
+        },
+        types::Backend,
+    },
+    window::{DisplayConfig, ScreenDimensions, Window, WindowBundle},
+};
+
+use std::{fs::File, io::BufWriter, path::PathBuf, thread};
+
+use crate::{
+    ecs::resources::{RenderScale, ScreenshotRequest},
+    rendering::post_processing::DrawPostProcessingDesc,
+};
+
+/// Everything is drawn to `Target::Main` as usual, this one copies it to the window.
+const WINDOW_TARGET: Target = Target::Custom("window");
+/// Screenshots are saved as 8-bit sRGB, which is what the main target is rendered to.
+const COLOR_FORMAT: Format = Format::Rgba8Srgb;
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Replaces `RenderToWindow`: the main target is rendered into an image instead of
+/// the window surface, so that it can be read back for a pending `ScreenshotRequest`.
+/// The image is then drawn to the window with post-processing, stretched if it's rendered
+/// at a lower `RenderScale`, so screenshots are taken without the effects.
+#[derive(Debug)]
+pub struct RenderToWindowWithScreenshots {
+    config: Option<DisplayConfig>,
+    dimensions: Option<ScreenDimensions>,
+    render_scale: f32,
+    dirty: bool,
+}
+
+impl RenderToWindowWithScreenshots {
+    pub fn from_config(config: DisplayConfig) -> Self {
+        Self {
+            config: Some(config),
+            dimensions: None,
+            render_scale: 1.0,
+            dirty: true,
+        }
+    }
+}
+
+impl<B: Backend> RenderPlugin<B> for RenderToWindowWithScreenshots {
+    fn on_build<'a, 'b>(
+        &mut self,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        if let Some(config) = self.config.take() {
+            WindowBundle::from_config(config).build(world, builder)?;
+        }
+        Ok(())
+    }
+
+    fn should_rebuild(&mut self, world: &World) -> bool {
+        let new_dimensions = world.try_fetch::<ScreenDimensions>();
+        if self.dimensions.as_ref() != new_dimensions.as_deref() {
+            self.dirty = true;
+            self.dimensions = new_dimensions.map(|dimensions| dimensions.clone());
+            return false;
+        }
+        let new_render_scale = world
+            .try_fetch::<RenderScale>()
+            .map_or(1.0, |render_scale| render_scale.0);
+        if (self.render_scale - new_render_scale).abs() > std::f32::EPSILON {
+            self.render_scale = new_render_scale;
+            self.dirty = true;
+        }
+        self.dirty
+    }
+
+    fn on_plan(
+        &mut self,
+        plan: &mut RenderPlan<B>,
+        factory: &mut Factory<B>,
+        world: &World,
+    ) -> Result<(), Error> {
+        self.dirty = false;
+
+        let window = <ReadExpect<'_, Window>>::fetch(world);
+        let surface = factory.create_surface(&window)?;
+        let dimensions = self
+            .dimensions
+            .as_ref()
+            .expect("Expected screen dimensions");
+        // Screenshots are taken at the render scale as well.
+        let width = ((dimensions.width() * self.render_scale).round() as u32).max(1);
+        let height = ((dimensions.height() * self.render_scale).round() as u32).max(1);
+        let main_kind = Kind::D2(width, height, 1, 1);
+        let filter = if self.render_scale < 1.0 {
+            Filter::Linear
+        } else {
+            Filter::Nearest
+        };
+
+        plan.define_pass(
+            Target::Main,
+            TargetPlanOutputs {
+                colors: vec![OutputColor::Image(ImageOptions {
+                    kind: main_kind,
+                    levels: 1,
+                    format: COLOR_FORMAT,
+                    clear: Some(ClearValue {
+                        color: ClearColor {
+                            float32: [0.0, 0.0, 0.0, 1.0],
+                        },
+                    }),
+                })],
+                depth: Some(ImageOptions {
+                    kind: main_kind,
+                    levels: 1,
+                    format: Format::D32Sfloat,
+                    clear: Some(ClearValue {
+                        depth_stencil: ClearDepthStencil {
+                            depth: 1.0,
+                            stencil: 0,
+                        },
+                    }),
+                }),
+            },
+        )?;
+
+        plan.add_root(WINDOW_TARGET);
+        plan.define_pass(
+            WINDOW_TARGET,
+            TargetPlanOutputs {
+                colors: vec![OutputColor::Surface(surface, None)],
+                depth: None,
+            },
+        )?;
+        plan.extend_target(WINDOW_TARGET, move |ctx| {
+            let main_node = ctx.get_node(Target::Main)?;
+            let color = ctx.get_image(TargetImage::Color(Target::Main, 0))?;
+            ctx.graph().add_node(
+                ScreenshotNodeDesc { width, height }
+                    .builder()
+                    .with_image(color)
+                    .with_dependency(main_node),
+            );
+            ctx.add(
+                RenderOrder::BeforeOpaque,
+                DrawPostProcessingDesc {
+                    format: COLOR_FORMAT,
+                    filter,
+                    width,
+                    height,
+                }
+                .builder()
+                .with_image(color),
+            )?;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ScreenshotNodeDesc {
+    width: u32,
+    height: u32,
+}
+
+impl<B: Backend> NodeDesc<B, World> for ScreenshotNodeDesc {
+    type Node = ScreenshotNode<B>;
+
+    fn images(&self) -> Vec<ImageAccess> {
+        vec![ImageAccess {
+            access: hal::image::Access::TRANSFER_READ,
+            usage: hal::image::Usage::TRANSFER_SRC,
+            layout: Layout::TransferSrcOptimal,
+            stages: pso::PipelineStage::TRANSFER,
+        }]
+    }
+
+    fn build<'a>(
+        self,
+        _ctx: &GraphContext<B>,
+        factory: &mut Factory<B>,
+        family: &mut Family<B>,
+        _queue: usize,
+        _aux: &World,
+        _buffers: Vec<NodeBuffer>,
+        mut images: Vec<NodeImage>,
+    ) -> Result<Self::Node, failure::Error> {
+        let pool = factory
+            .create_command_pool(family)?
+            .with_capability::<Transfer>()
+            .map_err(|_| failure::format_err!("Expected a queue family supporting transfers"))?;
+        Ok(ScreenshotNode {
+            image: images.remove(0),
+            width: self.width,
+            height: self.height,
+            pool,
+            free_command_buffers: Vec::new(),
+            submitted_command_buffers: Vec::new(),
+            readback: None,
+        })
+    }
+}
+
+type ScreenshotCommandBuffer<B, S> = CommandBuffer<B, Transfer, S, PrimaryLevel, IndividualReset>;
+
+struct Readback<B: Backend> {
+    frame: u64,
+    buffer: Escape<Buffer<B>>,
+    path: PathBuf,
+}
+
+/// Copies the main target image into a buffer for a pending `ScreenshotRequest`.
+/// The buffer is read once its frame is complete, and the PNG is encoded in the background.
+/// The image barriers are recorded every frame, as the graph expects.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+struct ScreenshotNode<B: Backend> {
+    #[derivative(Debug = "ignore")]
+    image: NodeImage,
+    width: u32,
+    height: u32,
+    #[derivative(Debug = "ignore")]
+    pool: CommandPool<B, Transfer, IndividualReset>,
+    #[derivative(Debug = "ignore")]
+    free_command_buffers: Vec<ScreenshotCommandBuffer<B, InitialState>>,
+    #[derivative(Debug = "ignore")]
+    submitted_command_buffers: Vec<(u64, ScreenshotCommandBuffer<B, PendingOnceState>)>,
+    #[derivative(Debug = "ignore")]
+    readback: Option<Readback<B>>,
+}
+
+impl<B: Backend> Node<B, World> for ScreenshotNode<B> {
+    type Capability = Transfer;
+
+    fn run<'a>(
+        &mut self,
+        ctx: &GraphContext<B>,
+        factory: &Factory<B>,
+        queue: &mut Queue<B>,
+        aux: &World,
+        frames: &Frames<B>,
+        waits: &[(&'a B::Semaphore, pso::PipelineStage)],
+        signals: &[&'a B::Semaphore],
+        fence: Option<&mut Fence<B>>,
+    ) {
+        let complete_upper_bound = frames.complete_upper_bound();
+        let (complete, submitted): (Vec<_>, Vec<_>) =
+            std::mem::replace(&mut self.submitted_command_buffers, Vec::new())
+                .into_iter()
+                .partition(|(frame, _)| *frame < complete_upper_bound);
+        self.submitted_command_buffers = submitted;
+        for (_, command_buffer) in complete {
+            self.free_command_buffers
+                .push(unsafe { command_buffer.mark_complete() }.reset());
+        }
+
+        let is_read_back = self
+            .readback
+            .as_ref()
+            .map_or(false, |readback| readback.frame < complete_upper_bound);
+        if is_read_back {
+            let readback = self.readback.take().unwrap();
+            self.save_png(factory, readback);
+        }
+
+        let frame = frames.next().index();
+        let size = u64::from(self.width * self.height * BYTES_PER_PIXEL);
+        let readback = if self.readback.is_none() {
+            aux.fetch_mut::<ScreenshotRequest>().path.take()
+        } else {
+            None
+        }
+        .and_then(|path| {
+            factory
+                .create_buffer(
+                    BufferInfo {
+                        size,
+                        usage: hal::buffer::Usage::TRANSFER_DST,
+                    },
+                    Download,
+                )
+                .map_err(|err| log::error!("Failed to create a screenshot buffer: {:?}", err))
+                .ok()
+                .map(|buffer| Readback {
+                    frame,
+                    buffer,
+                    path,
+                })
+        });
+
+        let command_buffer = self
+            .free_command_buffers
+            .pop()
+            .unwrap_or_else(|| self.pool.allocate_buffers(1).remove(0));
+        let mut command_buffer = command_buffer.begin(OneShot, ());
+        {
+            let mut encoder = command_buffer.encoder();
+            let (stages, barriers) = gfx_acquire_barriers(ctx, None, Some(&self.image));
+            unsafe {
+                encoder.pipeline_barrier(stages, hal::memory::Dependencies::empty(), barriers);
+            }
+            if let Some(readback) = &readback {
+                let image = ctx
+                    .get_image(self.image.id)
+                    .expect("Expected the main target image");
+                unsafe {
+                    encoder.copy_image_to_buffer(
+                        image.raw(),
+                        Layout::TransferSrcOptimal,
+                        readback.buffer.raw(),
+                        Some(BufferImageCopy {
+                            buffer_offset: 0,
+                            buffer_width: self.width,
+                            buffer_height: self.height,
+                            image_layers: SubresourceLayers {
+                                aspects: Aspects::COLOR,
+                                level: 0,
+                                layers: 0..1,
+                            },
+                            image_offset: Offset::ZERO,
+                            image_extent: Extent {
+                                width: self.width,
+                                height: self.height,
+                                depth: 1,
+                            },
+                        }),
+                    );
+                }
+            }
+            let (stages, barriers) = gfx_release_barriers(ctx, None, Some(&self.image));
+            unsafe {
+                encoder.pipeline_barrier(stages, hal::memory::Dependencies::empty(), barriers);
+            }
+        }
+        let (submit, command_buffer) = command_buffer.finish().submit_once();
+        unsafe {
+            queue.submit(
+                Some(
+                    Submission::new()
+                        .submits(Some(submit))
+                        .wait(waits.iter().cloned())
+                        .signal(signals.iter().cloned()),
+                ),
+                fence,
+            );
+        }
+        self.submitted_command_buffers.push((frame, command_buffer));
+        if readback.is_some() {
+            self.readback = readback;
+        }
+    }
+
+    unsafe fn dispose(mut self, factory: &mut Factory<B>, _aux: &World) {
+        // The graph waits for the device to be idle before disposing the nodes.
+        for (_, command_buffer) in self.submitted_command_buffers.drain(..) {
+            self.free_command_buffers
+                .push(command_buffer.mark_complete().reset());
+        }
+        self.pool.free_buffers(self.free_command_buffers.drain(..));
+        factory.destroy_command_pool(self.pool);
+    }
+}
+
+impl<B: Backend> ScreenshotNode<B> {
+    fn save_png(&self, factory: &Factory<B>, mut readback: Readback<B>) {
+        let size = u64::from(self.width * self.height * BYTES_PER_PIXEL);
+        let pixels = unsafe {
+            readback
+                .buffer
+                .map(factory.device(), 0..size)
+                .and_then(|mut mapped| {
+                    mapped
+                        .read::<u8>(factory.device(), 0..size)
+                        .map(|pixels| pixels.to_vec())
+                })
+        };
+        let pixels = match pixels {
+            Ok(pixels) => pixels,
+            Err(err) => {
+                log::error!("Failed to read a screenshot back: {:?}", err);
+                return;
+            }
+        };
+
+        let (width, height) = (self.width, self.height);
+        let path = readback.path;
+        thread::spawn(move || {
+            let result = File::create(&path)
+                .map_err(png::EncodingError::from)
+                .and_then(|file| {
+                    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+                    encoder.set_color(png::ColorType::RGBA);
+                    encoder.set_depth(png::BitDepth::Eight);
+                    encoder.write_header()?.write_image_data(&pixels)
+                });
+            match result {
+                Ok(()) => log::info!("Saved a screenshot to {}", path.display()),
+                Err(err) => log::error!("Failed to save a screenshot: {:?}", err),
+            }
+        });
+    }
+}
@@ -1,14 +1,67 @@
-use gv_core::net::server_message::DisconnectReason;
+use gv_core::net::server_message::{ClientNetworkError, DisconnectReason};
 
-pub fn disconnect_reason_title(disconnect_reason: DisconnectReason) -> String {
+pub fn disconnect_reason_title(disconnect_reason: &DisconnectReason) -> String {
     match disconnect_reason {
         DisconnectReason::Uninitialized => "The server is not initialized yet".to_owned(),
         DisconnectReason::GameIsStarted => "The server has already started the game".to_owned(),
+        DisconnectReason::GameIsNotStarted => "The server hasn't started the game yet".to_owned(),
         DisconnectReason::RoomIsFull => "The room is full".to_owned(),
         DisconnectReason::Kick => "You've been kicked".to_owned(),
+        DisconnectReason::AfkKick => "You've been kicked for being idle".to_owned(),
+        DisconnectReason::VoteKick => "You've been voted out".to_owned(),
         DisconnectReason::Closed => "The host has closed the server".to_owned(),
+        DisconnectReason::RoomNotFound => "The room doesn't exist".to_owned(),
+        DisconnectReason::RoomLimitReached => "The server can't host any more rooms".to_owned(),
+        DisconnectReason::MatchmakingTimedOut => {
+            "Couldn't find a match, try again later".to_owned()
+        }
+        DisconnectReason::RendezvousHostNotFound => {
+            "Couldn't find a host with this code".to_owned()
+        }
+        DisconnectReason::MalformedMessage => {
+            "The connection was dropped because of a malformed message".to_owned()
+        }
         DisconnectReason::ServerCrashed(exit_code) => {
             format!("The server unexpectedly closed: {}", exit_code)
         }
+        DisconnectReason::ClientNetworkError(error) => client_network_error_title(error),
+    }
+}
+
+fn client_network_error_title(error: &ClientNetworkError) -> String {
+    match error {
+        ClientNetworkError::StartGameBeforeHandshake => {
+            "The server started the game before accepting the connection".to_owned()
+        }
+        ClientNetworkError::PlayersCountMismatch {
+            players,
+            entity_net_ids,
+        } => format!(
+            "The server started the game for {} players, but the room has {}",
+            entity_net_ids, players
+        ),
+        ClientNetworkError::PlayerNotFound { connection_id } => format!(
+            "The server started the game without your player (connection id: {})",
+            connection_id
+        ),
+        ClientNetworkError::ServerTerminated => "The server was terminated".to_owned(),
+        ClientNetworkError::MissingContentPack(name) => format!(
+            "The server uses the content pack \"{}\", which you have to install",
+            name
+        ),
+        ClientNetworkError::ContentPackMismatch(name) => format!(
+            "Your content pack \"{}\" is a different version than the server's",
+            name
+        ),
+        ClientNetworkError::CorruptedContentPack(name) => {
+            format!("Couldn't download the content pack \"{}\"", name)
+        }
+        ClientNetworkError::OutdatedWorldUpdate {
+            frame_number,
+            first_available_frame_number,
+        } => format!(
+            "The server sent an update for frame {}, older than the oldest kept frame {}",
+            frame_number, first_available_frame_number
+        ),
     }
 }
@@ -1,5 +1,143 @@
-use std::net::SocketAddr;
+use ron::ser::PrettyConfig;
+
+use std::{collections::HashMap, fs, net::SocketAddr, path::PathBuf, time::Duration};
+
+use gv_core::{
+    anti_cheat::FlaggedFrame,
+    ecs::resources::net::SavedMatch,
+    net::{
+        client_message::ClientMessagePayload, session_recording::SessionRecordEntry, NetIdentifier,
+    },
+};
 
 pub struct LastBroadcastedFrame(pub u64);
 
+/// Gameplay messages (actions, augments, purchases, etc.) paired with connection ids, which
+/// `ServerNetworkSystem` passes to `ClientActionsSystem`.
+#[derive(Default)]
+pub struct ClientActionMessages(pub Vec<(NetIdentifier, ClientMessagePayload)>);
+
+/// StartVote and CastVote messages paired with connection ids, which `ServerNetworkSystem`
+/// passes to `VoteSystem`.
+#[derive(Default)]
+pub struct VoteMessages(pub Vec<(NetIdentifier, ClientMessagePayload)>);
+
+/// Connections watching the game without a player, see `SpectatorRelaySystem`.
+#[derive(Default)]
+pub struct Spectators(pub Vec<Spectator>);
+
+impl Spectators {
+    pub fn contains(&self, connection_id: NetIdentifier) -> bool {
+        self.0
+            .iter()
+            .any(|spectator| spectator.connection_id == connection_id)
+    }
+
+    pub fn remove(&mut self, connection_id: NetIdentifier) {
+        self.0
+            .retain(|spectator| spectator.connection_id != connection_id);
+    }
+}
+
+pub struct Spectator {
+    pub connection_id: NetIdentifier,
+    pub nickname: String,
+    /// Is set once StartSpectating is sent.
+    pub next_relayed_frame: Option<u64>,
+}
+
 pub struct HostClientAddress(pub Option<SocketAddr>);
+
+/// See `AfkDetectionSystem`.
+#[derive(Debug, Clone, Copy)]
+pub struct AfkConfig {
+    /// Players who haven't sent any input for this long are reported as AFK.
+    pub idle_timeout: Duration,
+    /// Players who haven't sent any input for this long get kicked, the host never is.
+    /// Is set only for public rooms.
+    pub kick_timeout: Option<Duration>,
+}
+
+impl Default for AfkConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(60),
+            kick_timeout: None,
+        }
+    }
+}
+
+/// See `ServerNetworkSystem`.
+#[derive(Debug, Clone, Copy)]
+pub struct RejoinConfig {
+    /// For how long players who have lost the connection during a game can rejoin it
+    /// with the same nickname, they're dropped from the room afterwards.
+    pub grace_period: Duration,
+}
+
+impl Default for RejoinConfig {
+    fn default() -> Self {
+        Self {
+            grace_period: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Where the progress of a multiplayer match is saved, matches aren't saved if it's not set.
+#[derive(Default)]
+pub struct MatchSaveFile(pub Option<PathBuf>);
+
+impl MatchSaveFile {
+    pub fn load(&self) -> Option<SavedMatch> {
+        let path = self.0.as_ref()?;
+        let contents = fs::read_to_string(path)
+            .map_err(|err| log::warn!("Failed to read {}: {:?}", path.display(), err))
+            .ok()?;
+        ron::de::from_str(&contents)
+            .map_err(|err| log::error!("Failed to parse {}: {:?}", path.display(), err))
+            .ok()
+    }
+
+    pub fn save(&self, saved_match: &SavedMatch) {
+        let path = match &self.0 {
+            Some(path) => path,
+            None => return,
+        };
+        let contents = ron::ser::to_string_pretty(saved_match, PrettyConfig::default())
+            .expect("Expected to serialize a saved match");
+        if let Err(err) = fs::write(path, contents) {
+            log::error!("Failed to save the match to {}: {:?}", path.display(), err);
+        }
+    }
+
+    pub fn delete(&self) {
+        if let Some(path) = self.0.as_ref().filter(|path| path.exists()) {
+            if let Err(err) = fs::remove_file(path) {
+                log::error!("Failed to delete {}: {:?}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// The balance file a server reloads and broadcasts whenever it changes, is set
+/// with `--watch-balance`.
+#[derive(Default)]
+pub struct BalanceWatchFile(pub Option<PathBuf>);
+
+/// Where `AntiCheatSystem` saves the sessions of suspicious players for review, is set with
+/// `--suspicious-sessions`. Gameplay messages aren't recorded if it's not set.
+#[derive(Default)]
+pub struct SuspiciousSessionsDir(pub Option<PathBuf>);
+
+/// Anti-cheat records of the current game by player entity net ids, see `gv_core::anti_cheat`.
+#[derive(Default)]
+pub struct AntiCheatRecords(pub HashMap<NetIdentifier, AntiCheatRecord>);
+
+#[derive(Default)]
+pub struct AntiCheatRecord {
+    /// Is filled only if `SuspiciousSessionsDir` is set.
+    pub entries: Vec<SessionRecordEntry>,
+    pub flagged_frames: Vec<FlaggedFrame>,
+    /// A session is reported once per game.
+    pub is_reported: bool,
+}
@@ -0,0 +1,137 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, System, Write, WriteExpect, WriteStorage},
+    network::simulation::TransportResource,
+};
+
+use std::{collections::HashMap, time::Duration};
+
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::{net::MultiplayerGameState, GameEngineState},
+        system_data::time::GameTimeService,
+    },
+    net::{
+        client_message::ClientMessagePayload,
+        server_message::{DisconnectReason, ServerMessagePayload},
+        NetIdentifier,
+    },
+};
+use gv_game::utils::net::{broadcast_message_reliable, send_message_reliable};
+
+use crate::ecs::resources::{AfkConfig, ClientActionMessages};
+
+/// Reports the players who haven't sent any input for `AfkConfig::idle_timeout`,
+/// and kicks them after `AfkConfig::kick_timeout` if it's set.
+#[derive(Default)]
+pub struct AfkDetectionSystem {
+    last_input_frames: HashMap<NetIdentifier, u64>,
+    last_frame: u64,
+}
+
+impl<'s> System<'s> for AfkDetectionSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, AfkConfig>,
+        ReadExpect<'s, ClientActionMessages>,
+        WriteExpect<'s, MultiplayerGameState>,
+        WriteStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            afk_config,
+            client_action_messages,
+            mut multiplayer_game_state,
+            mut net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if *game_engine_state != GameEngineState::Playing || !multiplayer_game_state.is_playing {
+            return;
+        }
+
+        let game_frame_number = game_time_service.game_frame_number();
+        if game_frame_number < self.last_frame {
+            // A new game has started.
+            self.last_input_frames.clear();
+        }
+        self.last_frame = game_frame_number;
+
+        for (connection_id, payload) in &client_action_messages.0 {
+            let is_input = match payload {
+                // Look actions are sent periodically, even if the mouse hasn't moved.
+                ClientMessagePayload::LookActions(look_actions) => !look_actions.updates.is_empty(),
+                _ => true,
+            };
+            if is_input {
+                self.last_input_frames
+                    .insert(*connection_id, game_frame_number);
+            }
+        }
+
+        let timeout_frames = |timeout: Duration| {
+            (timeout.as_secs_f32() * game_time_service.tick_rate() as f32) as u64
+        };
+        let idle_frames = timeout_frames(afk_config.idle_timeout);
+        let kick_frames = afk_config.kick_timeout.map(timeout_frames);
+
+        let mut afk_players = Vec::new();
+        let mut kicked_players = Vec::new();
+        for player in &multiplayer_game_state.players {
+            // Are reported as disconnected instead, and get a fresh timer once they rejoin.
+            if player.is_disconnected {
+                self.last_input_frames.remove(&player.connection_id);
+                continue;
+            }
+            let last_input_frame = *self
+                .last_input_frames
+                .entry(player.connection_id)
+                .or_insert(game_frame_number);
+            let idle_for = game_frame_number - last_input_frame;
+            if idle_for < idle_frames {
+                continue;
+            }
+            if kick_frames.map_or(false, |kick_frames| idle_for >= kick_frames) && !player.is_host {
+                kicked_players.push(player.connection_id);
+            } else {
+                afk_players.push(player.connection_id);
+            }
+        }
+
+        for connection_id in kicked_players {
+            log::info!(
+                "Kicking a player for being idle (connection id: {})",
+                connection_id
+            );
+            multiplayer_game_state.drop_player_by_connection_id(connection_id);
+            self.last_input_frames.remove(&connection_id);
+            if let Some(net_connection_model) = (&mut net_connection_models)
+                .join()
+                .find(|net_connection_model| net_connection_model.id == connection_id)
+            {
+                send_message_reliable(
+                    &mut transport,
+                    net_connection_model,
+                    ServerMessagePayload::Disconnect(DisconnectReason::AfkKick),
+                );
+                net_connection_model.disconnected = true;
+            }
+        }
+
+        if afk_players != multiplayer_game_state.afk_players {
+            log::info!("AFK players: {:?}", afk_players);
+            multiplayer_game_state.afk_players = afk_players.clone();
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateAfkPlayers(afk_players),
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use amethyst::ecs::{Read, ReadExpect, System, WriteExpect};
+
+use std::{
+    mem,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::{
+    anti_cheat::{SuspiciousSession, SUSPICIOUS_VIOLATIONS},
+    ecs::resources::net::MultiplayerGameState,
+};
+use gv_game::ecs::resources::AntiCheatLog;
+
+use crate::ecs::resources::{AntiCheatRecords, SuspiciousSessionsDir};
+
+/// Merges the casts flagged by the simulation into `AntiCheatRecords`, and reports players
+/// once they have `SUSPICIOUS_VIOLATIONS` flagged frames in a game. Sessions of reported
+/// players are saved into `SuspiciousSessionsDir`, for `gv_server --review`.
+#[derive(Default)]
+pub struct AntiCheatSystem;
+
+impl<'s> System<'s> for AntiCheatSystem {
+    type SystemData = (
+        Read<'s, SuspiciousSessionsDir>,
+        ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, AntiCheatLog>,
+        WriteExpect<'s, AntiCheatRecords>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            suspicious_sessions_dir,
+            multiplayer_game_state,
+            mut anti_cheat_log,
+            mut anti_cheat_records,
+        ): Self::SystemData,
+    ) {
+        if !multiplayer_game_state.is_playing {
+            anti_cheat_records.0.clear();
+            return;
+        }
+
+        for (entity_net_id, flagged_frame) in anti_cheat_log.flagged.drain(..) {
+            anti_cheat_records
+                .0
+                .entry(entity_net_id)
+                .or_default()
+                .flagged_frames
+                .push(flagged_frame);
+        }
+
+        for player in &multiplayer_game_state.players {
+            let anti_cheat_record = match anti_cheat_records.0.get_mut(&player.entity_net_id) {
+                Some(anti_cheat_record) => anti_cheat_record,
+                None => continue,
+            };
+            if anti_cheat_record.is_reported
+                || anti_cheat_record.flagged_frames.len() < SUSPICIOUS_VIOLATIONS
+            {
+                continue;
+            }
+            anti_cheat_record.is_reported = true;
+            log::warn!(
+                "{} (entity net id {}) has broken the rules on {} frames",
+                player.nickname,
+                player.entity_net_id,
+                anti_cheat_record.flagged_frames.len()
+            );
+
+            let suspicious_sessions_dir = match &suspicious_sessions_dir.0 {
+                Some(suspicious_sessions_dir) => suspicious_sessions_dir,
+                None => continue,
+            };
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs());
+            let path = suspicious_sessions_dir.join(format!(
+                "{}_{}.gvsuspicious",
+                timestamp, player.entity_net_id
+            ));
+            // Messages of the rest of the game aren't needed anymore.
+            let suspicious_session = SuspiciousSession {
+                nickname: player.nickname.clone(),
+                entity_net_id: player.entity_net_id,
+                flagged_frames: anti_cheat_record.flagged_frames.clone(),
+                entries: mem::take(&mut anti_cheat_record.entries),
+            };
+            match suspicious_session.save(&path) {
+                Ok(()) => log::warn!("Saved the session for review to {}", path.display()),
+                Err(err) => log::error!(
+                    "Failed to save the session to {}: {:?}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+    }
+}
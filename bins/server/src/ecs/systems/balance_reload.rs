@@ -0,0 +1,111 @@
+use amethyst::{
+    ecs::{Join, Read, ReadStorage, System, Write, WriteExpect},
+    network::simulation::TransportResource,
+};
+
+use std::fs;
+
+use gv_core::{
+    balance::Balance,
+    content_packs::ContentPacks,
+    ecs::{components::NetConnectionModel, system_data::time::GameTimeService},
+    net::server_message::ServerMessagePayload,
+};
+use gv_game::{
+    ecs::resources::{ClassDefinitions, MonsterDefinitions, SpellDefinitions},
+    utils::net::broadcast_message_reliable,
+};
+
+use crate::ecs::resources::BalanceWatchFile;
+
+const CHECK_FRAME_INTERVAL: u64 = 30;
+
+/// Reloads the watched balance file once its contents change and sends it to every client.
+/// Content packs are applied on top of it, the same way as on startup.
+///
+/// Clients apply the new balance as soon as they receive it, which may be a few frames apart
+/// from the server, the world updates correct the difference.
+#[derive(Default)]
+pub struct BalanceReloadSystem {
+    last_checked_frame: Option<u64>,
+    /// The first read only remembers the contents, as they've been loaded on startup.
+    last_contents: Option<Vec<u8>>,
+}
+
+impl<'s> System<'s> for BalanceReloadSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Read<'s, BalanceWatchFile>,
+        Read<'s, ContentPacks>,
+        WriteExpect<'s, Balance>,
+        WriteExpect<'s, ClassDefinitions>,
+        WriteExpect<'s, MonsterDefinitions>,
+        WriteExpect<'s, SpellDefinitions>,
+        ReadStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            balance_watch_file,
+            content_packs,
+            mut balance,
+            mut class_definitions,
+            mut monster_definitions,
+            mut spell_definitions,
+            net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        let path = match &balance_watch_file.0 {
+            Some(path) => path,
+            None => return,
+        };
+
+        let frame_number = game_time_service.engine_time().frame_number();
+        if self.last_checked_frame.map_or(false, |last_checked_frame| {
+            frame_number.saturating_sub(last_checked_frame) < CHECK_FRAME_INTERVAL
+        }) {
+            return;
+        }
+        self.last_checked_frame = Some(frame_number);
+
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::warn!("Failed to read {}: {:?}", path.display(), err);
+                return;
+            }
+        };
+        if self.last_contents.as_ref() == Some(&contents) {
+            return;
+        }
+        let is_first_read = self.last_contents.is_none();
+        let parsed_balance = Balance::from_bytes(&contents)
+            .and_then(|parsed_balance| content_packs.apply_to_balance(&parsed_balance));
+        self.last_contents = Some(contents);
+        if is_first_read {
+            return;
+        }
+
+        let new_balance = match parsed_balance {
+            Ok(new_balance) => new_balance,
+            Err(err) => {
+                log::error!("Failed to reload {}: {}", path.display(), err);
+                return;
+            }
+        };
+        log::info!("Reloaded {}", path.display());
+        *class_definitions = ClassDefinitions::new(&new_balance);
+        *monster_definitions = MonsterDefinitions::new(&new_balance);
+        *spell_definitions = SpellDefinitions::new(&new_balance);
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connection_models).join(),
+            ServerMessagePayload::UpdateBalance(new_balance.clone()),
+        );
+        *balance = new_balance;
+    }
+}
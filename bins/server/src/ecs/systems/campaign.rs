@@ -0,0 +1,166 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System, Write, WriteExpect},
+    network::simulation::TransportResource,
+};
+
+use gv_core::{
+    augments::PlayerAugments,
+    campaign::CAMPAIGN_LEVELS,
+    economy::PlayerWallet,
+    ecs::{
+        components::{Dead, NetConnectionModel, Player},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState, SavedMatch},
+            GameEngineState, GameLevelState, NewGameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    game_events::GameEvent,
+    net::server_message::ServerMessagePayload,
+};
+use gv_game::{
+    ecs::resources::{ActiveLevelScript, MatchSeed},
+    utils::net::broadcast_message_reliable,
+};
+
+use super::match_saving::saved_players;
+
+/// For how long clients show the loading screen between campaign levels.
+const LEVEL_TRANSITION_FRAMES: u64 = 180;
+
+/// Moves the lobby to the next campaign level once the objective of the current one is completed
+/// or its level script ends it.
+/// Players carry over their health, augments, coins and items,
+/// the next level is started as a resumed match from the first frame.
+#[derive(Default)]
+pub struct CampaignSystem {
+    transition_started_frame: Option<u64>,
+}
+
+impl<'s> System<'s> for CampaignSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        WriteExpect<'s, NewGameEngineState>,
+        WriteExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        ReadExpect<'s, MatchSeed>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, PlayerAugments>,
+        ReadStorage<'s, PlayerWallet>,
+        ReadStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            mut new_game_engine_state,
+            mut multiplayer_game_state,
+            game_level_state,
+            active_level_script,
+            match_seed,
+            entity_net_metadata_storage,
+            players,
+            dead,
+            player_augments,
+            player_wallets,
+            net_connections,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        match *game_engine_state {
+            GameEngineState::Playing
+                if multiplayer_game_state.is_playing
+                    && new_game_engine_state.0 == GameEngineState::Playing => {}
+            GameEngineState::LevelTransition => {
+                let engine_frame_number = game_time_service.engine_time().frame_number();
+                let transition_started_frame = *self
+                    .transition_started_frame
+                    .get_or_insert(engine_frame_number);
+                if engine_frame_number - transition_started_frame
+                    >= game_time_service.scale_frames(LEVEL_TRANSITION_FRAMES)
+                {
+                    self.transition_started_frame = None;
+                    new_game_engine_state.0 = GameEngineState::Playing;
+                }
+                return;
+            }
+            _ => return,
+        }
+        let level_number = match multiplayer_game_state.campaign_level {
+            Some(level_number) => level_number,
+            None => return,
+        };
+        let is_ended_by_script = active_level_script
+            .runner
+            .as_ref()
+            .map_or(false, |runner| runner.is_ended());
+        if game_level_state.spawn_level < CAMPAIGN_LEVELS[level_number].completed_at_spawn_level
+            && !is_ended_by_script
+        {
+            return;
+        }
+
+        // Clients unlock achievements with it.
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connections).join(),
+            ServerMessagePayload::GameEvents(vec![GameEvent::CampaignLevelCompleted(level_number)]),
+        );
+        let next_level_number = level_number + 1;
+        if next_level_number == CAMPAIGN_LEVELS.len() {
+            log::info!("The campaign is completed, the game goes on in the survival mode");
+            multiplayer_game_state.campaign_level = None;
+            return;
+        }
+
+        log::info!(
+            "Campaign level {} is completed, starting the level {}",
+            level_number,
+            next_level_number
+        );
+        let game_frame_number = game_time_service.game_frame_number();
+        let mut saved_players = saved_players(
+            game_frame_number,
+            &multiplayer_game_state,
+            &entity_net_metadata_storage,
+            &players,
+            &dead,
+            &player_augments,
+            &player_wallets,
+        );
+        // The next level starts from the first frame, items keep the rest of their duration.
+        for saved_player in &mut saved_players {
+            saved_player
+                .wallet
+                .items
+                .retain(|(_, expires_at)| *expires_at > game_frame_number);
+            for (_, expires_at) in &mut saved_player.wallet.items {
+                *expires_at -= game_frame_number;
+            }
+        }
+        multiplayer_game_state.resumed_match = Some(SavedMatch {
+            spawn_level: CAMPAIGN_LEVELS[next_level_number].spawn_level,
+            game_frame_number: 0,
+            seed: match_seed.0,
+            campaign_level: Some(next_level_number),
+            players: saved_players,
+            payload_checkpoint: 0.0,
+        });
+        multiplayer_game_state.campaign_level = Some(next_level_number);
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connections).join(),
+            ServerMessagePayload::LevelTransition {
+                level_number: next_level_number,
+            },
+        );
+        new_game_engine_state.0 = GameEngineState::LevelTransition;
+    }
+}
@@ -0,0 +1,536 @@
+use amethyst::{
+    ecs::{Join, Read, ReadExpect, System, Write, WriteExpect, WriteStorage},
+    network::simulation::TransportResource,
+};
+
+use gv_core::{
+    actions::{
+        player::{PlayerCastAction, PlayerWalkAction},
+        ClientActionUpdate, IdentifiableAction,
+    },
+    anti_cheat::{inspect_message, FlaggedFrame, Violation},
+    ecs::{
+        components::NetConnectionModel,
+        resources::{
+            net::{ActionUpdateIdProvider, MultiplayerGameState},
+            world::{
+                FramedUpdates, ImmediatePlayerActionsUpdates, InputFrame, PlayerLookActionUpdates,
+                ReceivedClientActionUpdates, ServerWorldUpdates, LAG_COMPENSATION_FRAMES_LIMIT,
+                PAUSE_FRAME_THRESHOLD,
+            },
+            GameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::{
+        client_message::ClientMessagePayload,
+        server_message::ServerMessagePayload,
+        session_recording::{RecordedMessage, SessionRecordEntry},
+        NetIdentifier, NetUpdate,
+    },
+    profile_scope,
+};
+use gv_game::{
+    ecs::resources::{
+        AugmentChoices, InteractionRequests, ShopPurchases, SummonRequests, WaveReadiness,
+    },
+    utils::net::{send_message_reliable, send_message_unreliable},
+};
+
+use crate::ecs::resources::{AntiCheatRecords, ClientActionMessages, SuspiciousSessionsDir};
+
+/// Applies the gameplay messages received by `ServerNetworkSystem`. Is kept separate
+/// from the connection handling, so that it doesn't block the systems which don't
+/// care about player actions.
+///
+/// Player actions are inspected for violations first, see `gv_core::anti_cheat`. Actions for
+/// someone else's entity get dropped, as the server can't tell whose they actually are.
+pub struct ClientActionsSystem;
+
+impl<'s> System<'s> for ClientActionsSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        Read<'s, SuspiciousSessionsDir>,
+        WriteStorage<'s, NetConnectionModel>,
+        WriteExpect<'s, ClientActionMessages>,
+        WriteExpect<'s, AntiCheatRecords>,
+        WriteExpect<'s, FramedUpdates<ReceivedClientActionUpdates>>,
+        WriteExpect<'s, ServerWorldUpdates>,
+        WriteExpect<'s, ActionUpdateIdProvider>,
+        WriteExpect<'s, AugmentChoices>,
+        WriteExpect<'s, SummonRequests>,
+        WriteExpect<'s, InteractionRequests>,
+        WriteExpect<'s, ShopPurchases>,
+        WriteExpect<'s, WaveReadiness>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            multiplayer_game_state,
+            suspicious_sessions_dir,
+            mut net_connection_models,
+            mut client_action_messages,
+            mut anti_cheat_records,
+            mut framed_updates,
+            mut server_world_updates,
+            mut action_update_id_provider,
+            mut augment_choices,
+            mut summon_requests,
+            mut interaction_requests,
+            mut shop_purchases,
+            mut wave_readiness,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        profile_scope!("ClientActionsSystem::run");
+        let game_frame_number = game_time_service.game_frame_number();
+
+        for (connection_id, payload) in client_action_messages.0.drain(..) {
+            if let Some(player) = multiplayer_game_state
+                .players
+                .iter()
+                .find(|player| player.connection_id == connection_id)
+            {
+                let violation = inspect_message(player.entity_net_id, game_frame_number, &payload);
+                let is_action = matches!(
+                    payload,
+                    ClientMessagePayload::WalkActions(_)
+                        | ClientMessagePayload::CastActions(_)
+                        | ClientMessagePayload::LookActions(_)
+                        | ClientMessagePayload::PlayerInputs(_)
+                );
+                if is_action && (violation.is_some() || suspicious_sessions_dir.0.is_some()) {
+                    let anti_cheat_record = anti_cheat_records
+                        .0
+                        .entry(player.entity_net_id)
+                        .or_default();
+                    if suspicious_sessions_dir.0.is_some() && !anti_cheat_record.is_reported {
+                        anti_cheat_record.entries.push(SessionRecordEntry {
+                            game_frame_number,
+                            message: RecordedMessage::Outcoming(payload.clone()),
+                        });
+                    }
+                    if let Some(violation) = violation {
+                        log::debug!(
+                            "Flagged a message of {} (frame {}): {:?}",
+                            player.nickname,
+                            game_frame_number,
+                            violation
+                        );
+                        let is_dropped = matches!(violation, Violation::ForeignEntity(_));
+                        anti_cheat_record.flagged_frames.push(FlaggedFrame {
+                            frame_number: game_frame_number,
+                            violation,
+                        });
+                        if is_dropped {
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            match payload {
+                ClientMessagePayload::WalkActions(actions) => {
+                    log::trace!(
+                        "Received WalkAction updates (frame {}): {:?}",
+                        game_frame_number,
+                        actions
+                    );
+                    let discarded_actions =
+                        add_walk_actions(&mut *framed_updates, actions, game_frame_number);
+
+                    if !discarded_actions.is_empty() {
+                        log::trace!(
+                            "{} walk actions have been discarded",
+                            discarded_actions.len()
+                        );
+                        if let Some(net_connection_model) = (&net_connection_models)
+                            .join()
+                            .find(|net_connection_model| net_connection_model.id == connection_id)
+                        {
+                            send_message_reliable(
+                                &mut transport,
+                                net_connection_model,
+                                ServerMessagePayload::DiscardWalkActions(discarded_actions),
+                            );
+                        }
+                    }
+                }
+
+                ClientMessagePayload::CastActions(actions) => {
+                    add_cast_actions(
+                        &mut *framed_updates,
+                        actions,
+                        &mut *action_update_id_provider,
+                        game_frame_number,
+                    );
+                }
+
+                ClientMessagePayload::LookActions(actions) => {
+                    add_look_actions(&mut *framed_updates, actions, game_frame_number);
+                }
+
+                ClientMessagePayload::PlayerInputs(input_frames) => {
+                    let net_connection_model = match (&mut net_connection_models)
+                        .join()
+                        .find(|net_connection_model| net_connection_model.id == connection_id)
+                    {
+                        Some(net_connection_model) => net_connection_model,
+                        None => continue,
+                    };
+
+                    let mut discarded_actions = Vec::new();
+                    for input_frame in input_frames {
+                        let input_frame = skip_received_inputs(
+                            input_frame,
+                            &mut net_connection_model.last_client_action_id,
+                        );
+                        if !input_frame.walk_action_updates.is_empty() {
+                            discarded_actions.append(&mut add_walk_actions(
+                                &mut *framed_updates,
+                                ImmediatePlayerActionsUpdates {
+                                    frame_number: input_frame.frame_number,
+                                    updates: input_frame.walk_action_updates,
+                                },
+                                game_frame_number,
+                            ));
+                        }
+                        if !input_frame.cast_action_updates.is_empty() {
+                            add_cast_actions(
+                                &mut *framed_updates,
+                                ImmediatePlayerActionsUpdates {
+                                    frame_number: input_frame.frame_number,
+                                    updates: input_frame.cast_action_updates,
+                                },
+                                &mut *action_update_id_provider,
+                                game_frame_number,
+                            );
+                        }
+                        if !input_frame.interact_action_updates.is_empty() {
+                            interaction_requests.received.push(connection_id);
+                        }
+                    }
+
+                    if !discarded_actions.is_empty() {
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::DiscardWalkActions(discarded_actions),
+                        );
+                    }
+                    // Is sent for duplicates too, in case the previous acknowledgement got lost.
+                    if let Some(last_client_action_id) = net_connection_model.last_client_action_id
+                    {
+                        send_message_unreliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::AcknowledgeInputs(last_client_action_id),
+                        );
+                    }
+                }
+
+                ClientMessagePayload::ChooseAugment(augment) => {
+                    augment_choices.received.push((connection_id, augment));
+                }
+
+                ClientMessagePayload::Summon => {
+                    summon_requests.received.push(connection_id);
+                }
+
+                ClientMessagePayload::BuyItem(item) => {
+                    shop_purchases.received.push((connection_id, item));
+                }
+
+                ClientMessagePayload::ReadyForWave => {
+                    wave_readiness.received.push(connection_id);
+                }
+
+                payload => {
+                    log::warn!(
+                        "Received an unexpected client action message (connection id: {}): {:?}",
+                        connection_id,
+                        payload
+                    );
+                }
+            }
+        }
+
+        // We should reserve new updates only if we're not paused. If we do it regardless, we'll
+        // get redundant updates reserved.
+        if *game_engine_state == GameEngineState::Playing
+            && !(multiplayer_game_state.waiting_network
+                || multiplayer_game_state.waiting_for_players)
+        {
+            server_world_updates.reserve_new_updates(
+                framed_updates.oldest_updated_frame.min(game_frame_number),
+                game_frame_number,
+            );
+        }
+    }
+}
+
+/// Drops the actions which have arrived with the previous PlayerInputs packets.
+/// Client action ids grow with every action, so anything up to the latest received id
+/// is either a redundant copy or too old to be applied in order.
+fn skip_received_inputs(
+    mut input_frame: InputFrame,
+    last_client_action_id: &mut Option<NetIdentifier>,
+) -> InputFrame {
+    let last_received_id = *last_client_action_id;
+    let is_new = |client_action_id: NetIdentifier| Some(client_action_id) > last_received_id;
+    input_frame
+        .walk_action_updates
+        .retain(|update| is_new(update.data.client_action_id));
+    input_frame
+        .cast_action_updates
+        .retain(|update| is_new(update.data.client_action_id));
+    input_frame
+        .interact_action_updates
+        .retain(|update| is_new(update.data.client_action_id));
+    *last_client_action_id = last_received_id.max(input_frame.last_client_action_id());
+    input_frame
+}
+
+/// Returns discarded actions.
+fn add_walk_actions(
+    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
+    actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>,
+    frame_number: u64,
+) -> Vec<NetIdentifier> {
+    let mut discarded_actions = Vec::new();
+
+    let added_actions_frame_number = actions.frame_number;
+
+    // Just ignore these updates, most probably these are lost packages from the previous game,
+    // or the client is just bonkers.
+    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+        return Vec::new();
+    }
+
+    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
+    let actual_frame = if are_lag_compensated {
+        added_actions_frame_number
+    } else {
+        oldest_possible_frame
+    };
+
+    let is_badly_late = added_actions_frame_number
+        < frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64 * 2);
+    for action in actions.updates {
+        let is_added = {
+            if is_badly_late {
+                // If there was any accepted update after this one, we're going to skip it,
+                // as it's impossible to postpone the other ones.
+                !framed_updates
+                    .updates
+                    .iter()
+                    .skip_while(|update| update.frame_number < added_actions_frame_number)
+                    .any(|update| {
+                        update
+                            .walk_action_updates
+                            .iter()
+                            .any(|net_update| net_update.entity_net_id == action.entity_net_id)
+                    })
+            } else {
+                true
+            }
+        };
+
+        if is_added {
+            let frames_to_move = oldest_possible_frame.saturating_sub(added_actions_frame_number);
+            if !is_badly_late && frames_to_move > 0 {
+                let mut moved_updates = Vec::with_capacity(LAG_COMPENSATION_FRAMES_LIMIT);
+                for framed_update in framed_updates
+                    .updates
+                    .iter_mut()
+                    .skip_while(|update| update.frame_number < actual_frame)
+                {
+                    if let Some(i) = framed_update
+                        .walk_action_updates
+                        .iter()
+                        .position(|net_update| net_update.entity_net_id == action.entity_net_id)
+                    {
+                        let moved_update = framed_update.walk_action_updates.remove(i);
+                        if framed_update.frame_number + frames_to_move > frame_number {
+                            discarded_actions.push(moved_update.data.client_action_id);
+                        } else {
+                            moved_updates.push((framed_update.frame_number, moved_update));
+                        }
+                    }
+                }
+
+                let mut framed_updates_iter =
+                    framed_updates.updates_iter_mut(actual_frame).peekable();
+                for (moved_update_frame_number, moved_update) in moved_updates.into_iter() {
+                    loop {
+                        let framed_update = framed_updates_iter.peek().unwrap();
+                        if framed_update.frame_number == moved_update_frame_number {
+                            break;
+                        }
+                    }
+                    framed_updates_iter
+                        .next()
+                        .expect("Expected a framed update to move a NetUpdate into")
+                        .walk_action_updates
+                        .push(moved_update);
+                }
+            }
+            let updated_frame = framed_updates
+                .update_frame(actual_frame)
+                .unwrap_or_else(|| panic!("Expected a frame {}", actual_frame));
+
+            log::trace!(
+                "Added a walk action update for frame {} to frame {}",
+                added_actions_frame_number,
+                updated_frame.frame_number
+            );
+
+            updated_frame.walk_action_updates.push(action);
+        } else {
+            discarded_actions.push(action.data.client_action_id);
+        }
+    }
+
+    discarded_actions
+}
+
+fn add_look_actions(
+    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
+    actions: PlayerLookActionUpdates,
+    frame_number: u64,
+) {
+    let frame_to_reserve = actions
+        .updates
+        .iter()
+        .filter(|(_, updates)| !updates.is_empty())
+        .map(|(frame_number, _)| frame_number)
+        .max_by(|prev_frame_number, next_frame_number| prev_frame_number.cmp(next_frame_number));
+
+    // Just ignore these updates, most probably these are lost packages from the previous game,
+    // or the client is just bonkers.
+    let is_outdated_update = frame_to_reserve.map_or(true, |frame_to_reserve| {
+        frame_to_reserve.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD
+    });
+    if is_outdated_update {
+        return;
+    }
+
+    if let Some(frame_to_reserve) = frame_to_reserve {
+        framed_updates.reserve_updates(*frame_to_reserve);
+    }
+
+    let mut oldest_updated_frame = framed_updates.oldest_updated_frame;
+    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let mut framed_updates_iter = framed_updates.updates_iter_mut(oldest_possible_frame);
+
+    'action_updates: for (update_frame_number, updates) in actions.updates {
+        let mut framed_update = framed_updates_iter
+            .next()
+            .expect("Expected at least one framed update");
+
+        if update_frame_number >= oldest_possible_frame {
+            loop {
+                if update_frame_number == framed_update.frame_number {
+                    break;
+                }
+                framed_update = if let Some(framed_update) = framed_updates_iter.next() {
+                    framed_update
+                } else {
+                    log::warn!(
+                        "Server couldn't apply a look action update for frame {}, while being at frame {}",
+                        update_frame_number,
+                        frame_number,
+                    );
+                    break 'action_updates;
+                }
+            }
+        }
+
+        if !updates.is_empty() {
+            oldest_updated_frame = oldest_updated_frame.min(framed_update.frame_number);
+        }
+
+        for update in updates {
+            if let Some(i) = framed_update
+                .look_action_updates
+                .iter()
+                .position(|net_update| net_update.entity_net_id == update.entity_net_id)
+            {
+                framed_update.look_action_updates[i] = update;
+            } else {
+                framed_update.look_action_updates.push(update);
+            }
+            log::trace!(
+                "Added a look action update for frame {} to frame {}",
+                update_frame_number,
+                framed_update.frame_number
+            );
+        }
+    }
+
+    drop(framed_updates_iter);
+    framed_updates.oldest_updated_frame = oldest_updated_frame;
+}
+
+fn add_cast_actions(
+    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
+    actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>,
+    action_update_id_provider: &mut ActionUpdateIdProvider,
+    frame_number: u64,
+) {
+    let added_actions_frame_number = actions.frame_number;
+
+    // Just ignore these updates, most probably these are lost packages from the previous game,
+    // or the client is just bonkers.
+    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+        return;
+    }
+
+    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
+    let actual_frame = if are_lag_compensated {
+        added_actions_frame_number
+    } else {
+        oldest_possible_frame
+    };
+
+    for action_update in actions.updates {
+        let is_added = !framed_updates
+            .updates
+            .iter()
+            .skip_while(|update| update.frame_number < actual_frame)
+            .any(|update| {
+                update
+                    .cast_action_updates
+                    .iter()
+                    .any(|net_update| net_update.entity_net_id == action_update.entity_net_id)
+            });
+
+        if is_added {
+            let updated_frame = framed_updates
+                .update_frame(actual_frame)
+                .unwrap_or_else(|| panic!("Expected a frame {}", actual_frame));
+
+            log::trace!(
+                "Added a walk action update for frame {} to frame {}",
+                added_actions_frame_number,
+                updated_frame.frame_number
+            );
+
+            updated_frame.cast_action_updates.push(NetUpdate {
+                entity_net_id: action_update.entity_net_id,
+                data: IdentifiableAction {
+                    action_id: action_update_id_provider.next_update_id(),
+                    action: action_update.data,
+                },
+            });
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use amethyst::{
-    ecs::{Join, ReadStorage, System, Write, WriteExpect},
+    ecs::{Join, ReadExpect, ReadStorage, System, Write, WriteExpect},
     network::simulation::TransportResource,
 };
 
@@ -8,16 +8,22 @@ use gv_core::{
         components::NetConnectionModel, resources::world::ServerWorldUpdates,
         system_data::time::GameTimeService,
     },
-    net::server_message::ServerMessagePayload,
+    net::{server_message::UpdateWorldRef, MessageEncoder},
+    profile_scope,
 };
-use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_unreliable};
+use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_encoded_message_unreliable};
 
-use crate::ecs::resources::LastBroadcastedFrame;
+use crate::ecs::resources::{LastBroadcastedFrame, Spectators};
 
 const BROADCAST_FRAME_INTERVAL: u64 = 5;
 
 #[derive(Default)]
-pub struct GameUpdatesBroadcastingSystem;
+pub struct GameUpdatesBroadcastingSystem {
+    last_resent_engine_frame: u64,
+    // Both are reused between broadcasts to avoid allocating on every one of them.
+    message_encoder: MessageEncoder,
+    update_indices: Vec<usize>,
+}
 
 impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
     type SystemData = (
@@ -25,6 +31,7 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         GameStateHelper<'s>,
         WriteExpect<'s, ServerWorldUpdates>,
         WriteExpect<'s, LastBroadcastedFrame>,
+        ReadExpect<'s, Spectators>,
         ReadStorage<'s, NetConnectionModel>,
         Write<'s, TransportResource>,
     );
@@ -36,82 +43,92 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
             game_state_helper,
             mut server_world_updates,
             mut last_broadcasted_frame,
+            spectators,
             net_connection_models,
             mut transport,
         ): Self::SystemData,
     ) {
-        if !game_state_helper.multiplayer_is_running() {
-            return;
-        }
+        if game_state_helper.multiplayer_is_waiting_for_players() {
+            // Game frames don't advance during the pause, but clients that have stalled
+            // the game with unacknowledged updates need them resent to catch up.
+            let engine_frame_number = game_time_service.engine_time().frame_number();
+            if engine_frame_number.saturating_sub(self.last_resent_engine_frame)
+                <= BROADCAST_FRAME_INTERVAL
+            {
+                return;
+            }
+            self.last_resent_engine_frame = engine_frame_number;
+        } else {
+            if !game_state_helper.multiplayer_is_running() {
+                return;
+            }
 
-        let last_broadcasted_frame = &mut last_broadcasted_frame.0;
+            let last_broadcasted_frame = &mut last_broadcasted_frame.0;
 
-        let is_time_to_broadcast = game_time_service
-            .game_frame_number()
-            .wrapping_sub(*last_broadcasted_frame)
-            > BROADCAST_FRAME_INTERVAL;
-        if !is_time_to_broadcast {
-            return;
+            let is_time_to_broadcast = game_time_service
+                .game_frame_number()
+                .wrapping_sub(*last_broadcasted_frame)
+                > game_time_service.scale_frames(BROADCAST_FRAME_INTERVAL);
+            if !is_time_to_broadcast {
+                return;
+            }
+            *last_broadcasted_frame = game_time_service.game_frame_number();
         }
-        *last_broadcasted_frame = game_time_service.game_frame_number();
 
-        let (latest_update_number, latest_update_frame_number) = {
-            let latest_update = server_world_updates
-                .updates
-                .back()
-                .expect("Expected at least one ServerWorldUpdate");
-            (latest_update.0, latest_update.1.frame_number)
-        };
+        profile_scope!("GameUpdatesBroadcastingSystem::broadcast");
+
+        let (latest_update_number, latest_update_frame_number) =
+            match server_world_updates.updates.back() {
+                Some((latest_update_number, latest_update)) => {
+                    (*latest_update_number, latest_update.frame_number)
+                }
+                None => return,
+            };
 
-        // We'll use these to drop server updates that are no longer needed.
-        let mut oldest_actual_update = latest_update_number + 1;
-        let mut oldest_actual_update_index = 0;
+        // We'll use it to drop server updates that are no longer needed.
+        let mut oldest_acknowledged_update = Some(latest_update_number);
 
-        for (i, net_connection_model) in (&net_connection_models).join().enumerate() {
-            let last_acknowledged_is_older = net_connection_model
-                .last_acknowledged_update
-                .map(|last_acknowledged| oldest_actual_update > last_acknowledged)
-                .unwrap_or(true);
-            if last_acknowledged_is_older {
-                oldest_actual_update = net_connection_model.last_acknowledged_update.unwrap_or(0);
-                oldest_actual_update_index = i;
+        for net_connection_model in (&net_connection_models).join() {
+            // Spectators get the finalized updates from `SpectatorRelaySystem` instead.
+            if spectators.contains(net_connection_model.id) {
+                continue;
+            }
+            if !net_connection_model.disconnected {
+                oldest_acknowledged_update =
+                    oldest_acknowledged_update.min(net_connection_model.last_acknowledged_update);
             }
 
             // Gather the updates this client needs based on its last_acknowledged_update.
+            self.update_indices.clear();
             let mut oldest_added_frame = latest_update_frame_number + 1;
-            let updates = server_world_updates
-                .updates
-                .iter()
-                .rev()
-                .take_while(|update| Some(update.0) > net_connection_model.last_acknowledged_update)
-                .filter_map(move |update| {
-                    let update = &update.1;
-                    // We may store some repetitive updates, so we need to filter them out.
-                    if oldest_added_frame > update.frame_number {
-                        oldest_added_frame = update.frame_number;
-                        Some(update.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>();
-            let updates = updates.into_iter().rev().collect();
+            for (i, (update_number, update)) in
+                server_world_updates.updates.iter().enumerate().rev()
+            {
+                if Some(*update_number) <= net_connection_model.last_acknowledged_update {
+                    break;
+                }
+                // We may store some repetitive updates, so we need to filter them out.
+                if oldest_added_frame > update.frame_number {
+                    oldest_added_frame = update.frame_number;
+                    self.update_indices.push(i);
+                }
+            }
+            self.update_indices.reverse();
 
-            send_message_unreliable(
-                &mut transport,
-                &net_connection_model,
-                ServerMessagePayload::UpdateWorld {
+            let message = self.message_encoder.encode_message(
+                net_connection_model.session_id,
+                &UpdateWorldRef {
                     id: latest_update_number,
-                    updates,
+                    server_world_updates: &server_world_updates,
+                    update_indices: &self.update_indices,
                 },
             );
+            send_encoded_message_unreliable(&mut transport, &net_connection_model, message);
         }
 
         // We don't need to store these updates anymore, as clients have already acknowledged them.
-        if oldest_actual_update <= latest_update_number {
-            server_world_updates
-                .updates
-                .drain(0..=oldest_actual_update_index);
+        if let Some(oldest_acknowledged_update) = oldest_acknowledged_update {
+            server_world_updates.drop_acknowledged(oldest_acknowledged_update);
         }
     }
 }
@@ -0,0 +1,148 @@
+use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, System};
+
+use gv_core::{
+    augments::PlayerAugments,
+    economy::PlayerWallet,
+    ecs::{
+        components::{Dead, Player},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState, SavedMatch, SavedPlayer},
+            GameLevelState,
+        },
+        system_data::time::GameTimeService,
+    },
+};
+use gv_game::ecs::{
+    resources::{ActiveLevelScript, MatchSeed},
+    system_data::GameStateHelper,
+};
+
+use crate::ecs::resources::MatchSaveFile;
+
+const SAVE_FRAME_INTERVAL: u64 = 600;
+
+/// Periodically saves the progress of a multiplayer match, so that the host can resume it.
+/// A save is deleted once all the players are dead, as there's nothing to resume,
+/// unless the payload of the level script has reached a checkpoint.
+#[derive(Default)]
+pub struct MatchSavingSystem {
+    last_saved_frame: u64,
+}
+
+impl<'s> System<'s> for MatchSavingSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        GameStateHelper<'s>,
+        Read<'s, MatchSaveFile>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, ActiveLevelScript>,
+        ReadExpect<'s, MatchSeed>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, PlayerAugments>,
+        ReadStorage<'s, PlayerWallet>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_state_helper,
+            match_save_file,
+            multiplayer_game_state,
+            game_level_state,
+            active_level_script,
+            match_seed,
+            entity_net_metadata_storage,
+            players,
+            dead,
+            player_augments,
+            player_wallets,
+        ): Self::SystemData,
+    ) {
+        if match_save_file.0.is_none() || !game_state_helper.multiplayer_is_running() {
+            return;
+        }
+
+        let game_frame_number = game_time_service.game_frame_number();
+        if game_frame_number < self.last_saved_frame {
+            // A new game has started.
+            self.last_saved_frame = 0;
+        }
+        if game_frame_number - self.last_saved_frame
+            < game_time_service.scale_frames(SAVE_FRAME_INTERVAL)
+        {
+            return;
+        }
+        self.last_saved_frame = game_frame_number;
+
+        let saved_players = saved_players(
+            game_frame_number,
+            &multiplayer_game_state,
+            &entity_net_metadata_storage,
+            &players,
+            &dead,
+            &player_augments,
+            &player_wallets,
+        );
+
+        let payload_checkpoint = active_level_script
+            .runner
+            .as_ref()
+            .and_then(|runner| runner.payload())
+            .map_or(0.0, |payload| payload.progress().checkpoint_distance);
+        let is_everyone_dead = (&players, dead.maybe())
+            .join()
+            .all(|(_, dead)| dead.map_or(false, |dead| dead.is_dead(game_frame_number)));
+        if is_everyone_dead && payload_checkpoint == 0.0 {
+            log::info!("All the players are dead, deleting the saved match");
+            match_save_file.delete();
+            return;
+        }
+
+        log::debug!("Saving the match (frame {})", game_frame_number);
+        match_save_file.save(&SavedMatch {
+            spawn_level: game_level_state.spawn_level,
+            game_frame_number,
+            seed: match_seed.0,
+            campaign_level: multiplayer_game_state.campaign_level,
+            players: saved_players,
+            payload_checkpoint,
+        });
+    }
+}
+
+/// Dead players are saved with full health, so that they keep their upgrades.
+pub(super) fn saved_players(
+    game_frame_number: u64,
+    multiplayer_game_state: &MultiplayerGameState,
+    entity_net_metadata_storage: &EntityNetMetadataStorage,
+    players: &ReadStorage<Player>,
+    dead: &ReadStorage<Dead>,
+    player_augments: &ReadStorage<PlayerAugments>,
+    player_wallets: &ReadStorage<PlayerWallet>,
+) -> Vec<SavedPlayer> {
+    multiplayer_game_state
+        .players
+        .iter()
+        .filter_map(|player| {
+            let entity = entity_net_metadata_storage.get_entity(player.entity_net_id)?;
+            let player_component = players.get(entity)?;
+            let is_dead = dead
+                .get(entity)
+                .map_or(false, |dead| dead.is_dead(game_frame_number));
+            Some(SavedPlayer {
+                nickname: player.nickname.clone(),
+                health: if is_dead {
+                    player_component.max_health
+                } else {
+                    player_component.health
+                },
+                augments: player_augments.get(entity).cloned().unwrap_or_default(),
+                wallet: player_wallets.get(entity).cloned().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
@@ -1,6 +1,19 @@
+mod afk_detection;
+mod anti_cheat;
+mod balance_reload;
+mod campaign;
+mod client_actions;
 mod game_updates_broadcasting;
+mod match_saving;
+mod reconciliation;
 mod server_network;
+mod spectator_relay;
+mod votes;
 
 pub use self::{
-    game_updates_broadcasting::GameUpdatesBroadcastingSystem, server_network::ServerNetworkSystem,
+    afk_detection::AfkDetectionSystem, anti_cheat::AntiCheatSystem,
+    balance_reload::BalanceReloadSystem, campaign::CampaignSystem,
+    client_actions::ClientActionsSystem, game_updates_broadcasting::GameUpdatesBroadcastingSystem,
+    match_saving::MatchSavingSystem, reconciliation::ReconciliationReportSystem,
+    server_network::ServerNetworkSystem, spectator_relay::SpectatorRelaySystem, votes::VoteSystem,
 };
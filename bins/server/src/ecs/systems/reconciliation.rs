@@ -0,0 +1,89 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System, Write},
+    network::simulation::TransportResource,
+};
+
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            world::WorldStates,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::server_message::ServerMessagePayload,
+};
+use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_unreliable};
+
+const REPORT_FRAME_INTERVAL: u64 = 60;
+
+/// Periodically sends each player the authoritative state of its own player, clients compare it
+/// with their prediction to detect divergences.
+#[derive(Default)]
+pub struct ReconciliationReportSystem {
+    last_reported_frame: u64,
+}
+
+impl<'s> System<'s> for ReconciliationReportSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        GameStateHelper<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        ReadExpect<'s, WorldStates>,
+        ReadStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_state_helper,
+            multiplayer_game_state,
+            entity_net_metadata_storage,
+            world_states,
+            net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.multiplayer_is_running() {
+            return;
+        }
+
+        let game_frame_number = game_time_service.game_frame_number();
+        if game_frame_number < self.last_reported_frame {
+            // A new game has started.
+            self.last_reported_frame = 0;
+        }
+        if game_frame_number - self.last_reported_frame
+            < game_time_service.scale_frames(REPORT_FRAME_INTERVAL)
+        {
+            return;
+        }
+        self.last_reported_frame = game_frame_number;
+
+        let world_state = match world_states.latest_state() {
+            Some(world_state) => world_state,
+            None => return,
+        };
+        for player in &multiplayer_game_state.players {
+            let player_reconciliation = entity_net_metadata_storage
+                .get_entity(player.entity_net_id)
+                .and_then(|player_entity| world_state.player_reconciliation(player_entity));
+            let net_connection_model = (&net_connection_models)
+                .join()
+                .find(|net_connection_model| net_connection_model.id == player.connection_id);
+            if let (Some(player_reconciliation), Some(net_connection_model)) =
+                (player_reconciliation, net_connection_model)
+            {
+                send_message_unreliable(
+                    &mut transport,
+                    net_connection_model,
+                    ServerMessagePayload::ReconciliationReport(player_reconciliation),
+                );
+            }
+        }
+    }
+}
@@ -1,50 +1,62 @@
 use amethyst::{
-    ecs::{Entities, Join, ReadExpect, System, Write, WriteExpect, WriteStorage},
+    ecs::{
+        Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+    },
     network::simulation::TransportResource,
 };
 
 use gv_core::{
-    actions::{
-        player::{PlayerCastAction, PlayerWalkAction},
-        ClientActionUpdate, IdentifiableAction,
-    },
+    actions::{player::PlayerWalkAction, ClientActionUpdate},
+    balance::Balance,
+    content_packs::ContentPacks,
     ecs::{
-        components::NetConnectionModel,
+        components::{Allegiance, EntityNetMetadata, NetConnectionModel},
         resources::{
-            net::{ActionUpdateIdProvider, MultiplayerGameState, MultiplayerRoomPlayer},
+            net::{MultiplayerGameState, MultiplayerRoomPlayer},
             world::{
-                FramedUpdates, ImmediatePlayerActionsUpdates, PlayerLookActionUpdates,
-                ReceivedClientActionUpdates, ServerWorldUpdates, LAG_COMPENSATION_FRAMES_LIMIT,
+                ImmediatePlayerActionsUpdates, ServerWorldUpdates, WorldStates,
                 PAUSE_FRAME_THRESHOLD,
             },
             GameEngineState, NewGameEngineState,
         },
         system_data::time::GameTimeService,
     },
+    emotes::EMOTE_COOLDOWN_FRAMES,
     net::{
         client_message::{ClientMessage, ClientMessagePayload},
-        server_message::{DisconnectReason, ServerMessagePayload},
-        NetEvent, NetIdentifier, NetUpdate, INTERPOLATION_FRAME_DELAY,
+        server_message::{DisconnectReason, LaggingPlayer, ServerMessagePayload},
+        NetEvent, NetIdentifier, NetUpdate,
     },
-    PLAYER_COLORS,
+    room_settings::{GameMode, RoomSettings},
 };
 use gv_game::{
+    console::Console,
     ecs::resources::ConnectionEvents,
     utils::net::{broadcast_message_reliable, broadcast_message_unreliable, send_message_reliable},
 };
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::ecs::resources::{HostClientAddress, LastBroadcastedFrame};
+use crate::ecs::resources::{
+    ClientActionMessages, HostClientAddress, LastBroadcastedFrame, MatchSaveFile, RejoinConfig,
+    Spectator, Spectators, VoteMessages,
+};
 use gv_core::net::server_message::PlayerNetStatus;
 
 const HEARTBEAT_FRAME_INTERVAL: u64 = 2;
 const REPORT_PLAYERS_STATUS_FRAME_INTERVAL: u64 = 50;
+const MAX_SPECTATORS: usize = 8;
 
 pub struct ServerNetworkSystem {
     host_connection_id: Option<NetIdentifier>,
     last_heartbeat_frame: u64,
     last_report_players_status_frame: u64,
+    last_report_lagging_players_frame: u64,
+    /// Engine frame numbers of the last accepted emotes per connection id.
+    last_emote_frames: HashMap<NetIdentifier, u64>,
+    /// Game frame numbers at which the players have lost the connection during a game,
+    /// per connection id. They can rejoin until `RejoinConfig::grace_period` ends.
+    disconnected_players: HashMap<NetIdentifier, u64>,
 }
 
 impl ServerNetworkSystem {
@@ -53,6 +65,9 @@ impl ServerNetworkSystem {
             host_connection_id: None,
             last_heartbeat_frame: 0,
             last_report_players_status_frame: 0,
+            last_report_lagging_players_frame: 0,
+            last_emote_frames: HashMap::new(),
+            disconnected_players: HashMap::new(),
         }
     }
 
@@ -63,20 +78,36 @@ impl ServerNetworkSystem {
     }
 }
 
+impl Default for ServerNetworkSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'s> System<'s> for ServerNetworkSystem {
     type SystemData = (
         GameTimeService<'s>,
         Entities<'s>,
+        ReadExpect<'s, Balance>,
         ReadExpect<'s, GameEngineState>,
         ReadExpect<'s, LastBroadcastedFrame>,
+        ReadExpect<'s, ServerWorldUpdates>,
+        ReadExpect<'s, WorldStates>,
+        ReadExpect<'s, RejoinConfig>,
+        Read<'s, MatchSaveFile>,
+        Read<'s, ContentPacks>,
+        WriteExpect<'s, Console>,
         WriteExpect<'s, ConnectionEvents>,
         WriteExpect<'s, HostClientAddress>,
         WriteExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, RoomSettings>,
         WriteExpect<'s, NewGameEngineState>,
-        WriteExpect<'s, FramedUpdates<ReceivedClientActionUpdates>>,
-        WriteExpect<'s, ServerWorldUpdates>,
-        WriteExpect<'s, ActionUpdateIdProvider>,
+        WriteExpect<'s, ClientActionMessages>,
+        WriteExpect<'s, VoteMessages>,
+        WriteExpect<'s, Spectators>,
         WriteStorage<'s, NetConnectionModel>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, Allegiance>,
         Write<'s, TransportResource>,
     );
 
@@ -86,16 +117,26 @@ impl<'s> System<'s> for ServerNetworkSystem {
         (
             game_time_service,
             entities,
+            balance,
             game_engine_state,
             last_broadcasted_frame,
+            server_world_updates,
+            world_states,
+            rejoin_config,
+            match_save_file,
+            content_packs,
+            mut console,
             mut connection_events,
             mut host_client_address,
             mut multiplayer_game_state,
+            mut room_settings,
             mut new_game_engine_state,
-            mut framed_updates,
-            mut server_world_updates,
-            mut action_update_id_provider,
+            mut client_action_messages,
+            mut vote_messages,
+            mut spectators,
             mut net_connection_models,
+            entity_net_metadata,
+            allegiances,
             mut transport,
         ): Self::SystemData,
     ) {
@@ -109,6 +150,8 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 ServerMessagePayload::Handshake {
                     net_id: 0,
                     is_host: true,
+                    tick_rate: game_time_service.tick_rate(),
+                    content_packs: content_packs.infos(),
                 },
             );
             entities
@@ -119,6 +162,8 @@ impl<'s> System<'s> for ServerNetworkSystem {
 
         let mut host_disconnected = false;
         let mut kicked_players = HashSet::new();
+        let mut emotes = Vec::new();
+        let mut room_settings_updated = false;
 
         for connection_event in connection_events.0.drain(..) {
             let connection_id = connection_event.connection_id;
@@ -136,10 +181,14 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 if *session_id < net_connection_model.session_id {
                     log::warn!("Ignoring a message with session id {} from a connection {} with session id {}", session_id, net_connection_model.id, net_connection_model.session_id);
                     continue;
-                } else if let ClientMessagePayload::JoinRoom { sent_at, .. } = payload {
+                } else if let ClientMessagePayload::JoinRoom { sent_at, .. }
+                | ClientMessagePayload::Spectate { sent_at, .. } = payload
+                {
                     if net_connection_model.session_created_at < *sent_at {
                         net_connection_model.session_id = *session_id;
                         net_connection_model.session_created_at = *sent_at;
+                        // A restarted client numbers its actions anew.
+                        net_connection_model.last_client_action_id = None;
                         // It might be the case that a player reconnects before the connection model
                         // entity is dropped, so we need to change this flag manually for previously
                         // existed connections.
@@ -156,12 +205,15 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 }) = &connection_event.event
                 {
                     let is_ignored = match payload {
-                        ClientMessagePayload::JoinRoom { .. } => {
-                            let player_is_in_game = multiplayer_game_state
-                                .players
-                                .iter()
-                                .any(|player| player.connection_id == connection_id);
-                            if !player_is_in_game {
+                        ClientMessagePayload::JoinRoom { nickname, .. } => {
+                            let player_is_in_game =
+                                multiplayer_game_state.players.iter().any(|player| {
+                                    player.connection_id == connection_id && !player.is_disconnected
+                                });
+                            let can_rejoin = multiplayer_game_state.players.iter().any(|player| {
+                                player.is_disconnected && player.nickname == *nickname
+                            });
+                            if !player_is_in_game && !can_rejoin {
                                 log::warn!(
                                     "A new client ({}) {} tried to connect while the game has already started",
                                     connection_id,
@@ -176,10 +228,13 @@ impl<'s> System<'s> for ServerNetworkSystem {
                                 );
                                 net_connection_model.disconnected = true;
                             }
-                            true
+                            // Rejoining players are handled below.
+                            player_is_in_game || !can_rejoin
                         }
 
-                        ClientMessagePayload::StartHostedGame => {
+                        ClientMessagePayload::StartHostedGame
+                        | ClientMessagePayload::StartHostedCampaign
+                        | ClientMessagePayload::ResumeHostedGame => {
                             log::warn!(
                                 "A client ({}) {} tried to start the game while it's already started",
                                 connection_id,
@@ -209,6 +264,11 @@ impl<'s> System<'s> for ServerNetworkSystem {
                         ClientMessagePayload::WalkActions(_) => true,
                         ClientMessagePayload::CastActions(_) => true,
                         ClientMessagePayload::LookActions(_) => true,
+                        ClientMessagePayload::PlayerInputs(_) => true,
+                        ClientMessagePayload::ChooseAugment(_) => true,
+                        ClientMessagePayload::Summon => true,
+                        ClientMessagePayload::BuyItem(_) => true,
+                        ClientMessagePayload::ReadyForWave => true,
                         _ => false,
                     };
 
@@ -223,8 +283,93 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     session_id: _,
                     payload,
                 }) => match payload {
+                    ClientMessagePayload::JoinRoom { nickname, .. }
+                        if multiplayer_game_state.is_playing =>
+                    {
+                        // Minions can't be told apart from monsters in a snapshot,
+                        // the rejoining client sees the ones summoned from now on.
+                        let snapshot = match world_states.latest_state() {
+                            Some(world_state) => world_state.snapshot(|entity| {
+                                if allegiances.contains(entity) {
+                                    return None;
+                                }
+                                entity_net_metadata
+                                    .get(entity)
+                                    .map(|entity_net_metadata| entity_net_metadata.id)
+                            }),
+                            None => {
+                                log::warn!(
+                                    "There's no world state to send to a rejoining client ({})",
+                                    connection_id
+                                );
+                                continue;
+                            }
+                        };
+
+                        let player = multiplayer_game_state
+                            .update_players()
+                            .iter_mut()
+                            .find(|player| player.is_disconnected && player.nickname == nickname)
+                            .expect("Expected a disconnected player to rejoin");
+                        log::info!(
+                            "A client ({}) has rejoined the game: {}",
+                            connection_id,
+                            nickname
+                        );
+                        let previous_connection_id =
+                            std::mem::replace(&mut player.connection_id, connection_id);
+                        player.is_disconnected = false;
+                        self.disconnected_players.remove(&previous_connection_id);
+                        // The snapshot already has everything the stored updates carry.
+                        net_connection_model.last_acknowledged_update = server_world_updates
+                            .updates
+                            .back()
+                            .map(|(update_number, _)| *update_number);
+
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::Handshake {
+                                net_id: connection_id,
+                                is_host: false,
+                                tick_rate: game_time_service.tick_rate(),
+                                content_packs: content_packs.infos(),
+                            },
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateRoomSettings(room_settings.clone()),
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateBalance(balance.clone()),
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateRoomPlayers(
+                                multiplayer_game_state.players.clone(),
+                            ),
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::RejoinGame {
+                                entity_net_ids: multiplayer_game_state
+                                    .players
+                                    .iter()
+                                    .map(|player| player.entity_net_id)
+                                    .collect(),
+                                snapshot,
+                            },
+                        );
+                    }
                     ClientMessagePayload::JoinRoom {
                         nickname,
+                        class,
+                        cosmetics,
                         sent_at: _,
                     } => {
                         let is_host = if multiplayer_game_state.players.is_empty() {
@@ -254,16 +399,22 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             connection_id,
                             nickname
                         );
-                        if let Some(player) = multiplayer_game_state
+                        if let Some((player_index, player)) = multiplayer_game_state
                             .update_players()
                             .iter_mut()
-                            .find(|player| player.connection_id == connection_id)
+                            .enumerate()
+                            .find(|(_, player)| player.connection_id == connection_id)
                         {
-                            log::info!("The player already existed, updating the nickname only");
+                            log::info!(
+                                "The player already existed, updating the nickname and looks only"
+                            );
                             player.nickname = nickname;
+                            player.class = class;
+                            player.color = cosmetics.color(player_index);
+                            player.cosmetics = cosmetics;
                         } else {
                             let new_player_count = multiplayer_game_state.players.len();
-                            if new_player_count >= 4 {
+                            if new_player_count >= room_settings.max_players {
                                 send_message_reliable(
                                     &mut transport,
                                     net_connection_model,
@@ -280,7 +431,10 @@ impl<'s> System<'s> for ServerNetworkSystem {
                                     entity_net_id: 0,
                                     nickname,
                                     is_host: self.is_host(connection_id),
-                                    color: PLAYER_COLORS[new_player_count],
+                                    class,
+                                    color: cosmetics.color(new_player_count),
+                                    cosmetics,
+                                    is_disconnected: false,
                                 });
                         }
 
@@ -291,73 +445,214 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             ServerMessagePayload::Handshake {
                                 net_id: connection_id,
                                 is_host,
+                                tick_rate: game_time_service.tick_rate(),
+                                content_packs: content_packs.infos(),
                             },
                         );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateRoomSettings(room_settings.clone()),
+                        );
                     }
 
-                    ClientMessagePayload::StartHostedGame
-                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
+                    ClientMessagePayload::Spectate {
+                        nickname,
+                        sent_at: _,
+                    } if multiplayer_game_state.is_playing => {
+                        if multiplayer_game_state
+                            .find_player_by_connection_id(connection_id)
+                            .is_some()
+                        {
+                            log::warn!("A player ({}) tried to join as a spectator", connection_id);
+                            continue;
+                        }
+
+                        if !spectators.contains(connection_id) {
+                            if spectators.0.len() >= MAX_SPECTATORS {
+                                send_message_reliable(
+                                    &mut transport,
+                                    net_connection_model,
+                                    ServerMessagePayload::Disconnect(DisconnectReason::RoomIsFull),
+                                );
+                                net_connection_model.disconnected = true;
+                                continue;
+                            }
+                            log::info!(
+                                "A client ({}) has joined as a spectator: {}",
+                                connection_id,
+                                nickname
+                            );
+                            spectators.0.push(Spectator {
+                                connection_id,
+                                nickname,
+                                next_relayed_frame: None,
+                            });
+                        }
+
+                        log::info!(
+                            "Sending a Handshake message to a spectator: {}",
+                            connection_id
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::Handshake {
+                                net_id: connection_id,
+                                is_host: false,
+                                tick_rate: game_time_service.tick_rate(),
+                                content_packs: content_packs.infos(),
+                            },
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateRoomPlayers(
+                                multiplayer_game_state.players.clone(),
+                            ),
+                        );
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::UpdateBalance(balance.clone()),
+                        );
+                    }
+                    ClientMessagePayload::Spectate { .. } => {
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::Disconnect(DisconnectReason::GameIsNotStarted),
+                        );
+                        net_connection_model.disconnected = true;
+                    }
+
+                    ClientMessagePayload::SelectClass(class)
+                        if !multiplayer_game_state.is_playing =>
                     {
-                        multiplayer_game_state.is_playing = true;
-                        new_game_engine_state.0 = GameEngineState::Playing;
+                        if let Some(player) = multiplayer_game_state
+                            .update_players()
+                            .iter_mut()
+                            .find(|player| player.connection_id == connection_id)
+                        {
+                            log::info!("{} has selected {}", player.nickname, class.name());
+                            player.class = class;
+                        }
                     }
-                    ClientMessagePayload::StartHostedGame => {
+                    ClientMessagePayload::SelectClass(_) => {
                         log::warn!(
-                            "Received an unexpected StartHostedGame message (connection id: {})",
-                            connection_id,
+                            "Received an unexpected SelectClass message (connection id: {})",
+                            connection_id
                         );
                     }
 
-                    ClientMessagePayload::WalkActions(actions) => {
-                        log::trace!(
-                            "Received WalkAction updates (frame {}): {:?}",
-                            game_time_service.game_frame_number(),
-                            actions
-                        );
-                        let discarded_actions = add_walk_actions(
-                            &mut *framed_updates,
-                            actions,
-                            game_time_service.game_frame_number(),
+                    ClientMessagePayload::Emote(emote) => {
+                        let frame_number = game_time_service.engine_time().frame_number();
+                        let is_rate_limited = self.last_emote_frames.get(&connection_id).map_or(
+                            false,
+                            |last_emote_frame| {
+                                frame_number - last_emote_frame < EMOTE_COOLDOWN_FRAMES
+                            },
                         );
-
-                        if !discarded_actions.is_empty() {
-                            log::trace!(
-                                "{} walk actions have been discarded",
-                                discarded_actions.len()
-                            );
-                            send_message_reliable(
-                                &mut transport,
-                                net_connection_model,
-                                ServerMessagePayload::DiscardWalkActions(discarded_actions),
+                        if is_rate_limited {
+                            log::debug!(
+                                "Dropping a rate-limited emote (connection id: {})",
+                                connection_id
                             );
+                        } else if multiplayer_game_state
+                            .find_player_by_connection_id(connection_id)
+                            .is_some()
+                        {
+                            self.last_emote_frames.insert(connection_id, frame_number);
+                            emotes.push(ServerMessagePayload::Emote {
+                                connection_id,
+                                emote,
+                            });
                         }
                     }
 
-                    ClientMessagePayload::CastActions(actions) => {
-                        add_cast_actions(
-                            &mut *framed_updates,
-                            actions,
-                            &mut *action_update_id_provider,
-                            game_time_service.game_frame_number(),
+                    ClientMessagePayload::StartHostedGame
+                    | ClientMessagePayload::StartHostedCampaign
+                    | ClientMessagePayload::ResumeHostedGame
+                        if self.is_host(connection_id)
+                            && !multiplayer_game_state.is_playing
+                            && room_settings
+                                .validate(multiplayer_game_state.players.len())
+                                .is_err() =>
+                    {
+                        log::warn!(
+                            "Refusing to start the game with invalid room settings ({} players): {:?}",
+                            multiplayer_game_state.players.len(),
+                            *room_settings
                         );
                     }
-
-                    ClientMessagePayload::LookActions(actions) => {
-                        add_look_actions(
-                            &mut *framed_updates,
-                            actions,
-                            game_time_service.game_frame_number(),
+                    ClientMessagePayload::StartHostedGame
+                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
+                    {
+                        multiplayer_game_state.campaign_level = match room_settings.mode {
+                            GameMode::Survival => None,
+                            GameMode::Campaign => Some(0),
+                        };
+                        multiplayer_game_state.is_playing = true;
+                        new_game_engine_state.0 = GameEngineState::Playing;
+                    }
+                    ClientMessagePayload::StartHostedCampaign
+                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
+                    {
+                        multiplayer_game_state.campaign_level = Some(0);
+                        multiplayer_game_state.is_playing = true;
+                        new_game_engine_state.0 = GameEngineState::Playing;
+                    }
+                    ClientMessagePayload::ResumeHostedGame
+                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
+                    {
+                        multiplayer_game_state.resumed_match = match_save_file.load();
+                        if multiplayer_game_state.resumed_match.is_none() {
+                            log::warn!("There's no saved match to resume, starting a new one");
+                        }
+                        multiplayer_game_state.is_playing = true;
+                        new_game_engine_state.0 = GameEngineState::Playing;
+                    }
+                    ClientMessagePayload::StartHostedGame
+                    | ClientMessagePayload::StartHostedCampaign
+                    | ClientMessagePayload::ResumeHostedGame => {
+                        log::warn!(
+                            "Received an unexpected StartHostedGame message (connection id: {})",
+                            connection_id,
                         );
                     }
 
+                    payload @ ClientMessagePayload::WalkActions(_)
+                    | payload @ ClientMessagePayload::CastActions(_)
+                    | payload @ ClientMessagePayload::LookActions(_)
+                    | payload @ ClientMessagePayload::PlayerInputs(_)
+                    | payload @ ClientMessagePayload::ChooseAugment(_)
+                    | payload @ ClientMessagePayload::Summon
+                    | payload @ ClientMessagePayload::BuyItem(_)
+                    | payload @ ClientMessagePayload::ReadyForWave => {
+                        client_action_messages.0.push((connection_id, payload));
+                    }
+
+                    payload @ ClientMessagePayload::StartVote(_)
+                    | payload @ ClientMessagePayload::CastVote { .. } => {
+                        vote_messages.0.push((connection_id, payload));
+                    }
+
                     ClientMessagePayload::AcknowledgeWorldUpdate(frame_number) => {
                         net_connection_model.last_acknowledged_update =
                             Some(frame_number).max(net_connection_model.last_acknowledged_update);
                     }
 
+                    // During a game the host can only drop the players the game is paused for.
                     ClientMessagePayload::Kick {
                         kicked_connection_id,
-                    } if self.is_host(connection_id) && !multiplayer_game_state.is_playing => {
+                    } if self.is_host(connection_id)
+                        && (!multiplayer_game_state.is_playing
+                            || multiplayer_game_state.lagging_players.iter().any(
+                                |lagging_player| {
+                                    lagging_player.connection_id == kicked_connection_id
+                                },
+                            )) =>
+                    {
                         if self.is_host(kicked_connection_id) {
                             log::warn!(
                                 "Tried to kick the host (connection id: {})",
@@ -386,6 +681,48 @@ impl<'s> System<'s> for ServerNetworkSystem {
                         );
                     }
 
+                    ClientMessagePayload::UpdateRoomSettings(new_room_settings)
+                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
+                    {
+                        match new_room_settings.validate(multiplayer_game_state.players.len()) {
+                            Ok(()) => {
+                                log::info!("Updating the room settings: {:?}", new_room_settings);
+                                *room_settings = new_room_settings;
+                                room_settings_updated = true;
+                            }
+                            Err(err) => {
+                                log::warn!("Rejecting the room settings: {}", err);
+                                // Reverts the settings edited by the host.
+                                send_message_reliable(
+                                    &mut transport,
+                                    net_connection_model,
+                                    ServerMessagePayload::UpdateRoomSettings(room_settings.clone()),
+                                );
+                            }
+                        }
+                    }
+                    ClientMessagePayload::UpdateRoomSettings(_) => {
+                        log::warn!(
+                            "Received an unexpected UpdateRoomSettings message (connection id: {})",
+                            connection_id
+                        );
+                    }
+
+                    ClientMessagePayload::SetPauseThreshold(lagging_frames) => {
+                        let lagging_frames = lagging_frames.clamp(1, PAUSE_FRAME_THRESHOLD);
+                        log::info!(
+                            "Setting the pause threshold to {} frames (connection id: {})",
+                            lagging_frames,
+                            connection_id
+                        );
+                        net_connection_model.pause_lagging_frames = Some(lagging_frames);
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::PauseThreshold(lagging_frames),
+                        );
+                    }
+
                     ClientMessagePayload::Disconnect => {
                         net_connection_model.disconnected = true;
                         if self.is_host(connection_id) {
@@ -393,6 +730,55 @@ impl<'s> System<'s> for ServerNetworkSystem {
                         }
                     }
 
+                    ClientMessagePayload::RequestContentPack(name) => {
+                        match content_packs
+                            .get(&name)
+                            .filter(|content_pack| content_pack.is_downloadable())
+                        {
+                            Some(content_pack) => {
+                                log::info!(
+                                    "Sending the content pack {} (connection id: {})",
+                                    name,
+                                    connection_id
+                                );
+                                send_message_reliable(
+                                    &mut transport,
+                                    net_connection_model,
+                                    ServerMessagePayload::ContentPack(content_pack.clone()),
+                                );
+                            }
+                            None => {
+                                log::warn!(
+                                    "Requested a content pack {} which can't be downloaded (connection id: {})",
+                                    name,
+                                    connection_id
+                                );
+                            }
+                        }
+                    }
+
+                    ClientMessagePayload::ConsoleCommand(line) if self.is_host(connection_id) => {
+                        log::info!("Received a console command from the host: {}", line);
+                        console.pending.push(line);
+                    }
+                    ClientMessagePayload::ConsoleCommand(_) => {
+                        log::warn!(
+                            "Received a console command from a client who isn't the host (connection id: {})",
+                            connection_id
+                        );
+                    }
+
+                    ClientMessagePayload::ListRooms
+                    | ClientMessagePayload::CreateRoom { .. }
+                    | ClientMessagePayload::EnterRoom(_)
+                    | ClientMessagePayload::FindMatch { .. }
+                    | ClientMessagePayload::CancelMatchmaking => {
+                        log::warn!(
+                            "Received a room management message, which is handled by RoomRouter only (connection id: {})",
+                            connection_id
+                        );
+                    }
+
                     ClientMessagePayload::Heartbeat
                     | ClientMessagePayload::Ping(_)
                     | ClientMessagePayload::Pong { .. } => {}
@@ -410,13 +796,60 @@ impl<'s> System<'s> for ServerNetworkSystem {
             }
 
             if net_connection_model.disconnected && !host_disconnected {
-                multiplayer_game_state.drop_player_by_connection_id(connection_id);
+                self.last_emote_frames.remove(&connection_id);
+                spectators.remove(connection_id);
+                let player = multiplayer_game_state
+                    .players
+                    .iter()
+                    .find(|player| player.connection_id == connection_id)
+                    .filter(|_| multiplayer_game_state.is_playing)
+                    .map(|player| (player.entity_net_id, player.nickname.clone()));
+                match player {
+                    Some((entity_net_id, nickname)) => {
+                        if self.disconnected_players.contains_key(&connection_id) {
+                            continue;
+                        }
+                        log::info!(
+                            "{} has lost the connection, keeping the player for {:?} to let them rejoin",
+                            nickname,
+                            rejoin_config.grace_period
+                        );
+                        self.disconnected_players
+                            .insert(connection_id, game_time_service.game_frame_number());
+                        multiplayer_game_state
+                            .update_players()
+                            .iter_mut()
+                            .find(|player| player.connection_id == connection_id)
+                            .expect("Expected a disconnected player")
+                            .is_disconnected = true;
+                        // Otherwise the player would keep walking until they rejoin.
+                        stop_walking(
+                            &mut client_action_messages,
+                            game_time_service.game_frame_number(),
+                            connection_id,
+                            entity_net_id,
+                        );
+                    }
+                    None => multiplayer_game_state.drop_player_by_connection_id(connection_id),
+                }
             }
         }
 
         for kicked_player_index in kicked_players.iter().cloned() {
             let player_connection_id =
                 multiplayer_game_state.players[kicked_player_index].connection_id;
+            if multiplayer_game_state.is_playing {
+                log::info!(
+                    "Dropping a lagging player (connection id: {})",
+                    player_connection_id
+                );
+                stop_walking(
+                    &mut client_action_messages,
+                    game_time_service.game_frame_number(),
+                    player_connection_id,
+                    multiplayer_game_state.players[kicked_player_index].entity_net_id,
+                );
+            }
             multiplayer_game_state.drop_player_by_index(kicked_player_index);
             let net_connection_model = (&mut net_connection_models)
                 .join()
@@ -444,6 +877,29 @@ impl<'s> System<'s> for ServerNetworkSystem {
             return;
         }
 
+        // The lobby only has room for the connected players, so nobody can rejoin
+        // once the game is over.
+        let game_frame_number = game_time_service.game_frame_number();
+        let grace_period_frames = (rejoin_config.grace_period.as_secs_f32()
+            * game_time_service.tick_rate() as f32) as u64;
+        let expired_players = self
+            .disconnected_players
+            .iter()
+            .filter(|(_, disconnected_at)| {
+                !multiplayer_game_state.is_playing
+                    || game_frame_number.saturating_sub(**disconnected_at) >= grace_period_frames
+            })
+            .map(|(connection_id, _)| *connection_id)
+            .collect::<Vec<_>>();
+        for connection_id in expired_players {
+            log::info!(
+                "Dropping a player who hasn't rejoined the game (connection id: {})",
+                connection_id
+            );
+            self.disconnected_players.remove(&connection_id);
+            multiplayer_game_state.drop_player_by_connection_id(connection_id);
+        }
+
         if let Some(players) = multiplayer_game_state.read_updated_players() {
             broadcast_message_reliable(
                 &mut transport,
@@ -452,6 +908,31 @@ impl<'s> System<'s> for ServerNetworkSystem {
             );
         }
 
+        if room_settings_updated {
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateRoomSettings(room_settings.clone()),
+            );
+        }
+
+        for emote in emotes {
+            broadcast_message_reliable(&mut transport, (&net_connection_models).join(), emote);
+        }
+
+        let host_connection_model = (&net_connection_models)
+            .join()
+            .find(|net_connection_model| self.is_host(net_connection_model.id));
+        for output in console.output.drain(..) {
+            if let Some(host_connection_model) = host_connection_model {
+                send_message_reliable(
+                    &mut transport,
+                    host_connection_model,
+                    ServerMessagePayload::ConsoleOutput(output),
+                );
+            }
+        }
+
         if game_time_service.engine_time().frame_number() - self.last_heartbeat_frame
             > HEARTBEAT_FRAME_INTERVAL
         {
@@ -475,26 +956,34 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     players: multiplayer_game_state
                         .players
                         .iter()
-                        .map(|player| {
-                            let player_connection_model = (&net_connection_models)
-                                .join()
-                                .find(|connection_model| {
+                        // Players who have lost the connection have nothing to report.
+                        .filter(|player| !player.is_disconnected)
+                        .filter_map(|player| {
+                            let player_connection_model =
+                                (&net_connection_models).join().find(|connection_model| {
                                     connection_model.id == player.connection_id
-                                })
-                                .expect("Expected a connection for a player");
+                                })?;
 
-                            PlayerNetStatus {
+                            Some(PlayerNetStatus {
                                 connection_id: player.connection_id,
                                 frame_number: player_connection_model
                                     .ping_pong_data
-                                    .last_stored_game_frame(),
+                                    .estimated_peer_frame_number(
+                                        game_time_service.game_frame_number_absolute(),
+                                    ),
                                 average_lagging_behind: player_connection_model
                                     .ping_pong_data
-                                    .average_lagging_behind(),
+                                    .lagging_behind(
+                                        game_time_service.game_frame_number(),
+                                        game_time_service.game_frame_number_absolute(),
+                                    ),
                                 latency_ms: player_connection_model
                                     .ping_pong_data
                                     .latency_ms(game_time_service.engine_time().delta_seconds()),
-                            }
+                                pending_world_updates: server_world_updates.pending_updates(
+                                    player_connection_model.last_acknowledged_update,
+                                ),
+                            })
                         })
                         .collect(),
                 },
@@ -506,7 +995,9 @@ impl<'s> System<'s> for ServerNetworkSystem {
         if *game_engine_state == GameEngineState::Playing && multiplayer_game_state.is_playing {
             let mut lagging_players = Vec::new();
             for net_connection_model in (&net_connection_models).join() {
-                if net_connection_model.disconnected {
+                // Spectators are relayed finalized frames, they never hold the game back.
+                if net_connection_model.disconnected || spectators.contains(net_connection_model.id)
+                {
                     continue;
                 }
 
@@ -514,21 +1005,34 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     .engine_time()
                     .frame_number()
                     .saturating_sub(net_connection_model.ping_pong_data.last_ponged_frame);
-                let average_lagging_behind =
-                    net_connection_model.ping_pong_data.average_lagging_behind();
+                // The clock estimate keeps running between pongs, unlike the frame numbers
+                // they carry, so a client that has stalled is noticed sooner.
+                let estimated_client_frame_number = net_connection_model
+                    .ping_pong_data
+                    .estimated_peer_frame_number(game_time_service.game_frame_number_absolute());
+                let lagging_behind = net_connection_model.ping_pong_data.lagging_behind(
+                    game_time_service.game_frame_number(),
+                    game_time_service.game_frame_number_absolute(),
+                );
+                // Pausing keeps the updates stored for a player that stopped acknowledging them
+                // from growing, until they either catch up or time out.
+                let pending_world_updates = server_world_updates
+                    .pending_updates(net_connection_model.last_acknowledged_update);
 
                 let expected_client_frame_number = last_broadcasted_frame
                     .0
-                    .saturating_sub(INTERPOLATION_FRAME_DELAY);
+                    .saturating_sub(game_time_service.interpolation_frame_delay());
 
                 let was_lagging = multiplayer_game_state
                     .lagging_players
                     .iter()
-                    .any(|connection_id| *connection_id == net_connection_model.id);
+                    .any(|lagging_player| lagging_player.connection_id == net_connection_model.id);
 
                 // If a player was already lagging we expect them to fully catch up with others.
-                let is_catching_up = net_connection_model.ping_pong_data.last_stored_game_frame()
-                    < expected_client_frame_number;
+                // Paused clients don't advance, so a pong has to confirm they've caught up.
+                let confirmed_client_frame_number = estimated_client_frame_number
+                    .min(net_connection_model.ping_pong_data.last_stored_game_frame());
+                let is_catching_up = confirmed_client_frame_number < expected_client_frame_number;
 
                 log::trace!(
                     "Frames since last pong (client {}): {}",
@@ -536,27 +1040,42 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     frames_since_last_pong
                 );
                 log::trace!(
-                    "Last_stored_game_frame (client {}): {}. Expected_client_frame_number: {}",
+                    "Estimated_client_frame_number (client {}): {}. Expected_client_frame_number: {}",
                     net_connection_model.id,
-                    net_connection_model.ping_pong_data.last_stored_game_frame(),
+                    estimated_client_frame_number,
                     expected_client_frame_number,
                 );
                 log::trace!(
-                    "Average lagging behind (client {}): {}",
+                    "Lagging behind (client {}): {}",
+                    net_connection_model.id,
+                    lagging_behind
+                );
+                log::trace!(
+                    "Pending world updates (client {}): {}",
                     net_connection_model.id,
-                    average_lagging_behind
+                    pending_world_updates
                 );
 
-                if frames_since_last_pong > PAUSE_FRAME_THRESHOLD
+                let lagging_frames = net_connection_model
+                    .pause_lagging_frames
+                    .unwrap_or(balance.pause.lagging_frames);
+                if frames_since_last_pong > lagging_frames
                     || was_lagging && is_catching_up
-                    || average_lagging_behind > PAUSE_FRAME_THRESHOLD
+                    || lagging_behind > lagging_frames
+                    || pending_world_updates > balance.pause.max_pending_world_updates
                 {
-                    lagging_players.push(net_connection_model.id);
+                    lagging_players.push(LaggingPlayer {
+                        connection_id: net_connection_model.id,
+                        frames_behind: expected_client_frame_number
+                            .saturating_sub(confirmed_client_frame_number),
+                    });
                 }
             }
 
             multiplayer_game_state.lagging_players = lagging_players.clone();
             if !multiplayer_game_state.waiting_for_players && !lagging_players.is_empty() {
+                self.last_report_lagging_players_frame =
+                    game_time_service.engine_time().frame_number();
                 multiplayer_game_state.waiting_for_players_pause_id += 1;
                 broadcast_message_reliable(
                     &mut transport,
@@ -576,260 +1095,43 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     ),
                 );
                 multiplayer_game_state.waiting_for_players = false;
-            }
-        }
-
-        // We should reserve new updates only if we're not paused. If we do it regardless, we'll
-        // get redundant updates reserved.
-        if *game_engine_state == GameEngineState::Playing
-            && !(multiplayer_game_state.waiting_network
-                || multiplayer_game_state.waiting_for_players)
-        {
-            let current_frame_number = game_time_service.game_frame_number();
-            server_world_updates.reserve_new_updates(
-                framed_updates
-                    .oldest_updated_frame
-                    .min(current_frame_number),
-                current_frame_number,
-            );
-        }
-    }
-}
-
-/// Returns discarded actions.
-fn add_walk_actions(
-    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
-    actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>,
-    frame_number: u64,
-) -> Vec<NetIdentifier> {
-    let mut discarded_actions = Vec::new();
-
-    let added_actions_frame_number = actions.frame_number;
-
-    // Just ignore these updates, most probably these are lost packages from the previous game,
-    // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
-        return Vec::new();
-    }
-
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
-    let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
-    let actual_frame = if are_lag_compensated {
-        added_actions_frame_number
-    } else {
-        oldest_possible_frame
-    };
-
-    let is_badly_late = added_actions_frame_number
-        < frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64 * 2);
-    for action in actions.updates {
-        let is_added = {
-            if is_badly_late {
-                // If there was any accepted update after this one, we're going to skip it,
-                // as it's impossible to postpone the other ones.
-                !framed_updates
-                    .updates
-                    .iter()
-                    .skip_while(|update| update.frame_number < added_actions_frame_number)
-                    .any(|update| {
-                        update
-                            .walk_action_updates
-                            .iter()
-                            .any(|net_update| net_update.entity_net_id == action.entity_net_id)
-                    })
-            } else {
-                true
-            }
-        };
-
-        if is_added {
-            let frames_to_move = oldest_possible_frame.saturating_sub(added_actions_frame_number);
-            if !is_badly_late && frames_to_move > 0 {
-                let mut moved_updates = Vec::with_capacity(LAG_COMPENSATION_FRAMES_LIMIT);
-                for framed_update in framed_updates
-                    .updates
-                    .iter_mut()
-                    .skip_while(|update| update.frame_number < actual_frame)
-                {
-                    if let Some(i) = framed_update
-                        .walk_action_updates
-                        .iter()
-                        .position(|net_update| net_update.entity_net_id == action.entity_net_id)
-                    {
-                        let moved_update = framed_update.walk_action_updates.remove(i);
-                        if framed_update.frame_number + frames_to_move > frame_number {
-                            discarded_actions.push(moved_update.data.client_action_id);
-                        } else {
-                            moved_updates.push((framed_update.frame_number, moved_update));
-                        }
-                    }
-                }
-
-                let mut framed_updates_iter =
-                    framed_updates.updates_iter_mut(actual_frame).peekable();
-                for (moved_update_frame_number, moved_update) in moved_updates.into_iter() {
-                    loop {
-                        let framed_update = framed_updates_iter.peek().unwrap();
-                        if framed_update.frame_number == moved_update_frame_number {
-                            break;
-                        }
-                    }
-                    framed_updates_iter
-                        .next()
-                        .expect("Expected a framed update to move a NetUpdate into")
-                        .walk_action_updates
-                        .push(moved_update);
-                }
-            }
-            let updated_frame = framed_updates
-                .update_frame(actual_frame)
-                .unwrap_or_else(|| panic!("Expected a frame {}", actual_frame));
-
-            log::trace!(
-                "Added a walk action update for frame {} to frame {}",
-                added_actions_frame_number,
-                updated_frame.frame_number
-            );
-
-            updated_frame.walk_action_updates.push(action);
-        } else {
-            discarded_actions.push(action.data.client_action_id);
-        }
-    }
-
-    discarded_actions
-}
-
-fn add_look_actions(
-    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
-    actions: PlayerLookActionUpdates,
-    frame_number: u64,
-) {
-    let frame_to_reserve = actions
-        .updates
-        .iter()
-        .filter(|(_, updates)| !updates.is_empty())
-        .map(|(frame_number, _)| frame_number)
-        .max_by(|prev_frame_number, next_frame_number| prev_frame_number.cmp(next_frame_number));
-
-    // Just ignore these updates, most probably these are lost packages from the previous game,
-    // or the client is just bonkers.
-    let is_outdated_update = frame_to_reserve.map_or(true, |frame_to_reserve| {
-        frame_to_reserve.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD
-    });
-    if is_outdated_update {
-        return;
-    }
-
-    if let Some(frame_to_reserve) = frame_to_reserve {
-        framed_updates.reserve_updates(*frame_to_reserve);
-    }
-
-    let mut oldest_updated_frame = framed_updates.oldest_updated_frame;
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
-    let mut framed_updates_iter = framed_updates.updates_iter_mut(oldest_possible_frame);
-
-    'action_updates: for (update_frame_number, updates) in actions.updates {
-        let mut framed_update = framed_updates_iter
-            .next()
-            .expect("Expected at least one framed update");
-
-        if update_frame_number >= oldest_possible_frame {
-            loop {
-                if update_frame_number == framed_update.frame_number {
-                    break;
-                }
-                framed_update = if let Some(framed_update) = framed_updates_iter.next() {
-                    framed_update
-                } else {
-                    log::warn!(
-                        "Server couldn't apply a look action update for frame {}, while being at frame {}",
-                        update_frame_number,
-                        frame_number,
-                    );
-                    break 'action_updates;
-                }
-            }
-        }
-
-        if !updates.is_empty() {
-            oldest_updated_frame = oldest_updated_frame.min(framed_update.frame_number);
-        }
-
-        for update in updates {
-            if let Some(i) = framed_update
-                .look_action_updates
-                .iter()
-                .position(|net_update| net_update.entity_net_id == update.entity_net_id)
+            } else if multiplayer_game_state.waiting_for_players
+                && game_time_service.engine_time().frame_number()
+                    - self.last_report_lagging_players_frame
+                    > REPORT_PLAYERS_STATUS_FRAME_INTERVAL
             {
-                framed_update.look_action_updates[i] = update;
-            } else {
-                framed_update.look_action_updates.push(update);
+                self.last_report_lagging_players_frame =
+                    game_time_service.engine_time().frame_number();
+                broadcast_message_reliable(
+                    &mut transport,
+                    (&net_connection_models).join(),
+                    ServerMessagePayload::UpdateLaggingPlayers {
+                        pause_id: multiplayer_game_state.waiting_for_players_pause_id,
+                        players: lagging_players,
+                    },
+                );
             }
-            log::trace!(
-                "Added a look action update for frame {} to frame {}",
-                update_frame_number,
-                framed_update.frame_number
-            );
         }
     }
-
-    drop(framed_updates_iter);
-    framed_updates.oldest_updated_frame = oldest_updated_frame;
 }
 
-fn add_cast_actions(
-    framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
-    actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>,
-    action_update_id_provider: &mut ActionUpdateIdProvider,
+fn stop_walking(
+    client_action_messages: &mut ClientActionMessages,
     frame_number: u64,
+    connection_id: NetIdentifier,
+    entity_net_id: NetIdentifier,
 ) {
-    let added_actions_frame_number = actions.frame_number;
-
-    // Just ignore these updates, most probably these are lost packages from the previous game,
-    // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
-        return;
-    }
-
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
-    let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
-    let actual_frame = if are_lag_compensated {
-        added_actions_frame_number
-    } else {
-        oldest_possible_frame
-    };
-
-    for action_update in actions.updates {
-        let is_added = !framed_updates
-            .updates
-            .iter()
-            .skip_while(|update| update.frame_number < actual_frame)
-            .any(|update| {
-                update
-                    .cast_action_updates
-                    .iter()
-                    .any(|net_update| net_update.entity_net_id == action_update.entity_net_id)
-            });
-
-        if is_added {
-            let updated_frame = framed_updates
-                .update_frame(actual_frame)
-                .unwrap_or_else(|| panic!("Expected a frame {}", actual_frame));
-
-            log::trace!(
-                "Added a walk action update for frame {} to frame {}",
-                added_actions_frame_number,
-                updated_frame.frame_number
-            );
-
-            updated_frame.cast_action_updates.push(NetUpdate {
-                entity_net_id: action_update.entity_net_id,
-                data: IdentifiableAction {
-                    action_id: action_update_id_provider.next_update_id(),
-                    action: action_update.data,
+    client_action_messages.0.push((
+        connection_id,
+        ClientMessagePayload::WalkActions(ImmediatePlayerActionsUpdates {
+            frame_number,
+            updates: vec![NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id: 0,
+                    action: PlayerWalkAction::Stop,
                 },
-            });
-        }
-    }
+            }],
+        }),
+    ));
 }
@@ -0,0 +1,198 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System, Write, WriteExpect},
+    network::simulation::TransportResource,
+};
+
+use gv_core::{
+    ecs::{
+        components::{EntityNetMetadata, NetConnectionModel},
+        resources::{
+            net::MultiplayerGameState,
+            world::{
+                RelayedWorldUpdates, ServerWorldUpdate, ServerWorldUpdates, WorldStates,
+                RELAY_BUFFER_SECONDS, SAVED_WORLD_STATES_LIMIT,
+            },
+        },
+        system_data::time::GameTimeService,
+    },
+    net::server_message::ServerMessagePayload,
+};
+use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_reliable};
+
+use crate::ecs::resources::Spectators;
+
+const RELAY_FRAME_INTERVAL: u64 = 5;
+/// Spectators who have just joined get the buffered frames this many times faster
+/// than the game runs.
+const CATCH_UP_SPEED: u64 = 4;
+/// Limits the frames relayed to catching up spectators at once, so several of them joining
+/// together don't cause a bandwidth spike. The rest wait for their turn.
+const MAX_CATCHING_UP_SPECTATORS: u64 = 2;
+const MAX_RELAYED_FRAMES_PER_MESSAGE: usize = 10;
+
+/// Buffers the finalized world updates and relays them to spectators, who start with
+/// a snapshot of the oldest buffered frame and fast-forward to the live game.
+#[derive(Default)]
+pub struct SpectatorRelaySystem {
+    last_recorded_frame: u64,
+    last_relayed_frame: u64,
+}
+
+impl<'s> System<'s> for SpectatorRelaySystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        GameStateHelper<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, ServerWorldUpdates>,
+        ReadExpect<'s, WorldStates>,
+        WriteExpect<'s, RelayedWorldUpdates>,
+        WriteExpect<'s, Spectators>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_state_helper,
+            multiplayer_game_state,
+            server_world_updates,
+            world_states,
+            mut relayed_world_updates,
+            mut spectators,
+            entity_net_metadata,
+            net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.multiplayer_is_running() {
+            return;
+        }
+
+        let game_frame_number = game_time_service.game_frame_number();
+        if game_frame_number < self.last_recorded_frame {
+            // A new game (or a campaign level) has started, spectators need a new snapshot.
+            relayed_world_updates.reset();
+            self.last_relayed_frame = 0;
+            for spectator in &mut spectators.0 {
+                spectator.next_relayed_frame = None;
+            }
+        }
+        self.last_recorded_frame = game_frame_number;
+
+        let capacity = (RELAY_BUFFER_SECONDS * u64::from(game_time_service.tick_rate()))
+            .min(SAVED_WORLD_STATES_LIMIT as u64 / 2);
+        relayed_world_updates.record(&server_world_updates, capacity as usize);
+
+        let relay_interval = game_time_service.scale_frames(RELAY_FRAME_INTERVAL);
+        if spectators.0.is_empty() || game_frame_number - self.last_relayed_frame < relay_interval {
+            return;
+        }
+        self.last_relayed_frame = game_frame_number;
+
+        let (oldest_frame, final_frame) = match (
+            relayed_world_updates.oldest_frame(),
+            relayed_world_updates.final_frame(),
+        ) {
+            (Some(oldest_frame), Some(final_frame)) => (oldest_frame, final_frame),
+            _ => return,
+        };
+
+        let mut catch_up_budget = relay_interval * CATCH_UP_SPEED * MAX_CATCHING_UP_SPECTATORS;
+        for spectator in &mut spectators.0 {
+            let net_connection_model = match (&net_connection_models)
+                .join()
+                .find(|net_connection_model| net_connection_model.id == spectator.connection_id)
+            {
+                Some(net_connection_model) if !net_connection_model.disconnected => {
+                    net_connection_model
+                }
+                _ => continue,
+            };
+
+            let next_relayed_frame = match spectator.next_relayed_frame {
+                Some(next_relayed_frame) if next_relayed_frame > oldest_frame => next_relayed_frame,
+                next_relayed_frame => {
+                    if catch_up_budget == 0 {
+                        // Waits until the catching up spectators are done.
+                        spectator.next_relayed_frame = None;
+                        continue;
+                    }
+                    if next_relayed_frame.is_some() {
+                        log::warn!(
+                            "A spectator ({}) has fallen behind the relay buffer, sending a new snapshot",
+                            spectator.connection_id
+                        );
+                    }
+                    let world_state = match world_states.state(oldest_frame) {
+                        Some(world_state) => world_state,
+                        None => {
+                            log::debug!("No world state to snapshot frame {}", oldest_frame);
+                            continue;
+                        }
+                    };
+                    let snapshot = world_state.snapshot(|entity| {
+                        entity_net_metadata
+                            .get(entity)
+                            .map(|entity_net_metadata| entity_net_metadata.id)
+                    });
+                    log::info!(
+                        "Relaying the game to {} starting with frame {} (connection id: {})",
+                        spectator.nickname,
+                        oldest_frame,
+                        spectator.connection_id
+                    );
+                    send_message_reliable(
+                        &mut transport,
+                        net_connection_model,
+                        ServerMessagePayload::StartSpectating {
+                            entity_net_ids: multiplayer_game_state
+                                .players
+                                .iter()
+                                .map(|player| player.entity_net_id)
+                                .collect(),
+                            snapshot,
+                        },
+                    );
+                    oldest_frame + 1
+                }
+            };
+            spectator.next_relayed_frame = Some(next_relayed_frame);
+
+            let pending_frames = (final_frame + 1).saturating_sub(next_relayed_frame);
+            let relayed_frames = if pending_frames > relay_interval {
+                let relayed_frames = pending_frames
+                    .min(relay_interval * CATCH_UP_SPEED)
+                    .min(catch_up_budget);
+                catch_up_budget -= relayed_frames;
+                relayed_frames
+            } else {
+                pending_frames
+            };
+
+            let updates = relayed_world_updates
+                .final_updates(next_relayed_frame, relayed_frames as usize)
+                .cloned()
+                .collect::<Vec<ServerWorldUpdate>>();
+            let last_relayed_frame = match updates.last() {
+                Some(update) => update.frame_number,
+                None => continue,
+            };
+            spectator.next_relayed_frame = Some(last_relayed_frame + 1);
+
+            let is_catching_up = final_frame - last_relayed_frame > relay_interval;
+            for updates in updates.chunks(MAX_RELAYED_FRAMES_PER_MESSAGE) {
+                send_message_reliable(
+                    &mut transport,
+                    net_connection_model,
+                    ServerMessagePayload::RelayWorld {
+                        updates: updates.to_vec(),
+                        is_catching_up,
+                    },
+                );
+            }
+        }
+    }
+}
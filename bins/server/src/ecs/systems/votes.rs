@@ -0,0 +1,293 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, System, Write, WriteExpect, WriteStorage},
+    network::simulation::TransportResource,
+};
+
+use std::collections::HashMap;
+
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::{
+            net::MultiplayerGameState, GameEngineState, GameLevelState, NewGameEngineState,
+            FRAME_RATE,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::{
+        client_message::ClientMessagePayload,
+        server_message::{DisconnectReason, ServerMessagePayload},
+        NetIdentifier,
+    },
+    votes::{Vote, VoteKind, VOTE_COOLDOWN_SECS, VOTE_DURATION_SECS},
+};
+use gv_game::utils::net::{broadcast_message_reliable, send_message_reliable};
+
+use crate::ecs::resources::VoteMessages;
+
+/// Runs the votes started by players: tallies the ballots, ends a vote once the majority
+/// is reached or the time runs out, and applies the decision.
+#[derive(Default)]
+pub struct VoteSystem {
+    /// The running vote and the engine frame it ends at.
+    vote: Option<(Vote, u64)>,
+    next_vote_id: NetIdentifier,
+    /// Engine frame numbers of the last votes started per connection id.
+    last_vote_frames: HashMap<NetIdentifier, u64>,
+}
+
+impl<'s> System<'s> for VoteSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, GameEngineState>,
+        WriteExpect<'s, NewGameEngineState>,
+        WriteExpect<'s, VoteMessages>,
+        WriteExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, GameLevelState>,
+        WriteStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            game_engine_state,
+            mut new_game_engine_state,
+            mut vote_messages,
+            mut multiplayer_game_state,
+            mut game_level_state,
+            mut net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        // Votes run in the lobby too, where game frames don't advance.
+        let frame_number = game_time_service.engine_time().frame_number();
+        let mut is_updated = false;
+
+        for (connection_id, payload) in vote_messages.0.drain(..) {
+            if multiplayer_game_state
+                .find_player_by_connection_id(connection_id)
+                .is_none()
+            {
+                log::warn!(
+                    "A vote message from a connection without a player (connection id: {})",
+                    connection_id
+                );
+                continue;
+            }
+
+            match payload {
+                ClientMessagePayload::StartVote(kind) => {
+                    if self.vote.is_some() {
+                        log::debug!(
+                            "Ignoring a vote while another one is running (connection id: {})",
+                            connection_id
+                        );
+                        continue;
+                    }
+                    let is_rate_limited = self.last_vote_frames.get(&connection_id).map_or(
+                        false,
+                        |last_vote_frame| {
+                            frame_number - last_vote_frame
+                                < VOTE_COOLDOWN_SECS * u64::from(FRAME_RATE)
+                        },
+                    );
+                    if is_rate_limited {
+                        log::debug!(
+                            "Dropping a rate-limited vote (connection id: {})",
+                            connection_id
+                        );
+                        continue;
+                    }
+                    if let Err(err) = validate_vote(
+                        kind,
+                        connection_id,
+                        *game_engine_state,
+                        &multiplayer_game_state,
+                        &game_level_state,
+                    ) {
+                        log::warn!(
+                            "Rejecting a vote {:?} (connection id: {}): {}",
+                            kind,
+                            connection_id,
+                            err
+                        );
+                        continue;
+                    }
+
+                    log::info!(
+                        "Starting a vote {:?} (connection id: {})",
+                        kind,
+                        connection_id
+                    );
+                    let vote = Vote::new(
+                        self.next_vote_id,
+                        kind,
+                        connection_id,
+                        voters_count(kind, &multiplayer_game_state),
+                    );
+                    self.next_vote_id += 1;
+                    self.last_vote_frames.insert(connection_id, frame_number);
+                    let ends_at = frame_number + VOTE_DURATION_SECS * u64::from(FRAME_RATE);
+                    self.vote = Some((vote, ends_at));
+                    is_updated = true;
+                }
+                ClientMessagePayload::CastVote { vote_id, is_yes } => match self.vote.as_mut() {
+                    Some((vote, _)) if vote.id == vote_id && vote.kind.is_voter(connection_id) => {
+                        vote.cast(connection_id, is_yes);
+                        is_updated = true;
+                    }
+                    _ => {
+                        log::debug!(
+                            "Ignoring a ballot for an outdated vote {} (connection id: {})",
+                            vote_id,
+                            connection_id
+                        );
+                    }
+                },
+                _ => unreachable!(),
+            }
+        }
+
+        let (mut vote, ends_at) = match self.vote.take() {
+            Some(vote) => vote,
+            None => return,
+        };
+
+        let voters = voters_count(vote.kind, &multiplayer_game_state);
+        if voters != vote.voters {
+            let players = &multiplayer_game_state.players;
+            vote.retain_voters(
+                |connection_id| {
+                    players.iter().any(|player| {
+                        player.connection_id == connection_id && !player.is_disconnected
+                    })
+                },
+                voters,
+            );
+            is_updated = true;
+        }
+
+        if is_updated {
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateVote(vote.clone()),
+            );
+        }
+
+        // Players may leave, or the intermission may end, while the vote is running.
+        let is_valid = validate_vote(
+            vote.kind,
+            vote.initiator,
+            *game_engine_state,
+            &multiplayer_game_state,
+            &game_level_state,
+        )
+        .is_ok();
+        let has_passed = match vote.outcome() {
+            Some(has_passed) => has_passed && is_valid,
+            None if !is_valid || frame_number >= ends_at => false,
+            None => {
+                self.vote = Some((vote, ends_at));
+                return;
+            }
+        };
+
+        log::info!(
+            "The vote {:?} has {} (yes: {}, no: {}, voters: {})",
+            vote.kind,
+            if has_passed { "passed" } else { "failed" },
+            vote.yes.len(),
+            vote.no.len(),
+            vote.voters
+        );
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connection_models).join(),
+            ServerMessagePayload::EndVote {
+                vote_id: vote.id,
+                has_passed,
+            },
+        );
+        if !has_passed {
+            return;
+        }
+
+        match vote.kind {
+            VoteKind::Kick(kicked_connection_id) => {
+                multiplayer_game_state.drop_player_by_connection_id(kicked_connection_id);
+                if let Some(net_connection_model) = (&mut net_connection_models)
+                    .join()
+                    .find(|net_connection_model| net_connection_model.id == kicked_connection_id)
+                {
+                    send_message_reliable(
+                        &mut transport,
+                        net_connection_model,
+                        ServerMessagePayload::Disconnect(DisconnectReason::VoteKick),
+                    );
+                    net_connection_model.disconnected = true;
+                }
+            }
+            VoteKind::ReturnToLobby => {
+                multiplayer_game_state.is_playing = false;
+                multiplayer_game_state.waiting_for_players = false;
+                multiplayer_game_state.lagging_players.clear();
+                multiplayer_game_state.campaign_level = None;
+                multiplayer_game_state.resumed_match = None;
+                // Makes MenuState clean up the finished game.
+                game_level_state.is_over = true;
+                new_game_engine_state.0 = GameEngineState::Menu;
+                broadcast_message_reliable(
+                    &mut transport,
+                    (&net_connection_models).join(),
+                    ServerMessagePayload::ReturnToLobby,
+                );
+            }
+            VoteKind::SkipIntermission => {
+                if let Some(intermission) = game_level_state.intermission.as_mut() {
+                    // IntermissionSystem ends it and lets the clients know.
+                    intermission.ends_at_frame = game_time_service.game_frame_number();
+                }
+            }
+        }
+    }
+}
+
+fn validate_vote(
+    kind: VoteKind,
+    initiator: NetIdentifier,
+    game_engine_state: GameEngineState,
+    multiplayer_game_state: &MultiplayerGameState,
+    game_level_state: &GameLevelState,
+) -> Result<(), &'static str> {
+    let is_playing =
+        multiplayer_game_state.is_playing && game_engine_state == GameEngineState::Playing;
+    match kind {
+        VoteKind::Kick(kicked_connection_id) => {
+            if kicked_connection_id == initiator {
+                return Err("players can't vote to kick themselves");
+            }
+            match multiplayer_game_state.find_player_by_connection_id(kicked_connection_id) {
+                Some(player) if player.is_host => Err("the host can't be kicked"),
+                Some(_) => Ok(()),
+                None => Err("there's no such player"),
+            }
+        }
+        VoteKind::ReturnToLobby if !is_playing => Err("the game isn't started"),
+        VoteKind::SkipIntermission if !is_playing || game_level_state.intermission.is_none() => {
+            Err("there's no intermission to skip")
+        }
+        VoteKind::ReturnToLobby | VoteKind::SkipIntermission => Ok(()),
+    }
+}
+
+fn voters_count(kind: VoteKind, multiplayer_game_state: &MultiplayerGameState) -> usize {
+    multiplayer_game_state
+        .players
+        .iter()
+        // Players who have lost the connection can't vote until they rejoin.
+        .filter(|player| !player.is_disconnected && kind.is_voter(player.connection_id))
+        .count()
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::type_complexity)]
+
+pub mod ecs;
+pub mod rendezvous;
+pub mod review;
+pub mod rooms;
+
+use amethyst::{
+    core::transform::TransformBundle,
+    error::Error,
+    prelude::{GameDataBuilder, SystemDesc, World},
+};
+
+use gv_core::ecs::resources::world::{
+    DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, RelayedWorldUpdates,
+    ServerWorldUpdates,
+};
+use gv_game::{
+    build_game_logic_systems,
+    ecs::systems::{
+        FrameTimingsSystem, NetConnectionManagerDesc, RendezvousDesc, RendezvousRelaySystem,
+        WorldPositionTransformSystem,
+    },
+    TimedSystemsBuilder,
+};
+
+use crate::ecs::{
+    resources::{
+        AfkConfig, AntiCheatRecords, ClientActionMessages, HostClientAddress, LastBroadcastedFrame,
+        RejoinConfig, Spectators, VoteMessages,
+    },
+    systems::*,
+};
+
+/// Registers server resources and systems. Expects a network bundle (or anything else
+/// providing `TransportResource` and `NetworkSimulationEvent` channel) to be added separately.
+pub fn build_server_systems<'a, 'b>(
+    game_data_builder: GameDataBuilder<'a, 'b>,
+    world: &mut World,
+    host_client_address: HostClientAddress,
+) -> Result<GameDataBuilder<'a, 'b>, Error> {
+    world.insert(FramedUpdates::<DummyFramedUpdate>::default());
+    world.insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
+    world.insert(host_client_address);
+    world.insert(ServerWorldUpdates::default());
+    world.insert(LastBroadcastedFrame(0));
+    world.insert(ClientActionMessages::default());
+    world.insert(VoteMessages::default());
+    world.insert(RelayedWorldUpdates::default());
+    world.insert(Spectators::default());
+    world.insert(AntiCheatRecords::default());
+    if !world.has_value::<AfkConfig>() {
+        world.insert(AfkConfig::default());
+    }
+    if !world.has_value::<RejoinConfig>() {
+        world.insert(RejoinConfig::default());
+    }
+
+    let game_data_builder = game_data_builder
+        .with_timed(
+            RendezvousDesc::default().build(world),
+            "rendezvous_system",
+            &[],
+        )
+        .with_timed(
+            NetConnectionManagerDesc::default().build(world),
+            "net_connection_manager_system",
+            &[],
+        )
+        .with_timed(
+            ServerNetworkSystem::new(),
+            "game_network_system",
+            &["net_connection_manager_system"],
+        )
+        .with_timed(
+            AfkDetectionSystem::default(),
+            "afk_detection_system",
+            &["game_network_system"],
+        )
+        .with_timed(
+            VoteSystem::default(),
+            "vote_system",
+            &["game_network_system"],
+        )
+        .with_timed(
+            ClientActionsSystem,
+            "client_actions_system",
+            // Client actions are drained here, so they have to be seen as activity first.
+            &["game_network_system", "afk_detection_system"],
+        );
+    let game_data_builder = build_game_logic_systems(game_data_builder, world, true)?
+        .with_timed(
+            WorldPositionTransformSystem,
+            "world_position_transform_system",
+            &["action_system"],
+        )
+        .with_timed(
+            SpectatorRelaySystem::default(),
+            "spectator_relay_system",
+            &["action_system"],
+        )
+        .with_timed(
+            GameUpdatesBroadcastingSystem::default(),
+            "game_updates_broadcasting_system",
+            // Acknowledged updates get dropped here, so they have to be recorded for relaying first.
+            &["action_system", "spectator_relay_system"],
+        )
+        .with_timed(
+            MatchSavingSystem::default(),
+            "match_saving_system",
+            &["action_system"],
+        )
+        .with_timed(
+            ReconciliationReportSystem::default(),
+            "reconciliation_report_system",
+            &["action_system"],
+        )
+        .with_timed(
+            AntiCheatSystem::default(),
+            "anti_cheat_system",
+            &["action_system"],
+        )
+        .with_timed(
+            BalanceReloadSystem::default(),
+            "balance_reload_system",
+            &["action_system"],
+        )
+        .with_timed(
+            CampaignSystem::default(),
+            "campaign_system",
+            &["game_updates_broadcasting_system"],
+        )
+        .with_timed(
+            RendezvousRelaySystem,
+            "rendezvous_relay_system",
+            &[
+                "net_connection_manager_system",
+                "game_network_system",
+                "game_updates_broadcasting_system",
+            ],
+        )
+        .with_bundle(TransformBundle::new().with_dep(&["world_position_transform_system"]))?
+        .with_barrier()
+        .with(FrameTimingsSystem, "frame_timings_system", &[]);
+    Ok(game_data_builder)
+}
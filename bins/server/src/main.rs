@@ -1,29 +1,39 @@
-#![allow(clippy::type_complexity)]
-
-mod ecs;
-
 use amethyst::{
-    core::{frame_limiter::FrameRateLimitStrategy, transform::TransformBundle},
+    core::frame_limiter::FrameRateLimitStrategy,
     network::simulation::laminar::{LaminarConfig, LaminarNetworkBundle, LaminarSocket},
-    prelude::{Application, GameDataBuilder, SystemDesc},
+    prelude::{Application, GameDataBuilder},
     Logger, LoggerConfig,
 };
 
-use gv_core::ecs::resources::world::{
-    DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
-};
-use gv_game::{
-    build_game_logic_systems,
-    ecs::systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
-    states::LoadingState,
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
 };
 
-use crate::ecs::{
-    resources::{HostClientAddress, LastBroadcastedFrame},
-    systems::*,
+use gv_core::{
+    anti_cheat::SuspiciousSession,
+    balance::Balance,
+    content_packs::ContentPacks,
+    crash_report,
+    ecs::resources::{is_supported_tick_rate, net::Rendezvous, GameTime, FRAME_RATE},
+    gv_dirs::{resolve_path, GvDirs, SERVER_LOGGING_CONFIG_FILE},
+    net::SERVER_READY_PREFIX,
+    profiling::{FrameTimings, SpikeTraceOutput},
+};
+use gv_game::states::LoadingState;
+use gv_server::{
+    build_server_systems,
+    ecs::resources::{BalanceWatchFile, HostClientAddress, MatchSaveFile, SuspiciousSessionsDir},
+    rendezvous::RendezvousServer,
+    review::review_report,
+    rooms::{RoomServer, RoomsConfig},
 };
 
 fn main() -> amethyst::Result<()> {
+    crash_report::install_panic_hook("gv_server");
+
     let cli_matches = clap::App::new("grumpy_visitors")
         .version("0.1")
         .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
@@ -45,8 +55,123 @@ fn main() -> amethyst::Result<()> {
                 .help("Specifies the address of the client hosting the game")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("rooms")
+                .short("r")
+                .long("rooms")
+                .value_name("MAX_ROOMS")
+                .help("Hosts up to MAX_ROOMS rooms, which clients can create, list and enter")
+                .conflicts_with("host-client-addr")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("afk-kick")
+                .long("afk-kick")
+                .value_name("SECONDS")
+                .help("Kicks players who haven't sent any input for SECONDS from the rooms")
+                .requires("rooms")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("rendezvous")
+                .long("rendezvous")
+                .value_name("RENDEZVOUS_ADDR")
+                .help(
+                    "Registers the server on a rendezvous server, to let clients behind NATs join",
+                )
+                .requires("host-code")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("host-code")
+                .long("host-code")
+                .value_name("CODE")
+                .help("Specifies the code clients use to join via the rendezvous server")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("serve-rendezvous")
+                .long("serve-rendezvous")
+                .help("Runs a rendezvous server instead of a game server")
+                .conflicts_with_all(&["host-client-addr", "rooms", "rendezvous"]),
+        )
+        .arg(
+            clap::Arg::with_name("save")
+                .long("save")
+                .value_name("FILE")
+                .help("Saves the match progress to a file, which the host can resume from")
+                .conflicts_with_all(&["rooms", "serve-rendezvous"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("balance")
+                .long("balance")
+                .value_name("FILE")
+                .help("Loads the game balance from a file instead of resources/balance.ron")
+                .conflicts_with_all(&["rooms", "serve-rendezvous"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("mods")
+                .long("mods")
+                .value_name("DIR")
+                .help("Loads content packs from the subdirectories of DIR")
+                .conflicts_with_all(&["rooms", "serve-rendezvous"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("watch-balance")
+                .long("watch-balance")
+                .help("Reloads the balance file whenever it changes and sends it to the clients")
+                .requires("balance"),
+        )
+        .arg(
+            clap::Arg::with_name("tick-rate")
+                .long("tick-rate")
+                .value_name("TICKS_PER_SECOND")
+                .help("Runs the simulation at a lower rate (30, 20, ...) on weak hosts")
+                .default_value("60")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("managed")
+                .long("managed")
+                .help("Reports readiness on stdout and exits along with the client that started it")
+                .requires("host-client-addr"),
+        )
+        .arg(
+            clap::Arg::with_name("frame-trace")
+                .long("frame-trace")
+                .value_name("FILE")
+                .help("Writes a Chrome tracing file with the recent frames when a tick runs late")
+                .conflicts_with_all(&["rooms", "serve-rendezvous"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("suspicious-sessions")
+                .long("suspicious-sessions")
+                .value_name("DIR")
+                .help("Saves the sessions of players who keep breaking the rules to DIR")
+                .conflicts_with_all(&["rooms", "serve-rendezvous"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("review")
+                .long("review")
+                .value_name("FILE")
+                .help("Prints the flagged frames of a saved suspicious session and exits")
+                .takes_value(true),
+        )
         .get_matches();
 
+    if let Some(review_path) = cli_matches.value_of("review") {
+        let suspicious_session = SuspiciousSession::load(review_path).map_err(|err| {
+            amethyst::Error::from_string(format!("Failed to read {}: {:?}", review_path, err))
+        })?;
+        print!("{}", review_report(&suspicious_session));
+        return Ok(());
+    }
+
     let socket_addr = cli_matches
         .value_of("addr")
         .expect("Expected a default value if not passed via CLI");
@@ -57,74 +182,165 @@ fn main() -> amethyst::Result<()> {
         HostClientAddress(None)
     };
 
-    let logging_config: LoggerConfig = ::std::fs::read_to_string("server_logging_config.toml")
+    let tick_rate: u32 = cli_matches
+        .value_of("tick-rate")
+        .expect("Expected a default value if not passed via CLI")
+        .parse()?;
+    if !is_supported_tick_rate(tick_rate) {
+        return Err(amethyst::Error::from_string(format!(
+            "Unsupported tick rate {}, expected a divisor of {}",
+            tick_rate, FRAME_RATE
+        )));
+    }
+
+    let dirs = GvDirs::new();
+    let logging_config_path = dirs.logging_config_path(SERVER_LOGGING_CONFIG_FILE);
+    let mut logging_config: LoggerConfig = ::std::fs::read_to_string(&logging_config_path)
         .map_err(|err| {
             log::warn!(
-                "Failed to read server_logging_config.toml, using the defaults: {:?}",
+                "Failed to read {}, using the defaults: {:?}",
+                logging_config_path.display(),
                 err
             )
         })
         .and_then(|config_contents| {
             toml::from_str(&config_contents).map_err(|err| {
                 log::warn!(
-                    "Failed to read server_logging_config.toml, using the defaults: {:?}",
+                    "Failed to read {}, using the defaults: {:?}",
+                    logging_config_path.display(),
                     err
                 )
             })
         })
         .unwrap_or_default();
+    logging_config.log_file = logging_config
+        .log_file
+        .map(|log_file| resolve_path(&dirs.logs_dir(), log_file));
     Logger::from_config_formatter(logging_config, |out, message, record| {
-        out.finish(format_args!(
+        let line = format!(
             "[{level}][SERVER][{target}] {message}",
             level = record.level(),
             target = record.target(),
             message = message,
-        ))
+        );
+        out.finish(format_args!("{}", line));
+        crash_report::record_log_line(line);
     })
     .start();
 
-    let mut builder = Application::build("./", LoadingState::default())?;
-    builder
-        .world
-        .insert(FramedUpdates::<DummyFramedUpdate>::default());
-    builder
-        .world
-        .insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
-    builder.world.insert(client_addr);
-    builder.world.insert(ServerWorldUpdates::default());
-    builder.world.insert(LastBroadcastedFrame(0));
-
     let laminar_config = LaminarConfig {
         receive_buffer_max_size: 14_500,
         ..LaminarConfig::default()
     };
 
     let socket = LaminarSocket::bind_with_config(socket_addr, laminar_config)?;
+    let local_addr = socket.local_addr()?;
 
-    let mut game_data_builder = GameDataBuilder::default()
-        .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
-        .with(
-            NetConnectionManagerDesc::default().build(&mut builder.world),
-            "net_connection_manager_system",
-            &[],
-        )
-        .with(ServerNetworkSystem::new(), "game_network_system", &[]);
-    game_data_builder = build_game_logic_systems(game_data_builder, &mut builder.world, true)?
-        .with(
-            WorldPositionTransformSystem,
-            "world_position_transform_system",
-            &["action_system"],
-        )
-        .with(
-            GameUpdatesBroadcastingSystem::default(),
-            "game_updates_broadcasting_system",
-            &["action_system"],
-        )
-        .with_bundle(TransformBundle::new().with_dep(&["world_position_transform_system"]))?;
+    if cli_matches.is_present("serve-rendezvous") {
+        log::info!("Starting a rendezvous server");
+        RendezvousServer::new(socket)?.run();
+        return Ok(());
+    }
+
+    if let Some(max_rooms) = cli_matches.value_of("rooms") {
+        let afk_kick_timeout = match cli_matches.value_of("afk-kick") {
+            Some(seconds) => Some(Duration::from_secs(seconds.parse()?)),
+            None => None,
+        };
+        let config = RoomsConfig {
+            max_rooms: max_rooms.parse()?,
+            tick_rate,
+            afk_kick_timeout,
+            ..RoomsConfig::default()
+        };
+        log::info!("Starting a server for up to {} rooms", config.max_rooms);
+        RoomServer::new(socket, config)?.run();
+        return Ok(());
+    }
+
+    let mut builder = Application::build("./", LoadingState::default())?;
+    // The server doesn't render, so it runs exactly one frame per tick.
+    builder
+        .world
+        .insert(GameTime::with_tick_rate(tick_rate, tick_rate));
+    if let Some(rendezvous_addr) = cli_matches.value_of("rendezvous") {
+        let host_code = cli_matches
+            .value_of("host-code")
+            .expect("Expected a host code along with a rendezvous address")
+            .parse()?;
+        builder
+            .world
+            .insert(Rendezvous::host(rendezvous_addr.parse()?, host_code));
+    }
+
+    if let Some(save_path) = cli_matches.value_of("save") {
+        builder
+            .world
+            .insert(MatchSaveFile(Some(PathBuf::from(save_path))));
+    }
+
+    if let Some(suspicious_sessions_dir) = cli_matches.value_of("suspicious-sessions") {
+        let suspicious_sessions_dir = PathBuf::from(suspicious_sessions_dir);
+        fs::create_dir_all(&suspicious_sessions_dir)?;
+        builder
+            .world
+            .insert(SuspiciousSessionsDir(Some(suspicious_sessions_dir)));
+    }
+
+    let content_packs = match cli_matches.value_of("mods") {
+        Some(mods_dir) => {
+            ContentPacks::load(Path::new(mods_dir)).map_err(amethyst::Error::from_string)?
+        }
+        None => ContentPacks::default(),
+    };
+    for content_pack in content_packs.packs() {
+        log::info!("Loaded the content pack {}", content_pack.name);
+    }
+    let balance = match cli_matches.value_of("balance") {
+        Some(balance_path) => {
+            let balance_path = PathBuf::from(balance_path);
+            let balance = Balance::load(&balance_path).map_err(amethyst::Error::from_string)?;
+            if cli_matches.is_present("watch-balance") {
+                builder.world.insert(BalanceWatchFile(Some(balance_path)));
+            }
+            balance
+        }
+        None => Balance::default(),
+    };
+    builder.world.insert(
+        content_packs
+            .apply_to_balance(&balance)
+            .map_err(amethyst::Error::from_string)?,
+    );
+    builder.world.insert(content_packs);
+
+    if let Some(frame_trace_path) = cli_matches.value_of("frame-trace") {
+        builder
+            .world
+            .insert(FrameTimings::with_spike_trace_output(SpikeTraceOutput {
+                path: PathBuf::from(frame_trace_path),
+                threshold: Duration::from_secs_f64(1.0 / f64::from(tick_rate)),
+            }));
+    }
+
+    let game_data_builder =
+        GameDataBuilder::default().with_bundle(LaminarNetworkBundle::new(Some(socket)))?;
+    let game_data_builder =
+        build_server_systems(game_data_builder, &mut builder.world, client_addr)?;
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        .with_frame_limit(FrameRateLimitStrategy::Yield, tick_rate)
         .build(game_data_builder)?;
+    if cli_matches.is_present("managed") {
+        // The hosting client holds the other end of stdin, so the pipe closes when it exits,
+        // even if it crashes.
+        thread::spawn(|| {
+            let _ = io::copy(&mut io::stdin(), &mut io::sink());
+            log::info!("The hosting client has exited. Shutting down the server...");
+            std::process::exit(0);
+        });
+        println!("{}{}", SERVER_READY_PREFIX, local_addr);
+    }
     game.run();
     Ok(())
 }
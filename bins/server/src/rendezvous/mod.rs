@@ -0,0 +1,186 @@
+mod server;
+
+pub use self::server::RendezvousServer;
+
+use amethyst::network::simulation::{
+    DeliveryRequirement, Message, NetworkSimulationEvent, TransportResource, UrgencyRequirement,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::Duration,
+};
+
+use gv_core::net::rendezvous::{
+    decode_rendezvous_packet, encode_rendezvous_packet, HostCode, RendezvousPacket,
+};
+
+/// Hosts which haven't sent anything for this long are forgotten.
+pub const HOST_EXPIRATION: Duration = Duration::from_secs(30);
+
+struct RegisteredHost {
+    addr: SocketAddr,
+    last_seen_at: Duration,
+}
+
+/// Introduces clients to hosts registered with a code, so that they can punch holes
+/// in their NATs. Relays messages between the introduced peers if punching fails.
+pub struct RendezvousCoordinator {
+    hosts: HashMap<HostCode, RegisteredHost>,
+    /// Only the peers which have been introduced to each other can relay messages.
+    introduced_peers: HashSet<(SocketAddr, SocketAddr)>,
+    elapsed: Duration,
+    transport: TransportResource,
+}
+
+impl RendezvousCoordinator {
+    pub fn new() -> Self {
+        Self {
+            hosts: HashMap::new(),
+            introduced_peers: HashSet::new(),
+            elapsed: Duration::new(0, 0),
+            transport: TransportResource::default(),
+        }
+    }
+
+    pub fn hosts_count(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn host_addr(&self, host_code: HostCode) -> Option<SocketAddr> {
+        self.hosts.get(&host_code).map(|host| host.addr)
+    }
+
+    pub fn process_event(&mut self, event: &NetworkSimulationEvent) {
+        match event {
+            NetworkSimulationEvent::Message(addr, bytes) => {
+                match decode_rendezvous_packet(bytes.as_ref()) {
+                    Some(Ok(packet)) => self.process_packet(*addr, packet),
+                    Some(Err(err)) => {
+                        log::warn!("Failed to decode a packet from {}: {:?}", addr, err);
+                    }
+                    None => {
+                        log::warn!("Ignoring a non-rendezvous message from {}", addr);
+                    }
+                }
+            }
+            NetworkSimulationEvent::Connect(addr) => {
+                log::debug!("Detected a new UDP connection: {}", addr);
+            }
+            NetworkSimulationEvent::Disconnect(addr) => {
+                log::debug!("Lost the UDP connection: {}", addr);
+            }
+            NetworkSimulationEvent::SendError(err, _) => {
+                log::error!("(SendError) {:?}", err);
+            }
+            NetworkSimulationEvent::RecvError(err) => {
+                log::error!("(RecvError) {:?}", err);
+            }
+            NetworkSimulationEvent::ConnectionError(err, _) => {
+                log::error!("(ConnectionError) {:?}", err);
+            }
+        }
+    }
+
+    /// Forgets the hosts which have stopped sending keep-alive packets.
+    pub fn update(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+        let now = self.elapsed;
+        let introduced_peers = &mut self.introduced_peers;
+        self.hosts.retain(|host_code, host| {
+            if now - host.last_seen_at < HOST_EXPIRATION {
+                return true;
+            }
+            log::info!("The host {} ({}) has expired", host_code, host.addr);
+            let host_addr = host.addr;
+            introduced_peers.retain(|(a, b)| *a != host_addr && *b != host_addr);
+            false
+        });
+    }
+
+    pub fn drain_outcoming_messages(&mut self) -> Vec<Message> {
+        self.transport.drain_messages(|_| true)
+    }
+
+    fn process_packet(&mut self, addr: SocketAddr, packet: RendezvousPacket) {
+        match packet {
+            RendezvousPacket::RegisterHost { host_code } => {
+                let now = self.elapsed;
+                match self.hosts.get_mut(&host_code) {
+                    Some(host) if host.addr != addr => {
+                        log::warn!("{} tried to register a taken code {}", addr, host_code);
+                        self.send(addr, &RendezvousPacket::HostCodeTaken { host_code });
+                        return;
+                    }
+                    Some(host) => host.last_seen_at = now,
+                    None => {
+                        log::info!("Registered the host {} ({})", host_code, addr);
+                        self.hosts.insert(
+                            host_code,
+                            RegisteredHost {
+                                addr,
+                                last_seen_at: now,
+                            },
+                        );
+                    }
+                }
+                self.send(addr, &RendezvousPacket::HostRegistered { host_code });
+            }
+            RendezvousPacket::KeepAlive => {
+                let now = self.elapsed;
+                for host in self.hosts.values_mut().filter(|host| host.addr == addr) {
+                    host.last_seen_at = now;
+                }
+            }
+            RendezvousPacket::Connect { host_code } => {
+                let host_addr = match self.host_addr(host_code) {
+                    Some(host_addr) => host_addr,
+                    None => {
+                        self.send(addr, &RendezvousPacket::HostNotFound { host_code });
+                        return;
+                    }
+                };
+                log::info!("Introducing {} to the host {}", addr, host_addr);
+                self.introduced_peers.insert((addr, host_addr));
+                self.introduced_peers.insert((host_addr, addr));
+                self.send(addr, &RendezvousPacket::PunchTo(host_addr));
+                self.send(host_addr, &RendezvousPacket::PunchTo(addr));
+            }
+            RendezvousPacket::Relay { to, payload } => {
+                if !self.introduced_peers.contains(&(addr, to)) {
+                    log::warn!("{} tried to relay a message to a stranger {}", addr, to);
+                    return;
+                }
+                // The original delivery requirements are lost, so relaying is always reliable.
+                self.transport.send_with_requirements(
+                    to,
+                    &encode_rendezvous_packet(&RendezvousPacket::Relayed {
+                        from: addr,
+                        payload,
+                    }),
+                    DeliveryRequirement::Reliable,
+                    UrgencyRequirement::Immediate,
+                );
+            }
+            packet => {
+                log::warn!("Unexpected packet from {}: {:?}", addr, packet);
+            }
+        }
+    }
+
+    fn send(&mut self, addr: SocketAddr, packet: &RendezvousPacket) {
+        self.transport.send_with_requirements(
+            addr,
+            &encode_rendezvous_packet(packet),
+            DeliveryRequirement::Reliable,
+            UrgencyRequirement::Immediate,
+        );
+    }
+}
+
+impl Default for RendezvousCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
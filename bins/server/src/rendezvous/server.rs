@@ -0,0 +1,94 @@
+use amethyst::{
+    core::Time,
+    ecs::{ReaderId, World, WorldExt},
+    error::Error,
+    network::simulation::{
+        laminar::{LaminarNetworkBundle, LaminarSocket},
+        NetworkSimulationEvent, TransportResource,
+    },
+    prelude::{DataInit, GameData, GameDataBuilder},
+    shrev::EventChannel,
+};
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::RendezvousCoordinator;
+
+const TICK_DURATION: Duration = Duration::from_millis(10);
+
+/// Runs a `RendezvousCoordinator` on a laminar socket.
+pub struct RendezvousServer {
+    world: World,
+    network_data: GameData<'static, 'static>,
+    reader: ReaderId<NetworkSimulationEvent>,
+    coordinator: RendezvousCoordinator,
+}
+
+impl RendezvousServer {
+    pub fn new(socket: LaminarSocket) -> Result<Self, Error> {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.insert(TransportResource::default());
+        world.insert(EventChannel::<NetworkSimulationEvent>::default());
+
+        let network_data = GameDataBuilder::default()
+            .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+            .build(&mut world);
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+
+        Ok(Self {
+            world,
+            network_data,
+            reader,
+            coordinator: RendezvousCoordinator::new(),
+        })
+    }
+
+    pub fn run(&mut self) {
+        let mut last_frame_at = Instant::now();
+        loop {
+            let frame_started_at = Instant::now();
+            let elapsed = frame_started_at - last_frame_at;
+            last_frame_at = frame_started_at;
+            self.update(elapsed);
+
+            if let Some(sleep_duration) = TICK_DURATION.checked_sub(frame_started_at.elapsed()) {
+                thread::sleep(sleep_duration);
+            }
+        }
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.increment_frame_number();
+            time.set_delta_time(elapsed);
+        }
+        self.network_data.update(&self.world);
+
+        {
+            let net_events = self
+                .world
+                .read_resource::<EventChannel<NetworkSimulationEvent>>();
+            for net_event in net_events.read(&mut self.reader) {
+                self.coordinator.process_event(net_event);
+            }
+        }
+        self.coordinator.update(elapsed);
+
+        let mut transport = self.world.write_resource::<TransportResource>();
+        for message in self.coordinator.drain_outcoming_messages() {
+            transport.send_with_requirements(
+                message.destination,
+                &message.payload,
+                message.delivery,
+                message.urgency,
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! `gv_server --review FILE`, which replays a saved `SuspiciousSession` for a reviewer.
+
+use std::fmt::Write;
+
+use gv_core::{
+    anti_cheat::SuspiciousSession,
+    net::session_recording::{RecordedMessage, SessionRecordEntry},
+};
+
+/// Lists the flagged frames of a session along with the messages the player has sent
+/// on each of them.
+pub fn review_report(suspicious_session: &SuspiciousSession) -> String {
+    let flagged_frames = suspicious_session.review();
+    let mut report = format!(
+        "{} (entity net id {}): {} flagged frames, {} recorded messages\n",
+        suspicious_session.nickname,
+        suspicious_session.entity_net_id,
+        flagged_frames.len(),
+        suspicious_session.entries.len()
+    );
+
+    let mut last_frame_number = None;
+    for flagged_frame in &flagged_frames {
+        writeln!(
+            report,
+            "frame {}: {:?}",
+            flagged_frame.frame_number, flagged_frame.violation
+        )
+        .expect("Expected to write into a String");
+        // A frame can be flagged several times, its messages are listed once.
+        if last_frame_number == Some(flagged_frame.frame_number) {
+            continue;
+        }
+        last_frame_number = Some(flagged_frame.frame_number);
+        for entry in suspicious_session
+            .entries
+            .iter()
+            .filter(|entry| entry.game_frame_number == flagged_frame.frame_number)
+        {
+            if let SessionRecordEntry {
+                message: RecordedMessage::Outcoming(payload),
+                ..
+            } = entry
+            {
+                writeln!(report, "    {:?}", payload).expect("Expected to write into a String");
+            }
+        }
+    }
+    report
+}
@@ -0,0 +1,138 @@
+use std::{net::SocketAddr, time::Duration};
+
+use gv_core::net::NetIdentifier;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MatchmakingConfig {
+    pub players_per_match: usize,
+    /// The maximum rating difference inside a match for players who have just been queued.
+    pub rating_spread: u32,
+    /// The allowed difference grows while players are waiting, so that nobody waits forever.
+    pub rating_spread_per_second: u32,
+    pub timeout: Duration,
+}
+
+impl Default for MatchmakingConfig {
+    fn default() -> Self {
+        Self {
+            players_per_match: 2,
+            rating_spread: 100,
+            rating_spread_per_second: 50,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedPlayer {
+    pub addr: SocketAddr,
+    pub session_id: NetIdentifier,
+    pub rating: u32,
+    pub waited: Duration,
+}
+
+pub struct MatchmakingQueue {
+    config: MatchmakingConfig,
+    players: Vec<QueuedPlayer>,
+}
+
+impl MatchmakingQueue {
+    pub fn new(config: MatchmakingConfig) -> Self {
+        Self {
+            config,
+            players: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn contains(&self, addr: SocketAddr) -> bool {
+        self.players.iter().any(|player| player.addr == addr)
+    }
+
+    /// Re-sending FindMatch updates the rating but doesn't reset the waiting time.
+    pub fn enqueue(&mut self, addr: SocketAddr, session_id: NetIdentifier, rating: u32) {
+        if let Some(player) = self.players.iter_mut().find(|player| player.addr == addr) {
+            player.session_id = session_id;
+            player.rating = rating;
+        } else {
+            self.players.push(QueuedPlayer {
+                addr,
+                session_id,
+                rating,
+                waited: Duration::new(0, 0),
+            });
+        }
+    }
+
+    pub fn cancel(&mut self, addr: SocketAddr) -> Option<QueuedPlayer> {
+        let i = self.players.iter().position(|player| player.addr == addr)?;
+        Some(self.players.remove(i))
+    }
+
+    /// Returns the players who have waited for longer than the timeout.
+    pub fn advance(&mut self, elapsed: Duration) -> Vec<QueuedPlayer> {
+        let timeout = self.config.timeout;
+        let mut timed_out = Vec::new();
+        self.players.retain(|player| {
+            if player.waited + elapsed >= timeout {
+                timed_out.push(*player);
+                false
+            } else {
+                true
+            }
+        });
+        for player in &mut self.players {
+            player.waited += elapsed;
+        }
+        timed_out
+    }
+
+    /// Picks the group of players with the closest ratings, if their spread is acceptable.
+    /// The group is sorted by waiting time, so the first player is the one who has waited the most.
+    pub fn find_match(&mut self) -> Option<Vec<QueuedPlayer>> {
+        let players_per_match = self.config.players_per_match.max(1);
+        if self.players.len() < players_per_match {
+            return None;
+        }
+
+        let mut by_rating: Vec<usize> = (0..self.players.len()).collect();
+        by_rating.sort_by_key(|i| self.players[*i].rating);
+
+        let (spread, group) = by_rating
+            .windows(players_per_match)
+            .filter_map(|group| {
+                let spread =
+                    self.players[*group.last().unwrap()].rating - self.players[group[0]].rating;
+                let waited = group.iter().map(|i| self.players[*i].waited).max().unwrap();
+                if spread <= self.allowed_spread(waited) {
+                    Some((spread, group))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|(spread, _)| *spread)?;
+        log::debug!("Found a match with the rating spread of {}", spread);
+
+        let mut group = group.to_vec();
+        group.sort_by(|a, b| b.cmp(a));
+        let mut matched_players: Vec<QueuedPlayer> =
+            group.into_iter().map(|i| self.players.remove(i)).collect();
+        // Restoring the queue order, so that the earliest queued player wins ties.
+        matched_players.reverse();
+        matched_players.sort_by(|a, b| b.waited.cmp(&a.waited));
+        Some(matched_players)
+    }
+
+    fn allowed_spread(&self, waited: Duration) -> u32 {
+        self.config.rating_spread.saturating_add(
+            (self.config.rating_spread_per_second as f32 * waited.as_secs_f32()) as u32,
+        )
+    }
+}
@@ -0,0 +1,410 @@
+mod matchmaking;
+mod room;
+mod server;
+
+pub use self::{
+    matchmaking::{MatchmakingConfig, MatchmakingQueue, QueuedPlayer},
+    room::Room,
+    server::RoomServer,
+};
+
+use amethyst::network::simulation::{
+    DeliveryRequirement, Message, NetworkSimulationEvent, TransportResource, UrgencyRequirement,
+};
+
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use gv_core::{
+    ecs::resources::DEFAULT_TICK_RATE,
+    net::{
+        client_message::{ClientMessage, ClientMessagePayload},
+        decode_client_message,
+        server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
+        NetIdentifier, RoomId,
+    },
+};
+
+/// If a room lags behind for more ticks than this, the rest of the backlog is skipped.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RoomsConfig {
+    pub max_rooms: usize,
+    pub tick_rate: u32,
+    pub matchmaking: MatchmakingConfig,
+    /// Players who haven't sent any input for this long get kicked, see `AfkConfig`.
+    pub afk_kick_timeout: Option<Duration>,
+}
+
+impl Default for RoomsConfig {
+    fn default() -> Self {
+        Self {
+            max_rooms: 8,
+            tick_rate: DEFAULT_TICK_RATE,
+            matchmaking: MatchmakingConfig::default(),
+            afk_kick_timeout: None,
+        }
+    }
+}
+
+struct ScheduledRoom {
+    room: Room,
+    accumulated_time: Duration,
+}
+
+/// Hosts several rooms behind one socket. Peers talk to the router itself until they create
+/// or enter a room, after that all their messages are forwarded to the room's world.
+pub struct RoomRouter {
+    config: RoomsConfig,
+    room_id_autoinc: RoomId,
+    rooms: Vec<ScheduledRoom>,
+    peers: HashMap<SocketAddr, RoomId>,
+    first_room_to_step: usize,
+    matchmaking_queue: MatchmakingQueue,
+    transport: TransportResource,
+    outcoming_messages: Vec<Message>,
+}
+
+impl RoomRouter {
+    pub fn new(config: RoomsConfig) -> Self {
+        Self {
+            config,
+            room_id_autoinc: 0,
+            rooms: Vec::new(),
+            peers: HashMap::new(),
+            first_room_to_step: 0,
+            matchmaking_queue: MatchmakingQueue::new(config.matchmaking),
+            transport: TransportResource::default(),
+            outcoming_messages: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> RoomsConfig {
+        self.config
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs(1) / self.config.tick_rate
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = &Room> {
+        self.rooms.iter().map(|scheduled_room| &scheduled_room.room)
+    }
+
+    pub fn room(&self, room_id: RoomId) -> Option<&Room> {
+        self.rooms().find(|room| room.id() == room_id)
+    }
+
+    pub fn peer_room_id(&self, addr: SocketAddr) -> Option<RoomId> {
+        self.peers.get(&addr).cloned()
+    }
+
+    /// Peers that have created or entered a room and haven't left it yet.
+    pub fn peers_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn matchmaking_queue(&self) -> &MatchmakingQueue {
+        &self.matchmaking_queue
+    }
+
+    pub fn process_event(&mut self, event: &NetworkSimulationEvent) {
+        match event {
+            NetworkSimulationEvent::Message(addr, bytes) => {
+                if let Some(room) = self.peer_room_mut(*addr) {
+                    room.push_event(NetworkSimulationEvent::Message(*addr, bytes.clone()));
+                    // The room drops a peer on Disconnect or on a malformed message,
+                    // so does the router, to let the peer enter another room.
+                    let is_leaving = match decode_client_message(bytes.as_ref()) {
+                        Ok(ClientMessage {
+                            payload: ClientMessagePayload::Disconnect,
+                            ..
+                        })
+                        | Err(_) => true,
+                        Ok(_) => false,
+                    };
+                    if is_leaving {
+                        self.peers.remove(addr);
+                    }
+                } else {
+                    self.process_lobby_message(*addr, bytes.as_ref());
+                }
+            }
+            NetworkSimulationEvent::Connect(addr) => {
+                if let Some(room) = self.peer_room_mut(*addr) {
+                    room.push_event(NetworkSimulationEvent::Connect(*addr));
+                } else {
+                    log::info!("Detected a new UDP connection: {}", addr);
+                }
+            }
+            NetworkSimulationEvent::Disconnect(addr) => {
+                if let Some(room) = self.peer_room_mut(*addr) {
+                    room.push_event(NetworkSimulationEvent::Disconnect(*addr));
+                }
+                self.peers.remove(addr);
+                self.matchmaking_queue.cancel(*addr);
+            }
+            NetworkSimulationEvent::SendError(err, _) => {
+                log::error!("(SendError) {:?}", err);
+            }
+            NetworkSimulationEvent::RecvError(err) => {
+                log::error!("(RecvError) {:?}", err);
+            }
+            NetworkSimulationEvent::ConnectionError(err, _) => {
+                log::error!("(ConnectionError) {:?}", err);
+            }
+        }
+    }
+
+    /// Matches queued players, steps every room as many times as its own schedule requires
+    /// and drops the rooms which have been shut down.
+    pub fn update(&mut self, elapsed: Duration) {
+        self.update_matchmaking(elapsed);
+
+        let tick_duration = self.tick_duration();
+        let rooms_count = self.rooms.len();
+        // Rotating the first room, so that the same room doesn't always get the freshest input.
+        for i in 0..rooms_count {
+            let scheduled_room = &mut self.rooms[(self.first_room_to_step + i) % rooms_count];
+            scheduled_room.accumulated_time += elapsed;
+
+            let mut ticks = 0;
+            while scheduled_room.accumulated_time >= tick_duration {
+                if ticks == MAX_CATCH_UP_TICKS {
+                    log::warn!(
+                        "The room {} is lagging behind, skipping {:?}",
+                        scheduled_room.room.id(),
+                        scheduled_room.accumulated_time
+                    );
+                    scheduled_room.accumulated_time = Duration::new(0, 0);
+                    break;
+                }
+                scheduled_room.room.step(tick_duration.as_secs_f32());
+                scheduled_room.accumulated_time -= tick_duration;
+                ticks += 1;
+            }
+        }
+        if rooms_count > 0 {
+            self.first_room_to_step = (self.first_room_to_step + 1) % rooms_count;
+        }
+
+        for scheduled_room in &mut self.rooms {
+            self.outcoming_messages
+                .append(&mut scheduled_room.room.drain_outcoming_messages());
+        }
+        self.drop_shut_down_rooms();
+    }
+
+    pub fn drain_outcoming_messages(&mut self) -> Vec<Message> {
+        let mut messages = self.transport.drain_messages(|_| true);
+        messages.append(&mut self.outcoming_messages);
+        messages
+    }
+
+    fn peer_room_mut(&mut self, addr: SocketAddr) -> Option<&mut Room> {
+        let room_id = self.peers.get(&addr).cloned()?;
+        self.rooms
+            .iter_mut()
+            .map(|scheduled_room| &mut scheduled_room.room)
+            .find(|room| room.id() == room_id)
+    }
+
+    fn process_lobby_message(&mut self, addr: SocketAddr, bytes: &[u8]) {
+        let ClientMessage {
+            session_id,
+            payload,
+        } = match decode_client_message(bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                log::warn!("Failed to decode a message from {}: {:?}", addr, err);
+                return;
+            }
+        };
+
+        match payload {
+            ClientMessagePayload::ListRooms => {
+                let rooms = self.rooms().map(Room::info).collect();
+                self.send(addr, session_id, ServerMessagePayload::RoomList(rooms));
+            }
+            ClientMessagePayload::CreateRoom { name } => {
+                if self.rooms.len() >= self.config.max_rooms {
+                    log::warn!("Couldn't create a room for {}: the limit is reached", addr);
+                    self.send(
+                        addr,
+                        session_id,
+                        ServerMessagePayload::Disconnect(DisconnectReason::RoomLimitReached),
+                    );
+                    return;
+                }
+
+                let room_id = self.next_room_id();
+                // The creator becomes the host, as it happens with a client starting its own server.
+                match Room::new(
+                    room_id,
+                    name,
+                    Some(addr),
+                    self.config.tick_rate,
+                    self.config.afk_kick_timeout,
+                ) {
+                    Ok(room) => {
+                        log::info!("Created a new room {} for {}", room_id, addr);
+                        self.rooms.push(ScheduledRoom {
+                            room,
+                            accumulated_time: Duration::new(0, 0),
+                        });
+                        self.enter_room(addr, session_id, room_id);
+                    }
+                    Err(err) => {
+                        log::error!("Failed to create a room: {:?}", err);
+                        self.send(
+                            addr,
+                            session_id,
+                            ServerMessagePayload::Disconnect(DisconnectReason::Uninitialized),
+                        );
+                    }
+                }
+            }
+            ClientMessagePayload::EnterRoom(room_id) => {
+                if self.room(room_id).is_some() {
+                    self.enter_room(addr, session_id, room_id);
+                } else {
+                    self.send(
+                        addr,
+                        session_id,
+                        ServerMessagePayload::Disconnect(DisconnectReason::RoomNotFound),
+                    );
+                }
+            }
+            ClientMessagePayload::FindMatch { rating } => {
+                log::info!("{} is looking for a match (rating: {})", addr, rating);
+                self.matchmaking_queue.enqueue(addr, session_id, rating);
+                let players_in_queue = self.matchmaking_queue.len();
+                self.send(
+                    addr,
+                    session_id,
+                    ServerMessagePayload::MatchmakingQueued { players_in_queue },
+                );
+            }
+            ClientMessagePayload::CancelMatchmaking | ClientMessagePayload::Disconnect => {
+                if self.matchmaking_queue.cancel(addr).is_some() {
+                    log::info!("{} has left the matchmaking queue", addr);
+                }
+            }
+            ClientMessagePayload::JoinRoom { .. } | ClientMessagePayload::Spectate { .. } => {
+                log::warn!("{} tried to join without entering a room first", addr);
+                self.send(
+                    addr,
+                    session_id,
+                    ServerMessagePayload::Disconnect(DisconnectReason::RoomNotFound),
+                );
+            }
+            _ => {
+                log::trace!("Ignoring a message from {} outside of any room", addr);
+            }
+        }
+    }
+
+    fn update_matchmaking(&mut self, elapsed: Duration) {
+        for player in self.matchmaking_queue.advance(elapsed) {
+            log::info!("Matchmaking has timed out for {}", player.addr);
+            self.send(
+                player.addr,
+                player.session_id,
+                ServerMessagePayload::Disconnect(DisconnectReason::MatchmakingTimedOut),
+            );
+        }
+
+        // Matched players stay in the queue until there's a free slot for their room.
+        while self.rooms.len() < self.config.max_rooms {
+            let players = match self.matchmaking_queue.find_match() {
+                Some(players) => players,
+                None => break,
+            };
+
+            let room_id = self.next_room_id();
+            // The player who has waited the most becomes the host.
+            let host = players[0];
+            match Room::new(
+                room_id,
+                format!("Match #{}", room_id),
+                Some(host.addr),
+                self.config.tick_rate,
+                self.config.afk_kick_timeout,
+            ) {
+                Ok(room) => {
+                    log::info!(
+                        "Created a room {} for {} matched players",
+                        room_id,
+                        players.len()
+                    );
+                    self.rooms.push(ScheduledRoom {
+                        room,
+                        accumulated_time: Duration::new(0, 0),
+                    });
+                    for player in players {
+                        log::info!("{} has entered the room {}", player.addr, room_id);
+                        self.peers.insert(player.addr, room_id);
+                        self.send(
+                            player.addr,
+                            player.session_id,
+                            ServerMessagePayload::MatchFound(room_id),
+                        );
+                    }
+                }
+                Err(err) => {
+                    log::error!("Failed to create a room: {:?}", err);
+                    for player in players {
+                        self.send(
+                            player.addr,
+                            player.session_id,
+                            ServerMessagePayload::Disconnect(DisconnectReason::Uninitialized),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn enter_room(&mut self, addr: SocketAddr, session_id: NetIdentifier, room_id: RoomId) {
+        log::info!("{} has entered the room {}", addr, room_id);
+        self.peers.insert(addr, room_id);
+        self.send(addr, session_id, ServerMessagePayload::RoomEntered(room_id));
+    }
+
+    fn send(&mut self, addr: SocketAddr, session_id: NetIdentifier, payload: ServerMessagePayload) {
+        let message = bincode::serialize(&ServerMessage {
+            session_id,
+            payload,
+        })
+        .expect("Expected to serialize a server message");
+        self.transport.send_with_requirements(
+            addr,
+            &message,
+            DeliveryRequirement::Reliable,
+            UrgencyRequirement::Immediate,
+        );
+    }
+
+    fn next_room_id(&mut self) -> RoomId {
+        let id = self.room_id_autoinc;
+        self.room_id_autoinc = self.room_id_autoinc.wrapping_add(1);
+        id
+    }
+
+    fn drop_shut_down_rooms(&mut self) {
+        let peers = &mut self.peers;
+        self.rooms.retain(|scheduled_room| {
+            let room = &scheduled_room.room;
+            if room.is_shut_down() {
+                log::info!("Dropping the room {}", room.id());
+                peers.retain(|_, room_id| *room_id != room.id());
+                false
+            } else {
+                true
+            }
+        });
+        if self.first_room_to_step >= self.rooms.len() {
+            self.first_room_to_step = 0;
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use amethyst::{
+    core::Time,
+    ecs::{Join, ReadStorage, World, WorldExt},
+    error::Error,
+    network::simulation::{Message, NetworkSimulationEvent, TransportResource},
+    prelude::{DataInit, GameData, GameDataBuilder, SimpleState, StateData},
+    shrev::EventChannel,
+};
+
+use std::{net::SocketAddr, time::Duration};
+
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::{net::MultiplayerGameState, GameEngineState, GameTime, NewGameEngineState},
+        system_data::time::GameTimeService,
+    },
+    net::{server_message::RoomInfo, RoomId},
+};
+use gv_game::states::{LevelTransitionState, LoadingState, MenuState, PlayingState};
+
+use crate::{
+    build_server_systems,
+    ecs::resources::{AfkConfig, HostClientAddress},
+};
+
+/// A game room with its own world, which is stepped manually instead of by an `Application`.
+///
+/// State transitions normally performed by `StateSwitcherSystem` via `TransEvent` are emulated
+/// in `step`, as there's no state machine to consume those events.
+pub struct Room {
+    id: RoomId,
+    name: String,
+    world: World,
+    game_data: GameData<'static, 'static>,
+    is_shut_down: bool,
+}
+
+impl Room {
+    pub fn new(
+        id: RoomId,
+        name: String,
+        host_client_address: Option<SocketAddr>,
+        tick_rate: u32,
+        afk_kick_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.insert(GameTime::with_tick_rate(tick_rate, tick_rate));
+        world.insert(AfkConfig {
+            kick_timeout: afk_kick_timeout,
+            ..AfkConfig::default()
+        });
+        world.insert(TransportResource::default());
+        world.insert(EventChannel::<NetworkSimulationEvent>::default());
+
+        let game_data_builder = build_server_systems(
+            GameDataBuilder::default(),
+            &mut world,
+            HostClientAddress(host_client_address),
+        )?;
+        let mut game_data = game_data_builder.build(&mut world);
+
+        let mut loading_state = LoadingState::default();
+        loading_state.on_start(StateData::new(&mut world, &mut game_data));
+        loading_state.update(&mut StateData::new(&mut world, &mut game_data));
+
+        let mut room = Self {
+            id,
+            name,
+            world,
+            game_data,
+            is_shut_down: false,
+        };
+        room.switch_state();
+        Ok(room)
+    }
+
+    pub fn id(&self) -> RoomId {
+        self.id
+    }
+
+    pub fn info(&self) -> RoomInfo {
+        RoomInfo {
+            id: self.id,
+            name: self.name.clone(),
+            players_count: self.multiplayer_game_state(|state| state.players.len()),
+            is_playing: self.multiplayer_game_state(|state| state.is_playing),
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// A room is shut down once its host leaves. Unlike the server process, which waits
+    /// for laminar to deliver the last messages before quitting, a room can be dropped right away.
+    pub fn is_shut_down(&self) -> bool {
+        self.is_shut_down
+    }
+
+    pub fn engine_state(&self) -> GameEngineState {
+        *self.world.read_resource::<GameEngineState>()
+    }
+
+    pub fn game_frame_number(&self) -> u64 {
+        self.world
+            .system_data::<GameTimeService>()
+            .game_frame_number()
+    }
+
+    pub fn push_event(&mut self, event: NetworkSimulationEvent) {
+        self.world
+            .write_resource::<EventChannel<NetworkSimulationEvent>>()
+            .single_write(event);
+    }
+
+    pub fn drain_outcoming_messages(&mut self) -> Vec<Message> {
+        self.world
+            .write_resource::<TransportResource>()
+            .drain_messages(|_| true)
+    }
+
+    pub fn step(&mut self, delta_seconds: f32) {
+        if self.is_shut_down {
+            return;
+        }
+
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.increment_frame_number();
+            time.set_delta_seconds(delta_seconds);
+        }
+        self.game_data.update(&self.world);
+        self.world.maintain();
+        self.switch_state();
+    }
+
+    pub fn multiplayer_game_state<R>(&self, f: impl FnOnce(&MultiplayerGameState) -> R) -> R {
+        f(&*self.world.read_resource::<MultiplayerGameState>())
+    }
+
+    pub fn connections_count(&self) -> usize {
+        let net_connection_models = self.world.system_data::<ReadStorage<NetConnectionModel>>();
+        (&net_connection_models)
+            .join()
+            .filter(|net_connection_model| !net_connection_model.disconnected)
+            .count()
+    }
+
+    fn switch_state(&mut self) {
+        let new_game_engine_state = self.world.read_resource::<NewGameEngineState>().0;
+        if *self.world.read_resource::<GameEngineState>() == new_game_engine_state {
+            return;
+        }
+
+        match new_game_engine_state {
+            // Servers don't load level assets, they start playing right away.
+            GameEngineState::Loading | GameEngineState::LoadingLevel => unreachable!(),
+            GameEngineState::Menu => {
+                MenuState.on_start(StateData::new(&mut self.world, &mut self.game_data))
+            }
+            GameEngineState::Playing => {
+                PlayingState.on_start(StateData::new(&mut self.world, &mut self.game_data))
+            }
+            GameEngineState::LevelTransition => {
+                LevelTransitionState.on_start(StateData::new(&mut self.world, &mut self.game_data))
+            }
+            GameEngineState::ShuttingDown { .. } | GameEngineState::Quit => {
+                log::info!("The room {} ({}) has been shut down", self.id, self.name);
+                *self.world.write_resource::<GameEngineState>() = new_game_engine_state;
+                self.is_shut_down = true;
+            }
+        }
+    }
+}
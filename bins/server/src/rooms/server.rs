@@ -0,0 +1,94 @@
+use amethyst::{
+    core::Time,
+    ecs::{ReaderId, World, WorldExt},
+    error::Error,
+    network::simulation::{
+        laminar::{LaminarNetworkBundle, LaminarSocket},
+        NetworkSimulationEvent, TransportResource,
+    },
+    prelude::{DataInit, GameData, GameDataBuilder},
+    shrev::EventChannel,
+};
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{RoomRouter, RoomsConfig};
+
+/// Runs a `RoomRouter` on a laminar socket. Only the network systems run in this world,
+/// every room is stepped in its own one.
+pub struct RoomServer {
+    world: World,
+    network_data: GameData<'static, 'static>,
+    reader: ReaderId<NetworkSimulationEvent>,
+    router: RoomRouter,
+}
+
+impl RoomServer {
+    pub fn new(socket: LaminarSocket, config: RoomsConfig) -> Result<Self, Error> {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.insert(TransportResource::default());
+        world.insert(EventChannel::<NetworkSimulationEvent>::default());
+
+        let network_data = GameDataBuilder::default()
+            .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+            .build(&mut world);
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+
+        Ok(Self {
+            world,
+            network_data,
+            reader,
+            router: RoomRouter::new(config),
+        })
+    }
+
+    pub fn run(&mut self) {
+        let tick_duration = self.router.tick_duration();
+        let mut last_frame_at = Instant::now();
+        loop {
+            let frame_started_at = Instant::now();
+            let elapsed = frame_started_at - last_frame_at;
+            last_frame_at = frame_started_at;
+            self.update(elapsed);
+
+            if let Some(sleep_duration) = tick_duration.checked_sub(frame_started_at.elapsed()) {
+                thread::sleep(sleep_duration);
+            }
+        }
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.increment_frame_number();
+            time.set_delta_time(elapsed);
+        }
+        self.network_data.update(&self.world);
+
+        {
+            let net_events = self
+                .world
+                .read_resource::<EventChannel<NetworkSimulationEvent>>();
+            for net_event in net_events.read(&mut self.reader) {
+                self.router.process_event(net_event);
+            }
+        }
+        self.router.update(elapsed);
+
+        let mut transport = self.world.write_resource::<TransportResource>();
+        for message in self.router.drain_outcoming_messages() {
+            transport.send_with_requirements(
+                message.destination,
+                &message.payload,
+                message.delivery,
+                message.urgency,
+            );
+        }
+    }
+}
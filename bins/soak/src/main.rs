@@ -0,0 +1,483 @@
+use amethyst::ecs::{Join, ReadStorage, WorldExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use gv_core::{
+    actions::{monster_spawn::SpawnActions, player::CastSpell},
+    ecs::{
+        components::NetConnectionModel,
+        resources::world::{
+            FramedUpdates, PlayerActionUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
+            WorldStates, SAVED_WORLD_STATES_LIMIT, SERVER_WORLD_UPDATES_LIMIT,
+        },
+    },
+    math::{deterministic, Vector2},
+    net::RoomId,
+};
+use gv_server::rooms::{Room, RoomRouter, RoomsConfig};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+/// Entering a room, joining and starting a game go through reliable messages only,
+/// so these never time out because of the simulated packet loss.
+const MAX_SETUP_FRAMES: u64 = 600;
+/// Frames given to the server to forget a peer after it leaves,
+/// or to close a room after its host leaves.
+const MAX_LEAVE_FRAMES: u64 = 300;
+const MIN_SESSION_FRAMES: u64 = 600;
+const MAX_SESSION_FRAMES: u64 = 3600;
+/// A chance per frame that a non-host player leaves, or that a new client enters to spectate.
+const CHURN_CHANCE: f64 = 1.0 / 300.0;
+const BOT_TURN_INTERVAL_FRAMES: u64 = 30;
+const BOT_CAST_INTERVAL_FRAMES: u64 = 20;
+const BOT_CAST_DISTANCE: f32 = 200.0;
+
+struct SoakConfig {
+    duration: Duration,
+    clients: usize,
+    check_interval_frames: u64,
+    max_entities: usize,
+    max_rss_growth_kb: u64,
+}
+
+/// Clients taking part in the current session, by their index in the harness.
+struct Session {
+    room_id: RoomId,
+    seated: Vec<usize>,
+    entering: Vec<usize>,
+    /// Every client that has entered the room, including the ones that have left.
+    entered_count: usize,
+    /// Frames at which clients have left, the server may still count them for a while.
+    left_at_frames: Vec<u64>,
+    frame: u64,
+}
+
+fn main() -> amethyst::Result<()> {
+    let cli_matches = clap::App::new("gv_soak")
+        .about("Keeps clients joining and leaving a server for hours, checking it for leaks")
+        .arg(
+            clap::Arg::with_name("duration")
+                .short("d")
+                .long("duration")
+                .value_name("MINUTES")
+                .help("Specifies how long to run the soak test for")
+                .default_value("60")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("clients")
+                .short("c")
+                .long("clients")
+                .value_name("CLIENTS")
+                .help("Specifies the maximum number of clients in a room")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("latency")
+                .long("latency")
+                .value_name("FRAMES")
+                .help("Specifies the delivery latency of every message")
+                .default_value("3")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("loss")
+                .long("loss")
+                .value_name("PERCENT")
+                .help("Specifies the share of unreliable messages that get lost")
+                .default_value("5")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("jitter")
+                .long("jitter")
+                .value_name("FRAMES")
+                .help("Specifies the maximum extra delay of unreliable messages")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seeds the churn and the network simulation to reproduce a failed run")
+                .default_value("0")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("check-interval")
+                .long("check-interval")
+                .value_name("FRAMES")
+                .help("Specifies how often the server invariants are checked")
+                .default_value("300")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("max-entities")
+                .long("max-entities")
+                .value_name("ENTITIES")
+                .help("Specifies how many entities a room may have before it's considered leaking")
+                .default_value("5000")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("max-rss-growth")
+                .long("max-rss-growth")
+                .value_name("MEGABYTES")
+                .help("Specifies how much the process may grow after the first session")
+                .default_value("256")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let parse_arg = |name: &str| -> amethyst::Result<u64> {
+        let value = cli_matches
+            .value_of(name)
+            .expect("Expected a default value");
+        value
+            .parse()
+            .map_err(|_| amethyst::Error::from_string(format!("Invalid {}: {}", name, value)))
+    };
+    let config = SoakConfig {
+        duration: Duration::from_secs(parse_arg("duration")? * 60),
+        clients: parse_arg("clients")?.max(1) as usize,
+        check_interval_frames: parse_arg("check-interval")?.max(1),
+        max_entities: parse_arg("max-entities")? as usize,
+        max_rss_growth_kb: parse_arg("max-rss-growth")? * 1024,
+    };
+    let seed = parse_arg("seed")?;
+    let loopback_config = LoopbackConfig {
+        latency_frames: parse_arg("latency")?,
+        loss_rate: parse_arg("loss")?.min(100) as f64 / 100.0,
+        jitter_frames: parse_arg("jitter")?,
+        seed,
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut harness = TestHarness::with_rooms(loopback_config, RoomsConfig::default());
+    for i in 0..config.clients {
+        harness.add_client(&format!("soak{}", i));
+    }
+
+    let started_at = Instant::now();
+    let mut baseline_rss_kb = None;
+    let mut session_number = 0;
+    while started_at.elapsed() < config.duration {
+        session_number += 1;
+        run_session(&mut harness, &mut rng, &config, baseline_rss_kb)
+            .and_then(|_| check_teardown(&mut harness))
+            .map_err(|err| {
+                amethyst::Error::from_string(format!(
+                    "Session {} (seed {}) has failed: {}",
+                    session_number, seed, err
+                ))
+            })?;
+        // Whatever gets allocated during the first session (pools, caches) is kept for good.
+        if baseline_rss_kb.is_none() {
+            baseline_rss_kb = rss_kb();
+        }
+
+        println!(
+            "[{:>6}s] session {} is over, rss: {}",
+            started_at.elapsed().as_secs(),
+            session_number,
+            rss_kb().map_or_else(|| "unknown".to_owned(), |rss| format!("{} KB", rss)),
+        );
+    }
+
+    println!(
+        "{} sessions have passed without leaks in {} minutes",
+        session_number,
+        config.duration.as_secs() / 60
+    );
+    Ok(())
+}
+
+/// Creates a room, plays a match with players leaving and spectators entering,
+/// then makes the host leave, which closes the room.
+fn run_session(
+    harness: &mut TestHarness<RoomRouter>,
+    rng: &mut StdRng,
+    config: &SoakConfig,
+    baseline_rss_kb: Option<u64>,
+) -> Result<(), String> {
+    let players_count = rng.gen_range(1, config.clients + 1);
+
+    harness.client_mut(0).create_room("Soak");
+    if !harness.run_until(MAX_SETUP_FRAMES, |harness| {
+        harness.client(0).room_id().is_some()
+    }) {
+        return Err("the host couldn't create a room".to_owned());
+    }
+    let room_id = harness.client(0).room_id().expect("Expected a room id");
+
+    for i in 1..players_count {
+        harness.client_mut(i).enter_room(room_id);
+    }
+    if !harness.run_until(MAX_SETUP_FRAMES, |harness| {
+        (0..players_count).all(|i| harness.client(i).room_id() == Some(room_id))
+    }) {
+        return Err("players couldn't enter the room".to_owned());
+    }
+
+    for i in 0..players_count {
+        harness.client_mut(i).join();
+    }
+    if !harness.run_until(MAX_SETUP_FRAMES, |harness| {
+        (0..players_count).all(|i| harness.client(i).room_players().len() == players_count)
+    }) {
+        return Err("players couldn't join the room".to_owned());
+    }
+    harness.client_mut(0).start_game();
+    if !harness.run_until(MAX_SETUP_FRAMES, |harness| {
+        (0..players_count).all(|i| harness.client(i).has_started_game())
+    }) {
+        return Err("the game hasn't started".to_owned());
+    }
+
+    let mut session = Session {
+        room_id,
+        seated: (0..players_count).collect(),
+        entering: Vec::new(),
+        entered_count: players_count,
+        left_at_frames: Vec::new(),
+        frame: 0,
+    };
+    let session_frames = rng.gen_range(MIN_SESSION_FRAMES, MAX_SESSION_FRAMES);
+    for frame in 0..session_frames {
+        session.frame = frame;
+        step_bots(harness, &session, frame);
+        churn(harness, rng, config, &mut session);
+        harness.step();
+
+        if frame % config.check_interval_frames == 0 {
+            check_room(harness, config, &session)?;
+            check_rss(config, baseline_rss_kb)?;
+        }
+    }
+    check_room(harness, config, &session)?;
+
+    harness.client_mut(0).disconnect();
+    harness.step();
+    // Nobody is left in the room after its host, every client gets a fresh seat.
+    for i in 0..config.clients {
+        harness.replace_client(i, &format!("soak{}", i));
+    }
+    Ok(())
+}
+
+/// Bots walk in circles of different phases and shoot ahead, the same way `gv_bench` bots do,
+/// but send walk actions as inputs, which are subject to the simulated packet loss.
+fn step_bots(harness: &mut TestHarness<RoomRouter>, session: &Session, frame: u64) {
+    for &i in &session.seated {
+        let client = harness.client_mut(i);
+        if client.disconnect_reason().is_some() || client.player_net_id().is_none() {
+            continue;
+        }
+        let phase = (frame / BOT_TURN_INTERVAL_FRAMES) as f32 * 0.5 + i as f32;
+        let direction = deterministic::rotate(&Vector2::new(1.0, 0.0), phase);
+        if frame % BOT_TURN_INTERVAL_FRAMES == 0 {
+            let input = client.walk_input(Some(direction), 0);
+            client.send_inputs(vec![input]);
+        }
+        if frame % BOT_CAST_INTERVAL_FRAMES == 0 {
+            client.cast(CastSpell::Missile, direction * BOT_CAST_DISTANCE);
+        }
+    }
+}
+
+fn churn(
+    harness: &mut TestHarness<RoomRouter>,
+    rng: &mut StdRng,
+    config: &SoakConfig,
+    session: &mut Session,
+) {
+    // Entering clients start spectating once the router has put them into the room,
+    // or give up their seat if they've been refused.
+    let mut i = 0;
+    while i < session.entering.len() {
+        let index = session.entering[i];
+        if harness.client(index).disconnect_reason().is_some() {
+            harness.replace_client(index, &format!("soak{}", index));
+            session.entering.swap_remove(i);
+        } else if harness.client(index).room_id() == Some(session.room_id) {
+            harness.client_mut(index).spectate();
+            session.seated.push(index);
+            session.entering.swap_remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    // The host never leaves in the middle of a session, as it would close the room.
+    if session.seated.len() > 1 && rng.gen_bool(CHURN_CHANCE) {
+        let seat = rng.gen_range(1, session.seated.len());
+        let index = session.seated.swap_remove(seat);
+        session.left_at_frames.push(session.frame);
+        if rng.gen_bool(0.5) {
+            harness.client_mut(index).disconnect();
+            // Letting the Disconnect message leave the client before it gets replaced.
+            harness.step();
+        } else {
+            harness.drop_client(index);
+        }
+        harness.replace_client(index, &format!("soak{}", index));
+    }
+
+    let free_index = (1..config.clients)
+        .find(|index| !session.seated.contains(index) && !session.entering.contains(index));
+    if let Some(index) = free_index {
+        if rng.gen_bool(CHURN_CHANCE) {
+            harness.client_mut(index).enter_room(session.room_id);
+            session.entering.push(index);
+            session.entered_count += 1;
+        }
+    }
+}
+
+fn check_room(
+    harness: &TestHarness<RoomRouter>,
+    config: &SoakConfig,
+    session: &Session,
+) -> Result<(), String> {
+    let router = harness.server();
+    let rooms_count = router.rooms().count();
+    if rooms_count != 1 {
+        return Err(format!("expected a single room, got {}", rooms_count));
+    }
+    let leaving_count = session
+        .left_at_frames
+        .iter()
+        .filter(|left_at_frame| session.frame < *left_at_frame + MAX_LEAVE_FRAMES)
+        .count();
+    let peers_limit = session.seated.len() + session.entering.len() + leaving_count;
+    if router.peers_count() > peers_limit {
+        return Err(format!(
+            "the router has {} peers, expected at most {}",
+            router.peers_count(),
+            peers_limit
+        ));
+    }
+    let room = router
+        .room(session.room_id)
+        .ok_or_else(|| format!("the room {} has been closed", session.room_id))?;
+
+    if room.connections_count() > peers_limit {
+        return Err(format!(
+            "the room has {} connections, expected at most {}",
+            room.connections_count(),
+            peers_limit
+        ));
+    }
+    let net_connection_models_count = {
+        let net_connection_models = room
+            .world()
+            .system_data::<ReadStorage<NetConnectionModel>>();
+        (&net_connection_models).join().count()
+    };
+    if net_connection_models_count > session.entered_count {
+        return Err(format!(
+            "the room keeps {} connection models for {} clients that have ever entered it",
+            net_connection_models_count, session.entered_count
+        ));
+    }
+
+    let entities_count = (&*room.world().entities()).join().count();
+    if entities_count > config.max_entities {
+        return Err(format!(
+            "the room has {} entities, expected at most {}",
+            entities_count, config.max_entities
+        ));
+    }
+
+    check_buffer_len(
+        "FramedUpdates<PlayerActionUpdates>",
+        framed_updates_len::<PlayerActionUpdates>(room),
+        SAVED_WORLD_STATES_LIMIT,
+    )?;
+    check_buffer_len(
+        "FramedUpdates<SpawnActions>",
+        framed_updates_len::<SpawnActions>(room),
+        SAVED_WORLD_STATES_LIMIT,
+    )?;
+    check_buffer_len(
+        "FramedUpdates<ReceivedClientActionUpdates>",
+        framed_updates_len::<ReceivedClientActionUpdates>(room),
+        SAVED_WORLD_STATES_LIMIT,
+    )?;
+    check_buffer_len(
+        "WorldStates",
+        room.world().read_resource::<WorldStates>().len(),
+        SAVED_WORLD_STATES_LIMIT,
+    )?;
+    check_buffer_len(
+        "ServerWorldUpdates",
+        room.world()
+            .read_resource::<ServerWorldUpdates>()
+            .updates
+            .len(),
+        SERVER_WORLD_UPDATES_LIMIT,
+    )
+}
+
+/// Once the host has left, the room has to be dropped along with every peer, and nothing
+/// should be left in flight.
+fn check_teardown(harness: &mut TestHarness<RoomRouter>) -> Result<(), String> {
+    let is_torn_down = harness.run_until(MAX_LEAVE_FRAMES, |harness| {
+        harness.server().rooms().next().is_none()
+            && harness.server().peers_count() == 0
+            && harness.in_flight_count() == 0
+    });
+    if is_torn_down && harness.server().matchmaking_queue().is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "the server hasn't been cleaned up: {} rooms, {} peers, {} queued players, {} messages in flight",
+        harness.server().rooms().count(),
+        harness.server().peers_count(),
+        harness.server().matchmaking_queue().len(),
+        harness.in_flight_count()
+    ))
+}
+
+fn check_rss(config: &SoakConfig, baseline_rss_kb: Option<u64>) -> Result<(), String> {
+    let (baseline_rss_kb, rss_kb) = match (baseline_rss_kb, rss_kb()) {
+        (Some(baseline_rss_kb), Some(rss_kb)) => (baseline_rss_kb, rss_kb),
+        _ => return Ok(()),
+    };
+    if rss_kb > baseline_rss_kb + config.max_rss_growth_kb {
+        return Err(format!(
+            "the process has grown from {} KB to {} KB",
+            baseline_rss_kb, rss_kb
+        ));
+    }
+    Ok(())
+}
+
+fn check_buffer_len(name: &str, len: usize, limit: usize) -> Result<(), String> {
+    if len > limit {
+        return Err(format!(
+            "{} has {} entries, the limit is {}",
+            name, len, limit
+        ));
+    }
+    Ok(())
+}
+
+fn framed_updates_len<T: Send + Sync + 'static>(room: &Room) -> usize {
+    room.world()
+        .read_resource::<FramedUpdates<T>>()
+        .updates
+        .len()
+}
+
+/// Reads the resident set size on Linux, returns `None` on other platforms.
+fn rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
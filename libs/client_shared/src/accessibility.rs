@@ -0,0 +1,237 @@
+use serde_derive::{Deserialize, Serialize};
+
+use gv_core::aim_assist::AimAssistStrength;
+
+pub const MIN_UI_SCALE: f32 = 0.75;
+/// Larger UI elements would start overlapping each other in the menus.
+pub const MAX_UI_SCALE: f32 = 1.5;
+/// The menu cycles through the scales with this step.
+pub const UI_SCALE_STEP: f32 = 0.25;
+
+const DEFAULT_DAMAGE_COLOR: [f32; 3] = [1.0, 0.3, 0.25];
+const DEFAULT_HEAL_COLOR: [f32; 3] = [0.35, 1.0, 0.4];
+
+/// Replaces the colors that tell players apart and the colors of damage and healing numbers.
+/// The alternative palettes are based on the Okabe-Ito one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorPalette {
+    Default,
+    /// Red-green, the most common kind of color blindness.
+    Deuteranopia,
+    Protanopia,
+    /// Blue-yellow.
+    Tritanopia,
+}
+
+impl ColorPalette {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorPalette::Default => "Default",
+            ColorPalette::Deuteranopia => "Deuteranopia",
+            ColorPalette::Protanopia => "Protanopia",
+            ColorPalette::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Is used to cycle through the palettes in the menu.
+    pub fn next(self) -> Self {
+        match self {
+            ColorPalette::Default => ColorPalette::Deuteranopia,
+            ColorPalette::Deuteranopia => ColorPalette::Protanopia,
+            ColorPalette::Protanopia => ColorPalette::Tritanopia,
+            ColorPalette::Tritanopia => ColorPalette::Default,
+        }
+    }
+
+    /// The default palette keeps the color the player has picked or got by their slot,
+    /// the other ones color players by their slot only, as picked colors can be hard to tell apart.
+    pub fn player_color(self, player_index: usize, color: [f32; 3]) -> [f32; 3] {
+        let colors = match self {
+            ColorPalette::Default => return color,
+            ColorPalette::Deuteranopia | ColorPalette::Protanopia => [
+                [0.0, 0.45, 0.7],
+                [0.9, 0.6, 0.0],
+                [0.34, 0.71, 0.91],
+                [0.94, 0.89, 0.26],
+            ],
+            ColorPalette::Tritanopia => [
+                [0.84, 0.15, 0.12],
+                [0.0, 0.6, 0.7],
+                [0.8, 0.47, 0.65],
+                [0.25, 0.25, 0.25],
+            ],
+        };
+        colors[player_index % colors.len()]
+    }
+
+    pub fn damage_color(self) -> [f32; 3] {
+        match self {
+            ColorPalette::Default => DEFAULT_DAMAGE_COLOR,
+            ColorPalette::Deuteranopia | ColorPalette::Protanopia => [0.9, 0.6, 0.0],
+            ColorPalette::Tritanopia => [1.0, 0.25, 0.25],
+        }
+    }
+
+    pub fn heal_color(self) -> [f32; 3] {
+        match self {
+            ColorPalette::Default => DEFAULT_HEAL_COLOR,
+            ColorPalette::Deuteranopia | ColorPalette::Protanopia => [0.34, 0.71, 0.91],
+            ColorPalette::Tritanopia => [0.3, 0.85, 0.95],
+        }
+    }
+}
+
+impl Default for ColorPalette {
+    fn default() -> Self {
+        ColorPalette::Default
+    }
+}
+
+/// Is drawn under a player when shape markers are on, so that players can be told apart
+/// without relying on colors. Sprites are numbered in the same order
+/// in `resources/assets/player_markers.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+}
+
+impl MarkerShape {
+    pub fn for_player(player_index: usize) -> Self {
+        match player_index % 4 {
+            0 => MarkerShape::Circle,
+            1 => MarkerShape::Square,
+            2 => MarkerShape::Triangle,
+            _ => MarkerShape::Diamond,
+        }
+    }
+
+    pub fn sprite_number(self) -> usize {
+        match self {
+            MarkerShape::Circle => 0,
+            MarkerShape::Square => 1,
+            MarkerShape::Triangle => 2,
+            MarkerShape::Diamond => 3,
+        }
+    }
+}
+
+/// Is stored next to the other client configs, isn't shared with the other players.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    #[serde(default)]
+    pub palette: ColorPalette,
+    /// Draws a `MarkerShape` under every player.
+    #[serde(default)]
+    pub shape_markers: bool,
+    /// Scales the menus and the HUD text.
+    #[serde(default = "default_scale")]
+    pub ui_scale: f32,
+    /// Scales the health orb, combat numbers and nameplates.
+    #[serde(default = "default_scale")]
+    pub hud_scale: f32,
+    /// Is ignored in rooms that disallow aim assist.
+    #[serde(default)]
+    pub aim_assist: AimAssistStrength,
+}
+
+impl AccessibilitySettings {
+    /// Makes sure that a hand-edited config doesn't make the UI unusable.
+    pub fn clamped(mut self) -> Self {
+        self.ui_scale = clamp_scale(self.ui_scale);
+        self.hud_scale = clamp_scale(self.hud_scale);
+        self
+    }
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            palette: ColorPalette::default(),
+            shape_markers: false,
+            ui_scale: default_scale(),
+            hud_scale: default_scale(),
+            aim_assist: AimAssistStrength::default(),
+        }
+    }
+}
+
+/// Is used to cycle through the scales in the menu, the smallest one comes after the largest.
+pub fn next_scale(scale: f32) -> f32 {
+    let next_scale = scale + UI_SCALE_STEP;
+    if next_scale > MAX_UI_SCALE + std::f32::EPSILON {
+        MIN_UI_SCALE
+    } else {
+        clamp_scale(next_scale)
+    }
+}
+
+fn clamp_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.max(MIN_UI_SCALE).min(MAX_UI_SCALE)
+    } else {
+        default_scale()
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use gv_core::cosmetics::PlayerCosmetics;
+
+    use super::*;
+
+    #[test]
+    fn default_palette_keeps_player_colors() {
+        let cosmetics = PlayerCosmetics {
+            color: Some(3),
+            hat: None,
+        };
+        let color = cosmetics.color(0);
+        assert_eq!(ColorPalette::Default.player_color(0, color), color);
+    }
+
+    #[test]
+    fn palettes_tell_players_apart() {
+        let mut palette = ColorPalette::Default.next();
+        while palette != ColorPalette::Default {
+            let colors = (0..4)
+                .map(|player_index| palette.player_color(player_index, [0.5, 0.5, 0.5]))
+                .collect::<Vec<_>>();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(colors[i + 1..].iter().all(|other| other != color));
+            }
+            assert_ne!(palette.damage_color(), palette.heal_color());
+            palette = palette.next();
+        }
+
+        let markers = (0..4).map(MarkerShape::for_player).collect::<Vec<_>>();
+        for (i, marker) in markers.iter().enumerate() {
+            assert!(markers[i + 1..].iter().all(|other| other != marker));
+        }
+    }
+
+    #[test]
+    fn scales_are_kept_in_bounds() {
+        let mut scale = 1.0;
+        for _ in 0..10 {
+            scale = next_scale(scale);
+            assert!(scale >= MIN_UI_SCALE && scale <= MAX_UI_SCALE);
+        }
+        assert_eq!(next_scale(MAX_UI_SCALE), MIN_UI_SCALE);
+
+        let accessibility = AccessibilitySettings {
+            ui_scale: 10.0,
+            hud_scale: std::f32::NAN,
+            ..AccessibilitySettings::default()
+        }
+        .clamped();
+        assert_eq!(accessibility.ui_scale, MAX_UI_SCALE);
+        assert_eq!(accessibility.hud_scale, 1.0);
+    }
+}
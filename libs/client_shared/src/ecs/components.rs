@@ -1,6 +1,8 @@
-use amethyst::ecs::{Component, DenseVecStorage};
+use amethyst::ecs::{Component, DenseVecStorage, NullStorage};
 
-use gv_core::math::Vector2;
+use std::time::Duration;
+
+use gv_core::{classes::PlayerClass, math::Vector2};
 
 #[derive(Component)]
 pub struct HealthUiGraphics {
@@ -19,3 +21,54 @@ pub struct SpellParticle {
     pub missile_energy: f32,
     pub frame_spawned: u64,
 }
+
+/// A bubble shown above a player, it's a child entity of the player.
+#[derive(Component)]
+pub struct EmoteBubble {
+    pub started_at: Duration,
+}
+
+/// A digit of a damage or healing number floating up from an entity.
+#[derive(Component)]
+pub struct CombatNumber {
+    pub started_at: Duration,
+}
+
+/// A marker of a predicted hit of the local player, or of a hit that didn't register.
+#[derive(Component)]
+pub struct HitMarker {
+    pub started_at: Duration,
+}
+
+/// Marks monsters and missiles that are off-screen, render passes and animations skip them.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct Culled;
+
+/// Marks dead monsters whose corpses have been cleared to fit the corpse budget,
+/// the culling leaves them hidden.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct BuriedCorpse;
+
+/// Marks the entities of the skirmish played behind the main menu, they are deleted
+/// once the game leaves the menu.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct MenuDemoEntity;
+
+/// A bot or a monster of the main menu skirmish, is animated like players and monsters.
+#[derive(Component)]
+pub struct MenuDemoActor {
+    pub velocity: Vector2,
+    pub looking_direction: Vector2,
+    pub is_monster: bool,
+    /// Is `None` for monsters.
+    pub class: Option<PlayerClass>,
+}
+
+/// A missile of the main menu skirmish, is drawn by `MissilePlugin`.
+#[derive(Component)]
+pub struct MenuDemoMissile {
+    pub age_secs: f32,
+}
@@ -2,17 +2,22 @@ use amethyst::{
     assets::{Handle, Prefab},
     core::{HiddenPropagate, Parent, Transform},
     ecs::{prelude::World, Entities, Entity, Read, ReadExpect, WriteStorage},
-    renderer::Camera,
+    renderer::{Camera, SpriteRender},
     shred::{ResourceId, SystemData},
     window::ScreenDimensions,
 };
 
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
-use gv_core::{ecs::components::ClientPlayerActions, math::Vector2};
+use gv_core::{cosmetics::Hat, ecs::components::ClientPlayerActions, math::Vector2};
 
-use crate::ecs::{
-    components::{HealthUiGraphics, PlayerColor},
-    resources::{AssetHandles, HEALTH_UI_SCREEN_PADDING},
+const MARKER_OFFSET_Y: f32 = -14.0;
+
+use crate::{
+    accessibility::MarkerShape,
+    ecs::{
+        components::{HealthUiGraphics, PlayerColor},
+        resources::{AssetHandles, HEALTH_UI_SCREEN_PADDING},
+    },
 };
 
 #[derive(SystemData)]
@@ -47,6 +52,7 @@ impl<'s> CameraFactory<'s> {
 
 #[derive(SystemData)]
 pub struct PlayerClientFactory<'s> {
+    entities: Entities<'s>,
     asset_handles: Option<Read<'s, AssetHandles>>,
     screen_dimensions: ReadExpect<'s, ScreenDimensions>,
     sprite_animation_handles: WriteStorage<'s, Handle<Prefab<GameSpriteAnimationPrefab>>>,
@@ -54,17 +60,24 @@ pub struct PlayerClientFactory<'s> {
     health_ui_graphics: WriteStorage<'s, HealthUiGraphics>,
     client_player_actions: WriteStorage<'s, ClientPlayerActions>,
     hidden_propagates: WriteStorage<'s, HiddenPropagate>,
+    transforms: WriteStorage<'s, Transform>,
+    parents: WriteStorage<'s, Parent>,
+    sprite_renders: WriteStorage<'s, SpriteRender>,
 }
 
 impl<'s> PlayerClientFactory<'s> {
-    pub fn create(&mut self, player_entity: Entity, color: [f32; 3], is_controllable: bool) {
+    pub fn create(
+        &mut self,
+        player_entity: Entity,
+        color: [f32; 3],
+        hat: Option<Hat>,
+        marker: Option<MarkerShape>,
+        is_controllable: bool,
+    ) {
         if self.asset_handles.is_none() {
             return;
         }
-        let mage_prefab = self.asset_handles.as_ref().unwrap().mage_prefab.clone();
-
-        let mut transform = Transform::default();
-        transform.set_translation_z(10.0);
+        let asset_handles = self.asset_handles.as_ref().unwrap();
 
         let (half_screen_width, half_screen_height) = (
             self.screen_dimensions.width() / 2.0,
@@ -72,7 +85,7 @@ impl<'s> PlayerClientFactory<'s> {
         );
 
         self.sprite_animation_handles
-            .insert(player_entity, mage_prefab)
+            .insert(player_entity, asset_handles.mage_prefab.clone())
             .expect("Expected to insert a HeroPrefab");
         self.player_colors
             .insert(player_entity, PlayerColor(color))
@@ -80,6 +93,40 @@ impl<'s> PlayerClientFactory<'s> {
         self.hidden_propagates
             .insert(player_entity, HiddenPropagate::new())
             .expect("Expected to insert a HiddenPropagate");
+        if let Some(hat) = hat {
+            // Hats are drawn by PaintMagePlugin, as their parent has a PlayerColor.
+            let mut transform = Transform::default();
+            transform.set_translation_z(0.1);
+            self.entities
+                .build_entity()
+                .with(
+                    SpriteRender {
+                        sprite_sheet: asset_handles.hats.clone(),
+                        sprite_number: hat.sprite_number(),
+                    },
+                    &mut self.sprite_renders,
+                )
+                .with(transform, &mut self.transforms)
+                .with(Parent::new(player_entity), &mut self.parents)
+                .build();
+        }
+        if let Some(marker) = marker {
+            // Markers are painted by PaintMagePlugin as well.
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(0.0, MARKER_OFFSET_Y, -0.1);
+            self.entities
+                .build_entity()
+                .with(
+                    SpriteRender {
+                        sprite_sheet: asset_handles.player_markers.clone(),
+                        sprite_number: marker.sprite_number(),
+                    },
+                    &mut self.sprite_renders,
+                )
+                .with(transform, &mut self.transforms)
+                .with(Parent::new(player_entity), &mut self.parents)
+                .build();
+        }
         if is_controllable {
             self.health_ui_graphics
                 .insert(
@@ -1,3 +1,4 @@
 pub mod components;
 pub mod factories;
 pub mod resources;
+pub mod system_data;
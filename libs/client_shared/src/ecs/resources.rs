@@ -1,7 +1,8 @@
 use amethyst::{
-    assets::{Handle, Loader, Prefab},
+    assets::{Handle, Loader, Prefab, ProgressCounter},
+    audio::SourceHandle,
     prelude::World,
-    renderer::{rendy::mesh::MeshBuilder, Material, Mesh, SpriteSheet},
+    renderer::{rendy::mesh::MeshBuilder, Material, Mesh, SpriteSheet, Texture},
     ui::FontHandle,
 };
 
@@ -9,6 +10,8 @@ use std::{io, time::Instant};
 
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
 use gv_core::{
+    ecs::resources::DEFAULT_TICK_RATE,
+    level_loading::{LevelKey, LevelLoadProgress},
     math::Vector3,
     net::{server_message::DisconnectReason, NetIdentifier},
 };
@@ -23,12 +26,80 @@ pub struct DummyAssetHandles {
 
 #[derive(Clone)]
 pub struct AssetHandles {
+    /// Is shared by all the classes, see `ClassDefinition::scale`.
     pub mage_prefab: Handle<Prefab<GameSpriteAnimationPrefab>>,
     pub beetle_prefab: Handle<Prefab<GameSpriteAnimationPrefab>>,
     pub landscape: Handle<SpriteSheet>,
+    pub hats: Handle<SpriteSheet>,
+    pub emotes: Handle<SpriteSheet>,
+    pub augments: Handle<SpriteSheet>,
+    pub minion_marker: Handle<SpriteSheet>,
+    /// Shapes in the order of `MarkerShape` variants.
+    pub player_markers: Handle<SpriteSheet>,
+    /// Digits from 0 to 9 followed by a plus sign.
+    pub combat_numbers: Handle<SpriteSheet>,
+    /// Decals of zones, in the order of `ZoneKind` variants.
+    pub zones: Handle<SpriteSheet>,
+    /// Ground rings of auras, in the order of `AuraEffect` variants.
+    pub auras: Handle<SpriteSheet>,
+    /// Nameplate lines of elite affixes, in the order of `MonsterAffix::ALL`.
+    pub elite_affixes: Handle<SpriteSheet>,
+    /// Nameplate icons of resistances, in the order of `DamageType::ALL`.
+    pub resistances: Handle<SpriteSheet>,
+    /// Pools of blood left under corpses.
+    pub blood: Handle<SpriteSheet>,
+    /// Markers of predicted hits followed by the marker of hits that didn't register.
+    pub hit_markers: Handle<SpriteSheet>,
+    /// Music stems in the order of `MusicStem::ALL`.
+    pub music: Vec<SourceHandle>,
+    pub hit_confirm: SourceHandle,
     pub ui_font: FontHandle,
 }
 
+/// The assets of a single level, see `LevelAssetsLoader`.
+pub struct LevelAssetHandles {
+    pub key: LevelKey,
+    pub progress: ProgressCounter,
+    /// A level script that fails to parse fails the loading as well.
+    pub script_error: Option<String>,
+    pub landscape: Handle<SpriteSheet>,
+    /// The lookup table of the color grading the level script picks.
+    pub color_grading_lut: Handle<Texture>,
+    /// Announcer lines in the order of `AnnouncerLine::ALL`, are loaded once for all levels.
+    pub announcer: Vec<SourceHandle>,
+    pub announcer_progress: ProgressCounter,
+}
+
+impl LevelAssetHandles {
+    pub fn progress(&self) -> LevelLoadProgress {
+        let counters = [&self.progress, &self.announcer_progress];
+        LevelLoadProgress {
+            loaded: counters.iter().map(|counter| counter.num_finished()).sum(),
+            total: counters.iter().map(|counter| counter.num_assets()).sum(),
+            errors: self
+                .script_error
+                .iter()
+                .cloned()
+                .chain(
+                    counters
+                        .iter()
+                        .flat_map(|counter| counter.errors())
+                        .map(|error| format!("{}: {}", error.asset_name, error.error)),
+                )
+                .collect(),
+        }
+    }
+}
+
+/// The assets of the level that is about to be played or is being played.
+#[derive(Default)]
+pub struct LevelAssets(pub Option<LevelAssetHandles>);
+
+/// Is set when `LevelLoadingState` gives up on a level and returns to the menu, which shows
+/// the error until it's confirmed.
+#[derive(Default)]
+pub struct LevelLoadingError(pub Option<String>);
+
 #[derive(Clone)]
 pub struct HealthUiMesh(pub Handle<Mesh>);
 
@@ -66,6 +137,12 @@ pub struct MultiplayerRoomState {
     pub is_host: bool,
     pub connection_status: ConnectionStatus,
     pub player_net_id: NetIdentifier,
+    /// What other players can join with, when hosting via a rendezvous server.
+    pub invite: Option<String>,
+    /// Is set by invites and command line, the lobby moves it into the join address field.
+    pub server_addr: Option<String>,
+    /// Is received with the Handshake, the game is simulated at this rate.
+    pub tick_rate: u32,
 }
 
 impl MultiplayerRoomState {
@@ -75,6 +152,9 @@ impl MultiplayerRoomState {
             is_host: false,
             connection_status: ConnectionStatus::NotConnected,
             player_net_id: 0,
+            invite: None,
+            server_addr: None,
+            tick_rate: DEFAULT_TICK_RATE,
         }
     }
 
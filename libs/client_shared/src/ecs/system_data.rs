@@ -0,0 +1,108 @@
+use amethyst::{
+    assets::{AssetStorage, Loader, ProgressCounter},
+    audio::{Source, WavFormat},
+    ecs::{Read, ReadExpect, Write},
+    renderer::{
+        formats::texture::{ImageTextureConfig, Repr},
+        rendy::hal::image::{Filter, SamplerInfo, WrapMode},
+        ImageFormat, SpriteSheet, SpriteSheetFormat, Texture,
+    },
+    shred::{ResourceId, SystemData},
+};
+
+use gv_core::{announcer::AnnouncerLine, level_loading::LevelKey, level_scripts::ColorGrading};
+
+use crate::ecs::resources::{LevelAssetHandles, LevelAssets};
+
+/// Starts loading the assets of a level in the background, `LevelAssets` keeps the handles
+/// of the last requested level.
+#[derive(SystemData)]
+pub struct LevelAssetsLoader<'s> {
+    loader: ReadExpect<'s, Loader>,
+    texture_storage: Read<'s, AssetStorage<Texture>>,
+    sprite_sheet_storage: Read<'s, AssetStorage<SpriteSheet>>,
+    source_storage: Read<'s, AssetStorage<Source>>,
+    level_assets: Write<'s, LevelAssets>,
+}
+
+impl<'s> LevelAssetsLoader<'s> {
+    /// Does nothing if the level is already loaded or being loaded.
+    pub fn load(&mut self, key: LevelKey) {
+        if self
+            .level_assets
+            .0
+            .as_ref()
+            .map_or(false, |level_asset_handles| level_asset_handles.key == key)
+        {
+            return;
+        }
+        log::info!("Loading the assets of {:?}", key);
+
+        let mut progress = ProgressCounter::new();
+        let (color_grading, script_error) = match key.script() {
+            Ok(script) => (
+                script.map_or(ColorGrading::default(), |script| script.color_grading),
+                None,
+            ),
+            Err(err) => (ColorGrading::default(), Some(err)),
+        };
+
+        let landscape_texture = self.loader.load(
+            key.landscape_texture_path(),
+            ImageFormat::default(),
+            &mut progress,
+            &self.texture_storage,
+        );
+        let landscape = self.loader.load(
+            key.landscape_sprite_sheet_path(),
+            SpriteSheetFormat(landscape_texture),
+            &mut progress,
+            &self.sprite_sheet_storage,
+        );
+        // Lookup tables are sampled with linear filtering and store colors as they are,
+        // without sRGB.
+        let color_grading_lut = self.loader.load(
+            color_grading.lut_path(),
+            ImageFormat(ImageTextureConfig {
+                repr: Repr::Unorm,
+                sampler_info: SamplerInfo::new(Filter::Linear, WrapMode::Clamp),
+                ..ImageTextureConfig::default()
+            }),
+            &mut progress,
+            &self.texture_storage,
+        );
+        // The announcer lines keep the progress counter they were loaded with, so a level
+        // that starts before they've finished loading still waits for them.
+        let (announcer, announcer_progress) = match self.level_assets.0.take() {
+            Some(level_asset_handles) => (
+                level_asset_handles.announcer,
+                level_asset_handles.announcer_progress,
+            ),
+            None => {
+                let mut announcer_progress = ProgressCounter::new();
+                let announcer = AnnouncerLine::ALL
+                    .iter()
+                    .map(|line| {
+                        self.loader.load(
+                            line.sound_path(),
+                            WavFormat,
+                            &mut announcer_progress,
+                            &self.source_storage,
+                        )
+                    })
+                    .collect();
+                (announcer, announcer_progress)
+            }
+        };
+
+        self.level_assets.0 = Some(LevelAssetHandles {
+            key,
+            progress,
+            script_error,
+            landscape,
+            color_grading_lut,
+            announcer,
+            announcer_progress,
+        });
+    }
+}
@@ -0,0 +1,260 @@
+//! FPS caps and frame pacing. Games are rendered at the focused cap, but never below
+//! `FRAME_RATE`: the simulation ticks at most once per frame (see `TickClock`), so slower
+//! frames would slow the game down. The unfocused cap only applies to menus and loading screens.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use gv_core::ecs::resources::FRAME_RATE;
+
+/// `FramePacer` sleeps until this much time is left before the next frame and spins for
+/// the rest of it, as sleeping alone can overshoot by a few milliseconds on some systems.
+pub const FRAME_PACING_SPIN: Duration = Duration::from_millis(2);
+
+/// The caps `FpsCap::next` goes through for the focused window.
+pub const FPS_CAPS: [FpsCap; 4] = [
+    FpsCap::Off,
+    FpsCap::Fps(60),
+    FpsCap::Fps(120),
+    FpsCap::Fps(144),
+];
+/// The caps `FpsCap::next` goes through while the window is in the background.
+pub const UNFOCUSED_FPS_CAPS: [FpsCap; 4] = [
+    FpsCap::Fps(15),
+    FpsCap::Fps(30),
+    FpsCap::Fps(60),
+    FpsCap::Off,
+];
+
+/// Set in `video.ron`, works the same whether vsync is on or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FpsCap {
+    Off,
+    Fps(u32),
+}
+
+impl FpsCap {
+    pub fn frame_rate(self) -> Option<u32> {
+        match self {
+            FpsCap::Off => None,
+            FpsCap::Fps(frame_rate) => Some(frame_rate.max(1)),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            FpsCap::Off => "Off".to_owned(),
+            FpsCap::Fps(frame_rate) => frame_rate.to_string(),
+        }
+    }
+
+    /// Caps that aren't in the list (e.g. set in `video.ron` by hand) go back to the first one.
+    pub fn next(self, caps: &[FpsCap]) -> Self {
+        caps.iter()
+            .position(|cap| *cap == self)
+            .and_then(|i| caps.get(i + 1))
+            .or_else(|| caps.first())
+            .copied()
+            .unwrap_or(self)
+    }
+}
+
+/// The frame rate frames are paced at, `None` if they aren't limited. The window is never
+/// drawn faster in the background than it would be if it was focused.
+pub fn frame_rate_limit(
+    fps_cap: FpsCap,
+    unfocused_fps_cap: FpsCap,
+    is_focused: bool,
+    is_playing: bool,
+) -> Option<u32> {
+    let focused_limit = fps_cap.frame_rate();
+    if is_playing {
+        return focused_limit.map(|focused_limit| focused_limit.max(FRAME_RATE));
+    }
+    if is_focused {
+        return focused_limit;
+    }
+    match (focused_limit, unfocused_fps_cap.frame_rate()) {
+        (Some(focused_limit), Some(unfocused_limit)) => Some(focused_limit.min(unfocused_limit)),
+        (focused_limit, unfocused_limit) => focused_limit.or(unfocused_limit),
+    }
+}
+
+/// Waits out the rest of every frame to keep them evenly spaced at the frame rate.
+#[derive(Debug, Default)]
+pub struct FramePacer {
+    frame_rate: Option<u32>,
+    frame_ends_at: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn frame_rate(&self) -> Option<u32> {
+        self.frame_rate
+    }
+
+    pub fn set_frame_rate(&mut self, frame_rate: Option<u32>) {
+        if self.frame_rate != frame_rate {
+            self.frame_rate = frame_rate;
+            self.frame_ends_at = None;
+        }
+    }
+
+    /// Returns when the current frame should end, a frame after the previous one ended.
+    /// Frames that are late end right away rather than shortening the following ones.
+    pub fn schedule(&mut self, now: Instant) -> Option<Instant> {
+        let frame_duration = Duration::from_secs(1) / self.frame_rate?;
+        let frame_ends_at = self
+            .frame_ends_at
+            .map(|previous_frame_end| previous_frame_end + frame_duration)
+            .filter(|frame_ends_at| *frame_ends_at > now)
+            .unwrap_or(now);
+        self.frame_ends_at = Some(frame_ends_at);
+        Some(frame_ends_at)
+    }
+
+    /// Sleeps until `FRAME_PACING_SPIN` is left before the end of the frame and spins
+    /// for the rest.
+    pub fn wait(&mut self) {
+        let frame_ends_at = match self.schedule(Instant::now()) {
+            Some(frame_ends_at) => frame_ends_at,
+            None => return,
+        };
+        loop {
+            let now = Instant::now();
+            if now >= frame_ends_at {
+                break;
+            }
+            let time_left = frame_ends_at - now;
+            if time_left > FRAME_PACING_SPIN {
+                thread::sleep(time_left - FRAME_PACING_SPIN);
+            } else {
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gv_core::ecs::resources::GameTime;
+
+    use super::*;
+
+    #[test]
+    fn games_run_at_the_focused_cap_but_not_below_the_frame_rate() {
+        for unfocused_fps_cap in UNFOCUSED_FPS_CAPS.iter() {
+            for is_focused in [false, true].iter() {
+                let limit =
+                    |fps_cap| frame_rate_limit(fps_cap, *unfocused_fps_cap, *is_focused, true);
+                assert_eq!(limit(FpsCap::Off), None);
+                assert_eq!(limit(FpsCap::Fps(144)), Some(144));
+                assert_eq!(limit(FpsCap::Fps(30)), Some(FRAME_RATE));
+            }
+        }
+    }
+
+    #[test]
+    fn menus_use_the_cap_of_the_window_focus() {
+        assert_eq!(
+            frame_rate_limit(FpsCap::Fps(144), FpsCap::Fps(15), true, false),
+            Some(144)
+        );
+        assert_eq!(
+            frame_rate_limit(FpsCap::Off, FpsCap::Fps(15), true, false),
+            None
+        );
+        assert_eq!(
+            frame_rate_limit(FpsCap::Fps(144), FpsCap::Fps(15), false, false),
+            Some(15)
+        );
+        assert_eq!(
+            frame_rate_limit(FpsCap::Off, FpsCap::Fps(30), false, false),
+            Some(30)
+        );
+    }
+
+    #[test]
+    fn unfocused_windows_are_never_drawn_faster_than_focused_ones() {
+        assert_eq!(
+            frame_rate_limit(FpsCap::Fps(60), FpsCap::Off, false, false),
+            Some(60)
+        );
+        assert_eq!(
+            frame_rate_limit(FpsCap::Fps(60), FpsCap::Fps(120), false, false),
+            Some(60)
+        );
+        assert_eq!(
+            frame_rate_limit(FpsCap::Off, FpsCap::Off, false, false),
+            None
+        );
+    }
+
+    #[test]
+    fn caps_cycle_through_the_presets() {
+        assert_eq!(FpsCap::Off.next(&FPS_CAPS), FpsCap::Fps(60));
+        assert_eq!(FpsCap::Fps(144).next(&FPS_CAPS), FpsCap::Off);
+        assert_eq!(FpsCap::Off.next(&UNFOCUSED_FPS_CAPS), FpsCap::Fps(15));
+        // Caps set in `video.ron` by hand start over.
+        assert_eq!(FpsCap::Fps(75).next(&FPS_CAPS), FpsCap::Off);
+        assert_eq!(FpsCap::Fps(0).frame_rate(), Some(1));
+    }
+
+    #[test]
+    fn frames_are_spaced_evenly_unless_late() {
+        let frame = Duration::from_secs(1) / 100;
+        let start = Instant::now();
+        let mut frame_pacer = FramePacer::default();
+        assert_eq!(frame_pacer.schedule(start), None);
+
+        frame_pacer.set_frame_rate(Some(100));
+        assert_eq!(frame_pacer.schedule(start), Some(start));
+        // A frame that took a bit longer still ends on schedule.
+        assert_eq!(frame_pacer.schedule(start + frame / 2), Some(start + frame));
+        // A late frame ends right away and the next ones are scheduled after it.
+        let late = start + frame * 5;
+        assert_eq!(frame_pacer.schedule(late), Some(late));
+        assert_eq!(frame_pacer.schedule(late), Some(late + frame));
+
+        frame_pacer.set_frame_rate(None);
+        assert_eq!(frame_pacer.schedule(late), None);
+    }
+
+    fn tick_frames(game_time: &mut GameTime, frame: Duration, frames: usize) -> usize {
+        (0..frames)
+            .filter(|_| {
+                game_time.advance_tick_clock(frame);
+                game_time.tick_clock.unwrap().is_tick()
+            })
+            .count()
+    }
+
+    #[test]
+    fn the_simulation_speed_does_not_depend_on_the_frame_rate() {
+        for frame_rate in [60, 120, 144, 1000].iter() {
+            let mut game_time = GameTime::with_tick_rate(60, FRAME_RATE);
+            game_time.start_tick_clock();
+            let frame = Duration::from_secs(1) / *frame_rate;
+            let ticks = tick_frames(&mut game_time, frame, *frame_rate as usize * 10);
+            assert!(
+                (599..=601).contains(&ticks),
+                "{} FPS: {}",
+                frame_rate,
+                ticks
+            );
+            assert_eq!(game_time.tick_clock.unwrap().ticks(), ticks as u64);
+        }
+    }
+
+    #[test]
+    fn the_tick_clock_ticks_at_most_once_per_frame() {
+        let mut game_time = GameTime::with_tick_rate(60, FRAME_RATE);
+        game_time.start_tick_clock();
+        // A long frame is caught up with on the next ones, but only by a few ticks.
+        assert_eq!(tick_frames(&mut game_time, Duration::from_secs(1), 1), 1);
+        assert_eq!(tick_frames(&mut game_time, Duration::from_secs(0), 100), 3);
+    }
+}
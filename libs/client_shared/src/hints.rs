@@ -0,0 +1,167 @@
+//! Contextual HUD hints, each shown once the first time its situation comes up. Hints name
+//! the keys that are actually bound to their actions, so they stay right after the bindings
+//! are edited in `bindings_config.ron`.
+
+use amethyst::{
+    input::{Bindings, Button, StringBindings},
+    winit::MouseButton,
+};
+use serde_derive::{Deserialize, Serialize};
+
+/// A hint stays on the HUD for this long.
+pub const HINT_DURATION_SECS: f32 = 6.0;
+/// The local player's health has to fall below this share to show `Hint::Heal`.
+pub const LOW_HEALTH_FRACTION: f32 = 0.35;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hint {
+    /// The local player is low on health for the first time.
+    Heal,
+    /// A lever is within reach.
+    Interact,
+    /// An intermission has started.
+    ReadyForWave,
+}
+
+impl Hint {
+    /// Hints are checked in this order, only one is shown at a time.
+    pub const ALL: [Hint; 3] = [Hint::Heal, Hint::Interact, Hint::ReadyForWave];
+
+    pub fn text(self, bindings: &Bindings<StringBindings>) -> String {
+        match self {
+            Hint::Heal => format!(
+                "Low on health! Hold {} to heal yourself",
+                action_keys(bindings, "cast_heal")
+            ),
+            Hint::Interact => format!(
+                "Press {} to pull the lever",
+                action_keys(bindings, "interact")
+            ),
+            Hint::ReadyForWave => format!(
+                "Press {} when you're ready for the next wave",
+                action_keys(bindings, "ready_for_wave")
+            ),
+        }
+    }
+}
+
+/// Returns the keys bound to the action, combinations are joined with `+` and alternative
+/// bindings with `/`. Is "(unbound)" if the action has no bindings.
+pub fn action_keys(bindings: &Bindings<StringBindings>, action: &str) -> String {
+    let keys = bindings
+        .action_bindings(action)
+        .map(|combination| {
+            combination
+                .iter()
+                .map(button_name)
+                .collect::<Vec<_>>()
+                .join("+")
+        })
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        "(unbound)".to_owned()
+    } else {
+        keys.join(" / ")
+    }
+}
+
+pub fn button_name(button: &Button) -> String {
+    match button {
+        Button::Key(key) => {
+            let name = format!("{:?}", key);
+            // Number keys are called `Key1`, `Key2` and so on.
+            let is_number_key = name.len() > 3
+                && name.starts_with("Key")
+                && name[3..].chars().all(|c| c.is_ascii_digit());
+            if is_number_key {
+                name[3..].to_owned()
+            } else {
+                name
+            }
+        }
+        Button::ScanCode(scan_code) => format!("Scan code {}", scan_code),
+        Button::Mouse(MouseButton::Left) => "LMB".to_owned(),
+        Button::Mouse(MouseButton::Right) => "RMB".to_owned(),
+        Button::Mouse(MouseButton::Middle) => "MMB".to_owned(),
+        Button::Mouse(MouseButton::Other(number)) => format!("Mouse {}", number),
+        Button::MouseWheel(direction) => format!("Wheel {:?}", direction),
+        Button::Controller(_, button) => format!("{:?}", button),
+    }
+}
+
+/// Returns the first hint whose situation has come up and that hasn't been shown yet
+/// (see `PlayerProfile::shown_hints`).
+pub fn next_hint(shown_hints: &[Hint], is_situation: impl Fn(Hint) -> bool) -> Option<Hint> {
+    Hint::ALL
+        .iter()
+        .copied()
+        .find(|hint| !shown_hints.contains(hint) && is_situation(*hint))
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::winit::VirtualKeyCode;
+
+    use super::*;
+
+    fn bindings() -> Bindings<StringBindings> {
+        let mut bindings = Bindings::<StringBindings>::new();
+        bindings
+            .insert_action_binding("interact".to_owned(), vec![Button::Key(VirtualKeyCode::G)])
+            .unwrap();
+        bindings
+            .insert_action_binding(
+                "interact".to_owned(),
+                vec![Button::Mouse(MouseButton::Middle)],
+            )
+            .unwrap();
+        bindings
+            .insert_action_binding(
+                "cast_heal".to_owned(),
+                vec![Button::Mouse(MouseButton::Right)],
+            )
+            .unwrap();
+        bindings
+            .insert_action_binding(
+                "ready_for_wave".to_owned(),
+                vec![
+                    Button::Key(VirtualKeyCode::LControl),
+                    Button::Key(VirtualKeyCode::Key4),
+                ],
+            )
+            .unwrap();
+        bindings
+    }
+
+    #[test]
+    fn buttons_are_named_for_the_hud() {
+        assert_eq!(button_name(&Button::Key(VirtualKeyCode::Key1)), "1");
+        assert_eq!(button_name(&Button::Key(VirtualKeyCode::F4)), "F4");
+        assert_eq!(button_name(&Button::Mouse(MouseButton::Right)), "RMB");
+    }
+
+    #[test]
+    fn hints_name_the_bound_keys() {
+        let bindings = bindings();
+        assert_eq!(action_keys(&bindings, "interact"), "G / MMB");
+        assert_eq!(action_keys(&bindings, "ready_for_wave"), "LControl+4");
+        assert_eq!(action_keys(&bindings, "summon"), "(unbound)");
+        assert!(Hint::Interact.text(&bindings).contains("G / MMB"));
+        assert!(Hint::Heal.text(&bindings).contains("RMB"));
+    }
+
+    #[test]
+    fn hints_are_shown_once_when_their_situation_comes_up() {
+        assert_eq!(next_hint(&[], |_| false), None);
+        assert_eq!(
+            next_hint(&[], |hint| hint == Hint::ReadyForWave),
+            Some(Hint::ReadyForWave)
+        );
+        assert_eq!(
+            next_hint(&[Hint::ReadyForWave], |hint| hint == Hint::ReadyForWave),
+            None
+        );
+        // Only one hint is shown at a time, in the order of `Hint::ALL`.
+        assert_eq!(next_hint(&[Hint::Heal], |_| true), Some(Hint::Interact));
+    }
+}
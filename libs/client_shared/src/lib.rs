@@ -1,4 +1,13 @@
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub mod accessibility;
 pub mod ecs;
+pub mod frame_pacing;
+pub mod hints;
+pub mod lighting;
+pub mod music;
+pub mod post_processing;
+pub mod render_scale;
 pub mod settings;
+pub mod sprite_atlas;
+pub mod ui_theme;
 pub mod utils;
@@ -0,0 +1,232 @@
+//! 2D lighting: clients darken a level down to its ambient light level, point lights attached
+//! to players, missiles and torches brighten it back up. Lights are stopped by the cells of
+//! `LevelCollisionGrid`: the lit area of a light is built from rays cast around it, casting
+//! them from several points around the light makes the shadow edges soft.
+
+use serde_derive::{Deserialize, Serialize};
+
+use gv_core::{ecs::resources::collision::LevelCollisionGrid, level_scripts::Torch, math::Vector2};
+
+/// Soft shadows are cast from points on a circle of this radius around a light.
+pub const LIGHT_SOURCE_RADIUS: f32 = 12.0;
+
+/// Set in `video.ron`, higher tiers cast more rays and shadows from more points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LightingQuality {
+    /// Levels are drawn in daylight.
+    Off,
+    /// Lights go through obstacles.
+    Low,
+    Medium,
+    /// Shadows have soft edges.
+    High,
+}
+
+impl LightingQuality {
+    pub fn name(self) -> &'static str {
+        match self {
+            LightingQuality::Off => "Off",
+            LightingQuality::Low => "Low",
+            LightingQuality::Medium => "Medium",
+            LightingQuality::High => "High",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            LightingQuality::Off => LightingQuality::Low,
+            LightingQuality::Low => LightingQuality::Medium,
+            LightingQuality::Medium => LightingQuality::High,
+            LightingQuality::High => LightingQuality::Off,
+        }
+    }
+
+    /// The lights closest to the center of the screen are drawn first, the rest are skipped.
+    pub fn max_lights(self) -> usize {
+        match self {
+            LightingQuality::Off => 0,
+            LightingQuality::Low => 16,
+            LightingQuality::Medium => 32,
+            LightingQuality::High => 64,
+        }
+    }
+
+    /// Rays cast around every light, the lit area is a polygon through their ends.
+    pub fn rays(self) -> usize {
+        match self {
+            LightingQuality::Off => 0,
+            LightingQuality::Low => 24,
+            LightingQuality::Medium => 64,
+            LightingQuality::High => 128,
+        }
+    }
+
+    /// Points around a light that shadows are cast from, zero if lights ignore obstacles.
+    pub fn shadow_samples(self) -> usize {
+        match self {
+            LightingQuality::Off | LightingQuality::Low => 0,
+            LightingQuality::Medium => 1,
+            LightingQuality::High => 4,
+        }
+    }
+}
+
+impl Default for LightingQuality {
+    fn default() -> Self {
+        LightingQuality::Medium
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: Vector2,
+    pub radius: f32,
+    /// Light added at the center, fades out towards the radius.
+    pub intensity: f32,
+}
+
+impl PointLight {
+    pub fn from_torch(torch: &Torch) -> Self {
+        Self {
+            position: Vector2::new(torch.position.0, torch.position.1),
+            radius: torch.radius,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Keeps the lights reaching into the area between `view_min` and `view_max`,
+/// up to `max_lights` of the ones closest to its center.
+pub fn retain_visible_lights(
+    lights: &mut Vec<PointLight>,
+    view_min: Vector2,
+    view_max: Vector2,
+    max_lights: usize,
+) {
+    lights.retain(|light| {
+        light.position.x + light.radius >= view_min.x
+            && light.position.x - light.radius <= view_max.x
+            && light.position.y + light.radius >= view_min.y
+            && light.position.y - light.radius <= view_max.y
+    });
+    if lights.len() > max_lights {
+        let center = (view_min + view_max) / 2.0;
+        lights.sort_by(|a, b| {
+            (a.position - center)
+                .norm_squared()
+                .partial_cmp(&(b.position - center).norm_squared())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        lights.truncate(max_lights);
+    }
+}
+
+/// Points around a light that its shadows are cast from, just its position for one sample.
+pub fn shadow_origins(position: Vector2, samples: usize) -> Vec<Vector2> {
+    if samples <= 1 {
+        return vec![position];
+    }
+    (0..samples)
+        .map(|i| {
+            let angle = i as f32 / samples as f32 * std::f32::consts::PI * 2.0;
+            position + Vector2::new(angle.cos(), angle.sin()) * LIGHT_SOURCE_RADIUS
+        })
+        .collect()
+}
+
+/// Replaces `shape` with the ends of `rays` rays cast from `origin` counter-clockwise,
+/// starting along the x axis. Rays are `radius` long, unless they hit a blocked cell of `grid`.
+pub fn light_shape(
+    grid: Option<&LevelCollisionGrid>,
+    origin: Vector2,
+    radius: f32,
+    rays: usize,
+    shape: &mut Vec<Vector2>,
+) {
+    shape.clear();
+    shape.extend((0..rays).map(|i| {
+        let angle = i as f32 / rays as f32 * std::f32::consts::PI * 2.0;
+        let end = origin + Vector2::new(angle.cos(), angle.sin()) * radius;
+        grid.and_then(|grid| grid.raycast(origin, end))
+            .map_or(end, |hit| hit.position)
+    }));
+    if shape.iter().all(|point| *point == origin) {
+        // The light is inside of a blocked cell.
+        shape.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gv_core::ecs::resources::collision::COLLISION_CELL_SIZE;
+
+    use super::*;
+
+    fn light(x: f32, y: f32) -> PointLight {
+        PointLight {
+            position: Vector2::new(x, y),
+            radius: 100.0,
+            intensity: 1.0,
+        }
+    }
+
+    #[test]
+    fn quality_tiers_cycle_and_grow() {
+        let mut quality = LightingQuality::Off;
+        let mut tiers = Vec::new();
+        for _ in 0..4 {
+            quality = quality.next();
+            tiers.push(quality);
+        }
+        assert_eq!(
+            tiers,
+            vec![
+                LightingQuality::Low,
+                LightingQuality::Medium,
+                LightingQuality::High,
+                LightingQuality::Off,
+            ]
+        );
+        assert_eq!(LightingQuality::Off.max_lights(), 0);
+        assert_eq!(LightingQuality::Low.shadow_samples(), 0);
+        assert!(LightingQuality::High.rays() > LightingQuality::Medium.rays());
+        assert!(LightingQuality::High.shadow_samples() > LightingQuality::Medium.shadow_samples());
+    }
+
+    #[test]
+    fn lights_stop_at_blocked_cells() {
+        let mut grid = LevelCollisionGrid::new(Vector2::new(1024.0, 1024.0), COLLISION_CELL_SIZE);
+        grid.set_area_blocked(Vector2::new(64.0, -256.0), Vector2::new(95.0, 256.0), true);
+
+        let mut shape = Vec::new();
+        light_shape(None, Vector2::new(0.0, 0.0), 100.0, 4, &mut shape);
+        assert_eq!(shape.len(), 4);
+        assert!((shape[0] - Vector2::new(100.0, 0.0)).norm() < 0.001);
+
+        light_shape(Some(&grid), Vector2::new(0.0, 0.0), 100.0, 4, &mut shape);
+        assert!((shape[0] - Vector2::new(64.0, 0.0)).norm() < 0.001);
+        assert!((shape[1] - Vector2::new(0.0, 100.0)).norm() < 0.001);
+        assert!((shape[2] - Vector2::new(-100.0, 0.0)).norm() < 0.001);
+
+        light_shape(Some(&grid), Vector2::new(80.0, 0.0), 100.0, 4, &mut shape);
+        assert!(shape.is_empty());
+    }
+
+    #[test]
+    fn only_the_closest_visible_lights_are_kept() {
+        let mut lights = vec![
+            light(500.0, 0.0),
+            light(-150.0, 0.0),
+            light(0.0, 0.0),
+            light(1000.0, 1000.0),
+            light(90.0, 50.0),
+        ];
+        let view_min = Vector2::new(-200.0, -200.0);
+        let view_max = Vector2::new(200.0, 200.0);
+        retain_visible_lights(&mut lights, view_min, view_max, 10);
+        assert_eq!(lights.len(), 3);
+
+        retain_visible_lights(&mut lights, view_min, view_max, 2);
+        assert_eq!(lights, vec![light(0.0, 0.0), light(90.0, 50.0)]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Intensity of the music played during a game. Clients compute it from the replicated world
+//! state around the local player and crossfade the layered music stems by it, so nothing
+//! about the music is sent over the network.
+
+/// Monsters closer than this to the local player count towards the intensity.
+pub const NEARBY_MONSTER_RADIUS: f32 = 400.0;
+/// The combat stem plays at full volume with this many monsters nearby.
+const MAX_NEARBY_MONSTERS: usize = 12;
+/// Missing health adds up to this much intensity while monsters are nearby.
+const LOW_HEALTH_INTENSITY: f32 = 0.4;
+/// An elite nearby keeps the intensity at least this high.
+const BOSS_INTENSITY: f32 = 0.8;
+
+/// Stems loop together with the same length, only their volumes change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicStem {
+    Calm,
+    Combat,
+    /// Replaces the combat stem while an elite is nearby.
+    Boss,
+}
+
+impl MusicStem {
+    pub const ALL: [MusicStem; 3] = [MusicStem::Calm, MusicStem::Combat, MusicStem::Boss];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MusicIntensity {
+    /// From 0 (calm) to 1 (full combat).
+    pub value: f32,
+    pub is_boss_active: bool,
+}
+
+impl MusicIntensity {
+    pub fn new(nearby_monsters: usize, is_boss_active: bool, health_fraction: f32) -> Self {
+        if nearby_monsters == 0 && !is_boss_active {
+            return Self::default();
+        }
+        let crowd = (nearby_monsters as f32 / MAX_NEARBY_MONSTERS as f32).min(1.0);
+        let missing_health = 1.0 - health_fraction.max(0.0).min(1.0);
+        let mut value = (crowd + missing_health * LOW_HEALTH_INTENSITY).min(1.0);
+        if is_boss_active {
+            value = value.max(BOSS_INTENSITY);
+        }
+        Self {
+            value,
+            is_boss_active,
+        }
+    }
+
+    /// Volumes of the stems in the order of `MusicStem::ALL`, they always add up to 1.
+    pub fn volumes(self) -> [f32; 3] {
+        let (combat, boss) = if self.is_boss_active {
+            (0.0, self.value)
+        } else {
+            (self.value, 0.0)
+        };
+        [1.0 - self.value, combat, boss]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calm_music_plays_without_monsters_nearby() {
+        assert_eq!(
+            MusicIntensity::new(0, false, 1.0).volumes(),
+            [1.0, 0.0, 0.0]
+        );
+        // Missing health alone doesn't start the combat music.
+        assert_eq!(
+            MusicIntensity::new(0, false, 0.1).volumes(),
+            [1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn intensity_grows_with_monsters_and_missing_health() {
+        let few = MusicIntensity::new(3, false, 1.0);
+        let many = MusicIntensity::new(9, false, 1.0);
+        let hurt = MusicIntensity::new(3, false, 0.25);
+        assert!(few.value > 0.0);
+        assert!(many.value > few.value);
+        assert!(hurt.value > few.value);
+        assert_eq!(
+            MusicIntensity::new(100, false, 0.0).volumes(),
+            [0.0, 1.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn boss_stem_replaces_combat_one() {
+        let volumes = MusicIntensity::new(1, true, 1.0).volumes();
+        assert_eq!(volumes[1], 0.0);
+        assert!(volumes[2] > volumes[0]);
+
+        for intensity in &[
+            MusicIntensity::new(5, false, 0.5),
+            MusicIntensity::new(5, true, 0.5),
+        ] {
+            let total: f32 = intensity.volumes().iter().sum();
+            assert!((total - 1.0).abs() < 0.0001);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Post-processing: effects that clients apply to the whole frame when it's drawn to the window.
+//! Bloom and the per-level color grading (`gv_core::level_scripts::ColorGrading`) are always
+//! there, chromatic aberration flashes when the local player gets hit and the screen turns red
+//! while their health is low.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// The red tint starts below this share of the max health and is the strongest at zero.
+pub const LOW_HEALTH_THRESHOLD: f32 = 0.35;
+/// How long chromatic aberration takes to fade out after a hit.
+pub const HIT_FLASH_SECS: f32 = 0.4;
+
+/// Every effect can be turned off separately in `video.ron`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessingSettings {
+    #[serde(default = "default_enabled")]
+    pub bloom: bool,
+    #[serde(default = "default_enabled")]
+    pub chromatic_aberration: bool,
+    #[serde(default = "default_enabled")]
+    pub color_grading: bool,
+    #[serde(default = "default_enabled")]
+    pub low_health_tint: bool,
+}
+
+impl Default for PostProcessingSettings {
+    fn default() -> Self {
+        Self {
+            bloom: true,
+            chromatic_aberration: true,
+            color_grading: true,
+            low_health_tint: true,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// From 0.0 at `LOW_HEALTH_THRESHOLD` of the max health and above to 1.0 at zero health.
+pub fn low_health_tint(health: f32, max_health: f32) -> f32 {
+    if max_health <= 0.0 {
+        return 0.0;
+    }
+    let share = (health / max_health).max(0.0);
+    (1.0 - share / LOW_HEALTH_THRESHOLD).max(0.0)
+}
+
+/// Follows the health of the local player and restarts whenever it drops.
+#[derive(Debug, Default)]
+pub struct HitFlash {
+    last_health: Option<f32>,
+    strength: f32,
+}
+
+impl HitFlash {
+    /// Returns the flash strength, from 1.0 right after a hit to 0.0. `None` health
+    /// (no local player, e.g. between games) resets it.
+    pub fn update(&mut self, health: Option<f32>, delta_seconds: f32) -> f32 {
+        self.strength = (self.strength - delta_seconds / HIT_FLASH_SECS).max(0.0);
+        let last_health = std::mem::replace(&mut self.last_health, health);
+        match (last_health, health) {
+            (Some(last_health), Some(health)) if health < last_health => self.strength = 1.0,
+            (_, None) => self.strength = 0.0,
+            _ => {}
+        }
+        self.strength
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_health_tints_the_screen() {
+        assert_eq!(low_health_tint(100.0, 100.0), 0.0);
+        assert_eq!(low_health_tint(LOW_HEALTH_THRESHOLD * 100.0, 100.0), 0.0);
+        assert!((low_health_tint(LOW_HEALTH_THRESHOLD * 50.0, 100.0) - 0.5).abs() < 0.001);
+        assert_eq!(low_health_tint(0.0, 100.0), 1.0);
+        assert_eq!(low_health_tint(-10.0, 100.0), 1.0);
+        assert_eq!(low_health_tint(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn hits_flash_and_fade_out() {
+        let mut hit_flash = HitFlash::default();
+        assert_eq!(hit_flash.update(Some(100.0), 0.016), 0.0);
+        assert_eq!(hit_flash.update(Some(90.0), 0.016), 1.0);
+        let strength = hit_flash.update(Some(90.0), HIT_FLASH_SECS / 2.0);
+        assert!((strength - 0.5).abs() < 0.001);
+        assert_eq!(hit_flash.update(Some(95.0), HIT_FLASH_SECS), 0.0);
+
+        hit_flash.update(Some(80.0), 0.016);
+        assert_eq!(hit_flash.update(None, 0.016), 0.0);
+        assert_eq!(hit_flash.update(Some(50.0), 0.016), 0.0);
+    }
+
+    #[test]
+    fn every_effect_is_on_by_default() {
+        let settings = PostProcessingSettings::default();
+        assert!(settings.bloom);
+        assert!(settings.chromatic_aberration);
+        assert!(settings.color_grading);
+        assert!(settings.low_health_tint);
+    }
+}
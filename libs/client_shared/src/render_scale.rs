@@ -0,0 +1,246 @@
+//! Dynamic resolution: the world is rendered at a fraction of the window size while frames
+//! take longer than the budget, and is scaled up to the window when drawn. Changing the scale
+//! rebuilds the render graph, so it moves between a few fixed steps and waits between them.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::time::Duration;
+
+/// The steps the controller moves between, from the full window resolution down.
+pub const RENDER_SCALES: [f32; 5] = [1.0, 0.85, 0.75, 0.6, 0.5];
+/// Frame times are averaged over this many frames before the controller decides anything.
+pub const RENDER_SCALE_SAMPLE_FRAMES: u32 = 30;
+/// Frames right after a change are skipped, as rebuilding the render graph makes them slow.
+pub const RENDER_SCALE_SETTLING_FRAMES: u32 = 30;
+/// The average frame time lowers the scale once it exceeds the budget by this factor.
+const OVER_BUDGET_FACTOR: f32 = 1.15;
+/// How many averaged windows on budget it takes to try a higher scale. The count doubles
+/// each time a higher scale turns out to be too slow, up to the max.
+const MIN_WINDOWS_BEFORE_RAISE: u32 = 4;
+const MAX_WINDOWS_BEFORE_RAISE: u32 = 64;
+
+/// Set in `video.ron`, `Fixed` overrides the controller with one of `RENDER_SCALES`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RenderScaleSetting {
+    Dynamic,
+    Fixed(f32),
+}
+
+impl RenderScaleSetting {
+    pub fn name(self) -> String {
+        match self {
+            RenderScaleSetting::Dynamic => "Dynamic".to_owned(),
+            RenderScaleSetting::Fixed(scale) => format!("{}%", (scale * 100.0).round()),
+        }
+    }
+
+    /// Goes from `Dynamic` through `RENDER_SCALES` and back.
+    pub fn next(self) -> Self {
+        match self {
+            RenderScaleSetting::Dynamic => RenderScaleSetting::Fixed(RENDER_SCALES[0]),
+            RenderScaleSetting::Fixed(scale) => RENDER_SCALES
+                .iter()
+                .find(|render_scale| **render_scale < scale)
+                .map_or(RenderScaleSetting::Dynamic, |render_scale| {
+                    RenderScaleSetting::Fixed(*render_scale)
+                }),
+        }
+    }
+}
+
+impl Default for RenderScaleSetting {
+    fn default() -> Self {
+        RenderScaleSetting::Dynamic
+    }
+}
+
+/// Picks a step of `RENDER_SCALES` from the frame times. Frames are capped by the frame
+/// limiter and vsync, so being under budget can't be measured: once frames have been on
+/// budget for a while, a higher scale is tried and is given up if it's too slow.
+#[derive(Debug)]
+pub struct RenderScaleController {
+    step: usize,
+    frame_times_sum: Duration,
+    sampled_frames: u32,
+    settling_frames: u32,
+    windows_on_budget: u32,
+    windows_before_raise: u32,
+    is_trying_higher_scale: bool,
+}
+
+impl Default for RenderScaleController {
+    fn default() -> Self {
+        Self {
+            step: 0,
+            frame_times_sum: Duration::default(),
+            sampled_frames: 0,
+            settling_frames: 0,
+            windows_on_budget: 0,
+            windows_before_raise: MIN_WINDOWS_BEFORE_RAISE,
+            is_trying_higher_scale: false,
+        }
+    }
+}
+
+impl RenderScaleController {
+    pub fn scale(&self) -> f32 {
+        RENDER_SCALES[self.step]
+    }
+
+    /// Drops the frames sampled so far and lets frames settle once it's updated again,
+    /// for the time frames aren't paced by the budget, such as while the window is unfocused.
+    pub fn pause(&mut self) {
+        self.clear_samples();
+        self.settling_frames = RENDER_SCALE_SETTLING_FRAMES;
+    }
+
+    /// Returns the new scale if it has changed.
+    pub fn update(&mut self, frame_time: Duration, budget: Duration) -> Option<f32> {
+        if self.settling_frames > 0 {
+            self.settling_frames -= 1;
+            return None;
+        }
+        self.frame_times_sum += frame_time;
+        self.sampled_frames += 1;
+        if self.sampled_frames < RENDER_SCALE_SAMPLE_FRAMES {
+            return None;
+        }
+        let average_frame_time = self.frame_times_sum / self.sampled_frames;
+        self.clear_samples();
+
+        let was_trying_higher_scale = std::mem::replace(&mut self.is_trying_higher_scale, false);
+        if average_frame_time.as_secs_f32() > budget.as_secs_f32() * OVER_BUDGET_FACTOR {
+            self.windows_on_budget = 0;
+            if was_trying_higher_scale {
+                self.windows_before_raise =
+                    (self.windows_before_raise * 2).min(MAX_WINDOWS_BEFORE_RAISE);
+            }
+            if self.step + 1 == RENDER_SCALES.len() {
+                return None;
+            }
+            self.step += 1;
+            self.settling_frames = RENDER_SCALE_SETTLING_FRAMES;
+            return Some(self.scale());
+        }
+
+        if was_trying_higher_scale {
+            self.windows_before_raise =
+                (self.windows_before_raise / 2).max(MIN_WINDOWS_BEFORE_RAISE);
+        }
+        self.windows_on_budget += 1;
+        if self.step == 0 || self.windows_on_budget < self.windows_before_raise {
+            return None;
+        }
+        self.windows_on_budget = 0;
+        self.step -= 1;
+        self.is_trying_higher_scale = true;
+        self.settling_frames = RENDER_SCALE_SETTLING_FRAMES;
+        Some(self.scale())
+    }
+
+    fn clear_samples(&mut self) {
+        self.frame_times_sum = Duration::default();
+        self.sampled_frames = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_micros(16_667);
+    const SLOW_FRAME: Duration = Duration::from_millis(25);
+
+    /// Returns the scale changes over a number of windows of frames taking `frame_time`.
+    fn run_windows(
+        controller: &mut RenderScaleController,
+        frame_time: Duration,
+        windows: u32,
+    ) -> Vec<f32> {
+        (0..windows * RENDER_SCALE_SAMPLE_FRAMES)
+            .filter_map(|_| controller.update(frame_time, BUDGET))
+            .collect()
+    }
+
+    fn settle(controller: &mut RenderScaleController) {
+        for _ in 0..RENDER_SCALE_SETTLING_FRAMES {
+            assert_eq!(controller.update(SLOW_FRAME, BUDGET), None);
+        }
+    }
+
+    #[test]
+    fn slow_frames_lower_the_scale_step_by_step() {
+        let mut controller = RenderScaleController::default();
+        assert_eq!(run_windows(&mut controller, BUDGET, 10), Vec::<f32>::new());
+
+        assert_eq!(
+            run_windows(&mut controller, SLOW_FRAME, 1),
+            vec![RENDER_SCALES[1]]
+        );
+        settle(&mut controller);
+        assert_eq!(
+            run_windows(&mut controller, SLOW_FRAME, 1),
+            vec![RENDER_SCALES[2]]
+        );
+        for _ in 2..RENDER_SCALES.len() - 1 {
+            settle(&mut controller);
+            run_windows(&mut controller, SLOW_FRAME, 1);
+        }
+        assert_eq!(controller.scale(), RENDER_SCALES[RENDER_SCALES.len() - 1]);
+        assert_eq!(
+            run_windows(&mut controller, SLOW_FRAME, 4),
+            Vec::<f32>::new()
+        );
+    }
+
+    #[test]
+    fn higher_scales_are_tried_less_often_after_failing() {
+        let mut controller = RenderScaleController::default();
+        run_windows(&mut controller, SLOW_FRAME, 1);
+        settle(&mut controller);
+        run_windows(&mut controller, SLOW_FRAME, 1);
+        assert_eq!(controller.scale(), RENDER_SCALES[2]);
+        settle(&mut controller);
+
+        assert_eq!(
+            run_windows(&mut controller, BUDGET, 4),
+            vec![RENDER_SCALES[1]]
+        );
+        // The higher scale is too slow, so the next try waits twice as long.
+        settle(&mut controller);
+        assert_eq!(
+            run_windows(&mut controller, SLOW_FRAME, 1),
+            vec![RENDER_SCALES[2]]
+        );
+        settle(&mut controller);
+        assert_eq!(run_windows(&mut controller, BUDGET, 7), Vec::<f32>::new());
+        assert_eq!(
+            run_windows(&mut controller, BUDGET, 1),
+            vec![RENDER_SCALES[1]]
+        );
+    }
+
+    #[test]
+    fn paused_controller_skips_frames() {
+        let mut controller = RenderScaleController::default();
+        for _ in 0..RENDER_SCALE_SAMPLE_FRAMES {
+            controller.pause();
+            assert_eq!(controller.update(Duration::from_millis(66), BUDGET), None);
+        }
+        assert_eq!(controller.scale(), RENDER_SCALES[0]);
+    }
+
+    #[test]
+    fn render_scale_setting_cycles_through_fixed_scales() {
+        let mut setting = RenderScaleSetting::default();
+        let mut names = Vec::new();
+        for _ in 0..=RENDER_SCALES.len() {
+            names.push(setting.name());
+            setting = setting.next();
+        }
+        assert_eq!(names, vec!["Dynamic", "100%", "85%", "75%", "60%", "50%"]);
+        assert_eq!(setting, RenderScaleSetting::Dynamic);
+    }
+}
@@ -2,12 +2,30 @@ use amethyst::{
     config::Config,
     input::{Bindings, Button, StringBindings},
     window::{DisplayConfig, MonitorIdent},
-    winit::{VirtualKeyCode},
+    winit::VirtualKeyCode,
 };
-use directories::ProjectDirs;
 use ron::ser::PrettyConfig;
+use serde_derive::{Deserialize, Serialize};
 
-use std::{fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gv_core::{
+    achievements::AchievementProgress,
+    classes::PlayerClass,
+    cosmetics::PlayerCosmetics,
+    ecs::resources::{world::PAUSE_FRAME_THRESHOLD, FRAME_RATE},
+    gv_dirs::GvDirs,
+};
+
+use crate::{
+    accessibility::AccessibilitySettings, frame_pacing::FpsCap, hints::Hint,
+    lighting::LightingQuality, post_processing::PostProcessingSettings,
+    render_scale::RenderScaleSetting, ui_theme::DEFAULT_UI_THEME,
+};
 
 static DEFAULT_BINDINGS_CONFIG_BYTES: &[u8] =
     include_bytes!("../../../resources/bindings_config.ron");
@@ -15,22 +33,271 @@ static DEFAULT_BINDINGS_CONFIG_BYTES: &[u8] =
 static DEFAULT_DISPLAY_CONFIG_BYTES: &[u8] =
     include_bytes!("../../../resources/display_config.ron");
 
+pub const DEFAULT_RATING: u32 = 1000;
+/// The rating a multiplayer match stands for grows by this much with every wave reached.
+const RATING_PER_WAVE: i64 = 50;
+/// The rating moves by this share of the difference with the rating of a match.
+const RATING_ADJUSTMENT_DIVISOR: i64 = 4;
+
+/// Moves the rating towards the one of the wave a multiplayer match has reached,
+/// so that it settles around the usual result of a player.
+pub fn rating_after_match(rating: u32, spawn_level: usize) -> u32 {
+    let rating = i64::from(rating);
+    let match_rating = spawn_level as i64 * RATING_PER_WAVE;
+    (rating + (match_rating - rating) / RATING_ADJUSTMENT_DIVISOR).max(0) as u32
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    /// Used by matchmaking to pair players of a similar skill.
+    pub rating: u32,
+    /// The best endless mode scores per map.
+    #[serde(default)]
+    pub personal_bests: HashMap<String, u64>,
+    /// The class picked last time, in single player or in a lobby.
+    #[serde(default)]
+    pub class: PlayerClass,
+    /// Is chosen in the customization menu and replicated to the other players.
+    #[serde(default)]
+    pub cosmetics: PlayerCosmetics,
+    /// HUD hints are shown once per profile.
+    #[serde(default)]
+    pub shown_hints: Vec<Hint>,
+    #[serde(default)]
+    pub achievements: AchievementProgress,
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            personal_bests: HashMap::new(),
+            class: PlayerClass::default(),
+            cosmetics: PlayerCosmetics::default(),
+            shown_hints: Vec::new(),
+            achievements: AchievementProgress::default(),
+        }
+    }
+}
+
+/// Graphics options that `DisplayConfig` doesn't have.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VideoSettings {
+    /// Plays a skirmish behind the main menu, can be turned off on low-end machines.
+    #[serde(default = "default_menu_background")]
+    pub menu_background: bool,
+    /// How fast the camera catches up with the player (per second), `0.0` turns smoothing off.
+    #[serde(default = "default_camera_smoothing")]
+    pub camera_smoothing: f32,
+    /// Leaves pools of blood under dead monsters.
+    #[serde(default = "default_gore")]
+    pub gore: bool,
+    /// How many corpses stay on the ground, the oldest ones are cleared first.
+    #[serde(default = "default_corpse_budget")]
+    pub corpse_budget: usize,
+    /// Lowers the resolution the world is rendered at while frames are slow, unless fixed.
+    #[serde(default)]
+    pub render_scale: RenderScaleSetting,
+    /// Dark levels and nights are drawn in daylight when it's `Off`.
+    #[serde(default)]
+    pub lighting_quality: LightingQuality,
+    #[serde(default)]
+    pub post_processing: PostProcessingSettings,
+    /// The name of a theme from `resources/ui/themes.ron`.
+    #[serde(default = "default_ui_theme")]
+    pub ui_theme: String,
+    /// Limits the frame rate of menus and loading screens, games always run at `FRAME_RATE`.
+    #[serde(default = "default_fps_cap")]
+    pub fps_cap: FpsCap,
+    #[serde(default = "default_unfocused_fps_cap")]
+    pub unfocused_fps_cap: FpsCap,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            menu_background: default_menu_background(),
+            camera_smoothing: default_camera_smoothing(),
+            gore: default_gore(),
+            corpse_budget: default_corpse_budget(),
+            render_scale: RenderScaleSetting::default(),
+            lighting_quality: LightingQuality::default(),
+            post_processing: PostProcessingSettings::default(),
+            ui_theme: default_ui_theme(),
+            fps_cap: default_fps_cap(),
+            unfocused_fps_cap: default_unfocused_fps_cap(),
+        }
+    }
+}
+
+fn default_menu_background() -> bool {
+    true
+}
+
+fn default_camera_smoothing() -> f32 {
+    8.0
+}
+
+fn default_gore() -> bool {
+    true
+}
+
+fn default_corpse_budget() -> usize {
+    150
+}
+
+fn default_ui_theme() -> String {
+    DEFAULT_UI_THEME.to_owned()
+}
+
+fn default_fps_cap() -> FpsCap {
+    FpsCap::Fps(FRAME_RATE)
+}
+
+fn default_unfocused_fps_cap() -> FpsCap {
+    FpsCap::Fps(15)
+}
+
+/// How soon the game pauses while the player's connection lags. Pausing early keeps the game
+/// smooth, pausing late keeps it responsive at the cost of corrections.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LagPause {
+    /// Leaves the threshold to the server's balance.
+    ServerDefault,
+    Smooth,
+    Balanced,
+    Responsive,
+}
+
+impl LagPause {
+    /// Is sent to the server with `SetPauseThreshold`, `None` doesn't send anything.
+    pub fn lagging_frames(self) -> Option<u64> {
+        match self {
+            LagPause::ServerDefault => None,
+            LagPause::Smooth => Some(PAUSE_FRAME_THRESHOLD / 3),
+            LagPause::Balanced => Some(PAUSE_FRAME_THRESHOLD * 2 / 3),
+            LagPause::Responsive => Some(PAUSE_FRAME_THRESHOLD),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            LagPause::ServerDefault => LagPause::Smooth,
+            LagPause::Smooth => LagPause::Balanced,
+            LagPause::Balanced => LagPause::Responsive,
+            LagPause::Responsive => LagPause::ServerDefault,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LagPause::ServerDefault => "Server default",
+            LagPause::Smooth => "Smooth",
+            LagPause::Balanced => "Balanced",
+            LagPause::Responsive => "Responsive",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    #[serde(default = "default_lag_pause")]
+    pub lag_pause: LagPause,
+    /// Shows a warning while the packet loss or jitter is high.
+    #[serde(default = "default_connection_warnings")]
+    pub connection_warnings: bool,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            lag_pause: default_lag_pause(),
+            connection_warnings: default_connection_warnings(),
+        }
+    }
+}
+
+fn default_lag_pause() -> LagPause {
+    LagPause::ServerDefault
+}
+
+fn default_connection_warnings() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AudioSettings {
+    /// From 0 (muted) to 1.
+    #[serde(default = "default_music_volume")]
+    pub music_volume: f32,
+    /// Sound effects, such as hits being confirmed, from 0 (muted) to 1.
+    #[serde(default = "default_effects_volume")]
+    pub effects_volume: f32,
+    /// Plays voice lines for game events, such as waves starting or allies dying.
+    #[serde(default = "default_announcer")]
+    pub announcer: bool,
+    /// From 0 (muted) to 1.
+    #[serde(default = "default_announcer_volume")]
+    pub announcer_volume: f32,
+    /// Silences the game while its window is in the background.
+    #[serde(default)]
+    pub mute_unfocused: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            music_volume: default_music_volume(),
+            effects_volume: default_effects_volume(),
+            announcer: default_announcer(),
+            announcer_volume: default_announcer_volume(),
+            mute_unfocused: false,
+        }
+    }
+}
+
+fn default_music_volume() -> f32 {
+    0.7
+}
+
+fn default_effects_volume() -> f32 {
+    0.8
+}
+
+fn default_announcer() -> bool {
+    true
+}
+
+fn default_announcer_volume() -> f32 {
+    1.0
+}
+
 pub struct Settings {
-    project_dirs: ProjectDirs,
+    dirs: GvDirs,
     bindings: Bindings<StringBindings>,
     display: DisplayConfig,
+    profile: PlayerProfile,
+    accessibility: AccessibilitySettings,
+    video: VideoSettings,
+    network: NetworkSettings,
+    audio: AudioSettings,
 }
 
 impl Settings {
     pub fn new() -> amethyst::Result<Self> {
-        let project_dirs = ProjectDirs::from("", "Psychedelic Donkey", "Grumpy Visitors")
-            .expect("Failed to get the project directory");
-        fs::create_dir_all(project_dirs.config_dir())?;
+        let dirs = GvDirs::new();
+        dirs.create_all()?;
+        for path in dirs.migrate_legacy_files(Path::new("."))? {
+            log::info!(
+                "Moved a file left by an older version to {}",
+                path.display()
+            );
+        }
 
         let default_bindings =
             Bindings::<StringBindings>::load_bytes(DEFAULT_BINDINGS_CONFIG_BYTES)?;
 
-        let bindings_config_path = bindings_config_path(&project_dirs);
+        let bindings_config_path = bindings_config_path(&dirs);
         let bindings = {
             let mut needs_update = false;
             let mut bindings = Bindings::load(bindings_config_path.as_path()).or_else(
@@ -84,7 +351,7 @@ impl Settings {
             }
         };
 
-        let display_config_path = display_config_path(&project_dirs);
+        let display_config_path = display_config_path(&dirs);
         let display = DisplayConfig::load(display_config_path.as_path()).or_else(
             move |_| -> amethyst::Result<DisplayConfig> {
                 let display = DisplayConfig::load_bytes(DEFAULT_DISPLAY_CONFIG_BYTES)?;
@@ -96,10 +363,97 @@ impl Settings {
             },
         )?;
 
+        let profile_path = dirs.profile_path();
+        let profile = fs::read_to_string(&profile_path)
+            .ok()
+            .and_then(|profile| ron::de::from_str(&profile).ok())
+            .map_or_else(
+                move || -> amethyst::Result<PlayerProfile> {
+                    let profile = PlayerProfile::default();
+                    fs::write(
+                        profile_path,
+                        ron::ser::to_string_pretty(&profile, PrettyConfig::default())?,
+                    )?;
+                    Ok(profile)
+                },
+                Ok,
+            )?;
+
+        let accessibility_path = accessibility_path(&dirs);
+        let accessibility = fs::read_to_string(&accessibility_path)
+            .ok()
+            .and_then(|accessibility| {
+                ron::de::from_str::<AccessibilitySettings>(&accessibility).ok()
+            })
+            .map_or_else(
+                move || -> amethyst::Result<AccessibilitySettings> {
+                    let accessibility = AccessibilitySettings::default();
+                    fs::write(
+                        accessibility_path,
+                        ron::ser::to_string_pretty(&accessibility, PrettyConfig::default())?,
+                    )?;
+                    Ok(accessibility)
+                },
+                |accessibility| Ok(accessibility.clamped()),
+            )?;
+
+        let video_path = video_path(&dirs);
+        let video = fs::read_to_string(&video_path)
+            .ok()
+            .and_then(|video| ron::de::from_str(&video).ok())
+            .map_or_else(
+                move || -> amethyst::Result<VideoSettings> {
+                    let video = VideoSettings::default();
+                    fs::write(
+                        video_path,
+                        ron::ser::to_string_pretty(&video, PrettyConfig::default())?,
+                    )?;
+                    Ok(video)
+                },
+                Ok,
+            )?;
+
+        let network_path = network_path(&dirs);
+        let network = fs::read_to_string(&network_path)
+            .ok()
+            .and_then(|network| ron::de::from_str(&network).ok())
+            .map_or_else(
+                move || -> amethyst::Result<NetworkSettings> {
+                    let network = NetworkSettings::default();
+                    fs::write(
+                        network_path,
+                        ron::ser::to_string_pretty(&network, PrettyConfig::default())?,
+                    )?;
+                    Ok(network)
+                },
+                Ok,
+            )?;
+
+        let audio_path = audio_path(&dirs);
+        let audio = fs::read_to_string(&audio_path)
+            .ok()
+            .and_then(|audio| ron::de::from_str(&audio).ok())
+            .map_or_else(
+                move || -> amethyst::Result<AudioSettings> {
+                    let audio = AudioSettings::default();
+                    fs::write(
+                        audio_path,
+                        ron::ser::to_string_pretty(&audio, PrettyConfig::default())?,
+                    )?;
+                    Ok(audio)
+                },
+                Ok,
+            )?;
+
         Ok(Self {
-            project_dirs,
+            dirs,
             bindings,
             display,
+            profile,
+            accessibility,
+            video,
+            network,
+            audio,
         })
     }
 
@@ -122,6 +476,67 @@ impl Settings {
         &self.display
     }
 
+    pub fn profile(&self) -> &PlayerProfile {
+        &self.profile
+    }
+
+    pub fn accessibility(&self) -> &AccessibilitySettings {
+        &self.accessibility
+    }
+
+    pub fn video(&self) -> &VideoSettings {
+        &self.video
+    }
+
+    pub fn network(&self) -> &NetworkSettings {
+        &self.network
+    }
+
+    pub fn audio(&self) -> &AudioSettings {
+        &self.audio
+    }
+
+    pub fn save_rating(&mut self, rating: u32) -> amethyst::Result<()> {
+        self.profile.rating = rating;
+        self.save_profile()
+    }
+
+    /// Returns `true` if the score beats the previous personal best for the map.
+    pub fn save_personal_best(&mut self, map_name: &str, score: u64) -> amethyst::Result<bool> {
+        let personal_best = self.profile.personal_bests.get(map_name).cloned();
+        if personal_best.map_or(false, |personal_best| personal_best >= score) {
+            return Ok(false);
+        }
+        self.profile
+            .personal_bests
+            .insert(map_name.to_owned(), score);
+        self.save_profile()?;
+        Ok(true)
+    }
+
+    pub fn save_class(&mut self, class: PlayerClass) -> amethyst::Result<()> {
+        self.profile.class = class;
+        self.save_profile()
+    }
+
+    pub fn save_cosmetics(&mut self, cosmetics: PlayerCosmetics) -> amethyst::Result<()> {
+        self.profile.cosmetics = cosmetics;
+        self.save_profile()
+    }
+
+    pub fn save_achievements(&mut self, achievements: AchievementProgress) -> amethyst::Result<()> {
+        self.profile.achievements = achievements;
+        self.save_profile()
+    }
+
+    pub fn save_shown_hint(&mut self, hint: Hint) -> amethyst::Result<()> {
+        if self.profile.shown_hints.contains(&hint) {
+            return Ok(());
+        }
+        self.profile.shown_hints.push(hint);
+        self.save_profile()
+    }
+
     #[allow(dead_code)]
     pub fn save_resolution(&mut self, dimensions: (u32, u32)) -> amethyst::Result<()> {
         self.display.dimensions = Some(dimensions);
@@ -135,7 +550,7 @@ impl Settings {
 
     #[allow(dead_code)]
     fn save_bindings(&mut self) -> amethyst::Result<()> {
-        fs::create_dir_all(self.project_dirs.config_dir())?;
+        fs::create_dir_all(self.dirs.config_dir())?;
         fs::write(
             self.bindings_config_path(),
             ron::ser::to_string_pretty(&self.bindings, PrettyConfig::default())?,
@@ -144,7 +559,7 @@ impl Settings {
     }
 
     fn save_display(&mut self) -> amethyst::Result<()> {
-        fs::create_dir_all(self.project_dirs.config_dir())?;
+        fs::create_dir_all(self.dirs.config_dir())?;
         fs::write(
             self.display_config_path(),
             ron::ser::to_string_pretty(&self.display, PrettyConfig::default())?,
@@ -152,20 +567,117 @@ impl Settings {
         Ok(())
     }
 
+    pub fn save_accessibility(
+        &mut self,
+        accessibility: AccessibilitySettings,
+    ) -> amethyst::Result<()> {
+        self.accessibility = accessibility.clamped();
+        fs::create_dir_all(self.dirs.config_dir())?;
+        fs::write(
+            accessibility_path(&self.dirs),
+            ron::ser::to_string_pretty(&self.accessibility, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn save_video(&mut self, video: VideoSettings) -> amethyst::Result<()> {
+        self.video = video;
+        fs::create_dir_all(self.dirs.config_dir())?;
+        fs::write(
+            video_path(&self.dirs),
+            ron::ser::to_string_pretty(&self.video, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn save_network(&mut self, network: NetworkSettings) -> amethyst::Result<()> {
+        self.network = network;
+        fs::create_dir_all(self.dirs.config_dir())?;
+        fs::write(
+            network_path(&self.dirs),
+            ron::ser::to_string_pretty(&self.network, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn save_audio(&mut self, audio: AudioSettings) -> amethyst::Result<()> {
+        self.audio = audio;
+        fs::create_dir_all(self.dirs.config_dir())?;
+        fs::write(
+            audio_path(&self.dirs),
+            ron::ser::to_string_pretty(&self.audio, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    fn save_profile(&mut self) -> amethyst::Result<()> {
+        fs::create_dir_all(self.dirs.profiles_dir())?;
+        fs::write(
+            self.dirs.profile_path(),
+            ron::ser::to_string_pretty(&self.profile, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    /// Is passed to the server started by the host, which saves the match progress there.
+    pub fn saved_match_path(&self) -> PathBuf {
+        self.dirs.saved_match_path()
+    }
+
+    /// Content packs are installed and downloaded here, the server started by the host
+    /// loads them from here too.
+    pub fn mods_dir(&self) -> PathBuf {
+        self.dirs.mods_dir()
+    }
+
     #[allow(dead_code)]
     fn bindings_config_path(&self) -> PathBuf {
-        bindings_config_path(&self.project_dirs)
+        bindings_config_path(&self.dirs)
     }
 
     fn display_config_path(&self) -> PathBuf {
-        display_config_path(&self.project_dirs)
+        display_config_path(&self.dirs)
     }
 }
 
-fn bindings_config_path(project_dirs: &ProjectDirs) -> PathBuf {
-    project_dirs.config_dir().join("bindings_config.ron")
+fn bindings_config_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("bindings_config.ron")
+}
+
+fn display_config_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("display_config.ron")
+}
+
+fn accessibility_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("accessibility.ron")
 }
 
-fn display_config_path(project_dirs: &ProjectDirs) -> PathBuf {
-    project_dirs.config_dir().join("display_config.ron")
+fn video_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("video.ron")
+}
+
+fn network_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("network.ron")
+}
+
+fn audio_path(dirs: &GvDirs) -> PathBuf {
+    dirs.config_dir().join("audio.ron")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rating_settles_around_the_usual_result() {
+        assert_eq!(rating_after_match(DEFAULT_RATING, 20), DEFAULT_RATING);
+        assert!(rating_after_match(DEFAULT_RATING, 30) > DEFAULT_RATING);
+        assert!(rating_after_match(DEFAULT_RATING, 5) < DEFAULT_RATING);
+
+        let mut rating = DEFAULT_RATING;
+        for _ in 0..50 {
+            rating = rating_after_match(rating, 10);
+        }
+        assert!((rating as i64 - 500).abs() < RATING_ADJUSTMENT_DIVISOR);
+    }
 }
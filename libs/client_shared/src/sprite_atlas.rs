@@ -0,0 +1,242 @@
+//! The sprite atlas: the small sprite sheets of `resources/assets` packed into a single texture
+//! by `assets_packer sprites`. Clients build every sheet from the atlas, so they're drawn
+//! from the same texture and only one image is decoded on startup. The packed atlas is
+//! committed, so it has to be packed again whenever a sheet changes.
+
+use amethyst::{
+    assets::{Asset, Format, Handle, ProcessingState},
+    ecs::VecStorage,
+    Error,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+pub const SPRITE_ATLAS_TEXTURE_PATH: &str = "resources/assets/sprites_atlas.png";
+pub const SPRITE_ATLAS_METADATA_PATH: &str = "resources/assets/sprites_atlas.ron";
+
+/// The sheets that are packed into the atlas, each one is `resources/assets/{name}.png`
+/// with its sprites listed in `resources/assets/{name}.ron`.
+pub const SPRITE_ATLAS_SHEETS: [&str; 12] = [
+    "hats",
+    "emotes",
+    "augments",
+    "minion_marker",
+    "player_markers",
+    "combat_numbers",
+    "zones",
+    "auras",
+    "elite_affixes",
+    "resistances",
+    "blood",
+    "hit_markers",
+];
+
+pub fn sheet_texture_path(name: &str) -> String {
+    format!("resources/assets/{}.png", name)
+}
+
+pub fn sheet_sprites_path(name: &str) -> String {
+    format!("resources/assets/{}.ron", name)
+}
+
+/// A region of the atlas texture, in pixels from its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasSprite {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpriteAtlas {
+    pub texture_width: u32,
+    pub texture_height: u32,
+    /// Sprites of every sheet in the same order as in the sheet itself, so sprite numbers
+    /// don't change when a sheet is drawn from the atlas.
+    pub sheets: BTreeMap<String, Vec<AtlasSprite>>,
+}
+
+impl SpriteAtlas {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let sprite_atlas: SpriteAtlas =
+            ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        for (name, sprites) in &sprite_atlas.sheets {
+            for (i, sprite) in sprites.iter().enumerate() {
+                if sprite.x + sprite.width > sprite_atlas.texture_width
+                    || sprite.y + sprite.height > sprite_atlas.texture_height
+                {
+                    return Err(format!(
+                        "Sprite {} of {} is outside of the atlas texture",
+                        i, name
+                    ));
+                }
+            }
+        }
+        Ok(sprite_atlas)
+    }
+
+    /// `None` if the sheet wasn't packed, e.g. it was added after the atlas had been built.
+    pub fn sheet(&self, name: &str) -> Option<&[AtlasSprite]> {
+        self.sheets.get(name).map(Vec::as_slice)
+    }
+}
+
+/// Is loaded in the background like the rest of the assets, clients need
+/// a `Processor::<SpriteAtlas>` to put it into the storage.
+impl Asset for SpriteAtlas {
+    const NAME: &'static str = "gv_client_shared::sprite_atlas::SpriteAtlas";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<SpriteAtlas> for Result<ProcessingState<SpriteAtlas>, Error> {
+    fn from(sprite_atlas: SpriteAtlas) -> Result<ProcessingState<SpriteAtlas>, Error> {
+        Ok(ProcessingState::Loaded(sprite_atlas))
+    }
+}
+
+/// Reads `SPRITE_ATLAS_METADATA_PATH`, with the same checks as `SpriteAtlas::from_bytes`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpriteAtlasFormat;
+
+impl Format<SpriteAtlas> for SpriteAtlasFormat {
+    fn name(&self) -> &'static str {
+        "SpriteAtlasFormat"
+    }
+
+    fn import_simple(&self, bytes: Vec<u8>) -> Result<SpriteAtlas, Error> {
+        SpriteAtlas::from_bytes(&bytes).map_err(Error::from_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use super::*;
+
+    fn repository_path(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(path)
+    }
+
+    #[test]
+    fn every_sprite_sheet_is_packed() {
+        for name in SPRITE_ATLAS_SHEETS.iter() {
+            assert!(repository_path(&sheet_texture_path(name)).is_file());
+            assert!(repository_path(&sheet_sprites_path(name)).is_file());
+        }
+
+        let sheets_dir = repository_path("resources/assets");
+        for entry in fs::read_dir(sheets_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "ron")
+            {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            if name == "sprites_atlas" {
+                continue;
+            }
+            assert!(
+                SPRITE_ATLAS_SHEETS.contains(&name),
+                "Sprite sheet {} isn't packed into the atlas",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn packed_atlas_is_up_to_date() {
+        use amethyst::renderer::sprite::Sprites;
+
+        assert!(repository_path(SPRITE_ATLAS_TEXTURE_PATH).is_file());
+        let sprite_atlas = SpriteAtlas::from_bytes(
+            &fs::read(repository_path(SPRITE_ATLAS_METADATA_PATH)).unwrap(),
+        )
+        .unwrap();
+        for name in SPRITE_ATLAS_SHEETS.iter() {
+            let sprites: Sprites =
+                ron::de::from_bytes(&fs::read(repository_path(&sheet_sprites_path(name))).unwrap())
+                    .unwrap();
+            let sprite_sizes = match sprites {
+                Sprites::List(sprite_list) => sprite_list
+                    .sprites
+                    .iter()
+                    .map(|sprite| (sprite.width, sprite.height))
+                    .collect::<Vec<_>>(),
+                Sprites::Grid(_) => panic!("Sprite sheet {} isn't a list", name),
+            };
+            let atlas_sprite_sizes = sprite_atlas
+                .sheet(name)
+                .unwrap_or_else(|| panic!("Sprite sheet {} isn't in the atlas", name))
+                .iter()
+                .map(|sprite| (sprite.width, sprite.height))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                atlas_sprite_sizes, sprite_sizes,
+                "Sprite sheet {} has changed since the atlas was packed, run `assets_packer sprites`",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn sheets_are_looked_up_by_name() {
+        let sprite_atlas = SpriteAtlas::from_bytes(
+            br#"(
+                texture_width: 64,
+                texture_height: 32,
+                sheets: {
+                    "hats": [
+                        (x: 0, y: 0, width: 24, height: 24),
+                        (x: 25, y: 0, width: 24, height: 24),
+                    ],
+                },
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sprite_atlas.sheet("hats"),
+            Some(
+                &[
+                    AtlasSprite {
+                        x: 0,
+                        y: 0,
+                        width: 24,
+                        height: 24,
+                    },
+                    AtlasSprite {
+                        x: 25,
+                        y: 0,
+                        width: 24,
+                        height: 24,
+                    },
+                ][..]
+            )
+        );
+        assert_eq!(sprite_atlas.sheet("emotes"), None);
+    }
+
+    #[test]
+    fn sprites_outside_of_the_atlas_are_rejected() {
+        let result = SpriteAtlas::from_bytes(
+            br#"(
+                texture_width: 32,
+                texture_height: 32,
+                sheets: {
+                    "blood": [(x: 16, y: 0, width: 32, height: 32)],
+                },
+            )"#,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! UI themes: the colors, font, button image and paddings of every UI screen, defined in
+//! `resources/ui/themes.ron`. The UI prefabs only lay the elements out, clients restyle them
+//! whenever the theme is switched.
+
+use serde_derive::Deserialize;
+
+use std::{collections::HashMap, fs, path::Path};
+
+pub const DEFAULT_UI_THEME: &str = "Dusk";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum TextStyle {
+    Normal,
+    Dim,
+    Accent,
+    Highlight,
+    Warning,
+}
+
+/// Which color of a theme an element takes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum UiColorRole {
+    Text(TextStyle),
+    /// Texts drawn over the game rather than over menu backgrounds.
+    HudText(TextStyle),
+    Background,
+    Panel,
+    Field,
+    Border,
+    Backdrop,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct UiTextColors {
+    pub normal: [f32; 3],
+    pub dim: [f32; 3],
+    pub accent: [f32; 3],
+    pub highlight: [f32; 3],
+    pub warning: [f32; 3],
+}
+
+impl UiTextColors {
+    pub fn color(&self, style: TextStyle) -> [f32; 3] {
+        match style {
+            TextStyle::Normal => self.normal,
+            TextStyle::Dim => self.dim,
+            TextStyle::Accent => self.accent,
+            TextStyle::Highlight => self.highlight,
+            TextStyle::Warning => self.warning,
+        }
+    }
+}
+
+/// Colors are RGB, elements keep their own alpha as menus fade them in and out.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct UiThemeColors {
+    pub text: UiTextColors,
+    pub hud_text: UiTextColors,
+    pub button_text: [f32; 3],
+    pub button_hover_text: [f32; 3],
+    pub selected_text: [f32; 3],
+    pub selected_background: [f32; 3],
+    pub background: [f32; 3],
+    pub panel: [f32; 3],
+    pub field: [f32; 3],
+    pub border: [f32; 3],
+    pub backdrop: [f32; 3],
+}
+
+impl UiThemeColors {
+    pub fn color(&self, role: UiColorRole) -> [f32; 3] {
+        match role {
+            UiColorRole::Text(style) => self.text.color(style),
+            UiColorRole::HudText(style) => self.hud_text.color(style),
+            UiColorRole::Background => self.background,
+            UiColorRole::Panel => self.panel,
+            UiColorRole::Field => self.field,
+            UiColorRole::Border => self.border,
+            UiColorRole::Backdrop => self.backdrop,
+        }
+    }
+}
+
+/// A texture stretched over buttons without stretching its edges.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct NineSliceImage {
+    pub texture: String,
+    pub texture_size: (u32, u32),
+    /// The widths of the edges, in pixels.
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct UiTheme {
+    pub name: String,
+    pub font: String,
+    pub colors: UiThemeColors,
+    /// Buttons are drawn as plain text without one.
+    #[serde(default)]
+    pub button_image: Option<NineSliceImage>,
+    /// How far panels are inset into the border containers around them.
+    pub panel_padding: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UiThemes {
+    /// The roles of the elements by their ids. Texts that aren't listed are `Text(Normal)`,
+    /// other elements that aren't listed keep their colors.
+    elements: HashMap<String, UiColorRole>,
+    themes: Vec<UiTheme>,
+}
+
+impl UiThemes {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let ui_themes: UiThemes = ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        if ui_themes.themes.is_empty() {
+            return Err("Expected at least one theme".to_owned());
+        }
+        for (i, theme) in ui_themes.themes.iter().enumerate() {
+            if ui_themes.themes[..i]
+                .iter()
+                .any(|other| other.name == theme.name)
+            {
+                return Err(format!("Theme {} is defined twice", theme.name));
+            }
+        }
+        Ok(ui_themes)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Self::from_bytes(&bytes).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn themes(&self) -> &[UiTheme] {
+        &self.themes
+    }
+
+    /// Unknown names fall back to the first theme, `None` only if there are no themes.
+    pub fn theme(&self, name: &str) -> Option<&UiTheme> {
+        self.themes
+            .iter()
+            .find(|theme| theme.name == name)
+            .or_else(|| self.themes.first())
+    }
+
+    /// The theme after the given one (or after the fallback), for cycling through them
+    /// from the menu.
+    pub fn next_theme(&self, name: &str) -> Option<&UiTheme> {
+        let i = self
+            .themes
+            .iter()
+            .position(|theme| theme.name == name)
+            .unwrap_or(0);
+        self.themes.get((i + 1) % self.themes.len().max(1))
+    }
+
+    pub fn role(&self, id: &str, is_text: bool) -> Option<UiColorRole> {
+        match self.elements.get(id) {
+            Some(role) => Some(*role),
+            None if is_text => Some(UiColorRole::Text(TextStyle::Normal)),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILT_IN_THEMES: &[u8] = include_bytes!("../../../resources/ui/themes.ron");
+
+    #[test]
+    fn built_in_themes_load() {
+        let ui_themes = UiThemes::from_bytes(BUILT_IN_THEMES).unwrap();
+        assert!(ui_themes.themes().len() >= 2);
+        assert_eq!(ui_themes.themes()[0].name, DEFAULT_UI_THEME);
+        assert!(ui_themes
+            .themes()
+            .iter()
+            .any(|theme| theme.button_image.is_some()));
+    }
+
+    #[test]
+    fn themes_cycle_and_fall_back_to_the_first_one() {
+        let ui_themes = UiThemes::from_bytes(BUILT_IN_THEMES).unwrap();
+        let names = ui_themes
+            .themes()
+            .iter()
+            .map(|theme| theme.name.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ui_themes.theme("Missing").unwrap().name, names[0]);
+        assert_eq!(ui_themes.theme(&names[1]).unwrap().name, names[1]);
+        assert_eq!(ui_themes.next_theme(&names[0]).unwrap().name, names[1]);
+        assert_eq!(
+            ui_themes.next_theme(names.last().unwrap()).unwrap().name,
+            names[0]
+        );
+        assert_eq!(ui_themes.next_theme("Missing").unwrap().name, names[1]);
+    }
+
+    #[test]
+    fn texts_are_normal_unless_listed() {
+        let ui_themes = UiThemes::from_bytes(BUILT_IN_THEMES).unwrap();
+        assert_eq!(
+            ui_themes.role("ui_afk_label", true),
+            Some(UiColorRole::HudText(TextStyle::Warning))
+        );
+        assert_eq!(
+            ui_themes.role("ui_modal_title", true),
+            Some(UiColorRole::Text(TextStyle::Normal))
+        );
+        assert_eq!(
+            ui_themes.role("ui_modal_window_container", false),
+            Some(UiColorRole::Panel)
+        );
+        assert_eq!(ui_themes.role("ui_payload_bar", false), None);
+    }
+
+    #[test]
+    fn invalid_theme_files_are_rejected() {
+        assert!(UiThemes::from_bytes(b"(elements: {}, themes: [])").is_err());
+
+        let theme = r#"(
+            name: "Plain",
+            font: "font.ttf",
+            colors: (
+                text: (normal: (1.0, 1.0, 1.0), dim: (1.0, 1.0, 1.0), accent: (1.0, 1.0, 1.0),
+                    highlight: (1.0, 1.0, 1.0), warning: (1.0, 1.0, 1.0)),
+                hud_text: (normal: (1.0, 1.0, 1.0), dim: (1.0, 1.0, 1.0), accent: (1.0, 1.0, 1.0),
+                    highlight: (1.0, 1.0, 1.0), warning: (1.0, 1.0, 1.0)),
+                button_text: (1.0, 1.0, 1.0),
+                button_hover_text: (1.0, 1.0, 1.0),
+                selected_text: (1.0, 1.0, 1.0),
+                selected_background: (0.0, 0.0, 0.0),
+                background: (0.0, 0.0, 0.0),
+                panel: (0.0, 0.0, 0.0),
+                field: (0.0, 0.0, 0.0),
+                border: (1.0, 1.0, 1.0),
+                backdrop: (0.0, 0.0, 0.0),
+            ),
+            panel_padding: 1.0,
+        )"#;
+        let one_theme = format!("(elements: {{}}, themes: [{}])", theme);
+        assert!(UiThemes::from_bytes(one_theme.as_bytes()).is_ok());
+        let same_name_twice = format!("(elements: {{}}, themes: [{}, {}])", theme, theme);
+        assert!(UiThemes::from_bytes(same_name_twice.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use gv_core::{
+    actions::{mob::MobAction, player::PlayerLookAction, ClientActionUpdate},
+    ecs::{
+        components::WorldPosition,
+        resources::world::{ServerWorldUpdate, ServerWorldUpdates},
+    },
+    math::Vector2,
+    net::{
+        server_message::{ServerMessage, ServerMessagePayload, UpdateWorldRef},
+        MessageEncoder, NetUpdate, NetUpdateWithPosition,
+    },
+};
+
+/// A broadcast to a client with ~200ms of latency covers about this many frames.
+const BROADCASTED_FRAMES: u64 = 15;
+const MONSTERS: u64 = 50;
+const PLAYERS: u64 = 4;
+
+fn fill_update(update: &mut ServerWorldUpdate) {
+    for entity_net_id in 0..MONSTERS {
+        update.mob_actions_updates.push(NetUpdateWithPosition {
+            entity_net_id,
+            position: WorldPosition::new(Vector2::new(entity_net_id as f32, 0.0)),
+            data: MobAction::Move(Vector2::new(0.0, entity_net_id as f32)),
+        });
+    }
+    for entity_net_id in MONSTERS..MONSTERS + PLAYERS {
+        update.player_look_actions_updates.push(NetUpdate {
+            entity_net_id,
+            data: ClientActionUpdate {
+                client_action_id: update.frame_number,
+                action: PlayerLookAction {
+                    direction: Vector2::new(1.0, 0.0),
+                    aim_point: Vector2::new(entity_net_id as f32 + 1.0, 0.0),
+                },
+            },
+        });
+    }
+}
+
+fn server_world_updates() -> ServerWorldUpdates {
+    let mut server_world_updates = ServerWorldUpdates::default();
+    server_world_updates.reserve_new_updates(0, BROADCASTED_FRAMES - 1);
+    for (_, update) in server_world_updates.updates.iter_mut() {
+        fill_update(update);
+    }
+    server_world_updates
+}
+
+fn encode_update_world(c: &mut Criterion) {
+    let server_world_updates = server_world_updates();
+    let update_indices = (0..server_world_updates.updates.len()).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("encode_update_world");
+    group.bench_function("cloned", |b| {
+        b.iter(|| {
+            let updates = server_world_updates
+                .updates
+                .iter()
+                .map(|(_, update)| update.clone())
+                .collect();
+            bincode::serialize(&ServerMessage {
+                session_id: 1,
+                payload: ServerMessagePayload::UpdateWorld { id: 0, updates },
+            })
+            .unwrap()
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        let mut message_encoder = MessageEncoder::default();
+        b.iter(|| {
+            message_encoder
+                .encode_message(
+                    1,
+                    &UpdateWorldRef {
+                        id: 0,
+                        server_world_updates: &server_world_updates,
+                        update_indices: &update_indices,
+                    },
+                )
+                .len()
+        })
+    });
+    group.finish();
+}
+
+fn reserve_new_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reserve_new_updates");
+    group.bench_function("new", |b| {
+        let mut frame_number = 0;
+        b.iter(|| {
+            frame_number += 1;
+            let mut update = ServerWorldUpdate::new(frame_number);
+            fill_update(&mut update);
+            update
+        })
+    });
+    group.bench_function("pooled", |b| {
+        let mut server_world_updates = server_world_updates();
+        let mut frame_number = BROADCASTED_FRAMES;
+        b.iter(|| {
+            server_world_updates.reserve_new_updates(frame_number, frame_number);
+            fill_update(server_world_updates.get_update(frame_number, frame_number));
+            let oldest_update_number = server_world_updates.updates.front().unwrap().0;
+            server_world_updates.drop_acknowledged(oldest_update_number);
+            frame_number += 1;
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encode_update_world, reserve_new_updates);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! Achievements are unlocked by the game events a client receives and are kept in the player
+//! profile along with the progress towards them.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{game_events::GameEvent, net::NetIdentifier, room_settings::Difficulty};
+
+pub const MONSTER_SLAYER_KILLS: u64 = 1000;
+pub const NIGHTMARE_SURVIVOR_WAVE: usize = 20;
+/// An unlocked achievement is announced on the HUD for this long.
+pub const ACHIEVEMENT_TOAST_SECS: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Kill `MONSTER_SLAYER_KILLS` monsters over all games.
+    MonsterSlayer,
+    /// Clear `NIGHTMARE_SURVIVOR_WAVE` waves on the hardest difficulty.
+    NightmareSurvivor,
+    /// Complete a campaign level without taking any damage.
+    Flawless,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [
+        Achievement::MonsterSlayer,
+        Achievement::NightmareSurvivor,
+        Achievement::Flawless,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::MonsterSlayer => "Monster Slayer",
+            Achievement::NightmareSurvivor => "Nightmare Survivor",
+            Achievement::Flawless => "Flawless",
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Achievement::MonsterSlayer => format!("Kill {} monsters", MONSTER_SLAYER_KILLS),
+            Achievement::NightmareSurvivor => {
+                format!("Clear wave {} on Hard", NIGHTMARE_SURVIVOR_WAVE)
+            }
+            Achievement::Flawless => "Complete a campaign level without taking damage".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AchievementProgress {
+    /// Monsters killed by the local player over all games.
+    pub kills: u64,
+    pub unlocked: Vec<Achievement>,
+}
+
+impl AchievementProgress {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Counts the event towards the achievements, returns the achievement it unlocks, if any.
+    /// `has_taken_damage` tells whether the local player has been damaged during the current
+    /// campaign level.
+    pub fn record_event(
+        &mut self,
+        event: &GameEvent,
+        local_player: Option<NetIdentifier>,
+        difficulty: Difficulty,
+        has_taken_damage: bool,
+    ) -> Option<Achievement> {
+        match event {
+            GameEvent::Kill { killer, .. } if killer.is_some() && *killer == local_player => {
+                self.kills += 1;
+                if self.kills >= MONSTER_SLAYER_KILLS {
+                    return self.unlock(Achievement::MonsterSlayer);
+                }
+                None
+            }
+            GameEvent::WaveCleared(wave)
+                if difficulty == Difficulty::Hard && *wave >= NIGHTMARE_SURVIVOR_WAVE =>
+            {
+                self.unlock(Achievement::NightmareSurvivor)
+            }
+            GameEvent::CampaignLevelCompleted(_) if !has_taken_damage => {
+                self.unlock(Achievement::Flawless)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the achievement if it hasn't been unlocked before.
+    fn unlock(&mut self, achievement: Achievement) -> Option<Achievement> {
+        if self.is_unlocked(achievement) {
+            return None;
+        }
+        self.unlocked.push(achievement);
+        Some(achievement)
+    }
+}
@@ -26,6 +26,26 @@ impl<T> Default for MobAction<T> {
     }
 }
 
+impl<T: Copy> MobAction<T> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MobAction::Idle => "Idle",
+            MobAction::Move(_) => "Move",
+            MobAction::Chase(_) => "Chase",
+            MobAction::Attack(_) => "Attack",
+        }
+    }
+
+    /// Returns the chased or attacked entity.
+    pub fn target(&self) -> Option<T> {
+        match self {
+            MobAction::Chase(target) => Some(*target),
+            MobAction::Attack(MobAttackAction { target, .. }) => Some(*target),
+            MobAction::Idle | MobAction::Move(_) => None,
+        }
+    }
+}
+
 impl MobAction<Entity> {
     pub fn load_entity_net_id(
         &self,
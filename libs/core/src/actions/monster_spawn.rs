@@ -6,7 +6,11 @@ use serde_derive::{Deserialize, Serialize};
 
 use std::ops::Range;
 
-use crate::{ecs::resources::world::FramedUpdate, math::Vector2, net::NetIdentifier};
+use crate::{
+    ecs::{components::affixes::MonsterAffix, resources::world::FramedUpdate},
+    math::Vector2,
+    net::NetIdentifier,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnActions {
@@ -27,9 +31,15 @@ impl FramedUpdate for SpawnActions {
     }
 }
 
+/// Monsters appearing in the middle of a level are telegraphed for this many frames.
+pub const SPAWN_TELEGRAPH_FRAMES: u64 = 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnAction {
     pub spawn_type: SpawnType,
+    /// Spawned monsters emerge for this many frames: clients show a warning decal
+    /// under them, but they don't act and can't be damaged yet.
+    pub telegraph_frames: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +48,8 @@ pub enum SpawnType {
     Single {
         entity_net_id: Option<NetIdentifier>,
         position: Vector2,
+        /// Is empty for regular monsters, elites have one or more affixes.
+        affixes: Vec<MonsterAffix>,
     },
 
     /// Ah
@@ -52,6 +64,14 @@ pub enum SpawnType {
         entity_net_id_range: Option<Range<NetIdentifier>>,
         side: Side,
     },
+
+    /// A minion summoned next to its owner.
+    Minion {
+        entity_net_id: Option<NetIdentifier>,
+        /// Is `None` in single player, where the only player is the owner.
+        owner_net_id: Option<NetIdentifier>,
+        position: Vector2,
+    },
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -1,6 +1,18 @@
 use serde_derive::{Deserialize, Serialize};
 
-use crate::math::Vector2;
+use crate::math::{deterministic, Vector2};
+
+/// Ground-targeted spells and aim points can't be further than this from the player.
+pub const MAX_CAST_RANGE: f32 = 600.0;
+
+/// Moves `point` onto the max cast range around `origin`, if it's further away.
+pub fn clamp_to_cast_range(origin: Vector2, point: Vector2) -> Vector2 {
+    let offset = point - origin;
+    if offset.norm_squared() <= MAX_CAST_RANGE * MAX_CAST_RANGE {
+        return point;
+    }
+    origin + deterministic::normalize(&offset) * MAX_CAST_RANGE
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PlayerWalkAction {
@@ -32,11 +44,24 @@ impl Default for PlayerWalkAction {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerLookAction {
     pub direction: Vector2,
+    /// The world point a player aims at, servers clamp it to `MAX_CAST_RANGE`. The direction
+    /// alone would be added to the position a server sees, which may differ from the one
+    /// the player aimed from.
+    pub aim_point: Vector2,
+}
+
+impl PlayerLookAction {
+    /// Moves the aim point onto `MAX_CAST_RANGE` around `position`, if it's further away.
+    pub fn clamp_aim_point(&mut self, position: Vector2) {
+        self.aim_point = clamp_to_cast_range(position, self.aim_point);
+    }
 }
 
 impl PartialEq for PlayerLookAction {
     fn eq(&self, other: &Self) -> bool {
+        // Moving the aim point by less than a unit isn't worth sending a new update.
         (self.direction - other.direction).norm_squared() < 0.001
+            && (self.aim_point - other.aim_point).norm_squared() < 1.0
     }
 }
 
@@ -44,19 +69,51 @@ impl Default for PlayerLookAction {
     fn default() -> Self {
         Self {
             direction: Vector2::new(0.0, 1.0),
+            aim_point: Vector2::new(0.0, 1.0),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CastSpell {
+    Missile,
+    /// Restores health of the allies around the target position.
+    Heal,
+    /// Leaves a zone (see `components::zone::Zone`) at the target position.
+    Zone,
+    /// Makes the monsters around the caster turn to them (see `components::ThreatTable`).
+    Taunt,
+}
+
+impl Default for CastSpell {
+    fn default() -> Self {
+        Self::Missile
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerCastAction {
     pub cast_position: Vector2,
     pub target_position: Vector2,
+    pub spell: CastSpell,
+}
+
+impl PlayerCastAction {
+    /// Moves the target position onto `MAX_CAST_RANGE` around the cast position,
+    /// if it's further away.
+    pub fn clamp_target_position(&mut self) {
+        self.target_position = clamp_to_cast_range(self.cast_position, self.target_position);
+    }
 }
 
 impl PartialEq for PlayerCastAction {
     fn eq(&self, other: &Self) -> bool {
-        (self.cast_position - other.cast_position).norm_squared() < 0.001
+        self.spell == other.spell
+            && (self.cast_position - other.cast_position).norm_squared() < 0.001
             && (self.target_position - other.target_position).norm_squared() < 0.001
     }
 }
+
+/// Activates the levers around a player, see `level_scripts`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInteractAction;
@@ -0,0 +1,122 @@
+//! Aim assist for aiming with a gamepad stick. It only bends the local aim direction before
+//! the look and cast actions are sent, so servers and other clients never see it, and rooms
+//! can disallow it with `RoomSettings::aim_assist`.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::math::{Vector2, ZeroVector};
+
+/// Monsters within this angle (in radians) of the aim direction, either way, pull the aim.
+pub const AIM_ASSIST_CONE: f32 = 0.35;
+pub const AIM_ASSIST_RANGE: f32 = 600.0;
+/// How long the aim takes to catch up with a target (or to let go of it).
+const AIM_ASSIST_SMOOTHING_SECS: f32 = 0.12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimAssistStrength {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl AimAssistStrength {
+    pub fn name(self) -> &'static str {
+        match self {
+            AimAssistStrength::Off => "Off",
+            AimAssistStrength::Low => "Low",
+            AimAssistStrength::Medium => "Medium",
+            AimAssistStrength::High => "High",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            AimAssistStrength::Off => AimAssistStrength::Low,
+            AimAssistStrength::Low => AimAssistStrength::Medium,
+            AimAssistStrength::Medium => AimAssistStrength::High,
+            AimAssistStrength::High => AimAssistStrength::Off,
+        }
+    }
+
+    /// The share of the angle to the target that the aim is bent by.
+    pub fn factor(self) -> f32 {
+        match self {
+            AimAssistStrength::Off => 0.0,
+            AimAssistStrength::Low => 0.35,
+            AimAssistStrength::Medium => 0.6,
+            AimAssistStrength::High => 0.85,
+        }
+    }
+}
+
+impl Default for AimAssistStrength {
+    fn default() -> Self {
+        AimAssistStrength::Medium
+    }
+}
+
+/// Returns the offset of the closest target within `AIM_ASSIST_CONE` of the aim direction
+/// and `AIM_ASSIST_RANGE`. Targets are passed as offsets from the player as well.
+pub fn aim_assist_target(
+    aim_direction: Vector2,
+    targets: impl IntoIterator<Item = Vector2>,
+) -> Option<Vector2> {
+    if aim_direction == Vector2::zero() {
+        return None;
+    }
+    targets
+        .into_iter()
+        .filter(|target| {
+            let distance_squared = target.norm_squared();
+            distance_squared > 0.0
+                && distance_squared <= AIM_ASSIST_RANGE * AIM_ASSIST_RANGE
+                && signed_angle(aim_direction, *target).abs() <= AIM_ASSIST_CONE
+        })
+        .min_by(|a, b| {
+            a.norm_squared()
+                .partial_cmp(&b.norm_squared())
+                .expect("Expected finite target offsets")
+        })
+}
+
+/// Keeps the angle the aim is bent by between frames, so that it eases towards targets
+/// instead of snapping to them.
+#[derive(Debug, Default)]
+pub struct AimAssist {
+    offset: f32,
+}
+
+impl AimAssist {
+    pub fn apply(
+        &mut self,
+        aim_direction: Vector2,
+        target: Option<Vector2>,
+        strength: AimAssistStrength,
+        delta_seconds: f32,
+    ) -> Vector2 {
+        let desired_offset = target.map_or(0.0, |target| {
+            signed_angle(aim_direction, target) * strength.factor()
+        });
+        let catch_up = (delta_seconds / AIM_ASSIST_SMOOTHING_SECS).min(1.0);
+        self.offset += (desired_offset - self.offset) * catch_up;
+        rotate(aim_direction, self.offset)
+    }
+
+    /// Is called when aiming with the mouse, which is never assisted.
+    pub fn reset(&mut self) {
+        self.offset = 0.0;
+    }
+}
+
+fn signed_angle(from: Vector2, to: Vector2) -> f32 {
+    (from.x * to.y - from.y * to.x).atan2(from.dot(&to))
+}
+
+fn rotate(vector: Vector2, angle: f32) -> Vector2 {
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(
+        vector.x * cos - vector.y * sin,
+        vector.x * sin + vector.y * cos,
+    )
+}
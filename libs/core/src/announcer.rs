@@ -0,0 +1,99 @@
+//! Announcer voice lines. Clients pick them from the `GameEvent` stream they receive,
+//! so nothing about the announcer is sent over the network.
+
+use std::time::Duration;
+
+use crate::{game_events::GameEvent, net::NetIdentifier};
+
+/// Lines don't talk over each other, one started less than this ago drops the next ones.
+pub const ANNOUNCER_GAP: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnouncerLine {
+    WaveIncoming,
+    WaveCleared,
+    AllyDown,
+    EliteSlain,
+    LevelUp,
+}
+
+impl AnnouncerLine {
+    /// Sounds of the lines are loaded in this order.
+    pub const ALL: [AnnouncerLine; 5] = [
+        AnnouncerLine::WaveIncoming,
+        AnnouncerLine::WaveCleared,
+        AnnouncerLine::AllyDown,
+        AnnouncerLine::EliteSlain,
+        AnnouncerLine::LevelUp,
+    ];
+
+    /// Returns the line announcing the event, if any. Regular kills aren't announced,
+    /// and neither are the deaths of the local player, who already sees the death screen.
+    pub fn from_event(event: &GameEvent, local_player: Option<NetIdentifier>) -> Option<Self> {
+        match event {
+            GameEvent::Kill { is_elite: true, .. } => Some(AnnouncerLine::EliteSlain),
+            GameEvent::Kill { .. } => None,
+            GameEvent::PlayerDied(player) if Some(*player) == local_player => None,
+            GameEvent::PlayerDied(_) => Some(AnnouncerLine::AllyDown),
+            GameEvent::LevelUp(_) => Some(AnnouncerLine::LevelUp),
+            GameEvent::WaveCleared(_) => Some(AnnouncerLine::WaveCleared),
+            GameEvent::WaveStarted(_) => Some(AnnouncerLine::WaveIncoming),
+            GameEvent::CampaignLevelCompleted(_) => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|line| *line == self)
+            .expect("Expected a line from AnnouncerLine::ALL")
+    }
+
+    pub fn sound_path(self) -> &'static str {
+        match self {
+            AnnouncerLine::WaveIncoming => "resources/announcer/wave_incoming.wav",
+            AnnouncerLine::WaveCleared => "resources/announcer/wave_cleared.wav",
+            AnnouncerLine::AllyDown => "resources/announcer/ally_down.wav",
+            AnnouncerLine::EliteSlain => "resources/announcer/elite_slain.wav",
+            AnnouncerLine::LevelUp => "resources/announcer/level_up.wav",
+        }
+    }
+
+    /// The line isn't repeated until this much time has passed since it was last played.
+    pub fn cooldown(self) -> Duration {
+        Duration::from_secs(match self {
+            AnnouncerLine::WaveIncoming | AnnouncerLine::WaveCleared => 10,
+            AnnouncerLine::AllyDown => 6,
+            AnnouncerLine::EliteSlain => 8,
+            AnnouncerLine::LevelUp => 5,
+        })
+    }
+}
+
+/// Keeps track of when the lines were played last.
+#[derive(Debug, Default)]
+pub struct AnnouncerCooldowns {
+    last_played: [Option<Duration>; 5],
+    last_any: Option<Duration>,
+}
+
+impl AnnouncerCooldowns {
+    /// Returns `true` and starts the cooldown if the line can be played at `now`.
+    pub fn try_play(&mut self, line: AnnouncerLine, now: Duration) -> bool {
+        let is_ready = |last_played: Option<Duration>, cooldown: Duration| {
+            last_played.map_or(true, |last_played| now >= last_played + cooldown)
+        };
+        let last_played = &mut self.last_played[line.index()];
+        if !is_ready(*last_played, line.cooldown()) || !is_ready(self.last_any, ANNOUNCER_GAP) {
+            return false;
+        }
+        *last_played = Some(now);
+        self.last_any = Some(now);
+        true
+    }
+
+    /// Is called when leaving a game, so that the next one starts with all the lines ready.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,178 @@
+//! Rules that unmodified clients never break. A server flags the frames its players break them
+//! on, and once a player has enough of them, saves the gameplay messages the player has sent
+//! as a `SuspiciousSession`, which `gv_server --review` replays for a reviewer.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    actions::player::CastSpell,
+    ecs::resources::world::PAUSE_FRAME_THRESHOLD,
+    net::{
+        client_message::ClientMessagePayload,
+        session_recording::{RecordedMessage, SessionRecordEntry},
+        NetIdentifier,
+    },
+};
+
+/// A session gets saved for review once a player has this many flagged frames in a game.
+/// Lag makes honest clients cast on cooldown now and then, so a few flags mean nothing.
+pub const SUSPICIOUS_VIOLATIONS: usize = 20;
+/// A server sees a caster a bit off from where the client was, casts further than
+/// `MAX_CAST_RANGE` by this much aren't flagged.
+pub const CAST_RANGE_TOLERANCE: f32 = 100.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Violation {
+    /// Actions for an entity the player doesn't control, they aren't applied.
+    ForeignEntity(NetIdentifier),
+    /// Actions for a frame too far ahead of the server, they aren't applied.
+    FutureFrame(u64),
+    /// A cast the server ignored, as the spell was still cooling down.
+    CastOnCooldown(CastSpell),
+    /// A cast targeting further than `MAX_CAST_RANGE`, the target gets clamped.
+    CastOutOfRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlaggedFrame {
+    /// Server game frame number.
+    pub frame_number: u64,
+    pub violation: Violation,
+}
+
+/// Checks a gameplay message of the player controlling `entity_net_id` that arrived at
+/// server frame `frame_number`. A message is flagged once, for its first violation.
+pub fn inspect_message(
+    entity_net_id: NetIdentifier,
+    frame_number: u64,
+    payload: &ClientMessagePayload,
+) -> Option<Violation> {
+    let (action_frames, entity_net_ids) = match payload {
+        ClientMessagePayload::WalkActions(actions) => (
+            vec![actions.frame_number],
+            actions
+                .updates
+                .iter()
+                .map(|update| update.entity_net_id)
+                .collect(),
+        ),
+        ClientMessagePayload::CastActions(actions) => (
+            vec![actions.frame_number],
+            actions
+                .updates
+                .iter()
+                .map(|update| update.entity_net_id)
+                .collect(),
+        ),
+        ClientMessagePayload::LookActions(actions) => (
+            actions
+                .updates
+                .iter()
+                .map(|(frame_number, _)| *frame_number)
+                .collect(),
+            actions
+                .updates
+                .iter()
+                .flat_map(|(_, updates)| updates.iter().map(|update| update.entity_net_id))
+                .collect(),
+        ),
+        ClientMessagePayload::PlayerInputs(input_frames) => (
+            input_frames
+                .iter()
+                .map(|input_frame| input_frame.frame_number)
+                .collect(),
+            input_frames
+                .iter()
+                .flat_map(|input_frame| {
+                    input_frame
+                        .walk_action_updates
+                        .iter()
+                        .map(|update| update.entity_net_id)
+                        .chain(
+                            input_frame
+                                .cast_action_updates
+                                .iter()
+                                .map(|update| update.entity_net_id),
+                        )
+                        .chain(
+                            input_frame
+                                .interact_action_updates
+                                .iter()
+                                .map(|update| update.entity_net_id),
+                        )
+                })
+                .collect::<Vec<_>>(),
+        ),
+        _ => return None,
+    };
+
+    if let Some(foreign_entity_net_id) = entity_net_ids.into_iter().find(|id| *id != entity_net_id)
+    {
+        return Some(Violation::ForeignEntity(foreign_entity_net_id));
+    }
+    action_frames
+        .into_iter()
+        .find(|action_frame| action_frame.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD)
+        .map(Violation::FutureFrame)
+}
+
+/// What a server saves for review, see the module docs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspiciousSession {
+    pub nickname: String,
+    pub entity_net_id: NetIdentifier,
+    /// Includes the frames flagged by the simulation, which messages alone can't tell.
+    pub flagged_frames: Vec<FlaggedFrame>,
+    /// Gameplay messages the player has sent (`RecordedMessage::Outcoming`), paired
+    /// with the server frames they arrived at.
+    pub entries: Vec<SessionRecordEntry>,
+}
+
+impl SuspiciousSession {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, self)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        bincode::deserialize_from(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Inspects the recorded messages again, the same way the server did, and merges
+    /// the results with the frames flagged by the simulation. Sorted by frame.
+    pub fn review(&self) -> Vec<FlaggedFrame> {
+        let mut flagged_frames = self
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.message {
+                RecordedMessage::Outcoming(payload) => {
+                    inspect_message(self.entity_net_id, entry.game_frame_number, payload).map(
+                        |violation| FlaggedFrame {
+                            frame_number: entry.game_frame_number,
+                            violation,
+                        },
+                    )
+                }
+                RecordedMessage::Incoming(_) => None,
+            })
+            .collect::<Vec<_>>();
+        flagged_frames.extend(self.flagged_frames.iter().cloned().filter(|flagged_frame| {
+            match flagged_frame.violation {
+                Violation::CastOnCooldown(_) | Violation::CastOutOfRange => true,
+                Violation::ForeignEntity(_) | Violation::FutureFrame(_) => false,
+            }
+        }));
+        flagged_frames.sort_by_key(|flagged_frame| flagged_frame.frame_number);
+        flagged_frames
+    }
+}
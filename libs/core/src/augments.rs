@@ -0,0 +1,65 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use serde_derive::{Deserialize, Serialize};
+
+/// Every this number of kills the team levels up and each player gets to choose an augment.
+pub const KILLS_PER_LEVEL: u32 = 25;
+pub const MAX_AUGMENTS: usize = 6;
+
+/// Angle between the neighbouring missiles of a multishot.
+pub const MULTISHOT_SPREAD: f32 = 0.2;
+/// Chained missiles jump only to monsters within this distance from the hit one.
+pub const CHAIN_RADIUS: f32 = 250.0;
+/// Each jump of a chained missile deals this fraction of the previous damage.
+pub const CHAIN_DAMAGE_FACTOR: f32 = 0.75;
+
+/// Augment icons are numbered in the same order in `resources/assets/augments.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Augment {
+    /// Casts an additional missile per stack.
+    Multishot,
+    /// Lets a missile go through an additional monster per stack.
+    Pierce,
+    /// Lets a missile jump to an additional nearby monster per stack.
+    Chain,
+}
+
+impl Augment {
+    /// Hotkeys are bound to `augment_1`, `augment_2`, etc. in the same order.
+    pub const ALL: [Augment; 3] = [Augment::Multishot, Augment::Pierce, Augment::Chain];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Augment::Multishot => "Multishot",
+            Augment::Pierce => "Pierce",
+            Augment::Chain => "Chain",
+        }
+    }
+
+    pub fn sprite_number(self) -> usize {
+        match self {
+            Augment::Multishot => 0,
+            Augment::Pierce => 1,
+            Augment::Chain => 2,
+        }
+    }
+}
+
+/// Augments in the order they were chosen, the same augment can be chosen several times.
+/// Isn't a part of SavedWorldState, so that rewinding the world doesn't drop chosen augments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Component)]
+pub struct PlayerAugments(pub Vec<Augment>);
+
+impl PlayerAugments {
+    pub fn count(&self, augment: Augment) -> u32 {
+        self.0.iter().filter(|chosen| **chosen == augment).count() as u32
+    }
+
+    /// A player gets a choice with every level after the first one.
+    pub fn can_choose(&self, level: u32) -> bool {
+        self.0.len() < MAX_AUGMENTS && (self.0.len() as u32) < level.saturating_sub(1)
+    }
+
+    pub fn missile_count(&self) -> u32 {
+        1 + self.count(Augment::Multishot)
+    }
+}
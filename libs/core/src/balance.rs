@@ -0,0 +1,418 @@
+//! Gameplay tunables, loaded from `resources/balance.ron`. A server sends its balance to clients
+//! before starting a game and, if it's run with `--watch-balance`, every time the file changes.
+//! Content packs can override parts of it, see `content_packs`.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    actions::mob::MobAttackType,
+    classes::PlayerClass,
+    collision_layers::CollisionMatrix,
+    damage::{DamageType, Resistances},
+    ecs::{
+        components::{aura::Aura, zone::ZoneKind},
+        resources::world::{
+            MAX_PENDING_WORLD_UPDATES, PAUSE_FRAME_THRESHOLD, ROLLBACK_INTERACTION_RADIUS,
+        },
+    },
+    math::{deterministic, Vector2},
+};
+
+static DEFAULT_BALANCE_BYTES: &[u8] = include_bytes!("../../../resources/balance.ron");
+/// Spawners look these definitions up by name.
+const SPAWNED_MONSTERS: [&str; 2] = ["Ghoul", "Wisp"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Balance {
+    pub classes: HashMap<PlayerClass, ClassDefinition>,
+    /// Definitions by monster names, `MonsterDefinition::name` is filled from the keys.
+    pub monsters: HashMap<String, MonsterDefinition>,
+    /// Missile spells by ids, classes refer to them with `MissileSpell::spell`.
+    pub spells: HashMap<String, SpellDefinition>,
+    pub pause: PauseThresholds,
+    #[serde(default)]
+    pub collisions: CollisionMatrix,
+}
+
+impl Balance {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Self::from_bytes(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut balance: Balance = ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        for (name, monster_definition) in &mut balance.monsters {
+            monster_definition.name = name.clone();
+        }
+        balance.validate()?;
+        Ok(balance)
+    }
+
+    /// Returns the balance with the definitions from a content pack's `balance.ron` replacing
+    /// the matching ones.
+    pub fn with_overrides(&self, bytes: &[u8]) -> Result<Self, String> {
+        let overrides: BalanceOverrides =
+            ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        let mut balance = self.clone();
+        balance.classes.extend(overrides.classes);
+        for (name, mut monster_definition) in overrides.monsters {
+            monster_definition.name = name.clone();
+            balance.monsters.insert(name, monster_definition);
+        }
+        balance.spells.extend(overrides.spells);
+        if let Some(pause) = overrides.pause {
+            balance.pause = pause;
+        }
+        if let Some(collisions) = overrides.collisions {
+            balance.collisions = collisions;
+        }
+        balance.validate()?;
+        Ok(balance)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for class in PlayerClass::ALL.iter() {
+            if !self.classes.contains_key(class) {
+                return Err(format!("Missing a definition for {}", class.name()));
+            }
+        }
+        for (class, class_definition) in &self.classes {
+            if !self.spells.contains_key(&class_definition.missile.spell) {
+                return Err(format!(
+                    "{}: unknown spell {}",
+                    class.name(),
+                    class_definition.missile.spell
+                ));
+            }
+            if let Some(melee) = &class_definition.melee {
+                // Rollbacks only re-simulate entities within this radius of the changed ones.
+                if melee.range <= 0.0 || melee.range > ROLLBACK_INTERACTION_RADIUS {
+                    return Err(format!(
+                        "{}: melee range must be in (0, {}]",
+                        class.name(),
+                        ROLLBACK_INTERACTION_RADIUS
+                    ));
+                }
+                if melee.arc_degrees <= 0.0 || melee.arc_degrees > 360.0 {
+                    return Err(format!(
+                        "{}: melee arc_degrees must be in (0, 360]",
+                        class.name()
+                    ));
+                }
+                if melee.max_targets == 0 {
+                    return Err(format!(
+                        "{}: melee max_targets must be at least 1",
+                        class.name()
+                    ));
+                }
+            }
+        }
+        for (id, spell_definition) in &self.spells {
+            if spell_definition.min_speed <= 0.0
+                || spell_definition.min_speed > spell_definition.max_speed
+            {
+                return Err(format!("{}: min_speed must be in (0, max_speed]", id));
+            }
+            if spell_definition.fade_secs <= 0.0
+                || spell_definition.fade_secs > spell_definition.lifespan_secs
+            {
+                return Err(format!("{}: fade_secs must be in (0, lifespan_secs]", id));
+            }
+            if spell_definition.damage_variance < 0.0 || spell_definition.damage_variance >= 1.0 {
+                return Err(format!("{}: damage_variance must be in [0, 1)", id));
+            }
+            if spell_definition.crit_chance < 0.0
+                || spell_definition.crit_chance > 1.0
+                || spell_definition.crit_multiplier < 1.0
+            {
+                return Err(format!(
+                    "{}: crit_chance must be in [0, 1] and crit_multiplier at least 1",
+                    id
+                ));
+            }
+        }
+        for name in &SPAWNED_MONSTERS {
+            if !self.monsters.contains_key(*name) {
+                return Err(format!("Missing a definition for {}", name));
+            }
+        }
+        // Rollbacks only re-simulate entities within this radius of the changed ones.
+        if let Some(monster_definition) = self.monsters.values().find(|monster_definition| {
+            monster_definition.aggro_radius > ROLLBACK_INTERACTION_RADIUS
+        }) {
+            return Err(format!(
+                "{}: aggro_radius can't be larger than {}",
+                monster_definition.name, ROLLBACK_INTERACTION_RADIUS
+            ));
+        }
+        let class_auras = self
+            .classes
+            .iter()
+            .map(|(class, class_definition)| (class.name().to_owned(), &class_definition.auras));
+        let monster_auras = self.monsters.values().map(|monster_definition| {
+            (
+                monster_definition.name.clone(),
+                &monster_definition.elite_auras,
+            )
+        });
+        for (name, auras) in class_auras.chain(monster_auras) {
+            if auras
+                .iter()
+                .any(|aura| aura.radius <= 0.0 || aura.radius > ROLLBACK_INTERACTION_RADIUS)
+            {
+                return Err(format!(
+                    "{}: aura radius must be in (0, {}]",
+                    name, ROLLBACK_INTERACTION_RADIUS
+                ));
+            }
+        }
+
+        // Clients can't lag behind longer than their actions stay valid on a server,
+        // and a server can't keep more pending updates than it has saved world states.
+        if self.pause.lagging_frames == 0 || self.pause.lagging_frames > PAUSE_FRAME_THRESHOLD {
+            return Err(format!(
+                "pause.lagging_frames must be in [1, {}]",
+                PAUSE_FRAME_THRESHOLD
+            ));
+        }
+        if self.pause.max_pending_world_updates == 0
+            || self.pause.max_pending_world_updates > MAX_PENDING_WORLD_UPDATES
+        {
+            return Err(format!(
+                "pause.max_pending_world_updates must be in [1, {}]",
+                MAX_PENDING_WORLD_UPDATES
+            ));
+        }
+        self.collisions.validate()?;
+        Ok(())
+    }
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        Self::from_bytes(DEFAULT_BALANCE_BYTES).expect("Expected a valid default balance")
+    }
+}
+
+/// Every field is optional, unlike in `Balance`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BalanceOverrides {
+    classes: HashMap<PlayerClass, ClassDefinition>,
+    monsters: HashMap<String, MonsterDefinition>,
+    spells: HashMap<String, SpellDefinition>,
+    pause: Option<PauseThresholds>,
+    collisions: Option<CollisionMatrix>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PauseThresholds {
+    /// A server pauses the game if a client hasn't answered a ping or has been lagging behind
+    /// for this many frames.
+    pub lagging_frames: u64,
+    /// A server pauses the game while a client has more world updates than this unacknowledged.
+    pub max_pending_world_updates: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonsterDefinition {
+    #[serde(default)]
+    pub name: String,
+    pub base_health: f32,
+    pub base_speed: f32,
+    pub base_attack_damage: f32,
+    pub attack_type: MobAttackType,
+    #[serde(default)]
+    pub attack_damage_type: DamageType,
+    /// Strength of the impulse pushing an attacked target away.
+    pub attack_knockback: f32,
+    pub collision_radius: f32,
+    /// Monsters without threat go after players closer than this.
+    pub aggro_radius: f32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Radiated by the monsters of this kind that spawn with affixes.
+    #[serde(default)]
+    pub elite_auras: Vec<Aura>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClassDefinition {
+    pub base_health: f32,
+    pub base_speed: f32,
+    /// A share taken off spell cooldowns before any modifiers, see `stats`.
+    #[serde(default)]
+    pub base_cooldown_reduction: f32,
+    /// Spell cooldowns recover this many times faster before any modifiers.
+    #[serde(default = "default_haste")]
+    pub base_haste: f32,
+    /// Scales the sprites all the classes share, doesn't affect collisions.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub missile: MissileSpell,
+    /// Replaces the missiles of the missile ability with melee swings.
+    #[serde(default)]
+    pub melee: Option<MeleeSpell>,
+    pub heal: HealSpell,
+    pub zone: ZoneSpell,
+    pub taunt: TauntSpell,
+    #[serde(default)]
+    pub auras: Vec<Aura>,
+    #[serde(default)]
+    pub resistances: Resistances,
+}
+
+fn default_haste() -> f32 {
+    1.0
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MissileSpell {
+    pub cooldown_secs: f32,
+    /// An id of the cast spell in `Balance::spells`.
+    pub spell: String,
+}
+
+/// A cone in front of the caster, swung with the missile ability. A swing keeps the cooldown
+/// of the ability and the damage, damage type, critical hits and knockback of its spell.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MeleeSpell {
+    /// Targets are hit if their circles reach this far from the caster.
+    pub range: f32,
+    /// The full angle of the cone, centered on the aimed direction.
+    pub arc_degrees: f32,
+    /// A swing cleaves through this many of the closest targets.
+    pub max_targets: usize,
+}
+
+impl MeleeSpell {
+    /// Whether a target circle is within the cone swung from `origin` towards `direction`.
+    pub fn hits(
+        &self,
+        origin: Vector2,
+        direction: Vector2,
+        target_position: Vector2,
+        target_radius: f32,
+    ) -> bool {
+        let offset = target_position - origin;
+        let distance = deterministic::norm(&offset);
+        if distance > self.range + target_radius {
+            return false;
+        }
+        // Targets overlapping the caster are hit from any side.
+        if distance <= target_radius || self.arc_degrees >= 360.0 {
+            return true;
+        }
+        let half_arc = self.arc_degrees.to_radians() / 2.0;
+        if deterministic::angle_between(&direction, &offset).abs() <= half_arc {
+            return true;
+        }
+        // The edges of the cone also catch the circles they cut through.
+        let direction = deterministic::normalize(&direction);
+        [half_arc, -half_arc].iter().any(|angle| {
+            let edge = deterministic::rotate(&direction, *angle);
+            let along = (offset.x * edge.x + offset.y * edge.y)
+                .max(0.0)
+                .min(self.range);
+            deterministic::norm(&(offset - edge * along)) <= target_radius
+        })
+    }
+}
+
+/// A projectile cast with the missile ability. Both a server and predicting clients
+/// spawn missiles from these, so they must be the same on every side (see `UpdateBalance`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpellDefinition {
+    pub damage: f32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    pub radius: f32,
+    /// Missiles are cast with this speed and slow down to `min_speed` while turning.
+    pub max_speed: f32,
+    pub min_speed: f32,
+    pub lifespan_secs: f32,
+    /// Missiles fade out during this many last seconds of their lifespan.
+    pub fade_secs: f32,
+    /// Strength of the impulse pushing a hit monster away.
+    pub knockback: f32,
+    /// How many times a missile ricochets off level borders.
+    pub bounces: u32,
+    #[serde(default)]
+    pub on_hit: Vec<OnHitEffect>,
+    /// Hits deal up to this share more or less than `damage`.
+    #[serde(default)]
+    pub damage_variance: f32,
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: f32,
+}
+
+fn default_crit_multiplier() -> f32 {
+    1.5
+}
+
+impl SpellDefinition {
+    pub fn pierces(&self) -> u32 {
+        self.on_hit
+            .iter()
+            .map(|effect| match effect {
+                OnHitEffect::Pierce(count) => *count,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    pub fn chains(&self) -> u32 {
+        self.on_hit
+            .iter()
+            .map(|effect| match effect {
+                OnHitEffect::Chain(count) => *count,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Adds up with the augments of the same names.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OnHitEffect {
+    /// Goes through this many more monsters before stopping.
+    Pierce(u32),
+    /// Jumps to this many more nearby monsters.
+    Chain(u32),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealSpell {
+    pub cooldown_secs: f32,
+    pub amount: f32,
+    pub radius: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZoneSpell {
+    pub kind: ZoneKind,
+    pub cooldown_secs: f32,
+    pub radius: f32,
+    pub duration_secs: f32,
+    /// Damage dealt to monsters inside of the zone.
+    pub damage_per_second: f32,
+    /// Monsters inside of the zone move with their speed multiplied by this factor.
+    pub speed_factor: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TauntSpell {
+    pub cooldown_secs: f32,
+    pub radius: f32,
+    /// Taunted monsters get this much more threat for the caster than for anyone else.
+    pub threat: f32,
+}
@@ -0,0 +1,34 @@
+/// A campaign level is completed once the players survive up to the given spawn level,
+/// the whole lobby then moves on to the next one.
+pub struct CampaignLevel {
+    pub name: &'static str,
+    pub spawn_level: usize,
+    pub completed_at_spawn_level: usize,
+    /// A `LevelScript` in RON, its `EndLevel` completes the level early.
+    pub script: Option<&'static [u8]>,
+}
+
+pub static CAMPAIGN_LEVELS: [CampaignLevel; 3] = [
+    CampaignLevel {
+        name: "The Outskirts",
+        spawn_level: 1,
+        completed_at_spawn_level: 3,
+        script: None,
+    },
+    CampaignLevel {
+        name: "The Dunes",
+        spawn_level: 3,
+        completed_at_spawn_level: 6,
+        script: Some(include_bytes!(
+            "../../../resources/levels/scripts/the_dunes.ron"
+        )),
+    },
+    CampaignLevel {
+        name: "The Nest",
+        spawn_level: 6,
+        completed_at_spawn_level: 10,
+        script: Some(include_bytes!(
+            "../../../resources/levels/scripts/the_nest.ron"
+        )),
+    },
+];
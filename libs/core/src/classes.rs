@@ -0,0 +1,45 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// Players choose a class in the lobby, its stats are defined in `resources/balance.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerClass {
+    Mage,
+    Warden,
+    Trickster,
+    /// Hits with melee swings instead of missiles, see `balance::MeleeSpell`.
+    Warrior,
+}
+
+impl PlayerClass {
+    pub const ALL: [PlayerClass; 4] = [
+        PlayerClass::Mage,
+        PlayerClass::Warden,
+        PlayerClass::Trickster,
+        PlayerClass::Warrior,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlayerClass::Mage => "Mage",
+            PlayerClass::Warden => "Warden",
+            PlayerClass::Trickster => "Trickster",
+            PlayerClass::Warrior => "Warrior",
+        }
+    }
+
+    /// Is used to cycle through the classes in the menu.
+    pub fn next(self) -> Self {
+        match self {
+            PlayerClass::Mage => PlayerClass::Warden,
+            PlayerClass::Warden => PlayerClass::Trickster,
+            PlayerClass::Trickster => PlayerClass::Warrior,
+            PlayerClass::Warrior => PlayerClass::Mage,
+        }
+    }
+}
+
+impl Default for PlayerClass {
+    fn default() -> Self {
+        PlayerClass::Mage
+    }
+}
@@ -0,0 +1,71 @@
+//! Who can hit whom. Missiles, zones, auras and mob attacks look up the layers of an attacker
+//! and of a target in the `CollisionMatrix` from `Balance::collisions`, so a balance file
+//! can make, for instance, monsters fight each other or player projectiles hit minions.
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CollisionLayer {
+    /// Players and their zones and auras.
+    Players,
+    /// Hostile monsters and their attacks and auras.
+    Monsters,
+    /// Monsters summoned by players.
+    Minions,
+    /// Missiles cast by players.
+    Projectiles,
+    /// Level obstacles, they stop missiles but never attack anything.
+    Props,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollisionMatrix {
+    /// Hits of the first layer of a pair land on the second one, everything else passes
+    /// through. Friendly fire in the room settings adds `(Projectiles, Players)` on top.
+    pub hits: Vec<(CollisionLayer, CollisionLayer)>,
+}
+
+impl CollisionMatrix {
+    pub fn hits(&self, attacker: CollisionLayer, target: CollisionLayer) -> bool {
+        self.hits.contains(&(attacker, target))
+    }
+
+    /// Layers that hits of the attacker land on.
+    pub fn targets(&self, attacker: CollisionLayer) -> impl Iterator<Item = CollisionLayer> + '_ {
+        self.hits
+            .iter()
+            .filter(move |(hit_attacker, _)| *hit_attacker == attacker)
+            .map(|(_, target)| *target)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (attacker, target) in &self.hits {
+            if *attacker == CollisionLayer::Props {
+                return Err("collisions: Props can't hit anything".to_owned());
+            }
+            if *target == CollisionLayer::Projectiles {
+                return Err("collisions: Projectiles can't be hit".to_owned());
+            }
+            if *target == CollisionLayer::Props && *attacker != CollisionLayer::Projectiles {
+                return Err(format!("collisions: {:?} can't hit Props", attacker));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CollisionMatrix {
+    /// Players and minions fight monsters, player projectiles pass through allies.
+    fn default() -> Self {
+        Self {
+            hits: vec![
+                (CollisionLayer::Players, CollisionLayer::Monsters),
+                (CollisionLayer::Monsters, CollisionLayer::Players),
+                (CollisionLayer::Monsters, CollisionLayer::Minions),
+                (CollisionLayer::Minions, CollisionLayer::Monsters),
+                (CollisionLayer::Projectiles, CollisionLayer::Monsters),
+                (CollisionLayer::Projectiles, CollisionLayer::Props),
+            ],
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Content packs are directories in `mods/` with monster and class definitions, levels, sprites
+//! and script hooks. A server advertises the hashes of its packs in the Handshake, clients have
+//! to have the same packs installed. Packs consisting only of definition files (`.ron` and
+//! `.rhai`) are small enough to be downloaded from the server, the rest have to be installed
+//! manually.
+//!
+//! Packs are applied in the order of their names:
+//! - `balance.ron` overrides the matching definitions of the balance (see `Balance::with_overrides`);
+//! - `scripts/hooks.rhai` replaces `resources/scripts/hooks.rhai`, the last pack having it wins.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::{fs, io, path::Path};
+
+use crate::{balance::Balance, net::server_message::ClientNetworkError};
+
+pub const BALANCE_FILE: &str = "balance.ron";
+pub const HOOKS_SCRIPT_FILE: &str = "scripts/hooks.rhai";
+/// Downloaded packs are sent in a single reliable message, which has to fit
+/// into a receive buffer.
+pub const MAX_DOWNLOAD_SIZE: usize = 12 * 1024;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// What a server advertises about each of its packs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContentPackInfo {
+    pub name: String,
+    pub hash: u64,
+    pub is_downloadable: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContentPackFile {
+    /// Relative to the pack directory, with `/` as a separator.
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContentPack {
+    pub name: String,
+    /// Sorted by paths.
+    pub files: Vec<ContentPackFile>,
+}
+
+impl ContentPack {
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let name = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| is_valid_name(name))
+            .ok_or_else(|| format!("{}: invalid content pack name", dir.display()))?
+            .to_owned();
+        let mut files = Vec::new();
+        load_files(dir, "", &mut files).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Self { name, files })
+    }
+
+    /// Writes the pack into `mods_dir/<name>`, replacing the files with the same paths.
+    pub fn save(&self, mods_dir: &Path) -> Result<(), String> {
+        self.validate()?;
+        let dir = mods_dir.join(&self.name);
+        for file in &self.files {
+            let path = file
+                .path
+                .split('/')
+                .fold(dir.clone(), |path, component| path.join(component));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("{}: {}", parent.display(), err))?;
+            }
+            fs::write(&path, &file.contents)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+        }
+        Ok(())
+    }
+
+    /// Downloaded packs come from untrusted servers, so their names and paths must not
+    /// point outside of a pack directory.
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_valid_name(&self.name) {
+            return Err(format!("Invalid content pack name {:?}", self.name));
+        }
+        if let Some(file) = self.files.iter().find(|file| !is_valid_path(&file.path)) {
+            return Err(format!("{}: invalid file path {:?}", self.name, file.path));
+        }
+        Ok(())
+    }
+
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.contents.as_slice())
+    }
+
+    /// FNV-1a of every path and its contents, it has to be the same on every platform.
+    pub fn hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        for file in &self.files {
+            let len = (file.contents.len() as u64).to_le_bytes();
+            let bytes = file
+                .path
+                .as_bytes()
+                .iter()
+                .chain(&[0])
+                .chain(&len)
+                .chain(&file.contents);
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    pub fn is_downloadable(&self) -> bool {
+        let size: usize = self.files.iter().map(|file| file.contents.len()).sum();
+        size <= MAX_DOWNLOAD_SIZE && self.files.iter().all(|file| is_definition_file(&file.path))
+    }
+
+    pub fn info(&self) -> ContentPackInfo {
+        ContentPackInfo {
+            name: self.name.clone(),
+            hash: self.hash(),
+            is_downloadable: self.is_downloadable(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ContentPacks {
+    /// Sorted by names.
+    packs: Vec<ContentPack>,
+}
+
+impl ContentPacks {
+    pub fn new(mut packs: Vec<ContentPack>) -> Self {
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { packs }
+    }
+
+    /// Loads every subdirectory of `mods_dir` as a pack, a missing directory means there are
+    /// no packs.
+    pub fn load(mods_dir: &Path) -> Result<Self, String> {
+        if !mods_dir.exists() {
+            return Ok(Self::default());
+        }
+        let mut packs = Vec::new();
+        let entries =
+            fs::read_dir(mods_dir).map_err(|err| format!("{}: {}", mods_dir.display(), err))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|err| format!("{}: {}", mods_dir.display(), err))?
+                .path();
+            if path.is_dir() {
+                packs.push(ContentPack::load(&path)?);
+            }
+        }
+        Ok(Self::new(packs))
+    }
+
+    pub fn packs(&self) -> &[ContentPack] {
+        &self.packs
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ContentPack> {
+        self.packs.iter().find(|pack| pack.name == name)
+    }
+
+    pub fn infos(&self) -> Vec<ContentPackInfo> {
+        self.packs.iter().map(ContentPack::info).collect()
+    }
+
+    /// Checks the installed packs against the ones advertised by a server, returns the names
+    /// of the packs that have to be downloaded.
+    pub fn verify(
+        &self,
+        advertised: &[ContentPackInfo],
+    ) -> Result<Vec<String>, ClientNetworkError> {
+        let mut missing = Vec::new();
+        for info in advertised {
+            match self.get(&info.name) {
+                Some(pack) if pack.hash() == info.hash => {}
+                Some(_) => return Err(ClientNetworkError::ContentPackMismatch(info.name.clone())),
+                None if info.is_downloadable => missing.push(info.name.clone()),
+                None => return Err(ClientNetworkError::MissingContentPack(info.name.clone())),
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Accepts a downloaded pack only if it's the one the server has advertised.
+    pub fn add_downloaded(
+        &mut self,
+        pack: ContentPack,
+        advertised: &[ContentPackInfo],
+    ) -> Result<(), ClientNetworkError> {
+        let is_advertised = advertised
+            .iter()
+            .any(|info| info.name == pack.name && info.hash == pack.hash());
+        if !is_advertised || pack.validate().is_err() {
+            return Err(ClientNetworkError::CorruptedContentPack(pack.name));
+        }
+        // Reliable messages may arrive twice.
+        if self.get(&pack.name).is_some() {
+            return Ok(());
+        }
+        self.packs.push(pack);
+        self.packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(())
+    }
+
+    /// The packs advertised by a server, a client plays only with them.
+    pub fn select(&self, advertised: &[ContentPackInfo]) -> Self {
+        Self::new(
+            advertised
+                .iter()
+                .filter_map(|info| self.get(&info.name).cloned())
+                .collect(),
+        )
+    }
+
+    pub fn apply_to_balance(&self, balance: &Balance) -> Result<Balance, String> {
+        self.packs
+            .iter()
+            .try_fold(balance.clone(), |balance, pack| {
+                match pack.file(BALANCE_FILE) {
+                    Some(overrides) => balance
+                        .with_overrides(overrides)
+                        .map_err(|err| format!("{}/{}: {}", pack.name, BALANCE_FILE, err)),
+                    None => Ok(balance),
+                }
+            })
+    }
+
+    /// Returns the name of the pack and the hooks script from the last pack having one.
+    pub fn hooks_script(&self) -> Option<(&str, &[u8])> {
+        self.packs
+            .iter()
+            .rev()
+            .find_map(|pack| Some((pack.name.as_str(), pack.file(HOOKS_SCRIPT_FILE)?)))
+    }
+}
+
+pub fn is_definition_file(path: &str) -> bool {
+    path.ends_with(".ron") || path.ends_with(".rhai")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.split('/').all(is_valid_name)
+}
+
+fn load_files(dir: &Path, prefix: &str, files: &mut Vec<ContentPackFile>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(file_name) if is_valid_name(file_name) => file_name,
+            // Hidden files, such as `.DS_Store`, and files with unsupported names aren't
+            // a part of a pack.
+            _ => continue,
+        };
+        let relative_path = format!("{}{}", prefix, file_name);
+        if path.is_dir() {
+            load_files(&path, &format!("{}/", relative_path), files)?;
+        } else {
+            files.push(ContentPackFile {
+                path: relative_path,
+                contents: fs::read(&path)?,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::PLAYER_COLORS;
+
+/// Colors a player can pick in the customization menu instead of the one assigned by slot.
+pub static COSMETIC_COLORS: [(&str, [f32; 3]); 8] = [
+    ("Red", [0.64, 0.12, 0.11]),
+    ("Blue", [0.04, 0.45, 0.69]),
+    ("Green", [0.0, 0.49, 0.26]),
+    ("Purple", [0.40, 0.3, 0.55]),
+    ("Orange", [0.85, 0.55, 0.1]),
+    ("Pink", [0.8, 0.35, 0.6]),
+    ("Teal", [0.1, 0.6, 0.6]),
+    ("Black", [0.2, 0.2, 0.2]),
+];
+
+/// Hat sprites are numbered in the same order in `resources/assets/hats.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hat {
+    Crown,
+    WizardHat,
+    Beanie,
+}
+
+impl Hat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hat::Crown => "Crown",
+            Hat::WizardHat => "Wizard hat",
+            Hat::Beanie => "Beanie",
+        }
+    }
+
+    pub fn sprite_number(self) -> usize {
+        match self {
+            Hat::Crown => 0,
+            Hat::WizardHat => 1,
+            Hat::Beanie => 2,
+        }
+    }
+}
+
+/// Is stored in the player profile and replicated to the other players in a room.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerCosmetics {
+    /// Index in `COSMETIC_COLORS`, players without a chosen color get one by their slot.
+    pub color: Option<usize>,
+    pub hat: Option<Hat>,
+}
+
+impl PlayerCosmetics {
+    pub fn color(&self, player_index: usize) -> [f32; 3] {
+        self.color
+            .and_then(|color| COSMETIC_COLORS.get(color))
+            .map(|(_, color)| *color)
+            .unwrap_or_else(|| PLAYER_COLORS[player_index.min(PLAYER_COLORS.len() - 1)])
+    }
+
+    /// Is used to cycle through the colors in the menu, the slot color comes after the last one.
+    pub fn next_color(&mut self) {
+        self.color = match self.color {
+            None => Some(0),
+            Some(color) if color + 1 < COSMETIC_COLORS.len() => Some(color + 1),
+            Some(_) => None,
+        };
+    }
+
+    /// Is used to cycle through the hats in the menu.
+    pub fn next_hat(&mut self) {
+        self.hat = match self.hat {
+            None => Some(Hat::Crown),
+            Some(Hat::Crown) => Some(Hat::WizardHat),
+            Some(Hat::WizardHat) => Some(Hat::Beanie),
+            Some(Hat::Beanie) => None,
+        };
+    }
+
+    pub fn color_name(&self) -> &'static str {
+        self.color
+            .and_then(|color| COSMETIC_COLORS.get(color))
+            .map_or("Default", |(name, _)| *name)
+    }
+
+    pub fn hat_name(&self) -> &'static str {
+        self.hat.map_or("None", Hat::name)
+    }
+}
@@ -0,0 +1,162 @@
+use backtrace::Backtrace;
+use lazy_static::lazy_static;
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as FmtWrite,
+    fs,
+    panic::{self, PanicInfo},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::gv_dirs::GvDirs;
+
+const LOG_TAIL_LINES: usize = 200;
+const CRASH_REPORT_EXTENSION: &str = "txt";
+
+lazy_static! {
+    static ref CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext::default());
+    static ref LOG_TAIL: Mutex<VecDeque<String>> =
+        Mutex::new(VecDeque::with_capacity(LOG_TAIL_LINES));
+}
+
+/// The state of the game which gets written into a crash report. Is updated every frame,
+/// as the panic hook can't access the world.
+#[derive(Debug, Default, Clone)]
+pub struct CrashContext {
+    pub engine_frame_number: u64,
+    pub game_frame_number: u64,
+    pub game_frame_number_absolute: u64,
+    /// Is set only on client side.
+    pub connection_status: Option<String>,
+    pub net_stats: Vec<String>,
+}
+
+pub fn update_crash_context(update: impl FnOnce(&mut CrashContext)) {
+    // Never blocks, as a panic might have happened while the lock was held.
+    if let Ok(mut crash_context) = CRASH_CONTEXT.try_lock() {
+        update(&mut crash_context);
+    }
+}
+
+/// Is meant to be called from a logger formatter, so that the crash report contains
+/// the most recent log lines.
+pub fn record_log_line(line: String) {
+    if let Ok(mut log_tail) = LOG_TAIL.try_lock() {
+        if log_tail.len() == LOG_TAIL_LINES {
+            log_tail.pop_front();
+        }
+        log_tail.push_back(line);
+    }
+}
+
+/// Writes a crash report before running the default panic hook.
+pub fn install_panic_hook(binary_name: &'static str) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        match write_crash_report(binary_name, panic_info) {
+            Ok(path) => eprintln!("The crash report is saved to {}", path.display()),
+            Err(err) => eprintln!("Failed to write a crash report: {:?}", err),
+        }
+        default_hook(panic_info);
+    }));
+}
+
+pub fn crash_reports_dir() -> PathBuf {
+    GvDirs::new().crash_reports_dir()
+}
+
+/// Returns the crash reports left by previous launches, the most recent ones go first.
+pub fn find_crash_reports() -> Vec<PathBuf> {
+    let mut crash_reports = fs::read_dir(crash_reports_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .map_or(false, |extension| extension == CRASH_REPORT_EXTENSION)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    crash_reports.sort_by(|lhs, rhs| rhs.cmp(lhs));
+    crash_reports
+}
+
+pub fn delete_crash_report(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        log::error!(
+            "Failed to delete the crash report {}: {:?}",
+            path.display(),
+            err
+        );
+    }
+}
+
+fn write_crash_report(binary_name: &str, panic_info: &PanicInfo) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let dir = crash_reports_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.{}",
+        timestamp, binary_name, CRASH_REPORT_EXTENSION
+    ));
+    fs::write(&path, crash_report(binary_name, panic_info))?;
+    Ok(path)
+}
+
+fn crash_report(binary_name: &str, panic_info: &PanicInfo) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "{} {} has crashed in the thread '{}': {}",
+        binary_name,
+        env!("CARGO_PKG_VERSION"),
+        thread::current().name().unwrap_or("<unnamed>"),
+        panic_info
+    );
+
+    let _ = writeln!(report, "\n== Game state ==");
+    match CRASH_CONTEXT.try_lock() {
+        Ok(crash_context) => {
+            let _ = writeln!(
+                report,
+                "Engine frame: {}\nGame frame: {} (absolute: {})",
+                crash_context.engine_frame_number,
+                crash_context.game_frame_number,
+                crash_context.game_frame_number_absolute
+            );
+            if let Some(connection_status) = &crash_context.connection_status {
+                let _ = writeln!(report, "Connection status: {}", connection_status);
+            }
+            for net_stats in &crash_context.net_stats {
+                let _ = writeln!(report, "{}", net_stats);
+            }
+        }
+        Err(_) => {
+            let _ = writeln!(report, "<unavailable>");
+        }
+    }
+
+    let _ = writeln!(report, "\n== Backtrace ==\n{:?}", Backtrace::new());
+
+    let _ = writeln!(report, "== Log tail ==");
+    match LOG_TAIL.try_lock() {
+        Ok(log_tail) => {
+            for line in log_tail.iter() {
+                let _ = writeln!(report, "{}", line);
+            }
+        }
+        Err(_) => {
+            let _ = writeln!(report, "<unavailable>");
+        }
+    }
+
+    report
+}
@@ -0,0 +1,88 @@
+//! Damage types and resistances. Hits keep the type of the spell, attack, zone or aura
+//! that dealt them in damage histories, and the health lost to them is resolved here
+//! against the resistances of the class or monster definition of the target.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Resistances can't make entities immune to a damage type.
+pub const MAX_RESISTANCE: f32 = 0.75;
+/// Negative resistances are weaknesses, they can't more than double the damage.
+pub const MIN_RESISTANCE: f32 = -1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Elemental,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        DamageType::Physical
+    }
+}
+
+impl DamageType {
+    /// Resistance icons are drawn in this order.
+    pub const ALL: [DamageType; 3] = [
+        DamageType::Physical,
+        DamageType::Magic,
+        DamageType::Elemental,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Physical => "Physical",
+            DamageType::Magic => "Magic",
+            DamageType::Elemental => "Elemental",
+        }
+    }
+}
+
+/// Shares of the damage of each type that are taken off, see `MAX_RESISTANCE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub magic: f32,
+    pub elemental: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Magic => self.magic,
+            DamageType::Elemental => self.elemental,
+        }
+    }
+
+    /// Resisted damage types, the ones with resistances above zero.
+    pub fn resisted(&self) -> impl Iterator<Item = DamageType> + '_ {
+        DamageType::ALL
+            .iter()
+            .copied()
+            .filter(move |damage_type| self.get(*damage_type) > 0.0)
+    }
+}
+
+/// Returns the health taken by a hit. The resistance to its type is applied first, then
+/// `damage_taken_factor` (see `Stat::DamageTaken`). Untyped damage isn't resisted,
+/// and negative damage heals in full.
+pub fn health_lost(
+    damage: f32,
+    damage_type: Option<DamageType>,
+    resistances: &Resistances,
+    damage_taken_factor: f32,
+) -> f32 {
+    if damage <= 0.0 {
+        return damage;
+    }
+    let resistance = damage_type.map_or(0.0, |damage_type| {
+        resistances
+            .get(damage_type)
+            .max(MIN_RESISTANCE)
+            .min(MAX_RESISTANCE)
+    });
+    damage * (1.0 - resistance) * damage_taken_factor
+}
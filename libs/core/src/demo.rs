@@ -0,0 +1,250 @@
+//! A lightweight skirmish that is played behind the main menu: a few bots circle around
+//! and shoot at the monsters that keep coming at them. It lives outside of the ECS world,
+//! so that it can't interfere with the game simulation, and it's deterministic for a seed.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::math::Vector2;
+
+pub const DEMO_BOTS: usize = 3;
+pub const DEMO_MONSTERS: usize = 8;
+
+const BOT_SPEED: f32 = 90.0;
+const MONSTER_SPEED: f32 = 45.0;
+const MISSILE_SPEED: f32 = 450.0;
+const MISSILE_TTL_SECS: f32 = 1.5;
+const CAST_INTERVAL_SECS: f32 = 0.7;
+const CAST_DISTANCE: f32 = 400.0;
+/// Bots back off from monsters that are closer than this.
+const KEEP_DISTANCE: f32 = 160.0;
+const HIT_DISTANCE: f32 = 24.0;
+const MONSTER_HEALTH: u32 = 3;
+/// Longer steps are split, so that a hitch doesn't let missiles skip over monsters.
+const MAX_STEP_SECS: f32 = 1.0 / 30.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoActor {
+    /// Is unique among all the actors and missiles of a skirmish.
+    pub id: u64,
+    pub position: Vector2,
+    pub velocity: Vector2,
+    pub looking_direction: Vector2,
+    health: u32,
+    cooldown: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoMissile {
+    pub id: u64,
+    pub position: Vector2,
+    pub velocity: Vector2,
+    pub age: f32,
+}
+
+pub struct DemoSkirmish {
+    half_size: Vector2,
+    rng: StdRng,
+    next_id: u64,
+    bots: Vec<DemoActor>,
+    monsters: Vec<DemoActor>,
+    missiles: Vec<DemoMissile>,
+    kills: u64,
+}
+
+impl DemoSkirmish {
+    /// Actors are kept inside the rectangle of `half_size` around the origin.
+    pub fn new(seed: u64, half_size: Vector2) -> Self {
+        let mut skirmish = Self {
+            half_size,
+            rng: StdRng::seed_from_u64(seed),
+            next_id: 0,
+            bots: Vec::with_capacity(DEMO_BOTS),
+            monsters: Vec::with_capacity(DEMO_MONSTERS),
+            missiles: Vec::new(),
+            kills: 0,
+        };
+        for i in 0..DEMO_BOTS {
+            let angle = i as f32 / DEMO_BOTS as f32 * std::f32::consts::PI * 2.0;
+            let position =
+                Vector2::new(angle.cos(), angle.sin()) * half_size.x.min(half_size.y) * 0.3;
+            let bot = skirmish.new_actor(position);
+            skirmish.bots.push(bot);
+        }
+        skirmish.respawn_monsters();
+        skirmish
+    }
+
+    pub fn bots(&self) -> &[DemoActor] {
+        &self.bots
+    }
+
+    pub fn monsters(&self) -> &[DemoActor] {
+        &self.monsters
+    }
+
+    pub fn missiles(&self) -> &[DemoMissile] {
+        &self.missiles
+    }
+
+    pub fn kills(&self) -> u64 {
+        self.kills
+    }
+
+    pub fn half_size(&self) -> Vector2 {
+        self.half_size
+    }
+
+    pub fn step(&mut self, delta_seconds: f32) {
+        let mut delta_seconds = delta_seconds.max(0.0);
+        while delta_seconds > 0.0 {
+            let step_seconds = delta_seconds.min(MAX_STEP_SECS);
+            self.step_once(step_seconds);
+            delta_seconds -= step_seconds;
+        }
+    }
+
+    fn step_once(&mut self, delta_seconds: f32) {
+        let half_size = self.half_size;
+
+        let mut new_missiles = Vec::new();
+        for bot in &mut self.bots {
+            let target = nearest(bot.position, &self.monsters);
+            let (walking_direction, looking_direction) = match target {
+                Some(target) => {
+                    let to_target = target - bot.position;
+                    let distance = to_target.norm();
+                    let radial = direction(to_target);
+                    // Circling around the target, backing off if it gets too close.
+                    let tangent = Vector2::new(-radial.y, radial.x);
+                    let walking_direction = if distance < KEEP_DISTANCE {
+                        direction(tangent - radial)
+                    } else {
+                        tangent
+                    };
+                    if bot.cooldown <= 0.0 && distance < CAST_DISTANCE {
+                        bot.cooldown = CAST_INTERVAL_SECS;
+                        new_missiles.push((bot.position, radial * MISSILE_SPEED));
+                    }
+                    (walking_direction, radial)
+                }
+                None => (Vector2::zeros(), bot.looking_direction),
+            };
+            bot.cooldown -= delta_seconds;
+            bot.velocity = walking_direction * BOT_SPEED;
+            bot.looking_direction = looking_direction;
+            bot.position = clamp(bot.position + bot.velocity * delta_seconds, half_size);
+        }
+        for (position, velocity) in new_missiles {
+            let id = self.next_id();
+            self.missiles.push(DemoMissile {
+                id,
+                position,
+                velocity,
+                age: 0.0,
+            });
+        }
+
+        for monster in &mut self.monsters {
+            if let Some(target) = nearest(monster.position, &self.bots) {
+                let to_target = target - monster.position;
+                monster.looking_direction = direction(to_target);
+                // Monsters stop once they reach a bot, bots never die.
+                monster.velocity = if to_target.norm() > HIT_DISTANCE {
+                    monster.looking_direction * MONSTER_SPEED
+                } else {
+                    Vector2::zeros()
+                };
+                monster.position += monster.velocity * delta_seconds;
+            }
+        }
+
+        let monsters = &mut self.monsters;
+        self.missiles.retain(|missile| {
+            missile.age < MISSILE_TTL_SECS
+                && missile.position.x.abs() <= half_size.x
+                && missile.position.y.abs() <= half_size.y
+        });
+        for missile in &mut self.missiles {
+            missile.position += missile.velocity * delta_seconds;
+            missile.age += delta_seconds;
+        }
+        let mut kills = 0;
+        self.missiles.retain(|missile| {
+            let hit_monster = monsters.iter_mut().find(|monster| {
+                monster.health > 0 && (monster.position - missile.position).norm() < HIT_DISTANCE
+            });
+            match hit_monster {
+                Some(monster) => {
+                    monster.health -= 1;
+                    if monster.health == 0 {
+                        kills += 1;
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+        monsters.retain(|monster| monster.health > 0);
+        self.kills += kills;
+
+        self.respawn_monsters();
+    }
+
+    /// Monsters come from the edges of the rectangle.
+    fn respawn_monsters(&mut self) {
+        while self.monsters.len() < DEMO_MONSTERS {
+            let half_size = self.half_size;
+            let along_edge = self.rng.gen_range(-1.0, 1.0);
+            let position = match self.rng.gen_range(0, 4) {
+                0 => Vector2::new(-half_size.x, half_size.y * along_edge),
+                1 => Vector2::new(half_size.x, half_size.y * along_edge),
+                2 => Vector2::new(half_size.x * along_edge, -half_size.y),
+                _ => Vector2::new(half_size.x * along_edge, half_size.y),
+            };
+            let mut monster = self.new_actor(position);
+            monster.health = MONSTER_HEALTH;
+            self.monsters.push(monster);
+        }
+    }
+
+    fn new_actor(&mut self, position: Vector2) -> DemoActor {
+        DemoActor {
+            id: self.next_id(),
+            position,
+            velocity: Vector2::zeros(),
+            looking_direction: Vector2::new(0.0, 1.0),
+            health: 1,
+            cooldown: 0.0,
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+fn nearest(position: Vector2, actors: &[DemoActor]) -> Option<Vector2> {
+    actors.iter().map(|actor| actor.position).min_by(|a, b| {
+        (a - position)
+            .norm_squared()
+            .partial_cmp(&(b - position).norm_squared())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })
+}
+
+fn direction(vector: Vector2) -> Vector2 {
+    if vector.norm_squared() > 0.0 {
+        vector.normalize()
+    } else {
+        Vector2::new(0.0, 1.0)
+    }
+}
+
+fn clamp(position: Vector2, half_size: Vector2) -> Vector2 {
+    Vector2::new(
+        position.x.max(-half_size.x).min(half_size.x),
+        position.y.max(-half_size.y).min(half_size.y),
+    )
+}
@@ -0,0 +1,90 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::stats::{Modifier, ModifierSource, Stat, StatModifiers};
+
+/// Coins for each player who has hurt a killed monster.
+pub const KILL_REWARD: u32 = 2;
+/// Coins for every player when a wave ends.
+pub const WAVE_REWARD: u32 = 20;
+/// Items last for this number of frames after being bought.
+pub const ITEM_DURATION_FRAMES: u64 = 60 * 60;
+
+/// Items are listed in the shop (and bound to `buy_1`, `buy_2`, etc.) in the order of `ALL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ShopItem {
+    /// Increases the damage of missiles.
+    Whetstone,
+    /// Decreases the damage taken from monsters.
+    Ward,
+    /// Increases the walking speed.
+    SwiftBoots,
+}
+
+impl ShopItem {
+    pub const ALL: [ShopItem; 3] = [ShopItem::Whetstone, ShopItem::Ward, ShopItem::SwiftBoots];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShopItem::Whetstone => "Whetstone",
+            ShopItem::Ward => "Ward",
+            ShopItem::SwiftBoots => "Swift boots",
+        }
+    }
+
+    pub fn price(self) -> u32 {
+        match self {
+            ShopItem::Whetstone => 40,
+            ShopItem::Ward => 40,
+            ShopItem::SwiftBoots => 25,
+        }
+    }
+
+    pub fn stat_modifier(self) -> (Stat, Modifier) {
+        match self {
+            ShopItem::Whetstone => (Stat::MissileDamage, Modifier::Multiply(1.3)),
+            ShopItem::Ward => (Stat::DamageTaken, Modifier::Multiply(0.7)),
+            ShopItem::SwiftBoots => (Stat::MoveSpeed, Modifier::Multiply(1.2)),
+        }
+    }
+}
+
+/// Coins and bought items of a player, the authoritative side is the only one to change it.
+/// Isn't a part of SavedWorldState, so that rewinding the world doesn't drop purchases.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Component)]
+pub struct PlayerWallet {
+    pub coins: u32,
+    /// Bought items paired with the frames they expire at.
+    pub items: Vec<(ShopItem, u64)>,
+}
+
+impl PlayerWallet {
+    pub fn has_item(&self, item: ShopItem, frame_number: u64) -> bool {
+        self.items
+            .iter()
+            .any(|(bought_item, expires_at)| *bought_item == item && frame_number < *expires_at)
+    }
+
+    /// Buying an item that is still active extends its duration.
+    pub fn buy(&mut self, item: ShopItem, frame_number: u64) -> bool {
+        if self.coins < item.price() {
+            return false;
+        }
+        self.coins -= item.price();
+        self.items
+            .retain(|(bought_item, expires_at)| *bought_item != item && frame_number < *expires_at);
+        let expires_at = frame_number + ITEM_DURATION_FRAMES;
+        self.items.push((item, expires_at));
+        true
+    }
+
+    /// Adds the modifiers of the items that haven't expired yet.
+    pub fn add_stat_modifiers(&self, frame_number: u64, stat_modifiers: &mut StatModifiers) {
+        for (item, _) in &self.items {
+            if self.has_item(*item, frame_number) {
+                let (stat, modifier) = item.stat_modifier();
+                stat_modifiers.add(stat, ModifierSource::Item(*item), modifier);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::stats::{Modifier, ModifierSource, Stat, StatModifiers};
+
+/// Regenerating monsters restore health every `REGENERATION_TICK_FRAMES` frames.
+pub const REGENERATION_TICK_FRAMES: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MonsterAffix {
+    Fast,
+    Armored,
+    Regenerating,
+    /// Splits into two monsters on death, the new ones keep the rest of the affixes.
+    Splitting,
+}
+
+impl MonsterAffix {
+    pub const ALL: [MonsterAffix; 4] = [
+        MonsterAffix::Fast,
+        MonsterAffix::Armored,
+        MonsterAffix::Regenerating,
+        MonsterAffix::Splitting,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MonsterAffix::Fast => "Fast",
+            MonsterAffix::Armored => "Armored",
+            MonsterAffix::Regenerating => "Regenerating",
+            MonsterAffix::Splitting => "Splitting",
+        }
+    }
+}
+
+impl Distribution<MonsterAffix> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MonsterAffix {
+        MonsterAffix::ALL[rng.gen_range(0, MonsterAffix::ALL.len())]
+    }
+}
+
+/// Affixes of an elite monster. They are rolled by a server when scheduling a spawn
+/// and never change afterwards, so they aren't a part of the saved world state.
+#[derive(Clone, Debug, Default, Component)]
+pub struct MonsterAffixes {
+    pub affixes: Vec<MonsterAffix>,
+}
+
+impl MonsterAffixes {
+    pub fn new(affixes: Vec<MonsterAffix>) -> Self {
+        Self { affixes }
+    }
+
+    pub fn has(&self, affix: MonsterAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// Fast monsters move one and a half times faster, armored ones take half of the damage.
+    pub fn add_stat_modifiers(&self, stat_modifiers: &mut StatModifiers) {
+        for affix in &self.affixes {
+            let (stat, modifier) = match affix {
+                MonsterAffix::Fast => (Stat::MoveSpeed, Modifier::Multiply(1.5)),
+                MonsterAffix::Armored => (Stat::DamageTaken, Modifier::Multiply(0.5)),
+                _ => continue,
+            };
+            stat_modifiers.add(stat, ModifierSource::Affix(*affix), modifier);
+        }
+    }
+
+    /// A share of the base health restored per second.
+    pub fn regeneration_rate(&self) -> f32 {
+        if self.has(MonsterAffix::Regenerating) {
+            0.08
+        } else {
+            0.0
+        }
+    }
+
+    /// Affixes passed to the monsters a splitting one turns into.
+    pub fn split_affixes(&self) -> Vec<MonsterAffix> {
+        self.affixes
+            .iter()
+            .cloned()
+            .filter(|affix| *affix != MonsterAffix::Splitting)
+            .collect()
+    }
+}
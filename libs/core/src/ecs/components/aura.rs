@@ -0,0 +1,42 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::damage::DamageType;
+
+/// Auras affect entities every `AURA_TICK_FRAMES` frames (scaled to the tick rate).
+pub const AURA_TICK_FRAMES: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AuraEffect {
+    /// Damages the enemies of the owner.
+    Damage {
+        per_second: f32,
+        #[serde(default)]
+        damage_type: DamageType,
+    },
+    /// Enemies of the owner move with their speed multiplied by this factor.
+    Slow { speed_factor: f32 },
+    /// Heals the allies of the owner, including the owner itself.
+    Healing { per_second: f32 },
+}
+
+impl AuraEffect {
+    pub fn affects_enemies(self) -> bool {
+        match self {
+            AuraEffect::Damage { .. } | AuraEffect::Slow { .. } => true,
+            AuraEffect::Healing { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aura {
+    pub radius: f32,
+    pub effect: AuraEffect,
+}
+
+/// Effects radiating from a player or an elite monster. Auras are attached from the class
+/// and monster definitions on spawn, so both a server and clients know them without
+/// sending anything over the network.
+#[derive(Debug, Clone, Default, Component)]
+pub struct Auras(pub Vec<Aura>);
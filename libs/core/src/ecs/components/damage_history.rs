@@ -1,6 +1,10 @@
 use amethyst::ecs::prelude::{Component, DenseVecStorage, FlaggedStorage};
 use serde_derive::{Deserialize, Serialize};
 
+use std::ops::RangeInclusive;
+
+use crate::{damage::DamageType, math::Vector2};
+
 pub struct DamageHistory {
     pub history: Vec<DamageHistoryEntries>,
 }
@@ -63,6 +67,15 @@ impl DamageHistory {
         &self.history[i as usize]
     }
 
+    /// Returns `true` if any damage of the type has been taken within the frames.
+    pub fn has_damage(&self, frames: RangeInclusive<u64>, damage_type: DamageType) -> bool {
+        self.history
+            .iter()
+            .filter(|entries| frames.contains(&entries.frame_number))
+            .flat_map(|entries| &entries.entries)
+            .any(|entry| entry.damage > 0.0 && entry.damage_type == Some(damage_type))
+    }
+
     fn reserve_entries(&mut self, frame_number: u64) {
         let start_frame_number = self
             .history
@@ -93,5 +106,14 @@ impl DamageHistoryEntries {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DamageHistoryEntry {
+    /// Negative damage heals.
     pub damage: f32,
+    /// Knockback added to the entity's Impulse.
+    pub impulse: Vector2,
+    /// Critical hits are shown with larger damage numbers.
+    #[serde(default)]
+    pub is_crit: bool,
+    /// Heals and untyped damage (minions expiring) ignore resistances.
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
 }
@@ -1,34 +1,108 @@
 use amethyst::ecs::{Component, DenseVecStorage, Entity};
 
-use crate::{math::Vector2, net::NetIdentifier};
+use crate::{
+    augments::{Augment, PlayerAugments},
+    balance::SpellDefinition,
+    damage::DamageType,
+    math::{deterministic, Vector2},
+    net::NetIdentifier,
+};
 
 #[derive(Clone, Debug, Component)]
 pub struct Missile {
     pub action_id: NetIdentifier,
+    /// Tells apart the missiles of a multishot cast, which share the action id.
+    pub shot: u32,
+    /// Monsters hit by the missile direct their threat at the caster.
+    pub caster: Entity,
     pub radius: f32,
     pub target: MissileTarget<Entity>,
     pub velocity: Vector2,
     pub frame_spawned: u64,
     pub damage: f32,
+    pub knockback: f32,
+    pub max_speed: f32,
+    pub min_speed: f32,
+    pub lifespan_secs: f32,
+    pub fade_secs: f32,
+    pub damage_type: DamageType,
+    pub damage_variance: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    /// How many more monsters the missile can go through (see `Augment::Pierce`).
+    pub pierces_left: u32,
+    /// How many more times the missile can jump to a nearby monster (see `Augment::Chain`).
+    pub chains_left: u32,
+    /// How many more times the missile can bounce off level borders.
+    pub bounces_left: u32,
+    /// Monsters that piercing and chained missiles have already hit and won't hit again.
+    pub hit_monsters: Vec<Entity>,
 }
 
 impl Missile {
     pub fn new(
         action_id: u64,
-        radius: f32,
+        shot: u32,
+        caster: Entity,
+        spell_definition: &SpellDefinition,
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
     ) -> Self {
         Self {
             action_id,
-            radius,
+            shot,
+            caster,
+            radius: spell_definition.radius,
             target,
             velocity,
             frame_spawned,
-            damage: 50.0,
+            damage: spell_definition.damage,
+            knockback: spell_definition.knockback,
+            max_speed: spell_definition.max_speed,
+            min_speed: spell_definition.min_speed,
+            lifespan_secs: spell_definition.lifespan_secs,
+            fade_secs: spell_definition.fade_secs,
+            damage_type: spell_definition.damage_type,
+            damage_variance: spell_definition.damage_variance,
+            crit_chance: spell_definition.crit_chance,
+            crit_multiplier: spell_definition.crit_multiplier,
+            pierces_left: spell_definition.pierces(),
+            chains_left: spell_definition.chains(),
+            bounces_left: spell_definition.bounces,
+            hit_monsters: Vec::new(),
         }
     }
+
+    pub fn with_augments(mut self, augments: &PlayerAugments) -> Self {
+        self.pierces_left += augments.count(Augment::Pierce);
+        self.chains_left += augments.count(Augment::Chain);
+        self
+    }
+
+    /// Rolls the damage of the next hit. Rolls are keyed by the frame, the cast action,
+    /// the shot and the number of monsters hit before, so every side agrees on them.
+    pub fn roll_damage(&self, frame_number: u64) -> DamageRoll {
+        let index = (u64::from(self.shot) << 32) | (self.hit_monsters.len() as u64 * 2);
+        let variance = deterministic::roll(frame_number, self.action_id, index) * 2.0 - 1.0;
+        let is_crit =
+            deterministic::roll(frame_number, self.action_id, index + 1) < self.crit_chance;
+        let damage = self.damage * (1.0 + self.damage_variance * variance);
+        DamageRoll {
+            damage: if is_crit {
+                damage * self.crit_multiplier
+            } else {
+                damage
+            },
+            is_crit,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DamageRoll {
+    pub damage: f32,
+    pub is_crit: bool,
 }
 
 #[derive(Clone, Debug)]
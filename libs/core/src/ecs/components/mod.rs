@@ -1,5 +1,8 @@
+pub mod affixes;
+pub mod aura;
 pub mod damage_history;
 pub mod missile;
+pub mod zone;
 
 use amethyst::ecs::{Component, DenseVecStorage, Entity, VecStorage};
 use serde_derive::{Deserialize, Serialize};
@@ -14,14 +17,21 @@ use std::{
 use crate::{
     actions::{
         mob::MobAction,
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        player::{
+            CastSpell, PlayerCastAction, PlayerInteractAction, PlayerLookAction, PlayerWalkAction,
+        },
         Action,
     },
+    classes::PlayerClass,
     math::{Vector2, ZeroVector},
-    net::NetIdentifier,
+    net::{clock_sync::ClockSync, NetIdentifier},
 };
 
 const PING_PONG_STORAGE_LIMIT: usize = 20;
+/// An impulse keeps this share of its speed each frame.
+const IMPULSE_DECAY: f32 = 0.8;
+const IMPULSE_MIN_SPEED: f32 = 5.0;
+const THREAT_MIN: f32 = 0.5;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Shrinkwrap, Component)]
 #[shrinkwrap(mutable)]
@@ -63,7 +73,9 @@ impl From<WorldPosition> for NetWorldPosition {
 
 #[derive(Clone, Debug, Component)]
 pub struct Player {
+    pub class: PlayerClass,
     pub health: f32,
+    pub max_health: f32,
     pub velocity: Vector2,
     pub walking_direction: Vector2,
     pub looking_direction: Vector2,
@@ -72,8 +84,14 @@ pub struct Player {
 
 impl Player {
     pub fn new() -> Self {
+        Self::with_class(PlayerClass::default(), 100.0)
+    }
+
+    pub fn with_class(class: PlayerClass, max_health: f32) -> Self {
         Self {
-            health: 100.0,
+            class,
+            health: max_health,
+            max_health,
             velocity: Vector2::zero(),
             walking_direction: Vector2::new(0.0, 1.0),
             looking_direction: Vector2::new(0.0, 1.0),
@@ -93,6 +111,8 @@ pub struct PlayerActions {
     pub walk_action: PlayerWalkAction,
     pub look_action: PlayerLookAction,
     pub cast_action: Option<PlayerCastAction>,
+    /// Is set for a single frame in single player, LevelScriptSystem reads it.
+    pub interact_action: Option<PlayerInteractAction>,
 }
 
 /// We write the actions to this component right on input from client, they get processed and
@@ -103,12 +123,36 @@ pub struct ClientPlayerActions {
     pub walk_action: PlayerWalkAction,
     pub look_action: PlayerLookAction,
     pub cast_action: Option<PlayerCastAction>,
+    pub interact_action: Option<PlayerInteractAction>,
 }
 
 /// Stores frame numbers.
-#[derive(Clone, Default, Component)]
+#[derive(Clone, Debug, Default, PartialEq, Component, Serialize, Deserialize)]
 pub struct PlayerLastCastedSpells {
     pub missile: u64,
+    pub heal: u64,
+    pub zone: u64,
+    pub taunt: u64,
+}
+
+impl PlayerLastCastedSpells {
+    pub fn get(&self, spell: CastSpell) -> u64 {
+        match spell {
+            CastSpell::Missile => self.missile,
+            CastSpell::Heal => self.heal,
+            CastSpell::Zone => self.zone,
+            CastSpell::Taunt => self.taunt,
+        }
+    }
+
+    pub fn set(&mut self, spell: CastSpell, frame_number: u64) {
+        match spell {
+            CastSpell::Missile => self.missile = frame_number,
+            CastSpell::Heal => self.heal = frame_number,
+            CastSpell::Zone => self.zone = frame_number,
+            CastSpell::Taunt => self.taunt = frame_number,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Component)]
@@ -121,6 +165,106 @@ pub struct Monster {
     pub action: Action<MobAction<Entity>>,
     pub name: String,
     pub radius: f32,
+    /// The frame a monster finishes emerging at, see `SpawnAction::telegraph_frames`.
+    pub active_at_frame: u64,
+}
+
+impl Monster {
+    pub fn is_emerging(&self, frame_number: u64) -> bool {
+        frame_number < self.active_at_frame
+    }
+}
+
+/// Marks a mob summoned by a player, such mobs attack monsters instead of players.
+/// Mobs without this component are hostile to everyone.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct Allegiance {
+    pub owner: Entity,
+    /// A minion dies on this frame if it hasn't been killed before.
+    pub expires_at_frame: u64,
+}
+
+/// Knockback pushing an entity on top of its own movement, fades out in a few frames.
+/// It's a part of the saved world state, so rewinds reproduce the same trajectories.
+#[derive(Clone, Debug, Default, Component)]
+pub struct Impulse {
+    pub velocity: Vector2,
+}
+
+impl Impulse {
+    pub fn add(&mut self, impulse: Vector2) {
+        self.velocity += impulse;
+    }
+
+    /// Returns the displacement for the frame and decays the impulse.
+    pub fn step(&mut self, frame_seconds: f32) -> Vector2 {
+        let displacement = self.velocity * frame_seconds;
+        self.velocity *= IMPULSE_DECAY;
+        if self.velocity.norm_squared() < IMPULSE_MIN_SPEED * IMPULSE_MIN_SPEED {
+            self.velocity = Vector2::zero();
+        }
+        displacement
+    }
+}
+
+/// Threat generated on a monster by players and minions, the monster chases the target
+/// with the highest threat. It's only updated on the authoritative side, which decides
+/// monster actions, but it's a part of the saved world state to survive rewinds.
+#[derive(Clone, Debug, Default, Component)]
+pub struct ThreatTable {
+    pub entries: Vec<(Entity, f32)>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, target: Entity, threat: f32) {
+        if let Some((_, target_threat)) = self
+            .entries
+            .iter_mut()
+            .find(|(entity, _)| *entity == target)
+        {
+            *target_threat += threat;
+        } else {
+            self.entries.push((target, threat));
+        }
+    }
+
+    pub fn threat(&self, target: Entity) -> f32 {
+        self.entries
+            .iter()
+            .find(|(entity, _)| *entity == target)
+            .map_or(0.0, |(_, threat)| *threat)
+    }
+
+    pub fn top_threat(&self) -> f32 {
+        self.entries
+            .iter()
+            .map(|(_, threat)| *threat)
+            .fold(0.0, f32::max)
+    }
+
+    /// Multiplies all the threat by `factor`, forgetting the targets with too little of it.
+    pub fn decay(&mut self, factor: f32) {
+        for (_, threat) in &mut self.entries {
+            *threat *= factor;
+        }
+        self.entries.retain(|(_, threat)| *threat >= THREAT_MIN);
+    }
+
+    /// Returns the target with the highest threat out of the ones passing the filter.
+    /// Ties are won by the target that has generated threat earlier.
+    pub fn top_target(&self, is_valid: impl Fn(Entity) -> bool) -> Option<Entity> {
+        self.entries
+            .iter()
+            .filter(|(entity, _)| is_valid(*entity))
+            .fold(
+                None,
+                |top: Option<(Entity, f32)>, (entity, threat)| match top {
+                    Some((_, top_threat)) if top_threat >= *threat => top,
+                    _ => Some((*entity, *threat)),
+                },
+            )
+            .map(|(entity, _)| entity)
+    }
 }
 
 #[derive(Clone, Default, Component)]
@@ -156,6 +300,12 @@ pub struct NetConnectionModel {
     pub disconnected: bool,
     pub session_created_at: Duration,
     pub session_id: NetIdentifier,
+    /// The latest client action id received with PlayerInputs, the ones up to it
+    /// are redundant copies.
+    pub last_client_action_id: Option<NetIdentifier>,
+    /// Overrides `PauseThresholds::lagging_frames` for this connection on a server,
+    /// and `PAUSE_FRAME_THRESHOLD` on a client, see `SetPauseThreshold`.
+    pub pause_lagging_frames: Option<u64>,
 }
 
 impl NetConnectionModel {
@@ -169,6 +319,8 @@ impl NetConnectionModel {
             disconnected: false,
             session_created_at: Duration::new(0, 0),
             session_id,
+            last_client_action_id: None,
+            pause_lagging_frames: None,
         }
     }
 }
@@ -177,6 +329,8 @@ impl NetConnectionModel {
 pub struct PingPongData {
     pub last_pinged_at: Instant,
     pub last_ponged_frame: u64,
+    /// Estimates the current frame of the peer, see `PingPongData::add_pong`.
+    pub clock_sync: ClockSync,
     data: VecDeque<PingPong>,
 }
 
@@ -185,11 +339,19 @@ impl PingPongData {
         Self {
             last_pinged_at: Instant::now(),
             last_ponged_frame: 0,
+            clock_sync: ClockSync::default(),
             data: VecDeque::with_capacity(PING_PONG_STORAGE_LIMIT),
         }
     }
 
-    pub fn add_ping(&mut self, ping_id: NetIdentifier, engine_frame_number: u64) {
+    /// `clock_frame_number` is `GameTimeService::game_frame_number_absolute`, which
+    /// keeps counting during pauses.
+    pub fn add_ping(
+        &mut self,
+        ping_id: NetIdentifier,
+        engine_frame_number: u64,
+        clock_frame_number: u64,
+    ) {
         self.last_pinged_at = Instant::now();
         if self.data.len() == PING_PONG_STORAGE_LIMIT {
             self.data.pop_front();
@@ -197,6 +359,7 @@ impl PingPongData {
         self.data.push_back(PingPong {
             ping_id,
             sent_ping_engine_frame: engine_frame_number,
+            sent_ping_clock_frame: clock_frame_number,
             pong: None,
         })
     }
@@ -207,6 +370,7 @@ impl PingPongData {
         peer_frame_number: u64,
         engine_frame_number: u64,
         frame_number: u64,
+        clock_frame_number: u64,
     ) {
         if self.last_ponged_frame < engine_frame_number {
             self.last_ponged_frame = engine_frame_number;
@@ -224,7 +388,35 @@ impl PingPongData {
                 received_engine_frame: engine_frame_number,
                 received_game_frame: frame_number,
                 estimated_peer_frame_number,
-            })
+            });
+
+            // A ping sent before a level start can't be measured with the restarted clock.
+            if let Some(round_trip_frames) =
+                clock_frame_number.checked_sub(ping_pong.sent_ping_clock_frame)
+            {
+                self.clock_sync.add_sample(
+                    clock_frame_number,
+                    peer_frame_number,
+                    round_trip_frames,
+                );
+            }
+        }
+    }
+
+    /// The smoothed estimate of the peer's current frame, falls back to the one
+    /// from the last pong if there are too few of them yet.
+    pub fn estimated_peer_frame_number(&self, clock_frame_number: u64) -> u64 {
+        self.clock_sync
+            .peer_frame_number(clock_frame_number)
+            .unwrap_or_else(|| self.last_stored_game_frame())
+    }
+
+    /// How many frames the peer is behind `frame_number`, according to the clock estimate.
+    /// Has the same fallbacks as `average_lagging_behind` if there are no pongs yet.
+    pub fn lagging_behind(&self, frame_number: u64, clock_frame_number: u64) -> u64 {
+        match self.clock_sync.peer_frame_number(clock_frame_number) {
+            Some(peer_frame_number) => frame_number.saturating_sub(peer_frame_number),
+            None => self.average_lagging_behind(),
         }
     }
 
@@ -281,8 +473,51 @@ impl PingPongData {
             .unwrap_or_default()
     }
 
+    /// The mean difference between consecutive round trips, in milliseconds.
+    pub fn jitter_ms(&self, delta_seconds: f32) -> u32 {
+        let round_trips = self
+            .data
+            .iter()
+            .filter_map(|ping_pong| {
+                ping_pong.pong.as_ref().map(|pong| {
+                    pong.received_engine_frame
+                        .saturating_sub(ping_pong.sent_ping_engine_frame)
+                })
+            })
+            .collect::<Vec<_>>();
+        if round_trips.len() < 2 {
+            return 0;
+        }
+        let differences_sum: u64 = round_trips
+            .windows(2)
+            .map(|pair| (pair[1] as i64 - pair[0] as i64).abs() as u64)
+            .sum();
+        let mean_difference = differences_sum as f32 / (round_trips.len() - 1) as f32;
+        (mean_difference * delta_seconds * 1000.0) as u32
+    }
+
+    /// The share of pings that haven't been answered, out of the ones sent at least
+    /// `timeout_frames` ago. Returns `0.0` if there're no such pings yet.
+    pub fn loss_rate(&self, engine_frame_number: u64, timeout_frames: u64) -> f32 {
+        let (sent, lost) = self
+            .data
+            .iter()
+            .filter(|ping_pong| {
+                ping_pong.sent_ping_engine_frame + timeout_frames <= engine_frame_number
+            })
+            .fold((0, 0), |(sent, lost), ping_pong| {
+                (sent + 1, lost + ping_pong.pong.is_none() as u32)
+            });
+        if sent == 0 {
+            0.0
+        } else {
+            lost as f32 / sent as f32
+        }
+    }
+
     pub fn reset(&mut self) {
         self.data.clear();
+        self.clock_sync.reset();
     }
 }
 
@@ -290,6 +525,7 @@ impl PingPongData {
 struct PingPong {
     ping_id: NetIdentifier,
     sent_ping_engine_frame: u64,
+    sent_ping_clock_frame: u64,
     pong: Option<Pong>,
 }
 
@@ -0,0 +1,47 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{damage::DamageType, net::NetIdentifier};
+
+/// Zones affect entities every `ZONE_TICK_FRAMES` frames.
+pub const ZONE_TICK_FRAMES: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ZoneKind {
+    FirePatch,
+    SlowField,
+}
+
+impl ZoneKind {
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            ZoneKind::FirePatch => DamageType::Elemental,
+            ZoneKind::SlowField => DamageType::Magic,
+        }
+    }
+}
+
+/// A spell effect lying on the ground for a while. Zones don't change after being spawned,
+/// so both a server and clients derive their effects from the cast action alone.
+#[derive(Clone, Debug, Component)]
+pub struct Zone {
+    pub action_id: NetIdentifier,
+    pub caster: Entity,
+    pub kind: ZoneKind,
+    pub radius: f32,
+    pub frame_spawned: u64,
+    pub frame_expires: u64,
+    pub damage_per_tick: f32,
+    /// Monsters inside of the zone move with their speed multiplied by this factor.
+    pub speed_factor: f32,
+}
+
+impl Zone {
+    pub fn is_active(&self, frame_number: u64) -> bool {
+        self.frame_spawned <= frame_number && frame_number < self.frame_expires
+    }
+
+    pub fn is_tick(&self, frame_number: u64) -> bool {
+        self.is_active(frame_number) && (frame_number - self.frame_spawned) % ZONE_TICK_FRAMES == 0
+    }
+}
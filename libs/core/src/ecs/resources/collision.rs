@@ -0,0 +1,181 @@
+use crate::math::{Vector2, ZeroVector};
+
+pub const COLLISION_CELL_SIZE: f32 = 32.0;
+
+/// Static obstacles of a level, stored as square cells that block sight, missiles and walking.
+/// Both a server and clients build the same grid from level data, so line of sight checks
+/// give the same results on every side. The desert has no obstacles yet.
+#[derive(Clone, Debug)]
+pub struct LevelCollisionGrid {
+    cell_size: f32,
+    /// The bottom left corner of the grid.
+    origin: Vector2,
+    columns: i64,
+    rows: i64,
+    blocked: Vec<bool>,
+    blocked_count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaycastHit {
+    /// The point where a ray enters a blocked cell.
+    pub position: Vector2,
+    /// The normal of the blocked cell side the ray hits.
+    pub normal: Vector2,
+}
+
+impl LevelCollisionGrid {
+    /// Creates a grid without obstacles, centered at the origin of the world.
+    pub fn new(dimensions: Vector2, cell_size: f32) -> Self {
+        let columns = (dimensions.x / cell_size).ceil() as i64;
+        let rows = (dimensions.y / cell_size).ceil() as i64;
+        Self {
+            cell_size,
+            origin: -dimensions / 2.0,
+            columns,
+            rows,
+            blocked: vec![false; (columns * rows) as usize],
+            blocked_count: 0,
+        }
+    }
+
+    /// Blocks the cell containing the position, positions outside of the grid are ignored.
+    pub fn block(&mut self, position: Vector2) {
+        if let Some(index) = self.index(self.cell(position)) {
+            if !self.blocked[index] {
+                self.blocked[index] = true;
+                self.blocked_count += 1;
+            }
+        }
+    }
+
+    pub fn unblock(&mut self, position: Vector2) {
+        if let Some(index) = self.index(self.cell(position)) {
+            if self.blocked[index] {
+                self.blocked[index] = false;
+                self.blocked_count -= 1;
+            }
+        }
+    }
+
+    /// Blocks or unblocks every cell overlapping the rectangle between the two corners.
+    pub fn set_area_blocked(&mut self, from: Vector2, to: Vector2, is_blocked: bool) {
+        let min = self.cell(Vector2::new(from.x.min(to.x), from.y.min(to.y)));
+        let max = self.cell(Vector2::new(from.x.max(to.x), from.y.max(to.y)));
+        for row in min.1..=max.1 {
+            for column in min.0..=max.0 {
+                let center = self.origin
+                    + Vector2::new(column as f32 + 0.5, row as f32 + 0.5) * self.cell_size;
+                if is_blocked {
+                    self.block(center);
+                } else {
+                    self.unblock(center);
+                }
+            }
+        }
+    }
+
+    pub fn is_blocked(&self, position: Vector2) -> bool {
+        self.is_cell_blocked(self.cell(position))
+    }
+
+    /// Returns where a walker moving from `from` to `to` ends up: it slides along
+    /// blocked cells and stays in place if both axes are blocked.
+    pub fn slide(&self, from: Vector2, to: Vector2) -> Vector2 {
+        if self.blocked_count == 0 || !self.is_blocked(to) {
+            return to;
+        }
+        let along_x = Vector2::new(to.x, from.y);
+        if !self.is_blocked(along_x) {
+            return along_x;
+        }
+        let along_y = Vector2::new(from.x, to.y);
+        if !self.is_blocked(along_y) {
+            return along_y;
+        }
+        from
+    }
+
+    pub fn has_line_of_sight(&self, from: Vector2, to: Vector2) -> bool {
+        self.raycast(from, to).is_none()
+    }
+
+    /// Walks the cells crossed by the segment from `from` to `to` and returns
+    /// where it enters the first blocked cell.
+    pub fn raycast(&self, from: Vector2, to: Vector2) -> Option<RaycastHit> {
+        if self.blocked_count == 0 {
+            return None;
+        }
+        let mut cell = self.cell(from);
+        if self.is_cell_blocked(cell) {
+            return Some(RaycastHit {
+                position: from,
+                normal: Vector2::zero(),
+            });
+        }
+
+        let direction = to - from;
+        let end = self.cell(to);
+        let (step_x, mut t_max_x, t_delta_x) = self.axis_traversal(from.x, direction.x, cell.0, 0);
+        let (step_y, mut t_max_y, t_delta_y) = self.axis_traversal(from.y, direction.y, cell.1, 1);
+
+        let steps = (end.0 - cell.0).abs() + (end.1 - cell.1).abs();
+        for _ in 0..steps {
+            let (t, normal) = if t_max_x < t_max_y {
+                cell.0 += step_x;
+                let t = t_max_x;
+                t_max_x += t_delta_x;
+                (t, Vector2::new(-step_x as f32, 0.0))
+            } else {
+                cell.1 += step_y;
+                let t = t_max_y;
+                t_max_y += t_delta_y;
+                (t, Vector2::new(0.0, -step_y as f32))
+            };
+            if self.is_cell_blocked(cell) {
+                return Some(RaycastHit {
+                    position: from + direction * t,
+                    normal,
+                });
+            }
+        }
+        None
+    }
+
+    /// Returns the step direction, the ray parameter of the first cell border crossing
+    /// and the parameter distance between cell borders along one axis.
+    fn axis_traversal(&self, from: f32, direction: f32, cell: i64, axis: usize) -> (i64, f32, f32) {
+        if direction == 0.0 {
+            return (0, std::f32::INFINITY, std::f32::INFINITY);
+        }
+        let step = if direction > 0.0 { 1 } else { -1 };
+        let border_cell = if step > 0 { cell + 1 } else { cell };
+        let border = self.origin[axis] + border_cell as f32 * self.cell_size;
+        (
+            step,
+            (border - from) / direction,
+            self.cell_size / direction.abs(),
+        )
+    }
+
+    fn cell(&self, position: Vector2) -> (i64, i64) {
+        let local = position - self.origin;
+        (
+            (local.x / self.cell_size).floor() as i64,
+            (local.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    fn index(&self, (column, row): (i64, i64)) -> Option<usize> {
+        if column < 0 || row < 0 || column >= self.columns || row >= self.rows {
+            None
+        } else {
+            Some((row * self.columns + column) as usize)
+        }
+    }
+
+    /// Cells outside of the grid are free, as monsters walk in from outside of the level.
+    fn is_cell_blocked(&self, cell: (i64, i64)) -> bool {
+        self.index(cell).map_or(false, |index| self.blocked[index])
+    }
+}
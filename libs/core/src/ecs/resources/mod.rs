@@ -1,14 +1,146 @@
+pub mod collision;
 pub mod net;
+pub mod pool;
 pub mod world;
 
+use amethyst::core::Time;
+use serde_derive::{Deserialize, Serialize};
+
 use std::time::{Duration, Instant};
 
-use crate::math::Vector2;
+use crate::{
+    augments::KILLS_PER_LEVEL,
+    level_scripts::{LevelBounds, OutOfBounds, SafeZone},
+    math::Vector2,
+    net::{clock_sync::ClockEstimate, NetIdentifier},
+};
+
+use self::collision::{LevelCollisionGrid, COLLISION_CELL_SIZE};
+
+/// The default FPS cap of clients and the lowest one they render games at, as the simulation
+/// ticks at most once per frame (see `TickClock`). Servers run one engine frame per tick.
+pub const FRAME_RATE: u32 = 60;
+/// Durations measured in frames are given for this tick rate, see `GameTimeService::scale_frames`.
+pub const DEFAULT_TICK_RATE: u32 = 60;
+
+/// Levels without a script declaring their bounds are this big.
+pub const DEFAULT_LEVEL_DIMENSIONS: (f32, f32) = (4096.0, 4096.0);
+/// Monsters walk into a level from outside of it, anything further than this from the bounds
+/// is removed.
+pub const OUT_OF_BOUNDS_MARGIN: f32 = 300.0;
+
+/// Offline games can be slowed down and sped up within these bounds, see `GameTime::time_scale`.
+pub const MIN_TIME_SCALE: f32 = 0.25;
+pub const MAX_TIME_SCALE: f32 = 8.0;
+
+/// The simulation must tick on whole frames, so only divisors of `FRAME_RATE` are supported.
+pub fn is_supported_tick_rate(tick_rate: u32) -> bool {
+    tick_rate != 0 && FRAME_RATE % tick_rate == 0
+}
 
 pub struct GameTime {
     pub level_started_at: Duration,
     pub started_at_frame_number: u64,
+    /// A game that a client has rejoined doesn't start with the first frame.
+    pub started_at_game_frame_number: u64,
+    /// Is counted in ticks.
     pub frames_skipped: u64,
+    pub tick_rate: u32,
+    /// A server runs one engine frame per tick, clients render several frames per tick.
+    pub frames_per_tick: u64,
+    /// Is set with the `set timescale` console command or the time scale hotkeys, only
+    /// single player games are scaled, multiplayer ones are paced by the server.
+    pub time_scale: f32,
+    /// While it's set, the simulation advances only by the frames stepped with `step_frame`.
+    pub is_frame_stepping: bool,
+    /// Is counted in ticks, sped up games advance by several frames on a tick.
+    pub frames_added: u64,
+    /// How many frames the simulation advances by on the current tick, see `scale_tick`.
+    pub advanced_frames: u64,
+    tick_fraction: f32,
+    pending_frame_steps: u64,
+    /// Is estimated by clients from the pongs of the server, see `ClockSync`.
+    pub server_clock: Option<ClockEstimate>,
+    /// Clients tick by the elapsed time instead of counting `frames_per_tick`.
+    pub tick_clock: Option<TickClock>,
+}
+
+impl GameTime {
+    pub fn with_tick_rate(tick_rate: u32, frame_rate: u32) -> Self {
+        let mut game_time = Self::default();
+        game_time.set_tick_rate(tick_rate, frame_rate);
+        game_time
+    }
+
+    pub fn set_game_start_time(&mut self, engine_time: &Time) {
+        self.level_started_at = engine_time.absolute_time();
+        self.started_at_frame_number = engine_time.frame_number();
+        self.started_at_game_frame_number = 0;
+        self.frames_skipped = 0;
+        self.time_scale = 1.0;
+        self.is_frame_stepping = false;
+        self.frames_added = 0;
+        self.advanced_frames = 1;
+        self.tick_fraction = 0.0;
+        self.pending_frame_steps = 0;
+        // The estimate is relative to the restarted clock.
+        self.server_clock = None;
+        if self.tick_clock.is_some() {
+            self.start_tick_clock();
+        }
+    }
+
+    /// Makes the simulation tick by the elapsed time, so it runs at the same speed whatever
+    /// the frame rate is. The first frame after it's started is a tick.
+    pub fn start_tick_clock(&mut self) {
+        self.tick_clock = Some(TickClock {
+            pending_seconds: 1.0 / f64::from(self.tick_rate),
+            ticks: 0,
+            is_tick: false,
+        });
+    }
+
+    /// Is called by clients once per frame, before the systems run.
+    pub fn advance_tick_clock(&mut self, delta: Duration) {
+        let tick_rate = self.tick_rate;
+        if let Some(tick_clock) = self.tick_clock.as_mut() {
+            tick_clock.advance(delta, tick_rate);
+        }
+    }
+
+    /// Slows the simulation down by skipping ticks and speeds it up by advancing by several
+    /// frames on a tick, returns false if the current tick is skipped.
+    pub fn scale_tick(&mut self) -> bool {
+        let frames = if self.is_frame_stepping {
+            let frames = self.pending_frame_steps.min(1);
+            self.pending_frame_steps -= frames;
+            frames
+        } else {
+            self.tick_fraction += self.time_scale;
+            let frames = self.tick_fraction.floor();
+            self.tick_fraction -= frames;
+            frames as u64
+        };
+
+        if frames == 0 {
+            self.frames_skipped += 1;
+            return false;
+        }
+        self.frames_added += frames - 1;
+        self.advanced_frames = frames;
+        true
+    }
+
+    /// Advances a frame-stepped simulation by one frame on the next tick.
+    pub fn step_frame(&mut self) {
+        self.pending_frame_steps += 1;
+    }
+
+    /// Clients pass their `FRAME_RATE`, a server runs at the tick rate itself.
+    pub fn set_tick_rate(&mut self, tick_rate: u32, frame_rate: u32) {
+        self.tick_rate = tick_rate;
+        self.frames_per_tick = u64::from(frame_rate / tick_rate);
+    }
 }
 
 impl Default for GameTime {
@@ -16,39 +148,193 @@ impl Default for GameTime {
         Self {
             level_started_at: Duration::new(0, 0),
             started_at_frame_number: 0,
+            started_at_game_frame_number: 0,
             frames_skipped: 0,
+            tick_rate: DEFAULT_TICK_RATE,
+            frames_per_tick: 1,
+            time_scale: 1.0,
+            is_frame_stepping: false,
+            frames_added: 0,
+            advanced_frames: 1,
+            tick_fraction: 0.0,
+            pending_frame_steps: 0,
+            server_clock: None,
+            tick_clock: None,
         }
     }
 }
 
+/// A tick is behind by up to this many ticks before the clock starts dropping them, e.g.
+/// after a long frame.
+const MAX_PENDING_TICKS: f64 = 4.0;
+/// Frames paced at the tick rate end a bit before or after a full tick, so a frame ticks
+/// once this much of a tick is left to catch up.
+const TICK_TOLERANCE: f64 = 0.25;
+
+/// Ticks at most once per frame: frames faster than the tick rate don't tick on some
+/// of them, slower ones catch up on the following frames.
+#[derive(Debug, Clone, Copy)]
+pub struct TickClock {
+    pending_seconds: f64,
+    /// Including the current one.
+    ticks: u64,
+    is_tick: bool,
+}
+
+impl TickClock {
+    pub fn is_tick(&self) -> bool {
+        self.is_tick
+    }
+
+    /// The number of ticks since the start, including the current one.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    fn advance(&mut self, delta: Duration, tick_rate: u32) {
+        let tick_seconds = 1.0 / f64::from(tick_rate);
+        self.pending_seconds =
+            (self.pending_seconds + delta.as_secs_f64()).min(tick_seconds * MAX_PENDING_TICKS);
+        self.is_tick = self.pending_seconds >= tick_seconds * (1.0 - TICK_TOLERANCE);
+        if self.is_tick {
+            self.pending_seconds -= tick_seconds;
+            self.ticks += 1;
+        }
+    }
+}
+
+/// Is false for the frames between simulation ticks, when clients only render.
+pub struct SimulationTick(pub bool);
+
+impl Default for SimulationTick {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
 pub struct GameLevelState {
+    /// Personal bests are stored per map.
+    pub map_name: &'static str,
+    /// Is changed with `set_bounds`, which resizes the collision grid as well.
     pub dimensions: Vector2,
+    pub out_of_bounds: OutOfBounds,
+    pub collision_grid: LevelCollisionGrid,
+    /// Are taken from the level script when a level starts.
+    pub safe_zones: Vec<SafeZone>,
     pub is_over: bool,
     pub spawn_level: usize,
     pub spawn_level_started: Duration,
     pub last_borderline_spawn: Duration,
     pub last_random_spawn: Duration,
+    /// Monsters don't spawn and the shop is open during an intermission after every wave.
+    pub intermission: Option<Intermission>,
+}
+
+/// Ends once every player is ready for the next wave or when the time runs out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Intermission {
+    /// The wave that has just ended.
+    pub wave: usize,
+    pub started_at_frame: u64,
+    pub ends_at_frame: u64,
+    /// Connection ids of the players who are ready for the next wave.
+    pub ready_players: Vec<NetIdentifier>,
 }
 
 impl GameLevelState {
     pub fn dimensions_half_size(&self) -> Vector2 {
         self.dimensions / 2.0
     }
+
+    /// Resizes the level, its collision grid is rebuilt without any obstacles.
+    pub fn set_bounds(&mut self, bounds: &LevelBounds) {
+        let (width, height) = bounds.size;
+        self.dimensions = Vector2::new(width, height);
+        self.out_of_bounds = bounds.out_of_bounds;
+        self.collision_grid = LevelCollisionGrid::new(self.dimensions, COLLISION_CELL_SIZE);
+    }
+
+    /// Whether a position is within the bounds extended by `margin`.
+    pub fn is_within_bounds(&self, position: Vector2, margin: f32) -> bool {
+        let half_size = self.dimensions_half_size();
+        position.x.abs() <= half_size.x + margin && position.y.abs() <= half_size.y + margin
+    }
+
+    pub fn clamp_to_bounds(&self, position: Vector2) -> Vector2 {
+        let half_size = self.dimensions_half_size();
+        Vector2::new(
+            position.x.max(-half_size.x).min(half_size.x),
+            position.y.max(-half_size.y).min(half_size.y),
+        )
+    }
+
+    /// The distance to the closest border, zero for positions outside of the bounds.
+    pub fn distance_to_border(&self, position: Vector2) -> f32 {
+        let half_size = self.dimensions_half_size();
+        (half_size.x - position.x.abs())
+            .min(half_size.y - position.y.abs())
+            .max(0.0)
+    }
 }
 
 impl Default for GameLevelState {
     fn default() -> Self {
+        let dimensions = Vector2::new(DEFAULT_LEVEL_DIMENSIONS.0, DEFAULT_LEVEL_DIMENSIONS.1);
         Self {
-            dimensions: Vector2::new(4096.0, 4096.0),
+            map_name: "desert",
+            dimensions,
+            out_of_bounds: OutOfBounds::default(),
+            collision_grid: LevelCollisionGrid::new(dimensions, COLLISION_CELL_SIZE),
+            safe_zones: Vec::new(),
             is_over: false,
             spawn_level: 1,
             spawn_level_started: Duration::new(0, 0),
             last_borderline_spawn: Duration::new(0, 0),
             last_random_spawn: Duration::new(0, 0),
+            intermission: None,
+        }
+    }
+}
+
+/// Is computed by the authoritative side, multiplayer clients receive it with `UpdateScore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchScore {
+    pub score: u64,
+    /// Grows with the kills made in a quick succession.
+    pub multiplier: u32,
+    pub combo: u32,
+    pub last_kill_frame: u64,
+    pub kills: u32,
+    /// Health restored by players' heals, is shown on the results screen.
+    pub healing_done: f32,
+    /// Critical missile hits, are shown on the results screen as well.
+    pub critical_hits: u32,
+    /// The frame the score was computed at, helps to filter out outdated updates.
+    pub frame_number: u64,
+}
+
+impl Default for MatchScore {
+    fn default() -> Self {
+        Self {
+            score: 0,
+            multiplier: 1,
+            combo: 0,
+            last_kill_frame: 0,
+            kills: 0,
+            healing_done: 0.0,
+            critical_hits: 0,
+            frame_number: 0,
         }
     }
 }
 
+impl MatchScore {
+    /// The team levels up with kills, which lets players choose augments.
+    pub fn level(&self) -> u32 {
+        1 + self.kills / KILLS_PER_LEVEL
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NewGameEngineState(pub GameEngineState);
 
@@ -56,8 +342,14 @@ pub struct NewGameEngineState(pub GameEngineState);
 pub enum GameEngineState {
     Loading,
     Menu,
+    /// Clients wait for the assets of a level before playing it.
+    LoadingLevel,
     Playing,
-    ShuttingDown { shutdown_at: Instant },
+    /// The world of a completed campaign level is cleaned up, the next one starts shortly.
+    LevelTransition,
+    ShuttingDown {
+        shutdown_at: Instant,
+    },
     Quit,
 }
 
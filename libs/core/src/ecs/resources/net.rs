@@ -2,12 +2,26 @@ use amethyst::ecs::Entity;
 use derivative::Derivative;
 use serde_derive::{Deserialize, Serialize};
 
-use std::{collections::HashMap, ops::Range};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+    ops::Range,
+    time::Instant,
+};
 
 use crate::{
     actions::{player::PlayerCastAction, IdentifiableAction},
-    net::{server_message::PlayerNetStatus, NetIdentifier},
-    PLAYER_COLORS,
+    augments::PlayerAugments,
+    classes::PlayerClass,
+    cosmetics::PlayerCosmetics,
+    economy::PlayerWallet,
+    ecs::{components::PlayerLastCastedSpells, resources::world::InputFrame},
+    math::Vector2,
+    net::{
+        rendezvous::HostCode,
+        server_message::{LaggingPlayer, MatchSnapshot, PlayerNetStatus, PlayerReconciliation},
+        NetIdentifier,
+    },
 };
 
 #[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
@@ -17,8 +31,42 @@ pub struct MultiplayerRoomPlayer {
     pub entity_net_id: NetIdentifier,
     pub nickname: String,
     pub is_host: bool,
+    pub class: PlayerClass,
+    pub cosmetics: PlayerCosmetics,
+    /// Is derived from `cosmetics` and the player's slot.
     #[derivative(PartialEq = "ignore")]
     pub color: [f32; 3],
+    /// The player has lost the connection during a game and can rejoin it for a while,
+    /// their character stands still meanwhile.
+    pub is_disconnected: bool,
+}
+
+/// The progress of a multiplayer match, which a server saves to let the host resume it later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedMatch {
+    pub spawn_level: usize,
+    /// A resumed match goes on from this frame.
+    pub game_frame_number: u64,
+    /// Monster spawns are rolled with it, see `MatchSeed`.
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub campaign_level: Option<usize>,
+    /// Dead players are saved with full health, players who aren't present here start afresh.
+    pub players: Vec<SavedPlayer>,
+    /// The distance of the last checkpoint the level script payload has reached.
+    #[serde(default)]
+    pub payload_checkpoint: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub nickname: String,
+    pub health: f32,
+    #[serde(default)]
+    pub augments: PlayerAugments,
+    #[serde(default)]
+    pub wallet: PlayerWallet,
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
@@ -31,8 +79,17 @@ pub struct MultiplayerGameState {
     pub waiting_for_players_pause_id: u64,
     /// To help keep the track of outdated status reports (they use unreliable channel).
     pub players_status_id: u64,
-    pub lagging_players: Vec<NetIdentifier>,
+    pub lagging_players: Vec<LaggingPlayer>,
+    /// Connection ids of the players who haven't sent any input for a while,
+    /// are kept after a game ends to let the host kick them in the lobby.
+    pub afk_players: Vec<NetIdentifier>,
     pub is_disconnected: bool,
+    /// Is applied to the world when a game starts.
+    pub resumed_match: Option<SavedMatch>,
+    /// Is applied to the world when a client starts a game it has rejoined.
+    pub rejoined_match: Option<MatchSnapshot>,
+    /// Index of the current campaign level, is `None` in the survival mode.
+    pub campaign_level: Option<usize>,
     players_updated: bool,
 }
 
@@ -46,7 +103,11 @@ impl MultiplayerGameState {
             waiting_for_players_pause_id: 0,
             players_status_id: 0,
             lagging_players: Vec::new(),
+            afk_players: Vec::new(),
             is_disconnected: false,
+            resumed_match: None,
+            rejoined_match: None,
+            campaign_level: None,
             players_updated: false,
         }
     }
@@ -88,7 +149,7 @@ impl MultiplayerGameState {
         self.players_updated = true;
         self.players.remove(player_index);
         for (player_index, player) in self.players.iter_mut().enumerate().skip(player_index) {
-            player.color = PLAYER_COLORS[player_index];
+            player.color = player.cosmetics.color(player_index);
         }
     }
 
@@ -165,7 +226,16 @@ impl ActionUpdateIdProvider {
 
 #[derive(Default)]
 pub struct CastActionsToExecute {
-    pub actions: Vec<IdentifiableAction<PlayerCastAction>>,
+    pub actions: Vec<CastActionToExecute>,
+}
+
+pub struct CastActionToExecute {
+    /// Monsters hit by the spell direct their threat at the caster.
+    pub caster: Entity,
+    /// Defines the spell that gets cast.
+    pub caster_class: PlayerClass,
+    pub caster_augments: PlayerAugments,
+    pub action: IdentifiableAction<PlayerCastAction>,
 }
 
 #[derive(Default)]
@@ -173,3 +243,214 @@ pub struct PlayersNetStatus {
     pub frame_received: u64,
     pub players: Vec<PlayerNetStatus>,
 }
+
+/// How many of the latest unacknowledged input frames every PlayerInputs packet carries.
+pub const INPUT_REDUNDANCY_FRAMES: usize = 4;
+/// Unacknowledged inputs are resent at least this often, even on a slow connection.
+pub const MAX_INPUT_RESEND_FRAMES: u64 = 15;
+const MIN_INPUT_RESEND_FRAMES: u64 = 2;
+
+/// The input frames a client keeps resending until the server acknowledges them, a lost packet
+/// gets covered by the next one.
+#[derive(Default)]
+pub struct UnacknowledgedInputs {
+    input_frames: VecDeque<InputFrame>,
+    has_new_frames: bool,
+    last_sent_frame: Option<u64>,
+}
+
+impl UnacknowledgedInputs {
+    /// Only the latest `INPUT_REDUNDANCY_FRAMES` are kept, older inputs would most probably
+    /// arrive too late for the server's lag compensation anyway.
+    pub fn push(&mut self, input_frame: InputFrame) {
+        self.input_frames.push_back(input_frame);
+        let overflow = self
+            .input_frames
+            .len()
+            .saturating_sub(INPUT_REDUNDANCY_FRAMES);
+        self.input_frames.drain(0..overflow);
+        self.has_new_frames = true;
+    }
+
+    /// Drops the frames with the actions up to `client_action_id`.
+    pub fn acknowledge(&mut self, client_action_id: NetIdentifier) {
+        while self.input_frames.front().map_or(false, |input_frame| {
+            input_frame.last_client_action_id() <= Some(client_action_id)
+        }) {
+            self.input_frames.pop_front();
+        }
+    }
+
+    /// Returns the frames to send with PlayerInputs. New inputs are sent right away,
+    /// the unacknowledged ones are resent every `resend_frames`.
+    pub fn packet(
+        &mut self,
+        game_frame_number: u64,
+        resend_frames: u64,
+    ) -> Option<Vec<InputFrame>> {
+        if self.input_frames.is_empty() {
+            return None;
+        }
+        let is_resend_due = self.last_sent_frame.map_or(true, |last_sent_frame| {
+            game_frame_number.saturating_sub(last_sent_frame) >= resend_frames
+        });
+        if !self.has_new_frames && !is_resend_due {
+            return None;
+        }
+
+        self.has_new_frames = false;
+        self.last_sent_frame = Some(game_frame_number);
+        Some(self.input_frames.iter().cloned().collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.input_frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input_frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// An acknowledgement can't arrive sooner than in a round trip, resending earlier
+/// would only waste bandwidth.
+pub fn input_resend_frames(latency_ms: u32, tick_rate: u32) -> u64 {
+    let round_trip_frames = u64::from(latency_ms) * 2 * u64::from(tick_rate) / 1000;
+    (round_trip_frames + MIN_INPUT_RESEND_FRAMES).min(MAX_INPUT_RESEND_FRAMES)
+}
+
+/// How many reconciliation reports in a row have to diverge to be reported.
+pub const PERSISTENT_DIVERGENCE_REPORTS: u32 = 3;
+/// In units, smaller differences are expected from smoothing out corrections.
+pub const POSITION_DIVERGENCE_THRESHOLD: f32 = 5.0;
+
+/// Compares a client's prediction of its own player with the server's reconciliation reports.
+/// Divergences that persist hint at prediction bugs or a tampered client.
+#[derive(Default)]
+pub struct ReconciliationStatus {
+    /// Reports waiting until the client receives the world updates up to their frames.
+    pub pending_reports: Vec<PlayerReconciliation>,
+    pub last_checked_frame: Option<u64>,
+    pub position_divergence: f32,
+    pub cooldowns_diverged: bool,
+    pub consecutive_divergences: u32,
+}
+
+impl ReconciliationStatus {
+    /// Returns true if the divergence has just become persistent.
+    pub fn check(
+        &mut self,
+        report: &PlayerReconciliation,
+        predicted_position: Vector2,
+        predicted_last_casted_spells: &PlayerLastCastedSpells,
+    ) -> bool {
+        self.last_checked_frame = Some(report.frame_number);
+        self.position_divergence = (report.position - predicted_position).norm();
+        self.cooldowns_diverged = report.last_casted_spells != *predicted_last_casted_spells;
+        if self.position_divergence > POSITION_DIVERGENCE_THRESHOLD || self.cooldowns_diverged {
+            self.consecutive_divergences += 1;
+        } else {
+            self.consecutive_divergences = 0;
+        }
+        self.consecutive_divergences == PERSISTENT_DIVERGENCE_REPORTS
+    }
+
+    pub fn is_diverged(&self) -> bool {
+        self.consecutive_divergences >= PERSISTENT_DIVERGENCE_REPORTS
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LastAcknowledgedUpdate {
+    pub id: u64,
+    pub frame_number: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RendezvousRole {
+    Host {
+        host_code: HostCode,
+        is_registered: bool,
+    },
+    Guest {
+        host_code: HostCode,
+        host: RendezvousHost,
+    },
+}
+
+/// The host as seen by a joining client.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RendezvousHost {
+    /// Waiting for the rendezvous server to introduce the host.
+    Unknown,
+    Punching(SocketAddr),
+    Punched(SocketAddr),
+    /// Punching has failed, messages go through the rendezvous server.
+    Relayed(SocketAddr),
+    NotFound,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PunchAttempt {
+    pub addr: SocketAddr,
+    pub started_at: Instant,
+}
+
+/// Is active if a game server or a client connect to each other through a rendezvous server.
+#[derive(Default)]
+pub struct Rendezvous {
+    pub server_addr: Option<SocketAddr>,
+    pub role: Option<RendezvousRole>,
+    pub punch_attempts: Vec<PunchAttempt>,
+    /// Messages to these peers are wrapped and sent through the rendezvous server.
+    pub relayed_peers: HashSet<SocketAddr>,
+}
+
+impl Rendezvous {
+    pub fn host(server_addr: SocketAddr, host_code: HostCode) -> Self {
+        Self {
+            server_addr: Some(server_addr),
+            role: Some(RendezvousRole::Host {
+                host_code,
+                is_registered: false,
+            }),
+            ..Self::default()
+        }
+    }
+
+    pub fn guest(server_addr: SocketAddr, host_code: HostCode) -> Self {
+        Self {
+            server_addr: Some(server_addr),
+            role: Some(RendezvousRole::Guest {
+                host_code,
+                host: RendezvousHost::Unknown,
+            }),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.server_addr.is_some()
+    }
+
+    pub fn guest_host(&self) -> Option<RendezvousHost> {
+        match self.role {
+            Some(RendezvousRole::Guest { host, .. }) => Some(host),
+            _ => None,
+        }
+    }
+
+    pub fn set_guest_host(&mut self, new_host: RendezvousHost) {
+        if let Some(RendezvousRole::Guest { host, .. }) = &mut self.role {
+            *host = new_host;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
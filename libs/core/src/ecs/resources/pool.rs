@@ -0,0 +1,111 @@
+use amethyst::ecs::{Entities, Entity};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+};
+
+/// A reference to a pooled entity, which stops resolving once the entity is released,
+/// even if the pool hands the same entity out again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PooledEntity {
+    entity: Entity,
+    generation: u32,
+}
+
+/// Reuses released entities instead of deleting and creating them again,
+/// `T` (usually the defining component) tells pools of different entities apart.
+///
+/// Callers have to strip released entities of their components, so that joins skip them.
+pub struct EntityPool<T> {
+    released: VecDeque<(Entity, u64)>,
+    generations: HashMap<Entity, u32>,
+    reuse_after_frames: u64,
+    created: usize,
+    reused: usize,
+    _component: PhantomData<T>,
+}
+
+impl<T> EntityPool<T> {
+    /// Released entities get reused only after `reuse_after_frames`, so that saved world states
+    /// (which refer to entities without generations) can't be restored onto a new entity.
+    pub fn new(reuse_after_frames: u64) -> Self {
+        Self {
+            released: VecDeque::new(),
+            generations: HashMap::new(),
+            reuse_after_frames,
+            created: 0,
+            reused: 0,
+            _component: PhantomData,
+        }
+    }
+
+    pub fn acquire(&mut self, entities: &Entities, frame_number: u64) -> Entity {
+        while let Some((entity, released_at_frame)) = self.released.front().cloned() {
+            // Frame numbers can go back during rollbacks, saturating keeps such entities parked.
+            if frame_number.saturating_sub(released_at_frame) < self.reuse_after_frames {
+                break;
+            }
+            self.released.pop_front();
+            // Entities can still be deleted from the outside, when a game gets reset for instance.
+            if entities.is_alive(entity) {
+                self.reused += 1;
+                return entity;
+            }
+            self.generations.remove(&entity);
+        }
+
+        self.created += 1;
+        entities.create()
+    }
+
+    pub fn release(&mut self, entity: Entity, frame_number: u64) {
+        *self.generations.entry(entity).or_insert(0) += 1;
+        self.released.push_back((entity, frame_number));
+    }
+
+    pub fn handle(&self, entity: Entity) -> PooledEntity {
+        PooledEntity {
+            entity,
+            generation: self.generation(entity),
+        }
+    }
+
+    /// Returns `None` if the entity has been released since the handle was taken.
+    pub fn get(&self, handle: PooledEntity) -> Option<Entity> {
+        if self.generation(handle.entity) == handle.generation {
+            Some(handle.entity)
+        } else {
+            None
+        }
+    }
+
+    /// Deletes the parked entities.
+    pub fn clear(&mut self, entities: &Entities) {
+        for (entity, _) in self.released.drain(..) {
+            if entities.is_alive(entity) {
+                entities
+                    .delete(entity)
+                    .expect("Expected to delete a pooled entity");
+            }
+        }
+        self.generations.clear();
+    }
+
+    pub fn released_count(&self) -> usize {
+        self.released.len()
+    }
+
+    /// Counts the entities created since the pool couldn't reuse any.
+    pub fn created_count(&self) -> usize {
+        self.created
+    }
+
+    pub fn reused_count(&self) -> usize {
+        self.reused
+    }
+
+    fn generation(&self, entity: Entity) -> u32 {
+        self.generations.get(&entity).cloned().unwrap_or(0)
+    }
+}
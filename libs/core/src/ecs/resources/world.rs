@@ -1,26 +1,44 @@
 use amethyst::ecs::{Component, Entities, Entity, Join, ReadStorage, WriteStorage};
 use serde_derive::{Deserialize, Serialize};
 
-use std::{collections::VecDeque, iter::FromIterator};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    iter::FromIterator,
+};
 
 use crate::{
     actions::{
         mob::MobAction,
         monster_spawn::SpawnAction,
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        player::{PlayerCastAction, PlayerInteractAction, PlayerLookAction, PlayerWalkAction},
         ClientActionUpdate, IdentifiableAction,
     },
     ecs::components::{
-        damage_history::DamageHistoryEntries, missile::Missile, Dead, Monster, Player,
-        PlayerActions, PlayerLastCastedSpells, WorldPosition,
+        damage_history::DamageHistoryEntries, missile::Missile, Dead, Impulse, Monster, Player,
+        PlayerActions, PlayerLastCastedSpells, ThreatTable, WorldPosition,
+    },
+    math::Vector2,
+    net::{
+        server_message::{EntitySnapshot, MatchSnapshot, PlayerReconciliation},
+        NetIdentifier, NetUpdate, NetUpdateWithPosition,
     },
-    net::{NetIdentifier, NetUpdate, NetUpdateWithPosition},
 };
 
 pub const SAVED_WORLD_STATES_LIMIT: usize = 600;
 pub const LAG_COMPENSATION_FRAMES_LIMIT: usize = 20;
 pub const PAUSE_FRAME_THRESHOLD: u64 =
     (LAG_COMPENSATION_FRAMES_LIMIT + LAG_COMPENSATION_FRAMES_LIMIT / 2) as u64;
+/// The server pauses the game while a client has more world updates than this unacknowledged.
+pub const MAX_PENDING_WORLD_UPDATES: usize = SAVED_WORLD_STATES_LIMIT;
+/// Hard cap of `ServerWorldUpdates`, the oldest updates get dropped past it.
+pub const SERVER_WORLD_UPDATES_LIMIT: usize = MAX_PENDING_WORLD_UPDATES * 2;
+/// How long a server keeps the finalized world updates for spectators joining mid-match.
+pub const RELAY_BUFFER_SECONDS: u64 = 5;
+/// Entities further than this from a dirty one can't interact with it during a frame,
+/// covers monster aggro, zones and a frame of missile flight.
+pub const ROLLBACK_INTERACTION_RADIUS: f32 = 300.0;
+/// Once this share of entities is dirty, tracking them costs more than re-simulating everything.
+const MAX_DIRTY_ENTITIES_SHARE: f32 = 0.5;
 
 #[derive(Debug)]
 pub struct OldFrameError {
@@ -68,6 +86,16 @@ impl WorldStates {
             .skip_while(move |world_state| world_state.frame_number < start_frame_number)
     }
 
+    pub fn state(&self, frame_number: u64) -> Option<&SavedWorldState> {
+        self.world_states
+            .iter()
+            .find(|world_state| world_state.frame_number == frame_number)
+    }
+
+    pub fn latest_state(&self) -> Option<&SavedWorldState> {
+        self.world_states.back()
+    }
+
     pub fn check_update_is_possible<T>(
         &self,
         framed_updates: &FramedUpdates<T>,
@@ -116,6 +144,8 @@ pub struct SavedWorldState {
     pub monsters: Vec<(Entity, Monster)>,
     pub missiles: Vec<(Entity, Missile)>,
     pub world_positions: Vec<(Entity, WorldPosition)>,
+    pub impulses: Vec<(Entity, Impulse)>,
+    pub threat_tables: Vec<(Entity, ThreatTable)>,
     pub dead: Vec<(Entity, Dead)>,
 }
 
@@ -142,6 +172,64 @@ impl SavedWorldState {
         )
     }
 
+    /// The state of a player needed for reconciliation reports.
+    pub fn player_reconciliation(&self, player_entity: Entity) -> Option<PlayerReconciliation> {
+        let position = self
+            .world_positions
+            .iter()
+            .find(|(entity, _)| *entity == player_entity)
+            .map(|(_, world_position)| **world_position)?;
+        let last_casted_spells = self
+            .player_last_casted_spells
+            .iter()
+            .find(|(entity, _)| *entity == player_entity)
+            .map(|(_, last_casted_spells)| last_casted_spells.clone())?;
+        Some(PlayerReconciliation {
+            frame_number: self.frame_number,
+            position,
+            last_casted_spells,
+        })
+    }
+
+    /// The alive players and monsters a spectator or a rejoining player starts with,
+    /// `entity_net_id` returns `None` for entities which have been deleted since the state
+    /// was saved, or which shouldn't be sent.
+    pub fn snapshot(
+        &self,
+        entity_net_id: impl Fn(Entity) -> Option<NetIdentifier>,
+    ) -> MatchSnapshot {
+        let is_dead = |entity: Entity| self.dead.iter().any(|(dead, _)| *dead == entity);
+        let entity_snapshot = |entity: Entity, health: f32| {
+            if is_dead(entity) {
+                return None;
+            }
+            let position = self
+                .world_positions
+                .iter()
+                .find(|(positioned, _)| *positioned == entity)
+                .map(|(_, world_position)| **world_position)?;
+            Some(EntitySnapshot {
+                entity_net_id: entity_net_id(entity)?,
+                position,
+                health,
+            })
+        };
+
+        MatchSnapshot {
+            frame_number: self.frame_number,
+            players: self
+                .players
+                .iter()
+                .filter_map(|(entity, player)| entity_snapshot(*entity, player.health))
+                .collect(),
+            monsters: self
+                .monsters
+                .iter()
+                .filter_map(|(entity, monster)| entity_snapshot(*entity, monster.health))
+                .collect(),
+        }
+    }
+
     pub fn load_storage_from<T: Clone + Component>(
         storage: &mut WriteStorage<T>,
         saved_components: &[(Entity, T)],
@@ -157,6 +245,179 @@ impl SavedWorldState {
     }
 }
 
+/// Decides what gets re-simulated when corrected client inputs arrive for past frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RollbackMode {
+    /// Re-simulates every entity starting with the oldest updated frame.
+    Full,
+    /// Re-simulates only the entities which corrected inputs could have affected,
+    /// the rest are restored from the world states saved by the previous simulation.
+    DirtyEntities,
+}
+
+impl Default for RollbackMode {
+    fn default() -> Self {
+        RollbackMode::DirtyEntities
+    }
+}
+
+/// Counts the rollbacks of the `RollbackMode::DirtyEntities` mode that didn't have to fall back
+/// to re-simulating everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RollbackStats {
+    pub rollbacks: u64,
+    pub partial_rollbacks: u64,
+    /// The dirty entities and all the entities with a position after the latest partial rollback.
+    pub last_partial_rollback: Option<(usize, usize)>,
+}
+
+/// Entities that have to be re-simulated during a rollback. Starts with the players whose inputs
+/// got corrected and spreads to everything that gets close to them or starts targeting them.
+#[derive(Default)]
+pub struct DirtyEntities {
+    entities: HashSet<Entity>,
+    is_all: bool,
+}
+
+impl DirtyEntities {
+    pub fn all() -> Self {
+        Self {
+            entities: HashSet::new(),
+            is_all: true,
+        }
+    }
+
+    pub fn with_seeds(seeds: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            entities: seeds.into_iter().collect(),
+            is_all: false,
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.is_all || self.entities.contains(&entity)
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.is_all
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.is_all && self.entities.is_empty()
+    }
+
+    pub fn extend(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        if !self.is_all {
+            self.entities.extend(entities);
+        }
+    }
+
+    /// Marks the entities which the dirty ones may affect during `frame_number`.
+    ///
+    /// `current_positions` are the re-simulated positions at the start of the frame,
+    /// `previous_state` is the world state the previous simulation saved at the end of it.
+    pub fn spread(
+        &mut self,
+        frame_number: u64,
+        current_positions: &[(Entity, Vector2)],
+        previous_state: &SavedWorldState,
+    ) {
+        if self.is_all {
+            return;
+        }
+
+        let previous_positions = previous_state
+            .world_positions
+            .iter()
+            .map(|(entity, position)| (*entity, **position))
+            .collect::<HashMap<_, _>>();
+        // Both timelines matter: an entity may interact with a dirty one where it is now,
+        // or stop interacting with it where it was before.
+        let dirty_positions = current_positions
+            .iter()
+            .filter(|(entity, _)| self.entities.contains(entity))
+            .map(|(_, position)| *position)
+            .chain(
+                previous_positions
+                    .iter()
+                    .filter(|(entity, _)| self.entities.contains(entity))
+                    .map(|(_, position)| *position),
+            )
+            .collect::<Vec<_>>();
+
+        let radius_squared = ROLLBACK_INTERACTION_RADIUS * ROLLBACK_INTERACTION_RADIUS;
+        let mut newly_dirty = Vec::new();
+        for (entity, position) in current_positions {
+            if self.entities.contains(entity) {
+                continue;
+            }
+            let is_spawned_by_rollback = !previous_positions.contains_key(entity);
+            let is_reachable = dirty_positions.iter().any(|dirty_position| {
+                (*dirty_position - *position).norm_squared() <= radius_squared
+            });
+            if is_spawned_by_rollback || is_reachable {
+                newly_dirty.push(*entity);
+            }
+        }
+
+        for (entity, monster) in &previous_state.monsters {
+            // We can't replay the side effects of a decision (damage, net updates),
+            // so monsters that made one during the frame get re-simulated.
+            let has_decided = monster.action.frame_number == frame_number;
+            let targets_dirty = monster
+                .action
+                .action
+                .target()
+                .map_or(false, |target| self.entities.contains(&target));
+            if has_decided || targets_dirty {
+                newly_dirty.push(*entity);
+            }
+        }
+        for (entity, threat_table) in &previous_state.threat_tables {
+            let threatened_by_dirty = threat_table
+                .entries
+                .iter()
+                .any(|(target, _)| self.entities.contains(target));
+            if threatened_by_dirty {
+                newly_dirty.push(*entity);
+            }
+        }
+        self.entities.extend(newly_dirty);
+
+        let max_dirty_entities = current_positions.len() as f32 * MAX_DIRTY_ENTITIES_SHARE;
+        if self.entities.len() as f32 > max_dirty_entities {
+            log::trace!(
+                "Too many dirty entities ({} out of {}), re-simulating everything",
+                self.entities.len(),
+                current_positions.len()
+            );
+            *self = Self::all();
+        }
+    }
+
+    /// Overwrites the components of clean entities with the ones the previous simulation saved.
+    pub fn restore_clean<T: Clone + Component>(
+        &self,
+        storage: &mut WriteStorage<T>,
+        saved_components: &[(Entity, T)],
+    ) {
+        if self.is_all {
+            return;
+        }
+        for (entity, component) in saved_components {
+            if !self.entities.contains(entity) && storage.contains(*entity) {
+                storage
+                    .insert(*entity, component.clone())
+                    .expect("Expected to insert a saved component");
+            }
+        }
+    }
+}
+
 pub struct FramedUpdates<T> {
     pub oldest_updated_frame: u64,
     pub updates: VecDeque<T>,
@@ -274,6 +535,8 @@ pub trait FramedUpdate {
 #[derive(Default)]
 pub struct ServerWorldUpdates {
     pub updates: VecDeque<(u64, ServerWorldUpdate)>,
+    /// Dropped updates, their buffers get reused for new frames.
+    pool: Vec<ServerWorldUpdate>,
 }
 
 impl ServerWorldUpdates {
@@ -290,10 +553,54 @@ impl ServerWorldUpdates {
             0
         };
         for frame_number in oldest_updated_frame..=current_frame_number {
-            self.updates
-                .push_back((update_number, ServerWorldUpdate::new(frame_number)));
+            let update = match self.pool.pop() {
+                Some(mut update) => {
+                    update.reset(frame_number);
+                    update
+                }
+                None => ServerWorldUpdate::new(frame_number),
+            };
+            self.updates.push_back((update_number, update));
             update_number += 1;
         }
+
+        // Lagging clients pause the game long before this, so we shouldn't ever get here,
+        // but if we do, it's better to desync a client than to run out of memory.
+        let overflow = self
+            .updates
+            .len()
+            .saturating_sub(SERVER_WORLD_UPDATES_LIMIT);
+        if overflow > 0 {
+            log::error!(
+                "ServerWorldUpdates limit exceeded, dropping {} unacknowledged updates",
+                overflow
+            );
+            self.updates.drain(0..overflow);
+        }
+    }
+
+    /// Drops the updates that all the clients have acknowledged. The latest update is always
+    /// kept to continue the numbering.
+    pub fn drop_acknowledged(&mut self, last_acknowledged_update: u64) {
+        while self.updates.len() > 1
+            && self.updates.front().map_or(false, |(update_number, _)| {
+                *update_number <= last_acknowledged_update
+            })
+        {
+            let (_, update) = self.updates.pop_front().unwrap();
+            if self.pool.len() < SAVED_WORLD_STATES_LIMIT {
+                self.pool.push(update);
+            }
+        }
+    }
+
+    /// Returns the number of updates a client hasn't acknowledged yet.
+    pub fn pending_updates(&self, last_acknowledged_update: Option<u64>) -> usize {
+        self.updates
+            .iter()
+            .rev()
+            .take_while(|(update_number, _)| Some(*update_number) > last_acknowledged_update)
+            .count()
     }
 
     pub fn get_update(
@@ -335,6 +642,79 @@ impl ServerWorldUpdates {
     }
 }
 
+/// The world updates of the last `RELAY_BUFFER_SECONDS`, which a server relays to spectators.
+/// Only the frames older than the lag compensation window are relayed, as late client actions
+/// can't change them anymore.
+#[derive(Default)]
+pub struct RelayedWorldUpdates {
+    updates: VecDeque<ServerWorldUpdate>,
+    last_recorded_update: Option<u64>,
+}
+
+impl RelayedWorldUpdates {
+    /// Copies the updates stored since the last call, re-simulated frames replace the recorded
+    /// ones. `capacity` is measured in frames.
+    pub fn record(&mut self, server_world_updates: &ServerWorldUpdates, capacity: usize) {
+        let last_recorded_update = self.last_recorded_update;
+        for (update_number, update) in server_world_updates
+            .updates
+            .iter()
+            .filter(|(update_number, _)| Some(*update_number) > last_recorded_update)
+        {
+            self.last_recorded_update = Some(*update_number);
+            match self
+                .updates
+                .binary_search_by_key(&update.frame_number, |update| update.frame_number)
+            {
+                Ok(index) => self.updates[index] = update.clone(),
+                Err(index) if index == self.updates.len() => self.updates.push_back(update.clone()),
+                Err(_) => {
+                    log::trace!(
+                        "Skipping an update for frame {} which is no longer relayed",
+                        update.frame_number
+                    );
+                }
+            }
+        }
+
+        let overflow = self.updates.len().saturating_sub(capacity);
+        self.updates.drain(0..overflow);
+    }
+
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.updates.front().map(|update| update.frame_number)
+    }
+
+    /// The latest frame which can be relayed.
+    pub fn final_frame(&self) -> Option<u64> {
+        self.updates
+            .back()?
+            .frame_number
+            .checked_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64)
+            .filter(|final_frame| Some(*final_frame) >= self.oldest_frame())
+    }
+
+    /// Returns the finalized updates starting with `start_frame`, at most `max_frames` of them.
+    pub fn final_updates(
+        &self,
+        start_frame: u64,
+        max_frames: usize,
+    ) -> impl Iterator<Item = &ServerWorldUpdate> {
+        let final_frame = self.final_frame();
+        self.updates
+            .iter()
+            .skip_while(move |update| update.frame_number < start_frame)
+            .take_while(move |update| Some(update.frame_number) <= final_frame)
+            .take(max_frames)
+    }
+
+    /// Is called when a new game starts.
+    pub fn reset(&mut self) {
+        self.updates.clear();
+        self.last_recorded_update = None;
+    }
+}
+
 /// The resource which aggregates all the updates a client is going to broadcast.
 #[derive(Default)]
 pub struct ClientWorldUpdates {
@@ -342,6 +722,8 @@ pub struct ClientWorldUpdates {
     pub walk_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerWalkAction>>>,
     /// Immediate update.
     pub cast_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerCastAction>>>,
+    /// Immediate update.
+    pub interact_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerInteractAction>>>,
     /// Batched update.
     pub look_actions_updates: VecDeque<(u64, Vec<NetUpdate<ClientActionUpdate<PlayerLookAction>>>)>,
 }
@@ -350,6 +732,7 @@ impl ClientWorldUpdates {
     pub fn clear(&mut self) {
         self.walk_action_updates.clear();
         self.cast_action_updates.clear();
+        self.interact_action_updates.clear();
         self.look_actions_updates.clear();
     }
 }
@@ -403,6 +786,36 @@ impl FramedUpdate for ReceivedClientActionUpdates {
     }
 }
 
+/// Actions a client has made during a frame, is sent with PlayerInputs until the server
+/// acknowledges it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub frame_number: u64,
+    pub walk_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerWalkAction>>>,
+    pub cast_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerCastAction>>>,
+    /// Isn't rolled back, a server applies interactions on the frame they arrive.
+    pub interact_action_updates: Vec<NetUpdate<ClientActionUpdate<PlayerInteractAction>>>,
+}
+
+impl InputFrame {
+    pub fn last_client_action_id(&self) -> Option<NetIdentifier> {
+        self.walk_action_updates
+            .iter()
+            .map(|update| update.data.client_action_id)
+            .chain(
+                self.cast_action_updates
+                    .iter()
+                    .map(|update| update.data.client_action_id),
+            )
+            .chain(
+                self.interact_action_updates
+                    .iter()
+                    .map(|update| update.data.client_action_id),
+            )
+            .max()
+    }
+}
+
 /// Is sent by client, gets aggregated into PlayerActionUpdates on server side.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImmediatePlayerActionsUpdates<T> {
@@ -448,6 +861,17 @@ impl ServerWorldUpdate {
             spawn_actions: Vec::new(),
         }
     }
+
+    /// Clears the update keeping the allocated buffers.
+    pub fn reset(&mut self, frame_number: u64) {
+        self.frame_number = frame_number;
+        self.player_walk_actions_updates.clear();
+        self.player_look_actions_updates.clear();
+        self.player_cast_actions_updates.clear();
+        self.mob_actions_updates.clear();
+        self.damage_histories_updates.clear();
+        self.spawn_actions.clear();
+    }
 }
 
 /// I hate this struct name.
@@ -1,43 +1,83 @@
 use amethyst::{
     core::Time,
-    ecs::{prelude::World, ReadExpect, WriteExpect},
+    ecs::{prelude::World, ReadExpect},
     shred::{ResourceId, SystemData},
 };
 
 use std::time::Duration;
 
-use crate::ecs::resources::GameTime;
+use crate::{
+    ecs::resources::{GameTime, DEFAULT_TICK_RATE},
+    net::interpolation_frame_delay,
+};
 
+/// Only reads `GameTime`, so that the systems using it can run in parallel.
 #[derive(SystemData)]
 pub struct GameTimeService<'a> {
     engine_time: ReadExpect<'a, Time>,
-    game_time: WriteExpect<'a, GameTime>,
+    game_time: ReadExpect<'a, GameTime>,
 }
 
 impl<'a> GameTimeService<'a> {
-    pub fn set_game_start_time(&mut self) {
-        self.game_time.level_started_at = self.engine_time.absolute_time();
-        self.game_time.started_at_frame_number = self.engine_time.frame_number();
-        self.game_time.frames_skipped = 0;
-    }
-
     pub fn engine_time(&self) -> &Time {
         &self.engine_time
     }
 
+    pub fn tick_rate(&self) -> u32 {
+        self.game_time.tick_rate
+    }
+
+    /// The simulation step, use it instead of `Time::fixed_seconds`.
+    pub fn tick_seconds(&self) -> f32 {
+        1.0 / self.game_time.tick_rate as f32
+    }
+
+    pub fn is_tick(&self) -> bool {
+        match self.game_time.tick_clock.as_ref() {
+            Some(tick_clock) => tick_clock.is_tick(),
+            None => self.engine_frames_since_start() % self.game_time.frames_per_tick == 0,
+        }
+    }
+
+    /// Converts a number of frames at `DEFAULT_TICK_RATE` to the current tick rate.
+    pub fn scale_frames(&self, frames: u64) -> u64 {
+        (frames * u64::from(self.game_time.tick_rate) / u64::from(DEFAULT_TICK_RATE)).max(1)
+    }
+
+    pub fn interpolation_frame_delay(&self) -> u64 {
+        interpolation_frame_delay(self.game_time.tick_rate)
+    }
+
     pub fn level_duration(&self) -> Duration {
-        let level_duration_secs =
-            self.game_frame_number() as f32 * self.engine_time.fixed_seconds();
+        let level_duration_secs = self.game_frame_number() as f32 * self.tick_seconds();
         Duration::from_secs_f32(level_duration_secs)
     }
 
     pub fn game_frame_number(&self) -> u64 {
-        (self.engine_time.frame_number() - self.game_time.started_at_frame_number)
+        (self.game_frame_number_absolute() + self.game_time.frames_added)
             .saturating_sub(self.game_time.frames_skipped)
     }
 
+    /// The number of frames the simulation advances by on the current tick, which is more
+    /// than one only for sped up offline games, see `GameTime::time_scale`.
+    pub fn advanced_frames(&self) -> u64 {
+        self.game_time.advanced_frames
+    }
+
+    /// The smoothed estimate of the server's current frame, is used by clients instead of
+    /// the frame numbers of the last received messages, which are behind by the latency.
+    pub fn estimated_server_frame_number(&self) -> Option<u64> {
+        self.game_time
+            .server_clock
+            .map(|server_clock| server_clock.peer_frame_number(self.game_frame_number_absolute()))
+    }
+
     pub fn game_frame_number_absolute(&self) -> u64 {
-        self.engine_time.frame_number() - self.game_time.started_at_frame_number
+        let ticks = match self.game_time.tick_clock.as_ref() {
+            Some(tick_clock) => tick_clock.ticks().saturating_sub(1),
+            None => self.engine_frames_since_start() / self.game_time.frames_per_tick,
+        };
+        self.game_time.started_at_game_frame_number + ticks
     }
 
     pub fn seconds_to_frame(&self, game_frame_number: u64) -> f32 {
@@ -52,6 +92,10 @@ impl<'a> GameTimeService<'a> {
     }
 
     pub fn seconds_between_frames(&self, lhs: u64, rhs: u64) -> f32 {
-        (lhs as f32 - rhs as f32).abs() * self.engine_time.fixed_seconds()
+        (lhs as f32 - rhs as f32).abs() * self.tick_seconds()
+    }
+
+    fn engine_frames_since_start(&self) -> u64 {
+        self.engine_time.frame_number() - self.game_time.started_at_frame_number
     }
 }
@@ -0,0 +1,39 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// Emotes sent more often than once per this number of frames are dropped by the server.
+pub const EMOTE_COOLDOWN_FRAMES: u64 = 60;
+/// For how long an emote bubble is shown above a player.
+pub const EMOTE_DURATION_MS: u64 = 2000;
+
+/// Emote sprites are numbered in the same order in `resources/assets/emotes.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Emote {
+    Wave,
+    Laugh,
+    Angry,
+    Heart,
+}
+
+impl Emote {
+    /// Hotkeys are bound to `emote_1`, `emote_2`, etc. in the same order.
+    pub const ALL: [Emote; 4] = [Emote::Wave, Emote::Laugh, Emote::Angry, Emote::Heart];
+
+    /// Is shown in the multiplayer room, where there are no sprites to draw a bubble with.
+    pub fn text(self) -> &'static str {
+        match self {
+            Emote::Wave => "Hi!",
+            Emote::Laugh => "Haha",
+            Emote::Angry => "Grr!",
+            Emote::Heart => "<3",
+        }
+    }
+
+    pub fn sprite_number(self) -> usize {
+        match self {
+            Emote::Wave => 0,
+            Emote::Laugh => 1,
+            Emote::Angry => 2,
+            Emote::Heart => 3,
+        }
+    }
+}
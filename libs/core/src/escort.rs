@@ -0,0 +1,243 @@
+//! Escort objectives: a payload follows a path through a level while players stay next to it.
+//! The authoritative side moves it as a part of `LevelScriptRunner::update` and broadcasts its
+//! progress with `UpdatePayload`, multiplayer clients only display the received progress.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::math::{deterministic, Vector2};
+
+/// The authoritative side broadcasts the payload progress once per this many frames.
+pub const PAYLOAD_BROADCAST_INTERVAL: u64 = 15;
+
+/// The path is measured with this many straight pieces per a pair of control points.
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// Is declared in a level script, see `LevelScript::payload`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptPayload {
+    /// Control points of a Catmull-Rom spline that passes through all of them,
+    /// the payload starts at the first one and arrives at the last one.
+    pub path: Vec<(f32, f32)>,
+    /// Units per second.
+    pub speed: f32,
+    /// The payload moves only while a living player is this close to it.
+    pub escort_radius: f32,
+    /// Shares of the path (between 0.0 and 1.0, ascending), the payload rolls back
+    /// to the last reached one when every player dies.
+    #[serde(default)]
+    pub checkpoints: Vec<f32>,
+}
+
+impl ScriptPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.len() < 2 {
+            return Err("a payload path needs at least 2 points".to_owned());
+        }
+        if self.speed <= 0.0 || self.escort_radius <= 0.0 {
+            return Err("a payload needs a positive speed and escort radius".to_owned());
+        }
+        let mut previous = 0.0;
+        for checkpoint in &self.checkpoints {
+            if *checkpoint <= previous || *checkpoint >= 1.0 {
+                return Err(format!("invalid payload checkpoint {}", checkpoint));
+            }
+            previous = *checkpoint;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PayloadProgress {
+    /// Travelled distance along the path.
+    pub distance: f32,
+    /// The distance of the last reached checkpoint, is 0.0 until the first one.
+    pub checkpoint_distance: f32,
+    pub is_escorted: bool,
+    /// The game frame the progress is computed at, clients ignore older updates.
+    pub frame_number: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PayloadEvent {
+    CheckpointReached,
+    /// Every player has died, the payload is back at the last reached checkpoint.
+    RolledBack,
+    Arrived,
+}
+
+#[derive(Clone, Debug)]
+pub struct PayloadEscort {
+    payload: ScriptPayload,
+    /// Points along the spline with the path length up to each of them.
+    samples: Vec<(Vector2, f32)>,
+    progress: PayloadProgress,
+}
+
+impl PayloadEscort {
+    pub fn new(payload: ScriptPayload) -> Self {
+        let samples = sample_path(&payload.path);
+        Self {
+            payload,
+            samples,
+            progress: PayloadProgress::default(),
+        }
+    }
+
+    pub fn payload(&self) -> &ScriptPayload {
+        &self.payload
+    }
+
+    pub fn progress(&self) -> PayloadProgress {
+        self.progress
+    }
+
+    pub fn length(&self) -> f32 {
+        self.samples.last().map_or(0.0, |(_, length)| *length)
+    }
+
+    /// Travelled share of the path, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        let length = self.length();
+        if length > 0.0 {
+            (self.progress.distance / length).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    pub fn checkpoint_fraction(&self) -> f32 {
+        let length = self.length();
+        if length > 0.0 {
+            self.progress.checkpoint_distance / length
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_arrived(&self) -> bool {
+        self.progress.distance >= self.length()
+    }
+
+    pub fn position(&self) -> Vector2 {
+        self.position_at(self.progress.distance)
+    }
+
+    pub fn position_at(&self, distance: f32) -> Vector2 {
+        let i = self
+            .samples
+            .iter()
+            .position(|(_, length)| *length >= distance)
+            .unwrap_or(self.samples.len() - 1);
+        if i == 0 {
+            return self.samples[0].0;
+        }
+        let (from, from_length) = self.samples[i - 1];
+        let (to, to_length) = self.samples[i];
+        let piece_length = to_length - from_length;
+        if piece_length <= 0.0 {
+            return to;
+        }
+        from + (to - from) * ((distance - from_length) / piece_length).min(1.0)
+    }
+
+    /// Applies a progress received from a server, older ones are ignored.
+    pub fn set_progress(&mut self, progress: PayloadProgress) {
+        if progress.frame_number >= self.progress.frame_number {
+            self.progress = progress;
+        }
+    }
+
+    /// Puts the payload at a checkpoint distance, is used when resuming a saved match.
+    pub fn restore_checkpoint(&mut self, checkpoint_distance: f32) {
+        let checkpoint_distance = checkpoint_distance.max(0.0).min(self.length());
+        self.progress.distance = checkpoint_distance;
+        self.progress.checkpoint_distance = checkpoint_distance;
+    }
+
+    /// Moves the payload if any of `player_positions` (players that are alive) is close enough.
+    pub fn update(
+        &mut self,
+        delta_seconds: f32,
+        player_positions: &[Vector2],
+        frame_number: u64,
+    ) -> Option<PayloadEvent> {
+        self.progress.frame_number = frame_number;
+        if self.is_arrived() {
+            self.progress.is_escorted = false;
+            return None;
+        }
+        if player_positions.is_empty() {
+            self.progress.is_escorted = false;
+            if self.progress.distance > self.progress.checkpoint_distance {
+                self.progress.distance = self.progress.checkpoint_distance;
+                return Some(PayloadEvent::RolledBack);
+            }
+            return None;
+        }
+
+        let position = self.position();
+        let escort_radius_squared = self.payload.escort_radius * self.payload.escort_radius;
+        self.progress.is_escorted = player_positions.iter().any(|player_position| {
+            (player_position - position).norm_squared() <= escort_radius_squared
+        });
+        if !self.progress.is_escorted {
+            return None;
+        }
+
+        let length = self.length();
+        self.progress.distance =
+            (self.progress.distance + self.payload.speed * delta_seconds).min(length);
+        if self.is_arrived() {
+            return Some(PayloadEvent::Arrived);
+        }
+        let reached_checkpoint = self
+            .payload
+            .checkpoints
+            .iter()
+            .map(|checkpoint| checkpoint * length)
+            .filter(|checkpoint_distance| *checkpoint_distance <= self.progress.distance)
+            .last();
+        match reached_checkpoint {
+            Some(checkpoint_distance)
+                if checkpoint_distance > self.progress.checkpoint_distance =>
+            {
+                self.progress.checkpoint_distance = checkpoint_distance;
+                Some(PayloadEvent::CheckpointReached)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn sample_path(path: &[(f32, f32)]) -> Vec<(Vector2, f32)> {
+    let points = path
+        .iter()
+        .map(|(x, y)| Vector2::new(*x, *y))
+        .collect::<Vec<_>>();
+    let mut samples = vec![(points[0], 0.0)];
+    for i in 0..points.len() - 1 {
+        // The end points are repeated to give the first and the last segment a tangent.
+        let p0 = points[i.saturating_sub(1)];
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        let p3 = points[(i + 2).min(points.len() - 1)];
+        for sample in 1..=SAMPLES_PER_SEGMENT {
+            let t = sample as f32 / SAMPLES_PER_SEGMENT as f32;
+            let point = catmull_rom(p0, p1, p2, p3, t);
+            let (previous, length) = *samples.last().unwrap();
+            samples.push((point, length + deterministic::norm(&(point - previous))));
+        }
+    }
+    samples
+}
+
+fn catmull_rom(p0: Vector2, p1: Vector2, p2: Vector2, p3: Vector2, t: f32) -> Vector2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
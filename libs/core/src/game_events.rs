@@ -0,0 +1,39 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::net::NetIdentifier;
+
+/// Something worth showing in the event ticker. The authoritative side detects events,
+/// a server sends them to the clients with `GameEvents`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// `killer` is the entity net id of the player who has dealt the most threat
+    /// (minions count for their owners), is `None` if nobody has.
+    Kill {
+        killer: Option<NetIdentifier>,
+        monster: String,
+        is_elite: bool,
+    },
+    /// Contains the player's entity net id.
+    PlayerDied(NetIdentifier),
+    /// The team has reached this level.
+    LevelUp(u32),
+    /// An intermission has started after this wave.
+    WaveCleared(usize),
+    WaveStarted(usize),
+    /// The lobby has completed the campaign level with this index.
+    CampaignLevelCompleted(usize),
+}
+
+impl GameEvent {
+    /// The player the event is about, the ticker colors the event with their color.
+    pub fn player(&self) -> Option<NetIdentifier> {
+        match self {
+            GameEvent::Kill { killer, .. } => *killer,
+            GameEvent::PlayerDied(player) => Some(*player),
+            GameEvent::LevelUp(_)
+            | GameEvent::WaveCleared(_)
+            | GameEvent::WaveStarted(_)
+            | GameEvent::CampaignLevelCompleted(_) => None,
+        }
+    }
+}
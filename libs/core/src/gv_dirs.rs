@@ -0,0 +1,220 @@
+//! Resolves where the game keeps its files: XDG directories on Linux, AppData on Windows
+//! and Library on macOS. Nothing the game writes should be placed relative to the working
+//! directory, which is wherever the binary happened to be launched from.
+
+use directories::{ProjectDirs, UserDirs};
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub const CLIENT_LOGGING_CONFIG_FILE: &str = "client_logging_config.toml";
+pub const SERVER_LOGGING_CONFIG_FILE: &str = "server_logging_config.toml";
+
+#[derive(Debug, Clone)]
+pub struct GvDirs {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    media_dir: PathBuf,
+    screenshots_dir: PathBuf,
+}
+
+impl GvDirs {
+    /// Falls back to the working directory if the platform doesn't have a home directory.
+    pub fn new() -> Self {
+        match ProjectDirs::from("", "Psychedelic Donkey", "Grumpy Visitors") {
+            Some(project_dirs) => {
+                let data_dir = project_dirs.data_local_dir().to_owned();
+                // Not every platform (or user) has videos and pictures directories.
+                let user_dirs = UserDirs::new();
+                let media_dir = user_dirs
+                    .as_ref()
+                    .and_then(UserDirs::video_dir)
+                    .map_or_else(
+                        || data_dir.join("media"),
+                        |video_dir| video_dir.join("Grumpy Visitors"),
+                    );
+                let screenshots_dir = user_dirs
+                    .as_ref()
+                    .and_then(UserDirs::picture_dir)
+                    .map_or_else(
+                        || data_dir.join("screenshots"),
+                        |picture_dir| picture_dir.join("Grumpy Visitors"),
+                    );
+                Self {
+                    config_dir: project_dirs.config_dir().to_owned(),
+                    data_dir,
+                    media_dir,
+                    screenshots_dir,
+                }
+            }
+            None => Self::with_root(Path::new(".")),
+        }
+    }
+
+    /// Keeps everything under a single directory, is used by tests.
+    pub fn with_root(root: &Path) -> Self {
+        Self {
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+            media_dir: root.join("media"),
+            screenshots_dir: root.join("screenshots"),
+        }
+    }
+
+    pub fn create_all(&self) -> io::Result<()> {
+        for dir in &[
+            self.config_dir(),
+            self.profiles_dir(),
+            self.saves_dir(),
+            self.replays_dir(),
+            self.highlights_dir(),
+            self.logs_dir(),
+        ] {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Bindings, display and logging configs.
+    pub fn config_dir(&self) -> PathBuf {
+        self.config_dir.clone()
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone()
+    }
+
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.data_dir.join("profiles")
+    }
+
+    pub fn profile_path(&self) -> PathBuf {
+        self.profiles_dir().join("profile.ron")
+    }
+
+    pub fn saves_dir(&self) -> PathBuf {
+        self.data_dir.join("saves")
+    }
+
+    pub fn saved_match_path(&self) -> PathBuf {
+        self.saves_dir().join("saved_match.ron")
+    }
+
+    /// Relative `--record-session` paths are resolved against this directory.
+    pub fn replays_dir(&self) -> PathBuf {
+        self.data_dir.join("replays")
+    }
+
+    /// Highlights saved around deaths and elite kills, in the `--record-session` format.
+    pub fn highlights_dir(&self) -> PathBuf {
+        self.replays_dir().join("highlights")
+    }
+
+    /// Saved on `report_desync` by clients started with `--desync-reports`.
+    pub fn desync_reports_dir(&self) -> PathBuf {
+        self.replays_dir().join("desync_reports")
+    }
+
+    /// Clips captured from the game window, is in the user's videos directory
+    /// and isn't created until something is saved there.
+    pub fn media_dir(&self) -> PathBuf {
+        self.media_dir.clone()
+    }
+
+    /// Is in the user's pictures directory and isn't created until a screenshot is taken.
+    pub fn screenshots_dir(&self) -> PathBuf {
+        self.screenshots_dir.clone()
+    }
+
+    /// Relative `log_file` paths from the logging configs are resolved against this directory.
+    pub fn logs_dir(&self) -> PathBuf {
+        self.data_dir.join("logs")
+    }
+
+    pub fn crash_reports_dir(&self) -> PathBuf {
+        self.data_dir.join("crash_reports")
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.data_dir.join("mods")
+    }
+
+    /// Prefers the config directory, but still reads a config placed in the working
+    /// directory, which is convenient when running the game from the repository.
+    pub fn logging_config_path(&self, file_name: &str) -> PathBuf {
+        let path = self.config_dir.join(file_name);
+        if !path.exists() && Path::new(file_name).exists() {
+            return PathBuf::from(file_name);
+        }
+        path
+    }
+
+    /// Moves the files left by older versions of the game to their current locations.
+    /// A file is never moved over an existing one. Returns the new paths of the moved files.
+    pub fn migrate_legacy_files(&self, legacy_working_dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut moved = Vec::new();
+        for (legacy_path, path) in &[
+            // The profile used to live next to the config files.
+            (self.config_dir.join("profile.ron"), self.profile_path()),
+            (
+                self.data_dir.join("saved_match.ron"),
+                self.saved_match_path(),
+            ),
+        ] {
+            if move_file(legacy_path, path)? {
+                moved.push(path.clone());
+            }
+        }
+
+        // Crash reports used to be written to the working directory if there was no home one.
+        let legacy_crash_reports_dir = legacy_working_dir.join("crash_reports");
+        if legacy_crash_reports_dir.is_dir() && legacy_crash_reports_dir != self.crash_reports_dir()
+        {
+            for entry in fs::read_dir(&legacy_crash_reports_dir)? {
+                let legacy_path = entry?.path();
+                let path = self
+                    .crash_reports_dir()
+                    .join(legacy_path.file_name().expect("Expected a file name"));
+                if move_file(&legacy_path, &path)? {
+                    moved.push(path);
+                }
+            }
+            // Fails if something couldn't be moved, which is fine.
+            let _ = fs::remove_dir(&legacy_crash_reports_dir);
+        }
+        Ok(moved)
+    }
+}
+
+impl Default for GvDirs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resolves a user-provided path against `dir` unless it's absolute.
+pub fn resolve_path(dir: &Path, path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        path.to_owned()
+    } else {
+        dir.join(path)
+    }
+}
+
+fn move_file(from: &Path, to: &Path) -> io::Result<bool> {
+    if !from.is_file() || to.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Renaming fails if the directories are on different file systems.
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,92 @@
+//! Feedback for the hits of the local player's missiles. Clients show hit markers as soon
+//! as their simulation predicts a hit, then reconcile the prediction with the damage sent
+//! by the server: a hit without matching server damage didn't register.
+
+use std::ops::RangeInclusive;
+
+use crate::damage::DamageType;
+
+/// The server may simulate a hit this many frames earlier or later than a client.
+pub const HIT_TOLERANCE_FRAMES: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitRegistration {
+    Confirmed,
+    /// The server didn't deal any damage of the hit's type around its frame.
+    NoReg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PredictedHit<T> {
+    pub target: T,
+    pub frame_number: u64,
+    pub damage_type: DamageType,
+}
+
+impl<T> PredictedHit<T> {
+    /// Frames of the server damage that can confirm the hit.
+    pub fn frames(&self) -> RangeInclusive<u64> {
+        self.frame_number.saturating_sub(HIT_TOLERANCE_FRAMES)
+            ..=self.frame_number + HIT_TOLERANCE_FRAMES
+    }
+}
+
+/// Predicted hits waiting for the server's damage.
+#[derive(Debug)]
+pub struct PredictedHits<T> {
+    pending: Vec<PredictedHit<T>>,
+}
+
+impl<T> Default for PredictedHits<T> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy> PredictedHits<T> {
+    pub fn add(&mut self, target: T, frame_number: u64, damage_type: DamageType) {
+        self.pending.push(PredictedHit {
+            target,
+            frame_number,
+            damage_type,
+        });
+    }
+
+    /// Confirms the hits with matching damage, `is_damaged` is passed a hit and returns
+    /// whether its target has taken damage of its type within its frames. Hits without
+    /// damage become no-regs once the server updates of all their frames are received,
+    /// up to `acknowledged_frame`.
+    pub fn resolve(
+        &mut self,
+        acknowledged_frame: u64,
+        mut is_damaged: impl FnMut(&PredictedHit<T>) -> bool,
+    ) -> Vec<(PredictedHit<T>, HitRegistration)> {
+        let mut resolved = Vec::new();
+        self.pending.retain(|hit| {
+            let registration = if is_damaged(hit) {
+                HitRegistration::Confirmed
+            } else if *hit.frames().end() <= acknowledged_frame {
+                HitRegistration::NoReg
+            } else {
+                return true;
+            };
+            resolved.push((*hit, registration));
+            false
+        });
+        resolved
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
@@ -0,0 +1,86 @@
+//! Level loading: clients load the assets of a level in the background as soon as they know
+//! which level comes next (in the menus and during level transitions), `LevelLoadingState`
+//! shows a progress bar for whatever isn't loaded by the time a game starts.
+
+use crate::{
+    campaign::CAMPAIGN_LEVELS, ecs::resources::net::MultiplayerGameState,
+    level_scripts::LevelScript, room_settings::RoomSettings,
+};
+
+/// Identifies the level that assets are loaded for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelKey {
+    /// One of `MAPS`.
+    pub map_name: &'static str,
+    pub campaign_level: Option<usize>,
+}
+
+impl LevelKey {
+    /// The level that starts with the next game, a resumed match continues its own
+    /// campaign level.
+    pub fn new(
+        room_settings: &RoomSettings,
+        multiplayer_game_state: &MultiplayerGameState,
+    ) -> Self {
+        let campaign_level = match multiplayer_game_state.resumed_match.as_ref() {
+            Some(saved_match) => saved_match.campaign_level,
+            None => multiplayer_game_state.campaign_level,
+        };
+        Self {
+            map_name: room_settings.map_name(),
+            campaign_level,
+        }
+    }
+
+    pub fn landscape_texture_path(&self) -> String {
+        format!("resources/assets/{}_level.png", self.map_name)
+    }
+
+    pub fn landscape_sprite_sheet_path(&self) -> String {
+        format!("resources/levels/{}.ron", self.map_name)
+    }
+
+    /// Parses the script of the campaign level, is `Ok(None)` for levels without one.
+    pub fn script(&self) -> Result<Option<LevelScript>, String> {
+        let campaign_level = match self.campaign_level {
+            Some(campaign_level) => campaign_level,
+            None => return Ok(None),
+        };
+        let level = CAMPAIGN_LEVELS
+            .get(campaign_level)
+            .ok_or_else(|| format!("Unknown campaign level {}", campaign_level))?;
+        level
+            .script
+            .map(|bytes| {
+                LevelScript::from_bytes(bytes).map_err(|err| format!("{}: {}", level.name, err))
+            })
+            .transpose()
+    }
+}
+
+/// How far the assets of a level have loaded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelLoadProgress {
+    pub loaded: usize,
+    pub total: usize,
+    /// Names of the assets that have failed to load, with the errors.
+    pub errors: Vec<String>,
+}
+
+impl LevelLoadProgress {
+    /// From 0.0 to 1.0, a level without assets to load is complete.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.loaded as f32 / self.total as f32).min(1.0)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.has_failed() && self.loaded >= self.total
+    }
+
+    pub fn has_failed(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
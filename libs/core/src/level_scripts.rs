@@ -0,0 +1,505 @@
+//! Data-driven level scripts: triggers that run actions once their conditions are met.
+//! Scripts are written in RON and embedded into campaign levels (see `campaign`).
+//! The authoritative side evaluates them every tick and multiplayer clients receive the fired
+//! actions with `LevelScriptActions`, so the evaluation has to stay deterministic: triggers are
+//! checked in the order they are declared and each of them fires only once.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    ecs::resources::collision::LevelCollisionGrid,
+    escort::{PayloadEscort, PayloadEvent, ScriptPayload},
+    math::{deterministic, Vector2},
+};
+
+/// Players inside a safe zone restore this share of their max health per second.
+pub const SAFE_ZONE_REGENERATION_RATE: f32 = 0.05;
+
+pub const DAYLIGHT: f32 = 1.0;
+/// The ambient light level in the middle of a night.
+pub const NIGHT_AMBIENT: f32 = 0.3;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelScript {
+    #[serde(default)]
+    pub switches: Vec<ScriptSwitch>,
+    #[serde(default)]
+    pub doors: Vec<ScriptDoor>,
+    #[serde(default)]
+    pub safe_zones: Vec<SafeZone>,
+    /// An escort objective, the level ends once the payload arrives (see `escort`).
+    #[serde(default)]
+    pub payload: Option<ScriptPayload>,
+    /// Replaces the default level size.
+    #[serde(default)]
+    pub bounds: Option<LevelBounds>,
+    /// Clients darken the level and light it up with torches and lights of players and spells.
+    #[serde(default)]
+    pub lighting: LevelLighting,
+    #[serde(default)]
+    pub torches: Vec<Torch>,
+    /// The lookup table clients grade the colors of the level with.
+    #[serde(default)]
+    pub color_grading: ColorGrading,
+    pub triggers: Vec<ScriptTrigger>,
+}
+
+/// How bright a level is without lights, declared in its level script.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LevelLighting {
+    Daylight,
+    /// Goes from noon to midnight and back once per `period` seconds.
+    DayNightCycle {
+        period: f32,
+    },
+    /// A constant ambient light level, from 0.0 (pitch black) to 1.0.
+    Dark(f32),
+}
+
+impl LevelLighting {
+    pub fn ambient_light(&self, seconds_since_start: f32) -> f32 {
+        match self {
+            LevelLighting::Daylight => DAYLIGHT,
+            LevelLighting::DayNightCycle { period } => {
+                let phase = (seconds_since_start / period.max(1.0)).fract();
+                let day = 0.5 + 0.5 * (phase * std::f32::consts::PI * 2.0).cos();
+                NIGHT_AMBIENT + (DAYLIGHT - NIGHT_AMBIENT) * day
+            }
+            LevelLighting::Dark(ambient) => ambient.max(0.0).min(DAYLIGHT),
+        }
+    }
+}
+
+impl Default for LevelLighting {
+    fn default() -> Self {
+        LevelLighting::Daylight
+    }
+}
+
+/// A light placed by a level script.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Torch {
+    pub position: (f32, f32),
+    #[serde(default = "default_torch_radius")]
+    pub radius: f32,
+}
+
+fn default_torch_radius() -> f32 {
+    200.0
+}
+
+/// Color grading lookup tables, see `resources/luts`. Level scripts pick one for their level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorGrading {
+    Neutral,
+    Warm,
+    Cold,
+}
+
+impl ColorGrading {
+    pub const ALL: [ColorGrading; 3] = [
+        ColorGrading::Neutral,
+        ColorGrading::Warm,
+        ColorGrading::Cold,
+    ];
+
+    pub fn lut_path(self) -> &'static str {
+        match self {
+            ColorGrading::Neutral => "resources/luts/neutral.png",
+            ColorGrading::Warm => "resources/luts/warm.png",
+            ColorGrading::Cold => "resources/luts/cold.png",
+        }
+    }
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        ColorGrading::Neutral
+    }
+}
+
+/// The size of a level centered at the origin of the world. Players are kept inside of it
+/// while they walk, `out_of_bounds` handles the ones that end up outside anyway (for instance,
+/// rejoining with a position from a bigger level).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelBounds {
+    pub size: (f32, f32),
+    #[serde(default)]
+    pub out_of_bounds: OutOfBounds,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OutOfBounds {
+    /// Moves players to the closest point within the bounds.
+    Clamp,
+    /// Moves players to the given point.
+    Teleport((f32, f32)),
+    Kill,
+}
+
+impl Default for OutOfBounds {
+    fn default() -> Self {
+        Self::Clamp
+    }
+}
+
+/// Is activated by a player within `radius` of `position`, stays activated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptSwitch {
+    pub name: String,
+    #[serde(default)]
+    pub kind: SwitchKind,
+    pub position: (f32, f32),
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SwitchKind {
+    /// Is activated as soon as a player steps on it.
+    PressurePlate,
+    /// Is activated when a player presses the interact key next to it.
+    Lever,
+}
+
+impl Default for SwitchKind {
+    fn default() -> Self {
+        Self::PressurePlate
+    }
+}
+
+/// Blocks the collision grid cells between the two corners until it's opened.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptDoor {
+    pub name: String,
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+}
+
+/// A circle that monsters can't enter and where players regenerate, e.g. a team base.
+/// Clients tint its boundary with `color`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SafeZone {
+    pub name: String,
+    pub center: (f32, f32),
+    pub radius: f32,
+    #[serde(default = "default_safe_zone_color")]
+    pub color: (f32, f32, f32),
+}
+
+fn default_safe_zone_color() -> (f32, f32, f32) {
+    (0.4, 0.9, 0.5)
+}
+
+impl SafeZone {
+    pub fn contains(&self, position: Vector2) -> bool {
+        (position - vector(self.center)).norm_squared() < self.radius * self.radius
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptTrigger {
+    pub name: String,
+    pub condition: ScriptCondition,
+    pub actions: Vec<ScriptAction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScriptCondition {
+    /// A player is within `radius` of `center`.
+    Region {
+        center: (f32, f32),
+        radius: f32,
+    },
+    /// Seconds since the level has started.
+    Timer(f32),
+    /// Monsters killed since the level has started, minions don't count.
+    KillCount(u32),
+    Switch(String),
+    /// Another trigger has fired, either on an earlier tick or earlier on the same one.
+    Fired(String),
+    All(Vec<ScriptCondition>),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScriptAction {
+    /// Spawns this many monsters at random positions, in addition to the regular waves.
+    SpawnWave(u32),
+    OpenDoor(String),
+    /// Is emitted when a player activates a switch, so that clients can display it,
+    /// scripts can also use it to activate switches on their own.
+    ActivateSwitch(String),
+    EndLevel,
+    ShowMessage(String),
+}
+
+impl LevelScript {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let level_script: LevelScript =
+            ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        level_script.validate()?;
+        Ok(level_script)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(bounds) = self.bounds.as_ref() {
+            let (width, height) = bounds.size;
+            if width <= 0.0 || height <= 0.0 {
+                return Err("Bounds: the size must be positive".to_owned());
+            }
+            if let OutOfBounds::Teleport((x, y)) = bounds.out_of_bounds {
+                if x.abs() > width / 2.0 || y.abs() > height / 2.0 {
+                    return Err("Bounds: the teleport point is out of bounds".to_owned());
+                }
+            }
+        }
+        if let Some(payload) = self.payload.as_ref() {
+            payload
+                .validate()
+                .map_err(|err| format!("Payload: {}", err))?;
+        }
+        for trigger in &self.triggers {
+            self.validate_condition(&trigger.condition)
+                .map_err(|err| format!("Trigger {}: {}", trigger.name, err))?;
+            for action in &trigger.actions {
+                match action {
+                    ScriptAction::OpenDoor(name) if self.door(name).is_none() => {
+                        return Err(format!("Trigger {}: unknown door {}", trigger.name, name));
+                    }
+                    ScriptAction::ActivateSwitch(name) if self.switch_index(name).is_none() => {
+                        return Err(format!("Trigger {}: unknown switch {}", trigger.name, name));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_condition(&self, condition: &ScriptCondition) -> Result<(), String> {
+        match condition {
+            ScriptCondition::Switch(name) => {
+                if self.switch_index(name).is_none() {
+                    return Err(format!("unknown switch {}", name));
+                }
+            }
+            ScriptCondition::Fired(name) => {
+                if !self.triggers.iter().any(|trigger| &trigger.name == name) {
+                    return Err(format!("unknown trigger {}", name));
+                }
+            }
+            ScriptCondition::All(conditions) => {
+                for condition in conditions {
+                    self.validate_condition(condition)?;
+                }
+            }
+            ScriptCondition::Region { .. }
+            | ScriptCondition::Timer(_)
+            | ScriptCondition::KillCount(_) => {}
+        }
+        Ok(())
+    }
+
+    pub fn door(&self, name: &str) -> Option<&ScriptDoor> {
+        self.doors.iter().find(|door| door.name == name)
+    }
+
+    fn switch_index(&self, name: &str) -> Option<usize> {
+        self.switches.iter().position(|switch| switch.name == name)
+    }
+}
+
+/// What happened during a tick, is passed to `LevelScriptRunner::update`.
+pub struct ScriptTick<'a> {
+    pub delta_seconds: f32,
+    pub player_positions: &'a [Vector2],
+    /// Positions of the players that have pressed the interact key during the tick.
+    pub interact_positions: &'a [Vector2],
+    pub kills: u32,
+    pub frame_number: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LevelScriptRunner {
+    script: LevelScript,
+    elapsed_seconds: f32,
+    kills: u32,
+    activated_switches: Vec<bool>,
+    fired_triggers: Vec<bool>,
+    payload: Option<PayloadEscort>,
+    is_ended: bool,
+}
+
+impl LevelScriptRunner {
+    pub fn new(script: LevelScript) -> Self {
+        let activated_switches = vec![false; script.switches.len()];
+        let fired_triggers = vec![false; script.triggers.len()];
+        let payload = script.payload.clone().map(PayloadEscort::new);
+        Self {
+            script,
+            elapsed_seconds: 0.0,
+            kills: 0,
+            activated_switches,
+            fired_triggers,
+            payload,
+            is_ended: false,
+        }
+    }
+
+    pub fn script(&self) -> &LevelScript {
+        &self.script
+    }
+
+    pub fn payload(&self) -> Option<&PayloadEscort> {
+        self.payload.as_ref()
+    }
+
+    /// Multiplayer clients apply the received progress to it.
+    pub fn payload_mut(&mut self) -> Option<&mut PayloadEscort> {
+        self.payload.as_mut()
+    }
+
+    /// Is set by `EndLevel` and by the payload arriving.
+    pub fn is_ended(&self) -> bool {
+        self.is_ended
+    }
+
+    pub fn is_switch_activated(&self, switch_name: &str) -> bool {
+        self.script
+            .switch_index(switch_name)
+            .map_or(false, |i| self.activated_switches[i])
+    }
+
+    pub fn has_fired(&self, trigger_name: &str) -> bool {
+        self.script
+            .triggers
+            .iter()
+            .position(|trigger| trigger.name == trigger_name)
+            .map_or(false, |i| self.fired_triggers[i])
+    }
+
+    /// Blocks the cells of every door, is called when a level starts.
+    pub fn close_doors(&self, collision_grid: &mut LevelCollisionGrid) {
+        for door in &self.script.doors {
+            set_door_blocked(door, collision_grid, true);
+        }
+    }
+
+    /// Returns the actions of the triggers that fire on this tick, in the order of declaration.
+    pub fn update(&mut self, tick: ScriptTick) -> Vec<ScriptAction> {
+        self.elapsed_seconds += tick.delta_seconds;
+        self.kills += tick.kills;
+        let mut actions = Vec::new();
+        if let Some(payload) = self.payload.as_mut() {
+            match payload.update(tick.delta_seconds, tick.player_positions, tick.frame_number) {
+                Some(PayloadEvent::CheckpointReached) => {
+                    actions.push(ScriptAction::ShowMessage("Checkpoint reached".to_owned()));
+                }
+                Some(PayloadEvent::RolledBack) => actions.push(ScriptAction::ShowMessage(
+                    "The payload is back at the checkpoint".to_owned(),
+                )),
+                Some(PayloadEvent::Arrived) => {
+                    self.is_ended = true;
+                    actions.push(ScriptAction::ShowMessage(
+                        "The payload has arrived".to_owned(),
+                    ));
+                    actions.push(ScriptAction::EndLevel);
+                }
+                None => {}
+            }
+        }
+        for (switch, is_activated) in self
+            .script
+            .switches
+            .iter()
+            .zip(self.activated_switches.iter_mut())
+        {
+            let activators = match switch.kind {
+                SwitchKind::PressurePlate => tick.player_positions,
+                SwitchKind::Lever => tick.interact_positions,
+            };
+            if !*is_activated && any_within(activators, switch.position, switch.radius) {
+                *is_activated = true;
+                actions.push(ScriptAction::ActivateSwitch(switch.name.clone()));
+            }
+        }
+
+        for i in 0..self.script.triggers.len() {
+            if self.fired_triggers[i]
+                || !self.is_met(&self.script.triggers[i].condition, tick.player_positions)
+            {
+                continue;
+            }
+            self.fired_triggers[i] = true;
+            for action in &self.script.triggers[i].actions {
+                match action {
+                    ScriptAction::EndLevel => self.is_ended = true,
+                    ScriptAction::ActivateSwitch(name) => {
+                        let switch_i = self.script.switch_index(name).unwrap();
+                        // Activating an already activated switch doesn't need a broadcast.
+                        if std::mem::replace(&mut self.activated_switches[switch_i], true) {
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+                actions.push(action.clone());
+            }
+        }
+        actions
+    }
+
+    fn is_met(&self, condition: &ScriptCondition, player_positions: &[Vector2]) -> bool {
+        match condition {
+            ScriptCondition::Region { center, radius } => {
+                any_within(player_positions, *center, *radius)
+            }
+            ScriptCondition::Timer(seconds) => self.elapsed_seconds >= *seconds,
+            ScriptCondition::KillCount(count) => self.kills >= *count,
+            ScriptCondition::Switch(name) => self.is_switch_activated(name),
+            ScriptCondition::Fired(name) => self.has_fired(name),
+            ScriptCondition::All(conditions) => conditions
+                .iter()
+                .all(|condition| self.is_met(condition, player_positions)),
+        }
+    }
+}
+
+/// Unblocks the cells of a door, is applied by multiplayer clients as well.
+pub fn open_door(script: &LevelScript, name: &str, collision_grid: &mut LevelCollisionGrid) {
+    if let Some(door) = script.door(name) {
+        set_door_blocked(door, collision_grid, false);
+    }
+}
+
+/// Moves a position that is inside a safe zone onto its boundary, away from the center.
+/// Monsters are kept out of safe zones with it after they move.
+pub fn push_out_of_safe_zones(safe_zones: &[SafeZone], mut position: Vector2) -> Vector2 {
+    for safe_zone in safe_zones {
+        if !safe_zone.contains(position) {
+            continue;
+        }
+        let center = vector(safe_zone.center);
+        let offset = position - center;
+        let direction = if offset.norm_squared() > 0.0 {
+            deterministic::normalize(&offset)
+        } else {
+            Vector2::new(1.0, 0.0)
+        };
+        position = center + direction * safe_zone.radius;
+    }
+    position
+}
+
+fn set_door_blocked(door: &ScriptDoor, collision_grid: &mut LevelCollisionGrid, is_blocked: bool) {
+    collision_grid.set_area_blocked(vector(door.from), vector(door.to), is_blocked);
+}
+
+fn any_within(positions: &[Vector2], center: (f32, f32), radius: f32) -> bool {
+    positions
+        .iter()
+        .any(|position| (position - vector(center)).norm() <= radius)
+}
+
+fn vector((x, y): (f32, f32)) -> Vector2 {
+    Vector2::new(x, y)
+}
@@ -1,7 +1,36 @@
+pub mod achievements;
 pub mod actions;
+pub mod aim_assist;
+pub mod announcer;
+pub mod anti_cheat;
+pub mod augments;
+pub mod balance;
+pub mod campaign;
+pub mod classes;
+pub mod collision_layers;
+pub mod content_packs;
+pub mod cosmetics;
+pub mod crash_report;
+pub mod damage;
+pub mod demo;
+pub mod economy;
 pub mod ecs;
+pub mod emotes;
+pub mod escort;
+pub mod game_events;
+pub mod gv_dirs;
+pub mod hit_registration;
+pub mod level_loading;
+pub mod level_scripts;
 pub mod math;
+pub mod mutators;
 pub mod net;
+pub mod objectives;
+pub mod profiling;
+pub mod room_settings;
+pub mod stats;
+pub mod tutorial;
+pub mod votes;
 
 pub static PLAYER_COLORS: [[f32; 3]; 5] = [
     [0.64, 0.12, 0.11],
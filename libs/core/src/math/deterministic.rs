@@ -0,0 +1,191 @@
+//! Simulation math that gives bit-identical results on every platform, so that a server and
+//! its clients don't drift apart when they simulate the same frames.
+//!
+//! Additions, multiplications, divisions and `sqrt` are correctly rounded by IEEE 754, but
+//! `sin`, `cos` and `atan2` of the standard library call the platform's libm, which may differ
+//! in the last bits. The functions below evaluate polynomials in `f64` with a fixed order of
+//! operations instead, and round the results to `f32`.
+//!
+//! Random rolls (such as critical hits) are hashed from the frame number and the action id,
+//! which keeps them deterministic too.
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use crate::math::{Vector2, ZeroVector};
+
+const TAU: f64 = 2.0 * PI;
+const TAN_PI_8: f64 = 0.41421356237309503;
+
+/// Taylor series coefficients of `sin(x) / x` by powers of x², starting from x².
+const SIN_COEFFICIENTS: [f64; 7] = [
+    -0.16666666666666666,
+    0.008333333333333333,
+    -0.0001984126984126984,
+    2.7557319223985893e-06,
+    -2.505210838544172e-08,
+    1.6059043836821613e-10,
+    -7.647163731819816e-13,
+];
+/// Taylor series coefficients of `cos(x)` by powers of x², starting from x².
+const COS_COEFFICIENTS: [f64; 8] = [
+    -0.5,
+    0.041666666666666664,
+    -0.001388888888888889,
+    2.48015873015873e-05,
+    -2.755731922398589e-07,
+    2.08767569878681e-09,
+    -1.1470745597729725e-11,
+    4.779477332387385e-14,
+];
+/// Taylor series coefficients of `atan(x) / x` by powers of x², starting from x².
+const ATAN_COEFFICIENTS: [f64; 14] = [
+    -0.3333333333333333,
+    0.2,
+    -0.14285714285714285,
+    0.1111111111111111,
+    -0.09090909090909091,
+    0.07692307692307693,
+    -0.06666666666666667,
+    0.058823529411764705,
+    -0.05263157894736842,
+    0.047619047619047616,
+    -0.043478260869565216,
+    0.04,
+    -0.037037037037037035,
+    0.034482758620689655,
+];
+
+/// Returns `(sin(angle), cos(angle))`.
+pub fn sin_cos(angle: f32) -> (f32, f32) {
+    let angle = f64::from(angle);
+    // Reduces the angle to [-PI, PI] and then to [-PI / 2, PI / 2],
+    // where the series converge fast enough.
+    let mut x = angle - TAU * (angle / TAU).round();
+    let mut cos_sign = 1.0;
+    if x > FRAC_PI_2 {
+        x = PI - x;
+        cos_sign = -1.0;
+    } else if x < -FRAC_PI_2 {
+        x = -PI - x;
+        cos_sign = -1.0;
+    }
+
+    let x2 = x * x;
+    let sin = x * (1.0 + x2 * horner(&SIN_COEFFICIENTS, x2));
+    let cos = cos_sign * (1.0 + x2 * horner(&COS_COEFFICIENTS, x2));
+    (sin as f32, cos as f32)
+}
+
+/// Returns the angle between the positive X axis and `(x, y)`, in [-PI, PI].
+pub fn atan2(y: f32, x: f32) -> f32 {
+    let (y, x) = (f64::from(y), f64::from(x));
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+
+    let (abs_x, abs_y) = (x.abs(), y.abs());
+    let mut angle = if abs_y <= abs_x {
+        atan_unit(abs_y / abs_x)
+    } else {
+        FRAC_PI_2 - atan_unit(abs_x / abs_y)
+    };
+    if x < 0.0 {
+        angle = PI - angle;
+    }
+    if y < 0.0 {
+        angle = -angle;
+    }
+    angle as f32
+}
+
+/// Returns the signed angle to rotate `from` by to make it point the same way as `to`.
+pub fn angle_between(from: &Vector2, to: &Vector2) -> f32 {
+    let perp = from.x * to.y - from.y * to.x;
+    let dot = from.x * to.x + from.y * to.y;
+    atan2(perp, dot)
+}
+
+/// Rotates a vector counter-clockwise.
+pub fn rotate(vector: &Vector2, angle: f32) -> Vector2 {
+    let (sin, cos) = sin_cos(angle);
+    Vector2::new(
+        cos * vector.x - sin * vector.y,
+        sin * vector.x + cos * vector.y,
+    )
+}
+
+pub fn norm(vector: &Vector2) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+/// Returns a zero vector for zero vectors, instead of NaNs.
+pub fn normalize(vector: &Vector2) -> Vector2 {
+    let norm = norm(vector);
+    if norm == 0.0 {
+        return Vector2::zero();
+    }
+    Vector2::new(vector.x / norm, vector.y / norm)
+}
+
+/// Returns the share of `movement` (from 0.0 to 1.0) after which a circle moving from `from`
+/// first touches a point, if it does. Fast objects test the whole way they move during a frame
+/// this way, instead of only the positions they end up at, so they can't skip over anything.
+pub fn sweep_circle(from: Vector2, movement: Vector2, radius: f32, point: Vector2) -> Option<f32> {
+    let offset = from - point;
+    let c = offset.x * offset.x + offset.y * offset.y - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let a = movement.x * movement.x + movement.y * movement.y;
+    let b = offset.x * movement.x + offset.y * movement.y;
+    if a == 0.0 || b >= 0.0 {
+        return None;
+    }
+    // The smaller root of a * t² + 2 * b * t + c = 0.
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    if t <= 1.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Returns a number in [0, 1) derived from the keys alone, a server and its clients get
+/// the same rolls without sharing any RNG state. Rolls of the same keys are always equal,
+/// so every roll of an action has to use a different `index`.
+pub fn roll(frame_number: u64, action_id: u64, index: u64) -> f32 {
+    let hash = split_mix(split_mix(split_mix(frame_number) ^ action_id) ^ index);
+    // The top 24 bits fit into an f32 mantissa exactly.
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// The SplitMix64 finalizer.
+fn split_mix(x: u64) -> u64 {
+    let mut x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Expects `x` in [0, 1].
+fn atan_unit(x: f64) -> f64 {
+    // atan(x) = PI / 4 + atan((x - 1) / (x + 1)), which keeps the series argument under tan(PI / 8).
+    let (offset, x) = if x > TAN_PI_8 {
+        (FRAC_PI_4, (x - 1.0) / (x + 1.0))
+    } else {
+        (0.0, x)
+    };
+    let x2 = x * x;
+    offset + x * (1.0 + x2 * horner(&ATAN_COEFFICIENTS, x2))
+}
+
+fn horner(coefficients: &[f64], x: f64) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0.0, |result, coefficient| coefficient + x * result)
+}
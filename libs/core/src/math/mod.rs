@@ -1,3 +1,5 @@
+pub mod deterministic;
+
 pub type Vector2 = amethyst::core::math::Vector2<f32>;
 pub type Vector3 = amethyst::core::math::Vector3<f32>;
 
@@ -0,0 +1,100 @@
+//! Mutators change the rules of a custom game. The host picks them in the room settings, and
+//! they are applied as stat modifiers of players and monsters, so that a server and predicting
+//! clients derive the same stats from them.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::stats::{Modifier, ModifierSource, Stat, StatModifiers};
+
+pub const FAST_MONSTERS_SPEED_FACTOR: f32 = 2.0;
+/// Cooldowns recover this many times faster, which makes them shorter than a frame.
+pub const NO_COOLDOWNS_HASTE_FACTOR: f32 = 1000.0;
+/// Makes any hit deal more damage than a player can have.
+pub const ONE_HIT_DEATHS_DAMAGE_FACTOR: f32 = 1000.0;
+/// A share of the dealt missile damage that heals the caster.
+pub const VAMPIRISM_LIFE_STEAL: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Mutator {
+    FastMonsters,
+    NoCooldowns,
+    OneHitDeaths,
+    Vampirism,
+}
+
+impl Mutator {
+    /// Room settings keep mutators in this order.
+    pub const ALL: [Mutator; 4] = [
+        Mutator::FastMonsters,
+        Mutator::NoCooldowns,
+        Mutator::OneHitDeaths,
+        Mutator::Vampirism,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mutator::FastMonsters => "Fast monsters",
+            Mutator::NoCooldowns => "No cooldowns",
+            Mutator::OneHitDeaths => "One-hit deaths",
+            Mutator::Vampirism => "Vampirism",
+        }
+    }
+
+    fn add_player_modifiers(self, stat_modifiers: &mut StatModifiers) {
+        let (stat, modifier) = match self {
+            Mutator::NoCooldowns => (Stat::Haste, Modifier::Multiply(NO_COOLDOWNS_HASTE_FACTOR)),
+            Mutator::OneHitDeaths => (
+                Stat::DamageTaken,
+                Modifier::Multiply(ONE_HIT_DEATHS_DAMAGE_FACTOR),
+            ),
+            Mutator::Vampirism => (Stat::LifeSteal, Modifier::Add(VAMPIRISM_LIFE_STEAL)),
+            Mutator::FastMonsters => return,
+        };
+        stat_modifiers.add(stat, ModifierSource::Mutator(self), modifier);
+    }
+
+    fn add_monster_modifiers(self, stat_modifiers: &mut StatModifiers) {
+        let (stat, modifier) = match self {
+            Mutator::FastMonsters => (
+                Stat::MoveSpeed,
+                Modifier::Multiply(FAST_MONSTERS_SPEED_FACTOR),
+            ),
+            Mutator::NoCooldowns | Mutator::OneHitDeaths | Mutator::Vampirism => return,
+        };
+        stat_modifiers.add(stat, ModifierSource::Mutator(self), modifier);
+    }
+}
+
+pub fn add_player_modifiers(mutators: &[Mutator], stat_modifiers: &mut StatModifiers) {
+    for mutator in mutators {
+        mutator.add_player_modifiers(stat_modifiers);
+    }
+}
+
+pub fn add_monster_modifiers(mutators: &[Mutator], stat_modifiers: &mut StatModifiers) {
+    for mutator in mutators {
+        mutator.add_monster_modifiers(stat_modifiers);
+    }
+}
+
+/// Turns the mutator on or off, keeping the order of `Mutator::ALL`.
+pub fn toggle_mutator(mutators: &mut Vec<Mutator>, mutator: Mutator) {
+    if mutators.contains(&mutator) {
+        mutators.retain(|enabled| *enabled != mutator);
+    } else {
+        mutators.push(mutator);
+        mutators.sort();
+    }
+}
+
+/// Lists the mutators for the lobby and the results screen.
+pub fn mutators_text(mutators: &[Mutator]) -> String {
+    if mutators.is_empty() {
+        return "None".to_owned();
+    }
+    mutators
+        .iter()
+        .map(|mutator| mutator.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -7,31 +7,59 @@ use crate::{
         player::{PlayerCastAction, PlayerWalkAction},
         ClientActionUpdate,
     },
-    ecs::resources::world::{ImmediatePlayerActionsUpdates, PlayerLookActionUpdates},
-    net::NetIdentifier,
+    augments::Augment,
+    classes::PlayerClass,
+    cosmetics::PlayerCosmetics,
+    economy::ShopItem,
+    ecs::resources::world::{ImmediatePlayerActionsUpdates, InputFrame, PlayerLookActionUpdates},
+    emotes::Emote,
+    net::{NetIdentifier, RoomId},
+    room_settings::RoomSettings,
+    votes::VoteKind,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientMessage {
     pub session_id: NetIdentifier,
     pub payload: ClientMessagePayload,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientMessagePayload {
     Heartbeat,
     JoinRoom {
         // As the server stores session id coming with this message
         // (see `ServerMessage::session_id`), `sent_at` is used to filter out outdated handshakes
-        // in case there are duplicates of reliable messages.
+        // in case there are duplicates of reliable messages. It only orders sessions,
+        // the server clock is estimated from pongs (see `ClockSync`).
         sent_at: Duration,
         nickname: String,
+        class: PlayerClass,
+        cosmetics: PlayerCosmetics,
     },
+    /// Is accepted only in the lobby, the server replies with UpdateRoomPlayers.
+    SelectClass(PlayerClass),
+    /// Is rate-limited by the server, which broadcasts accepted emotes to the room.
+    Emote(Emote),
     StartHostedGame,
+    StartHostedCampaign,
+    /// Starts the match saved by the server, or a new one if there's no save.
+    ResumeHostedGame,
     AcknowledgeWorldUpdate(u64),
     WalkActions(ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>),
     CastActions(ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>),
     LookActions(PlayerLookActionUpdates),
+    /// Is accepted only if the player has a level-up to spend,
+    /// the server replies with UpdateAugments.
+    ChooseAugment(Augment),
+    /// Is accepted only while the shop is open and the player has enough coins,
+    /// the server replies with UpdateWallet.
+    BuyItem(ShopItem),
+    /// Is accepted only during an intermission, the next wave starts once all the players are ready.
+    ReadyForWave,
+    /// Is accepted only if the player hasn't reached the minion cap,
+    /// the minion is replicated in world updates like the monsters.
+    Summon,
     Ping(NetIdentifier),
     Pong {
         ping_id: NetIdentifier,
@@ -42,6 +70,51 @@ pub enum ClientMessagePayload {
         kicked_connection_id: NetIdentifier,
     },
     Disconnect,
+    /// Only handled by a server hosting several rooms, which replies with RoomList.
+    ListRooms,
+    /// Only handled by a server hosting several rooms, which replies with RoomEntered.
+    CreateRoom {
+        name: String,
+    },
+    /// Only handled by a server hosting several rooms, which replies with RoomEntered.
+    EnterRoom(RoomId),
+    /// Puts the client into the matchmaking queue, the server replies with MatchmakingQueued
+    /// and later with MatchFound.
+    FindMatch {
+        rating: u32,
+    },
+    CancelMatchmaking,
+    /// Is accepted only for downloadable packs advertised in the Handshake,
+    /// the server replies with ContentPack.
+    RequestContentPack(String),
+    /// A cheat command typed into the developer console, is accepted only from the host
+    /// by servers built with debug assertions. The server replies with ConsoleOutput.
+    ConsoleCommand(String),
+    /// Joins a running game without a player, the server replies with a Handshake,
+    /// StartSpectating and a stream of RelayWorld messages. `sent_at` is handled
+    /// the same way as the one of JoinRoom.
+    Spectate {
+        sent_at: Duration,
+        nickname: String,
+    },
+    /// Is sent unreliably instead of WalkActions and CastActions, carries the latest input
+    /// frames the server hasn't acknowledged yet. The server skips the actions it has already
+    /// received and replies with AcknowledgeInputs.
+    PlayerInputs(Vec<InputFrame>),
+    /// Is rate-limited per player and accepted only if no other vote is running,
+    /// the server broadcasts UpdateVote. The initiator votes yes.
+    StartVote(VoteKind),
+    /// Votes in the running vote, the server broadcasts UpdateVote.
+    CastVote {
+        vote_id: NetIdentifier,
+        is_yes: bool,
+    },
+    /// Is accepted from the host only while the game isn't started, the server validates
+    /// the settings and broadcasts them with UpdateRoomSettings.
+    UpdateRoomSettings(RoomSettings),
+    /// For how many frames the player can lag behind before the game pauses for them,
+    /// the server clamps it to `[1, PAUSE_FRAME_THRESHOLD]` and confirms with PauseThreshold.
+    SetPauseThreshold(u64),
 }
 
 impl ClientMessagePayload {
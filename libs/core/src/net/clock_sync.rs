@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+/// Only the latest samples are filtered, so that the estimate follows a changing route.
+const CLOCK_SAMPLES_LIMIT: usize = 8;
+/// The share of a new measurement blended into the offset, smooths out the jitter.
+const OFFSET_SMOOTHING: f64 = 0.1;
+/// The share of a new measurement blended into the drift.
+const DRIFT_SMOOTHING: f64 = 0.05;
+/// Clocks that run more than 1% apart are rather paused or restarted than drifting.
+const MAX_DRIFT: f64 = 0.01;
+/// Larger corrections (a pause, a new level) aren't smoothed out, the estimate starts anew.
+const OFFSET_RESET_FRAMES: f64 = 30.0;
+
+/// Estimates the frame number of a peer from ping/pong round trips, the way NTP does:
+/// a pong tells the peer's frame at the moment it was sent, which is half a round trip ago.
+///
+/// The local clock is the number of ticks since the level start, it keeps running when
+/// the game is paused, so the estimate keeps running too.
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    samples: VecDeque<ClockSample>,
+    estimate: Option<ClockEstimate>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClockSample {
+    clock_frame_number: u64,
+    offset: f64,
+    round_trip_frames: u64,
+}
+
+/// A smoothed offset between a peer's frame number and the local clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    pub offset: f64,
+    /// How much the offset changes each frame of the local clock.
+    pub drift: f64,
+    pub clock_frame_number: u64,
+}
+
+impl ClockEstimate {
+    pub fn offset_at(&self, clock_frame_number: u64) -> f64 {
+        self.offset + self.drift * (clock_frame_number as f64 - self.clock_frame_number as f64)
+    }
+
+    pub fn peer_frame_number(&self, clock_frame_number: u64) -> u64 {
+        (clock_frame_number as f64 + self.offset_at(clock_frame_number))
+            .round()
+            .max(0.0) as u64
+    }
+}
+
+impl ClockSync {
+    /// `clock_frame_number` is the local clock when the pong was received,
+    /// `round_trip_frames` is measured with the same clock.
+    pub fn add_sample(
+        &mut self,
+        clock_frame_number: u64,
+        peer_frame_number: u64,
+        round_trip_frames: u64,
+    ) {
+        let sample = ClockSample {
+            clock_frame_number,
+            offset: peer_frame_number as f64 + round_trip_frames as f64 / 2.0
+                - clock_frame_number as f64,
+            round_trip_frames,
+        };
+        // A sample can be off by half of its round trip at most.
+        let is_jump = self.estimate.map_or(false, |estimate| {
+            (sample.offset - estimate.offset_at(clock_frame_number)).abs()
+                > OFFSET_RESET_FRAMES + round_trip_frames as f64 / 2.0
+        });
+        if is_jump {
+            // The older samples describe the clocks before the jump.
+            self.samples.clear();
+        }
+        if self.samples.len() == CLOCK_SAMPLES_LIMIT {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        if is_jump {
+            self.reset_estimate(clock_frame_number, sample.offset);
+            return;
+        }
+
+        // The fastest round trip has spent the least time in queues, so its halves
+        // are the most symmetric. The newest one is preferred of the equally fast.
+        let drift = self.estimate.map_or(0.0, |estimate| estimate.drift);
+        let measured_offset = self
+            .samples
+            .iter()
+            .rev()
+            .min_by_key(|sample| sample.round_trip_frames)
+            .map(|sample| {
+                sample.offset
+                    + drift * (clock_frame_number as f64 - sample.clock_frame_number as f64)
+            })
+            .expect("Expected a clock sample");
+
+        let estimate = match self.estimate {
+            Some(estimate) => estimate,
+            None => {
+                self.reset_estimate(clock_frame_number, measured_offset);
+                return;
+            }
+        };
+        let predicted_offset = estimate.offset_at(clock_frame_number);
+        let offset = predicted_offset + (measured_offset - predicted_offset) * OFFSET_SMOOTHING;
+        let elapsed_frames = clock_frame_number.saturating_sub(estimate.clock_frame_number);
+        let drift = if elapsed_frames > 0 {
+            let measured_drift = (offset - estimate.offset) / elapsed_frames as f64;
+            (estimate.drift + (measured_drift - estimate.drift) * DRIFT_SMOOTHING)
+                .max(-MAX_DRIFT)
+                .min(MAX_DRIFT)
+        } else {
+            estimate.drift
+        };
+        self.estimate = Some(ClockEstimate {
+            offset,
+            drift,
+            clock_frame_number,
+        });
+    }
+
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.estimate
+    }
+
+    pub fn peer_frame_number(&self, clock_frame_number: u64) -> Option<u64> {
+        self.estimate
+            .map(|estimate| estimate.peer_frame_number(clock_frame_number))
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.estimate = None;
+    }
+
+    fn reset_estimate(&mut self, clock_frame_number: u64, offset: f64) {
+        self.estimate = Some(ClockEstimate {
+            offset,
+            drift: 0.0,
+            clock_frame_number,
+        });
+    }
+}
@@ -1,16 +1,83 @@
+use serde::{de::DeserializeOwned, Serialize};
 use serde_derive::{Deserialize, Serialize};
 
 pub mod client_message;
+pub mod clock_sync;
+pub mod rendezvous;
 pub mod server_message;
+pub mod session_recording;
 
 use amethyst::network::Bytes;
 
-use crate::ecs::components::WorldPosition;
+use crate::{
+    ecs::{components::WorldPosition, resources::DEFAULT_TICK_RATE},
+    net::{client_message::ClientMessage, server_message::ServerMessage},
+};
 
 pub type EncodedMessage = Bytes;
 pub type NetIdentifier = u64;
+pub type RoomId = NetIdentifier;
 
+/// Is measured in frames at `DEFAULT_TICK_RATE`, see `interpolation_frame_delay`.
 pub const INTERPOLATION_FRAME_DELAY: u64 = 10;
+/// A message can't be larger than a UDP datagram, anything bigger is rejected while decoding.
+pub const MAX_MESSAGE_SIZE: u64 = 65_535;
+/// A server started by a hosting client with `--managed` prints a line with this prefix
+/// and its address once it's ready to accept connections.
+pub const SERVER_READY_PREFIX: &str = "GV_SERVER_READY ";
+
+pub type DecodeError = bincode::Error;
+
+/// Keeps the interpolation delay the same in seconds for any tick rate.
+pub fn interpolation_frame_delay(tick_rate: u32) -> u64 {
+    (INTERPOLATION_FRAME_DELAY * u64::from(tick_rate) / u64::from(DEFAULT_TICK_RATE)).max(1)
+}
+
+/// Decodes a message sent by a client. Never panics on malformed input.
+pub fn decode_client_message(bytes: &[u8]) -> Result<ClientMessage, DecodeError> {
+    decode_message(bytes)
+}
+
+/// Decodes a message sent by the server. Never panics on malformed input.
+pub fn decode_server_message(bytes: &[u8]) -> Result<ServerMessage, DecodeError> {
+    decode_message(bytes)
+}
+
+fn decode_message<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+    bincode::config().limit(MAX_MESSAGE_SIZE).deserialize(bytes)
+}
+
+/// Serializes messages into a reused buffer, so that encoding them doesn't allocate.
+#[derive(Default)]
+pub struct MessageEncoder {
+    buffer: Vec<u8>,
+}
+
+impl MessageEncoder {
+    /// Encodes the same bytes as serializing a `ServerMessage` or a `ClientMessage`,
+    /// bincode writes struct fields one after another.
+    pub fn encode_message<P: Serialize>(
+        &mut self,
+        session_id: NetIdentifier,
+        payload: &P,
+    ) -> &[u8] {
+        self.buffer.clear();
+        bincode::serialize_into(&mut self.buffer, &session_id)
+            .expect("Expected to serialize a session id");
+        bincode::serialize_into(&mut self.buffer, payload)
+            .expect("Expected to serialize a message payload");
+        &self.buffer
+    }
+
+    /// Replaces the session id of the last encoded message, which lets broadcasting
+    /// serialize a payload once for all the connections.
+    pub fn with_session_id(&mut self, session_id: NetIdentifier) -> &[u8] {
+        let session_id_len = std::mem::size_of::<NetIdentifier>();
+        bincode::serialize_into(&mut self.buffer[..session_id_len], &session_id)
+            .expect("Expected to serialize a session id");
+        &self.buffer
+    }
+}
 
 pub struct ConnectionNetEvent<T> {
     pub connection_id: NetIdentifier,
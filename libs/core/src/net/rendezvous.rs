@@ -0,0 +1,69 @@
+use serde_derive::{Deserialize, Serialize};
+
+use std::net::SocketAddr;
+
+use crate::net::{decode_message, DecodeError};
+
+/// Rendezvous packets share sockets with game messages, this prefix tells them apart.
+/// Game messages start with a session id, which never gets anywhere near this value.
+pub const RENDEZVOUS_PACKET_MAGIC: &[u8; 4] = b"GVRZ";
+
+pub type HostCode = u32;
+
+/// Packets exchanged with a rendezvous server, which introduces peers behind NATs to each other
+/// and relays their messages if punching a hole fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RendezvousPacket {
+    /// Sent by a game server, the code is chosen by the hosting client and is shown to the player.
+    RegisterHost {
+        host_code: HostCode,
+    },
+    /// Keeps the NAT mapping and the host registration alive.
+    KeepAlive,
+    /// Sent by a client wishing to join the host with the code.
+    Connect {
+        host_code: HostCode,
+    },
+    /// Asks the rendezvous server to forward a game message to the peer it has introduced.
+    Relay {
+        to: SocketAddr,
+        payload: Vec<u8>,
+    },
+
+    HostRegistered {
+        host_code: HostCode,
+    },
+    HostCodeTaken {
+        host_code: HostCode,
+    },
+    HostNotFound {
+        host_code: HostCode,
+    },
+    /// Is sent to both peers simultaneously, with the public endpoint of the other one.
+    PunchTo(SocketAddr),
+    Relayed {
+        from: SocketAddr,
+        payload: Vec<u8>,
+    },
+
+    /// Sent between peers, receiving one means that the hole is punched.
+    Punch,
+}
+
+pub fn is_rendezvous_packet(bytes: &[u8]) -> bool {
+    bytes.starts_with(RENDEZVOUS_PACKET_MAGIC)
+}
+
+pub fn encode_rendezvous_packet(packet: &RendezvousPacket) -> Vec<u8> {
+    let mut bytes = RENDEZVOUS_PACKET_MAGIC.to_vec();
+    bincode::serialize_into(&mut bytes, packet).expect("Expected to serialize a rendezvous packet");
+    bytes
+}
+
+/// Returns `None` if the bytes aren't a rendezvous packet. Never panics on malformed input.
+pub fn decode_rendezvous_packet(bytes: &[u8]) -> Option<Result<RendezvousPacket, DecodeError>> {
+    if !is_rendezvous_packet(bytes) {
+        return None;
+    }
+    Some(decode_message(&bytes[RENDEZVOUS_PACKET_MAGIC.len()..]))
+}
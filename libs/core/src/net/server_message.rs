@@ -1,8 +1,30 @@
+use serde::{
+    ser::{SerializeSeq, SerializeStructVariant},
+    Serialize, Serializer,
+};
 use serde_derive::{Deserialize, Serialize};
 
 use crate::{
-    ecs::resources::{net::MultiplayerRoomPlayer, world::ServerWorldUpdate},
-    net::NetIdentifier,
+    augments::PlayerAugments,
+    balance::Balance,
+    content_packs::{ContentPack, ContentPackInfo},
+    economy::PlayerWallet,
+    ecs::{
+        components::PlayerLastCastedSpells,
+        resources::{
+            net::{MultiplayerRoomPlayer, SavedMatch},
+            world::{ServerWorldUpdate, ServerWorldUpdates},
+            Intermission, MatchScore,
+        },
+    },
+    emotes::Emote,
+    escort::PayloadProgress,
+    game_events::GameEvent,
+    level_scripts::ScriptAction,
+    math::Vector2,
+    net::{NetIdentifier, RoomId},
+    room_settings::RoomSettings,
+    votes::Vote,
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -18,9 +40,24 @@ pub enum ServerMessagePayload {
     /// Must have the same length as a last sent UpdateRoomPlayers,
     /// contains server (entity) ids for corresponding players.
     StartGame(Vec<NetIdentifier>),
+    /// Is sent instead of StartGame when the host resumes a saved match.
+    ResumeGame {
+        entity_net_ids: Vec<NetIdentifier>,
+        saved_match: SavedMatch,
+    },
+    /// A campaign level is completed, the next one is started with ResumeGame
+    /// (carrying over the players' progress) after a short delay.
+    LevelTransition {
+        level_number: usize,
+    },
     Handshake {
         net_id: NetIdentifier,
         is_host: bool,
+        /// Clients run their simulation at the server's tick rate.
+        tick_rate: u32,
+        /// Clients have to have the same packs, downloadable ones are requested
+        /// with RequestContentPack.
+        content_packs: Vec<ContentPackInfo>,
     },
     UpdateWorld {
         id: u64,
@@ -36,13 +73,179 @@ pub enum ServerMessagePayload {
         id: NetIdentifier,
         players: Vec<PlayerNetStatus>,
     },
-    /// Contains connection ids of players a server is waiting for.
+    /// Contains the players a server is waiting for.
     PauseWaitingForPlayers {
         id: NetIdentifier,
-        players: Vec<NetIdentifier>,
+        players: Vec<LaggingPlayer>,
     },
     UnpauseWaitingForPlayers(NetIdentifier),
+    UpdateScore(MatchScore),
+    /// Is sent to every player after someone chooses an augment.
+    UpdateAugments {
+        entity_net_id: NetIdentifier,
+        augments: PlayerAugments,
+    },
+    /// Is sent to every player after rewards and purchases.
+    UpdateWallet {
+        entity_net_id: NetIdentifier,
+        wallet: PlayerWallet,
+    },
+    /// Is sent to every player after a wave ends and after every ready-up.
+    UpdateIntermission(Intermission),
+    /// Contains the wave that the intermission has started with.
+    EndIntermission(usize),
+    /// Is sent to every player in the room, including the one playing the emote.
+    Emote {
+        connection_id: NetIdentifier,
+        emote: Emote,
+    },
     Disconnect(DisconnectReason),
+    RoomList(Vec<RoomInfo>),
+    /// The client is expected to send JoinRoom next, all its messages are routed to the room.
+    RoomEntered(RoomId),
+    MatchmakingQueued {
+        players_in_queue: usize,
+    },
+    /// A room is created for the matched players, it's handled the same way as RoomEntered.
+    MatchFound(RoomId),
+    /// Is sent before StartGame and ResumeGame, and again whenever a server reloads its balance.
+    UpdateBalance(Balance),
+    /// A reply to RequestContentPack, contains every file of a downloadable pack.
+    ContentPack(ContentPack),
+    /// A reply to ConsoleCommand, is sent only to the host.
+    ConsoleOutput(String),
+    /// Is sent periodically to each player about its own player, see `ReconciliationStatus`.
+    ReconciliationReport(PlayerReconciliation),
+    /// A reply to Spectate, is sent once the server has finalized enough frames to relay.
+    /// `entity_net_ids` correspond to the players of the last UpdateRoomPlayers.
+    StartSpectating {
+        entity_net_ids: Vec<NetIdentifier>,
+        snapshot: MatchSnapshot,
+    },
+    /// Finalized world updates following the snapshot, spectators get them faster than
+    /// the game runs until they catch up with the relay.
+    RelayWorld {
+        updates: Vec<ServerWorldUpdate>,
+        is_catching_up: bool,
+    },
+    /// The latest client action id received with PlayerInputs, is sent unreliably.
+    AcknowledgeInputs(NetIdentifier),
+    /// Connection ids of the players who haven't sent any input for a while,
+    /// is sent whenever the list changes.
+    UpdateAfkPlayers(Vec<NetIdentifier>),
+    /// Is sent to every player when a vote starts and whenever someone votes.
+    UpdateVote(Vote),
+    EndVote {
+        vote_id: NetIdentifier,
+        has_passed: bool,
+    },
+    /// Is sent after a vote to end the game, the players get back to the lobby.
+    ReturnToLobby,
+    /// Is sent to joining players and whenever the host changes the settings. The host gets
+    /// the current settings back if the server rejects the new ones.
+    UpdateRoomSettings(RoomSettings),
+    /// Is sent instead of StartGame to a player who rejoins a running game. The client starts
+    /// at the snapshot's frame and takes over its player, `entity_net_ids` correspond to
+    /// the players of the last UpdateRoomPlayers.
+    RejoinGame {
+        entity_net_ids: Vec<NetIdentifier>,
+        snapshot: MatchSnapshot,
+    },
+    /// Is sent periodically during a pause, so that the overlay shows how far behind
+    /// the lagging players are.
+    UpdateLaggingPlayers {
+        pause_id: NetIdentifier,
+        players: Vec<LaggingPlayer>,
+    },
+    /// The actions of the level script triggers that have fired on a tick, in their order.
+    LevelScriptActions(Vec<ScriptAction>),
+    /// The pause threshold the server has accepted from SetPauseThreshold, the client
+    /// pauses itself after getting this many frames ahead of the server.
+    PauseThreshold(u64),
+    /// Events of a tick for the event ticker, is sent to every player.
+    GameEvents(Vec<GameEvent>),
+    /// Is broadcast periodically while a level script has a payload to escort.
+    UpdatePayload(PayloadProgress),
+}
+
+/// Position of `UpdateWorld` in `ServerMessagePayload`, has to be kept in sync with it.
+const UPDATE_WORLD_VARIANT_INDEX: u32 = 6;
+
+/// Serializes the same as `ServerMessagePayload::UpdateWorld`, but borrows the updates
+/// instead of cloning them.
+pub struct UpdateWorldRef<'a> {
+    pub id: u64,
+    pub server_world_updates: &'a ServerWorldUpdates,
+    /// Indices of the sent updates in `server_world_updates`.
+    pub update_indices: &'a [usize],
+}
+
+struct ServerWorldUpdatesRef<'a> {
+    server_world_updates: &'a ServerWorldUpdates,
+    update_indices: &'a [usize],
+}
+
+impl<'a> Serialize for UpdateWorldRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct_variant(
+            "ServerMessagePayload",
+            UPDATE_WORLD_VARIANT_INDEX,
+            "UpdateWorld",
+            2,
+        )?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field(
+            "updates",
+            &ServerWorldUpdatesRef {
+                server_world_updates: self.server_world_updates,
+                update_indices: self.update_indices,
+            },
+        )?;
+        state.end()
+    }
+}
+
+impl<'a> Serialize for ServerWorldUpdatesRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.update_indices.len()))?;
+        for i in self.update_indices {
+            seq.serialize_element(&self.server_world_updates.updates[*i].1)?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    pub name: String,
+    pub players_count: usize,
+    pub is_playing: bool,
+}
+
+/// The authoritative state of a player at a saved frame, which the client compares
+/// with its own prediction of the same frame.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerReconciliation {
+    pub frame_number: u64,
+    pub position: Vector2,
+    pub last_casted_spells: PlayerLastCastedSpells,
+}
+
+/// The state of the world at a finalized frame, the relayed updates start with the next one.
+/// Rejoining players get a snapshot of the latest frame instead.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MatchSnapshot {
+    pub frame_number: u64,
+    pub players: Vec<EntitySnapshot>,
+    pub monsters: Vec<EntitySnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    pub entity_net_id: NetIdentifier,
+    pub position: Vector2,
+    pub health: f32,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -51,18 +254,68 @@ pub struct PlayerNetStatus {
     pub frame_number: u64,
     pub average_lagging_behind: u64,
     pub latency_ms: u32,
+    /// World updates the player hasn't acknowledged yet.
+    pub pending_world_updates: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaggingPlayer {
+    pub connection_id: NetIdentifier,
+    /// How many frames the player is behind the ones the server expects it to have reached.
+    pub frames_behind: u64,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum DisconnectReason {
     /// For rejecting any connections while the server
     /// isn't connected to a host (in case of self-hosting).
     Uninitialized,
     GameIsStarted,
+    /// Spectators can join only while a game is being played.
+    GameIsNotStarted,
     RoomIsFull,
     Kick,
+    /// Public rooms kick the players who haven't sent any input for too long.
+    AfkKick,
+    /// Players can vote out anyone but the host.
+    VoteKick,
     Closed,
+    /// A peer sent a message that couldn't be decoded.
+    MalformedMessage,
+    RoomNotFound,
+    RoomLimitReached,
+    MatchmakingTimedOut,
+    /// Is never sent by the server, a rendezvous server didn't know the host code.
+    RendezvousHostNotFound,
     ServerCrashed(i32),
+    /// Is never sent by the server, a client disconnects itself on receiving unexpected data.
+    ClientNetworkError(ClientNetworkError),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientNetworkError {
+    /// StartGame was received before the Handshake.
+    StartGameBeforeHandshake,
+    /// StartGame doesn't contain an entity net id for every player in the room.
+    PlayersCountMismatch {
+        players: usize,
+        entity_net_ids: usize,
+    },
+    /// The room from StartGame doesn't have a player with our connection id.
+    PlayerNotFound { connection_id: NetIdentifier },
+    /// The server process was terminated without an exit status code.
+    ServerTerminated,
+    /// The server uses a content pack that isn't installed and can't be downloaded.
+    MissingContentPack(String),
+    /// An installed content pack differs from the server's one with the same name.
+    ContentPackMismatch(String),
+    /// A downloaded content pack isn't the one the server has advertised.
+    CorruptedContentPack(String),
+    /// UpdateWorld has an update older than the oldest frame the client keeps.
+    OutdatedWorldUpdate {
+        frame_number: u64,
+        first_available_frame_number: u64,
+    },
 }
 
 impl ServerMessagePayload {
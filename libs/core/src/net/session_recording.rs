@@ -0,0 +1,249 @@
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    math::Vector2,
+    net::{
+        client_message::ClientMessagePayload,
+        server_message::{ServerMessage, ServerMessagePayload},
+        NetIdentifier,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecordedMessage {
+    Incoming(ServerMessage),
+    Outcoming(ClientMessagePayload),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecordEntry {
+    /// Client game frame number at the moment of processing the message.
+    pub game_frame_number: u64,
+    pub message: RecordedMessage,
+}
+
+/// What a client saves when a player reports a desync, see `SessionRecorder::with_desync_reports`.
+/// Replaying `entries` gives the state the client had at `reported_frame_number`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesyncReport {
+    /// Client game frame number at the moment of the report.
+    pub reported_frame_number: u64,
+    /// Positions of the networked entities as the client simulated them at that frame.
+    pub client_positions: Vec<(NetIdentifier, Vector2)>,
+    /// The messages that set the game up, followed by the rest of the game's ones.
+    pub entries: Vec<SessionRecordEntry>,
+}
+
+/// Writes every message a client processes to a file, so a session can be replayed in tests.
+/// With highlights on, it also keeps the last messages in memory to save them as a highlight.
+#[derive(Default)]
+pub struct SessionRecorder {
+    writer: Option<BufWriter<File>>,
+    highlights: Option<HighlightBuffer>,
+    whole_game: Option<HighlightBuffer>,
+}
+
+impl SessionRecorder {
+    pub fn disabled() -> Self {
+        Self {
+            writer: None,
+            highlights: None,
+            whole_game: None,
+        }
+    }
+
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            writer: Some(BufWriter::new(file)),
+            highlights: None,
+            whole_game: None,
+        })
+    }
+
+    /// Keeps the messages of the last `window_frames` for `save_highlight`.
+    pub fn with_highlights(mut self, window_frames: u64) -> Self {
+        self.highlights = Some(HighlightBuffer {
+            window_frames,
+            setup: Vec::new(),
+            recent: VecDeque::new(),
+        });
+        self
+    }
+
+    /// Keeps the messages of the whole current game for `save_desync_report`.
+    pub fn with_desync_reports(mut self) -> Self {
+        self.whole_game = Some(HighlightBuffer {
+            window_frames: u64::MAX,
+            setup: Vec::new(),
+            recent: VecDeque::new(),
+        });
+        self
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some() || self.highlights.is_some() || self.whole_game.is_some()
+    }
+
+    pub fn record(&mut self, game_frame_number: u64, message: RecordedMessage) {
+        let entry = SessionRecordEntry {
+            game_frame_number,
+            message,
+        };
+        if let Some(highlights) = self.highlights.as_mut() {
+            highlights.push(entry.clone());
+        }
+        if let Some(whole_game) = self.whole_game.as_mut() {
+            whole_game.push(entry.clone());
+        }
+
+        let writer = if let Some(writer) = self.writer.as_mut() {
+            writer
+        } else {
+            return;
+        };
+        if let Err(err) = bincode::serialize_into(writer, &entry) {
+            log::error!(
+                "Failed to record a message, stopping the recording: {:?}",
+                err
+            );
+            self.writer = None;
+        }
+    }
+
+    /// Writes the messages that set the session up, followed by the ones of the last
+    /// `window_frames`, in the same format as a whole session recording.
+    pub fn save_highlight(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let highlights = self.highlights.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "Highlights aren't being recorded")
+        })?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        for entry in highlights.setup.iter().chain(highlights.recent.iter()) {
+            bincode::serialize_into(&mut writer, entry)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        }
+        writer.flush()
+    }
+
+    /// Writes a `DesyncReport` with the messages of the whole current game.
+    pub fn save_desync_report(
+        &self,
+        path: impl AsRef<Path>,
+        reported_frame_number: u64,
+        client_positions: Vec<(NetIdentifier, Vector2)>,
+    ) -> io::Result<()> {
+        let whole_game = self.whole_game.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "Desync reports aren't being recorded")
+        })?;
+        let report = DesyncReport {
+            reported_frame_number,
+            client_positions,
+            entries: whole_game
+                .setup
+                .iter()
+                .chain(whole_game.recent.iter())
+                .cloned()
+                .collect(),
+        };
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, &report)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        writer.flush()
+    }
+}
+
+/// Is also used for desync reports, with a window that never ends.
+struct HighlightBuffer {
+    window_frames: u64,
+    /// Messages a replay can't do without, are kept for the whole session.
+    setup: Vec<SessionRecordEntry>,
+    recent: VecDeque<SessionRecordEntry>,
+}
+
+impl HighlightBuffer {
+    fn push(&mut self, entry: SessionRecordEntry) {
+        let payload = match &entry.message {
+            RecordedMessage::Incoming(ServerMessage { payload, .. }) => Some(payload),
+            RecordedMessage::Outcoming(_) => None,
+        };
+        match payload {
+            Some(ServerMessagePayload::Handshake { .. }) => {
+                self.setup.clear();
+                self.recent.clear();
+                self.setup.push(entry);
+            }
+            // A new game starts, the previous one is of no use to its highlights.
+            Some(ServerMessagePayload::StartGame(_))
+            | Some(ServerMessagePayload::ResumeGame { .. })
+            | Some(ServerMessagePayload::RejoinGame { .. }) => {
+                self.setup.retain(|entry| !is_game_start(&entry.message));
+                self.recent.clear();
+                self.setup.push(entry);
+            }
+            Some(ServerMessagePayload::UpdateRoomPlayers(_))
+            | Some(ServerMessagePayload::UpdateBalance(_)) => self.setup.push(entry),
+            _ => {
+                let game_frame_number = entry.game_frame_number;
+                self.recent.push_back(entry);
+                while self.recent.front().map_or(false, |entry| {
+                    entry.game_frame_number.saturating_add(self.window_frames) < game_frame_number
+                }) {
+                    self.recent.pop_front();
+                }
+            }
+        }
+    }
+}
+
+fn is_game_start(message: &RecordedMessage) -> bool {
+    match message {
+        RecordedMessage::Incoming(ServerMessage { payload, .. }) => matches!(
+            payload,
+            ServerMessagePayload::StartGame(_)
+                | ServerMessagePayload::ResumeGame { .. }
+                | ServerMessagePayload::RejoinGame { .. }
+        ),
+        RecordedMessage::Outcoming(_) => false,
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(err) = writer.flush() {
+                log::error!("Failed to flush a session recording: {:?}", err);
+            }
+        }
+    }
+}
+
+pub fn read_session_record(path: impl AsRef<Path>) -> io::Result<Vec<SessionRecordEntry>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    loop {
+        match bincode::deserialize_from(&mut reader) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                if let bincode::ErrorKind::Io(ref io_err) = *err {
+                    if io_err.kind() == io::ErrorKind::UnexpectedEof {
+                        break;
+                    }
+                }
+                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+pub fn read_desync_report(path: impl AsRef<Path>) -> io::Result<DesyncReport> {
+    let reader = BufReader::new(File::open(path)?);
+    bincode::deserialize_from(reader).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
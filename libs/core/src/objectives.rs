@@ -0,0 +1,140 @@
+//! Scripted objectives that are completed one after another. The tutorial is built from them,
+//! and a campaign level can be given a list of objectives the same way.
+
+use crate::{actions::player::CastSpell, math::Vector2};
+
+/// What a player has to do to complete an objective.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// A player gets within `radius` of `center`.
+    EnterArea {
+        center: Vector2,
+        radius: f32,
+    },
+    /// Monsters killed after the objective has started, minions don't count.
+    KillMonsters(u32),
+    CastSpell(CastSpell),
+    /// Players don't take any damage for this long, getting hit starts the countdown over.
+    AvoidDamage {
+        seconds: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Objective {
+    pub description: &'static str,
+    /// Is shown under the description, tells which keys to press.
+    pub hint: &'static str,
+    pub trigger: Trigger,
+    /// Monsters spawned around the players once the objective starts.
+    pub monsters_to_spawn: u32,
+}
+
+/// Is fed to `ObjectiveTracker` by the simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectiveEvent {
+    PlayerMoved(Vector2),
+    MonsterKilled,
+    SpellCast(CastSpell),
+    PlayerDamaged,
+    /// Is sent every tick.
+    TimePassed(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectiveTracker {
+    objectives: Vec<Objective>,
+    current: usize,
+    progress: f32,
+    is_current_started: bool,
+}
+
+impl ObjectiveTracker {
+    pub fn new(objectives: Vec<Objective>) -> Self {
+        Self {
+            objectives,
+            current: 0,
+            progress: 0.0,
+            is_current_started: false,
+        }
+    }
+
+    /// Starts from the first objective again.
+    pub fn restart(&mut self) {
+        self.current = 0;
+        self.progress = 0.0;
+        self.is_current_started = false;
+    }
+
+    pub fn objectives(&self) -> &[Objective] {
+        &self.objectives
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<&Objective> {
+        self.objectives.get(self.current)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.current >= self.objectives.len()
+    }
+
+    /// Returns the current objective once, right after it becomes current,
+    /// so that its monsters get spawned a single time.
+    pub fn take_started(&mut self) -> Option<&Objective> {
+        if self.is_current_started {
+            return None;
+        }
+        self.is_current_started = true;
+        self.objectives.get(self.current)
+    }
+
+    /// Returns `true` if the event completes the current objective.
+    pub fn handle(&mut self, event: ObjectiveEvent) -> bool {
+        let trigger = match self.current() {
+            Some(objective) => objective.trigger,
+            None => return false,
+        };
+        let is_completed = match (trigger, event) {
+            (Trigger::EnterArea { center, radius }, ObjectiveEvent::PlayerMoved(position)) => {
+                (position - center).norm() <= radius
+            }
+            (Trigger::KillMonsters(count), ObjectiveEvent::MonsterKilled) => {
+                self.progress += 1.0;
+                self.progress >= count as f32
+            }
+            (Trigger::CastSpell(spell), ObjectiveEvent::SpellCast(cast_spell)) => {
+                spell == cast_spell
+            }
+            (Trigger::AvoidDamage { .. }, ObjectiveEvent::PlayerDamaged) => {
+                self.progress = 0.0;
+                false
+            }
+            (Trigger::AvoidDamage { seconds }, ObjectiveEvent::TimePassed(delta_seconds)) => {
+                self.progress += delta_seconds;
+                self.progress >= seconds
+            }
+            _ => false,
+        };
+        if is_completed {
+            self.current += 1;
+            self.progress = 0.0;
+            self.is_current_started = false;
+        }
+        is_completed
+    }
+
+    /// Kills or seconds counted towards the current objective, e.g. "2/3".
+    pub fn progress_text(&self) -> Option<String> {
+        match self.current()?.trigger {
+            Trigger::KillMonsters(count) => Some(format!("{}/{}", self.progress as u32, count)),
+            Trigger::AvoidDamage { seconds } => {
+                Some(format!("{:.0}/{:.0}s", self.progress.floor(), seconds))
+            }
+            Trigger::EnterArea { .. } | Trigger::CastSpell(_) => None,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    fmt::Write as FmtWrite,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// How many frames the overlay chart and the exported traces cover.
+pub const FRAME_TIMINGS_HISTORY: usize = 240;
+
+static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    // Chrome tracing expects numeric thread ids, `ThreadId` can't be converted to one on stable.
+    static THREAD_INDEX: Cell<usize> = Cell::new(0);
+}
+
+fn current_thread_index() -> usize {
+    THREAD_INDEX.with(|thread_index| {
+        if thread_index.get() == 0 {
+            thread_index.set(NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed));
+        }
+        thread_index.get()
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct SystemTiming {
+    pub name: &'static str,
+    pub thread_index: usize,
+    pub started_at: Instant,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct FrameTiming {
+    pub frame_number: u64,
+    /// Wall time since the end of the previous frame, includes rendering and frame limiting.
+    pub duration: Duration,
+    /// Time between the start of the first timed system and the end of the last one.
+    pub busy_duration: Duration,
+    pub systems: Vec<SystemTiming>,
+}
+
+impl FrameTiming {
+    pub fn started_at(&self) -> Option<Instant> {
+        self.systems.iter().map(|system| system.started_at).min()
+    }
+}
+
+/// Spike traces get written to `path` when a frame keeps the systems busy for longer than
+/// `threshold`.
+pub struct SpikeTraceOutput {
+    pub path: PathBuf,
+    pub threshold: Duration,
+}
+
+struct FrameTimingsState {
+    current_frame: Vec<SystemTiming>,
+    frames: VecDeque<FrameTiming>,
+    last_frame_ended_at: Instant,
+    frames_since_spike_trace: usize,
+}
+
+/// Per-system execution times of the last `FRAME_TIMINGS_HISTORY` frames.
+///
+/// Recording goes through a mutex, so that timed systems can keep reading the resource
+/// with `ReadExpect` and still be dispatched in parallel.
+pub struct FrameTimings {
+    epoch: Instant,
+    state: Mutex<FrameTimingsState>,
+    spike_trace_output: Option<SpikeTraceOutput>,
+}
+
+impl Default for FrameTimings {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            epoch: now,
+            state: Mutex::new(FrameTimingsState {
+                current_frame: Vec::new(),
+                frames: VecDeque::with_capacity(FRAME_TIMINGS_HISTORY),
+                last_frame_ended_at: now,
+                frames_since_spike_trace: FRAME_TIMINGS_HISTORY,
+            }),
+            spike_trace_output: None,
+        }
+    }
+}
+
+impl FrameTimings {
+    pub fn with_spike_trace_output(spike_trace_output: SpikeTraceOutput) -> Self {
+        Self {
+            spike_trace_output: Some(spike_trace_output),
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&self, name: &'static str, started_at: Instant, duration: Duration) {
+        let thread_index = current_thread_index();
+        let mut state = self.state.lock().expect("Expected to lock FrameTimings");
+        state.current_frame.push(SystemTiming {
+            name,
+            thread_index,
+            started_at,
+            duration,
+        });
+    }
+
+    /// Moves the systems recorded during the frame into the history.
+    pub fn end_frame(&self, frame_number: u64) {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("Expected to lock FrameTimings");
+
+        let systems = std::mem::replace(&mut state.current_frame, Vec::new());
+        let busy_duration = systems
+            .iter()
+            .map(|system| system.started_at)
+            .min()
+            .map_or(Duration::default(), |started_at| now - started_at);
+        let frame_timing = FrameTiming {
+            frame_number,
+            duration: now - state.last_frame_ended_at,
+            busy_duration,
+            systems,
+        };
+        state.last_frame_ended_at = now;
+
+        if state.frames.len() == FRAME_TIMINGS_HISTORY {
+            state.frames.pop_front();
+        }
+        state.frames.push_back(frame_timing);
+        state.frames_since_spike_trace += 1;
+
+        if let Some(spike_trace_output) = self.spike_trace_output.as_ref() {
+            // Waiting for a full history between traces lets each one show what led to the spike.
+            let is_spike = busy_duration > spike_trace_output.threshold;
+            if is_spike && state.frames_since_spike_trace >= FRAME_TIMINGS_HISTORY {
+                state.frames_since_spike_trace = 0;
+                log::warn!(
+                    "Frame {} took {:.2}ms, writing a trace to {}",
+                    frame_number,
+                    busy_duration.as_secs_f64() * 1000.0,
+                    spike_trace_output.path.display()
+                );
+                let trace = chrome_trace(self.epoch, state.frames.iter());
+                if let Err(err) = fs::write(&spike_trace_output.path, trace) {
+                    log::error!("Failed to write a frame trace: {:?}", err);
+                }
+            }
+        }
+    }
+
+    pub fn with_history<R>(&self, f: impl FnOnce(&VecDeque<FrameTiming>) -> R) -> R {
+        let state = self.state.lock().expect("Expected to lock FrameTimings");
+        f(&state.frames)
+    }
+
+    /// Writes the history in the Chrome tracing format (chrome://tracing, Perfetto).
+    pub fn write_chrome_trace(&self, path: &Path) -> io::Result<()> {
+        let trace = self.with_history(|frames| chrome_trace(self.epoch, frames.iter()));
+        fs::write(path, trace)
+    }
+}
+
+fn chrome_trace<'a>(epoch: Instant, frames: impl Iterator<Item = &'a FrameTiming>) -> String {
+    let micros_since_epoch = |instant: Instant| (instant - epoch).as_micros();
+
+    let mut events = Vec::new();
+    for frame in frames {
+        if let Some(started_at) = frame.started_at() {
+            events.push(format!(
+                r#"{{"name":"frame {}","ph":"X","ts":{},"dur":{},"pid":1,"tid":0}}"#,
+                frame.frame_number,
+                micros_since_epoch(started_at),
+                frame.busy_duration.as_micros(),
+            ));
+        }
+        for system in &frame.systems {
+            events.push(format!(
+                r#"{{"name":"{}","ph":"X","ts":{},"dur":{},"pid":1,"tid":{}}}"#,
+                system.name,
+                micros_since_epoch(system.started_at),
+                system.duration.as_micros(),
+                system.thread_index,
+            ));
+        }
+    }
+
+    let mut trace = String::from("{\"traceEvents\":[\n");
+    for (i, event) in events.iter().enumerate() {
+        let separator = if i + 1 == events.len() { "" } else { "," };
+        writeln!(trace, "{}{}", event, separator).expect("Expected to write to a String");
+    }
+    trace.push_str("]}\n");
+    trace
+}
@@ -0,0 +1,167 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::mutators::Mutator;
+
+/// A room never takes more players than this, whatever its settings are.
+pub const MAX_PLAYERS: usize = 4;
+/// Names of the maps that a room can be played on, personal bests are stored per map.
+pub const MAPS: [&str; 1] = ["desert"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Is used to cycle through the difficulties in the menu.
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// Multiplies the base health of the spawned monsters, minions aren't affected.
+    pub fn monster_health_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplies the base attack damage of the spawned monsters, minions aren't affected.
+    pub fn monster_damage_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Waves of monsters keep coming until every player is dead.
+    Survival,
+    /// Goes through `CAMPAIGN_LEVELS` one after another.
+    Campaign,
+}
+
+impl GameMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "Survival",
+            GameMode::Campaign => "Campaign",
+        }
+    }
+
+    /// Is used to cycle through the modes in the menu.
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Survival => GameMode::Campaign,
+            GameMode::Campaign => GameMode::Survival,
+        }
+    }
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Survival
+    }
+}
+
+/// The host edits the settings in the lobby, the server validates them and shares them
+/// with every player in the room. Single player games use the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomSettings {
+    /// New players can't join once the room has this many players, is up to `MAX_PLAYERS`.
+    pub max_players: usize,
+    pub difficulty: Difficulty,
+    /// The mode that the Start button starts, the Campaign button always starts a campaign.
+    pub mode: GameMode,
+    /// Lets players' missiles damage other players.
+    pub friendly_fire: bool,
+    /// Lets players use aim assist with gamepads, competitive rooms may turn it off.
+    pub aim_assist: bool,
+    /// One of `MAPS`.
+    pub map: String,
+    /// Enabled mutators in the order of `Mutator::ALL`, see `mutators::toggle_mutator`.
+    pub mutators: Vec<Mutator>,
+}
+
+impl RoomSettings {
+    /// Checks the settings against the players who are already in the room.
+    pub fn validate(&self, players_count: usize) -> Result<(), String> {
+        if self.max_players == 0 || self.max_players > MAX_PLAYERS {
+            return Err(format!(
+                "max players should be from 1 to {}, got {}",
+                MAX_PLAYERS, self.max_players
+            ));
+        }
+        if self.max_players < players_count {
+            return Err(format!(
+                "max players ({}) is less than the number of players in the room ({})",
+                self.max_players, players_count
+            ));
+        }
+        if !MAPS.contains(&self.map.as_str()) {
+            return Err(format!("unknown map: {}", self.map));
+        }
+        for (index, mutator) in self.mutators.iter().enumerate() {
+            if self.mutators[..index].contains(mutator) {
+                return Err(format!("duplicate mutator: {}", mutator.name()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the map to store in `GameLevelState`, unknown maps fall back
+    /// to the first one.
+    pub fn map_name(&self) -> &'static str {
+        MAPS.iter()
+            .find(|map| **map == self.map)
+            .copied()
+            .unwrap_or(MAPS[0])
+    }
+
+    /// Is used to cycle through the maps in the menu.
+    pub fn next_map(&self) -> &'static str {
+        let map_index = MAPS
+            .iter()
+            .position(|map| *map == self.map)
+            .map_or(0, |map_index| (map_index + 1) % MAPS.len());
+        MAPS[map_index]
+    }
+}
+
+impl Default for RoomSettings {
+    fn default() -> Self {
+        Self {
+            max_players: MAX_PLAYERS,
+            difficulty: Difficulty::default(),
+            mode: GameMode::default(),
+            friendly_fire: false,
+            aim_assist: true,
+            map: MAPS[0].to_owned(),
+            mutators: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Derived stats of players and monsters. A stat starts from a base value in the balance,
+//! then modifiers from bought items, elite affixes, status effects (slowing zones and auras)
+//! and room mutators are applied on top of it: additive ones first, multiplicative ones after
+//! them.
+
+use crate::{economy::ShopItem, ecs::components::affixes::MonsterAffix, mutators::Mutator};
+
+/// Spell cooldowns can't be reduced by more than this share.
+pub const MAX_COOLDOWN_REDUCTION: f32 = 0.6;
+/// Keeps cooldowns finite whatever slows the casting down.
+const MIN_HASTE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MoveSpeed,
+    /// Damage of cast missiles, the base value comes from the cast spell.
+    MissileDamage,
+    /// Multiplies the damage taken (but not the healing).
+    DamageTaken,
+    /// A share taken off spell cooldowns, capped at `MAX_COOLDOWN_REDUCTION`.
+    CooldownReduction,
+    /// Spell cooldowns recover this many times faster.
+    Haste,
+    /// A chance of a missile hit to be critical, the base value comes from the cast spell.
+    CritChance,
+    /// Critical hits deal this many times more damage.
+    CritMultiplier,
+    /// A share of the dealt missile damage that heals the caster.
+    LifeSteal,
+}
+
+impl Stat {
+    /// The character panel lists stats in this order.
+    pub const ALL: [Stat; 8] = [
+        Stat::MoveSpeed,
+        Stat::MissileDamage,
+        Stat::DamageTaken,
+        Stat::CooldownReduction,
+        Stat::Haste,
+        Stat::CritChance,
+        Stat::CritMultiplier,
+        Stat::LifeSteal,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stat::MoveSpeed => "Move speed",
+            Stat::MissileDamage => "Missile damage",
+            Stat::DamageTaken => "Damage taken",
+            Stat::CooldownReduction => "Cooldown reduction",
+            Stat::Haste => "Haste",
+            Stat::CritChance => "Critical chance",
+            Stat::CritMultiplier => "Critical damage",
+            Stat::LifeSteal => "Life steal",
+        }
+    }
+}
+
+/// Modifiers are applied in the order of their sources, so that a server and predicting
+/// clients get the same floating point results whatever order they collect them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModifierSource {
+    Item(ShopItem),
+    Affix(MonsterAffix),
+    /// Status effects of the zones an entity stands in, they are collected every frame.
+    Zone,
+    /// Status effects of the auras an entity is within, they are collected every frame.
+    Aura,
+    /// Mutators picked in the room settings.
+    Mutator(Mutator),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    Add(f32),
+    Multiply(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub source: ModifierSource,
+    pub modifier: Modifier,
+}
+
+/// Modifiers affecting an entity during a frame.
+#[derive(Debug, Clone, Default)]
+pub struct StatModifiers(Vec<StatModifier>);
+
+impl StatModifiers {
+    pub fn add(&mut self, stat: Stat, source: ModifierSource, modifier: Modifier) {
+        self.0.push(StatModifier {
+            stat,
+            source,
+            modifier,
+        });
+    }
+
+    pub fn value(&self, stat: Stat, base: f32) -> f32 {
+        let mut modifiers = self
+            .0
+            .iter()
+            .filter(|modifier| modifier.stat == stat)
+            .collect::<Vec<_>>();
+        // The sort is stable, modifiers of the same source keep the order they were added in.
+        modifiers.sort_by_key(|modifier| modifier.source);
+
+        let added = modifiers
+            .iter()
+            .fold(base, |value, modifier| match modifier.modifier {
+                Modifier::Add(addend) => value + addend,
+                Modifier::Multiply(_) => value,
+            });
+        let value = modifiers
+            .iter()
+            .fold(added, |value, modifier| match modifier.modifier {
+                Modifier::Add(_) => value,
+                Modifier::Multiply(factor) => value * factor,
+            });
+        match stat {
+            Stat::CooldownReduction => value.max(0.0).min(MAX_COOLDOWN_REDUCTION),
+            Stat::Haste => value.max(MIN_HASTE),
+            Stat::CritChance => value.max(0.0).min(1.0),
+            Stat::CritMultiplier => value.max(1.0),
+            _ => value.max(0.0),
+        }
+    }
+
+    /// Shortens `base_cooldown_secs` with the cooldown reduction and haste.
+    pub fn cooldown_secs(
+        &self,
+        base_cooldown_secs: f32,
+        base_cooldown_reduction: f32,
+        base_haste: f32,
+    ) -> f32 {
+        base_cooldown_secs * (1.0 - self.value(Stat::CooldownReduction, base_cooldown_reduction))
+            / self.value(Stat::Haste, base_haste)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    actions::player::CastSpell,
+    math::Vector2,
+    objectives::{Objective, Trigger},
+};
+
+/// The spot the players have to walk to first, players spawn in the middle of the map.
+pub const TUTORIAL_AREA_CENTER: (f32, f32) = (0.0, 250.0);
+pub const TUTORIAL_AREA_RADIUS: f32 = 60.0;
+
+/// The tutorial is played in single player, monster waves don't spawn while it's running.
+/// There are no revives in the game yet, healing is taught instead.
+pub fn tutorial_objectives() -> Vec<Objective> {
+    vec![
+        Objective {
+            description: "Walk to the marked spot",
+            hint: "Move with W, A, S and D",
+            trigger: Trigger::EnterArea {
+                center: Vector2::new(TUTORIAL_AREA_CENTER.0, TUTORIAL_AREA_CENTER.1),
+                radius: TUTORIAL_AREA_RADIUS,
+            },
+            monsters_to_spawn: 0,
+        },
+        Objective {
+            description: "Cast a missile",
+            hint: "Aim with the mouse and hold the left mouse button",
+            trigger: Trigger::CastSpell(CastSpell::Missile),
+            monsters_to_spawn: 0,
+        },
+        Objective {
+            description: "Defeat the monsters",
+            hint: "Missiles fly where the cursor points",
+            trigger: Trigger::KillMonsters(3),
+            monsters_to_spawn: 3,
+        },
+        Objective {
+            description: "Don't let the monsters touch you",
+            hint: "Keep walking away from the monsters while shooting back at them",
+            trigger: Trigger::AvoidDamage { seconds: 15.0 },
+            monsters_to_spawn: 5,
+        },
+        Objective {
+            description: "Heal yourself",
+            hint: "Press the right mouse button, it heals allies around the cursor too",
+            trigger: Trigger::CastSpell(CastSpell::Heal),
+            monsters_to_spawn: 0,
+        },
+    ]
+}
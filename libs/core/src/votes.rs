@@ -0,0 +1,85 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::net::NetIdentifier;
+
+/// Votes that don't reach the majority in this time fail.
+pub const VOTE_DURATION_SECS: u64 = 30;
+/// A player can start a new vote only after this time since their previous one.
+pub const VOTE_COOLDOWN_SECS: u64 = 60;
+
+/// Decisions that any player can put to a vote, the host makes them without voting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteKind {
+    /// Contains the connection id of the player to kick, the host can't be voted out.
+    Kick(NetIdentifier),
+    /// Ends the game, the room gets back to the lobby.
+    ReturnToLobby,
+    /// Starts the next wave without waiting for the intermission to end.
+    SkipIntermission,
+}
+
+impl VoteKind {
+    /// The kicked player doesn't get a say.
+    pub fn is_voter(self, connection_id: NetIdentifier) -> bool {
+        match self {
+            VoteKind::Kick(kicked_connection_id) => kicked_connection_id != connection_id,
+            VoteKind::ReturnToLobby | VoteKind::SkipIntermission => true,
+        }
+    }
+}
+
+/// A vote in progress, the server broadcasts it whenever the tally changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vote {
+    pub id: NetIdentifier,
+    pub kind: VoteKind,
+    /// Connection id of the player who has started the vote, it counts as a yes.
+    pub initiator: NetIdentifier,
+    pub yes: Vec<NetIdentifier>,
+    pub no: Vec<NetIdentifier>,
+    /// The number of players who can vote.
+    pub voters: usize,
+}
+
+impl Vote {
+    pub fn new(id: NetIdentifier, kind: VoteKind, initiator: NetIdentifier, voters: usize) -> Self {
+        Self {
+            id,
+            kind,
+            initiator,
+            yes: vec![initiator],
+            no: Vec::new(),
+            voters,
+        }
+    }
+
+    /// Players can change their mind until the vote ends.
+    pub fn cast(&mut self, connection_id: NetIdentifier, is_yes: bool) {
+        self.yes.retain(|voter| *voter != connection_id);
+        self.no.retain(|voter| *voter != connection_id);
+        if is_yes {
+            self.yes.push(connection_id);
+        } else {
+            self.no.push(connection_id);
+        }
+    }
+
+    /// Drops the ballots of the players who are no longer in the room.
+    pub fn retain_voters(&mut self, is_voter: impl Fn(NetIdentifier) -> bool, voters: usize) {
+        self.yes.retain(|voter| is_voter(*voter));
+        self.no.retain(|voter| is_voter(*voter));
+        self.voters = voters;
+    }
+
+    /// Returns whether the vote has passed once the majority of the voters agree,
+    /// or once it can't be reached anymore.
+    pub fn outcome(&self) -> Option<bool> {
+        if self.yes.len() * 2 > self.voters {
+            Some(true)
+        } else if self.no.len() * 2 >= self.voters {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! The developer console: crates register commands in `ConsoleCommands`, a client's console
+//! window pushes typed lines into `Console`, and ConsoleSystem runs them with access to
+//! the whole world.
+//!
+//! Cheat commands change the match, so they work only offline or, in dev builds, for the host
+//! of a multiplayer game, whose client sends them to the server.
+
+use amethyst::ecs::{Join, ReadStorage, World};
+
+use gv_core::{
+    classes::PlayerClass,
+    ecs::{
+        components::{Dead, Player},
+        resources::{GameEngineState, GameTime, MAX_TIME_SCALE, MIN_TIME_SCALE},
+    },
+};
+
+#[cfg(feature = "client")]
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+#[cfg(feature = "client")]
+use gv_core::ecs::resources::net::MultiplayerGameState;
+
+use crate::ecs::resources::ConsoleSpawnRequests;
+
+pub const MAX_CONSOLE_SPAWN_COUNT: usize = 20;
+
+/// Returns the text to print, arguments are the words following the command name.
+pub type CommandHandler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommandPermission {
+    Anyone,
+    /// Commands changing the local simulation, which can't be done in multiplayer.
+    Offline,
+    /// Commands changing the match, multiplayer hosts of dev builds run them on the server.
+    Cheat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommandRoute {
+    Local,
+    Server,
+}
+
+impl CommandPermission {
+    /// Decides where a client runs a command.
+    pub fn route(self, is_multiplayer: bool, is_host: bool) -> Result<CommandRoute, String> {
+        match self {
+            Self::Anyone => Ok(CommandRoute::Local),
+            _ if !is_multiplayer => Ok(CommandRoute::Local),
+            Self::Offline => Err("The command is available only offline".to_owned()),
+            Self::Cheat if is_host && cfg!(debug_assertions) => Ok(CommandRoute::Server),
+            Self::Cheat => Err(
+                "Cheat commands are available only offline or for the host in dev builds"
+                    .to_owned(),
+            ),
+        }
+    }
+
+    /// A server accepts commands only from the host, see `route`.
+    pub fn is_allowed_on_server(self) -> bool {
+        match self {
+            Self::Anyone => true,
+            Self::Offline => false,
+            Self::Cheat => cfg!(debug_assertions),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ConsoleCommand {
+    /// May consist of several words, such as `net stats`.
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub permission: CommandPermission,
+    pub handler: CommandHandler,
+}
+
+#[derive(Default)]
+pub struct ConsoleCommands {
+    commands: Vec<ConsoleCommand>,
+}
+
+impl ConsoleCommands {
+    pub fn with_game_commands() -> Self {
+        let mut console_commands = Self::default();
+        console_commands.register(ConsoleCommand {
+            name: "help",
+            usage: "help",
+            description: "Lists the available commands",
+            permission: CommandPermission::Anyone,
+            handler: help,
+        });
+        console_commands.register(ConsoleCommand {
+            name: "spawn monster",
+            usage: "spawn monster <class> [count]",
+            description: "Spawns monsters around the player of the class",
+            permission: CommandPermission::Cheat,
+            handler: spawn_monster,
+        });
+        console_commands.register(ConsoleCommand {
+            name: "set timescale",
+            usage: "set timescale <0.25..8>",
+            description: "Slows the simulation down or speeds it up",
+            permission: CommandPermission::Offline,
+            handler: set_time_scale,
+        });
+        console_commands
+    }
+
+    /// Replaces a command with the same name.
+    pub fn register(&mut self, command: ConsoleCommand) {
+        self.commands
+            .retain(|registered| registered.name != command.name);
+        self.commands.push(command);
+        self.commands.sort_by_key(|command| command.name);
+    }
+
+    pub fn commands(&self) -> &[ConsoleCommand] {
+        &self.commands
+    }
+
+    /// Returns the command with the longest name matching the first words of the line
+    /// and the rest of the words as its arguments.
+    pub fn find<'a>(&self, line: &'a str) -> Option<(ConsoleCommand, Vec<&'a str>)> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        self.commands
+            .iter()
+            .filter(|command| {
+                let name_words = command.name.split(' ').collect::<Vec<_>>();
+                words.len() >= name_words.len()
+                    && words
+                        .iter()
+                        .zip(&name_words)
+                        .all(|(word, name_word)| word.eq_ignore_ascii_case(name_word))
+            })
+            .max_by_key(|command| command.name.split(' ').count())
+            .map(|command| {
+                let arguments = words[command.name.split(' ').count()..].to_vec();
+                (*command, arguments)
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct Console {
+    /// Lines waiting to be run by ConsoleSystem.
+    pub pending: Vec<String>,
+    /// Is shown by a client, a server sends it to the host.
+    pub output: Vec<String>,
+    /// Lines that a multiplayer host sends to a server.
+    #[cfg(feature = "client")]
+    pub outgoing: Vec<String>,
+}
+
+pub fn run_console_command(world: &mut World, line: &str) {
+    let (command, arguments) = match world.fetch::<ConsoleCommands>().find(line) {
+        Some(found) => found,
+        None => {
+            let output = format!("Unknown command {:?}, see `help`", line.trim());
+            world.fetch_mut::<Console>().output.push(output);
+            return;
+        }
+    };
+
+    let output = match route(world, command.permission) {
+        Ok(CommandRoute::Local) => {
+            (command.handler)(world, &arguments).unwrap_or_else(|err| format!("Error: {}", err))
+        }
+        Ok(CommandRoute::Server) => {
+            #[cfg(feature = "client")]
+            world.fetch_mut::<Console>().outgoing.push(line.to_owned());
+            return;
+        }
+        Err(err) => format!("Error: {}", err),
+    };
+    log::info!("Console command {:?}: {}", line, output);
+    world.fetch_mut::<Console>().output.push(output);
+}
+
+#[cfg(feature = "client")]
+fn route(world: &World, permission: CommandPermission) -> Result<CommandRoute, String> {
+    permission.route(
+        world.fetch::<MultiplayerGameState>().is_playing,
+        world.fetch::<MultiplayerRoomState>().is_host,
+    )
+}
+
+#[cfg(not(feature = "client"))]
+fn route(_world: &World, permission: CommandPermission) -> Result<CommandRoute, String> {
+    if permission.is_allowed_on_server() {
+        Ok(CommandRoute::Local)
+    } else {
+        Err("The command isn't available on the server".to_owned())
+    }
+}
+
+fn help(world: &mut World, _arguments: &[&str]) -> Result<String, String> {
+    let console_commands = world.fetch::<ConsoleCommands>();
+    let lines = console_commands
+        .commands()
+        .iter()
+        .map(|command| format!("{} - {}", command.usage, command.description))
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn spawn_monster(world: &mut World, arguments: &[&str]) -> Result<String, String> {
+    let (class, count) = match arguments {
+        [class] => (*class, "1"),
+        [class, count] => (*class, *count),
+        _ => return Err("Expected a class and an optional count".to_owned()),
+    };
+    let class = PlayerClass::ALL
+        .iter()
+        .cloned()
+        .find(|player_class| player_class.name().eq_ignore_ascii_case(class))
+        .ok_or_else(|| format!("Unknown class {:?}", class))?;
+    let count = count
+        .parse::<usize>()
+        .ok()
+        .filter(|count| (1..=MAX_CONSOLE_SPAWN_COUNT).contains(count))
+        .ok_or_else(|| format!("The count must be from 1 to {}", MAX_CONSOLE_SPAWN_COUNT))?;
+
+    if *world.fetch::<GameEngineState>() != GameEngineState::Playing {
+        return Err("The game isn't started".to_owned());
+    }
+    let has_player = {
+        let (players, dead) = world.system_data::<(ReadStorage<Player>, ReadStorage<Dead>)>();
+        (&players, !&dead)
+            .join()
+            .any(|(player, _)| player.class == class)
+    };
+    if !has_player {
+        return Err(format!("There's no alive {} player", class.name()));
+    }
+
+    world
+        .fetch_mut::<ConsoleSpawnRequests>()
+        .0
+        .push((class, count));
+    Ok(format!(
+        "Spawning {} monster(s) around the {}",
+        count,
+        class.name()
+    ))
+}
+
+fn set_time_scale(world: &mut World, arguments: &[&str]) -> Result<String, String> {
+    let time_scale = match arguments {
+        [time_scale] => time_scale.parse::<f32>().ok(),
+        _ => None,
+    }
+    .filter(|time_scale| (MIN_TIME_SCALE..=MAX_TIME_SCALE).contains(time_scale))
+    .ok_or_else(|| {
+        format!(
+            "Expected a time scale from {} to {}",
+            MIN_TIME_SCALE, MAX_TIME_SCALE
+        )
+    })?;
+    world.fetch_mut::<GameTime>().time_scale = time_scale;
+    Ok(format!("Time scale is set to {}", time_scale))
+}
@@ -3,12 +3,12 @@
 #[cfg(feature = "client")]
 use amethyst::{
     assets::{Handle, Prefab},
-    ecs::{Read, ReadExpect},
+    ecs::Read,
     renderer::SpriteRender,
 };
 use amethyst::{
     core::Transform,
-    ecs::{prelude::World, Entities, Entity, WriteStorage},
+    ecs::{prelude::World, Entities, Entity, ReadExpect, WriteStorage},
     shred::{ResourceId, SystemData},
     utils::tag::Tag,
 };
@@ -16,36 +16,48 @@ use amethyst::{
 #[cfg(feature = "client")]
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
 #[cfg(feature = "client")]
-use gv_client_shared::ecs::resources::AssetHandles;
+use gv_client_shared::ecs::resources::{AssetHandles, LevelAssets};
 use gv_core::{
     actions::{mob::MobAction, Action},
+    augments::PlayerAugments,
+    classes::PlayerClass,
+    economy::PlayerWallet,
     ecs::{
-        components::{damage_history::DamageHistory, *},
+        components::{aura::Auras, damage_history::DamageHistory, *},
         tags::*,
     },
     math::{Vector2, ZeroVector},
 };
 
-use crate::ecs::resources::MonsterDefinition;
+use crate::ecs::resources::{ClassDefinitions, MonsterDefinition};
 
 #[derive(SystemData)]
 pub struct PlayerFactory<'s> {
     entities: Entities<'s>,
+    class_definitions: ReadExpect<'s, ClassDefinitions>,
     transforms: WriteStorage<'s, Transform>,
     player_actions: WriteStorage<'s, PlayerActions>,
     world_positions: WriteStorage<'s, WorldPosition>,
     net_world_positions: WriteStorage<'s, NetWorldPosition>,
     players: WriteStorage<'s, Player>,
+    player_augments: WriteStorage<'s, PlayerAugments>,
+    player_wallets: WriteStorage<'s, PlayerWallet>,
     player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
     damage_histories: WriteStorage<'s, DamageHistory>,
+    impulses: WriteStorage<'s, Impulse>,
+    auras: WriteStorage<'s, Auras>,
 }
 
 impl<'s> PlayerFactory<'s> {
-    pub fn create(&mut self) -> Entity {
+    pub fn create(&mut self, class: PlayerClass) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_z(10.0);
+        let class_definition = self.class_definitions.get(class);
+        let base_health = class_definition.base_health;
+        let auras = class_definition.auras.clone();
 
-        self.entities
+        let player_entity = self
+            .entities
             .build_entity()
             .with(transform, &mut self.transforms)
             .with(PlayerActions::default(), &mut self.player_actions)
@@ -57,13 +69,22 @@ impl<'s> PlayerFactory<'s> {
                 NetWorldPosition::new(Vector2::zero()),
                 &mut self.net_world_positions,
             )
-            .with(Player::new(), &mut self.players)
+            .with(Player::with_class(class, base_health), &mut self.players)
+            .with(PlayerAugments::default(), &mut self.player_augments)
+            .with(PlayerWallet::default(), &mut self.player_wallets)
             .with(
                 PlayerLastCastedSpells::default(),
                 &mut self.player_last_casted_spells,
             )
             .with(DamageHistory::new(0), &mut self.damage_histories)
-            .build()
+            .with(Impulse::default(), &mut self.impulses)
+            .build();
+        if !auras.is_empty() {
+            self.auras
+                .insert(player_entity, Auras(auras))
+                .expect("Expected to insert Auras component");
+        }
+        player_entity
     }
 }
 
@@ -72,6 +93,8 @@ pub struct LandscapeFactory<'s> {
     entities: Entities<'s>,
     #[cfg(feature = "client")]
     asset_handles: ReadExpect<'s, AssetHandles>,
+    #[cfg(feature = "client")]
+    level_assets: Read<'s, LevelAssets>,
     tags: WriteStorage<'s, Tag<Landscape>>,
     transforms: WriteStorage<'s, Transform>,
     #[cfg(feature = "client")]
@@ -81,7 +104,11 @@ pub struct LandscapeFactory<'s> {
 impl<'s> LandscapeFactory<'s> {
     #[cfg(feature = "client")]
     pub fn create(&mut self) -> Entity {
-        let AssetHandles { landscape, .. } = self.asset_handles.clone();
+        // Is the menu landscape only if no level has been loaded.
+        let landscape = self.level_assets.0.as_ref().map_or_else(
+            || self.asset_handles.landscape.clone(),
+            |level_asset_handles| level_asset_handles.landscape.clone(),
+        );
 
         let mut transform = Transform::default();
         transform.set_translation_z(-1.0);
@@ -124,6 +151,8 @@ pub struct MonsterFactory<'s> {
     pub monsters: WriteStorage<'s, Monster>,
     pub damage_histories: WriteStorage<'s, DamageHistory>,
     pub world_positions: WriteStorage<'s, WorldPosition>,
+    pub impulses: WriteStorage<'s, Impulse>,
+    pub threat_tables: WriteStorage<'s, ThreatTable>,
 }
 
 impl<'s> MonsterFactory<'s> {
@@ -135,6 +164,7 @@ impl<'s> MonsterFactory<'s> {
         position: Vector2,
         destination: Vector2,
         action: Action<MobAction<Entity>>,
+        active_at_frame: u64,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 5.0);
@@ -171,6 +201,7 @@ impl<'s> MonsterFactory<'s> {
                     action,
                     name,
                     radius,
+                    active_at_frame,
                 },
                 &mut self.monsters,
             )
@@ -178,6 +209,8 @@ impl<'s> MonsterFactory<'s> {
                 DamageHistory::new(frame_spawned),
                 &mut self.damage_histories,
             )
+            .with(Impulse::default(), &mut self.impulses)
+            .with(ThreatTable::default(), &mut self.threat_tables)
             .build()
     }
 
@@ -189,6 +222,7 @@ impl<'s> MonsterFactory<'s> {
         position: Vector2,
         destination: Vector2,
         action: Action<MobAction<Entity>>,
+        active_at_frame: u64,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 5.0);
@@ -223,6 +257,7 @@ impl<'s> MonsterFactory<'s> {
                     action,
                     name,
                     radius,
+                    active_at_frame,
                 },
                 &mut self.monsters,
             )
@@ -230,6 +265,8 @@ impl<'s> MonsterFactory<'s> {
                 DamageHistory::new(frame_spawned),
                 &mut self.damage_histories,
             )
+            .with(Impulse::default(), &mut self.impulses)
+            .with(ThreatTable::default(), &mut self.threat_tables)
             .build()
     }
 }
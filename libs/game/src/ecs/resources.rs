@@ -1,62 +1,313 @@
 use amethyst::ecs::World;
+use rand::{rngs::StdRng, SeedableRng};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+pub use gv_core::balance::{
+    ClassDefinition, HealSpell, MeleeSpell, MissileSpell, MonsterDefinition, OnHitEffect,
+    SpellDefinition, TauntSpell, ZoneSpell,
+};
 #[cfg(not(feature = "client"))]
 use gv_core::net::client_message::ClientMessage;
+use gv_core::{
+    actions::player::CastSpell,
+    anti_cheat::{FlaggedFrame, Violation},
+    augments::{Augment, PlayerAugments},
+    balance::Balance,
+    classes::PlayerClass,
+    economy::{PlayerWallet, ShopItem},
+    ecs::{
+        components::missile::Missile,
+        resources::{pool::EntityPool, world::SAVED_WORLD_STATES_LIMIT},
+    },
+    level_scripts::LevelScriptRunner,
+    net::{ConnectionNetEvent, NetIdentifier},
+    objectives::ObjectiveTracker,
+    stats::StatModifiers,
+};
 #[cfg(feature = "client")]
-use gv_core::net::server_message::ServerMessage;
-use gv_core::{actions::mob::MobAttackType, net::ConnectionNetEvent};
+use gv_core::{
+    ecs::resources::Intermission, escort::PayloadProgress, game_events::GameEvent,
+    level_scripts::ScriptAction, net::server_message::ServerMessage,
+};
 
-#[derive(Clone)]
-pub struct MonsterDefinition {
-    pub name: String,
-    pub base_health: f32,
-    pub base_speed: f32,
-    pub base_attack_damage: f32,
-    pub attack_type: MobAttackType,
-    pub collision_radius: f32,
-}
+/// Minions summoned by players are spawned with this definition.
+pub const MINION_DEFINITION: &str = "Wisp";
 
 pub struct MonsterDefinitions(pub HashMap<String, MonsterDefinition>);
 
 impl MonsterDefinitions {
-    #[cfg(feature = "client")]
     pub fn register(world: &mut World) {
-        let mut map = HashMap::new();
-        map.insert(
-            "Ghoul".to_owned(),
-            MonsterDefinition {
-                name: "Ghoul".to_owned(),
-                base_health: 100.0,
-                base_speed: 180.0,
-                base_attack_damage: 15.0,
-                attack_type: MobAttackType::SlowMelee { cooldown: 0.75 },
-                collision_radius: 12.0,
-            },
-        );
-        world.insert(Self(map))
+        let monster_definitions = Self::new(&world.fetch::<Balance>());
+        world.insert(monster_definitions)
     }
 
-    #[cfg(not(feature = "client"))]
+    pub fn new(balance: &Balance) -> Self {
+        Self(balance.monsters.clone())
+    }
+}
+
+pub struct ClassDefinitions(pub HashMap<PlayerClass, ClassDefinition>);
+
+impl ClassDefinitions {
+    pub fn register(world: &mut World) {
+        let class_definitions = Self::new(&world.fetch::<Balance>());
+        world.insert(class_definitions)
+    }
+
+    pub fn new(balance: &Balance) -> Self {
+        Self(balance.classes.clone())
+    }
+
+    pub fn get(&self, class: PlayerClass) -> &ClassDefinition {
+        self.0
+            .get(&class)
+            .unwrap_or_else(|| panic!("Expected a definition for {}", class.name()))
+    }
+
+    pub fn cooldown_secs(&self, class: PlayerClass, spell: CastSpell) -> f32 {
+        let class_definition = self.get(class);
+        match spell {
+            CastSpell::Missile => class_definition.missile.cooldown_secs,
+            CastSpell::Heal => class_definition.heal.cooldown_secs,
+            CastSpell::Zone => class_definition.zone.cooldown_secs,
+            CastSpell::Taunt => class_definition.taunt.cooldown_secs,
+        }
+    }
+
+    /// The cooldown shortened by the class's base cooldown reduction and haste and by modifiers.
+    pub fn modified_cooldown_secs(
+        &self,
+        class: PlayerClass,
+        spell: CastSpell,
+        stat_modifiers: &StatModifiers,
+    ) -> f32 {
+        let class_definition = self.get(class);
+        stat_modifiers.cooldown_secs(
+            self.cooldown_secs(class, spell),
+            class_definition.base_cooldown_reduction,
+            class_definition.base_haste,
+        )
+    }
+}
+
+/// Missile spells by ids, shared by a server resolving casts and clients predicting them.
+pub struct SpellDefinitions(pub HashMap<String, SpellDefinition>);
+
+impl SpellDefinitions {
     pub fn register(world: &mut World) {
-        let mut map = HashMap::new();
-        map.insert(
-            "Ghoul".to_owned(),
-            MonsterDefinition {
-                name: "Ghoul".to_owned(),
-                base_health: 100.0,
-                base_speed: 180.0,
-                base_attack_damage: 15.0,
-                attack_type: MobAttackType::SlowMelee { cooldown: 0.75 },
-                collision_radius: 12.0,
-            },
-        );
-        world.insert(Self(map))
+        let spell_definitions = Self::new(&world.fetch::<Balance>());
+        world.insert(spell_definitions)
     }
+
+    pub fn new(balance: &Balance) -> Self {
+        Self(balance.spells.clone())
+    }
+
+    pub fn get(&self, id: &str) -> &SpellDefinition {
+        self.0
+            .get(id)
+            .unwrap_or_else(|| panic!("Expected a definition for {}", id))
+    }
+
+    /// The spell cast by the class with the missile ability.
+    pub fn missile(&self, class_definition: &ClassDefinition) -> &SpellDefinition {
+        self.get(&class_definition.missile.spell)
+    }
+}
+
+/// Augments chosen on level-ups, AugmentSystem validates them and applies to the players.
+#[derive(Default)]
+pub struct AugmentChoices {
+    /// Is sent to a server in multiplayer.
+    #[cfg(feature = "client")]
+    pub local: Option<Augment>,
+    /// Augments confirmed by a server, paired with entity net ids of the players.
+    #[cfg(feature = "client")]
+    pub confirmed: Vec<(NetIdentifier, PlayerAugments)>,
+    /// Choices received from clients, paired with their connection ids.
+    #[cfg(not(feature = "client"))]
+    pub received: Vec<(NetIdentifier, Augment)>,
+}
+
+/// Shop purchases, EconomySystem validates them and updates the players' wallets.
+#[derive(Default)]
+pub struct ShopPurchases {
+    /// Is sent to a server in multiplayer.
+    #[cfg(feature = "client")]
+    pub local: Option<ShopItem>,
+    /// Wallets sent by a server, paired with entity net ids of the players.
+    #[cfg(feature = "client")]
+    pub confirmed: Vec<(NetIdentifier, PlayerWallet)>,
+    /// Purchases received from clients, paired with their connection ids.
+    #[cfg(not(feature = "client"))]
+    pub received: Vec<(NetIdentifier, ShopItem)>,
+}
+
+/// Ready-ups for the next wave, IntermissionSystem ends an intermission once everyone is ready.
+#[derive(Default)]
+pub struct WaveReadiness {
+    /// Is sent to a server in multiplayer.
+    #[cfg(feature = "client")]
+    pub local: bool,
+    /// The last intermission state sent by a server.
+    #[cfg(feature = "client")]
+    pub confirmed: Option<Intermission>,
+    /// The wave of the last intermission ended by a server.
+    #[cfg(feature = "client")]
+    pub confirmed_end: Option<usize>,
+    /// Connection ids of the clients that are ready for the next wave.
+    #[cfg(not(feature = "client"))]
+    pub received: Vec<NetIdentifier>,
+}
+
+/// Events for the event ticker, GameEventSystem detects them on the authoritative side.
+#[derive(Default)]
+pub struct GameEvents {
+    /// Events detected in single player or received with `GameEvents`, the ticker drains them.
+    #[cfg(feature = "client")]
+    pub received: Vec<GameEvent>,
+}
+
+/// Summon requests, SummonSystem validates them and schedules minion spawns.
+#[derive(Default)]
+pub struct SummonRequests {
+    /// Is sent to a server in multiplayer.
+    #[cfg(feature = "client")]
+    pub local: bool,
+    /// Connection ids of the clients that requested a summon.
+    #[cfg(not(feature = "client"))]
+    pub received: Vec<NetIdentifier>,
 }
 
+/// Interactions received with PlayerInputs, LevelScriptSystem activates the levers around
+/// the players. Single player keeps them in PlayerActions instead.
+#[derive(Default)]
+pub struct InteractionRequests {
+    /// Connection ids of the clients whose players have pressed the interact key.
+    #[cfg(not(feature = "client"))]
+    pub received: Vec<NetIdentifier>,
+}
+
+/// Monsters requested by the `spawn monster` console command,
+/// ConsoleSpawnSystem spawns them around a player of the class.
+#[derive(Default)]
+pub struct ConsoleSpawnRequests(pub Vec<(PlayerClass, usize)>);
+
 #[cfg(feature = "client")]
 pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ServerMessage>>);
 #[cfg(not(feature = "client"))]
 pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ClientMessage>>);
+
+/// Scripted objectives of a single player game, are set by the Tutorial button.
+/// LevelSystem spawns the monsters of the objectives instead of waves while they are active.
+#[derive(Default)]
+pub struct ActiveObjectives(pub Option<ObjectiveTracker>);
+
+/// The level script of the current campaign level, LevelScriptSystem evaluates it on
+/// the authoritative side and applies the actions sent by a server on multiplayer clients.
+#[derive(Default)]
+pub struct ActiveLevelScript {
+    pub runner: Option<LevelScriptRunner>,
+    /// Monsters of the fired `SpawnWave` actions, LevelSystem spawns them on the next tick.
+    pub pending_spawns: u32,
+    /// The last shown message with the game frame it was shown at.
+    pub message: Option<(String, u64)>,
+    /// Names of the activated switches and opened doors, clients display them.
+    pub activated_switches: Vec<String>,
+    pub opened_doors: Vec<String>,
+    /// The actions received with `LevelScriptActions`.
+    #[cfg(feature = "client")]
+    pub confirmed: Vec<ScriptAction>,
+    /// The last progress received with `UpdatePayload`.
+    #[cfg(feature = "client")]
+    pub confirmed_payload: Option<PayloadProgress>,
+}
+
+/// Monster spawns are rolled with the RNG of their frame, which is derived from the seed,
+/// so that a match resumed with its seed and frame number goes on the way it would have.
+pub struct MatchSeed(pub u64);
+
+impl MatchSeed {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    pub fn frame_rng(&self, frame_number: u64) -> StdRng {
+        StdRng::seed_from_u64(self.0.wrapping_add(frame_number))
+    }
+}
+
+/// Action ids of the heals that are already counted in MatchScore, as the server
+/// resolves a cast again each time it rewinds past it, with the frames they've been cast on.
+#[derive(Default)]
+pub struct CountedHeals(pub HashMap<u64, u64>);
+
+impl CountedHeals {
+    /// Returns false if the heal has already been counted. Heals older than
+    /// `SAVED_WORLD_STATES_LIMIT` frames are forgotten, as the server never rewinds that far.
+    pub fn count(&mut self, action_id: u64, frame_number: u64) -> bool {
+        self.0.retain(|_, counted_frame_number| {
+            *counted_frame_number + SAVED_WORLD_STATES_LIMIT as u64 >= frame_number
+        });
+        if self.0.contains_key(&action_id) {
+            return false;
+        }
+        self.0.insert(action_id, frame_number);
+        true
+    }
+}
+
+/// Missile hits (action id, shot, monsters hit before) and melee hits (action id, 0,
+/// targets cleaved before) that are already counted as critical in MatchScore, with
+/// the frames they've landed on.
+#[derive(Default)]
+pub struct CountedCrits(pub HashMap<(u64, u32, usize), u64>);
+
+impl CountedCrits {
+    /// Returns false if the hit has already been counted. Hits older than
+    /// `SAVED_WORLD_STATES_LIMIT` frames are forgotten, as the server never rewinds that far.
+    pub fn count(&mut self, hit: (u64, u32, usize), frame_number: u64) -> bool {
+        self.0.retain(|_, counted_frame_number| {
+            *counted_frame_number + SAVED_WORLD_STATES_LIMIT as u64 >= frame_number
+        });
+        if self.0.contains_key(&hit) {
+            return false;
+        }
+        self.0.insert(hit, frame_number);
+        true
+    }
+}
+
+/// Casts the simulation has flagged, which a server drains into its anti-cheat records,
+/// see `gv_core::anti_cheat`. Casts are flagged once per action id, as the server resolves
+/// a cast again each time it rewinds past it.
+#[derive(Default)]
+pub struct AntiCheatLog {
+    pub flagged: Vec<(NetIdentifier, FlaggedFrame)>,
+    flagged_casts: HashSet<u64>,
+}
+
+impl AntiCheatLog {
+    pub fn flag_cast(
+        &mut self,
+        action_id: u64,
+        entity_net_id: NetIdentifier,
+        frame_number: u64,
+        violation: Violation,
+    ) {
+        if self.flagged_casts.insert(action_id) {
+            self.flagged.push((
+                entity_net_id,
+                FlaggedFrame {
+                    frame_number,
+                    violation,
+                },
+            ));
+        }
+    }
+}
+
+/// Dead missiles are parked here instead of being deleted, spell spam would churn
+/// through entities otherwise.
+pub type MissilePool = EntityPool<Missile>;
@@ -3,13 +3,16 @@ use amethyst::{
     shred::{ResourceId, SystemData},
 };
 
-use gv_core::ecs::resources::{net::MultiplayerGameState, GameEngineState, NewGameEngineState};
+use gv_core::ecs::resources::{
+    net::MultiplayerGameState, GameEngineState, NewGameEngineState, SimulationTick,
+};
 
 #[derive(SystemData)]
 pub struct GameStateHelper<'s> {
     game_engine_state: ReadExpect<'s, GameEngineState>,
     new_game_engine_state: ReadExpect<'s, NewGameEngineState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    simulation_tick: ReadExpect<'s, SimulationTick>,
 }
 
 impl<'s> GameStateHelper<'s> {
@@ -22,6 +25,7 @@ impl<'s> GameStateHelper<'s> {
         *self.game_engine_state == GameEngineState::Playing
             && self.new_game_engine_state.0 == GameEngineState::Playing
             && multiplayer_is_unpaused
+            && self.simulation_tick.0
     }
 
     pub fn is_multiplayer(&self) -> bool {
@@ -42,6 +46,7 @@ impl<'s> GameStateHelper<'s> {
         *self.game_engine_state == GameEngineState::Playing
             && self.new_game_engine_state.0 == GameEngineState::Playing
             && self.multiplayer_is_unpaused()
+            && self.simulation_tick.0
     }
 
     pub fn multiplayer_is_unpaused(&self) -> bool {
@@ -50,4 +55,11 @@ impl<'s> GameStateHelper<'s> {
             && !self.multiplayer_game_state.waiting_for_players
             && !self.multiplayer_game_state.is_disconnected
     }
+
+    pub fn multiplayer_is_waiting_for_players(&self) -> bool {
+        *self.game_engine_state == GameEngineState::Playing
+            && self.multiplayer_game_state.is_playing
+            && self.multiplayer_game_state.waiting_for_players
+            && !self.multiplayer_game_state.is_disconnected
+    }
 }
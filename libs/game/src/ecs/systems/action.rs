@@ -26,42 +26,60 @@ use gv_core::{
         player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
         ClientActionUpdate, IdentifiableAction,
     },
+    augments::PlayerAugments,
+    balance::Balance,
+    economy::PlayerWallet,
     ecs::{
         components::{
+            affixes::MonsterAffixes,
+            aura::Auras,
             damage_history::{DamageHistory, DamageHistoryEntries},
             missile::Missile,
-            ClientPlayerActions, Dead, EntityNetMetadata, Monster, NetWorldPosition, Player,
-            PlayerActions, PlayerLastCastedSpells, WorldPosition,
+            zone::Zone,
+            Allegiance, ClientPlayerActions, Dead, EntityNetMetadata, Impulse, Monster,
+            NetWorldPosition, Player, PlayerActions, PlayerLastCastedSpells, ThreatTable,
+            WorldPosition,
         },
         resources::{
             net::{
                 ActionUpdateIdProvider, CastActionsToExecute, EntityNetMetadataStorage,
                 MultiplayerGameState,
             },
-            world::{FramedUpdates, SavedWorldState, WorldStates},
-            GameLevelState,
+            world::{
+                DirtyEntities, FramedUpdates, RollbackMode, RollbackStats, SavedWorldState,
+                WorldStates,
+            },
+            GameLevelState, MatchScore,
         },
         system_data::time::GameTimeService,
     },
-    net::{NetUpdate, INTERPOLATION_FRAME_DELAY},
+    net::NetUpdate,
+    room_settings::RoomSettings,
 };
 
 use crate::{
     ecs::{
-        resources::MonsterDefinitions,
+        resources::{
+            ActiveLevelScript, AntiCheatLog, ClassDefinitions, CountedCrits, CountedHeals,
+            MissilePool, MonsterDefinitions, SpellDefinitions,
+        },
         system_data::GameStateHelper,
         systems::{
             missile::{MissileFactory, MissilePhysicsSubsystem, MissileSpawnerSubsystem},
-            monster::{ApplyMonsterActionNetArgs, MonsterActionSubsystem},
+            monster::{ApplyMonsterActionNetArgs, MobTargets, MonsterActionSubsystem},
             player::{
-                ApplyCastActionNetArgs, ApplyLookActionNetArgs, ApplyWalkActionNetArgs,
-                PlayerActionSubsystem,
+                ApplyCastActionNetArgs, ApplyInteractActionNetArgs, ApplyLookActionNetArgs,
+                ApplyWalkActionNetArgs, PlayerActionSubsystem,
             },
+            threat_subsystem::ThreatSubsystem,
             world_state_subsystem::WorldStateSubsystem,
-            AggregatedOutcomingUpdates, AnimationsResourceBundle, ClientFrameUpdate,
-            DamageSubsystem, FrameUpdate, GraphicsResourceBundle,
+            zone::ZoneSubsystem,
+            AggregatedOutcomingUpdates, AnimationsResourceBundle, AuraSubsystem, BoundsSubsystem,
+            ClientFrameUpdate, DamageSubsystem, FrameUpdate, GraphicsResourceBundle, HealSubsystem,
+            MeleeSubsystem,
         },
     },
+    scripting::ScriptHooks,
     utils::{entities::is_dead, world::outcoming_net_updates_mut},
 };
 
@@ -74,6 +92,11 @@ pub struct ActionSystemData<'s> {
     animations_system_data: AnimationsSystemData<'s>,
     game_level_state: ReadExpect<'s, GameLevelState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    room_settings: ReadExpect<'s, RoomSettings>,
+    balance: ReadExpect<'s, Balance>,
+    active_level_script: ReadExpect<'s, ActiveLevelScript>,
+    rollback_mode: ReadExpect<'s, RollbackMode>,
+    rollback_stats: WriteExpect<'s, RollbackStats>,
     framed_updates: WriteExpect<'s, FramedUpdates<FrameUpdate>>,
     framed_client_side_actions: WriteExpect<'s, FramedUpdates<ClientFrameUpdate>>,
     world_states: WriteExpect<'s, WorldStates>,
@@ -81,8 +104,21 @@ pub struct ActionSystemData<'s> {
     entity_net_metadata_storage: WriteExpect<'s, EntityNetMetadataStorage>,
     action_update_id_provider: WriteExpect<'s, ActionUpdateIdProvider>,
     cast_actions_to_execute: WriteExpect<'s, CastActionsToExecute>,
+    match_score: WriteExpect<'s, MatchScore>,
+    counted_heals: WriteExpect<'s, CountedHeals>,
+    counted_crits: WriteExpect<'s, CountedCrits>,
+    anti_cheat_log: WriteExpect<'s, AntiCheatLog>,
+    missile_pool: WriteExpect<'s, MissilePool>,
     monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    class_definitions: ReadExpect<'s, ClassDefinitions>,
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
+    script_hooks: ReadExpect<'s, ScriptHooks>,
     client_player_actions: ReadStorage<'s, ClientPlayerActions>,
+    player_augments: ReadStorage<'s, PlayerAugments>,
+    player_wallets: ReadStorage<'s, PlayerWallet>,
+    allegiances: ReadStorage<'s, Allegiance>,
+    monster_affixes: ReadStorage<'s, MonsterAffixes>,
+    auras: ReadStorage<'s, Auras>,
     transforms: WriteStorage<'s, Transform>,
     entity_net_metadata: WriteStorage<'s, EntityNetMetadata>,
     players: WriteStorage<'s, Player>,
@@ -90,10 +126,13 @@ pub struct ActionSystemData<'s> {
     player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
     monsters: WriteStorage<'s, Monster>,
     missiles: WriteStorage<'s, Missile>,
+    zones: WriteStorage<'s, Zone>,
     world_positions: WriteStorage<'s, WorldPosition>,
     net_world_positions: WriteStorage<'s, NetWorldPosition>,
+    impulses: WriteStorage<'s, Impulse>,
     dead: WriteStorage<'s, Dead>,
     damage_histories: WriteStorage<'s, DamageHistory>,
+    threat_tables: WriteStorage<'s, ThreatTable>,
 }
 
 #[cfg(feature = "client")]
@@ -136,6 +175,10 @@ impl<'s> System<'s> for ActionSystem {
         }
         let game_frame_number = system_data.game_time_service.game_frame_number();
         log::trace!("Frame number: {}", game_frame_number);
+        // Sped up offline games advance by several frames on a tick, simulating them for
+        // the first time isn't a rollback.
+        let first_new_frame =
+            game_frame_number + 1 - system_data.game_time_service.advanced_frames();
 
         let graphics_resource_bundle =
             create_graphics_resource_bundle(system_data.graphics_system_data);
@@ -154,11 +197,19 @@ impl<'s> System<'s> for ActionSystem {
             Rc::new(RefCell::new(system_data.player_last_casted_spells));
         let monsters = Rc::new(RefCell::new(system_data.monsters));
         let missiles = Rc::new(RefCell::new(system_data.missiles));
+        let zones = Rc::new(RefCell::new(system_data.zones));
         let cast_actions_to_execute = Rc::new(RefCell::new(system_data.cast_actions_to_execute));
+        let match_score = Rc::new(RefCell::new(system_data.match_score));
+        let counted_heals = Rc::new(RefCell::new(system_data.counted_heals));
+        let counted_crits = Rc::new(RefCell::new(system_data.counted_crits));
+        let anti_cheat_log = Rc::new(RefCell::new(system_data.anti_cheat_log));
+        let missile_pool = Rc::new(RefCell::new(system_data.missile_pool));
         let world_positions = Rc::new(RefCell::new(system_data.world_positions));
         let net_world_positions = Rc::new(RefCell::new(system_data.net_world_positions));
+        let impulses = Rc::new(RefCell::new(system_data.impulses));
         let dead = Rc::new(RefCell::new(system_data.dead));
         let damage_histories = Rc::new(RefCell::new(system_data.damage_histories));
+        let threat_tables = Rc::new(RefCell::new(system_data.threat_tables));
 
         let world_state_subsystem = WorldStateSubsystem {
             entities: &system_data.entities,
@@ -168,7 +219,9 @@ impl<'s> System<'s> for ActionSystem {
             monsters: monsters.clone(),
             missiles: missiles.clone(),
             world_positions: world_positions.clone(),
+            impulses: impulses.clone(),
             dead: dead.clone(),
+            threat_tables: threat_tables.clone(),
         };
         let player_action_subsystem = PlayerActionSubsystem {
             game_time_service: &system_data.game_time_service,
@@ -176,31 +229,133 @@ impl<'s> System<'s> for ActionSystem {
             entities: &system_data.entities,
             game_level_state: &system_data.game_level_state,
             multiplayer_game_state: &system_data.multiplayer_game_state,
+            room_settings: &system_data.room_settings,
+            class_definitions: &system_data.class_definitions,
             client_player_actions: &system_data.client_player_actions,
+            player_augments: &system_data.player_augments,
+            player_wallets: &system_data.player_wallets,
             action_update_id_provider: action_update_id_provider.clone(),
             cast_actions_to_execute: cast_actions_to_execute.clone(),
+            anti_cheat_log,
             player_actions: player_actions.clone(),
             player_last_casted_spells: player_last_casted_spells.clone(),
             missiles: missiles.clone(),
             world_positions: world_positions.clone(),
+            impulses: impulses.clone(),
             animations_resource_bundle: &animations_resource_bundle,
         };
+        // Idle monsters head for the payload while it's being escorted to its destination.
+        let payload_position = system_data
+            .active_level_script
+            .runner
+            .as_ref()
+            .and_then(|runner| runner.payload())
+            .filter(|payload| !payload.is_arrived())
+            .map(|payload| payload.position());
         let monster_action_subsystem = MonsterActionSubsystem {
             entities: &system_data.entities,
             game_time_service: &system_data.game_time_service,
             game_state_helper: &system_data.game_state_helper,
             monster_definitions: &system_data.monster_definitions,
+            script_hooks: &system_data.script_hooks,
             game_level_state: &system_data.game_level_state,
+            room_settings: &system_data.room_settings,
+            collisions: &system_data.balance.collisions,
+            payload_position,
             client_player_actions: &system_data.client_player_actions,
+            allegiances: &system_data.allegiances,
+            monster_affixes: &system_data.monster_affixes,
             entity_net_metadata: entity_net_metadata.clone(),
             players: players.clone(),
             world_positions: world_positions.clone(),
             net_world_positions: net_world_positions.clone(),
             damage_histories: damage_histories.clone(),
+            impulses: impulses.clone(),
+            threat_tables: threat_tables.clone(),
             animations_resource_bundle: &animations_resource_bundle,
         };
+        let heal_subsystem = HealSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            game_level_state: &system_data.game_level_state,
+            class_definitions: &system_data.class_definitions,
+            monster_definitions: &system_data.monster_definitions,
+            monster_affixes: &system_data.monster_affixes,
+            cast_actions_to_execute: cast_actions_to_execute.clone(),
+            match_score: match_score.clone(),
+            counted_heals,
+            entity_net_metadata: entity_net_metadata.clone(),
+            players: players.clone(),
+            monsters: monsters.clone(),
+            world_positions: world_positions.clone(),
+            damage_histories: damage_histories.clone(),
+            dead: dead.clone(),
+        };
+        let zone_subsystem = ZoneSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            class_definitions: &system_data.class_definitions,
+            collisions: &system_data.balance.collisions,
+            cast_actions_to_execute: cast_actions_to_execute.clone(),
+            entity_net_metadata: entity_net_metadata.clone(),
+            transforms: transforms.clone(),
+            players: players.clone(),
+            zones,
+            world_positions: world_positions.clone(),
+            damage_histories: damage_histories.clone(),
+            threat_tables: threat_tables.clone(),
+            dead: dead.clone(),
+        };
+        let aura_subsystem = AuraSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            monster_definitions: &system_data.monster_definitions,
+            auras: &system_data.auras,
+            allegiances: &system_data.allegiances,
+            collisions: &system_data.balance.collisions,
+            entity_net_metadata: entity_net_metadata.clone(),
+            players: players.clone(),
+            monsters: monsters.clone(),
+            world_positions: world_positions.clone(),
+            damage_histories: damage_histories.clone(),
+            threat_tables: threat_tables.clone(),
+            dead: dead.clone(),
+        };
+        let threat_subsystem = ThreatSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            allegiances: &system_data.allegiances,
+            class_definitions: &system_data.class_definitions,
+            cast_actions_to_execute: cast_actions_to_execute.clone(),
+            world_positions: world_positions.clone(),
+            threat_tables: threat_tables.clone(),
+            dead: dead.clone(),
+        };
+        let melee_subsystem = MeleeSubsystem {
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            class_definitions: &system_data.class_definitions,
+            spell_definitions: &system_data.spell_definitions,
+            room_settings: &system_data.room_settings,
+            collisions: &system_data.balance.collisions,
+            player_wallets: &system_data.player_wallets,
+            cast_actions_to_execute: cast_actions_to_execute.clone(),
+            match_score: match_score.clone(),
+            counted_crits: counted_crits.clone(),
+            entity_net_metadata: entity_net_metadata.clone(),
+            players: players.clone(),
+            world_positions: world_positions.clone(),
+            damage_histories: damage_histories.clone(),
+            threat_tables: threat_tables.clone(),
+            dead: dead.clone(),
+        };
         let missile_factory = MissileFactory::new(
             &system_data.entities,
+            missile_pool,
             transforms.clone(),
             missiles.clone(),
             &graphics_resource_bundle,
@@ -209,6 +364,13 @@ impl<'s> System<'s> for ActionSystem {
             game_time_service: &system_data.game_time_service,
             game_state_helper: &system_data.game_state_helper,
             entities: &system_data.entities,
+            allegiances: &system_data.allegiances,
+            collisions: &system_data.balance.collisions,
+            class_definitions: &system_data.class_definitions,
+            spell_definitions: &system_data.spell_definitions,
+            script_hooks: &system_data.script_hooks,
+            room_settings: &system_data.room_settings,
+            player_wallets: &system_data.player_wallets,
             missile_factory: &missile_factory,
             cast_actions_to_execute: cast_actions_to_execute.clone(),
             monsters: monsters.clone(),
@@ -219,22 +381,49 @@ impl<'s> System<'s> for ActionSystem {
             game_time_service: &system_data.game_time_service,
             game_state_helper: &system_data.game_state_helper,
             game_level_state: &system_data.game_level_state,
+            room_settings: &system_data.room_settings,
+            script_hooks: &system_data.script_hooks,
             entities: &system_data.entities,
+            allegiances: &system_data.allegiances,
+            collisions: &system_data.balance.collisions,
+            player_wallets: &system_data.player_wallets,
+            players: players.clone(),
             monsters: monsters.clone(),
             missiles: missiles.clone(),
             dead: dead.clone(),
             damage_histories: damage_histories.clone(),
             world_positions: world_positions.clone(),
+            threat_tables,
+            match_score,
+            counted_crits,
+        };
+        let bounds_subsystem = BoundsSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            game_level_state: &system_data.game_level_state,
+            players: players.clone(),
+            monsters: monsters.clone(),
+            missiles: missiles.clone(),
+            world_positions: world_positions.clone(),
+            damage_histories: damage_histories.clone(),
+            dead: dead.clone(),
         };
         let damage_subsystem = DamageSubsystem {
             game_state_helper: &system_data.game_state_helper,
             game_time_service: &system_data.game_time_service,
             entities: &system_data.entities,
+            class_definitions: &system_data.class_definitions,
+            monster_definitions: &system_data.monster_definitions,
+            room_settings: &system_data.room_settings,
+            monster_affixes: &system_data.monster_affixes,
+            player_wallets: &system_data.player_wallets,
             entity_net_metadata_storage: entity_net_metadata_storage.clone(),
             entity_net_metadata: entity_net_metadata.clone(),
             players: players.clone(),
             monsters: monsters.clone(),
             damage_histories: damage_histories.clone(),
+            impulses,
             dead: dead.clone(),
         };
 
@@ -252,10 +441,12 @@ impl<'s> System<'s> for ActionSystem {
             .oldest_updated_frame
             .min(system_data.framed_client_side_actions.oldest_updated_frame);
 
-        // Add a world state to save the components to, ensure the update is possible.
-        system_data
-            .world_states
-            .add_world_state(SavedWorldState::default());
+        // Add world states to save the components to, ensure the update is possible.
+        for _ in 0..system_data.game_time_service.advanced_frames() {
+            system_data
+                .world_states
+                .add_world_state(SavedWorldState::default());
+        }
         system_data
             .world_states
             .check_update_is_possible(&system_data.framed_updates)
@@ -267,6 +458,7 @@ impl<'s> System<'s> for ActionSystem {
             });
 
         let oldest_updated_frame = system_data.framed_updates.oldest_updated_frame;
+        let interpolation_frame_delay = system_data.game_time_service.interpolation_frame_delay();
 
         // Load NetWorldPositions from currently available saved world states.
         let mut framed_net_positions: Vec<Vec<(Entity, NetWorldPosition)>> =
@@ -278,10 +470,10 @@ impl<'s> System<'s> for ActionSystem {
                 let mut framed_net_positions = Vec::with_capacity(capacity as usize);
                 let mut world_states_iter = system_data
                     .world_states
-                    .states_iter(oldest_updated_frame.saturating_sub(INTERPOLATION_FRAME_DELAY));
-                // Filling with empty values as the first INTERPOLATION_FRAME_DELAY frames
+                    .states_iter(oldest_updated_frame.saturating_sub(interpolation_frame_delay));
+                // Filling with empty values as the first interpolation_frame_delay frames
                 // we have zero data.
-                let zero_data_frames = INTERPOLATION_FRAME_DELAY
+                let zero_data_frames = interpolation_frame_delay
                     .saturating_sub(oldest_updated_frame)
                     .min(capacity);
                 for _ in 0..zero_data_frames {
@@ -302,10 +494,32 @@ impl<'s> System<'s> for ActionSystem {
                 framed_net_positions
             };
 
+        let mut dirty_entities = if *system_data.rollback_mode == RollbackMode::DirtyEntities
+            && oldest_updated_frame < first_new_frame
+        {
+            rollback_seeds(
+                &system_data.framed_updates,
+                &entity_net_metadata_storage.borrow(),
+            )
+            .map_or_else(DirtyEntities::all, |seeds| {
+                let mut dirty_entities = DirtyEntities::with_seeds(seeds);
+                // Expiring minions damage themselves without deciding on an action.
+                dirty_entities.extend(
+                    (&system_data.entities, &system_data.allegiances)
+                        .join()
+                        .map(|(entity, _)| entity),
+                );
+                dirty_entities
+            })
+        } else {
+            DirtyEntities::all()
+        };
+
         // Load the world state of the oldest updated frame.
         let mut world_states_iter = system_data
             .world_states
-            .states_iter_mut(oldest_updated_frame);
+            .states_iter_mut(oldest_updated_frame)
+            .peekable();
         let mut world_state = world_states_iter.next().unwrap_or_else(|| {
             panic!(
                 "Expected to store a world state for frame {}",
@@ -327,6 +541,25 @@ impl<'s> System<'s> for ActionSystem {
                 .next()
                 .expect("Expected a framed client-side action");
 
+            // The new frames are simulated for the first time, there's nothing to restore.
+            let is_rolled_back_frame = frame_updated.frame_number < first_new_frame;
+            if is_rolled_back_frame && !dirty_entities.is_all() {
+                let previous_state = world_states_iter
+                    .peek()
+                    .expect("Expected a world state saved by the previous simulation");
+                let current_positions = (&system_data.entities, &*world_positions.borrow())
+                    .join()
+                    .map(|(entity, position)| (entity, **position))
+                    .collect::<Vec<_>>();
+                dirty_entities.spread(
+                    frame_updated.frame_number,
+                    &current_positions,
+                    previous_state,
+                );
+            }
+            let is_simulated =
+                |entity: Entity| !is_rolled_back_frame || dirty_entities.contains(entity);
+
             let outcoming_net_updates = outcoming_net_updates_mut(
                 &mut system_data.aggregated_outcoming_updates,
                 frame_updated.frame_number,
@@ -342,6 +575,8 @@ impl<'s> System<'s> for ActionSystem {
             }
 
             damage_subsystem.reset_damage_entries(frame_updated.frame_number);
+            // Players are slowed by auras as well, so they're applied before any movement.
+            let aura_slows = aura_subsystem.apply_auras(frame_updated.frame_number);
 
             let dead_entities = dead.borrow();
             // Run player actions.
@@ -350,7 +585,8 @@ impl<'s> System<'s> for ActionSystem {
                 (&system_data.entities, &mut *players.borrow_mut())
                     .join()
                     .filter(|(entity, _)| {
-                        !is_dead(*entity, &*dead_entities, frame_updated.frame_number)
+                        is_simulated(*entity)
+                            && !is_dead(*entity, &*dead_entities, frame_updated.frame_number)
                     })
                     .map(move |(entity, player)| {
                         (entity, player, players_net_metadata.get(entity).cloned())
@@ -376,6 +612,7 @@ impl<'s> System<'s> for ActionSystem {
                     frame_updated.frame_number,
                     entity,
                     &mut player,
+                    &aura_slows,
                     net_args,
                     client_side_actions,
                 );
@@ -420,19 +657,48 @@ impl<'s> System<'s> for ActionSystem {
                 player_action_subsystem.apply_cast_action(
                     frame_updated.frame_number,
                     entity,
+                    &player,
                     net_args,
                     client_side_actions,
                 );
+
+                // Run interact action.
+                let net_args = if system_data.multiplayer_game_state.is_playing {
+                    let player_net_metadata =
+                        player_net_metadata.expect("Expected EntityNetMetadata for a player");
+                    Some(ApplyInteractActionNetArgs {
+                        entity_net_id: player_net_metadata.id,
+                        outcoming_net_updates,
+                    })
+                } else {
+                    None
+                };
+                player_action_subsystem.apply_interact_action(
+                    frame_updated.frame_number,
+                    entity,
+                    net_args,
+                );
             }
 
             // Run mob actions.
+            threat_subsystem.decay_threat();
+            let mob_targets = MobTargets::collect(
+                &system_data.entities,
+                &*monsters.borrow(),
+                &system_data.allegiances,
+                &*dead_entities,
+                frame_updated.frame_number,
+            );
+            let zone_slows =
+                zone_subsystem.apply_zone_effects(frame_updated.frame_number, &mob_targets);
             let entity_net_metadata_storage = entity_net_metadata_storage.borrow();
             let monsters_net_metadata = entity_net_metadata.borrow();
             for (entity, mut monster, monster_net_metadata) in
                 (&system_data.entities, &mut *monsters.borrow_mut())
                     .join()
                     .filter(|(entity, _)| {
-                        !is_dead(*entity, &*dead_entities, frame_updated.frame_number)
+                        is_simulated(*entity)
+                            && !is_dead(*entity, &*dead_entities, frame_updated.frame_number)
                     })
                     .map(move |(entity, monster)| {
                         (entity, monster, monsters_net_metadata.get(entity).cloned())
@@ -444,7 +710,8 @@ impl<'s> System<'s> for ActionSystem {
                         net_metadata.spawned_frame_number <= frame_updated.frame_number
                     })
                     .unwrap_or(true);
-                if monster_is_spawned {
+                // Emerging monsters stay in place until their spawn telegraph ends.
+                if monster_is_spawned && !monster.is_emerging(frame_updated.frame_number) {
                     let net_args = if system_data.multiplayer_game_state.is_playing {
                         let monster_net_metadata =
                             monster_net_metadata.expect("Expected EntityNetMetadata for a monster");
@@ -467,17 +734,33 @@ impl<'s> System<'s> for ActionSystem {
                         frame_updated.frame_number,
                         entity,
                         &mut monster,
+                        &mob_targets,
                         net_args,
                     );
-                    monster_action_subsystem.process_monster_movement(entity, &mut monster);
+                    monster_action_subsystem.process_monster_movement(
+                        entity,
+                        &mut monster,
+                        &zone_slows,
+                        &aura_slows,
+                    );
                 }
             }
             drop(dead_entities);
             drop(entity_net_metadata_storage);
 
+            // Resolve heals, zones, taunts and melee swings before spawning missiles
+            // from the rest of the casts.
+            heal_subsystem.resolve_heals(frame_updated.frame_number);
+            heal_subsystem.regenerate_monsters(frame_updated.frame_number);
+            heal_subsystem.regenerate_players_in_safe_zones(frame_updated.frame_number);
+            zone_subsystem.spawn_zones(frame_updated.frame_number);
+            threat_subsystem.resolve_taunts(frame_updated.frame_number);
+            melee_subsystem.resolve_swings(frame_updated.frame_number, &mob_targets);
+
             // Run missile systems.
             missile_spawner_subsystem.spawn_missiles(frame_updated.frame_number);
             missile_physics_subsystem.process_physics(frame_updated.frame_number);
+            bounds_subsystem.enforce_bounds(frame_updated.frame_number);
 
             // Process damage history and add updates, if server.
             damage_subsystem.process_damage_history(
@@ -493,14 +776,17 @@ impl<'s> System<'s> for ActionSystem {
                     frame_updated.frame_number,
                 )
             });
+            if is_rolled_back_frame {
+                world_state_subsystem.restore_clean_entities(&dirty_entities, world_state);
+            }
             world_state_subsystem.save_world_state(world_state);
 
-            // Update net_positions if we're updating more than INTERPOLATION_FRAME_DELAY frames.
-            if frame_updated.frame_number - oldest_updated_frame >= INTERPOLATION_FRAME_DELAY
+            // Update net_positions if we're updating more than interpolation_frame_delay frames.
+            if frame_updated.frame_number - oldest_updated_frame >= interpolation_frame_delay
                 && !system_data.game_state_helper.is_authoritative()
             {
                 let i =
-                    frame_updated.frame_number - oldest_updated_frame - INTERPOLATION_FRAME_DELAY;
+                    frame_updated.frame_number - oldest_updated_frame - interpolation_frame_delay;
                 framed_net_positions[i as usize] = world_state
                     .world_positions
                     .iter()
@@ -511,6 +797,22 @@ impl<'s> System<'s> for ActionSystem {
         }
 
         drop(client_side_actions_iter);
+        if oldest_updated_frame < first_new_frame {
+            system_data.rollback_stats.rollbacks += 1;
+            if !dirty_entities.is_all() {
+                log::trace!(
+                    "Re-simulated {} dirty entities since frame {}",
+                    dirty_entities.len(),
+                    oldest_updated_frame
+                );
+                let entities_count = (&system_data.entities, &*world_positions.borrow())
+                    .join()
+                    .count();
+                system_data.rollback_stats.partial_rollbacks += 1;
+                system_data.rollback_stats.last_partial_rollback =
+                    Some((dirty_entities.len(), entities_count));
+            }
+        }
         system_data.framed_updates.oldest_updated_frame = game_frame_number + 1;
         system_data.framed_client_side_actions.oldest_updated_frame = game_frame_number + 1;
     }
@@ -552,6 +854,50 @@ fn create_animations_resource_bundle(
     }
 }
 
+/// Clients receive corrections for the whole world from the server, so they can't tell
+/// which entities a rollback affects and re-simulate everything.
+#[cfg(feature = "client")]
+fn rollback_seeds(
+    _framed_updates: &FramedUpdates<FrameUpdate>,
+    _entity_net_metadata_storage: &EntityNetMetadataStorage,
+) -> Option<Vec<Entity>> {
+    None
+}
+
+/// Returns the players whose inputs have been updated since the oldest updated frame.
+#[cfg(not(feature = "client"))]
+fn rollback_seeds(
+    framed_updates: &FramedUpdates<FrameUpdate>,
+    entity_net_metadata_storage: &EntityNetMetadataStorage,
+) -> Option<Vec<Entity>> {
+    let mut seeds = Vec::new();
+    for frame_updates in framed_updates.iter_from_oldest_update() {
+        let entity_net_ids = frame_updates
+            .walk_action_updates
+            .iter()
+            .map(|update| update.entity_net_id)
+            .chain(
+                frame_updates
+                    .look_action_updates
+                    .iter()
+                    .map(|update| update.entity_net_id),
+            )
+            .chain(
+                frame_updates
+                    .cast_action_updates
+                    .iter()
+                    .map(|update| update.entity_net_id),
+            );
+        for entity_net_id in entity_net_ids {
+            // Players who have left the game don't have entities anymore.
+            if let Some(entity) = entity_net_metadata_storage.get_entity(entity_net_id) {
+                seeds.push(entity);
+            }
+        }
+    }
+    Some(seeds)
+}
+
 #[cfg(feature = "client")]
 fn damage_histories_updates(
     frame_updates: &FrameUpdate,
@@ -0,0 +1,179 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+#[cfg(not(feature = "client"))]
+use amethyst::{ecs::Write, network::simulation::TransportResource};
+
+#[cfg(feature = "client")]
+use gv_core::ecs::components::ClientPlayerActions;
+use gv_core::{
+    augments::{Augment, PlayerAugments},
+    ecs::resources::{
+        net::{EntityNetMetadataStorage, MultiplayerGameState},
+        MatchScore,
+    },
+};
+#[cfg(not(feature = "client"))]
+use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
+
+use crate::ecs::{resources::AugmentChoices, system_data::GameStateHelper};
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+#[cfg(not(feature = "client"))]
+type AugmentsSideData<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type AugmentsSideData<'s> = ReadStorage<'s, ClientPlayerActions>;
+
+/// Applies augments chosen on level-ups. The server and the single player client validate
+/// the choices against the team level, multiplayer clients apply the ones sent by the server.
+pub struct AugmentSystem;
+
+impl<'s> System<'s> for AugmentSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        ReadExpect<'s, MatchScore>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        WriteExpect<'s, AugmentChoices>,
+        Entities<'s>,
+        WriteStorage<'s, PlayerAugments>,
+        AugmentsSideData<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            match_score,
+            multiplayer_game_state,
+            entity_net_metadata_storage,
+            mut augment_choices,
+            entities,
+            mut player_augments,
+            side_data,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        self.apply_choices(
+            &game_state_helper,
+            &match_score,
+            &multiplayer_game_state,
+            &entity_net_metadata_storage,
+            &mut augment_choices,
+            &entities,
+            &mut player_augments,
+            side_data,
+        );
+    }
+}
+
+impl AugmentSystem {
+    #[cfg(not(feature = "client"))]
+    fn apply_choices(
+        &self,
+        _game_state_helper: &GameStateHelper,
+        match_score: &MatchScore,
+        multiplayer_game_state: &MultiplayerGameState,
+        entity_net_metadata_storage: &EntityNetMetadataStorage,
+        augment_choices: &mut AugmentChoices,
+        _entities: &Entities,
+        player_augments: &mut WriteStorage<PlayerAugments>,
+        (net_connection_models, mut transport): AugmentsSideData,
+    ) {
+        for (connection_id, augment) in augment_choices.received.drain(..) {
+            let player = multiplayer_game_state
+                .find_player_by_connection_id(connection_id)
+                .and_then(|player| {
+                    entity_net_metadata_storage
+                        .get_entity(player.entity_net_id)
+                        .map(|entity| (player.entity_net_id, entity))
+                });
+            let (entity_net_id, augments) = match player.and_then(|(entity_net_id, entity)| {
+                player_augments
+                    .get_mut(entity)
+                    .map(|augments| (entity_net_id, augments))
+            }) {
+                Some(player) => player,
+                None => {
+                    log::warn!(
+                        "Couldn't find a player to apply {} to (connection id: {})",
+                        augment.name(),
+                        connection_id
+                    );
+                    continue;
+                }
+            };
+
+            if !choose_augment(augments, augment, match_score) {
+                continue;
+            }
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateAugments {
+                    entity_net_id,
+                    augments: augments.clone(),
+                },
+            );
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn apply_choices(
+        &self,
+        game_state_helper: &GameStateHelper,
+        match_score: &MatchScore,
+        _multiplayer_game_state: &MultiplayerGameState,
+        entity_net_metadata_storage: &EntityNetMetadataStorage,
+        augment_choices: &mut AugmentChoices,
+        entities: &Entities,
+        player_augments: &mut WriteStorage<PlayerAugments>,
+        client_player_actions: AugmentsSideData,
+    ) {
+        if !game_state_helper.is_authoritative() {
+            // The local choice is sent to the server by ClientNetworkSystem.
+            for (entity_net_id, augments) in augment_choices.confirmed.drain(..) {
+                let entity = entity_net_metadata_storage.get_entity(entity_net_id);
+                if let Some(player_augments) =
+                    entity.and_then(|entity| player_augments.get_mut(entity))
+                {
+                    *player_augments = augments;
+                }
+            }
+            return;
+        }
+
+        if let Some(augment) = augment_choices.local.take() {
+            let player_augments = (entities, &client_player_actions, player_augments)
+                .join()
+                .next()
+                .map(|(_, _, player_augments)| player_augments);
+            if let Some(player_augments) = player_augments {
+                choose_augment(player_augments, augment, match_score);
+            }
+        }
+    }
+}
+
+fn choose_augment(
+    player_augments: &mut PlayerAugments,
+    augment: Augment,
+    match_score: &MatchScore,
+) -> bool {
+    if !player_augments.can_choose(match_score.level()) {
+        log::debug!("Ignoring {}, there's no level-up to spend", augment.name());
+        return false;
+    }
+    log::info!(
+        "Applying {} (level {})",
+        augment.name(),
+        match_score.level()
+    );
+    player_augments.0.push(augment);
+    true
+}
@@ -0,0 +1,217 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage};
+
+use std::collections::HashMap;
+
+use gv_core::{
+    collision_layers::{CollisionLayer, CollisionMatrix},
+    ecs::{
+        components::{
+            aura::{AuraEffect, Auras, AURA_TICK_FRAMES},
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            Allegiance, Dead, EntityNetMetadata, Monster, Player, ThreatTable, WorldPosition,
+        },
+        system_data::time::GameTimeService,
+    },
+    math::{Vector2, ZeroVector},
+    profile_scope,
+    stats::{Modifier, ModifierSource, Stat, StatModifiers},
+};
+
+use crate::{
+    ecs::{resources::MonsterDefinitions, system_data::GameStateHelper, systems::WriteStorageCell},
+    utils::{entities::is_dead, spatial_grid::SpatialGrid},
+};
+
+const GRID_CELL_SIZE: f32 = 128.0;
+
+/// Speed factors of the entities within slowing auras during a frame.
+#[derive(Default)]
+pub struct AuraSlows(HashMap<Entity, f32>);
+
+impl AuraSlows {
+    pub fn add_stat_modifiers(&self, entity: Entity, stat_modifiers: &mut StatModifiers) {
+        if let Some(speed_factor) = self.0.get(&entity) {
+            stat_modifiers.add(
+                Stat::MoveSpeed,
+                ModifierSource::Aura,
+                Modifier::Multiply(*speed_factor),
+            );
+        }
+    }
+}
+
+pub struct AuraSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub monster_definitions: &'s ReadExpect<'s, MonsterDefinitions>,
+    pub auras: &'s ReadStorage<'s, Auras>,
+    pub allegiances: &'s ReadStorage<'s, Allegiance>,
+    pub collisions: &'s CollisionMatrix,
+    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub monsters: WriteStorageCell<'s, Monster>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> AuraSubsystem<'s> {
+    /// Looks up the entities within the auras of players and elites. Damage and healing ticks
+    /// are added only on the authoritative side, while slows are applied by both a server
+    /// and clients. Harmful auras affect the layers that their owners' hits land on, healing
+    /// ones affect the owners' own layer.
+    pub fn apply_auras(&self, frame_number: u64) -> AuraSlows {
+        profile_scope!("AuraSubsystem::apply_auras");
+        let mut aura_slows = AuraSlows::default();
+        if self.auras.join().next().is_none() {
+            return aura_slows;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let players = self.players.borrow();
+        let monsters = self.monsters.borrow();
+        let world_positions = self.world_positions.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut threat_tables = self.threat_tables.borrow_mut();
+        let dead = self.dead.borrow();
+
+        let is_alive = |entity: Entity| {
+            let is_spawned = entity_net_metadata
+                .get(entity)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            is_spawned && !is_dead(entity, &*dead, frame_number)
+        };
+        let mut players_grid = SpatialGrid::new(GRID_CELL_SIZE);
+        for (entity, _, world_position) in (self.entities, &*players, &*world_positions).join() {
+            if is_alive(entity) {
+                players_grid.insert(entity, **world_position);
+            }
+        }
+        let mut monsters_grid = SpatialGrid::new(GRID_CELL_SIZE);
+        let mut minions_grid = SpatialGrid::new(GRID_CELL_SIZE);
+        for (entity, _, world_position) in (self.entities, &*monsters, &*world_positions).join() {
+            if !is_alive(entity) {
+                continue;
+            }
+            if self.allegiances.contains(entity) {
+                minions_grid.insert(entity, **world_position);
+            } else {
+                monsters_grid.insert(entity, **world_position);
+            }
+        }
+        let layer_grid = |layer: CollisionLayer| match layer {
+            CollisionLayer::Players => Some(&players_grid),
+            CollisionLayer::Monsters => Some(&monsters_grid),
+            CollisionLayer::Minions => Some(&minions_grid),
+            _ => None,
+        };
+
+        let aura_tick_frames = self.game_time_service.scale_frames(AURA_TICK_FRAMES);
+        let is_tick =
+            self.game_state_helper.is_authoritative() && frame_number % aura_tick_frames == 0;
+        let tick_seconds = self.game_time_service.tick_seconds() * aura_tick_frames as f32;
+        let mut heals = HashMap::new();
+
+        for (owner, auras, owner_position) in (self.entities, self.auras, &*world_positions).join()
+        {
+            let is_player = players.contains(owner);
+            if !is_player && (!monsters.contains(owner) || self.allegiances.contains(owner)) {
+                continue;
+            }
+            if !is_alive(owner) {
+                continue;
+            }
+
+            let owner_layer = if is_player {
+                CollisionLayer::Players
+            } else {
+                CollisionLayer::Monsters
+            };
+
+            for aura in &auras.0 {
+                let target_layers = if aura.effect.affects_enemies() {
+                    self.collisions.targets(owner_layer).collect::<Vec<_>>()
+                } else {
+                    vec![owner_layer]
+                };
+                let targets = target_layers
+                    .into_iter()
+                    .filter_map(layer_grid)
+                    .flat_map(|targets_grid| {
+                        targets_grid.query_radius(**owner_position, aura.radius)
+                    })
+                    .filter(|target| !aura.effect.affects_enemies() || *target != owner)
+                    .collect::<Vec<_>>();
+                for target in targets {
+                    match aura.effect {
+                        AuraEffect::Slow { speed_factor } => {
+                            let slow = aura_slows.0.entry(target).or_insert(1.0);
+                            *slow = slow.min(speed_factor);
+                        }
+                        AuraEffect::Damage {
+                            per_second,
+                            damage_type,
+                        } if is_tick => {
+                            let damage = per_second * tick_seconds;
+                            damage_histories
+                                .get_mut(target)
+                                .expect("Expected a DamageHistory")
+                                .add_entry(
+                                    frame_number,
+                                    DamageHistoryEntry {
+                                        damage,
+                                        impulse: Vector2::zero(),
+                                        is_crit: false,
+                                        damage_type: Some(damage_type),
+                                    },
+                                );
+                            if let Some(threat_table) = threat_tables.get_mut(target) {
+                                threat_table.add(owner, damage);
+                            }
+                        }
+                        AuraEffect::Healing { per_second } if is_tick => {
+                            *heals.entry(target).or_insert(0.0) += per_second * tick_seconds;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Overlapping healing auras don't heal beyond maximum health.
+        for (target, heal) in heals {
+            let missing_health = if let Some(player) = players.get(target) {
+                player.max_health - player.health
+            } else {
+                let monster = monsters.get(target).expect("Expected a Monster");
+                let base_health = self
+                    .monster_definitions
+                    .0
+                    .get(&monster.name)
+                    .expect("Expected a MonsterDefinition")
+                    .base_health;
+                base_health - monster.health
+            };
+            let heal = heal.min(missing_health);
+            if heal > 0.0 {
+                damage_histories
+                    .get_mut(target)
+                    .expect("Expected a DamageHistory")
+                    .add_entry(
+                        frame_number,
+                        DamageHistoryEntry {
+                            damage: -heal,
+                            impulse: Vector2::zero(),
+                            is_crit: false,
+                            damage_type: None,
+                        },
+                    );
+            }
+        }
+        aura_slows
+    }
+}
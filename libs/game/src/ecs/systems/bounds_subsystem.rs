@@ -0,0 +1,126 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, WriteStorage};
+
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            missile::Missile,
+            Dead, Monster, Player, WorldPosition,
+        },
+        resources::{GameLevelState, OUT_OF_BOUNDS_MARGIN},
+        system_data::time::GameTimeService,
+    },
+    level_scripts::OutOfBounds,
+    math::{Vector2, ZeroVector},
+    profile_scope,
+};
+
+use crate::{
+    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    utils::entities::is_dead,
+};
+
+pub struct BoundsSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub monsters: WriteStorageCell<'s, Monster>,
+    pub missiles: WriteStorageCell<'s, Missile>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> BoundsSubsystem<'s> {
+    /// Players outside of the level bounds are handled according to
+    /// `GameLevelState::out_of_bounds`, monsters and missiles further than `OUT_OF_BOUNDS_MARGIN`
+    /// from them are removed. Positions are fixed on every side, while kills are added only
+    /// on the authoritative one.
+    pub fn enforce_bounds(&self, frame_number: u64) {
+        profile_scope!("BoundsSubsystem::enforce_bounds");
+        let players = self.players.borrow();
+        let monsters = self.monsters.borrow();
+        let missiles = self.missiles.borrow();
+        let mut world_positions = self.world_positions.borrow_mut();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut dead = self.dead.borrow_mut();
+        let is_authoritative = self.game_state_helper.is_authoritative();
+
+        for (entity, player, world_position) in
+            (self.entities, &*players, &mut *world_positions).join()
+        {
+            if is_dead(entity, &*dead, frame_number)
+                || self
+                    .game_level_state
+                    .is_within_bounds(**world_position, 0.0)
+            {
+                continue;
+            }
+            match self.game_level_state.out_of_bounds {
+                OutOfBounds::Clamp => {
+                    **world_position = self.game_level_state.clamp_to_bounds(**world_position);
+                }
+                OutOfBounds::Teleport((x, y)) => **world_position = Vector2::new(x, y),
+                OutOfBounds::Kill if is_authoritative => {
+                    add_killing_entry(&mut damage_histories, entity, player.health, frame_number);
+                }
+                OutOfBounds::Kill => {}
+            }
+        }
+
+        if is_authoritative {
+            for (entity, monster, world_position) in
+                (self.entities, &*monsters, &*world_positions).join()
+            {
+                if !is_dead(entity, &*dead, frame_number)
+                    && !self
+                        .game_level_state
+                        .is_within_bounds(**world_position, OUT_OF_BOUNDS_MARGIN)
+                {
+                    add_killing_entry(&mut damage_histories, entity, monster.health, frame_number);
+                }
+            }
+        }
+
+        let lost_missiles = (self.entities, &*missiles, &*world_positions)
+            .join()
+            .filter(|(entity, missile, world_position)| {
+                missile.frame_spawned <= frame_number
+                    && !is_dead(*entity, &*dead, frame_number)
+                    && !self
+                        .game_level_state
+                        .is_within_bounds(***world_position, OUT_OF_BOUNDS_MARGIN)
+            })
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        for missile in lost_missiles {
+            let dead_since_frame = frame_number + 1;
+            let frame_acknowledged =
+                dead_since_frame.max(self.game_time_service.game_frame_number());
+            dead.insert(missile, Dead::new(dead_since_frame, frame_acknowledged))
+                .expect("Expected to insert a Dead component");
+        }
+    }
+}
+
+fn add_killing_entry(
+    damage_histories: &mut WriteStorage<DamageHistory>,
+    entity: Entity,
+    health: f32,
+    frame_number: u64,
+) {
+    damage_histories
+        .get_mut(entity)
+        .expect("Expected a DamageHistory")
+        .add_entry(
+            frame_number,
+            DamageHistoryEntry {
+                damage: health,
+                impulse: Vector2::zero(),
+                is_crit: false,
+                damage_type: None,
+            },
+        );
+}
@@ -0,0 +1,107 @@
+use amethyst::ecs::{Join, LazyUpdate, Read, ReadStorage, System, WriteExpect};
+
+use gv_core::{
+    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+    ecs::{
+        components::{Dead, Player, WorldPosition},
+        resources::{net::EntityNetMetadataStorage, world::FramedUpdates},
+        system_data::time::GameTimeService,
+    },
+    math::{deterministic, Vector2},
+};
+
+use crate::{
+    console::{run_console_command, Console},
+    ecs::{resources::ConsoleSpawnRequests, system_data::GameStateHelper},
+};
+
+const CONSOLE_SPAWN_DISTANCE: f32 = 200.0;
+
+/// Runs the typed commands at the end of the frame, as they need the whole world.
+pub struct ConsoleSystem;
+
+impl<'s> System<'s> for ConsoleSystem {
+    type SystemData = (Read<'s, LazyUpdate>, WriteExpect<'s, Console>);
+
+    fn run(&mut self, (lazy_update, mut console): Self::SystemData) {
+        for line in console.pending.drain(..) {
+            lazy_update.exec_mut(move |world| run_console_command(world, &line));
+        }
+    }
+}
+
+/// Schedules the monsters requested with the console on the authoritative side,
+/// they are spawned in a circle around a player of the requested class.
+pub struct ConsoleSpawnSystem;
+
+impl<'s> System<'s> for ConsoleSpawnSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, ConsoleSpawnRequests>,
+        WriteExpect<'s, FramedUpdates<SpawnActions>>,
+        WriteExpect<'s, EntityNetMetadataStorage>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Dead>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut console_spawn_requests,
+            mut spawn_actions,
+            mut entity_net_metadata_storage,
+            players,
+            world_positions,
+            dead,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running()
+            || !game_state_helper.is_authoritative()
+            || console_spawn_requests.0.is_empty()
+        {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let spawn_actions = spawn_actions
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number));
+        for (class, count) in console_spawn_requests.0.drain(..) {
+            let player_position = (&players, &world_positions, !&dead)
+                .join()
+                .find(|(player, _, _)| player.class == class)
+                .map(|(_, world_position, _)| **world_position);
+            let player_position = match player_position {
+                Some(player_position) => player_position,
+                None => {
+                    log::warn!("Couldn't find a {} player to spawn monsters", class.name());
+                    continue;
+                }
+            };
+
+            let mut entity_net_ids = if game_state_helper.is_multiplayer() {
+                Some(entity_net_metadata_storage.reserve_ids(count))
+            } else {
+                None
+            };
+            for i in 0..count {
+                let angle = std::f32::consts::PI * 2.0 * i as f32 / count as f32;
+                let (sin, cos) = deterministic::sin_cos(angle);
+                spawn_actions.spawn_actions.push(SpawnAction {
+                    spawn_type: SpawnType::Single {
+                        entity_net_id: entity_net_ids
+                            .as_mut()
+                            .and_then(|entity_net_ids| entity_net_ids.next()),
+                        position: player_position + Vector2::new(cos, sin) * CONSOLE_SPAWN_DISTANCE,
+                        affixes: Vec::new(),
+                    },
+                    telegraph_frames: 0,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "client")]
+use amethyst::ecs::ReadExpect;
+use amethyst::ecs::{Join, ReadStorage, System};
+
+#[cfg(feature = "client")]
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+#[cfg(feature = "client")]
+use gv_core::ecs::resources::net::PlayersNetStatus;
+use gv_core::{
+    crash_report::{update_crash_context, CrashContext},
+    ecs::{components::NetConnectionModel, system_data::time::GameTimeService},
+};
+
+#[cfg(feature = "client")]
+type ClientNetStatus<'s> = (
+    Option<ReadExpect<'s, MultiplayerRoomState>>,
+    Option<ReadExpect<'s, PlayersNetStatus>>,
+);
+#[cfg(not(feature = "client"))]
+type ClientNetStatus<'s> = ();
+
+/// Keeps the crash context up to date, so that a crash report describes the last frame.
+pub struct CrashContextSystem;
+
+impl<'s> System<'s> for CrashContextSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadStorage<'s, NetConnectionModel>,
+        ClientNetStatus<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (game_time_service, net_connection_models, client_net_status): Self::SystemData,
+    ) {
+        update_crash_context(|crash_context| {
+            crash_context.engine_frame_number = game_time_service.engine_time().frame_number();
+            crash_context.game_frame_number = game_time_service.game_frame_number();
+            crash_context.game_frame_number_absolute =
+                game_time_service.game_frame_number_absolute();
+            crash_context.net_stats = net_connection_models
+                .join()
+                .map(|net_connection_model| {
+                    format!(
+                        "Connection {} ({}): session {}, last acknowledged update {:?}, lagging behind {}, disconnected: {}",
+                        net_connection_model.id,
+                        net_connection_model.addr,
+                        net_connection_model.session_id,
+                        net_connection_model.last_acknowledged_update,
+                        net_connection_model.ping_pong_data.average_lagging_behind(),
+                        net_connection_model.disconnected,
+                    )
+                })
+                .collect();
+            update_client_net_status(crash_context, client_net_status);
+        });
+    }
+}
+
+#[cfg(feature = "client")]
+fn update_client_net_status(
+    crash_context: &mut CrashContext,
+    (multiplayer_room_state, players_net_status): ClientNetStatus,
+) {
+    crash_context.connection_status = multiplayer_room_state
+        .map(|multiplayer_room_state| format!("{:?}", multiplayer_room_state.connection_status));
+    if let Some(players_net_status) = players_net_status {
+        crash_context
+            .net_stats
+            .extend(players_net_status.players.iter().map(|player| {
+                format!(
+                    "Player {}: frame {}, lagging behind {}, latency {}ms (reported at frame {})",
+                    player.connection_id,
+                    player.frame_number,
+                    player.average_lagging_behind,
+                    player.latency_ms,
+                    players_net_status.frame_received,
+                )
+            }));
+    }
+}
+
+#[cfg(not(feature = "client"))]
+fn update_client_net_status(_crash_context: &mut CrashContext, _: ClientNetStatus) {}
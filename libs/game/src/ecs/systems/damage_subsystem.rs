@@ -1,35 +1,47 @@
-use amethyst::ecs::{Entities, Join, WriteStorage};
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, WriteStorage};
 
 use gv_core::{
+    damage,
+    economy::PlayerWallet,
     ecs::{
         components::{
+            affixes::MonsterAffixes,
             damage_history::{DamageHistory, DamageHistoryEntries},
-            Dead, EntityNetMetadata, Monster, Player,
+            Dead, EntityNetMetadata, Impulse, Monster, Player,
         },
         resources::net::EntityNetMetadataStorage,
         system_data::time::GameTimeService,
     },
     net::NetUpdate,
     profile_scope,
+    room_settings::RoomSettings,
+    stats::Stat,
 };
 
 use crate::{
     ecs::{
+        resources::{ClassDefinitions, MonsterDefinitions},
         system_data::GameStateHelper,
         systems::{OutcomingNetUpdates, WriteExpectCell, WriteStorageCell},
     },
-    utils::entities::is_dead,
+    utils::entities::{is_dead, monster_stat_modifiers, player_stat_modifiers},
 };
 
 pub struct DamageSubsystem<'s> {
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub game_time_service: &'s GameTimeService<'s>,
     pub entities: &'s Entities<'s>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub monster_definitions: &'s ReadExpect<'s, MonsterDefinitions>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub monster_affixes: &'s ReadStorage<'s, MonsterAffixes>,
+    pub player_wallets: &'s ReadStorage<'s, PlayerWallet>,
     pub entity_net_metadata_storage: WriteExpectCell<'s, EntityNetMetadataStorage>,
     pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
     pub players: WriteStorageCell<'s, Player>,
     pub monsters: WriteStorageCell<'s, Monster>,
     pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub impulses: WriteStorageCell<'s, Impulse>,
     pub dead: WriteStorageCell<'s, Dead>,
 }
 
@@ -71,6 +83,7 @@ impl<'s> DamageSubsystem<'s> {
         let entity_net_metadata = self.entity_net_metadata.borrow();
         let mut players = self.players.borrow_mut();
         let mut monsters = self.monsters.borrow_mut();
+        let mut impulses = self.impulses.borrow_mut();
         let mut dead = self.dead.borrow_mut();
 
         for (entity, damage_history) in (self.entities, &*damage_histories).join() {
@@ -101,10 +114,46 @@ impl<'s> DamageSubsystem<'s> {
 
             for damage_history_entry in &damage_history.get_entries(frame_number).entries {
                 if let Some(player) = players.get_mut(entity) {
-                    player.health -= damage_history_entry.damage;
+                    let damage_taken_factor = player_stat_modifiers(
+                        entity,
+                        self.player_wallets,
+                        &self.room_settings.mutators,
+                        frame_number,
+                    )
+                    .value(Stat::DamageTaken, 1.0);
+                    let health_lost = damage::health_lost(
+                        damage_history_entry.damage,
+                        damage_history_entry.damage_type,
+                        &self.class_definitions.get(player.class).resistances,
+                        damage_taken_factor,
+                    );
+                    player.health = (player.health - health_lost).min(player.max_health);
                 } else if let Some(monster) = monsters.get_mut(entity) {
-                    monster.health -= damage_history_entry.damage;
+                    if monster.is_emerging(frame_number) {
+                        continue;
+                    }
+                    let damage_taken_factor = monster_stat_modifiers(
+                        entity,
+                        self.monster_affixes,
+                        &self.room_settings.mutators,
+                    )
+                    .value(Stat::DamageTaken, 1.0);
+                    let monster_definition = self
+                        .monster_definitions
+                        .0
+                        .get(&monster.name)
+                        .expect("Expected a MonsterDefinition");
+                    let health_lost = damage::health_lost(
+                        damage_history_entry.damage,
+                        damage_history_entry.damage_type,
+                        &monster_definition.resistances,
+                        damage_taken_factor,
+                    );
+                    monster.health -= health_lost;
                 };
+                if let Some(impulse) = impulses.get_mut(entity) {
+                    impulse.add(damage_history_entry.impulse);
+                }
             }
         }
 
@@ -0,0 +1,292 @@
+use amethyst::ecs::{
+    Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage,
+};
+#[cfg(not(feature = "client"))]
+use amethyst::{ecs::Write, network::simulation::TransportResource};
+
+#[cfg(not(feature = "client"))]
+use std::collections::HashMap;
+
+#[cfg(feature = "client")]
+use gv_core::ecs::components::ClientPlayerActions;
+use gv_core::{
+    economy::{PlayerWallet, ShopItem, KILL_REWARD, WAVE_REWARD},
+    ecs::{
+        components::{Allegiance, Dead, Monster, Player, ThreatTable},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            GameLevelState,
+        },
+        system_data::time::GameTimeService,
+    },
+};
+#[cfg(not(feature = "client"))]
+use gv_core::{
+    ecs::components::NetConnectionModel,
+    net::{server_message::ServerMessagePayload, NetIdentifier},
+};
+
+use crate::ecs::{resources::ShopPurchases, system_data::GameStateHelper};
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+#[cfg(not(feature = "client"))]
+const WALLET_BROADCAST_INTERVAL: u64 = 10;
+
+#[cfg(not(feature = "client"))]
+type EconomySideData<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type EconomySideData<'s> = ReadStorage<'s, ClientPlayerActions>;
+
+/// Rewards players with coins for kills and finished waves. The server and the single player
+/// client validate purchases, multiplayer clients apply the wallets sent by the server.
+#[derive(Default)]
+pub struct EconomySystem {
+    #[cfg(not(feature = "client"))]
+    last_frame_number: u64,
+    #[cfg(not(feature = "client"))]
+    last_broadcasted_wallets: HashMap<NetIdentifier, PlayerWallet>,
+}
+
+impl<'s> System<'s> for EconomySystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, EntityNetMetadataStorage>,
+        ReadExpect<'s, GameLevelState>,
+        WriteExpect<'s, ShopPurchases>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, ThreatTable>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, PlayerWallet>,
+        EconomySideData<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            multiplayer_game_state,
+            entity_net_metadata_storage,
+            game_level_state,
+            mut shop_purchases,
+            entities,
+            players,
+            monsters,
+            allegiances,
+            threat_tables,
+            dead,
+            mut player_wallets,
+            side_data,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        if game_state_helper.is_authoritative() {
+            let killers = (&entities, &monsters, !&allegiances, &dead)
+                .join()
+                .filter(|(_, _, _, dead)| dead.frame_acknowledged == frame_number)
+                .flat_map(|(monster, _, _, _)| {
+                    killers(monster, &entities, &players, &allegiances, &threat_tables)
+                })
+                .collect::<Vec<_>>();
+            for killer in killers {
+                if let Some(wallet) = player_wallets.get_mut(killer) {
+                    wallet.coins += KILL_REWARD;
+                }
+            }
+
+            let wave_ended = game_level_state
+                .intermission
+                .as_ref()
+                .map_or(false, |intermission| {
+                    intermission.started_at_frame == frame_number
+                });
+            if wave_ended {
+                for wallet in (&mut player_wallets).join() {
+                    wallet.coins += WAVE_REWARD;
+                }
+            }
+        }
+
+        self.apply_purchases(
+            &game_state_helper,
+            frame_number,
+            &multiplayer_game_state,
+            &entity_net_metadata_storage,
+            &game_level_state,
+            &mut shop_purchases,
+            &entities,
+            &mut player_wallets,
+            side_data,
+        );
+    }
+}
+
+impl EconomySystem {
+    #[cfg(not(feature = "client"))]
+    fn apply_purchases(
+        &mut self,
+        _game_state_helper: &GameStateHelper,
+        frame_number: u64,
+        multiplayer_game_state: &MultiplayerGameState,
+        entity_net_metadata_storage: &EntityNetMetadataStorage,
+        game_level_state: &GameLevelState,
+        shop_purchases: &mut ShopPurchases,
+        _entities: &Entities,
+        player_wallets: &mut WriteStorage<PlayerWallet>,
+        (net_connection_models, mut transport): EconomySideData,
+    ) {
+        if frame_number < self.last_frame_number {
+            // A new game has started.
+            self.last_broadcasted_wallets.clear();
+        }
+        self.last_frame_number = frame_number;
+
+        let mut has_purchases = false;
+        for (connection_id, item) in shop_purchases.received.drain(..) {
+            let wallet = multiplayer_game_state
+                .find_player_by_connection_id(connection_id)
+                .and_then(|player| entity_net_metadata_storage.get_entity(player.entity_net_id))
+                .and_then(|entity| player_wallets.get_mut(entity));
+            match wallet {
+                Some(wallet) => {
+                    has_purchases |= buy_item(wallet, item, game_level_state, frame_number);
+                }
+                None => log::warn!(
+                    "Couldn't find a player to buy {} for (connection id: {})",
+                    item.name(),
+                    connection_id
+                ),
+            }
+        }
+
+        if !has_purchases && frame_number % WALLET_BROADCAST_INTERVAL != 0 {
+            return;
+        }
+        for player in &multiplayer_game_state.players {
+            let wallet = entity_net_metadata_storage
+                .get_entity(player.entity_net_id)
+                .and_then(|entity| player_wallets.get(entity));
+            let wallet = match wallet {
+                Some(wallet) => wallet,
+                None => continue,
+            };
+            if self.last_broadcasted_wallets.get(&player.entity_net_id) == Some(wallet) {
+                continue;
+            }
+            self.last_broadcasted_wallets
+                .insert(player.entity_net_id, wallet.clone());
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateWallet {
+                    entity_net_id: player.entity_net_id,
+                    wallet: wallet.clone(),
+                },
+            );
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn apply_purchases(
+        &mut self,
+        game_state_helper: &GameStateHelper,
+        frame_number: u64,
+        _multiplayer_game_state: &MultiplayerGameState,
+        entity_net_metadata_storage: &EntityNetMetadataStorage,
+        game_level_state: &GameLevelState,
+        shop_purchases: &mut ShopPurchases,
+        entities: &Entities,
+        player_wallets: &mut WriteStorage<PlayerWallet>,
+        client_player_actions: EconomySideData,
+    ) {
+        if !game_state_helper.is_authoritative() {
+            // The local purchase is sent to the server by ClientNetworkSystem.
+            for (entity_net_id, wallet) in shop_purchases.confirmed.drain(..) {
+                let entity = entity_net_metadata_storage.get_entity(entity_net_id);
+                if let Some(player_wallet) =
+                    entity.and_then(|entity| player_wallets.get_mut(entity))
+                {
+                    *player_wallet = wallet;
+                }
+            }
+            return;
+        }
+
+        if let Some(item) = shop_purchases.local.take() {
+            let wallet = (entities, &client_player_actions, player_wallets)
+                .join()
+                .next()
+                .map(|(_, _, wallet)| wallet);
+            if let Some(wallet) = wallet {
+                buy_item(wallet, item, game_level_state, frame_number);
+            }
+        }
+    }
+}
+
+/// Players who have hurt a monster, minions count for their owners.
+/// If the monster was killed by anything else, every player gets the reward.
+fn killers(
+    monster: Entity,
+    entities: &Entities,
+    players: &ReadStorage<Player>,
+    allegiances: &ReadStorage<Allegiance>,
+    threat_tables: &ReadStorage<ThreatTable>,
+) -> Vec<Entity> {
+    let mut killers = threat_tables
+        .get(monster)
+        .map(|threat_table| {
+            threat_table
+                .entries
+                .iter()
+                .filter_map(|(target, _)| {
+                    if players.contains(*target) {
+                        Some(*target)
+                    } else {
+                        allegiances.get(*target).map(|allegiance| allegiance.owner)
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    killers.sort();
+    killers.dedup();
+    if killers.is_empty() {
+        killers = (entities, players)
+            .join()
+            .map(|(player, _)| player)
+            .collect();
+    }
+    killers
+}
+
+fn buy_item(
+    wallet: &mut PlayerWallet,
+    item: ShopItem,
+    game_level_state: &GameLevelState,
+    frame_number: u64,
+) -> bool {
+    if game_level_state.intermission.is_none() {
+        log::debug!("Ignoring a purchase of {}, the shop is closed", item.name());
+        return false;
+    }
+    if !wallet.buy(item, frame_number) {
+        log::debug!("Ignoring a purchase of {}, not enough coins", item.name());
+        return false;
+    }
+    log::info!("Bought {} ({} coins left)", item.name(), wallet.coins);
+    true
+}
@@ -0,0 +1,52 @@
+use amethyst::{
+    core::Time,
+    ecs::{Read, ReadExpect, System, World},
+};
+
+use std::time::Instant;
+
+use gv_core::profiling::FrameTimings;
+
+/// Records how long the wrapped system runs each frame.
+pub struct Timed<S> {
+    system: S,
+    name: &'static str,
+}
+
+impl<S> Timed<S> {
+    pub fn new(system: S, name: &'static str) -> Self {
+        Self { system, name }
+    }
+}
+
+impl<'s, S: System<'s>> System<'s> for Timed<S> {
+    type SystemData = (S::SystemData, ReadExpect<'s, FrameTimings>);
+
+    fn run(&mut self, (system_data, frame_timings): Self::SystemData) {
+        let started_at = Instant::now();
+        self.system.run(system_data);
+        frame_timings.record(self.name, started_at, started_at.elapsed());
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.system.setup(world);
+        if !world.has_value::<FrameTimings>() {
+            world.insert(FrameTimings::default());
+        }
+    }
+
+    fn dispose(self, world: &mut World) {
+        self.system.dispose(world);
+    }
+}
+
+/// Closes the frame in `FrameTimings`, has to be registered after a barrier.
+pub struct FrameTimingsSystem;
+
+impl<'s> System<'s> for FrameTimingsSystem {
+    type SystemData = (Read<'s, Time>, ReadExpect<'s, FrameTimings>);
+
+    fn run(&mut self, (time, frame_timings): Self::SystemData) {
+        frame_timings.end_frame(time.frame_number());
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(feature = "client")]
+use amethyst::ecs::WriteExpect;
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System};
+#[cfg(not(feature = "client"))]
+use amethyst::{ecs::Write, network::simulation::TransportResource};
+
+#[cfg(not(feature = "client"))]
+use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
+use gv_core::{
+    ecs::{
+        components::{
+            affixes::MonsterAffixes, Allegiance, Dead, EntityNetMetadata, Monster, Player,
+            ThreatTable,
+        },
+        resources::{GameLevelState, MatchScore},
+        system_data::time::GameTimeService,
+    },
+    game_events::GameEvent,
+};
+
+#[cfg(feature = "client")]
+use crate::ecs::resources::GameEvents;
+use crate::ecs::system_data::GameStateHelper;
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+#[cfg(not(feature = "client"))]
+type GameEventsSideData<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type GameEventsSideData<'s> = WriteExpect<'s, GameEvents>;
+
+/// Detects kills, player deaths, level-ups and wave transitions on the authoritative side.
+/// A server broadcasts them, the single player client passes them to its event ticker.
+#[derive(Default)]
+pub struct GameEventSystem {
+    last_frame_number: u64,
+    last_level: u32,
+    last_intermission_wave: Option<usize>,
+}
+
+impl<'s> System<'s> for GameEventSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, MatchScore>,
+        ReadExpect<'s, GameLevelState>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, MonsterAffixes>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, ThreatTable>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, EntityNetMetadata>,
+        GameEventsSideData<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            match_score,
+            game_level_state,
+            entities,
+            players,
+            monsters,
+            monster_affixes,
+            allegiances,
+            threat_tables,
+            dead,
+            entity_net_metadata,
+            side_data,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || !game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        // A new game has started.
+        if frame_number < self.last_frame_number {
+            self.last_level = 0;
+            self.last_intermission_wave = None;
+        }
+        self.last_frame_number = frame_number;
+        let net_id = |entity: Entity| {
+            entity_net_metadata
+                .get(entity)
+                .map(|entity_net_metadata| entity_net_metadata.id)
+        };
+        let mut events = Vec::new();
+
+        // Minions dying aren't worth mentioning.
+        for (monster_entity, monster, _, dead) in
+            (&entities, &monsters, !&allegiances, &dead).join()
+        {
+            if dead.frame_acknowledged != frame_number {
+                continue;
+            }
+            events.push(GameEvent::Kill {
+                killer: top_threat_player(monster_entity, &players, &allegiances, &threat_tables)
+                    .and_then(net_id),
+                monster: monster.name.clone(),
+                is_elite: monster_affixes
+                    .get(monster_entity)
+                    .map_or(false, |monster_affixes| !monster_affixes.affixes.is_empty()),
+            });
+        }
+        for (player_entity, _, dead) in (&entities, &players, &dead).join() {
+            if dead.frame_acknowledged == frame_number {
+                if let Some(player_net_id) = net_id(player_entity) {
+                    events.push(GameEvent::PlayerDied(player_net_id));
+                }
+            }
+        }
+
+        let level = match_score.level();
+        if self.last_level != 0 && level > self.last_level {
+            events.push(GameEvent::LevelUp(level));
+        }
+        self.last_level = level;
+
+        let intermission_wave = game_level_state
+            .intermission
+            .as_ref()
+            .map(|intermission| intermission.wave);
+        match (self.last_intermission_wave, intermission_wave) {
+            (None, Some(wave)) => events.push(GameEvent::WaveCleared(wave)),
+            (Some(wave), None) => events.push(GameEvent::WaveStarted(wave + 1)),
+            _ => {}
+        }
+        self.last_intermission_wave = intermission_wave;
+
+        if !events.is_empty() {
+            self.send_events(events, side_data);
+        }
+    }
+}
+
+impl GameEventSystem {
+    #[cfg(not(feature = "client"))]
+    fn send_events(
+        &mut self,
+        events: Vec<GameEvent>,
+        (net_connection_models, mut transport): GameEventsSideData,
+    ) {
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connection_models).join(),
+            ServerMessagePayload::GameEvents(events),
+        );
+    }
+
+    #[cfg(feature = "client")]
+    fn send_events(&mut self, events: Vec<GameEvent>, mut game_events: GameEventsSideData) {
+        game_events.received.extend(events);
+    }
+}
+
+/// The player who has dealt the most threat to a monster, minions count for their owners.
+fn top_threat_player(
+    monster: Entity,
+    players: &ReadStorage<Player>,
+    allegiances: &ReadStorage<Allegiance>,
+    threat_tables: &ReadStorage<ThreatTable>,
+) -> Option<Entity> {
+    let threat_table = threat_tables.get(monster)?;
+    let mut threat_by_player: Vec<(Entity, f32)> = Vec::new();
+    for (target, threat) in &threat_table.entries {
+        let player = if players.contains(*target) {
+            *target
+        } else if let Some(allegiance) = allegiances.get(*target) {
+            allegiance.owner
+        } else {
+            continue;
+        };
+        match threat_by_player
+            .iter_mut()
+            .find(|(entity, _)| *entity == player)
+        {
+            Some((_, player_threat)) => *player_threat += threat,
+            None => threat_by_player.push((player, *threat)),
+        }
+    }
+    threat_by_player
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).expect("Expected a non-NaN threat"))
+        .map(|(player, _)| player)
+}
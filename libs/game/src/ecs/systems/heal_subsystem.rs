@@ -0,0 +1,245 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage};
+
+use gv_core::{
+    actions::player::CastSpell,
+    ecs::{
+        components::{
+            affixes::{MonsterAffixes, REGENERATION_TICK_FRAMES},
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            Dead, EntityNetMetadata, Monster, Player, WorldPosition,
+        },
+        resources::{
+            net::{CastActionToExecute, CastActionsToExecute},
+            GameLevelState, MatchScore,
+        },
+        system_data::time::GameTimeService,
+    },
+    level_scripts::SAFE_ZONE_REGENERATION_RATE,
+    math::{Vector2, ZeroVector},
+    profile_scope,
+};
+
+use crate::{
+    ecs::{
+        resources::{ClassDefinitions, CountedHeals, MonsterDefinitions},
+        system_data::GameStateHelper,
+        systems::{WriteExpectCell, WriteStorageCell},
+    },
+    utils::entities::is_dead,
+};
+
+pub struct HealSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub monster_definitions: &'s ReadExpect<'s, MonsterDefinitions>,
+    pub monster_affixes: &'s ReadStorage<'s, MonsterAffixes>,
+    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub match_score: WriteExpectCell<'s, MatchScore>,
+    pub counted_heals: WriteExpectCell<'s, CountedHeals>,
+    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub monsters: WriteStorageCell<'s, Monster>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> HealSubsystem<'s> {
+    /// Takes heal casts out of CastActionsToExecute, so MissileSpawnerSubsystem doesn't
+    /// spawn missiles for them. Heals are added as negative damage entries, which makes
+    /// clients receive them with the rest of damage history updates.
+    pub fn resolve_heals(&self, frame_number: u64) {
+        profile_scope!("HealSubsystem::resolve_heals");
+        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
+        let (heals, other_casts): (Vec<_>, Vec<_>) = cast_actions_to_execute
+            .actions
+            .drain(..)
+            .partition(|cast_action| cast_action.action.action.spell == CastSpell::Heal);
+        cast_actions_to_execute.actions = other_casts;
+
+        if !self.game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let players = self.players.borrow();
+        let world_positions = self.world_positions.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let dead = self.dead.borrow();
+        let mut match_score = self.match_score.borrow_mut();
+        let mut counted_heals = self.counted_heals.borrow_mut();
+
+        for CastActionToExecute {
+            caster_class,
+            action: cast_action,
+            ..
+        } in heals
+        {
+            let heal_spell = &self.class_definitions.get(caster_class).heal;
+            let mut healing_done = 0.0;
+            // All the players are allies, monsters can't be healed.
+            for (entity, player, world_position, damage_history) in (
+                self.entities,
+                &*players,
+                &*world_positions,
+                &mut *damage_histories,
+            )
+                .join()
+            {
+                let is_spawned = entity_net_metadata
+                    .get(entity)
+                    .map_or(true, |entity_net_metadata| {
+                        entity_net_metadata.spawned_frame_number <= frame_number
+                    });
+                let distance = (cast_action.action.target_position - **world_position).norm();
+                if !is_spawned
+                    || is_dead(entity, &*dead, frame_number)
+                    || distance > heal_spell.radius + player.radius
+                {
+                    continue;
+                }
+
+                damage_history.add_entry(
+                    frame_number,
+                    DamageHistoryEntry {
+                        damage: -heal_spell.amount,
+                        impulse: Vector2::zero(),
+                        is_crit: false,
+                        damage_type: None,
+                    },
+                );
+                healing_done += heal_spell
+                    .amount
+                    .min(player.max_health - player.health)
+                    .max(0.0);
+            }
+
+            // Action ids are unique only in multiplayer, but single player never rewinds.
+            let is_counted = self.game_state_helper.is_multiplayer()
+                && !counted_heals.count(cast_action.action_id, frame_number);
+            if !is_counted {
+                match_score.healing_done += healing_done;
+            }
+        }
+    }
+
+    /// Regenerating elites restore a share of their base health every few frames,
+    /// but never get more health than they have been spawned with.
+    pub fn regenerate_monsters(&self, frame_number: u64) {
+        profile_scope!("HealSubsystem::regenerate_monsters");
+        let regeneration_tick_frames = self
+            .game_time_service
+            .scale_frames(REGENERATION_TICK_FRAMES);
+        if !self.game_state_helper.is_authoritative()
+            || frame_number % regeneration_tick_frames != 0
+        {
+            return;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let monsters = self.monsters.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let dead = self.dead.borrow();
+        let tick_seconds = self.game_time_service.tick_seconds() * regeneration_tick_frames as f32;
+
+        for (entity, monster, monster_affixes, damage_history) in (
+            self.entities,
+            &*monsters,
+            self.monster_affixes,
+            &mut *damage_histories,
+        )
+            .join()
+        {
+            let is_spawned = entity_net_metadata
+                .get(entity)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            if !is_spawned || is_dead(entity, &*dead, frame_number) {
+                continue;
+            }
+
+            let base_health = self
+                .monster_definitions
+                .0
+                .get(&monster.name)
+                .expect("Expected a MonsterDefinition")
+                .base_health;
+            let heal = (base_health * monster_affixes.regeneration_rate() * tick_seconds)
+                .min(base_health - monster.health);
+            if heal > 0.0 {
+                damage_history.add_entry(
+                    frame_number,
+                    DamageHistoryEntry {
+                        damage: -heal,
+                        impulse: Vector2::zero(),
+                        is_crit: false,
+                        damage_type: None,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Players inside a safe zone restore a share of their max health every few frames,
+    /// on the same ticks as regenerating elites.
+    pub fn regenerate_players_in_safe_zones(&self, frame_number: u64) {
+        profile_scope!("HealSubsystem::regenerate_players_in_safe_zones");
+        let regeneration_tick_frames = self
+            .game_time_service
+            .scale_frames(REGENERATION_TICK_FRAMES);
+        if !self.game_state_helper.is_authoritative()
+            || self.game_level_state.safe_zones.is_empty()
+            || frame_number % regeneration_tick_frames != 0
+        {
+            return;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let players = self.players.borrow();
+        let world_positions = self.world_positions.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let dead = self.dead.borrow();
+        let tick_seconds = self.game_time_service.tick_seconds() * regeneration_tick_frames as f32;
+
+        for (entity, player, world_position, damage_history) in (
+            self.entities,
+            &*players,
+            &*world_positions,
+            &mut *damage_histories,
+        )
+            .join()
+        {
+            let is_spawned = entity_net_metadata
+                .get(entity)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            let is_in_safe_zone = self
+                .game_level_state
+                .safe_zones
+                .iter()
+                .any(|safe_zone| safe_zone.contains(**world_position));
+            if !is_spawned || !is_in_safe_zone || is_dead(entity, &*dead, frame_number) {
+                continue;
+            }
+
+            let heal = (player.max_health * SAFE_ZONE_REGENERATION_RATE * tick_seconds)
+                .min(player.max_health - player.health);
+            if heal > 0.0 {
+                damage_history.add_entry(
+                    frame_number,
+                    DamageHistoryEntry {
+                        damage: -heal,
+                        impulse: Vector2::zero(),
+                        is_crit: false,
+                        damage_type: None,
+                    },
+                );
+            }
+        }
+    }
+}
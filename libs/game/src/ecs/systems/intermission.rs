@@ -0,0 +1,176 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+#[cfg(not(feature = "client"))]
+use amethyst::{
+    ecs::{Join, ReadStorage, Write},
+    network::simulation::TransportResource,
+};
+
+use gv_core::ecs::{
+    resources::{net::MultiplayerGameState, GameLevelState},
+    system_data::time::GameTimeService,
+};
+#[cfg(not(feature = "client"))]
+use gv_core::{
+    ecs::{components::NetConnectionModel, resources::Intermission},
+    net::server_message::ServerMessagePayload,
+};
+
+use crate::ecs::{resources::WaveReadiness, system_data::GameStateHelper};
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+#[cfg(not(feature = "client"))]
+type IntermissionSideData<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type IntermissionSideData<'s> = ();
+
+/// Ends intermissions started by LevelSystem once every player is ready for the next wave
+/// or when the time runs out. Multiplayer clients apply the state sent by the server.
+#[derive(Default)]
+pub struct IntermissionSystem {
+    #[cfg(not(feature = "client"))]
+    last_broadcasted_intermission: Option<Intermission>,
+}
+
+impl<'s> System<'s> for IntermissionSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, GameLevelState>,
+        WriteExpect<'s, WaveReadiness>,
+        IntermissionSideData<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            multiplayer_game_state,
+            mut game_level_state,
+            mut wave_readiness,
+            side_data,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        self.update_intermission(
+            &game_state_helper,
+            game_time_service.game_frame_number(),
+            &multiplayer_game_state,
+            &mut game_level_state,
+            &mut wave_readiness,
+            side_data,
+        );
+    }
+}
+
+impl IntermissionSystem {
+    #[cfg(not(feature = "client"))]
+    fn update_intermission(
+        &mut self,
+        _game_state_helper: &GameStateHelper,
+        frame_number: u64,
+        multiplayer_game_state: &MultiplayerGameState,
+        game_level_state: &mut GameLevelState,
+        wave_readiness: &mut WaveReadiness,
+        (net_connection_models, mut transport): IntermissionSideData,
+    ) {
+        let intermission = match game_level_state.intermission.as_mut() {
+            Some(intermission) => intermission,
+            None => {
+                for connection_id in wave_readiness.received.drain(..) {
+                    log::debug!(
+                        "Ignoring a ready-up outside of an intermission (connection id: {})",
+                        connection_id
+                    );
+                }
+                return;
+            }
+        };
+
+        for connection_id in wave_readiness.received.drain(..) {
+            if !intermission.ready_players.contains(&connection_id) {
+                log::info!(
+                    "Player (connection id: {}) is ready for wave {}",
+                    connection_id,
+                    intermission.wave + 1
+                );
+                intermission.ready_players.push(connection_id);
+            }
+        }
+
+        let is_everyone_ready = multiplayer_game_state
+            .players
+            .iter()
+            .all(|player| intermission.ready_players.contains(&player.connection_id));
+        if is_everyone_ready || frame_number >= intermission.ends_at_frame {
+            let wave = intermission.wave;
+            log::info!("Starting wave {}", wave + 1);
+            game_level_state.intermission = None;
+            self.last_broadcasted_intermission = None;
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::EndIntermission(wave),
+            );
+            return;
+        }
+
+        if self.last_broadcasted_intermission.as_ref() != Some(intermission) {
+            self.last_broadcasted_intermission = Some(intermission.clone());
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdateIntermission(intermission.clone()),
+            );
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn update_intermission(
+        &mut self,
+        game_state_helper: &GameStateHelper,
+        frame_number: u64,
+        _multiplayer_game_state: &MultiplayerGameState,
+        game_level_state: &mut GameLevelState,
+        wave_readiness: &mut WaveReadiness,
+        _: IntermissionSideData,
+    ) {
+        if !game_state_helper.is_authoritative() {
+            // The local ready-up is sent to the server by ClientNetworkSystem.
+            let ended_wave = wave_readiness.confirmed_end;
+            if let Some(intermission) = wave_readiness.confirmed.take() {
+                if ended_wave.map_or(true, |ended_wave| ended_wave < intermission.wave) {
+                    game_level_state.intermission = Some(intermission);
+                }
+            }
+            let has_ended = match (game_level_state.intermission.as_ref(), ended_wave) {
+                (Some(intermission), Some(ended_wave)) => intermission.wave <= ended_wave,
+                _ => false,
+            };
+            if has_ended {
+                game_level_state.intermission = None;
+            }
+            return;
+        }
+
+        let is_ready = std::mem::replace(&mut wave_readiness.local, false);
+        let has_ended = game_level_state
+            .intermission
+            .as_ref()
+            .map_or(false, |intermission| {
+                is_ready || frame_number >= intermission.ends_at_frame
+            });
+        if has_ended {
+            log::info!("Starting wave {}", game_level_state.spawn_level);
+            game_level_state.intermission = None;
+        }
+    }
+}
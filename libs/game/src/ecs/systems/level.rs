@@ -1,24 +1,36 @@
-use amethyst::ecs::{System, WriteExpect};
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
 use std::time::Duration;
 
 use gv_core::{
-    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType, SPAWN_TELEGRAPH_FRAMES},
     ecs::{
-        resources::{net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState},
+        components::affixes::MonsterAffix,
+        resources::{
+            net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState, Intermission,
+        },
         system_data::time::GameTimeService,
     },
     math::Vector2,
 };
 
 use crate::{
-    ecs::system_data::GameStateHelper,
+    ecs::{
+        resources::{ActiveLevelScript, ActiveObjectives, MatchSeed},
+        system_data::GameStateHelper,
+    },
     utils::world::{random_spawn_position, spawning_side},
 };
 
 const SECS_PER_LEVEL: u64 = 30;
+const INTERMISSION_FRAMES: u64 = 60 * 20;
 const MIN_BORDERLINE_INTERVAL_SECS: f32 = 30.0;
 const MAX_BORDERLINE_INTERVAL_SECS: f32 = 5.0;
+const ELITE_CHANCE_PER_LEVEL: f32 = 0.02;
+const MAX_ELITE_CHANCE: f32 = 0.25;
+/// Starting from this level elites can get two affixes.
+const DOUBLE_AFFIX_LEVEL: usize = 10;
 
 #[derive(Default)]
 pub struct LevelSystem;
@@ -27,7 +39,10 @@ impl<'s> System<'s> for LevelSystem {
     type SystemData = (
         GameStateHelper<'s>,
         GameTimeService<'s>,
+        ReadExpect<'s, MatchSeed>,
         WriteExpect<'s, GameLevelState>,
+        WriteExpect<'s, ActiveObjectives>,
+        WriteExpect<'s, ActiveLevelScript>,
         WriteExpect<'s, FramedUpdates<SpawnActions>>,
         WriteExpect<'s, EntityNetMetadataStorage>,
     );
@@ -37,7 +52,10 @@ impl<'s> System<'s> for LevelSystem {
         (
             game_state_helper,
             game_time_service,
+            match_seed,
             mut game_level_state,
+            mut active_objectives,
+            mut active_level_script,
             mut spawn_actions,
             mut entity_net_metadata_storage,
         ): Self::SystemData,
@@ -54,12 +72,62 @@ impl<'s> System<'s> for LevelSystem {
                     game_time_service.game_frame_number()
                 )
             });
+        let mut rng = match_seed.frame_rng(game_time_service.game_frame_number());
+
+        // Objectives replace waves, only their own monsters are spawned.
+        if let Some(objective_tracker) = active_objectives.0.as_mut() {
+            if !game_state_helper.is_multiplayer() {
+                if let Some(objective) = objective_tracker.take_started() {
+                    for _ in 0..objective.monsters_to_spawn {
+                        spawn_actions.spawn_actions.push(SpawnAction {
+                            spawn_type: SpawnType::Single {
+                                entity_net_id: None,
+                                position: random_spawn_position(&game_level_state, &mut rng),
+                                affixes: Vec::new(),
+                            },
+                            telegraph_frames: SPAWN_TELEGRAPH_FRAMES,
+                        });
+                    }
+                }
+                return;
+            }
+        }
+
+        // Waves spawned by the level script don't wait for intermissions.
+        let pending_spawns = std::mem::replace(&mut active_level_script.pending_spawns, 0);
+        for _ in 0..pending_spawns {
+            spawn_actions.spawn_actions.push(SpawnAction {
+                spawn_type: SpawnType::Single {
+                    entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
+                    position: random_spawn_position(&game_level_state, &mut rng),
+                    affixes: Vec::new(),
+                },
+                telegraph_frames: SPAWN_TELEGRAPH_FRAMES,
+            });
+        }
 
         let now = game_time_service.level_duration();
 
+        // The timers are frozen until the intermission ends.
+        if game_level_state.intermission.is_some() {
+            game_level_state.spawn_level_started = now;
+            game_level_state.last_borderline_spawn = now;
+            game_level_state.last_random_spawn = now;
+            return;
+        }
+
         if now - game_level_state.spawn_level_started > Duration::from_secs(SECS_PER_LEVEL) {
+            let frame_number = game_time_service.game_frame_number();
+            log::info!("Wave {} has ended", game_level_state.spawn_level);
+            game_level_state.intermission = Some(Intermission {
+                wave: game_level_state.spawn_level,
+                started_at_frame: frame_number,
+                ends_at_frame: frame_number + game_time_service.scale_frames(INTERMISSION_FRAMES),
+                ready_players: Vec::new(),
+            });
             game_level_state.spawn_level += 1;
             game_level_state.spawn_level_started = now;
+            return;
         }
 
         if game_time_service.game_frame_number() == 10 {
@@ -67,7 +135,9 @@ impl<'s> System<'s> for LevelSystem {
                 spawn_type: SpawnType::Single {
                     entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
                     position: Vector2::new(0.0, 300.0),
+                    affixes: Vec::new(),
                 },
+                telegraph_frames: SPAWN_TELEGRAPH_FRAMES,
             });
         }
 
@@ -80,7 +150,7 @@ impl<'s> System<'s> for LevelSystem {
         if now - game_level_state.last_borderline_spawn > borderline_spawn_interval {
             game_level_state.last_borderline_spawn = now;
 
-            let side = rand::random();
+            let side = rng.gen();
 
             let spawn_margin = 50.0;
             let (side_start, side_end, _) = spawning_side(side, &game_level_state);
@@ -103,6 +173,8 @@ impl<'s> System<'s> for LevelSystem {
                     entity_net_id_range,
                     side,
                 },
+                // Borderline monsters walk in from outside of the level.
+                telegraph_frames: 0,
             });
         }
 
@@ -118,10 +190,29 @@ impl<'s> System<'s> for LevelSystem {
                 spawn_actions.spawn_actions.push(SpawnAction {
                     spawn_type: SpawnType::Single {
                         entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
-                        position: random_spawn_position(&game_level_state),
+                        position: random_spawn_position(&game_level_state, &mut rng),
+                        affixes: roll_affixes(game_level_state.spawn_level, &mut rng),
                     },
+                    telegraph_frames: SPAWN_TELEGRAPH_FRAMES,
                 });
             }
         }
     }
 }
+
+/// Randomly turns monsters spawned one by one into elites, they get more common with levels.
+fn roll_affixes(spawn_level: usize, rng: &mut StdRng) -> Vec<MonsterAffix> {
+    let elite_chance = (spawn_level as f32 * ELITE_CHANCE_PER_LEVEL).min(MAX_ELITE_CHANCE);
+    if !rng.gen_bool(f64::from(elite_chance)) {
+        return Vec::new();
+    }
+    let affixes_count = if spawn_level >= DOUBLE_AFFIX_LEVEL && rng.gen_bool(0.5) {
+        2
+    } else {
+        1
+    };
+    MonsterAffix::ALL
+        .choose_multiple(rng, affixes_count)
+        .cloned()
+        .collect()
+}
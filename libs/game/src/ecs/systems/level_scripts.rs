@@ -0,0 +1,247 @@
+use amethyst::ecs::{Join, ReadStorage, System, WriteExpect};
+#[cfg(not(feature = "client"))]
+use amethyst::{
+    ecs::{ReadExpect, Write},
+    network::simulation::TransportResource,
+};
+
+#[cfg(feature = "client")]
+use gv_core::ecs::components::PlayerActions;
+#[cfg(not(feature = "client"))]
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::net::{EntityNetMetadataStorage, MultiplayerGameState},
+    },
+    net::server_message::ServerMessagePayload,
+};
+use gv_core::{
+    ecs::{
+        components::{Allegiance, Dead, Monster, Player, WorldPosition},
+        resources::GameLevelState,
+        system_data::time::GameTimeService,
+    },
+    escort::{PayloadProgress, PAYLOAD_BROADCAST_INTERVAL},
+    level_scripts::{open_door, ScriptAction, ScriptTick},
+    math::Vector2,
+};
+
+#[cfg(not(feature = "client"))]
+use crate::ecs::resources::InteractionRequests;
+use crate::ecs::{resources::ActiveLevelScript, system_data::GameStateHelper};
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+#[cfg(not(feature = "client"))]
+type LevelScriptBroadcast<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type LevelScriptBroadcast<'s> = ();
+
+#[cfg(not(feature = "client"))]
+type InteractionSideData<'s> = (
+    ReadExpect<'s, MultiplayerGameState>,
+    ReadExpect<'s, EntityNetMetadataStorage>,
+    WriteExpect<'s, InteractionRequests>,
+);
+#[cfg(feature = "client")]
+type InteractionSideData<'s> = ReadStorage<'s, PlayerActions>;
+
+/// Evaluates the level script on the authoritative side and broadcasts the fired actions.
+/// Multiplayer clients only apply the actions they receive from a server.
+#[derive(Default)]
+pub struct LevelScriptSystem;
+
+impl<'s> System<'s> for LevelScriptSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, ActiveLevelScript>,
+        WriteExpect<'s, GameLevelState>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, Dead>,
+        InteractionSideData<'s>,
+        LevelScriptBroadcast<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut active_level_script,
+            mut game_level_state,
+            players,
+            world_positions,
+            monsters,
+            allegiances,
+            dead,
+            interaction_side_data,
+            level_script_broadcast,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+        // Is drained even if a level has no script, so that requests don't pile up.
+        let interact_positions = self.interact_positions(&world_positions, interaction_side_data);
+        if active_level_script.runner.is_none() {
+            return;
+        }
+        let frame_number = game_time_service.game_frame_number();
+
+        #[cfg(feature = "client")]
+        {
+            if !game_state_helper.is_authoritative() {
+                if let Some(progress) = active_level_script.confirmed_payload.take() {
+                    let runner = active_level_script.runner.as_mut().unwrap();
+                    if let Some(payload) = runner.payload_mut() {
+                        payload.set_progress(progress);
+                    }
+                }
+                let actions = std::mem::replace(&mut active_level_script.confirmed, Vec::new());
+                apply_actions(
+                    actions,
+                    &mut active_level_script,
+                    &mut game_level_state,
+                    frame_number,
+                );
+                return;
+            }
+        }
+
+        let player_positions = (&players, &world_positions, !&dead)
+            .join()
+            .map(|(_, world_position, _)| **world_position)
+            .collect::<Vec<_>>();
+        // Minions dying don't count as kills.
+        let kills = (&monsters, !&allegiances, &dead)
+            .join()
+            .filter(|(_, _, dead)| dead.frame_acknowledged == frame_number)
+            .count();
+        let runner = active_level_script.runner.as_mut().unwrap();
+        let actions = runner.update(ScriptTick {
+            delta_seconds: game_time_service.tick_seconds(),
+            player_positions: &player_positions,
+            interact_positions: &interact_positions,
+            kills: kills as u32,
+            frame_number,
+        });
+        // Is sent along with the actions too, so that the bar doesn't lag behind the messages.
+        let payload_progress = runner
+            .payload()
+            .map(|payload| payload.progress())
+            .filter(|_| !actions.is_empty() || frame_number % PAYLOAD_BROADCAST_INTERVAL == 0);
+        self.broadcast(&actions, payload_progress, level_script_broadcast);
+        if actions.is_empty() {
+            return;
+        }
+        if runner.is_ended() && !game_state_helper.is_multiplayer() {
+            game_level_state.is_over = true;
+        }
+
+        apply_actions(
+            actions,
+            &mut active_level_script,
+            &mut game_level_state,
+            frame_number,
+        );
+    }
+}
+
+impl LevelScriptSystem {
+    #[cfg(not(feature = "client"))]
+    fn interact_positions(
+        &self,
+        world_positions: &ReadStorage<WorldPosition>,
+        (multiplayer_game_state, entity_net_metadata_storage, mut interaction_requests): InteractionSideData,
+    ) -> Vec<Vector2> {
+        interaction_requests
+            .received
+            .drain(..)
+            .filter_map(|connection_id| {
+                multiplayer_game_state
+                    .find_player_by_connection_id(connection_id)
+                    .and_then(|player| entity_net_metadata_storage.get_entity(player.entity_net_id))
+                    .and_then(|entity| world_positions.get(entity))
+                    .map(|world_position| **world_position)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "client")]
+    fn interact_positions(
+        &self,
+        world_positions: &ReadStorage<WorldPosition>,
+        player_actions: InteractionSideData,
+    ) -> Vec<Vector2> {
+        (&player_actions, world_positions)
+            .join()
+            .filter(|(player_actions, _)| player_actions.interact_action.is_some())
+            .map(|(_, world_position)| **world_position)
+            .collect()
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn broadcast(
+        &mut self,
+        actions: &[ScriptAction],
+        payload_progress: Option<PayloadProgress>,
+        (net_connection_models, mut transport): LevelScriptBroadcast,
+    ) {
+        if !actions.is_empty() {
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::LevelScriptActions(actions.to_vec()),
+            );
+        }
+        if let Some(progress) = payload_progress {
+            broadcast_message_reliable(
+                &mut transport,
+                (&net_connection_models).join(),
+                ServerMessagePayload::UpdatePayload(progress),
+            );
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn broadcast(
+        &mut self,
+        _actions: &[ScriptAction],
+        _payload_progress: Option<PayloadProgress>,
+        _: LevelScriptBroadcast,
+    ) {
+    }
+}
+
+fn apply_actions(
+    actions: Vec<ScriptAction>,
+    active_level_script: &mut ActiveLevelScript,
+    game_level_state: &mut GameLevelState,
+    frame_number: u64,
+) {
+    for action in actions {
+        match action {
+            ScriptAction::SpawnWave(count) => active_level_script.pending_spawns += count,
+            ScriptAction::OpenDoor(name) => {
+                if let Some(runner) = active_level_script.runner.as_ref() {
+                    open_door(runner.script(), &name, &mut game_level_state.collision_grid);
+                }
+                active_level_script.opened_doors.push(name);
+            }
+            ScriptAction::ActivateSwitch(name) => {
+                active_level_script.activated_switches.push(name);
+            }
+            ScriptAction::EndLevel => log::info!("The level script has ended the level"),
+            ScriptAction::ShowMessage(message) => {
+                active_level_script.message = Some((message, frame_number));
+            }
+        }
+    }
+}
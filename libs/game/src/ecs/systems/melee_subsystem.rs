@@ -0,0 +1,232 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage};
+
+use std::collections::HashMap;
+
+use gv_core::{
+    actions::player::CastSpell,
+    collision_layers::{CollisionLayer, CollisionMatrix},
+    economy::PlayerWallet,
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            Dead, EntityNetMetadata, Player, ThreatTable, WorldPosition,
+        },
+        resources::{
+            net::{CastActionToExecute, CastActionsToExecute},
+            MatchScore,
+        },
+    },
+    math::deterministic,
+    profile_scope,
+    room_settings::RoomSettings,
+    stats::Stat,
+};
+
+use crate::{
+    ecs::{
+        resources::{ClassDefinitions, CountedCrits, SpellDefinitions},
+        system_data::GameStateHelper,
+        systems::{monster::MobTargets, WriteExpectCell, WriteStorageCell},
+    },
+    utils::{
+        entities::{is_dead, player_stat_modifiers},
+        spatial_grid::SpatialGrid,
+        world::knockback_impulse,
+    },
+};
+
+const GRID_CELL_SIZE: f32 = 128.0;
+
+pub struct MeleeSubsystem<'s> {
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub spell_definitions: &'s ReadExpect<'s, SpellDefinitions>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub collisions: &'s CollisionMatrix,
+    pub player_wallets: &'s ReadStorage<'s, PlayerWallet>,
+    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub match_score: WriteExpectCell<'s, MatchScore>,
+    pub counted_crits: WriteExpectCell<'s, CountedCrits>,
+    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> MeleeSubsystem<'s> {
+    /// Takes the missile casts of classes with a melee swing out of CastActionsToExecute,
+    /// so MissileSpawnerSubsystem doesn't spawn missiles for them. A server inserts casts
+    /// at the frames clients made them on and re-simulates the world from there, so swings
+    /// are tested against the positions saved for that frame, which are what the attacker saw.
+    /// The cast only aims the swing: range, arc and the number of cleaved targets come
+    /// from the balance. Damage is added only on the authoritative side.
+    pub fn resolve_swings(&self, frame_number: u64, mob_targets: &MobTargets) {
+        profile_scope!("MeleeSubsystem::resolve_swings");
+        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
+        let (swings, other_casts): (Vec<_>, Vec<_>) = cast_actions_to_execute
+            .actions
+            .drain(..)
+            .partition(|cast_action| {
+                cast_action.action.action.spell == CastSpell::Missile
+                    && self
+                        .class_definitions
+                        .get(cast_action.caster_class)
+                        .melee
+                        .is_some()
+            });
+        cast_actions_to_execute.actions = other_casts;
+
+        if swings.is_empty() || !self.game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let players = self.players.borrow();
+        let world_positions = self.world_positions.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut threat_tables = self.threat_tables.borrow_mut();
+        let dead = self.dead.borrow();
+
+        let mut targets = Vec::new();
+        for layer in self.collisions.targets(CollisionLayer::Players) {
+            match layer {
+                CollisionLayer::Monsters => targets.extend(mob_targets.monsters.iter().cloned()),
+                CollisionLayer::Minions => targets.extend(mob_targets.minions.iter().cloned()),
+                CollisionLayer::Players => targets.extend(
+                    (self.entities, &*players)
+                        .join()
+                        .map(|(player, player_component)| (player, player_component.radius)),
+                ),
+                _ => {}
+            }
+        }
+        let mut targets_grid = SpatialGrid::new(GRID_CELL_SIZE);
+        let mut target_radii = HashMap::new();
+        let mut max_target_radius = 0.0f32;
+        for (target, radius) in targets {
+            let is_spawned = entity_net_metadata
+                .get(target)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            if !is_spawned || is_dead(target, &*dead, frame_number) {
+                continue;
+            }
+            if let Some(world_position) = world_positions.get(target) {
+                targets_grid.insert(target, **world_position);
+                target_radii.insert(target, radius);
+                max_target_radius = max_target_radius.max(radius);
+            }
+        }
+
+        for CastActionToExecute {
+            caster,
+            caster_class,
+            action: cast_action,
+            ..
+        } in swings
+        {
+            let class_definition = self.class_definitions.get(caster_class);
+            let melee = class_definition
+                .melee
+                .as_ref()
+                .expect("Expected a MeleeSpell");
+            let mut spell_definition = self.spell_definitions.missile(class_definition).clone();
+            let stat_modifiers = player_stat_modifiers(
+                caster,
+                self.player_wallets,
+                &self.room_settings.mutators,
+                frame_number,
+            );
+            spell_definition.damage =
+                stat_modifiers.value(Stat::MissileDamage, spell_definition.damage);
+            spell_definition.crit_chance =
+                stat_modifiers.value(Stat::CritChance, spell_definition.crit_chance);
+            spell_definition.crit_multiplier =
+                stat_modifiers.value(Stat::CritMultiplier, spell_definition.crit_multiplier);
+
+            let origin = cast_action.action.cast_position;
+            let mut direction = cast_action.action.target_position - origin;
+            if direction.norm_squared() == 0.0 {
+                direction = players
+                    .get(caster)
+                    .map_or(direction, |player| player.looking_direction);
+            }
+
+            let mut hits = targets_grid
+                .query_radius(origin, melee.range + max_target_radius)
+                .into_iter()
+                .filter(|target| *target != caster)
+                .filter_map(|target| {
+                    let radius = target_radii[&target];
+                    let position = **world_positions
+                        .get(target)
+                        .expect("Expected a WorldPosition");
+                    if melee.hits(origin, direction, position, radius) {
+                        Some((target, position, (position - origin).norm_squared()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            // Grid cells aren't ordered, entity ids break the ties between equal distances.
+            hits.sort_by(|(a, _, a_distance), (b, _, b_distance)| {
+                a_distance
+                    .partial_cmp(b_distance)
+                    .expect("Expected comparable distances")
+                    .then(a.id().cmp(&b.id()))
+            });
+            hits.truncate(melee.max_targets);
+
+            for (i, (target, position, _)) in hits.into_iter().enumerate() {
+                let index = i as u64 * 2;
+                let variance =
+                    deterministic::roll(frame_number, cast_action.action_id, index) * 2.0 - 1.0;
+                let is_crit = deterministic::roll(frame_number, cast_action.action_id, index + 1)
+                    < spell_definition.crit_chance;
+                let mut damage =
+                    spell_definition.damage * (1.0 + spell_definition.damage_variance * variance);
+                if is_crit {
+                    damage *= spell_definition.crit_multiplier;
+                    self.count_crit(cast_action.action_id, i, frame_number);
+                }
+                damage_histories
+                    .get_mut(target)
+                    .expect("Expected a DamageHistory")
+                    .add_entry(
+                        frame_number,
+                        DamageHistoryEntry {
+                            damage,
+                            impulse: knockback_impulse(
+                                origin,
+                                position,
+                                spell_definition.knockback,
+                            ),
+                            is_crit,
+                            damage_type: Some(spell_definition.damage_type),
+                        },
+                    );
+                if let Some(threat_table) = threat_tables.get_mut(target) {
+                    threat_table.add(caster, damage);
+                }
+            }
+        }
+    }
+
+    /// The server swings again each time it rewinds past a cast, so critical hits
+    /// are counted in MatchScore once per cleaved target.
+    fn count_crit(&self, action_id: u64, hit: usize, frame_number: u64) {
+        // Action ids are unique only in multiplayer, but single player never rewinds.
+        let is_new_crit = !self.game_state_helper.is_multiplayer()
+            || self
+                .counted_crits
+                .borrow_mut()
+                .count((action_id, 0, hit), frame_number);
+        if is_new_crit {
+            self.match_score.borrow_mut().critical_hits += 1;
+        }
+    }
+}
@@ -1,13 +1,19 @@
-use amethyst::ecs::{Entities, Join, ReadStorage, System};
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, Entity, Join, System, WriteExpect, WriteStorage},
+};
 
 use gv_core::ecs::{
-    components::{missile::Missile, Dead},
+    components::{missile::Missile, Dead, WorldPosition},
     system_data::time::GameTimeService,
 };
 
-use crate::ecs::system_data::GameStateHelper;
+use crate::ecs::{resources::MissilePool, system_data::GameStateHelper};
 
-pub struct MissileDyingSystem;
+#[derive(Default)]
+pub struct MissileDyingSystem {
+    dying_missiles: Vec<Entity>,
+}
 
 pub const MISSILE_TTL_SECS: f32 = 0.35;
 
@@ -16,26 +22,45 @@ impl<'s> System<'s> for MissileDyingSystem {
         GameStateHelper<'s>,
         GameTimeService<'s>,
         Entities<'s>,
-        ReadStorage<'s, Dead>,
-        ReadStorage<'s, Missile>,
+        WriteExpect<'s, MissilePool>,
+        WriteStorage<'s, Dead>,
+        WriteStorage<'s, Missile>,
+        WriteStorage<'s, WorldPosition>,
+        WriteStorage<'s, Transform>,
     );
 
     fn run(
         &mut self,
-        (game_state_helper, game_time_service, entities, dead, missiles): Self::SystemData,
+        (
+            game_state_helper,
+            game_time_service,
+            entities,
+            mut missile_pool,
+            mut dead,
+            mut missiles,
+            mut world_positions,
+            mut transforms,
+        ): Self::SystemData,
     ) {
         if !game_state_helper.is_running() {
             return;
         }
 
+        self.dying_missiles.clear();
         for (missile_entity, dead, _) in (&entities, &dead, &missiles).join() {
             let to_be_deleted =
                 game_time_service.seconds_to_frame(dead.dead_since_frame) > MISSILE_TTL_SECS;
             if to_be_deleted {
-                entities
-                    .delete(missile_entity)
-                    .expect("Expected to delete a Missile");
+                self.dying_missiles.push(missile_entity);
             }
         }
+
+        for missile_entity in self.dying_missiles.drain(..) {
+            dead.remove(missile_entity);
+            missiles.remove(missile_entity);
+            world_positions.remove(missile_entity);
+            transforms.remove(missile_entity);
+            missile_pool.release(missile_entity, game_time_service.game_frame_number());
+        }
     }
 }
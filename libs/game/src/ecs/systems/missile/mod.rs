@@ -4,9 +4,6 @@ mod spawner_subsystem;
 
 pub use self::{
     dying::{MissileDyingSystem, MISSILE_TTL_SECS},
-    physics_subsystem::{
-        MissilePhysicsSubsystem, MISSILE_LIFESPAN_SECS, MISSILE_MAX_SPEED, MISSILE_MIN_SPEED,
-        MISSILE_TIME_TO_FADE,
-    },
+    physics_subsystem::MissilePhysicsSubsystem,
     spawner_subsystem::{MissileFactory, MissileSpawnerSubsystem},
 };
@@ -1,59 +1,81 @@
 use amethyst::{
-    core::math::{clamp, Rotation2},
-    ecs::{Entities, Join, ReadExpect},
+    core::math::clamp,
+    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage},
 };
 use gv_core::profile_scope;
 
-use gv_core::ecs::{
-    components::{
-        damage_history::{DamageHistory, DamageHistoryEntry},
-        missile::{Missile, MissileTarget},
-        Dead, Monster, WorldPosition,
+use gv_core::{
+    augments::{CHAIN_DAMAGE_FACTOR, CHAIN_RADIUS},
+    collision_layers::{CollisionLayer, CollisionMatrix},
+    economy::PlayerWallet,
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            missile::{Missile, MissileTarget},
+            Allegiance, Dead, Monster, Player, ThreatTable, WorldPosition,
+        },
+        resources::{GameLevelState, MatchScore},
+        system_data::time::GameTimeService,
     },
-    resources::GameLevelState,
-    system_data::time::GameTimeService,
+    math::{deterministic, Vector2, ZeroVector},
+    room_settings::RoomSettings,
+    stats::Stat,
 };
 
 use crate::{
-    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    ecs::{
+        resources::CountedCrits,
+        system_data::GameStateHelper,
+        systems::{WriteExpectCell, WriteStorageCell},
+    },
+    scripting::ScriptHooks,
     utils::{
-        entities::{is_dead, missile_energy},
-        world::{closest_monster, find_first_hit_monster, random_scene_position},
+        entities::{is_dead, missile_energy, player_stat_modifiers},
+        world::{
+            closest_monster, find_first_hit_monster, knockback_impulse,
+            move_bouncing_off_level_borders, random_scene_position, reflect,
+        },
     },
 };
 
-pub const MISSILE_MAX_SPEED: f32 = 300.0;
-pub const MISSILE_MIN_SPEED: f32 = 80.0;
-pub const MISSILE_TIME_TO_FADE: f32 = 0.5;
-pub const MISSILE_LIFESPAN_SECS: f32 = 5.0;
-
-const MS_PER_FRAME: f32 = 1000.0 / 60.0;
+/// Missiles bouncing off an obstacle are put this far from it.
+const OBSTACLE_MARGIN: f32 = 0.01;
+/// Missiles hitting other players with friendly fire enabled deal this share of their damage.
+const FRIENDLY_FIRE_DAMAGE_FACTOR: f32 = 0.5;
 
 const TIME_TO_ACCELERATE: f32 = 2000.0;
-const MISSILE_ACCELERATION: f32 =
-    (MISSILE_MAX_SPEED - MISSILE_MIN_SPEED) / TIME_TO_ACCELERATE * MS_PER_FRAME;
 const TIME_TO_ROTATE: f32 = 1000.0;
-const MAX_ROTATION: f32 = std::f32::consts::PI / TIME_TO_ROTATE * MS_PER_FRAME;
 
 pub struct MissilePhysicsSubsystem<'s> {
     pub game_time_service: &'s GameTimeService<'s>,
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub script_hooks: &'s ReadExpect<'s, ScriptHooks>,
     pub entities: &'s Entities<'s>,
+    pub allegiances: &'s ReadStorage<'s, Allegiance>,
+    pub collisions: &'s CollisionMatrix,
+    pub player_wallets: &'s ReadStorage<'s, PlayerWallet>,
+    pub players: WriteStorageCell<'s, Player>,
     pub monsters: WriteStorageCell<'s, Monster>,
     pub missiles: WriteStorageCell<'s, Missile>,
     pub dead: WriteStorageCell<'s, Dead>,
     pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub match_score: WriteExpectCell<'s, MatchScore>,
+    pub counted_crits: WriteExpectCell<'s, CountedCrits>,
 }
 
 impl<'s> MissilePhysicsSubsystem<'s> {
     pub fn process_physics(&self, frame_number: u64) {
         profile_scope!("MissilePhysicsSubsystem::process_physics");
+        let players = self.players.borrow();
         let monsters = self.monsters.borrow();
         let mut missiles = self.missiles.borrow_mut();
         let mut dead = self.dead.borrow_mut();
         let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut threat_tables = self.threat_tables.borrow_mut();
         let mut world_positions = self.world_positions.borrow_mut();
 
         for (missile_entity, mut missile) in (self.entities, &mut *missiles).join() {
@@ -82,15 +104,23 @@ impl<'s> MissilePhysicsSubsystem<'s> {
 
             let (destination, new_target) = match missile.target {
                 MissileTarget::Target(target) => {
-                    if let Some(target_position) = world_positions.get(target) {
+                    let target_position = if missile.hit_monsters.contains(&target) {
+                        None
+                    } else {
+                        world_positions.get(target)
+                    };
+                    if let Some(target_position) = target_position {
                         (**target_position, None)
                     } else if let Some((target, target_position)) = closest_monster(
                         missile_position,
                         &world_positions,
                         &self.entities,
                         &monsters,
+                        self.allegiances,
+                        self.collisions,
                         &*dead,
                         frame_number,
+                        &missile.hit_monsters,
                     ) {
                         (target_position, Some(MissileTarget::Target(target)))
                     } else {
@@ -107,8 +137,11 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                         &world_positions,
                         &self.entities,
                         &monsters,
+                        self.allegiances,
+                        self.collisions,
                         &*dead,
                         frame_number,
+                        &missile.hit_monsters,
                     ) {
                         (target_position, Some(MissileTarget::Target(target)))
                     } else if (destination - missile_position).norm_squared()
@@ -128,28 +161,183 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                 missile.target = new_target;
             }
 
+            let hits_players = self.room_settings.friendly_fire
+                || self
+                    .collisions
+                    .hits(CollisionLayer::Projectiles, CollisionLayer::Players);
+            // Hits are tested along the whole way a missile is going to move during the frame,
+            // so fast missiles can't fly through anything at low tick rates.
+            let frame_movement = missile.velocity * self.game_time_service.tick_seconds();
+            if hits_players && missile_energy >= 1.0 {
+                let hit_player = (self.entities, &*players, &*world_positions)
+                    .join()
+                    .filter(|(player_entity, _, _)| {
+                        *player_entity != missile.caster
+                            && !is_dead(*player_entity, &*dead, frame_number)
+                    })
+                    .filter_map(|(player_entity, player, player_position)| {
+                        deterministic::sweep_circle(
+                            missile_position,
+                            frame_movement,
+                            missile.radius + player.radius,
+                            **player_position,
+                        )
+                        .map(|share| (player_entity, **player_position, share))
+                    })
+                    .fold(
+                        None,
+                        |first_hit: Option<(Entity, Vector2, f32)>, hit| match first_hit {
+                            Some((_, _, first_share)) if first_share <= hit.2 => first_hit,
+                            _ => Some(hit),
+                        },
+                    );
+                if let Some((hit_player, hit_player_position, share)) = hit_player {
+                    let hit_position = missile_position + frame_movement * share;
+                    if self.game_state_helper.is_authoritative() {
+                        damage_histories
+                            .get_mut(hit_player)
+                            .expect("Expected a DamageHistory")
+                            .add_entry(
+                                frame_number,
+                                DamageHistoryEntry {
+                                    damage: missile.damage * FRIENDLY_FIRE_DAMAGE_FACTOR,
+                                    impulse: knockback_impulse(
+                                        hit_position,
+                                        hit_player_position,
+                                        missile.knockback,
+                                    ),
+                                    is_crit: false,
+                                    damage_type: Some(missile.damage_type),
+                                },
+                            );
+                    }
+                    **world_positions
+                        .get_mut(missile_entity)
+                        .expect("Expected a Missile") = hit_position;
+
+                    let dead_since_frame = frame_number + 1;
+                    let frame_acknowledged =
+                        dead_since_frame.max(self.game_time_service.game_frame_number());
+                    dead.insert(
+                        missile_entity,
+                        Dead::new(dead_since_frame, frame_acknowledged),
+                    )
+                    .expect("Expected to insert a Dead component");
+                    continue;
+                }
+            }
+
             let direction = if let MissileTarget::Target(target) = missile.target {
                 if missile_energy >= 1.0 {
-                    if let Some(hit_monster) = find_first_hit_monster(
+                    if let Some((hit_monster, hit_position)) = find_first_hit_monster(
                         missile_position,
                         missile.radius,
+                        frame_movement,
                         &monsters,
                         &world_positions,
+                        self.allegiances,
+                        self.collisions,
                         &self.entities,
                         &*dead,
                         frame_number,
+                        &missile.hit_monsters,
                     ) {
                         if self.game_state_helper.is_authoritative() {
+                            let hit_monster_position = **world_positions
+                                .get(hit_monster)
+                                .expect("Expected a WorldPosition for a Monster");
+                            let monster =
+                                monsters.get(hit_monster).expect("Expected a hit Monster");
+                            let damage_roll = missile.roll_damage(frame_number);
+                            let damage = self.script_hooks.on_hit(
+                                frame_number,
+                                &monster.name,
+                                monster.health,
+                                damage_roll.damage,
+                            );
                             damage_histories
                                 .get_mut(hit_monster)
                                 .expect("Expected a DamageHistory")
                                 .add_entry(
                                     frame_number,
                                     DamageHistoryEntry {
-                                        damage: missile.damage,
+                                        damage,
+                                        impulse: knockback_impulse(
+                                            hit_position,
+                                            hit_monster_position,
+                                            missile.knockback,
+                                        ),
+                                        is_crit: damage_roll.is_crit,
+                                        damage_type: Some(missile.damage_type),
                                     },
                                 );
+                            if damage_roll.is_crit {
+                                self.count_crit(&missile, frame_number);
+                            }
+                            if let Some(threat_table) = threat_tables.get_mut(hit_monster) {
+                                threat_table.add(missile.caster, damage);
+                            }
+                            if players.get(missile.caster).is_some() {
+                                let life_steal = player_stat_modifiers(
+                                    missile.caster,
+                                    self.player_wallets,
+                                    &self.room_settings.mutators,
+                                    frame_number,
+                                )
+                                .value(Stat::LifeSteal, 0.0);
+                                if life_steal > 0.0 {
+                                    damage_histories
+                                        .get_mut(missile.caster)
+                                        .expect("Expected a DamageHistory")
+                                        .add_entry(
+                                            frame_number,
+                                            DamageHistoryEntry {
+                                                damage: -damage * life_steal,
+                                                impulse: Vector2::zero(),
+                                                is_crit: false,
+                                                damage_type: None,
+                                            },
+                                        );
+                                }
+                            }
                         }
+                        missile.hit_monsters.push(hit_monster);
+                        **world_positions
+                            .get_mut(missile_entity)
+                            .expect("Expected a Missile") = hit_position;
+
+                        // Chaining goes first, as it doesn't let a missile fly past its target.
+                        let chain_target = if missile.chains_left > 0 {
+                            closest_monster(
+                                hit_position,
+                                &world_positions,
+                                &self.entities,
+                                &monsters,
+                                self.allegiances,
+                                self.collisions,
+                                &*dead,
+                                frame_number,
+                                &missile.hit_monsters,
+                            )
+                            .filter(|(_, target_position)| {
+                                (target_position - hit_position).norm_squared()
+                                    <= CHAIN_RADIUS * CHAIN_RADIUS
+                            })
+                        } else {
+                            None
+                        };
+                        if let Some((chain_target, _)) = chain_target {
+                            missile.chains_left -= 1;
+                            missile.damage *= CHAIN_DAMAGE_FACTOR;
+                            missile.target = MissileTarget::Target(chain_target);
+                            continue;
+                        }
+                        if missile.pierces_left > 0 {
+                            // The missile picks a new target on the next frame.
+                            missile.pierces_left -= 1;
+                            continue;
+                        }
+
                         let dead_since_frame = frame_number + 1;
                         let frame_acknowledged =
                             dead_since_frame.max(self.game_time_service.game_frame_number());
@@ -166,24 +354,94 @@ impl<'s> MissilePhysicsSubsystem<'s> {
             } else {
                 destination
             };
-            let needed_angle = Rotation2::rotation_between(&missile.velocity, &direction).angle();
-            let angle = needed_angle.abs().min(MAX_ROTATION) * needed_angle.signum();
+            let ms_per_frame = self.game_time_service.tick_seconds() * 1000.0;
+            let missile_acceleration =
+                (missile.max_speed - missile.min_speed) / TIME_TO_ACCELERATE * ms_per_frame;
+            let max_rotation = std::f32::consts::PI / TIME_TO_ROTATE * ms_per_frame;
+            let needed_angle = deterministic::angle_between(&missile.velocity, &direction);
+            let angle = needed_angle.abs().min(max_rotation) * needed_angle.signum();
             let a = if needed_angle.abs() > angle.abs() {
-                -MISSILE_ACCELERATION
+                -missile_acceleration
             } else {
-                MISSILE_ACCELERATION
+                missile_acceleration
             };
-            let current_speed = missile.velocity.norm();
-            let speed = clamp(current_speed + a, MISSILE_MIN_SPEED, MISSILE_MAX_SPEED);
-            let new_direction = Rotation2::new(angle) * missile.velocity.normalize();
+            let current_speed = deterministic::norm(&missile.velocity);
+            let speed = clamp(current_speed + a, missile.min_speed, missile.max_speed);
+            let new_direction =
+                deterministic::rotate(&deterministic::normalize(&missile.velocity), angle);
 
             missile.velocity = new_direction * speed;
 
             let missile_position = world_positions
                 .get_mut(missile_entity)
                 .expect("Expected a Missile");
-            **missile_position +=
-                missile.velocity * self.game_time_service.engine_time().fixed_seconds();
+            let frame_seconds = self.game_time_service.tick_seconds();
+            let moved_position = **missile_position + missile.velocity * frame_seconds;
+            let obstacle_hit = if self
+                .collisions
+                .hits(CollisionLayer::Projectiles, CollisionLayer::Props)
+            {
+                self.game_level_state
+                    .collision_grid
+                    .raycast(**missile_position, moved_position)
+            } else {
+                None
+            };
+            if let Some(obstacle_hit) = obstacle_hit {
+                // Obstacles stop missiles, unless they can bounce off them.
+                if missile.bounces_left > 0 && obstacle_hit.normal != Vector2::zero() {
+                    missile.bounces_left -= 1;
+                    missile.velocity = reflect(missile.velocity, obstacle_hit.normal);
+                    **missile_position =
+                        obstacle_hit.position + obstacle_hit.normal * OBSTACLE_MARGIN;
+                } else {
+                    **missile_position = obstacle_hit.position;
+                    let dead_since_frame = frame_number + 1;
+                    let frame_acknowledged =
+                        dead_since_frame.max(self.game_time_service.game_frame_number());
+                    dead.insert(
+                        missile_entity,
+                        Dead::new(dead_since_frame, frame_acknowledged),
+                    )
+                    .expect("Expected to insert a Dead component");
+                }
+                continue;
+            }
+
+            // Missiles chasing monsters that haven't walked into the level yet fly through borders.
+            let half_size = self.game_level_state.dimensions_half_size();
+            let is_chasing_outside = if let MissileTarget::Target(_) = missile.target {
+                destination.x.abs() > half_size.x || destination.y.abs() > half_size.y
+            } else {
+                false
+            };
+            if missile.bounces_left > 0 && !is_chasing_outside {
+                let has_bounced = move_bouncing_off_level_borders(
+                    &mut **missile_position,
+                    &mut missile.velocity,
+                    frame_seconds,
+                    &*self.game_level_state,
+                );
+                if has_bounced {
+                    missile.bounces_left -= 1;
+                }
+            } else {
+                **missile_position += missile.velocity * frame_seconds;
+            }
+        }
+    }
+
+    /// The server rolls a hit again each time it rewinds past it, so critical hits
+    /// are counted in MatchScore once per missile hit.
+    fn count_crit(&self, missile: &Missile, frame_number: u64) {
+        // Action ids are unique only in multiplayer, but single player never rewinds.
+        let is_new_crit = !self.game_state_helper.is_multiplayer()
+            || self.counted_crits.borrow_mut().count(
+                (missile.action_id, missile.shot, missile.hit_monsters.len()),
+                frame_number,
+            );
+        if is_new_crit {
+            self.match_score.borrow_mut().critical_hits += 1;
         }
     }
 }
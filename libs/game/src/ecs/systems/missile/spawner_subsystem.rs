@@ -1,34 +1,45 @@
 use amethyst::{
     core::Transform,
-    ecs::{Entities, Entity, WriteStorage},
+    ecs::{Entities, Entity, ReadExpect, ReadStorage, WriteStorage},
 };
 use gv_core::profile_scope;
 
 use gv_core::{
     actions::IdentifiableAction,
+    augments::{PlayerAugments, MULTISHOT_SPREAD},
+    collision_layers::CollisionMatrix,
+    economy::PlayerWallet,
     ecs::{
-        components::{missile::*, Dead, Monster, WorldPosition},
-        resources::net::CastActionsToExecute,
+        components::{missile::*, Allegiance, Dead, Monster, WorldPosition},
+        resources::net::{CastActionToExecute, CastActionsToExecute},
         system_data::time::GameTimeService,
     },
-    math::Vector2,
+    math::{deterministic, Vector2},
+    room_settings::RoomSettings,
+    stats::Stat,
 };
 
 use crate::{
     ecs::{
+        resources::{ClassDefinitions, MissilePool, SpellDefinition, SpellDefinitions},
         system_data::GameStateHelper,
-        systems::{
-            missile::physics_subsystem::MISSILE_MAX_SPEED, GraphicsResourceBundle, WriteExpectCell,
-            WriteStorageCell,
-        },
+        systems::{GraphicsResourceBundle, WriteExpectCell, WriteStorageCell},
     },
-    utils::world::closest_monster,
+    scripting::ScriptHooks,
+    utils::{entities::player_stat_modifiers, world::closest_monster},
 };
 
 pub struct MissileSpawnerSubsystem<'a, 's> {
     pub game_time_service: &'s GameTimeService<'s>,
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub entities: &'s Entities<'s>,
+    pub allegiances: &'s ReadStorage<'s, Allegiance>,
+    pub collisions: &'s CollisionMatrix,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub spell_definitions: &'s ReadExpect<'s, SpellDefinitions>,
+    pub script_hooks: &'s ReadExpect<'s, ScriptHooks>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub player_wallets: &'s ReadStorage<'s, PlayerWallet>,
     pub missile_factory: &'a MissileFactory<'a, 's>,
     pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
     pub monsters: WriteStorageCell<'s, Monster>,
@@ -45,9 +56,15 @@ impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
         let monsters = self.monsters.borrow();
 
         for cast_action in cast_actions_to_execute.actions.drain(..) {
-            let IdentifiableAction {
-                action_id,
-                action: cast_action,
+            let CastActionToExecute {
+                caster,
+                caster_class,
+                caster_augments,
+                action:
+                    IdentifiableAction {
+                        action_id,
+                        action: cast_action,
+                    },
             } = cast_action;
 
             let search_result = closest_monster(
@@ -55,8 +72,11 @@ impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
                 &*world_positions,
                 &self.entities,
                 &*monsters,
+                self.allegiances,
+                self.collisions,
                 &*dead,
                 frame_number,
+                &[],
             );
 
             let target = if let Some((monster, _)) = search_result {
@@ -64,24 +84,57 @@ impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
             } else {
                 MissileTarget::Destination(cast_action.target_position)
             };
-            let direction = cast_action.target_position - cast_action.cast_position;
-            let velocity = direction.normalize() * MISSILE_MAX_SPEED;
-
-            self.missile_factory.create(
-                action_id,
-                &mut *world_positions,
-                5.0,
-                target,
-                velocity,
+            let mut spell_definition = self
+                .spell_definitions
+                .missile(self.class_definitions.get(caster_class))
+                .clone();
+            let stat_modifiers = player_stat_modifiers(
+                caster,
+                self.player_wallets,
+                &self.room_settings.mutators,
                 frame_number,
-                cast_action.cast_position,
             );
+            spell_definition.damage =
+                stat_modifiers.value(Stat::MissileDamage, spell_definition.damage);
+            spell_definition.crit_chance =
+                stat_modifiers.value(Stat::CritChance, spell_definition.crit_chance);
+            spell_definition.crit_multiplier =
+                stat_modifiers.value(Stat::CritMultiplier, spell_definition.crit_multiplier);
+            self.script_hooks
+                .on_missile_cast(frame_number, caster_class, &mut spell_definition);
+            let direction = cast_action.target_position - cast_action.cast_position;
+            let velocity = deterministic::normalize(&direction) * spell_definition.max_speed;
+
+            // Multishot missiles are spread evenly around the aimed direction,
+            // so that every side computes the same velocities.
+            let missile_count = caster_augments.missile_count();
+            for i in 0..missile_count {
+                let angle = (i as f32 - (missile_count - 1) as f32 / 2.0) * MULTISHOT_SPREAD;
+                self.missile_factory.create(
+                    action_id,
+                    i,
+                    caster,
+                    &mut *world_positions,
+                    &spell_definition,
+                    &caster_augments,
+                    target.clone(),
+                    deterministic::rotate(&velocity, angle),
+                    frame_number,
+                    cast_action.cast_position,
+                );
+            }
         }
     }
 }
 
+#[cfg(feature = "client")]
+const MISSILE_Z: f32 = 50.0;
+#[cfg(not(feature = "client"))]
+const MISSILE_Z: f32 = 0.0;
+
 pub struct MissileFactory<'a, 's> {
     entities: &'s Entities<'s>,
+    missile_pool: WriteExpectCell<'s, MissilePool>,
     transforms: WriteStorageCell<'s, Transform>,
     missiles: WriteStorageCell<'s, Missile>,
     //#[cfg_attr(not(feature = "client"), allow(dead_code))]
@@ -92,65 +145,63 @@ pub struct MissileFactory<'a, 's> {
 impl<'a, 's> MissileFactory<'a, 's> {
     pub fn new(
         entities: &'s Entities<'s>,
+        missile_pool: WriteExpectCell<'s, MissilePool>,
         transforms: WriteStorageCell<'s, Transform>,
         missiles: WriteStorageCell<'s, Missile>,
         graphics_resource_bundle: &'a GraphicsResourceBundle<'s>,
     ) -> Self {
         Self {
             entities,
+            missile_pool,
             transforms,
             missiles,
             graphics_resource_bundle,
         }
     }
 
-    #[cfg(feature = "client")]
-    pub fn create(
-        &self,
-        action_id: u64,
-        world_positions: &mut WriteStorage<'s, WorldPosition>,
-        radius: f32,
-        target: MissileTarget<Entity>,
-        velocity: Vector2,
-        frame_spawned: u64,
-        position: Vector2,
-    ) -> Entity {
-        let mut transform = Transform::default();
-        transform.set_translation_xyz(position.x, position.y, 50.0);
-
-        self.entities
-            .build_entity()
-            .with(transform, &mut self.transforms.borrow_mut())
-            .with(WorldPosition::new(position), world_positions)
-            .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
-                &mut self.missiles.borrow_mut(),
-            )
-            .build()
-    }
-
-    #[cfg(not(feature = "client"))]
     pub fn create(
         &self,
         action_id: u64,
+        shot: u32,
+        caster: Entity,
         world_positions: &mut WriteStorage<'s, WorldPosition>,
-        radius: f32,
+        spell_definition: &SpellDefinition,
+        augments: &PlayerAugments,
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
         position: Vector2,
     ) -> Entity {
         let mut transform = Transform::default();
-        transform.set_translation_xyz(position.x, position.y, 0.0);
+        transform.set_translation_xyz(position.x, position.y, MISSILE_Z);
 
-        self.entities
-            .build_entity()
-            .with(transform, &mut self.transforms.borrow_mut())
-            .with(WorldPosition::new(position), world_positions)
-            .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
-                &mut self.missiles.borrow_mut(),
+        let entity = self
+            .missile_pool
+            .borrow_mut()
+            .acquire(self.entities, frame_spawned);
+        self.transforms
+            .borrow_mut()
+            .insert(entity, transform)
+            .expect("Expected to insert a Transform");
+        world_positions
+            .insert(entity, WorldPosition::new(position))
+            .expect("Expected to insert a WorldPosition");
+        self.missiles
+            .borrow_mut()
+            .insert(
+                entity,
+                Missile::new(
+                    action_id,
+                    shot,
+                    caster,
+                    spell_definition,
+                    target,
+                    velocity,
+                    frame_spawned,
+                )
+                .with_augments(augments),
             )
-            .build()
+            .expect("Expected to insert a Missile");
+        entity
     }
 }
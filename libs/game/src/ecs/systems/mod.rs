@@ -1,23 +1,58 @@
 pub mod missile;
 pub mod monster;
 pub mod player;
+pub mod zone;
 
 mod action;
+mod augments;
+mod aura_subsystem;
+mod bounds_subsystem;
+mod console;
+mod crash_context;
 mod damage_subsystem;
+mod economy;
+mod frame_timings;
+mod game_events;
+mod heal_subsystem;
+mod intermission;
 mod level;
+mod level_scripts;
+mod melee_subsystem;
 mod net_connection_manager;
+mod objectives;
 mod pause;
+mod rendezvous;
+mod score;
 mod state_switcher;
+mod summon;
+mod threat_subsystem;
 mod world_position_transform;
 mod world_state_subsystem;
 
 pub use self::{
     action::ActionSystem,
+    augments::AugmentSystem,
+    aura_subsystem::{AuraSlows, AuraSubsystem},
+    bounds_subsystem::BoundsSubsystem,
+    console::{ConsoleSpawnSystem, ConsoleSystem},
+    crash_context::CrashContextSystem,
     damage_subsystem::DamageSubsystem,
+    economy::EconomySystem,
+    frame_timings::{FrameTimingsSystem, Timed},
+    game_events::GameEventSystem,
+    heal_subsystem::HealSubsystem,
+    intermission::IntermissionSystem,
     level::LevelSystem,
+    level_scripts::LevelScriptSystem,
+    melee_subsystem::MeleeSubsystem,
     net_connection_manager::{NetConnectionManagerDesc, NetConnectionManagerSystem},
+    objectives::ObjectiveSystem,
     pause::PauseSystem,
+    rendezvous::{RendezvousDesc, RendezvousRelaySystem, RendezvousSystem},
+    score::ScoreSystem,
     state_switcher::StateSwitcherSystem,
+    summon::{SummonSystem, MAX_MINIONS_PER_PLAYER, MINION_LIFETIME_FRAMES},
+    threat_subsystem::ThreatSubsystem,
     world_position_transform::WorldPositionTransformSystem,
     world_state_subsystem::WorldStateSubsystem,
 };
@@ -50,7 +85,7 @@ use gv_core::ecs::resources::world::{
 };
 
 #[cfg(feature = "client")]
-use crate::utils::entities::{play_animation, remove_animation};
+use crate::utils::entities::{play_animation, remove_animation, set_animation_input};
 
 #[cfg(feature = "client")]
 pub type AggregatedOutcomingUpdates = ClientWorldUpdates;
@@ -182,4 +217,37 @@ impl<'s> AnimationsResourceBundle<'s> {
 
     #[cfg(not(feature = "client"))]
     fn play_animation(&self, _entity: Entity, _body_part_name: &str, _animation_id: AnimationId) {}
+
+    /// Plays an animation as if it had started `start_secs` ago.
+    #[cfg(feature = "client")]
+    fn play_animation_from(
+        &self,
+        entity: Entity,
+        body_part_name: &str,
+        animation_id: AnimationId,
+        start_secs: f32,
+    ) {
+        self.play_animation(entity, body_part_name, animation_id);
+        if start_secs > 0.0 {
+            set_animation_input(
+                &self.parent_hierarchy,
+                &self.named,
+                &mut self.animation_control_sets.borrow_mut(),
+                entity,
+                body_part_name,
+                animation_id,
+                start_secs,
+            );
+        }
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn play_animation_from(
+        &self,
+        _entity: Entity,
+        _body_part_name: &str,
+        _animation_id: AnimationId,
+        _start_secs: f32,
+    ) {
+    }
 }
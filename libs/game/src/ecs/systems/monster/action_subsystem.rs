@@ -1,4 +1,6 @@
-use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, WriteStorage};
+use amethyst::ecs::{
+    storage::GenericReadStorage, Entities, Entity, Join, ReadExpect, ReadStorage, WriteStorage,
+};
 use gv_core::profile_scope;
 
 use gv_animation_prefabs::{AnimationId, MONSTER_BODY};
@@ -7,45 +9,107 @@ use gv_core::{
         mob::{MobAction, MobAttackAction, MobAttackType},
         Action,
     },
+    collision_layers::{CollisionLayer, CollisionMatrix},
     ecs::{
         components::{
+            affixes::MonsterAffixes,
             damage_history::{DamageHistory, DamageHistoryEntry},
-            ClientPlayerActions, EntityNetMetadata, Monster, NetWorldPosition, Player,
-            WorldPosition,
+            Allegiance, ClientPlayerActions, Dead, EntityNetMetadata, Impulse, Monster,
+            NetWorldPosition, Player, ThreatTable, WorldPosition,
         },
-        resources::GameLevelState,
+        resources::{collision::LevelCollisionGrid, GameLevelState},
         system_data::time::GameTimeService,
     },
-    math::{Vector2, ZeroVector},
+    level_scripts::push_out_of_safe_zones,
+    math::{deterministic, Vector2, ZeroVector},
     net::{NetIdentifier, NetUpdateWithPosition},
+    room_settings::RoomSettings,
+    stats::Stat,
 };
 
 use crate::{
     ecs::{
         resources::MonsterDefinitions,
         system_data::GameStateHelper,
-        systems::{AnimationsResourceBundle, OutcomingNetUpdates, WriteStorageCell},
+        systems::{
+            zone::ZoneSlows, AnimationsResourceBundle, AuraSlows, OutcomingNetUpdates,
+            WriteStorageCell,
+        },
+    },
+    scripting::{MonsterContext, ScriptHooks, ScriptedMonsterAction},
+    utils::{
+        entities::{is_dead, monster_stat_modifiers},
+        world::{apply_impulse, knockback_impulse, random_scene_position},
     },
-    utils::world::random_scene_position,
 };
 
 const MAX_IDLE_TIME_SECS: f32 = 0.5;
+/// Idle minions return to their owner if they're further than this distance.
+const MINION_FOLLOW_DISTANCE: f32 = 60.0;
 
 pub struct MonsterActionSubsystem<'a, 's> {
     pub entities: &'s Entities<'s>,
     pub game_time_service: &'s GameTimeService<'s>,
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub monster_definitions: &'s ReadExpect<'s, MonsterDefinitions>,
+    pub script_hooks: &'s ReadExpect<'s, ScriptHooks>,
     pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub collisions: &'s CollisionMatrix,
+    /// Where the level script payload is, idle monsters go there to attack its escort.
+    pub payload_position: Option<Vector2>,
     pub client_player_actions: &'s ReadStorage<'s, ClientPlayerActions>,
+    pub allegiances: &'s ReadStorage<'s, Allegiance>,
+    pub monster_affixes: &'s ReadStorage<'s, MonsterAffixes>,
     pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
     pub players: WriteStorageCell<'s, Player>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
     pub net_world_positions: WriteStorageCell<'s, NetWorldPosition>,
     pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub impulses: WriteStorageCell<'s, Impulse>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
     pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
 }
 
+/// Mobs which are alive at the start of a frame, paired with their radius.
+/// Monsters and minions target the layers that `CollisionMatrix` lets them hit.
+#[derive(Default)]
+pub struct MobTargets {
+    pub monsters: Vec<(Entity, f32)>,
+    pub minions: Vec<(Entity, f32)>,
+}
+
+impl MobTargets {
+    pub fn collect(
+        entities: &Entities,
+        monsters: &WriteStorage<Monster>,
+        allegiances: &ReadStorage<Allegiance>,
+        dead: &impl GenericReadStorage<Component = Dead>,
+        frame_number: u64,
+    ) -> Self {
+        let mut mob_targets = Self::default();
+        for (entity, monster) in (entities, monsters).join() {
+            // Emerging monsters can't be targeted yet.
+            if is_dead(entity, dead, frame_number) || monster.is_emerging(frame_number) {
+                continue;
+            }
+            if allegiances.contains(entity) {
+                mob_targets.minions.push((entity, monster.radius));
+            } else {
+                mob_targets.monsters.push((entity, monster.radius));
+            }
+        }
+        mob_targets
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.monsters
+            .iter()
+            .chain(self.minions.iter())
+            .any(|(target, _)| *target == entity)
+    }
+}
+
 pub struct ApplyMonsterActionNetArgs<'a> {
     pub entity_net_id: NetIdentifier,
     pub outcoming_net_updates: &'a mut OutcomingNetUpdates,
@@ -59,6 +123,7 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
         frame_number: u64,
         entity: Entity,
         monster: &mut Monster,
+        mob_targets: &MobTargets,
         net_args: Option<ApplyMonsterActionNetArgs<'n>>,
     ) {
         profile_scope!("MonsterActionSubsystem::decide_monster_action");
@@ -88,7 +153,13 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             } = net_args.expect("Expected ApplyMonsterActionNetArgs in multiplayer");
 
             if self.game_state_helper.is_authoritative() {
-                let action = self.new_action(frame_number, &monster, monster_position.clone());
+                let action = self.new_action(
+                    frame_number,
+                    entity,
+                    &monster,
+                    monster_position.clone(),
+                    mob_targets,
+                );
                 if let Some(action) = &action {
                     let update = NetUpdateWithPosition {
                         entity_net_id,
@@ -102,7 +173,13 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                 updates.map(|updates| updates.1)
             }
         } else {
-            self.new_action(frame_number, &monster, monster_position.clone())
+            self.new_action(
+                frame_number,
+                entity,
+                &monster,
+                monster_position.clone(),
+                mob_targets,
+            )
         };
 
         let world_positions = self.world_positions.borrow();
@@ -175,7 +252,13 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
         }
     }
 
-    pub fn process_monster_movement(&self, entity: Entity, monster: &mut Monster) {
+    pub fn process_monster_movement(
+        &self,
+        entity: Entity,
+        monster: &mut Monster,
+        zone_slows: &ZoneSlows,
+        aura_slows: &AuraSlows,
+    ) {
         profile_scope!("MonsterActionSubsystem::process_monster_movement");
         let mut world_positions = self.world_positions.borrow_mut();
         let monster_position = world_positions
@@ -189,35 +272,56 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             .expect("Expected a MonsterDefinition");
 
         let monster_position = &mut **monster_position;
-        let monster_speed = monster_definition.base_speed;
-        let time = self.game_time_service.engine_time().fixed_seconds();
+        let mut stat_modifiers =
+            monster_stat_modifiers(entity, self.monster_affixes, &self.room_settings.mutators);
+        zone_slows.add_stat_modifiers(entity, &mut stat_modifiers);
+        aura_slows.add_stat_modifiers(entity, &mut stat_modifiers);
+        let monster_speed = stat_modifiers.value(Stat::MoveSpeed, monster_definition.base_speed);
+        let time = self.game_time_service.tick_seconds();
         let travel_distance_squared = monster_speed * monster_speed * time * time;
 
         let displacement = monster.destination - *monster_position;
+        let previous_position = *monster_position;
 
         if displacement.norm_squared() > 0.0 {
-            monster.facing_direction = displacement.normalize();
+            monster.facing_direction = deterministic::normalize(&displacement);
         }
 
         *monster_position = if displacement.norm_squared() - travel_distance_squared < 0.01 {
             monster.velocity = Vector2::zero();
             monster.destination
         } else {
-            monster.velocity = displacement.normalize() * monster_speed * time;
+            monster.velocity = deterministic::normalize(&displacement) * monster_speed * time;
             *monster_position + monster.velocity
         };
+        *monster_position = self
+            .game_level_state
+            .collision_grid
+            .slide(previous_position, *monster_position);
+
+        if let Some(impulse) = self.impulses.borrow_mut().get_mut(entity) {
+            apply_impulse(monster_position, impulse, time, &*self.game_level_state);
+        }
+        // Minions fight for the players, so they can follow them into safe zones.
+        if self.allegiances.get(entity).is_none() {
+            *monster_position =
+                push_out_of_safe_zones(&self.game_level_state.safe_zones, *monster_position);
+        }
     }
 
     fn new_action(
         &self,
         frame_number: u64,
+        entity: Entity,
         monster: &Monster,
         monster_position: WorldPosition,
+        mob_targets: &MobTargets,
     ) -> Option<MobAction<Entity>> {
         profile_scope!("MonsterActionSubsystem::new_action");
         let players = self.players.borrow();
         let world_positions = self.world_positions.borrow();
         let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut threat_tables = self.threat_tables.borrow_mut();
 
         let monster_definition = self
             .monster_definitions
@@ -225,61 +329,178 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             .get(&monster.name)
             .expect("Expected a MonsterDefinition");
 
+        let allegiance = self.allegiances.get(entity);
+        if let Some(allegiance) = allegiance {
+            if allegiance.expires_at_frame <= frame_number {
+                damage_histories
+                    .get_mut(entity)
+                    .expect("Expected minion's DamageHistory")
+                    .add_entry(
+                        frame_number,
+                        DamageHistoryEntry {
+                            damage: monster.health,
+                            impulse: Vector2::zero(),
+                            is_crit: false,
+                            damage_type: None,
+                        },
+                    );
+                return None;
+            }
+        }
+
+        let scripted_action = self.script_hooks.decide_action(&MonsterContext {
+            frame_number,
+            name: &monster.name,
+            health: monster.health,
+            position: *monster_position,
+            action: monster.action.action.name(),
+            seconds_since_action: self
+                .game_time_service
+                .seconds_between_frames(frame_number, monster.action.frame_number),
+        });
+        // Repeating the current action would restart it and send an update every frame.
+        match (scripted_action, &monster.action.action) {
+            (Some(ScriptedMonsterAction::Keep), _)
+            | (Some(ScriptedMonsterAction::Idle), MobAction::Idle) => return None,
+            (Some(ScriptedMonsterAction::Idle), _) => return Some(MobAction::Idle),
+            (Some(ScriptedMonsterAction::Move(destination)), MobAction::Move(current))
+                if destination == *current =>
+            {
+                return None
+            }
+            (Some(ScriptedMonsterAction::Move(destination)), _) => {
+                return Some(MobAction::Move(destination))
+            }
+            (None, _) => {}
+        }
+
+        let mob_positions = |mobs: &[(Entity, f32)]| {
+            mobs.iter()
+                .filter(|(target, _)| *target != entity)
+                .filter_map(|(target, target_radius)| {
+                    world_positions
+                        .get(*target)
+                        .map(|position| (*target, *target_radius, **position))
+                })
+                .collect::<Vec<_>>()
+        };
+        let layer = if allegiance.is_some() {
+            CollisionLayer::Minions
+        } else {
+            CollisionLayer::Monsters
+        };
+        let mut targets = Vec::new();
+        for target_layer in self.collisions.targets(layer) {
+            match target_layer {
+                CollisionLayer::Players => targets.extend(
+                    (self.entities, &*players, &*world_positions)
+                        .join()
+                        .map(|(target, player, position)| (target, player.radius, **position)),
+                ),
+                CollisionLayer::Monsters => targets.extend(mob_positions(&mob_targets.monsters)),
+                CollisionLayer::Minions => targets.extend(mob_positions(&mob_targets.minions)),
+                _ => {}
+            }
+        }
+        let find_target = |radius: f32| {
+            find_target_in_radius(
+                targets.iter().copied(),
+                *monster_position,
+                radius,
+                &self.game_level_state.collision_grid,
+            )
+        };
+
+        // Monsters go after whoever has caused them the most threat,
+        // as long as it's alive and they can hit it.
+        let threat_target = threat_tables.get(entity).and_then(|threat_table| {
+            threat_table.top_target(|target| targets.iter().any(|(hit, _, _)| *hit == target))
+        });
+
+        let attack_entry = |target: Entity| {
+            let target_position = world_positions
+                .get(target)
+                .map_or(*monster_position, |target_position| **target_position);
+            DamageHistoryEntry {
+                damage: monster.attack_damage,
+                impulse: knockback_impulse(
+                    *monster_position,
+                    target_position,
+                    monster_definition.attack_knockback,
+                ),
+                is_crit: false,
+                damage_type: Some(monster_definition.attack_damage_type),
+            }
+        };
+
         match monster.action.action {
             MobAction::Idle => {
-                if let Some((entity, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
-                    *monster_position,
-                    200.0,
-                ) {
-                    Some(MobAction::Chase(entity))
+                if let Some(target) =
+                    threat_target.or_else(|| find_target(monster_definition.aggro_radius))
+                {
+                    Some(MobAction::Chase(target))
                 } else {
                     let time_being_idle = self
                         .game_time_service
                         .seconds_between_frames(frame_number, monster.action.frame_number);
-                    if MAX_IDLE_TIME_SECS < time_being_idle {
-                        Some(MobAction::Move(random_scene_position(
-                            &*self.game_level_state,
-                        )))
-                    } else {
+                    if MAX_IDLE_TIME_SECS >= time_being_idle {
                         None
+                    } else if let Some(allegiance) = allegiance {
+                        world_positions
+                            .get(allegiance.owner)
+                            .map(|owner_position| **owner_position)
+                            .filter(|owner_position| {
+                                (*owner_position - *monster_position).norm_squared()
+                                    > MINION_FOLLOW_DISTANCE * MINION_FOLLOW_DISTANCE
+                            })
+                            .map(|owner_position| {
+                                MobAction::Move(
+                                    owner_position
+                                        + deterministic::normalize(
+                                            &(*monster_position - owner_position),
+                                        ) * MINION_FOLLOW_DISTANCE,
+                                )
+                            })
+                    } else {
+                        Some(MobAction::Move(self.payload_position.unwrap_or_else(
+                            || random_scene_position(&*self.game_level_state),
+                        )))
                     }
                 }
             }
             MobAction::Move(destination) => {
-                if let Some((entity, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
-                    *monster_position,
-                    200.0,
-                ) {
-                    Some(MobAction::Chase(entity))
+                if let Some(target) =
+                    threat_target.or_else(|| find_target(monster_definition.aggro_radius))
+                {
+                    Some(MobAction::Chase(target))
                 } else if (*monster_position - destination).norm_squared() < 0.01 {
                     Some(MobAction::Idle)
                 } else {
                     None
                 }
             }
-            MobAction::Chase(_) => {
-                if let Some((target, _player_position)) = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
-                    *monster_position,
-                    monster.radius,
-                ) {
+            MobAction::Chase(chased) => {
+                if let Some(target) = find_target(monster.radius) {
                     if self.game_state_helper.is_authoritative() {
                         let damage_history = damage_histories
                             .get_mut(target)
-                            .expect("Expected player's DamageHistory");
-                        damage_history.add_entry(
-                            frame_number,
-                            DamageHistoryEntry {
-                                damage: monster.attack_damage,
-                            },
-                        );
+                            .expect("Expected target's DamageHistory");
+                        damage_history.add_entry(frame_number, attack_entry(target));
+                        if allegiance.is_some() {
+                            if let Some(threat_table) = threat_tables.get_mut(target) {
+                                threat_table.add(entity, monster.attack_damage);
+                            }
+                        }
                     }
                     Some(MobAction::Attack(MobAttackAction {
                         target,
                         attack_type: monster_definition.attack_type.randomize_params(0.2),
                     }))
+                } else if let Some(target) = threat_target.filter(|target| *target != chased) {
+                    Some(MobAction::Chase(target))
+                } else if !players.contains(chased) && !mob_targets.contains(chased) {
+                    // The chased mob has died.
+                    Some(MobAction::Idle)
                 } else {
                     None
                 }
@@ -293,25 +514,21 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                     }
                     _ => false,
                 };
-                let player_in_radius = find_player_in_radius(
-                    (self.entities, &*players, &*world_positions).join(),
-                    *monster_position,
-                    monster.radius,
-                );
-                match (&attack_action.attack_type, player_in_radius) {
+                let target_in_radius = find_target(monster.radius);
+                match (&attack_action.attack_type, target_in_radius) {
                     // TODO: implement cooling down for other attacks as well.
                     (MobAttackType::SlowMelee { .. }, _) if is_cooling_down => None,
-                    (_, Some((target, _player_position))) => {
+                    (_, Some(target)) => {
                         if self.game_state_helper.is_authoritative() {
                             let damage_history = damage_histories
                                 .get_mut(target)
-                                .expect("Expected player's DamageHistory");
-                            damage_history.add_entry(
-                                frame_number,
-                                DamageHistoryEntry {
-                                    damage: monster.attack_damage,
-                                },
-                            );
+                                .expect("Expected target's DamageHistory");
+                            damage_history.add_entry(frame_number, attack_entry(target));
+                            if allegiance.is_some() {
+                                if let Some(threat_table) = threat_tables.get_mut(target) {
+                                    threat_table.add(entity, monster.attack_damage);
+                                }
+                            }
                         }
                         Some(MobAction::Attack(MobAttackAction {
                             target,
@@ -325,18 +542,21 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
     }
 }
 
-fn find_player_in_radius<'a>(
-    mut players: impl Iterator<Item = (Entity, &'a Player, &'a WorldPosition)>,
+/// Monsters only acquire and attack the targets they can see.
+fn find_target_in_radius(
+    mut targets: impl Iterator<Item = (Entity, f32, Vector2)>,
     position: Vector2,
     radius: f32,
-) -> Option<(Entity, &'a WorldPosition)> {
+    collision_grid: &LevelCollisionGrid,
+) -> Option<Entity> {
     let radius_squared = radius * radius;
-    players
-        .find(|(_, player, player_position)| {
-            let player_radius_squared = player.radius * player.radius;
-            (position - ***player_position).norm_squared() < radius_squared + player_radius_squared
+    targets
+        .find(|(_, target_radius, target_position)| {
+            let target_radius_squared = target_radius * target_radius;
+            (position - *target_position).norm_squared() < radius_squared + target_radius_squared
+                && collision_grid.has_line_of_sight(position, *target_position)
         })
-        .map(|(entity, _, player_position)| (entity, player_position))
+        .map(|(entity, _, _)| entity)
 }
 
 #[cfg(feature = "client")]
@@ -1,9 +1,11 @@
 mod action_subsystem;
 mod dying;
 mod spawner;
+mod splitting;
 
 pub use self::{
-    action_subsystem::{ApplyMonsterActionNetArgs, MonsterActionSubsystem},
+    action_subsystem::{ApplyMonsterActionNetArgs, MobTargets, MonsterActionSubsystem},
     dying::MonsterDyingSystem,
-    spawner::MonsterSpawnerSystem,
+    spawner::{MonsterSpawnerSystem, MonsterSpawnerSystemData},
+    splitting::MonsterSplittingSystem,
 };
@@ -1,5 +1,5 @@
 use amethyst::{
-    ecs::{Entity, ReadExpect, System, World, WriteExpect, WriteStorage},
+    ecs::{Entity, Join, ReadExpect, ReadStorage, System, World, WriteExpect, WriteStorage},
     shred::{ResourceId, SystemData},
 };
 
@@ -10,20 +10,23 @@ use gv_core::{
         Action,
     },
     ecs::{
-        components::EntityNetMetadata,
+        components::{affixes::MonsterAffixes, aura::Auras, Allegiance, EntityNetMetadata, Player},
         resources::{net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState},
         system_data::time::GameTimeService,
     },
     math::{Vector2, ZeroVector},
     net::NetIdentifier,
+    room_settings::RoomSettings,
 };
 
 use crate::{
     ecs::{
         factories::MonsterFactory,
-        resources::{MonsterDefinition, MonsterDefinitions},
+        resources::{MonsterDefinition, MonsterDefinitions, MINION_DEFINITION},
         system_data::GameStateHelper,
-        systems::{AggregatedOutcomingUpdates, FrameUpdate, OutcomingNetUpdates},
+        systems::{
+            AggregatedOutcomingUpdates, FrameUpdate, OutcomingNetUpdates, MINION_LIFETIME_FRAMES,
+        },
     },
     utils::world::{outcoming_net_updates_mut, spawning_side},
 };
@@ -34,8 +37,13 @@ pub struct MonsterSpawnerSystemData<'s> {
     pub game_state_helper: GameStateHelper<'s>,
     pub monster_definitions: ReadExpect<'s, MonsterDefinitions>,
     pub game_level_state: ReadExpect<'s, GameLevelState>,
+    pub room_settings: ReadExpect<'s, RoomSettings>,
     pub entity_net_metadata: WriteStorage<'s, EntityNetMetadata>,
     pub entity_net_metadata_storage: WriteExpect<'s, EntityNetMetadataStorage>,
+    pub players: ReadStorage<'s, Player>,
+    pub allegiances: WriteStorage<'s, Allegiance>,
+    pub monster_affixes: WriteStorage<'s, MonsterAffixes>,
+    pub auras: WriteStorage<'s, Auras>,
     pub monster_factory: MonsterFactory<'s>,
 }
 
@@ -96,20 +104,28 @@ impl<'s> MonsterSpawnerSystemData<'s> {
         }
         let spawn_actions = self.get_spawn_actions(&spawn_actions);
 
+        let difficulty = self.room_settings.difficulty;
         for spawn_action in spawn_actions {
-            let ghoul = self
+            let active_at_frame = frame_number
+                + self
+                    .game_time_service
+                    .scale_frames(spawn_action.telegraph_frames);
+            let mut ghoul = self
                 .monster_definitions
                 .0
                 .get("Ghoul")
                 .expect("Failed to get Ghoul monster definition")
                 .clone();
+            ghoul.base_health *= difficulty.monster_health_factor();
+            ghoul.base_attack_damage *= difficulty.monster_damage_factor();
 
             match spawn_action.spawn_type {
                 SpawnType::Single {
                     entity_net_id,
                     position,
+                    affixes,
                 } => {
-                    self.spawn_monster(
+                    let monster_entity = self.spawn_monster(
                         frame_number,
                         position,
                         Action {
@@ -118,7 +134,18 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                         },
                         &ghoul,
                         entity_net_id,
+                        active_at_frame,
                     );
+                    if !affixes.is_empty() {
+                        self.monster_affixes
+                            .insert(monster_entity, MonsterAffixes::new(affixes))
+                            .expect("Expected to insert MonsterAffixes component");
+                        if !ghoul.elite_auras.is_empty() {
+                            self.auras
+                                .insert(monster_entity, Auras(ghoul.elite_auras.clone()))
+                                .expect("Expected to insert Auras component");
+                        }
+                    }
                 }
                 SpawnType::Borderline {
                     count,
@@ -145,10 +172,64 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                                     .next()
                                     .expect("Expected a reserved EntityIdentifier")
                             }),
+                            active_at_frame,
                         );
                         position += spawn_distance;
                     }
                 }
+                SpawnType::Minion {
+                    entity_net_id,
+                    owner_net_id,
+                    position,
+                } => {
+                    let owner = match owner_net_id {
+                        Some(owner_net_id) => {
+                            self.entity_net_metadata_storage.get_entity(owner_net_id)
+                        }
+                        None => (&self.monster_factory.entities, &self.players)
+                            .join()
+                            .next()
+                            .map(|(entity, _)| entity),
+                    };
+                    let owner = match owner {
+                        Some(owner) => owner,
+                        None => {
+                            log::error!(
+                                "Couldn't find an owner (net id: {:?}) of a minion",
+                                owner_net_id
+                            );
+                            continue;
+                        }
+                    };
+
+                    let minion = self
+                        .monster_definitions
+                        .0
+                        .get(MINION_DEFINITION)
+                        .expect("Failed to get the minion definition")
+                        .clone();
+                    let minion_entity = self.spawn_monster(
+                        frame_number,
+                        position,
+                        Action {
+                            frame_number,
+                            action: MobAction::Idle,
+                        },
+                        &minion,
+                        entity_net_id,
+                        active_at_frame,
+                    );
+                    self.allegiances
+                        .insert(
+                            minion_entity,
+                            Allegiance {
+                                owner,
+                                expires_at_frame: frame_number
+                                    + self.game_time_service.scale_frames(MINION_LIFETIME_FRAMES),
+                            },
+                        )
+                        .expect("Expected to insert Allegiance component");
+                }
             }
         }
     }
@@ -162,7 +243,8 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                 .filter(|action| {
                     // Filter out already spawned entities.
                     let entity_net_id = match &action.spawn_type {
-                        SpawnType::Single { entity_net_id, .. } => *entity_net_id,
+                        SpawnType::Single { entity_net_id, .. }
+                        | SpawnType::Minion { entity_net_id, .. } => *entity_net_id,
                         SpawnType::Borderline {
                             entity_net_id_range,
                             ..
@@ -197,14 +279,16 @@ impl<'s> MonsterSpawnerSystemData<'s> {
         outcoming_net_update.spawn_actions = spawn_actions;
     }
 
-    fn spawn_monster(
+    /// Is also used to restore the monsters of a rejoined match.
+    pub fn spawn_monster(
         &mut self,
         frame_number: u64,
         position: Vector2,
         action: Action<MobAction<Entity>>,
         monster_definition: &MonsterDefinition,
         net_id: Option<NetIdentifier>,
-    ) {
+        active_at_frame: u64,
+    ) -> Entity {
         log::trace!("Spawning a monster with net id {:?}", net_id);
         let destination = if let MobAction::Move(destination) = action.action {
             destination
@@ -217,6 +301,7 @@ impl<'s> MonsterSpawnerSystemData<'s> {
             position,
             destination,
             action,
+            active_at_frame,
         );
 
         if let Some(net_id) = net_id {
@@ -233,5 +318,6 @@ impl<'s> MonsterSpawnerSystemData<'s> {
             self.entity_net_metadata_storage
                 .set_net_id(monster_entity, net_id);
         }
+        monster_entity
     }
 }
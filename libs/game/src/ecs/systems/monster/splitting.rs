@@ -0,0 +1,97 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System, WriteExpect, WriteStorage};
+
+use gv_core::{
+    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+    ecs::{
+        components::{
+            affixes::{MonsterAffix, MonsterAffixes},
+            Dead, Monster, WorldPosition,
+        },
+        resources::{net::EntityNetMetadataStorage, world::FramedUpdates},
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+};
+
+use crate::ecs::system_data::GameStateHelper;
+
+const SPLIT_DISTANCE: f32 = 20.0;
+
+/// Schedules two monsters to spawn in place of a dead splitting elite. The dead elite loses
+/// the affix, so it is split only once.
+pub struct MonsterSplittingSystem;
+
+impl<'s> System<'s> for MonsterSplittingSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, FramedUpdates<SpawnActions>>,
+        WriteExpect<'s, EntityNetMetadataStorage>,
+        Entities<'s>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, MonsterAffixes>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut spawn_actions,
+            mut entity_net_metadata_storage,
+            entities,
+            monsters,
+            world_positions,
+            dead,
+            mut monster_affixes,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || !game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let mut splits = Vec::new();
+        for (_, monster, world_position, dead, monster_affixes) in (
+            &entities,
+            &monsters,
+            &world_positions,
+            &dead,
+            &mut monster_affixes,
+        )
+            .join()
+        {
+            if dead.dead_since_frame > frame_number || !monster_affixes.has(MonsterAffix::Splitting)
+            {
+                continue;
+            }
+            let offset = Vector2::new(-monster.facing_direction.y, monster.facing_direction.x)
+                * SPLIT_DISTANCE;
+            splits.push((**world_position, offset, monster_affixes.split_affixes()));
+            monster_affixes.affixes = monster_affixes.split_affixes();
+        }
+        if splits.is_empty() {
+            return;
+        }
+
+        let spawn_actions = spawn_actions
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number));
+        for (position, offset, affixes) in splits {
+            log::trace!("Splitting a monster with affixes {:?}", affixes);
+            for position in &[position + offset, position - offset] {
+                spawn_actions.spawn_actions.push(SpawnAction {
+                    spawn_type: SpawnType::Single {
+                        entity_net_id: Some(entity_net_metadata_storage.reserve_ids(1).start),
+                        position: *position,
+                        affixes: affixes.clone(),
+                    },
+                    // Split monsters come out of the dying one, they don't need a warning.
+                    telegraph_frames: 0,
+                });
+            }
+        }
+    }
+}
@@ -18,11 +18,19 @@ use gv_core::{
     ecs::{components::NetConnectionModel, system_data::time::GameTimeService},
     net::{
         client_message::{ClientMessage, ClientMessagePayload},
+        rendezvous::is_rendezvous_packet,
         server_message::{ServerMessage, ServerMessagePayload},
         ConnectionNetEvent, EncodedMessage, NetEvent, NetIdentifier,
     },
 };
 
+#[cfg(feature = "client")]
+use gv_core::net::decode_server_message as decode_incoming_message;
+#[cfg(not(feature = "client"))]
+use gv_core::net::{
+    decode_client_message as decode_incoming_message, server_message::DisconnectReason,
+};
+
 use crate::ecs::resources::ConnectionEvents;
 
 const PING_INTERVAL_MILLIS: u64 = 500;
@@ -116,6 +124,13 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
         }
 
         for net_event in net_events.read(&mut self.reader) {
+            // Are handled by RendezvousSystem.
+            if let NetworkSimulationEvent::Message(_, bytes) = net_event {
+                if is_rendezvous_packet(bytes.as_ref()) {
+                    continue;
+                }
+            }
+
             let (event, response) = self.process_connection_event(
                 &net_event,
                 &entities,
@@ -147,9 +162,11 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
                 + Duration::from_millis(PING_INTERVAL_MILLIS)
                 < Instant::now()
             {
-                connection_model
-                    .ping_pong_data
-                    .add_ping(ping_id, game_time_service.engine_time().frame_number());
+                connection_model.ping_pong_data.add_ping(
+                    ping_id,
+                    game_time_service.engine_time().frame_number(),
+                    game_time_service.game_frame_number_absolute(),
+                );
                 transport.send_with_requirements(
                     connection_model.addr,
                     &ping_message(connection_model.session_id, ping_id),
@@ -186,6 +203,25 @@ fn pong_message(
     .into()
 }
 
+#[cfg(feature = "client")]
+fn malformed_message_disconnect_payload() -> OutcomingMessagePayload {
+    ClientMessagePayload::Disconnect
+}
+
+#[cfg(not(feature = "client"))]
+fn malformed_message_disconnect_payload() -> OutcomingMessagePayload {
+    ServerMessagePayload::Disconnect(DisconnectReason::MalformedMessage)
+}
+
+fn malformed_message_disconnect_message(session_id: NetIdentifier) -> EncodedMessage {
+    bincode::serialize(&OutcomingMessage {
+        session_id,
+        payload: malformed_message_disconnect_payload(),
+    })
+    .expect("Expected to serialize Disconnect message")
+    .into()
+}
+
 impl NetConnectionManagerSystem {
     fn process_connection_event(
         &mut self,
@@ -253,66 +289,87 @@ impl NetConnectionManagerSystem {
                 )
             }
             NetworkSimulationEvent::Message(_, bytes) => {
-                if let Ok(IncomingMessage {
+                let IncomingMessage {
                     session_id,
                     payload,
-                }) = bincode::deserialize::<IncomingMessage>(bytes.as_ref())
-                {
-                    match payload {
-                        IncomingMessagePayload::Ping(ping_id) => {
-                            log::trace!("Received a new ping message: {:?}", &payload);
-                            if connection_model.disconnected {
-                                return (None, None);
-                            }
-                            (
-                                None,
-                                Some(pong_message(
-                                    session_id,
-                                    ping_id,
-                                    game_time_service.game_frame_number(),
-                                )),
-                            )
+                } = match decode_incoming_message(bytes.as_ref()) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        log::warn!(
+                            "Dropping a connection ({}) to {} because of a malformed message: {:?}",
+                            connection_id,
+                            connection_model.addr,
+                            err,
+                        );
+                        connection_model.disconnected = true;
+                        entities
+                            .delete(connection_model_entity)
+                            .expect("Expected to delete a NetConnectionModel");
+                        return (
+                            Some(ConnectionNetEvent {
+                                connection_id,
+                                event: NetEvent::Disconnected,
+                            }),
+                            Some(malformed_message_disconnect_message(
+                                connection_model.session_id,
+                            )),
+                        );
+                    }
+                };
+
+                match payload {
+                    IncomingMessagePayload::Ping(ping_id) => {
+                        log::trace!("Received a new ping message: {:?}", &payload);
+                        if connection_model.disconnected {
+                            return (None, None);
                         }
-                        IncomingMessagePayload::Pong {
-                            ping_id,
-                            frame_number: peer_frame_number,
-                        } => {
-                            log::trace!("Received a new pong message: {:?}", &payload);
-                            connection_model.ping_pong_data.add_pong(
+                        (
+                            None,
+                            Some(pong_message(
+                                session_id,
                                 ping_id,
-                                peer_frame_number,
-                                game_time_service.engine_time().frame_number(),
                                 game_time_service.game_frame_number(),
-                            );
-                            (None, None)
-                        }
-                        message if message.is_heartbeat() => {
-                            log::trace!(
-                                "Received a new Heartbeat message (connection_id: {})",
-                                connection_id
-                            );
-                            (None, None)
-                        }
-                        _ => {
-                            log::debug!(
-                                "Received a new message (connection_id: {}): {:?}",
+                            )),
+                        )
+                    }
+                    IncomingMessagePayload::Pong {
+                        ping_id,
+                        frame_number: peer_frame_number,
+                    } => {
+                        log::trace!("Received a new pong message: {:?}", &payload);
+                        connection_model.ping_pong_data.add_pong(
+                            ping_id,
+                            peer_frame_number,
+                            game_time_service.engine_time().frame_number(),
+                            game_time_service.game_frame_number(),
+                            game_time_service.game_frame_number_absolute(),
+                        );
+                        (None, None)
+                    }
+                    message if message.is_heartbeat() => {
+                        log::trace!(
+                            "Received a new Heartbeat message (connection_id: {})",
+                            connection_id
+                        );
+                        (None, None)
+                    }
+                    _ => {
+                        log::debug!(
+                            "Received a new message (connection_id: {}): {:?}",
+                            connection_id,
+                            &payload
+                        );
+                        (
+                            Some(ConnectionNetEvent {
                                 connection_id,
-                                &payload
-                            );
-                            (
-                                Some(ConnectionNetEvent {
-                                    connection_id,
-                                    event: NetEvent::Message(IncomingMessage {
-                                        session_id,
-                                        payload,
-                                    }),
+                                event: NetEvent::Message(IncomingMessage {
+                                    session_id,
+                                    payload,
                                 }),
-                                None,
-                            )
-                        }
+                            }),
+                            None,
+                        )
                     }
-                } else {
-                    (None, None)
                 }
             }
             NetworkSimulationEvent::SendError(err, _) => {
@@ -0,0 +1,113 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadStorage, System, WriteExpect};
+
+use std::collections::HashMap;
+
+use gv_core::{
+    ecs::{
+        components::{Allegiance, Dead, Monster, Player, PlayerActions, WorldPosition},
+        resources::GameLevelState,
+        system_data::time::GameTimeService,
+    },
+    objectives::ObjectiveEvent,
+};
+
+use crate::ecs::{resources::ActiveObjectives, system_data::GameStateHelper};
+
+/// Gives players some time to read that the last objective is completed.
+const COMPLETION_DELAY_FRAMES: u64 = 60 * 3;
+
+/// Feeds what happens in a single player game to the active objectives and ends the game
+/// once all of them are completed. The monsters of the objectives are spawned by LevelSystem.
+#[derive(Default)]
+pub struct ObjectiveSystem {
+    last_health: HashMap<Entity, f32>,
+    completed_at_frame: Option<u64>,
+}
+
+impl<'s> System<'s> for ObjectiveSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, ActiveObjectives>,
+        WriteExpect<'s, GameLevelState>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PlayerActions>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, Dead>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut active_objectives,
+            mut game_level_state,
+            entities,
+            players,
+            player_actions,
+            world_positions,
+            monsters,
+            allegiances,
+            dead,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || game_state_helper.is_multiplayer() {
+            return;
+        }
+        let objective_tracker = match active_objectives.0.as_mut() {
+            Some(objective_tracker) => objective_tracker,
+            None => return,
+        };
+
+        let frame_number = game_time_service.game_frame_number();
+        if objective_tracker.is_completed() {
+            let completed_at_frame = *self.completed_at_frame.get_or_insert(frame_number);
+            if frame_number - completed_at_frame
+                >= game_time_service.scale_frames(COMPLETION_DELAY_FRAMES)
+            {
+                game_level_state.is_over = true;
+                self.completed_at_frame = None;
+                self.last_health.clear();
+            }
+            return;
+        }
+
+        // Getting hit goes before the time passing, so that it resets the countdown first.
+        let mut events = Vec::new();
+        for (entity, player, world_position, player_actions) in
+            (&entities, &players, &world_positions, &player_actions).join()
+        {
+            events.push(ObjectiveEvent::PlayerMoved(**world_position));
+            if let Some(cast_action) = &player_actions.cast_action {
+                events.push(ObjectiveEvent::SpellCast(cast_action.spell));
+            }
+            let last_health = self.last_health.insert(entity, player.health);
+            if last_health.map_or(false, |last_health| player.health < last_health) {
+                events.push(ObjectiveEvent::PlayerDamaged);
+            }
+        }
+        // Minions dying don't count as kills.
+        let kills = (&monsters, !&allegiances, &dead)
+            .join()
+            .filter(|(_, _, dead)| dead.frame_acknowledged == frame_number)
+            .count();
+        events.extend((0..kills).map(|_| ObjectiveEvent::MonsterKilled));
+        events.push(ObjectiveEvent::TimePassed(game_time_service.tick_seconds()));
+
+        // The events that come after completing an objective don't count towards the next one.
+        for event in events {
+            if objective_tracker.handle(event) {
+                log::info!(
+                    "Completed objective {}/{}",
+                    objective_tracker.current_index(),
+                    objective_tracker.objectives().len()
+                );
+                break;
+            }
+        }
+    }
+}
@@ -1,16 +1,21 @@
 use amethyst::ecs::{ReadExpect, System, WriteExpect};
 
-use gv_core::ecs::resources::{net::MultiplayerGameState, GameTime};
+use gv_core::ecs::resources::{net::MultiplayerGameState, GameTime, SimulationTick};
 
 pub struct PauseSystem;
 
 impl<'s> System<'s> for PauseSystem {
     type SystemData = (
         ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, SimulationTick>,
         WriteExpect<'s, GameTime>,
     );
 
-    fn run(&mut self, (multiplayer_game_state, mut game_time): Self::SystemData) {
+    fn run(&mut self, (multiplayer_game_state, simulation_tick, mut game_time): Self::SystemData) {
+        if !simulation_tick.0 {
+            return;
+        }
+
         if multiplayer_game_state.waiting_network {
             game_time.frames_skipped += 1;
             log::info!(
@@ -1,58 +1,72 @@
-use amethyst::{
-    core::math::clamp,
-    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage},
-};
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage};
 use rand::seq::SliceRandom;
 
-use std::time::Duration;
-
 use gv_animation_prefabs::{AnimationId, MAGE_TORSO};
 #[cfg(not(feature = "client"))]
 use gv_core::net::NetUpdateWithPosition;
 use gv_core::{
     actions::{
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        player::{
+            CastSpell, PlayerCastAction, PlayerInteractAction, PlayerLookAction, PlayerWalkAction,
+            MAX_CAST_RANGE,
+        },
         ClientActionUpdate, IdentifiableAction,
     },
+    anti_cheat::{Violation, CAST_RANGE_TOLERANCE},
+    augments::PlayerAugments,
+    economy::PlayerWallet,
     ecs::{
         components::{
-            missile::Missile, ClientPlayerActions, Player, PlayerActions, PlayerLastCastedSpells,
-            WorldPosition,
+            missile::Missile, ClientPlayerActions, Impulse, Player, PlayerActions,
+            PlayerLastCastedSpells, WorldPosition,
         },
         resources::{
-            net::{ActionUpdateIdProvider, CastActionsToExecute, MultiplayerGameState},
+            net::{
+                ActionUpdateIdProvider, CastActionToExecute, CastActionsToExecute,
+                MultiplayerGameState,
+            },
             GameLevelState,
         },
         system_data::time::GameTimeService,
     },
-    math::{Vector2, ZeroVector},
+    math::{deterministic, Vector2, ZeroVector},
     net::{NetIdentifier, NetUpdate},
     profile_scope,
+    room_settings::RoomSettings,
+    stats::Stat,
 };
 
-use crate::ecs::{
-    system_data::GameStateHelper,
-    systems::{
-        AnimationsResourceBundle, ClientFrameUpdate, OutcomingNetUpdates, WriteExpectCell,
-        WriteStorageCell,
+use crate::{
+    ecs::{
+        resources::{AntiCheatLog, ClassDefinitions},
+        system_data::GameStateHelper,
+        systems::{
+            AnimationsResourceBundle, AuraSlows, ClientFrameUpdate, OutcomingNetUpdates,
+            WriteExpectCell, WriteStorageCell,
+        },
     },
+    utils::{entities::player_stat_modifiers, world::apply_impulse},
 };
 
-const MISSILE_CAST_COOLDOWN: Duration = Duration::from_millis(500);
-
 pub struct PlayerActionSubsystem<'a, 's> {
     pub game_time_service: &'s GameTimeService<'s>,
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub entities: &'s Entities<'s>,
     pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
     pub multiplayer_game_state: &'s ReadExpect<'s, MultiplayerGameState>,
+    pub room_settings: &'s ReadExpect<'s, RoomSettings>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
     pub client_player_actions: &'s ReadStorage<'s, ClientPlayerActions>,
+    pub player_augments: &'s ReadStorage<'s, PlayerAugments>,
+    pub player_wallets: &'s ReadStorage<'s, PlayerWallet>,
     pub action_update_id_provider: WriteExpectCell<'s, ActionUpdateIdProvider>,
     pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub anti_cheat_log: WriteExpectCell<'s, AntiCheatLog>,
     pub player_actions: WriteStorageCell<'s, PlayerActions>,
     pub player_last_casted_spells: WriteStorageCell<'s, PlayerLastCastedSpells>,
     pub missiles: WriteStorageCell<'s, Missile>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub impulses: WriteStorageCell<'s, Impulse>,
     #[cfg_attr(not(feature = "client"), allow(dead_code))]
     pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
 }
@@ -78,7 +92,10 @@ pub struct ApplyCastActionNetArgs<'a> {
     pub update: Option<IdentifiableAction<ClientActionUpdate<PlayerCastAction>>>,
 }
 
-const PLAYER_SPEED: f32 = 200.0;
+pub struct ApplyInteractActionNetArgs<'a> {
+    pub entity_net_id: NetIdentifier,
+    pub outcoming_net_updates: &'a mut OutcomingNetUpdates,
+}
 
 impl<'a, 's> PlayerActionSubsystem<'a, 's> {
     pub fn apply_walk_action<'n>(
@@ -86,6 +103,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         frame_number: u64,
         entity: Entity,
         player: &mut Player,
+        aura_slows: &AuraSlows,
         net_args: Option<ApplyWalkActionNetArgs<'n>>,
         client_side_actions: &mut ClientFrameUpdate,
     ) {
@@ -162,20 +180,41 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let PlayerWalkAction::Walk { direction } = &player_actions.walk_action {
             player.walking_direction = *direction;
             player.velocity = if *direction != Vector2::zero() {
-                direction.normalize() * PLAYER_SPEED
+                let mut stat_modifiers = player_stat_modifiers(
+                    entity,
+                    self.player_wallets,
+                    &self.room_settings.mutators,
+                    frame_number,
+                );
+                aura_slows.add_stat_modifiers(entity, &mut stat_modifiers);
+                deterministic::normalize(direction)
+                    * stat_modifiers.value(
+                        Stat::MoveSpeed,
+                        self.class_definitions.get(player.class).base_speed,
+                    )
             } else {
                 Vector2::zero()
             };
-            **player_position +=
-                player.velocity * self.game_time_service.engine_time().fixed_seconds();
-
-            let scene_half_size_x = self.game_level_state.dimensions.x / 2.0;
-            let scene_half_size_y = self.game_level_state.dimensions.y / 2.0;
-            player_position.x = clamp(player_position.x, -scene_half_size_x, scene_half_size_x);
-            player_position.y = clamp(player_position.y, -scene_half_size_y, scene_half_size_y);
+            let previous_position = **player_position;
+            **player_position += player.velocity * self.game_time_service.tick_seconds();
+
+            **player_position = self.game_level_state.clamp_to_bounds(**player_position);
+            **player_position = self
+                .game_level_state
+                .collision_grid
+                .slide(previous_position, **player_position);
         } else {
             player.velocity = Vector2::zero();
         }
+
+        if let Some(impulse) = self.impulses.borrow_mut().get_mut(entity) {
+            apply_impulse(
+                &mut **player_position,
+                impulse,
+                self.game_time_service.tick_seconds(),
+                &*self.game_level_state,
+            );
+        }
     }
 
     pub fn apply_look_action<'n>(
@@ -215,7 +254,14 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 entity_net_id,
             );
 
-            if let Some(look_action_update) = look_action_update {
+            if let Some(mut look_action_update) = look_action_update {
+                if self.game_state_helper.is_authoritative() {
+                    let world_positions = self.world_positions.borrow();
+                    let player_position = world_positions
+                        .get(entity)
+                        .expect("Expected a WorldPosition");
+                    look_action_update.action.clamp_aim_point(**player_position);
+                }
                 log::trace!(
                     "Applying a new look update for {} (frame {}): {:?}",
                     entity_net_id,
@@ -248,6 +294,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         &self,
         frame_number: u64,
         entity: Entity,
+        player: &Player,
         mut net_args: Option<ApplyCastActionNetArgs<'n>>,
         _client_side_actions: &mut ClientFrameUpdate,
     ) {
@@ -273,10 +320,6 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         let client_player_actions = self.client_player_actions.get(entity);
 
         let is_latest_frame = self.game_time_service.game_frame_number() == frame_number;
-        let is_cooling_down = self
-            .game_time_service
-            .seconds_between_frames(frame_number, player_last_casted_spells.missile)
-            < MISSILE_CAST_COOLDOWN.as_secs_f32();
 
         player_actions.cast_action = None;
 
@@ -294,6 +337,13 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 action: mut cast_action,
             }) = cast_action_update.clone()
             {
+                let is_cooling_down = self.is_cooling_down(
+                    frame_number,
+                    entity,
+                    player,
+                    player_last_casted_spells,
+                    cast_action.action.spell,
+                );
                 if !is_cooling_down || !self.game_state_helper.is_authoritative() {
                     log::trace!(
                         "Applying a new cast update ({}) for {} (frame {}): {:?}",
@@ -304,10 +354,31 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                     );
                 }
 
+                if self.game_state_helper.is_authoritative() && is_cooling_down {
+                    self.anti_cheat_log.borrow_mut().flag_cast(
+                        action_id,
+                        *entity_net_id,
+                        frame_number,
+                        Violation::CastOnCooldown(cast_action.action.spell),
+                    );
+                }
+
                 if self.game_state_helper.is_authoritative() && !is_cooling_down {
                     // Update player actions.
-                    player_last_casted_spells.missile = frame_number;
+                    player_last_casted_spells.set(cast_action.action.spell, frame_number);
                     cast_action.action.cast_position = *player_position;
+                    let target_distance = deterministic::norm(
+                        &(cast_action.action.target_position - cast_action.action.cast_position),
+                    );
+                    if target_distance > MAX_CAST_RANGE + CAST_RANGE_TOLERANCE {
+                        self.anti_cheat_log.borrow_mut().flag_cast(
+                            action_id,
+                            *entity_net_id,
+                            frame_number,
+                            Violation::CastOutOfRange,
+                        );
+                    }
+                    cast_action.action.clamp_target_position();
                     player_actions.cast_action = Some(cast_action.action.clone());
 
                     // Add to network broadcasted updates.
@@ -318,30 +389,32 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                         cast_action,
                     );
                 } else if !self.game_state_helper.is_authoritative() {
-                    player_last_casted_spells.missile = frame_number;
+                    player_last_casted_spells.set(cast_action.action.spell, frame_number);
                     player_actions.cast_action = Some(cast_action.action);
                 }
 
                 if let Some(cast_action) = &player_actions.cast_action {
-                    if let Some(missile) = self.already_casted_missile(action_id) {
-                        let missile_position = world_positions
-                            .get_mut(missile)
-                            .expect("Expected a WorldPosition for a Missile");
-                        **missile_position = cast_action.cast_position;
+                    let casted_missiles = self.already_casted_missiles(action_id);
+                    if !casted_missiles.is_empty() {
+                        // A multishot spawns several missiles with the same action id.
+                        for missile in casted_missiles {
+                            let missile_position = world_positions
+                                .get_mut(missile)
+                                .expect("Expected a WorldPosition for a Missile");
+                            **missile_position = cast_action.cast_position;
+                        }
                     } else {
-                        cast_actions_to_execute.actions.push(IdentifiableAction {
-                            action_id,
-                            action: cast_action.clone(),
+                        cast_actions_to_execute.actions.push(CastActionToExecute {
+                            caster: entity,
+                            caster_class: player.class,
+                            caster_augments: self.caster_augments(entity),
+                            action: IdentifiableAction {
+                                action_id,
+                                action: cast_action.clone(),
+                            },
                         });
 
-                        let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
-                            .choose(&mut rand::thread_rng())
-                            .unwrap();
-                        self.animations_resource_bundle.play_animation(
-                            entity,
-                            MAGE_TORSO,
-                            *animation_id,
-                        );
+                        self.play_cast_animation(frame_number, entity, player, cast_action.spell);
                     }
 
                     return;
@@ -352,6 +425,13 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let Some(client_player_actions) = client_player_actions.cloned() {
             if is_latest_frame {
                 if let Some(mut cast_action) = client_player_actions.cast_action {
+                    let is_cooling_down = self.is_cooling_down(
+                        frame_number,
+                        entity,
+                        player,
+                        player_last_casted_spells,
+                        cast_action.spell,
+                    );
                     if !is_cooling_down {
                         if self.multiplayer_game_state.is_playing {
                             let ApplyCastActionNetArgs {
@@ -386,18 +466,21 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                                 frame_number,
                                 &cast_action
                             );
-                            cast_actions_to_execute.actions.push(IdentifiableAction {
-                                action_id: 0,
-                                action: cast_action.clone(),
+                            cast_actions_to_execute.actions.push(CastActionToExecute {
+                                caster: entity,
+                                caster_class: player.class,
+                                caster_augments: self.caster_augments(entity),
+                                action: IdentifiableAction {
+                                    action_id: 0,
+                                    action: cast_action.clone(),
+                                },
                             });
 
-                            let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
-                                .choose(&mut rand::thread_rng())
-                                .unwrap();
-                            self.animations_resource_bundle.play_animation(
+                            self.play_cast_animation(
+                                frame_number,
                                 entity,
-                                MAGE_TORSO,
-                                *animation_id,
+                                player,
+                                cast_action.spell,
                             );
                         }
 
@@ -405,12 +488,104 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                     }
                 }
             }
-            if player_actions.cast_action.is_some() {
-                player_last_casted_spells.missile = frame_number;
+            if let Some(cast_action) = &player_actions.cast_action {
+                player_last_casted_spells.set(cast_action.spell, frame_number);
             }
         }
     }
 
+    /// Interactions aren't rolled back: multiplayer clients send them to a server, which applies
+    /// them on the frame they arrive, single player keeps them in PlayerActions for a frame.
+    pub fn apply_interact_action<'n>(
+        &self,
+        frame_number: u64,
+        entity: Entity,
+        net_args: Option<ApplyInteractActionNetArgs<'n>>,
+    ) {
+        if self.game_time_service.game_frame_number() != frame_number {
+            return;
+        }
+        let mut player_actions = self.player_actions.borrow_mut();
+        let player_actions = player_actions
+            .get_mut(entity)
+            .expect("Expected player actions");
+        let interact_action = self
+            .client_player_actions
+            .get(entity)
+            .and_then(|actions| actions.interact_action.clone());
+
+        if self.multiplayer_game_state.is_playing {
+            if let Some(interact_action) = interact_action {
+                let ApplyInteractActionNetArgs {
+                    entity_net_id,
+                    outcoming_net_updates,
+                } = net_args.expect("Expected ApplyInteractActionNetArgs in multiplayer");
+                let mut action_update_id_provider = self.action_update_id_provider.borrow_mut();
+                self.add_interact_action_net_update(
+                    outcoming_net_updates,
+                    entity_net_id,
+                    ClientActionUpdate {
+                        client_action_id: action_update_id_provider.next_update_id(),
+                        action: interact_action,
+                    },
+                );
+            }
+        } else {
+            player_actions.interact_action = interact_action;
+        }
+    }
+
+    /// Melee swings are played from the frame they have been made on, a cast received
+    /// from a server a few frames late still hits when the weapon comes down.
+    fn play_cast_animation(
+        &self,
+        frame_number: u64,
+        entity: Entity,
+        player: &Player,
+        spell: CastSpell,
+    ) {
+        let is_swing =
+            spell == CastSpell::Missile && self.class_definitions.get(player.class).melee.is_some();
+        if is_swing {
+            let start_secs = self
+                .game_time_service
+                .seconds_between_frames(self.game_time_service.game_frame_number(), frame_number);
+            self.animations_resource_bundle.play_animation_from(
+                entity,
+                MAGE_TORSO,
+                AnimationId::Attack,
+                start_secs,
+            );
+        } else {
+            let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
+                .choose(&mut rand::thread_rng())
+                .unwrap();
+            self.animations_resource_bundle
+                .play_animation(entity, MAGE_TORSO, *animation_id);
+        }
+    }
+
+    fn is_cooling_down(
+        &self,
+        frame_number: u64,
+        entity: Entity,
+        player: &Player,
+        player_last_casted_spells: &PlayerLastCastedSpells,
+        spell: CastSpell,
+    ) -> bool {
+        let stat_modifiers = player_stat_modifiers(
+            entity,
+            self.player_wallets,
+            &self.room_settings.mutators,
+            frame_number,
+        );
+        self.game_time_service
+            .seconds_between_frames(frame_number, player_last_casted_spells.get(spell))
+            < self
+                .class_definitions
+                .modified_cooldown_secs(player.class, spell, &stat_modifiers)
+    }
+
     #[cfg(feature = "client")]
     fn actual_walk_action_update(
         &self,
@@ -632,11 +807,44 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
             });
     }
 
-    fn already_casted_missile(&self, cast_action_id: NetIdentifier) -> Option<Entity> {
+    #[cfg(feature = "client")]
+    fn add_interact_action_net_update(
+        &self,
+        outcoming_net_updates: &mut OutcomingNetUpdates,
+        entity_net_id: NetIdentifier,
+        interact_action_update: ClientActionUpdate<PlayerInteractAction>,
+    ) {
+        outcoming_net_updates
+            .interact_action_updates
+            .push(NetUpdate {
+                entity_net_id,
+                data: interact_action_update,
+            });
+    }
+
+    /// A server receives interactions with PlayerInputs, see `InteractionRequests`.
+    #[cfg(not(feature = "client"))]
+    fn add_interact_action_net_update(
+        &self,
+        _outcoming_net_updates: &mut OutcomingNetUpdates,
+        _entity_net_id: NetIdentifier,
+        _interact_action_update: ClientActionUpdate<PlayerInteractAction>,
+    ) {
+    }
+
+    fn caster_augments(&self, entity: Entity) -> PlayerAugments {
+        self.player_augments
+            .get(entity)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn already_casted_missiles(&self, cast_action_id: NetIdentifier) -> Vec<Entity> {
         let missiles = self.missiles.borrow();
         (&*missiles, self.entities)
             .join()
-            .find(|(missile, _)| missile.action_id == cast_action_id)
+            .filter(|(missile, _)| missile.action_id == cast_action_id)
             .map(|(_, entity)| entity)
+            .collect()
     }
 }
@@ -1,5 +1,6 @@
 mod action_subsystem;
 
 pub use self::action_subsystem::{
-    ApplyCastActionNetArgs, ApplyLookActionNetArgs, ApplyWalkActionNetArgs, PlayerActionSubsystem,
+    ApplyCastActionNetArgs, ApplyInteractActionNetArgs, ApplyLookActionNetArgs,
+    ApplyWalkActionNetArgs, PlayerActionSubsystem,
 };
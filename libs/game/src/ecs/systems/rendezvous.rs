@@ -0,0 +1,260 @@
+use amethyst::{
+    core::SystemDesc,
+    ecs::{Read, ReaderId, System, SystemData, World, Write},
+    network::simulation::{
+        DeliveryRequirement, NetworkSimulationEvent, TransportResource, UrgencyRequirement,
+    },
+    shrev::EventChannel,
+};
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use gv_core::{
+    ecs::resources::net::{PunchAttempt, Rendezvous, RendezvousHost, RendezvousRole},
+    net::rendezvous::{decode_rendezvous_packet, encode_rendezvous_packet, RendezvousPacket},
+};
+
+const RESEND_INTERVAL: Duration = Duration::from_secs(1);
+// Should be shorter than the idle connection timeout of laminar.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(2);
+const PUNCH_INTERVAL: Duration = Duration::from_millis(100);
+const PUNCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Default)]
+pub struct RendezvousDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, RendezvousSystem> for RendezvousDesc {
+    fn build(self, world: &mut World) -> RendezvousSystem {
+        <RendezvousSystem as System<'_>>::SystemData::setup(world);
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+        RendezvousSystem::new(reader)
+    }
+}
+
+/// Talks to a rendezvous server and punches holes to the peers it introduces.
+/// Game messages relayed by the rendezvous server are unwrapped into the network event channel,
+/// so that the rest of the systems don't know about relaying.
+pub struct RendezvousSystem {
+    reader: ReaderId<NetworkSimulationEvent>,
+    last_sent_at: Option<Instant>,
+    last_punched_at: Option<Instant>,
+}
+
+impl RendezvousSystem {
+    fn new(reader: ReaderId<NetworkSimulationEvent>) -> Self {
+        Self {
+            reader,
+            last_sent_at: None,
+            last_punched_at: None,
+        }
+    }
+}
+
+impl<'s> System<'s> for RendezvousSystem {
+    type SystemData = (
+        Write<'s, Rendezvous>,
+        Write<'s, TransportResource>,
+        Write<'s, EventChannel<NetworkSimulationEvent>>,
+    );
+
+    fn run(&mut self, (mut rendezvous, mut transport, mut net_events): Self::SystemData) {
+        let packets = net_events
+            .read(&mut self.reader)
+            .filter_map(|net_event| match net_event {
+                NetworkSimulationEvent::Message(addr, bytes) => {
+                    match decode_rendezvous_packet(bytes.as_ref())? {
+                        Ok(packet) => Some((*addr, packet)),
+                        Err(err) => {
+                            log::warn!("Failed to decode a rendezvous packet: {:?}", err);
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let server_addr = match rendezvous.server_addr {
+            Some(server_addr) => server_addr,
+            None => {
+                self.last_sent_at = None;
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        let mut relayed_events = Vec::new();
+        for (addr, packet) in packets {
+            if addr == server_addr {
+                process_server_packet(&mut rendezvous, &mut relayed_events, packet, now);
+                continue;
+            }
+
+            match packet {
+                RendezvousPacket::Punch => {
+                    log::info!("Received a punch from {}", addr);
+                    if rendezvous.guest_host() == Some(RendezvousHost::Punching(addr)) {
+                        rendezvous.set_guest_host(RendezvousHost::Punched(addr));
+                    }
+                    // Making sure the peer receives at least one punch from us too.
+                    send_packet(&mut transport, addr, &RendezvousPacket::Punch);
+                    rendezvous
+                        .punch_attempts
+                        .retain(|punch_attempt| punch_attempt.addr != addr);
+                }
+                packet => {
+                    log::warn!("Unexpected rendezvous packet from {}: {:?}", addr, packet);
+                }
+            }
+        }
+        for (from, payload) in relayed_events {
+            net_events.single_write(NetworkSimulationEvent::Message(from, payload.into()));
+        }
+
+        let (packet, resend_interval) = match rendezvous.role {
+            Some(RendezvousRole::Host {
+                host_code,
+                is_registered: false,
+            }) => (
+                RendezvousPacket::RegisterHost { host_code },
+                RESEND_INTERVAL,
+            ),
+            Some(RendezvousRole::Guest {
+                host_code,
+                host: RendezvousHost::Unknown,
+            }) => (RendezvousPacket::Connect { host_code }, RESEND_INTERVAL),
+            _ => (RendezvousPacket::KeepAlive, KEEP_ALIVE_INTERVAL),
+        };
+        if self
+            .last_sent_at
+            .map_or(true, |last_sent_at| now - last_sent_at >= resend_interval)
+        {
+            self.last_sent_at = Some(now);
+            send_packet(&mut transport, server_addr, &packet);
+        }
+
+        let mut timed_out_attempts = Vec::new();
+        rendezvous.punch_attempts.retain(|punch_attempt| {
+            if now - punch_attempt.started_at >= PUNCH_TIMEOUT {
+                timed_out_attempts.push(punch_attempt.addr);
+                false
+            } else {
+                true
+            }
+        });
+        for addr in timed_out_attempts {
+            if rendezvous.guest_host() == Some(RendezvousHost::Punching(addr)) {
+                log::warn!(
+                    "Couldn't punch a hole to {}, falling back to relaying",
+                    addr
+                );
+                rendezvous.relayed_peers.insert(addr);
+                rendezvous.set_guest_host(RendezvousHost::Relayed(addr));
+            }
+        }
+
+        if !rendezvous.punch_attempts.is_empty()
+            && self.last_punched_at.map_or(true, |last_punched_at| {
+                now - last_punched_at >= PUNCH_INTERVAL
+            })
+        {
+            self.last_punched_at = Some(now);
+            for punch_attempt in &rendezvous.punch_attempts {
+                send_packet(&mut transport, punch_attempt.addr, &RendezvousPacket::Punch);
+            }
+        }
+    }
+}
+
+/// Wraps messages addressed to relayed peers, so that they are sent via the rendezvous server.
+/// Should run after all the systems sending messages.
+pub struct RendezvousRelaySystem;
+
+impl<'s> System<'s> for RendezvousRelaySystem {
+    type SystemData = (Read<'s, Rendezvous>, Write<'s, TransportResource>);
+
+    fn run(&mut self, (rendezvous, mut transport): Self::SystemData) {
+        let server_addr = match rendezvous.server_addr {
+            Some(server_addr) if !rendezvous.relayed_peers.is_empty() => server_addr,
+            _ => return,
+        };
+
+        let relayed_messages = transport
+            .drain_messages(|message| rendezvous.relayed_peers.contains(&message.destination));
+        for message in relayed_messages {
+            let packet = encode_rendezvous_packet(&RendezvousPacket::Relay {
+                to: message.destination,
+                payload: message.payload.to_vec(),
+            });
+            transport.send_with_requirements(
+                server_addr,
+                &packet,
+                message.delivery,
+                message.urgency,
+            );
+        }
+    }
+}
+
+fn process_server_packet(
+    rendezvous: &mut Rendezvous,
+    relayed_events: &mut Vec<(SocketAddr, Vec<u8>)>,
+    packet: RendezvousPacket,
+    now: Instant,
+) {
+    match packet {
+        RendezvousPacket::HostRegistered { host_code } => {
+            log::info!(
+                "Registered on the rendezvous server (host code: {})",
+                host_code
+            );
+            if let Some(RendezvousRole::Host { is_registered, .. }) = &mut rendezvous.role {
+                *is_registered = true;
+            }
+        }
+        RendezvousPacket::HostCodeTaken { host_code } => {
+            log::error!("The host code {} is already taken", host_code);
+        }
+        RendezvousPacket::HostNotFound { host_code } => {
+            log::warn!("The rendezvous server doesn't know the host {}", host_code);
+            rendezvous.set_guest_host(RendezvousHost::NotFound);
+        }
+        RendezvousPacket::PunchTo(addr) => {
+            log::info!("Punching a hole to {}", addr);
+            if rendezvous.guest_host() == Some(RendezvousHost::Unknown) {
+                rendezvous.set_guest_host(RendezvousHost::Punching(addr));
+            }
+            rendezvous
+                .punch_attempts
+                .retain(|punch_attempt| punch_attempt.addr != addr);
+            rendezvous.punch_attempts.push(PunchAttempt {
+                addr,
+                started_at: now,
+            });
+        }
+        RendezvousPacket::Relayed { from, payload } => {
+            // The host learns about relayed guests only when their messages start arriving.
+            if rendezvous.relayed_peers.insert(from) {
+                log::info!("Relaying messages to {} via the rendezvous server", from);
+            }
+            relayed_events.push((from, payload));
+        }
+        packet => {
+            log::warn!("Unexpected packet from the rendezvous server: {:?}", packet);
+        }
+    }
+}
+
+fn send_packet(transport: &mut TransportResource, addr: SocketAddr, packet: &RendezvousPacket) {
+    transport.send_with_requirements(
+        addr,
+        &encode_rendezvous_packet(packet),
+        DeliveryRequirement::Unreliable,
+        UrgencyRequirement::Immediate,
+    );
+}
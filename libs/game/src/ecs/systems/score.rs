@@ -0,0 +1,126 @@
+use amethyst::ecs::{Join, ReadStorage, System, WriteExpect};
+#[cfg(not(feature = "client"))]
+use amethyst::{ecs::Write, network::simulation::TransportResource};
+
+use gv_core::ecs::{
+    components::{Allegiance, Dead, Monster},
+    resources::MatchScore,
+    system_data::time::GameTimeService,
+};
+#[cfg(not(feature = "client"))]
+use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
+
+use crate::ecs::system_data::GameStateHelper;
+#[cfg(not(feature = "client"))]
+use crate::utils::net::broadcast_message_reliable;
+
+const KILL_SCORE: u64 = 10;
+/// A kill made within this window after the previous one continues the combo.
+const COMBO_WINDOW_FRAMES: u64 = 120;
+const KILLS_PER_MULTIPLIER: u32 = 5;
+const MAX_MULTIPLIER: u32 = 5;
+#[cfg(not(feature = "client"))]
+const SCORE_BROADCAST_INTERVAL: u64 = 10;
+
+#[cfg(not(feature = "client"))]
+type ScoreBroadcast<'s> = (
+    ReadStorage<'s, NetConnectionModel>,
+    Write<'s, TransportResource>,
+);
+#[cfg(feature = "client")]
+type ScoreBroadcast<'s> = ();
+
+/// Counts monster kills on the authoritative side, the score is multiplied by combos.
+/// Healing and critical hits are counted by HealSubsystem and MissilePhysicsSubsystem,
+/// this system only broadcasts them.
+#[derive(Default)]
+pub struct ScoreSystem {
+    #[cfg(not(feature = "client"))]
+    last_broadcasted_score: (u64, u32, f32, u32),
+}
+
+impl<'s> System<'s> for ScoreSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, MatchScore>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, Dead>,
+        ScoreBroadcast<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut match_score,
+            monsters,
+            allegiances,
+            dead,
+            score_broadcast,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || !game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let combo_window_frames = game_time_service.scale_frames(COMBO_WINDOW_FRAMES);
+        // Minions dying don't count as kills.
+        let kills = (&monsters, !&allegiances, &dead)
+            .join()
+            .filter(|(_, _, dead)| dead.frame_acknowledged == frame_number)
+            .count();
+        for _ in 0..kills {
+            if frame_number - match_score.last_kill_frame > combo_window_frames {
+                match_score.combo = 0;
+            }
+            match_score.combo += 1;
+            match_score.kills += 1;
+            match_score.multiplier =
+                (1 + (match_score.combo - 1) / KILLS_PER_MULTIPLIER).min(MAX_MULTIPLIER);
+            match_score.score += KILL_SCORE * u64::from(match_score.multiplier);
+            match_score.last_kill_frame = frame_number;
+        }
+        if match_score.combo > 0 && frame_number - match_score.last_kill_frame > combo_window_frames
+        {
+            match_score.combo = 0;
+            match_score.multiplier = 1;
+        }
+        match_score.frame_number = frame_number;
+
+        self.broadcast_score(&match_score, score_broadcast);
+    }
+}
+
+impl ScoreSystem {
+    #[cfg(not(feature = "client"))]
+    fn broadcast_score(
+        &mut self,
+        match_score: &MatchScore,
+        (net_connection_models, mut transport): ScoreBroadcast,
+    ) {
+        let score = (
+            match_score.score,
+            match_score.multiplier,
+            match_score.healing_done,
+            match_score.critical_hits,
+        );
+        if score == self.last_broadcasted_score
+            || match_score.frame_number % SCORE_BROADCAST_INTERVAL != 0
+        {
+            return;
+        }
+        self.last_broadcasted_score = score;
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connection_models).join(),
+            ServerMessagePayload::UpdateScore(match_score.clone()),
+        );
+    }
+
+    #[cfg(feature = "client")]
+    fn broadcast_score(&mut self, _match_score: &MatchScore, _: ScoreBroadcast) {}
+}
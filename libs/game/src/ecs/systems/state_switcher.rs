@@ -1,6 +1,6 @@
 use amethyst::{
     ecs::{ReadExpect, System, Write},
-    prelude::{GameData, StateEvent, Trans, TransEvent},
+    prelude::{GameData, SimpleTrans, StateEvent, Trans, TransEvent},
     shrev::EventChannel,
 };
 
@@ -24,11 +24,15 @@ impl<'s> System<'s> for StateSwitcherSystem {
         (new_game_engine_state, game_engine_state, mut trans_events): Self::SystemData,
     ) {
         let new_game_engine_state = *new_game_engine_state;
-        if *game_engine_state != new_game_engine_state.0 {
+        // `LevelLoadingState` switches to `PlayingState` by itself once the level is loaded.
+        let is_loading_level = *game_engine_state == GameEngineState::LoadingLevel
+            && new_game_engine_state.0 == GameEngineState::Playing;
+        if *game_engine_state != new_game_engine_state.0 && !is_loading_level {
             let trans = Box::new(move || match new_game_engine_state.0 {
-                GameEngineState::Loading => unreachable!(),
+                GameEngineState::Loading | GameEngineState::LoadingLevel => unreachable!(),
                 GameEngineState::Menu => Trans::Switch(Box::new(MenuState)),
-                GameEngineState::Playing => Trans::Switch(Box::new(PlayingState)),
+                GameEngineState::Playing => start_playing(),
+                GameEngineState::LevelTransition => Trans::Switch(Box::new(LevelTransitionState)),
                 GameEngineState::ShuttingDown { shutdown_at } if Instant::now() > shutdown_at => {
                     Trans::Quit
                 }
@@ -39,3 +43,13 @@ impl<'s> System<'s> for StateSwitcherSystem {
         }
     }
 }
+
+#[cfg(feature = "client")]
+fn start_playing() -> SimpleTrans {
+    Trans::Switch(Box::new(LevelLoadingState::default()))
+}
+
+#[cfg(not(feature = "client"))]
+fn start_playing() -> SimpleTrans {
+    Trans::Switch(Box::new(PlayingState))
+}
@@ -0,0 +1,197 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect};
+use rand::Rng;
+
+#[cfg(feature = "client")]
+use gv_core::ecs::components::ClientPlayerActions;
+use gv_core::{
+    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+    ecs::{
+        components::{Allegiance, Dead, EntityNetMetadata, WorldPosition},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            world::FramedUpdates,
+        },
+        system_data::time::GameTimeService,
+    },
+    math::{deterministic, Vector2},
+};
+
+use crate::{
+    ecs::{resources::SummonRequests, system_data::GameStateHelper},
+    utils::entities::is_dead,
+};
+
+pub const MAX_MINIONS_PER_PLAYER: usize = 3;
+pub const MINION_LIFETIME_FRAMES: u64 = 60 * 20;
+const MINION_SPAWN_DISTANCE: f32 = 40.0;
+
+#[cfg(not(feature = "client"))]
+type SummonSideData<'s> = ();
+#[cfg(feature = "client")]
+type SummonSideData<'s> = ReadStorage<'s, ClientPlayerActions>;
+
+/// Schedules minion spawns on the authoritative side, MonsterSpawnerSystem spawns them
+/// and replicates them to clients like any other monsters.
+pub struct SummonSystem;
+
+impl<'s> System<'s> for SummonSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, SummonRequests>,
+        WriteExpect<'s, FramedUpdates<SpawnActions>>,
+        WriteExpect<'s, EntityNetMetadataStorage>,
+        Entities<'s>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, WorldPosition>,
+        ReadStorage<'s, Allegiance>,
+        ReadStorage<'s, Dead>,
+        SummonSideData<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            multiplayer_game_state,
+            mut summon_requests,
+            mut spawn_actions,
+            mut entity_net_metadata_storage,
+            entities,
+            entity_net_metadata,
+            world_positions,
+            allegiances,
+            dead,
+            side_data,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || !game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let owners = self.summoners(
+            &multiplayer_game_state,
+            &entity_net_metadata_storage,
+            &mut summon_requests,
+            &entities,
+            side_data,
+        );
+        if owners.is_empty() {
+            return;
+        }
+
+        let spawn_actions = spawn_actions
+            .update_frame(frame_number)
+            .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number));
+        for owner in owners {
+            if is_dead(owner, &dead, frame_number) {
+                continue;
+            }
+            let owner_position = match world_positions.get(owner) {
+                Some(world_position) => **world_position,
+                None => continue,
+            };
+            let owner_net_id = entity_net_metadata
+                .get(owner)
+                .map(|entity_net_metadata| entity_net_metadata.id);
+
+            let alive_minions = (&entities, &allegiances)
+                .join()
+                .filter(|(entity, allegiance)| {
+                    allegiance.owner == owner && !is_dead(*entity, &dead, frame_number)
+                })
+                .count();
+            let scheduled_minions = spawn_actions
+                .spawn_actions
+                .iter()
+                .filter(|spawn_action| match spawn_action.spawn_type {
+                    SpawnType::Minion {
+                        owner_net_id: scheduled_owner_net_id,
+                        ..
+                    } => scheduled_owner_net_id == owner_net_id,
+                    _ => false,
+                })
+                .count();
+            if alive_minions + scheduled_minions >= MAX_MINIONS_PER_PLAYER {
+                log::debug!(
+                    "Ignoring a summon, the player (net id: {:?}) has reached the minion cap",
+                    owner_net_id
+                );
+                continue;
+            }
+
+            let angle = rand::thread_rng().gen_range(0.0, std::f32::consts::PI * 2.0);
+            let entity_net_id = if game_state_helper.is_multiplayer() {
+                Some(entity_net_metadata_storage.reserve_ids(1).start)
+            } else {
+                None
+            };
+            log::trace!(
+                "Summoning a minion with net id {:?} (owner net id: {:?})",
+                entity_net_id,
+                owner_net_id
+            );
+            let (sin, cos) = deterministic::sin_cos(angle);
+            spawn_actions.spawn_actions.push(SpawnAction {
+                spawn_type: SpawnType::Minion {
+                    entity_net_id,
+                    owner_net_id,
+                    position: owner_position + Vector2::new(cos, sin) * MINION_SPAWN_DISTANCE,
+                },
+                telegraph_frames: 0,
+            });
+        }
+    }
+}
+
+impl SummonSystem {
+    #[cfg(not(feature = "client"))]
+    fn summoners(
+        &self,
+        multiplayer_game_state: &MultiplayerGameState,
+        entity_net_metadata_storage: &EntityNetMetadataStorage,
+        summon_requests: &mut SummonRequests,
+        _entities: &Entities,
+        _side_data: SummonSideData,
+    ) -> Vec<Entity> {
+        summon_requests
+            .received
+            .drain(..)
+            .filter_map(|connection_id| {
+                let owner = multiplayer_game_state
+                    .find_player_by_connection_id(connection_id)
+                    .and_then(|player| {
+                        entity_net_metadata_storage.get_entity(player.entity_net_id)
+                    });
+                if owner.is_none() {
+                    log::warn!(
+                        "Couldn't find a player to summon a minion for (connection id: {})",
+                        connection_id
+                    );
+                }
+                owner
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "client")]
+    fn summoners(
+        &self,
+        _multiplayer_game_state: &MultiplayerGameState,
+        _entity_net_metadata_storage: &EntityNetMetadataStorage,
+        summon_requests: &mut SummonRequests,
+        entities: &Entities,
+        client_player_actions: SummonSideData,
+    ) -> Vec<Entity> {
+        if !std::mem::replace(&mut summon_requests.local, false) {
+            return Vec::new();
+        }
+        (entities, &client_player_actions)
+            .join()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+}
@@ -0,0 +1,95 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage};
+
+use gv_core::{
+    actions::player::CastSpell,
+    ecs::{
+        components::{Allegiance, Dead, ThreatTable, WorldPosition},
+        resources::net::{CastActionToExecute, CastActionsToExecute},
+        system_data::time::GameTimeService,
+    },
+    profile_scope,
+};
+
+use crate::{
+    ecs::{
+        resources::ClassDefinitions,
+        system_data::GameStateHelper,
+        systems::{WriteExpectCell, WriteStorageCell},
+    },
+    utils::entities::is_dead,
+};
+
+/// Threat that isn't renewed halves every few seconds.
+const THREAT_HALF_LIFE_SECS: f32 = 4.0;
+
+pub struct ThreatSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub allegiances: &'s ReadStorage<'s, Allegiance>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> ThreatSubsystem<'s> {
+    pub fn decay_threat(&self) {
+        profile_scope!("ThreatSubsystem::decay_threat");
+        if !self.game_state_helper.is_authoritative() {
+            return;
+        }
+        let frame_seconds = self.game_time_service.tick_seconds();
+        let factor = 0.5f32.powf(frame_seconds / THREAT_HALF_LIFE_SECS);
+        for threat_table in (&mut *self.threat_tables.borrow_mut()).join() {
+            threat_table.decay(factor);
+        }
+    }
+
+    /// Takes taunt casts out of CastActionsToExecute. Monsters around a caster get more
+    /// threat for the caster than for anyone else, so they turn to the caster.
+    pub fn resolve_taunts(&self, frame_number: u64) {
+        profile_scope!("ThreatSubsystem::resolve_taunts");
+        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
+        let (taunts, other_casts): (Vec<_>, Vec<_>) = cast_actions_to_execute
+            .actions
+            .drain(..)
+            .partition(|cast_action| cast_action.action.action.spell == CastSpell::Taunt);
+        cast_actions_to_execute.actions = other_casts;
+
+        if !self.game_state_helper.is_authoritative() {
+            return;
+        }
+
+        let world_positions = self.world_positions.borrow();
+        let mut threat_tables = self.threat_tables.borrow_mut();
+        let dead = self.dead.borrow();
+
+        for CastActionToExecute {
+            caster,
+            caster_class,
+            action: cast_action,
+            ..
+        } in taunts
+        {
+            let taunt_spell = &self.class_definitions.get(caster_class).taunt;
+            // Minions don't listen to taunts.
+            for (monster, world_position, threat_table, _) in (
+                self.entities,
+                &*world_positions,
+                &mut *threat_tables,
+                !self.allegiances,
+            )
+                .join()
+            {
+                let distance = (cast_action.action.cast_position - **world_position).norm();
+                if is_dead(monster, &*dead, frame_number) || distance > taunt_spell.radius {
+                    continue;
+                }
+                let threat = threat_table.top_threat() - threat_table.threat(caster);
+                threat_table.add(caster, threat + taunt_spell.threat);
+            }
+        }
+    }
+}
@@ -2,10 +2,10 @@ use amethyst::ecs::Entities;
 
 use gv_core::ecs::{
     components::{
-        missile::Missile, Dead, Monster, Player, PlayerActions, PlayerLastCastedSpells,
-        WorldPosition,
+        missile::Missile, Dead, Impulse, Monster, Player, PlayerActions, PlayerLastCastedSpells,
+        ThreatTable, WorldPosition,
     },
-    resources::world::SavedWorldState,
+    resources::world::{DirtyEntities, SavedWorldState},
 };
 
 use crate::ecs::systems::WriteStorageCell;
@@ -18,6 +18,8 @@ pub struct WorldStateSubsystem<'s> {
     pub monsters: WriteStorageCell<'s, Monster>,
     pub missiles: WriteStorageCell<'s, Missile>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub impulses: WriteStorageCell<'s, Impulse>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
     pub dead: WriteStorageCell<'s, Dead>,
 }
 
@@ -41,6 +43,12 @@ impl<'s> WorldStateSubsystem<'s> {
             &self.entities,
             &*self.world_positions.borrow_mut(),
         );
+        saved_world_state.impulses =
+            SavedWorldState::copy_from_write_storage(&self.entities, &*self.impulses.borrow_mut());
+        saved_world_state.threat_tables = SavedWorldState::copy_from_write_storage(
+            &self.entities,
+            &*self.threat_tables.borrow_mut(),
+        );
         saved_world_state.dead =
             SavedWorldState::copy_from_write_storage(&self.entities, &*self.dead.borrow_mut());
     }
@@ -70,6 +78,43 @@ impl<'s> WorldStateSubsystem<'s> {
             &mut self.world_positions.borrow_mut(),
             &saved_world_state.world_positions,
         );
+        SavedWorldState::load_storage_from(
+            &mut self.impulses.borrow_mut(),
+            &saved_world_state.impulses,
+        );
+        SavedWorldState::load_storage_from(
+            &mut self.threat_tables.borrow_mut(),
+            &saved_world_state.threat_tables,
+        );
         SavedWorldState::load_storage_from(&mut self.dead.borrow_mut(), &saved_world_state.dead);
     }
+
+    /// Puts the clean entities back to the state the previous simulation has left them in.
+    pub fn restore_clean_entities(
+        &self,
+        dirty_entities: &DirtyEntities,
+        saved_world_state: &SavedWorldState,
+    ) {
+        dirty_entities.restore_clean(&mut self.players.borrow_mut(), &saved_world_state.players);
+        dirty_entities.restore_clean(
+            &mut self.player_actions.borrow_mut(),
+            &saved_world_state.player_actions,
+        );
+        dirty_entities.restore_clean(
+            &mut self.player_last_casted_spells.borrow_mut(),
+            &saved_world_state.player_last_casted_spells,
+        );
+        dirty_entities.restore_clean(&mut self.monsters.borrow_mut(), &saved_world_state.monsters);
+        dirty_entities.restore_clean(&mut self.missiles.borrow_mut(), &saved_world_state.missiles);
+        dirty_entities.restore_clean(
+            &mut self.world_positions.borrow_mut(),
+            &saved_world_state.world_positions,
+        );
+        dirty_entities.restore_clean(&mut self.impulses.borrow_mut(), &saved_world_state.impulses);
+        dirty_entities.restore_clean(
+            &mut self.threat_tables.borrow_mut(),
+            &saved_world_state.threat_tables,
+        );
+        dirty_entities.restore_clean(&mut self.dead.borrow_mut(), &saved_world_state.dead);
+    }
 }
@@ -0,0 +1,35 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System};
+
+use gv_core::ecs::{components::zone::Zone, system_data::time::GameTimeService};
+
+use crate::ecs::system_data::GameStateHelper;
+
+pub struct ZoneDyingSystem;
+
+/// Expired zones are kept for a while, as rewinds may re-simulate the frames they were active.
+pub const ZONE_TTL_SECS: f32 = 1.0;
+
+impl<'s> System<'s> for ZoneDyingSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Zone>,
+    );
+
+    fn run(&mut self, (game_state_helper, game_time_service, entities, zones): Self::SystemData) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        for (zone_entity, zone) in (&entities, &zones).join() {
+            let to_be_deleted = game_time_service.game_frame_number() > zone.frame_expires
+                && game_time_service.seconds_to_frame(zone.frame_expires) > ZONE_TTL_SECS;
+            if to_be_deleted {
+                entities
+                    .delete(zone_entity)
+                    .expect("Expected to delete a Zone");
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod dying;
+mod subsystem;
+
+pub use self::{
+    dying::{ZoneDyingSystem, ZONE_TTL_SECS},
+    subsystem::{ZoneSlows, ZoneSubsystem},
+};
@@ -0,0 +1,218 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, Entity, Join, ReadExpect},
+};
+
+use std::collections::HashMap;
+
+use gv_core::{
+    actions::player::CastSpell,
+    collision_layers::{CollisionLayer, CollisionMatrix},
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            zone::{Zone, ZONE_TICK_FRAMES},
+            Dead, EntityNetMetadata, Player, ThreatTable, WorldPosition,
+        },
+        resources::net::{CastActionToExecute, CastActionsToExecute},
+        system_data::time::GameTimeService,
+    },
+    math::{Vector2, ZeroVector},
+    profile_scope,
+    stats::{Modifier, ModifierSource, Stat, StatModifiers},
+};
+
+use crate::{
+    ecs::{
+        resources::ClassDefinitions,
+        system_data::GameStateHelper,
+        systems::{monster::MobTargets, WriteExpectCell, WriteStorageCell},
+    },
+    utils::{entities::is_dead, spatial_grid::SpatialGrid},
+};
+
+const GRID_CELL_SIZE: f32 = 128.0;
+
+/// Speed factors of the mobs standing in slowing zones during a frame.
+#[derive(Default)]
+pub struct ZoneSlows(HashMap<Entity, f32>);
+
+impl ZoneSlows {
+    pub fn add_stat_modifiers(&self, entity: Entity, stat_modifiers: &mut StatModifiers) {
+        if let Some(speed_factor) = self.0.get(&entity) {
+            stat_modifiers.add(
+                Stat::MoveSpeed,
+                ModifierSource::Zone,
+                Modifier::Multiply(*speed_factor),
+            );
+        }
+    }
+}
+
+pub struct ZoneSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub class_definitions: &'s ReadExpect<'s, ClassDefinitions>,
+    pub collisions: &'s CollisionMatrix,
+    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub entity_net_metadata: WriteStorageCell<'s, EntityNetMetadata>,
+    pub transforms: WriteStorageCell<'s, Transform>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub zones: WriteStorageCell<'s, Zone>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub threat_tables: WriteStorageCell<'s, ThreatTable>,
+    pub dead: WriteStorageCell<'s, Dead>,
+}
+
+impl<'s> ZoneSubsystem<'s> {
+    /// Takes zone casts out of CastActionsToExecute and spawns zones at their target positions.
+    /// Clients spawn zones from the same cast actions they receive from a server.
+    pub fn spawn_zones(&self, frame_number: u64) {
+        profile_scope!("ZoneSubsystem::spawn_zones");
+        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
+        let (zone_casts, other_casts): (Vec<_>, Vec<_>) = cast_actions_to_execute
+            .actions
+            .drain(..)
+            .partition(|cast_action| cast_action.action.action.spell == CastSpell::Zone);
+        cast_actions_to_execute.actions = other_casts;
+
+        let mut zones = self.zones.borrow_mut();
+        let mut world_positions = self.world_positions.borrow_mut();
+        let mut transforms = self.transforms.borrow_mut();
+        let frame_seconds = self.game_time_service.tick_seconds();
+
+        for CastActionToExecute {
+            caster,
+            caster_class,
+            action: cast_action,
+            ..
+        } in zone_casts
+        {
+            // Rewinds execute the same cast actions again (action ids are unique only
+            // in multiplayer, but single player never rewinds).
+            let is_spawned = self.game_state_helper.is_multiplayer()
+                && (&*zones)
+                    .join()
+                    .any(|zone| zone.action_id == cast_action.action_id);
+            if is_spawned {
+                continue;
+            }
+
+            let zone_spell = &self.class_definitions.get(caster_class).zone;
+            let duration_frames = (zone_spell.duration_secs / frame_seconds).round() as u64;
+            let position = cast_action.action.target_position;
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(position.x, position.y, 0.0);
+
+            self.entities
+                .build_entity()
+                .with(transform, &mut transforms)
+                .with(WorldPosition::new(position), &mut world_positions)
+                .with(
+                    Zone {
+                        action_id: cast_action.action_id,
+                        caster,
+                        kind: zone_spell.kind,
+                        radius: zone_spell.radius,
+                        frame_spawned: frame_number,
+                        frame_expires: frame_number + duration_frames,
+                        damage_per_tick: zone_spell.damage_per_second
+                            * frame_seconds
+                            * ZONE_TICK_FRAMES as f32,
+                        speed_factor: zone_spell.speed_factor,
+                    },
+                    &mut zones,
+                )
+                .build();
+        }
+    }
+
+    /// Looks up the targets inside of the active zones, zones hit the layers that players'
+    /// hits land on. Damage ticks are added only on the authoritative side, while slows
+    /// are applied by both a server and clients. Players hit by zones only take damage,
+    /// as they have already moved by then.
+    pub fn apply_zone_effects(&self, frame_number: u64, mob_targets: &MobTargets) -> ZoneSlows {
+        profile_scope!("ZoneSubsystem::apply_zone_effects");
+        let zones = self.zones.borrow();
+        let mut zone_slows = ZoneSlows::default();
+        if !(&*zones).join().any(|zone| zone.is_active(frame_number)) {
+            return zone_slows;
+        }
+
+        let entity_net_metadata = self.entity_net_metadata.borrow();
+        let players = self.players.borrow();
+        let world_positions = self.world_positions.borrow();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut threat_tables = self.threat_tables.borrow_mut();
+        let dead = self.dead.borrow();
+
+        let mut targets = Vec::new();
+        for layer in self.collisions.targets(CollisionLayer::Players) {
+            match layer {
+                CollisionLayer::Monsters => {
+                    targets.extend(mob_targets.monsters.iter().map(|(monster, _)| *monster))
+                }
+                CollisionLayer::Minions => {
+                    targets.extend(mob_targets.minions.iter().map(|(minion, _)| *minion))
+                }
+                CollisionLayer::Players => {
+                    targets.extend((self.entities, &*players).join().map(|(player, _)| player))
+                }
+                _ => {}
+            }
+        }
+        let mut targets_grid = SpatialGrid::new(GRID_CELL_SIZE);
+        for target in targets {
+            let is_spawned = entity_net_metadata
+                .get(target)
+                .map_or(true, |entity_net_metadata| {
+                    entity_net_metadata.spawned_frame_number <= frame_number
+                });
+            if !is_spawned || is_dead(target, &*dead, frame_number) {
+                continue;
+            }
+            if let Some(world_position) = world_positions.get(target) {
+                targets_grid.insert(target, **world_position);
+            }
+        }
+
+        for (zone, zone_position) in (&*zones, &*world_positions).join() {
+            if !zone.is_active(frame_number) {
+                continue;
+            }
+            let is_tick = self.game_state_helper.is_authoritative()
+                && zone.damage_per_tick > 0.0
+                && zone.is_tick(frame_number);
+
+            for target in targets_grid.query_radius(**zone_position, zone.radius) {
+                if target == zone.caster {
+                    continue;
+                }
+                if zone.speed_factor < 1.0 {
+                    let speed_factor = zone_slows.0.entry(target).or_insert(1.0);
+                    *speed_factor = speed_factor.min(zone.speed_factor);
+                }
+                if is_tick {
+                    damage_histories
+                        .get_mut(target)
+                        .expect("Expected a DamageHistory")
+                        .add_entry(
+                            frame_number,
+                            DamageHistoryEntry {
+                                damage: zone.damage_per_tick,
+                                impulse: Vector2::zero(),
+                                is_crit: false,
+                                damage_type: Some(zone.kind.damage_type()),
+                            },
+                        );
+                    if let Some(threat_table) = threat_tables.get_mut(target) {
+                        threat_table.add(zone.caster, zone.damage_per_tick);
+                    }
+                }
+            }
+        }
+        zone_slows
+    }
+}
@@ -2,11 +2,14 @@
 #![feature(clamp)]
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
 
+pub mod console;
 pub mod ecs;
+pub mod scripting;
 pub mod states;
 pub mod utils;
 
 use amethyst::{
+    ecs::System,
     error::Error,
     prelude::{GameDataBuilder, World},
 };
@@ -18,13 +21,25 @@ use gv_core::{
             ActionUpdateIdProvider, CastActionsToExecute, EntityNetMetadataStorage,
             MultiplayerGameState,
         },
-        world::{FramedUpdates, PlayerActionUpdates, WorldStates},
+        world::{
+            FramedUpdates, PlayerActionUpdates, RollbackMode, RollbackStats, WorldStates,
+            SAVED_WORLD_STATES_LIMIT,
+        },
+        SimulationTick,
     },
+    profiling::FrameTimings,
 };
 
-use crate::ecs::{
-    resources::ConnectionEvents,
-    systems::{missile::MissileDyingSystem, monster::*, *},
+use crate::{
+    console::{Console, ConsoleCommands},
+    ecs::{
+        resources::{
+            ActiveLevelScript, ActiveObjectives, AntiCheatLog, AugmentChoices, ConnectionEvents,
+            ConsoleSpawnRequests, CountedCrits, CountedHeals, GameEvents, InteractionRequests,
+            MatchSeed, MissilePool, ShopPurchases, SummonRequests, WaveReadiness,
+        },
+        systems::{missile::MissileDyingSystem, monster::*, zone::ZoneDyingSystem, *},
+    },
 };
 
 pub fn build_game_logic_systems<'a, 'b>(
@@ -35,6 +50,18 @@ pub fn build_game_logic_systems<'a, 'b>(
     world.insert(ConnectionEvents(Vec::new()));
     world.insert(MultiplayerGameState::new());
     world.insert(ActionUpdateIdProvider::default());
+    world.insert(SimulationTick::default());
+    world.insert(RollbackMode::default());
+    world.insert(RollbackStats::default());
+    world.insert(ConsoleCommands::with_game_commands());
+    world.insert(Console::default());
+    world.insert(ActiveObjectives::default());
+    world.insert(ActiveLevelScript::default());
+    // Missiles can be reused only after the world states referring to them are gone.
+    world.insert(MissilePool::new(SAVED_WORLD_STATES_LIMIT as u64));
+    if !world.has_value::<FrameTimings>() {
+        world.insert(FrameTimings::default());
+    }
 
     // The resources which we need to remember to reset on starting a game.
     world.insert(FramedUpdates::<PlayerActionUpdates>::default());
@@ -42,38 +69,128 @@ pub fn build_game_logic_systems<'a, 'b>(
     world.insert(WorldStates::default());
     world.insert(CastActionsToExecute::default());
     world.insert(EntityNetMetadataStorage::new());
+    world.insert(AugmentChoices::default());
+    world.insert(ShopPurchases::default());
+    world.insert(SummonRequests::default());
+    world.insert(InteractionRequests::default());
+    world.insert(ConsoleSpawnRequests::default());
+    world.insert(WaveReadiness::default());
+    world.insert(CountedHeals::default());
+    world.insert(CountedCrits::default());
+    world.insert(MatchSeed::random());
+    world.insert(AntiCheatLog::default());
+    world.insert(GameEvents::default());
 
     let game_data_builder = game_data_builder
-        .with(PauseSystem, "pause_system", &["game_network_system"])
-        .with(LevelSystem::default(), "level_system", &["pause_system"])
-        .with(MonsterSpawnerSystem, "spawner_system", &["level_system"])
-        .with(
+        .with_timed(
+            PauseSystem,
+            "pause_system",
+            &dependencies_with_optional(
+                &["game_network_system"],
+                is_server,
+                &["client_actions_system"],
+            ),
+        )
+        .with_timed(LevelSystem::default(), "level_system", &["pause_system"])
+        .with_timed(
+            IntermissionSystem::default(),
+            "intermission_system",
+            &["level_system"],
+        )
+        // LevelSystem reserves the frame's spawn actions, the systems adding to them only
+        // have to run after it and before they get spawned.
+        .with_timed(SummonSystem, "summon_system", &["level_system"])
+        .with_timed(
+            ConsoleSpawnSystem,
+            "console_spawn_system",
+            &["level_system"],
+        )
+        .with_timed(
+            MonsterSplittingSystem,
+            "monster_splitting_system",
+            &["level_system"],
+        )
+        .with_timed(
+            MonsterSpawnerSystem,
+            "spawner_system",
+            &[
+                "summon_system",
+                "console_spawn_system",
+                "monster_splitting_system",
+            ],
+        )
+        .with_timed(
             ActionSystem,
             "action_system",
             &dependencies_with_optional(&["spawner_system"], !is_server, &["input_system"]),
         )
-        .with(
+        .with_timed(
             MonsterDyingSystem,
             "monster_dying_system",
             &["action_system"],
         )
-        .with(
-            MissileDyingSystem,
+        .with_timed(
+            MissileDyingSystem::default(),
             "missile_dying_system",
             &["action_system"],
         )
-        .with(
+        .with_timed(ZoneDyingSystem, "zone_dying_system", &["action_system"])
+        .with_timed(ScoreSystem::default(), "score_system", &["action_system"])
+        .with_timed(
+            ObjectiveSystem::default(),
+            "objective_system",
+            &["action_system"],
+        )
+        .with_timed(LevelScriptSystem, "level_script_system", &["action_system"])
+        .with_timed(AugmentSystem, "augment_system", &["score_system"])
+        .with_timed(
+            GameEventSystem::default(),
+            "game_event_system",
+            &["score_system"],
+        )
+        .with_timed(
+            EconomySystem::default(),
+            "economy_system",
+            &["action_system"],
+        )
+        .with_timed(
             StateSwitcherSystem,
             "state_switcher_system",
             &dependencies_with_optional(
-                &["monster_dying_system", "missile_dying_system"],
+                &[
+                    "monster_dying_system",
+                    "missile_dying_system",
+                    "zone_dying_system",
+                ],
                 !is_server,
                 &["menu_system"],
             ),
+        )
+        .with_timed(ConsoleSystem, "console_system", &[])
+        .with_timed(
+            CrashContextSystem,
+            "crash_context_system",
+            &["state_switcher_system"],
         );
     Ok(game_data_builder)
 }
 
+pub trait TimedSystemsBuilder {
+    /// Same as `with`, but also records the system in `FrameTimings`.
+    fn with_timed<S>(self, system: S, name: &'static str, dependencies: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static;
+}
+
+impl<'a, 'b> TimedSystemsBuilder for GameDataBuilder<'a, 'b> {
+    fn with_timed<S>(self, system: S, name: &'static str, dependencies: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        self.with(Timed::new(system, name), name, dependencies)
+    }
+}
+
 fn optional_dependencies(dependencies: &[&'static str], condition: bool) -> Vec<&'static str> {
     if condition {
         dependencies.to_vec()
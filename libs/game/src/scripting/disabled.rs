@@ -0,0 +1,27 @@
+use gv_core::classes::PlayerClass;
+
+use crate::{
+    ecs::resources::SpellDefinition,
+    scripting::{MonsterContext, ScriptedMonsterAction},
+};
+
+/// Can't be constructed, as the game is built without the `scripting` feature.
+pub enum Script {}
+
+impl Script {
+    pub fn compile(_source: &str) -> Result<Self, String> {
+        Err("the game is built without the scripting feature".to_owned())
+    }
+
+    pub fn on_missile_cast(&self, _: u64, _: PlayerClass, _: &mut SpellDefinition) {
+        match *self {}
+    }
+
+    pub fn on_hit(&self, _: u64, _: &str, _: f32, _: f32) -> f32 {
+        match *self {}
+    }
+
+    pub fn decide_action(&self, _: &MonsterContext) -> Option<ScriptedMonsterAction> {
+        match *self {}
+    }
+}
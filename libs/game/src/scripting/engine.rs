@@ -0,0 +1,211 @@
+use rhai::{
+    packages::{
+        ArithmeticPackage, BasicArrayPackage, BasicMapPackage, BasicStringPackage, LogicPackage,
+        Package,
+    },
+    Array, Dynamic, Engine, ImmutableString, Map, Scope, AST, INT,
+};
+
+use std::collections::HashSet;
+
+use gv_core::{
+    classes::PlayerClass,
+    math::{deterministic, Vector2},
+};
+
+use crate::{
+    ecs::resources::SpellDefinition,
+    scripting::{MonsterContext, ScriptedMonsterAction},
+};
+
+/// Operations are counted the same way on every machine, so a hook running out of them
+/// falls back to the default behaviour everywhere.
+const MAX_OPERATIONS: u64 = 10_000;
+const MAX_CALL_LEVELS: usize = 16;
+const MAX_COLLECTION_SIZE: usize = 256;
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// Names of the functions defined by the script, hooks that aren't defined aren't called.
+    functions: HashSet<String>,
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let engine = create_engine();
+        let ast = engine.compile(source).map_err(|err| err.to_string())?;
+        let functions = ast
+            .iter_functions()
+            .map(|(_, name, _)| name.to_owned())
+            .collect();
+        Ok(Self {
+            engine,
+            ast,
+            functions,
+        })
+    }
+
+    pub fn on_missile_cast(
+        &self,
+        frame_number: u64,
+        caster_class: PlayerClass,
+        spell_definition: &mut SpellDefinition,
+    ) {
+        let mut cast = Map::new();
+        cast.insert("spell".into(), Dynamic::from("Missile".to_owned()));
+        cast.insert(
+            "class".into(),
+            Dynamic::from(caster_class.name().to_owned()),
+        );
+        cast.insert("frame".into(), Dynamic::from(frame_number as INT));
+        cast.insert("damage".into(), Dynamic::from(spell_definition.damage));
+        cast.insert("radius".into(), Dynamic::from(spell_definition.radius));
+        cast.insert(
+            "knockback".into(),
+            Dynamic::from(spell_definition.knockback),
+        );
+        cast.insert(
+            "bounces".into(),
+            Dynamic::from(INT::from(spell_definition.bounces)),
+        );
+
+        let changes = match self
+            .call("on_cast", cast)
+            .and_then(|result| result.try_cast::<Map>())
+        {
+            Some(changes) => changes,
+            None => return,
+        };
+        let changed = |key: &str| changes.get(key).and_then(number);
+        // Every value is checked before applying any, so that a cast is either changed
+        // by a hook completely or not at all.
+        let damage = changed("damage").unwrap_or(spell_definition.damage);
+        let radius = changed("radius").unwrap_or(spell_definition.radius);
+        let knockback = changed("knockback").unwrap_or(spell_definition.knockback);
+        let bounces = match changes.get("bounces") {
+            Some(bounces) => match bounces.clone().try_cast::<INT>() {
+                Some(bounces) if bounces >= 0 && bounces <= INT::from(u8::MAX) => bounces as u32,
+                _ => {
+                    log::warn!("Script hook on_cast returned invalid bounces");
+                    return;
+                }
+            },
+            None => spell_definition.bounces,
+        };
+        if damage < 0.0 || radius <= 0.0 {
+            log::warn!("Script hook on_cast returned a negative damage or radius");
+            return;
+        }
+
+        spell_definition.damage = damage;
+        spell_definition.radius = radius;
+        spell_definition.knockback = knockback;
+        spell_definition.bounces = bounces;
+    }
+
+    pub fn on_hit(
+        &self,
+        frame_number: u64,
+        monster_name: &str,
+        monster_health: f32,
+        damage: f32,
+    ) -> f32 {
+        let mut hit = Map::new();
+        hit.insert("frame".into(), Dynamic::from(frame_number as INT));
+        hit.insert("monster".into(), Dynamic::from(monster_name.to_owned()));
+        hit.insert("monster_health".into(), Dynamic::from(monster_health));
+        hit.insert("damage".into(), Dynamic::from(damage));
+
+        self.call("on_hit", hit)
+            .as_ref()
+            .and_then(number)
+            .filter(|damage| *damage >= 0.0)
+            .unwrap_or(damage)
+    }
+
+    pub fn decide_action(&self, monster: &MonsterContext) -> Option<ScriptedMonsterAction> {
+        let mut context = Map::new();
+        context.insert("frame".into(), Dynamic::from(monster.frame_number as INT));
+        context.insert("name".into(), Dynamic::from(monster.name.to_owned()));
+        context.insert("health".into(), Dynamic::from(monster.health));
+        context.insert("x".into(), Dynamic::from(monster.position.x));
+        context.insert("y".into(), Dynamic::from(monster.position.y));
+        context.insert("action".into(), Dynamic::from(monster.action.to_owned()));
+        context.insert(
+            "seconds_since_action".into(),
+            Dynamic::from(monster.seconds_since_action),
+        );
+
+        let result = self.call("decide_action", context)?;
+        if let Some(action) = result.clone().try_cast::<ImmutableString>() {
+            return match action.as_str() {
+                "default" => None,
+                "keep" => Some(ScriptedMonsterAction::Keep),
+                "idle" => Some(ScriptedMonsterAction::Idle),
+                other => {
+                    log::warn!("Script hook decide_action returned {:?}", other);
+                    None
+                }
+            };
+        }
+
+        let destination = result
+            .try_cast::<Map>()
+            .and_then(|mut action| action.remove("move_to"))
+            .and_then(|move_to| move_to.try_cast::<Array>())
+            .filter(|move_to| move_to.len() == 2)
+            .and_then(|move_to| Some((number(&move_to[0])?, number(&move_to[1])?)));
+        match destination {
+            Some((x, y)) => Some(ScriptedMonsterAction::Move(Vector2::new(x, y))),
+            None => {
+                log::warn!("Script hook decide_action returned an unexpected value");
+                None
+            }
+        }
+    }
+
+    fn call(&self, hook: &str, context: Map) -> Option<Dynamic> {
+        if !self.functions.contains(hook) {
+            return None;
+        }
+        // Every call gets a fresh scope, so that hooks can't carry anything over to the next
+        // frame, which a rollback would re-simulate.
+        self.engine
+            .call_fn::<_, Dynamic>(&mut Scope::new(), &self.ast, hook, (context,))
+            .map_err(|err| log::warn!("Script hook {} failed: {}", hook, err))
+            .ok()
+    }
+}
+
+/// Only the packages without platform-dependent or time-dependent functions are loaded.
+fn create_engine() -> Engine {
+    let mut engine = Engine::new_raw();
+    engine.load_package(ArithmeticPackage::new().get());
+    engine.load_package(LogicPackage::new().get());
+    engine.load_package(BasicStringPackage::new().get());
+    engine.load_package(BasicArrayPackage::new().get());
+    engine.load_package(BasicMapPackage::new().get());
+
+    engine.register_fn("sin", |angle: f32| deterministic::sin_cos(angle).0);
+    engine.register_fn("cos", |angle: f32| deterministic::sin_cos(angle).1);
+    engine.register_fn("atan2", deterministic::atan2);
+    // Square roots are correctly rounded by IEEE 754.
+    engine.register_fn("sqrt", f32::sqrt);
+
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_COLLECTION_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine
+}
+
+/// Scripts may write whole numbers as integers, non-finite results are rejected.
+fn number(value: &Dynamic) -> Option<f32> {
+    value
+        .clone()
+        .try_cast::<f32>()
+        .or_else(|| value.clone().try_cast::<INT>().map(|value| value as f32))
+        .filter(|value| value.is_finite())
+}
@@ -0,0 +1,129 @@
+//! Mod hooks written in [Rhai](https://rhai.rs), which are loaded from
+//! `resources/scripts/hooks.rhai` (or a content pack's `scripts/hooks.rhai`)
+//! if the game is built with the `scripting` feature.
+//!
+//! A script can define any of these functions, each of them receives a map describing
+//! what's happening:
+//! - `on_cast(cast)` is called when a missile is cast, it returns a map with the changed
+//!   `damage`, `radius`, `knockback` or `bounces`;
+//! - `on_hit(hit)` is called when a missile hits a monster, it returns the dealt damage;
+//! - `decide_action(monster)` is called when a monster picks its next action, it returns
+//!   `"keep"`, `"idle"`, `#{ move_to: [x, y] }` or `"default"` to let the game decide.
+//!
+//! Servers and predicting clients have to get the same results from the hooks, so scripts
+//! can't keep any state between calls and can't read the time or random numbers, trigonometry
+//! goes through `math::deterministic`. A hook that fails, runs for too long or returns
+//! something unexpected falls back to the default behaviour.
+
+#[cfg(not(feature = "scripting"))]
+mod disabled;
+#[cfg(feature = "scripting")]
+mod engine;
+
+use amethyst::ecs::World;
+
+use std::{fs, path::Path};
+
+use gv_core::{
+    classes::PlayerClass,
+    content_packs::{ContentPacks, HOOKS_SCRIPT_FILE},
+    math::Vector2,
+};
+
+use crate::ecs::resources::SpellDefinition;
+#[cfg(not(feature = "scripting"))]
+use disabled as engine;
+
+pub const HOOKS_SCRIPT_PATH: &str = "resources/scripts/hooks.rhai";
+
+/// Everything a monster's `decide_action` hook gets to know.
+pub struct MonsterContext<'a> {
+    pub frame_number: u64,
+    pub name: &'a str,
+    pub health: f32,
+    pub position: Vector2,
+    /// The name of the current `MobAction` variant.
+    pub action: &'static str,
+    pub seconds_since_action: f32,
+}
+
+pub enum ScriptedMonsterAction {
+    Keep,
+    Idle,
+    Move(Vector2),
+}
+
+#[derive(Default)]
+pub struct ScriptHooks {
+    script: Option<engine::Script>,
+}
+
+impl ScriptHooks {
+    pub fn register(world: &mut World) {
+        let script_hooks = Self::from_content_packs(&world.fetch::<ContentPacks>());
+        world.insert(script_hooks);
+    }
+
+    /// Uses the hooks from the last content pack having them, or the ones
+    /// from `resources/scripts/hooks.rhai`.
+    pub fn from_content_packs(content_packs: &ContentPacks) -> Self {
+        let script_hooks = match content_packs.hooks_script() {
+            Some((pack_name, source)) => std::str::from_utf8(source)
+                .map_err(|err| err.to_string())
+                .and_then(Self::from_source)
+                .map_err(|err| format!("{}/{}: {}", pack_name, HOOKS_SCRIPT_FILE, err)),
+            None => Self::load(Path::new(HOOKS_SCRIPT_PATH))
+                .map_err(|err| format!("{}: {}", HOOKS_SCRIPT_PATH, err)),
+        };
+        script_hooks.unwrap_or_else(|err| {
+            log::error!("Failed to load the script hooks: {}", err);
+            Self::default()
+        })
+    }
+
+    /// A missing file means there are no hooks.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Self::from_source(&source)
+    }
+
+    pub fn from_source(source: &str) -> Result<Self, String> {
+        Ok(Self {
+            script: Some(engine::Script::compile(source)?),
+        })
+    }
+
+    pub fn on_missile_cast(
+        &self,
+        frame_number: u64,
+        caster_class: PlayerClass,
+        spell_definition: &mut SpellDefinition,
+    ) {
+        if let Some(script) = &self.script {
+            script.on_missile_cast(frame_number, caster_class, spell_definition);
+        }
+    }
+
+    /// Returns the damage a missile deals to a hit monster.
+    pub fn on_hit(
+        &self,
+        frame_number: u64,
+        monster_name: &str,
+        monster_health: f32,
+        damage: f32,
+    ) -> f32 {
+        self.script.as_ref().map_or(damage, |script| {
+            script.on_hit(frame_number, monster_name, monster_health, damage)
+        })
+    }
+
+    /// Returns `None` if the game should decide itself.
+    pub fn decide_action(&self, monster: &MonsterContext) -> Option<ScriptedMonsterAction> {
+        self.script
+            .as_ref()
+            .and_then(|script| script.decide_action(monster))
+    }
+}
@@ -0,0 +1,79 @@
+use amethyst::{
+    ecs::{Read, ReadExpect},
+    prelude::{GameData, SimpleState, SimpleTrans, StateData, StateEvent, Trans},
+};
+
+use gv_client_shared::{
+    ecs::{
+        resources::{LevelAssets, LevelLoadingError},
+        system_data::LevelAssetsLoader,
+    },
+    utils,
+};
+use gv_core::{
+    ecs::resources::{net::MultiplayerGameState, GameEngineState, NewGameEngineState},
+    level_loading::LevelKey,
+    room_settings::RoomSettings,
+};
+
+use crate::states::{MenuState, PlayingState};
+
+/// Is entered on clients before `PlayingState`, waits for the assets of the level while
+/// the menu shows a progress bar. They're usually prefetched, so it lasts a single frame.
+/// If they fail to load, it switches back to `MenuState` with a `LevelLoadingError`.
+#[derive(Default)]
+pub struct LevelLoadingState;
+
+impl SimpleState for LevelLoadingState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        log::info!("LevelLoadingState started");
+        let world = data.world;
+        *world.fetch_mut::<GameEngineState>() = GameEngineState::LoadingLevel;
+
+        world.exec(
+            |(room_settings, multiplayer_game_state, mut level_assets_loader): (
+                ReadExpect<RoomSettings>,
+                ReadExpect<MultiplayerGameState>,
+                LevelAssetsLoader,
+            )| {
+                level_assets_loader.load(LevelKey::new(&room_settings, &multiplayer_game_state));
+            },
+        );
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let progress = data.world.exec(|level_assets: Read<LevelAssets>| {
+            level_assets
+                .0
+                .as_ref()
+                .map(|level_asset_handles| level_asset_handles.progress())
+        });
+        let progress = match progress {
+            Some(progress) => progress,
+            None => return Trans::None,
+        };
+        if progress.has_failed() {
+            let error = progress.errors.join(", ");
+            log::error!("Failed to load the level: {}", error);
+            data.world.insert(LevelLoadingError(Some(error)));
+            *data.world.write_resource::<NewGameEngineState>() =
+                NewGameEngineState(GameEngineState::Menu);
+            return Trans::Switch(Box::new(MenuState));
+        }
+        if progress.is_complete() {
+            Trans::Switch(Box::new(PlayingState))
+        } else {
+            Trans::None
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        utils::handle_window_event(&world, &event);
+        Trans::None
+    }
+}
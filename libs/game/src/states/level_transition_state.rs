@@ -0,0 +1,55 @@
+#[cfg(not(feature = "client"))]
+use amethyst::ecs::{Join, WriteStorage};
+#[cfg(feature = "client")]
+use amethyst::prelude::{SimpleTrans, StateEvent, Trans};
+use amethyst::{
+    ecs::World,
+    prelude::{GameData, SimpleState, StateData},
+};
+
+#[cfg(feature = "client")]
+use gv_client_shared::utils;
+use gv_core::ecs::resources::GameEngineState;
+#[cfg(not(feature = "client"))]
+use gv_core::ecs::{components::NetConnectionModel, resources::world::ServerWorldUpdates};
+
+use super::menu_state::reset_game_world;
+
+/// Is entered between campaign levels, the next level is started with `PlayingState`
+/// once the server decides to (see `ServerMessagePayload::LevelTransition`).
+pub struct LevelTransitionState;
+
+impl SimpleState for LevelTransitionState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        log::info!("LevelTransitionState started");
+        let world = data.world;
+        *world.fetch_mut::<GameEngineState>() = GameEngineState::LevelTransition;
+
+        reset_game_world(world);
+        reset_server_world_updates(world);
+    }
+
+    #[cfg(feature = "client")]
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        utils::handle_window_event(&world, &event);
+        Trans::None
+    }
+}
+
+/// Clients start acknowledging updates from scratch with the next level.
+#[cfg(not(feature = "client"))]
+fn reset_server_world_updates(world: &mut World) {
+    world.fetch_mut::<ServerWorldUpdates>().updates.clear();
+    let mut net_connection_models = world.system_data::<WriteStorage<NetConnectionModel>>();
+    for net_connection_model in (&mut net_connection_models).join() {
+        net_connection_model.last_acknowledged_update = None;
+    }
+}
+
+#[cfg(feature = "client")]
+fn reset_server_world_updates(_world: &mut World) {}
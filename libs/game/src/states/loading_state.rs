@@ -2,7 +2,8 @@
 use amethyst::{
     assets::ProgressCounter,
     assets::{AssetStorage, Handle, Loader, PrefabLoader, RonFormat},
-    renderer::{ImageFormat, SpriteSheet, SpriteSheetFormat, Texture},
+    audio::{output::init_output, Source, SourceHandle, WavFormat},
+    renderer::{ImageFormat, Sprite, SpriteSheet, SpriteSheetFormat, Texture},
     ui::{FontAsset, TtfFormat, UiCreator},
 };
 use amethyst::{
@@ -13,19 +14,34 @@ use amethyst::{
 #[cfg(feature = "client")]
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
 #[cfg(feature = "client")]
-use gv_client_shared::ecs::{
-    components::{PlayerColor, SpellParticle},
-    resources::{AssetHandles, DummyAssetHandles, HealthUiMesh},
+use gv_client_shared::{
+    ecs::{
+        components::{PlayerColor, SpellParticle},
+        resources::{AssetHandles, DummyAssetHandles, HealthUiMesh},
+    },
+    sprite_atlas::{
+        sheet_sprites_path, sheet_texture_path, SpriteAtlas, SpriteAtlasFormat,
+        SPRITE_ATLAS_METADATA_PATH, SPRITE_ATLAS_TEXTURE_PATH,
+    },
+};
+use gv_core::{
+    balance::Balance,
+    content_packs::ContentPacks,
+    ecs::resources::{GameEngineState, GameLevelState, GameTime, MatchScore, NewGameEngineState},
+    room_settings::RoomSettings,
 };
-use gv_core::ecs::resources::{GameEngineState, GameLevelState, GameTime, NewGameEngineState};
 
-use crate::ecs::resources::MonsterDefinitions;
+use crate::{
+    ecs::resources::{ClassDefinitions, MonsterDefinitions, SpellDefinitions},
+    scripting::ScriptHooks,
+};
 
 #[cfg(feature = "client")]
 #[derive(Default)]
 pub struct LoadingState {
     progress_counter: ProgressCounter,
     atlas_progress_counter: ProgressCounter,
+    sprite_atlas: Option<Handle<SpriteAtlas>>,
     atlas_is_loaded: bool,
     rest_is_loaded: bool,
 }
@@ -40,9 +56,25 @@ impl SimpleState for LoadingState {
         let world = data.world;
 
         self.register_client_dependencies(world);
+        // A server inserts its own Balance with the content packs applied, a client loads
+        // the installed packs on startup.
+        if !world.has_value::<Balance>() {
+            world.insert(Balance::default());
+        }
+        if !world.has_value::<ContentPacks>() {
+            world.insert(ContentPacks::default());
+        }
         MonsterDefinitions::register(world);
+        ClassDefinitions::register(world);
+        SpellDefinitions::register(world);
+        ScriptHooks::register(world);
+        world.insert(RoomSettings::default());
         world.insert(GameLevelState::default());
-        world.insert(GameTime::default());
+        world.insert(MatchScore::default());
+        // A server inserts its own GameTime to run at a configured tick rate.
+        if !world.has_value::<GameTime>() {
+            world.insert(GameTime::default());
+        }
         world.insert(GameEngineState::Loading);
         world.insert(NewGameEngineState(GameEngineState::Loading));
     }
@@ -79,6 +111,17 @@ impl LoadingState {
                     },
                 );
                 world.insert(DummyAssetHandles { dummy_prefab });
+                // Sheets are built from the atlas, so its metadata has to be loaded before them.
+                self.sprite_atlas = Some({
+                    let loader = world.read_resource::<Loader>();
+                    let sprite_atlas_storage = world.read_resource::<AssetStorage<SpriteAtlas>>();
+                    loader.load(
+                        SPRITE_ATLAS_METADATA_PATH,
+                        SpriteAtlasFormat,
+                        &mut self.atlas_progress_counter,
+                        &sprite_atlas_storage,
+                    )
+                });
                 false
             }
             (true, true, false, _) => {
@@ -103,9 +146,62 @@ impl LoadingState {
                     "resources/levels/desert.ron",
                     &mut self.progress_counter,
                 );
-
+                let sprite_sheet_loader = SpriteSheetLoader::new(
+                    world,
+                    self.sprite_atlas.take(),
+                    &mut self.progress_counter,
+                );
+                let hats_handle =
+                    sprite_sheet_loader.load(world, "hats", &mut self.progress_counter);
+                let emotes_handle =
+                    sprite_sheet_loader.load(world, "emotes", &mut self.progress_counter);
+                let augments_handle =
+                    sprite_sheet_loader.load(world, "augments", &mut self.progress_counter);
+                let minion_marker_handle =
+                    sprite_sheet_loader.load(world, "minion_marker", &mut self.progress_counter);
+                let player_markers_handle =
+                    sprite_sheet_loader.load(world, "player_markers", &mut self.progress_counter);
+                let combat_numbers_handle =
+                    sprite_sheet_loader.load(world, "combat_numbers", &mut self.progress_counter);
+                let zones_handle =
+                    sprite_sheet_loader.load(world, "zones", &mut self.progress_counter);
+                let auras_handle =
+                    sprite_sheet_loader.load(world, "auras", &mut self.progress_counter);
+                let elite_affixes_handle =
+                    sprite_sheet_loader.load(world, "elite_affixes", &mut self.progress_counter);
+                let resistances_handle =
+                    sprite_sheet_loader.load(world, "resistances", &mut self.progress_counter);
+                let blood_handle =
+                    sprite_sheet_loader.load(world, "blood", &mut self.progress_counter);
+                let hit_markers_handle =
+                    sprite_sheet_loader.load(world, "hit_markers", &mut self.progress_counter);
+                // Sounds stay silent if there's no audio device.
+                init_output(world);
+                let music_handles = vec![
+                    load_sound(
+                        world,
+                        "resources/music/calm.wav",
+                        &mut self.progress_counter,
+                    ),
+                    load_sound(
+                        world,
+                        "resources/music/combat.wav",
+                        &mut self.progress_counter,
+                    ),
+                    load_sound(
+                        world,
+                        "resources/music/boss.wav",
+                        &mut self.progress_counter,
+                    ),
+                ];
+                let hit_confirm_handle = load_sound(
+                    world,
+                    "resources/sounds/hit_confirm.wav",
+                    &mut self.progress_counter,
+                );
                 let (mage_prefab, beetle_prefab) = world.exec(
                     |prefab_loader: PrefabLoader<'_, GameSpriteAnimationPrefab>| {
+                        // Every class uses the mage sprites, scaled with `ClassDefinition::scale`.
                         let mage_prefab = prefab_loader.load(
                             "resources/prefabs/mage.ron",
                             RonFormat,
@@ -128,6 +224,7 @@ impl LoadingState {
                         creator.create("resources/ui/lobby_menu.ron", ()),
                         creator.create("resources/ui/multiplayer_menu.ron", ()),
                         creator.create("resources/ui/restart_menu.ron", ()),
+                        creator.create("resources/ui/customization_menu.ron", ()),
                         creator.create("resources/ui/modal.ron", ()),
                         creator.create("resources/ui/game_overlays.ron", ()),
                     )
@@ -137,6 +234,20 @@ impl LoadingState {
                     mage_prefab,
                     beetle_prefab,
                     landscape: landscape_handle,
+                    hats: hats_handle,
+                    emotes: emotes_handle,
+                    augments: augments_handle,
+                    minion_marker: minion_marker_handle,
+                    player_markers: player_markers_handle,
+                    combat_numbers: combat_numbers_handle,
+                    zones: zones_handle,
+                    auras: auras_handle,
+                    elite_affixes: elite_affixes_handle,
+                    resistances: resistances_handle,
+                    blood: blood_handle,
+                    hit_markers: hit_markers_handle,
+                    music: music_handles,
+                    hit_confirm: hit_confirm_handle,
                     ui_font: ui_font_handle,
                 });
 
@@ -179,3 +290,107 @@ fn load_sprite_sheet(
         &sprite_sheet_store,
     )
 }
+
+/// Builds the small sprite sheets from the sprite atlas. Sheets that weren't packed (or all
+/// of them, if the atlas hasn't been built) are loaded from their own images.
+#[cfg(feature = "client")]
+struct SpriteSheetLoader {
+    atlas: Option<(SpriteAtlas, Handle<Texture>)>,
+}
+
+#[cfg(feature = "client")]
+impl SpriteSheetLoader {
+    /// Expects `sprite_atlas` to have finished loading, an atlas that has failed to load
+    /// is skipped.
+    fn new(
+        world: &mut World,
+        sprite_atlas: Option<Handle<SpriteAtlas>>,
+        progress: &mut ProgressCounter,
+    ) -> Self {
+        let sprite_atlas = sprite_atlas.and_then(|sprite_atlas| {
+            world
+                .read_resource::<AssetStorage<SpriteAtlas>>()
+                .get(&sprite_atlas)
+                .cloned()
+        });
+        let sprite_atlas = match sprite_atlas {
+            Some(sprite_atlas) => sprite_atlas,
+            None => {
+                log::warn!(
+                    "Loading sprite sheets without the sprite atlas, run `assets_packer sprites`"
+                );
+                return Self { atlas: None };
+            }
+        };
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        let texture = loader.load(
+            SPRITE_ATLAS_TEXTURE_PATH,
+            ImageFormat::default(),
+            progress,
+            &texture_storage,
+        );
+        Self {
+            atlas: Some((sprite_atlas, texture)),
+        }
+    }
+
+    fn load(
+        &self,
+        world: &mut World,
+        name: &str,
+        progress: &mut ProgressCounter,
+    ) -> Handle<SpriteSheet> {
+        let atlas_sheet = self.atlas.as_ref().and_then(|(sprite_atlas, texture)| {
+            sprite_atlas
+                .sheet(name)
+                .map(|sprites| (sprite_atlas, texture, sprites))
+        });
+        let (sprite_atlas, texture, sprites) = match atlas_sheet {
+            Some(atlas_sheet) => atlas_sheet,
+            None => {
+                if self.atlas.is_some() {
+                    log::warn!(
+                        "Sprite sheet {} isn't in the sprite atlas, run `assets_packer sprites`",
+                        name
+                    );
+                }
+                return load_sprite_sheet(
+                    world,
+                    &sheet_texture_path(name),
+                    &sheet_sprites_path(name),
+                    progress,
+                );
+            }
+        };
+        let sprite_sheet = SpriteSheet {
+            texture: texture.clone(),
+            sprites: sprites
+                .iter()
+                .map(|sprite| {
+                    Sprite::from_pixel_values(
+                        sprite_atlas.texture_width,
+                        sprite_atlas.texture_height,
+                        sprite.width,
+                        sprite.height,
+                        sprite.x,
+                        sprite.y,
+                        [0.0, 0.0],
+                        false,
+                        false,
+                    )
+                })
+                .collect(),
+        };
+        let loader = world.read_resource::<Loader>();
+        let sprite_sheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
+        loader.load_from_data(sprite_sheet, progress, &sprite_sheet_storage)
+    }
+}
+
+#[cfg(feature = "client")]
+fn load_sound(world: &mut World, wav_path: &str, progress: &mut ProgressCounter) -> SourceHandle {
+    let loader = world.read_resource::<Loader>();
+    let source_storage = world.read_resource::<AssetStorage<Source>>();
+    loader.load(wav_path, WavFormat, progress, &source_storage)
+}
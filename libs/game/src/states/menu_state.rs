@@ -1,5 +1,5 @@
 use amethyst::{
-    ecs::{Entities, Join, ReadStorage},
+    ecs::{Entities, Join, ReadStorage, World, WriteExpect},
     prelude::{GameData, SimpleState, StateData},
     renderer::Camera,
 };
@@ -16,7 +16,10 @@ use gv_core::{
     },
 };
 
-use crate::ecs::systems::{ClientFrameUpdate, FrameUpdate};
+use crate::ecs::{
+    resources::MissilePool,
+    systems::{ClientFrameUpdate, FrameUpdate},
+};
 
 pub struct MenuState;
 
@@ -31,49 +34,64 @@ impl SimpleState for MenuState {
             game_level_state.is_over = false;
             drop(game_level_state);
 
-            world.insert(FramedUpdates::<ClientFrameUpdate>::default());
-            world.insert(FramedUpdates::<FrameUpdate>::default());
-            world.insert(FramedUpdates::<SpawnActions>::default());
-            world.insert(WorldStates::default());
-            world.insert(CastActionsToExecute::default());
-            world.insert(EntityNetMetadataStorage::new());
-
-            world.exec(
-                |(entities, entity_net_metadata, players, monsters, missiles, cameras): (
-                    Entities,
-                    ReadStorage<EntityNetMetadata>,
-                    ReadStorage<Player>,
-                    ReadStorage<Monster>,
-                    ReadStorage<Missile>,
-                    ReadStorage<Camera>,
-                )| {
-                    for (entity_net_metadata, _) in (&entities, &entity_net_metadata).join() {
-                        entities
-                            .delete(entity_net_metadata)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (player_entity, _) in (&entities, &players).join() {
-                        entities
-                            .delete(player_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (monster_entity, _) in (&entities, &monsters).join() {
-                        entities
-                            .delete(monster_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (missile_entity, _) in (&entities, &missiles).join() {
-                        entities
-                            .delete(missile_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                    for (camera_entity, _) in (&entities, &cameras).join() {
-                        entities
-                            .delete(camera_entity)
-                            .expect("Expected to clean up an entity");
-                    }
-                },
-            );
+            reset_game_world(world);
         }
     }
 }
+
+/// Deletes the entities of a finished game and resets its updates history.
+pub(super) fn reset_game_world(world: &mut World) {
+    world.insert(FramedUpdates::<ClientFrameUpdate>::default());
+    world.insert(FramedUpdates::<FrameUpdate>::default());
+    world.insert(FramedUpdates::<SpawnActions>::default());
+    world.insert(WorldStates::default());
+    world.insert(CastActionsToExecute::default());
+    world.insert(EntityNetMetadataStorage::new());
+
+    world.exec(
+        |(
+            entities,
+            mut missile_pool,
+            entity_net_metadata,
+            players,
+            monsters,
+            missiles,
+            cameras,
+        ): (
+            Entities,
+            WriteExpect<MissilePool>,
+            ReadStorage<EntityNetMetadata>,
+            ReadStorage<Player>,
+            ReadStorage<Monster>,
+            ReadStorage<Missile>,
+            ReadStorage<Camera>,
+        )| {
+            for (entity_net_metadata, _) in (&entities, &entity_net_metadata).join() {
+                entities
+                    .delete(entity_net_metadata)
+                    .expect("Expected to clean up an entity");
+            }
+            for (player_entity, _) in (&entities, &players).join() {
+                entities
+                    .delete(player_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (monster_entity, _) in (&entities, &monsters).join() {
+                entities
+                    .delete(monster_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (missile_entity, _) in (&entities, &missiles).join() {
+                entities
+                    .delete(missile_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            for (camera_entity, _) in (&entities, &cameras).join() {
+                entities
+                    .delete(camera_entity)
+                    .expect("Expected to clean up an entity");
+            }
+            missile_pool.clear(&entities);
+        },
+    );
+}
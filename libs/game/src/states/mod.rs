@@ -1,5 +1,13 @@
+#[cfg(feature = "client")]
+mod level_loading_state;
+mod level_transition_state;
 mod loading_state;
 mod menu_state;
 mod playing_state;
 
-pub use self::{loading_state::LoadingState, menu_state::MenuState, playing_state::PlayingState};
+#[cfg(feature = "client")]
+pub use self::level_loading_state::LevelLoadingState;
+pub use self::{
+    level_transition_state::LevelTransitionState, loading_state::LoadingState,
+    menu_state::MenuState, playing_state::PlayingState,
+};
@@ -1,39 +1,58 @@
 #[cfg(feature = "client")]
+use amethyst::ecs::Entities;
+#[cfg(feature = "client")]
+use amethyst::prelude::StateEvent;
 use amethyst::{
-    ecs::ReadExpect,
-    prelude::{SimpleTrans, StateEvent, Trans},
+    core::Time,
+    ecs::{Join, ReadExpect, ReadStorage, SystemData, World, WriteExpect, WriteStorage},
+    prelude::{GameData, SimpleState, SimpleTrans, StateData, Trans},
 };
 #[cfg(not(feature = "client"))]
-use amethyst::{
-    ecs::{Join, ReadStorage, Write},
-    network::simulation::TransportResource,
-};
-use amethyst::{
-    ecs::{SystemData, World, WriteExpect, WriteStorage},
-    prelude::{GameData, SimpleState, StateData},
-};
+use amethyst::{ecs::Write, network::simulation::TransportResource};
+
+use std::time::Duration;
 
 #[cfg(feature = "client")]
-use gv_client_shared::ecs::factories::PlayerClientFactory;
+use gv_client_shared::{accessibility::MarkerShape, ecs::factories::PlayerClientFactory};
 #[cfg(feature = "client")]
 use gv_client_shared::{
-    ecs::{factories::CameraFactory, resources::MultiplayerRoomState},
+    ecs::{components::MenuDemoEntity, factories::CameraFactory, resources::MultiplayerRoomState},
+    settings::Settings,
     utils,
 };
-use gv_core::ecs::{
-    components::EntityNetMetadata,
-    resources::{
-        net::{EntityNetMetadataStorage, MultiplayerGameState},
-        GameEngineState, GameLevelState,
+#[cfg(feature = "client")]
+use gv_core::ecs::resources::{DEFAULT_TICK_RATE, FRAME_RATE};
+use gv_core::{
+    actions::{mob::MobAction, Action},
+    augments::PlayerAugments,
+    campaign::CAMPAIGN_LEVELS,
+    economy::PlayerWallet,
+    ecs::{
+        components::{EntityNetMetadata, NetWorldPosition, Player, WorldPosition},
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            GameEngineState, GameLevelState, GameTime, MatchScore, SimulationTick,
+        },
+        system_data::time::GameTimeService,
     },
-    system_data::time::GameTimeService,
+    level_scripts::{LevelScript, LevelScriptRunner},
+    room_settings::RoomSettings,
 };
-#[cfg(feature = "client")]
-use gv_core::PLAYER_COLORS;
 #[cfg(not(feature = "client"))]
-use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
+use gv_core::{
+    balance::Balance, ecs::components::NetConnectionModel,
+    net::server_message::ServerMessagePayload,
+};
 
-use crate::ecs::factories::{LandscapeFactory, PlayerFactory};
+use crate::ecs::{
+    factories::{LandscapeFactory, PlayerFactory},
+    resources::{
+        ActiveLevelScript, ActiveObjectives, AntiCheatLog, AugmentChoices, ConsoleSpawnRequests,
+        CountedCrits, CountedHeals, GameEvents, InteractionRequests, MatchSeed, ShopPurchases,
+        SummonRequests, WaveReadiness,
+    },
+    systems::monster::MonsterSpawnerSystemData,
+};
 #[cfg(not(feature = "client"))]
 use crate::utils::net::broadcast_message_reliable;
 
@@ -46,15 +65,55 @@ impl SimpleState for PlayingState {
         let world = data.world;
         *world.fetch_mut::<GameEngineState>() = GameEngineState::Playing;
 
-        world.insert(GameLevelState::default());
+        let map_name = world.fetch::<RoomSettings>().map_name();
+        world.insert(GameLevelState {
+            map_name,
+            ..GameLevelState::default()
+        });
+        world.insert(MatchScore::default());
+        world.insert(AugmentChoices::default());
+        world.insert(ShopPurchases::default());
+        world.insert(SummonRequests::default());
+        world.insert(InteractionRequests::default());
+        world.insert(ConsoleSpawnRequests::default());
+        world.insert(WaveReadiness::default());
+        world.insert(CountedHeals::default());
+        world.insert(CountedCrits::default());
+        world.insert(MatchSeed::random());
+        world.insert(AntiCheatLog::default());
+        world.insert(GameEvents::default());
+        // Restarting the tutorial starts it from the first objective.
+        if let Some(objective_tracker) = world.fetch_mut::<ActiveObjectives>().0.as_mut() {
+            objective_tracker.restart();
+        }
 
-        GameTimeService::fetch(&world).set_game_start_time();
+        start_game_time(world);
 
+        #[cfg(feature = "client")]
+        delete_menu_demo(world);
         initialize_players(world);
+        let payload_checkpoint = resume_match(world);
+        rejoin_match(world);
+        start_level_script(world, payload_checkpoint);
 
         world.exec(|mut landscape_factory: LandscapeFactory| landscape_factory.create());
     }
 
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // Is updated before running the systems, so they all agree on whether it's a tick.
+        #[cfg(feature = "client")]
+        {
+            let delta = data.world.fetch::<Time>().delta_real_time();
+            data.world.fetch_mut::<GameTime>().advance_tick_clock(delta);
+        }
+        let is_tick = GameTimeService::fetch(&data.world).is_tick();
+        let is_multiplayer = data.world.fetch::<MultiplayerGameState>().is_playing;
+        let is_tick =
+            is_tick && (is_multiplayer || data.world.fetch_mut::<GameTime>().scale_tick());
+        *data.world.write_resource::<SimulationTick>() = SimulationTick(is_tick);
+        Trans::None
+    }
+
     #[cfg(feature = "client")]
     fn handle_event(
         &mut self,
@@ -67,6 +126,55 @@ impl SimpleState for PlayingState {
     }
 }
 
+#[cfg(feature = "client")]
+fn start_game_time(world: &mut World) {
+    world.exec(
+        |(engine_time, multiplayer_room_state, multiplayer_game_state, mut game_time): (
+            ReadExpect<Time>,
+            ReadExpect<MultiplayerRoomState>,
+            ReadExpect<MultiplayerGameState>,
+            WriteExpect<GameTime>,
+        )| {
+            // Clients render at their FPS cap and simulate at the server's tick rate.
+            let tick_rate = if multiplayer_game_state.is_playing {
+                multiplayer_room_state.tick_rate
+            } else {
+                DEFAULT_TICK_RATE
+            };
+            game_time.set_tick_rate(tick_rate, FRAME_RATE);
+            game_time.set_game_start_time(&engine_time);
+            game_time.start_tick_clock();
+            if let Some(snapshot) = multiplayer_game_state.rejoined_match.as_ref() {
+                game_time.started_at_game_frame_number = snapshot.frame_number;
+            }
+        },
+    );
+}
+
+#[cfg(not(feature = "client"))]
+fn start_game_time(world: &mut World) {
+    world.exec(
+        |(engine_time, mut game_time): (ReadExpect<Time>, WriteExpect<GameTime>)| {
+            game_time.set_game_start_time(&engine_time);
+        },
+    );
+}
+
+/// `MenuDemoSystem` would delete the menu skirmish only on the next frame, which would
+/// otherwise be drawn with two cameras.
+#[cfg(feature = "client")]
+fn delete_menu_demo(world: &mut World) {
+    world.exec(
+        |(entities, menu_demo_entities): (Entities, ReadStorage<MenuDemoEntity>)| {
+            for (entity, _) in (&entities, &menu_demo_entities).join() {
+                entities
+                    .delete(entity)
+                    .expect("Expected to delete a menu demo entity");
+            }
+        },
+    );
+}
+
 #[cfg(feature = "client")]
 fn initialize_players(world: &mut World) {
     let mut main_player = None;
@@ -79,6 +187,7 @@ fn initialize_players(world: &mut World) {
             mut entity_net_metadata_service,
             multiplayer_room_state,
             multiplayer_game_state,
+            settings,
         ): (
             PlayerFactory,
             PlayerClientFactory,
@@ -86,15 +195,36 @@ fn initialize_players(world: &mut World) {
             WriteExpect<EntityNetMetadataStorage>,
             ReadExpect<MultiplayerRoomState>,
             ReadExpect<MultiplayerGameState>,
+            ReadExpect<Settings>,
         )| {
+            let accessibility = settings.accessibility();
+            let marker = |player_index| {
+                if accessibility.shape_markers {
+                    Some(MarkerShape::for_player(player_index))
+                } else {
+                    None
+                }
+            };
             if !multiplayer_game_state.is_playing {
-                let player_entity = player_factory.create();
-                player_client_factory.create(player_entity, PLAYER_COLORS[4], true);
+                let profile = settings.profile();
+                let player_entity = player_factory.create(profile.class);
+                player_client_factory.create(
+                    player_entity,
+                    accessibility
+                        .palette
+                        .player_color(0, profile.cosmetics.color(4)),
+                    profile.cosmetics.hat,
+                    marker(0),
+                    true,
+                );
                 main_player = Some(player_entity);
             }
 
-            for player in &multiplayer_game_state.players {
-                let player_entity = player_factory.create();
+            for (player_index, player) in multiplayer_game_state.players.iter().enumerate() {
+                let color = accessibility
+                    .palette
+                    .player_color(player_index, player.color);
+                let player_entity = player_factory.create(player.class);
                 entity_net_metadata_service.set_net_id(player_entity, player.entity_net_id);
                 entity_net_metadata
                     .insert(
@@ -107,10 +237,22 @@ fn initialize_players(world: &mut World) {
                     .expect("Expected to insert EntityNetMetadata component");
 
                 if player.entity_net_id == multiplayer_room_state.player_net_id {
-                    player_client_factory.create(player_entity, player.color, true);
+                    player_client_factory.create(
+                        player_entity,
+                        color,
+                        player.cosmetics.hat,
+                        marker(player_index),
+                        true,
+                    );
                     main_player = Some(player_entity);
                 } else {
-                    player_client_factory.create(player_entity, player.color, false);
+                    player_client_factory.create(
+                        player_entity,
+                        color,
+                        player.cosmetics.hat,
+                        marker(player_index),
+                        false,
+                    );
                 }
             }
         },
@@ -132,6 +274,7 @@ fn initialize_players(world: &mut World) {
             mut multiplayer_game_state,
             net_connections,
             mut transport,
+            balance,
         ): (
             PlayerFactory,
             WriteStorage<EntityNetMetadata>,
@@ -139,12 +282,13 @@ fn initialize_players(world: &mut World) {
             WriteExpect<MultiplayerGameState>,
             ReadStorage<NetConnectionModel>,
             Write<TransportResource>,
+            ReadExpect<Balance>,
         )| {
             let player_net_identifiers = multiplayer_game_state
                 .players
                 .iter_mut()
                 .map(|player| {
-                    let player_entity = player_factory.create();
+                    let player_entity = player_factory.create(player.class);
                     let entity_net_id =
                         entity_net_metadata_service.register_new_entity(player_entity);
                     player.entity_net_id = entity_net_id;
@@ -160,11 +304,217 @@ fn initialize_players(world: &mut World) {
                     entity_net_id
                 })
                 .collect();
+            let payload = match multiplayer_game_state.resumed_match.clone() {
+                Some(saved_match) => ServerMessagePayload::ResumeGame {
+                    entity_net_ids: player_net_identifiers,
+                    saved_match,
+                },
+                None => ServerMessagePayload::StartGame(player_net_identifiers),
+            };
             broadcast_message_reliable(
                 &mut transport,
                 (&net_connections).join(),
-                ServerMessagePayload::StartGame(player_net_identifiers),
+                ServerMessagePayload::UpdateBalance(balance.clone()),
+            );
+            broadcast_message_reliable(&mut transport, (&net_connections).join(), payload);
+        },
+    );
+}
+
+/// Returns the payload checkpoint of the saved match, if there's one.
+fn resume_match(world: &mut World) -> f32 {
+    world.exec(
+        |(
+            mut multiplayer_game_state,
+            mut game_level_state,
+            mut game_time,
+            mut match_seed,
+            entity_net_metadata_service,
+            mut players,
+            mut player_augments,
+            mut player_wallets,
+        ): (
+            WriteExpect<MultiplayerGameState>,
+            WriteExpect<GameLevelState>,
+            WriteExpect<GameTime>,
+            WriteExpect<MatchSeed>,
+            ReadExpect<EntityNetMetadataStorage>,
+            WriteStorage<Player>,
+            WriteStorage<PlayerAugments>,
+            WriteStorage<PlayerWallet>,
+        )| {
+            let saved_match = match multiplayer_game_state.resumed_match.take() {
+                Some(saved_match) => saved_match,
+                None => {
+                    if let Some(campaign_level) = multiplayer_game_state.campaign_level {
+                        game_level_state.spawn_level = CAMPAIGN_LEVELS[campaign_level].spawn_level;
+                    }
+                    return 0.0;
+                }
+            };
+            log::info!(
+                "Resuming a match saved at frame {} (spawn level: {})",
+                saved_match.game_frame_number,
+                saved_match.spawn_level
+            );
+            game_level_state.spawn_level = saved_match.spawn_level;
+            multiplayer_game_state.campaign_level = saved_match.campaign_level;
+            *match_seed = MatchSeed(saved_match.seed);
+            // Spawn timers are compared with `GameTimeService::level_duration`.
+            game_time.started_at_game_frame_number = saved_match.game_frame_number;
+            let level_duration = Duration::from_secs_f32(
+                saved_match.game_frame_number as f32 / game_time.tick_rate as f32,
             );
+            game_level_state.spawn_level_started = level_duration;
+            game_level_state.last_borderline_spawn = level_duration;
+            game_level_state.last_random_spawn = level_duration;
+
+            for player in &multiplayer_game_state.players {
+                let saved_player = saved_match
+                    .players
+                    .iter()
+                    .find(|saved_player| saved_player.nickname == player.nickname);
+                let player_entity = entity_net_metadata_service.get_entity(player.entity_net_id);
+                let (saved_player, player_entity) = match (saved_player, player_entity) {
+                    (Some(saved_player), Some(player_entity)) => (saved_player, player_entity),
+                    _ => continue,
+                };
+                if let Some(player_component) = players.get_mut(player_entity) {
+                    player_component.health = saved_player.health;
+                }
+                if let Some(augments) = player_augments.get_mut(player_entity) {
+                    *augments = saved_player.augments.clone();
+                }
+                if let Some(wallet) = player_wallets.get_mut(player_entity) {
+                    *wallet = saved_player.wallet.clone();
+                }
+            }
+            saved_match.payload_checkpoint
+        },
+    )
+}
+
+/// Level scripts come with campaign levels, so it has to be called after `resume_match`.
+fn start_level_script(world: &mut World, payload_checkpoint: f32) {
+    world.exec(
+        |(multiplayer_game_state, mut game_level_state, mut active_level_script): (
+            ReadExpect<MultiplayerGameState>,
+            WriteExpect<GameLevelState>,
+            WriteExpect<ActiveLevelScript>,
+        )| {
+            // Clients check the script while loading the level, so only a server can get here
+            // with an invalid one, the level is played without it then.
+            let mut runner = multiplayer_game_state
+                .campaign_level
+                .and_then(|campaign_level| CAMPAIGN_LEVELS[campaign_level].script)
+                .and_then(|bytes| match LevelScript::from_bytes(bytes) {
+                    Ok(script) => Some(LevelScriptRunner::new(script)),
+                    Err(err) => {
+                        log::error!("Failed to parse the level script: {}", err);
+                        None
+                    }
+                });
+            if let Some(runner) = runner.as_mut() {
+                // Resizing the level resets its collision grid, so it goes before closing doors.
+                if let Some(bounds) = runner.script().bounds.as_ref() {
+                    game_level_state.set_bounds(bounds);
+                }
+                runner.close_doors(&mut game_level_state.collision_grid);
+                if let Some(payload) = runner.payload_mut() {
+                    payload.restore_checkpoint(payload_checkpoint);
+                }
+            }
+            game_level_state.safe_zones = runner
+                .as_ref()
+                .map(|runner| runner.script().safe_zones.clone())
+                .unwrap_or_default();
+            *active_level_script = ActiveLevelScript {
+                runner,
+                ..ActiveLevelScript::default()
+            };
         },
     );
 }
+
+fn rejoin_match(world: &mut World) {
+    let snapshot = match world
+        .fetch_mut::<MultiplayerGameState>()
+        .rejoined_match
+        .take()
+    {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+    log::info!(
+        "Rejoining a match at frame {} ({} monsters)",
+        snapshot.frame_number,
+        snapshot.monsters.len()
+    );
+
+    world.exec(
+        |(
+            entity_net_metadata_service,
+            mut players,
+            mut world_positions,
+            mut net_world_positions,
+        ): (
+            ReadExpect<EntityNetMetadataStorage>,
+            WriteStorage<Player>,
+            WriteStorage<WorldPosition>,
+            WriteStorage<NetWorldPosition>,
+        )| {
+            for player_snapshot in &snapshot.players {
+                let player_entity =
+                    match entity_net_metadata_service.get_entity(player_snapshot.entity_net_id) {
+                        Some(player_entity) => player_entity,
+                        None => continue,
+                    };
+                if let Some(player) = players.get_mut(player_entity) {
+                    player.health = player_snapshot.health;
+                }
+                if let Some(world_position) = world_positions.get_mut(player_entity) {
+                    world_position.position = player_snapshot.position;
+                }
+                if let Some(net_world_position) = net_world_positions.get_mut(player_entity) {
+                    net_world_position.position = player_snapshot.position;
+                }
+            }
+        },
+    );
+
+    world.exec(|mut monster_spawner: MonsterSpawnerSystemData| {
+        let mut ghoul = monster_spawner
+            .monster_definitions
+            .0
+            .get("Ghoul")
+            .expect("Failed to get Ghoul monster definition")
+            .clone();
+        // Health comes from the snapshot, only the damage has to be scaled.
+        ghoul.base_attack_damage *= monster_spawner
+            .room_settings
+            .difficulty
+            .monster_damage_factor();
+
+        for monster_snapshot in &snapshot.monsters {
+            let monster_entity = monster_spawner.spawn_monster(
+                snapshot.frame_number,
+                monster_snapshot.position,
+                Action {
+                    frame_number: snapshot.frame_number,
+                    action: MobAction::Idle,
+                },
+                &ghoul,
+                Some(monster_snapshot.entity_net_id),
+                // Restored monsters have already emerged.
+                snapshot.frame_number,
+            );
+            if let Some(monster) = monster_spawner
+                .monster_factory
+                .monsters
+                .get_mut(monster_entity)
+            {
+                monster.health = monster_snapshot.health;
+            }
+        }
+    });
+}
@@ -9,13 +9,17 @@ use amethyst::{
 
 #[cfg(feature = "client")]
 use gv_animation_prefabs::AnimationId;
-use gv_core::ecs::{
-    components::{missile::Missile, Dead},
-    system_data::time::GameTimeService,
+use gv_core::{
+    collision_layers::CollisionLayer,
+    economy::PlayerWallet,
+    ecs::{
+        components::{affixes::MonsterAffixes, missile::Missile, Allegiance, Dead},
+        system_data::time::GameTimeService,
+    },
+    mutators::{self, Mutator},
+    stats::StatModifiers,
 };
 
-use crate::ecs::systems::missile::{MISSILE_LIFESPAN_SECS, MISSILE_TIME_TO_FADE};
-
 pub fn is_dead(
     entity: Entity,
     dead: &impl GenericReadStorage<Component = Dead>,
@@ -25,6 +29,18 @@ pub fn is_dead(
         .map_or(false, |dead| dead.is_dead(frame_number))
 }
 
+/// Monsters summoned by players are minions.
+pub fn mob_layer(
+    entity: Entity,
+    allegiances: &impl GenericReadStorage<Component = Allegiance>,
+) -> CollisionLayer {
+    if allegiances.get(entity).is_some() {
+        CollisionLayer::Minions
+    } else {
+        CollisionLayer::Monsters
+    }
+}
+
 #[cfg(feature = "client")]
 pub fn body_part_entity(
     parent_hierarchy: &ReadExpect<ParentHierarchy>,
@@ -106,17 +122,66 @@ pub fn remove_animation(
     }
 }
 
+/// Moves a playing animation to `input_secs` from its start, animations of actions
+/// that have been simulated a few frames late catch up this way.
+#[cfg(feature = "client")]
+pub fn set_animation_input(
+    parent_hierarchy: &ReadExpect<ParentHierarchy>,
+    named: &ReadStorage<Named>,
+    animation_control_sets: &mut WriteStorage<AnimationControlSet<AnimationId, SpriteRender>>,
+    entity: Entity,
+    body_part_name: &str,
+    animation_id: AnimationId,
+    input_secs: f32,
+) {
+    let body_part_entity = body_part_entity(parent_hierarchy, named, entity, body_part_name);
+    if let Some(animation_control_set) = body_part_entity
+        .and_then(|body_part_entity| animation_control_sets.get_mut(body_part_entity))
+    {
+        animation_control_set.set_input(animation_id, input_secs);
+    }
+}
+
+/// Modifiers of a player's stats from the items bought in the shop and the room mutators.
+pub fn player_stat_modifiers(
+    entity: Entity,
+    player_wallets: &impl GenericReadStorage<Component = PlayerWallet>,
+    mutators: &[Mutator],
+    frame_number: u64,
+) -> StatModifiers {
+    let mut stat_modifiers = StatModifiers::default();
+    if let Some(wallet) = player_wallets.get(entity) {
+        wallet.add_stat_modifiers(frame_number, &mut stat_modifiers);
+    }
+    mutators::add_player_modifiers(mutators, &mut stat_modifiers);
+    stat_modifiers
+}
+
+/// Modifiers of a monster's stats from its elite affixes and the room mutators.
+pub fn monster_stat_modifiers(
+    entity: Entity,
+    monster_affixes: &impl GenericReadStorage<Component = MonsterAffixes>,
+    mutators: &[Mutator],
+) -> StatModifiers {
+    let mut stat_modifiers = StatModifiers::default();
+    if let Some(monster_affixes) = monster_affixes.get(entity) {
+        monster_affixes.add_stat_modifiers(&mut stat_modifiers);
+    }
+    mutators::add_monster_modifiers(mutators, &mut stat_modifiers);
+    stat_modifiers
+}
+
 /// Returns values within the range [0.1; 1.0].
-/// Energy start dropping below 1.0 on (MISSILE_LIFESPAN_SECS - MISSILE_TIME_TO_FADE).
+/// Energy start dropping below 1.0 on (`Missile::lifespan_secs` - `Missile::fade_secs`).
 pub fn missile_energy(
     missile: &Missile,
     is_dead: bool,
     game_time_service: &GameTimeService,
     frame_number: u64,
 ) -> f32 {
-    let energy = ((MISSILE_LIFESPAN_SECS
+    let energy = ((missile.lifespan_secs
         - game_time_service.seconds_between_frames(frame_number, missile.frame_spawned))
-        / MISSILE_TIME_TO_FADE)
+        / missile.fade_secs)
         .clamp(0.0, 1.0);
     if energy == 0.0 {
         return 0.0;
@@ -1,3 +1,5 @@
 pub mod entities;
 pub mod net;
+pub mod spatial_grid;
 pub mod world;
+pub mod world_updates;
@@ -1,32 +1,93 @@
 use amethyst::network::simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement};
+use serde::Serialize;
 
-use gv_core::ecs::components::NetConnectionModel;
 #[cfg(feature = "client")]
-use gv_core::net::client_message::{ClientMessage, ClientMessagePayload};
+use gv_core::net::client_message::ClientMessagePayload;
 #[cfg(not(feature = "client"))]
-use gv_core::net::server_message::{ServerMessage, ServerMessagePayload};
+use gv_core::net::server_message::ServerMessagePayload;
+use gv_core::{ecs::components::NetConnectionModel, net::MessageEncoder};
 
+use std::cell::RefCell;
+
+thread_local! {
+    // Systems sending messages can run on any thread of the dispatcher's pool.
+    static MESSAGE_ENCODER: RefCell<MessageEncoder> = RefCell::new(MessageEncoder::default());
+}
+
+/// Encodes the payload once and sends it to every connection with its session id.
 #[cfg(not(feature = "client"))]
-pub fn broadcast_message_reliable<'a>(
+fn broadcast_message<'a>(
     transport: &mut TransportResource,
     net_connections: impl Iterator<Item = &'a NetConnectionModel>,
-    payload: ServerMessagePayload,
+    payload: &ServerMessagePayload,
+    delivery: DeliveryRequirement,
 ) {
-    for connection in net_connections {
-        let sent_message = bincode::serialize(&ServerMessage {
-            session_id: connection.session_id,
-            payload: payload.clone(),
-        })
-        .expect("Expected to serialize a broadcasted message");
-        if !connection.disconnected {
-            transport.send_with_requirements(
-                connection.addr,
-                &sent_message,
-                DeliveryRequirement::Reliable,
-                UrgencyRequirement::Immediate,
-            );
+    MESSAGE_ENCODER.with(|message_encoder| {
+        let mut message_encoder = message_encoder.borrow_mut();
+        message_encoder.encode_message(0, payload);
+        for connection in net_connections {
+            if !connection.disconnected {
+                transport.send_with_requirements(
+                    connection.addr,
+                    message_encoder.with_session_id(connection.session_id),
+                    delivery,
+                    UrgencyRequirement::Immediate,
+                );
+            }
         }
+    });
+}
+
+/// Sends an already encoded message, see `MessageEncoder`.
+pub fn send_encoded_message_unreliable(
+    transport: &mut TransportResource,
+    net_connection: &NetConnectionModel,
+    message: &[u8],
+) {
+    if net_connection.disconnected {
+        return;
+    }
+    transport.send_with_requirements(
+        net_connection.addr,
+        message,
+        DeliveryRequirement::Unreliable,
+        UrgencyRequirement::Immediate,
+    );
+}
+
+fn send_message<P: Serialize>(
+    transport: &mut TransportResource,
+    net_connection: &NetConnectionModel,
+    payload: &P,
+    delivery: DeliveryRequirement,
+) {
+    if net_connection.disconnected {
+        return;
     }
+    MESSAGE_ENCODER.with(|message_encoder| {
+        transport.send_with_requirements(
+            net_connection.addr,
+            message_encoder
+                .borrow_mut()
+                .encode_message(net_connection.session_id, payload),
+            delivery,
+            UrgencyRequirement::Immediate,
+        );
+    });
+}
+
+#[cfg(not(feature = "client"))]
+pub fn broadcast_message_reliable<'a>(
+    transport: &mut TransportResource,
+    net_connections: impl Iterator<Item = &'a NetConnectionModel>,
+    payload: ServerMessagePayload,
+) {
+    broadcast_message(
+        transport,
+        net_connections,
+        &payload,
+        DeliveryRequirement::Reliable,
+    );
 }
 
 #[cfg(not(feature = "client"))]
@@ -35,21 +96,12 @@ pub fn broadcast_message_unreliable<'a>(
     net_connections: impl Iterator<Item = &'a NetConnectionModel>,
     payload: ServerMessagePayload,
 ) {
-    for connection in net_connections {
-        let sent_message = bincode::serialize(&ServerMessage {
-            session_id: connection.session_id,
-            payload: payload.clone(),
-        })
-        .expect("Expected to serialize a broadcasted message");
-        if !connection.disconnected {
-            transport.send_with_requirements(
-                connection.addr,
-                &sent_message,
-                DeliveryRequirement::Unreliable,
-                UrgencyRequirement::Immediate,
-            );
-        }
-    }
+    broadcast_message(
+        transport,
+        net_connections,
+        &payload,
+        DeliveryRequirement::Unreliable,
+    );
 }
 
 #[cfg(feature = "client")]
@@ -58,19 +110,11 @@ pub fn send_message_reliable(
     net_connection: &NetConnectionModel,
     payload: ClientMessagePayload,
 ) {
-    if net_connection.disconnected {
-        return;
-    }
-    let sent_message = bincode::serialize(&ClientMessage {
-        session_id: net_connection.session_id,
-        payload,
-    })
-    .expect("Expected to serialize a client message");
-    transport.send_with_requirements(
-        net_connection.addr,
-        &sent_message,
+    send_message(
+        transport,
+        net_connection,
+        &payload,
         DeliveryRequirement::Reliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -80,19 +124,11 @@ pub fn send_message_reliable(
     net_connection: &NetConnectionModel,
     payload: ServerMessagePayload,
 ) {
-    if net_connection.disconnected {
-        return;
-    }
-    let sent_message = bincode::serialize(&ServerMessage {
-        session_id: net_connection.session_id,
-        payload,
-    })
-    .expect("Expected to serialize a server message");
-    transport.send_with_requirements(
-        net_connection.addr,
-        &sent_message,
+    send_message(
+        transport,
+        net_connection,
+        &payload,
         DeliveryRequirement::Reliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -102,21 +138,12 @@ pub fn send_message_unreliable(
     net_connection: &NetConnectionModel,
     payload: ClientMessagePayload,
 ) {
-    if net_connection.disconnected {
-        return;
-    }
-    let message = ClientMessage {
-        session_id: net_connection.session_id,
-        payload,
-    };
-    log::trace!("Sending: {:#?}", message);
-    let sent_message =
-        bincode::serialize(&message).expect("Expected to serialize a client message");
-    transport.send_with_requirements(
-        net_connection.addr,
-        &sent_message,
+    log::trace!("Sending: {:#?}", payload);
+    send_message(
+        transport,
+        net_connection,
+        &payload,
         DeliveryRequirement::Unreliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -126,21 +153,11 @@ pub fn send_message_unreliable(
     net_connection: &NetConnectionModel,
     payload: ServerMessagePayload,
 ) {
-    if net_connection.disconnected {
-        return;
-    }
-    let message = ServerMessage {
-        session_id: net_connection.session_id,
-        payload,
-    };
-    log::trace!("Sending: {:#?}", message);
-    let sent_message =
-        bincode::serialize(&message).expect("Expected to serialize a server message");
-    log::trace!("Packet len: {}", sent_message.len());
-    transport.send_with_requirements(
-        net_connection.addr,
-        &sent_message,
+    log::trace!("Sending: {:#?}", payload);
+    send_message(
+        transport,
+        net_connection,
+        &payload,
         DeliveryRequirement::Unreliable,
-        UrgencyRequirement::Immediate,
     );
 }
@@ -0,0 +1,58 @@
+use amethyst::ecs::Entity;
+
+use std::collections::HashMap;
+
+use gv_core::math::Vector2;
+
+/// Buckets entities by square cells, so that area queries only look at the entities
+/// in the cells overlapping the area.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(Entity, Vector2)>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vector2) {
+        self.cells
+            .entry(self.cell(position))
+            .or_insert_with(Vec::new)
+            .push((entity, position));
+    }
+
+    /// Returns the entities within `radius` from `center`, ordered by insertion within a cell.
+    pub fn query_radius(&self, center: Vector2, radius: f32) -> Vec<Entity> {
+        let (min_x, min_y) = self.cell(center - Vector2::new(radius, radius));
+        let (max_x, max_y) = self.cell(center + Vector2::new(radius, radius));
+        let mut entities = Vec::new();
+        for cell_x in min_x..=max_x {
+            for cell_y in min_y..=max_y {
+                let cell = match self.cells.get(&(cell_x, cell_y)) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+                entities.extend(
+                    cell.iter()
+                        .filter(|(_, position)| {
+                            (position - center).norm_squared() <= radius * radius
+                        })
+                        .map(|(entity, _)| *entity),
+                );
+            }
+        }
+        entities
+    }
+
+    fn cell(&self, position: Vector2) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+}
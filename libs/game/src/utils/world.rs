@@ -1,6 +1,6 @@
 use amethyst::ecs::{
     storage::{GenericReadStorage, MaskedStorage, Storage},
-    Entities, Entity, Join,
+    Entities, Entity, Join, ReadStorage,
 };
 use rand::{self, Rng};
 
@@ -8,16 +8,17 @@ use std::ops::Deref;
 
 use gv_core::{
     actions::monster_spawn::Side,
+    collision_layers::{CollisionLayer, CollisionMatrix},
     ecs::{
-        components::{Dead, Monster, WorldPosition},
+        components::{Allegiance, Dead, Impulse, Monster, WorldPosition},
         resources::GameLevelState,
     },
-    math::Vector2,
+    math::{deterministic, Vector2, ZeroVector},
 };
 
 use crate::{
     ecs::systems::{AggregatedOutcomingUpdates, OutcomingNetUpdates},
-    utils::entities::is_dead,
+    utils::entities::{is_dead, mob_layer},
 };
 
 pub fn closest_monster<
@@ -29,12 +30,21 @@ pub fn closest_monster<
     world_positions: &Storage<'_, WorldPosition, DW>,
     entities: &Entities<'_>,
     monsters: &Storage<'_, Monster, DM>,
+    allegiances: &ReadStorage<'_, Allegiance>,
+    collisions: &CollisionMatrix,
     dead: &G,
     frame_number: u64,
+    excluded: &[Entity],
 ) -> Option<(Entity, Vector2)> {
+    // Missiles only go after the mobs they can hit.
     (world_positions, entities, monsters)
         .join()
-        .filter(|(_, entity, _)| !is_dead(*entity, dead, frame_number))
+        .filter(|(_, entity, monster)| {
+            !is_dead(*entity, dead, frame_number)
+                && !monster.is_emerging(frame_number)
+                && !excluded.contains(entity)
+                && collisions.hits(CollisionLayer::Projectiles, mob_layer(*entity, allegiances))
+        })
         .fold(None, |res, (monster_position, monster, _)| {
             if let Some((closest_monster, closest_monster_position)) = res {
                 if (closest_monster_position - missile_position).norm_squared()
@@ -50,6 +60,8 @@ pub fn closest_monster<
         })
 }
 
+/// Returns the monster that an object moving by `movement` during a frame touches first,
+/// along with the object's position at the moment of the hit.
 pub fn find_first_hit_monster<
     DT: Deref<Target = MaskedStorage<Monster>>,
     DP: Deref<Target = MaskedStorage<WorldPosition>>,
@@ -57,22 +69,41 @@ pub fn find_first_hit_monster<
 >(
     object_position: Vector2,
     object_radius: f32,
+    movement: Vector2,
     targets: &Storage<'_, Monster, DT>,
     target_positions: &Storage<'_, WorldPosition, DP>,
+    allegiances: &ReadStorage<'_, Allegiance>,
+    collisions: &CollisionMatrix,
     entities: &Entities<'_>,
     dead: &G,
     frame_number: u64,
-) -> Option<Entity> {
+    excluded: &[Entity],
+) -> Option<(Entity, Vector2)> {
     (target_positions, entities, targets)
         .join()
-        .filter(|(_, entity, _)| !is_dead(*entity, dead, frame_number))
-        .find(|(target_position, _, target)| {
-            let distance_squared = (object_position - ***target_position).norm_squared();
-            let impact_distance = object_radius + target.radius;
-            let impact_distance_squared = impact_distance * impact_distance;
-            distance_squared <= impact_distance_squared
+        .filter(|(_, entity, target)| {
+            !is_dead(*entity, dead, frame_number)
+                && !target.is_emerging(frame_number)
+                && !excluded.contains(entity)
+                && collisions.hits(CollisionLayer::Projectiles, mob_layer(*entity, allegiances))
+        })
+        .filter_map(|(target_position, entity, target)| {
+            deterministic::sweep_circle(
+                object_position,
+                movement,
+                object_radius + target.radius,
+                **target_position,
+            )
+            .map(|share| (entity, share))
         })
-        .map(|result| result.1)
+        .fold(
+            None,
+            |first_hit: Option<(Entity, f32)>, (entity, share)| match first_hit {
+                Some((_, first_share)) if first_share <= share => first_hit,
+                _ => Some((entity, share)),
+            },
+        )
+        .map(|(entity, share)| (entity, object_position + movement * share))
 }
 
 pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
@@ -89,10 +120,111 @@ pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
     )
 }
 
-pub fn random_spawn_position(game_level_state: &GameLevelState) -> Vector2 {
-    let mut rng = rand::thread_rng();
+/// Knockback pushing a hit entity away from the hitter.
+pub fn knockback_impulse(from: Vector2, to: Vector2, strength: f32) -> Vector2 {
+    let direction = to - from;
+    if strength == 0.0 || direction.norm_squared() == 0.0 {
+        return Vector2::zero();
+    }
+    deterministic::normalize(&direction) * strength
+}
+
+/// Moves an entity by its impulse. An impulse can't push an entity out of the level,
+/// it stops on the axis the entity hits a border on. Monsters walk in from outside
+/// of the level, so those are only kept from being pushed further away.
+pub fn apply_impulse(
+    position: &mut Vector2,
+    impulse: &mut Impulse,
+    frame_seconds: f32,
+    game_level_state: &GameLevelState,
+) {
+    let displacement = impulse.step(frame_seconds);
+    let half_size = game_level_state.dimensions_half_size();
+    let previous_position = *position;
+
+    let (x, is_stopped_x) = push_along_axis(position.x, displacement.x, half_size.x);
+    position.x = x;
+    if is_stopped_x {
+        impulse.velocity.x = 0.0;
+    }
+    let (y, is_stopped_y) = push_along_axis(position.y, displacement.y, half_size.y);
+    position.y = y;
+    if is_stopped_y {
+        impulse.velocity.y = 0.0;
+    }
+    *position = game_level_state
+        .collision_grid
+        .slide(previous_position, *position);
+}
+
+/// Returns the new coordinate and whether the push was stopped by a border.
+fn push_along_axis(coordinate: f32, displacement: f32, half_size: f32) -> (f32, bool) {
+    let moved = coordinate + displacement;
+    let is_pushed_outwards = moved.abs() > half_size && moved.abs() > coordinate.abs();
+    if !is_pushed_outwards {
+        (moved, false)
+    } else if coordinate.abs() <= half_size {
+        (half_size * moved.signum(), true)
+    } else {
+        (coordinate, true)
+    }
+}
+
+/// Level borders are the only static geometry so far. Returns the normals (pointing into
+/// the level) of the borders crossed when moving from `from` to `to`.
+pub fn level_border_normals(
+    from: Vector2,
+    to: Vector2,
+    game_level_state: &GameLevelState,
+) -> impl Iterator<Item = Vector2> {
+    let half_size = game_level_state.dimensions_half_size();
+    let is_crossed =
+        |from: f32, to: f32, half_size: f32| from.abs() <= half_size && to.abs() > half_size;
+    let normal_x = if is_crossed(from.x, to.x, half_size.x) {
+        Some(Vector2::new(-to.x.signum(), 0.0))
+    } else {
+        None
+    };
+    let normal_y = if is_crossed(from.y, to.y, half_size.y) {
+        Some(Vector2::new(0.0, -to.y.signum()))
+    } else {
+        None
+    };
+    normal_x.into_iter().chain(normal_y)
+}
+
+pub fn reflect(vector: Vector2, normal: Vector2) -> Vector2 {
+    vector - normal * 2.0 * vector.dot(&normal)
+}
+
+/// Moves an object by its velocity, reflecting it off the level borders it crosses.
+/// The part of the movement past a border is mirrored back into the level.
+/// Returns whether the object has bounced.
+pub fn move_bouncing_off_level_borders(
+    position: &mut Vector2,
+    velocity: &mut Vector2,
+    frame_seconds: f32,
+    game_level_state: &GameLevelState,
+) -> bool {
+    let half_size = game_level_state.dimensions_half_size();
+    let mut moved = *position + *velocity * frame_seconds;
+    let mut has_bounced = false;
+    for normal in level_border_normals(*position, moved, game_level_state) {
+        let border_distance = normal.x.abs() * half_size.x + normal.y.abs() * half_size.y;
+        let depth = -normal.dot(&moved) - border_distance;
+        moved += normal * 2.0 * depth;
+        *velocity = reflect(*velocity, normal);
+        has_bounced = true;
+    }
+    *position = moved;
+    has_bounced
+}
 
-    let (side_start, side_end, _) = spawning_side(rand::random(), &game_level_state);
+pub fn random_spawn_position<R: Rng + ?Sized>(
+    game_level_state: &GameLevelState,
+    rng: &mut R,
+) -> Vector2 {
+    let (side_start, side_end, _) = spawning_side(rng.gen(), &game_level_state);
     let d = side_end - side_start;
     let random_displacement = Vector2::new(
         if d.x == 0.0 {
@@ -0,0 +1,200 @@
+use gv_core::{
+    actions::monster_spawn::SpawnActions,
+    ecs::resources::{
+        net::LastAcknowledgedUpdate,
+        world::{
+            FramedUpdates, PlayerActionUpdates, ReceivedPlayerUpdate, ReceivedServerWorldUpdate,
+            ServerWorldUpdate,
+        },
+    },
+    net::{server_message::ClientNetworkError, NetIdentifier},
+};
+
+/// Handles an UpdateWorld message on client. Returns `Ok(false)` if the update is outdated
+/// and an error if it contains frames the client doesn't keep anymore.
+pub fn receive_world_update(
+    controlled_players: &[NetIdentifier],
+    last_acknowledged_update: &mut LastAcknowledgedUpdate,
+    framed_updates: &mut FramedUpdates<ReceivedServerWorldUpdate>,
+    spawn_actions: &mut FramedUpdates<SpawnActions>,
+    game_frame_number: u64,
+    interpolation_frame_delay: u64,
+    id: u64,
+    mut updates: Vec<ServerWorldUpdate>,
+) -> Result<bool, ClientNetworkError> {
+    if last_acknowledged_update.id >= id {
+        return Ok(false);
+    }
+
+    updates.sort_by(|a, b| a.frame_number.cmp(&b.frame_number));
+
+    last_acknowledged_update.id = id;
+    last_acknowledged_update.frame_number = last_acknowledged_update.frame_number.max(
+        updates
+            .last()
+            .map(|update| update.frame_number)
+            .unwrap_or(0),
+    );
+
+    let frame_to_reserve = last_acknowledged_update.frame_number.max(game_frame_number);
+    framed_updates.reserve_updates(frame_to_reserve);
+    spawn_actions.reserve_updates(frame_to_reserve);
+
+    apply_world_updates(
+        controlled_players,
+        framed_updates,
+        spawn_actions,
+        interpolation_frame_delay,
+        updates,
+    )?;
+    Ok(true)
+}
+
+// Expects incoming_updates to be sorted (lowest frame first).
+pub fn apply_world_updates(
+    controlled_players: &[NetIdentifier],
+    framed_updates: &mut FramedUpdates<ReceivedServerWorldUpdate>,
+    spawn_actions: &mut FramedUpdates<SpawnActions>,
+    interpolation_frame_delay: u64,
+    mut incoming_updates: Vec<ServerWorldUpdate>,
+) -> Result<(), ClientNetworkError> {
+    if incoming_updates.is_empty() {
+        return Ok(());
+    }
+
+    let first_incoming_frame_number = incoming_updates
+        .first()
+        .unwrap()
+        .frame_number
+        .saturating_sub(interpolation_frame_delay);
+    let first_available_frame_number = framed_updates
+        .updates
+        .front()
+        .map_or(first_incoming_frame_number, |update| update.frame_number);
+    if first_incoming_frame_number < first_available_frame_number {
+        return Err(ClientNetworkError::OutdatedWorldUpdate {
+            frame_number: first_incoming_frame_number,
+            first_available_frame_number,
+        });
+    }
+
+    let controlled_player_updates = collect_controlled_player_updates(
+        controlled_players,
+        interpolation_frame_delay,
+        &mut incoming_updates,
+    );
+
+    let (controlled_start_frame_number, others_start_frame_number) = incoming_updates
+        .first()
+        .map(|update| {
+            (
+                update
+                    .frame_number
+                    .saturating_sub(interpolation_frame_delay),
+                update.frame_number,
+            )
+        })
+        .unwrap();
+
+    spawn_actions.oldest_updated_frame = others_start_frame_number;
+    for (spawn_actions, server_update) in spawn_actions
+        .updates_iter_mut(others_start_frame_number)
+        .zip(incoming_updates.iter_mut())
+    {
+        // `apply_server_update` doesn't need spawn actions, so we can move them out.
+        spawn_actions.spawn_actions =
+            std::mem::replace(&mut server_update.spawn_actions, Vec::new());
+    }
+
+    framed_updates.oldest_updated_frame = controlled_start_frame_number;
+    let mut controlled_player_updates_iter = controlled_player_updates.into_iter();
+    let mut incoming_updates_iter = incoming_updates.into_iter();
+
+    for frame_updates in framed_updates.updates_iter_mut(controlled_start_frame_number) {
+        if let Some(controlled_player_updates) = controlled_player_updates_iter.next() {
+            frame_updates.controlled_player_updates = controlled_player_updates;
+        }
+        if frame_updates.frame_number >= others_start_frame_number {
+            let server_update = incoming_updates_iter.next();
+            if server_update.is_none() {
+                return Ok(());
+            }
+            frame_updates.apply_server_update(server_update.unwrap());
+        }
+    }
+    Ok(())
+}
+
+fn collect_controlled_player_updates(
+    controlled_players: &[NetIdentifier],
+    interpolation_frame_delay: u64,
+    incoming_updates: &mut Vec<ServerWorldUpdate>,
+) -> Vec<ReceivedPlayerUpdate> {
+    incoming_updates
+        .iter_mut()
+        .skip_while(|update| {
+            // Skips the first frames, as there shouldn't be any player updates on game start.
+            update.frame_number < interpolation_frame_delay
+        })
+        .map(|update| {
+            let mut controlled_player_update = ReceivedPlayerUpdate::default();
+
+            let walk_action_pos = update
+                .player_walk_actions_updates
+                .iter()
+                .position(|action| controlled_players.contains(&action.entity_net_id));
+            if let Some(walk_action_pos) = walk_action_pos {
+                let walk_action = update.player_walk_actions_updates.remove(walk_action_pos);
+                controlled_player_update
+                    .player_walk_actions_updates
+                    .push(walk_action);
+            }
+
+            let cast_action_pos = update
+                .player_cast_actions_updates
+                .iter()
+                .position(|action| controlled_players.contains(&action.entity_net_id));
+            if let Some(cast_action_pos) = cast_action_pos {
+                let cast_action = update.player_cast_actions_updates.remove(cast_action_pos);
+                controlled_player_update
+                    .player_cast_actions_updates
+                    .push(cast_action);
+            }
+
+            // Look actions of controlled players aren't replayed: clients apply their own
+            // immediately and clamp aim points to the max cast range the same way servers do.
+            update
+                .player_look_actions_updates
+                .retain(|action| !controlled_players.contains(&action.entity_net_id));
+
+            controlled_player_update
+        })
+        .collect()
+}
+
+pub fn discard_walk_actions(
+    client_player_updates: &mut FramedUpdates<PlayerActionUpdates>,
+    mut discarded_updates: Vec<NetIdentifier>,
+) {
+    let mut oldest_updated_frame = client_player_updates.oldest_updated_frame;
+    for update in client_player_updates.updates.iter_mut().rev() {
+        let update_frame_number = update.frame_number;
+        update.walk_action_updates.retain(|net_update| {
+            if let Some(i) = discarded_updates
+                .iter()
+                .position(|discarded_update| *discarded_update == net_update.data.client_action_id)
+            {
+                discarded_updates.remove(i);
+                oldest_updated_frame = update_frame_number;
+                false
+            } else {
+                true
+            }
+        });
+
+        if discarded_updates.is_empty() {
+            break;
+        }
+    }
+    client_player_updates.oldest_updated_frame = oldest_updated_frame;
+}
@@ -0,0 +1,913 @@
+use std::{io, net::SocketAddr, path::Path, time::Duration};
+
+use gv_core::{
+    actions::{
+        player::{CastSpell, PlayerCastAction, PlayerInteractAction, PlayerWalkAction},
+        ClientActionUpdate,
+    },
+    augments::{Augment, PlayerAugments},
+    balance::Balance,
+    classes::PlayerClass,
+    content_packs::{ContentPackInfo, ContentPacks},
+    cosmetics::PlayerCosmetics,
+    economy::{PlayerWallet, ShopItem},
+    ecs::resources::{
+        net::{MultiplayerRoomPlayer, SavedMatch},
+        world::{ImmediatePlayerActionsUpdates, InputFrame, ServerWorldUpdate},
+        Intermission, MatchScore, DEFAULT_TICK_RATE,
+    },
+    emotes::Emote,
+    escort::PayloadProgress,
+    game_events::GameEvent,
+    level_scripts::ScriptAction,
+    math::{Vector2, ZeroVector},
+    net::{
+        client_message::{ClientMessage, ClientMessagePayload},
+        decode_server_message, interpolation_frame_delay,
+        server_message::{
+            ClientNetworkError, DisconnectReason, LaggingPlayer, MatchSnapshot,
+            PlayerReconciliation, RoomInfo, ServerMessagePayload,
+        },
+        session_recording::{RecordedMessage, SessionRecorder},
+        EncodedMessage, NetIdentifier, NetUpdate, RoomId,
+    },
+    room_settings::RoomSettings,
+    votes::{Vote, VoteKind},
+};
+
+/// A client speaking the wire protocol without running the client-side simulation.
+///
+/// `gv_game` can't be built with and without the `client` feature in the same process,
+/// so headless clients only track what they've received from the server and reply
+/// the way `ClientNetworkSystem` does (pongs, acknowledgements, heartbeats).
+pub struct HeadlessClient {
+    addr: SocketAddr,
+    nickname: String,
+    class: PlayerClass,
+    cosmetics: PlayerCosmetics,
+    session_id: NetIdentifier,
+    session_created_at: Duration,
+    client_action_id_autoinc: NetIdentifier,
+    connection_id: Option<NetIdentifier>,
+    is_host: bool,
+    tick_rate: u32,
+    room_players: Vec<MultiplayerRoomPlayer>,
+    player_net_ids: Option<Vec<NetIdentifier>>,
+    resumed_match: Option<SavedMatch>,
+    campaign_level: Option<usize>,
+    match_score: MatchScore,
+    game_frame_number: u64,
+    last_world_update_id: Option<u64>,
+    world_updates: Vec<ServerWorldUpdate>,
+    acknowledges_world_updates: bool,
+    discarded_walk_actions: Vec<NetIdentifier>,
+    is_paused: bool,
+    lagging_players: Vec<LaggingPlayer>,
+    disconnect_reason: Option<DisconnectReason>,
+    rooms: Vec<RoomInfo>,
+    room_id: Option<RoomId>,
+    players_in_queue: Option<usize>,
+    emotes: Vec<(NetIdentifier, Emote)>,
+    augments: Vec<(NetIdentifier, PlayerAugments)>,
+    wallets: Vec<(NetIdentifier, PlayerWallet)>,
+    intermission: Option<Intermission>,
+    level_script_actions: Vec<ScriptAction>,
+    game_events: Vec<GameEvent>,
+    payload_progress: Option<PayloadProgress>,
+    balance: Option<Balance>,
+    balance_updates_count: usize,
+    content_packs: ContentPacks,
+    advertised_content_packs: Vec<ContentPackInfo>,
+    console_output: Vec<String>,
+    reconciliation_reports: Vec<PlayerReconciliation>,
+    spectator_snapshot: Option<MatchSnapshot>,
+    relayed_updates: Vec<ServerWorldUpdate>,
+    is_catching_up: bool,
+    acknowledged_input: Option<NetIdentifier>,
+    afk_players: Vec<NetIdentifier>,
+    vote: Option<Vote>,
+    vote_results: Vec<(NetIdentifier, bool)>,
+    room_settings: Option<RoomSettings>,
+    rejoin_snapshot: Option<MatchSnapshot>,
+    pause_threshold: Option<u64>,
+    outcoming_messages: Vec<ClientMessagePayload>,
+    session_recorder: SessionRecorder,
+}
+
+impl HeadlessClient {
+    pub fn new(addr: SocketAddr, nickname: &str) -> Self {
+        Self {
+            addr,
+            nickname: nickname.to_owned(),
+            class: PlayerClass::default(),
+            cosmetics: PlayerCosmetics::default(),
+            session_id: 0,
+            session_created_at: Duration::new(0, 0),
+            client_action_id_autoinc: 0,
+            connection_id: None,
+            is_host: false,
+            tick_rate: DEFAULT_TICK_RATE,
+            room_players: Vec::new(),
+            player_net_ids: None,
+            resumed_match: None,
+            campaign_level: None,
+            match_score: MatchScore::default(),
+            game_frame_number: 0,
+            last_world_update_id: None,
+            world_updates: Vec::new(),
+            acknowledges_world_updates: true,
+            discarded_walk_actions: Vec::new(),
+            is_paused: false,
+            lagging_players: Vec::new(),
+            disconnect_reason: None,
+            rooms: Vec::new(),
+            room_id: None,
+            players_in_queue: None,
+            emotes: Vec::new(),
+            augments: Vec::new(),
+            wallets: Vec::new(),
+            intermission: None,
+            level_script_actions: Vec::new(),
+            game_events: Vec::new(),
+            payload_progress: None,
+            balance: None,
+            balance_updates_count: 0,
+            content_packs: ContentPacks::default(),
+            advertised_content_packs: Vec::new(),
+            console_output: Vec::new(),
+            reconciliation_reports: Vec::new(),
+            spectator_snapshot: None,
+            relayed_updates: Vec::new(),
+            is_catching_up: false,
+            acknowledged_input: None,
+            afk_players: Vec::new(),
+            vote: None,
+            vote_results: Vec::new(),
+            room_settings: None,
+            rejoin_snapshot: None,
+            pause_threshold: None,
+            outcoming_messages: Vec::new(),
+            session_recorder: SessionRecorder::disabled(),
+        }
+    }
+
+    /// Records the session in the same format as `gv_client --record-session`.
+    pub fn record_session(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.session_recorder = SessionRecorder::create(path)?;
+        Ok(())
+    }
+
+    /// Finishes the recording started with `record_session`.
+    pub fn stop_recording(&mut self) {
+        self.session_recorder = SessionRecorder::disabled();
+    }
+
+    /// Keeps the messages of the last `window_frames` for `save_highlight`, as the client does.
+    pub fn record_highlights(&mut self, window_frames: u64) {
+        self.session_recorder = SessionRecorder::disabled().with_highlights(window_frames);
+    }
+
+    pub fn save_highlight(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.session_recorder.save_highlight(path)
+    }
+
+    /// Keeps the messages of the whole game for `save_desync_report`, along with the ones
+    /// recorded already, as `gv_client --desync-reports` does.
+    pub fn record_desync_reports(&mut self) {
+        self.session_recorder = std::mem::take(&mut self.session_recorder).with_desync_reports();
+    }
+
+    /// Headless clients don't simulate the world, so their reports have no client positions.
+    pub fn save_desync_report(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.session_recorder
+            .save_desync_report(path, self.game_frame_number, Vec::new())
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn connection_id(&self) -> Option<NetIdentifier> {
+        self.connection_id
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.is_host
+    }
+
+    pub fn room_players(&self) -> &[MultiplayerRoomPlayer] {
+        &self.room_players
+    }
+
+    pub fn has_started_game(&self) -> bool {
+        self.player_net_ids.is_some()
+    }
+
+    /// Entity net id of the player controlled by this client, is known after `StartGame`.
+    pub fn player_net_id(&self) -> Option<NetIdentifier> {
+        let connection_id = self.connection_id?;
+        let player_index = self
+            .room_players
+            .iter()
+            .position(|player| player.connection_id == connection_id)?;
+        self.player_net_ids
+            .as_ref()
+            .and_then(|player_net_ids| player_net_ids.get(player_index).cloned())
+    }
+
+    /// Is set if the game was started with `ResumeGame`.
+    pub fn resumed_match(&self) -> Option<&SavedMatch> {
+        self.resumed_match.as_ref()
+    }
+
+    /// Is set once the server moves the lobby to the next campaign level.
+    pub fn campaign_level(&self) -> Option<usize> {
+        self.campaign_level
+    }
+
+    pub fn match_score(&self) -> &MatchScore {
+        &self.match_score
+    }
+
+    /// Is received with the Handshake.
+    pub fn tick_rate(&self) -> u32 {
+        self.tick_rate
+    }
+
+    pub fn game_frame_number(&self) -> u64 {
+        self.game_frame_number
+    }
+
+    pub fn last_world_update_id(&self) -> Option<u64> {
+        self.last_world_update_id
+    }
+
+    pub fn world_updates(&self) -> &[ServerWorldUpdate] {
+        &self.world_updates
+    }
+
+    pub fn discarded_walk_actions(&self) -> &[NetIdentifier] {
+        &self.discarded_walk_actions
+    }
+
+    /// Imitates a client whose acknowledgements get lost, world updates are still received.
+    pub fn set_acknowledges_world_updates(&mut self, acknowledges_world_updates: bool) {
+        self.acknowledges_world_updates = acknowledges_world_updates;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// The players the server is waiting for, with how far behind they are.
+    pub fn lagging_players(&self) -> &[LaggingPlayer] {
+        &self.lagging_players
+    }
+
+    pub fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.disconnect_reason.clone()
+    }
+
+    /// Rooms from the last RoomList message.
+    pub fn rooms(&self) -> &[RoomInfo] {
+        &self.rooms
+    }
+
+    /// The queue size from the last MatchmakingQueued message.
+    pub fn players_in_queue(&self) -> Option<usize> {
+        self.players_in_queue
+    }
+
+    /// Connection ids of the players and the emotes they've played, in the received order.
+    pub fn emotes(&self) -> &[(NetIdentifier, Emote)] {
+        &self.emotes
+    }
+
+    /// Entity net ids of the players and their augments from every UpdateAugments message.
+    pub fn augments(&self) -> &[(NetIdentifier, PlayerAugments)] {
+        &self.augments
+    }
+
+    /// Entity net ids of the players and their wallets from every UpdateWallet message.
+    pub fn wallets(&self) -> &[(NetIdentifier, PlayerWallet)] {
+        &self.wallets
+    }
+
+    /// The current intermission, is reset by EndIntermission.
+    pub fn intermission(&self) -> Option<&Intermission> {
+        self.intermission.as_ref()
+    }
+
+    /// Actions from every LevelScriptActions message.
+    pub fn level_script_actions(&self) -> &[ScriptAction] {
+        &self.level_script_actions
+    }
+
+    /// Events from every GameEvents message.
+    pub fn game_events(&self) -> &[GameEvent] {
+        &self.game_events
+    }
+
+    /// The progress from the last UpdatePayload message.
+    pub fn payload_progress(&self) -> Option<PayloadProgress> {
+        self.payload_progress
+    }
+
+    /// The balance from the last UpdateBalance message.
+    pub fn balance(&self) -> Option<&Balance> {
+        self.balance.as_ref()
+    }
+
+    /// How many UpdateBalance messages have been received.
+    pub fn balance_updates_count(&self) -> usize {
+        self.balance_updates_count
+    }
+
+    /// The installed packs, including the downloaded ones.
+    pub fn content_packs(&self) -> &ContentPacks {
+        &self.content_packs
+    }
+
+    /// Installs packs the way the client loads them from its mods directory,
+    /// downloaded packs are kept only in memory.
+    pub fn set_content_packs(&mut self, content_packs: ContentPacks) {
+        self.content_packs = content_packs;
+    }
+
+    /// The packs from the last Handshake.
+    pub fn advertised_content_packs(&self) -> &[ContentPackInfo] {
+        &self.advertised_content_packs
+    }
+
+    /// Replies to `console_command`.
+    pub fn console_output(&self) -> &[String] {
+        &self.console_output
+    }
+
+    /// The states of the own player the server has reported.
+    pub fn reconciliation_reports(&self) -> &[PlayerReconciliation] {
+        &self.reconciliation_reports
+    }
+
+    /// The snapshot from the last RejoinGame message.
+    pub fn rejoin_snapshot(&self) -> Option<&MatchSnapshot> {
+        self.rejoin_snapshot.as_ref()
+    }
+
+    /// The snapshot from the last StartSpectating message.
+    pub fn spectator_snapshot(&self) -> Option<&MatchSnapshot> {
+        self.spectator_snapshot.as_ref()
+    }
+
+    /// Updates relayed since the last StartSpectating message.
+    pub fn relayed_updates(&self) -> &[ServerWorldUpdate] {
+        &self.relayed_updates
+    }
+
+    /// Is set by the last RelayWorld message.
+    pub fn is_catching_up(&self) -> bool {
+        self.is_catching_up
+    }
+
+    /// The last client action id the server has acknowledged with AcknowledgeInputs.
+    pub fn acknowledged_input(&self) -> Option<NetIdentifier> {
+        self.acknowledged_input
+    }
+
+    /// Connection ids from the last UpdateAfkPlayers.
+    pub fn afk_players(&self) -> &[NetIdentifier] {
+        &self.afk_players
+    }
+
+    /// The running vote from the last UpdateVote.
+    pub fn vote(&self) -> Option<&Vote> {
+        self.vote.as_ref()
+    }
+
+    /// Vote ids and whether the votes have passed, from EndVote messages.
+    pub fn vote_results(&self) -> &[(NetIdentifier, bool)] {
+        &self.vote_results
+    }
+
+    /// The room settings from the last UpdateRoomSettings.
+    pub fn room_settings(&self) -> Option<&RoomSettings> {
+        self.room_settings.as_ref()
+    }
+
+    /// The room entered via `create_room`, `enter_room` or `find_match`.
+    pub fn room_id(&self) -> Option<RoomId> {
+        self.room_id
+    }
+
+    /// The pause threshold the server has accepted after `set_pause_threshold`.
+    pub fn pause_threshold(&self) -> Option<u64> {
+        self.pause_threshold
+    }
+
+    pub fn list_rooms(&mut self) {
+        self.send(ClientMessagePayload::ListRooms);
+    }
+
+    pub fn create_room(&mut self, name: &str) {
+        self.send(ClientMessagePayload::CreateRoom {
+            name: name.to_owned(),
+        });
+    }
+
+    pub fn enter_room(&mut self, room_id: RoomId) {
+        self.send(ClientMessagePayload::EnterRoom(room_id));
+    }
+
+    pub fn find_match(&mut self, rating: u32) {
+        self.send(ClientMessagePayload::FindMatch { rating });
+    }
+
+    pub fn cancel_matchmaking(&mut self) {
+        self.players_in_queue = None;
+        self.send(ClientMessagePayload::CancelMatchmaking);
+    }
+
+    pub fn join(&mut self) {
+        self.session_id += 1;
+        self.session_created_at += Duration::from_millis(1);
+        self.send(ClientMessagePayload::JoinRoom {
+            sent_at: self.session_created_at,
+            nickname: self.nickname.clone(),
+            class: self.class,
+            cosmetics: self.cosmetics.clone(),
+        });
+    }
+
+    /// Joins a running game without a player.
+    pub fn spectate(&mut self) {
+        self.session_id += 1;
+        self.session_created_at += Duration::from_millis(1);
+        self.send(ClientMessagePayload::Spectate {
+            sent_at: self.session_created_at,
+            nickname: self.nickname.clone(),
+        });
+    }
+
+    /// Cosmetics are sent with `join`, the same way the client does it.
+    pub fn set_cosmetics(&mut self, cosmetics: PlayerCosmetics) {
+        self.cosmetics = cosmetics;
+    }
+
+    pub fn select_class(&mut self, class: PlayerClass) {
+        self.class = class;
+        self.send(ClientMessagePayload::SelectClass(class));
+    }
+
+    pub fn emote(&mut self, emote: Emote) {
+        self.send(ClientMessagePayload::Emote(emote));
+    }
+
+    pub fn choose_augment(&mut self, augment: Augment) {
+        self.send(ClientMessagePayload::ChooseAugment(augment));
+    }
+
+    pub fn summon(&mut self) {
+        self.send(ClientMessagePayload::Summon);
+    }
+
+    /// Sends a line the way a host's client forwards cheat commands.
+    pub fn console_command(&mut self, line: &str) {
+        self.send(ClientMessagePayload::ConsoleCommand(line.to_owned()));
+    }
+
+    pub fn buy_item(&mut self, item: ShopItem) {
+        self.send(ClientMessagePayload::BuyItem(item));
+    }
+
+    pub fn ready_for_wave(&mut self) {
+        self.send(ClientMessagePayload::ReadyForWave);
+    }
+
+    pub fn start_game(&mut self) {
+        self.send(ClientMessagePayload::StartHostedGame);
+    }
+
+    pub fn start_campaign(&mut self) {
+        self.send(ClientMessagePayload::StartHostedCampaign);
+    }
+
+    pub fn resume_game(&mut self) {
+        self.send(ClientMessagePayload::ResumeHostedGame);
+    }
+
+    pub fn kick(&mut self, kicked_connection_id: NetIdentifier) {
+        self.send(ClientMessagePayload::Kick {
+            kicked_connection_id,
+        });
+    }
+
+    pub fn start_vote(&mut self, kind: VoteKind) {
+        self.send(ClientMessagePayload::StartVote(kind));
+    }
+
+    /// Votes in the running vote, does nothing if there's none.
+    pub fn cast_vote(&mut self, is_yes: bool) {
+        if let Some(vote_id) = self.vote.as_ref().map(|vote| vote.id) {
+            self.send(ClientMessagePayload::CastVote { vote_id, is_yes });
+        }
+    }
+
+    pub fn update_room_settings(&mut self, room_settings: RoomSettings) {
+        self.send(ClientMessagePayload::UpdateRoomSettings(room_settings));
+    }
+
+    pub fn set_pause_threshold(&mut self, lagging_frames: u64) {
+        self.send(ClientMessagePayload::SetPauseThreshold(lagging_frames));
+    }
+
+    pub fn disconnect(&mut self) {
+        self.send(ClientMessagePayload::Disconnect);
+    }
+
+    /// Sends a walk action for the current client frame. Pass `frames_ago` to emulate a late
+    /// update which the server has to roll back for.
+    pub fn walk(&mut self, direction: Option<Vector2>, frames_ago: u64) -> NetIdentifier {
+        let entity_net_id = self
+            .player_net_id()
+            .expect("Expected the game to be started to send walk actions");
+        let client_action_id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc += 1;
+
+        let action = direction
+            .map(|direction| PlayerWalkAction::Walk { direction })
+            .unwrap_or(PlayerWalkAction::Stop);
+        self.send(ClientMessagePayload::WalkActions(
+            ImmediatePlayerActionsUpdates {
+                frame_number: (self.game_frame_number + interpolation_frame_delay(self.tick_rate))
+                    .saturating_sub(frames_ago),
+                updates: vec![NetUpdate {
+                    entity_net_id,
+                    data: ClientActionUpdate {
+                        client_action_id,
+                        action,
+                    },
+                }],
+            },
+        ));
+        client_action_id
+    }
+
+    /// Builds an input frame with a walk action, it's sent with `send_inputs` and can be
+    /// sent several times to emulate redundant packets.
+    pub fn walk_input(&mut self, direction: Option<Vector2>, frames_ago: u64) -> InputFrame {
+        let entity_net_id = self
+            .player_net_id()
+            .expect("Expected the game to be started to send walk actions");
+        let client_action_id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc += 1;
+
+        let action = direction
+            .map(|direction| PlayerWalkAction::Walk { direction })
+            .unwrap_or(PlayerWalkAction::Stop);
+        InputFrame {
+            frame_number: (self.game_frame_number + interpolation_frame_delay(self.tick_rate))
+                .saturating_sub(frames_ago),
+            walk_action_updates: vec![NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id,
+                    action,
+                },
+            }],
+            cast_action_updates: Vec::new(),
+            interact_action_updates: Vec::new(),
+        }
+    }
+
+    /// Builds an input frame with an interact action, it's sent with `send_inputs`.
+    pub fn interact_input(&mut self) -> InputFrame {
+        let entity_net_id = self
+            .player_net_id()
+            .expect("Expected the game to be started to send interact actions");
+        let client_action_id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc += 1;
+
+        InputFrame {
+            frame_number: self.game_frame_number + interpolation_frame_delay(self.tick_rate),
+            walk_action_updates: Vec::new(),
+            cast_action_updates: Vec::new(),
+            interact_action_updates: vec![NetUpdate {
+                entity_net_id,
+                data: ClientActionUpdate {
+                    client_action_id,
+                    action: PlayerInteractAction,
+                },
+            }],
+        }
+    }
+
+    pub fn send_inputs(&mut self, input_frames: Vec<InputFrame>) {
+        self.send(ClientMessagePayload::PlayerInputs(input_frames));
+    }
+
+    /// Casts a spell for the current client frame, the server fills in the cast position.
+    pub fn cast(&mut self, spell: CastSpell, target_position: Vector2) -> NetIdentifier {
+        let entity_net_id = self
+            .player_net_id()
+            .expect("Expected the game to be started to send cast actions");
+        let client_action_id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc += 1;
+
+        self.send(ClientMessagePayload::CastActions(
+            ImmediatePlayerActionsUpdates {
+                frame_number: self.game_frame_number + interpolation_frame_delay(self.tick_rate),
+                updates: vec![NetUpdate {
+                    entity_net_id,
+                    data: ClientActionUpdate {
+                        client_action_id,
+                        action: PlayerCastAction {
+                            cast_position: Vector2::zero(),
+                            target_position,
+                            spell,
+                        },
+                    },
+                }],
+            },
+        ));
+        client_action_id
+    }
+
+    pub fn send(&mut self, payload: ClientMessagePayload) {
+        self.outcoming_messages.push(payload);
+    }
+
+    pub(crate) fn drain_outcoming_messages(&mut self) -> Vec<EncodedMessage> {
+        let session_id = self.session_id;
+        let game_frame_number = self.game_frame_number;
+        let session_recorder = &mut self.session_recorder;
+        self.outcoming_messages
+            .drain(..)
+            .map(|payload| {
+                if let ClientMessagePayload::WalkActions(_) = payload {
+                    session_recorder.record(
+                        game_frame_number,
+                        RecordedMessage::Outcoming(payload.clone()),
+                    );
+                }
+                bincode::serialize(&ClientMessage {
+                    session_id,
+                    payload,
+                })
+                .expect("Expected to serialize a client message")
+                .into()
+            })
+            .collect()
+    }
+
+    pub(crate) fn step(&mut self) {
+        if self.has_started_game() && !self.is_paused && self.disconnect_reason.is_none() {
+            self.game_frame_number += 1;
+        }
+        if self.connection_id.is_some() && self.disconnect_reason.is_none() {
+            self.send(ClientMessagePayload::Heartbeat);
+        }
+    }
+
+    pub(crate) fn receive(&mut self, bytes: &[u8]) {
+        let message = match decode_server_message(bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                log::error!("Headless client failed to decode a message: {:?}", err);
+                return;
+            }
+        };
+        if message.session_id != self.session_id {
+            log::warn!(
+                "Headless client ignores a message with session id {} (current: {})",
+                message.session_id,
+                self.session_id
+            );
+            return;
+        }
+        if self.session_recorder.is_recording() {
+            self.session_recorder.record(
+                self.game_frame_number,
+                RecordedMessage::Incoming(message.clone()),
+            );
+        }
+
+        match message.payload {
+            ServerMessagePayload::Heartbeat => {}
+            ServerMessagePayload::Handshake {
+                net_id,
+                is_host,
+                tick_rate,
+                content_packs,
+            } => {
+                self.connection_id = Some(net_id);
+                self.is_host = is_host;
+                self.tick_rate = tick_rate;
+                match self.content_packs.verify(&content_packs) {
+                    Ok(missing_content_packs) => {
+                        for name in missing_content_packs {
+                            self.send(ClientMessagePayload::RequestContentPack(name));
+                        }
+                    }
+                    Err(err) => self.disconnect_with_error(err),
+                }
+                self.advertised_content_packs = content_packs;
+            }
+            ServerMessagePayload::UpdateRoomPlayers(players) => {
+                self.room_players = players;
+            }
+            ServerMessagePayload::StartGame(player_net_ids) => {
+                self.player_net_ids = Some(player_net_ids);
+                self.match_score = MatchScore::default();
+                self.game_frame_number = 0;
+            }
+            ServerMessagePayload::ResumeGame {
+                entity_net_ids,
+                saved_match,
+            } => {
+                self.player_net_ids = Some(entity_net_ids);
+                self.resumed_match = Some(saved_match);
+                self.match_score = MatchScore::default();
+                self.game_frame_number = 0;
+            }
+            ServerMessagePayload::LevelTransition { level_number } => {
+                self.campaign_level = Some(level_number);
+                self.player_net_ids = None;
+                self.last_world_update_id = None;
+            }
+            ServerMessagePayload::UpdateWorld { id, mut updates } => {
+                if self.acknowledges_world_updates {
+                    self.send(ClientMessagePayload::AcknowledgeWorldUpdate(id));
+                }
+                if self
+                    .last_world_update_id
+                    .map_or(false, |last_id| last_id >= id)
+                {
+                    return;
+                }
+                self.last_world_update_id = Some(id);
+                self.world_updates.append(&mut updates);
+            }
+            ServerMessagePayload::DiscardWalkActions(mut discarded_actions) => {
+                self.discarded_walk_actions.append(&mut discarded_actions);
+            }
+            ServerMessagePayload::Ping(ping_id) => {
+                self.send(ClientMessagePayload::Pong {
+                    ping_id,
+                    frame_number: self.game_frame_number,
+                });
+            }
+            ServerMessagePayload::Pong { .. } => {}
+            ServerMessagePayload::ReportPlayersNetStatus { .. } => {}
+            ServerMessagePayload::PauseWaitingForPlayers { players, .. } => {
+                self.is_paused = true;
+                self.lagging_players = players;
+            }
+            ServerMessagePayload::UpdateLaggingPlayers { players, .. } => {
+                if self.is_paused {
+                    self.lagging_players = players;
+                }
+            }
+            ServerMessagePayload::UnpauseWaitingForPlayers(_) => {
+                self.is_paused = false;
+                self.lagging_players.clear();
+            }
+            ServerMessagePayload::UpdateScore(match_score) => {
+                if self.match_score.frame_number <= match_score.frame_number {
+                    self.match_score = match_score;
+                }
+            }
+            ServerMessagePayload::Emote {
+                connection_id,
+                emote,
+            } => {
+                self.emotes.push((connection_id, emote));
+            }
+            ServerMessagePayload::UpdateAugments {
+                entity_net_id,
+                augments,
+            } => {
+                self.augments.push((entity_net_id, augments));
+            }
+            ServerMessagePayload::UpdateWallet {
+                entity_net_id,
+                wallet,
+            } => {
+                self.wallets.push((entity_net_id, wallet));
+            }
+            ServerMessagePayload::UpdateIntermission(intermission) => {
+                self.intermission = Some(intermission);
+            }
+            ServerMessagePayload::EndIntermission(wave) => {
+                if self
+                    .intermission
+                    .as_ref()
+                    .map_or(false, |intermission| intermission.wave <= wave)
+                {
+                    self.intermission = None;
+                }
+            }
+            ServerMessagePayload::LevelScriptActions(actions) => {
+                self.level_script_actions.extend(actions);
+            }
+            ServerMessagePayload::GameEvents(events) => {
+                self.game_events.extend(events);
+            }
+            ServerMessagePayload::UpdatePayload(progress) => {
+                self.payload_progress = Some(progress);
+            }
+            ServerMessagePayload::Disconnect(disconnect_reason) => {
+                self.disconnect_reason = Some(disconnect_reason);
+            }
+            ServerMessagePayload::RoomList(rooms) => {
+                self.rooms = rooms;
+            }
+            ServerMessagePayload::MatchmakingQueued { players_in_queue } => {
+                self.players_in_queue = Some(players_in_queue);
+            }
+            ServerMessagePayload::RoomEntered(room_id)
+            | ServerMessagePayload::MatchFound(room_id) => {
+                self.room_id = Some(room_id);
+            }
+            ServerMessagePayload::UpdateBalance(balance) => {
+                self.balance = Some(balance);
+                self.balance_updates_count += 1;
+            }
+            ServerMessagePayload::ContentPack(content_pack) => {
+                if let Err(err) = self
+                    .content_packs
+                    .add_downloaded(content_pack, &self.advertised_content_packs)
+                {
+                    self.disconnect_with_error(err);
+                }
+            }
+            ServerMessagePayload::ConsoleOutput(output) => {
+                self.console_output.push(output);
+            }
+            ServerMessagePayload::ReconciliationReport(report) => {
+                self.reconciliation_reports.push(report);
+            }
+            ServerMessagePayload::StartSpectating {
+                entity_net_ids,
+                snapshot,
+            } => {
+                self.player_net_ids = Some(entity_net_ids);
+                self.game_frame_number = snapshot.frame_number;
+                self.spectator_snapshot = Some(snapshot);
+                self.relayed_updates.clear();
+                self.is_catching_up = true;
+            }
+            ServerMessagePayload::RelayWorld {
+                mut updates,
+                is_catching_up,
+            } => {
+                self.relayed_updates.append(&mut updates);
+                self.is_catching_up = is_catching_up;
+            }
+            ServerMessagePayload::AcknowledgeInputs(client_action_id) => {
+                self.acknowledged_input = Some(client_action_id);
+            }
+            ServerMessagePayload::UpdateAfkPlayers(afk_players) => {
+                self.afk_players = afk_players;
+            }
+            ServerMessagePayload::UpdateVote(vote) => {
+                self.vote = Some(vote);
+            }
+            ServerMessagePayload::EndVote {
+                vote_id,
+                has_passed,
+            } => {
+                if self.vote.as_ref().map_or(false, |vote| vote.id == vote_id) {
+                    self.vote = None;
+                }
+                self.vote_results.push((vote_id, has_passed));
+            }
+            ServerMessagePayload::ReturnToLobby => {
+                self.player_net_ids = None;
+                self.last_world_update_id = None;
+                self.intermission = None;
+                self.is_paused = false;
+            }
+            ServerMessagePayload::UpdateRoomSettings(room_settings) => {
+                self.room_settings = Some(room_settings);
+            }
+            ServerMessagePayload::RejoinGame {
+                entity_net_ids,
+                snapshot,
+            } => {
+                self.player_net_ids = Some(entity_net_ids);
+                self.match_score = MatchScore::default();
+                self.game_frame_number = snapshot.frame_number;
+                self.rejoin_snapshot = Some(snapshot);
+            }
+            ServerMessagePayload::PauseThreshold(lagging_frames) => {
+                self.pause_threshold = Some(lagging_frames);
+            }
+        }
+    }
+
+    /// Mirrors `disconnect_with_error` of `ClientNetworkSystem`.
+    fn disconnect_with_error(&mut self, error: ClientNetworkError) {
+        self.send(ClientMessagePayload::Disconnect);
+        self.disconnect_reason = Some(DisconnectReason::ClientNetworkError(error));
+    }
+}
@@ -0,0 +1,327 @@
+//! Runs the server systems and headless clients in the same process, connected with
+//! a loopback transport, so networking flows can be tested without sockets.
+
+mod client;
+mod replay;
+mod server;
+
+pub use self::{
+    client::HeadlessClient,
+    replay::{assert_matches_golden, dump_desync_report, SessionReplay, UPDATE_GOLDEN_ENV},
+    server::{HeadlessServer, ServerEndpoint},
+};
+
+use amethyst::network::simulation::{DeliveryRequirement, NetworkSimulationEvent};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::{
+    collections::VecDeque,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+
+use gv_core::net::{
+    client_message::{ClientMessage, ClientMessagePayload},
+    decode_client_message, EncodedMessage,
+};
+use gv_server::rooms::{RoomRouter, RoomsConfig};
+
+pub const FRAME_SECONDS: f32 = 1.0 / 60.0;
+
+const CLIENT_PORT_BASE: u16 = 4000;
+/// Replaced clients get ports past the ones of `add_client`, moving to the next IP
+/// once these run out.
+const REPLACED_CLIENT_PORTS: u64 = 60_000;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoopbackConfig {
+    /// Every message is delivered this many frames after being sent.
+    pub latency_frames: u64,
+    /// A share of unreliable messages (0.0 to 1.0) that never gets delivered.
+    pub loss_rate: f64,
+    /// Unreliable messages are delayed by up to this many frames on top of `latency_frames`,
+    /// which also reorders them. Reliable messages aren't affected by loss or jitter,
+    /// as laminar resends and orders them.
+    pub jitter_frames: u64,
+    /// Seeds the loss and jitter, so that a failing run can be reproduced.
+    pub seed: u64,
+}
+
+enum Destination {
+    Server,
+    Client(SocketAddr),
+}
+
+struct InFlightMessage {
+    deliver_at_frame: u64,
+    source: SocketAddr,
+    destination: Destination,
+    payload: EncodedMessage,
+}
+
+pub struct TestHarness<S = HeadlessServer> {
+    config: LoopbackConfig,
+    frame_number: u64,
+    server: S,
+    clients: Vec<HeadlessClient>,
+    replaced_clients_count: u64,
+    in_flight: VecDeque<InFlightMessage>,
+    rng: StdRng,
+}
+
+impl TestHarness<HeadlessServer> {
+    pub fn new(config: LoopbackConfig) -> amethyst::Result<Self> {
+        Ok(Self::with_server(config, HeadlessServer::new(None)?))
+    }
+
+    /// Checks that every connected client has acknowledged the latest world update
+    /// and that the server isn't waiting for anyone.
+    pub fn is_converged(&self) -> bool {
+        let latest_update_id = self
+            .clients
+            .iter()
+            .filter_map(HeadlessClient::last_world_update_id)
+            .max();
+        let clients_are_synced = self
+            .clients
+            .iter()
+            .filter(|client| client.disconnect_reason().is_none())
+            .all(|client| client.last_world_update_id() == latest_update_id);
+        let server_is_running = self
+            .server
+            .multiplayer_game_state(|multiplayer_game_state| {
+                !multiplayer_game_state.waiting_for_players
+                    && multiplayer_game_state.lagging_players.is_empty()
+            });
+        clients_are_synced && server_is_running
+    }
+}
+
+impl TestHarness<RoomRouter> {
+    /// Connects clients to a server hosting several rooms, clients have to create
+    /// or enter a room before joining.
+    pub fn with_rooms(config: LoopbackConfig, rooms_config: RoomsConfig) -> Self {
+        Self::with_server(config, RoomRouter::new(rooms_config))
+    }
+}
+
+impl<S: ServerEndpoint> TestHarness<S> {
+    pub fn with_server(config: LoopbackConfig, server: S) -> Self {
+        Self {
+            config,
+            frame_number: 0,
+            server,
+            clients: Vec::new(),
+            replaced_clients_count: 0,
+            in_flight: VecDeque::new(),
+            rng: StdRng::seed_from_u64(config.seed),
+        }
+    }
+
+    /// Adds a client and returns its index, which is used to address it in the harness.
+    pub fn add_client(&mut self, nickname: &str) -> usize {
+        let port = CLIENT_PORT_BASE + self.clients.len() as u16;
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+        self.clients.push(HeadlessClient::new(addr, nickname));
+        self.clients.len() - 1
+    }
+
+    /// Replaces a client with a new one with a fresh address, as if another player took
+    /// its seat. Messages already sent by the old client still reach the server,
+    /// the ones addressed to it are lost.
+    pub fn replace_client(&mut self, index: usize, nickname: &str) {
+        let old_addr = self.clients[index].addr();
+        self.in_flight.retain(
+            |message| !matches!(message.destination, Destination::Client(addr) if addr == old_addr),
+        );
+
+        let n = self.replaced_clients_count;
+        self.replaced_clients_count += 1;
+        let ip = Ipv4Addr::new(127, 1 + (n / REPLACED_CLIENT_PORTS % 254) as u8, 0, 1);
+        let port = CLIENT_PORT_BASE + (n % REPLACED_CLIENT_PORTS) as u16;
+        self.clients[index] = HeadlessClient::new(SocketAddr::new(IpAddr::V4(ip), port), nickname);
+    }
+
+    pub fn server(&self) -> &S {
+        &self.server
+    }
+
+    pub fn server_mut(&mut self) -> &mut S {
+        &mut self.server
+    }
+
+    pub fn client(&self, index: usize) -> &HeadlessClient {
+        &self.clients[index]
+    }
+
+    pub fn client_mut(&mut self, index: usize) -> &mut HeadlessClient {
+        &mut self.clients[index]
+    }
+
+    pub fn clients(&self) -> &[HeadlessClient] {
+        &self.clients
+    }
+
+    /// Messages sent but not delivered yet, both to the server and to clients.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Emulates a dropped connection (as if laminar timed out), without sending `Disconnect`.
+    pub fn drop_client(&mut self, index: usize) {
+        let addr = self.clients[index].addr();
+        self.in_flight.retain(|message| message.source != addr);
+        self.server
+            .push_event(NetworkSimulationEvent::Disconnect(addr));
+    }
+
+    pub fn step(&mut self) {
+        self.frame_number += 1;
+
+        for client in &mut self.clients {
+            client.step();
+            let source = client.addr();
+            for payload in client.drain_outcoming_messages() {
+                let is_unreliable = match decode_client_message(payload.as_ref()) {
+                    Ok(ClientMessage { payload, .. }) => matches!(
+                        payload,
+                        ClientMessagePayload::Pong { .. }
+                            | ClientMessagePayload::AcknowledgeWorldUpdate(_)
+                            | ClientMessagePayload::PlayerInputs(_)
+                    ),
+                    Err(_) => false,
+                };
+                let deliver_at_frame = match Self::deliver_at_frame(
+                    &self.config,
+                    &mut self.rng,
+                    self.frame_number,
+                    is_unreliable,
+                ) {
+                    Some(deliver_at_frame) => deliver_at_frame,
+                    None => continue,
+                };
+                self.in_flight.push_back(InFlightMessage {
+                    deliver_at_frame,
+                    source,
+                    destination: Destination::Server,
+                    payload,
+                });
+            }
+        }
+
+        self.deliver(|message| matches!(message.destination, Destination::Server));
+        self.server.step();
+
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+        for message in self.server.drain_outcoming_messages() {
+            let is_unreliable = matches!(
+                message.delivery,
+                DeliveryRequirement::Unreliable | DeliveryRequirement::UnreliableSequenced(_)
+            );
+            let deliver_at_frame = match Self::deliver_at_frame(
+                &self.config,
+                &mut self.rng,
+                self.frame_number,
+                is_unreliable,
+            ) {
+                Some(deliver_at_frame) => deliver_at_frame,
+                None => continue,
+            };
+            self.in_flight.push_back(InFlightMessage {
+                deliver_at_frame,
+                source: server_addr,
+                destination: Destination::Client(message.destination),
+                payload: message.payload,
+            });
+        }
+        self.deliver(|message| matches!(message.destination, Destination::Client(_)));
+    }
+
+    pub fn run_frames(&mut self, frames: u64) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Steps frames until `condition` is met, returns `false` if it wasn't met in `max_frames`.
+    pub fn run_until(&mut self, max_frames: u64, condition: impl Fn(&Self) -> bool) -> bool {
+        for _ in 0..max_frames {
+            if condition(self) {
+                return true;
+            }
+            self.step();
+        }
+        condition(self)
+    }
+
+    /// Joins all the clients (the first one becomes the host) and waits until every one
+    /// of them sees the whole room.
+    pub fn join_all(&mut self, max_frames: u64) -> bool {
+        for client in &mut self.clients {
+            client.join();
+        }
+        let clients_count = self.clients.len();
+        self.run_until(max_frames, |harness| {
+            harness
+                .clients
+                .iter()
+                .all(|client| client.room_players().len() == clients_count)
+        })
+    }
+
+    /// Joins all the clients (the first one becomes the host) and starts the game.
+    pub fn join_and_start(&mut self, max_frames: u64) -> bool {
+        if !self.join_all(max_frames) {
+            return false;
+        }
+
+        if let Some(host) = self.clients.iter_mut().find(|client| client.is_host()) {
+            host.start_game();
+        }
+        self.run_until(max_frames, |harness| {
+            harness.clients.iter().all(HeadlessClient::has_started_game)
+        })
+    }
+
+    /// Returns `None` if the message gets lost.
+    fn deliver_at_frame(
+        config: &LoopbackConfig,
+        rng: &mut StdRng,
+        frame_number: u64,
+        is_unreliable: bool,
+    ) -> Option<u64> {
+        let deliver_at_frame = frame_number + config.latency_frames;
+        if !is_unreliable {
+            return Some(deliver_at_frame);
+        }
+        if config.loss_rate > 0.0 && rng.gen_bool(config.loss_rate.min(1.0)) {
+            return None;
+        }
+        Some(deliver_at_frame + rng.gen_range(0, config.jitter_frames + 1))
+    }
+
+    fn deliver(&mut self, filter: impl Fn(&InFlightMessage) -> bool) {
+        let frame_number = self.frame_number;
+        let (ready, pending): (VecDeque<_>, VecDeque<_>) = self
+            .in_flight
+            .drain(..)
+            .partition(|message| message.deliver_at_frame <= frame_number && filter(message));
+        self.in_flight = pending;
+
+        for message in ready {
+            match message.destination {
+                Destination::Server => self.server.push_event(NetworkSimulationEvent::Message(
+                    message.source,
+                    message.payload,
+                )),
+                Destination::Client(addr) => {
+                    let client = self.clients.iter_mut().find(|client| client.addr() == addr);
+                    if let Some(client) = client {
+                        client.receive(message.payload.as_ref());
+                    } else {
+                        log::warn!("Dropping a message for an unknown client {}", addr);
+                    }
+                }
+            }
+        }
+    }
+}
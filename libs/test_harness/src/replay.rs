@@ -0,0 +1,256 @@
+use std::{collections::BTreeMap, env, fmt::Write, fs, path::Path};
+
+use gv_core::{
+    actions::monster_spawn::SpawnActions,
+    ecs::resources::{
+        net::{LastAcknowledgedUpdate, MultiplayerRoomPlayer},
+        world::{FramedUpdates, PlayerActionUpdates, ReceivedServerWorldUpdate},
+        DEFAULT_TICK_RATE,
+    },
+    net::{
+        client_message::ClientMessagePayload,
+        interpolation_frame_delay,
+        server_message::ServerMessagePayload,
+        session_recording::{
+            read_session_record, DesyncReport, RecordedMessage, SessionRecordEntry,
+        },
+        NetIdentifier,
+    },
+};
+use gv_game::utils::world_updates::{discard_walk_actions, receive_world_update};
+
+/// If set, `assert_matches_golden` overwrites golden files instead of comparing against them.
+pub const UPDATE_GOLDEN_ENV: &str = "GV_UPDATE_GOLDEN";
+
+/// Replays a recorded session through the same functions `ClientNetworkSystem` uses
+/// to apply world updates and discard walk actions.
+pub struct SessionReplay {
+    connection_id: Option<NetIdentifier>,
+    tick_rate: u32,
+    players: Vec<MultiplayerRoomPlayer>,
+    player_net_id: NetIdentifier,
+    last_acknowledged_update: LastAcknowledgedUpdate,
+    framed_updates: FramedUpdates<ReceivedServerWorldUpdate>,
+    spawn_actions: FramedUpdates<SpawnActions>,
+    player_actions_updates: FramedUpdates<PlayerActionUpdates>,
+}
+
+impl SessionReplay {
+    pub fn new() -> Self {
+        Self {
+            connection_id: None,
+            tick_rate: DEFAULT_TICK_RATE,
+            players: Vec::new(),
+            player_net_id: 0,
+            last_acknowledged_update: LastAcknowledgedUpdate::default(),
+            framed_updates: FramedUpdates::default(),
+            spawn_actions: FramedUpdates::default(),
+            player_actions_updates: FramedUpdates::default(),
+        }
+    }
+
+    pub fn replay(entries: &[SessionRecordEntry]) -> Self {
+        let mut replay = Self::new();
+        for entry in entries {
+            replay.apply(entry);
+        }
+        replay
+    }
+
+    pub fn last_acknowledged_update(&self) -> LastAcknowledgedUpdate {
+        self.last_acknowledged_update
+    }
+
+    pub fn apply(&mut self, entry: &SessionRecordEntry) {
+        match &entry.message {
+            RecordedMessage::Incoming(message) => {
+                self.apply_incoming(entry.game_frame_number, message.payload.clone())
+            }
+            RecordedMessage::Outcoming(ClientMessagePayload::WalkActions(actions)) => {
+                self.player_actions_updates
+                    .update_frame(entry.game_frame_number)
+                    .expect("Expected a frame for a recorded walk action")
+                    .walk_action_updates
+                    .extend(actions.updates.iter().cloned());
+            }
+            RecordedMessage::Outcoming(_) => {}
+        }
+    }
+
+    /// Renders the replayed state, to be compared with a golden file.
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        writeln!(dump, "{:#?}", self.last_acknowledged_update).unwrap();
+        writeln!(
+            dump,
+            "framed_updates.oldest_updated_frame: {}",
+            self.framed_updates.oldest_updated_frame
+        )
+        .unwrap();
+        writeln!(dump, "{:#?}", self.framed_updates.updates).unwrap();
+        writeln!(
+            dump,
+            "spawn_actions.oldest_updated_frame: {}",
+            self.spawn_actions.oldest_updated_frame
+        )
+        .unwrap();
+        writeln!(dump, "{:#?}", self.spawn_actions.updates).unwrap();
+        writeln!(
+            dump,
+            "player_actions_updates.oldest_updated_frame: {}",
+            self.player_actions_updates.oldest_updated_frame
+        )
+        .unwrap();
+        writeln!(dump, "{:#?}", self.player_actions_updates.updates).unwrap();
+        self.dump_entities(&mut dump);
+        dump
+    }
+
+    /// Renders the latest state of every entity the applied updates mention, the one
+    /// `ActionSystem` of the client would reconcile its entities to.
+    fn dump_entities(&self, dump: &mut String) {
+        let mut entities = BTreeMap::new();
+        for update in &self.framed_updates.updates {
+            let player_walk_actions_updates = update
+                .player_updates
+                .player_walk_actions_updates
+                .iter()
+                .chain(
+                    update
+                        .controlled_player_updates
+                        .player_walk_actions_updates
+                        .iter(),
+                );
+            for walk_update in player_walk_actions_updates {
+                entities.insert(
+                    walk_update.entity_net_id,
+                    format!(
+                        "frame {}, ({}, {}), {:?}",
+                        update.frame_number,
+                        walk_update.position.x,
+                        walk_update.position.y,
+                        walk_update.data.action
+                    ),
+                );
+            }
+            for mob_update in &update.mob_actions_updates {
+                entities.insert(
+                    mob_update.entity_net_id,
+                    format!(
+                        "frame {}, ({}, {}), {:?}",
+                        update.frame_number,
+                        mob_update.position.x,
+                        mob_update.position.y,
+                        mob_update.data
+                    ),
+                );
+            }
+        }
+        writeln!(dump, "entities:").unwrap();
+        for (entity_net_id, state) in entities {
+            writeln!(dump, "{}: {}", entity_net_id, state).unwrap();
+        }
+    }
+
+    fn apply_incoming(&mut self, game_frame_number: u64, payload: ServerMessagePayload) {
+        match payload {
+            ServerMessagePayload::Handshake {
+                net_id, tick_rate, ..
+            } => {
+                self.connection_id = Some(net_id);
+                self.tick_rate = tick_rate;
+            }
+            ServerMessagePayload::UpdateRoomPlayers(players) => {
+                self.players = players;
+            }
+            ServerMessagePayload::StartGame(entity_net_ids)
+            | ServerMessagePayload::ResumeGame { entity_net_ids, .. }
+            | ServerMessagePayload::RejoinGame { entity_net_ids, .. } => {
+                self.last_acknowledged_update = LastAcknowledgedUpdate::default();
+                let connection_id = self.connection_id;
+                if let Some(i) = self
+                    .players
+                    .iter()
+                    .position(|player| Some(player.connection_id) == connection_id)
+                {
+                    if let Some(entity_net_id) = entity_net_ids.get(i) {
+                        self.player_net_id = *entity_net_id;
+                    }
+                }
+            }
+            ServerMessagePayload::UpdateWorld { id, updates } => {
+                self.player_actions_updates
+                    .reserve_updates(game_frame_number);
+                if let Err(err) = receive_world_update(
+                    &[self.player_net_id],
+                    &mut self.last_acknowledged_update,
+                    &mut self.framed_updates,
+                    &mut self.spawn_actions,
+                    game_frame_number,
+                    interpolation_frame_delay(self.tick_rate),
+                    id,
+                    updates,
+                ) {
+                    // The recording client disconnected at this point as well.
+                    log::error!("Failed to replay a world update {}: {:?}", id, err);
+                }
+            }
+            ServerMessagePayload::DiscardWalkActions(discarded_actions) => {
+                discard_walk_actions(&mut self.player_actions_updates, discarded_actions);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for SessionReplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replays the messages of a desync report and renders the replayed state followed by
+/// the positions the reporting client simulated, to see where it diverged from the server.
+pub fn dump_desync_report(report: &DesyncReport) -> String {
+    let mut dump = SessionReplay::replay(&report.entries).dump();
+    writeln!(
+        dump,
+        "client_positions (frame {}):",
+        report.reported_frame_number
+    )
+    .unwrap();
+    let mut client_positions = report.client_positions.clone();
+    client_positions.sort_by_key(|(entity_net_id, _)| *entity_net_id);
+    for (entity_net_id, position) in client_positions {
+        writeln!(dump, "{}: ({}, {})", entity_net_id, position.x, position.y).unwrap();
+    }
+    dump
+}
+
+/// Replays a session recording and compares the result with `<recording>.golden`.
+pub fn assert_matches_golden(recording_path: impl AsRef<Path>) {
+    let recording_path = recording_path.as_ref();
+    let entries = read_session_record(recording_path)
+        .unwrap_or_else(|err| panic!("Failed to read {:?}: {:?}", recording_path, err));
+    let dump = SessionReplay::replay(&entries).dump();
+
+    let golden_path = recording_path.with_extension("golden");
+    if env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        fs::write(&golden_path, dump)
+            .unwrap_or_else(|err| panic!("Failed to write {:?}: {:?}", golden_path, err));
+        return;
+    }
+
+    let golden = fs::read_to_string(&golden_path).unwrap_or_else(|err| {
+        panic!(
+            "Failed to read {:?} (run with {} set to create it): {:?}",
+            golden_path, UPDATE_GOLDEN_ENV, err
+        )
+    });
+    assert!(
+        golden == dump,
+        "Replaying {:?} produced a different state than {:?}",
+        recording_path,
+        golden_path
+    );
+}
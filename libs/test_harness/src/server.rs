@@ -0,0 +1,131 @@
+use amethyst::{
+    ecs::{Join, ReadStorage, World},
+    network::simulation::{Message, NetworkSimulationEvent},
+};
+
+use std::{net::SocketAddr, time::Duration};
+
+use gv_core::ecs::{
+    components::{zone::Zone, Monster, Player, WorldPosition},
+    resources::{net::MultiplayerGameState, GameEngineState, DEFAULT_TICK_RATE},
+};
+use gv_server::rooms::{Room, RoomRouter};
+
+use crate::FRAME_SECONDS;
+
+/// Anything a `TestHarness` can deliver client messages to.
+pub trait ServerEndpoint {
+    fn push_event(&mut self, event: NetworkSimulationEvent);
+
+    fn step(&mut self);
+
+    fn drain_outcoming_messages(&mut self) -> Vec<Message>;
+}
+
+/// A single room server, as started by a hosting client.
+pub struct HeadlessServer {
+    room: Room,
+}
+
+impl HeadlessServer {
+    pub fn new(host_client_address: Option<SocketAddr>) -> amethyst::Result<Self> {
+        Ok(Self {
+            room: Room::new(
+                0,
+                String::new(),
+                host_client_address,
+                DEFAULT_TICK_RATE,
+                None,
+            )?,
+        })
+    }
+
+    pub fn room(&self) -> &Room {
+        &self.room
+    }
+
+    pub fn world(&self) -> &World {
+        self.room.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.room.world_mut()
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.room.is_shut_down()
+    }
+
+    pub fn engine_state(&self) -> GameEngineState {
+        self.room.engine_state()
+    }
+
+    pub fn game_frame_number(&self) -> u64 {
+        self.room.game_frame_number()
+    }
+
+    pub fn multiplayer_game_state<R>(&self, f: impl FnOnce(&MultiplayerGameState) -> R) -> R {
+        self.room.multiplayer_game_state(f)
+    }
+
+    pub fn connections_count(&self) -> usize {
+        self.room.connections_count()
+    }
+
+    pub fn push_event(&mut self, event: NetworkSimulationEvent) {
+        self.room.push_event(event);
+    }
+
+    pub fn player_positions(&self) -> Vec<WorldPosition> {
+        let (players, world_positions) = self
+            .world()
+            .system_data::<(ReadStorage<Player>, ReadStorage<WorldPosition>)>();
+        (&players, &world_positions)
+            .join()
+            .map(|(_, world_position)| world_position.clone())
+            .collect()
+    }
+
+    pub fn monsters(&self) -> Vec<(Monster, WorldPosition)> {
+        let (monsters, world_positions) = self
+            .world()
+            .system_data::<(ReadStorage<Monster>, ReadStorage<WorldPosition>)>();
+        (&monsters, &world_positions)
+            .join()
+            .map(|(monster, world_position)| (monster.clone(), world_position.clone()))
+            .collect()
+    }
+
+    pub fn zones(&self) -> Vec<Zone> {
+        let zones = self.world().system_data::<ReadStorage<Zone>>();
+        (&zones).join().cloned().collect()
+    }
+}
+
+impl ServerEndpoint for HeadlessServer {
+    fn push_event(&mut self, event: NetworkSimulationEvent) {
+        self.room.push_event(event);
+    }
+
+    fn step(&mut self) {
+        self.room.step(FRAME_SECONDS);
+    }
+
+    fn drain_outcoming_messages(&mut self) -> Vec<Message> {
+        self.room.drain_outcoming_messages()
+    }
+}
+
+impl ServerEndpoint for RoomRouter {
+    fn push_event(&mut self, event: NetworkSimulationEvent) {
+        self.process_event(&event);
+    }
+
+    fn step(&mut self) {
+        self.update(Duration::from_secs_f32(FRAME_SECONDS));
+    }
+
+    fn drain_outcoming_messages(&mut self) -> Vec<Message> {
+        RoomRouter::drain_outcoming_messages(self)
+    }
+}
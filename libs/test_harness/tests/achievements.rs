@@ -0,0 +1,86 @@
+use gv_core::{
+    achievements::{
+        Achievement, AchievementProgress, MONSTER_SLAYER_KILLS, NIGHTMARE_SURVIVOR_WAVE,
+    },
+    game_events::GameEvent,
+    room_settings::Difficulty,
+};
+
+fn kill(killer: Option<u64>) -> GameEvent {
+    GameEvent::Kill {
+        killer,
+        monster: "Ghoul".to_owned(),
+        is_elite: false,
+    }
+}
+
+#[test]
+fn kills_of_the_local_player_unlock_monster_slayer() {
+    let mut progress = AchievementProgress {
+        kills: MONSTER_SLAYER_KILLS - 2,
+        unlocked: Vec::new(),
+    };
+
+    assert_eq!(
+        progress.record_event(&kill(Some(2)), Some(1), Difficulty::Normal, false),
+        None
+    );
+    assert_eq!(
+        progress.record_event(&kill(None), Some(1), Difficulty::Normal, false),
+        None
+    );
+    assert_eq!(
+        progress.record_event(&kill(Some(1)), Some(1), Difficulty::Normal, false),
+        None
+    );
+    assert_eq!(
+        progress.record_event(&kill(Some(1)), Some(1), Difficulty::Normal, false),
+        Some(Achievement::MonsterSlayer)
+    );
+    // Achievements are unlocked once.
+    assert_eq!(
+        progress.record_event(&kill(Some(1)), Some(1), Difficulty::Normal, false),
+        None
+    );
+    assert_eq!(progress.kills, MONSTER_SLAYER_KILLS + 1);
+}
+
+#[test]
+fn nightmare_survivor_needs_the_hard_difficulty() {
+    let mut progress = AchievementProgress::default();
+    let cleared = GameEvent::WaveCleared(NIGHTMARE_SURVIVOR_WAVE);
+
+    assert_eq!(
+        progress.record_event(&cleared, None, Difficulty::Normal, false),
+        None
+    );
+    assert_eq!(
+        progress.record_event(
+            &GameEvent::WaveCleared(NIGHTMARE_SURVIVOR_WAVE - 1),
+            None,
+            Difficulty::Hard,
+            false
+        ),
+        None
+    );
+    assert_eq!(
+        progress.record_event(&cleared, None, Difficulty::Hard, false),
+        Some(Achievement::NightmareSurvivor)
+    );
+}
+
+#[test]
+fn flawless_needs_a_campaign_level_without_damage() {
+    let mut progress = AchievementProgress::default();
+    let completed = GameEvent::CampaignLevelCompleted(0);
+
+    assert_eq!(
+        progress.record_event(&completed, None, Difficulty::Easy, true),
+        None
+    );
+    assert_eq!(
+        progress.record_event(&completed, None, Difficulty::Easy, false),
+        Some(Achievement::Flawless)
+    );
+    assert!(progress.is_unlocked(Achievement::Flawless));
+}
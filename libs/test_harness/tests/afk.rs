@@ -0,0 +1,67 @@
+use amethyst::ecs::WorldExt;
+
+use std::time::Duration;
+
+use gv_core::{math::Vector2, net::server_message::DisconnectReason};
+use gv_server::ecs::resources::AfkConfig;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 600;
+
+#[test]
+fn idle_players_are_reported_and_kicked() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    harness.server_mut().world_mut().insert(AfkConfig {
+        idle_timeout: Duration::from_secs(1),
+        kick_timeout: Some(Duration::from_secs(3)),
+    });
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_connection_id = harness.client(guest).connection_id().unwrap();
+
+    let is_reported = |harness: &TestHarness| {
+        harness
+            .client(host)
+            .afk_players()
+            .contains(&guest_connection_id)
+    };
+    let mut walks_left = true;
+    for _ in 0..MAX_FRAMES / 10 {
+        // The host keeps changing the direction, so it's never idle.
+        let direction = if walks_left { -1.0 } else { 1.0 };
+        walks_left = !walks_left;
+        let input = harness
+            .client_mut(host)
+            .walk_input(Some(Vector2::new(direction, 0.0)), 0);
+        harness.client_mut(host).send_inputs(vec![input]);
+        harness.run_frames(10);
+        if is_reported(&harness) {
+            break;
+        }
+    }
+    assert!(is_reported(&harness));
+    assert!(harness.client(guest).disconnect_reason().is_none());
+    assert!(!harness
+        .client(host)
+        .afk_players()
+        .contains(&harness.client(host).connection_id().unwrap()));
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(guest).disconnect_reason(),
+        Some(DisconnectReason::AfkKick)
+    ));
+    // Kicking the host would shut the room down.
+    harness.run_frames(MAX_FRAMES);
+    assert!(harness.client(host).disconnect_reason().is_none());
+    assert!(harness
+        .server()
+        .multiplayer_game_state(|multiplayer_game_state| {
+            multiplayer_game_state
+                .find_player_by_connection_id(guest_connection_id)
+                .is_none()
+        }));
+}
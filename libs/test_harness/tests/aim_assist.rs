@@ -0,0 +1,71 @@
+use gv_core::{
+    aim_assist::{aim_assist_target, AimAssist, AimAssistStrength, AIM_ASSIST_RANGE},
+    math::Vector2,
+    room_settings::RoomSettings,
+};
+
+fn angle(vector: Vector2) -> f32 {
+    vector.y.atan2(vector.x)
+}
+
+#[test]
+fn closest_monster_within_the_cone_is_targeted() {
+    let aim_direction = Vector2::new(1.0, 0.0);
+    let near = Vector2::new(200.0, 30.0);
+    let far = Vector2::new(400.0, -20.0);
+    let behind = Vector2::new(-100.0, 0.0);
+    let aside = Vector2::new(50.0, 150.0);
+    let out_of_range = Vector2::new(AIM_ASSIST_RANGE + 1.0, 0.0);
+
+    assert_eq!(
+        aim_assist_target(aim_direction, vec![far, behind, near, aside, out_of_range]),
+        Some(near)
+    );
+    assert_eq!(
+        aim_assist_target(aim_direction, vec![behind, aside, out_of_range]),
+        None
+    );
+    assert_eq!(aim_assist_target(Vector2::new(0.0, 0.0), vec![near]), None);
+}
+
+#[test]
+fn aim_eases_towards_the_target() {
+    let aim_direction = Vector2::new(300.0, 0.0);
+    let target = Vector2::new(200.0, 40.0);
+    let mut aim_assist = AimAssist::default();
+
+    let first = aim_assist.apply(aim_direction, Some(target), AimAssistStrength::High, 0.016);
+    let later = (0..60).fold(first, |_, _| {
+        aim_assist.apply(aim_direction, Some(target), AimAssistStrength::High, 0.016)
+    });
+    assert!(angle(first) > 0.0);
+    assert!(angle(later) > angle(first));
+    // The aim is bent towards the target, but doesn't snap onto it.
+    assert!(angle(later) < angle(target));
+    assert!((later.norm() - aim_direction.norm()).abs() < 0.01);
+
+    // Letting go of the target is eased as well.
+    let released = aim_assist.apply(aim_direction, None, AimAssistStrength::High, 0.016);
+    assert!(angle(released) > 0.0 && angle(released) < angle(later));
+}
+
+#[test]
+fn stronger_assist_bends_the_aim_more() {
+    let aim_direction = Vector2::new(300.0, 0.0);
+    let target = Vector2::new(200.0, 40.0);
+    let settled_aim = |strength| {
+        let mut aim_assist = AimAssist::default();
+        (0..120).fold(aim_direction, |_, _| {
+            aim_assist.apply(aim_direction, Some(target), strength, 0.016)
+        })
+    };
+    assert_eq!(angle(settled_aim(AimAssistStrength::Off)), 0.0);
+    assert!(
+        angle(settled_aim(AimAssistStrength::High)) > angle(settled_aim(AimAssistStrength::Low))
+    );
+}
+
+#[test]
+fn rooms_allow_aim_assist_by_default() {
+    assert!(RoomSettings::default().aim_assist);
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use gv_core::{
+    announcer::{AnnouncerCooldowns, AnnouncerLine, ANNOUNCER_GAP},
+    game_events::GameEvent,
+};
+
+#[test]
+fn events_map_to_lines() {
+    let kill = |is_elite| GameEvent::Kill {
+        killer: Some(1),
+        monster: "Ghoul".to_owned(),
+        is_elite,
+    };
+    assert_eq!(
+        AnnouncerLine::from_event(&kill(true), Some(1)),
+        Some(AnnouncerLine::EliteSlain)
+    );
+    assert_eq!(AnnouncerLine::from_event(&kill(false), Some(1)), None);
+    assert_eq!(
+        AnnouncerLine::from_event(&GameEvent::WaveStarted(2), None),
+        Some(AnnouncerLine::WaveIncoming)
+    );
+    assert_eq!(
+        AnnouncerLine::from_event(&GameEvent::WaveCleared(2), None),
+        Some(AnnouncerLine::WaveCleared)
+    );
+}
+
+#[test]
+fn only_allies_dying_is_announced() {
+    assert_eq!(
+        AnnouncerLine::from_event(&GameEvent::PlayerDied(2), Some(1)),
+        Some(AnnouncerLine::AllyDown)
+    );
+    assert_eq!(
+        AnnouncerLine::from_event(&GameEvent::PlayerDied(1), Some(1)),
+        None
+    );
+}
+
+#[test]
+fn lines_wait_for_their_cooldowns() {
+    let mut cooldowns = AnnouncerCooldowns::default();
+    let start = Duration::from_secs(100);
+    let cooldown = AnnouncerLine::AllyDown.cooldown();
+    assert!(cooldowns.try_play(AnnouncerLine::AllyDown, start));
+    assert!(!cooldowns.try_play(AnnouncerLine::AllyDown, start + cooldown / 2));
+    assert!(cooldowns.try_play(AnnouncerLine::AllyDown, start + cooldown));
+
+    cooldowns.reset();
+    assert!(cooldowns.try_play(AnnouncerLine::AllyDown, start));
+}
+
+#[test]
+fn lines_dont_talk_over_each_other() {
+    let mut cooldowns = AnnouncerCooldowns::default();
+    let start = Duration::from_secs(100);
+    assert!(cooldowns.try_play(AnnouncerLine::WaveCleared, start));
+    assert!(!cooldowns.try_play(AnnouncerLine::LevelUp, start));
+    assert!(cooldowns.try_play(AnnouncerLine::LevelUp, start + ANNOUNCER_GAP));
+}
@@ -0,0 +1,106 @@
+use amethyst::ecs::WorldExt;
+
+use gv_core::{
+    actions::{player::PlayerWalkAction, ClientActionUpdate},
+    anti_cheat::{inspect_message, SuspiciousSession, Violation, SUSPICIOUS_VIOLATIONS},
+    ecs::resources::world::{
+        FramedUpdates, ImmediatePlayerActionsUpdates, ReceivedClientActionUpdates,
+        PAUSE_FRAME_THRESHOLD,
+    },
+    math::Vector2,
+    net::{client_message::ClientMessagePayload, NetUpdate},
+};
+use gv_server::{ecs::resources::SuspiciousSessionsDir, review::review_report};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+use std::{env, fs};
+
+const MAX_FRAMES: u64 = 300;
+
+fn walk_actions(entity_net_id: u64, frame_number: u64) -> ClientMessagePayload {
+    ClientMessagePayload::WalkActions(ImmediatePlayerActionsUpdates {
+        frame_number,
+        updates: vec![NetUpdate {
+            entity_net_id,
+            data: ClientActionUpdate {
+                client_action_id: 0,
+                action: PlayerWalkAction::Stop,
+            },
+        }],
+    })
+}
+
+#[test]
+fn messages_breaking_the_rules_are_flagged() {
+    assert_eq!(inspect_message(1, 100, &walk_actions(1, 100)), None);
+    assert_eq!(
+        inspect_message(1, 100, &walk_actions(1, 100 + PAUSE_FRAME_THRESHOLD)),
+        None
+    );
+    assert_eq!(
+        inspect_message(1, 100, &walk_actions(1, 101 + PAUSE_FRAME_THRESHOLD)),
+        Some(Violation::FutureFrame(101 + PAUSE_FRAME_THRESHOLD))
+    );
+    assert_eq!(
+        inspect_message(1, 100, &walk_actions(2, 500 + PAUSE_FRAME_THRESHOLD)),
+        Some(Violation::ForeignEntity(2))
+    );
+    assert_eq!(
+        inspect_message(1, 100, &ClientMessagePayload::ReadyForWave),
+        None
+    );
+}
+
+#[test]
+fn actions_for_foreign_entities_are_dropped_and_reported() {
+    let suspicious_sessions_dir = env::temp_dir().join("gv_anti_cheat_foreign_entities");
+    let _ = fs::remove_dir_all(&suspicious_sessions_dir);
+    fs::create_dir_all(&suspicious_sessions_dir).unwrap();
+
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let cheater = harness.add_client("cheater");
+    harness
+        .server_mut()
+        .world_mut()
+        .insert(SuspiciousSessionsDir(Some(suspicious_sessions_dir.clone())));
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let host_net_id = harness.client(host).player_net_id().unwrap();
+
+    for _ in 0..SUSPICIOUS_VIOLATIONS {
+        let mut input_frame = harness
+            .client_mut(cheater)
+            .walk_input(Some(Vector2::new(1.0, 0.0)), 0);
+        input_frame.walk_action_updates[0].entity_net_id = host_net_id;
+        harness.client_mut(cheater).send_inputs(vec![input_frame]);
+        harness.run_frames(2);
+    }
+    harness.run_frames(2);
+
+    let host_walk_actions = harness
+        .server()
+        .world()
+        .fetch::<FramedUpdates<ReceivedClientActionUpdates>>()
+        .updates
+        .iter()
+        .flat_map(|update| update.walk_action_updates.iter())
+        .filter(|walk_action| walk_action.entity_net_id == host_net_id)
+        .count();
+    assert_eq!(host_walk_actions, 0);
+
+    let saved_sessions = fs::read_dir(&suspicious_sessions_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(saved_sessions.len(), 1);
+    let suspicious_session = SuspiciousSession::load(&saved_sessions[0]).unwrap();
+    assert_eq!(suspicious_session.nickname, "cheater");
+    assert_eq!(suspicious_session.entries.len(), SUSPICIOUS_VIOLATIONS);
+
+    let flagged_frames = suspicious_session.review();
+    assert_eq!(flagged_frames.len(), SUSPICIOUS_VIOLATIONS);
+    assert!(flagged_frames
+        .iter()
+        .all(|flagged_frame| flagged_frame.violation == Violation::ForeignEntity(host_net_id)));
+    assert!(review_report(&suspicious_session).starts_with("cheater"));
+}
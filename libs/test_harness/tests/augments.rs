@@ -0,0 +1,63 @@
+use gv_core::{
+    augments::{Augment, PlayerAugments, KILLS_PER_LEVEL, MAX_AUGMENTS},
+    ecs::resources::MatchScore,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn every_level_up_grants_one_augment() {
+    let mut match_score = MatchScore::default();
+    let mut augments = PlayerAugments::default();
+    assert!(!augments.can_choose(match_score.level()));
+
+    match_score.kills = KILLS_PER_LEVEL * 2;
+    augments.0.push(Augment::Pierce);
+    assert!(augments.can_choose(match_score.level()));
+    augments.0.push(Augment::Pierce);
+    assert!(!augments.can_choose(match_score.level()));
+    assert_eq!(augments.count(Augment::Pierce), 2);
+
+    match_score.kills = KILLS_PER_LEVEL * 100;
+    augments.0 = vec![Augment::Multishot; MAX_AUGMENTS];
+    assert!(!augments.can_choose(match_score.level()));
+    assert_eq!(augments.missile_count(), MAX_AUGMENTS as u32 + 1);
+}
+
+#[test]
+fn augments_without_a_level_up_are_ignored() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.client_mut(host).choose_augment(Augment::Chain);
+    harness.run_frames(60);
+    assert!(harness.client(host).augments().is_empty());
+}
+
+#[test]
+fn chosen_augments_are_broadcast_to_the_room() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_net_id = harness.client(guest).player_net_id().unwrap();
+
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<MatchScore>()
+        .kills = KILLS_PER_LEVEL;
+    harness.client_mut(guest).choose_augment(Augment::Multishot);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            client.augments() == [(guest_net_id, PlayerAugments(vec![Augment::Multishot]))]
+        })
+    }));
+
+    // The level-up has already been spent.
+    harness.client_mut(guest).choose_augment(Augment::Pierce);
+    harness.run_frames(60);
+    assert_eq!(harness.client(guest).augments().len(), 1);
+}
@@ -0,0 +1,69 @@
+use amethyst::ecs::{Join, ReadStorage};
+use gv_core::{
+    balance::Balance,
+    classes::PlayerClass,
+    ecs::components::{aura::Auras, Player},
+};
+use gv_game::ecs::resources::ClassDefinitions;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+const DEFAULT_BALANCE: &str = include_str!("../../../resources/balance.ron");
+
+#[test]
+fn players_spawn_with_class_auras() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    harness.client_mut(host).join();
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_players().len() == 2)
+    }));
+    harness.client_mut(guest).select_class(PlayerClass::Warden);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            client
+                .room_players()
+                .iter()
+                .any(|player| player.class == PlayerClass::Warden)
+        })
+    }));
+
+    harness.client_mut(host).start_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+
+    let world = harness.server().world();
+    let class_definitions = world.fetch::<ClassDefinitions>();
+    let (players, auras) = world.system_data::<(ReadStorage<Player>, ReadStorage<Auras>)>();
+    let players_with_auras = (&players, &auras)
+        .join()
+        .map(|(player, auras)| (player.class, auras.0.clone()))
+        .collect::<Vec<_>>();
+    // Mages don't have any auras, so they don't get the component at all.
+    assert_eq!(
+        players_with_auras,
+        vec![(
+            PlayerClass::Warden,
+            class_definitions.get(PlayerClass::Warden).auras.clone()
+        )]
+    );
+}
+
+#[test]
+fn auras_reaching_beyond_rollbacks_are_rejected() {
+    let wide_aura = DEFAULT_BALANCE.replace(
+        "(radius: 100.0, effect: Slow(speed_factor: 0.85))",
+        "(radius: 1000.0, effect: Slow(speed_factor: 0.85))",
+    );
+    assert_ne!(wide_aura, DEFAULT_BALANCE);
+    assert!(Balance::from_bytes(wide_aura.as_bytes()).is_err());
+}
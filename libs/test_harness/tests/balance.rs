@@ -0,0 +1,109 @@
+use gv_core::{balance::Balance, classes::PlayerClass};
+use gv_game::ecs::resources::ClassDefinitions;
+use gv_server::ecs::resources::BalanceWatchFile;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+use std::{env, fs};
+
+const MAX_FRAMES: u64 = 300;
+const DEFAULT_BALANCE: &str = include_str!("../../../resources/balance.ron");
+
+fn mage_speed(balance: &Balance) -> f32 {
+    balance.classes[&PlayerClass::Mage].base_speed
+}
+
+#[test]
+fn invalid_balances_are_rejected() {
+    assert!(Balance::from_bytes(DEFAULT_BALANCE.as_bytes()).is_ok());
+
+    let without_wisps = DEFAULT_BALANCE.replace("\"Wisp\"", "\"Imp\"");
+    assert!(Balance::from_bytes(without_wisps.as_bytes()).is_err());
+
+    let unknown_spell = DEFAULT_BALANCE.replace("spell: \"Dart\"", "spell: \"Fireball\"");
+    assert!(Balance::from_bytes(unknown_spell.as_bytes()).is_err());
+
+    let late_pause = DEFAULT_BALANCE.replace("lagging_frames: 30", "lagging_frames: 3000");
+    assert!(Balance::from_bytes(late_pause.as_bytes()).is_err());
+}
+
+#[test]
+fn overrides_add_spells() {
+    let overrides = r#"(
+        spells: {
+            "Spear": (
+                damage: 80.0,
+                radius: 3.0,
+                max_speed: 500.0,
+                min_speed: 500.0,
+                lifespan_secs: 1.0,
+                fade_secs: 0.2,
+                knockback: 0.0,
+                bounces: 0,
+                on_hit: [Pierce(2), Chain(1), Pierce(1)],
+            ),
+        },
+    )"#;
+    let balance = Balance::default()
+        .with_overrides(overrides.as_bytes())
+        .unwrap();
+    assert_eq!(balance.spells["Spear"].pierces(), 3);
+    assert_eq!(balance.spells["Spear"].chains(), 1);
+    assert!(balance.spells.contains_key("MagicMissile"));
+
+    let min_above_max = overrides.replace("min_speed: 500.0", "min_speed: 600.0");
+    assert!(Balance::default()
+        .with_overrides(min_above_max.as_bytes())
+        .is_err());
+}
+
+#[test]
+fn clients_receive_the_balance_with_start_game() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    let balance = harness
+        .client(host)
+        .balance()
+        .expect("Expected a balance before StartGame");
+    assert_eq!(mage_speed(balance), mage_speed(&Balance::default()));
+    assert_eq!(balance.monsters["Ghoul"].name, "Ghoul");
+}
+
+#[test]
+fn watched_balance_is_reloaded_and_broadcasted() {
+    let balance_path = env::temp_dir().join("gv_watched_balance.ron");
+    fs::write(&balance_path, DEFAULT_BALANCE).unwrap();
+
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness
+        .server_mut()
+        .world_mut()
+        .insert(BalanceWatchFile(Some(balance_path.clone())));
+    assert!(harness.join_and_start(MAX_FRAMES));
+    assert_eq!(harness.client(host).balance_updates_count(), 1);
+
+    let faster_mages = DEFAULT_BALANCE.replacen("base_speed: 200.0", "base_speed: 260.0", 1);
+    fs::write(&balance_path, faster_mages).unwrap();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).balance_updates_count() == 2
+    }));
+
+    assert_eq!(mage_speed(harness.client(host).balance().unwrap()), 260.0);
+    let world = harness.server().world();
+    assert_eq!(mage_speed(&world.fetch::<Balance>()), 260.0);
+    assert_eq!(
+        world
+            .fetch::<ClassDefinitions>()
+            .get(PlayerClass::Mage)
+            .base_speed,
+        260.0
+    );
+
+    // Broken files are logged and ignored.
+    fs::write(&balance_path, "(").unwrap();
+    harness.run_frames(MAX_FRAMES);
+    assert_eq!(harness.client(host).balance_updates_count(), 2);
+    fs::remove_file(&balance_path).unwrap();
+}
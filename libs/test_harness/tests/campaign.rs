@@ -0,0 +1,151 @@
+use amethyst::ecs::{Join, ReadStorage, WriteStorage};
+use gv_core::{
+    augments::{Augment, PlayerAugments},
+    campaign::CAMPAIGN_LEVELS,
+    economy::PlayerWallet,
+    ecs::resources::{net::MultiplayerGameState, GameEngineState, GameLevelState},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 600;
+
+fn start_campaign(harness: &mut TestHarness) -> bool {
+    for index in 0..harness.clients().len() {
+        harness.client_mut(index).join();
+    }
+    let clients_count = harness.clients().len();
+    let joined = harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_players().len() == clients_count)
+    });
+    if !joined {
+        return false;
+    }
+    harness.client_mut(0).start_campaign();
+    harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    })
+}
+
+fn complete_current_level(harness: &mut TestHarness) {
+    let campaign_level = harness
+        .server()
+        .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.campaign_level)
+        .expect("Expected a campaign to be started");
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<GameLevelState>()
+        .spawn_level = CAMPAIGN_LEVELS[campaign_level].completed_at_spawn_level;
+}
+
+#[test]
+fn lobby_moves_on_to_the_next_level() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    harness.add_client("guest");
+    assert!(start_campaign(&mut harness));
+    assert_eq!(
+        harness
+            .server()
+            .world()
+            .fetch::<GameLevelState>()
+            .spawn_level,
+        CAMPAIGN_LEVELS[0].spawn_level
+    );
+
+    complete_current_level(&mut harness);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.server().engine_state() == GameEngineState::LevelTransition
+    }));
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.campaign_level() == Some(1)));
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+    assert_eq!(harness.server().engine_state(), GameEngineState::Playing);
+    assert_eq!(harness.server().player_positions().len(), 2);
+    assert_eq!(
+        harness
+            .server()
+            .world()
+            .fetch::<GameLevelState>()
+            .spawn_level,
+        CAMPAIGN_LEVELS[1].spawn_level
+    );
+    assert!(harness.clients().iter().all(|client| {
+        client
+            .resumed_match()
+            .map_or(false, |saved_match| saved_match.players.len() == 2)
+    }));
+}
+
+#[test]
+fn upgrades_carry_over_to_the_next_level() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    assert!(start_campaign(&mut harness));
+
+    {
+        let world = harness.server_mut().world_mut();
+        let (mut player_augments, mut player_wallets) =
+            world.system_data::<(WriteStorage<PlayerAugments>, WriteStorage<PlayerWallet>)>();
+        for (augments, wallet) in (&mut player_augments, &mut player_wallets).join() {
+            augments.0.push(Augment::Chain);
+            wallet.coins = 55;
+        }
+    }
+    complete_current_level(&mut harness);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.server().engine_state() == GameEngineState::LevelTransition
+    }));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.server().engine_state() == GameEngineState::Playing
+            && harness.client(0).has_started_game()
+    }));
+
+    let (player_augments, player_wallets) = harness
+        .server()
+        .world()
+        .system_data::<(ReadStorage<PlayerAugments>, ReadStorage<PlayerWallet>)>();
+    let upgrades = (&player_augments, &player_wallets)
+        .join()
+        .map(|(augments, wallet)| (augments.0.clone(), wallet.coins))
+        .collect::<Vec<_>>();
+    assert_eq!(upgrades, vec![(vec![Augment::Chain], 55)]);
+}
+
+#[test]
+fn completed_campaign_goes_on_in_survival_mode() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    assert!(start_campaign(&mut harness));
+
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<MultiplayerGameState>()
+        .campaign_level = Some(CAMPAIGN_LEVELS.len() - 1);
+    complete_current_level(&mut harness);
+    harness.run_frames(10);
+
+    assert_eq!(harness.server().engine_state(), GameEngineState::Playing);
+    assert_eq!(
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.campaign_level),
+        None
+    );
+    assert_eq!(harness.client(0).campaign_level(), None);
+}
@@ -0,0 +1,79 @@
+use amethyst::ecs::{Join, ReadStorage};
+use gv_core::{classes::PlayerClass, ecs::components::Player};
+use gv_game::ecs::resources::ClassDefinitions;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn room_classes_are(harness: &TestHarness, classes: &[PlayerClass]) -> bool {
+    harness.clients().iter().all(|client| {
+        client
+            .room_players()
+            .iter()
+            .map(|player| player.class)
+            .eq(classes.iter().cloned())
+    })
+}
+
+#[test]
+fn selected_class_is_replicated_to_room_players() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_all(MAX_FRAMES));
+    assert!(room_classes_are(
+        &harness,
+        &[PlayerClass::Mage, PlayerClass::Mage]
+    ));
+
+    harness.client_mut(guest).select_class(PlayerClass::Warden);
+    assert!(harness.run_until(MAX_FRAMES, |harness| room_classes_are(
+        harness,
+        &[PlayerClass::Mage, PlayerClass::Warden]
+    )));
+}
+
+#[test]
+fn players_spawn_with_class_stats() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_all(MAX_FRAMES));
+    harness
+        .client_mut(guest)
+        .select_class(PlayerClass::Trickster);
+    assert!(harness.run_until(MAX_FRAMES, |harness| room_classes_are(
+        harness,
+        &[PlayerClass::Mage, PlayerClass::Trickster]
+    )));
+
+    harness.client_mut(host).start_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+
+    let world = harness.server().world();
+    let class_definitions = world.fetch::<ClassDefinitions>();
+    let mut players = world
+        .system_data::<ReadStorage<Player>>()
+        .join()
+        .map(|player| (player.class, player.health))
+        .collect::<Vec<_>>();
+    players.sort_by_key(|(class, _)| class.name());
+    assert_eq!(
+        players,
+        vec![
+            (
+                PlayerClass::Mage,
+                class_definitions.get(PlayerClass::Mage).base_health
+            ),
+            (
+                PlayerClass::Trickster,
+                class_definitions.get(PlayerClass::Trickster).base_health
+            ),
+        ]
+    );
+}
@@ -0,0 +1,71 @@
+use gv_core::net::clock_sync::ClockSync;
+
+/// Emulates a pong received at `clock_frame_number` from a peer which is `offset` frames ahead,
+/// the pong spends `outbound_frames` of the round trip on its way back.
+fn add_pong(
+    clock_sync: &mut ClockSync,
+    clock_frame_number: u64,
+    offset: u64,
+    round_trip_frames: u64,
+    outbound_frames: u64,
+) {
+    let peer_frame_number = clock_frame_number + offset - outbound_frames;
+    clock_sync.add_sample(clock_frame_number, peer_frame_number, round_trip_frames);
+}
+
+#[test]
+fn offset_is_estimated_from_the_fastest_round_trips() {
+    let mut clock_sync = ClockSync::default();
+    assert!(clock_sync.peer_frame_number(100).is_none());
+
+    add_pong(&mut clock_sync, 100, 50, 10, 5);
+    assert_eq!(clock_sync.peer_frame_number(100), Some(150));
+    assert_eq!(clock_sync.peer_frame_number(130), Some(180));
+
+    // Queued pongs have asymmetric round trips, they don't move the estimate much.
+    for i in 1..=6 {
+        add_pong(&mut clock_sync, 100 + i * 30, 50, 30, 25);
+    }
+    let estimated_peer_frame = clock_sync.peer_frame_number(300).unwrap();
+    assert!((349..=351).contains(&estimated_peer_frame));
+}
+
+#[test]
+fn estimate_follows_a_drifting_clock() {
+    let mut clock_sync = ClockSync::default();
+    // The peer runs a frame ahead every 200 frames.
+    for i in 0..100 {
+        let clock_frame_number = i * 30;
+        add_pong(
+            &mut clock_sync,
+            clock_frame_number,
+            20 + clock_frame_number / 200,
+            8,
+            4,
+        );
+    }
+    let estimate = clock_sync.estimate().unwrap();
+    assert!(estimate.drift > 0.0);
+    let expected_peer_frame = 3000 + 20 + 3000 / 200;
+    let estimated_peer_frame = clock_sync.peer_frame_number(3000).unwrap();
+    assert!((expected_peer_frame - 2..=expected_peer_frame + 2).contains(&estimated_peer_frame));
+}
+
+#[test]
+fn large_corrections_restart_the_estimate() {
+    let mut clock_sync = ClockSync::default();
+    for i in 0..5 {
+        add_pong(&mut clock_sync, i * 30, 110, 6, 3);
+    }
+    assert_eq!(clock_sync.peer_frame_number(150), Some(260));
+
+    // The peer has been paused for a hundred frames.
+    add_pong(&mut clock_sync, 150, 10, 6, 3);
+    assert_eq!(clock_sync.peer_frame_number(150), Some(160));
+    add_pong(&mut clock_sync, 180, 10, 20, 10);
+    assert_eq!(clock_sync.peer_frame_number(180), Some(190));
+    assert_eq!(clock_sync.estimate().unwrap().drift, 0.0);
+
+    clock_sync.reset();
+    assert!(clock_sync.estimate().is_none());
+}
@@ -0,0 +1,61 @@
+use gv_core::{
+    balance::Balance,
+    collision_layers::{CollisionLayer, CollisionMatrix},
+};
+
+const DEFAULT_BALANCE: &str = include_str!("../../../resources/balance.ron");
+
+#[test]
+fn player_projectiles_pass_through_allies_by_default() {
+    let collisions = Balance::default().collisions;
+    assert_eq!(collisions, CollisionMatrix::default());
+
+    assert!(collisions.hits(CollisionLayer::Projectiles, CollisionLayer::Monsters));
+    assert!(collisions.hits(CollisionLayer::Projectiles, CollisionLayer::Props));
+    assert!(!collisions.hits(CollisionLayer::Projectiles, CollisionLayer::Players));
+    assert!(!collisions.hits(CollisionLayer::Projectiles, CollisionLayer::Minions));
+    assert_eq!(
+        collisions
+            .targets(CollisionLayer::Monsters)
+            .collect::<Vec<_>>(),
+        vec![CollisionLayer::Players, CollisionLayer::Minions]
+    );
+}
+
+#[test]
+fn invalid_collisions_are_rejected() {
+    let attacking_props = DEFAULT_BALANCE.replace(
+        "(Minions, Monsters),",
+        "(Minions, Monsters), (Props, Players),",
+    );
+    assert!(Balance::from_bytes(attacking_props.as_bytes()).is_err());
+
+    let hit_projectiles =
+        DEFAULT_BALANCE.replace("(Minions, Monsters),", "(Monsters, Projectiles),");
+    assert!(Balance::from_bytes(hit_projectiles.as_bytes()).is_err());
+}
+
+#[test]
+fn overrides_replace_the_collisions() {
+    let overrides = r#"(
+        collisions: (
+            hits: [
+                (Monsters, Players),
+                (Monsters, Monsters),
+                (Projectiles, Monsters),
+                (Projectiles, Minions),
+            ],
+        ),
+    )"#;
+    let balance = Balance::default()
+        .with_overrides(overrides.as_bytes())
+        .unwrap();
+    assert!(balance
+        .collisions
+        .hits(CollisionLayer::Monsters, CollisionLayer::Monsters));
+    // Missiles fly through obstacles without (Projectiles, Props).
+    assert!(!balance
+        .collisions
+        .hits(CollisionLayer::Projectiles, CollisionLayer::Props));
+    assert_eq!(balance.pause.lagging_frames, 30);
+}
@@ -0,0 +1,97 @@
+use gv_core::{actions::monster_spawn::SpawnType, math::Vector2};
+use gv_game::console::{CommandPermission, CommandRoute, ConsoleCommand, ConsoleCommands};
+use gv_test_harness::{HeadlessClient, LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn spawned_positions(client: &HeadlessClient) -> Vec<Vector2> {
+    client
+        .world_updates()
+        .iter()
+        .flat_map(|world_update| world_update.spawn_actions.iter())
+        .filter_map(|spawn_action| match spawn_action.spawn_type {
+            SpawnType::Single { position, .. } => Some(position),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn commands_are_found_by_the_longest_name() {
+    let mut console_commands = ConsoleCommands::with_game_commands();
+    console_commands.register(ConsoleCommand {
+        name: "spawn",
+        usage: "spawn <anything>",
+        description: "",
+        permission: CommandPermission::Anyone,
+        handler: |_, _| Ok(String::new()),
+    });
+
+    let (command, arguments) = console_commands.find("Spawn  monster mage 5").unwrap();
+    assert_eq!(command.name, "spawn monster");
+    assert_eq!(arguments, vec!["mage", "5"]);
+    let (command, arguments) = console_commands.find("spawn wisp").unwrap();
+    assert_eq!(command.name, "spawn");
+    assert_eq!(arguments, vec!["wisp"]);
+    assert!(console_commands.find("teleport").is_none());
+    assert!(console_commands.find("").is_none());
+}
+
+#[test]
+fn cheat_commands_are_allowed_offline_or_for_the_host() {
+    assert_eq!(
+        CommandPermission::Cheat.route(false, false),
+        Ok(CommandRoute::Local)
+    );
+    // Tests are built with debug assertions, as dev builds are.
+    assert_eq!(
+        CommandPermission::Cheat.route(true, true),
+        Ok(CommandRoute::Server)
+    );
+    assert!(CommandPermission::Cheat.route(true, false).is_err());
+    assert!(CommandPermission::Offline.route(true, true).is_err());
+    assert_eq!(
+        CommandPermission::Anyone.route(true, false),
+        Ok(CommandRoute::Local)
+    );
+}
+
+#[test]
+fn host_commands_are_run_by_the_server() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(guest)
+        .console_command("spawn monster mage 3");
+    harness
+        .client_mut(host)
+        .console_command("spawn monster mage 3");
+    harness
+        .client_mut(host)
+        .console_command("set timescale 0.5");
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).console_output().len() == 2
+    }));
+
+    let console_output = harness.client(host).console_output();
+    assert!(console_output[0].starts_with("Spawning 3"));
+    assert!(console_output[1].starts_with("Error"));
+    assert!(harness.client(guest).console_output().is_empty());
+
+    // Players don't move, so the monsters are spawned in a circle around one of them.
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let player_positions = harness.server().player_positions();
+        let around_players = spawned_positions(harness.client(host))
+            .into_iter()
+            .filter(|position| {
+                player_positions.iter().any(|player_position| {
+                    ((**player_position - *position).norm() - 200.0).abs() < 1.0
+                })
+            })
+            .count();
+        around_players == 3
+    }));
+}
@@ -0,0 +1,170 @@
+use gv_core::{
+    balance::Balance,
+    classes::PlayerClass,
+    content_packs::{ContentPack, ContentPackFile, ContentPacks, BALANCE_FILE},
+    net::server_message::{ClientNetworkError, DisconnectReason},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+use std::{env, fs};
+
+const MAX_FRAMES: u64 = 300;
+const EMPTY_OVERRIDES: &[u8] = b"(classes: {}, monsters: {}, pause: None)";
+
+fn content_pack(name: &str, files: &[(&str, &[u8])]) -> ContentPack {
+    ContentPack {
+        name: name.to_owned(),
+        files: files
+            .iter()
+            .map(|(path, contents)| ContentPackFile {
+                path: (*path).to_owned(),
+                contents: contents.to_vec(),
+            })
+            .collect(),
+    }
+}
+
+fn balance_overrides(lagging_frames: u64) -> Vec<u8> {
+    format!(
+        "(pause: Some((lagging_frames: {}, max_pending_world_updates: 300)))",
+        lagging_frames
+    )
+    .into_bytes()
+}
+
+#[test]
+fn content_packs_are_hashed_and_applied_in_order() {
+    let first = content_pack("a_first", &[(BALANCE_FILE, &balance_overrides(20))]);
+    let second = content_pack("b_second", &[(BALANCE_FILE, &balance_overrides(10))]);
+    assert_ne!(first.hash(), second.hash());
+    assert_eq!(first.hash(), first.clone().hash());
+    assert!(first.is_downloadable());
+    assert!(!content_pack("sprites", &[("hats.png", &[0; 4])]).is_downloadable());
+
+    let balance = ContentPacks::new(vec![second, first])
+        .apply_to_balance(&Balance::default())
+        .unwrap();
+    assert_eq!(balance.pause.lagging_frames, 10);
+    assert_eq!(balance.pause.max_pending_world_updates, 300);
+    assert_eq!(
+        balance.classes[&PlayerClass::Mage].base_speed,
+        Balance::default().classes[&PlayerClass::Mage].base_speed
+    );
+
+    let empty_overrides = content_pack("empty", &[(BALANCE_FILE, EMPTY_OVERRIDES)]);
+    assert!(ContentPacks::new(vec![empty_overrides])
+        .apply_to_balance(&Balance::default())
+        .is_ok());
+    let broken_overrides = content_pack("broken", &[(BALANCE_FILE, b"(")]);
+    assert!(ContentPacks::new(vec![broken_overrides])
+        .apply_to_balance(&Balance::default())
+        .is_err());
+}
+
+#[test]
+fn saved_content_packs_are_loaded_with_the_same_hash() {
+    let mods_dir = env::temp_dir().join("gv_content_packs");
+    let _ = fs::remove_dir_all(&mods_dir);
+    let pack = content_pack(
+        "shorter_pauses",
+        &[
+            (BALANCE_FILE, &balance_overrides(20)),
+            ("scripts/hooks.rhai", b"fn on_hit(hit) { hit.damage }"),
+        ],
+    );
+    pack.save(&mods_dir).unwrap();
+
+    let content_packs = ContentPacks::load(&mods_dir).unwrap();
+    assert_eq!(content_packs.packs().len(), 1);
+    assert_eq!(content_packs.infos(), vec![pack.info()]);
+    assert_eq!(
+        content_packs.hooks_script().map(|(name, _)| name),
+        Some("shorter_pauses")
+    );
+
+    // Downloaded packs can't write outside of their directories.
+    let escaping = content_pack("shorter_pauses", &[("../balance.ron", b"()")]);
+    assert!(escaping.save(&mods_dir).is_err());
+    fs::remove_dir_all(&mods_dir).unwrap();
+}
+
+#[test]
+fn missing_content_packs_are_downloaded() {
+    let pack = content_pack("shorter_pauses", &[(BALANCE_FILE, &balance_overrides(20))]);
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    harness
+        .client_mut(host)
+        .set_content_packs(ContentPacks::new(vec![pack.clone()]));
+    harness
+        .server_mut()
+        .world_mut()
+        .insert(ContentPacks::new(vec![pack.clone()]));
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    assert_eq!(
+        harness.client(guest).advertised_content_packs(),
+        &[pack.info()]
+    );
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(guest)
+        .content_packs()
+        .get("shorter_pauses")
+        .is_some()));
+    assert!(harness.client(guest).disconnect_reason().is_none());
+}
+
+#[test]
+fn mismatched_content_packs_produce_a_join_error() {
+    let server_packs = ContentPacks::new(vec![
+        content_pack("shorter_pauses", &[(BALANCE_FILE, &balance_overrides(20))]),
+        content_pack("desert_sprites", &[("desert_level.png", &[0; 4])]),
+    ]);
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let outdated = harness.add_client("outdated");
+    let without_sprites = harness.add_client("without_sprites");
+    harness
+        .client_mut(host)
+        .set_content_packs(server_packs.clone());
+    harness
+        .client_mut(outdated)
+        .set_content_packs(ContentPacks::new(vec![
+            content_pack("shorter_pauses", &[(BALANCE_FILE, &balance_overrides(25))]),
+            content_pack("desert_sprites", &[("desert_level.png", &[0; 4])]),
+        ]));
+    harness.server_mut().world_mut().insert(server_packs);
+
+    for client in &[host, outdated, without_sprites] {
+        harness.client_mut(*client).join();
+    }
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(outdated).disconnect_reason().is_some()
+            && harness
+                .client(without_sprites)
+                .disconnect_reason()
+                .is_some()
+    }));
+
+    assert!(harness.client(host).disconnect_reason().is_none());
+    match harness.client(outdated).disconnect_reason() {
+        Some(DisconnectReason::ClientNetworkError(ClientNetworkError::ContentPackMismatch(
+            name,
+        ))) => assert_eq!(name, "shorter_pauses"),
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+    match harness.client(without_sprites).disconnect_reason() {
+        Some(DisconnectReason::ClientNetworkError(ClientNetworkError::MissingContentPack(
+            name,
+        ))) => assert_eq!(name, "desert_sprites"),
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| {
+                multiplayer_game_state.players.len() == 1
+            })
+    }));
+}
@@ -0,0 +1,47 @@
+use gv_core::{
+    cosmetics::{Hat, PlayerCosmetics, COSMETIC_COLORS},
+    PLAYER_COLORS,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn cosmetics_are_replicated_to_room_players() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    let guest_cosmetics = PlayerCosmetics {
+        color: Some(5),
+        hat: Some(Hat::WizardHat),
+    };
+    harness
+        .client_mut(guest)
+        .set_cosmetics(guest_cosmetics.clone());
+    assert!(harness.join_all(MAX_FRAMES));
+
+    for client in harness.clients() {
+        let players = client.room_players();
+        assert_eq!(players[0].cosmetics, PlayerCosmetics::default());
+        assert_eq!(players[0].color, PLAYER_COLORS[0]);
+        assert_eq!(players[1].cosmetics, guest_cosmetics);
+        assert_eq!(players[1].color, COSMETIC_COLORS[5].1);
+    }
+}
+
+#[test]
+fn slot_colors_are_reassigned_when_a_player_leaves() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let first_guest = harness.add_client("first guest");
+    harness.add_client("second guest");
+    assert!(harness.join_all(MAX_FRAMES));
+
+    harness.client_mut(first_guest).disconnect();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).room_players().len() == 2
+    }));
+    let players = harness.client(host).room_players();
+    assert_eq!(players[1].nickname, "second guest");
+    assert_eq!(players[1].color, PLAYER_COLORS[1]);
+}
@@ -0,0 +1,89 @@
+use amethyst::ecs::{Builder, World, WorldExt};
+
+use gv_core::{
+    balance::Balance,
+    ecs::{
+        components::missile::{Missile, MissileTarget},
+        resources::world::SAVED_WORLD_STATES_LIMIT,
+    },
+    math::{deterministic, Vector2},
+};
+use gv_game::ecs::resources::CountedCrits;
+
+const ROLLS: u64 = 10_000;
+
+#[test]
+fn rolls_are_spread_evenly() {
+    let mean = (0..ROLLS)
+        .map(|frame_number| deterministic::roll(frame_number, 7, 0))
+        .inspect(|roll| assert!(*roll >= 0.0 && *roll < 1.0))
+        .sum::<f32>()
+        / ROLLS as f32;
+    assert!((mean - 0.5).abs() < 0.02, "mean roll: {}", mean);
+
+    // Every key changes the roll.
+    let roll = deterministic::roll(10, 20, 30);
+    assert_eq!(roll, deterministic::roll(10, 20, 30));
+    assert_ne!(roll, deterministic::roll(11, 20, 30));
+    assert_ne!(roll, deterministic::roll(10, 21, 30));
+    assert_ne!(roll, deterministic::roll(10, 20, 31));
+}
+
+#[test]
+fn missile_hits_vary_within_the_spell_limits() {
+    let mut world = World::new();
+    let caster = world.create_entity().build();
+    let mut spell_definition = Balance::default().spells["Dart"].clone();
+    spell_definition.crit_chance = 0.5;
+    let missile = Missile::new(
+        1,
+        0,
+        caster,
+        &spell_definition,
+        MissileTarget::Destination(Vector2::new(100.0, 0.0)),
+        Vector2::new(1.0, 0.0),
+        0,
+    );
+
+    let min_damage = spell_definition.damage * (1.0 - spell_definition.damage_variance);
+    let max_damage = spell_definition.damage
+        * (1.0 + spell_definition.damage_variance)
+        * spell_definition.crit_multiplier;
+    let mut crits = 0;
+    for frame_number in 0..ROLLS {
+        let damage_roll = missile.roll_damage(frame_number);
+        assert_eq!(damage_roll, missile.roll_damage(frame_number));
+        assert!(damage_roll.damage >= min_damage && damage_roll.damage <= max_damage);
+        if damage_roll.is_crit {
+            crits += 1;
+        }
+    }
+    let crit_rate = crits as f32 / ROLLS as f32;
+    assert!((crit_rate - 0.5).abs() < 0.02, "crit rate: {}", crit_rate);
+
+    // Missiles of a multishot cast roll separately.
+    let second_shot = Missile::new(
+        1,
+        1,
+        caster,
+        &spell_definition,
+        MissileTarget::Destination(Vector2::new(100.0, 0.0)),
+        Vector2::new(1.0, 0.0),
+        0,
+    );
+    assert!((0..10).any(|frame_number| {
+        second_shot.roll_damage(frame_number) != missile.roll_damage(frame_number)
+    }));
+}
+
+#[test]
+fn crits_are_counted_once_within_the_rewind_window() {
+    let mut counted_crits = CountedCrits::default();
+    assert!(counted_crits.count((1, 0, 0), 10));
+    assert!(!counted_crits.count((1, 0, 0), 10));
+    assert!(counted_crits.count((1, 0, 1), 10));
+
+    let frame_number = 10 + SAVED_WORLD_STATES_LIMIT as u64 + 1;
+    assert!(counted_crits.count((2, 0, 0), frame_number));
+    assert_eq!(counted_crits.0.len(), 1);
+}
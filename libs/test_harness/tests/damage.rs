@@ -0,0 +1,102 @@
+use gv_core::{
+    balance::Balance,
+    classes::PlayerClass,
+    damage::{health_lost, DamageType, Resistances, MAX_RESISTANCE},
+};
+
+const DEFAULT_BALANCE: &str = include_str!("../../../resources/balance.ron");
+
+#[test]
+fn resistances_reduce_damage_of_their_type() {
+    let resistances = Resistances {
+        physical: 0.5,
+        magic: -0.5,
+        elemental: 0.0,
+    };
+    assert_eq!(
+        health_lost(40.0, Some(DamageType::Physical), &resistances, 1.0),
+        20.0
+    );
+    assert_eq!(
+        health_lost(40.0, Some(DamageType::Magic), &resistances, 1.0),
+        60.0
+    );
+    assert_eq!(
+        health_lost(40.0, Some(DamageType::Elemental), &resistances, 1.0),
+        40.0
+    );
+    assert_eq!(
+        resistances.resisted().collect::<Vec<_>>(),
+        vec![DamageType::Physical]
+    );
+}
+
+#[test]
+fn resistances_are_applied_before_damage_taken() {
+    let resistances = Resistances {
+        physical: 0.5,
+        ..Resistances::default()
+    };
+    assert_eq!(
+        health_lost(40.0, Some(DamageType::Physical), &resistances, 0.5),
+        10.0
+    );
+    // Untyped damage still goes through `Stat::DamageTaken`.
+    assert_eq!(health_lost(40.0, None, &resistances, 0.5), 20.0);
+}
+
+#[test]
+fn heals_and_untyped_damage_are_not_resisted() {
+    let resistances = Resistances {
+        physical: 0.5,
+        magic: 0.5,
+        elemental: 0.5,
+    };
+    assert_eq!(health_lost(-30.0, None, &resistances, 0.5), -30.0);
+    assert_eq!(
+        health_lost(-30.0, Some(DamageType::Magic), &resistances, 0.5),
+        -30.0
+    );
+    assert_eq!(health_lost(30.0, None, &resistances, 1.0), 30.0);
+}
+
+#[test]
+fn resistances_are_capped() {
+    let resistances = Resistances {
+        physical: 1.0,
+        magic: -3.0,
+        elemental: 0.0,
+    };
+    assert_eq!(
+        health_lost(100.0, Some(DamageType::Physical), &resistances, 1.0),
+        100.0 * (1.0 - MAX_RESISTANCE)
+    );
+    assert_eq!(
+        health_lost(100.0, Some(DamageType::Magic), &resistances, 1.0),
+        200.0
+    );
+}
+
+#[test]
+fn damage_types_default_when_omitted() {
+    let balance = Balance::default();
+    assert_eq!(
+        balance.spells["MagicMissile"].damage_type,
+        DamageType::Magic
+    );
+    assert!(balance.monsters["Ghoul"].resistances.physical > 0.0);
+
+    let untyped = DEFAULT_BALANCE
+        .replace("            damage_type: Magic,\n", "")
+        .replace("            resistances: (magic: 0.2),\n", "");
+    assert_ne!(untyped, DEFAULT_BALANCE);
+    let balance = Balance::from_bytes(untyped.as_bytes()).unwrap();
+    assert_eq!(
+        balance.spells["MagicMissile"].damage_type,
+        DamageType::Physical
+    );
+    assert_eq!(
+        balance.classes[&PlayerClass::Mage].resistances,
+        Resistances::default()
+    );
+}
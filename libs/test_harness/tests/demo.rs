@@ -0,0 +1,37 @@
+use gv_core::{
+    demo::{DemoSkirmish, DEMO_BOTS, DEMO_MONSTERS},
+    math::Vector2,
+};
+
+const FRAME_SECS: f32 = 1.0 / 60.0;
+
+#[test]
+fn skirmish_is_deterministic_for_a_seed() {
+    let half_size = Vector2::new(640.0, 360.0);
+    let mut a = DemoSkirmish::new(42, half_size);
+    let mut b = DemoSkirmish::new(42, half_size);
+    for _ in 0..600 {
+        a.step(FRAME_SECS);
+        b.step(FRAME_SECS);
+    }
+    assert_eq!(a.bots(), b.bots());
+    assert_eq!(a.monsters(), b.monsters());
+    assert_eq!(a.missiles(), b.missiles());
+    assert_eq!(a.kills(), b.kills());
+}
+
+#[test]
+fn bots_keep_killing_monsters_inside_the_screen() {
+    let half_size = Vector2::new(640.0, 360.0);
+    let mut skirmish = DemoSkirmish::new(7, half_size);
+    for _ in 0..60 * 60 {
+        skirmish.step(FRAME_SECS);
+        assert_eq!(skirmish.bots().len(), DEMO_BOTS);
+        assert_eq!(skirmish.monsters().len(), DEMO_MONSTERS);
+        for actor in skirmish.bots().iter().chain(skirmish.monsters()) {
+            assert!(actor.position.x.abs() <= half_size.x + std::f32::EPSILON);
+            assert!(actor.position.y.abs() <= half_size.y + std::f32::EPSILON);
+        }
+    }
+    assert!(skirmish.kills() > 0);
+}
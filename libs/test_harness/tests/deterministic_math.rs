@@ -0,0 +1,101 @@
+use gv_core::math::{deterministic, Vector2};
+
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn trigonometry_is_close_to_std() {
+    for i in -2000..=2000 {
+        let angle = i as f32 / 100.0;
+        let (sin, cos) = deterministic::sin_cos(angle);
+        assert!((sin - angle.sin()).abs() < EPSILON, "sin({})", angle);
+        assert!((cos - angle.cos()).abs() < EPSILON, "cos({})", angle);
+
+        let (y, x) = (angle, (i % 7 - 3) as f32);
+        assert!(
+            (deterministic::atan2(y, x) - y.atan2(x)).abs() < EPSILON,
+            "atan2({}, {})",
+            y,
+            x
+        );
+    }
+    assert_eq!(deterministic::atan2(0.0, 0.0), 0.0);
+}
+
+#[test]
+fn results_are_bit_exact() {
+    // These bits have to stay the same on every platform, changing them breaks
+    // cross-platform multiplayer and replays.
+    let bits = |(sin, cos): (f32, f32)| (sin.to_bits(), cos.to_bits());
+    assert_eq!(
+        bits(deterministic::sin_cos(1.0)),
+        (0x3f57_6aa4, 0x3f0a_5140)
+    );
+    assert_eq!(
+        bits(deterministic::sin_cos(-3.0)),
+        (0xbe10_81c3, 0xbf7d_7026)
+    );
+    assert_eq!(
+        bits(deterministic::sin_cos(100.0)),
+        (0xbf01_a12e, 0x3f5c_c0ee)
+    );
+    assert_eq!(deterministic::atan2(1.0, -2.0).to_bits(), 0x402b_6374);
+    assert_eq!(deterministic::atan2(-3.0, 0.5).to_bits(), 0xbfb3_ec43);
+
+    let rotated = deterministic::rotate(&Vector2::new(300.0, 0.0), 0.25);
+    assert_eq!(bits((rotated.x, rotated.y)), (0x4391_563d, 0x4294_7140));
+
+    assert_eq!(
+        deterministic::roll(1, 2, 3),
+        13_660_999.0 / (1u64 << 24) as f32
+    );
+}
+
+#[test]
+fn vectors_rotate_by_the_angle_between_them() {
+    let from = Vector2::new(3.0, 1.0);
+    let to = Vector2::new(-2.0, 4.0);
+    let angle = deterministic::angle_between(&from, &to);
+    let rotated = deterministic::normalize(&deterministic::rotate(&from, angle));
+    assert!((rotated - deterministic::normalize(&to)).norm() < EPSILON);
+
+    assert_eq!(
+        deterministic::normalize(&Vector2::new(0.0, 0.0)),
+        Vector2::new(0.0, 0.0)
+    );
+}
+
+#[test]
+fn swept_circles_catch_fast_objects() {
+    let point = Vector2::new(0.0, 0.0);
+    // Flies 100 units past the point during a frame, while both ends are far from it.
+    let from = Vector2::new(-50.0, 1.0);
+    let movement = Vector2::new(100.0, 0.0);
+    let share = deterministic::sweep_circle(from, movement, 5.0, point).unwrap();
+    let contact = from + movement * share;
+    assert!((contact.norm() - 5.0).abs() < 1e-3);
+    assert!(contact.x < 0.0);
+
+    // Moving away, stopping short and standing still don't touch it.
+    assert_eq!(
+        deterministic::sweep_circle(from, -movement, 5.0, point),
+        None
+    );
+    assert_eq!(
+        deterministic::sweep_circle(from, movement * 0.4, 5.0, point),
+        None
+    );
+    assert_eq!(
+        deterministic::sweep_circle(from, Vector2::new(0.0, 0.0), 5.0, point),
+        None
+    );
+    // Passes by.
+    assert_eq!(
+        deterministic::sweep_circle(Vector2::new(-50.0, 6.0), movement, 5.0, point),
+        None
+    );
+    // Overlapping objects are hit right away.
+    assert_eq!(
+        deterministic::sweep_circle(Vector2::new(3.0, 0.0), movement, 5.0, point),
+        Some(0.0)
+    );
+}
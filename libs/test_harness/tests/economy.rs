@@ -0,0 +1,91 @@
+use amethyst::ecs::WorldExt;
+
+use gv_core::{
+    economy::{PlayerWallet, ShopItem, ITEM_DURATION_FRAMES},
+    ecs::resources::{net::EntityNetMetadataStorage, GameLevelState, Intermission},
+    stats::{Stat, StatModifiers},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn damage_taken(wallet: &PlayerWallet, frame_number: u64) -> f32 {
+    let mut stat_modifiers = StatModifiers::default();
+    wallet.add_stat_modifiers(frame_number, &mut stat_modifiers);
+    stat_modifiers.value(Stat::DamageTaken, 1.0)
+}
+
+#[test]
+fn bought_items_expire() {
+    let mut wallet = PlayerWallet::default();
+    assert!(!wallet.buy(ShopItem::Ward, 0));
+
+    wallet.coins = ShopItem::Ward.price() * 2;
+    assert!(wallet.buy(ShopItem::Ward, 0));
+    assert!(damage_taken(&wallet, 0) < 1.0);
+    assert_eq!(damage_taken(&wallet, ITEM_DURATION_FRAMES), 1.0);
+
+    // Buying the same item again extends it instead of adding a copy.
+    assert!(wallet.buy(ShopItem::Ward, 100));
+    assert_eq!(
+        wallet.items,
+        vec![(ShopItem::Ward, 100 + ITEM_DURATION_FRAMES)]
+    );
+    assert_eq!(wallet.coins, 0);
+}
+
+#[test]
+fn purchases_are_validated_by_the_server() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_net_id = harness.client(guest).player_net_id().unwrap();
+
+    {
+        let world = harness.server_mut().world_mut();
+        let guest_entity = world
+            .fetch::<EntityNetMetadataStorage>()
+            .get_entity(guest_net_id)
+            .unwrap();
+        world
+            .write_storage::<PlayerWallet>()
+            .get_mut(guest_entity)
+            .unwrap()
+            .coins = ShopItem::SwiftBoots.price();
+    }
+
+    // The shop is open only during intermissions.
+    harness.client_mut(guest).buy_item(ShopItem::SwiftBoots);
+    harness.run_frames(60);
+    assert!(harness
+        .client(guest)
+        .wallets()
+        .iter()
+        .all(|(_, wallet)| wallet.items.is_empty()));
+
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<GameLevelState>()
+        .intermission = Some(Intermission {
+        wave: 1,
+        started_at_frame: 0,
+        ends_at_frame: u64::max_value(),
+        ready_players: Vec::new(),
+    });
+    harness.client_mut(guest).buy_item(ShopItem::SwiftBoots);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            client.wallets().iter().any(|(entity_net_id, wallet)| {
+                *entity_net_id == guest_net_id && wallet.coins == 0 && wallet.items.len() == 1
+            })
+        })
+    }));
+
+    // Not enough coins for another one.
+    let wallet_updates = harness.client(guest).wallets().len();
+    harness.client_mut(guest).buy_item(ShopItem::Whetstone);
+    harness.run_frames(60);
+    assert_eq!(harness.client(guest).wallets().len(), wallet_updates);
+}
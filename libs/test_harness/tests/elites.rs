@@ -0,0 +1,34 @@
+use gv_core::{
+    ecs::components::affixes::{MonsterAffix, MonsterAffixes},
+    stats::{Stat, StatModifiers},
+};
+
+fn stat(monster_affixes: &MonsterAffixes, stat: Stat) -> f32 {
+    let mut stat_modifiers = StatModifiers::default();
+    monster_affixes.add_stat_modifiers(&mut stat_modifiers);
+    stat_modifiers.value(stat, 1.0)
+}
+
+#[test]
+fn affixes_modify_monster_stats() {
+    let regular = MonsterAffixes::default();
+    assert_eq!(stat(&regular, Stat::MoveSpeed), 1.0);
+    assert_eq!(stat(&regular, Stat::DamageTaken), 1.0);
+    assert_eq!(regular.regeneration_rate(), 0.0);
+
+    let elite = MonsterAffixes::new(vec![MonsterAffix::Fast, MonsterAffix::Armored]);
+    assert!(stat(&elite, Stat::MoveSpeed) > 1.0);
+    assert_eq!(stat(&elite, Stat::DamageTaken), 0.5);
+    assert_eq!(elite.regeneration_rate(), 0.0);
+}
+
+#[test]
+fn split_monsters_keep_the_rest_of_affixes() {
+    let elite = MonsterAffixes::new(vec![MonsterAffix::Splitting, MonsterAffix::Regenerating]);
+    assert_eq!(elite.split_affixes(), vec![MonsterAffix::Regenerating]);
+
+    // Monsters spawned from a split don't split any further.
+    let split = MonsterAffixes::new(elite.split_affixes());
+    assert!(!split.has(MonsterAffix::Splitting));
+    assert!(split.regeneration_rate() > 0.0);
+}
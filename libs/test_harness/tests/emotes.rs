@@ -0,0 +1,47 @@
+use gv_core::emotes::{Emote, EMOTE_COOLDOWN_FRAMES};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn emotes_are_broadcast_to_the_room() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_all(MAX_FRAMES));
+    let guest_connection_id = harness.client(guest).connection_id().unwrap();
+
+    harness.client_mut(guest).emote(Emote::Wave);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.emotes() == [(guest_connection_id, Emote::Wave)])
+    }));
+}
+
+#[test]
+fn emotes_are_rate_limited() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_all(MAX_FRAMES));
+    let host_connection_id = harness.client(host).connection_id().unwrap();
+
+    harness.client_mut(host).emote(Emote::Wave);
+    harness.client_mut(host).emote(Emote::Laugh);
+    harness.run_frames(EMOTE_COOLDOWN_FRAMES / 2);
+    assert_eq!(
+        harness.client(host).emotes(),
+        [(host_connection_id, Emote::Wave)]
+    );
+
+    harness.run_frames(EMOTE_COOLDOWN_FRAMES);
+    harness.client_mut(host).emote(Emote::Heart);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).emotes()
+            == [
+                (host_connection_id, Emote::Wave),
+                (host_connection_id, Emote::Heart),
+            ]
+    }));
+}
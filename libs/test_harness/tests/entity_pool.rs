@@ -0,0 +1,100 @@
+use amethyst::ecs::{Entities, Join, World, WorldExt};
+
+use gv_core::{
+    actions::player::CastSpell,
+    ecs::{
+        components::missile::Missile,
+        resources::{pool::EntityPool, world::SAVED_WORLD_STATES_LIMIT},
+    },
+    math::Vector2,
+};
+use gv_game::ecs::resources::MissilePool;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+const REUSE_AFTER_FRAMES: u64 = 10;
+
+#[test]
+fn released_entities_are_reused_after_a_delay() {
+    let world = World::new();
+    let entities = world.system_data::<Entities>();
+    let mut pool = EntityPool::<Missile>::new(REUSE_AFTER_FRAMES);
+
+    let missile = pool.acquire(&entities, 0);
+    pool.release(missile, 5);
+    assert_ne!(pool.acquire(&entities, 5 + REUSE_AFTER_FRAMES - 1), missile);
+    // Rollbacks can ask for an entity on a frame before the release.
+    assert_ne!(pool.acquire(&entities, 0), missile);
+    assert_eq!(pool.acquire(&entities, 5 + REUSE_AFTER_FRAMES), missile);
+    assert_eq!(pool.created_count(), 3);
+    assert_eq!(pool.reused_count(), 1);
+}
+
+#[test]
+fn handles_of_released_entities_dont_resolve() {
+    let world = World::new();
+    let entities = world.system_data::<Entities>();
+    let mut pool = EntityPool::<Missile>::new(0);
+
+    let missile = pool.acquire(&entities, 0);
+    let handle = pool.handle(missile);
+    assert_eq!(pool.get(handle), Some(missile));
+
+    pool.release(missile, 0);
+    assert_eq!(pool.get(handle), None);
+    let reused_missile = pool.acquire(&entities, 0);
+    assert_eq!(reused_missile, missile);
+    assert_eq!(pool.get(handle), None);
+    assert_eq!(pool.get(pool.handle(reused_missile)), Some(missile));
+}
+
+#[test]
+fn cleared_pools_delete_released_entities() {
+    let mut world = World::new();
+    let mut pool = EntityPool::<Missile>::new(0);
+    let missile = {
+        let entities = world.system_data::<Entities>();
+        let missile = pool.acquire(&entities, 0);
+        pool.release(missile, 0);
+        pool.clear(&entities);
+        missile
+    };
+    world.maintain();
+
+    assert!(!world.is_alive(missile));
+    assert_eq!(pool.released_count(), 0);
+    assert_ne!(pool.acquire(&world.system_data::<Entities>(), 0), missile);
+}
+
+fn missiles_count(harness: &TestHarness) -> usize {
+    (&harness.server().world().read_storage::<Missile>())
+        .join()
+        .count()
+}
+
+#[test]
+fn server_reuses_dead_missiles() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Missile, Vector2::new(100.0, 0.0));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let missile_pool = harness.server().world().read_resource::<MissilePool>();
+        missile_pool.released_count() == 1
+    }));
+    assert_eq!(missiles_count(&harness), 0);
+
+    // Saved world states might still refer to the missile until they're gone.
+    harness.run_frames(SAVED_WORLD_STATES_LIMIT as u64);
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Missile, Vector2::new(100.0, 0.0));
+    assert!(harness.run_until(MAX_FRAMES, |harness| missiles_count(harness) == 1));
+
+    let missile_pool = harness.server().world().read_resource::<MissilePool>();
+    assert_eq!(missile_pool.created_count(), 1);
+    assert_eq!(missile_pool.reused_count(), 1);
+}
@@ -0,0 +1,162 @@
+use amethyst::ecs::WorldExt;
+
+use gv_core::{
+    level_scripts::{LevelScript, LevelScriptRunner, ScriptAction, ScriptTick},
+    math::Vector2,
+};
+use gv_game::ecs::resources::ActiveLevelScript;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+const SCRIPT: &str = r#"(
+  payload: Some((
+    path: [(0.0, 0.0), (100.0, 0.0)],
+    speed: 10.0,
+    escort_radius: 20.0,
+    checkpoints: [0.5],
+  )),
+  triggers: [],
+)"#;
+
+fn runner() -> LevelScriptRunner {
+    LevelScriptRunner::new(LevelScript::from_bytes(SCRIPT.as_bytes()).unwrap())
+}
+
+fn tick(
+    runner: &mut LevelScriptRunner,
+    player_positions: &[Vector2],
+    frame_number: u64,
+) -> Vec<ScriptAction> {
+    runner.update(ScriptTick {
+        delta_seconds: 1.0,
+        player_positions,
+        interact_positions: &[],
+        kills: 0,
+        frame_number,
+    })
+}
+
+/// Keeps a player right next to the payload until it reports an action.
+fn escort_until_action(runner: &mut LevelScriptRunner) -> Vec<ScriptAction> {
+    for frame_number in 0..100 {
+        let payload_position = runner.payload().unwrap().position();
+        let actions = tick(runner, &[payload_position], frame_number);
+        if !actions.is_empty() {
+            return actions;
+        }
+    }
+    panic!("Expected the payload to report an action");
+}
+
+#[test]
+fn payload_moves_only_while_escorted() {
+    let mut runner = runner();
+    assert!(tick(&mut runner, &[Vector2::new(0.0, 50.0)], 1).is_empty());
+    let progress = runner.payload().unwrap().progress();
+    assert_eq!(progress.distance, 0.0);
+    assert!(!progress.is_escorted);
+
+    assert!(tick(&mut runner, &[Vector2::new(0.0, 10.0)], 2).is_empty());
+    let progress = runner.payload().unwrap().progress();
+    assert!((progress.distance - 10.0).abs() < 0.01);
+    assert!(progress.is_escorted);
+    assert_eq!(progress.frame_number, 2);
+}
+
+#[test]
+fn payload_rolls_back_to_the_last_checkpoint() {
+    let mut runner = runner();
+    assert_eq!(
+        escort_until_action(&mut runner),
+        vec![ScriptAction::ShowMessage("Checkpoint reached".to_owned())]
+    );
+    let checkpoint_distance = runner.payload().unwrap().progress().checkpoint_distance;
+    assert!((checkpoint_distance - 50.0).abs() < 0.5);
+
+    let payload_position = runner.payload().unwrap().position();
+    tick(&mut runner, &[payload_position], 100);
+    assert!(runner.payload().unwrap().progress().distance > checkpoint_distance);
+
+    // Every player is dead.
+    assert_eq!(
+        tick(&mut runner, &[], 101),
+        vec![ScriptAction::ShowMessage(
+            "The payload is back at the checkpoint".to_owned()
+        )]
+    );
+    assert_eq!(
+        runner.payload().unwrap().progress().distance,
+        checkpoint_distance
+    );
+    assert!(tick(&mut runner, &[], 102).is_empty());
+}
+
+#[test]
+fn payload_arrival_ends_the_level() {
+    let mut runner = runner();
+    escort_until_action(&mut runner);
+    assert!(!runner.is_ended());
+    assert_eq!(
+        escort_until_action(&mut runner),
+        vec![
+            ScriptAction::ShowMessage("The payload has arrived".to_owned()),
+            ScriptAction::EndLevel,
+        ]
+    );
+    assert!(runner.is_ended());
+    assert!(runner.payload().unwrap().is_arrived());
+    assert_eq!(runner.payload().unwrap().fraction(), 1.0);
+}
+
+#[test]
+fn invalid_payloads_are_rejected() {
+    let script = r#"(
+      payload: Some((path: [(0.0, 0.0)], speed: 10.0, escort_radius: 20.0)),
+      triggers: [],
+    )"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+    let script = r#"(
+      payload: Some((
+        path: [(0.0, 0.0), (100.0, 0.0)],
+        speed: 10.0,
+        escort_radius: 20.0,
+        checkpoints: [0.6, 0.3],
+      )),
+      triggers: [],
+    )"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+}
+
+#[test]
+fn payload_progress_is_replicated() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    // The escort radius covers the whole level, so that it doesn't matter where the player spawns.
+    let script = r#"(
+      payload: Some((
+        path: [(-500.0, 0.0), (500.0, 0.0)],
+        speed: 10.0,
+        escort_radius: 10000.0,
+      )),
+      triggers: [],
+    )"#;
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<ActiveLevelScript>()
+        .runner = Some(LevelScriptRunner::new(
+        LevelScript::from_bytes(script.as_bytes()).unwrap(),
+    ));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .client(host)
+            .payload_progress()
+            .map_or(false, |progress| progress.distance > 0.0)
+    }));
+    let progress = harness.client(host).payload_progress().unwrap();
+    assert!(progress.is_escorted);
+    assert_eq!(progress.checkpoint_distance, 0.0);
+}
@@ -0,0 +1,131 @@
+use amethyst::network::{simulation::NetworkSimulationEvent, Bytes};
+use gv_core::{
+    ecs::resources::GameEngineState, math::Vector2, net::server_message::DisconnectReason,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn clients_join_the_room() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+
+    harness.client_mut(host).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness.client(host).is_host()));
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_players().len() == 2)
+    }));
+
+    assert!(!harness.client(guest).is_host());
+    assert_eq!(harness.server().connections_count(), 2);
+}
+
+#[test]
+fn host_starts_the_game() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    harness.add_client("guest");
+
+    assert!(harness.join_and_start(MAX_FRAMES));
+    assert_eq!(harness.server().engine_state(), GameEngineState::Playing);
+    assert_eq!(harness.server().player_positions().len(), 2);
+
+    harness.run_frames(120);
+    assert!(harness.is_converged());
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.last_world_update_id().is_some()));
+}
+
+#[test]
+fn late_walk_actions_are_rolled_back() {
+    let mut harness = TestHarness::new(LoopbackConfig {
+        latency_frames: 3,
+        ..LoopbackConfig::default()
+    })
+    .unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(60);
+
+    let initial_position = harness.server().player_positions()[0].clone();
+    let client_action_id = harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(1.0, 0.0)), 15);
+    harness.run_frames(60);
+
+    assert!(!harness
+        .client(host)
+        .discarded_walk_actions()
+        .contains(&client_action_id));
+    assert!(harness.server().player_positions()[0].x > initial_position.x);
+    assert!(harness.is_converged());
+}
+
+#[test]
+fn guest_disconnect_drops_the_player() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.drop_client(guest);
+    harness.run_frames(10);
+
+    assert_eq!(
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.players.len()),
+        1
+    );
+}
+
+#[test]
+fn malformed_message_drops_the_player() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    let guest_addr = harness.client(guest).addr();
+    harness
+        .server_mut()
+        .push_event(NetworkSimulationEvent::Message(
+            guest_addr,
+            Bytes::from(vec![0xff; 12]),
+        ));
+    harness.run_frames(10);
+
+    assert_eq!(
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.players.len()),
+        1
+    );
+    match harness.client(guest).disconnect_reason() {
+        Some(DisconnectReason::MalformedMessage) => {}
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+}
+
+#[test]
+fn host_disconnect_shuts_the_server_down() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.client_mut(host).disconnect();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness.server().is_shut_down()));
+    match harness.client(guest).disconnect_reason() {
+        Some(DisconnectReason::Closed) => {}
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+}
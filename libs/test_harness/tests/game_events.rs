@@ -0,0 +1,74 @@
+use gv_core::{
+    augments::KILLS_PER_LEVEL,
+    ecs::resources::{GameLevelState, Intermission, MatchScore},
+    game_events::GameEvent,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn wave_transitions_are_sent_to_every_player() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let ends_at_frame = harness.server().game_frame_number() + 30;
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<GameLevelState>()
+        .intermission = Some(Intermission {
+        wave: 1,
+        started_at_frame: 0,
+        ends_at_frame,
+        ready_players: Vec::new(),
+    });
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.game_events().contains(&GameEvent::WaveStarted(2)))
+    }));
+    for client in harness.clients() {
+        let waves = client
+            .game_events()
+            .iter()
+            .filter(|event| matches!(event, GameEvent::WaveCleared(_) | GameEvent::WaveStarted(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            waves,
+            [&GameEvent::WaveCleared(1), &GameEvent::WaveStarted(2)]
+        );
+    }
+}
+
+#[test]
+fn level_up_is_sent_once() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    // Letting the server see the first level.
+    harness.run_frames(10);
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<MatchScore>()
+        .kills = KILLS_PER_LEVEL;
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .client(host)
+            .game_events()
+            .contains(&GameEvent::LevelUp(2))
+    }));
+    harness.run_frames(30);
+    let level_ups = harness
+        .client(host)
+        .game_events()
+        .iter()
+        .filter(|event| matches!(event, GameEvent::LevelUp(_)))
+        .count();
+    assert_eq!(level_ups, 1);
+}
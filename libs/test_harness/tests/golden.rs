@@ -0,0 +1,214 @@
+use std::{env, fs, path::Path};
+
+use gv_core::{
+    actions::player::CastSpell,
+    math::Vector2,
+    net::{
+        server_message::{ServerMessage, ServerMessagePayload},
+        session_recording::{read_desync_report, read_session_record, RecordedMessage},
+    },
+};
+use gv_test_harness::{
+    assert_matches_golden, dump_desync_report, LoopbackConfig, SessionReplay, TestHarness,
+    UPDATE_GOLDEN_ENV,
+};
+
+const MAX_FRAMES: u64 = 300;
+const SCRIPTED_SESSION: &str = "two_players_walking.gvrec";
+
+/// Sessions captured with `gv_client --record-session <FILE>` are put into `tests/sessions`
+/// along with their `.golden` files (created by running tests with `GV_UPDATE_GOLDEN=1`).
+/// Running with `GV_UPDATE_GOLDEN=1` also records `SCRIPTED_SESSION` if it's missing.
+/// Until it's committed, `SCRIPTED_SESSION` is recorded into a temporary directory instead,
+/// with its golden file written from the first replay.
+#[test]
+fn recorded_sessions_match_golden_files() {
+    let sessions_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sessions");
+    let scripted_session_path = sessions_dir.join(SCRIPTED_SESSION);
+    if env::var_os(UPDATE_GOLDEN_ENV).is_some() && !scripted_session_path.exists() {
+        record_scripted_session(&scripted_session_path);
+    }
+
+    for entry in fs::read_dir(&sessions_dir).expect("Expected a tests/sessions directory") {
+        let path = entry.expect("Expected a directory entry").path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "gvrec")
+        {
+            assert_matches_golden(&path);
+        }
+    }
+
+    if !scripted_session_path.exists() {
+        let recording_path = env::temp_dir().join(SCRIPTED_SESSION);
+        let golden_path = recording_path.with_extension("golden");
+        record_scripted_session(&recording_path);
+        let entries = read_session_record(&recording_path).unwrap();
+        fs::write(&golden_path, SessionReplay::replay(&entries).dump()).unwrap();
+        assert_matches_golden(&recording_path);
+
+        fs::remove_file(recording_path).ok();
+        fs::remove_file(golden_path).ok();
+    }
+}
+
+/// Two players, the host walking around and casting, recorded from the host's side.
+fn record_scripted_session(recording_path: &Path) {
+    let mut harness = TestHarness::new(LoopbackConfig {
+        latency_frames: 2,
+        ..LoopbackConfig::default()
+    })
+    .unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    harness
+        .client_mut(host)
+        .record_session(recording_path)
+        .unwrap();
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.run_frames(30);
+    harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(0.0, 1.0)), 0);
+    harness.run_frames(30);
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Missile, Vector2::new(0.0, 500.0));
+    harness.client_mut(host).walk(None, 0);
+    harness.run_frames(60);
+    harness.client_mut(host).stop_recording();
+}
+
+#[test]
+fn harness_session_replay_is_deterministic() {
+    let recording_path = env::temp_dir().join("gv_harness_session.gvrec");
+
+    let mut harness = TestHarness::new(LoopbackConfig {
+        latency_frames: 2,
+        ..LoopbackConfig::default()
+    })
+    .unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    harness
+        .client_mut(host)
+        .record_session(&recording_path)
+        .unwrap();
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.run_frames(30);
+    harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(0.0, 1.0)), 0);
+    harness.run_frames(30);
+    harness.client_mut(host).walk(None, 25);
+    harness.run_frames(60);
+    harness.client_mut(host).stop_recording();
+
+    let entries = read_session_record(&recording_path).unwrap();
+    let replay = SessionReplay::replay(&entries);
+    assert_eq!(
+        Some(replay.last_acknowledged_update().id),
+        harness.client(host).last_world_update_id()
+    );
+    assert_eq!(replay.dump(), SessionReplay::replay(&entries).dump());
+
+    fs::remove_file(recording_path).ok();
+}
+
+#[test]
+fn highlights_keep_the_session_setup_and_the_last_frames() {
+    const WINDOW_FRAMES: u64 = 30;
+    let highlight_path = env::temp_dir().join("gv_harness_highlight.gvrec");
+
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.client_mut(host).record_highlights(WINDOW_FRAMES);
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(120);
+    harness
+        .client(host)
+        .save_highlight(&highlight_path)
+        .unwrap();
+
+    let entries = read_session_record(&highlight_path).unwrap();
+    assert!(matches!(
+        entries[0].message,
+        RecordedMessage::Incoming(ServerMessage {
+            payload: ServerMessagePayload::Handshake { .. },
+            ..
+        })
+    ));
+    assert!(entries.iter().any(|entry| matches!(
+        entry.message,
+        RecordedMessage::Incoming(ServerMessage {
+            payload: ServerMessagePayload::StartGame(_),
+            ..
+        })
+    )));
+    let last_frame_number = entries.last().unwrap().game_frame_number;
+    let world_updates = entries
+        .iter()
+        .filter(|entry| {
+            matches!(
+                entry.message,
+                RecordedMessage::Incoming(ServerMessage {
+                    payload: ServerMessagePayload::UpdateWorld { .. },
+                    ..
+                })
+            )
+        })
+        .collect::<Vec<_>>();
+    assert!(!world_updates.is_empty());
+    assert!(world_updates
+        .iter()
+        .all(|entry| entry.game_frame_number + WINDOW_FRAMES >= last_frame_number));
+    // The highlight is replayed the same way as a whole session.
+    SessionReplay::replay(&entries);
+
+    fs::remove_file(highlight_path).ok();
+}
+
+#[test]
+fn desync_reports_replay_like_the_whole_session() {
+    let recording_path = env::temp_dir().join("gv_harness_desync_session.gvrec");
+    let report_path = env::temp_dir().join("gv_harness_desync_report.gvdesync");
+
+    let mut harness = TestHarness::new(LoopbackConfig {
+        latency_frames: 2,
+        ..LoopbackConfig::default()
+    })
+    .unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    harness
+        .client_mut(host)
+        .record_session(&recording_path)
+        .unwrap();
+    harness.client_mut(host).record_desync_reports();
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.run_frames(30);
+    harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(1.0, 0.0)), 0);
+    harness.run_frames(60);
+    harness
+        .client(host)
+        .save_desync_report(&report_path)
+        .unwrap();
+    harness.client_mut(host).stop_recording();
+
+    let report = read_desync_report(&report_path).unwrap();
+    assert_eq!(
+        report.reported_frame_number,
+        harness.client(host).game_frame_number()
+    );
+    let session_dump = SessionReplay::replay(&read_session_record(&recording_path).unwrap()).dump();
+    assert_eq!(SessionReplay::replay(&report.entries).dump(), session_dump);
+    assert!(dump_desync_report(&report).starts_with(&session_dump));
+
+    fs::remove_file(recording_path).ok();
+    fs::remove_file(report_path).ok();
+}
@@ -0,0 +1,63 @@
+use gv_core::gv_dirs::{resolve_path, GvDirs};
+
+use std::{env, fs, path::Path};
+
+#[test]
+fn legacy_files_are_migrated() {
+    let root = env::temp_dir().join("gv_dirs_migration");
+    let _ = fs::remove_dir_all(&root);
+    let legacy_working_dir = root.join("working_dir");
+    let dirs = GvDirs::with_root(&root);
+    dirs.create_all().unwrap();
+
+    fs::write(dirs.config_dir().join("profile.ron"), "legacy profile").unwrap();
+    fs::write(dirs.data_dir().join("saved_match.ron"), "legacy match").unwrap();
+    fs::create_dir_all(legacy_working_dir.join("crash_reports")).unwrap();
+    fs::write(
+        legacy_working_dir.join("crash_reports/gv_client_1.txt"),
+        "legacy crash",
+    )
+    .unwrap();
+
+    let moved = dirs.migrate_legacy_files(&legacy_working_dir).unwrap();
+    assert_eq!(moved.len(), 3);
+    assert_eq!(
+        fs::read_to_string(dirs.profile_path()).unwrap(),
+        "legacy profile"
+    );
+    assert_eq!(
+        fs::read_to_string(dirs.saved_match_path()).unwrap(),
+        "legacy match"
+    );
+    assert_eq!(
+        fs::read_to_string(dirs.crash_reports_dir().join("gv_client_1.txt")).unwrap(),
+        "legacy crash"
+    );
+    assert!(!dirs.config_dir().join("profile.ron").exists());
+    assert!(!legacy_working_dir.join("crash_reports").exists());
+
+    // Newer files are never overwritten.
+    fs::write(dirs.config_dir().join("profile.ron"), "older profile").unwrap();
+    assert!(dirs
+        .migrate_legacy_files(&legacy_working_dir)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        fs::read_to_string(dirs.profile_path()).unwrap(),
+        "legacy profile"
+    );
+}
+
+#[test]
+fn relative_paths_are_resolved_against_the_dir() {
+    let dirs = GvDirs::with_root(Path::new("/gv"));
+    assert_eq!(
+        resolve_path(&dirs.replays_dir(), "session.gvrec"),
+        Path::new("/gv/data/replays/session.gvrec")
+    );
+    let absolute_path = env::temp_dir().join("session.gvrec");
+    assert_eq!(
+        resolve_path(&dirs.replays_dir(), &absolute_path),
+        absolute_path
+    );
+}
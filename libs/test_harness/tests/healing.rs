@@ -0,0 +1,83 @@
+use gv_core::{
+    actions::player::CastSpell,
+    ecs::resources::world::SAVED_WORLD_STATES_LIMIT,
+    math::{Vector2, ZeroVector},
+    net::NetIdentifier,
+};
+use gv_game::ecs::resources::CountedHeals;
+use gv_test_harness::{HeadlessClient, LoopbackConfig, TestHarness};
+
+use std::collections::HashSet;
+
+const MAX_FRAMES: u64 = 300;
+
+/// Counts the frames an entity received healing (negative damage) at, the server may
+/// send the same frame again after a rewind.
+fn heals_received(client: &HeadlessClient, entity_net_id: NetIdentifier) -> usize {
+    client
+        .world_updates()
+        .iter()
+        .flat_map(|world_update| world_update.damage_histories_updates.iter())
+        .filter(|net_update| {
+            net_update.entity_net_id == entity_net_id
+                && net_update
+                    .data
+                    .entries
+                    .iter()
+                    .any(|entry| entry.damage < 0.0)
+        })
+        .map(|net_update| net_update.data.frame_number)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[test]
+fn heals_are_replicated_for_allies_in_radius() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let host_net_id = harness.client(host).player_net_id().unwrap();
+    let guest_net_id = harness.client(guest).player_net_id().unwrap();
+
+    // Players spawn in the same spot, so both of them are healed.
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Heal, Vector2::zero());
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            heals_received(client, host_net_id) == 1 && heals_received(client, guest_net_id) == 1
+        })
+    }));
+}
+
+#[test]
+fn heals_are_limited_by_cooldown() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let host_net_id = harness.client(host).player_net_id().unwrap();
+
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Heal, Vector2::zero());
+    harness.run_frames(5);
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Heal, Vector2::zero());
+    harness.run_frames(60);
+
+    assert_eq!(heals_received(harness.client(host), host_net_id), 1);
+}
+
+#[test]
+fn heals_are_counted_once_within_the_rewind_window() {
+    let mut counted_heals = CountedHeals::default();
+    assert!(counted_heals.count(1, 10));
+    assert!(!counted_heals.count(1, 10));
+    assert!(counted_heals.count(2, 10));
+
+    let frame_number = 10 + SAVED_WORLD_STATES_LIMIT as u64 + 1;
+    assert!(counted_heals.count(3, frame_number));
+    assert_eq!(counted_heals.0.len(), 1);
+}
@@ -0,0 +1,71 @@
+use gv_core::{
+    damage::DamageType,
+    ecs::components::damage_history::{DamageHistory, DamageHistoryEntry},
+    hit_registration::{HitRegistration, PredictedHits, HIT_TOLERANCE_FRAMES},
+    math::{Vector2, ZeroVector},
+};
+
+fn damage_entry(damage: f32, damage_type: Option<DamageType>) -> DamageHistoryEntry {
+    DamageHistoryEntry {
+        damage,
+        impulse: Vector2::zero(),
+        is_crit: false,
+        damage_type,
+    }
+}
+
+#[test]
+fn damage_history_matches_damage_by_frames_and_type() {
+    let mut damage_history = DamageHistory::new(0);
+    damage_history.add_entry(100, damage_entry(10.0, Some(DamageType::Magic)));
+    damage_history.add_entry(120, damage_entry(-5.0, None));
+
+    assert!(damage_history.has_damage(95..=105, DamageType::Magic));
+    assert!(!damage_history.has_damage(95..=105, DamageType::Physical));
+    assert!(!damage_history.has_damage(101..=130, DamageType::Magic));
+    // Heals don't confirm hits.
+    assert!(!damage_history.has_damage(115..=125, DamageType::Magic));
+}
+
+#[test]
+fn hits_with_server_damage_are_confirmed() {
+    let mut predicted_hits = PredictedHits::default();
+    predicted_hits.add(1u64, 100, DamageType::Magic);
+    predicted_hits.add(2u64, 100, DamageType::Magic);
+
+    let resolved = predicted_hits.resolve(90, |hit| hit.target == 1);
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].0.target, 1);
+    assert_eq!(resolved[0].1, HitRegistration::Confirmed);
+    assert_eq!(predicted_hits.len(), 1);
+}
+
+#[test]
+fn hits_become_no_regs_once_their_frames_are_acknowledged() {
+    let mut predicted_hits = PredictedHits::default();
+    predicted_hits.add(1u64, 100, DamageType::Physical);
+
+    assert!(predicted_hits
+        .resolve(100 + HIT_TOLERANCE_FRAMES - 1, |_| false)
+        .is_empty());
+    let resolved = predicted_hits.resolve(100 + HIT_TOLERANCE_FRAMES, |_| false);
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].1, HitRegistration::NoReg);
+    assert!(predicted_hits.is_empty());
+}
+
+#[test]
+fn server_damage_within_tolerance_confirms_hits() {
+    let mut damage_history = DamageHistory::new(0);
+    damage_history.add_entry(
+        100 + HIT_TOLERANCE_FRAMES,
+        damage_entry(7.0, Some(DamageType::Physical)),
+    );
+    let mut predicted_hits = PredictedHits::default();
+    predicted_hits.add(1u64, 100, DamageType::Physical);
+
+    let resolved = predicted_hits.resolve(200, |hit| {
+        damage_history.has_damage(hit.frames(), hit.damage_type)
+    });
+    assert_eq!(resolved[0].1, HitRegistration::Confirmed);
+}
@@ -0,0 +1,128 @@
+use amethyst::ecs::WorldExt;
+
+use gv_core::{
+    actions::{player::PlayerWalkAction, ClientActionUpdate},
+    ecs::resources::{
+        net::{
+            input_resend_frames, UnacknowledgedInputs, INPUT_REDUNDANCY_FRAMES,
+            MAX_INPUT_RESEND_FRAMES,
+        },
+        world::{FramedUpdates, InputFrame, ReceivedClientActionUpdates},
+    },
+    math::Vector2,
+    net::NetUpdate,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn input_frame(frame_number: u64, client_action_id: u64) -> InputFrame {
+    InputFrame {
+        frame_number,
+        walk_action_updates: vec![NetUpdate {
+            entity_net_id: 0,
+            data: ClientActionUpdate {
+                client_action_id,
+                action: PlayerWalkAction::Stop,
+            },
+        }],
+        cast_action_updates: Vec::new(),
+        interact_action_updates: Vec::new(),
+    }
+}
+
+fn packet_frames(input_frames: Option<Vec<InputFrame>>) -> Option<Vec<u64>> {
+    input_frames.map(|input_frames| {
+        input_frames
+            .iter()
+            .map(|input_frame| input_frame.frame_number)
+            .collect()
+    })
+}
+
+#[test]
+fn unacknowledged_inputs_are_resent() {
+    let mut unacknowledged_inputs = UnacknowledgedInputs::default();
+    assert!(unacknowledged_inputs.packet(10, 5).is_none());
+
+    unacknowledged_inputs.push(input_frame(10, 0));
+    assert_eq!(
+        packet_frames(unacknowledged_inputs.packet(10, 5)),
+        Some(vec![10])
+    );
+    // Nothing new, and it's not the time to resend yet.
+    assert!(unacknowledged_inputs.packet(12, 5).is_none());
+
+    unacknowledged_inputs.push(input_frame(13, 1));
+    assert_eq!(
+        packet_frames(unacknowledged_inputs.packet(13, 5)),
+        Some(vec![10, 13])
+    );
+    assert!(unacknowledged_inputs.packet(17, 5).is_none());
+    assert_eq!(
+        packet_frames(unacknowledged_inputs.packet(18, 5)),
+        Some(vec![10, 13])
+    );
+
+    unacknowledged_inputs.acknowledge(0);
+    assert_eq!(unacknowledged_inputs.len(), 1);
+    unacknowledged_inputs.acknowledge(1);
+    assert!(unacknowledged_inputs.is_empty());
+    assert!(unacknowledged_inputs.packet(30, 5).is_none());
+
+    for i in 0..INPUT_REDUNDANCY_FRAMES as u64 + 2 {
+        unacknowledged_inputs.push(input_frame(40 + i, 2 + i));
+    }
+    assert_eq!(unacknowledged_inputs.len(), INPUT_REDUNDANCY_FRAMES);
+    assert_eq!(
+        packet_frames(unacknowledged_inputs.packet(50, 5)).unwrap()[0],
+        42
+    );
+}
+
+#[test]
+fn resend_interval_depends_on_latency() {
+    assert_eq!(input_resend_frames(0, 60), 2);
+    assert_eq!(input_resend_frames(50, 60), 8);
+    assert!(input_resend_frames(100, 60) > input_resend_frames(50, 60));
+    assert_eq!(input_resend_frames(1000, 60), MAX_INPUT_RESEND_FRAMES);
+}
+
+#[test]
+fn redundant_inputs_are_applied_once() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    let first_input = harness
+        .client_mut(host)
+        .walk_input(Some(Vector2::new(1.0, 0.0)), 0);
+    harness
+        .client_mut(host)
+        .send_inputs(vec![first_input.clone()]);
+    // The same packet arriving twice.
+    harness
+        .client_mut(host)
+        .send_inputs(vec![first_input.clone()]);
+    harness.run_frames(2);
+    let second_input = harness.client_mut(host).walk_input(None, 0);
+    harness
+        .client_mut(host)
+        .send_inputs(vec![first_input.clone(), second_input.clone()]);
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).acknowledged_input() == second_input.last_client_action_id()
+    }));
+
+    let first_action_id = first_input.last_client_action_id();
+    let applied_times = harness
+        .server()
+        .world()
+        .fetch::<FramedUpdates<ReceivedClientActionUpdates>>()
+        .updates
+        .iter()
+        .flat_map(|update| update.walk_action_updates.iter())
+        .filter(|walk_action| Some(walk_action.data.client_action_id) == first_action_id)
+        .count();
+    assert_eq!(applied_times, 1);
+}
@@ -0,0 +1,75 @@
+use gv_core::ecs::resources::{GameLevelState, Intermission};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn start_intermission(harness: &mut TestHarness, ends_at_frame: u64) {
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<GameLevelState>()
+        .intermission = Some(Intermission {
+        wave: 1,
+        started_at_frame: 0,
+        ends_at_frame,
+        ready_players: Vec::new(),
+    });
+}
+
+#[test]
+fn next_wave_starts_when_everyone_is_ready() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    start_intermission(&mut harness, u64::max_value());
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.intermission().is_some())
+    }));
+
+    let host_connection_id = harness.client(host).connection_id().unwrap();
+    harness.client_mut(host).ready_for_wave();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            client.intermission().map_or(false, |intermission| {
+                intermission.ready_players == [host_connection_id]
+            })
+        })
+    }));
+
+    harness.client_mut(guest).ready_for_wave();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.intermission().is_none())
+    }));
+    assert!(harness
+        .server()
+        .world()
+        .fetch::<GameLevelState>()
+        .intermission
+        .is_none());
+}
+
+#[test]
+fn intermission_ends_when_time_runs_out() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let ends_at_frame = harness.server().game_frame_number() + 30;
+    start_intermission(&mut harness, ends_at_frame);
+
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .server()
+            .world()
+            .fetch::<GameLevelState>()
+            .intermission
+            .is_none()
+    }));
+    assert!(harness.server().game_frame_number() >= ends_at_frame);
+}
@@ -0,0 +1,53 @@
+use gv_core::{
+    ecs::{components::Impulse, resources::GameLevelState},
+    math::{Vector2, ZeroVector},
+};
+use gv_game::utils::world::{apply_impulse, knockback_impulse};
+
+const FRAME_SECONDS: f32 = 1.0 / 60.0;
+
+fn push(position: Vector2, impulse: Vector2, frames: usize) -> (Vector2, Impulse) {
+    let game_level_state = GameLevelState::default();
+    let mut position = position;
+    let mut impulse = Impulse { velocity: impulse };
+    for _ in 0..frames {
+        apply_impulse(
+            &mut position,
+            &mut impulse,
+            FRAME_SECONDS,
+            &game_level_state,
+        );
+    }
+    (position, impulse)
+}
+
+#[test]
+fn impulses_fade_out_and_replay_the_same_trajectory() {
+    let impulse = knockback_impulse(Vector2::zero(), Vector2::new(3.0, 4.0), 250.0);
+    assert!((impulse.norm() - 250.0).abs() < 0.001);
+
+    let (position, impulse_left) = push(Vector2::zero(), impulse, 60);
+    assert!(impulse_left.velocity.norm_squared() < std::f32::EPSILON);
+    assert!(position.x > 0.0 && position.y > 0.0);
+    // Rewinding and re-simulating the same frames lands at the same position.
+    assert_eq!(push(Vector2::zero(), impulse, 60).0, position);
+}
+
+#[test]
+fn impulses_stop_at_level_borders() {
+    let half_size = GameLevelState::default().dimensions_half_size();
+
+    let (position, impulse) = push(
+        Vector2::new(half_size.x - 1.0, 0.0),
+        Vector2::new(1000.0, 100.0),
+        1,
+    );
+    assert!((position.x - half_size.x).abs() < std::f32::EPSILON);
+    assert!(position.y > 0.0);
+    assert!(impulse.velocity.x.abs() < std::f32::EPSILON);
+    assert!(impulse.velocity.y > 0.0);
+
+    // Monsters walking in from outside of the level aren't pulled in, nor pushed further.
+    let outside = Vector2::new(half_size.x + 50.0, 0.0);
+    assert_eq!(push(outside, Vector2::new(1000.0, 0.0), 1).0, outside);
+}
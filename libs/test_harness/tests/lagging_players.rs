@@ -0,0 +1,68 @@
+use gv_core::{
+    ecs::resources::world::{MAX_PENDING_WORLD_UPDATES, PAUSE_FRAME_THRESHOLD},
+    net::server_message::DisconnectReason,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn host_drops_a_lagging_player() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_connection_id = harness.client(guest).connection_id().unwrap();
+
+    // Players who don't hold the game back can't be dropped.
+    harness.client_mut(host).kick(guest_connection_id);
+    harness.run_frames(10);
+    assert!(harness.client(guest).disconnect_reason().is_none());
+
+    harness
+        .client_mut(guest)
+        .set_acknowledges_world_updates(false);
+    assert!(
+        harness.run_until(MAX_PENDING_WORLD_UPDATES as u64 + MAX_FRAMES, |harness| {
+            harness.client(host).is_paused()
+        })
+    );
+    let is_reported = |harness: &TestHarness| {
+        harness
+            .client(host)
+            .lagging_players()
+            .iter()
+            .any(|lagging_player| lagging_player.connection_id == guest_connection_id)
+    };
+    assert!(is_reported(&harness));
+    // The list keeps being updated during the pause.
+    harness.run_frames(100);
+    assert!(is_reported(&harness));
+
+    harness.client_mut(host).kick(guest_connection_id);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(guest).disconnect_reason(),
+        Some(DisconnectReason::Kick)
+    ));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        !harness.client(host).is_paused() && harness.client(host).room_players().len() == 1
+    }));
+    assert!(harness.client(host).lagging_players().is_empty());
+}
+
+#[test]
+fn server_clamps_the_pause_threshold() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    for (requested, accepted) in &[(10, 10), (0, 1), (1000, PAUSE_FRAME_THRESHOLD)] {
+        harness.client_mut(host).set_pause_threshold(*requested);
+        assert!(harness.run_until(MAX_FRAMES, |harness| {
+            harness.client(host).pause_threshold() == Some(*accepted)
+        }));
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use gv_core::{
+    announcer::AnnouncerLine,
+    campaign::CAMPAIGN_LEVELS,
+    ecs::resources::net::{MultiplayerGameState, SavedMatch},
+    level_loading::{LevelKey, LevelLoadProgress},
+    level_scripts::ColorGrading,
+    room_settings::{RoomSettings, MAPS},
+};
+
+fn resource_exists(path: &str) -> bool {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path)
+        .is_file()
+}
+
+#[test]
+fn resumed_matches_load_their_own_campaign_level() {
+    let room_settings = RoomSettings::default();
+    let mut multiplayer_game_state = MultiplayerGameState::new();
+    assert_eq!(
+        LevelKey::new(&room_settings, &multiplayer_game_state),
+        LevelKey {
+            map_name: room_settings.map_name(),
+            campaign_level: None,
+        }
+    );
+
+    multiplayer_game_state.campaign_level = Some(1);
+    let key = LevelKey::new(&room_settings, &multiplayer_game_state);
+    assert_eq!(key.campaign_level, Some(1));
+
+    multiplayer_game_state.resumed_match = Some(SavedMatch {
+        spawn_level: 6,
+        game_frame_number: 600,
+        seed: 0,
+        campaign_level: Some(2),
+        players: Vec::new(),
+        payload_checkpoint: 0.0,
+    });
+    let key = LevelKey::new(&room_settings, &multiplayer_game_state);
+    assert_eq!(key.campaign_level, Some(2));
+}
+
+#[test]
+fn level_assets_exist() {
+    for map_name in MAPS.iter() {
+        let key = LevelKey {
+            map_name: *map_name,
+            campaign_level: None,
+        };
+        assert!(resource_exists(&key.landscape_texture_path()));
+        assert!(resource_exists(&key.landscape_sprite_sheet_path()));
+    }
+    for color_grading in ColorGrading::ALL.iter() {
+        assert!(resource_exists(color_grading.lut_path()));
+    }
+    for line in AnnouncerLine::ALL.iter() {
+        assert!(resource_exists(line.sound_path()));
+    }
+}
+
+#[test]
+fn campaign_level_scripts_are_checked_before_playing() {
+    let key = |campaign_level| LevelKey {
+        map_name: MAPS[0],
+        campaign_level,
+    };
+    assert!(key(None).script().unwrap().is_none());
+    for campaign_level in 0..CAMPAIGN_LEVELS.len() {
+        assert_eq!(
+            key(Some(campaign_level)).script().unwrap().is_some(),
+            CAMPAIGN_LEVELS[campaign_level].script.is_some()
+        );
+    }
+    assert!(key(Some(CAMPAIGN_LEVELS.len())).script().is_err());
+}
+
+#[test]
+fn progress_completes_unless_an_asset_fails() {
+    let mut progress = LevelLoadProgress {
+        loaded: 0,
+        total: 0,
+        errors: Vec::new(),
+    };
+    assert_eq!(progress.fraction(), 1.0);
+    assert!(progress.is_complete());
+
+    progress.total = 4;
+    progress.loaded = 3;
+    assert_eq!(progress.fraction(), 0.75);
+    assert!(!progress.is_complete());
+
+    progress.loaded = 4;
+    assert!(progress.is_complete());
+
+    progress
+        .errors
+        .push("resources/levels/desert.ron: missing".to_owned());
+    assert!(progress.has_failed());
+    assert!(!progress.is_complete());
+}
@@ -0,0 +1,293 @@
+use amethyst::ecs::WorldExt;
+
+use gv_core::{
+    campaign::CAMPAIGN_LEVELS,
+    ecs::resources::collision::{LevelCollisionGrid, COLLISION_CELL_SIZE},
+    level_scripts::{
+        open_door, push_out_of_safe_zones, ColorGrading, LevelLighting, LevelScript,
+        LevelScriptRunner, ScriptAction, ScriptTick, DAYLIGHT, NIGHT_AMBIENT,
+    },
+    math::Vector2,
+};
+use gv_game::ecs::resources::ActiveLevelScript;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+const SCRIPT: &str = r#"(
+  switches: [(name: "plate", position: (100.0, 0.0), radius: 20.0)],
+  doors: [(name: "gate", from: (-64.0, 200.0), to: (64.0, 220.0))],
+  triggers: [
+    (name: "welcome", condition: Timer(1.0), actions: [ShowMessage("Hello")]),
+    (name: "plate_pressed", condition: Switch("plate"), actions: [OpenDoor("gate"), SpawnWave(3)]),
+    (
+      name: "done",
+      condition: All([Fired("plate_pressed"), KillCount(2)]),
+      actions: [EndLevel],
+    ),
+  ],
+)"#;
+
+const LEVER_SCRIPT: &str = r#"(
+  switches: [
+    (name: "lever", kind: Lever, position: (100.0, 0.0), radius: 20.0),
+    (name: "plate", position: (-100.0, 0.0), radius: 20.0),
+  ],
+  triggers: [
+    (name: "lever_pulled", condition: Switch("lever"), actions: [ActivateSwitch("plate")]),
+    (name: "plate_pressed", condition: Switch("plate"), actions: [ShowMessage("Pressed")]),
+  ],
+)"#;
+
+fn tick(
+    runner: &mut LevelScriptRunner,
+    player_positions: &[Vector2],
+    kills: u32,
+) -> Vec<ScriptAction> {
+    interact(runner, player_positions, &[], kills)
+}
+
+fn interact(
+    runner: &mut LevelScriptRunner,
+    player_positions: &[Vector2],
+    interact_positions: &[Vector2],
+    kills: u32,
+) -> Vec<ScriptAction> {
+    runner.update(ScriptTick {
+        delta_seconds: 0.5,
+        player_positions,
+        interact_positions,
+        kills,
+        frame_number: 0,
+    })
+}
+
+#[test]
+fn triggers_fire_once_in_order() {
+    let mut runner = LevelScriptRunner::new(LevelScript::from_bytes(SCRIPT.as_bytes()).unwrap());
+    let origin = [Vector2::new(0.0, 0.0)];
+    let at_plate = [Vector2::new(90.0, 5.0)];
+
+    assert!(tick(&mut runner, &origin, 0).is_empty());
+    assert_eq!(
+        tick(&mut runner, &origin, 0),
+        vec![ScriptAction::ShowMessage("Hello".to_owned())]
+    );
+    assert!(tick(&mut runner, &origin, 0).is_empty());
+
+    // Kills made before the plate is pressed still count.
+    assert!(tick(&mut runner, &origin, 2).is_empty());
+    assert_eq!(
+        tick(&mut runner, &at_plate, 0),
+        vec![
+            ScriptAction::ActivateSwitch("plate".to_owned()),
+            ScriptAction::OpenDoor("gate".to_owned()),
+            ScriptAction::SpawnWave(3),
+            ScriptAction::EndLevel,
+        ]
+    );
+    assert!(runner.is_ended());
+    assert!(runner.has_fired("plate_pressed"));
+    assert!(tick(&mut runner, &at_plate, 5).is_empty());
+}
+
+#[test]
+fn switches_stay_activated() {
+    let mut runner = LevelScriptRunner::new(LevelScript::from_bytes(SCRIPT.as_bytes()).unwrap());
+    let actions = tick(&mut runner, &[Vector2::new(100.0, 0.0)], 0);
+    assert_eq!(actions.len(), 3);
+    // Walking away from the plate doesn't deactivate it.
+    let actions = tick(&mut runner, &[Vector2::new(0.0, 0.0)], 2);
+    assert_eq!(
+        actions,
+        vec![
+            ScriptAction::ShowMessage("Hello".to_owned()),
+            ScriptAction::EndLevel
+        ]
+    );
+}
+
+#[test]
+fn levers_need_an_interaction() {
+    let mut runner =
+        LevelScriptRunner::new(LevelScript::from_bytes(LEVER_SCRIPT.as_bytes()).unwrap());
+    let at_lever = [Vector2::new(95.0, 0.0)];
+
+    // Standing next to a lever isn't enough, neither is interacting far from it.
+    assert!(tick(&mut runner, &at_lever, 0).is_empty());
+    assert!(interact(&mut runner, &at_lever, &[Vector2::new(0.0, 0.0)], 0).is_empty());
+    assert!(!runner.is_switch_activated("lever"));
+
+    // The lever activates the plate on the same tick, which isn't reported twice later.
+    assert_eq!(
+        interact(&mut runner, &at_lever, &at_lever, 0),
+        vec![
+            ScriptAction::ActivateSwitch("lever".to_owned()),
+            ScriptAction::ActivateSwitch("plate".to_owned()),
+            ScriptAction::ShowMessage("Pressed".to_owned()),
+        ]
+    );
+    assert!(runner.is_switch_activated("plate"));
+    assert!(tick(&mut runner, &[Vector2::new(-100.0, 0.0)], 0).is_empty());
+    assert!(interact(&mut runner, &at_lever, &at_lever, 0).is_empty());
+}
+
+#[test]
+fn doors_block_until_opened() {
+    let runner = LevelScriptRunner::new(LevelScript::from_bytes(SCRIPT.as_bytes()).unwrap());
+    let mut collision_grid =
+        LevelCollisionGrid::new(Vector2::new(1024.0, 1024.0), COLLISION_CELL_SIZE);
+    let (from, to) = (Vector2::new(0.0, 0.0), Vector2::new(0.0, 400.0));
+    assert!(collision_grid.has_line_of_sight(from, to));
+
+    runner.close_doors(&mut collision_grid);
+    assert!(!collision_grid.has_line_of_sight(from, to));
+    assert!(collision_grid.has_line_of_sight(Vector2::new(200.0, 0.0), Vector2::new(200.0, 400.0)));
+
+    // Walkers slide along a closed door and stop when they walk straight into it.
+    assert_eq!(
+        collision_grid.slide(Vector2::new(0.0, 180.0), Vector2::new(10.0, 210.0)),
+        Vector2::new(10.0, 180.0)
+    );
+    assert_eq!(
+        collision_grid.slide(Vector2::new(0.0, 180.0), Vector2::new(0.0, 210.0)),
+        Vector2::new(0.0, 180.0)
+    );
+
+    open_door(runner.script(), "gate", &mut collision_grid);
+    assert!(collision_grid.has_line_of_sight(from, to));
+    assert_eq!(
+        collision_grid.slide(Vector2::new(0.0, 180.0), Vector2::new(0.0, 210.0)),
+        Vector2::new(0.0, 210.0)
+    );
+}
+
+#[test]
+fn monsters_are_pushed_out_of_safe_zones() {
+    let script = r#"(
+      safe_zones: [
+        (name: "base", center: (0.0, 0.0), radius: 100.0),
+        (name: "red_base", center: (500.0, 0.0), radius: 50.0, color: (1.0, 0.2, 0.2)),
+      ],
+      triggers: [],
+    )"#;
+    let script = LevelScript::from_bytes(script.as_bytes()).unwrap();
+    let safe_zones = &script.safe_zones;
+    assert_eq!(safe_zones[1].color, (1.0, 0.2, 0.2));
+    assert!(safe_zones[0].contains(Vector2::new(50.0, 50.0)));
+    assert!(!safe_zones[0].contains(Vector2::new(100.0, 0.0)));
+
+    assert_eq!(
+        push_out_of_safe_zones(safe_zones, Vector2::new(0.0, 40.0)),
+        Vector2::new(0.0, 100.0)
+    );
+    assert_eq!(
+        push_out_of_safe_zones(safe_zones, Vector2::new(480.0, 0.0)),
+        Vector2::new(450.0, 0.0)
+    );
+    let outside = Vector2::new(200.0, 200.0);
+    assert_eq!(push_out_of_safe_zones(safe_zones, outside), outside);
+}
+
+#[test]
+fn invalid_references_are_rejected() {
+    let script = r#"(triggers: [(name: "a", condition: Switch("missing"), actions: [])])"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+    let script =
+        r#"(triggers: [(name: "a", condition: Timer(1.0), actions: [OpenDoor("missing")])])"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+    let script = r#"(triggers: [(name: "a", condition: All([Fired("b")]), actions: [])])"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+    let script =
+        r#"(triggers: [(name: "a", condition: Timer(1.0), actions: [ActivateSwitch("b")])])"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+}
+
+#[test]
+fn campaign_level_scripts_are_valid() {
+    for campaign_level in CAMPAIGN_LEVELS.iter() {
+        if let Some(script) = campaign_level.script {
+            if let Err(err) = LevelScript::from_bytes(script) {
+                panic!("{}: {}", campaign_level.name, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn interactions_are_sent_with_player_inputs() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    // The lever covers the whole level, so that it doesn't matter where the player spawns.
+    let script = r#"(
+      switches: [(name: "lever", kind: Lever, position: (0.0, 0.0), radius: 10000.0)],
+      triggers: [],
+    )"#;
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<ActiveLevelScript>()
+        .runner = Some(LevelScriptRunner::new(
+        LevelScript::from_bytes(script.as_bytes()).unwrap(),
+    ));
+    harness.run_frames(30);
+    assert!(harness.client(host).level_script_actions().is_empty());
+
+    let input_frame = harness.client_mut(host).interact_input();
+    // A redundant copy of the same input doesn't activate anything again.
+    harness
+        .client_mut(host)
+        .send_inputs(vec![input_frame.clone(), input_frame]);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        !harness.client(host).level_script_actions().is_empty()
+    }));
+    harness.run_frames(30);
+    assert_eq!(
+        harness.client(host).level_script_actions(),
+        &[ScriptAction::ActivateSwitch("lever".to_owned())]
+    );
+}
+
+#[test]
+fn nights_are_darker_than_days() {
+    let cycle = LevelLighting::DayNightCycle { period: 240.0 };
+    assert!((cycle.ambient_light(0.0) - DAYLIGHT).abs() < 0.001);
+    assert!((cycle.ambient_light(120.0) - NIGHT_AMBIENT).abs() < 0.001);
+    assert!((cycle.ambient_light(240.0) - DAYLIGHT).abs() < 0.001);
+    assert!(cycle.ambient_light(60.0) < DAYLIGHT);
+
+    assert_eq!(LevelLighting::Daylight.ambient_light(120.0), DAYLIGHT);
+    assert_eq!(LevelLighting::Dark(0.2).ambient_light(0.0), 0.2);
+    assert_eq!(LevelLighting::Dark(-1.0).ambient_light(0.0), 0.0);
+}
+
+#[test]
+fn level_scripts_declare_lighting_and_torches() {
+    let script = LevelScript::from_bytes(
+        br#"(
+          lighting: Dark(0.3),
+          torches: [(position: (10.0, 20.0)), (position: (0.0, 0.0), radius: 50.0)],
+          triggers: [],
+        )"#,
+    )
+    .unwrap();
+    assert_eq!(script.lighting, LevelLighting::Dark(0.3));
+    assert_eq!(script.torches.len(), 2);
+    assert_eq!(script.torches[1].radius, 50.0);
+    assert!(script.torches[0].radius > 0.0);
+
+    let script = LevelScript::from_bytes(b"(triggers: [])").unwrap();
+    assert_eq!(script.lighting, LevelLighting::Daylight);
+    assert!(script.torches.is_empty());
+}
+
+#[test]
+fn level_scripts_pick_color_grading() {
+    let script = LevelScript::from_bytes(b"(color_grading: Cold, triggers: [])").unwrap();
+    assert_eq!(script.color_grading, ColorGrading::Cold);
+
+    let script = LevelScript::from_bytes(b"(triggers: [])").unwrap();
+    assert_eq!(script.color_grading, ColorGrading::Neutral);
+}
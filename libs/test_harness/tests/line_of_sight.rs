@@ -0,0 +1,47 @@
+use gv_core::{
+    ecs::resources::collision::LevelCollisionGrid,
+    math::{Vector2, ZeroVector},
+};
+
+fn grid_with_wall() -> LevelCollisionGrid {
+    // A vertical wall covering the cells from x 64 to 96.
+    let mut grid = LevelCollisionGrid::new(Vector2::new(1024.0, 1024.0), 32.0);
+    for y in -4..4 {
+        grid.block(Vector2::new(80.0, y as f32 * 32.0 + 16.0));
+    }
+    grid
+}
+
+#[test]
+fn walls_block_line_of_sight() {
+    let grid = grid_with_wall();
+    assert!(grid.is_blocked(Vector2::new(70.0, 0.0)));
+    assert!(!grid.has_line_of_sight(Vector2::zero(), Vector2::new(200.0, 10.0)));
+    // Looking past the end of the wall, or away from it.
+    assert!(grid.has_line_of_sight(Vector2::zero(), Vector2::new(100.0, 400.0)));
+    assert!(grid.has_line_of_sight(Vector2::zero(), Vector2::new(-200.0, 10.0)));
+
+    let hit = grid
+        .raycast(Vector2::zero(), Vector2::new(200.0, 0.0))
+        .unwrap();
+    assert!((hit.position - Vector2::new(64.0, 0.0)).norm() < 0.001);
+    assert_eq!(hit.normal, Vector2::new(-1.0, 0.0));
+
+    // Rays coming from the other side hit the opposite side of the wall.
+    let hit = grid
+        .raycast(Vector2::new(200.0, 0.0), Vector2::zero())
+        .unwrap();
+    assert!((hit.position - Vector2::new(96.0, 0.0)).norm() < 0.001);
+    assert_eq!(hit.normal, Vector2::new(1.0, 0.0));
+}
+
+#[test]
+fn levels_without_obstacles_are_fully_visible() {
+    let grid = LevelCollisionGrid::new(Vector2::new(1024.0, 1024.0), 32.0);
+    assert!(grid.has_line_of_sight(Vector2::new(-500.0, -500.0), Vector2::new(500.0, 500.0)));
+
+    // Monsters walking in from outside of the level see through the borders.
+    let grid = grid_with_wall();
+    assert!(grid.has_line_of_sight(Vector2::new(-900.0, 0.0), Vector2::new(-400.0, 0.0)));
+    assert!(!grid.is_blocked(Vector2::new(900.0, 0.0)));
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use gv_core::net::server_message::DisconnectReason;
+use gv_server::rooms::{MatchmakingConfig, RoomRouter, RoomsConfig};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn with_matchmaking(matchmaking: MatchmakingConfig) -> TestHarness<RoomRouter> {
+    TestHarness::with_rooms(
+        LoopbackConfig::default(),
+        RoomsConfig {
+            matchmaking,
+            ..RoomsConfig::default()
+        },
+    )
+}
+
+#[test]
+fn close_ratings_are_matched() {
+    let mut harness = with_matchmaking(MatchmakingConfig::default());
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+
+    harness.client_mut(first).find_match(1000);
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(first)
+        .players_in_queue()
+        .is_some()));
+    harness.client_mut(second).find_match(1050);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first).room_id().is_some() && harness.client(second).room_id().is_some()
+    }));
+    assert_eq!(
+        harness.client(first).room_id(),
+        harness.client(second).room_id()
+    );
+    assert!(harness.server().matchmaking_queue().is_empty());
+
+    harness.client_mut(first).join();
+    harness.client_mut(second).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first).room_players().len() == 2
+            && harness.client(second).room_players().len() == 2
+    }));
+    // The player who has waited the most hosts the match.
+    assert!(harness.client(first).is_host());
+    assert!(!harness.client(second).is_host());
+}
+
+#[test]
+fn distant_ratings_wait_for_the_spread_to_widen() {
+    let mut harness = with_matchmaking(MatchmakingConfig::default());
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+
+    harness.client_mut(first).find_match(1000);
+    harness.client_mut(second).find_match(1400);
+    harness.run_frames(60);
+    assert_eq!(harness.client(first).room_id(), None);
+    assert_eq!(harness.client(second).room_id(), None);
+    assert_eq!(harness.server().matchmaking_queue().len(), 2);
+
+    // 100 + 50 * 6 seconds covers the difference of 400.
+    assert!(harness.run_until(MAX_FRAMES * 2, |harness| {
+        harness.client(first).room_id().is_some() && harness.client(second).room_id().is_some()
+    }));
+}
+
+#[test]
+fn cancelled_player_is_not_matched() {
+    let mut harness = with_matchmaking(MatchmakingConfig::default());
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+
+    harness.client_mut(first).find_match(1000);
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .server()
+        .matchmaking_queue()
+        .len()
+        == 1));
+    harness.client_mut(first).cancel_matchmaking();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .server()
+        .matchmaking_queue()
+        .is_empty()));
+
+    harness.client_mut(second).find_match(1000);
+    harness.run_frames(60);
+    assert_eq!(harness.client(first).room_id(), None);
+    assert_eq!(harness.client(second).room_id(), None);
+    assert_eq!(harness.server().matchmaking_queue().len(), 1);
+    assert_eq!(harness.server().rooms().count(), 0);
+}
+
+#[test]
+fn matchmaking_times_out() {
+    let mut harness = with_matchmaking(MatchmakingConfig {
+        rating_spread_per_second: 0,
+        timeout: Duration::from_secs(1),
+        ..MatchmakingConfig::default()
+    });
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+
+    harness.client_mut(first).find_match(1000);
+    harness.client_mut(second).find_match(2000);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first).disconnect_reason().is_some()
+            && harness.client(second).disconnect_reason().is_some()
+    }));
+    for client in &[first, second] {
+        match harness.client(*client).disconnect_reason() {
+            Some(DisconnectReason::MatchmakingTimedOut) => {}
+            disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+        }
+    }
+    assert!(harness.server().matchmaking_queue().is_empty());
+    assert_eq!(harness.server().rooms().count(), 0);
+}
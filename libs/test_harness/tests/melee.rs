@@ -0,0 +1,91 @@
+use amethyst::ecs::{Join, ReadStorage};
+
+use gv_core::{
+    actions::player::CastSpell,
+    balance::{Balance, MeleeSpell},
+    classes::PlayerClass,
+    ecs::components::{missile::Missile, Player, PlayerLastCastedSpells},
+    math::{Vector2, ZeroVector},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+const DEFAULT_BALANCE: &str = include_str!("../../../resources/balance.ron");
+
+#[test]
+fn swings_hit_circles_inside_the_cone() {
+    let melee = MeleeSpell {
+        range: 70.0,
+        arc_degrees: 90.0,
+        max_targets: 3,
+    };
+    let origin = Vector2::zero();
+    let right = Vector2::new(1.0, 0.0);
+
+    assert!(melee.hits(origin, right, Vector2::new(50.0, 10.0), 10.0));
+    // The circle reaches into the range with its edge.
+    assert!(melee.hits(origin, right, Vector2::new(78.0, 0.0), 10.0));
+    assert!(!melee.hits(origin, right, Vector2::new(90.0, 0.0), 10.0));
+    // Behind the caster.
+    assert!(!melee.hits(origin, right, Vector2::new(-40.0, 0.0), 10.0));
+    // At 60 degrees, outside of the 45 degrees half-arc, but cut by the edge of the cone.
+    assert!(melee.hits(origin, right, Vector2::new(25.0, 43.3), 15.0));
+    assert!(!melee.hits(origin, right, Vector2::new(25.0, 43.3), 2.0));
+    // Overlapping the caster.
+    assert!(melee.hits(origin, right, Vector2::new(-5.0, 0.0), 10.0));
+
+    let whirlwind = MeleeSpell {
+        arc_degrees: 360.0,
+        ..melee
+    };
+    assert!(whirlwind.hits(origin, right, Vector2::new(-40.0, 0.0), 10.0));
+}
+
+#[test]
+fn invalid_melee_spells_are_rejected() {
+    let balance = Balance::default();
+    let warrior = balance.classes[&PlayerClass::Warrior].melee.as_ref();
+    assert!(warrior.is_some());
+    assert!(balance.classes[&PlayerClass::Mage].melee.is_none());
+
+    let no_arc = DEFAULT_BALANCE.replace("arc_degrees: 100.0", "arc_degrees: 0.0");
+    assert!(Balance::from_bytes(no_arc.as_bytes()).is_err());
+    // Rollbacks wouldn't re-simulate the targets hit this far away.
+    let too_long = DEFAULT_BALANCE.replace("range: 70.0", "range: 1000.0");
+    assert!(Balance::from_bytes(too_long.as_bytes()).is_err());
+    let no_targets = DEFAULT_BALANCE.replace("max_targets: 3", "max_targets: 0");
+    assert!(Balance::from_bytes(no_targets.as_bytes()).is_err());
+}
+
+#[test]
+fn warriors_swing_instead_of_casting_missiles() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.client_mut(host).select_class(PlayerClass::Warrior);
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Missile, Vector2::new(100.0, 0.0));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let (players, last_casted_spells) = harness
+            .server()
+            .world()
+            .system_data::<(ReadStorage<Player>, ReadStorage<PlayerLastCastedSpells>)>();
+        (&players, &last_casted_spells)
+            .join()
+            .any(|(player, last_casted_spells)| {
+                player.class == PlayerClass::Warrior
+                    && last_casted_spells.get(CastSpell::Missile) > 0
+            })
+    }));
+    harness.run_frames(30);
+
+    let missiles = harness
+        .server()
+        .world()
+        .system_data::<ReadStorage<Missile>>()
+        .join()
+        .count();
+    assert_eq!(missiles, 0);
+}
@@ -0,0 +1,65 @@
+use gv_core::{actions::monster_spawn::SpawnType, net::NetIdentifier};
+use gv_game::ecs::systems::MAX_MINIONS_PER_PLAYER;
+use gv_test_harness::{HeadlessClient, LoopbackConfig, TestHarness};
+
+use std::collections::HashMap;
+
+const MAX_FRAMES: u64 = 300;
+
+/// Maps entity net ids of the summoned minions to the net ids of their owners.
+fn minion_spawns(client: &HeadlessClient) -> HashMap<NetIdentifier, NetIdentifier> {
+    client
+        .world_updates()
+        .iter()
+        .flat_map(|world_update| world_update.spawn_actions.iter())
+        .filter_map(|spawn_action| match spawn_action.spawn_type {
+            SpawnType::Minion {
+                entity_net_id,
+                owner_net_id,
+                ..
+            } => Some((
+                entity_net_id.expect("Expected a minion net id in multiplayer"),
+                owner_net_id.expect("Expected an owner net id in multiplayer"),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn summons_are_capped_per_player() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let host_net_id = harness.client(host).player_net_id().unwrap();
+
+    for _ in 0..MAX_MINIONS_PER_PLAYER + 2 {
+        harness.client_mut(host).summon();
+    }
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        minion_spawns(harness.client(host)).len() == MAX_MINIONS_PER_PLAYER
+    }));
+    harness.run_frames(60);
+
+    let minions = minion_spawns(harness.client(host));
+    assert_eq!(minions.len(), MAX_MINIONS_PER_PLAYER);
+    assert!(minions.values().all(|owner| *owner == host_net_id));
+}
+
+#[test]
+fn minions_are_replicated_with_their_owner() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_net_id = harness.client(guest).player_net_id().unwrap();
+
+    harness.client_mut(guest).summon();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            minion_spawns(client)
+                .values()
+                .eq(std::iter::once(&guest_net_id))
+        })
+    }));
+}
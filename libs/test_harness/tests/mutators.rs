@@ -0,0 +1,68 @@
+use gv_core::{
+    mutators::{self, mutators_text, toggle_mutator, Mutator, FAST_MONSTERS_SPEED_FACTOR},
+    room_settings::RoomSettings,
+    stats::{Stat, StatModifiers},
+};
+
+#[test]
+fn toggling_keeps_mutators_ordered() {
+    let mut mutators = Vec::new();
+    toggle_mutator(&mut mutators, Mutator::Vampirism);
+    toggle_mutator(&mut mutators, Mutator::FastMonsters);
+    toggle_mutator(&mut mutators, Mutator::NoCooldowns);
+    assert_eq!(
+        mutators,
+        vec![
+            Mutator::FastMonsters,
+            Mutator::NoCooldowns,
+            Mutator::Vampirism
+        ]
+    );
+    assert_eq!(
+        mutators_text(&mutators),
+        "Fast monsters, No cooldowns, Vampirism"
+    );
+
+    toggle_mutator(&mut mutators, Mutator::NoCooldowns);
+    assert_eq!(mutators, vec![Mutator::FastMonsters, Mutator::Vampirism]);
+    assert_eq!(mutators_text(&[]), "None");
+}
+
+#[test]
+fn mutators_modify_stats() {
+    let enabled = Mutator::ALL.to_vec();
+
+    let mut player_modifiers = StatModifiers::default();
+    mutators::add_player_modifiers(&enabled, &mut player_modifiers);
+    assert!(player_modifiers.value(Stat::Haste, 1.0) > 1.0);
+    assert!(player_modifiers.value(Stat::DamageTaken, 1.0) > 1.0);
+    assert!(player_modifiers.value(Stat::LifeSteal, 0.0) > 0.0);
+    assert_eq!(player_modifiers.value(Stat::MoveSpeed, 100.0), 100.0);
+
+    let mut monster_modifiers = StatModifiers::default();
+    mutators::add_monster_modifiers(&enabled, &mut monster_modifiers);
+    assert_eq!(
+        monster_modifiers.value(Stat::MoveSpeed, 100.0),
+        100.0 * FAST_MONSTERS_SPEED_FACTOR
+    );
+    assert_eq!(monster_modifiers.value(Stat::DamageTaken, 1.0), 1.0);
+
+    let mut no_modifiers = StatModifiers::default();
+    mutators::add_player_modifiers(&[], &mut no_modifiers);
+    assert_eq!(no_modifiers.value(Stat::Haste, 1.0), 1.0);
+}
+
+#[test]
+fn duplicate_mutators_are_rejected() {
+    let room_settings = RoomSettings {
+        mutators: vec![Mutator::OneHitDeaths],
+        ..RoomSettings::default()
+    };
+    assert_eq!(room_settings.validate(1), Ok(()));
+
+    let room_settings = RoomSettings {
+        mutators: vec![Mutator::OneHitDeaths, Mutator::OneHitDeaths],
+        ..RoomSettings::default()
+    };
+    assert!(room_settings.validate(1).is_err());
+}
@@ -0,0 +1,64 @@
+use gv_core::{
+    actions::player::CastSpell,
+    math::Vector2,
+    objectives::{ObjectiveEvent, ObjectiveTracker, Trigger},
+    tutorial::{tutorial_objectives, TUTORIAL_AREA_CENTER},
+};
+
+#[test]
+fn tutorial_is_completed_step_by_step() {
+    let mut objective_tracker = ObjectiveTracker::new(tutorial_objectives());
+    assert_eq!(
+        objective_tracker
+            .take_started()
+            .map(|objective| objective.monsters_to_spawn),
+        Some(0)
+    );
+    assert!(objective_tracker.take_started().is_none());
+
+    assert!(!objective_tracker.handle(ObjectiveEvent::PlayerMoved(Vector2::new(0.0, 0.0))));
+    assert!(!objective_tracker.handle(ObjectiveEvent::SpellCast(CastSpell::Missile)));
+    let area_center = Vector2::new(TUTORIAL_AREA_CENTER.0, TUTORIAL_AREA_CENTER.1);
+    assert!(objective_tracker.handle(ObjectiveEvent::PlayerMoved(area_center)));
+
+    assert!(!objective_tracker.handle(ObjectiveEvent::SpellCast(CastSpell::Heal)));
+    assert!(objective_tracker.handle(ObjectiveEvent::SpellCast(CastSpell::Missile)));
+
+    assert_eq!(
+        objective_tracker
+            .take_started()
+            .map(|objective| objective.monsters_to_spawn),
+        Some(3)
+    );
+    assert!(!objective_tracker.handle(ObjectiveEvent::MonsterKilled));
+    assert!(!objective_tracker.handle(ObjectiveEvent::MonsterKilled));
+    assert_eq!(objective_tracker.progress_text(), Some("2/3".to_owned()));
+    assert!(objective_tracker.handle(ObjectiveEvent::MonsterKilled));
+
+    let seconds = match objective_tracker.current().unwrap().trigger {
+        Trigger::AvoidDamage { seconds } => seconds,
+        trigger => panic!("Expected AvoidDamage, got {:?}", trigger),
+    };
+    assert!(!objective_tracker.handle(ObjectiveEvent::TimePassed(seconds - 1.0)));
+    // Getting hit starts the countdown over.
+    assert!(!objective_tracker.handle(ObjectiveEvent::PlayerDamaged));
+    assert!(!objective_tracker.handle(ObjectiveEvent::TimePassed(seconds - 1.0)));
+    assert!(objective_tracker.handle(ObjectiveEvent::TimePassed(1.0)));
+
+    assert!(objective_tracker.handle(ObjectiveEvent::SpellCast(CastSpell::Heal)));
+    assert!(objective_tracker.is_completed());
+    assert!(objective_tracker.current().is_none());
+    assert!(!objective_tracker.handle(ObjectiveEvent::MonsterKilled));
+}
+
+#[test]
+fn restarting_starts_from_the_first_objective() {
+    let mut objective_tracker = ObjectiveTracker::new(tutorial_objectives());
+    objective_tracker.take_started();
+    let area_center = Vector2::new(TUTORIAL_AREA_CENTER.0, TUTORIAL_AREA_CENTER.1);
+    assert!(objective_tracker.handle(ObjectiveEvent::PlayerMoved(area_center)));
+
+    objective_tracker.restart();
+    assert_eq!(objective_tracker.current_index(), 0);
+    assert!(objective_tracker.take_started().is_some());
+}
@@ -0,0 +1,42 @@
+use std::{env, fs};
+
+use gv_core::profiling::{FrameTimings, FRAME_TIMINGS_HISTORY};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+#[test]
+fn server_systems_are_timed_every_frame() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(FRAME_TIMINGS_HISTORY as u64 + 10);
+
+    let frame_timings = harness.server().world().read_resource::<FrameTimings>();
+    frame_timings.with_history(|frames| {
+        assert_eq!(frames.len(), FRAME_TIMINGS_HISTORY);
+        assert!(frames
+            .iter()
+            .zip(frames.iter().skip(1))
+            .all(|(previous, next)| next.frame_number == previous.frame_number + 1));
+        for frame in frames {
+            let action_system_runs = frame
+                .systems
+                .iter()
+                .filter(|system| system.name == "action_system")
+                .count();
+            assert_eq!(action_system_runs, 1);
+            assert!(frame
+                .systems
+                .iter()
+                .all(|system| system.duration <= frame.busy_duration));
+        }
+    });
+
+    let trace_path = env::temp_dir().join("gv_frame_trace.json");
+    frame_timings.write_chrome_trace(&trace_path).unwrap();
+    let trace = fs::read_to_string(&trace_path).unwrap();
+    assert!(trace.starts_with("{\"traceEvents\":["));
+    assert!(trace.contains("\"name\":\"action_system\""));
+    let _ = fs::remove_file(trace_path);
+}
@@ -0,0 +1,98 @@
+use gv_core::{
+    ecs::{
+        components::PlayerLastCastedSpells,
+        resources::net::{ReconciliationStatus, PERSISTENT_DIVERGENCE_REPORTS},
+    },
+    math::Vector2,
+    net::server_message::PlayerReconciliation,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn report(frame_number: u64, position: Vector2) -> PlayerReconciliation {
+    PlayerReconciliation {
+        frame_number,
+        position,
+        last_casted_spells: PlayerLastCastedSpells::default(),
+    }
+}
+
+#[test]
+fn divergence_becomes_persistent_after_several_reports() {
+    let mut status = ReconciliationStatus::default();
+    let predicted_position = Vector2::new(100.0, 100.0);
+    let predicted_spells = PlayerLastCastedSpells::default();
+
+    assert!(!status.check(
+        &report(60, Vector2::new(101.0, 100.0)),
+        predicted_position,
+        &predicted_spells
+    ));
+    assert!(!status.is_diverged());
+
+    for i in 1..=PERSISTENT_DIVERGENCE_REPORTS {
+        let has_diverged = status.check(
+            &report(60 + 60 * u64::from(i), Vector2::new(150.0, 100.0)),
+            predicted_position,
+            &predicted_spells,
+        );
+        assert_eq!(has_diverged, i == PERSISTENT_DIVERGENCE_REPORTS);
+    }
+    assert!(status.is_diverged());
+    // The divergence is reported only once.
+    assert!(!status.check(
+        &report(300, Vector2::new(150.0, 100.0)),
+        predicted_position,
+        &predicted_spells
+    ));
+
+    assert!(!status.check(
+        &report(360, predicted_position),
+        predicted_position,
+        &predicted_spells
+    ));
+    assert!(!status.is_diverged());
+    assert_eq!(status.last_checked_frame, Some(360));
+
+    let cast_spells = PlayerLastCastedSpells {
+        missile: 340,
+        ..PlayerLastCastedSpells::default()
+    };
+    status.check(
+        &report(420, predicted_position),
+        predicted_position,
+        &cast_spells,
+    );
+    assert!(status.cooldowns_diverged);
+    assert_eq!(status.consecutive_divergences, 1);
+}
+
+#[test]
+fn players_receive_reports_about_their_own_players() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(1.0, 0.0)), 0);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).reconciliation_reports().len() >= 2
+            && harness.client(guest).reconciliation_reports().len() >= 2
+    }));
+
+    for &client in &[host, guest] {
+        let reports = harness.client(client).reconciliation_reports();
+        assert!(reports
+            .windows(2)
+            .all(|reports| reports[0].frame_number < reports[1].frame_number));
+    }
+
+    // Only the host walks.
+    let host_reports = harness.client(host).reconciliation_reports();
+    let guest_reports = harness.client(guest).reconciliation_reports();
+    assert!(host_reports[0].position.x < host_reports[1].position.x);
+    assert_eq!(guest_reports[0].position, guest_reports[1].position);
+}
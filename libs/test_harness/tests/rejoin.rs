@@ -0,0 +1,92 @@
+use amethyst::ecs::WorldExt;
+
+use std::time::Duration;
+
+use gv_core::net::server_message::DisconnectReason;
+use gv_server::ecs::resources::RejoinConfig;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn guest_is_disconnected(harness: &TestHarness, host: usize, nickname: &str) -> bool {
+    harness
+        .client(host)
+        .room_players()
+        .iter()
+        .any(|player| player.nickname == nickname && player.is_disconnected)
+}
+
+#[test]
+fn disconnected_player_rejoins_the_match() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    let guest_net_id = harness.client(guest).player_net_id().unwrap();
+
+    harness.client_mut(guest).disconnect();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        guest_is_disconnected(harness, host, "guest")
+    }));
+    assert_eq!(
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.players.len()),
+        2
+    );
+
+    // Strangers still can't join a running game.
+    let stranger = harness.add_client("stranger");
+    harness.client_mut(stranger).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(stranger).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(stranger).disconnect_reason(),
+        Some(DisconnectReason::GameIsStarted)
+    ));
+
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).rejoin_snapshot().is_some()
+            && !guest_is_disconnected(harness, host, "guest")
+    }));
+    assert!(harness.client(guest).has_started_game());
+    assert_eq!(harness.client(guest).player_net_id(), Some(guest_net_id));
+    let snapshot = harness.client(guest).rejoin_snapshot().unwrap();
+    assert!(snapshot
+        .players
+        .iter()
+        .any(|player| player.entity_net_id == guest_net_id));
+    assert!(harness.client(guest).game_frame_number() >= snapshot.frame_number);
+}
+
+#[test]
+fn player_is_dropped_after_the_grace_period() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    harness.server_mut().world_mut().insert(RejoinConfig {
+        grace_period: Duration::from_secs(1),
+    });
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness.client_mut(guest).disconnect();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        guest_is_disconnected(harness, host, "guest")
+    }));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).room_players().len() == 1
+    }));
+    assert!(harness.client(host).disconnect_reason().is_none());
+
+    // The player can't rejoin anymore.
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(guest).disconnect_reason(),
+        Some(DisconnectReason::GameIsStarted)
+    ));
+}
@@ -0,0 +1,170 @@
+use amethyst::network::{simulation::NetworkSimulationEvent, Bytes};
+use gv_core::net::rendezvous::{
+    decode_rendezvous_packet, encode_rendezvous_packet, RendezvousPacket,
+};
+use gv_server::rendezvous::{RendezvousCoordinator, HOST_EXPIRATION};
+
+use std::{net::SocketAddr, time::Duration};
+
+const HOST_CODE: u32 = 123_456;
+
+fn peer_addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+fn send(coordinator: &mut RendezvousCoordinator, from: SocketAddr, packet: RendezvousPacket) {
+    coordinator.process_event(&NetworkSimulationEvent::Message(
+        from,
+        Bytes::from(encode_rendezvous_packet(&packet)),
+    ));
+}
+
+fn received(coordinator: &mut RendezvousCoordinator) -> Vec<(SocketAddr, RendezvousPacket)> {
+    coordinator
+        .drain_outcoming_messages()
+        .into_iter()
+        .map(|message| {
+            let packet = decode_rendezvous_packet(message.payload.as_ref())
+                .expect("Expected a rendezvous packet")
+                .expect("Expected to decode a rendezvous packet");
+            (message.destination, packet)
+        })
+        .collect()
+}
+
+fn register_host(coordinator: &mut RendezvousCoordinator, host: SocketAddr) {
+    send(
+        coordinator,
+        host,
+        RendezvousPacket::RegisterHost {
+            host_code: HOST_CODE,
+        },
+    );
+    match received(coordinator).as_slice() {
+        [(addr, RendezvousPacket::HostRegistered { host_code })]
+            if *addr == host && *host_code == HOST_CODE => {}
+        packets => panic!("Unexpected packets: {:?}", packets),
+    }
+}
+
+#[test]
+fn guest_is_introduced_to_the_host() {
+    let mut coordinator = RendezvousCoordinator::new();
+    let host = peer_addr(3455);
+    let guest = peer_addr(3456);
+
+    register_host(&mut coordinator, host);
+    send(
+        &mut coordinator,
+        guest,
+        RendezvousPacket::Connect {
+            host_code: HOST_CODE,
+        },
+    );
+
+    let packets = received(&mut coordinator);
+    assert_eq!(packets.len(), 2);
+    assert!(packets.iter().any(|(addr, packet)| match packet {
+        RendezvousPacket::PunchTo(punch_addr) => *addr == guest && *punch_addr == host,
+        _ => false,
+    }));
+    assert!(packets.iter().any(|(addr, packet)| match packet {
+        RendezvousPacket::PunchTo(punch_addr) => *addr == host && *punch_addr == guest,
+        _ => false,
+    }));
+}
+
+#[test]
+fn unknown_host_code_is_rejected() {
+    let mut coordinator = RendezvousCoordinator::new();
+    let guest = peer_addr(3456);
+
+    send(
+        &mut coordinator,
+        guest,
+        RendezvousPacket::Connect {
+            host_code: HOST_CODE,
+        },
+    );
+    match received(&mut coordinator).as_slice() {
+        [(addr, RendezvousPacket::HostNotFound { .. })] if *addr == guest => {}
+        packets => panic!("Unexpected packets: {:?}", packets),
+    }
+}
+
+#[test]
+fn taken_host_code_is_rejected() {
+    let mut coordinator = RendezvousCoordinator::new();
+    let host = peer_addr(3455);
+    let another_host = peer_addr(3457);
+
+    register_host(&mut coordinator, host);
+    send(
+        &mut coordinator,
+        another_host,
+        RendezvousPacket::RegisterHost {
+            host_code: HOST_CODE,
+        },
+    );
+    match received(&mut coordinator).as_slice() {
+        [(addr, RendezvousPacket::HostCodeTaken { .. })] if *addr == another_host => {}
+        packets => panic!("Unexpected packets: {:?}", packets),
+    }
+    assert_eq!(coordinator.host_addr(HOST_CODE), Some(host));
+}
+
+#[test]
+fn only_introduced_peers_can_relay() {
+    let mut coordinator = RendezvousCoordinator::new();
+    let host = peer_addr(3455);
+    let guest = peer_addr(3456);
+    let stranger = peer_addr(3457);
+
+    register_host(&mut coordinator, host);
+    send(
+        &mut coordinator,
+        stranger,
+        RendezvousPacket::Relay {
+            to: host,
+            payload: vec![1, 2, 3],
+        },
+    );
+    assert!(received(&mut coordinator).is_empty());
+
+    send(
+        &mut coordinator,
+        guest,
+        RendezvousPacket::Connect {
+            host_code: HOST_CODE,
+        },
+    );
+    received(&mut coordinator);
+    send(
+        &mut coordinator,
+        guest,
+        RendezvousPacket::Relay {
+            to: host,
+            payload: vec![1, 2, 3],
+        },
+    );
+    match received(&mut coordinator).as_slice() {
+        [(addr, RendezvousPacket::Relayed { from, payload })]
+            if *addr == host && *from == guest && payload == &[1, 2, 3] => {}
+        packets => panic!("Unexpected packets: {:?}", packets),
+    }
+}
+
+#[test]
+fn hosts_expire_without_keep_alive() {
+    let mut coordinator = RendezvousCoordinator::new();
+    let host = peer_addr(3455);
+
+    register_host(&mut coordinator, host);
+    coordinator.update(HOST_EXPIRATION / 2);
+    send(&mut coordinator, host, RendezvousPacket::KeepAlive);
+    coordinator.update(HOST_EXPIRATION / 2 + Duration::from_secs(1));
+    assert_eq!(coordinator.hosts_count(), 1);
+
+    coordinator.update(HOST_EXPIRATION);
+    assert_eq!(coordinator.hosts_count(), 0);
+}
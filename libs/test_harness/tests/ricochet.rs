@@ -0,0 +1,62 @@
+use gv_core::{ecs::resources::GameLevelState, math::Vector2};
+use gv_game::utils::world::{level_border_normals, move_bouncing_off_level_borders};
+
+const FRAME_SECONDS: f32 = 1.0 / 60.0;
+
+#[test]
+fn missiles_reflect_off_level_borders() {
+    let game_level_state = GameLevelState::default();
+    let half_size = game_level_state.dimensions_half_size();
+
+    let mut position = Vector2::new(half_size.x - 1.0, 0.0);
+    let mut velocity = Vector2::new(300.0, 120.0);
+    assert!(move_bouncing_off_level_borders(
+        &mut position,
+        &mut velocity,
+        FRAME_SECONDS,
+        &game_level_state,
+    ));
+    assert_eq!(velocity, Vector2::new(-300.0, 120.0));
+    // The missile moves by 5 units with the border 1 unit away, 4 units past it are mirrored.
+    assert!((position.x - (half_size.x - 4.0)).abs() < 0.001);
+    assert!((position.y - 2.0).abs() < 0.001);
+
+    assert!(!move_bouncing_off_level_borders(
+        &mut position,
+        &mut velocity,
+        FRAME_SECONDS,
+        &game_level_state,
+    ));
+}
+
+#[test]
+fn corners_reflect_on_both_axes() {
+    let game_level_state = GameLevelState::default();
+    let half_size = game_level_state.dimensions_half_size();
+
+    let from = half_size - Vector2::new(1.0, 1.0);
+    let normals = level_border_normals(from, half_size + Vector2::new(1.0, 1.0), &game_level_state)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        normals,
+        vec![Vector2::new(-1.0, 0.0), Vector2::new(0.0, -1.0)]
+    );
+
+    let mut position = from;
+    let mut velocity = Vector2::new(120.0, 120.0);
+    move_bouncing_off_level_borders(
+        &mut position,
+        &mut velocity,
+        FRAME_SECONDS,
+        &game_level_state,
+    );
+    assert_eq!(velocity, Vector2::new(-120.0, -120.0));
+    assert!(position.x < half_size.x && position.y < half_size.y);
+
+    // Objects already outside of the level aren't reflected back in.
+    let outside = half_size + Vector2::new(10.0, 0.0);
+    assert_eq!(
+        level_border_normals(outside, outside + Vector2::new(5.0, 0.0), &game_level_state).count(),
+        0
+    );
+}
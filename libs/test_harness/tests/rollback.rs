@@ -0,0 +1,315 @@
+use amethyst::ecs::{Builder, Entities, Entity, Join, ReadStorage, World, WorldExt, WriteStorage};
+
+use gv_core::{
+    actions::{mob::MobAction, player::CastSpell, Action},
+    classes::PlayerClass,
+    ecs::{
+        components::{
+            damage_history::DamageHistory, missile::Missile, EntityNetMetadata, Monster, Player,
+            WorldPosition,
+        },
+        resources::{
+            world::{
+                DirtyEntities, RollbackMode, RollbackStats, SavedWorldState,
+                ROLLBACK_INTERACTION_RADIUS,
+            },
+            GameLevelState,
+        },
+    },
+    math::{Vector2, ZeroVector},
+};
+use gv_game::ecs::resources::ConsoleSpawnRequests;
+use gv_test_harness::{HeadlessServer, LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn create_entities(count: usize) -> Vec<Entity> {
+    let mut world = World::new();
+    (0..count).map(|_| world.create_entity().build()).collect()
+}
+
+fn monster(action: Action<MobAction<Entity>>) -> Monster {
+    Monster {
+        health: 100.0,
+        attack_damage: 10.0,
+        destination: Vector2::zero(),
+        facing_direction: Vector2::new(1.0, 0.0),
+        velocity: Vector2::zero(),
+        action,
+        name: "Ghoul".to_owned(),
+        radius: 16.0,
+        active_at_frame: 0,
+    }
+}
+
+fn saved_positions(positions: &[(Entity, Vector2)]) -> SavedWorldState {
+    SavedWorldState {
+        world_positions: positions
+            .iter()
+            .map(|(entity, position)| (*entity, WorldPosition::new(*position)))
+            .collect(),
+        ..SavedWorldState::default()
+    }
+}
+
+#[test]
+fn dirty_entities_spread_to_the_reachable_ones() {
+    let entities = create_entities(10);
+    let (seed, near, far, chasing, new) = (
+        entities[0],
+        entities[1],
+        entities[2],
+        entities[3],
+        entities[4],
+    );
+    let far_away = Vector2::new(ROLLBACK_INTERACTION_RADIUS * 3.0, 0.0);
+    let mut positions = vec![
+        (seed, Vector2::zero()),
+        (near, Vector2::new(ROLLBACK_INTERACTION_RADIUS / 2.0, 0.0)),
+        (far, far_away),
+        (chasing, -far_away),
+    ];
+    for (i, entity) in entities[5..].iter().enumerate() {
+        positions.push((*entity, far_away * (i + 2) as f32));
+    }
+    let mut previous_state = saved_positions(&positions);
+    previous_state.monsters.push((
+        chasing,
+        monster(Action {
+            frame_number: 0,
+            action: MobAction::Chase(seed),
+        }),
+    ));
+
+    let mut current_positions = positions;
+    current_positions.push((new, far_away * 3.0));
+    let mut dirty_entities = DirtyEntities::with_seeds(vec![seed]);
+    dirty_entities.spread(10, &current_positions, &previous_state);
+
+    assert!(dirty_entities.contains(seed));
+    assert!(dirty_entities.contains(near));
+    assert!(!dirty_entities.contains(far));
+    assert!(dirty_entities.contains(chasing));
+    // Entities that the previous simulation hasn't seen are always re-simulated.
+    assert!(dirty_entities.contains(new));
+    assert!(!dirty_entities.is_all());
+}
+
+#[test]
+fn monsters_deciding_on_the_frame_are_dirty() {
+    let entities = create_entities(6);
+    let positions = entities
+        .iter()
+        .enumerate()
+        .map(|(i, entity)| {
+            let position = Vector2::new(ROLLBACK_INTERACTION_RADIUS * 2.0 * i as f32, 0.0);
+            (*entity, position)
+        })
+        .collect::<Vec<_>>();
+    let mut previous_state = saved_positions(&positions);
+    previous_state.monsters.push((
+        entities[3],
+        monster(Action {
+            frame_number: 10,
+            action: MobAction::Move(Vector2::zero()),
+        }),
+    ));
+    previous_state.monsters.push((
+        entities[4],
+        monster(Action {
+            frame_number: 9,
+            action: MobAction::Move(Vector2::zero()),
+        }),
+    ));
+
+    let mut dirty_entities = DirtyEntities::with_seeds(vec![entities[0]]);
+    dirty_entities.spread(10, &positions, &previous_state);
+    assert!(dirty_entities.contains(entities[3]));
+    assert!(!dirty_entities.contains(entities[4]));
+}
+
+#[test]
+fn crowded_rollbacks_re_simulate_everything() {
+    let entities = create_entities(4);
+    let positions = entities
+        .iter()
+        .map(|entity| (*entity, Vector2::zero()))
+        .collect::<Vec<_>>();
+    let mut dirty_entities = DirtyEntities::with_seeds(vec![entities[0]]);
+    dirty_entities.spread(10, &positions, &saved_positions(&positions));
+    assert!(dirty_entities.is_all());
+}
+
+/// Where `arrange_far_apart` puts the players and the monsters, everything is further than
+/// `ROLLBACK_INTERACTION_RADIUS` from the host.
+const HOST_POSITION: (f32, f32) = (-1000.0, 0.0);
+const GUEST_POSITION: (f32, f32) = (1000.0, 0.0);
+const MONSTER_POSITIONS: [(f32, f32); 5] = [
+    (-1000.0, 600.0),
+    (-1000.0, -600.0),
+    (1000.0, 600.0),
+    (1000.0, -600.0),
+    (0.0, 600.0),
+];
+/// The monsters walk towards the level borders and don't reach them before the runs end.
+const MONSTER_DESTINATION_Y: f32 = 1950.0;
+/// The monsters spawned by the level at the start and with `ConsoleSpawnRequests`.
+const MONSTERS_COUNT: usize = MONSTER_POSITIONS.len();
+
+/// Overwrites the randomly chosen paths of the monsters, so both runs simulate the same world.
+/// The monsters walk away from the players and don't decide on anything until they arrive.
+fn arrange_far_apart(harness: &mut TestHarness, host: usize) {
+    let frame_number = harness.server().game_frame_number();
+    let host_net_id = harness.client(host).player_net_id();
+    let world = harness.server_mut().world_mut();
+    let (entities, entity_net_metadata, players, mut monsters, mut world_positions) = world
+        .system_data::<(
+            Entities,
+            ReadStorage<EntityNetMetadata>,
+            ReadStorage<Player>,
+            WriteStorage<Monster>,
+            WriteStorage<WorldPosition>,
+        )>();
+
+    for (entity, _) in (&entities, &players).join() {
+        let is_host = entity_net_metadata
+            .get(entity)
+            .map(|net_metadata| net_metadata.id)
+            == host_net_id;
+        let (x, y) = if is_host {
+            HOST_POSITION
+        } else {
+            GUEST_POSITION
+        };
+        world_positions
+            .insert(entity, WorldPosition::new(Vector2::new(x, y)))
+            .unwrap();
+    }
+
+    let arranged_monsters = (&entities, &mut monsters).join().collect::<Vec<_>>();
+    assert_eq!(arranged_monsters.len(), MONSTERS_COUNT);
+    for ((entity, monster), (x, y)) in arranged_monsters.into_iter().zip(MONSTER_POSITIONS.iter()) {
+        let position = Vector2::new(*x, *y);
+        let destination = Vector2::new(*x, y.signum() * MONSTER_DESTINATION_Y);
+        world_positions
+            .insert(entity, WorldPosition::new(position))
+            .unwrap();
+        monster.destination = destination;
+        monster.velocity = Vector2::zero();
+        monster.action = Action {
+            frame_number,
+            action: MobAction::Move(destination),
+        };
+    }
+}
+
+/// Everything a rollback restores for clean entities, formatted to be compared between runs.
+fn simulated_state(server: &HeadlessServer) -> Vec<String> {
+    let (entities, world_positions, monsters, missiles, damage_histories) =
+        server.world().system_data::<(
+            Entities,
+            ReadStorage<WorldPosition>,
+            ReadStorage<Monster>,
+            ReadStorage<Missile>,
+            ReadStorage<DamageHistory>,
+        )>();
+    (
+        &entities,
+        &world_positions,
+        monsters.maybe(),
+        missiles.maybe(),
+        damage_histories.maybe(),
+    )
+        .join()
+        .map(
+            |(entity, world_position, monster, missile, damage_history)| {
+                format!(
+                    "{}: {:?} {:?} {:?} {:?}",
+                    entity.id(),
+                    **world_position,
+                    monster,
+                    missile,
+                    damage_history.map(|damage_history| &damage_history.history)
+                )
+            },
+        )
+        .collect()
+}
+
+fn run_late_walks(rollback_mode: RollbackMode) -> (Vec<String>, RollbackStats) {
+    let mut harness = TestHarness::new(LoopbackConfig {
+        latency_frames: 3,
+        ..LoopbackConfig::default()
+    })
+    .unwrap();
+    harness.server_mut().world_mut().insert(rollback_mode);
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    // Random spawns would differ between the runs.
+    harness
+        .server_mut()
+        .world_mut()
+        .write_resource::<GameLevelState>()
+        .spawn_level = 0;
+    harness
+        .server_mut()
+        .world_mut()
+        .write_resource::<ConsoleSpawnRequests>()
+        .0
+        .push((PlayerClass::default(), MONSTERS_COUNT - 1));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let frame_number = harness.server().game_frame_number();
+        let monsters = harness.server().monsters();
+        monsters.len() == MONSTERS_COUNT
+            && monsters
+                .iter()
+                .all(|(monster, _)| !monster.is_emerging(frame_number))
+    }));
+    arrange_far_apart(&mut harness, host);
+    // Rollbacks don't reach back to the frames before the world got arranged.
+    harness.run_frames(30);
+
+    harness.client_mut(guest).cast(
+        CastSpell::Missile,
+        Vector2::new(GUEST_POSITION.0, MONSTER_DESTINATION_Y),
+    );
+    harness.run_frames(90);
+
+    harness
+        .client_mut(host)
+        .walk(Some(Vector2::new(1.0, 0.0)), 15);
+    harness.run_frames(5);
+    harness
+        .client_mut(guest)
+        .walk(Some(Vector2::new(0.0, -1.0)), 0);
+    harness.run_frames(10);
+    harness.client_mut(host).walk(None, 10);
+    harness.run_frames(30);
+    assert!(harness.is_converged());
+
+    let rollback_stats = *harness.server().world().read_resource::<RollbackStats>();
+    (simulated_state(harness.server()), rollback_stats)
+}
+
+#[test]
+fn dirty_entities_rollback_matches_full_re_simulation() {
+    let (full, full_stats) = run_late_walks(RollbackMode::Full);
+    let (dirty_entities, dirty_entities_stats) = run_late_walks(RollbackMode::DirtyEntities);
+    assert!(full_stats.rollbacks > 0);
+    assert_eq!(full_stats.partial_rollbacks, 0);
+
+    // The guest, the monsters and the missile are out of the host's reach, so only a part
+    // of the world is re-simulated.
+    assert!(dirty_entities_stats.partial_rollbacks > 0);
+    let (dirty, all) = dirty_entities_stats.last_partial_rollback.unwrap();
+    assert!(dirty > 0 && dirty < all, "{} out of {}", dirty, all);
+    let count = |component: &str| {
+        full.iter()
+            .filter(|entity| entity.contains(&format!("Some({} {{", component)))
+            .count()
+    };
+    assert_eq!(count("Monster"), MONSTERS_COUNT);
+    assert!(count("Missile") > 0);
+    assert_eq!(full, dirty_entities);
+}
@@ -0,0 +1,153 @@
+use gv_core::{
+    net::server_message::DisconnectReason,
+    room_settings::{Difficulty, GameMode, RoomSettings, MAX_PLAYERS},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn join(harness: &mut TestHarness) {
+    for index in 0..harness.clients().len() {
+        harness.client_mut(index).join();
+    }
+    let clients_count = harness.clients().len();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_players().len() == clients_count)
+    }));
+}
+
+#[test]
+fn settings_are_validated() {
+    let room_settings = RoomSettings::default();
+    assert_eq!(room_settings.validate(MAX_PLAYERS), Ok(()));
+    assert!(room_settings.validate(MAX_PLAYERS + 1).is_err());
+
+    let room_settings = RoomSettings {
+        max_players: 0,
+        ..RoomSettings::default()
+    };
+    assert!(room_settings.validate(0).is_err());
+
+    let room_settings = RoomSettings {
+        map: "swamp".to_owned(),
+        ..RoomSettings::default()
+    };
+    assert!(room_settings.validate(1).is_err());
+    assert_eq!(room_settings.map_name(), "desert");
+}
+
+#[test]
+fn host_edits_settings_for_everyone() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    join(&mut harness);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_settings() == Some(&RoomSettings::default()))
+    }));
+
+    let room_settings = RoomSettings {
+        max_players: 3,
+        difficulty: Difficulty::Hard,
+        mode: GameMode::Campaign,
+        friendly_fire: true,
+        aim_assist: false,
+        ..RoomSettings::default()
+    };
+    harness
+        .client_mut(host)
+        .update_room_settings(room_settings.clone());
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_settings() == Some(&room_settings))
+    }));
+
+    // Only the host can edit the settings.
+    harness
+        .client_mut(guest)
+        .update_room_settings(RoomSettings::default());
+    // Can't hold fewer players than there are in the room.
+    harness.client_mut(host).update_room_settings(RoomSettings {
+        max_players: 1,
+        ..room_settings.clone()
+    });
+    harness.run_frames(10);
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.room_settings() == Some(&room_settings)));
+    assert_eq!(
+        *harness.server().world().fetch::<RoomSettings>(),
+        room_settings
+    );
+}
+
+#[test]
+fn max_players_limits_joining() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    join(&mut harness);
+    harness.client_mut(host).update_room_settings(RoomSettings {
+        max_players: 1,
+        ..RoomSettings::default()
+    });
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .client(host)
+            .room_settings()
+            .map_or(false, |room_settings| room_settings.max_players == 1)
+    }));
+
+    let guest = harness.add_client("guest");
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(guest).disconnect_reason(),
+        Some(DisconnectReason::RoomIsFull)
+    ));
+    assert_eq!(harness.client(host).room_players().len(), 1);
+}
+
+#[test]
+fn start_button_starts_the_selected_mode() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    join(&mut harness);
+    harness.client_mut(host).update_room_settings(RoomSettings {
+        mode: GameMode::Campaign,
+        ..RoomSettings::default()
+    });
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .client(host)
+            .room_settings()
+            .map_or(false, |room_settings| {
+                room_settings.mode == GameMode::Campaign
+            })
+    }));
+
+    harness.client_mut(host).start_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+    assert_eq!(
+        harness
+            .server()
+            .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.campaign_level),
+        Some(0)
+    );
+}
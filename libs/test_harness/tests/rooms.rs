@@ -0,0 +1,164 @@
+use gv_core::{ecs::resources::GameEngineState, net::server_message::DisconnectReason};
+use gv_server::rooms::{RoomRouter, RoomsConfig};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn create_and_join(harness: &mut TestHarness<RoomRouter>, client: usize, room_name: &str) {
+    harness.client_mut(client).create_room(room_name);
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(client)
+        .room_id()
+        .is_some()));
+    harness.client_mut(client).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(client).is_host() && harness.client(client).room_players().len() == 1
+    }));
+}
+
+#[test]
+fn rooms_are_listed() {
+    let mut harness = TestHarness::with_rooms(LoopbackConfig::default(), RoomsConfig::default());
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+    let observer = harness.add_client("observer");
+
+    create_and_join(&mut harness, first, "first room");
+    create_and_join(&mut harness, second, "second room");
+    assert_ne!(
+        harness.client(first).room_id(),
+        harness.client(second).room_id()
+    );
+
+    harness.client_mut(observer).list_rooms();
+    assert!(
+        harness.run_until(MAX_FRAMES, |harness| harness.client(observer).rooms().len()
+            == 2)
+    );
+    assert!(harness
+        .client(observer)
+        .rooms()
+        .iter()
+        .all(|room| room.players_count == 1 && !room.is_playing));
+}
+
+#[test]
+fn rooms_run_independently() {
+    let mut harness = TestHarness::with_rooms(LoopbackConfig::default(), RoomsConfig::default());
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+    let guest = harness.add_client("guest");
+
+    create_and_join(&mut harness, first, "first room");
+    create_and_join(&mut harness, second, "second room");
+    let first_room = harness.client(first).room_id().unwrap();
+    let second_room = harness.client(second).room_id().unwrap();
+
+    harness.client_mut(guest).enter_room(first_room);
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(guest)
+        .room_id()
+        .is_some()));
+    harness.client_mut(guest).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first).room_players().len() == 2
+            && harness.client(guest).room_players().len() == 2
+    }));
+
+    harness.client_mut(first).start_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first).has_started_game() && harness.client(guest).has_started_game()
+    }));
+    harness.run_frames(60);
+
+    let router = harness.server();
+    assert_eq!(
+        router.room(first_room).unwrap().engine_state(),
+        GameEngineState::Playing
+    );
+    assert_eq!(
+        router.room(second_room).unwrap().engine_state(),
+        GameEngineState::Menu
+    );
+    assert!(!harness.client(second).has_started_game());
+    assert_eq!(harness.client(second).room_players().len(), 1);
+}
+
+#[test]
+fn entering_an_unknown_room_is_rejected() {
+    let mut harness = TestHarness::with_rooms(LoopbackConfig::default(), RoomsConfig::default());
+    let client = harness.add_client("client");
+
+    harness.client_mut(client).enter_room(42);
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(client)
+        .disconnect_reason()
+        .is_some()));
+    match harness.client(client).disconnect_reason() {
+        Some(DisconnectReason::RoomNotFound) => {}
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+}
+
+#[test]
+fn rooms_are_limited() {
+    let mut harness = TestHarness::with_rooms(
+        LoopbackConfig::default(),
+        RoomsConfig {
+            max_rooms: 1,
+            ..RoomsConfig::default()
+        },
+    );
+    let first = harness.add_client("first");
+    let second = harness.add_client("second");
+
+    create_and_join(&mut harness, first, "first room");
+    harness.client_mut(second).create_room("second room");
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(second)
+        .disconnect_reason()
+        .is_some()));
+    match harness.client(second).disconnect_reason() {
+        Some(DisconnectReason::RoomLimitReached) => {}
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+}
+
+#[test]
+fn room_is_dropped_when_the_host_leaves() {
+    let mut harness = TestHarness::with_rooms(LoopbackConfig::default(), RoomsConfig::default());
+    let host = harness.add_client("host");
+
+    create_and_join(&mut harness, host, "room");
+    assert_eq!(harness.server().rooms().count(), 1);
+
+    harness.client_mut(host).disconnect();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness.server().rooms().count() == 0));
+    assert_eq!(
+        harness.server().peer_room_id(harness.client(host).addr()),
+        None
+    );
+}
+
+#[test]
+fn rooms_share_their_tick_rate() {
+    let mut harness = TestHarness::with_rooms(
+        LoopbackConfig::default(),
+        RoomsConfig {
+            tick_rate: 30,
+            ..RoomsConfig::default()
+        },
+    );
+    let host = harness.add_client("host");
+
+    create_and_join(&mut harness, host, "room");
+    assert_eq!(harness.client(host).tick_rate(), 30);
+
+    let room_id = harness.client(host).room_id().unwrap();
+    let frame_number = harness.server().room(room_id).unwrap().game_frame_number();
+    harness.run_frames(60);
+    assert_eq!(
+        harness.server().room(room_id).unwrap().game_frame_number() - frame_number,
+        30
+    );
+}
@@ -0,0 +1,106 @@
+use amethyst::ecs::{Join, ReadStorage, SystemData};
+use gv_core::{
+    augments::{Augment, PlayerAugments},
+    economy::{PlayerWallet, ShopItem},
+    ecs::{
+        components::Player,
+        resources::{
+            net::{SavedMatch, SavedPlayer},
+            GameLevelState,
+        },
+        system_data::time::GameTimeService,
+    },
+};
+use gv_game::ecs::resources::MatchSeed;
+use gv_server::ecs::resources::MatchSaveFile;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+use std::{env, path::PathBuf};
+
+const MAX_FRAMES: u64 = 300;
+
+fn save_file_path(test_name: &str) -> PathBuf {
+    env::temp_dir().join(format!("gv_saved_match_{}.ron", test_name))
+}
+
+#[test]
+fn host_resumes_the_saved_match() {
+    let save_file = MatchSaveFile(Some(save_file_path("host_resumes_the_saved_match")));
+    let saved_match = SavedMatch {
+        spawn_level: 4,
+        game_frame_number: 1200,
+        seed: 42,
+        campaign_level: None,
+        players: vec![SavedPlayer {
+            nickname: "guest".to_owned(),
+            health: 42.0,
+            augments: PlayerAugments(vec![Augment::Pierce]),
+            wallet: PlayerWallet {
+                coins: 30,
+                items: vec![(ShopItem::Ward, 1500)],
+            },
+        }],
+        payload_checkpoint: 0.0,
+    };
+    save_file.save(&saved_match);
+
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    harness.server_mut().world_mut().insert(save_file);
+
+    assert!(harness.join_all(MAX_FRAMES));
+    harness.client_mut(host).resume_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.resumed_match() == Some(&saved_match)));
+    let world = harness.server().world();
+    assert_eq!(
+        world.fetch::<GameLevelState>().spawn_level,
+        saved_match.spawn_level
+    );
+    let mut health = world
+        .system_data::<ReadStorage<Player>>()
+        .join()
+        .map(|player| player.health)
+        .collect::<Vec<_>>();
+    health.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+    assert_eq!(health, vec![42.0, Player::new().health]);
+    assert!(world
+        .system_data::<ReadStorage<PlayerAugments>>()
+        .join()
+        .any(|augments| augments.0 == vec![Augment::Pierce]));
+    assert!(world
+        .system_data::<ReadStorage<PlayerWallet>>()
+        .join()
+        .any(|wallet| wallet.coins == 30 && wallet.items == vec![(ShopItem::Ward, 1500)]));
+    assert_eq!(world.fetch::<MatchSeed>().0, saved_match.seed);
+    assert!(GameTimeService::fetch(world).game_frame_number() >= saved_match.game_frame_number);
+}
+
+#[test]
+fn resuming_without_a_save_starts_a_new_match() {
+    let save_file = MatchSaveFile(Some(save_file_path(
+        "resuming_without_a_save_starts_a_new_match",
+    )));
+    save_file.delete();
+
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.server_mut().world_mut().insert(save_file);
+
+    assert!(harness.join_all(MAX_FRAMES));
+    harness.client_mut(host).resume_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness
+        .client(host)
+        .has_started_game()));
+    assert!(harness.client(host).resumed_match().is_none());
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "scripting")]
+
+use gv_core::{
+    balance::{Balance, SpellDefinition},
+    classes::PlayerClass,
+    math::Vector2,
+};
+use gv_game::scripting::{MonsterContext, ScriptHooks, ScriptedMonsterAction};
+
+fn monster(health: f32) -> MonsterContext<'static> {
+    MonsterContext {
+        frame_number: 100,
+        name: "Ghoul",
+        health,
+        position: Vector2::new(10.0, 20.0),
+        action: "Idle",
+        seconds_since_action: 1.0,
+    }
+}
+
+fn missile_spell(balance: &Balance, class: PlayerClass) -> SpellDefinition {
+    balance.spells[&balance.classes[&class].missile.spell].clone()
+}
+
+#[test]
+fn hooks_change_casts_hits_and_monster_actions() {
+    let script_hooks = ScriptHooks::from_source(
+        r#"
+        fn on_cast(cast) {
+            if cast.class == "Warden" {
+                #{ damage: cast.damage * 2.0, bounces: 3 }
+            }
+        }
+
+        fn on_hit(hit) {
+            if hit.monster == "Ghoul" { hit.damage + 10 } else { hit.damage }
+        }
+
+        fn decide_action(monster) {
+            if monster.health < 50.0 {
+                #{ move_to: [monster.x - 100.0, monster.y] }
+            } else {
+                "default"
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let balance = Balance::default();
+    let mut warden_missile = missile_spell(&balance, PlayerClass::Warden);
+    script_hooks.on_missile_cast(0, PlayerClass::Warden, &mut warden_missile);
+    assert_eq!(
+        warden_missile.damage,
+        missile_spell(&balance, PlayerClass::Warden).damage * 2.0
+    );
+    assert_eq!(warden_missile.bounces, 3);
+    let mut mage_missile = missile_spell(&balance, PlayerClass::Mage);
+    script_hooks.on_missile_cast(0, PlayerClass::Mage, &mut mage_missile);
+    assert_eq!(
+        mage_missile.damage,
+        missile_spell(&balance, PlayerClass::Mage).damage
+    );
+
+    assert_eq!(script_hooks.on_hit(0, "Ghoul", 100.0, 50.0), 60.0);
+    assert_eq!(script_hooks.on_hit(0, "Wisp", 100.0, 50.0), 50.0);
+
+    match script_hooks.decide_action(&monster(20.0)) {
+        Some(ScriptedMonsterAction::Move(destination)) => {
+            assert_eq!(destination, Vector2::new(-90.0, 20.0))
+        }
+        _ => panic!("Expected a Move action"),
+    }
+    assert!(script_hooks.decide_action(&monster(100.0)).is_none());
+}
+
+#[test]
+fn misbehaving_hooks_fall_back_to_the_defaults() {
+    let script_hooks = ScriptHooks::from_source(
+        r#"
+        fn on_hit(hit) {
+            loop {}
+        }
+
+        fn decide_action(monster) {
+            timestamp()
+        }
+
+        fn on_cast(cast) {
+            #{ damage: 1.0 / 0.0 }
+        }
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(script_hooks.on_hit(0, "Ghoul", 100.0, 50.0), 50.0);
+    assert!(script_hooks.decide_action(&monster(20.0)).is_none());
+    let mut missile = missile_spell(&Balance::default(), PlayerClass::Mage);
+    let damage = missile.damage;
+    script_hooks.on_missile_cast(0, PlayerClass::Mage, &mut missile);
+    assert_eq!(missile.damage, damage);
+}
+
+#[test]
+fn script_trigonometry_is_deterministic() {
+    let script_hooks =
+        ScriptHooks::from_source("fn on_hit(hit) { hit.damage * (1.0 + sin(1.0)) }").unwrap();
+    let expected = 10.0 * (1.0 + gv_core::math::deterministic::sin_cos(1.0).0);
+    assert_eq!(
+        script_hooks.on_hit(0, "Ghoul", 100.0, 10.0).to_bits(),
+        expected.to_bits()
+    );
+}
@@ -0,0 +1,40 @@
+use gv_core::{
+    actions::monster_spawn::SPAWN_TELEGRAPH_FRAMES,
+    ecs::components::{Monster, WorldPosition},
+};
+use gv_test_harness::{HeadlessServer, LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+/// The monster that LevelSystem spawns on the 10th frame, it's the first one.
+fn first_monster(server: &HeadlessServer) -> Option<(Monster, WorldPosition)> {
+    server
+        .monsters()
+        .into_iter()
+        .min_by_key(|(monster, _)| monster.active_at_frame)
+}
+
+#[test]
+fn spawned_monsters_emerge_before_acting() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        first_monster(harness.server()).is_some()
+    }));
+
+    let frame_number = harness.server().game_frame_number();
+    let (monster, spawn_position) = first_monster(harness.server()).unwrap();
+    assert!(monster.is_emerging(frame_number));
+    assert!(monster.active_at_frame <= frame_number + SPAWN_TELEGRAPH_FRAMES);
+
+    harness.run_frames(SPAWN_TELEGRAPH_FRAMES / 2);
+    let (monster, position) = first_monster(harness.server()).unwrap();
+    assert!(monster.is_emerging(harness.server().game_frame_number()));
+    assert_eq!(*position, *spawn_position);
+
+    // Once active, an idle monster heads somewhere else.
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        first_monster(harness.server()).map_or(false, |(_, position)| *position != *spawn_position)
+    }));
+}
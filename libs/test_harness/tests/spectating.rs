@@ -0,0 +1,127 @@
+use gv_core::{
+    actions::monster_spawn::{SpawnAction, SpawnType},
+    ecs::resources::world::{
+        RelayedWorldUpdates, ServerWorldUpdates, LAG_COMPENSATION_FRAMES_LIMIT,
+    },
+    math::Vector2,
+    net::server_message::DisconnectReason,
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn relayed_frames(relayed_world_updates: &RelayedWorldUpdates, start_frame: u64) -> Vec<u64> {
+    relayed_world_updates
+        .final_updates(start_frame, usize::max_value())
+        .map(|update| update.frame_number)
+        .collect()
+}
+
+#[test]
+fn relay_keeps_the_latest_simulation_of_each_frame() {
+    let final_lag = LAG_COMPENSATION_FRAMES_LIMIT as u64;
+    let mut server_world_updates = ServerWorldUpdates::default();
+    let mut relayed_world_updates = RelayedWorldUpdates::default();
+
+    server_world_updates.reserve_new_updates(0, 5);
+    relayed_world_updates.record(&server_world_updates, 100);
+    assert_eq!(relayed_world_updates.oldest_frame(), Some(0));
+    assert_eq!(relayed_world_updates.final_frame(), None);
+
+    // A late client action re-simulates the frames starting with the 3rd one.
+    server_world_updates.reserve_new_updates(3, final_lag + 6);
+    for (_, update) in server_world_updates
+        .updates
+        .iter_mut()
+        .filter(|(update_number, update)| *update_number > 5 && update.frame_number <= 5)
+    {
+        update.spawn_actions.push(SpawnAction {
+            spawn_type: SpawnType::Single {
+                entity_net_id: Some(update.frame_number),
+                position: Vector2::new(0.0, 0.0),
+                affixes: Vec::new(),
+            },
+            telegraph_frames: 0,
+        });
+    }
+    relayed_world_updates.record(&server_world_updates, 100);
+    assert_eq!(
+        relayed_frames(&relayed_world_updates, 0),
+        (0..=6).collect::<Vec<_>>()
+    );
+    let spawned_frames = relayed_world_updates
+        .final_updates(0, usize::max_value())
+        .filter(|update| !update.spawn_actions.is_empty())
+        .map(|update| update.frame_number)
+        .collect::<Vec<_>>();
+    assert_eq!(spawned_frames, vec![3, 4, 5]);
+
+    // Older frames get dropped past the capacity.
+    server_world_updates.reserve_new_updates(final_lag + 7, final_lag + 10);
+    relayed_world_updates.record(&server_world_updates, 8);
+    assert_eq!(relayed_world_updates.oldest_frame(), Some(final_lag + 3));
+    assert_eq!(relayed_world_updates.final_frame(), None);
+
+    relayed_world_updates.reset();
+    assert_eq!(relayed_world_updates.oldest_frame(), None);
+}
+
+#[test]
+fn late_spectators_catch_up_with_the_game() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(MAX_FRAMES);
+
+    let spectator = harness.add_client("spectator");
+    harness.client_mut(spectator).spectate();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let spectator = harness.client(spectator);
+        spectator.spectator_snapshot().is_some()
+            && !spectator.relayed_updates().is_empty()
+            && !spectator.is_catching_up()
+    }));
+
+    let spectator = harness.client(spectator);
+    let snapshot = spectator.spectator_snapshot().unwrap();
+    assert_eq!(snapshot.players.len(), 2);
+    assert_eq!(spectator.room_players().len(), 2);
+    // The snapshot starts with the oldest buffered frame, not with the live one.
+    assert!(
+        snapshot.frame_number + LAG_COMPENSATION_FRAMES_LIMIT as u64 * 2
+            < harness.client(host).game_frame_number()
+    );
+
+    let relayed_frames = spectator
+        .relayed_updates()
+        .iter()
+        .map(|update| update.frame_number)
+        .collect::<Vec<_>>();
+    let expected_frames = (snapshot.frame_number + 1..)
+        .take(relayed_frames.len())
+        .collect::<Vec<_>>();
+    assert_eq!(relayed_frames, expected_frames);
+
+    // Spectators don't acknowledge world updates, which mustn't stall the players.
+    harness.run_frames(MAX_FRAMES);
+    assert!(!harness.client(host).is_paused());
+}
+
+#[test]
+fn spectators_cannot_join_the_lobby() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let spectator = harness.add_client("spectator");
+    harness.client_mut(host).join();
+    assert!(harness.run_until(MAX_FRAMES, |harness| harness.client(host).is_host()));
+
+    harness.client_mut(spectator).spectate();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(spectator).disconnect_reason().is_some()
+    }));
+    match harness.client(spectator).disconnect_reason() {
+        Some(DisconnectReason::GameIsNotStarted) => {}
+        disconnect_reason => panic!("Unexpected disconnect reason: {:?}", disconnect_reason),
+    }
+}
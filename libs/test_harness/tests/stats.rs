@@ -0,0 +1,76 @@
+use gv_core::{
+    economy::ShopItem,
+    ecs::components::affixes::MonsterAffix,
+    stats::{Modifier, ModifierSource, Stat, StatModifiers, MAX_COOLDOWN_REDUCTION},
+};
+
+#[test]
+fn additions_are_applied_before_multiplications() {
+    let mut stat_modifiers = StatModifiers::default();
+    stat_modifiers.add(
+        Stat::MoveSpeed,
+        ModifierSource::Zone,
+        Modifier::Multiply(0.5),
+    );
+    stat_modifiers.add(
+        Stat::MoveSpeed,
+        ModifierSource::Item(ShopItem::SwiftBoots),
+        Modifier::Add(20.0),
+    );
+    stat_modifiers.add(
+        Stat::MissileDamage,
+        ModifierSource::Item(ShopItem::Whetstone),
+        Modifier::Multiply(2.0),
+    );
+
+    assert_eq!(stat_modifiers.value(Stat::MoveSpeed, 180.0), 100.0);
+    assert_eq!(stat_modifiers.value(Stat::MissileDamage, 50.0), 100.0);
+    assert_eq!(stat_modifiers.value(Stat::DamageTaken, 1.0), 1.0);
+}
+
+#[test]
+fn collection_order_doesnt_change_values() {
+    let modifiers = [
+        (
+            ModifierSource::Affix(MonsterAffix::Fast),
+            Modifier::Multiply(1.1),
+        ),
+        (ModifierSource::Zone, Modifier::Multiply(0.37)),
+        (
+            ModifierSource::Item(ShopItem::SwiftBoots),
+            Modifier::Multiply(1.3),
+        ),
+    ];
+    let mut forward = StatModifiers::default();
+    for (source, modifier) in modifiers.iter() {
+        forward.add(Stat::MoveSpeed, *source, *modifier);
+    }
+    let mut backward = StatModifiers::default();
+    for (source, modifier) in modifiers.iter().rev() {
+        backward.add(Stat::MoveSpeed, *source, *modifier);
+    }
+
+    assert_eq!(
+        forward.value(Stat::MoveSpeed, 173.3).to_bits(),
+        backward.value(Stat::MoveSpeed, 173.3).to_bits()
+    );
+}
+
+#[test]
+fn cooldowns_are_shortened_within_limits() {
+    let mut stat_modifiers = StatModifiers::default();
+    assert_eq!(stat_modifiers.cooldown_secs(10.0, 0.0, 1.0), 10.0);
+    assert_eq!(stat_modifiers.cooldown_secs(10.0, 0.5, 2.0), 2.5);
+
+    stat_modifiers.add(
+        Stat::CooldownReduction,
+        ModifierSource::Zone,
+        Modifier::Add(5.0),
+    );
+    let capped = 10.0 * (1.0 - MAX_COOLDOWN_REDUCTION);
+    assert!((stat_modifiers.cooldown_secs(10.0, 0.0, 1.0) - capped).abs() < 0.001);
+
+    // Haste can't drop to zero and make cooldowns infinite.
+    stat_modifiers.add(Stat::Haste, ModifierSource::Zone, Modifier::Multiply(0.0));
+    assert!(stat_modifiers.cooldown_secs(10.0, 0.0, 1.0).is_finite());
+}
@@ -0,0 +1,42 @@
+use amethyst::ecs::{Builder, World, WorldExt};
+
+use gv_core::ecs::components::ThreatTable;
+
+#[test]
+fn threat_table_picks_the_top_target() {
+    let mut world = World::new();
+    let tank = world.create_entity().build();
+    let damage_dealer = world.create_entity().build();
+
+    let mut threat_table = ThreatTable::default();
+    threat_table.add(tank, 10.0);
+    threat_table.add(damage_dealer, 10.0);
+    // Ties are won by the target that has generated threat first.
+    assert_eq!(threat_table.top_target(|_| true), Some(tank));
+
+    threat_table.add(damage_dealer, 5.0);
+    assert_eq!(threat_table.top_target(|_| true), Some(damage_dealer));
+    assert_eq!(threat_table.top_threat(), 15.0);
+    // Dead targets are skipped.
+    assert_eq!(
+        threat_table.top_target(|target| target != damage_dealer),
+        Some(tank)
+    );
+}
+
+#[test]
+fn threat_decays_until_forgotten() {
+    let mut world = World::new();
+    let target = world.create_entity().build();
+
+    let mut threat_table = ThreatTable::default();
+    threat_table.add(target, 8.0);
+    threat_table.decay(0.5);
+    assert_eq!(threat_table.threat(target), 4.0);
+
+    for _ in 0..4 {
+        threat_table.decay(0.5);
+    }
+    assert_eq!(threat_table.threat(target), 0.0);
+    assert_eq!(threat_table.top_target(|_| true), None);
+}
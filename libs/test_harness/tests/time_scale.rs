@@ -0,0 +1,52 @@
+use gv_core::ecs::resources::GameTime;
+
+fn scale_ticks(game_time: &mut GameTime, ticks: usize) -> Vec<u64> {
+    (0..ticks)
+        .map(|_| {
+            if game_time.scale_tick() {
+                game_time.advanced_frames
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn slowed_down_games_skip_ticks() {
+    let mut game_time = GameTime::default();
+    assert_eq!(scale_ticks(&mut game_time, 3), vec![1, 1, 1]);
+
+    game_time.time_scale = 0.25;
+    assert_eq!(scale_ticks(&mut game_time, 8), vec![0, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(game_time.frames_skipped, 6);
+    assert_eq!(game_time.frames_added, 0);
+}
+
+#[test]
+fn sped_up_games_advance_by_several_frames() {
+    let mut game_time = GameTime::default();
+    game_time.time_scale = 4.0;
+    assert_eq!(scale_ticks(&mut game_time, 2), vec![4, 4]);
+    assert_eq!(game_time.frames_added, 6);
+
+    game_time.time_scale = 1.5;
+    assert_eq!(scale_ticks(&mut game_time, 4), vec![1, 2, 1, 2]);
+    assert_eq!(game_time.frames_added, 8);
+    assert_eq!(game_time.frames_skipped, 0);
+}
+
+#[test]
+fn frame_stepping_advances_by_stepped_frames_only() {
+    let mut game_time = GameTime::default();
+    game_time.time_scale = 8.0;
+    game_time.is_frame_stepping = true;
+    assert_eq!(scale_ticks(&mut game_time, 2), vec![0, 0]);
+
+    game_time.step_frame();
+    game_time.step_frame();
+    assert_eq!(scale_ticks(&mut game_time, 3), vec![1, 1, 0]);
+
+    game_time.is_frame_stepping = false;
+    assert_eq!(scale_ticks(&mut game_time, 1), vec![8]);
+}
@@ -0,0 +1,195 @@
+use gv_core::{
+    ecs::resources::{GameLevelState, Intermission},
+    net::server_message::DisconnectReason,
+    votes::{Vote, VoteKind},
+};
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+
+fn join(harness: &mut TestHarness) {
+    for index in 0..harness.clients().len() {
+        harness.client_mut(index).join();
+    }
+    let clients_count = harness.clients().len();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.room_players().len() == clients_count)
+    }));
+}
+
+fn vote_has_ended(harness: &TestHarness, has_passed: bool) -> bool {
+    harness.clients().iter().all(|client| {
+        client.vote().is_none()
+            && client
+                .vote_results()
+                .last()
+                .map_or(false, |(_, result)| *result == has_passed)
+    })
+}
+
+#[test]
+fn majority_decides_the_outcome() {
+    let mut vote = Vote::new(0, VoteKind::ReturnToLobby, 1, 4);
+    assert_eq!(vote.outcome(), None);
+    vote.cast(2, true);
+    assert_eq!(vote.outcome(), None);
+    vote.cast(3, true);
+    assert_eq!(vote.outcome(), Some(true));
+
+    // Players can change their mind.
+    vote.cast(3, false);
+    assert_eq!(vote.yes, vec![1, 2]);
+    assert_eq!(vote.outcome(), None);
+    // A tie means the majority can't be reached anymore.
+    vote.cast(4, false);
+    assert_eq!(vote.outcome(), Some(false));
+
+    vote.retain_voters(|connection_id| connection_id != 4, 3);
+    assert_eq!(vote.outcome(), Some(true));
+
+    assert!(!VoteKind::Kick(2).is_voter(2));
+    assert!(VoteKind::Kick(2).is_voter(1));
+}
+
+#[test]
+fn players_vote_to_kick_in_the_lobby() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let first_guest = harness.add_client("first_guest");
+    let second_guest = harness.add_client("second_guest");
+    join(&mut harness);
+    let host_connection_id = harness.client(host).connection_id().unwrap();
+    let kicked_connection_id = harness.client(first_guest).connection_id().unwrap();
+
+    // The host can't be voted out.
+    harness
+        .client_mut(first_guest)
+        .start_vote(VoteKind::Kick(host_connection_id));
+    harness.run_frames(10);
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.vote().is_none()));
+
+    harness
+        .client_mut(second_guest)
+        .start_vote(VoteKind::Kick(kicked_connection_id));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.clients().iter().all(|client| {
+            client.vote().map_or(false, |vote| {
+                vote.kind == VoteKind::Kick(kicked_connection_id) && vote.voters == 2
+            })
+        })
+    }));
+    // The kicked player doesn't get a say.
+    harness.client_mut(first_guest).cast_vote(false);
+    harness.run_frames(10);
+    assert_eq!(harness.client(host).vote().unwrap().no, Vec::new());
+
+    harness.client_mut(host).cast_vote(true);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(first_guest).disconnect_reason().is_some()
+    }));
+    assert!(matches!(
+        harness.client(first_guest).disconnect_reason(),
+        Some(DisconnectReason::VoteKick)
+    ));
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).room_players().len() == 2
+    }));
+    assert!(harness.client(second_guest).vote_results()[0].1);
+}
+
+#[test]
+fn players_vote_to_skip_the_intermission() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness
+        .server_mut()
+        .world_mut()
+        .fetch_mut::<GameLevelState>()
+        .intermission = Some(Intermission {
+        wave: 1,
+        started_at_frame: 0,
+        ends_at_frame: u64::max_value(),
+        ready_players: Vec::new(),
+    });
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.intermission().is_some())
+    }));
+
+    harness
+        .client_mut(guest)
+        .start_vote(VoteKind::SkipIntermission);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).vote().is_some()
+    }));
+    harness.client_mut(host).cast_vote(true);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        vote_has_ended(harness, true)
+            && harness
+                .clients()
+                .iter()
+                .all(|client| client.intermission().is_none())
+    }));
+}
+
+#[test]
+fn players_vote_to_return_to_the_lobby() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(guest)
+        .start_vote(VoteKind::ReturnToLobby);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(host).vote().is_some()
+    }));
+    harness.client_mut(host).cast_vote(false);
+    assert!(harness.run_until(MAX_FRAMES, |harness| vote_has_ended(harness, false)));
+    assert!(harness
+        .clients()
+        .iter()
+        .all(|client| client.has_started_game()));
+
+    // The guest has to wait for the cooldown, but anyone else can start a vote.
+    harness
+        .client_mut(guest)
+        .start_vote(VoteKind::ReturnToLobby);
+    harness.run_frames(10);
+    assert!(harness.client(host).vote().is_none());
+    harness.client_mut(host).start_vote(VoteKind::ReturnToLobby);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness.client(guest).vote().is_some()
+    }));
+    harness.client_mut(guest).cast_vote(true);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        vote_has_ended(harness, true)
+            && harness
+                .clients()
+                .iter()
+                .all(|client| !client.has_started_game())
+    }));
+    assert!(!harness
+        .server()
+        .multiplayer_game_state(|multiplayer_game_state| multiplayer_game_state.is_playing));
+
+    // The host can start a new game from the lobby.
+    harness.client_mut(host).start_game();
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        harness
+            .clients()
+            .iter()
+            .all(|client| client.has_started_game())
+    }));
+}
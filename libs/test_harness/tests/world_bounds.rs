@@ -0,0 +1,46 @@
+use gv_core::{
+    ecs::resources::GameLevelState,
+    level_scripts::{LevelScript, OutOfBounds},
+    math::Vector2,
+};
+
+#[test]
+fn scripts_set_the_level_bounds() {
+    let script = r#"(
+      bounds: Some((size: (400.0, 200.0), out_of_bounds: Teleport((0.0, 50.0)))),
+      triggers: [],
+    )"#;
+    let script = LevelScript::from_bytes(script.as_bytes()).unwrap();
+    let bounds = script.bounds.as_ref().unwrap();
+    assert_eq!(bounds.out_of_bounds, OutOfBounds::Teleport((0.0, 50.0)));
+
+    let mut game_level_state = GameLevelState::default();
+    assert!(game_level_state.is_within_bounds(Vector2::new(1000.0, 0.0), 0.0));
+    game_level_state.set_bounds(bounds);
+    assert_eq!(game_level_state.dimensions, Vector2::new(400.0, 200.0));
+    assert!(!game_level_state.is_within_bounds(Vector2::new(250.0, 0.0), 0.0));
+    assert!(game_level_state.is_within_bounds(Vector2::new(250.0, 0.0), 100.0));
+    assert_eq!(
+        game_level_state.clamp_to_bounds(Vector2::new(250.0, -150.0)),
+        Vector2::new(200.0, -100.0)
+    );
+    assert_eq!(
+        game_level_state.distance_to_border(Vector2::new(150.0, 20.0)),
+        50.0
+    );
+    assert_eq!(
+        game_level_state.distance_to_border(Vector2::new(0.0, 300.0)),
+        0.0
+    );
+}
+
+#[test]
+fn invalid_bounds_are_rejected() {
+    let script = r#"(bounds: Some((size: (0.0, 200.0))), triggers: [])"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+    let script = r#"(
+      bounds: Some((size: (400.0, 200.0), out_of_bounds: Teleport((300.0, 0.0)))),
+      triggers: [],
+    )"#;
+    assert!(LevelScript::from_bytes(script.as_bytes()).is_err());
+}
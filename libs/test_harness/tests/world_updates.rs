@@ -0,0 +1,221 @@
+use gv_core::{
+    actions::{
+        monster_spawn::{SpawnAction, SpawnType},
+        player::{CastSpell, PlayerCastAction, PlayerLookAction, MAX_CAST_RANGE},
+        ClientActionUpdate,
+    },
+    ecs::resources::world::{
+        FramedUpdates, ServerWorldUpdate, ServerWorldUpdates, MAX_PENDING_WORLD_UPDATES,
+        SAVED_WORLD_STATES_LIMIT, SERVER_WORLD_UPDATES_LIMIT,
+    },
+    math::Vector2,
+    net::{
+        decode_server_message,
+        server_message::{ClientNetworkError, ServerMessage, ServerMessagePayload, UpdateWorldRef},
+        MessageEncoder, NetUpdate,
+    },
+};
+use gv_game::utils::world_updates::apply_world_updates;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 300;
+/// Acknowledged updates are dropped on each broadcast, only a few frames worth should remain.
+const CAUGHT_UP_UPDATES_LIMIT: usize = 30;
+
+fn stored_updates(harness: &TestHarness) -> usize {
+    harness
+        .server()
+        .world()
+        .read_resource::<ServerWorldUpdates>()
+        .updates
+        .len()
+}
+
+#[test]
+fn acknowledged_updates_are_dropped() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    harness.add_client("host");
+    harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(MAX_FRAMES);
+
+    assert!(stored_updates(&harness) < CAUGHT_UP_UPDATES_LIMIT);
+}
+
+#[test]
+fn stalled_acknowledgements_pause_the_game() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    let guest = harness.add_client("guest");
+    assert!(harness.join_and_start(MAX_FRAMES));
+    harness.run_frames(30);
+    assert!(!harness.client(host).is_paused());
+
+    harness
+        .client_mut(guest)
+        .set_acknowledges_world_updates(false);
+    assert!(
+        harness.run_until(MAX_PENDING_WORLD_UPDATES as u64 + MAX_FRAMES, |harness| {
+            harness.client(host).is_paused()
+        })
+    );
+    let paused_at_updates = stored_updates(&harness);
+    assert!(paused_at_updates > MAX_PENDING_WORLD_UPDATES);
+    assert!(paused_at_updates <= SERVER_WORLD_UPDATES_LIMIT);
+
+    // Nothing gets simulated during the pause, so the buffer stops growing.
+    harness.run_frames(MAX_FRAMES);
+    assert_eq!(stored_updates(&harness), paused_at_updates);
+
+    harness
+        .client_mut(guest)
+        .set_acknowledges_world_updates(true);
+    assert!(harness.run_until(MAX_FRAMES, |harness| !harness.client(host).is_paused()));
+    harness.run_frames(30);
+    assert!(stored_updates(&harness) < CAUGHT_UP_UPDATES_LIMIT);
+}
+
+#[test]
+fn borrowed_update_world_encodes_like_owned() {
+    let mut server_world_updates = ServerWorldUpdates::default();
+    server_world_updates.reserve_new_updates(0, 5);
+    server_world_updates.reserve_new_updates(3, 6);
+    for (i, (_, update)) in server_world_updates.updates.iter_mut().enumerate() {
+        update.spawn_actions.push(SpawnAction {
+            spawn_type: SpawnType::Single {
+                entity_net_id: Some(i as u64),
+                position: Vector2::new(i as f32, 0.0),
+                affixes: Vec::new(),
+            },
+            telegraph_frames: 0,
+        });
+    }
+    let update_indices = vec![1, 4, 8];
+
+    let mut message_encoder = MessageEncoder::default();
+    let encoded = message_encoder
+        .encode_message(
+            7,
+            &UpdateWorldRef {
+                id: 10,
+                server_world_updates: &server_world_updates,
+                update_indices: &update_indices,
+            },
+        )
+        .to_vec();
+    let owned = bincode::serialize(&ServerMessage {
+        session_id: 7,
+        payload: ServerMessagePayload::UpdateWorld {
+            id: 10,
+            updates: update_indices
+                .iter()
+                .map(|i| server_world_updates.updates[*i].1.clone())
+                .collect(),
+        },
+    })
+    .unwrap();
+    assert_eq!(encoded, owned);
+
+    // Broadcasting encodes a payload once and only replaces session ids.
+    assert_eq!(message_encoder.with_session_id(8)[8..], owned[8..]);
+    assert!(matches!(
+        decode_server_message(message_encoder.with_session_id(8)),
+        Ok(ServerMessage { session_id: 8, .. })
+    ));
+}
+
+#[test]
+fn aim_points_are_clamped_to_max_cast_range() {
+    let position = Vector2::new(100.0, 100.0);
+    let mut look_action = PlayerLookAction {
+        direction: Vector2::new(1.0, 0.0),
+        aim_point: Vector2::new(300.0, 100.0),
+    };
+    look_action.clamp_aim_point(position);
+    assert_eq!(look_action.aim_point, Vector2::new(300.0, 100.0));
+
+    look_action.aim_point = Vector2::new(100.0 + MAX_CAST_RANGE * 2.0, 100.0);
+    look_action.clamp_aim_point(position);
+    assert!((look_action.aim_point - Vector2::new(100.0 + MAX_CAST_RANGE, 100.0)).norm() < 0.001);
+
+    let mut cast_action = PlayerCastAction {
+        cast_position: position,
+        target_position: Vector2::new(100.0, 100.0 - MAX_CAST_RANGE * 3.0),
+        spell: CastSpell::Zone,
+    };
+    cast_action.clamp_target_position();
+    assert!(
+        (cast_action.target_position - Vector2::new(100.0, 100.0 - MAX_CAST_RANGE)).norm() < 0.001
+    );
+}
+
+#[test]
+fn too_old_world_updates_are_errors() {
+    let latest_frame_number = SAVED_WORLD_STATES_LIMIT as u64 + 10;
+    let mut framed_updates = FramedUpdates::default();
+    framed_updates.reserve_updates(latest_frame_number);
+    let mut spawn_actions = FramedUpdates::default();
+    spawn_actions.reserve_updates(latest_frame_number);
+
+    let result = apply_world_updates(
+        &[1],
+        &mut framed_updates,
+        &mut spawn_actions,
+        0,
+        vec![ServerWorldUpdate::new(5)],
+    );
+    assert!(matches!(
+        result,
+        Err(ClientNetworkError::OutdatedWorldUpdate {
+            frame_number: 5,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn look_actions_of_controlled_players_are_not_replayed() {
+    let mut framed_updates = FramedUpdates::default();
+    framed_updates.reserve_updates(10);
+    let mut spawn_actions = FramedUpdates::default();
+    spawn_actions.reserve_updates(10);
+
+    let mut update = ServerWorldUpdate::new(5);
+    for entity_net_id in 1..=3 {
+        update.player_look_actions_updates.push(NetUpdate {
+            entity_net_id,
+            data: ClientActionUpdate {
+                client_action_id: entity_net_id,
+                action: PlayerLookAction {
+                    direction: Vector2::new(1.0, 0.0),
+                    aim_point: Vector2::new(entity_net_id as f32, 0.0),
+                },
+            },
+        });
+    }
+    apply_world_updates(
+        &[1, 3],
+        &mut framed_updates,
+        &mut spawn_actions,
+        0,
+        vec![update],
+    )
+    .unwrap();
+
+    let frame_updates = framed_updates
+        .updates
+        .iter()
+        .find(|frame_updates| frame_updates.frame_number == 5)
+        .unwrap();
+    assert!(frame_updates
+        .controlled_player_updates
+        .player_look_actions_updates
+        .is_empty());
+    let look_actions = &frame_updates.player_updates.player_look_actions_updates;
+    assert_eq!(look_actions.len(), 1);
+    assert_eq!(look_actions[0].entity_net_id, 2);
+    assert_eq!(
+        look_actions[0].data.action.aim_point,
+        Vector2::new(2.0, 0.0)
+    );
+}
@@ -0,0 +1,50 @@
+use amethyst::ecs::{Builder, World, WorldExt};
+
+use std::cell::Cell;
+
+use gv_core::{actions::player::CastSpell, math::Vector2};
+use gv_game::utils::spatial_grid::SpatialGrid;
+use gv_test_harness::{LoopbackConfig, TestHarness};
+
+const MAX_FRAMES: u64 = 600;
+
+#[test]
+fn zones_are_spawned_once_and_expire() {
+    let mut harness = TestHarness::new(LoopbackConfig::default()).unwrap();
+    let host = harness.add_client("host");
+    assert!(harness.join_and_start(MAX_FRAMES));
+
+    harness
+        .client_mut(host)
+        .cast(CastSpell::Zone, Vector2::new(100.0, 0.0));
+    assert!(harness.run_until(MAX_FRAMES, |harness| !harness.server().zones().is_empty()));
+    let zone = harness.server().zones()[0].clone();
+    // Mage fire patches last for 4 seconds.
+    assert_eq!(zone.frame_expires - zone.frame_spawned, 240);
+
+    // Rewinds caused by late client updates don't spawn the same zone again.
+    let max_zones = Cell::new(0);
+    assert!(harness.run_until(MAX_FRAMES, |harness| {
+        let zones_count = harness.server().zones().len();
+        max_zones.set(max_zones.get().max(zones_count));
+        zones_count == 0
+    }));
+    assert_eq!(max_zones.get(), 1);
+}
+
+#[test]
+fn spatial_grid_finds_entities_across_cells() {
+    let mut world = World::new();
+    let inside = world.create_entity().build();
+    let neighbour_cell = world.create_entity().build();
+    let outside = world.create_entity().build();
+
+    let mut grid = SpatialGrid::new(128.0);
+    grid.insert(inside, Vector2::new(10.0, 10.0));
+    grid.insert(neighbour_cell, Vector2::new(-20.0, 0.0));
+    grid.insert(outside, Vector2::new(300.0, 0.0));
+
+    let found = grid.query_radius(Vector2::new(0.0, 0.0), 50.0);
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&inside) && found.contains(&neighbour_cell));
+}
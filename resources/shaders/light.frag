@@ -0,0 +1,13 @@
+#version 450
+
+layout(location = 0) in VertexData {
+    float intensity;
+    float distance;
+} vertex;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+    float falloff = 1.0 - clamp(vertex.distance, 0.0, 1.0);
+    out_color = vec4(vec3(0.0), vertex.intensity * falloff * falloff);
+}
@@ -0,0 +1,22 @@
+#version 450
+
+layout(std140, set = 0, binding = 0) uniform ViewArgs {
+    uniform mat4 proj;
+    uniform mat4 view;
+    uniform mat4 proj_view;
+};
+
+layout(location = 0) in vec2 pos;
+layout(location = 1) in float intensity;
+layout(location = 2) in float distance;
+
+layout(location = 0) out VertexData {
+    float intensity;
+    float distance;
+} vertex;
+
+void main() {
+    vertex.intensity = intensity;
+    vertex.distance = distance;
+    gl_Position = proj_view * vec4(pos, 0.0, 1.0);
+}
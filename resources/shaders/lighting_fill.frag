@@ -0,0 +1,16 @@
+#version 450
+
+layout(push_constant) uniform FragmentPushConstants {
+    float light;
+} pushConstants;
+
+layout(location = 0) in VertexData {
+    vec2 uv;
+} vertex;
+
+layout(location = 0) out vec4 out_color;
+
+// The light level is kept in the alpha channel until it's applied to the colors.
+void main() {
+    out_color = vec4(vec3(0.0), pushConstants.light);
+}
@@ -0,0 +1,20 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform sampler2D albedo;
+
+layout(location = 0) in VertexData {
+    vec2 tex_uv;
+    vec4 color;
+} vertex;
+layout(location = 0) out vec4 out_color;
+
+// Monsters write depth, so their faint edges mustn't hide the sprites drawn under them later.
+const float alpha_cutoff = 0.1;
+
+void main() {
+    vec4 color = texture(albedo, vertex.tex_uv) * vertex.color;
+    if (color.a < alpha_cutoff) {
+        discard;
+    }
+    out_color = color;
+}
@@ -0,0 +1,82 @@
+#version 450
+
+const float BLOOM_THRESHOLD = 0.7;
+const float BLOOM_RADIUS = 6.0;
+const float CHROMATIC_ABERRATION_OFFSET = 0.012;
+const float LUT_SIZE = 16.0;
+const vec3 LOW_HEALTH_COLOR = vec3(0.6, 0.0, 0.0);
+
+layout(set = 0, binding = 0) uniform sampler2D screen;
+// A 16x16x16 lookup table, stored as 16 slices of blue side by side.
+layout(set = 1, binding = 0) uniform sampler2D lut;
+
+layout(push_constant) uniform FragmentPushConstants {
+    vec2 texel_size;
+    float bloom;
+    float chromatic_aberration;
+    float low_health_tint;
+    float color_grading;
+} pushConstants;
+
+layout(location = 0) in VertexData {
+    vec2 uv;
+} vertex;
+
+layout(location = 0) out vec4 out_color;
+
+vec3 to_srgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+vec3 to_linear(vec3 color) {
+    return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(0.04045, color));
+}
+
+vec3 bright_part(vec2 uv) {
+    vec3 color = texture(screen, uv).rgb;
+    return max(color - BLOOM_THRESHOLD, 0.0);
+}
+
+vec3 bloom(vec2 uv) {
+    vec3 sum = vec3(0.0);
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            vec2 offset = vec2(x, y) * BLOOM_RADIUS * 0.5 * pushConstants.texel_size;
+            sum += bright_part(uv + offset) / (1.0 + float(x * x + y * y));
+        }
+    }
+    return sum * 0.25;
+}
+
+vec3 grade(vec3 color) {
+    vec3 cell = clamp(to_srgb(color), 0.0, 1.0) * (LUT_SIZE - 1.0);
+    float slice = floor(cell.b);
+    vec2 uv = vec2((cell.r + 0.5) / (LUT_SIZE * LUT_SIZE), (cell.g + 0.5) / LUT_SIZE);
+    vec3 low = texture(lut, uv + vec2(slice / LUT_SIZE, 0.0)).rgb;
+    vec3 high = texture(lut, uv + vec2(min(slice + 1.0, LUT_SIZE - 1.0) / LUT_SIZE, 0.0)).rgb;
+    return to_linear(mix(low, high, cell.b - slice));
+}
+
+void main() {
+    vec2 uv = vertex.uv;
+    vec3 color = texture(screen, uv).rgb;
+
+    if (pushConstants.chromatic_aberration > 0.0) {
+        vec2 offset = (uv - 0.5) * pushConstants.chromatic_aberration * CHROMATIC_ABERRATION_OFFSET;
+        color.r = texture(screen, uv + offset).r;
+        color.b = texture(screen, uv - offset).b;
+    }
+    if (pushConstants.bloom > 0.0) {
+        color += bloom(uv) * pushConstants.bloom;
+    }
+    if (pushConstants.color_grading > 0.0) {
+        color = grade(color);
+    }
+    if (pushConstants.low_health_tint > 0.0) {
+        float vignette = clamp(length(uv - 0.5) * 1.6, 0.0, 1.0);
+        float tint = pushConstants.low_health_tint * (0.25 + 0.5 * vignette);
+        color = mix(color, LOW_HEALTH_COLOR, tint);
+    }
+
+    out_color = vec4(color, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) out VertexData {
+    vec2 uv;
+} vertex;
+
+// A triangle covering the whole screen, without any vertex buffers.
+void main() {
+    vertex.uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(vertex.uv * 2.0 - 1.0, 0.0, 1.0);
+}